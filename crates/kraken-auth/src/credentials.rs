@@ -153,6 +153,9 @@ impl std::fmt::Debug for Credentials {
 }
 
 /// Request signer for building authenticated requests
+///
+/// Holds the path and a fresh nonce for one request. See
+/// [`crate::signing`] for building signed bodies and `reqwest` requests.
 #[derive(Debug)]
 pub struct RequestSigner<'a> {
     credentials: &'a Credentials,
@@ -170,6 +173,23 @@ impl<'a> RequestSigner<'a> {
         }
     }
 
+    /// Create a signer with a caller-supplied nonce
+    ///
+    /// Nonces must increase per API key; prefer [`new`](Self::new) unless
+    /// nonces are managed elsewhere.
+    pub fn with_nonce(credentials: &'a Credentials, path: impl Into<String>, nonce: impl Into<String>) -> Self {
+        Self {
+            credentials,
+            path: path.into(),
+            nonce: nonce.into(),
+        }
+    }
+
+    /// Get the endpoint path
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
     /// Get the nonce for this request
     pub fn nonce(&self) -> &str {
         &self.nonce
@@ -0,0 +1,235 @@
+//! Account fee schedule and volume tier
+//!
+//! Kraken charges each pair a maker and a taker fee that depend on the
+//! account's 30-day trade volume. The private `TradeVolume` endpoint reports
+//! the current tier per pair; [`FeeClient::trade_volume`] fetches it as a
+//! typed [`TradeVolume`] so cost models use the account's real fees instead
+//! of the public schedule's top tier.
+//!
+//! | Field | Meaning |
+//! |-------|---------|
+//! | [`TradeVolume::volume`] | 30-day volume in [`TradeVolume::currency`] |
+//! | [`FeeTier::fee`] | current fee, in percent |
+//! | [`FeeTier::next_fee`] / [`FeeTier::next_volume`] | fee of the next tier and the volume that reaches it |
+//! | [`FeeTier::tier_volume`] | volume at which the current tier starts |
+//!
+//! Fees are quoted in percent; [`TradeVolume::taker_rate`] and
+//! [`TradeVolume::maker_rate`] convert them to fractions of notional.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_auth::fees::TradeVolume;
+//! use rust_decimal_macros::dec;
+//!
+//! let volume = TradeVolume::from_json(r#"{"error":[],"result":{
+//!     "currency":"ZUSD","volume":"120000.0000",
+//!     "fees":{"XXBTZUSD":{"fee":"0.2200","minfee":"0.1000","maxfee":"0.4000",
+//!         "nextfee":"0.2000","nextvolume":"250000.0000","tiervolume":"100000.0000"}},
+//!     "fees_maker":{"XXBTZUSD":{"fee":"0.1200","minfee":"0.0000","maxfee":"0.2500",
+//!         "nextfee":"0.1000","nextvolume":"250000.0000","tiervolume":"100000.0000"}}
+//! }}"#).unwrap();
+//!
+//! assert_eq!(volume.taker_rate("XXBTZUSD"), Some(dec!(0.0022)));
+//! assert_eq!(volume.maker_rate("XXBTZUSD"), Some(dec!(0.0012)));
+//! ```
+
+use crate::credentials::{Credentials, RequestSigner};
+use crate::error::{AuthError, AuthResult};
+use crate::signing::BASE_URL;
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{debug, instrument};
+
+/// Path of the private TradeVolume endpoint
+pub const TRADE_VOLUME_PATH: &str = "/0/private/TradeVolume";
+
+/// Fee tier of one pair, for one side of the book
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeeTier {
+    /// Current fee, in percent
+    pub fee: Decimal,
+    /// Lowest fee of the schedule, in percent
+    #[serde(rename = "minfee")]
+    pub min_fee: Decimal,
+    /// Highest fee of the schedule, in percent
+    #[serde(rename = "maxfee")]
+    pub max_fee: Decimal,
+    /// Fee of the next tier, if the account isn't in the last one
+    #[serde(rename = "nextfee", default)]
+    pub next_fee: Option<Decimal>,
+    /// Volume that reaches the next tier
+    #[serde(rename = "nextvolume", default)]
+    pub next_volume: Option<Decimal>,
+    /// Volume at which the current tier starts
+    #[serde(rename = "tiervolume")]
+    pub tier_volume: Decimal,
+}
+
+impl FeeTier {
+    /// Current fee as a fraction of notional
+    pub fn rate(&self) -> Decimal {
+        self.fee / Decimal::ONE_HUNDRED
+    }
+}
+
+/// Account volume and fee tiers per pair
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradeVolume {
+    /// Currency the volume is counted in (e.g. "ZUSD")
+    pub currency: String,
+    /// 30-day trade volume
+    pub volume: Decimal,
+    /// Taker fees by pair
+    #[serde(default)]
+    pub fees: HashMap<String, FeeTier>,
+    /// Maker fees by pair; absent for pairs charging one fee to both sides
+    #[serde(default)]
+    pub fees_maker: HashMap<String, FeeTier>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TradeVolumeResponse {
+    error: Vec<String>,
+    result: Option<TradeVolume>,
+}
+
+impl TradeVolume {
+    /// Parse a `TradeVolume` response
+    pub fn from_json(json: &str) -> AuthResult<Self> {
+        let response: TradeVolumeResponse =
+            serde_json::from_str(json).map_err(|e| AuthError::Parse(e.to_string()))?;
+        if !response.error.is_empty() {
+            return Err(AuthError::Api(response.error.join(", ")));
+        }
+        response
+            .result
+            .ok_or_else(|| AuthError::Parse("Missing result in response".to_string()))
+    }
+
+    /// Taker tier of a pair
+    pub fn taker(&self, pair: &str) -> Option<&FeeTier> {
+        self.fees.get(pair)
+    }
+
+    /// Maker tier of a pair, falling back to the taker tier
+    pub fn maker(&self, pair: &str) -> Option<&FeeTier> {
+        self.fees_maker.get(pair).or_else(|| self.taker(pair))
+    }
+
+    /// Taker fee of a pair as a fraction of notional
+    pub fn taker_rate(&self, pair: &str) -> Option<Decimal> {
+        self.taker(pair).map(FeeTier::rate)
+    }
+
+    /// Maker fee of a pair as a fraction of notional
+    pub fn maker_rate(&self, pair: &str) -> Option<Decimal> {
+        self.maker(pair).map(FeeTier::rate)
+    }
+}
+
+/// Client for the account fee endpoints
+///
+/// # Example
+///
+/// ```no_run
+/// use kraken_auth::{Credentials, FeeClient};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = FeeClient::new(Credentials::from_env()?);
+/// let volume = client.trade_volume(&["XBTUSD", "ETHUSD"]).await?;
+/// println!("30-day volume: {} {}", volume.volume, volume.currency);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FeeClient {
+    credentials: Credentials,
+    client: Client,
+    base_url: String,
+}
+
+impl FeeClient {
+    /// Create a client against [`BASE_URL`]
+    pub fn new(credentials: Credentials) -> Self {
+        Self {
+            credentials,
+            client: Client::new(),
+            base_url: BASE_URL.to_string(),
+        }
+    }
+
+    /// Send requests to another base URL (a proxy or mock server)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Use an existing HTTP client
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Fetch the account volume and the fee tiers of `pairs`
+    ///
+    /// Pairs use REST names (e.g. "XBTUSD"). With no pairs, only the volume
+    /// is returned.
+    ///
+    /// # Errors
+    /// Returns an error if the request fails, Kraken rejects it, or the
+    /// response can't be parsed.
+    #[instrument(skip(self))]
+    pub async fn trade_volume(&self, pairs: &[&str]) -> AuthResult<TradeVolume> {
+        let signer = RequestSigner::new(&self.credentials, TRADE_VOLUME_PATH);
+        let signed = if pairs.is_empty() {
+            signer.sign_form::<&str, &str>(&[])?
+        } else {
+            signer.sign_form(&[("pair", pairs.join(","))])?
+        };
+
+        debug!("Requesting trade volume");
+
+        let body = signed
+            .to_builder_with_base(&self.client, &self.base_url)
+            .send()
+            .await?
+            .text()
+            .await?;
+        TradeVolume::from_json(&body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_parses_tiers_and_falls_back_to_taker() {
+        let volume = TradeVolume::from_json(
+            r#"{"error":[],"result":{"currency":"ZUSD","volume":"0.0000",
+                "fees":{"XXBTZUSD":{"fee":"0.4000","minfee":"0.1000","maxfee":"0.4000",
+                    "nextfee":"0.3500","nextvolume":"10000.0000","tiervolume":"0.0000"},
+                    "USDTZUSD":{"fee":"0.2000","minfee":"0.0000","maxfee":"0.2000",
+                    "nextfee":null,"nextvolume":null,"tiervolume":"0.0000"}},
+                "fees_maker":{"XXBTZUSD":{"fee":"0.2500","minfee":"0.0000","maxfee":"0.2500",
+                    "nextfee":"0.2000","nextvolume":"10000.0000","tiervolume":"0.0000"}}}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(volume.volume, Decimal::ZERO);
+        assert_eq!(volume.taker_rate("XXBTZUSD"), Some(dec!(0.004)));
+        assert_eq!(volume.maker_rate("XXBTZUSD"), Some(dec!(0.0025)));
+        assert_eq!(volume.taker("XXBTZUSD").unwrap().next_volume, Some(dec!(10000)));
+        // No maker schedule: maker pays the taker fee
+        assert_eq!(volume.maker_rate("USDTZUSD"), Some(dec!(0.002)));
+        assert_eq!(volume.taker("USDTZUSD").unwrap().next_fee, None);
+        assert_eq!(volume.taker_rate("ETHUSD"), None);
+
+        let err = TradeVolume::from_json(r#"{"error":["EGeneral:Permission denied"]}"#).unwrap_err();
+        assert!(matches!(err, AuthError::Api(message) if message.contains("Permission denied")));
+    }
+}
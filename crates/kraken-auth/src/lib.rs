@@ -2,6 +2,8 @@
 //!
 //! This crate provides authentication utilities for Kraken's WebSocket APIs.
 //! The primary use case is obtaining WebSocket tokens for private channel subscriptions.
+//! The [`signing`] module also signs arbitrary private REST calls.
+//! [`fees`] fetches the account's fee tiers from the `TradeVolume` endpoint.
 //!
 //! # Example
 //!
@@ -26,8 +28,12 @@
 
 mod credentials;
 mod error;
+pub mod fees;
+pub mod signing;
 mod token;
 
 pub use credentials::{Credentials, RequestSigner};
 pub use error::{AuthError, AuthResult};
+pub use fees::{FeeClient, FeeTier, TradeVolume};
+pub use signing::{SignedRequest, API_KEY_HEADER, API_SIGN_HEADER, BASE_URL};
 pub use token::{TokenProvider, WsToken};
@@ -8,8 +8,6 @@ use reqwest::Client;
 use serde::Deserialize;
 use tracing::{debug, instrument};
 
-const BASE_URL: &str = "https://api.kraken.com";
-
 /// WebSocket authentication token
 #[derive(Debug, Clone)]
 pub struct WsToken {
@@ -93,25 +91,12 @@ impl TokenProvider {
     #[instrument(skip(self))]
     pub async fn get_ws_token(&self) -> AuthResult<WsToken> {
         let path = "/0/private/GetWebSocketsToken";
-        let signer = RequestSigner::new(&self.credentials, path);
-        let nonce = signer.nonce();
-
-        // Build POST data with nonce
-        let post_data =
-            serde_urlencoded::to_string([("nonce", nonce)]).map_err(|e| AuthError::Parse(e.to_string()))?;
-
-        let signature = signer.sign(&post_data);
-        let url = format!("{}{}", BASE_URL, path);
+        let signed = RequestSigner::new(&self.credentials, path).sign_form::<&str, &str>(&[])?;
 
         debug!("Requesting WebSocket token");
 
-        let response: TokenResponse = self
-            .client
-            .post(&url)
-            .header("API-Key", signer.api_key())
-            .header("API-Sign", signature)
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .body(post_data)
+        let response: TokenResponse = signed
+            .to_builder(&self.client)
             .send()
             .await?
             .json()
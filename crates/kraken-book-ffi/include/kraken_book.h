@@ -0,0 +1,170 @@
+#ifndef KRAKEN_BOOK_H
+#define KRAKEN_BOOK_H
+
+/* Generated by cbindgen from crates/kraken-book-ffi. Do not edit by hand. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Result of a fallible call
+enum KrakenStatus
+#if defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+  : int32_t
+#endif // defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+ {
+  // A snapshot was applied
+  KRAKEN_STATUS_SNAPSHOT = 0,
+  // A delta update was applied
+  KRAKEN_STATUS_UPDATE = 1,
+  // The message wasn't a book message (or the book isn't synced yet)
+  KRAKEN_STATUS_IGNORED = 2,
+  // A null pointer or invalid UTF-8 was passed
+  KRAKEN_STATUS_INVALID_ARGUMENT = -1,
+  // The message wasn't valid Kraken JSON
+  KRAKEN_STATUS_PARSE_ERROR = -2,
+  // The book failed checksum validation and needs a new snapshot
+  KRAKEN_STATUS_CHECKSUM_MISMATCH = -3,
+  // The engine panicked; the handle should be freed
+  KRAKEN_STATUS_PANIC = -4,
+  // An update older than the last applied one was dropped; the book needs a new snapshot
+  KRAKEN_STATUS_OUT_OF_ORDER_UPDATE = -5,
+};
+#ifndef __cplusplus
+#if __STDC_VERSION__ >= 202311L
+typedef enum KrakenStatus KrakenStatus;
+#else
+typedef int32_t KrakenStatus;
+#endif // __STDC_VERSION__ >= 202311L
+#endif // __cplusplus
+
+// Synchronization state of a book
+enum KrakenBookState
+#if defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+  : int32_t
+#endif // defined(__cplusplus) || __STDC_VERSION__ >= 202311L
+ {
+  // No data yet
+  KRAKEN_BOOK_STATE_UNINITIALIZED = 0,
+  // Waiting for a snapshot
+  KRAKEN_BOOK_STATE_AWAITING_SNAPSHOT = 1,
+  // Applying updates normally
+  KRAKEN_BOOK_STATE_SYNCED = 2,
+  // Checksum failed, needs a new snapshot
+  KRAKEN_BOOK_STATE_DESYNCHRONIZED = 3,
+};
+#ifndef __cplusplus
+#if __STDC_VERSION__ >= 202311L
+typedef enum KrakenBookState KrakenBookState;
+#else
+typedef int32_t KrakenBookState;
+#endif // __STDC_VERSION__ >= 202311L
+#endif // __cplusplus
+
+// Opaque orderbook handle
+typedef struct KrakenBook KrakenBook;
+
+// One price level, copied into caller buffers
+typedef struct {
+  // Price
+  double price;
+  // Quantity
+  double qty;
+} KrakenLevel;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Description of the last error on this thread, or NULL
+//
+// The string stays valid until the next failing call on the same thread.
+const char *kraken_last_error(void);
+
+// Create a book for `symbol` keeping `depth` levels per side
+//
+// Returns NULL if `symbol` is NULL or not UTF-8.
+//
+// # Safety
+//
+// `symbol` must be NULL or a valid NUL-terminated string.
+KrakenBook *kraken_book_new(const char *symbol, uint32_t depth);
+
+// Release a book; NULL is ignored
+//
+// # Safety
+//
+// `book` must be NULL or a handle from [`kraken_book_new`] that hasn't
+// been freed.
+void kraken_book_free(KrakenBook *book);
+
+// Apply one raw WebSocket message (`len` bytes of UTF-8 JSON)
+//
+// # Safety
+//
+// `book` must be a live handle and `json` must point to `len` readable
+// bytes.
+KrakenStatus kraken_book_apply_message(KrakenBook *book, const uint8_t *json, size_t len);
+
+// Set the decimal precision used for checksums (from instrument data)
+//
+// # Safety
+//
+// `book` must be NULL or a live handle.
+void kraken_book_set_precision(KrakenBook *book, uint8_t price_precision, uint8_t qty_precision);
+
+// Copy up to `capacity` bid levels, best first, into `out`
+//
+// Returns the number of levels written.
+//
+// # Safety
+//
+// `book` must be NULL or a live handle, and `out` must be NULL or point
+// to `capacity` writable levels.
+size_t kraken_book_top_bids(const KrakenBook *book, KrakenLevel *out, size_t capacity);
+
+// Copy up to `capacity` ask levels, best first, into `out`
+//
+// Returns the number of levels written.
+//
+// # Safety
+//
+// `book` must be NULL or a live handle, and `out` must be NULL or point
+// to `capacity` writable levels.
+size_t kraken_book_top_asks(const KrakenBook *book, KrakenLevel *out, size_t capacity);
+
+// Checksum of the last validated message (0 before the first snapshot)
+//
+// # Safety
+//
+// `book` must be NULL or a live handle.
+uint32_t kraken_book_checksum(const KrakenBook *book);
+
+// Recompute the Kraken checksum from the book's current levels
+//
+// # Safety
+//
+// `book` must be NULL or a live handle.
+uint32_t kraken_book_compute_checksum(const KrakenBook *book);
+
+// Synchronization state of the book
+//
+// # Safety
+//
+// `book` must be NULL or a live handle.
+KrakenBookState kraken_book_state(const KrakenBook *book);
+
+// Clear all levels and return to the uninitialized state
+//
+// # Safety
+//
+// `book` must be NULL or a live handle.
+void kraken_book_reset(KrakenBook *book);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* KRAKEN_BOOK_H */
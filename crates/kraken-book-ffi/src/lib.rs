@@ -0,0 +1,446 @@
+//! C ABI for the Kraken orderbook engine
+//!
+//! Lets C, C++ and C# systems embed the same checksum-validated book the
+//! SDK uses. The matching header is `include/kraken_book.h`, generated with
+//! cbindgen (see `cbindgen.toml`).
+//!
+//! # Conventions
+//!
+//! - Books are opaque handles from [`kraken_book_new`], released with
+//!   [`kraken_book_free`]. A handle must not be used from two threads at
+//!   once.
+//! - Fallible calls return a [`KrakenStatus`]; negative values are errors,
+//!   and [`kraken_last_error`] describes the most recent one on the calling
+//!   thread.
+//! - Levels are copied into caller-owned buffers, so no Rust memory is ever
+//!   handed out except the handle itself.
+//! - Panics never cross the boundary; they're reported as
+//!   [`KrakenStatus::Panic`].
+//!
+//! ```c
+//! KrakenBook *book = kraken_book_new("BTC/USD", 10);
+//! if (kraken_book_apply_message(book, msg, msg_len) < 0) {
+//!     fprintf(stderr, "%s\n", kraken_last_error());
+//! }
+//! KrakenLevel bids[10];
+//! size_t n = kraken_book_top_bids(book, bids, 10);
+//! kraken_book_free(book);
+//! ```
+
+use kraken_book::{compute_checksum_with_precision, ApplyError, ApplyResult, Orderbook, OrderbookState};
+use kraken_types::{Level, WsMessage};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+/// Opaque orderbook handle
+pub struct KrakenBook {
+    inner: Orderbook,
+}
+
+/// One price level, copied into caller buffers
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct KrakenLevel {
+    /// Price
+    pub price: f64,
+    /// Quantity
+    pub qty: f64,
+}
+
+/// Result of a fallible call
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KrakenStatus {
+    /// A snapshot was applied
+    Snapshot = 0,
+    /// A delta update was applied
+    Update = 1,
+    /// The message wasn't a book message (or the book isn't synced yet)
+    Ignored = 2,
+    /// A null pointer or invalid UTF-8 was passed
+    InvalidArgument = -1,
+    /// The message wasn't valid Kraken JSON
+    ParseError = -2,
+    /// The book failed checksum validation and needs a new snapshot
+    ChecksumMismatch = -3,
+    /// The engine panicked; the handle should be freed
+    Panic = -4,
+    /// An update older than the last applied one was dropped; the book needs a new snapshot
+    OutOfOrderUpdate = -5,
+}
+
+/// Synchronization state of a book
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KrakenBookState {
+    /// No data yet
+    Uninitialized = 0,
+    /// Waiting for a snapshot
+    AwaitingSnapshot = 1,
+    /// Applying updates normally
+    Synced = 2,
+    /// Checksum failed, needs a new snapshot
+    Desynchronized = 3,
+}
+
+impl From<OrderbookState> for KrakenBookState {
+    fn from(state: OrderbookState) -> Self {
+        match state {
+            OrderbookState::Uninitialized => Self::Uninitialized,
+            OrderbookState::AwaitingSnapshot => Self::AwaitingSnapshot,
+            OrderbookState::Synced => Self::Synced,
+            OrderbookState::Desynchronized => Self::Desynchronized,
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_error(message: impl Into<String>) {
+    // Interior NULs would truncate the message; replace them
+    let message = message.into().replace('\0', " ");
+    LAST_ERROR.with(|e| *e.borrow_mut() = CString::new(message).ok());
+}
+
+fn guard<T>(on_panic: T, f: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|_| {
+        set_error("panic inside the orderbook engine");
+        on_panic
+    })
+}
+
+/// Description of the last error on this thread, or NULL
+///
+/// The string stays valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn kraken_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| e.borrow().as_ref().map_or(std::ptr::null(), |s| s.as_ptr()))
+}
+
+/// Create a book for `symbol` keeping `depth` levels per side
+///
+/// Returns NULL if `symbol` is NULL or not UTF-8.
+///
+/// # Safety
+///
+/// `symbol` must be NULL or a valid NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn kraken_book_new(symbol: *const c_char, depth: u32) -> *mut KrakenBook {
+    if symbol.is_null() {
+        set_error("symbol is NULL");
+        return std::ptr::null_mut();
+    }
+    let Ok(symbol) = CStr::from_ptr(symbol).to_str() else {
+        set_error("symbol is not valid UTF-8");
+        return std::ptr::null_mut();
+    };
+    guard(std::ptr::null_mut(), || {
+        Box::into_raw(Box::new(KrakenBook {
+            inner: Orderbook::with_depth(symbol, depth),
+        }))
+    })
+}
+
+/// Release a book; NULL is ignored
+///
+/// # Safety
+///
+/// `book` must be NULL or a handle from [`kraken_book_new`] that hasn't
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn kraken_book_free(book: *mut KrakenBook) {
+    if !book.is_null() {
+        drop(Box::from_raw(book));
+    }
+}
+
+/// Apply one raw WebSocket message (`len` bytes of UTF-8 JSON)
+///
+/// # Safety
+///
+/// `book` must be a live handle and `json` must point to `len` readable
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn kraken_book_apply_message(
+    book: *mut KrakenBook,
+    json: *const u8,
+    len: usize,
+) -> KrakenStatus {
+    let Some(book) = book.as_mut() else {
+        set_error("book is NULL");
+        return KrakenStatus::InvalidArgument;
+    };
+    if json.is_null() {
+        set_error("json is NULL");
+        return KrakenStatus::InvalidArgument;
+    }
+    let Ok(json) = std::str::from_utf8(std::slice::from_raw_parts(json, len)) else {
+        set_error("message is not valid UTF-8");
+        return KrakenStatus::InvalidArgument;
+    };
+    guard(KrakenStatus::Panic, || apply_message(&mut book.inner, json))
+}
+
+fn apply_message(book: &mut Orderbook, json: &str) -> KrakenStatus {
+    let msg = match WsMessage::parse(json) {
+        Ok(msg) => msg,
+        Err(e) => {
+            set_error(e.to_string());
+            return KrakenStatus::ParseError;
+        }
+    };
+    let WsMessage::Book(msg) = msg else {
+        return KrakenStatus::Ignored;
+    };
+    let Some(data) = msg.data.first() else {
+        return KrakenStatus::Ignored;
+    };
+    let is_snapshot = msg.msg_type == "snapshot";
+    match book.apply_book_data(data, is_snapshot) {
+        Ok(ApplyResult::Snapshot) => KrakenStatus::Snapshot,
+        Ok(ApplyResult::Update) => KrakenStatus::Update,
+        Ok(ApplyResult::Ignored) => KrakenStatus::Ignored,
+        Err(ApplyError::UpdateBeforeSnapshot { .. }) => KrakenStatus::Ignored,
+        // Applied; the condition stays readable through kraken_last_error
+        Err(e) if e.was_applied() => {
+            set_error(e.to_string());
+            if is_snapshot {
+                KrakenStatus::Snapshot
+            } else {
+                KrakenStatus::Update
+            }
+        }
+        Err(e @ ApplyError::OutOfOrderUpdate { .. }) => {
+            set_error(e.to_string());
+            KrakenStatus::OutOfOrderUpdate
+        }
+        Err(e) => {
+            set_error(e.to_string());
+            KrakenStatus::ChecksumMismatch
+        }
+    }
+}
+
+/// Set the decimal precision used for checksums (from instrument data)
+///
+/// # Safety
+///
+/// `book` must be NULL or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn kraken_book_set_precision(book: *mut KrakenBook, price_precision: u8, qty_precision: u8) {
+    if let Some(book) = book.as_mut() {
+        book.inner.set_precision(price_precision, qty_precision);
+    }
+}
+
+unsafe fn copy_levels(levels: Vec<Level>, out: *mut KrakenLevel, capacity: usize) -> usize {
+    if out.is_null() {
+        return 0;
+    }
+    let out = std::slice::from_raw_parts_mut(out, capacity);
+    for (slot, level) in out.iter_mut().zip(&levels) {
+        *slot = KrakenLevel {
+            price: level.price_f64(),
+            qty: level.qty_f64(),
+        };
+    }
+    levels.len().min(capacity)
+}
+
+/// Copy up to `capacity` bid levels, best first, into `out`
+///
+/// Returns the number of levels written.
+///
+/// # Safety
+///
+/// `book` must be NULL or a live handle, and `out` must be NULL or point
+/// to `capacity` writable levels.
+#[no_mangle]
+pub unsafe extern "C" fn kraken_book_top_bids(book: *const KrakenBook, out: *mut KrakenLevel, capacity: usize) -> usize {
+    match book.as_ref() {
+        Some(book) => guard(0, || copy_levels(book.inner.top_bids(capacity), out, capacity)),
+        None => 0,
+    }
+}
+
+/// Copy up to `capacity` ask levels, best first, into `out`
+///
+/// Returns the number of levels written.
+///
+/// # Safety
+///
+/// `book` must be NULL or a live handle, and `out` must be NULL or point
+/// to `capacity` writable levels.
+#[no_mangle]
+pub unsafe extern "C" fn kraken_book_top_asks(book: *const KrakenBook, out: *mut KrakenLevel, capacity: usize) -> usize {
+    match book.as_ref() {
+        Some(book) => guard(0, || copy_levels(book.inner.top_asks(capacity), out, capacity)),
+        None => 0,
+    }
+}
+
+/// Checksum of the last validated message (0 before the first snapshot)
+///
+/// # Safety
+///
+/// `book` must be NULL or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn kraken_book_checksum(book: *const KrakenBook) -> u32 {
+    book.as_ref().map_or(0, |book| book.inner.last_checksum())
+}
+
+/// Recompute the Kraken checksum from the book's current levels
+///
+/// # Safety
+///
+/// `book` must be NULL or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn kraken_book_compute_checksum(book: *const KrakenBook) -> u32 {
+    let Some(book) = book.as_ref() else {
+        return 0;
+    };
+    guard(0, || {
+        let book = &book.inner;
+        compute_checksum_with_precision(
+            &book.bids_vec(),
+            &book.asks_vec(),
+            book.price_precision(),
+            book.qty_precision(),
+        )
+    })
+}
+
+/// Synchronization state of the book
+///
+/// # Safety
+///
+/// `book` must be NULL or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn kraken_book_state(book: *const KrakenBook) -> KrakenBookState {
+    book.as_ref()
+        .map_or(KrakenBookState::Uninitialized, |book| book.inner.state().into())
+}
+
+/// Clear all levels and return to the uninitialized state
+///
+/// # Safety
+///
+/// `book` must be NULL or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn kraken_book_reset(book: *mut KrakenBook) {
+    if let Some(book) = book.as_mut() {
+        book.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_book::compute_checksum;
+
+    fn snapshot_message() -> String {
+        let bids = [Level::from_f64(100.0, 1.0), Level::from_f64(99.0, 2.0)];
+        let asks = [Level::from_f64(101.0, 1.5)];
+        format!(
+            r#"{{"channel":"book","type":"snapshot","data":[{{"symbol":"BTC/USD",
+            "bids":[{{"price":100.0,"qty":1.0}},{{"price":99.0,"qty":2.0}}],
+            "asks":[{{"price":101.0,"qty":1.5}}],"checksum":{}}}]}}"#,
+            compute_checksum(&bids, &asks)
+        )
+    }
+
+    #[test]
+    fn test_book_lifecycle() {
+        unsafe {
+            let symbol = CString::new("BTC/USD").unwrap();
+            let book = kraken_book_new(symbol.as_ptr(), 10);
+            assert!(!book.is_null());
+            assert_eq!(kraken_book_state(book), KrakenBookState::Uninitialized);
+
+            let msg = snapshot_message();
+            assert_eq!(kraken_book_apply_message(book, msg.as_ptr(), msg.len()), KrakenStatus::Snapshot);
+            assert_eq!(kraken_book_state(book), KrakenBookState::Synced);
+            assert_eq!(kraken_book_checksum(book), kraken_book_compute_checksum(book));
+
+            let mut levels = [KrakenLevel::default(); 1];
+            assert_eq!(kraken_book_top_bids(book, levels.as_mut_ptr(), levels.len()), 1);
+            assert_eq!(levels[0], KrakenLevel { price: 100.0, qty: 1.0 });
+            let mut levels = [KrakenLevel::default(); 4];
+            assert_eq!(kraken_book_top_asks(book, levels.as_mut_ptr(), levels.len()), 1);
+
+            kraken_book_reset(book);
+            assert_eq!(kraken_book_top_bids(book, levels.as_mut_ptr(), levels.len()), 0);
+            kraken_book_free(book);
+        }
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        unsafe {
+            let symbol = CString::new("BTC/USD").unwrap();
+            let book = kraken_book_new(symbol.as_ptr(), 10);
+            let bad = b"not json";
+            assert_eq!(kraken_book_apply_message(book, bad.as_ptr(), bad.len()), KrakenStatus::ParseError);
+            assert!(!kraken_last_error().is_null());
+
+            let msg = snapshot_message().replace("\"qty\":1.5", "\"qty\":2.5");
+            assert_eq!(kraken_book_apply_message(book, msg.as_ptr(), msg.len()), KrakenStatus::ChecksumMismatch);
+            let error = CStr::from_ptr(kraken_last_error()).to_str().unwrap();
+            assert!(error.contains("Checksum mismatch"));
+            assert_eq!(kraken_book_state(book), KrakenBookState::Desynchronized);
+
+            assert_eq!(
+                kraken_book_apply_message(std::ptr::null_mut(), bad.as_ptr(), bad.len()),
+                KrakenStatus::InvalidArgument
+            );
+            kraken_book_free(book);
+        }
+    }
+
+    #[test]
+    fn test_header_declares_every_export() {
+        let header = include_str!("../include/kraken_book.h");
+        for name in [
+            "kraken_last_error",
+            "kraken_book_new",
+            "kraken_book_free",
+            "kraken_book_apply_message",
+            "kraken_book_set_precision",
+            "kraken_book_top_bids",
+            "kraken_book_top_asks",
+            "kraken_book_checksum",
+            "kraken_book_compute_checksum",
+            "kraken_book_state",
+            "kraken_book_reset",
+        ] {
+            assert!(header.contains(&format!("{}(", name)), "{} missing from header", name);
+        }
+    }
+    /// Fails when the committed header drifts from what cbindgen generates
+    ///
+    /// Set `KRAKEN_BLESS_HEADER=1` to rewrite `include/kraken_book.h`.
+    #[test]
+    fn test_header_is_up_to_date() {
+        let crate_dir = env!("CARGO_MANIFEST_DIR");
+        let config = cbindgen::Config::from_file(format!("{}/cbindgen.toml", crate_dir)).unwrap();
+        let mut generated = Vec::new();
+        cbindgen::generate_with_config(crate_dir, config)
+            .expect("cbindgen failed")
+            .write(&mut generated);
+
+        let path = format!("{}/include/kraken_book.h", crate_dir);
+        if std::env::var_os("KRAKEN_BLESS_HEADER").is_some() {
+            std::fs::write(&path, &generated).unwrap();
+            return;
+        }
+        let committed = std::fs::read(&path).unwrap();
+        assert!(
+            committed == generated,
+            "include/kraken_book.h is out of date; rerun with KRAKEN_BLESS_HEADER=1"
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! Python bindings for the Kraken orderbook engine
+//!
+//! Exposes the same [`Orderbook`], [`L3Book`] and [`HistoryBuffer`] that the
+//! SDK and the WASM build use, so backtests replay recorded WebSocket
+//! messages through the production book logic, checksums included.
+//!
+//! Prices and quantities cross the boundary as strings when they go into
+//! the book (no float rounding on input) and as floats when they come out,
+//! which is what pandas and numpy expect. Full-precision snapshots are
+//! available as JSON via `snapshot_json`.
+//!
+//! ```python
+//! from kraken_book import Orderbook
+//!
+//! book = Orderbook("BTC/USD", depth=10)
+//! book.enable_history(1000)
+//! for line in open("capture.jsonl"):
+//!     book.apply_message(line)
+//! print(book.best_bid(), book.best_ask(), book.spread())
+//! ```
+//!
+//! Build with `maturin develop` (or `maturin build --release`) from this
+//! directory.
+
+// pyo3 0.22's #[pymethods] expansion converts PyErr into itself
+#![allow(clippy::useless_conversion)]
+
+use kraken_book::{ApplyError, ApplyResult, HistoryBuffer, L3Book, L3Order, L3Side, Orderbook, OrderbookState};
+use kraken_types::{Level, WsMessage};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use rust_decimal::Decimal;
+use std::str::FromStr;
+
+fn decimal(value: &str) -> PyResult<Decimal> {
+    Decimal::from_str(value).map_err(|e| PyValueError::new_err(format!("invalid decimal {:?}: {}", value, e)))
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.to_string().parse().unwrap_or(0.0)
+}
+
+fn levels(levels: &[Level], n: Option<usize>) -> Vec<(f64, f64)> {
+    levels
+        .iter()
+        .take(n.unwrap_or(usize::MAX))
+        .map(|l| (l.price_f64(), l.qty_f64()))
+        .collect()
+}
+
+fn state_name(state: OrderbookState) -> &'static str {
+    match state {
+        OrderbookState::Uninitialized => "uninitialized",
+        OrderbookState::AwaitingSnapshot => "awaiting_snapshot",
+        OrderbookState::Synced => "synced",
+        OrderbookState::Desynchronized => "desynchronized",
+    }
+}
+
+fn side(value: &str) -> PyResult<L3Side> {
+    match value {
+        "bid" | "buy" => Ok(L3Side::Bid),
+        "ask" | "sell" => Ok(L3Side::Ask),
+        other => Err(PyValueError::new_err(format!("side must be 'bid' or 'ask', got {:?}", other))),
+    }
+}
+
+/// Level 2 orderbook with checksum validation
+#[pyclass(name = "Orderbook", module = "kraken_book")]
+pub struct PyOrderbook {
+    inner: Orderbook,
+    history: Option<HistoryBuffer>,
+}
+
+#[pymethods]
+impl PyOrderbook {
+    #[new]
+    #[pyo3(signature = (symbol, depth = 10))]
+    fn new(symbol: &str, depth: u32) -> Self {
+        Self {
+            inner: Orderbook::with_depth(symbol, depth),
+            history: None,
+        }
+    }
+
+    /// Apply a raw WebSocket message
+    ///
+    /// Returns "snapshot", "update" or "ignored". Raises ValueError on
+    /// malformed JSON, a checksum mismatch or an out-of-order update.
+    fn apply_message(&mut self, json: &str) -> PyResult<&'static str> {
+        let msg = WsMessage::parse(json).map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let WsMessage::Book(book) = msg else {
+            return Ok("ignored");
+        };
+        let Some(data) = book.data.first() else {
+            return Ok("ignored");
+        };
+        let is_snapshot = book.msg_type == "snapshot";
+        let result = match self.inner.apply_book_data(data, is_snapshot) {
+            Ok(result) => result,
+            Err(ApplyError::UpdateBeforeSnapshot { .. }) => ApplyResult::Ignored,
+            Err(e) if e.was_applied() && is_snapshot => ApplyResult::Snapshot,
+            Err(e) if e.was_applied() => ApplyResult::Update,
+            Err(e) => return Err(PyValueError::new_err(e.to_string())),
+        };
+        if let Some(history) = &mut self.history {
+            history.push(self.inner.snapshot());
+        }
+        Ok(match result {
+            ApplyResult::Snapshot => "snapshot",
+            ApplyResult::Update => "update",
+            ApplyResult::Ignored => "ignored",
+        })
+    }
+
+    /// Set the price and quantity precision used for checksums
+    fn set_precision(&mut self, price_precision: u8, qty_precision: u8) {
+        self.inner.set_precision(price_precision, qty_precision);
+    }
+
+    #[getter]
+    fn symbol(&self) -> String {
+        self.inner.symbol().to_string()
+    }
+
+    #[getter]
+    fn state(&self) -> &'static str {
+        state_name(self.inner.state())
+    }
+
+    #[getter]
+    fn checksum(&self) -> u32 {
+        self.inner.last_checksum()
+    }
+
+    fn is_synced(&self) -> bool {
+        self.inner.is_synced()
+    }
+
+    /// Bid levels as (price, qty) tuples, best first
+    #[pyo3(signature = (n = None))]
+    fn bids(&self, n: Option<usize>) -> Vec<(f64, f64)> {
+        levels(&self.inner.bids_vec(), n)
+    }
+
+    /// Ask levels as (price, qty) tuples, best first
+    #[pyo3(signature = (n = None))]
+    fn asks(&self, n: Option<usize>) -> Vec<(f64, f64)> {
+        levels(&self.inner.asks_vec(), n)
+    }
+
+    fn best_bid(&self) -> Option<(f64, f64)> {
+        self.inner.best_bid().map(|l| (l.price_f64(), l.qty_f64()))
+    }
+
+    fn best_ask(&self) -> Option<(f64, f64)> {
+        self.inner.best_ask().map(|l| (l.price_f64(), l.qty_f64()))
+    }
+
+    fn spread(&self) -> Option<f64> {
+        self.inner.spread().map(to_f64)
+    }
+
+    fn mid_price(&self) -> Option<f64> {
+        self.inner.mid_price().map(to_f64)
+    }
+
+    fn microprice(&self) -> Option<f64> {
+        self.inner.snapshot().microprice().map(to_f64)
+    }
+
+    /// Full-precision snapshot as JSON (decimals as strings)
+    fn snapshot_json(&self) -> PyResult<String> {
+        serde_json::to_string(&self.inner.snapshot()).map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn reset(&mut self) {
+        self.inner.reset();
+        if let Some(history) = &mut self.history {
+            history.clear();
+        }
+    }
+
+    /// Record a snapshot after every applied message
+    fn enable_history(&mut self, max_snapshots: usize) {
+        self.history = Some(HistoryBuffer::new(max_snapshots));
+    }
+
+    fn disable_history(&mut self) {
+        self.history = None;
+    }
+
+    fn history_len(&self) -> usize {
+        self.history.as_ref().map_or(0, HistoryBuffer::len)
+    }
+
+    /// Historical snapshot by index (0 = oldest) as JSON
+    fn history_json(&self, index: usize) -> PyResult<Option<String>> {
+        let Some(entry) = self.history.as_ref().and_then(|h| h.get(index)) else {
+            return Ok(None);
+        };
+        serde_json::to_string(&entry.snapshot)
+            .map(Some)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "Orderbook(symbol={:?}, state={}, bids={}, asks={})",
+            self.inner.symbol(),
+            state_name(self.inner.state()),
+            self.inner.bid_count(),
+            self.inner.ask_count()
+        )
+    }
+}
+
+/// Level 3 (order-by-order) book with queue positions
+#[pyclass(name = "L3Book", module = "kraken_book")]
+pub struct PyL3Book {
+    inner: L3Book,
+}
+
+#[pymethods]
+impl PyL3Book {
+    #[new]
+    #[pyo3(signature = (symbol, depth = 10))]
+    fn new(symbol: &str, depth: u32) -> Self {
+        Self {
+            inner: L3Book::new(symbol, depth),
+        }
+    }
+
+    /// Add an order; `side` is "bid" or "ask", price and qty are strings
+    fn add_order(&mut self, order_id: &str, side_name: &str, price: &str, qty: &str) -> PyResult<bool> {
+        let order = L3Order::new(order_id, decimal(price)?, decimal(qty)?);
+        Ok(self.inner.add_order(order, side(side_name)?))
+    }
+
+    fn remove_order(&mut self, order_id: &str) -> bool {
+        self.inner.remove_order(order_id).is_some()
+    }
+
+    fn modify_order(&mut self, order_id: &str, qty: &str) -> PyResult<bool> {
+        Ok(self.inner.modify_order(order_id, decimal(qty)?))
+    }
+
+    /// (position, qty_ahead, total_orders, total_qty) for an order
+    fn queue_position(&self, order_id: &str) -> Option<(usize, f64, usize, f64)> {
+        self.inner
+            .queue_position(order_id)
+            .map(|p| (p.position, to_f64(p.qty_ahead), p.total_orders, to_f64(p.total_qty)))
+    }
+
+    /// Aggregated bid levels as (price, qty) tuples, best first
+    #[pyo3(signature = (n = None))]
+    fn bids(&self, n: Option<usize>) -> Vec<(f64, f64)> {
+        levels(&self.inner.aggregated_bids(), n)
+    }
+
+    /// Aggregated ask levels as (price, qty) tuples, best first
+    #[pyo3(signature = (n = None))]
+    fn asks(&self, n: Option<usize>) -> Vec<(f64, f64)> {
+        levels(&self.inner.aggregated_asks(), n)
+    }
+
+    fn best_bid(&self) -> Option<f64> {
+        self.inner.best_bid_price().map(to_f64)
+    }
+
+    fn best_ask(&self) -> Option<f64> {
+        self.inner.best_ask_price().map(to_f64)
+    }
+
+    fn spread(&self) -> Option<f64> {
+        self.inner.spread().map(to_f64)
+    }
+
+    fn mid_price(&self) -> Option<f64> {
+        self.inner.mid_price().map(to_f64)
+    }
+
+    fn imbalance(&self) -> Option<f64> {
+        self.inner.imbalance()
+    }
+
+    fn order_count(&self) -> usize {
+        self.inner.order_count()
+    }
+
+    fn checksum(&self) -> u32 {
+        self.inner.compute_checksum()
+    }
+
+    fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    fn __repr__(&self) -> String {
+        format!("L3Book(symbol={:?}, orders={})", self.inner.symbol(), self.inner.order_count())
+    }
+}
+
+/// Compute a Kraken book checksum from (price, qty) string pairs
+#[pyfunction]
+fn compute_checksum(bids: Vec<(String, String)>, asks: Vec<(String, String)>) -> PyResult<u32> {
+    let parse = |side: Vec<(String, String)>| -> PyResult<Vec<Level>> {
+        side.iter()
+            .map(|(p, q)| Ok(Level::new(decimal(p)?, decimal(q)?)))
+            .collect()
+    };
+    Ok(kraken_book::compute_checksum(&parse(bids)?, &parse(asks)?))
+}
+
+#[pymodule]
+#[pyo3(name = "kraken_book")]
+fn kraken_book_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyOrderbook>()?;
+    m.add_class::<PyL3Book>()?;
+    m.add_function(wrap_pyfunction!(compute_checksum, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,79 @@
+"""Smoke test for the kraken_book extension module.
+
+Run after `maturin develop`:
+
+    python -m unittest discover -s tests
+"""
+
+import json
+import unittest
+
+from kraken_book import L3Book, Orderbook, compute_checksum
+
+BIDS = [("50000.0", "1.5"), ("49999.0", "2.0")]
+ASKS = [("50001.0", "1.0"), ("50002.0", "3.0")]
+
+
+def book_message(msg_type, bids, asks, checksum):
+    levels = lambda side: [{"price": float(p), "qty": float(q)} for p, q in side]
+    return json.dumps({
+        "channel": "book",
+        "type": msg_type,
+        "data": [{
+            "symbol": "BTC/USD",
+            "bids": levels(bids),
+            "asks": levels(asks),
+            "checksum": checksum,
+        }],
+    })
+
+
+class OrderbookTest(unittest.TestCase):
+    def test_snapshot_and_update_stay_synced(self):
+        book = Orderbook("BTC/USD", depth=10)
+        book.enable_history(10)
+
+        checksum = compute_checksum(BIDS, ASKS)
+        self.assertEqual(book.apply_message(book_message("snapshot", BIDS, ASKS, checksum)), "snapshot")
+        self.assertTrue(book.is_synced())
+        self.assertEqual(book.checksum, checksum)
+        self.assertEqual(book.best_bid(), (50000.0, 1.5))
+        self.assertEqual(book.best_ask(), (50001.0, 1.0))
+        self.assertEqual(book.spread(), 1.0)
+
+        bids = [("50000.0", "0.5"), ("49999.0", "2.0")]
+        update = book_message("update", bids[:1], [], compute_checksum(bids, ASKS))
+        self.assertEqual(book.apply_message(update), "update")
+        self.assertTrue(book.is_synced())
+        self.assertEqual(book.bids(1), [(50000.0, 0.5)])
+        self.assertEqual(book.history_len(), 2)
+        self.assertEqual(json.loads(book.snapshot_json())["symbol"], "BTC/USD")
+
+    def test_checksum_mismatch_raises(self):
+        book = Orderbook("BTC/USD")
+        with self.assertRaises(ValueError):
+            book.apply_message(book_message("snapshot", BIDS, ASKS, 1))
+        self.assertFalse(book.is_synced())
+
+    def test_non_book_messages_are_ignored(self):
+        book = Orderbook("BTC/USD")
+        self.assertEqual(book.apply_message('{"channel":"heartbeat"}'), "ignored")
+
+
+class L3BookTest(unittest.TestCase):
+    def test_queue_position(self):
+        book = L3Book("BTC/USD")
+        self.assertTrue(book.add_order("O1", "buy", "100", "2"))
+        self.assertTrue(book.add_order("O2", "buy", "100", "3"))
+        self.assertTrue(book.add_order("O3", "sell", "101", "1"))
+
+        self.assertEqual(book.order_count(), 3)
+        self.assertEqual(book.bids(), [(100.0, 5.0)])
+        self.assertEqual(book.queue_position("O2")[0], 1)
+        self.assertTrue(book.remove_order("O1"))
+        self.assertEqual(book.queue_position("O2")[0], 0)
+        self.assertIsNone(book.queue_position("O1"))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -181,7 +181,7 @@ fn bench_l3_vwap(c: &mut Criterion) {
             &size,
             |b, &size| {
                 b.iter(|| {
-                    let result = book.vwap_bid(black_box(size));
+                    let result = book.bid_vwap(black_box(size));
                     black_box(result)
                 })
             },
@@ -202,6 +202,69 @@ fn bench_l3_snapshot(c: &mut Criterion) {
     });
 }
 
+/// Create a D1000 book with `orders_per_level` resting orders at each level
+fn create_deep_l3_book(levels: usize, orders_per_level: usize) -> L3Book {
+    let mut book = L3Book::new("BTC/USD", 1000);
+    for level in 0..levels {
+        let bid = dec!(100000) - Decimal::from(level as i64);
+        let ask = dec!(100001) + Decimal::from(level as i64);
+        for i in 0..orders_per_level {
+            let qty = dec!(0.1) + Decimal::from(i as i64) / dec!(1000);
+            book.add_order(L3Order::new(format!("b{}_{}", level, i), bid, qty), L3Side::Bid);
+            book.add_order(L3Order::new(format!("a{}_{}", level, i), ask, qty), L3Side::Ask);
+        }
+    }
+    book
+}
+
+fn bench_l3_large_book(c: &mut Criterion) {
+    let mut group = c.benchmark_group("l3_large_book");
+    group.throughput(Throughput::Elements(1));
+
+    // 1000 levels x 20 orders x 2 sides = 40,000 resting orders
+    let mut book = create_deep_l3_book(1000, 20);
+    let ids: Vec<String> = (0..20).map(|i| format!("b500_{}", i)).collect();
+    let mut next = 0usize;
+    group.bench_function("fifo_churn_40k", |b| {
+        b.iter(|| {
+            // Consume the front of a level and requeue a fresh order at the back
+            let id = &ids[next % ids.len()];
+            let order = book.remove_order(black_box(id)).unwrap();
+            book.add_order(black_box(order), L3Side::Bid);
+            next += 1;
+        })
+    });
+
+    // A single hot level holding 2,000 orders
+    let mut book = create_deep_l3_book(1, 2000);
+    group.bench_function("modify_mid_queue_2k", |b| {
+        let mut qty = dec!(1);
+        b.iter(|| {
+            qty += dec!(0.001);
+            black_box(book.modify_order(black_box("b0_1000"), qty))
+        })
+    });
+
+    group.bench_function("cancel_readd_mid_queue_2k", |b| {
+        b.iter(|| {
+            let order = book.remove_order(black_box("b0_1000")).unwrap();
+            black_box(book.add_order(order, L3Side::Bid))
+        })
+    });
+
+    // Same as above, skipping the order-ID lookup via handles
+    group.bench_function("modify_by_handle_2k", |b| {
+        let handle = book.order_handle("b0_500").unwrap();
+        let mut qty = dec!(1);
+        b.iter(|| {
+            qty += dec!(0.001);
+            black_box(book.modify_by_handle(black_box(handle), qty))
+        })
+    });
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_l3_add_order,
@@ -212,6 +275,7 @@ criterion_group!(
     bench_l3_best_bid_ask,
     bench_l3_vwap,
     bench_l3_snapshot,
+    bench_l3_large_book,
 );
 
 criterion_main!(benches);
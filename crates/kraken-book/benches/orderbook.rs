@@ -1,10 +1,23 @@
 //! Benchmarks for orderbook operations
 //!
 //! Run with: cargo bench --bench orderbook
+//!
+//! Apply-path results before/after removing allocations from checksum
+//! validation (one forward + one reverse delta per iteration):
+//!
+//! | benchmark                   | before  | after  |
+//! |-----------------------------|---------|--------|
+//! | apply_path/top_of_book/10   | 44.7 µs | 9.1 µs |
+//! | apply_path/top_of_book/100  | 42.3 µs | 8.6 µs |
+//! | apply_path/top_of_book/1000 | 67.5 µs | 8.6 µs |
+//! | apply_path/deep_level/100   | 44.6 µs | 8.2 µs |
+//! | apply_path/deep_level/1000  | 75.2 µs | 9.1 µs |
+//! | checksum_compute/10         | 24.0 µs | 4.6 µs |
+//! | apply_snapshot/500          | 304 µs  | 196 µs |
 
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use kraken_book::{compute_checksum, Orderbook, TreeBook};
-use kraken_types::{BookData, Level};
+use kraken_types::{BookData, Level, Qty};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
@@ -218,6 +231,63 @@ fn bench_orderbook_snapshot(c: &mut Criterion) {
     });
 }
 
+/// Build a synced book at `depth` plus two deltas that toggle one level back and forth
+fn create_toggle_deltas(depth: usize, level: usize) -> (Orderbook, BookData, BookData) {
+    let snapshot = create_book_data(depth, depth);
+    let mut book = Orderbook::with_depth("BTC/USD", depth as u32);
+    book.apply_book_data(&snapshot, true).unwrap();
+
+    let original = snapshot.bids[level].clone();
+    let changed = Level::new(original.price, original.qty + Qty::new(dec!(0.5)));
+
+    let mut bids = snapshot.bids.clone();
+    bids[level] = changed.clone();
+    let forward = BookData {
+        symbol: "BTC/USD".to_string(),
+        bids: vec![changed],
+        asks: vec![],
+        checksum: compute_checksum(&bids, &snapshot.asks),
+        timestamp: None,
+    };
+    let back = BookData {
+        symbol: "BTC/USD".to_string(),
+        bids: vec![original],
+        asks: vec![],
+        checksum: snapshot.checksum,
+        timestamp: None,
+    };
+    (book, forward, back)
+}
+
+fn bench_orderbook_apply_path(c: &mut Criterion) {
+    let mut group = c.benchmark_group("orderbook_apply_path");
+    group.throughput(Throughput::Elements(2));
+
+    for depth in [10, 100, 1000] {
+        // Update inside the checksummed top-10 window
+        let (mut book, forward, back) = create_toggle_deltas(depth, 0);
+        group.bench_function(BenchmarkId::new("top_of_book", depth), |b| {
+            b.iter(|| {
+                book.apply_book_data(black_box(&forward), false).unwrap();
+                book.apply_book_data(black_box(&back), false).unwrap();
+            })
+        });
+
+        // Update deep in the book, outside the checksum window
+        if depth > 10 {
+            let (mut book, forward, back) = create_toggle_deltas(depth, depth - 1);
+            group.bench_function(BenchmarkId::new("deep_level", depth), |b| {
+                b.iter(|| {
+                    book.apply_book_data(black_box(&forward), false).unwrap();
+                    book.apply_book_data(black_box(&back), false).unwrap();
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_treebook_insert,
@@ -227,6 +297,7 @@ criterion_group!(
     bench_checksum_compute,
     bench_spread_calculation,
     bench_orderbook_snapshot,
+    bench_orderbook_apply_path,
 );
 
 criterion_main!(benches);
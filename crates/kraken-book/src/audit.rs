@@ -0,0 +1,183 @@
+//! Consistency checks for a live orderbook
+//!
+//! [`audit`] re-derives what the apply path is supposed to guarantee and
+//! reports anything that doesn't hold: the checksum recomputed from scratch
+//! (bypassing the incremental [`ChecksumCache`](crate::ChecksumCache)),
+//! price ordering on both sides, a non-crossed touch, the level limit and
+//! positive quantities. A clean book yields no violations.
+//!
+//! The checksum is only compared while the book is synced; restored or
+//! desynchronized books carry a checksum that isn't expected to match.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_book::audit::audit;
+//! use kraken_book::Orderbook;
+//!
+//! let book = Orderbook::new("BTC/USD");
+//! assert!(audit(&book).is_empty());
+//! ```
+
+use crate::checksum::compute_checksum_iter;
+use crate::orderbook::Orderbook;
+use kraken_types::Level;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// An invariant a book failed to hold
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditViolation {
+    /// Checksum recomputed from the levels differs from the last validated one
+    ChecksumDrift {
+        /// Last checksum the book validated
+        stored: u32,
+        /// Checksum of the levels held now
+        recomputed: u32,
+    },
+    /// Bids not strictly descending at `index`
+    UnsortedBids {
+        /// Position of the first out-of-order level
+        index: usize,
+    },
+    /// Asks not strictly ascending at `index`
+    UnsortedAsks {
+        /// Position of the first out-of-order level
+        index: usize,
+    },
+    /// Best bid at or above best ask
+    Crossed {
+        /// Best bid price
+        best_bid: Decimal,
+        /// Best ask price
+        best_ask: Decimal,
+    },
+    /// More levels on one side than the depth (or level cap) allows
+    DepthExceeded {
+        /// Levels on the deeper side
+        levels: usize,
+        /// Allowed levels per side
+        limit: usize,
+    },
+    /// Level with a zero or negative quantity
+    NonPositiveQty {
+        /// Price of the level
+        price: Decimal,
+    },
+}
+
+impl AuditViolation {
+    /// Stable snake_case name of the variant, for logs and metric labels
+    pub fn kind(&self) -> &'static str {
+        match self {
+            AuditViolation::ChecksumDrift { .. } => "checksum_drift",
+            AuditViolation::UnsortedBids { .. } => "unsorted_bids",
+            AuditViolation::UnsortedAsks { .. } => "unsorted_asks",
+            AuditViolation::Crossed { .. } => "crossed",
+            AuditViolation::DepthExceeded { .. } => "depth_exceeded",
+            AuditViolation::NonPositiveQty { .. } => "non_positive_qty",
+        }
+    }
+}
+
+/// Check a book's invariants, returning every violation found
+pub fn audit(book: &Orderbook) -> Vec<AuditViolation> {
+    let bids = book.bids_vec();
+    let asks = book.asks_vec();
+    let mut violations = Vec::new();
+
+    if book.is_synced() {
+        let recomputed = compute_checksum_iter(&bids, &asks, book.price_precision(), book.qty_precision());
+        if recomputed != book.last_checksum() {
+            violations.push(AuditViolation::ChecksumDrift {
+                stored: book.last_checksum(),
+                recomputed,
+            });
+        }
+    }
+
+    if let Some(index) = first_unsorted(&bids, |prev, next| prev > next) {
+        violations.push(AuditViolation::UnsortedBids { index });
+    }
+    if let Some(index) = first_unsorted(&asks, |prev, next| prev < next) {
+        violations.push(AuditViolation::UnsortedAsks { index });
+    }
+
+    if let (Some(bid), Some(ask)) = (bids.first(), asks.first()) {
+        if bid.price >= ask.price {
+            violations.push(AuditViolation::Crossed {
+                best_bid: bid.price.0,
+                best_ask: ask.price.0,
+            });
+        }
+    }
+
+    let depth = book.depth() as usize;
+    let limit = book.level_cap().map_or(depth, |cap| cap.min(depth));
+    let levels = bids.len().max(asks.len());
+    if levels > limit {
+        violations.push(AuditViolation::DepthExceeded { levels, limit });
+    }
+
+    for level in bids.iter().chain(&asks) {
+        if level.qty.0 <= Decimal::ZERO {
+            violations.push(AuditViolation::NonPositiveQty { price: level.price.0 });
+        }
+    }
+
+    violations
+}
+
+/// Index of the first level not in order after its predecessor
+fn first_unsorted(levels: &[Level], in_order: impl Fn(Decimal, Decimal) -> bool) -> Option<usize> {
+    levels
+        .windows(2)
+        .position(|pair| !in_order(pair[0].price.0, pair[1].price.0))
+        .map(|position| position + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum::compute_checksum;
+    use kraken_types::BookData;
+    use rust_decimal_macros::dec;
+
+    fn synced_book(depth: u32, bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> Orderbook {
+        let bids: Vec<Level> = bids.iter().map(|&(p, q)| Level::new(p, q)).collect();
+        let asks: Vec<Level> = asks.iter().map(|&(p, q)| Level::new(p, q)).collect();
+        let data = BookData {
+            symbol: "BTC/USD".to_string(),
+            checksum: compute_checksum(&bids, &asks),
+            bids,
+            asks,
+            timestamp: None,
+        };
+        let mut book = Orderbook::with_depth("BTC/USD", depth);
+        let _ = book.apply_book_data(&data, true);
+        book
+    }
+
+    #[test]
+    fn test_consistent_book_passes() {
+        let book = synced_book(
+            10,
+            &[(dec!(100.0), dec!(1.0)), (dec!(99.5), dec!(2.0))],
+            &[(dec!(100.5), dec!(1.5)), (dec!(101.0), dec!(0.5))],
+        );
+        assert!(book.is_synced());
+        assert!(audit(&book).is_empty());
+    }
+
+    #[test]
+    fn test_drift_crossing_and_depth_are_reported() {
+        // Crossed books are applied and stay synced
+        let mut book = synced_book(1, &[(dec!(101.0), dec!(1.0))], &[(dec!(100.5), dec!(1.0))]);
+        assert!(book.is_synced());
+        // A level slipping past the apply path
+        book.storage_mut().insert_bid(dec!(99.0), dec!(3.0));
+
+        let kinds: Vec<_> = audit(&book).iter().map(AuditViolation::kind).collect();
+        assert_eq!(kinds, vec!["checksum_drift", "crossed", "depth_exceeded"]);
+    }
+}
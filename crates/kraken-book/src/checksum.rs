@@ -14,9 +14,13 @@
 //!
 //! Kraken v2 API sends prices and quantities as JSON floats. The precision information
 //! must be obtained from the instrument channel to correctly format values for checksum.
+//! Until it arrives, [`infer_precision`] estimates it from the snapshot itself:
+//! floats drop trailing zeros, so the most decimals seen on any level is a lower
+//! bound that, over a full snapshot, almost always equals the real precision.
 
 use crc32fast::Hasher;
-use kraken_types::Level;
+use kraken_types::formatting::fixed_point;
+use kraken_types::{Level, Price, Qty};
 use rust_decimal::Decimal;
 
 /// Default price precision if not specified (BTC/USD typically has 1)
@@ -25,6 +29,9 @@ pub const DEFAULT_PRICE_PRECISION: u8 = 1;
 /// Default quantity precision if not specified (typically 8)
 pub const DEFAULT_QTY_PRECISION: u8 = 8;
 
+/// Number of levels per side covered by the checksum
+pub const CHECKSUM_DEPTH: usize = 10;
+
 /// Compute Kraken's CRC32 checksum for orderbook validation
 ///
 /// # Arguments
@@ -42,30 +49,63 @@ pub fn compute_checksum_with_precision(
     asks: &[Level],
     price_precision: u8,
     qty_precision: u8,
+) -> u32 {
+    compute_checksum_iter(bids, asks, price_precision, qty_precision)
+}
+
+/// Compute the checksum directly from level iterators
+///
+/// Same as [`compute_checksum_with_precision`], but avoids collecting the book
+/// into vectors first. Only the first [`CHECKSUM_DEPTH`] items of each iterator
+/// are consumed.
+pub fn compute_checksum_iter<'a>(
+    bids: impl IntoIterator<Item = &'a Level>,
+    asks: impl IntoIterator<Item = &'a Level>,
+    price_precision: u8,
+    qty_precision: u8,
 ) -> u32 {
     let mut hasher = Hasher::new();
+    let mut buf = [0u8; DIGIT_BUFFER_LEN];
+
+    // Asks first (sorted low to high), then bids (sorted high to low)
+    for level in asks
+        .into_iter()
+        .take(CHECKSUM_DEPTH)
+        .chain(bids.into_iter().take(CHECKSUM_DEPTH))
+    {
+        hasher.update(checksum_digits(&level.price.0, price_precision, &mut buf));
+        hasher.update(checksum_digits(&level.qty.0, qty_precision, &mut buf));
+    }
 
-    // Take top 10 of each side
-    let top_asks: Vec<_> = asks.iter().take(10).collect();
-    let top_bids: Vec<_> = bids.iter().take(10).collect();
+    hasher.finalize()
+}
 
-    // Process asks first (already sorted low to high)
-    for ask in &top_asks {
-        let price_str = format_for_checksum_with_precision(&ask.price, price_precision);
-        let qty_str = format_for_checksum_with_precision(&ask.qty, qty_precision);
-        hasher.update(price_str.as_bytes());
-        hasher.update(qty_str.as_bytes());
-    }
+/// Enough room for the digits of any `Decimal` mantissa (at most 29 digits)
+const DIGIT_BUFFER_LEN: usize = 40;
 
-    // Then bids (already sorted high to low)
-    for bid in &top_bids {
-        let price_str = format_for_checksum_with_precision(&bid.price, price_precision);
-        let qty_str = format_for_checksum_with_precision(&bid.qty, qty_precision);
-        hasher.update(price_str.as_bytes());
-        hasher.update(qty_str.as_bytes());
+/// Write the checksum representation of a value into `buf`
+///
+/// After rounding and rescaling to `precision` decimal places, the decimal's
+/// mantissa is exactly the value with the decimal point removed, so the digits
+/// are produced without any intermediate string or float conversion. Integer
+/// formatting never emits leading zeros, and zero formats as "0".
+fn checksum_digits<'b>(
+    value: &Decimal,
+    precision: u8,
+    buf: &'b mut [u8; DIGIT_BUFFER_LEN],
+) -> &'b [u8] {
+    let mut mantissa = fixed_point(*value, precision).mantissa().unsigned_abs();
+
+    let mut pos = buf.len();
+    loop {
+        pos -= 1;
+        buf[pos] = b'0' + (mantissa % 10) as u8;
+        mantissa /= 10;
+        if mantissa == 0 {
+            break;
+        }
     }
-
-    hasher.finalize()
+    &buf[pos..]
 }
 
 /// Compute checksum with default precision (for backwards compatibility)
@@ -75,6 +115,20 @@ pub fn compute_checksum(bids: &[Level], asks: &[Level]) -> u32 {
     compute_checksum_with_precision(bids, asks, DEFAULT_PRICE_PRECISION, DEFAULT_QTY_PRECISION)
 }
 
+/// Price and quantity decimals inferred from a snapshot's levels
+///
+/// Returns the largest number of decimals any price and any quantity was
+/// sent with, or `None` if there are no levels. Values that lost trailing
+/// zeros can only under-report, so this is a lower bound on the true
+/// precision.
+pub fn infer_precision(bids: &[Level], asks: &[Level]) -> Option<(u8, u8)> {
+    let levels = || bids.iter().chain(asks);
+    let scale = |value: &Decimal| value.normalize().scale().min(u32::from(u8::MAX)) as u8;
+    let price = levels().map(|level| scale(&level.price.0)).max()?;
+    let qty = levels().map(|level| scale(&level.qty.0)).max()?;
+    Some((price, qty))
+}
+
 /// Format a decimal for checksum with specified precision
 ///
 /// The value is formatted with exactly `precision` decimal places, then:
@@ -89,35 +143,15 @@ pub fn compute_checksum(bids: &[Level], asks: &[Level]) -> u32 {
 /// With qty_precision=8:
 /// - 0.00460208 → "0.00460208" → "000460208" → "460208"
 /// - 0.001 → "0.00100000" → "000100000" → "100000"
+///
+/// The checksum itself hashes the same digits straight from a stack buffer;
+/// this owned-string form is kept for tests.
+#[cfg(test)]
 fn format_for_checksum_with_precision(value: &Decimal, precision: u8) -> String {
-    use rust_decimal::prelude::ToPrimitive;
-
-    // Format with exact precision
-    // We need to round/truncate to the specified precision
-    let scale = Decimal::new(1, precision as u32);
-    let rounded = (value / scale).round() * scale;
-
-    // Format with the exact number of decimal places
-    let formatted = if precision == 0 {
-        format!("{}", rounded.trunc())
-    } else {
-        // Get the value as a string with proper precision
-        let f = rounded.to_f64().unwrap_or(0.0);
-        format!("{:.prec$}", f, prec = precision as usize)
-    };
-
-    // Remove the decimal point
-    let without_decimal = formatted.replace('.', "");
-
-    // Strip leading zeros
-    let trimmed = without_decimal.trim_start_matches('0');
-
-    // If all zeros, return "0"
-    if trimmed.is_empty() {
-        "0".to_string()
-    } else {
-        trimmed.to_string()
-    }
+    let mut buf = [0u8; DIGIT_BUFFER_LEN];
+    let digits = checksum_digits(value, precision, &mut buf);
+    // Digits are always ASCII
+    String::from_utf8_lossy(digits).into_owned()
 }
 
 /// Format a decimal for checksum without precision (legacy behavior)
@@ -164,11 +198,184 @@ impl ChecksumResult {
     }
 }
 
+/// Formatted checksum bytes for one level (price digits followed by qty digits)
+#[derive(Debug, Clone)]
+struct FormattedLevel {
+    price: Price,
+    qty: Qty,
+    bytes: [u8; 2 * DIGIT_BUFFER_LEN],
+    len: usize,
+}
+
+impl FormattedLevel {
+    fn new(level: &Level, price_precision: u8, qty_precision: u8) -> Self {
+        let mut bytes = [0u8; 2 * DIGIT_BUFFER_LEN];
+        let mut buf = [0u8; DIGIT_BUFFER_LEN];
+
+        let price = checksum_digits(&level.price.0, price_precision, &mut buf);
+        let price_len = price.len();
+        bytes[..price_len].copy_from_slice(price);
+
+        let qty = checksum_digits(&level.qty.0, qty_precision, &mut buf);
+        let len = price_len + qty.len();
+        bytes[price_len..len].copy_from_slice(qty);
+
+        Self {
+            price: level.price,
+            qty: level.qty,
+            bytes,
+            len,
+        }
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+}
+
+/// Incrementally maintained checksum over the top-10 window
+///
+/// Callers report every price they change via [`touch_bid`](Self::touch_bid) /
+/// [`touch_ask`](Self::touch_ask). Changes strictly outside the window keep the
+/// cached checksum; changes inside it mark the cache dirty, and the next
+/// [`checksum`](Self::checksum) call rehashes the window, reformatting only
+/// the levels whose price or quantity actually changed.
+///
+/// The result is always identical to [`compute_checksum_iter`] over the same
+/// levels.
+#[derive(Debug, Clone)]
+pub struct ChecksumCache {
+    price_precision: u8,
+    qty_precision: u8,
+    checksum: Option<u32>,
+    asks: Vec<FormattedLevel>,
+    bids: Vec<FormattedLevel>,
+    scratch: Vec<FormattedLevel>,
+}
+
+impl Default for ChecksumCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_PRICE_PRECISION, DEFAULT_QTY_PRECISION)
+    }
+}
+
+impl ChecksumCache {
+    /// Create an empty cache for the given precision
+    pub fn new(price_precision: u8, qty_precision: u8) -> Self {
+        Self {
+            price_precision,
+            qty_precision,
+            checksum: None,
+            asks: Vec::with_capacity(CHECKSUM_DEPTH),
+            bids: Vec::with_capacity(CHECKSUM_DEPTH),
+            scratch: Vec::with_capacity(CHECKSUM_DEPTH),
+        }
+    }
+
+    /// Change precision, discarding all formatted levels
+    pub fn set_precision(&mut self, price_precision: u8, qty_precision: u8) {
+        if (price_precision, qty_precision) != (self.price_precision, self.qty_precision) {
+            self.price_precision = price_precision;
+            self.qty_precision = qty_precision;
+            self.asks.clear();
+            self.bids.clear();
+        }
+        self.checksum = None;
+    }
+
+    /// Force the next [`checksum`](Self::checksum) call to rehash the window
+    pub fn invalidate(&mut self) {
+        self.checksum = None;
+    }
+
+    /// Check whether a cached checksum is available
+    pub fn is_valid(&self) -> bool {
+        self.checksum.is_some()
+    }
+
+    /// Record an insert, update, or removal of a bid level
+    pub fn touch_bid(&mut self, price: impl Into<Price>) {
+        let price = price.into();
+        // Bids are best-first (descending), so the window ends at the lowest cached price
+        let outside = self.bids.len() == CHECKSUM_DEPTH
+            && self.bids.last().is_some_and(|worst| price < worst.price);
+        if !outside {
+            self.checksum = None;
+        }
+    }
+
+    /// Record an insert, update, or removal of an ask level
+    pub fn touch_ask(&mut self, price: impl Into<Price>) {
+        let price = price.into();
+        // Asks are best-first (ascending), so the window ends at the highest cached price
+        let outside = self.asks.len() == CHECKSUM_DEPTH
+            && self.asks.last().is_some_and(|worst| price > worst.price);
+        if !outside {
+            self.checksum = None;
+        }
+    }
+
+    /// Get the checksum for the current book, rehashing only if needed
+    ///
+    /// `bids` and `asks` must be the full book sides in best-first order and
+    /// reflect every change reported since the last call.
+    pub fn checksum<'a>(
+        &mut self,
+        bids: impl IntoIterator<Item = &'a Level>,
+        asks: impl IntoIterator<Item = &'a Level>,
+    ) -> u32 {
+        if let Some(checksum) = self.checksum {
+            return checksum;
+        }
+
+        let (price_precision, qty_precision) = (self.price_precision, self.qty_precision);
+        Self::refresh(&mut self.asks, &mut self.scratch, asks, price_precision, qty_precision);
+        Self::refresh(&mut self.bids, &mut self.scratch, bids, price_precision, qty_precision);
+
+        let mut hasher = Hasher::new();
+        for level in self.asks.iter().chain(&self.bids) {
+            hasher.update(level.as_bytes());
+        }
+        let checksum = hasher.finalize();
+        self.checksum = Some(checksum);
+        checksum
+    }
+
+    /// Rebuild one side's window, reusing unchanged formatted levels
+    fn refresh<'a>(
+        cached: &mut Vec<FormattedLevel>,
+        scratch: &mut Vec<FormattedLevel>,
+        levels: impl IntoIterator<Item = &'a Level>,
+        price_precision: u8,
+        qty_precision: u8,
+    ) {
+        scratch.clear();
+        for level in levels.into_iter().take(CHECKSUM_DEPTH) {
+            let reused = cached
+                .iter()
+                .find(|f| f.price == level.price && f.qty == level.qty)
+                .cloned();
+            scratch.push(
+                reused.unwrap_or_else(|| FormattedLevel::new(level, price_precision, qty_precision)),
+            );
+        }
+        std::mem::swap(cached, scratch);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use rust_decimal_macros::dec;
 
+    #[test]
+    fn test_infer_precision_takes_widest_level() {
+        let bids = vec![Level::new(dec!(0.2505), dec!(1200.5)), Level::new(dec!(0.2504), dec!(3.00420000))];
+        let asks = vec![Level::new(dec!(0.251), dec!(10))];
+        assert_eq!(infer_precision(&bids, &asks), Some((4, 4)));
+        assert_eq!(infer_precision(&[], &[]), None);
+    }
+
     #[test]
     fn test_format_for_checksum_legacy() {
         // Test the legacy format function (natural decimal representation)
@@ -193,6 +400,30 @@ mod tests {
         assert_eq!(format_for_checksum_with_precision(&dec!(2.85806499), 8), "285806499");
     }
 
+    #[test]
+    fn test_format_rounding_and_zero() {
+        // Extra digits round to the precision, missing digits are padded
+        assert_eq!(format_for_checksum_with_precision(&dec!(88813.46), 1), "888135");
+        assert_eq!(format_for_checksum_with_precision(&dec!(5), 2), "500");
+        assert_eq!(format_for_checksum_with_precision(&dec!(0), 8), "0");
+        assert_eq!(format_for_checksum_with_precision(&dec!(0.000000001), 8), "0");
+    }
+
+    #[test]
+    fn test_checksum_iter_matches_slices() {
+        let asks: Vec<Level> = (1..=15)
+            .map(|i| Level::new(Decimal::from(100 + i), dec!(0.5)))
+            .collect();
+        let bids: Vec<Level> = (1..=15)
+            .map(|i| Level::new(Decimal::from(100 - i), dec!(1.25)))
+            .collect();
+
+        assert_eq!(
+            compute_checksum_iter(&bids, &asks, DEFAULT_PRICE_PRECISION, DEFAULT_QTY_PRECISION),
+            compute_checksum(&bids, &asks)
+        );
+    }
+
     #[test]
     fn test_checksum_computation() {
         // Simple test case
@@ -248,6 +479,73 @@ mod tests {
         assert_eq!(checksum1, checksum2);
     }
 
+    #[test]
+    fn test_cache_matches_full_recompute() {
+        use crate::testing::BookGenerator;
+        use crate::TreeBook;
+
+        for seed in 0..20 {
+            let mut generator = BookGenerator::new("BTC/USD", seed).with_depth(25);
+            let mut book = TreeBook::new();
+            let mut cache = ChecksumCache::default();
+
+            let snapshot = generator.snapshot();
+            for level in &snapshot.bids {
+                book.insert_bid(level.price, level.qty);
+            }
+            for level in &snapshot.asks {
+                book.insert_ask(level.price, level.qty);
+            }
+
+            for step in 0..300 {
+                let delta = generator.delta();
+                for level in &delta.bids {
+                    book.insert_bid(level.price, level.qty);
+                    cache.touch_bid(level.price);
+                }
+                for level in &delta.asks {
+                    book.insert_ask(level.price, level.qty);
+                    cache.touch_ask(level.price);
+                }
+
+                let expected = compute_checksum_iter(
+                    book.bids(),
+                    book.asks(),
+                    DEFAULT_PRICE_PRECISION,
+                    DEFAULT_QTY_PRECISION,
+                );
+                let cached = cache.checksum(book.bids(), book.asks());
+                assert_eq!(cached, expected, "seed {} step {}", seed, step);
+                assert_eq!(cached, delta.checksum, "seed {} step {}", seed, step);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cache_skips_changes_outside_window() {
+        let asks: Vec<Level> = (1..=15)
+            .map(|i| Level::new(Decimal::from(100 + i), dec!(1)))
+            .collect();
+        let bids: Vec<Level> = (1..=15)
+            .map(|i| Level::new(Decimal::from(100 - i), dec!(1)))
+            .collect();
+        let mut cache = ChecksumCache::default();
+        let checksum = cache.checksum(&bids, &asks);
+
+        // Deeper than the 10th level on either side
+        cache.touch_bid(Price::new(dec!(85)));
+        cache.touch_ask(Price::new(dec!(115)));
+        assert!(cache.is_valid());
+
+        // The 10th level itself is inside the window
+        cache.touch_bid(Price::new(dec!(90)));
+        assert!(!cache.is_valid());
+        assert_eq!(cache.checksum(&bids, &asks), checksum);
+
+        cache.set_precision(2, 8);
+        assert_ne!(cache.checksum(&bids, &asks), checksum);
+    }
+
     #[test]
     fn test_checksum_result() {
         let result = ChecksumResult::new(12345, 12345);
@@ -0,0 +1,216 @@
+//! Level-by-level differences between orderbook snapshots
+//!
+//! [`OrderbookSnapshot::diff`] compares two snapshots of the same book and
+//! produces a [`SnapshotDiff`] listing the levels that appeared, disappeared
+//! or changed size. Shipping diffs instead of full snapshots keeps IPC and
+//! message-queue payloads small, and lets UIs redraw only the rows that
+//! changed. [`OrderbookSnapshot::apply_diff`] is the inverse: applying the
+//! diff to the older snapshot reproduces the newer one.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_book::OrderbookSnapshot;
+//! use kraken_types::{Level, Price};
+//! use rust_decimal_macros::dec;
+//!
+//! let before = OrderbookSnapshot {
+//!     symbol: "BTC/USD".into(),
+//!     bids: vec![Level::new(dec!(100), dec!(1))],
+//!     asks: vec![Level::new(dec!(101), dec!(1))],
+//!     ..Default::default()
+//! };
+//! let after = OrderbookSnapshot {
+//!     bids: vec![Level::new(dec!(100), dec!(2))],
+//!     asks: vec![Level::new(dec!(102), dec!(1))],
+//!     ..before.clone()
+//! };
+//!
+//! let diff = before.diff(&after);
+//! assert_eq!(diff.bids.changed.len(), 1);
+//! assert_eq!(diff.asks.removed, vec![Price::new(dec!(101))]);
+//!
+//! let mut replica = before.clone();
+//! replica.apply_diff(&diff);
+//! assert_eq!(replica.asks, after.asks);
+//! ```
+
+use crate::orderbook::{OrderbookSnapshot, OrderbookState};
+use kraken_types::{Level, Price, Qty};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+
+/// Changes to one side of the book
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SideDiff {
+    /// Levels at prices that weren't in the old snapshot
+    pub added: Vec<Level>,
+    /// Prices that are no longer in the book
+    pub removed: Vec<Price>,
+    /// Levels whose quantity changed (new quantity)
+    pub changed: Vec<Level>,
+}
+
+impl SideDiff {
+    /// Returns true if the side didn't change
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Number of levels touched
+    pub fn len(&self) -> usize {
+        self.added.len() + self.removed.len() + self.changed.len()
+    }
+
+    fn between(old: &[Level], new: &[Level]) -> Self {
+        let old_levels: HashMap<Price, Qty> = old.iter().map(|l| (l.price, l.qty)).collect();
+        let new_prices: HashSet<Price> = new.iter().map(|l| l.price).collect();
+
+        let mut diff = Self::default();
+        for level in new {
+            match old_levels.get(&level.price) {
+                None => diff.added.push(level.clone()),
+                Some(qty) if *qty != level.qty => diff.changed.push(level.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = old
+            .iter()
+            .filter(|l| !new_prices.contains(&l.price))
+            .map(|l| l.price)
+            .collect();
+        diff
+    }
+
+    fn apply(&self, levels: &mut Vec<Level>, order: fn(&Price, &Price) -> Ordering) {
+        levels.retain(|l| !self.removed.contains(&l.price));
+        for update in &self.changed {
+            if let Some(level) = levels.iter_mut().find(|l| l.price == update.price) {
+                level.qty = update.qty;
+            }
+        }
+        for level in &self.added {
+            match levels.binary_search_by(|l| order(&l.price, &level.price)) {
+                Ok(index) => levels[index].qty = level.qty,
+                Err(index) => levels.insert(index, level.clone()),
+            }
+        }
+    }
+}
+
+/// Difference between two snapshots of the same book
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Bid side changes
+    pub bids: SideDiff,
+    /// Ask side changes
+    pub asks: SideDiff,
+    /// Checksum of the newer snapshot
+    pub checksum: u32,
+    /// State of the newer snapshot
+    pub state: OrderbookState,
+}
+
+impl SnapshotDiff {
+    /// Returns true if no level changed
+    pub fn is_empty(&self) -> bool {
+        self.bids.is_empty() && self.asks.is_empty()
+    }
+
+    /// Number of levels touched on both sides
+    pub fn len(&self) -> usize {
+        self.bids.len() + self.asks.len()
+    }
+}
+
+impl OrderbookSnapshot {
+    /// Compute the changes that turn this snapshot into `other`
+    pub fn diff(&self, other: &OrderbookSnapshot) -> SnapshotDiff {
+        SnapshotDiff {
+            symbol: other.symbol.clone(),
+            bids: SideDiff::between(&self.bids, &other.bids),
+            asks: SideDiff::between(&self.asks, &other.asks),
+            checksum: other.checksum,
+            state: other.state,
+        }
+    }
+
+    /// Apply a diff produced by [`diff`](Self::diff)
+    ///
+    /// Levels stay sorted best-first: bids descending, asks ascending. The
+    /// checksum and state are taken from the diff. Applying the same diff
+    /// twice has no further effect.
+    pub fn apply_diff(&mut self, diff: &SnapshotDiff) {
+        diff.bids.apply(&mut self.bids, |a, b| b.cmp(a));
+        diff.asks.apply(&mut self.asks, |a, b| a.cmp(b));
+        self.checksum = diff.checksum;
+        self.state = diff.state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    fn snapshot(bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> OrderbookSnapshot {
+        OrderbookSnapshot {
+            symbol: "BTC/USD".to_string(),
+            bids: bids.iter().map(|&(p, q)| Level::new(p, q)).collect(),
+            asks: asks.iter().map(|&(p, q)| Level::new(p, q)).collect(),
+            checksum: 0,
+            state: OrderbookState::Synced,
+        }
+    }
+
+    #[test]
+    fn test_diff_classifies_levels() {
+        let before = snapshot(
+            &[(dec!(100), dec!(1)), (dec!(99), dec!(2)), (dec!(98), dec!(3))],
+            &[(dec!(101), dec!(1))],
+        );
+        let after = snapshot(
+            &[(dec!(100), dec!(1)), (dec!(99), dec!(5)), (dec!(97), dec!(4))],
+            &[(dec!(101), dec!(1))],
+        );
+
+        let diff = before.diff(&after);
+        assert_eq!(diff.bids.added, vec![Level::new(dec!(97), dec!(4))]);
+        assert_eq!(diff.bids.removed, vec![Price::new(dec!(98))]);
+        assert_eq!(diff.bids.changed, vec![Level::new(dec!(99), dec!(5))]);
+        assert!(diff.asks.is_empty());
+        assert_eq!(diff.len(), 3);
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn test_apply_diff_reconstructs_snapshot() {
+        let before = snapshot(
+            &[(dec!(100), dec!(1)), (dec!(98), dec!(3))],
+            &[(dec!(101), dec!(1)), (dec!(103), dec!(2))],
+        );
+        let mut after = snapshot(
+            &[(dec!(101), dec!(1)), (dec!(99), dec!(2)), (dec!(98), dec!(1))],
+            &[(dec!(102), dec!(1)), (dec!(103), dec!(2)), (dec!(104), dec!(6))],
+        );
+        after.checksum = 42;
+
+        // Diffs survive a serialization round trip
+        let json = serde_json::to_string(&before.diff(&after)).unwrap();
+        let diff: SnapshotDiff = serde_json::from_str(&json).unwrap();
+
+        let mut replica = before.clone();
+        replica.apply_diff(&diff);
+        assert_eq!(replica.bids, after.bids);
+        assert_eq!(replica.asks, after.asks);
+        assert_eq!(replica.checksum, 42);
+
+        replica.apply_diff(&diff);
+        assert_eq!(replica.bids, after.bids);
+        assert_eq!(replica.asks, after.asks);
+    }
+}
@@ -0,0 +1,421 @@
+//! Flat row export for research tooling
+//!
+//! Snapshots are nested (a symbol with two vectors of levels), which is awkward
+//! to load into dataframes. This module flattens snapshots and trades into
+//! rows with a fixed schema and writes them as JSON Lines, which pandas
+//! (`read_json(lines=True)`) and polars (`read_ndjson`) load directly.
+//!
+//! # Schemas
+//!
+//! [`BookLevelRow`]: one row per level per snapshot
+//!
+//! | column         | type            | notes                        |
+//! |----------------|-----------------|------------------------------|
+//! | `sequence`     | u64             | snapshot sequence            |
+//! | `timestamp_ms` | u64 (nullable)  | caller-supplied capture time |
+//! | `symbol`       | string          |                              |
+//! | `side`         | "bid" / "ask"   |                              |
+//! | `level`        | u32             | 0 = best price               |
+//! | `price`        | decimal string  |                              |
+//! | `qty`          | decimal string  |                              |
+//! | `checksum`     | u32             | book checksum at capture     |
+//!
+//! [`TradeRow`]: one row per trade
+//!
+//! | column      | type           |
+//! |-------------|----------------|
+//! | `trade_id`  | u64            |
+//! | `timestamp` | RFC 3339 string|
+//! | `symbol`    | string         |
+//! | `side`      | "buy" / "sell" |
+//! | `price`     | decimal string |
+//! | `qty`       | decimal string |
+//! | `ord_type`  | string         |
+//!
+//! Decimals are written as strings so no precision is lost; cast them to
+//! float or decimal columns after loading.
+//!
+//! # Parquet
+//!
+//! With the `parquet` feature, [`ParquetRowWriter`] writes the same rows to
+//! Parquet with the schemas above (strings as `Utf8`, integers unsigned,
+//! `timestamp_ms` nullable). Each [`write`](ParquetRowWriter::write) call
+//! becomes a row group, so a capture can be streamed batch by batch:
+//!
+//! ```ignore
+//! let mut writer = ParquetRowWriter::<_, BookLevelRow>::new(File::create("book.parquet")?)?;
+//! for entry in history.iter() {
+//!     writer.write(&history_rows(entry))?;
+//! }
+//! writer.finish()?;
+//! ```
+
+use crate::history::TimestampedSnapshot;
+use crate::l3::L3BookSnapshot;
+use crate::orderbook::OrderbookSnapshot;
+use kraken_types::{Level, Side, TradeData};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+
+/// Book side in exported rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BookSide {
+    /// Bid side
+    Bid,
+    /// Ask side
+    Ask,
+}
+
+/// One price level of one snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BookLevelRow {
+    /// Snapshot sequence number
+    pub sequence: u64,
+    /// Capture time in milliseconds, if known
+    pub timestamp_ms: Option<u64>,
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Book side
+    pub side: BookSide,
+    /// Distance from the top of the book (0 = best)
+    pub level: u32,
+    /// Level price
+    pub price: Decimal,
+    /// Level quantity
+    pub qty: Decimal,
+    /// Book checksum at capture
+    pub checksum: u32,
+}
+
+/// One public trade
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradeRow {
+    /// Unique trade ID
+    pub trade_id: u64,
+    /// Trade timestamp as sent by Kraken
+    pub timestamp: String,
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Aggressor side
+    pub side: Side,
+    /// Trade price
+    pub price: Decimal,
+    /// Trade quantity
+    pub qty: Decimal,
+    /// Order type
+    pub ord_type: String,
+}
+
+impl From<&TradeData> for TradeRow {
+    fn from(trade: &TradeData) -> Self {
+        Self {
+            trade_id: trade.trade_id,
+            timestamp: trade.timestamp.clone(),
+            symbol: trade.symbol.clone(),
+            side: trade.side,
+            price: trade.price,
+            qty: trade.qty,
+            ord_type: trade.ord_type.clone(),
+        }
+    }
+}
+
+/// Flatten an L2 snapshot into level rows (bids first, then asks)
+pub fn book_rows(
+    snapshot: &OrderbookSnapshot,
+    sequence: u64,
+    timestamp_ms: Option<u64>,
+) -> Vec<BookLevelRow> {
+    level_rows(
+        &snapshot.symbol,
+        &snapshot.bids,
+        &snapshot.asks,
+        snapshot.checksum,
+        sequence,
+        timestamp_ms,
+    )
+}
+
+/// Flatten a history entry into level rows
+pub fn history_rows(entry: &TimestampedSnapshot) -> Vec<BookLevelRow> {
+    book_rows(&entry.snapshot, entry.sequence, entry.timestamp_ms)
+}
+
+/// Flatten the aggregated levels of an L3 snapshot into level rows
+pub fn l3_book_rows(snapshot: &L3BookSnapshot, timestamp_ms: Option<u64>) -> Vec<BookLevelRow> {
+    level_rows(
+        &snapshot.symbol,
+        &snapshot.bids,
+        &snapshot.asks,
+        snapshot.checksum,
+        snapshot.sequence,
+        timestamp_ms,
+    )
+}
+
+fn level_rows(
+    symbol: &str,
+    bids: &[Level],
+    asks: &[Level],
+    checksum: u32,
+    sequence: u64,
+    timestamp_ms: Option<u64>,
+) -> Vec<BookLevelRow> {
+    let sides = [(BookSide::Bid, bids), (BookSide::Ask, asks)];
+    sides
+        .iter()
+        .flat_map(|(side, levels)| {
+            levels.iter().enumerate().map(move |(i, level)| BookLevelRow {
+                sequence,
+                timestamp_ms,
+                symbol: symbol.to_string(),
+                side: *side,
+                level: i as u32,
+                price: level.price.0,
+                qty: level.qty.0,
+                checksum,
+            })
+        })
+        .collect()
+}
+
+/// Write rows as JSON Lines, returning the number of rows written
+pub fn write_jsonl<W, T, I>(mut writer: W, rows: I) -> io::Result<usize>
+where
+    W: Write,
+    T: Serialize,
+    I: IntoIterator<Item = T>,
+{
+    let mut count = 0;
+    for row in rows {
+        serde_json::to_writer(&mut writer, &row)?;
+        writer.write_all(b"\n")?;
+        count += 1;
+    }
+    writer.flush()?;
+    Ok(count)
+}
+
+#[cfg(feature = "parquet")]
+pub use self::parquet_rows::{write_parquet, ParquetRow, ParquetRowWriter};
+
+#[cfg(feature = "parquet")]
+mod parquet_rows {
+    use super::{BookLevelRow, BookSide, TradeRow};
+    use arrow_array::{ArrayRef, RecordBatch, StringArray, UInt32Array, UInt64Array};
+    use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef};
+    use kraken_types::Side;
+    use parquet::arrow::ArrowWriter;
+    use parquet::errors::ParquetError;
+    use std::io::Write;
+    use std::marker::PhantomData;
+    use std::sync::Arc;
+
+    /// A row type with a fixed Parquet schema
+    pub trait ParquetRow: Sized {
+        /// Arrow schema of the written file
+        fn schema() -> SchemaRef;
+
+        /// Convert rows into one record batch
+        fn to_batch(rows: &[Self]) -> Result<RecordBatch, ArrowError>;
+    }
+
+    fn strings<'a>(values: impl Iterator<Item = &'a str>) -> ArrayRef {
+        Arc::new(StringArray::from_iter_values(values))
+    }
+
+    impl ParquetRow for BookLevelRow {
+        fn schema() -> SchemaRef {
+            Arc::new(Schema::new(vec![
+                Field::new("sequence", DataType::UInt64, false),
+                Field::new("timestamp_ms", DataType::UInt64, true),
+                Field::new("symbol", DataType::Utf8, false),
+                Field::new("side", DataType::Utf8, false),
+                Field::new("level", DataType::UInt32, false),
+                Field::new("price", DataType::Utf8, false),
+                Field::new("qty", DataType::Utf8, false),
+                Field::new("checksum", DataType::UInt32, false),
+            ]))
+        }
+
+        fn to_batch(rows: &[Self]) -> Result<RecordBatch, ArrowError> {
+            let prices: Vec<String> = rows.iter().map(|r| r.price.to_string()).collect();
+            let qtys: Vec<String> = rows.iter().map(|r| r.qty.to_string()).collect();
+            RecordBatch::try_new(
+                Self::schema(),
+                vec![
+                    Arc::new(UInt64Array::from_iter_values(rows.iter().map(|r| r.sequence))),
+                    Arc::new(UInt64Array::from_iter(rows.iter().map(|r| r.timestamp_ms))),
+                    strings(rows.iter().map(|r| r.symbol.as_str())),
+                    strings(rows.iter().map(|r| match r.side {
+                        BookSide::Bid => "bid",
+                        BookSide::Ask => "ask",
+                    })),
+                    Arc::new(UInt32Array::from_iter_values(rows.iter().map(|r| r.level))),
+                    strings(prices.iter().map(String::as_str)),
+                    strings(qtys.iter().map(String::as_str)),
+                    Arc::new(UInt32Array::from_iter_values(rows.iter().map(|r| r.checksum))),
+                ],
+            )
+        }
+    }
+
+    impl ParquetRow for TradeRow {
+        fn schema() -> SchemaRef {
+            Arc::new(Schema::new(vec![
+                Field::new("trade_id", DataType::UInt64, false),
+                Field::new("timestamp", DataType::Utf8, false),
+                Field::new("symbol", DataType::Utf8, false),
+                Field::new("side", DataType::Utf8, false),
+                Field::new("price", DataType::Utf8, false),
+                Field::new("qty", DataType::Utf8, false),
+                Field::new("ord_type", DataType::Utf8, false),
+            ]))
+        }
+
+        fn to_batch(rows: &[Self]) -> Result<RecordBatch, ArrowError> {
+            let prices: Vec<String> = rows.iter().map(|r| r.price.to_string()).collect();
+            let qtys: Vec<String> = rows.iter().map(|r| r.qty.to_string()).collect();
+            RecordBatch::try_new(
+                Self::schema(),
+                vec![
+                    Arc::new(UInt64Array::from_iter_values(rows.iter().map(|r| r.trade_id))),
+                    strings(rows.iter().map(|r| r.timestamp.as_str())),
+                    strings(rows.iter().map(|r| r.symbol.as_str())),
+                    strings(rows.iter().map(|r| match r.side {
+                        Side::Buy => "buy",
+                        Side::Sell => "sell",
+                    })),
+                    strings(prices.iter().map(String::as_str)),
+                    strings(qtys.iter().map(String::as_str)),
+                    strings(rows.iter().map(|r| r.ord_type.as_str())),
+                ],
+            )
+        }
+    }
+
+    /// Streams batches of rows into a Parquet file
+    pub struct ParquetRowWriter<W: Write + Send, T: ParquetRow> {
+        inner: ArrowWriter<W>,
+        rows: usize,
+        _rows: PhantomData<fn(&T)>,
+    }
+
+    impl<W: Write + Send, T: ParquetRow> ParquetRowWriter<W, T> {
+        /// Start a file with `T`'s schema
+        pub fn new(writer: W) -> Result<Self, ParquetError> {
+            Ok(Self {
+                inner: ArrowWriter::try_new(writer, T::schema(), None)?,
+                rows: 0,
+                _rows: PhantomData,
+            })
+        }
+
+        /// Write a batch of rows as one row group
+        pub fn write(&mut self, rows: &[T]) -> Result<(), ParquetError> {
+            if rows.is_empty() {
+                return Ok(());
+            }
+            self.inner.write(&T::to_batch(rows)?)?;
+            self.inner.flush()?;
+            self.rows += rows.len();
+            Ok(())
+        }
+
+        /// Rows written so far
+        pub fn rows_written(&self) -> usize {
+            self.rows
+        }
+
+        /// Write the footer, returning the number of rows written
+        pub fn finish(self) -> Result<usize, ParquetError> {
+            self.inner.close()?;
+            Ok(self.rows)
+        }
+    }
+
+    /// Write rows as a single Parquet file, returning the number of rows written
+    pub fn write_parquet<W: Write + Send, T: ParquetRow>(writer: W, rows: &[T]) -> Result<usize, ParquetError> {
+        let mut out = ParquetRowWriter::<W, T>::new(writer)?;
+        out.write(rows)?;
+        out.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orderbook::OrderbookState;
+    use rust_decimal_macros::dec;
+
+    fn snapshot() -> OrderbookSnapshot {
+        OrderbookSnapshot {
+            symbol: "BTC/USD".to_string(),
+            bids: vec![Level::new(dec!(100), dec!(1)), Level::new(dec!(99), dec!(2))],
+            asks: vec![Level::new(dec!(101), dec!(0.5))],
+            checksum: 42,
+            state: OrderbookState::Synced,
+        }
+    }
+
+    #[test]
+    fn test_book_rows_flatten_levels() {
+        let rows = book_rows(&snapshot(), 7, Some(1_700_000_000_000));
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1].side, BookSide::Bid);
+        assert_eq!(rows[1].level, 1);
+        assert_eq!(rows[1].price, dec!(99));
+        assert_eq!(rows[2].side, BookSide::Ask);
+        assert_eq!(rows[2].level, 0);
+        assert!(rows.iter().all(|r| r.sequence == 7 && r.checksum == 42));
+    }
+
+    #[test]
+    fn test_write_jsonl_round_trip() {
+        let rows = book_rows(&snapshot(), 1, None);
+        let mut out = Vec::new();
+        assert_eq!(write_jsonl(&mut out, &rows).unwrap(), 3);
+
+        let text = String::from_utf8(out).unwrap();
+        let first = text.lines().next().unwrap();
+        assert!(first.contains(r#""side":"bid""#));
+        assert!(first.contains(r#""price":"100""#));
+
+        let parsed: Vec<BookLevelRow> =
+            text.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(parsed, rows);
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn test_write_parquet_schema() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let mut out = Vec::new();
+        {
+            let mut writer = ParquetRowWriter::<_, BookLevelRow>::new(&mut out).unwrap();
+            writer.write(&book_rows(&snapshot(), 1, None)).unwrap();
+            writer.write(&book_rows(&snapshot(), 2, Some(5))).unwrap();
+            assert_eq!(writer.finish().unwrap(), 6);
+        }
+
+        let reader = SerializedFileReader::new(bytes::Bytes::from(out)).unwrap();
+        let meta = reader.metadata();
+        assert_eq!(meta.file_metadata().num_rows(), 6);
+        assert_eq!(meta.num_row_groups(), 2);
+        let columns: Vec<&str> = meta
+            .file_metadata()
+            .schema_descr()
+            .columns()
+            .iter()
+            .map(|c| c.name())
+            .collect();
+        assert_eq!(
+            columns,
+            ["sequence", "timestamp_ms", "symbol", "side", "level", "price", "qty", "checksum"]
+        );
+    }
+}
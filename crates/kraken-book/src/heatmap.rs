@@ -0,0 +1,612 @@
+//! Liquidity heatmaps (price × time × quantity)
+//!
+//! [`HeatmapBuilder`] samples the book at a fixed cadence and keeps one
+//! column per sample, with resting quantity summed into fixed-size price
+//! buckets. [`HeatmapBuilder::build`] turns the retained columns into a dense
+//! [`Heatmap`] matrix ready for a bookmap-style renderer, exported either as
+//! JSON or as a compact little-endian binary blob.
+//!
+//! The matrix is capped at [`HeatmapBuilder::with_max_rows`] rows (1000 by
+//! default). When the sampled prices span more buckets than that, the rows
+//! are a band centred on the newest mid price, so one stray level far from
+//! the market can't blow up the allocation.
+//!
+//! Sampling takes the caller's clock (`timestamp_ms`) so the builder stays
+//! usable in WASM.
+//!
+//! # Matrix layout
+//!
+//! `bids` and `asks` are flat, column-major: the cell for column `c` (time)
+//! and row `r` (price bucket) is at `c * rows + r`. Row 0 is the lowest
+//! bucket, starting at `price_min`; each row spans `bucket_size`.
+//!
+//! # Binary format (version 1)
+//!
+//! | field          | type                     |
+//! |----------------|--------------------------|
+//! | magic          | `b"HVHM"`                |
+//! | version        | u8                       |
+//! | symbol         | u16 length + UTF-8 bytes |
+//! | `bucket_size`  | f64                      |
+//! | `price_min`    | f64                      |
+//! | rows           | u32                      |
+//! | columns        | u32                      |
+//! | `timestamps`   | u64 × columns            |
+//! | `bids`         | f32 × columns × rows     |
+//! | `asks`         | f32 × columns × rows     |
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_book::heatmap::{HeatmapBuilder, Normalization};
+//! use kraken_book::OrderbookSnapshot;
+//! use kraken_types::Level;
+//! use rust_decimal_macros::dec;
+//!
+//! let mut builder = HeatmapBuilder::new("BTC/USD", dec!(10))
+//!     .with_interval_ms(1_000)
+//!     .with_normalization(Normalization::Global);
+//!
+//! let snapshot = OrderbookSnapshot {
+//!     symbol: "BTC/USD".to_string(),
+//!     bids: vec![Level::new(dec!(99995), dec!(2))],
+//!     asks: vec![Level::new(dec!(100005), dec!(1))],
+//!     ..Default::default()
+//! };
+//! assert!(builder.sample(&snapshot, 0));
+//! assert!(!builder.sample(&snapshot, 500)); // not due yet
+//!
+//! let heatmap = builder.build();
+//! assert_eq!((heatmap.columns(), heatmap.rows), (1, 2));
+//! assert_eq!(heatmap.bid(0, 0), Some(1.0));
+//! ```
+
+use crate::orderbook::OrderbookSnapshot;
+use kraken_types::Level;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// Magic bytes starting a binary heatmap
+pub const HEATMAP_MAGIC: &[u8; 4] = b"HVHM";
+
+/// Binary format version written by [`Heatmap::to_bytes`]
+pub const HEATMAP_VERSION: u8 = 1;
+
+/// Error decoding a binary heatmap
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum HeatmapError {
+    /// Input doesn't start with [`HEATMAP_MAGIC`]
+    #[error("not a heatmap (bad magic bytes)")]
+    BadMagic,
+    /// Written by a newer version of the format
+    #[error("unsupported heatmap version {0}")]
+    UnsupportedVersion(u8),
+    /// Input ended before the declared matrix
+    #[error("heatmap data is truncated")]
+    Truncated,
+    /// Symbol wasn't valid UTF-8
+    #[error("heatmap symbol is not valid UTF-8")]
+    InvalidSymbol,
+}
+
+/// How cell values are scaled on [`HeatmapBuilder::build`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Normalization {
+    /// Raw quantities
+    #[default]
+    None,
+    /// Divide by the largest cell, so values fall in `0.0..=1.0`
+    Global,
+    /// Divide each column by its largest cell; highlights relative depth
+    /// at every instant regardless of overall activity
+    PerColumn,
+    /// `ln(1 + qty)`, then [`Global`](Self::Global); keeps thin levels
+    /// visible next to walls
+    Log,
+}
+
+/// One sample: bucket index → (bid qty, ask qty)
+#[derive(Debug, Clone)]
+struct Column {
+    timestamp_ms: u64,
+    cells: BTreeMap<i64, (f64, f64)>,
+    /// Bucket of the mid price, if both sides were present
+    mid: Option<i64>,
+}
+
+/// Samples a book into price-bucketed liquidity columns
+#[derive(Debug, Clone)]
+pub struct HeatmapBuilder {
+    symbol: String,
+    bucket_size: Decimal,
+    interval_ms: u64,
+    max_columns: usize,
+    max_rows: usize,
+    decay: f64,
+    normalization: Normalization,
+    columns: VecDeque<Column>,
+    next_due_ms: Option<u64>,
+}
+
+impl HeatmapBuilder {
+    /// Create a builder bucketing prices into steps of `bucket_size`
+    ///
+    /// Defaults: one sample per second, 600 columns and 1000 rows kept, no
+    /// decay, raw quantities. A non-positive `bucket_size` is treated as 1.
+    pub fn new(symbol: impl Into<String>, bucket_size: Decimal) -> Self {
+        Self {
+            symbol: symbol.into(),
+            bucket_size: if bucket_size > Decimal::ZERO { bucket_size } else { Decimal::ONE },
+            interval_ms: 1_000,
+            max_columns: 600,
+            max_rows: 1_000,
+            decay: 1.0,
+            normalization: Normalization::None,
+            columns: VecDeque::new(),
+            next_due_ms: None,
+        }
+    }
+
+    /// Set the sampling cadence
+    pub fn with_interval_ms(mut self, interval_ms: u64) -> Self {
+        self.interval_ms = interval_ms;
+        self
+    }
+
+    /// Set how many columns are kept; the oldest are dropped first
+    pub fn with_max_columns(mut self, max_columns: usize) -> Self {
+        self.max_columns = max_columns.max(1);
+        self
+    }
+
+    /// Set the most rows a built matrix may have
+    ///
+    /// Wider price ranges are cut to a band of this many rows around the
+    /// newest mid price.
+    pub fn with_max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = max_rows.max(1);
+        self
+    }
+
+    /// Fade older columns by `factor` per column of age
+    ///
+    /// `1.0` (the default) disables decay; `0.99` leaves a column at about
+    /// a third of its weight after 100 samples. Clamped to `0.0..=1.0`.
+    pub fn with_decay(mut self, factor: f64) -> Self {
+        self.decay = factor.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Set how cell values are scaled
+    pub fn with_normalization(mut self, normalization: Normalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Trading pair symbol
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Price step of one row
+    pub fn bucket_size(&self) -> Decimal {
+        self.bucket_size
+    }
+
+    /// Number of columns currently kept
+    pub fn len(&self) -> usize {
+        self.columns.len()
+    }
+
+    /// Returns true if nothing has been sampled
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+
+    /// Sample the book if a column is due at `timestamp_ms`
+    ///
+    /// Returns true if a column was added. Samples arriving before the next
+    /// slot are skipped, so this can be called on every book update.
+    pub fn sample(&mut self, snapshot: &OrderbookSnapshot, timestamp_ms: u64) -> bool {
+        if self.next_due_ms.is_some_and(|due| timestamp_ms < due) {
+            return false;
+        }
+        self.record(snapshot, timestamp_ms);
+        self.next_due_ms = Some(timestamp_ms.saturating_add(self.interval_ms));
+        true
+    }
+
+    /// Add a column unconditionally, ignoring the cadence
+    pub fn record(&mut self, snapshot: &OrderbookSnapshot, timestamp_ms: u64) {
+        let mut cells: BTreeMap<i64, (f64, f64)> = BTreeMap::new();
+        let mut add = |levels: &[Level], bid: bool| {
+            for level in levels {
+                let Some(bucket) = self.bucket(level.price.0) else {
+                    continue;
+                };
+                let qty = level.qty_f64();
+                let cell = cells.entry(bucket).or_default();
+                if bid {
+                    cell.0 += qty;
+                } else {
+                    cell.1 += qty;
+                }
+            }
+        };
+        add(&snapshot.bids, true);
+        add(&snapshot.asks, false);
+
+        let best_bid = snapshot.bids.iter().map(|l| l.price.0).max();
+        let best_ask = snapshot.asks.iter().map(|l| l.price.0).min();
+        let mid = match (best_bid, best_ask) {
+            (Some(bid), Some(ask)) => self.bucket((bid + ask) / Decimal::TWO),
+            _ => None,
+        };
+
+        self.columns.push_back(Column { timestamp_ms, cells, mid });
+        while self.columns.len() > self.max_columns {
+            self.columns.pop_front();
+        }
+    }
+
+    fn bucket(&self, price: Decimal) -> Option<i64> {
+        (price / self.bucket_size).floor().to_i64()
+    }
+
+    /// Drop every column
+    pub fn clear(&mut self) {
+        self.columns.clear();
+        self.next_due_ms = None;
+    }
+
+    /// Build a matrix covering every bucket seen in the kept columns
+    ///
+    /// Capped at the row limit; see [`with_max_rows`](Self::with_max_rows).
+    pub fn build(&self) -> Heatmap {
+        let mut buckets = self.columns.iter().flat_map(|c| c.cells.keys().copied());
+        let Some(first) = buckets.next() else {
+            return self.matrix(0, 0);
+        };
+        let (low, high) = buckets.fold((first, first), |(low, high), b| (low.min(b), high.max(b)));
+        self.matrix(low, high)
+    }
+
+    /// Build a matrix limited to prices in `low..=high`
+    ///
+    /// Use this to keep the matrix small when sampling a deep book with a
+    /// fine bucket size. The row limit still applies.
+    pub fn build_range(&self, low: Decimal, high: Decimal) -> Heatmap {
+        match (self.bucket(low.min(high)), self.bucket(low.max(high))) {
+            (Some(low), Some(high)) => self.matrix(low, high),
+            _ => self.matrix(0, 0),
+        }
+    }
+
+    /// Narrow `low..=high` to at most `max_rows` buckets around the newest mid
+    fn band(&self, low: i64, high: i64) -> (i64, i64) {
+        let span = high as i128 - low as i128 + 1;
+        if span <= self.max_rows as i128 {
+            return (low, high);
+        }
+        let rows = self.max_rows as i128;
+        let center = self
+            .columns
+            .iter()
+            .rev()
+            .find_map(|c| c.mid)
+            .map_or(low as i128 + span / 2, |mid| (mid as i128).clamp(low as i128, high as i128));
+        let start = (center - rows / 2).clamp(low as i128, high as i128 - rows + 1);
+        (start as i64, (start + rows - 1) as i64)
+    }
+
+    fn matrix(&self, low: i64, high: i64) -> Heatmap {
+        let (low, high) = self.band(low, high);
+        let rows = if self.columns.is_empty() { 0 } else { (high - low + 1) as usize };
+        let mut bids = vec![0.0f32; rows * self.columns.len()];
+        let mut asks = vec![0.0f32; rows * self.columns.len()];
+        let newest = self.columns.len().saturating_sub(1);
+
+        for (c, column) in self.columns.iter().enumerate() {
+            let weight = self.decay.powi((newest - c) as i32);
+            for (&bucket, &(bid, ask)) in column.cells.range(low..=high) {
+                let index = c * rows + (bucket - low) as usize;
+                bids[index] = (bid * weight) as f32;
+                asks[index] = (ask * weight) as f32;
+            }
+        }
+        normalize(&mut bids, &mut asks, rows, self.normalization);
+
+        Heatmap {
+            symbol: self.symbol.clone(),
+            bucket_size: self.bucket_size.to_f64().unwrap_or_default(),
+            price_min: (Decimal::from(low) * self.bucket_size).to_f64().unwrap_or_default(),
+            rows,
+            timestamps: self.columns.iter().map(|c| c.timestamp_ms).collect(),
+            bids,
+            asks,
+        }
+    }
+}
+
+fn normalize(bids: &mut [f32], asks: &mut [f32], rows: usize, normalization: Normalization) {
+    let scale = |cells: &mut [f32], max: f32| {
+        if max > 0.0 {
+            cells.iter_mut().for_each(|v| *v /= max);
+        }
+    };
+    let max_of = |a: &[f32], b: &[f32]| a.iter().chain(b).fold(0.0f32, |m, &v| m.max(v));
+
+    match normalization {
+        Normalization::None => {}
+        Normalization::Global => {
+            let max = max_of(bids, asks);
+            scale(bids, max);
+            scale(asks, max);
+        }
+        Normalization::PerColumn if rows > 0 => {
+            for (bid_col, ask_col) in bids.chunks_mut(rows).zip(asks.chunks_mut(rows)) {
+                let max = max_of(bid_col, ask_col);
+                scale(bid_col, max);
+                scale(ask_col, max);
+            }
+        }
+        Normalization::PerColumn => {}
+        Normalization::Log => {
+            bids.iter_mut().chain(asks.iter_mut()).for_each(|v| *v = v.ln_1p());
+            normalize(bids, asks, rows, Normalization::Global);
+        }
+    }
+}
+
+/// Dense price × time liquidity matrix
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Heatmap {
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Price step of one row
+    pub bucket_size: f64,
+    /// Lower bound of row 0
+    pub price_min: f64,
+    /// Number of price buckets per column
+    pub rows: usize,
+    /// Sample time of each column, oldest first
+    pub timestamps: Vec<u64>,
+    /// Bid quantity per cell, column-major
+    pub bids: Vec<f32>,
+    /// Ask quantity per cell, column-major
+    pub asks: Vec<f32>,
+}
+
+impl Heatmap {
+    /// Number of time columns
+    pub fn columns(&self) -> usize {
+        self.timestamps.len()
+    }
+
+    /// Lower bound of a row's price bucket
+    pub fn row_price(&self, row: usize) -> f64 {
+        self.price_min + row as f64 * self.bucket_size
+    }
+
+    /// Bid value at (`column`, `row`)
+    pub fn bid(&self, column: usize, row: usize) -> Option<f32> {
+        self.cell(&self.bids, column, row)
+    }
+
+    /// Ask value at (`column`, `row`)
+    pub fn ask(&self, column: usize, row: usize) -> Option<f32> {
+        self.cell(&self.asks, column, row)
+    }
+
+    fn cell(&self, cells: &[f32], column: usize, row: usize) -> Option<f32> {
+        if row >= self.rows {
+            return None;
+        }
+        cells.get(column * self.rows + row).copied()
+    }
+
+    /// Serialize to JSON
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// Encode in the compact binary format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut end = self.symbol.len().min(u16::MAX as usize);
+        while !self.symbol.is_char_boundary(end) {
+            end -= 1;
+        }
+        let symbol = &self.symbol.as_bytes()[..end];
+        let cells = self.bids.len() + self.asks.len();
+        let mut out = Vec::with_capacity(40 + symbol.len() + self.timestamps.len() * 8 + cells * 4);
+        out.extend_from_slice(HEATMAP_MAGIC);
+        out.push(HEATMAP_VERSION);
+        out.extend_from_slice(&(symbol.len() as u16).to_le_bytes());
+        out.extend_from_slice(symbol);
+        out.extend_from_slice(&self.bucket_size.to_le_bytes());
+        out.extend_from_slice(&self.price_min.to_le_bytes());
+        out.extend_from_slice(&(self.rows as u32).to_le_bytes());
+        out.extend_from_slice(&(self.timestamps.len() as u32).to_le_bytes());
+        for timestamp in &self.timestamps {
+            out.extend_from_slice(&timestamp.to_le_bytes());
+        }
+        for value in self.bids.iter().chain(&self.asks) {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        out
+    }
+
+    /// Decode the binary format
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, HeatmapError> {
+        let mut reader = Reader(bytes);
+        if reader.take(4)? != HEATMAP_MAGIC {
+            return Err(HeatmapError::BadMagic);
+        }
+        let version = reader.array::<1>()?[0];
+        if version != HEATMAP_VERSION {
+            return Err(HeatmapError::UnsupportedVersion(version));
+        }
+        let symbol_len = u16::from_le_bytes(reader.array()?) as usize;
+        let symbol = std::str::from_utf8(reader.take(symbol_len)?)
+            .map_err(|_| HeatmapError::InvalidSymbol)?
+            .to_string();
+        let bucket_size = f64::from_le_bytes(reader.array()?);
+        let price_min = f64::from_le_bytes(reader.array()?);
+        let rows = u32::from_le_bytes(reader.array()?) as usize;
+        let columns = u32::from_le_bytes(reader.array()?) as usize;
+
+        let timestamps = (0..columns)
+            .map(|_| reader.array().map(u64::from_le_bytes))
+            .collect::<Result<Vec<_>, _>>()?;
+        let cells = rows.checked_mul(columns).ok_or(HeatmapError::Truncated)?;
+        if reader.0.len() < cells.saturating_mul(8) {
+            return Err(HeatmapError::Truncated);
+        }
+        let mut side = || {
+            (0..cells)
+                .map(|_| reader.array().map(f32::from_le_bytes))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let bids = side()?;
+        let asks = side()?;
+
+        Ok(Self {
+            symbol,
+            bucket_size,
+            price_min,
+            rows,
+            timestamps,
+            bids,
+            asks,
+        })
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], HeatmapError> {
+        if self.0.len() < n {
+            return Err(HeatmapError::Truncated);
+        }
+        let (head, tail) = self.0.split_at(n);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], HeatmapError> {
+        Ok(self.take(N)?.try_into().expect("take returns N bytes"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn snapshot(bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> OrderbookSnapshot {
+        OrderbookSnapshot {
+            symbol: "BTC/USD".to_string(),
+            bids: bids.iter().map(|&(p, q)| Level::new(p, q)).collect(),
+            asks: asks.iter().map(|&(p, q)| Level::new(p, q)).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_buckets_cadence_and_retention() {
+        let mut builder = HeatmapBuilder::new("BTC/USD", dec!(10))
+            .with_interval_ms(100)
+            .with_max_columns(2);
+        let book = snapshot(&[(dec!(99), dec!(1)), (dec!(95), dec!(2)), (dec!(85), dec!(4))], &[(dec!(101), dec!(3))]);
+
+        assert!(builder.sample(&book, 1_000));
+        assert!(!builder.sample(&book, 1_050));
+        assert!(builder.sample(&book, 1_100));
+        assert!(builder.sample(&snapshot(&[], &[(dec!(120), dec!(5))]), 1_200));
+        assert_eq!(builder.len(), 2);
+
+        let heatmap = builder.build();
+        assert_eq!(heatmap.timestamps, vec![1_100, 1_200]);
+        assert_eq!(heatmap.price_min, 80.0);
+        assert_eq!(heatmap.rows, 5); // 80..130
+        assert_eq!(heatmap.bid(0, 1), Some(3.0)); // 99 + 95 share the 90 bucket
+        assert_eq!(heatmap.bid(0, 0), Some(4.0));
+        assert_eq!(heatmap.ask(0, 2), Some(3.0));
+        assert_eq!(heatmap.ask(1, 4), Some(5.0));
+        assert_eq!(heatmap.bid(1, 5), None);
+
+        let window = builder.build_range(dec!(90), dec!(109));
+        assert_eq!((window.price_min, window.rows), (90.0, 2));
+        assert_eq!(window.bid(0, 0), Some(3.0));
+    }
+
+    #[test]
+    fn test_rows_are_capped_around_mid() {
+        let mut builder = HeatmapBuilder::new("BTC/USD", dec!(1)).with_max_rows(10);
+        // A stray ask a million buckets away from the market
+        builder.record(&snapshot(&[(dec!(100), dec!(1))], &[(dec!(102), dec!(2)), (dec!(1000000), dec!(9))]), 0);
+
+        let heatmap = builder.build();
+        assert_eq!(heatmap.rows, 10);
+        assert_eq!(heatmap.price_min, 100.0); // band starts at the lowest bucket seen
+        assert_eq!(heatmap.bid(0, 0), Some(1.0));
+        assert_eq!(heatmap.ask(0, 2), Some(2.0));
+
+        let window = builder.build_range(dec!(0), dec!(1000000));
+        assert_eq!((window.price_min, window.rows), (96.0, 10)); // mid 101, five rows below
+    }
+
+    #[test]
+    fn test_decay_and_normalization() {
+        let mut builder = HeatmapBuilder::new("BTC/USD", dec!(1)).with_decay(0.5);
+        builder.record(&snapshot(&[(dec!(10), dec!(4))], &[]), 0);
+        builder.record(&snapshot(&[(dec!(10), dec!(2))], &[(dec!(11), dec!(1))]), 1);
+        let raw = builder.build();
+        assert_eq!(raw.bid(0, 0), Some(2.0)); // 4 × 0.5
+        assert_eq!(raw.bid(1, 0), Some(2.0));
+
+        let builder = builder.with_decay(1.0).with_normalization(Normalization::Global);
+        let global = builder.build();
+        assert_eq!(global.bid(0, 0), Some(1.0));
+        assert_eq!(global.ask(1, 1), Some(0.25));
+
+        let per_column = builder.clone().with_normalization(Normalization::PerColumn).build();
+        assert_eq!(per_column.bid(1, 0), Some(1.0));
+        assert_eq!(per_column.ask(1, 1), Some(0.5));
+
+        let log = builder.with_normalization(Normalization::Log).build();
+        assert_eq!(log.bid(0, 0), Some(1.0));
+        assert!(log.ask(1, 1).unwrap() > 0.25);
+    }
+
+    #[test]
+    fn test_binary_and_json_round_trip() {
+        let mut builder = HeatmapBuilder::new("BTC/USD", dec!(0.5));
+        builder.record(&snapshot(&[(dec!(100.2), dec!(1.5))], &[(dec!(101.7), dec!(0.25))]), 42);
+        let heatmap = builder.build();
+
+        let bytes = heatmap.to_bytes();
+        assert_eq!(&bytes[..4], HEATMAP_MAGIC);
+        assert_eq!(Heatmap::from_bytes(&bytes).unwrap(), heatmap);
+        assert_eq!(Heatmap::from_bytes(&bytes[..bytes.len() - 1]), Err(HeatmapError::Truncated));
+        assert_eq!(Heatmap::from_bytes(b"nope"), Err(HeatmapError::BadMagic));
+
+        let json: Heatmap = serde_json::from_str(&heatmap.to_json().unwrap()).unwrap();
+        assert_eq!(json, heatmap);
+        assert_eq!(HeatmapBuilder::new("X", dec!(1)).build().rows, 0);
+    }
+
+    #[test]
+    fn test_long_symbol_is_cut_on_a_char_boundary() {
+        let mut heatmap = HeatmapBuilder::new("X", dec!(1)).build();
+        heatmap.symbol = "é".repeat(u16::MAX as usize);
+
+        let decoded = Heatmap::from_bytes(&heatmap.to_bytes()).unwrap();
+        assert_eq!(decoded.symbol.len(), u16::MAX as usize - 1);
+        assert!(heatmap.symbol.starts_with(&decoded.symbol));
+    }
+}
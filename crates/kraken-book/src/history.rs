@@ -3,6 +3,8 @@
 //! Enables the Track 2 visualizer to replay orderbook states.
 
 use crate::orderbook::OrderbookSnapshot;
+use kraken_types::Level;
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 
 /// Ring buffer for storing orderbook snapshots
@@ -16,10 +18,12 @@ pub struct HistoryBuffer {
     max_size: usize,
     /// Next sequence number
     next_sequence: u64,
+    /// Approximate bytes held by the stored snapshots
+    bytes: usize,
 }
 
 /// Snapshot with sequence number for ordering
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TimestampedSnapshot {
     /// The orderbook snapshot
     pub snapshot: OrderbookSnapshot,
@@ -36,9 +40,27 @@ impl HistoryBuffer {
             snapshots: VecDeque::with_capacity(max_size.min(1024)),
             max_size,
             next_sequence: 0,
+            bytes: 0,
         }
     }
 
+    /// Rebuild a buffer from previously stored snapshots
+    ///
+    /// Entries are ordered by sequence and only the newest `max_size` are
+    /// kept. New snapshots continue the sequence after the last restored one.
+    pub fn from_snapshots(max_size: usize, snapshots: impl IntoIterator<Item = TimestampedSnapshot>) -> Self {
+        let mut entries: Vec<TimestampedSnapshot> = snapshots.into_iter().collect();
+        entries.sort_by_key(|entry| entry.sequence);
+        let skip = entries.len().saturating_sub(max_size);
+        let next_sequence = entries.last().map_or(0, |entry| entry.sequence + 1);
+
+        let mut buffer = Self::new(max_size);
+        buffer.snapshots.extend(entries.into_iter().skip(skip));
+        buffer.next_sequence = next_sequence;
+        buffer.bytes = buffer.snapshots.iter().map(|e| Self::snapshot_bytes(&e.snapshot)).sum();
+        buffer
+    }
+
     /// Push a snapshot to the buffer
     ///
     /// If the buffer is full, the oldest snapshot is removed.
@@ -56,8 +78,9 @@ impl HistoryBuffer {
         self.next_sequence += 1;
 
         if self.snapshots.len() >= self.max_size {
-            self.snapshots.pop_front();
+            self.pop_oldest();
         }
+        self.bytes += Self::snapshot_bytes(&entry.snapshot);
         self.snapshots.push_back(entry);
     }
 
@@ -122,9 +145,43 @@ impl HistoryBuffer {
         self.latest().map(|s| s.sequence)
     }
 
+    /// Approximate bytes held by one stored snapshot
+    pub fn snapshot_bytes(snapshot: &OrderbookSnapshot) -> usize {
+        std::mem::size_of::<TimestampedSnapshot>()
+            + snapshot.symbol.len()
+            + (snapshot.bids.len() + snapshot.asks.len()) * std::mem::size_of::<Level>()
+    }
+
+    /// Approximate bytes held by the stored snapshots
+    pub fn approx_bytes(&self) -> usize {
+        self.bytes
+    }
+
+    /// Drop the oldest snapshots until at most `bytes` are held
+    ///
+    /// Returns the number of snapshots dropped.
+    pub fn shrink_to_bytes(&mut self, bytes: usize) -> usize {
+        let mut dropped = 0;
+        while self.bytes > bytes && self.pop_oldest() {
+            dropped += 1;
+        }
+        dropped
+    }
+
+    fn pop_oldest(&mut self) -> bool {
+        match self.snapshots.pop_front() {
+            Some(entry) => {
+                self.bytes = self.bytes.saturating_sub(Self::snapshot_bytes(&entry.snapshot));
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Clear all snapshots
     pub fn clear(&mut self) {
         self.snapshots.clear();
+        self.bytes = 0;
         // Don't reset sequence to maintain monotonicity
     }
 
@@ -215,6 +272,24 @@ mod tests {
         assert_eq!(range[2].sequence, 3);
     }
 
+    #[test]
+    fn test_from_snapshots_continues_sequence() {
+        let mut buffer = HistoryBuffer::new(10);
+        for i in 0..5 {
+            buffer.push(make_snapshot(100.0 + i as f64, 101.0 + i as f64));
+        }
+        let mut stored: Vec<TimestampedSnapshot> = buffer.iter().cloned().collect();
+        stored.reverse();
+
+        let mut restored = HistoryBuffer::from_snapshots(3, stored);
+        assert_eq!(restored.len(), 3);
+        assert_eq!(restored.oldest().unwrap().sequence, 2);
+
+        restored.push(make_snapshot(200.0, 201.0));
+        assert_eq!(restored.latest().unwrap().sequence, 5);
+        assert_eq!(restored.len(), 3);
+    }
+
     #[test]
     fn test_clear_preserves_sequence() {
         let mut buffer = HistoryBuffer::new(10);
@@ -0,0 +1,403 @@
+//! Order-flow analytics for the L3 orderbook
+//!
+//! Tracks how queues at each price level are consumed so the book can answer
+//! questions the static queue snapshot can't:
+//!
+//! - **Iceberg detection**: an order at the front of a level is consumed and a
+//!   new order with the same displayed size appears at the same price shortly
+//!   after. Repeated refills suggest a hidden reserve behind the displayed size.
+//! - **Dynamic fill probability**: instead of the naive position ratio, use the
+//!   rate at which the front of the queue has recently been depleted to estimate
+//!   how much of an order fills over the same horizon.
+//!
+//! L3 delete and modify events carry no timestamp of their own, so the book
+//! keeps a logical clock advanced by order timestamps and by
+//! [`L3Book::advance_clock`](crate::l3::L3Book::advance_clock).
+//!
+//! State is kept per price level and outlives the level itself: an iceberg
+//! tranche is often the only order at its price, so the level empties on
+//! every refill. Instead, state is pruned by age as the clock advances.
+//! Depletion samples and iceberg candidates are kept for the retention
+//! window (see [`L3Book::with_activity_retention`](crate::l3::L3Book::with_activity_retention)),
+//! pending refills for the refill window.
+
+use crate::l3::intern::OrderHandle;
+use crate::l3::order::{L3Order, L3Side, QueuePosition};
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+
+/// Maximum depletion samples retained per price level
+const MAX_DEPLETION_SAMPLES: usize = 256;
+
+/// Default age after which depletion samples and iceberg candidates are dropped (60s)
+pub const DEFAULT_ACTIVITY_RETENTION_US: u64 = 60_000_000;
+
+/// Clock time between pruning passes (microseconds)
+const PRUNE_INTERVAL_US: u64 = 1_000_000;
+
+/// Configuration for iceberg detection
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IcebergConfig {
+    /// Maximum time between a front-of-queue removal and the refill (microseconds)
+    pub refill_window_us: u64,
+    /// Minimum refills before a level is reported as a candidate
+    pub min_refills: u32,
+}
+
+impl Default for IcebergConfig {
+    fn default() -> Self {
+        Self {
+            refill_window_us: 1_000_000,
+            min_refills: 3,
+        }
+    }
+}
+
+/// A price level showing an iceberg refill pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IcebergCandidate {
+    /// Side of the book
+    pub side: L3Side,
+    /// Price level
+    pub price: Decimal,
+    /// Displayed (tranche) size
+    pub display_qty: Decimal,
+    /// Number of observed refills
+    pub refills: u32,
+    /// Estimated quantity executed through the iceberg so far
+    pub executed_qty: Decimal,
+    /// Clock value of the latest refill (microseconds)
+    pub last_refill: u64,
+}
+
+/// Queue position adjusted for recent depletion at the level
+#[derive(Debug, Clone, PartialEq)]
+pub struct DynamicQueuePosition {
+    /// Static queue position
+    pub position: QueuePosition,
+    /// Remaining quantity of the order itself
+    pub order_qty: Decimal,
+    /// Quantity consumed from the front of the level within the lookback
+    pub depleted_qty: Decimal,
+    /// Depletion rate in quantity per second
+    pub depletion_rate: f64,
+    /// Estimated seconds until the order reaches the front, if the level is moving
+    pub time_to_front_secs: Option<f64>,
+    /// Estimated fraction of the order filled over the next lookback window (0.0 to 1.0)
+    pub fill_probability: f64,
+}
+
+#[derive(Debug, Clone)]
+struct PendingRefill {
+    at: u64,
+    display_qty: Decimal,
+}
+
+/// Per-book order-flow state maintained by [`L3Book`](crate::l3::L3Book)
+#[derive(Debug, Clone)]
+pub(crate) struct L3Activity {
+    pub(crate) config: IcebergConfig,
+    /// Age after which depletion samples and iceberg candidates are dropped
+    pub(crate) retention_us: u64,
+    clock: u64,
+    /// Clock value at which the next pruning pass runs
+    next_prune: u64,
+    /// Quantity each live order had when it was added
+    initial_qty: HashMap<OrderHandle, Decimal>,
+    depletions: HashMap<(L3Side, Decimal), VecDeque<(u64, Decimal)>>,
+    pending_refills: HashMap<(L3Side, Decimal), PendingRefill>,
+    icebergs: HashMap<(L3Side, Decimal), IcebergCandidate>,
+}
+
+impl Default for L3Activity {
+    fn default() -> Self {
+        Self {
+            config: IcebergConfig::default(),
+            retention_us: DEFAULT_ACTIVITY_RETENTION_US,
+            clock: 0,
+            next_prune: 0,
+            initial_qty: HashMap::new(),
+            depletions: HashMap::new(),
+            pending_refills: HashMap::new(),
+            icebergs: HashMap::new(),
+        }
+    }
+}
+
+impl L3Activity {
+    pub(crate) fn clock(&self) -> u64 {
+        self.clock
+    }
+
+    pub(crate) fn advance_clock(&mut self, timestamp: u64) {
+        self.clock = self.clock.max(timestamp);
+        if self.clock >= self.next_prune {
+            self.prune();
+        }
+    }
+
+    /// Drop level state too old to affect any answer
+    fn prune(&mut self) {
+        let clock = self.clock;
+        let refill_window = self.config.refill_window_us;
+        let retention = self.retention_us.max(refill_window);
+
+        self.pending_refills.retain(|_, pending| clock.saturating_sub(pending.at) <= refill_window);
+        self.depletions.retain(|_, samples| {
+            // Samples are recorded in clock order
+            let expired = |at: u64| clock.saturating_sub(at) > retention;
+            while matches!(samples.front(), Some(&(at, _)) if expired(at)) {
+                samples.pop_front();
+            }
+            !samples.is_empty()
+        });
+        self.icebergs
+            .retain(|_, candidate| clock.saturating_sub(candidate.last_refill) <= retention);
+        self.next_prune = clock.saturating_add(PRUNE_INTERVAL_US);
+    }
+
+    pub(crate) fn on_add(&mut self, side: L3Side, handle: OrderHandle, order: &L3Order) {
+        self.advance_clock(order.timestamp);
+        self.initial_qty.insert(handle, order.qty);
+
+        let key = (side, order.price);
+        let Some(pending) = self.pending_refills.remove(&key) else {
+            return;
+        };
+        if pending.display_qty != order.qty
+            || self.clock.saturating_sub(pending.at) > self.config.refill_window_us
+        {
+            return;
+        }
+
+        let candidate = self.icebergs.entry(key).or_insert_with(|| IcebergCandidate {
+            side,
+            price: order.price,
+            display_qty: order.qty,
+            refills: 0,
+            executed_qty: Decimal::ZERO,
+            last_refill: 0,
+        });
+        if candidate.display_qty != order.qty {
+            // Different tranche size: start a new pattern at this level
+            candidate.display_qty = order.qty;
+            candidate.refills = 0;
+            candidate.executed_qty = Decimal::ZERO;
+        }
+        candidate.refills += 1;
+        candidate.executed_qty += pending.display_qty;
+        candidate.last_refill = self.clock;
+    }
+
+    pub(crate) fn on_remove(
+        &mut self,
+        side: L3Side,
+        handle: OrderHandle,
+        order: &L3Order,
+        was_front: bool,
+    ) {
+        let initial = self.initial_qty.remove(&handle);
+        if !was_front {
+            return;
+        }
+        self.record_depletion(side, order.price, order.qty);
+        if let Some(display_qty) = initial {
+            self.pending_refills.insert(
+                (side, order.price),
+                PendingRefill {
+                    at: self.clock,
+                    display_qty,
+                },
+            );
+        }
+    }
+
+    pub(crate) fn on_modify(
+        &mut self,
+        side: L3Side,
+        price: Decimal,
+        old_qty: Decimal,
+        new_qty: Decimal,
+        was_front: bool,
+    ) {
+        if was_front && new_qty < old_qty {
+            self.record_depletion(side, price, old_qty - new_qty);
+        }
+    }
+
+    pub(crate) fn forget_order(&mut self, handle: OrderHandle) {
+        self.initial_qty.remove(&handle);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        let (config, retention_us) = (self.config, self.retention_us);
+        *self = Self::default();
+        self.config = config;
+        self.retention_us = retention_us;
+    }
+
+    fn record_depletion(&mut self, side: L3Side, price: Decimal, qty: Decimal) {
+        let samples = self.depletions.entry((side, price)).or_default();
+        samples.push_back((self.clock, qty));
+        while samples.len() > MAX_DEPLETION_SAMPLES {
+            samples.pop_front();
+        }
+    }
+
+    /// Quantity depleted at a level within the lookback window
+    pub(crate) fn depleted_since(&self, side: L3Side, price: Decimal, lookback_us: u64) -> Decimal {
+        let since = self.clock.saturating_sub(lookback_us);
+        self.depletions
+            .get(&(side, price))
+            .map(|samples| {
+                samples
+                    .iter()
+                    .filter(|(at, _)| *at >= since)
+                    .map(|(_, qty)| *qty)
+                    .sum()
+            })
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    pub(crate) fn iceberg_candidates(&self) -> Vec<IcebergCandidate> {
+        let mut candidates: Vec<_> = self
+            .icebergs
+            .values()
+            .filter(|c| c.refills >= self.config.min_refills)
+            .cloned()
+            .collect();
+        candidates.sort_by(|a, b| b.refills.cmp(&a.refills).then(a.price.cmp(&b.price)));
+        candidates
+    }
+}
+
+impl DynamicQueuePosition {
+    pub(crate) fn new(
+        position: QueuePosition,
+        order_qty: Decimal,
+        depleted_qty: Decimal,
+        lookback_us: u64,
+    ) -> Self {
+        let lookback_secs = lookback_us as f64 / 1_000_000.0;
+        let depleted = depleted_qty.to_f64().unwrap_or(0.0);
+        let ahead = position.qty_ahead.to_f64().unwrap_or(0.0);
+        let own = order_qty.to_f64().unwrap_or(0.0);
+
+        let depletion_rate = if lookback_secs > 0.0 {
+            depleted / lookback_secs
+        } else {
+            0.0
+        };
+        let time_to_front_secs = (depletion_rate > 0.0).then(|| ahead / depletion_rate);
+        let fill_probability = if own > 0.0 {
+            ((depleted - ahead) / own).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        Self {
+            position,
+            order_qty,
+            depleted_qty,
+            depletion_rate,
+            time_to_front_secs,
+            fill_probability,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::l3::intern::OrderInterner;
+    use rust_decimal_macros::dec;
+
+    /// Add an order to the front of an otherwise empty level, then remove it
+    fn consume(activity: &mut L3Activity, interner: &mut OrderInterner, id: &str, at: u64) {
+        let order = L3Order::with_metadata(id, dec!(100), dec!(1), at, 0);
+        let handle = interner.intern(id);
+        activity.on_add(L3Side::Ask, handle, &order);
+        activity.on_remove(L3Side::Ask, handle, &order, true);
+    }
+
+    fn tracked_levels(activity: &L3Activity) -> (usize, usize, usize) {
+        (activity.depletions.len(), activity.pending_refills.len(), activity.icebergs.len())
+    }
+
+    #[test]
+    fn test_state_outlives_level_within_windows() {
+        let mut activity = L3Activity::default();
+        activity.config.min_refills = 1;
+        let mut interner = OrderInterner::new();
+        consume(&mut activity, &mut interner, "t0", 1_000);
+        consume(&mut activity, &mut interner, "t1", 1_100);
+
+        // The level emptied twice; the refill still counts
+        let candidates = activity.iceberg_candidates();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].refills, 1);
+        assert_eq!(activity.depleted_since(L3Side::Ask, dec!(100), 1_000), dec!(2));
+
+        activity.advance_clock(30_000_000);
+        assert_eq!(tracked_levels(&activity), (1, 0, 1));
+    }
+
+    #[test]
+    fn test_stale_state_is_pruned() {
+        let mut activity = L3Activity::default();
+        let mut interner = OrderInterner::new();
+        for i in 0..100u64 {
+            let price = Decimal::from(i + 1);
+            let order = L3Order::with_metadata(format!("o{}", i), price, dec!(1), i, 0);
+            let handle = interner.intern(&order.order_id);
+            activity.on_add(L3Side::Bid, handle, &order);
+            activity.on_remove(L3Side::Bid, handle, &order, true);
+        }
+        consume(&mut activity, &mut interner, "t0", 1_000);
+        consume(&mut activity, &mut interner, "t1", 1_100);
+        assert_eq!(tracked_levels(&activity), (101, 101, 1));
+
+        // Pending refills go once the refill window has passed
+        activity.advance_clock(2_000_000);
+        assert_eq!(tracked_levels(&activity), (101, 0, 1));
+
+        // Everything else once the retention window has
+        activity.advance_clock(DEFAULT_ACTIVITY_RETENTION_US + 2_000_000);
+        assert_eq!(tracked_levels(&activity), (0, 0, 0));
+        assert_eq!(activity.depleted_since(L3Side::Ask, dec!(100), u64::MAX), dec!(0));
+    }
+
+    #[test]
+    fn test_old_samples_pruned_from_active_level() {
+        let mut activity = L3Activity { retention_us: 10_000_000, ..L3Activity::default() };
+        for at in [0, 5_000_000, 12_000_000] {
+            activity.advance_clock(at);
+            activity.on_modify(L3Side::Bid, dec!(100), dec!(2), dec!(1), true);
+        }
+
+        // The sample at 0 is past retention, the others are kept
+        activity.advance_clock(14_000_000);
+        assert_eq!(activity.depletions[&(L3Side::Bid, dec!(100))].len(), 2);
+        assert_eq!(activity.depleted_since(L3Side::Bid, dec!(100), u64::MAX), dec!(2));
+    }
+
+    #[test]
+    fn test_retention_never_below_refill_window() {
+        let mut activity = L3Activity {
+            config: IcebergConfig { refill_window_us: 5_000_000, min_refills: 1 },
+            retention_us: 1_000_000,
+            ..L3Activity::default()
+        };
+        let mut interner = OrderInterner::new();
+        consume(&mut activity, &mut interner, "t0", 0);
+        consume(&mut activity, &mut interner, "t1", 4_000_000);
+        activity.advance_clock(8_000_000);
+        assert_eq!(tracked_levels(&activity), (1, 1, 1));
+
+        activity.clear();
+        assert_eq!(activity.retention_us, 1_000_000);
+        assert_eq!(activity.config.refill_window_us, 5_000_000);
+        assert_eq!(tracked_levels(&activity), (0, 0, 0));
+    }
+}
@@ -4,18 +4,25 @@
 //! individual orders with FIFO queue semantics at each price level.
 
 use crate::checksum::{compute_checksum_with_precision, DEFAULT_PRICE_PRECISION, DEFAULT_QTY_PRECISION};
+use crate::l3::analytics::{DynamicQueuePosition, IcebergCandidate, IcebergConfig, L3Activity};
+use crate::l3::intern::{OrderHandle, OrderInterner};
 use crate::l3::order::{L3Order, L3PriceLevel, L3Side, OrderLocation, QueuePosition};
-use kraken_types::Level;
+use kraken_types::{Level, Notional, Price, Qty};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::cmp::Reverse;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 
 /// L3 orderbook with individual order tracking
 ///
 /// This is a hybrid data structure that uses:
 /// - BTreeMap for price levels (sorted order for efficient iteration)
-/// - HashMap for order index (O(1) lookup by order ID)
+/// - An [`OrderInterner`] mapping order IDs to compact handles, and a dense
+///   handle-indexed table locating each order's level and queue slot
+/// - Slab-backed intrusive FIFO queues per level (O(1) remove/modify)
+///
+/// The string-keyed methods hash the order ID once; the `*_by_handle`
+/// variants skip even that.
 ///
 /// # Example
 ///
@@ -42,8 +49,10 @@ pub struct L3Book {
     bids: BTreeMap<Reverse<Decimal>, L3PriceLevel>,
     /// Ask levels (lowest first - natural ascending order)
     asks: BTreeMap<Decimal, L3PriceLevel>,
-    /// Order index: order_id -> (price, side) for O(1) lookup
-    order_index: HashMap<String, OrderLocation>,
+    /// Order ID <-> handle map
+    interner: OrderInterner,
+    /// Handle -> location, indexed by handle value
+    order_index: Vec<Option<IndexEntry>>,
     /// Maximum depth to maintain
     depth: u32,
     /// Last sequence number processed
@@ -52,6 +61,8 @@ pub struct L3Book {
     price_precision: u8,
     /// Quantity precision for checksum
     qty_precision: u8,
+    /// Order-flow state for iceberg and depletion analytics
+    activity: L3Activity,
 }
 
 impl L3Book {
@@ -61,14 +72,33 @@ impl L3Book {
             symbol: symbol.into(),
             bids: BTreeMap::new(),
             asks: BTreeMap::new(),
-            order_index: HashMap::new(),
+            interner: OrderInterner::new(),
+            order_index: Vec::new(),
             depth,
             last_sequence: 0,
             price_precision: DEFAULT_PRICE_PRECISION,
             qty_precision: DEFAULT_QTY_PRECISION,
+            activity: L3Activity::default(),
         }
     }
 
+    /// Set the iceberg detection parameters
+    pub fn with_iceberg_config(mut self, config: IcebergConfig) -> Self {
+        self.activity.config = config;
+        self
+    }
+
+    /// Set how long order-flow state is kept (microseconds of book clock)
+    ///
+    /// Depletion samples and iceberg candidates older than this are dropped
+    /// as the clock advances, so a dynamic queue position can look back at
+    /// most this far. Never less than the iceberg refill window. Defaults to
+    /// [`DEFAULT_ACTIVITY_RETENTION_US`](crate::l3::DEFAULT_ACTIVITY_RETENTION_US).
+    pub fn with_activity_retention(mut self, retention_us: u64) -> Self {
+        self.activity.retention_us = retention_us;
+        self
+    }
+
     /// Set precision for checksum calculation
     pub fn set_precision(&mut self, price_precision: u8, qty_precision: u8) {
         self.price_precision = price_precision;
@@ -103,125 +133,179 @@ impl L3Book {
     ///
     /// Returns true if the order was added, false if it already exists
     pub fn add_order(&mut self, order: L3Order, side: L3Side) -> bool {
-        // Check if order already exists
-        if self.order_index.contains_key(&order.order_id) {
-            return false;
-        }
+        self.insert_order(order, side).is_some()
+    }
+
+    /// Add a new order and return its handle
+    ///
+    /// Returns None if an order with the same ID already exists.
+    pub fn insert_order(&mut self, order: L3Order, side: L3Side) -> Option<OrderHandle> {
+        let handle = self.interner.intern_new(&order.order_id)?;
+        self.activity.on_add(side, handle, &order);
 
         let price = order.price;
-        let order_id = order.order_id.clone();
+        let slot = match side {
+            L3Side::Bid => self
+                .bids
+                .entry(Reverse(price))
+                .or_insert_with(|| L3PriceLevel::new(price))
+                .push_back(order),
+            L3Side::Ask => self
+                .asks
+                .entry(price)
+                .or_insert_with(|| L3PriceLevel::new(price))
+                .push_back(order),
+        };
 
-        match side {
-            L3Side::Bid => {
-                let level = self.bids
-                    .entry(Reverse(price))
-                    .or_insert_with(|| L3PriceLevel::new(price));
-                level.add_order(order);
-            }
-            L3Side::Ask => {
-                let level = self.asks
-                    .entry(price)
-                    .or_insert_with(|| L3PriceLevel::new(price));
-                level.add_order(order);
-            }
+        if self.order_index.len() < self.interner.capacity() {
+            self.order_index.resize(self.interner.capacity(), None);
         }
-
-        // Update index
-        self.order_index.insert(order_id, OrderLocation { price, side });
-        true
+        self.order_index[handle.index()] = Some(IndexEntry { price, side, slot });
+        Some(handle)
     }
 
     /// Remove an order from the book
     ///
     /// Returns the removed order if found
     pub fn remove_order(&mut self, order_id: &str) -> Option<L3Order> {
-        // Look up order location
-        let location = self.order_index.remove(order_id)?;
+        let handle = self.interner.get(order_id)?;
+        self.remove_by_handle(handle)
+    }
+
+    /// Remove an order by handle
+    pub fn remove_by_handle(&mut self, handle: OrderHandle) -> Option<L3Order> {
+        if !self.interner.is_live(handle) {
+            return None;
+        }
+        let entry = self.order_index.get_mut(handle.index())?.take()?;
 
-        let removed = match location.side {
+        let (order, was_front, emptied) = match entry.side {
             L3Side::Bid => {
-                let level = self.bids.get_mut(&Reverse(location.price))?;
-                let order = level.remove_order(order_id)?;
-                // Clean up empty level
-                if level.is_empty() {
-                    self.bids.remove(&Reverse(location.price));
-                }
-                order
+                let level = self.bids.get_mut(&Reverse(entry.price))?;
+                let was_front = level.is_front(entry.slot);
+                let order = level.remove_slot(entry.slot)?;
+                (order, was_front, level.is_empty())
             }
             L3Side::Ask => {
-                let level = self.asks.get_mut(&location.price)?;
-                let order = level.remove_order(order_id)?;
-                // Clean up empty level
-                if level.is_empty() {
-                    self.asks.remove(&location.price);
-                }
-                order
+                let level = self.asks.get_mut(&entry.price)?;
+                let was_front = level.is_front(entry.slot);
+                let order = level.remove_slot(entry.slot)?;
+                (order, was_front, level.is_empty())
             }
         };
 
-        Some(removed)
+        // Clean up empty level
+        if emptied {
+            match entry.side {
+                L3Side::Bid => self.bids.remove(&Reverse(entry.price)),
+                L3Side::Ask => self.asks.remove(&entry.price),
+            };
+        }
+
+        self.activity.on_remove(entry.side, handle, &order, was_front);
+        self.interner.release(handle);
+        Some(order)
     }
 
     /// Modify an order's quantity
     ///
     /// Returns true if the order was found and modified
     pub fn modify_order(&mut self, order_id: &str, new_qty: Decimal) -> bool {
-        let location = match self.order_index.get(order_id) {
-            Some(loc) => loc.clone(),
-            None => return false,
+        match self.interner.get(order_id) {
+            Some(handle) => self.modify_by_handle(handle, new_qty),
+            None => false,
+        }
+    }
+
+    /// Modify an order's quantity by handle
+    pub fn modify_by_handle(&mut self, handle: OrderHandle, new_qty: Decimal) -> bool {
+        let Some(entry) = self.entry(handle).cloned() else {
+            return false;
+        };
+        let Some(level) = self.level_mut(entry.side, entry.price) else {
+            return false;
         };
 
-        match location.side {
-            L3Side::Bid => {
-                if let Some(level) = self.bids.get_mut(&Reverse(location.price)) {
-                    return level.modify_order(order_id, new_qty);
-                }
-            }
-            L3Side::Ask => {
-                if let Some(level) = self.asks.get_mut(&location.price) {
-                    return level.modify_order(order_id, new_qty);
-                }
-            }
-        }
-        false
+        let was_front = level.is_front(entry.slot);
+        let Some(old_qty) = level.modify_slot(entry.slot, new_qty) else {
+            return false;
+        };
+        self.activity
+            .on_modify(entry.side, entry.price, old_qty, new_qty, was_front);
+        true
     }
 
     /// Get an order by ID
     pub fn get_order(&self, order_id: &str) -> Option<&L3Order> {
-        let location = self.order_index.get(order_id)?;
+        self.get_by_handle(self.interner.get(order_id)?)
+    }
 
-        match location.side {
-            L3Side::Bid => {
-                self.bids.get(&Reverse(location.price))?.get_order(order_id)
-            }
-            L3Side::Ask => {
-                self.asks.get(&location.price)?.get_order(order_id)
-            }
-        }
+    /// Get an order by handle
+    pub fn get_by_handle(&self, handle: OrderHandle) -> Option<&L3Order> {
+        let entry = self.entry(handle)?;
+        self.level(entry.side, entry.price)?.get_slot(entry.slot)
     }
 
     /// Get the queue position for an order
     pub fn queue_position(&self, order_id: &str) -> Option<QueuePosition> {
-        let location = self.order_index.get(order_id)?;
+        self.queue_position_by_handle(self.interner.get(order_id)?)
+    }
 
-        match location.side {
-            L3Side::Bid => {
-                self.bids.get(&Reverse(location.price))?.queue_position(order_id)
-            }
-            L3Side::Ask => {
-                self.asks.get(&location.price)?.queue_position(order_id)
-            }
-        }
+    /// Get the queue position for an order by handle
+    pub fn queue_position_by_handle(&self, handle: OrderHandle) -> Option<QueuePosition> {
+        let entry = self.entry(handle)?;
+        self.level(entry.side, entry.price)?
+            .queue_position_slot(entry.slot)
     }
 
     /// Get the side of an order
     pub fn order_side(&self, order_id: &str) -> Option<L3Side> {
-        self.order_index.get(order_id).map(|loc| loc.side)
+        self.order_location(order_id).map(|loc| loc.side)
+    }
+
+    /// Get the price and side of an order
+    pub fn order_location(&self, order_id: &str) -> Option<OrderLocation> {
+        let entry = self.entry(self.interner.get(order_id)?)?;
+        Some(OrderLocation {
+            price: entry.price,
+            side: entry.side,
+        })
     }
 
     /// Check if an order exists
     pub fn has_order(&self, order_id: &str) -> bool {
-        self.order_index.contains_key(order_id)
+        self.interner.get(order_id).is_some()
+    }
+
+    /// Get the handle for a live order
+    pub fn order_handle(&self, order_id: &str) -> Option<OrderHandle> {
+        self.interner.get(order_id)
+    }
+
+    /// Resolve a handle to its order ID
+    pub fn order_id(&self, handle: OrderHandle) -> Option<&str> {
+        self.interner.resolve(handle)
+    }
+
+    fn entry(&self, handle: OrderHandle) -> Option<&IndexEntry> {
+        if !self.interner.is_live(handle) {
+            return None;
+        }
+        self.order_index.get(handle.index())?.as_ref()
+    }
+
+    fn level(&self, side: L3Side, price: Decimal) -> Option<&L3PriceLevel> {
+        match side {
+            L3Side::Bid => self.bids.get(&Reverse(price)),
+            L3Side::Ask => self.asks.get(&price),
+        }
+    }
+
+    fn level_mut(&mut self, side: L3Side, price: Decimal) -> Option<&mut L3PriceLevel> {
+        match side {
+            L3Side::Bid => self.bids.get_mut(&Reverse(price)),
+            L3Side::Ask => self.asks.get_mut(&price),
+        }
     }
 
     // ========================================================================
@@ -232,8 +316,10 @@ impl L3Book {
     pub fn clear(&mut self) {
         self.bids.clear();
         self.asks.clear();
+        self.interner.clear();
         self.order_index.clear();
         self.last_sequence = 0;
+        self.activity.clear();
     }
 
     /// Get the best bid (highest price level)
@@ -304,12 +390,12 @@ impl L3Book {
 
     /// Total number of orders in the book
     pub fn order_count(&self) -> usize {
-        self.order_index.len()
+        self.interner.len()
     }
 
     /// Check if book is empty
     pub fn is_empty(&self) -> bool {
-        self.order_index.is_empty()
+        self.interner.is_empty()
     }
 
     // ========================================================================
@@ -350,6 +436,82 @@ impl L3Book {
             .collect()
     }
 
+    // ========================================================================
+    // Banded Queries
+    // ========================================================================
+
+    /// Aggregated bids priced within `bps_from_mid` basis points below the mid
+    ///
+    /// Only levels inside the band are visited. Empty while either side is
+    /// empty.
+    pub fn aggregated_bids_within(&self, bps_from_mid: Decimal) -> Vec<Level> {
+        let Some((floor, _)) = self.mid_band(bps_from_mid) else {
+            return Vec::new();
+        };
+        self.bids
+            .range(..=Reverse(floor))
+            .map(|(_, level)| Level::new(level.price, level.total_qty()))
+            .collect()
+    }
+
+    /// Aggregated asks priced within `bps_from_mid` basis points above the mid
+    ///
+    /// Only levels inside the band are visited. Empty while either side is
+    /// empty.
+    pub fn aggregated_asks_within(&self, bps_from_mid: Decimal) -> Vec<Level> {
+        let Some((_, ceiling)) = self.mid_band(bps_from_mid) else {
+            return Vec::new();
+        };
+        self.asks
+            .range(..=ceiling)
+            .map(|(_, level)| Level::new(level.price, level.total_qty()))
+            .collect()
+    }
+
+    /// Orders on both sides priced in `lo..=hi`
+    ///
+    /// Bids come first (best first), then asks (best first); each level's
+    /// orders are in queue order. Nothing is copied.
+    pub fn orders_within_price_range(
+        &self,
+        lo: Decimal,
+        hi: Decimal,
+    ) -> impl Iterator<Item = (L3Side, &L3Order)> {
+        // `range` panics on an inverted range
+        let (bids, asks) = if lo <= hi {
+            (Some(self.bids.range(Reverse(hi)..=Reverse(lo))), Some(self.asks.range(lo..=hi)))
+        } else {
+            (None, None)
+        };
+        let bids = bids
+            .into_iter()
+            .flatten()
+            .flat_map(|(_, level)| level.orders().map(|order| (L3Side::Bid, order)));
+        let asks = asks
+            .into_iter()
+            .flatten()
+            .flat_map(|(_, level)| level.orders().map(|order| (L3Side::Ask, order)));
+        bids.chain(asks)
+    }
+
+    /// Quantity a new order at `price` on `side` would queue behind
+    ///
+    /// Sums every level priced better than `price` plus the whole level at
+    /// `price`, since a new order joins the back of that queue.
+    pub fn qty_ahead_of_price(&self, side: L3Side, price: Decimal) -> Decimal {
+        match side {
+            L3Side::Bid => self.bids.range(..=Reverse(price)).map(|(_, l)| l.total_qty()).sum(),
+            L3Side::Ask => self.asks.range(..=price).map(|(_, l)| l.total_qty()).sum(),
+        }
+    }
+
+    /// Prices `bps` basis points below and above the mid
+    fn mid_band(&self, bps: Decimal) -> Option<(Decimal, Decimal)> {
+        let mid = self.mid_price()?;
+        let offset = mid * bps / Decimal::from(10_000);
+        Some((mid - offset, mid + offset))
+    }
+
     // ========================================================================
     // Checksum Validation
     // ========================================================================
@@ -393,7 +555,7 @@ impl L3Book {
             for key in keys_to_remove {
                 if let Some(level) = self.bids.remove(&key) {
                     for order in level.orders() {
-                        self.order_index.remove(&order.order_id);
+                        self.forget(&order.order_id);
                     }
                 }
             }
@@ -405,13 +567,22 @@ impl L3Book {
             for key in keys_to_remove {
                 if let Some(level) = self.asks.remove(&key) {
                     for order in level.orders() {
-                        self.order_index.remove(&order.order_id);
+                        self.forget(&order.order_id);
                     }
                 }
             }
         }
     }
 
+    /// Drop index state for an order removed without going through `remove_by_handle`
+    fn forget(&mut self, order_id: &str) {
+        if let Some(handle) = self.interner.get(order_id) {
+            self.order_index[handle.index()] = None;
+            self.activity.forget_order(handle);
+            self.interner.release(handle);
+        }
+    }
+
     // ========================================================================
     // Analytics
     // ========================================================================
@@ -444,18 +615,40 @@ impl L3Book {
     }
 
     /// Get VWAP (Volume Weighted Average Price) for bids up to a quantity
+    pub fn bid_vwap(&self, target_qty: impl Into<Qty>) -> Option<Price> {
+        Self::vwap(self.bids.values(), target_qty.into())
+    }
+
+    /// Get VWAP (Volume Weighted Average Price) for asks up to a quantity
+    pub fn ask_vwap(&self, target_qty: impl Into<Qty>) -> Option<Price> {
+        Self::vwap(self.asks.values(), target_qty.into())
+    }
+
+    /// Get VWAP for bids up to a quantity, as a bare Decimal
+    #[deprecated(note = "use `bid_vwap`, which takes a `Qty` and returns a `Price`")]
     pub fn vwap_bid(&self, target_qty: Decimal) -> Option<Decimal> {
+        self.bid_vwap(target_qty).map(Decimal::from)
+    }
+
+    /// Get VWAP for asks up to a quantity, as a bare Decimal
+    #[deprecated(note = "use `ask_vwap`, which takes a `Qty` and returns a `Price`")]
+    pub fn vwap_ask(&self, target_qty: Decimal) -> Option<Decimal> {
+        self.ask_vwap(target_qty).map(Decimal::from)
+    }
+
+    /// Average fill price walking levels best-first until `target_qty` is filled
+    fn vwap<'a>(levels: impl Iterator<Item = &'a L3PriceLevel>, target_qty: Qty) -> Option<Price> {
         let mut remaining = target_qty;
-        let mut total_value = Decimal::ZERO;
-        let mut total_qty = Decimal::ZERO;
+        let mut total_value = Notional::ZERO;
+        let mut total_qty = Qty::ZERO;
 
-        for level in self.bids.values() {
+        for level in levels {
             if remaining.is_zero() {
                 break;
             }
 
-            let fill_qty = remaining.min(level.total_qty());
-            total_value += level.price * fill_qty;
+            let fill_qty = remaining.min(Qty(level.total_qty()));
+            total_value += Price(level.price) * fill_qty;
             total_qty += fill_qty;
             remaining -= fill_qty;
         }
@@ -467,28 +660,53 @@ impl L3Book {
         }
     }
 
-    /// Get VWAP (Volume Weighted Average Price) for asks up to a quantity
-    pub fn vwap_ask(&self, target_qty: Decimal) -> Option<Decimal> {
-        let mut remaining = target_qty;
-        let mut total_value = Decimal::ZERO;
-        let mut total_qty = Decimal::ZERO;
+    // ========================================================================
+    // Order Flow Analytics
+    // ========================================================================
 
-        for level in self.asks.values() {
-            if remaining.is_zero() {
-                break;
-            }
+    /// Current value of the book's logical clock (microseconds)
+    ///
+    /// Advanced by the timestamps of added orders and by [`advance_clock`](Self::advance_clock).
+    pub fn clock(&self) -> u64 {
+        self.activity.clock()
+    }
 
-            let fill_qty = remaining.min(level.total_qty());
-            total_value += level.price * fill_qty;
-            total_qty += fill_qty;
-            remaining -= fill_qty;
-        }
+    /// Advance the logical clock
+    ///
+    /// Call with the message timestamp before applying delete/modify events,
+    /// which carry no timestamp of their own. The clock never moves backwards.
+    pub fn advance_clock(&mut self, timestamp: u64) {
+        self.activity.advance_clock(timestamp);
+    }
 
-        if total_qty.is_zero() {
-            None
-        } else {
-            Some(total_value / total_qty)
-        }
+    /// Price levels showing a repeated refill pattern typical of iceberg orders
+    ///
+    /// A refill is counted when the order at the front of a level is removed and
+    /// a new order with the same initial size is added at the same price within
+    /// the configured window. Sorted by refill count, most refills first.
+    pub fn iceberg_candidates(&self) -> Vec<IcebergCandidate> {
+        self.activity.iceberg_candidates()
+    }
+
+    /// Queue position adjusted by the recent depletion rate at the order's level
+    ///
+    /// `lookback_us` is the window (in microseconds of book clock) over which
+    /// front-of-queue consumption is measured, capped by the
+    /// [activity retention](Self::with_activity_retention). Returns None if
+    /// the order is not in the book.
+    pub fn queue_position_dynamic(
+        &self,
+        order_id: &str,
+        lookback_us: u64,
+    ) -> Option<DynamicQueuePosition> {
+        let handle = self.interner.get(order_id)?;
+        let entry = self.entry(handle)?;
+        let position = self.queue_position_by_handle(handle)?;
+        let order_qty = self.get_by_handle(handle)?.qty;
+        let depleted = self
+            .activity
+            .depleted_since(entry.side, entry.price, lookback_us);
+        Some(DynamicQueuePosition::new(position, order_qty, depleted, lookback_us))
     }
 
     /// Take a snapshot of the current book state
@@ -505,6 +723,15 @@ impl L3Book {
     }
 }
 
+/// Where a live order sits in the book
+#[derive(Debug, Clone, Copy)]
+struct IndexEntry {
+    price: Decimal,
+    side: L3Side,
+    /// Slot in the level's order slab
+    slot: u32,
+}
+
 /// Checksum mismatch error for L3 book
 #[derive(Debug, Clone)]
 pub struct L3ChecksumMismatch {
@@ -552,12 +779,12 @@ impl L3BookSnapshot {
 
     /// Get best bid price
     pub fn best_bid_price(&self) -> Option<Decimal> {
-        self.bids.first().map(|l| l.price)
+        self.bids.first().map(|l| l.price.0)
     }
 
     /// Get best ask price
     pub fn best_ask_price(&self) -> Option<Decimal> {
-        self.asks.first().map(|l| l.price)
+        self.asks.first().map(|l| l.price.0)
     }
 
     /// Get spread
@@ -703,6 +930,50 @@ mod tests {
         assert_eq!(bids[1].qty, dec!(3));
     }
 
+    #[test]
+    fn test_banded_aggregation_around_mid() {
+        let mut book = L3Book::new("BTC/USD", 10);
+
+        book.add_order(L3Order::new("b1", dec!(99.95), dec!(1)), L3Side::Bid);
+        book.add_order(L3Order::new("b2", dec!(99.95), dec!(2)), L3Side::Bid);
+        book.add_order(L3Order::new("b3", dec!(99), dec!(5)), L3Side::Bid);
+        book.add_order(L3Order::new("a1", dec!(100.05), dec!(1)), L3Side::Ask);
+        book.add_order(L3Order::new("a2", dec!(100.10), dec!(4)), L3Side::Ask);
+
+        // Mid 100, 10 bps = 0.10 either side
+        let bids = book.aggregated_bids_within(dec!(10));
+        assert_eq!(bids.len(), 1);
+        assert_eq!(bids[0].qty, dec!(3));
+        let asks = book.aggregated_asks_within(dec!(10));
+        assert_eq!(asks.len(), 2);
+        assert_eq!(asks[1].price, dec!(100.10));
+
+        assert!(L3Book::new("ETH/USD", 10).aggregated_bids_within(dec!(10)).is_empty());
+    }
+
+    #[test]
+    fn test_orders_within_price_range_and_qty_ahead() {
+        let mut book = L3Book::new("BTC/USD", 10);
+
+        book.add_order(L3Order::new("b1", dec!(100), dec!(1)), L3Side::Bid);
+        book.add_order(L3Order::new("b2", dec!(99), dec!(2)), L3Side::Bid);
+        book.add_order(L3Order::new("b3", dec!(98), dec!(3)), L3Side::Bid);
+        book.add_order(L3Order::new("a1", dec!(101), dec!(1)), L3Side::Ask);
+        book.add_order(L3Order::new("a2", dec!(102), dec!(2)), L3Side::Ask);
+
+        let ids: Vec<_> = book
+            .orders_within_price_range(dec!(99), dec!(101))
+            .map(|(side, order)| (side, order.order_id.as_str()))
+            .collect();
+        assert_eq!(ids, vec![(L3Side::Bid, "b1"), (L3Side::Bid, "b2"), (L3Side::Ask, "a1")]);
+        assert_eq!(book.orders_within_price_range(dec!(101), dec!(99)).count(), 0);
+
+        assert_eq!(book.qty_ahead_of_price(L3Side::Bid, dec!(99)), dec!(3));
+        assert_eq!(book.qty_ahead_of_price(L3Side::Bid, dec!(99.5)), dec!(1));
+        assert_eq!(book.qty_ahead_of_price(L3Side::Ask, dec!(101.5)), dec!(1));
+        assert_eq!(book.qty_ahead_of_price(L3Side::Ask, dec!(100)), dec!(0));
+    }
+
     #[test]
     fn test_truncate() {
         let mut book = L3Book::new("BTC/USD", 2);
@@ -737,10 +1008,134 @@ mod tests {
         book.add_order(L3Order::new("a3", dec!(102), dec!(3)), L3Side::Ask);
 
         // VWAP for buying 3 units: (100*1 + 101*2) / 3 = 302/3 = ~100.67
-        let vwap = book.vwap_ask(dec!(3)).unwrap();
+        let vwap = book.ask_vwap(dec!(3)).unwrap();
         assert!(vwap > dec!(100.66) && vwap < dec!(100.68));
     }
 
+    #[test]
+    fn test_handle_operations() {
+        let mut book = L3Book::new("BTC/USD", 10);
+
+        let h1 = book.insert_order(L3Order::new("o1", dec!(100), dec!(1)), L3Side::Bid).unwrap();
+        let h2 = book.insert_order(L3Order::new("o2", dec!(100), dec!(2)), L3Side::Bid).unwrap();
+        assert!(book.insert_order(L3Order::new("o1", dec!(100), dec!(1)), L3Side::Bid).is_none());
+
+        assert_eq!(book.order_handle("o2"), Some(h2));
+        assert_eq!(book.order_id(h1), Some("o1"));
+        assert!(book.modify_by_handle(h2, dec!(5)));
+        assert_eq!(book.get_by_handle(h2).unwrap().qty, dec!(5));
+        assert_eq!(book.queue_position_by_handle(h2).unwrap().qty_ahead, dec!(1));
+
+        assert_eq!(book.remove_by_handle(h1).unwrap().order_id, "o1");
+        assert!(book.remove_by_handle(h1).is_none());
+        assert!(!book.has_order("o1"));
+        assert_eq!(book.queue_position("o2").unwrap().position, 0);
+
+        // The slot is recycled, but the stale handle doesn't address the new order
+        let h3 = book.insert_order(L3Order::new("o3", dec!(101), dec!(1)), L3Side::Ask).unwrap();
+        assert_ne!(h3, h1);
+        assert!(book.get_by_handle(h1).is_none());
+        assert!(book.order_id(h1).is_none());
+        assert!(!book.modify_by_handle(h1, dec!(9)));
+        assert!(book.remove_by_handle(h1).is_none());
+        assert_eq!(book.get_by_handle(h3).unwrap().qty, dec!(1));
+        assert_eq!(book.order_location("o3").unwrap().side, L3Side::Ask);
+        assert_eq!(book.order_count(), 2);
+    }
+
+    #[test]
+    fn test_truncate_releases_handles() {
+        let mut book = L3Book::new("BTC/USD", 1);
+        book.add_order(L3Order::new("b1", dec!(100), dec!(1)), L3Side::Bid);
+        book.add_order(L3Order::new("b2", dec!(99), dec!(1)), L3Side::Bid);
+        book.truncate();
+
+        assert!(book.order_handle("b2").is_none());
+        assert!(!book.modify_order("b2", dec!(2)));
+        assert!(book.add_order(L3Order::new("b2", dec!(98), dec!(1)), L3Side::Bid));
+    }
+
+    #[test]
+    fn test_iceberg_refills_detected() {
+        let mut book = L3Book::new("BTC/USD", 10);
+        book.add_order(L3Order::with_metadata("a1", dec!(101), dec!(5), 1_000, 0), L3Side::Ask);
+        book.add_order(L3Order::with_metadata("ice0", dec!(100), dec!(1), 1_000, 1), L3Side::Ask);
+        book.add_order(L3Order::with_metadata("other", dec!(100), dec!(2), 1_000, 2), L3Side::Ask);
+
+        // Each tranche is consumed at the front and replaced at the back
+        for i in 1..=3u64 {
+            let ts = 1_000 + i * 100;
+            book.advance_clock(ts);
+            book.remove_order(&format!("ice{}", i - 1));
+            book.add_order(
+                L3Order::with_metadata(format!("ice{}", i), dec!(100), dec!(1), ts + 10, 0),
+                L3Side::Ask,
+            );
+            // Move the other order out of the way so the next tranche is at the front
+            if i == 1 {
+                book.remove_order("other");
+            }
+        }
+
+        let candidates = book.iceberg_candidates();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].price, dec!(100));
+        assert_eq!(candidates[0].display_qty, dec!(1));
+        assert_eq!(candidates[0].refills, 3);
+        assert_eq!(candidates[0].executed_qty, dec!(3));
+    }
+
+    #[test]
+    fn test_iceberg_ignores_slow_or_resized_refills() {
+        let mut book = L3Book::new("BTC/USD", 10).with_iceberg_config(IcebergConfig {
+            refill_window_us: 50,
+            min_refills: 1,
+        });
+        book.add_order(L3Order::with_metadata("o1", dec!(100), dec!(1), 0, 0), L3Side::Bid);
+        book.remove_order("o1");
+        // Arrives too late
+        book.add_order(L3Order::with_metadata("o2", dec!(100), dec!(1), 1_000, 0), L3Side::Bid);
+        book.remove_order("o2");
+        // Different size
+        book.add_order(L3Order::with_metadata("o3", dec!(100), dec!(2), 1_010, 0), L3Side::Bid);
+
+        assert!(book.iceberg_candidates().is_empty());
+    }
+
+    #[test]
+    fn test_queue_position_dynamic() {
+        let mut book = L3Book::new("BTC/USD", 10);
+        book.add_order(L3Order::with_metadata("o1", dec!(100), dec!(4), 0, 0), L3Side::Bid);
+        book.add_order(L3Order::with_metadata("o2", dec!(100), dec!(2), 0, 1), L3Side::Bid);
+        book.add_order(L3Order::with_metadata("mine", dec!(100), dec!(2), 0, 2), L3Side::Bid);
+
+        // 3 units consumed from the front over one second
+        book.advance_clock(500_000);
+        book.modify_order("o1", dec!(1));
+        book.advance_clock(1_000_000);
+
+        let dynamic = book.queue_position_dynamic("mine", 1_000_000).unwrap();
+        assert_eq!(dynamic.position.qty_ahead, dec!(3));
+        assert_eq!(dynamic.depleted_qty, dec!(3));
+        assert!((dynamic.depletion_rate - 3.0).abs() < 1e-9);
+        assert!((dynamic.time_to_front_secs.unwrap() - 1.0).abs() < 1e-9);
+        assert_eq!(dynamic.fill_probability, 0.0);
+
+        // Another 4 units consumed: front order gone, o2 partially filled
+        book.remove_order("o1");
+        book.modify_order("o2", dec!(0.5));
+        let dynamic = book.queue_position_dynamic("mine", 1_000_000).unwrap();
+        assert_eq!(dynamic.depleted_qty, dec!(5.5));
+        assert!(dynamic.fill_probability > 0.99);
+
+        // Depletion outside the lookback window is ignored
+        book.advance_clock(10_000_000);
+        let dynamic = book.queue_position_dynamic("mine", 1_000_000).unwrap();
+        assert_eq!(dynamic.depleted_qty, dec!(0));
+        assert!(dynamic.time_to_front_secs.is_none());
+        assert!(book.queue_position_dynamic("missing", 1_000_000).is_none());
+    }
+
     #[test]
     fn test_snapshot() {
         let mut book = L3Book::new("BTC/USD", 10);
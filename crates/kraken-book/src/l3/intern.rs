@@ -0,0 +1,219 @@
+//! Order ID interning for the L3 orderbook
+//!
+//! Kraken order IDs are strings. Hashing and comparing them on every
+//! add/modify/delete dominates the cost of large books, so the book maps each
+//! live order ID to a compact [`OrderHandle`] once and uses the handle for
+//! everything else.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Compact handle for an interned order ID
+///
+/// Handles are only valid while the order is in the book. Slots are recycled
+/// after the order is removed, but each reuse bumps the slot's generation, so
+/// a stale handle never resolves to the order that took its slot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OrderHandle {
+    index: u32,
+    generation: u32,
+}
+
+impl OrderHandle {
+    /// Raw handle value (generation in the high 32 bits, slot in the low)
+    pub fn as_u64(&self) -> u64 {
+        (u64::from(self.generation) << 32) | u64::from(self.index)
+    }
+
+    /// Generation of the slot this handle was issued for
+    pub fn generation(&self) -> u32 {
+        self.generation
+    }
+
+    pub(crate) fn index(&self) -> usize {
+        self.index as usize
+    }
+}
+
+/// One interner slot: the ID it holds and how often it has been reused
+#[derive(Debug, Clone, Default)]
+struct Slot {
+    id: Option<Arc<str>>,
+    generation: u32,
+}
+
+/// Bidirectional map between order IDs and handles
+///
+/// Both directions share a single allocation per ID.
+#[derive(Debug, Clone, Default)]
+pub struct OrderInterner {
+    by_id: HashMap<Arc<str>, OrderHandle>,
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+}
+
+impl OrderInterner {
+    /// Create an empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Intern an order ID, returning the existing handle if already present
+    pub fn intern(&mut self, order_id: &str) -> OrderHandle {
+        match self.by_id.get(order_id) {
+            Some(handle) => *handle,
+            None => self.insert(order_id),
+        }
+    }
+
+    /// Intern an order ID that must not already be present
+    ///
+    /// Returns None if the ID is already interned.
+    pub fn intern_new(&mut self, order_id: &str) -> Option<OrderHandle> {
+        if self.by_id.contains_key(order_id) {
+            return None;
+        }
+        Some(self.insert(order_id))
+    }
+
+    fn insert(&mut self, order_id: &str) -> OrderHandle {
+        let id: Arc<str> = Arc::from(order_id);
+        let handle = match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.slots[index as usize];
+                slot.id = Some(id.clone());
+                OrderHandle {
+                    index,
+                    generation: slot.generation,
+                }
+            }
+            None => {
+                self.slots.push(Slot {
+                    id: Some(id.clone()),
+                    generation: 0,
+                });
+                OrderHandle {
+                    index: (self.slots.len() - 1) as u32,
+                    generation: 0,
+                }
+            }
+        };
+        self.by_id.insert(id, handle);
+        handle
+    }
+
+    /// Look up the handle for an order ID
+    pub fn get(&self, order_id: &str) -> Option<OrderHandle> {
+        self.by_id.get(order_id).copied()
+    }
+
+    /// Resolve a handle back to its order ID
+    ///
+    /// Returns None for released handles, even if their slot was reused.
+    pub fn resolve(&self, handle: OrderHandle) -> Option<&str> {
+        self.live_slot(handle)?.id.as_deref()
+    }
+
+    /// Check that a handle still addresses the order it was issued for
+    pub fn is_live(&self, handle: OrderHandle) -> bool {
+        self.live_slot(handle).is_some()
+    }
+
+    /// Release a handle so its slot can be reused
+    ///
+    /// Returns true if the handle was live.
+    pub fn release(&mut self, handle: OrderHandle) -> bool {
+        if !self.is_live(handle) {
+            return false;
+        }
+        let slot = &mut self.slots[handle.index()];
+        if let Some(id) = slot.id.take() {
+            self.by_id.remove(&id);
+        }
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        true
+    }
+
+    fn live_slot(&self, handle: OrderHandle) -> Option<&Slot> {
+        self.slots
+            .get(handle.index())
+            .filter(|slot| slot.id.is_some() && slot.generation == handle.generation)
+    }
+
+    /// Number of live handles
+    pub fn len(&self) -> usize {
+        self.by_id.len()
+    }
+
+    /// Check if no handles are live
+    pub fn is_empty(&self) -> bool {
+        self.by_id.is_empty()
+    }
+
+    /// Upper bound on handle indices (for sizing dense side tables)
+    pub(crate) fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Release all handles
+    ///
+    /// Slots keep their generations, so handles issued before the clear stay
+    /// invalid after their slots are reused.
+    pub fn clear(&mut self) {
+        self.by_id.clear();
+        self.free.clear();
+        for (index, slot) in self.slots.iter_mut().enumerate().rev() {
+            if slot.id.take().is_some() {
+                slot.generation = slot.generation.wrapping_add(1);
+            }
+            self.free.push(index as u32);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_intern_and_resolve() {
+        let mut interner = OrderInterner::new();
+        let a = interner.intern("OABC-1");
+        let b = interner.intern("OABC-2");
+
+        assert_ne!(a, b);
+        assert_eq!(interner.intern("OABC-1"), a);
+        assert_eq!(interner.get("OABC-2"), Some(b));
+        assert_eq!(interner.resolve(a), Some("OABC-1"));
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn test_release_recycles_handles() {
+        let mut interner = OrderInterner::new();
+        let a = interner.intern("o1");
+        interner.intern("o2");
+
+        assert!(interner.release(a));
+        assert_eq!(interner.get("o1"), None);
+        assert_eq!(interner.resolve(a), None);
+        assert!(!interner.release(a));
+        assert_eq!(interner.intern_new("o2"), None);
+
+        // The slot is reused under a new generation
+        let c = interner.intern("o3");
+        assert_ne!(c, a);
+        assert_eq!(c.index(), a.index());
+        assert_eq!(c.generation(), a.generation() + 1);
+        assert_eq!(interner.resolve(a), None);
+        assert!(!interner.release(a));
+        assert_eq!(interner.resolve(c), Some("o3"));
+        assert_eq!(interner.capacity(), 2);
+
+        interner.clear();
+        let d = interner.intern("o4");
+        assert_eq!(d.index(), c.index());
+        assert_eq!(interner.resolve(c), None);
+    }
+}
@@ -8,8 +8,9 @@
 //! - **Order-level tracking**: Each order is tracked with its unique ID
 //! - **FIFO queue semantics**: Orders at the same price are queued in FIFO order
 //! - **Queue position**: Calculate your position in the queue and quantity ahead
-//! - **O(1) order lookup**: HashMap index for fast order operations
+//! - **O(1) order operations**: Interned order IDs and intrusive per-level queues
 //! - **L2 compatibility**: Can generate aggregated L2 levels for checksum validation
+//! - **Order-flow analytics**: Iceberg detection and depletion-adjusted fill estimates
 //!
 //! # Use Cases
 //!
@@ -54,9 +55,15 @@
 //! | 100   | 25           | Day trading |
 //! | 1000  | 100          | Market making, full depth |
 
+pub mod analytics;
 pub mod book;
+pub mod intern;
 pub mod order;
 
 // Re-export main types
+pub use analytics::{
+    DynamicQueuePosition, IcebergCandidate, IcebergConfig, DEFAULT_ACTIVITY_RETENTION_US,
+};
 pub use book::{L3Book, L3BookSnapshot, L3ChecksumMismatch};
+pub use intern::{OrderHandle, OrderInterner};
 pub use order::{L3Order, L3PriceLevel, L3Side, OrderLocation, QueuePosition};
@@ -53,65 +53,172 @@ impl L3Order {
     }
 }
 
+/// Sentinel for "no slot" in the intrusive list
+const NIL: u32 = u32::MAX;
+
+/// Arena slot holding one order and its queue links
+#[derive(Debug, Clone)]
+struct Slot {
+    order: Option<L3Order>,
+    prev: u32,
+    next: u32,
+}
+
 /// Price level containing multiple orders (FIFO queue)
 ///
 /// Orders at the same price are maintained in FIFO order (oldest first).
 /// This is critical for queue position calculation.
-#[derive(Debug, Clone, Default)]
+///
+/// Orders live in a per-level slab and are threaded into an intrusive doubly
+/// linked list, so an order whose slot is known (as [`L3Book`](crate::l3::L3Book)
+/// tracks) can be removed or modified in O(1) without shifting the rest of the
+/// queue. The ID-based methods remain for standalone use and scan the queue.
+#[derive(Debug, Clone)]
 pub struct L3PriceLevel {
     /// Price for this level
     pub price: Decimal,
-    /// Orders at this price level (FIFO order - oldest first)
-    orders: Vec<L3Order>,
+    /// Order slab; freed slots are recycled via `free`
+    slots: Vec<Slot>,
+    free: Vec<u32>,
+    /// Oldest order (front of queue)
+    head: u32,
+    /// Newest order (back of queue)
+    tail: u32,
+    len: usize,
     /// Cached total quantity (sum of all order quantities)
     total_qty: Decimal,
 }
 
+impl Default for L3PriceLevel {
+    fn default() -> Self {
+        Self::new(Decimal::ZERO)
+    }
+}
+
 impl L3PriceLevel {
     /// Create a new empty price level
     pub fn new(price: Decimal) -> Self {
         Self {
             price,
-            orders: Vec::new(),
+            slots: Vec::new(),
+            free: Vec::new(),
+            head: NIL,
+            tail: NIL,
+            len: 0,
             total_qty: Decimal::ZERO,
         }
     }
 
     /// Add an order to this level (appended at the end - newest)
     pub fn add_order(&mut self, order: L3Order) {
+        self.push_back(order);
+    }
+
+    /// Append an order and return its slot
+    pub(crate) fn push_back(&mut self, order: L3Order) -> u32 {
         self.total_qty += order.qty;
-        self.orders.push(order);
+        let slot = Slot {
+            order: Some(order),
+            prev: self.tail,
+            next: NIL,
+        };
+        let idx = match self.free.pop() {
+            Some(idx) => {
+                self.slots[idx as usize] = slot;
+                idx
+            }
+            None => {
+                self.slots.push(slot);
+                (self.slots.len() - 1) as u32
+            }
+        };
+
+        if self.tail == NIL {
+            self.head = idx;
+        } else {
+            self.slots[self.tail as usize].next = idx;
+        }
+        self.tail = idx;
+        self.len += 1;
+        idx
+    }
+
+    /// Find the slot holding an order ID (linear scan)
+    fn find_slot(&self, order_id: &str) -> Option<u32> {
+        let mut cursor = self.head;
+        while cursor != NIL {
+            let slot = &self.slots[cursor as usize];
+            if slot.order.as_ref().is_some_and(|o| o.order_id == order_id) {
+                return Some(cursor);
+            }
+            cursor = slot.next;
+        }
+        None
     }
 
     /// Remove an order by ID
     ///
     /// Returns the removed order if found
     pub fn remove_order(&mut self, order_id: &str) -> Option<L3Order> {
-        if let Some(idx) = self.orders.iter().position(|o| o.order_id == order_id) {
-            let order = self.orders.remove(idx);
-            self.total_qty -= order.qty;
-            Some(order)
+        let slot = self.find_slot(order_id)?;
+        self.remove_slot(slot)
+    }
+
+    /// Unlink and free a slot in O(1)
+    pub(crate) fn remove_slot(&mut self, idx: u32) -> Option<L3Order> {
+        let slot = self.slots.get_mut(idx as usize)?;
+        let order = slot.order.take()?;
+        let (prev, next) = (slot.prev, slot.next);
+
+        if prev == NIL {
+            self.head = next;
         } else {
-            None
+            self.slots[prev as usize].next = next;
+        }
+        if next == NIL {
+            self.tail = prev;
+        } else {
+            self.slots[next as usize].prev = prev;
         }
+
+        self.free.push(idx);
+        self.len -= 1;
+        self.total_qty -= order.qty;
+        Some(order)
     }
 
     /// Modify an order's quantity
     ///
     /// Returns true if the order was found and modified
     pub fn modify_order(&mut self, order_id: &str, new_qty: Decimal) -> bool {
-        if let Some(order) = self.orders.iter_mut().find(|o| o.order_id == order_id) {
-            self.total_qty = self.total_qty - order.qty + new_qty;
-            order.qty = new_qty;
-            true
-        } else {
-            false
+        match self.find_slot(order_id) {
+            Some(slot) => self.modify_slot(slot, new_qty).is_some(),
+            None => false,
         }
     }
 
+    /// Modify the quantity of the order in a slot, returning the old quantity
+    pub(crate) fn modify_slot(&mut self, idx: u32, new_qty: Decimal) -> Option<Decimal> {
+        let order = self.slots.get_mut(idx as usize)?.order.as_mut()?;
+        let old_qty = order.qty;
+        order.qty = new_qty;
+        self.total_qty = self.total_qty - old_qty + new_qty;
+        Some(old_qty)
+    }
+
     /// Get an order by ID
     pub fn get_order(&self, order_id: &str) -> Option<&L3Order> {
-        self.orders.iter().find(|o| o.order_id == order_id)
+        self.get_slot(self.find_slot(order_id)?)
+    }
+
+    /// Get the order in a slot
+    pub(crate) fn get_slot(&self, idx: u32) -> Option<&L3Order> {
+        self.slots.get(idx as usize)?.order.as_ref()
+    }
+
+    /// Check if a slot is at the front of the queue
+    pub(crate) fn is_front(&self, idx: u32) -> bool {
+        self.head == idx
     }
 
     /// Get queue position for an order
@@ -119,21 +226,35 @@ impl L3PriceLevel {
     /// Returns the position in the queue (0-indexed) and the total quantity
     /// ahead of this order. Returns None if order not found.
     pub fn queue_position(&self, order_id: &str) -> Option<QueuePosition> {
-        let mut qty_ahead = Decimal::ZERO;
+        self.queue_position_slot(self.find_slot(order_id)?)
+    }
+
+    /// Queue position of the order in a slot
+    ///
+    /// Walks from the slot towards the front, so cost is proportional to the
+    /// number of orders ahead rather than the size of the level.
+    pub(crate) fn queue_position_slot(&self, idx: u32) -> Option<QueuePosition> {
+        self.get_slot(idx)?;
 
-        for (idx, order) in self.orders.iter().enumerate() {
-            if order.order_id == order_id {
-                return Some(QueuePosition {
-                    position: idx,
-                    orders_ahead: idx,
-                    qty_ahead,
-                    total_orders: self.orders.len(),
-                    total_qty: self.total_qty,
-                });
+        let mut position = 0;
+        let mut qty_ahead = Decimal::ZERO;
+        let mut cursor = self.slots[idx as usize].prev;
+        while cursor != NIL {
+            let slot = &self.slots[cursor as usize];
+            if let Some(order) = &slot.order {
+                qty_ahead += order.qty;
             }
-            qty_ahead += order.qty;
+            position += 1;
+            cursor = slot.prev;
         }
-        None
+
+        Some(QueuePosition {
+            position,
+            orders_ahead: position,
+            qty_ahead,
+            total_orders: self.len,
+            total_qty: self.total_qty,
+        })
     }
 
     /// Get total quantity at this price level
@@ -143,41 +264,63 @@ impl L3PriceLevel {
 
     /// Get number of orders at this level
     pub fn order_count(&self) -> usize {
-        self.orders.len()
+        self.len
     }
 
     /// Check if level is empty
     pub fn is_empty(&self) -> bool {
-        self.orders.is_empty()
+        self.len == 0
     }
 
     /// Iterate over orders (oldest first)
     pub fn orders(&self) -> impl Iterator<Item = &L3Order> {
-        self.orders.iter()
+        LevelOrders {
+            slots: &self.slots,
+            cursor: self.head,
+        }
     }
 
     /// Get oldest order (front of queue)
     pub fn oldest(&self) -> Option<&L3Order> {
-        self.orders.first()
+        self.get_slot(self.head)
     }
 
     /// Get newest order (back of queue)
     pub fn newest(&self) -> Option<&L3Order> {
-        self.orders.last()
+        self.get_slot(self.tail)
     }
 
     /// Get the average order size at this level
     pub fn avg_order_size(&self) -> Option<Decimal> {
-        if self.orders.is_empty() {
+        if self.len == 0 {
             None
         } else {
-            Some(self.total_qty / Decimal::from(self.orders.len()))
+            Some(self.total_qty / Decimal::from(self.len))
         }
     }
 
     /// Recalculate the total quantity (for validation)
     pub fn recalculate_total(&mut self) {
-        self.total_qty = self.orders.iter().map(|o| o.qty).sum();
+        self.total_qty = self.orders().map(|o| o.qty).sum();
+    }
+}
+
+/// Iterator over a level's queue, front to back
+struct LevelOrders<'a> {
+    slots: &'a [Slot],
+    cursor: u32,
+}
+
+impl<'a> Iterator for LevelOrders<'a> {
+    type Item = &'a L3Order;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.cursor == NIL {
+            return None;
+        }
+        let slot = &self.slots[self.cursor as usize];
+        self.cursor = slot.next;
+        slot.order.as_ref()
     }
 }
 
@@ -323,6 +466,36 @@ mod tests {
         assert_eq!(level.newest().unwrap().order_id, "third");
     }
 
+    #[test]
+    fn test_slots_are_recycled_in_fifo_order() {
+        let mut level = L3PriceLevel::new(dec!(100));
+
+        level.add_order(L3Order::new("o1", dec!(100), dec!(1)));
+        level.add_order(L3Order::new("o2", dec!(100), dec!(2)));
+        level.add_order(L3Order::new("o3", dec!(100), dec!(3)));
+
+        // Remove from the middle, then add: reused slot must go to the back
+        assert!(level.remove_order("o2").is_some());
+        level.add_order(L3Order::new("o4", dec!(100), dec!(4)));
+
+        let ids: Vec<_> = level.orders().map(|o| o.order_id.as_str()).collect();
+        assert_eq!(ids, vec!["o1", "o3", "o4"]);
+        assert_eq!(level.total_qty(), dec!(8));
+        assert_eq!(level.newest().unwrap().order_id, "o4");
+
+        let pos = level.queue_position("o4").unwrap();
+        assert_eq!(pos.position, 2);
+        assert_eq!(pos.qty_ahead, dec!(4));
+
+        // Drain completely
+        level.remove_order("o1");
+        level.remove_order("o3");
+        level.remove_order("o4");
+        assert!(level.is_empty());
+        assert!(level.oldest().is_none());
+        assert!(level.newest().is_none());
+    }
+
     #[test]
     fn test_queue_position_fill_probability() {
         let mut level = L3PriceLevel::new(dec!(100));
@@ -0,0 +1,318 @@
+//! Per-level change tracking for L2 books
+//!
+//! With metadata enabled ([`Orderbook::set_level_metadata`]), the book
+//! records for every resting level how often its quantity changed, when it
+//! appeared and when it last changed. That is enough for spoof detection
+//! (large levels that flicker) and level-persistence analytics without an
+//! L3 feed.
+//!
+//! Times are exchange timestamps from the book messages, in microseconds
+//! since the Unix epoch, so tracking works the same in WASM and when
+//! replaying recorded data. Messages without a timestamp (Kraken snapshots)
+//! reuse the latest one seen.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_book::{compute_checksum, Orderbook};
+//! use kraken_types::{BookData, Level};
+//! use rust_decimal_macros::dec;
+//!
+//! let mut book = Orderbook::with_depth("BTC/USD", 10);
+//! book.set_level_metadata(true);
+//!
+//! let update = |qty, timestamp: &str| {
+//!     let bids = vec![Level::new(dec!(100), qty)];
+//!     let checksum = compute_checksum(&bids, &[]);
+//!     BookData { symbol: "BTC/USD".into(), bids, asks: vec![], checksum, timestamp: Some(timestamp.into()) }
+//! };
+//! book.apply_book_data(&update(dec!(1), "2024-01-01T00:00:00.000000Z"), true).unwrap();
+//! book.apply_book_data(&update(dec!(2), "2024-01-01T00:00:01.500000Z"), false).unwrap();
+//!
+//! let meta = book.level_meta(dec!(100)).unwrap();
+//! assert_eq!(meta.update_count, 1);
+//! assert_eq!(meta.lifetime_us(meta.last_changed_us.unwrap()), Some(1_500_000));
+//! ```
+
+use crate::orderbook::OrderbookSnapshot;
+use crate::storage::TreeBook;
+use kraken_types::{Level, Price};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Approximate bytes per tracked level (key, metadata and hash map overhead)
+pub(crate) const APPROX_META_BYTES: usize =
+    (std::mem::size_of::<Price>() + std::mem::size_of::<LevelMeta>()) * 2;
+
+/// Change history of one resting price level
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelMeta {
+    /// Quantity changes since the level appeared
+    pub update_count: u32,
+    /// When the level appeared (µs since epoch), if known
+    pub first_seen_us: Option<i64>,
+    /// When the quantity last changed (µs since epoch), if known
+    pub last_changed_us: Option<i64>,
+}
+
+impl LevelMeta {
+    fn appeared(at_us: Option<i64>) -> Self {
+        Self {
+            update_count: 0,
+            first_seen_us: at_us,
+            last_changed_us: at_us,
+        }
+    }
+
+    /// How long the current quantity has been resting as of `now_us`
+    pub fn qty_age_us(&self, now_us: i64) -> Option<i64> {
+        self.last_changed_us.map(|at| now_us - at)
+    }
+
+    /// How long the level has existed as of `now_us`
+    pub fn lifetime_us(&self, now_us: i64) -> Option<i64> {
+        self.first_seen_us.map(|at| now_us - at)
+    }
+}
+
+/// Orderbook snapshot with the metadata of every level
+///
+/// `bid_meta[i]` describes `snapshot.bids[i]`, and likewise for asks.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExtendedSnapshot {
+    /// Plain snapshot
+    pub snapshot: OrderbookSnapshot,
+    /// Metadata for each bid level, best first
+    pub bid_meta: Vec<LevelMeta>,
+    /// Metadata for each ask level, best first
+    pub ask_meta: Vec<LevelMeta>,
+    /// Exchange time of the latest applied message (µs since epoch), if known
+    pub as_of_us: Option<i64>,
+}
+
+/// Per-level metadata for both sides of a book
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LevelMetaTracker {
+    bids: HashMap<Price, LevelMeta>,
+    asks: HashMap<Price, LevelMeta>,
+    as_of_us: Option<i64>,
+}
+
+impl LevelMetaTracker {
+    /// Move the clock to a message's timestamp (never backwards)
+    pub(crate) fn advance(&mut self, timestamp: Option<&str>) {
+        if let Some(at) = timestamp.and_then(parse_timestamp_us) {
+            self.as_of_us = Some(self.as_of_us.map_or(at, |now| now.max(at)));
+        }
+    }
+
+    /// Latest exchange time seen
+    pub(crate) fn as_of_us(&self) -> Option<i64> {
+        self.as_of_us
+    }
+
+    /// Start over from the levels currently in the book
+    pub(crate) fn seed(&mut self, book: &TreeBook) {
+        let now = self.as_of_us;
+        self.bids = book.bids().map(|l| (l.price, LevelMeta::appeared(now))).collect();
+        self.asks = book.asks().map(|l| (l.price, LevelMeta::appeared(now))).collect();
+    }
+
+    /// Record a delta for a bid level
+    pub(crate) fn touch_bid(&mut self, level: &Level) {
+        Self::touch(&mut self.bids, level, self.as_of_us);
+    }
+
+    /// Record a delta for an ask level
+    pub(crate) fn touch_ask(&mut self, level: &Level) {
+        Self::touch(&mut self.asks, level, self.as_of_us);
+    }
+
+    fn touch(side: &mut HashMap<Price, LevelMeta>, level: &Level, now: Option<i64>) {
+        if level.qty.is_zero() {
+            side.remove(&level.price);
+            return;
+        }
+        side.entry(level.price)
+            .and_modify(|meta| {
+                meta.update_count = meta.update_count.saturating_add(1);
+                meta.last_changed_us = now;
+            })
+            .or_insert_with(|| LevelMeta::appeared(now));
+    }
+
+    /// Drop metadata for levels no longer in the book (after truncation)
+    pub(crate) fn retain_in(&mut self, book: &TreeBook) {
+        if self.bids.len() > book.bid_count() {
+            self.bids.retain(|price, _| book.get_bid(price).is_some());
+        }
+        if self.asks.len() > book.ask_count() {
+            self.asks.retain(|price, _| book.get_ask(price).is_some());
+        }
+    }
+
+    /// Metadata for a price on either side
+    pub(crate) fn get(&self, price: &Price) -> Option<LevelMeta> {
+        self.bids.get(price).or_else(|| self.asks.get(price)).copied()
+    }
+
+    /// Metadata for a bid level (default if untracked)
+    pub(crate) fn bid(&self, price: &Price) -> LevelMeta {
+        self.bids.get(price).copied().unwrap_or_default()
+    }
+
+    /// Metadata for an ask level (default if untracked)
+    pub(crate) fn ask(&self, price: &Price) -> LevelMeta {
+        self.asks.get(price).copied().unwrap_or_default()
+    }
+
+    /// Approximate bytes held
+    pub(crate) fn approx_bytes(&self) -> usize {
+        (self.bids.len() + self.asks.len()) * APPROX_META_BYTES
+    }
+}
+
+/// Parse an RFC 3339 UTC timestamp (`2024-01-01T00:00:00.123456Z`) into
+/// microseconds since the Unix epoch
+///
+/// Accepts `Z` or a `±HH:MM` offset and up to nine fractional digits
+/// (truncated to microseconds).
+pub fn parse_timestamp_us(timestamp: &str) -> Option<i64> {
+    let bytes = timestamp.as_bytes();
+    if bytes.len() < 20 || bytes[4] != b'-' || bytes[7] != b'-' || !matches!(bytes[10], b'T' | b't' | b' ') {
+        return None;
+    }
+    let num = |range: std::ops::Range<usize>| -> Option<i64> { timestamp.get(range)?.parse().ok() };
+    let (year, month, day) = (num(0..4)?, num(5..7)?, num(8..10)?);
+    let (hour, minute, second) = (num(11..13)?, num(14..16)?, num(17..19)?);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+
+    let mut rest = &timestamp[19..];
+    let mut micros = 0i64;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        for (i, digit) in fraction[..digits].bytes().enumerate().take(6) {
+            micros += i64::from(digit - b'0') * 10i64.pow(5 - i as u32);
+        }
+        rest = &fraction[digits..];
+    }
+
+    let offset_secs = match rest {
+        "Z" | "z" => 0,
+        _ if rest.len() == 6 && rest.as_bytes()[3] == b':' => {
+            let sign = match rest.as_bytes()[0] {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let (h, m): (i64, i64) = (rest[1..3].parse().ok()?, rest[4..6].parse().ok()?);
+            sign * (h * 3600 + m * 60)
+        }
+        _ => return None,
+    };
+
+    let secs = days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second - offset_secs;
+    Some(secs * 1_000_000 + micros)
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month_index = (month + 9) % 12;
+    let day_of_year = (153 * month_index + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum::compute_checksum;
+    use crate::orderbook::Orderbook;
+    use kraken_types::BookData;
+    use rust_decimal_macros::dec;
+
+    /// Apply a message carrying the checksum the resulting book should have
+    fn apply(book: &mut Orderbook, mirror: &mut TreeBook, bids: Vec<Level>, asks: Vec<Level>, timestamp: &str) {
+        let is_snapshot = !book.is_synced();
+        if is_snapshot {
+            mirror.clear();
+        }
+        for level in &bids {
+            mirror.insert_bid(level.price, level.qty);
+        }
+        for level in &asks {
+            mirror.insert_ask(level.price, level.qty);
+        }
+        let data = BookData {
+            symbol: "BTC/USD".to_string(),
+            bids,
+            asks,
+            checksum: compute_checksum(&mirror.bids_vec(), &mirror.asks_vec()),
+            timestamp: Some(timestamp.to_string()),
+        };
+        book.apply_book_data(&data, is_snapshot).unwrap();
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp_us("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(
+            parse_timestamp_us("2023-10-06T17:35:55.440295Z"),
+            Some(1_696_613_755_440_295)
+        );
+        assert_eq!(
+            parse_timestamp_us("2023-10-06T19:35:55.440295123+02:00"),
+            Some(1_696_613_755_440_295)
+        );
+        assert_eq!(parse_timestamp_us("2023-10-06"), None);
+        assert_eq!(parse_timestamp_us("2023-13-06T17:35:55Z"), None);
+    }
+
+    #[test]
+    fn test_metadata_follows_level_changes() {
+        let mut book = Orderbook::with_depth("BTC/USD", 10);
+        let mut mirror = TreeBook::new();
+        book.set_level_metadata(true);
+        apply(
+            &mut book,
+            &mut mirror,
+            vec![Level::new(dec!(100), dec!(1)), Level::new(dec!(99), dec!(1))],
+            vec![Level::new(dec!(101), dec!(1)), Level::new(dec!(102), dec!(1))],
+            "2024-01-01T00:00:00Z",
+        );
+        apply(&mut book, &mut mirror, vec![Level::new(dec!(100), dec!(2))], vec![], "2024-01-01T00:00:01Z");
+        apply(&mut book, &mut mirror, vec![Level::new(dec!(100), dec!(3))], vec![], "2024-01-01T00:00:02Z");
+        apply(&mut book, &mut mirror, vec![], vec![Level::new(dec!(101), dec!(0))], "2024-01-01T00:00:03Z");
+
+        let start = parse_timestamp_us("2024-01-01T00:00:00Z").unwrap();
+        let busy = book.level_meta(dec!(100)).unwrap();
+        assert_eq!(busy.update_count, 2);
+        assert_eq!(busy.qty_age_us(start + 3_000_000), Some(1_000_000));
+        assert_eq!(busy.lifetime_us(start + 3_000_000), Some(3_000_000));
+        let quiet = book.level_meta(dec!(99)).unwrap();
+        assert_eq!(quiet.update_count, 0);
+        assert!(book.level_meta(dec!(101)).is_none());
+
+        let extended = book.snapshot_extended().unwrap();
+        assert_eq!(extended.bid_meta, vec![busy, quiet]);
+        assert_eq!(extended.ask_meta.len(), 1);
+        assert_eq!(extended.as_of_us, Some(start + 3_000_000));
+    }
+
+    #[test]
+    fn test_metadata_is_off_by_default() {
+        let mut book = Orderbook::with_depth("BTC/USD", 10);
+        let mut mirror = TreeBook::new();
+        apply(&mut book, &mut mirror, vec![Level::new(dec!(100), dec!(1))], vec![], "2024-01-01T00:00:00Z");
+        assert!(book.level_meta(dec!(100)).is_none());
+        assert!(book.snapshot_extended().is_none());
+    }
+}
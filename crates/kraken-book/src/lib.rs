@@ -38,19 +38,34 @@
 //! }
 //! ```
 
+pub mod audit;
 pub mod checksum;
+pub mod diff;
+pub mod export;
+pub mod heatmap;
 pub mod history;
 pub mod l3;
+pub mod level_meta;
+pub mod memory;
 pub mod orderbook;
 pub mod storage;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod testing;
 
 // Re-export main types
+pub use audit::AuditViolation;
 pub use checksum::{
-    compute_checksum, compute_checksum_with_precision, ChecksumResult,
-    DEFAULT_PRICE_PRECISION, DEFAULT_QTY_PRECISION,
+    compute_checksum, compute_checksum_iter, compute_checksum_with_precision, infer_precision, ChecksumCache,
+    ChecksumResult, CHECKSUM_DEPTH, DEFAULT_PRICE_PRECISION, DEFAULT_QTY_PRECISION,
 };
+pub use diff::{SideDiff, SnapshotDiff};
+pub use heatmap::{Heatmap, HeatmapBuilder, HeatmapError, Normalization};
 pub use history::{HistoryBuffer, TimestampedSnapshot};
-pub use orderbook::{ApplyResult, ChecksumMismatch, Orderbook, OrderbookSnapshot, OrderbookState};
+pub use level_meta::{ExtendedSnapshot, LevelMeta};
+pub use memory::{Eviction, MemoryLimits};
+pub use orderbook::{
+    ApplyError, ApplyResult, ChecksumMismatch, Orderbook, OrderbookSnapshot, OrderbookState, PrecisionSource,
+};
 pub use storage::TreeBook;
 
 // Re-export L3 types at crate root for convenience
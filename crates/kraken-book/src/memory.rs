@@ -0,0 +1,240 @@
+//! Approximate memory accounting and level caps
+//!
+//! A D1000 book holds up to 2000 levels; across hundreds of symbols, plus
+//! history, that adds up. Sizes here are estimates (level payload plus
+//! B-tree overhead), good enough for budgeting but not exact.
+//!
+//! [`MemoryLimits`] sets a per-book and a total budget. When a book exceeds
+//! its budget, [`enforce_book_limit`] drops history first and then picks a
+//! smaller subscription depth for the book.
+//! [`MemoryLimits::plan_total`] picks one level cap that brings a set of
+//! books under the total budget, trimming the deepest books first.
+//!
+//! Levels are not dropped from a live book in place. Kraken only reports a
+//! level entering the subscribed depth once, so a book holding fewer levels
+//! than it subscribed to loses the levels that move up and fails its next
+//! checksum. Shrinking a book means resubscribing at a smaller [`Depth`]
+//! ([`depth_within`] picks it); the new snapshot brings the book down.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_book::memory::{enforce_book_limit, MemoryLimits, APPROX_LEVEL_BYTES};
+//! use kraken_book::Orderbook;
+//! use kraken_types::{Depth, Level};
+//! use rust_decimal::Decimal;
+//!
+//! let mut book = Orderbook::with_depth("BTC/USD", 1000);
+//! let bids: Vec<Level> = (0..1000).map(|i| Level::new(Decimal::from(50_000 - i), Decimal::ONE)).collect();
+//! let asks: Vec<Level> = (0..1000).map(|i| Level::new(Decimal::from(50_001 + i), Decimal::ONE)).collect();
+//! book.restore_snapshot(&kraken_book::OrderbookSnapshot { bids, asks, ..Default::default() });
+//!
+//! let limits = MemoryLimits::new().with_per_book_bytes(200 * APPROX_LEVEL_BYTES);
+//! let eviction = enforce_book_limit(&mut book, None, &limits).unwrap();
+//! assert_eq!(eviction.depth, Some(Depth::D25));
+//! // Untouched until resubscribed at that depth
+//! assert_eq!(book.bid_count(), 1000);
+//! ```
+
+use crate::checksum::CHECKSUM_DEPTH;
+use crate::history::HistoryBuffer;
+use crate::orderbook::Orderbook;
+use kraken_types::{Depth, Level};
+use rust_decimal::Decimal;
+
+/// Approximate bytes per stored price level (key, level and B-tree overhead)
+pub const APPROX_LEVEL_BYTES: usize =
+    (std::mem::size_of::<Decimal>() + std::mem::size_of::<Level>()) * 3 / 2;
+
+/// Approximate fixed bytes per book (struct, symbol and checksum cache)
+pub const BOOK_OVERHEAD_BYTES: usize = 512;
+
+/// Smallest level cap per side, so the checksum window stays intact
+pub const MIN_LEVEL_CAP: usize = CHECKSUM_DEPTH;
+
+/// Deepest Kraken book depth holding at most `levels` per side
+///
+/// Floors at [`Depth::D10`], the smallest depth Kraken offers.
+pub fn depth_within(levels: usize) -> Depth {
+    [Depth::D1000, Depth::D500, Depth::D100, Depth::D25]
+        .into_iter()
+        .find(|depth| depth.as_u32() as usize <= levels)
+        .unwrap_or(Depth::D10)
+}
+
+/// Memory budgets for a set of books
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryLimits {
+    /// Budget for one book including its history (None = unlimited)
+    pub per_book_bytes: Option<usize>,
+    /// Budget for all books together (None = unlimited)
+    pub total_bytes: Option<usize>,
+}
+
+impl MemoryLimits {
+    /// Create limits without any budget
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the budget for one book including its history
+    pub fn with_per_book_bytes(mut self, bytes: usize) -> Self {
+        self.per_book_bytes = Some(bytes);
+        self
+    }
+
+    /// Set the budget for all books together
+    pub fn with_total_bytes(mut self, bytes: usize) -> Self {
+        self.total_bytes = Some(bytes);
+        self
+    }
+
+    /// Level cap per side that fits a book into `bytes`
+    pub fn level_cap_for(bytes: usize) -> usize {
+        let per_side = bytes.saturating_sub(BOOK_OVERHEAD_BYTES) / (2 * APPROX_LEVEL_BYTES);
+        per_side.max(MIN_LEVEL_CAP)
+    }
+
+    /// Level cap per side that brings books of these sizes under the total budget
+    ///
+    /// `levels` holds the (bids, asks) counts of each book. Returns None if
+    /// the books already fit. Otherwise returns the largest cap that fits,
+    /// or [`MIN_LEVEL_CAP`] if even that does not.
+    pub fn plan_total(&self, levels: &[(usize, usize)]) -> Option<usize> {
+        let budget = self.total_bytes?;
+        let bytes_at = |cap: usize| -> usize {
+            levels
+                .iter()
+                .map(|(bids, asks)| {
+                    BOOK_OVERHEAD_BYTES + ((*bids).min(cap) + (*asks).min(cap)) * APPROX_LEVEL_BYTES
+                })
+                .sum()
+        };
+        let deepest = levels.iter().map(|(b, a)| (*b).max(*a)).max().unwrap_or(0);
+        if bytes_at(deepest) <= budget {
+            return None;
+        }
+
+        // Largest cap in [MIN_LEVEL_CAP, deepest) that fits
+        let (mut lo, mut hi) = (MIN_LEVEL_CAP, deepest);
+        if bytes_at(lo) > budget {
+            return Some(MIN_LEVEL_CAP);
+        }
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if bytes_at(mid) <= budget {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        Some(lo)
+    }
+}
+
+/// What [`enforce_book_limit`] removed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Eviction {
+    /// History snapshots dropped
+    pub history_dropped: usize,
+    /// Smaller depth to resubscribe the book at, if dropping history was
+    /// not enough
+    pub depth: Option<Depth>,
+    /// Approximate bytes afterwards, before any resubscription
+    pub bytes_after: usize,
+}
+
+/// Bring one book (and its history) under the per-book budget
+///
+/// History goes first, oldest snapshots first. If the book alone is still
+/// over budget, the eviction names a smaller depth to resubscribe it at; the
+/// book's levels are left alone (see the [module docs](self)). Returns None
+/// if the book already fit.
+pub fn enforce_book_limit(
+    book: &mut Orderbook,
+    history: Option<&mut HistoryBuffer>,
+    limits: &MemoryLimits,
+) -> Option<Eviction> {
+    let budget = limits.per_book_bytes?;
+    let history_bytes = history.as_ref().map_or(0, |h| h.approx_bytes());
+    if book.approx_bytes() + history_bytes <= budget {
+        return None;
+    }
+
+    let mut eviction = Eviction::default();
+    let mut history_after = history_bytes;
+    if let Some(history) = history {
+        let room = budget.saturating_sub(book.approx_bytes());
+        eviction.history_dropped = history.shrink_to_bytes(room);
+        history_after = history.approx_bytes();
+    }
+
+    if book.approx_bytes() > budget {
+        let depth = depth_within(MemoryLimits::level_cap_for(budget));
+        eviction.depth = Some(depth).filter(|depth| depth.as_u32() < book.depth());
+    }
+    eviction.bytes_after = book.approx_bytes() + history_after;
+    Some(eviction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::OrderbookSnapshot;
+
+    fn deep_book(symbol: &str, levels: i64) -> Orderbook {
+        let mut book = Orderbook::with_depth(symbol, 1000);
+        let bids = (0..levels).map(|i| Level::new(Decimal::from(10_000 - i), Decimal::ONE)).collect();
+        let asks = (0..levels).map(|i| Level::new(Decimal::from(10_001 + i), Decimal::ONE)).collect();
+        book.restore_snapshot(&OrderbookSnapshot {
+            bids,
+            asks,
+            ..Default::default()
+        });
+        book
+    }
+
+    #[test]
+    fn test_history_is_dropped_before_levels() {
+        let mut book = deep_book("BTC/USD", 50);
+        let mut history = HistoryBuffer::new(100);
+        for _ in 0..20 {
+            history.push(book.snapshot());
+        }
+        let budget = book.approx_bytes() + 5 * history.latest().map_or(0, |s| HistoryBuffer::snapshot_bytes(&s.snapshot));
+        let limits = MemoryLimits::new().with_per_book_bytes(budget);
+
+        let eviction = enforce_book_limit(&mut book, Some(&mut history), &limits).unwrap();
+        assert_eq!(eviction.history_dropped, 15);
+        assert_eq!(eviction.depth, None);
+        assert_eq!(book.bid_count(), 50);
+        assert!(enforce_book_limit(&mut book, Some(&mut history), &limits).is_none());
+    }
+
+    #[test]
+    fn test_over_budget_book_gets_a_smaller_depth() {
+        let mut book = deep_book("BTC/USD", 500);
+        let limits = MemoryLimits::new().with_per_book_bytes(300 * APPROX_LEVEL_BYTES);
+        let eviction = enforce_book_limit(&mut book, None, &limits).unwrap();
+        assert_eq!(eviction.depth, Some(Depth::D100));
+        assert_eq!(book.bid_count(), 500);
+
+        // Already at the smallest depth: nothing left to shrink
+        let mut book = Orderbook::with_depth("BTC/USD", 10);
+        let limits = MemoryLimits::new().with_per_book_bytes(1);
+        assert_eq!(enforce_book_limit(&mut book, None, &limits).unwrap().depth, None);
+        assert_eq!(depth_within(24), Depth::D10);
+        assert_eq!(depth_within(5000), Depth::D1000);
+    }
+
+    #[test]
+    fn test_total_plan_trims_deepest_books_first() {
+        let limits = MemoryLimits::new()
+            .with_total_bytes(3 * BOOK_OVERHEAD_BYTES + (2 * 100 + 2 * 40 + 2 * 20) * APPROX_LEVEL_BYTES);
+        assert_eq!(limits.plan_total(&[(100, 100), (40, 40), (20, 20)]), None);
+        // The 500-level book must give up 400 per side; the others are untouched
+        assert_eq!(limits.plan_total(&[(500, 500), (40, 40), (20, 20)]), Some(100));
+        // Nothing fits: floor at the checksum window
+        assert_eq!(MemoryLimits::new().with_total_bytes(1).plan_total(&[(50, 50)]), Some(MIN_LEVEL_CAP));
+    }
+}
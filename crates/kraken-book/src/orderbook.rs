@@ -7,17 +7,34 @@
 //! ```text
 //! Uninitialized → AwaitingSnapshot → Synced ↔ Desynchronized
 //! ```
+//!
+//! A book seeded with [`Orderbook::restore_snapshot`] waits in
+//! `AwaitingSnapshot` like a fresh subscription. When subscribing without a
+//! snapshot, [`Orderbook::trust_restored_state`] moves it straight to
+//! `Synced`; the first delta's checksum then confirms the restored levels
+//! or desynchronizes the book.
+//!
+//! # Precision
+//!
+//! Checksums depend on the pair's price and quantity decimals, which arrive
+//! on the instrument channel ([`Orderbook::set_precision`]). If a snapshot
+//! fails its checksum before then, the book retries with precision inferred
+//! from the snapshot's levels and keeps it if the checksum matches. Instrument
+//! data arriving later replaces the inferred values, and resyncs a book
+//! whose last message only failed because of the wrong precision.
 
 use crate::{
-    checksum::{compute_checksum_with_precision, DEFAULT_PRICE_PRECISION, DEFAULT_QTY_PRECISION},
+    checksum::{infer_precision, ChecksumCache, DEFAULT_PRICE_PRECISION, DEFAULT_QTY_PRECISION},
+    level_meta::{ExtendedSnapshot, LevelMeta, LevelMetaTracker},
+    memory::{APPROX_LEVEL_BYTES, BOOK_OVERHEAD_BYTES, MIN_LEVEL_CAP},
     storage::TreeBook,
 };
-use kraken_types::{BookData, Level};
+use kraken_types::{BookData, Level, Price};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 /// Orderbook synchronization state
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum OrderbookState {
     /// No subscription, no data
     #[default]
@@ -30,9 +47,21 @@ pub enum OrderbookState {
     Desynchronized,
 }
 
+/// Where a book's checksum precision came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PrecisionSource {
+    /// Built-in defaults (1 price and 8 quantity decimals)
+    #[default]
+    Default,
+    /// Inferred from a snapshot whose checksum failed with the defaults
+    Inferred,
+    /// Set with [`Orderbook::set_precision`], e.g. from instrument data
+    Configured,
+}
+
 
 /// Checksum mismatch error
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct ChecksumMismatch {
     /// Symbol that had the mismatch
     pub symbol: String,
@@ -54,6 +83,96 @@ impl std::fmt::Display for ChecksumMismatch {
 
 impl std::error::Error for ChecksumMismatch {}
 
+/// Why a book message was not applied cleanly
+///
+/// Some variants are reported after the message has been applied; see
+/// [`was_applied`](Self::was_applied). The book's [`OrderbookState`] after
+/// each variant is documented on the variant.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ApplyError {
+    /// Checksum did not match after applying (book left `Desynchronized`)
+    #[error(transparent)]
+    ChecksumMismatch(#[from] ChecksumMismatch),
+    /// Update timestamped before the last applied one; dropped (book left `Desynchronized`)
+    #[error("Out-of-order update for {symbol}: {received} precedes {last}")]
+    OutOfOrderUpdate {
+        /// Trading pair symbol
+        symbol: String,
+        /// Timestamp of the last applied message
+        last: String,
+        /// Timestamp of the rejected update
+        received: String,
+    },
+    /// Update arrived before any snapshot; dropped (state unchanged)
+    #[error("Update for {symbol} arrived before a snapshot")]
+    UpdateBeforeSnapshot {
+        /// Trading pair symbol
+        symbol: String,
+    },
+    /// Best bid at or above best ask after applying; applied (book stays `Synced`)
+    #[error("Crossed book for {symbol}: bid {best_bid} >= ask {best_ask}")]
+    CrossedBook {
+        /// Trading pair symbol
+        symbol: String,
+        /// Best bid price
+        best_bid: Decimal,
+        /// Best ask price
+        best_ask: Decimal,
+    },
+    /// Snapshot had more levels than the book's depth; truncated and applied (book `Synced`)
+    #[error("Snapshot for {symbol} has {levels} levels, book depth is {depth}")]
+    DepthOverflow {
+        /// Trading pair symbol
+        symbol: String,
+        /// Levels on the deeper side of the snapshot
+        levels: usize,
+        /// Book depth
+        depth: u32,
+    },
+}
+
+impl ApplyError {
+    /// Symbol the error is for
+    pub fn symbol(&self) -> &str {
+        match self {
+            ApplyError::ChecksumMismatch(mismatch) => &mismatch.symbol,
+            ApplyError::OutOfOrderUpdate { symbol, .. }
+            | ApplyError::UpdateBeforeSnapshot { symbol }
+            | ApplyError::CrossedBook { symbol, .. }
+            | ApplyError::DepthOverflow { symbol, .. } => symbol,
+        }
+    }
+
+    /// Returns true if the message was applied despite the error
+    pub fn was_applied(&self) -> bool {
+        matches!(self, ApplyError::CrossedBook { .. } | ApplyError::DepthOverflow { .. })
+    }
+
+    /// Returns true if the book needs a fresh snapshot to recover
+    pub fn requires_resync(&self) -> bool {
+        matches!(self, ApplyError::ChecksumMismatch(_) | ApplyError::OutOfOrderUpdate { .. })
+    }
+
+    /// Stable snake_case name of the variant, for logs and bindings
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ApplyError::ChecksumMismatch(_) => "checksum_mismatch",
+            ApplyError::OutOfOrderUpdate { .. } => "out_of_order_update",
+            ApplyError::UpdateBeforeSnapshot { .. } => "update_before_snapshot",
+            ApplyError::CrossedBook { .. } => "crossed_book",
+            ApplyError::DepthOverflow { .. } => "depth_overflow",
+        }
+    }
+}
+
+/// Returns true if exchange timestamp `a` is strictly earlier than `b`
+///
+/// Kraken timestamps are fixed-width RFC 3339 UTC strings, so they order
+/// lexically. Timestamps of different widths are not compared.
+fn timestamp_precedes(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a < b
+}
+
 /// Result of applying a message
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ApplyResult {
@@ -81,6 +200,18 @@ pub struct Orderbook {
     price_precision: u8,
     /// Quantity precision (decimal places) for checksum calculation
     qty_precision: u8,
+    /// Incrementally maintained top-10 checksum
+    checksum_cache: ChecksumCache,
+    /// Timestamp of the last applied message, for ordering checks
+    last_timestamp: Option<String>,
+    /// Levels kept per side below the subscribed depth (None = depth)
+    level_cap: Option<usize>,
+    /// Per-level change tracking (None = disabled)
+    level_meta: Option<LevelMetaTracker>,
+    /// Where `price_precision` and `qty_precision` came from
+    precision_source: PrecisionSource,
+    /// Expected checksum of the last message, if it failed before precision was configured
+    unconfirmed_mismatch: Option<u32>,
 }
 
 impl Orderbook {
@@ -94,6 +225,12 @@ impl Orderbook {
             depth: 10, // Default depth
             price_precision: DEFAULT_PRICE_PRECISION,
             qty_precision: DEFAULT_QTY_PRECISION,
+            checksum_cache: ChecksumCache::default(),
+            last_timestamp: None,
+            level_cap: None,
+            level_meta: None,
+            precision_source: PrecisionSource::Default,
+            unconfirmed_mismatch: None,
         }
     }
 
@@ -107,16 +244,42 @@ impl Orderbook {
             depth,
             price_precision: DEFAULT_PRICE_PRECISION,
             qty_precision: DEFAULT_QTY_PRECISION,
+            checksum_cache: ChecksumCache::default(),
+            last_timestamp: None,
+            level_cap: None,
+            level_meta: None,
+            precision_source: PrecisionSource::Default,
+            unconfirmed_mismatch: None,
         }
     }
 
     /// Set the precision values (from instrument channel)
     ///
     /// This should be called before applying any book data to ensure
-    /// correct checksum validation.
+    /// correct checksum validation. Called afterwards, it replaces inferred
+    /// precision, and resyncs the book if the last message's checksum
+    /// matches under the new precision.
     pub fn set_precision(&mut self, price_precision: u8, qty_precision: u8) {
+        self.use_precision(price_precision, qty_precision);
+        self.precision_source = PrecisionSource::Configured;
+        if self.state == OrderbookState::Desynchronized {
+            if let Some(expected) = self.unconfirmed_mismatch.take() {
+                if self.validate_checksum(expected).is_ok() {
+                    self.state = OrderbookState::Synced;
+                }
+            }
+        }
+    }
+
+    fn use_precision(&mut self, price_precision: u8, qty_precision: u8) {
         self.price_precision = price_precision;
         self.qty_precision = qty_precision;
+        self.checksum_cache.set_precision(price_precision, qty_precision);
+    }
+
+    /// Where the current precision came from
+    pub fn precision_source(&self) -> PrecisionSource {
+        self.precision_source
     }
 
     /// Get the current price precision
@@ -134,6 +297,11 @@ impl Orderbook {
         &self.symbol
     }
 
+    /// Exchange timestamp of the last applied message that carried one
+    pub fn last_timestamp(&self) -> Option<&str> {
+        self.last_timestamp.as_deref()
+    }
+
     /// Get the current state
     pub fn state(&self) -> OrderbookState {
         self.state
@@ -154,6 +322,109 @@ impl Orderbook {
         self.depth
     }
 
+    /// Change the subscribed depth, dropping levels beyond it immediately
+    ///
+    /// Use when a book outlives its subscription, e.g. after resubscribing
+    /// with a different depth. Returns the number of levels removed.
+    pub fn set_depth(&mut self, depth: u32) -> usize {
+        self.depth = depth;
+        // A deeper subscription lifts the cap along with it
+        self.level_cap = self.level_cap.map(|cap| cap.max(depth as usize));
+        let before = self.storage.level_count();
+        self.storage.truncate(self.max_levels());
+        if let Some(meta) = &mut self.level_meta {
+            meta.retain_in(&self.storage);
+        }
+        before - self.storage.level_count()
+    }
+
+    /// Levels kept per side below the subscribed depth, if capped
+    pub fn level_cap(&self) -> Option<usize> {
+        self.level_cap
+    }
+
+    /// Keep at most `cap` levels per side, dropping the tails immediately
+    ///
+    /// The cap never goes below the subscribed depth (or [`MIN_LEVEL_CAP`]):
+    /// Kraken only reports a level entering the top `depth` once, so a book
+    /// holding fewer levels would lose the ones that move up and fail its
+    /// next checksum. Hold fewer levels by subscribing at a smaller depth.
+    /// `None` lifts the cap; levels dropped earlier come back with the next
+    /// snapshot. Returns the number of levels removed.
+    pub fn set_level_cap(&mut self, cap: Option<usize>) -> usize {
+        self.level_cap = cap.map(|cap| cap.max(MIN_LEVEL_CAP).max(self.depth as usize));
+        let before = self.storage.level_count();
+        self.storage.truncate(self.max_levels());
+        if let Some(meta) = &mut self.level_meta {
+            meta.retain_in(&self.storage);
+        }
+        before - self.storage.level_count()
+    }
+
+    /// Turn per-level metadata tracking on or off
+    ///
+    /// Levels already in the book start with unknown times. See
+    /// [`crate::level_meta`].
+    pub fn set_level_metadata(&mut self, enabled: bool) {
+        match (enabled, &self.level_meta) {
+            (true, None) => {
+                let mut meta = LevelMetaTracker::default();
+                meta.seed(&self.storage);
+                self.level_meta = Some(meta);
+            }
+            (false, Some(_)) => self.level_meta = None,
+            _ => {}
+        }
+    }
+
+    /// Check if per-level metadata is tracked
+    pub fn tracks_level_metadata(&self) -> bool {
+        self.level_meta.is_some()
+    }
+
+    /// Change count and timing of the level at `price` on either side
+    ///
+    /// Returns None if tracking is off or no level rests at that price.
+    pub fn level_meta(&self, price: impl Into<Price>) -> Option<LevelMeta> {
+        self.level_meta.as_ref()?.get(&price.into())
+    }
+
+    /// Capture current state with the metadata of every level
+    ///
+    /// Returns None if tracking is off.
+    pub fn snapshot_extended(&self) -> Option<ExtendedSnapshot> {
+        let meta = self.level_meta.as_ref()?;
+        let snapshot = self.snapshot();
+        Some(ExtendedSnapshot {
+            bid_meta: snapshot.bids.iter().map(|l| meta.bid(&l.price)).collect(),
+            ask_meta: snapshot.asks.iter().map(|l| meta.ask(&l.price)).collect(),
+            as_of_us: meta.as_of_us(),
+            snapshot,
+        })
+    }
+
+    /// Levels kept per side: the subscribed depth or the cap, whichever is lower
+    fn max_levels(&self) -> usize {
+        let depth = self.depth as usize;
+        self.level_cap.map_or(depth, |cap| cap.min(depth))
+    }
+
+    /// Direct access to the levels, bypassing the apply path
+    #[cfg(test)]
+    pub(crate) fn storage_mut(&mut self) -> &mut TreeBook {
+        &mut self.storage
+    }
+
+    /// Approximate heap and inline bytes held by the book
+    ///
+    /// See [`crate::memory`] for how this is estimated.
+    pub fn approx_bytes(&self) -> usize {
+        BOOK_OVERHEAD_BYTES
+            + self.symbol.len()
+            + self.storage.level_count() * APPROX_LEVEL_BYTES
+            + self.level_meta.as_ref().map_or(0, |meta| meta.approx_bytes())
+    }
+
     /// Get the best bid
     pub fn best_bid(&self) -> Option<&Level> {
         self.storage.best_bid()
@@ -167,7 +438,7 @@ impl Orderbook {
     /// Get the spread (ask - bid)
     pub fn spread(&self) -> Option<Decimal> {
         match (self.best_ask(), self.best_bid()) {
-            (Some(ask), Some(bid)) => Some(ask.price - bid.price),
+            (Some(ask), Some(bid)) => Some((ask.price - bid.price).0),
             _ => None,
         }
     }
@@ -175,7 +446,7 @@ impl Orderbook {
     /// Get the mid price ((ask + bid) / 2)
     pub fn mid_price(&self) -> Option<Decimal> {
         match (self.best_ask(), self.best_bid()) {
-            (Some(ask), Some(bid)) => Some((ask.price + bid.price) / Decimal::TWO),
+            (Some(ask), Some(bid)) => Some((ask.price + bid.price).0 / Decimal::TWO),
             _ => None,
         }
     }
@@ -210,17 +481,44 @@ impl Orderbook {
         self.storage.ask_count()
     }
 
+    /// Total quantity across all bid levels
+    pub fn total_bid_qty(&self) -> Decimal {
+        self.storage.bids().map(|l| l.qty.0).sum()
+    }
+
+    /// Total quantity across all ask levels
+    pub fn total_ask_qty(&self) -> Decimal {
+        self.storage.asks().map(|l| l.qty.0).sum()
+    }
+
+    /// Bid/ask quantity imbalance over the top `levels` of each side
+    ///
+    /// `(bid_qty - ask_qty) / (bid_qty + ask_qty)`, from -1 (only asks) to
+    /// 1 (only bids). None if both sides are empty.
+    pub fn imbalance(&self, levels: usize) -> Option<Decimal> {
+        let bid_qty: Decimal = self.storage.bids().take(levels).map(|l| l.qty.0).sum();
+        let ask_qty: Decimal = self.storage.asks().take(levels).map(|l| l.qty.0).sum();
+        let total = bid_qty + ask_qty;
+        (!total.is_zero()).then(|| (bid_qty - ask_qty) / total)
+    }
+
     /// Mark as awaiting snapshot (call when subscribing)
     pub fn set_awaiting_snapshot(&mut self) {
         self.state = OrderbookState::AwaitingSnapshot;
     }
 
     /// Apply book data from a channel message
+    ///
+    /// Updates are dropped with [`ApplyError::UpdateBeforeSnapshot`] until a
+    /// snapshot has been applied, and silently ignored while the book is
+    /// desynchronized (the mismatch has already been reported).
     pub fn apply_book_data(
         &mut self,
         data: &BookData,
         is_snapshot: bool,
-    ) -> Result<ApplyResult, ChecksumMismatch> {
+    ) -> Result<ApplyResult, ApplyError> {
+        // Once another message arrives the earlier mismatch can't be re-checked
+        self.unconfirmed_mismatch = None;
         if is_snapshot {
             self.apply_snapshot_data(data)
         } else {
@@ -229,9 +527,10 @@ impl Orderbook {
     }
 
     /// Apply a snapshot (full orderbook state)
-    fn apply_snapshot_data(&mut self, data: &BookData) -> Result<ApplyResult, ChecksumMismatch> {
+    fn apply_snapshot_data(&mut self, data: &BookData) -> Result<ApplyResult, ApplyError> {
         // Clear existing state
         self.storage.clear();
+        self.checksum_cache.invalidate();
 
         // Load all levels
         for level in &data.bids {
@@ -242,20 +541,55 @@ impl Orderbook {
         }
 
         // Truncate to subscribed depth
-        self.storage.truncate(self.depth as usize);
+        self.storage.truncate(self.max_levels());
+        if let Some(meta) = &mut self.level_meta {
+            meta.advance(data.timestamp.as_deref());
+            meta.seed(&self.storage);
+        }
 
         // Validate checksum
-        self.validate_checksum(data.checksum)?;
+        self.validate_snapshot_checksum(data)?;
 
         self.state = OrderbookState::Synced;
+        self.last_timestamp = data.timestamp.clone();
+        self.check_crossed()?;
+
+        let levels = data.bids.len().max(data.asks.len());
+        if levels > self.depth as usize {
+            return Err(ApplyError::DepthOverflow {
+                symbol: self.symbol.clone(),
+                levels,
+                depth: self.depth,
+            });
+        }
         Ok(ApplyResult::Snapshot)
     }
 
     /// Apply a delta update
-    fn apply_delta_data(&mut self, data: &BookData) -> Result<ApplyResult, ChecksumMismatch> {
-        // Skip if not synced
-        if self.state != OrderbookState::Synced {
-            return Ok(ApplyResult::Ignored);
+    fn apply_delta_data(&mut self, data: &BookData) -> Result<ApplyResult, ApplyError> {
+        match self.state {
+            OrderbookState::Synced => {}
+            OrderbookState::Desynchronized => return Ok(ApplyResult::Ignored),
+            OrderbookState::Uninitialized | OrderbookState::AwaitingSnapshot => {
+                return Err(ApplyError::UpdateBeforeSnapshot {
+                    symbol: self.symbol.clone(),
+                });
+            }
+        }
+
+        if let (Some(last), Some(received)) = (&self.last_timestamp, &data.timestamp) {
+            if timestamp_precedes(received, last) {
+                self.state = OrderbookState::Desynchronized;
+                return Err(ApplyError::OutOfOrderUpdate {
+                    symbol: self.symbol.clone(),
+                    last: last.clone(),
+                    received: received.clone(),
+                });
+            }
+        }
+
+        if let Some(meta) = &mut self.level_meta {
+            meta.advance(data.timestamp.as_deref());
         }
 
         // Apply bid updates (qty == 0 means remove)
@@ -265,6 +599,10 @@ impl Orderbook {
             } else {
                 self.storage.insert_bid(level.price, level.qty);
             }
+            self.checksum_cache.touch_bid(level.price);
+            if let Some(meta) = &mut self.level_meta {
+                meta.touch_bid(level);
+            }
         }
 
         // Apply ask updates
@@ -274,30 +612,70 @@ impl Orderbook {
             } else {
                 self.storage.insert_ask(level.price, level.qty);
             }
+            self.checksum_cache.touch_ask(level.price);
+            if let Some(meta) = &mut self.level_meta {
+                meta.touch_ask(level);
+            }
         }
 
-        // Truncate to subscribed depth
-        self.storage.truncate(self.depth as usize);
+        // Truncate to subscribed depth (never reaches into a full checksum window)
+        self.storage.truncate(self.max_levels());
+        if let Some(meta) = &mut self.level_meta {
+            meta.retain_in(&self.storage);
+        }
 
         // Validate checksum
         self.validate_checksum(data.checksum)?;
 
+        if data.timestamp.is_some() {
+            self.last_timestamp = data.timestamp.clone();
+        }
+        self.check_crossed()?;
         Ok(ApplyResult::Update)
     }
 
+    /// Report a book whose best bid is at or above its best ask
+    fn check_crossed(&self) -> Result<(), ApplyError> {
+        match (self.best_bid(), self.best_ask()) {
+            (Some(bid), Some(ask)) if bid.price >= ask.price => Err(ApplyError::CrossedBook {
+                symbol: self.symbol.clone(),
+                best_bid: bid.price.0,
+                best_ask: ask.price.0,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Validate a snapshot, retrying with inferred precision if none was configured
+    fn validate_snapshot_checksum(&mut self, data: &BookData) -> Result<(), ChecksumMismatch> {
+        let mismatch = match self.validate_checksum(data.checksum) {
+            Ok(()) => return Ok(()),
+            Err(mismatch) => mismatch,
+        };
+        let current = (self.price_precision, self.qty_precision);
+        let inferred = match infer_precision(&data.bids, &data.asks) {
+            Some(inferred) if inferred != current && self.precision_source != PrecisionSource::Configured => inferred,
+            _ => return Err(mismatch),
+        };
+        self.use_precision(inferred.0, inferred.1);
+        if self.validate_checksum(data.checksum).is_ok() {
+            self.precision_source = PrecisionSource::Inferred;
+            return Ok(());
+        }
+        self.use_precision(current.0, current.1);
+        self.unconfirmed_mismatch = Some(data.checksum);
+        Err(mismatch)
+    }
+
     /// Validate the current state against expected checksum
     fn validate_checksum(&mut self, expected: u32) -> Result<(), ChecksumMismatch> {
-        let bids = self.storage.bids_vec();
-        let asks = self.storage.asks_vec();
-        let computed = compute_checksum_with_precision(
-            &bids,
-            &asks,
-            self.price_precision,
-            self.qty_precision,
-        );
+        let computed = self.checksum_cache.checksum(self.storage.bids(), self.storage.asks());
 
         if computed != expected {
             self.state = OrderbookState::Desynchronized;
+            if self.precision_source != PrecisionSource::Configured {
+                self.unconfirmed_mismatch = Some(expected);
+            }
             return Err(ChecksumMismatch {
                 symbol: self.symbol.clone(),
                 expected,
@@ -312,8 +690,52 @@ impl Orderbook {
     /// Clear and reset the orderbook
     pub fn reset(&mut self) {
         self.storage.clear();
+        self.checksum_cache.invalidate();
         self.last_checksum = 0;
+        self.last_timestamp = None;
+        self.unconfirmed_mismatch = None;
         self.state = OrderbookState::Uninitialized;
+        if let Some(meta) = &mut self.level_meta {
+            *meta = LevelMetaTracker::default();
+        }
+    }
+
+    /// Load levels from a stored snapshot, e.g. after a page reload
+    ///
+    /// The book is left awaiting a fresh snapshot: restored levels are shown
+    /// as-is, deltas are ignored, and the next live snapshot replaces them.
+    pub fn restore_snapshot(&mut self, snapshot: &OrderbookSnapshot) {
+        self.storage.clear();
+        self.checksum_cache.invalidate();
+        for level in &snapshot.bids {
+            self.storage.insert_bid(level.price, level.qty);
+        }
+        for level in &snapshot.asks {
+            self.storage.insert_ask(level.price, level.qty);
+        }
+        self.storage.truncate(self.max_levels());
+        if let Some(meta) = &mut self.level_meta {
+            *meta = LevelMetaTracker::default();
+            meta.seed(&self.storage);
+        }
+        self.last_checksum = snapshot.checksum;
+        self.last_timestamp = None;
+        self.state = OrderbookState::AwaitingSnapshot;
+    }
+
+    /// Apply deltas on top of restored levels without a fresh snapshot
+    ///
+    /// For subscriptions sent with `snapshot: false`. Returns false, leaving
+    /// the state alone, unless the book holds levels from
+    /// [`restore_snapshot`](Self::restore_snapshot) and is still awaiting a
+    /// snapshot; such a book can only be synced by a live snapshot.
+    pub fn trust_restored_state(&mut self) -> bool {
+        let restored = self.state == OrderbookState::AwaitingSnapshot
+            && (self.storage.bid_count() > 0 || self.storage.ask_count() > 0);
+        if restored {
+            self.state = OrderbookState::Synced;
+        }
+        restored
     }
 
     /// Capture current state as a snapshot
@@ -340,7 +762,7 @@ pub struct OrderbookSnapshot {
     /// Checksum at time of snapshot
     pub checksum: u32,
     /// State at time of snapshot
-    #[serde(skip)]
+    #[serde(default)]
     pub state: OrderbookState,
 }
 
@@ -359,12 +781,12 @@ impl Default for OrderbookSnapshot {
 impl OrderbookSnapshot {
     /// Get the best bid price
     pub fn best_bid_price(&self) -> Option<Decimal> {
-        self.bids.first().map(|l| l.price)
+        self.bids.first().map(|l| l.price.0)
     }
 
     /// Get the best ask price
     pub fn best_ask_price(&self) -> Option<Decimal> {
-        self.asks.first().map(|l| l.price)
+        self.asks.first().map(|l| l.price.0)
     }
 
     /// Get the spread
@@ -382,6 +804,19 @@ impl OrderbookSnapshot {
             _ => None,
         }
     }
+
+    /// Get the microprice (top-of-book mid weighted by the opposite size)
+    ///
+    /// Leans towards the side with less size, which is the side more likely
+    /// to trade through next.
+    pub fn microprice(&self) -> Option<Decimal> {
+        let (bid, ask) = (self.bids.first()?, self.asks.first()?);
+        let total = bid.qty + ask.qty;
+        if total.is_zero() {
+            return self.mid_price();
+        }
+        Some(((bid.price * ask.qty + ask.price * bid.qty) / total).0)
+    }
 }
 
 #[cfg(test)]
@@ -450,6 +885,20 @@ mod tests {
         assert_eq!(book.mid_price(), Some(dec!(101)));
     }
 
+    #[test]
+    fn test_depth_totals_and_imbalance() {
+        let mut book = Orderbook::new("BTC/USD");
+        assert_eq!(book.imbalance(10), None);
+
+        let data = make_book_data(vec![(100.0, 3.0), (99.0, 5.0)], vec![(101.0, 1.0), (102.0, 2.0)]);
+        book.apply_book_data(&data, true).unwrap();
+
+        assert_eq!(book.total_bid_qty(), dec!(8));
+        assert_eq!(book.total_ask_qty(), dec!(3));
+        assert_eq!(book.imbalance(1), Some(dec!(0.5)));
+        assert_eq!(book.imbalance(10), Some(dec!(5) / dec!(11)));
+    }
+
     #[test]
     fn test_checksum_mismatch() {
         let mut book = Orderbook::new("BTC/USD");
@@ -463,6 +912,94 @@ mod tests {
         assert_eq!(book.state(), OrderbookState::Desynchronized);
     }
 
+    #[test]
+    fn test_out_of_order_update_desynchronizes() {
+        let mut book = Orderbook::new("BTC/USD");
+        assert!(matches!(
+            book.apply_book_data(&make_book_data(vec![(100.0, 1.0)], vec![]), false),
+            Err(ApplyError::UpdateBeforeSnapshot { .. })
+        ));
+
+        book.apply_book_data(&make_book_data(vec![(100.0, 1.0)], vec![(101.0, 1.0)]), true).unwrap();
+        let mut delta = make_book_data(vec![(100.0, 2.0)], vec![(101.0, 1.0)]);
+        delta.timestamp = Some("2025-01-01T00:00:01.000000Z".to_string());
+        book.apply_book_data(&delta, false).unwrap();
+
+        delta.timestamp = Some("2025-01-01T00:00:00.500000Z".to_string());
+        let err = book.apply_book_data(&delta, false).unwrap_err();
+        assert_eq!(err.kind(), "out_of_order_update");
+        assert!(err.requires_resync() && !err.was_applied());
+        assert_eq!(book.state(), OrderbookState::Desynchronized);
+        // Further updates are ignored until the next snapshot
+        assert_eq!(book.apply_book_data(&delta, false).unwrap(), ApplyResult::Ignored);
+    }
+
+    #[test]
+    fn test_crossed_and_overflowing_books_are_applied() {
+        let mut book = Orderbook::with_depth("BTC/USD", 1);
+        let deep = make_book_data(vec![(100.0, 1.0), (99.0, 1.0)], vec![(101.0, 1.0)]);
+        // Checksum covers the truncated book
+        let mut data = make_book_data(vec![(100.0, 1.0)], vec![(101.0, 1.0)]);
+        data.bids = deep.bids;
+        let err = book.apply_book_data(&data, true).unwrap_err();
+        assert_eq!(err, ApplyError::DepthOverflow { symbol: "BTC/USD".into(), levels: 2, depth: 1 });
+        assert!(err.was_applied());
+        assert!(book.is_synced());
+
+        let crossed = make_book_data(vec![(101.0, 1.0)], vec![(101.0, 1.0)]);
+        let err = book.apply_book_data(&crossed, true).unwrap_err();
+        assert!(matches!(err, ApplyError::CrossedBook { best_bid, .. } if best_bid == dec!(101)));
+        assert!(book.is_synced());
+    }
+
+    #[test]
+    fn test_set_depth_truncates_consistently() {
+        let mut book = Orderbook::with_depth("BTC/USD", 25);
+        let bids: Vec<(f64, f64)> = (0..15).map(|i| (100.0 - i as f64, 1.0)).collect();
+        let asks: Vec<(f64, f64)> = (0..15).map(|i| (101.0 + i as f64, 1.0)).collect();
+        book.apply_book_data(&make_book_data(bids, asks), true).unwrap();
+
+        assert_eq!(book.set_depth(10), 10);
+        assert_eq!(book.depth(), 10);
+        assert_eq!(book.bids_vec().len(), 10);
+        assert_eq!(book.asks_vec().len(), 10);
+
+        // Deltas beyond the new depth are dropped
+        let mut delta = make_book_data(vec![(80.0, 1.0)], vec![]);
+        delta.checksum = compute_checksum(&book.bids_vec(), &book.asks_vec());
+        book.apply_book_data(&delta, false).unwrap();
+        assert_eq!(book.bids_vec().len(), 10);
+        assert_eq!(book.bids_vec()[9].price.0, dec!(91));
+    }
+
+    #[test]
+    fn test_level_cap_keeps_subscribed_depth_in_sync() {
+        let mut book = Orderbook::with_depth("BTC/USD", 25);
+        let bids: Vec<(f64, f64)> = (0..25).map(|i| (100.0 - i as f64, 1.0)).collect();
+        let asks: Vec<(f64, f64)> = (0..25).map(|i| (101.0 + i as f64, 1.0)).collect();
+        book.apply_book_data(&make_book_data(bids, asks), true).unwrap();
+
+        assert_eq!(book.set_level_cap(Some(10)), 0);
+        assert_eq!(book.level_cap(), Some(25));
+
+        // Kraken deletes the top bids and sends the levels entering at 25
+        for i in 0..15 {
+            let mut delta = make_book_data(vec![(100.0 - i as f64, 0.0), (75.0 - i as f64, 1.0)], vec![]);
+            let mut expected = book.bids_vec();
+            expected.remove(0);
+            expected.push(Level::from_f64(75.0 - i as f64, 1.0));
+            delta.checksum = compute_checksum(&expected, &book.asks_vec());
+            book.apply_book_data(&delta, false).unwrap();
+            assert!(book.is_synced());
+        }
+        assert_eq!(book.bid_count(), 25);
+        assert_eq!(book.best_bid().unwrap().price.0, dec!(85));
+
+        // A deeper subscription lifts the cap too
+        book.set_depth(100);
+        assert_eq!(book.level_cap(), Some(100));
+    }
+
     #[test]
     fn test_reset() {
         let mut book = Orderbook::new("BTC/USD");
@@ -474,4 +1011,137 @@ mod tests {
         assert_eq!(book.bid_count(), 0);
         assert_eq!(book.ask_count(), 0);
     }
+
+    #[test]
+    fn test_restore_snapshot_awaits_live_data() {
+        let mut live = Orderbook::new("BTC/USD");
+        let data = make_book_data(vec![(100.0, 1.0)], vec![(101.0, 1.5)]);
+        live.apply_book_data(&data, true).unwrap();
+
+        let mut book = Orderbook::new("BTC/USD");
+        book.restore_snapshot(&live.snapshot());
+        assert_eq!(book.state(), OrderbookState::AwaitingSnapshot);
+        assert_eq!(book.best_ask().unwrap().qty, dec!(1.5));
+
+        // Deltas wait for a live snapshot, which replaces the restored levels
+        let delta = make_book_data(vec![(100.0, 2.0)], vec![]);
+        assert!(matches!(
+            book.apply_book_data(&delta, false),
+            Err(ApplyError::UpdateBeforeSnapshot { .. })
+        ));
+        let fresh = make_book_data(vec![(99.0, 1.0)], vec![(102.0, 1.0)]);
+        book.apply_book_data(&fresh, true).unwrap();
+        assert_eq!(book.best_bid().unwrap().price, dec!(99));
+    }
+
+    #[test]
+    fn test_trusted_restore_applies_deltas_without_snapshot() {
+        let mut live = Orderbook::new("BTC/USD");
+        live.apply_book_data(&make_book_data(vec![(100.0, 1.0)], vec![(101.0, 1.5)]), true)
+            .unwrap();
+        // Kraken's delta carries the checksum of the book after applying it
+        let mut delta = make_book_data(vec![(100.0, 2.0)], vec![]);
+        delta.checksum = make_book_data(vec![(100.0, 2.0)], vec![(101.0, 1.5)]).checksum;
+
+        let mut book = Orderbook::new("BTC/USD");
+        assert!(!book.trust_restored_state());
+        book.restore_snapshot(&live.snapshot());
+        assert!(book.trust_restored_state());
+        assert!(book.is_synced());
+        book.apply_book_data(&delta, false).unwrap();
+        assert_eq!(book.best_bid().unwrap().qty, dec!(2));
+
+        // Stale restored levels fail the first checksum
+        let mut stale = Orderbook::new("BTC/USD");
+        stale.restore_snapshot(&OrderbookSnapshot {
+            bids: vec![Level::new(dec!(99), dec!(1))],
+            asks: vec![Level::new(dec!(101), dec!(1.5))],
+            ..Default::default()
+        });
+        assert!(stale.trust_restored_state());
+        assert!(matches!(
+            stale.apply_book_data(&delta, false),
+            Err(ApplyError::ChecksumMismatch(_))
+        ));
+        assert_eq!(stale.state(), OrderbookState::Desynchronized);
+    }
+
+    #[test]
+    fn test_snapshot_infers_precision_until_configured() {
+        use crate::checksum::compute_checksum_with_precision;
+
+        let bids = vec![Level::new(dec!(3150.25), dec!(1.5)), Level::new(dec!(3150.1), dec!(0.25))];
+        let asks = vec![Level::new(dec!(3150.5), dec!(2))];
+        let snapshot = BookData {
+            symbol: "ETH/USD".to_string(),
+            checksum: compute_checksum_with_precision(&bids, &asks, 2, 2),
+            bids,
+            asks,
+            timestamp: None,
+        };
+
+        let mut book = Orderbook::new("ETH/USD");
+        book.apply_book_data(&snapshot, true).unwrap();
+        assert!(book.is_synced());
+        assert_eq!((book.price_precision(), book.qty_precision()), (2, 2));
+        assert_eq!(book.precision_source(), PrecisionSource::Inferred);
+
+        // Configured precision is never second-guessed
+        let mut configured = Orderbook::new("ETH/USD");
+        configured.set_precision(1, 8);
+        assert!(configured.apply_book_data(&snapshot, true).is_err());
+        assert_eq!(configured.price_precision(), 1);
+    }
+
+    #[test]
+    fn test_late_precision_resyncs_book() {
+        use crate::checksum::compute_checksum_with_precision;
+
+        // Real precision is wider than anything the levels show
+        let bids = vec![Level::new(dec!(0.25), dec!(100))];
+        let asks = vec![Level::new(dec!(0.26), dec!(50))];
+        let snapshot = BookData {
+            symbol: "XRP/USD".to_string(),
+            checksum: compute_checksum_with_precision(&bids, &asks, 5, 8),
+            bids,
+            asks,
+            timestamp: None,
+        };
+
+        let mut book = Orderbook::new("XRP/USD");
+        assert!(book.apply_book_data(&snapshot, true).is_err());
+        assert_eq!(book.state(), OrderbookState::Desynchronized);
+        book.set_precision(5, 8);
+        assert!(book.is_synced());
+        assert_eq!(book.precision_source(), PrecisionSource::Configured);
+    }
+
+    #[test]
+    fn test_snapshot_microprice() {
+        let snapshot = OrderbookSnapshot {
+            bids: vec![Level::new(dec!(100), dec!(3))],
+            asks: vec![Level::new(dec!(102), dec!(1))],
+            ..Default::default()
+        };
+        assert_eq!(snapshot.mid_price(), Some(dec!(101)));
+        assert_eq!(snapshot.microprice(), Some(dec!(101.5)));
+    }
+
+    #[test]
+    fn test_snapshot_serde_round_trip() {
+        let mut book = Orderbook::new("BTC/USD");
+        let data = make_book_data(vec![(100.0, 1.0)], vec![(101.0, 1.5)]);
+        book.apply_book_data(&data, true).unwrap();
+
+        let json = serde_json::to_string(&book.snapshot()).unwrap();
+        let restored: OrderbookSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.state, OrderbookState::Synced);
+        assert_eq!(restored.asks, book.asks_vec());
+        assert_eq!(restored.checksum, data.checksum);
+
+        // Captures written before the state was serialized still load
+        let legacy = r#"{"symbol":"BTC/USD","bids":[],"asks":[],"checksum":0}"#;
+        let restored: OrderbookSnapshot = serde_json::from_str(legacy).unwrap();
+        assert_eq!(restored.state, OrderbookState::Uninitialized);
+    }
 }
@@ -1,23 +1,23 @@
 //! BTreeMap-based orderbook storage
 //!
 //! Provides O(log N) operations for orderbook management.
-//! Uses `Reverse<Decimal>` for bids to maintain descending order.
+//! Uses `Reverse<Price>` for bids to maintain descending order.
 
-use kraken_types::Level;
+use kraken_types::{Level, Price, Qty};
 use rust_decimal::Decimal;
 use std::cmp::Reverse;
 use std::collections::BTreeMap;
 
 /// Orderbook storage using BTreeMap for O(log N) operations
 ///
-/// - Bids: Stored with `Reverse<Decimal>` key for descending order (highest first)
-/// - Asks: Stored with `Decimal` key for ascending order (lowest first)
+/// - Bids: Stored with `Reverse<Price>` key for descending order (highest first)
+/// - Asks: Stored with `Price` key for ascending order (lowest first)
 #[derive(Debug, Clone, Default)]
 pub struct TreeBook {
     /// Bids: highest price first (use Reverse for descending order)
-    bids: BTreeMap<Reverse<Decimal>, Level>,
+    bids: BTreeMap<Reverse<Price>, Level>,
     /// Asks: lowest price first (natural ascending order)
-    asks: BTreeMap<Decimal, Level>,
+    asks: BTreeMap<Price, Level>,
 }
 
 impl TreeBook {
@@ -31,7 +31,8 @@ impl TreeBook {
 
     /// Insert or update a bid level
     /// If qty is zero, the level is removed
-    pub fn insert_bid(&mut self, price: Decimal, qty: Decimal) {
+    pub fn insert_bid(&mut self, price: impl Into<Price>, qty: impl Into<Qty>) {
+        let (price, qty) = (price.into(), qty.into());
         if qty.is_zero() {
             self.bids.remove(&Reverse(price));
         } else {
@@ -41,7 +42,8 @@ impl TreeBook {
 
     /// Insert or update an ask level
     /// If qty is zero, the level is removed
-    pub fn insert_ask(&mut self, price: Decimal, qty: Decimal) {
+    pub fn insert_ask(&mut self, price: impl Into<Price>, qty: impl Into<Qty>) {
+        let (price, qty) = (price.into(), qty.into());
         if qty.is_zero() {
             self.asks.remove(&price);
         } else {
@@ -50,15 +52,25 @@ impl TreeBook {
     }
 
     /// Remove a bid level by price
-    pub fn remove_bid(&mut self, price: &Decimal) {
+    pub fn remove_bid(&mut self, price: &Price) {
         self.bids.remove(&Reverse(*price));
     }
 
     /// Remove an ask level by price
-    pub fn remove_ask(&mut self, price: &Decimal) {
+    pub fn remove_ask(&mut self, price: &Price) {
         self.asks.remove(price);
     }
 
+    /// Get the bid level at a price
+    pub fn get_bid(&self, price: &Price) -> Option<&Level> {
+        self.bids.get(&Reverse(*price))
+    }
+
+    /// Get the ask level at a price
+    pub fn get_ask(&self, price: &Price) -> Option<&Level> {
+        self.asks.get(price)
+    }
+
     /// Get the best bid (highest price)
     pub fn best_bid(&self) -> Option<&Level> {
         self.bids.values().next()
@@ -71,12 +83,12 @@ impl TreeBook {
 
     /// Get the best bid price
     pub fn best_bid_price(&self) -> Option<Decimal> {
-        self.best_bid().map(|l| l.price)
+        self.best_bid().map(|l| l.price.0)
     }
 
     /// Get the best ask price
     pub fn best_ask_price(&self) -> Option<Decimal> {
-        self.best_ask().map(|l| l.price)
+        self.best_ask().map(|l| l.price.0)
     }
 
     /// Iterator over bids (highest to lowest price)
@@ -137,20 +149,12 @@ impl TreeBook {
 
     /// Truncate to maximum depth (removes levels beyond the limit)
     pub fn truncate(&mut self, max_depth: usize) {
-        // Keep only the top `max_depth` bids
-        if self.bids.len() > max_depth {
-            let keys_to_remove: Vec<_> = self.bids.keys().skip(max_depth).cloned().collect();
-            for key in keys_to_remove {
-                self.bids.remove(&key);
-            }
+        // Both maps are ordered best-first, so the worst levels are at the end
+        while self.bids.len() > max_depth {
+            self.bids.pop_last();
         }
-
-        // Keep only the top `max_depth` asks
-        if self.asks.len() > max_depth {
-            let keys_to_remove: Vec<_> = self.asks.keys().skip(max_depth).cloned().collect();
-            for key in keys_to_remove {
-                self.asks.remove(&key);
-            }
+        while self.asks.len() > max_depth {
+            self.asks.pop_last();
         }
     }
 }
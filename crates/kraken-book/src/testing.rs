@@ -0,0 +1,448 @@
+//! Property-testing helpers for orderbook consistency
+//!
+//! Generators for random but valid book snapshots and deltas whose checksums
+//! always match the book they describe, plus an independent reference checksum
+//! implementation for cross-checking. Generators are seeded, so they plug into
+//! any property-testing framework: draw a `u64` seed and build a [`BookGenerator`]
+//! from it.
+//!
+//! Available with the `test-utils` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_book::testing::{BookGenerator, assert_book_consistent};
+//! use kraken_book::Orderbook;
+//!
+//! let mut generator = BookGenerator::new("BTC/USD", 42);
+//! let mut book = Orderbook::new("BTC/USD");
+//!
+//! book.apply_book_data(&generator.snapshot(), true).unwrap();
+//! for _ in 0..100 {
+//!     book.apply_book_data(&generator.delta(), false).unwrap();
+//! }
+//! assert_book_consistent(&book);
+//! ```
+
+use crate::checksum::{DEFAULT_PRICE_PRECISION, DEFAULT_QTY_PRECISION};
+use crate::orderbook::Orderbook;
+use kraken_types::{BookData, Level};
+use rust_decimal::Decimal;
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+/// Small deterministic PRNG (SplitMix64)
+///
+/// Keeps the generators dependency-free and reproducible across platforms.
+#[derive(Debug, Clone)]
+pub struct SeededRng {
+    state: u64,
+}
+
+impl SeededRng {
+    /// Create a generator from a seed
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Next random `u64`
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Random boolean
+    pub fn coin(&mut self) -> bool {
+        self.next_u64() & 1 == 0
+    }
+
+    /// Random value in `low..=high`
+    pub fn range(&mut self, low: u64, high: u64) -> u64 {
+        debug_assert!(low <= high);
+        low + self.next_u64() % (high - low + 1)
+    }
+}
+
+/// Generator for checksum-consistent book snapshots and deltas
+///
+/// Maintains a shadow book so every generated message carries the checksum
+/// of the book after it is applied. The generator never produces a crossed
+/// book and never holds more than `depth` levels per side: when an insert
+/// would exceed the depth, the delta also removes the worst level, the way
+/// Kraken does.
+#[derive(Debug, Clone)]
+pub struct BookGenerator {
+    symbol: String,
+    rng: SeededRng,
+    depth: usize,
+    mid_ticks: u64,
+    price_precision: u8,
+    qty_precision: u8,
+    bids: BTreeMap<Reverse<Decimal>, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+impl BookGenerator {
+    /// Create a generator with depth 10 and default precision
+    pub fn new(symbol: impl Into<String>, seed: u64) -> Self {
+        Self {
+            symbol: symbol.into(),
+            rng: SeededRng::new(seed),
+            depth: 10,
+            mid_ticks: 1_000_000,
+            price_precision: DEFAULT_PRICE_PRECISION,
+            qty_precision: DEFAULT_QTY_PRECISION,
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+        }
+    }
+
+    /// Set the maximum number of levels per side
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// Set price and quantity precision
+    pub fn with_precision(mut self, price_precision: u8, qty_precision: u8) -> Self {
+        self.price_precision = price_precision;
+        self.qty_precision = qty_precision;
+        self
+    }
+
+    /// Current bids (best first)
+    pub fn bids(&self) -> Vec<Level> {
+        self.bids
+            .iter()
+            .map(|(Reverse(p), q)| Level::new(*p, *q))
+            .collect()
+    }
+
+    /// Current asks (best first)
+    pub fn asks(&self) -> Vec<Level> {
+        self.asks.iter().map(|(p, q)| Level::new(*p, *q)).collect()
+    }
+
+    /// Checksum of the shadow book
+    pub fn checksum(&self) -> u32 {
+        reference_checksum(
+            &self.bids(),
+            &self.asks(),
+            self.price_precision,
+            self.qty_precision,
+        )
+    }
+
+    /// Generate a full snapshot, replacing the shadow book
+    pub fn snapshot(&mut self) -> BookData {
+        self.bids.clear();
+        self.asks.clear();
+
+        let levels = self.rng.range(1, self.depth as u64);
+        for _ in 0..levels {
+            let bid = self.bid_price();
+            let qty = self.qty();
+            self.bids.insert(Reverse(bid), qty);
+            let ask = self.ask_price();
+            let qty = self.qty();
+            self.asks.insert(ask, qty);
+        }
+
+        BookData {
+            symbol: self.symbol.clone(),
+            bids: self.bids(),
+            asks: self.asks(),
+            checksum: self.checksum(),
+            timestamp: None,
+        }
+    }
+
+    /// Generate a delta against the shadow book
+    ///
+    /// Each delta touches one to three levels: quantity changes, removals
+    /// (qty = 0), and inserts.
+    pub fn delta(&mut self) -> BookData {
+        let mut bids = Vec::new();
+        let mut asks = Vec::new();
+
+        for _ in 0..self.rng.range(1, 3) {
+            if self.rng.coin() {
+                self.mutate_bids(&mut bids);
+            } else {
+                self.mutate_asks(&mut asks);
+            }
+        }
+
+        BookData {
+            symbol: self.symbol.clone(),
+            bids,
+            asks,
+            checksum: self.checksum(),
+            timestamp: None,
+        }
+    }
+
+    fn mutate_bids(&mut self, out: &mut Vec<Level>) {
+        match self.rng.range(0, 2) {
+            0 if !self.bids.is_empty() => {
+                let price = self.pick_existing(self.bids.keys().map(|r| r.0).collect());
+                let qty = self.qty();
+                self.bids.insert(Reverse(price), qty);
+                out.push(Level::new(price, qty));
+            }
+            1 if self.bids.len() > 1 => {
+                let price = self.pick_existing(self.bids.keys().map(|r| r.0).collect());
+                self.bids.remove(&Reverse(price));
+                out.push(Level::new(price, Decimal::ZERO));
+            }
+            _ => {
+                let price = self.bid_price();
+                let qty = self.qty();
+                self.bids.insert(Reverse(price), qty);
+                out.push(Level::new(price, qty));
+                while self.bids.len() > self.depth {
+                    let (Reverse(worst), _) = self.bids.pop_last().expect("non-empty");
+                    out.push(Level::new(worst, Decimal::ZERO));
+                }
+            }
+        }
+    }
+
+    fn mutate_asks(&mut self, out: &mut Vec<Level>) {
+        match self.rng.range(0, 2) {
+            0 if !self.asks.is_empty() => {
+                let price = self.pick_existing(self.asks.keys().copied().collect());
+                let qty = self.qty();
+                self.asks.insert(price, qty);
+                out.push(Level::new(price, qty));
+            }
+            1 if self.asks.len() > 1 => {
+                let price = self.pick_existing(self.asks.keys().copied().collect());
+                self.asks.remove(&price);
+                out.push(Level::new(price, Decimal::ZERO));
+            }
+            _ => {
+                let price = self.ask_price();
+                let qty = self.qty();
+                self.asks.insert(price, qty);
+                out.push(Level::new(price, qty));
+                while self.asks.len() > self.depth {
+                    let (worst, _) = self.asks.pop_last().expect("non-empty");
+                    out.push(Level::new(worst, Decimal::ZERO));
+                }
+            }
+        }
+    }
+
+    fn pick_existing(&mut self, prices: Vec<Decimal>) -> Decimal {
+        let idx = self.rng.range(0, prices.len() as u64 - 1) as usize;
+        prices[idx]
+    }
+
+    fn tick_price(&self, ticks: u64) -> Decimal {
+        Decimal::new(ticks as i64, self.price_precision as u32)
+    }
+
+    fn bid_price(&mut self) -> Decimal {
+        let offset = self.rng.range(1, 2 * self.depth as u64);
+        self.tick_price(self.mid_ticks - offset)
+    }
+
+    fn ask_price(&mut self) -> Decimal {
+        let offset = self.rng.range(1, 2 * self.depth as u64);
+        self.tick_price(self.mid_ticks + offset)
+    }
+
+    fn qty(&mut self) -> Decimal {
+        let units = self.rng.range(1, 10u64.pow(self.qty_precision as u32 + 1));
+        Decimal::new(units as i64, self.qty_precision as u32)
+    }
+}
+
+/// Reference implementation of Kraken's book checksum
+///
+/// Written independently of [`compute_checksum_with_precision`](crate::compute_checksum_with_precision):
+/// values are formatted with decimal string arithmetic instead of from the
+/// rescaled mantissa, and CRC32 is computed bitwise instead of via `crc32fast`.
+/// Use it to cross-check other implementations.
+pub fn reference_checksum(
+    bids: &[Level],
+    asks: &[Level],
+    price_precision: u8,
+    qty_precision: u8,
+) -> u32 {
+    let mut payload = String::new();
+    for level in asks.iter().take(10).chain(bids.iter().take(10)) {
+        payload.push_str(&reference_format(level.price.0, price_precision));
+        payload.push_str(&reference_format(level.qty.0, qty_precision));
+    }
+    crc32_bitwise(payload.as_bytes())
+}
+
+fn reference_format(value: Decimal, precision: u8) -> String {
+    let mut rounded = value.round_dp(precision as u32);
+    rounded.rescale(precision as u32);
+    let digits: String = rounded.to_string().chars().filter(|c| *c != '.').collect();
+    let trimmed = digits.trim_start_matches('0');
+    if trimmed.is_empty() {
+        "0".to_string()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+fn crc32_bitwise(bytes: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Assert that an orderbook satisfies its structural invariants
+///
+/// Checks that both sides are strictly sorted, contain no zero quantities,
+/// are not crossed, respect the configured depth, and (when synced) that the
+/// reference checksum matches the last validated checksum.
+///
+/// # Panics
+///
+/// Panics with a description of the first violated invariant.
+pub fn assert_book_consistent(book: &Orderbook) {
+    let bids = book.bids_vec();
+    let asks = book.asks_vec();
+
+    assert!(
+        bids.windows(2).all(|w| w[0].price > w[1].price),
+        "{}: bids not strictly descending",
+        book.symbol()
+    );
+    assert!(
+        asks.windows(2).all(|w| w[0].price < w[1].price),
+        "{}: asks not strictly ascending",
+        book.symbol()
+    );
+    assert!(
+        bids.iter().chain(asks.iter()).all(|l| !l.qty.is_zero()),
+        "{}: zero-quantity level present",
+        book.symbol()
+    );
+    if let (Some(bid), Some(ask)) = (bids.first(), asks.first()) {
+        assert!(
+            bid.price < ask.price,
+            "{}: crossed book (bid {} >= ask {})",
+            book.symbol(),
+            bid.price,
+            ask.price
+        );
+    }
+    assert!(
+        bids.len() <= book.depth() as usize && asks.len() <= book.depth() as usize,
+        "{}: more levels than subscribed depth {}",
+        book.symbol(),
+        book.depth()
+    );
+
+    if book.is_synced() {
+        let expected = reference_checksum(
+            &bids,
+            &asks,
+            book.price_precision(),
+            book.qty_precision(),
+        );
+        assert_eq!(
+            expected,
+            book.last_checksum(),
+            "{}: reference checksum disagrees with last validated checksum",
+            book.symbol()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::checksum::compute_checksum_with_precision;
+    use crate::orderbook::OrderbookState;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_crc32_known_value() {
+        // Standard CRC32 check value
+        assert_eq!(crc32_bitwise(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_reference_checksum_matches_production() {
+        for seed in 0..50 {
+            let mut generator = BookGenerator::new("BTC/USD", seed);
+            let snapshot = generator.snapshot();
+            assert_eq!(
+                reference_checksum(&snapshot.bids, &snapshot.asks, 1, 8),
+                compute_checksum_with_precision(&snapshot.bids, &snapshot.asks, 1, 8),
+                "seed {}",
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn test_reference_format() {
+        assert_eq!(reference_format(dec!(88813.5), 1), "888135");
+        assert_eq!(reference_format(dec!(0.00460208), 8), "460208");
+        assert_eq!(reference_format(dec!(0.001), 8), "100000");
+        assert_eq!(reference_format(dec!(0), 8), "0");
+    }
+
+    #[test]
+    fn test_generated_sequences_stay_in_sync() {
+        for seed in 0..20 {
+            let mut generator = BookGenerator::new("BTC/USD", seed).with_depth(10);
+            let mut book = Orderbook::with_depth("BTC/USD", 10);
+
+            book.apply_book_data(&generator.snapshot(), true).unwrap();
+            for step in 0..200 {
+                let delta = generator.delta();
+                book.apply_book_data(&delta, false)
+                    .unwrap_or_else(|e| panic!("seed {} step {}: {}", seed, step, e));
+            }
+            assert_eq!(book.state(), OrderbookState::Synced);
+            assert_book_consistent(&book);
+        }
+    }
+
+    #[test]
+    fn test_generator_is_deterministic() {
+        let mut a = BookGenerator::new("ETH/USD", 7);
+        let mut b = BookGenerator::new("ETH/USD", 7);
+        assert_eq!(a.snapshot().checksum, b.snapshot().checksum);
+        assert_eq!(a.delta().checksum, b.delta().checksum);
+    }
+
+    #[test]
+    #[should_panic(expected = "crossed book")]
+    fn test_assert_detects_crossed_book() {
+        let mut book = Orderbook::new("BTC/USD");
+        let bids = vec![Level::new(dec!(101), dec!(1))];
+        let asks = vec![Level::new(dec!(100), dec!(1))];
+        let checksum = compute_checksum_with_precision(&bids, &asks, 1, 8);
+        let data = BookData {
+            symbol: "BTC/USD".into(),
+            bids,
+            asks,
+            checksum,
+            timestamp: None,
+        };
+        // Applied, but reported
+        let result = book.apply_book_data(&data, true);
+        assert!(matches!(result, Err(crate::ApplyError::CrossedBook { .. })));
+        assert_book_consistent(&book);
+    }
+}
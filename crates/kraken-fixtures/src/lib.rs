@@ -0,0 +1,37 @@
+//! Deterministic Kraken market data for tests and benchmarks
+//!
+//! Generates realistic sessions of book snapshots, deltas and trades in the
+//! exact wire format of Kraken API v2, from a seed. Use it to benchmark a
+//! strategy against hours of synthetic flow or to drive integration tests
+//! through a mock transport without recording live data.
+//!
+//! # Features
+//!
+//! - **Random-walk mid** with configurable volatility and tick size
+//! - **Clustered volume**: calm and busy trade regimes
+//! - **Valid checksums** on every update, computed from a shadow book
+//! - **Fault injection**: occasional bad checksums or dropped updates
+//! - **Canned frames** in [`messages`] for single-message tests
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_fixtures::{FrameKind, SessionConfig, SessionGenerator};
+//! use kraken_types::WsMessage;
+//!
+//! let config = SessionConfig::new("BTC/USD")
+//!     .with_volatility_bps(2.0)
+//!     .with_checksum_errors(0.01);
+//!
+//! for frame in SessionGenerator::new(config, 42).take(100) {
+//!     let message = WsMessage::parse(&frame.json).unwrap();
+//!     if frame.kind == FrameKind::Trade {
+//!         assert!(matches!(message, WsMessage::Trade(_)));
+//!     }
+//! }
+//! ```
+
+pub mod messages;
+pub mod session;
+
+pub use session::{Fault, Frame, FrameKind, SessionConfig, SessionGenerator};
@@ -0,0 +1,84 @@
+//! Hand-written server frames in Kraken API v2 format
+//!
+//! Building blocks for tests that need a specific message rather than a
+//! whole generated session.
+
+use kraken_types::{Level, Side};
+use rust_decimal::Decimal;
+
+/// Status message sent by Kraken on connection
+pub const STATUS_MESSAGE: &str = r#"{"channel":"status","type":"update","data":[{"api_version":"v2","connection_id":12345678901234567890,"system":"online","version":"2.0.10"}]}"#;
+
+/// Heartbeat message
+pub const HEARTBEAT_MESSAGE: &str = r#"{"channel":"heartbeat"}"#;
+
+/// Successful subscribe acknowledgement
+pub fn subscribe_ack(channel: &str, symbol: &str, req_id: u64) -> String {
+    format!(
+        r#"{{"method":"subscribe","req_id":{},"result":{{"channel":"{}","snapshot":true,"symbol":"{}"}},"success":true,"time_in":"2025-12-21T12:28:24.000000Z","time_out":"2025-12-21T12:28:24.001000Z"}}"#,
+        req_id, channel, symbol
+    )
+}
+
+/// Book frame (`kind` is `"snapshot"` or `"update"`) with an explicit checksum
+pub fn book_message(symbol: &str, kind: &str, bids: &[Level], asks: &[Level], checksum: u32, timestamp: &str) -> String {
+    format!(
+        r#"{{"channel":"book","type":"{}","data":[{{"symbol":"{}","bids":[{}],"asks":[{}],"checksum":{},"timestamp":"{}"}}]}}"#,
+        kind,
+        symbol,
+        levels_json(bids),
+        levels_json(asks),
+        checksum,
+        timestamp
+    )
+}
+
+/// Trade frame with a single trade
+pub fn trade_message(symbol: &str, side: Side, price: Decimal, qty: Decimal, trade_id: u64, timestamp: &str) -> String {
+    let side = match side {
+        Side::Buy => "buy",
+        Side::Sell => "sell",
+    };
+    format!(
+        r#"{{"channel":"trade","type":"update","data":[{{"symbol":"{}","side":"{}","price":{},"qty":{},"ord_type":"market","trade_id":{},"timestamp":"{}"}}]}}"#,
+        symbol, side, price, qty, trade_id, timestamp
+    )
+}
+
+fn levels_json(levels: &[Level]) -> String {
+    levels
+        .iter()
+        .map(|level| format!(r#"{{"price":{},"qty":{}}}"#, level.price, level.qty))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_types::WsMessage;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_messages_parse() {
+        assert!(matches!(WsMessage::parse(STATUS_MESSAGE).unwrap(), WsMessage::Status(_)));
+        assert!(matches!(WsMessage::parse(HEARTBEAT_MESSAGE).unwrap(), WsMessage::Heartbeat));
+        assert!(matches!(
+            WsMessage::parse(&subscribe_ack("book", "BTC/USD", 1)).unwrap(),
+            WsMessage::Method(_)
+        ));
+
+        let bids = [Level::new(dec!(100.0), dec!(1.5))];
+        let book = book_message("BTC/USD", "snapshot", &bids, &[], 7, "2025-01-01T00:00:00.000000Z");
+        assert!(matches!(WsMessage::parse(&book).unwrap(), WsMessage::Book(_)));
+
+        let trade = trade_message("BTC/USD", Side::Sell, dec!(100.0), dec!(0.25), 9, "2025-01-01T00:00:00.000000Z");
+        match WsMessage::parse(&trade).unwrap() {
+            WsMessage::Trade(msg) => {
+                assert_eq!(msg.data[0].side, Side::Sell);
+                assert_eq!(msg.data[0].trade_id, 9);
+            }
+            other => panic!("expected trade, got {:?}", other),
+        }
+    }
+}
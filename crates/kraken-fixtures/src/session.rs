@@ -0,0 +1,573 @@
+//! Seeded generator for whole market sessions
+//!
+//! A [`SessionGenerator`] keeps a shadow L2 book and walks it forward one
+//! step at a time:
+//!
+//! - the mid follows a Gaussian random walk scaled by `volatility_bps`
+//! - the touch follows the mid, crossed levels are removed and the book is
+//!   refilled to `depth` levels with 1–3 tick gaps
+//! - a few resting quantities change every step
+//! - trades hit the touch and arrive in bursts: the session switches
+//!   between a calm and a busy regime, and busy trades are larger and
+//!   tend to repeat the previous side
+//!
+//! Every update carries the checksum of the shadow book, so a consumer that
+//! applies all frames stays in sync. Faults are opt-in: a bad checksum
+//! corrupts a single frame, a gap drops one update and flags the frame
+//! after it.
+
+use crate::messages::{book_message, trade_message};
+use kraken_book::{compute_checksum_with_precision, DEFAULT_PRICE_PRECISION, DEFAULT_QTY_PRECISION};
+use kraken_types::{Formatting, Level, Precision, Side};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+
+/// Shape of a generated session
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionConfig {
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Mid price at the start of the session
+    pub start_mid: Decimal,
+    /// Decimal places of prices (the tick size is `10^-price_precision`)
+    pub price_precision: u8,
+    /// Decimal places of quantities
+    pub qty_precision: u8,
+    /// Levels per side
+    pub depth: usize,
+    /// Exchange time of the first frame (Unix microseconds)
+    pub start_time_us: i64,
+    /// Exchange time between book updates
+    pub step_interval: Duration,
+    /// Standard deviation of the mid's move per step, in basis points
+    pub volatility_bps: f64,
+    /// Average resting quantity per level
+    pub mean_level_qty: f64,
+    /// Average trade size in the calm regime
+    pub mean_trade_qty: f64,
+    /// Chance of a trade per step in the calm regime
+    pub trade_probability: f64,
+    /// Chance per step of switching from calm to busy
+    pub burst_probability: f64,
+    /// Average length of a busy regime, in steps
+    pub mean_burst_steps: f64,
+    /// Chance that an update carries a wrong checksum
+    pub checksum_error_rate: f64,
+    /// Chance that an update is dropped
+    pub gap_rate: f64,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            symbol: "BTC/USD".to_string(),
+            start_mid: Decimal::new(50_000, 0),
+            price_precision: DEFAULT_PRICE_PRECISION,
+            qty_precision: DEFAULT_QTY_PRECISION,
+            depth: 10,
+            // 2025-01-01T00:00:00Z
+            start_time_us: 1_735_689_600_000_000,
+            step_interval: Duration::from_millis(100),
+            volatility_bps: 1.0,
+            mean_level_qty: 1.0,
+            mean_trade_qty: 0.05,
+            trade_probability: 0.2,
+            burst_probability: 0.02,
+            mean_burst_steps: 20.0,
+            checksum_error_rate: 0.0,
+            gap_rate: 0.0,
+        }
+    }
+}
+
+impl SessionConfig {
+    /// Default session for a symbol
+    pub fn new(symbol: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the starting mid price
+    pub fn with_start_mid(mut self, mid: Decimal) -> Self {
+        self.start_mid = mid;
+        self
+    }
+
+    /// Set price and quantity decimal places
+    pub fn with_precision(mut self, price_precision: u8, qty_precision: u8) -> Self {
+        self.price_precision = price_precision;
+        self.qty_precision = qty_precision;
+        self
+    }
+
+    /// Set the number of levels per side
+    pub fn with_depth(mut self, depth: usize) -> Self {
+        self.depth = depth.max(1);
+        self
+    }
+
+    /// Set the exchange time of the first frame (Unix microseconds)
+    pub fn with_start_time_us(mut self, start_time_us: i64) -> Self {
+        self.start_time_us = start_time_us;
+        self
+    }
+
+    /// Set the exchange time between book updates
+    pub fn with_step_interval(mut self, interval: Duration) -> Self {
+        self.step_interval = interval;
+        self
+    }
+
+    /// Set the per-step volatility of the mid, in basis points
+    pub fn with_volatility_bps(mut self, bps: f64) -> Self {
+        self.volatility_bps = bps;
+        self
+    }
+
+    /// Set the calm trade probability per step and the average trade size
+    pub fn with_trades(mut self, probability: f64, mean_qty: f64) -> Self {
+        self.trade_probability = probability;
+        self.mean_trade_qty = mean_qty;
+        self
+    }
+
+    /// Set how often busy regimes start and how long they last on average
+    pub fn with_bursts(mut self, probability: f64, mean_steps: f64) -> Self {
+        self.burst_probability = probability;
+        self.mean_burst_steps = mean_steps;
+        self
+    }
+
+    /// Corrupt the checksum of this fraction of updates
+    pub fn with_checksum_errors(mut self, rate: f64) -> Self {
+        self.checksum_error_rate = rate;
+        self
+    }
+
+    /// Drop this fraction of updates
+    pub fn with_gaps(mut self, rate: f64) -> Self {
+        self.gap_rate = rate;
+        self
+    }
+
+    /// Price and quantity precision of the session's symbol
+    pub fn precision(&self) -> Precision {
+        Precision::new(self.price_precision, self.qty_precision)
+    }
+
+    fn tick(&self) -> Decimal {
+        self.precision().tick()
+    }
+}
+
+/// What a generated frame carries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameKind {
+    /// Full book
+    Snapshot,
+    /// Book delta
+    Update,
+    /// One trade
+    Trade,
+}
+
+/// Fault injected into a frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The update's checksum is wrong; its levels are correct
+    BadChecksum,
+    /// The update before this one was dropped
+    Gap,
+}
+
+/// One generated server frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// What the frame carries
+    pub kind: FrameKind,
+    /// Exchange time of the frame (Unix microseconds)
+    pub timestamp_us: i64,
+    /// Injected fault, if any
+    pub fault: Option<Fault>,
+    /// The frame as Kraken would send it
+    pub json: String,
+}
+
+/// Deterministic, endless stream of frames for one symbol
+///
+/// The same config and seed always produce the same frames.
+#[derive(Debug, Clone)]
+pub struct SessionGenerator {
+    config: SessionConfig,
+    /// Rounds generated quantities to the symbol's lot
+    formatting: Formatting,
+    rng: StdRng,
+    /// Shadow book, ascending by price on both sides
+    bids: BTreeMap<Decimal, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+    mid: f64,
+    time_us: i64,
+    next_trade_id: u64,
+    /// Steps left in the busy regime (0 = calm)
+    burst_left: u32,
+    last_trade_side: Side,
+    gap_pending: bool,
+    started: bool,
+    queued: VecDeque<Frame>,
+}
+
+impl SessionGenerator {
+    /// Create a generator for `config`, seeded with `seed`
+    pub fn new(config: SessionConfig, seed: u64) -> Self {
+        let mid = config.start_mid.to_f64().unwrap_or(1.0);
+        let time_us = config.start_time_us;
+        let formatting = Formatting::new().with_precision(config.symbol.clone(), config.precision());
+        let mut generator = Self {
+            config,
+            formatting,
+            rng: StdRng::seed_from_u64(seed),
+            bids: BTreeMap::new(),
+            asks: BTreeMap::new(),
+            mid,
+            time_us,
+            next_trade_id: 1,
+            burst_left: 0,
+            last_trade_side: Side::Buy,
+            gap_pending: false,
+            started: false,
+            queued: VecDeque::new(),
+        };
+        generator.reshape_book();
+        generator
+    }
+
+    /// Session configuration
+    pub fn config(&self) -> &SessionConfig {
+        &self.config
+    }
+
+    /// Current shadow book as (bids best first, asks best first)
+    pub fn book(&self) -> (Vec<Level>, Vec<Level>) {
+        let bids = self.bids.iter().rev().map(|(p, q)| Level::new(*p, *q)).collect();
+        let asks = self.asks.iter().map(|(p, q)| Level::new(*p, *q)).collect();
+        (bids, asks)
+    }
+
+    /// Generate the next `count` frames
+    pub fn frames(&mut self, count: usize) -> Vec<Frame> {
+        self.take(count).collect()
+    }
+
+    /// Whether the session is in a busy regime
+    pub fn is_bursting(&self) -> bool {
+        self.burst_left > 0
+    }
+
+    fn step(&mut self) {
+        self.time_us += self.config.step_interval.as_micros() as i64;
+        self.update_regime();
+        let before = (self.bids.clone(), self.asks.clone());
+        self.emit_trades();
+
+        self.mid *= 1.0 + self.gaussian() * self.config.volatility_bps / 10_000.0;
+        self.reshape_book();
+        self.perturb_levels();
+
+        if self.rng.gen_bool(self.config.gap_rate.clamp(0.0, 1.0)) {
+            self.gap_pending = true;
+            return;
+        }
+        let bids = diff(&before.0, &self.bids, true);
+        let asks = diff(&before.1, &self.asks, false);
+        let mut fault = self.gap_pending.then_some(Fault::Gap);
+        self.gap_pending = false;
+        let mut checksum = self.checksum();
+        if self.rng.gen_bool(self.config.checksum_error_rate.clamp(0.0, 1.0)) {
+            checksum = checksum.wrapping_add(1);
+            fault = Some(Fault::BadChecksum);
+        }
+        let json = book_message(&self.config.symbol, "update", &bids, &asks, checksum, &self.timestamp());
+        self.queued.push_back(Frame {
+            kind: FrameKind::Update,
+            timestamp_us: self.time_us,
+            fault,
+            json,
+        });
+    }
+
+    /// The current shadow book as a snapshot frame
+    ///
+    /// Lets a test answer a consumer's resubscribe mid-session.
+    pub fn snapshot_frame(&self) -> Frame {
+        let (bids, asks) = self.book();
+        Frame {
+            kind: FrameKind::Snapshot,
+            timestamp_us: self.time_us,
+            fault: None,
+            json: book_message(&self.config.symbol, "snapshot", &bids, &asks, self.checksum(), &self.timestamp()),
+        }
+    }
+
+    fn update_regime(&mut self) {
+        if self.burst_left > 0 {
+            self.burst_left -= 1;
+        } else if self.rng.gen_bool(self.config.burst_probability.clamp(0.0, 1.0)) {
+            let steps = self.exponential(self.config.mean_burst_steps.max(1.0));
+            self.burst_left = steps.ceil() as u32;
+        }
+    }
+
+    /// Trades at the touch; each one takes liquidity from the shadow book
+    fn emit_trades(&mut self) {
+        let (probability, count, size) = if self.is_bursting() {
+            ((self.config.trade_probability * 4.0).min(1.0), self.rng.gen_range(1..=3), 3.0)
+        } else {
+            (self.config.trade_probability.clamp(0.0, 1.0), 1, 1.0)
+        };
+        if !self.rng.gen_bool(probability) {
+            return;
+        }
+        for _ in 0..count {
+            let side = if self.is_bursting() && self.rng.gen_bool(0.7) {
+                self.last_trade_side
+            } else if self.rng.gen_bool(0.5) {
+                Side::Buy
+            } else {
+                Side::Sell
+            };
+            let wanted = self.exponential(self.config.mean_trade_qty * size);
+            let wanted = self.round_qty(wanted);
+            let book = match side {
+                Side::Buy => &mut self.asks,
+                Side::Sell => &mut self.bids,
+            };
+            let touch = match side {
+                Side::Buy => book.iter_mut().next(),
+                Side::Sell => book.iter_mut().next_back(),
+            };
+            let Some((&price, resting)) = touch else {
+                continue;
+            };
+            let qty = wanted.min(*resting);
+            if qty.is_zero() {
+                continue;
+            }
+            *resting -= qty;
+            if resting.is_zero() {
+                book.remove(&price);
+            }
+            self.last_trade_side = side;
+            let json = trade_message(&self.config.symbol, side, price, qty, self.next_trade_id, &self.timestamp());
+            self.next_trade_id += 1;
+            self.queued.push_back(Frame {
+                kind: FrameKind::Trade,
+                timestamp_us: self.time_us,
+                fault: None,
+                json,
+            });
+        }
+    }
+
+    /// Move the touch to the mid, drop crossed levels and refill to depth
+    fn reshape_book(&mut self) {
+        let tick = self.config.tick();
+        let mid = Decimal::from_f64(self.mid).unwrap_or(tick).max(tick * Decimal::TWO);
+        let best_bid = (mid / tick).floor() * tick;
+        let best_ask = best_bid + tick;
+        self.bids.retain(|price, _| *price <= best_bid);
+        self.asks.retain(|price, _| *price >= best_ask);
+
+        if !self.bids.contains_key(&best_bid) {
+            let qty = self.level_qty();
+            self.bids.insert(best_bid, qty);
+        }
+        if !self.asks.contains_key(&best_ask) {
+            let qty = self.level_qty();
+            self.asks.insert(best_ask, qty);
+        }
+
+        let depth = self.config.depth;
+        while self.bids.len() > depth {
+            self.bids.pop_first();
+        }
+        while self.asks.len() > depth {
+            self.asks.pop_last();
+        }
+        while self.bids.len() < depth {
+            let worst = *self.bids.keys().next().expect("touch inserted");
+            let price = worst - tick * Decimal::from(self.rng.gen_range(1..=3u32));
+            if price <= Decimal::ZERO {
+                break;
+            }
+            let qty = self.level_qty();
+            self.bids.insert(price, qty);
+        }
+        while self.asks.len() < depth {
+            let worst = *self.asks.keys().next_back().expect("touch inserted");
+            let price = worst + tick * Decimal::from(self.rng.gen_range(1..=3u32));
+            let qty = self.level_qty();
+            self.asks.insert(price, qty);
+        }
+    }
+
+    /// Change the resting quantity of a few levels
+    fn perturb_levels(&mut self) {
+        for _ in 0..self.rng.gen_range(1..=3) {
+            let qty = self.level_qty();
+            let book = if self.rng.gen_bool(0.5) { &mut self.bids } else { &mut self.asks };
+            let index = self.rng.gen_range(0..book.len());
+            if let Some(resting) = book.values_mut().nth(index) {
+                *resting = qty;
+            }
+        }
+    }
+
+    fn checksum(&self) -> u32 {
+        let (bids, asks) = self.book();
+        compute_checksum_with_precision(&bids, &asks, self.config.price_precision, self.config.qty_precision)
+    }
+
+    fn timestamp(&self) -> String {
+        chrono::DateTime::from_timestamp_micros(self.time_us)
+            .unwrap_or_default()
+            .to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+    }
+
+    /// Positive resting quantity around `mean_level_qty`
+    fn level_qty(&mut self) -> Decimal {
+        let qty = self.exponential(self.config.mean_level_qty) + self.config.mean_level_qty * 0.1;
+        self.round_qty(qty).max(self.config.precision().lot())
+    }
+
+    /// Quantity rounded down to the symbol's lot
+    fn round_qty(&self, qty: f64) -> Decimal {
+        self.formatting
+            .round_qty(&self.config.symbol, Decimal::from_f64(qty).unwrap_or_default())
+    }
+
+    fn exponential(&mut self, mean: f64) -> f64 {
+        let u: f64 = self.rng.gen_range(f64::EPSILON..1.0);
+        -u.ln() * mean
+    }
+
+    /// Standard normal sample (Box-Muller)
+    fn gaussian(&mut self) -> f64 {
+        let u1: f64 = self.rng.gen_range(f64::EPSILON..1.0);
+        let u2: f64 = self.rng.gen();
+        (-2.0 * u1.ln()).sqrt() * (2.0 * std::f64::consts::PI * u2).cos()
+    }
+}
+
+impl Iterator for SessionGenerator {
+    type Item = Frame;
+
+    fn next(&mut self) -> Option<Frame> {
+        if !self.started {
+            self.started = true;
+            return Some(self.snapshot_frame());
+        }
+        while self.queued.is_empty() {
+            self.step();
+        }
+        self.queued.pop_front()
+    }
+}
+
+/// Levels that changed between two sides, best first, removals as qty 0
+fn diff(before: &BTreeMap<Decimal, Decimal>, after: &BTreeMap<Decimal, Decimal>, bids: bool) -> Vec<Level> {
+    let mut changes: Vec<Level> = after
+        .iter()
+        .filter(|(price, qty)| before.get(*price) != Some(*qty))
+        .map(|(price, qty)| Level::new(*price, *qty))
+        .chain(
+            before
+                .keys()
+                .filter(|price| !after.contains_key(*price))
+                .map(|price| Level::new(*price, Decimal::ZERO)),
+        )
+        .collect();
+    changes.sort_by_key(|level| level.price);
+    if bids {
+        changes.reverse();
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_book::{ApplyError, Orderbook, OrderbookState};
+    use kraken_types::WsMessage;
+
+    fn consumer(config: &SessionConfig) -> Orderbook {
+        let mut book = Orderbook::with_depth(&config.symbol, config.depth as u32);
+        book.set_precision(config.price_precision, config.qty_precision);
+        book
+    }
+
+    /// Apply a book frame; false on a checksum mismatch
+    fn apply(book: &mut Orderbook, frame: &Frame) -> bool {
+        let WsMessage::Book(msg) = WsMessage::parse(&frame.json).unwrap() else {
+            return true;
+        };
+        match book.apply_book_data(&msg.data[0], msg.msg_type == "snapshot") {
+            Ok(_) => true,
+            Err(ApplyError::ChecksumMismatch(_)) => false,
+            Err(e) => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_same_seed_same_session() {
+        let config = SessionConfig::new("ETH/USD").with_start_mid(Decimal::new(3000, 0));
+        let a = SessionGenerator::new(config.clone(), 7).frames(200);
+        let b = SessionGenerator::new(config.clone(), 7).frames(200);
+        let c = SessionGenerator::new(config, 8).frames(200);
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(a[0].kind, FrameKind::Snapshot);
+        assert!(a.iter().any(|frame| frame.kind == FrameKind::Trade));
+        assert!(a.windows(2).all(|w| w[0].timestamp_us <= w[1].timestamp_us));
+    }
+
+    #[test]
+    fn test_clean_session_keeps_consumer_in_sync() {
+        let config = SessionConfig::default().with_volatility_bps(3.0).with_bursts(0.1, 10.0);
+        let mut generator = SessionGenerator::new(config.clone(), 42);
+        let mut book = consumer(&config);
+        for frame in generator.frames(1_000) {
+            assert!(apply(&mut book, &frame), "mismatch at {}", frame.json);
+        }
+
+        assert_eq!(book.state(), OrderbookState::Synced);
+        let (bids, asks) = generator.book();
+        assert_eq!(book.bids_vec(), bids);
+        assert_eq!(book.asks_vec(), asks);
+        assert!(bids[0].price < asks[0].price);
+    }
+
+    #[test]
+    fn test_injected_checksum_errors_are_flagged() {
+        let config = SessionConfig::default().with_checksum_errors(0.05);
+        let mut generator = SessionGenerator::new(config.clone(), 3);
+        let mut book = consumer(&config);
+        let (mut flagged, mut mismatches) = (0, 0);
+        for _ in 0..500 {
+            let frame = generator.next().unwrap();
+            flagged += usize::from(frame.fault == Some(Fault::BadChecksum));
+            if !apply(&mut book, &frame) {
+                mismatches += 1;
+                // Resync the way a client would, with a fresh snapshot
+                assert!(apply(&mut book, &generator.snapshot_frame()));
+            }
+        }
+        assert!(flagged > 0);
+        assert_eq!(mismatches, flagged);
+    }
+}
@@ -85,7 +85,7 @@ impl BookChannel {
         // Apply bid updates
         for level in &update.bids {
             if level.qty.is_zero() {
-                book.remove_bid(&level.price);
+                book.remove_bid(&level.price.into());
             } else {
                 book.insert_bid(level.price, level.qty);
             }
@@ -94,7 +94,7 @@ impl BookChannel {
         // Apply ask updates
         for level in &update.asks {
             if level.qty.is_zero() {
-                book.remove_ask(&level.price);
+                book.remove_ask(&level.price.into());
             } else {
                 book.insert_ask(level.price, level.qty);
             }
@@ -109,28 +109,28 @@ impl BookChannel {
     /// Get best bid for a product (returns qty, price)
     pub fn best_bid(&self, product_id: &str) -> Option<(Decimal, Decimal)> {
         let level = self.books.get(product_id)?.best_bid()?;
-        Some((level.qty, level.price))
+        Some((level.qty.0, level.price.0))
     }
 
     /// Get best ask for a product (returns qty, price)
     pub fn best_ask(&self, product_id: &str) -> Option<(Decimal, Decimal)> {
         let level = self.books.get(product_id)?.best_ask()?;
-        Some((level.qty, level.price))
+        Some((level.qty.0, level.price.0))
     }
 
     /// Get spread for a product
     pub fn spread(&self, product_id: &str) -> Option<Decimal> {
         let book = self.books.get(product_id)?;
-        let bid_price = book.best_bid()?.price;
-        let ask_price = book.best_ask()?.price;
+        let bid_price = book.best_bid()?.price.0;
+        let ask_price = book.best_ask()?.price.0;
         Some(ask_price - bid_price)
     }
 
     /// Get mid price for a product
     pub fn mid_price(&self, product_id: &str) -> Option<Decimal> {
         let book = self.books.get(product_id)?;
-        let bid_price = book.best_bid()?.price;
-        let ask_price = book.best_ask()?.price;
+        let bid_price = book.best_bid()?.price.0;
+        let ask_price = book.best_ask()?.price.0;
         Some((bid_price + ask_price) / Decimal::TWO)
     }
 
@@ -207,7 +207,7 @@ impl FundingChannel {
             .map(|(id, rate)| (id.as_str(), rate.funding_rate))
             .collect();
 
-        products.sort_by(|a, b| b.1.cmp(&a.1));
+        products.sort_by_key(|p| std::cmp::Reverse(p.1));
         products
     }
 
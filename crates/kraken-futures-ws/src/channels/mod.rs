@@ -26,6 +26,10 @@ pub mod channels {
     pub const HEARTBEAT: &str = "heartbeat";
     /// Funding rates feed
     pub const FUNDING_RATES: &str = "funding_rates";
+    /// Mark price feed
+    pub const MARK_PRICE: &str = "mark_price";
+    /// Index price feed
+    pub const INDEX_PRICE: &str = "index_price";
 
     // Private channels
     /// Open positions
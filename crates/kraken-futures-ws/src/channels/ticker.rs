@@ -1,6 +1,6 @@
 //! Ticker channel handler
 
-use crate::types::{FuturesEvent, FuturesTicker, FundingRate, IndexPrice, MarkPrice};
+use crate::types::{Basis, FuturesEvent, FuturesTicker, FundingRate, IndexPrice, MarkPrice};
 use rust_decimal::Decimal;
 use std::collections::HashMap;
 use tracing::debug;
@@ -11,6 +11,12 @@ pub struct TickerChannel {
     tickers: HashMap<String, FuturesTicker>,
     /// Latest funding rates by product ID
     funding_rates: HashMap<String, FundingRate>,
+    /// Latest mark prices by product ID (from ticker or mark price feed)
+    mark_prices: HashMap<String, Decimal>,
+    /// Latest index prices by product ID (from ticker or index price feed)
+    index_prices: HashMap<String, Decimal>,
+    /// Last emitted basis by product ID
+    bases: HashMap<String, Basis>,
 }
 
 impl TickerChannel {
@@ -19,12 +25,21 @@ impl TickerChannel {
         Self {
             tickers: HashMap::new(),
             funding_rates: HashMap::new(),
+            mark_prices: HashMap::new(),
+            index_prices: HashMap::new(),
+            bases: HashMap::new(),
         }
     }
 
     /// Process a ticker update
     pub fn process_ticker(&mut self, ticker: FuturesTicker) -> FuturesEvent {
         debug!("Ticker update for {}", ticker.product_id);
+        if let Some(mark) = ticker.mark_price {
+            self.mark_prices.insert(ticker.product_id.clone(), mark);
+        }
+        if let Some(index) = ticker.index_price {
+            self.index_prices.insert(ticker.product_id.clone(), index);
+        }
         self.tickers.insert(ticker.product_id.clone(), ticker.clone());
         FuturesEvent::Ticker(ticker)
     }
@@ -38,6 +53,7 @@ impl TickerChannel {
 
     /// Process a mark price update
     pub fn process_mark_price(&mut self, mark: MarkPrice) -> FuturesEvent {
+        self.mark_prices.insert(mark.product_id.clone(), mark.mark_price);
         // Update ticker if we have one
         if let Some(ticker) = self.tickers.get_mut(&mark.product_id) {
             ticker.mark_price = Some(mark.mark_price);
@@ -47,6 +63,7 @@ impl TickerChannel {
 
     /// Process an index price update
     pub fn process_index_price(&mut self, index: IndexPrice) -> FuturesEvent {
+        self.index_prices.insert(index.product_id.clone(), index.index_price);
         // Update ticker if we have one
         if let Some(ticker) = self.tickers.get_mut(&index.product_id) {
             ticker.index_price = Some(index.index_price);
@@ -66,12 +83,36 @@ impl TickerChannel {
 
     /// Get mark price for a product
     pub fn mark_price(&self, product_id: &str) -> Option<Decimal> {
-        self.tickers.get(product_id)?.mark_price
+        self.mark_prices.get(product_id).copied()
     }
 
     /// Get index price for a product
     pub fn index_price(&self, product_id: &str) -> Option<Decimal> {
-        self.tickers.get(product_id)?.index_price
+        self.index_prices.get(product_id).copied()
+    }
+
+    /// Get the last computed basis for a product
+    pub fn basis(&self, product_id: &str) -> Option<&Basis> {
+        self.bases.get(product_id)
+    }
+
+    /// Recompute the basis for a product after a price update
+    ///
+    /// Returns a [`FuturesEvent::Basis`] when both prices are known and the
+    /// basis differs from the last one emitted for the product.
+    pub fn update_basis(&mut self, product_id: &str) -> Option<FuturesEvent> {
+        let mark = self.mark_price(product_id)?;
+        let index = self.index_price(product_id)?;
+        if self
+            .bases
+            .get(product_id)
+            .is_some_and(|b| b.mark_price == mark && b.index_price == index)
+        {
+            return None;
+        }
+        let basis = Basis::new(product_id, mark, index);
+        self.bases.insert(product_id.to_string(), basis.clone());
+        Some(FuturesEvent::Basis(basis))
     }
 
     /// Get all tracked product IDs
@@ -128,4 +169,45 @@ mod tests {
         assert!(channel.ticker("PI_XBTUSD").is_some());
         assert_eq!(channel.mark_price("PI_XBTUSD"), Some(Decimal::from(50000)));
     }
+
+    #[test]
+    fn test_basis_from_ticker_and_feeds() {
+        let mut channel = TickerChannel::new();
+        channel.process_ticker(create_test_ticker());
+
+        let Some(FuturesEvent::Basis(basis)) = channel.update_basis("PI_XBTUSD") else {
+            panic!("expected basis event");
+        };
+        assert_eq!(basis.basis, Decimal::from(5));
+        // Unchanged prices don't emit again
+        assert!(channel.update_basis("PI_XBTUSD").is_none());
+
+        channel.process_mark_price(MarkPrice {
+            product_id: "PI_XBTUSD".to_string(),
+            mark_price: Decimal::from(50010),
+            time: "2024-01-01T00:00:01Z".to_string(),
+        });
+        assert!(channel.update_basis("PI_XBTUSD").is_some());
+        assert_eq!(channel.basis("PI_XBTUSD").unwrap().basis, Decimal::from(15));
+    }
+
+    #[test]
+    fn test_price_feeds_without_ticker() {
+        let mut channel = TickerChannel::new();
+        channel.process_index_price(IndexPrice {
+            product_id: "PI_ETHUSD".to_string(),
+            index_price: Decimal::from(3000),
+            time: "2024-01-01T00:00:00Z".to_string(),
+        });
+        assert_eq!(channel.index_price("PI_ETHUSD"), Some(Decimal::from(3000)));
+        assert!(channel.update_basis("PI_ETHUSD").is_none());
+
+        channel.process_mark_price(MarkPrice {
+            product_id: "PI_ETHUSD".to_string(),
+            mark_price: Decimal::from(2997),
+            time: "2024-01-01T00:00:00Z".to_string(),
+        });
+        assert!(channel.update_basis("PI_ETHUSD").is_some());
+        assert_eq!(channel.basis("PI_ETHUSD").unwrap().premium_pct, Some(Decimal::new(-1, 1)));
+    }
 }
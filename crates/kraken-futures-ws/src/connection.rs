@@ -1,9 +1,12 @@
 //! WebSocket connection management for Kraken Futures
 
 use crate::auth::{AuthState, FuturesCredentials};
-use crate::channels::{BookChannel, PositionChannel, SubscriptionRequest, TickerChannel, TradeChannel};
+use crate::channels::{channels, BookChannel, PositionChannel, SubscriptionRequest, TickerChannel, TradeChannel};
 use crate::error::{FuturesError, FuturesResult};
-use crate::types::FuturesEvent;
+use crate::instruments::InstrumentRegistry;
+use crate::order_tracker::OrderTracker;
+use crate::trade_tape::TradeTape;
+use crate::types::{Basis, FuturesEvent};
 use futures_util::{SinkExt, StreamExt};
 use std::sync::Arc;
 use std::time::Duration;
@@ -17,6 +20,14 @@ pub mod endpoints {
     pub const PRODUCTION: &str = "wss://futures.kraken.com/ws/v1";
     /// Demo/sandbox endpoint
     pub const DEMO: &str = "wss://demo-futures.kraken.com/ws/v1";
+    /// Production REST base URL
+    pub const REST_PRODUCTION: &str = "https://futures.kraken.com/derivatives/api/v3";
+    /// Demo/sandbox REST base URL
+    pub const REST_DEMO: &str = "https://demo-futures.kraken.com/derivatives/api/v3";
+    /// Production history API base URL, used for funding payments
+    pub const REST_HISTORY_PRODUCTION: &str = "https://futures.kraken.com/api/history/v3";
+    /// Demo/sandbox history API base URL
+    pub const REST_HISTORY_DEMO: &str = "https://demo-futures.kraken.com/api/history/v3";
 }
 
 /// Connection configuration
@@ -24,6 +35,8 @@ pub mod endpoints {
 pub struct FuturesConfig {
     /// WebSocket endpoint URL
     pub endpoint: String,
+    /// REST base URL, used for contract specs
+    pub rest_endpoint: String,
     /// Credentials (optional for public channels)
     pub credentials: Option<FuturesCredentials>,
     /// Products to subscribe to
@@ -38,12 +51,15 @@ pub struct FuturesConfig {
     pub reconnect_delay: Duration,
     /// Heartbeat interval
     pub heartbeat_interval: Duration,
+    /// Subscribe to the dedicated mark and index price feeds
+    pub price_feeds: bool,
 }
 
 impl Default for FuturesConfig {
     fn default() -> Self {
         Self {
             endpoint: endpoints::PRODUCTION.to_string(),
+            rest_endpoint: endpoints::REST_PRODUCTION.to_string(),
             credentials: None,
             products: vec!["PI_XBTUSD".to_string()],
             book_depth: 25,
@@ -51,6 +67,7 @@ impl Default for FuturesConfig {
             max_reconnect_attempts: 10,
             reconnect_delay: Duration::from_secs(1),
             heartbeat_interval: Duration::from_secs(30),
+            price_feeds: false,
         }
     }
 }
@@ -67,9 +84,16 @@ impl FuturesConfig {
         self
     }
 
-    /// Use demo/sandbox endpoint
+    /// Set the REST base URL
+    pub fn with_rest_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.rest_endpoint = endpoint.into();
+        self
+    }
+
+    /// Use demo/sandbox endpoints
     pub fn demo(mut self) -> Self {
         self.endpoint = endpoints::DEMO.to_string();
+        self.rest_endpoint = endpoints::REST_DEMO.to_string();
         self
     }
 
@@ -97,6 +121,15 @@ impl FuturesConfig {
         self
     }
 
+    /// Subscribe to the mark and index price feeds in addition to ticker
+    ///
+    /// Mark and index prices are also taken from ticker updates; the
+    /// dedicated feeds deliver them without the rest of the ticker payload.
+    pub fn with_price_feeds(mut self) -> Self {
+        self.price_feeds = true;
+        self
+    }
+
     /// Disable auto reconnection
     pub fn without_reconnect(mut self) -> Self {
         self.auto_reconnect = false;
@@ -136,7 +169,10 @@ pub struct FuturesConnection {
     book_channel: Arc<RwLock<BookChannel>>,
     ticker_channel: Arc<RwLock<TickerChannel>>,
     trade_channel: Arc<TradeChannel>,
+    trade_tape: Arc<RwLock<TradeTape>>,
     position_channel: Arc<RwLock<PositionChannel>>,
+    order_tracker: Arc<RwLock<OrderTracker>>,
+    instruments: Arc<RwLock<InstrumentRegistry>>,
 }
 
 impl FuturesConnection {
@@ -148,7 +184,10 @@ impl FuturesConnection {
             book_channel: Arc::new(RwLock::new(BookChannel::new(config.book_depth))),
             ticker_channel: Arc::new(RwLock::new(TickerChannel::new())),
             trade_channel: Arc::new(TradeChannel::new()),
+            trade_tape: Arc::new(RwLock::new(TradeTape::new().with_default_windows())),
             position_channel: Arc::new(RwLock::new(PositionChannel::new())),
+            order_tracker: Arc::new(RwLock::new(OrderTracker::new())),
+            instruments: Arc::new(RwLock::new(InstrumentRegistry::new())),
             config,
             state: Arc::new(RwLock::new(ConnectionState::Disconnected)),
             auth_state: Arc::new(RwLock::new(AuthState::Unauthenticated)),
@@ -305,6 +344,15 @@ impl FuturesConnection {
         let trade_sub = SubscriptionRequest::new("trade", products.clone());
         self.send_subscription(write, trade_sub).await?;
 
+        // Subscribe to mark and index price feeds
+        if self.config.price_feeds {
+            let mark_sub = SubscriptionRequest::new(channels::MARK_PRICE, products.clone());
+            self.send_subscription(write, mark_sub).await?;
+
+            let index_sub = SubscriptionRequest::new(channels::INDEX_PRICE, products.clone());
+            self.send_subscription(write, index_sub).await?;
+        }
+
         // Subscribe to heartbeat
         let heartbeat_sub = SubscriptionRequest::new("heartbeat", vec![]);
         self.send_subscription(write, heartbeat_sub).await?;
@@ -341,9 +389,24 @@ impl FuturesConnection {
         if let Some(feed) = value.get("feed").and_then(|v| v.as_str()) {
             match feed {
                 "ticker" => {
-                    if let Ok(ticker) = serde_json::from_value(value["data"].clone()) {
+                    if let Ok(ticker) = serde_json::from_value::<crate::types::FuturesTicker>(value["data"].clone()) {
+                        let product_id = ticker.product_id.clone();
                         let event = self.ticker_channel.write().await.process_ticker(ticker);
-                        let _ = self.event_tx.send(event).await;
+                        self.emit_with_basis(event, &product_id).await;
+                    }
+                }
+                "mark_price" => {
+                    if let Ok(mark) = serde_json::from_value::<crate::types::MarkPrice>(value.clone()) {
+                        let product_id = mark.product_id.clone();
+                        let event = self.ticker_channel.write().await.process_mark_price(mark);
+                        self.emit_with_basis(event, &product_id).await;
+                    }
+                }
+                "index_price" => {
+                    if let Ok(index) = serde_json::from_value::<crate::types::IndexPrice>(value.clone()) {
+                        let product_id = index.product_id.clone();
+                        let event = self.ticker_channel.write().await.process_index_price(index);
+                        self.emit_with_basis(event, &product_id).await;
                     }
                 }
                 "book_snapshot" => {
@@ -361,10 +424,33 @@ impl FuturesConnection {
                 }
                 "trade" => {
                     if let Ok(trade) = serde_json::from_value(value.clone()) {
+                        self.trade_tape.write().await.record(&trade);
                         let event = self.trade_channel.process_trade(trade);
                         let _ = self.event_tx.send(event).await;
                     }
                 }
+                "open_orders_snapshot" => {
+                    if let Ok(snapshot) = serde_json::from_value(value.clone()) {
+                        self.emit_order_event(FuturesEvent::OpenOrders(snapshot)).await;
+                    }
+                }
+                "open_orders" => {
+                    if let Ok(order) = serde_json::from_value(value["order"].clone()) {
+                        self.emit_order_event(FuturesEvent::OpenOrderUpdate(order)).await;
+                    }
+                }
+                "fills_snapshot" => {
+                    if let Ok(snapshot) = serde_json::from_value(value.clone()) {
+                        self.emit_order_event(FuturesEvent::Fills(snapshot)).await;
+                    }
+                }
+                "fills" => {
+                    if let Ok(fills) = serde_json::from_value::<Vec<crate::types::Fill>>(value["fills"].clone()) {
+                        for fill in fills {
+                            self.emit_order_event(FuturesEvent::Fill(fill)).await;
+                        }
+                    }
+                }
                 "heartbeat" => {
                     let _ = self.event_tx.send(FuturesEvent::Heartbeat).await;
                 }
@@ -405,6 +491,21 @@ impl FuturesConnection {
         Ok(())
     }
 
+    /// Send a price event followed by a basis event if the basis moved
+    async fn emit_with_basis(&self, event: FuturesEvent, product_id: &str) {
+        let basis = self.ticker_channel.write().await.update_basis(product_id);
+        let _ = self.event_tx.send(event).await;
+        if let Some(basis) = basis {
+            let _ = self.event_tx.send(basis).await;
+        }
+    }
+
+    /// Update the order tracker, then forward the event
+    async fn emit_order_event(&self, event: FuturesEvent) {
+        self.order_tracker.write().await.handle_event(&event);
+        let _ = self.event_tx.send(event).await;
+    }
+
     // Public API methods
 
     /// Subscribe to orderbook
@@ -433,11 +534,106 @@ impl FuturesConnection {
         self.ticker_channel.read().await.ticker(product_id).cloned()
     }
 
+    /// Get the latest mark price for a product
+    pub async fn mark_price(&self, product_id: &str) -> Option<rust_decimal::Decimal> {
+        self.ticker_channel.read().await.mark_price(product_id)
+    }
+
+    /// Get the latest index price for a product
+    pub async fn index_price(&self, product_id: &str) -> Option<rust_decimal::Decimal> {
+        self.ticker_channel.read().await.index_price(product_id)
+    }
+
+    /// Get the latest basis (mark - index) and premium for a product
+    pub async fn basis(&self, product_id: &str) -> Option<Basis> {
+        self.ticker_channel.read().await.basis(product_id).cloned()
+    }
+
     /// Get last trade price for a product
     pub async fn last_price(&self, product_id: &str) -> Option<rust_decimal::Decimal> {
         self.trade_channel.last_price(product_id)
     }
 
+    /// Cumulative volume delta of a product since its first trade
+    pub async fn cvd(&self, product_id: &str) -> Option<rust_decimal::Decimal> {
+        self.trade_tape.read().await.cvd(product_id)
+    }
+
+    /// Shared trade tape fed by the trade feed
+    ///
+    /// Tracks CVD, liquidations and buy/sell imbalance over 10s, 1m and 5m.
+    pub fn trade_tape(&self) -> Arc<RwLock<TradeTape>> {
+        Arc::clone(&self.trade_tape)
+    }
+
+    /// Shared order tracker fed by the open_orders and fills feeds
+    ///
+    /// Register submissions with
+    /// [`OrderTracker::track_submission`] using the `cliOrdId` sent with the
+    /// order so feed updates are correlated to them.
+    pub fn order_tracker(&self) -> Arc<RwLock<OrderTracker>> {
+        Arc::clone(&self.order_tracker)
+    }
+
+    /// Fetch contract specs from the REST API, replacing any loaded before
+    ///
+    /// Returns the number of contracts loaded.
+    pub async fn load_instruments(&self) -> FuturesResult<usize> {
+        let registry = InstrumentRegistry::fetch(&self.config.rest_endpoint).await?;
+        let count = registry.len();
+        self.instruments.write().await.replace(registry);
+        info!("Loaded {} futures contract specs", count);
+        Ok(count)
+    }
+
+    /// Shared contract spec registry (empty until [`load_instruments`](Self::load_instruments))
+    pub fn instruments(&self) -> Arc<RwLock<InstrumentRegistry>> {
+        Arc::clone(&self.instruments)
+    }
+
+    /// Check an order against its product's contract spec
+    ///
+    /// See [`InstrumentRegistry::validate_order`].
+    pub async fn validate_order(
+        &self,
+        product_id: &str,
+        price: Option<rust_decimal::Decimal>,
+        qty: rust_decimal::Decimal,
+    ) -> FuturesResult<()> {
+        self.instruments.read().await.validate_order(product_id, price, qty)
+    }
+
+    /// Round a price to the product's tick size
+    pub async fn round_price(&self, product_id: &str, price: rust_decimal::Decimal) -> rust_decimal::Decimal {
+        self.instruments.read().await.round_price(product_id, price)
+    }
+
+    /// Round a size down to the product's lot size
+    pub async fn round_qty(&self, product_id: &str, qty: rust_decimal::Decimal) -> rust_decimal::Decimal {
+        self.instruments.read().await.round_qty(product_id, qty)
+    }
+
+    /// Validate an outgoing order and register it with the order tracker
+    ///
+    /// Orders that fail validation, including every order before
+    /// [`load_instruments`](Self::load_instruments) has succeeded, are not
+    /// tracked; see [`OrderTracker::track_submission`].
+    pub async fn track_submission(
+        &self,
+        cli_ord_id: &str,
+        product_id: &str,
+        side: crate::types::TradeSide,
+        qty: rust_decimal::Decimal,
+        limit_price: Option<rust_decimal::Decimal>,
+    ) -> FuturesResult<()> {
+        self.validate_order(product_id, limit_price, qty).await?;
+        self.order_tracker
+            .write()
+            .await
+            .track_submission(cli_ord_id, product_id, side, qty, limit_price);
+        Ok(())
+    }
+
     /// Get total trade count
     pub fn trade_count(&self) -> u64 {
         self.trade_channel.trade_count()
@@ -476,10 +672,86 @@ mod tests {
             .demo();
 
         assert_eq!(config.endpoint, endpoints::DEMO);
+        assert_eq!(config.rest_endpoint, endpoints::REST_DEMO);
         assert_eq!(config.products.len(), 3); // Default + 2 added
         assert_eq!(config.book_depth, 50);
     }
 
+    #[tokio::test]
+    async fn test_price_feed_messages() {
+        let mut conn = FuturesConnection::new(FuturesConfig::new().with_price_feeds());
+        let mut events = conn.take_event_receiver().unwrap();
+        assert!(conn.config.price_feeds);
+
+        conn.handle_message(r#"{"feed":"index_price","product_id":"PI_XBTUSD","index_price":"50000","time":"1"}"#)
+            .await
+            .unwrap();
+        conn.handle_message(r#"{"feed":"mark_price","product_id":"PI_XBTUSD","mark_price":"50050","time":"1"}"#)
+            .await
+            .unwrap();
+
+        assert!(matches!(events.recv().await, Some(FuturesEvent::IndexPrice(_))));
+        assert!(matches!(events.recv().await, Some(FuturesEvent::MarkPrice(_))));
+        let Some(FuturesEvent::Basis(basis)) = events.recv().await else {
+            panic!("expected basis event");
+        };
+        assert_eq!(basis.basis, rust_decimal::Decimal::from(50));
+
+        assert_eq!(conn.mark_price("PI_XBTUSD").await, Some(rust_decimal::Decimal::from(50050)));
+        assert_eq!(conn.index_price("PI_XBTUSD").await, Some(rust_decimal::Decimal::from(50000)));
+        assert_eq!(conn.basis("PI_XBTUSD").await, Some(basis));
+    }
+
+    #[tokio::test]
+    async fn test_order_feeds_update_tracker() {
+        let mut conn = FuturesConnection::new(FuturesConfig::new());
+        let mut events = conn.take_event_receiver().unwrap();
+        conn.order_tracker().write().await.track_submission(
+            "cli-1",
+            "PI_XBTUSD",
+            crate::types::TradeSide::Buy,
+            rust_decimal::Decimal::from(2),
+            None,
+        );
+
+        conn.handle_message(
+            r#"{"feed":"fills","fills":[{"instrument":"PI_XBTUSD","order_id":"o1","cli_ord_id":"cli-1","fill_id":"f1","time":"1","side":"buy","price":"50000","qty":"2","fill_type":"taker"}]}"#,
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(events.recv().await, Some(FuturesEvent::Fill(_))));
+        let tracker = conn.order_tracker();
+        let tracker = tracker.read().await;
+        let order = tracker.get_by_cli_ord_id("cli-1").unwrap();
+        assert_eq!(order.order_id.as_deref(), Some("o1"));
+        assert_eq!(order.state, crate::order_tracker::LifecycleState::Filled);
+    }
+
+    #[tokio::test]
+    async fn test_submissions_are_checked_against_specs() {
+        use rust_decimal_macros::dec;
+
+        let conn = FuturesConnection::new(FuturesConfig::new());
+        let side = crate::types::TradeSide::Buy;
+        let unchecked = conn.track_submission("cli-0", "PI_XBTUSD", side, dec!(10), Some(dec!(50000.5))).await;
+        assert!(matches!(unchecked, Err(FuturesError::SpecsUnavailable)));
+
+        let specs = r#"{"result":"success","instruments":[{"symbol":"PI_XBTUSD","tickSize":0.5,"contractSize":1,"tradeable":true}]}"#;
+        conn.instruments().write().await.replace(InstrumentRegistry::from_json(specs).unwrap());
+        assert_eq!(conn.round_price("PI_XBTUSD", dec!(50000.2)).await, dec!(50000));
+
+        let rejected = conn.track_submission("cli-1", "PI_XBTUSD", side, dec!(10), Some(dec!(50000.2))).await;
+        assert!(matches!(rejected, Err(FuturesError::InvalidOrder { .. })));
+        conn.track_submission("cli-2", "PI_XBTUSD", side, dec!(10), Some(dec!(50000.5))).await.unwrap();
+
+        let tracker = conn.order_tracker();
+        let tracker = tracker.read().await;
+        assert!(tracker.get_by_cli_ord_id("cli-0").is_none());
+        assert!(tracker.get_by_cli_ord_id("cli-1").is_none());
+        assert!(tracker.get_by_cli_ord_id("cli-2").is_some());
+    }
+
     #[test]
     fn test_connection_state() {
         assert_eq!(ConnectionState::default(), ConnectionState::Disconnected);
@@ -58,6 +58,23 @@ pub enum FuturesError {
     /// Channel send error
     #[error("Channel closed")]
     ChannelClosed,
+
+    /// REST request failed
+    #[error("HTTP error: {0}")]
+    Http(String),
+
+    /// Order breaks its contract's specification
+    #[error("Invalid order for {product_id}: {reason}")]
+    InvalidOrder {
+        /// Product the order is for
+        product_id: String,
+        /// What is wrong with it
+        reason: String,
+    },
+
+    /// Contract specs haven't been loaded, so orders can't be checked
+    #[error("Contract specs unavailable; load instruments before validating orders")]
+    SpecsUnavailable,
 }
 
 impl FuturesError {
@@ -88,7 +105,13 @@ impl FuturesError {
                 delay_ms: 1000,
             },
             Self::Json(_) | Self::InvalidMessage(_) => RecoveryStrategy::Skip,
-            Self::EnvVarNotSet(_) | Self::ChannelClosed => RecoveryStrategy::Fatal,
+            Self::Http(_) => RecoveryStrategy::Retry {
+                max_attempts: 3,
+                delay_ms: 1000,
+            },
+            Self::EnvVarNotSet(_) | Self::ChannelClosed | Self::InvalidOrder { .. } | Self::SpecsUnavailable => {
+                RecoveryStrategy::Fatal
+            }
         }
     }
 
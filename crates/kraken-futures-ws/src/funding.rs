@@ -0,0 +1,380 @@
+//! Historical funding payments
+//!
+//! Perpetual positions pay or receive funding every hour. The rate is on
+//! the ticker feed, but what the account actually paid is only in the
+//! futures history API's account log, as `funding rate change` entries.
+//! [`FundingClient`] pages through that log and returns them as
+//! [`FundingPayment`]s, so execution analytics can charge real funding to
+//! a position instead of estimating it from the rate.
+//!
+//! The log is paged newest first by booking time. Several entries can share
+//! a millisecond, so each page after the first asks for entries up to and
+//! including the oldest millisecond already seen, and entries fetched twice
+//! are dropped by their log id.
+//!
+//! Requests are signed with [`FuturesCredentials::sign_rest`]. See
+//! [`endpoints`](crate::connection::endpoints) for the history base URLs.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_futures_ws::funding::{net_funding, FundingPayment};
+//! use rust_decimal_macros::dec;
+//!
+//! let payments = FundingPayment::from_account_log(r#"{"logs":[
+//!     {"date":"2024-03-01T08:00:00.000Z","asset":"usd","info":"funding rate change",
+//!      "margin_account":"flex","contract":"pf_xbtusd","funding_rate":0.0000125,
+//!      "realized_funding":-0.42},
+//!     {"date":"2024-03-01T07:59:12.000Z","asset":"usd","info":"futures trade",
+//!      "margin_account":"flex","contract":"pf_xbtusd","fee":0.31}
+//! ]}"#).unwrap();
+//!
+//! assert_eq!(payments.len(), 1);
+//! assert_eq!(payments[0].contract, "PF_XBTUSD");
+//! assert_eq!(net_funding(&payments, "PF_XBTUSD"), dec!(-0.42));
+//! ```
+
+use crate::auth::FuturesCredentials;
+use crate::connection::endpoints;
+use crate::error::{FuturesError, FuturesResult};
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::future::Future;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::debug;
+
+/// Account log path, relative to the history base URL
+pub const ACCOUNT_LOG_PATH: &str = "/account-log";
+
+/// Account log `info` value of funding entries
+pub const FUNDING_INFO: &str = "funding rate change";
+
+/// Maximum entries the account log returns per request
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// One funding payment, as booked in the account log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FundingPayment {
+    /// Account log entry ID, if reported
+    pub id: Option<u64>,
+    /// When the payment was booked (ISO 8601)
+    pub date: String,
+    /// Contract the funding was for, uppercased (e.g. `PF_XBTUSD`)
+    pub contract: String,
+    /// Asset the payment was settled in
+    pub asset: String,
+    /// Margin account charged or credited
+    pub margin_account: String,
+    /// Funding rate applied, if reported
+    pub funding_rate: Option<Decimal>,
+    /// Amount received (positive) or paid (negative)
+    pub amount: Decimal,
+}
+
+/// Raw account log entry
+#[derive(Debug, Deserialize)]
+struct LogEntry {
+    #[serde(default)]
+    id: Option<u64>,
+    date: String,
+    #[serde(default)]
+    asset: String,
+    info: String,
+    #[serde(default)]
+    margin_account: String,
+    #[serde(default)]
+    contract: Option<String>,
+    #[serde(default)]
+    funding_rate: Option<Decimal>,
+    #[serde(default)]
+    realized_funding: Option<Decimal>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AccountLogResponse {
+    #[serde(default)]
+    logs: Vec<LogEntry>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+impl FundingPayment {
+    /// Extract the funding payments from an account log response
+    ///
+    /// Entries other than funding, and funding entries without a contract
+    /// or amount, are skipped.
+    pub fn from_account_log(json: &str) -> FuturesResult<Vec<Self>> {
+        Ok(AccountLogResponse::parse(json)?.funding_payments())
+    }
+}
+
+impl AccountLogResponse {
+    fn parse(json: &str) -> FuturesResult<Self> {
+        let response: Self = serde_json::from_str(json)?;
+        match response.error {
+            Some(error) => Err(FuturesError::from_api_error(&error)),
+            None => Ok(response),
+        }
+    }
+
+    /// Booking time of the oldest entry, funding or not
+    fn oldest(&self) -> Option<DateTime<Utc>> {
+        self.logs
+            .iter()
+            .filter_map(|entry| DateTime::parse_from_rfc3339(&entry.date).ok())
+            .map(|date| date.with_timezone(&Utc))
+            .min()
+    }
+
+    /// Drop entries whose ID is in `seen`, recording the rest
+    ///
+    /// Returns how many entries were kept.
+    fn retain_unseen(&mut self, seen: &mut HashSet<u64>) -> usize {
+        self.logs.retain(|entry| entry.id.is_none_or(|id| seen.insert(id)));
+        self.logs.len()
+    }
+
+    fn funding_payments(self) -> Vec<FundingPayment> {
+        self.logs
+            .into_iter()
+            .filter(|entry| entry.info == FUNDING_INFO)
+            .filter_map(|entry| {
+                Some(FundingPayment {
+                    id: entry.id,
+                    contract: entry.contract?.to_uppercase(),
+                    amount: entry.realized_funding?,
+                    date: entry.date,
+                    asset: entry.asset,
+                    margin_account: entry.margin_account,
+                    funding_rate: entry.funding_rate,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Net funding of a contract (case-insensitive): received minus paid
+pub fn net_funding(payments: &[FundingPayment], contract: &str) -> Decimal {
+    payments
+        .iter()
+        .filter(|payment| payment.contract.eq_ignore_ascii_case(contract))
+        .map(|payment| payment.amount)
+        .sum()
+}
+
+/// Client for the futures history API's funding entries
+///
+/// # Example
+///
+/// ```no_run
+/// use kraken_futures_ws::{FundingClient, FuturesCredentials};
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = FundingClient::new(FuturesCredentials::from_env()?);
+/// // Payments of the last 24 hours
+/// let since = std::time::SystemTime::now()
+///     .duration_since(std::time::UNIX_EPOCH)?
+///     .as_millis() as u64 - 86_400_000;
+/// for payment in client.payments(Some(since), None).await? {
+///     println!("{} {} {}", payment.date, payment.contract, payment.amount);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FundingClient {
+    credentials: FuturesCredentials,
+    client: Client,
+    base_url: String,
+}
+
+impl FundingClient {
+    /// Create a client against [`endpoints::REST_HISTORY_PRODUCTION`]
+    pub fn new(credentials: FuturesCredentials) -> Self {
+        Self {
+            credentials,
+            client: Client::new(),
+            base_url: endpoints::REST_HISTORY_PRODUCTION.to_string(),
+        }
+    }
+
+    /// Use another history base URL (demo, a proxy or a mock server)
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Use an existing HTTP client
+    pub fn with_http_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Funding payments booked in `[since, before)`, oldest first
+    ///
+    /// Bounds are Unix milliseconds; `None` leaves that side open. Pages
+    /// are fetched until the log runs out.
+    pub async fn payments(&self, since: Option<u64>, before: Option<u64>) -> FuturesResult<Vec<FundingPayment>> {
+        collect_payments(since, before, |query| async move { self.get(ACCOUNT_LOG_PATH, &query).await }).await
+    }
+
+    /// Signed GET against the history API
+    async fn get(&self, path: &str, query: &str) -> FuturesResult<String> {
+        let base = self.base_url.trim_end_matches('/');
+        let nonce = nonce();
+        let authent = self.credentials.sign_rest(&signing_path(base, path), &nonce, query);
+        let url = if query.is_empty() {
+            format!("{}{}", base, path)
+        } else {
+            format!("{}{}?{}", base, path, query)
+        };
+        self.client
+            .get(url)
+            .header("APIKey", self.credentials.api_key())
+            .header("Nonce", nonce)
+            .header("Authent", authent)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| FuturesError::Http(e.to_string()))?
+            .text()
+            .await
+            .map_err(|e| FuturesError::Http(e.to_string()))
+    }
+}
+
+/// Page through the account log, `fetch` returning the body for a query
+async fn collect_payments<F, Fut>(since: Option<u64>, before: Option<u64>, mut fetch: F) -> FuturesResult<Vec<FundingPayment>>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = FuturesResult<String>>,
+{
+    let mut payments = Vec::new();
+    let mut seen = HashSet::new();
+    let mut before = before;
+    loop {
+        let body = fetch(account_log_query(since, before)).await?;
+        let mut page = AccountLogResponse::parse(&body)?;
+        let full = page.logs.len() >= MAX_PAGE_SIZE;
+        let oldest = page.oldest().map(|date| date.timestamp_millis() as u64);
+        let new = page.retain_unseen(&mut seen);
+        let funding = page.funding_payments();
+        debug!(funding = funding.len(), new, "Fetched account log page");
+        payments.extend(funding);
+
+        // `before` is exclusive; one past the oldest millisecond keeps the
+        // entries that share it. A page with nothing new can't move on.
+        match oldest.map(|oldest| oldest + 1) {
+            Some(next) if full && new > 0 && before != Some(next) => before = Some(next),
+            _ => break,
+        }
+    }
+    payments.sort_by(|a, b| a.date.cmp(&b.date).then(a.id.cmp(&b.id)));
+    payments.dedup();
+    Ok(payments)
+}
+
+/// Query string for one account log page, newest entries first
+fn account_log_query(since: Option<u64>, before: Option<u64>) -> String {
+    let mut params = vec![format!("count={}", MAX_PAGE_SIZE), "sort=desc".to_string()];
+    params.extend(since.map(|since| format!("since={}", since)));
+    params.extend(before.map(|before| format!("before={}", before)));
+    params.push("info=funding%20rate%20change".to_string());
+    params.join("&")
+}
+
+/// Path the signature is computed over: the URL path without `/derivatives`
+fn signing_path(base_url: &str, path: &str) -> String {
+    let base_path = base_url
+        .split_once("://")
+        .map_or(base_url, |(_, rest)| rest.find('/').map_or("", |i| &rest[i..]));
+    let full = format!("{}{}", base_path, path);
+    full.strip_prefix("/derivatives").map(str::to_string).unwrap_or(full)
+}
+
+fn nonce() -> String {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis())
+        .unwrap_or_default()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_funding_entries_and_request_signing_path() {
+        let log = AccountLogResponse::parse(
+            r#"{"accountUid":"f7d5571c","logs":[
+                {"date":"2024-03-01T09:00:00.000Z","asset":"usd","info":"funding rate change",
+                 "margin_account":"flex","contract":"pf_ethusd","funding_rate":-0.00002,"realized_funding":0.08},
+                {"date":"2024-03-01T08:00:00.000Z","asset":"usd","info":"funding rate change",
+                 "margin_account":"flex","contract":"pf_xbtusd","funding_rate":0.0000125,"realized_funding":-0.42},
+                {"date":"2024-03-01T07:00:00.000Z","asset":"usd","info":"funding rate change",
+                 "margin_account":"flex","contract":"pf_xbtusd","realized_funding":-0.40},
+                {"date":"2024-03-01T06:30:00.500Z","asset":"usd","info":"futures trade",
+                 "margin_account":"flex","contract":"pf_xbtusd","fee":0.31}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(log.oldest().unwrap().timestamp_millis(), 1_709_274_600_500);
+
+        let payments = log.funding_payments();
+        assert_eq!(payments.len(), 3);
+        assert_eq!(payments[1].funding_rate, Some(dec!(0.0000125)));
+        assert_eq!(net_funding(&payments, "pf_xbtusd"), dec!(-0.82));
+        assert_eq!(net_funding(&payments, "PF_ETHUSD"), dec!(0.08));
+
+        assert!(FundingPayment::from_account_log(r#"{"result":"error","error":"apiLimitExceeded"}"#).is_err());
+
+        assert_eq!(
+            signing_path(endpoints::REST_HISTORY_PRODUCTION, ACCOUNT_LOG_PATH),
+            "/api/history/v3/account-log"
+        );
+        assert_eq!(signing_path("https://futures.kraken.com/derivatives/api/v3", "/fills"), "/api/v3/fills");
+        assert_eq!(
+            account_log_query(Some(1), None),
+            "count=500&sort=desc&since=1&info=funding%20rate%20change"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pages_keep_entries_sharing_the_boundary_millisecond() {
+        const BASE_MS: i64 = 1_709_251_200_000;
+        let entry = |id: u64, ms: i64| {
+            let date = DateTime::from_timestamp_millis(BASE_MS + ms)
+                .unwrap()
+                .to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+            format!(
+                r#"{{"id":{id},"date":"{date}","asset":"usd","info":"funding rate change","margin_account":"flex","contract":"pf_xbtusd","realized_funding":-1}}"#
+            )
+        };
+        let page = |entries: Vec<String>| format!(r#"{{"logs":[{}]}}"#, entries.join(","));
+        // Entries 499, 500 and 501 share a millisecond; the first page ends at 501
+        let boundary = 501_000;
+        let first = page((501..=1000).rev().map(|id| entry(id, if id <= 501 { boundary } else { id as i64 * 1000 })).collect());
+        let second = page(vec![entry(501, boundary), entry(500, boundary), entry(499, boundary), entry(498, 498_000)]);
+
+        let queries = std::sync::Mutex::new(Vec::new());
+        let payments = collect_payments(None, None, |query| {
+            let body = if query.contains("before=") { second.clone() } else { first.clone() };
+            queries.lock().unwrap().push(query);
+            async move { Ok(body) }
+        })
+        .await
+        .unwrap();
+
+        let queries = queries.into_inner().unwrap();
+        assert_eq!(queries.len(), 2);
+        assert!(queries[1].contains(&format!("before={}", BASE_MS + boundary + 1)));
+        assert_eq!(payments.len(), 503);
+        let ids: Vec<u64> = payments.iter().filter_map(|p| p.id).take(4).collect();
+        assert_eq!(ids, vec![498, 499, 500, 501]);
+    }
+}
@@ -0,0 +1,321 @@
+//! Futures contract specifications and order validation
+//!
+//! Tick size, contract size, size precision and leverage limits differ per
+//! product and change when Kraken lists new contracts. [`InstrumentRegistry`]
+//! loads them from the futures REST `instruments` endpoint (or from a saved
+//! response with [`InstrumentRegistry::from_json`]) so orders can be checked
+//! and rounded before they are sent.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_futures_ws::instruments::InstrumentRegistry;
+//! use rust_decimal_macros::dec;
+//!
+//! let registry = InstrumentRegistry::from_json(r#"{"result":"success","instruments":[
+//!     {"symbol":"PF_XBTUSD","type":"flexible_futures","tickSize":1,"contractSize":1,
+//!      "contractValueTradePrecision":4,"tradeable":true,
+//!      "marginLevels":[{"contracts":0,"initialMargin":0.02,"maintenanceMargin":0.01}]}
+//! ]}"#).unwrap();
+//!
+//! let spec = registry.get("PF_XBTUSD").unwrap();
+//! assert_eq!(spec.max_leverage(), Some(dec!(50)));
+//! assert_eq!(spec.round_price(dec!(50000.6)), dec!(50001));
+//! assert_eq!(spec.round_qty(dec!(0.123456)), dec!(0.1234));
+//! assert!(registry.validate_order("PF_XBTUSD", Some(dec!(50000.5)), dec!(0.1)).is_err());
+//! ```
+
+use crate::error::{FuturesError, FuturesResult};
+use kraken_types::formatting;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// One initial/maintenance margin tier
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MarginLevel {
+    /// Position size (contracts) from which the tier applies
+    pub contracts: Decimal,
+    /// Initial margin as a fraction of notional
+    pub initial_margin: Decimal,
+    /// Maintenance margin as a fraction of notional
+    pub maintenance_margin: Decimal,
+}
+
+/// Specification of one futures contract
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContractSpec {
+    /// Product ID (e.g. `PF_XBTUSD`)
+    pub symbol: String,
+    /// Contract type (`flexible_futures`, `futures_inverse`, ...)
+    #[serde(rename = "type", default)]
+    pub contract_type: String,
+    /// Minimum price increment
+    pub tick_size: Decimal,
+    /// Underlying amount per contract
+    #[serde(default = "one")]
+    pub contract_size: Decimal,
+    /// Decimal places allowed in order sizes (negative for multiples of 10)
+    #[serde(rename = "contractValueTradePrecision", default)]
+    pub size_precision: i32,
+    /// Largest position allowed, in contracts
+    #[serde(default)]
+    pub max_position_size: Option<Decimal>,
+    /// Margin tiers, smallest position first
+    #[serde(default)]
+    pub margin_levels: Vec<MarginLevel>,
+    /// Whether the contract accepts orders
+    #[serde(default)]
+    pub tradeable: bool,
+    /// Whether only post-only orders are accepted
+    #[serde(default)]
+    pub post_only: bool,
+}
+
+fn one() -> Decimal {
+    Decimal::ONE
+}
+
+impl ContractSpec {
+    /// Smallest order size increment
+    pub fn lot_size(&self) -> Decimal {
+        let mut lot = Decimal::ONE;
+        if self.size_precision >= 0 {
+            lot.set_scale(self.size_precision.min(28) as u32).unwrap_or_default();
+        } else {
+            for _ in 0..self.size_precision.unsigned_abs().min(28) {
+                lot *= Decimal::TEN;
+            }
+        }
+        lot
+    }
+
+    /// Leverage allowed on the smallest margin tier
+    pub fn max_leverage(&self) -> Option<Decimal> {
+        let initial = self.margin_levels.first()?.initial_margin;
+        (!initial.is_zero()).then(|| Decimal::ONE / initial)
+    }
+
+    /// Round a price to the nearest tick
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        formatting::round_to_increment(price, self.tick_size)
+    }
+
+    /// Round a size down to a whole number of lots
+    pub fn round_qty(&self, qty: Decimal) -> Decimal {
+        formatting::floor_to_increment(qty, self.lot_size())
+    }
+
+    /// Check an order's price and size against the spec
+    ///
+    /// `price` is None for market orders.
+    pub fn validate_order(&self, price: Option<Decimal>, qty: Decimal) -> FuturesResult<()> {
+        let invalid = |reason: String| FuturesError::InvalidOrder {
+            product_id: self.symbol.clone(),
+            reason,
+        };
+        if !self.tradeable {
+            return Err(invalid("contract is not tradeable".to_string()));
+        }
+        if qty <= Decimal::ZERO {
+            return Err(invalid(format!("size {} must be positive", qty)));
+        }
+        if !(qty / self.lot_size()).fract().is_zero() {
+            return Err(invalid(format!("size {} is not a multiple of {}", qty, self.lot_size())));
+        }
+        if let Some(max) = self.max_position_size {
+            if qty > max {
+                return Err(invalid(format!("size {} exceeds the maximum position of {}", qty, max)));
+            }
+        }
+        if let Some(price) = price {
+            if price <= Decimal::ZERO {
+                return Err(invalid(format!("price {} must be positive", price)));
+            }
+            if self.tick_size > Decimal::ZERO && !(price / self.tick_size).fract().is_zero() {
+                return Err(invalid(format!("price {} is not a multiple of tick {}", price, self.tick_size)));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Response body of the `instruments` endpoint
+#[derive(Debug, Deserialize)]
+struct InstrumentsResponse {
+    result: String,
+    #[serde(default)]
+    instruments: Vec<serde_json::Value>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Contract specs by product ID
+#[derive(Debug, Clone, Default)]
+pub struct InstrumentRegistry {
+    specs: HashMap<String, ContractSpec>,
+}
+
+impl InstrumentRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parse an `instruments` response
+    ///
+    /// Entries without a tick size (indices, spot references) are skipped.
+    pub fn from_json(json: &str) -> FuturesResult<Self> {
+        let response: InstrumentsResponse = serde_json::from_str(json)?;
+        if response.result != "success" {
+            return Err(FuturesError::from_api_error(response.error.as_deref().unwrap_or("instruments request failed")));
+        }
+        let mut registry = Self::new();
+        for instrument in response.instruments {
+            if let Ok(spec) = serde_json::from_value::<ContractSpec>(instrument) {
+                registry.insert(spec);
+            }
+        }
+        Ok(registry)
+    }
+
+    /// Fetch all specs from a futures REST base URL
+    ///
+    /// See [`endpoints`](crate::connection::endpoints) for the base URLs.
+    pub async fn fetch(rest_endpoint: &str) -> FuturesResult<Self> {
+        let url = format!("{}/instruments", rest_endpoint.trim_end_matches('/'));
+        let body = reqwest::get(&url)
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| FuturesError::Http(e.to_string()))?
+            .text()
+            .await
+            .map_err(|e| FuturesError::Http(e.to_string()))?;
+        Self::from_json(&body)
+    }
+
+    /// Add or replace a spec
+    pub fn insert(&mut self, spec: ContractSpec) {
+        self.specs.insert(spec.symbol.to_uppercase(), spec);
+    }
+
+    /// Replace every spec with another registry's
+    pub fn replace(&mut self, other: InstrumentRegistry) {
+        self.specs = other.specs;
+    }
+
+    /// Spec for a product (case-insensitive)
+    pub fn get(&self, product_id: &str) -> Option<&ContractSpec> {
+        self.specs.get(&product_id.to_uppercase())
+    }
+
+    /// Product IDs, sorted
+    pub fn symbols(&self) -> Vec<&str> {
+        let mut symbols: Vec<_> = self.specs.values().map(|spec| spec.symbol.as_str()).collect();
+        symbols.sort_unstable();
+        symbols
+    }
+
+    /// Number of specs
+    pub fn len(&self) -> usize {
+        self.specs.len()
+    }
+
+    /// Whether no specs are loaded
+    pub fn is_empty(&self) -> bool {
+        self.specs.is_empty()
+    }
+
+    /// Check an order against its product's spec
+    ///
+    /// Fails for products missing from a loaded registry, and with
+    /// [`FuturesError::SpecsUnavailable`] for every order while the registry
+    /// is empty (specs never loaded).
+    pub fn validate_order(&self, product_id: &str, price: Option<Decimal>, qty: Decimal) -> FuturesResult<()> {
+        if self.is_empty() {
+            return Err(FuturesError::SpecsUnavailable);
+        }
+        match self.get(product_id) {
+            Some(spec) => spec.validate_order(price, qty),
+            None => Err(FuturesError::InvalidOrder {
+                product_id: product_id.to_string(),
+                reason: "unknown product".to_string(),
+            }),
+        }
+    }
+
+    /// Round a price to the product's tick (unchanged for unknown products)
+    pub fn round_price(&self, product_id: &str, price: Decimal) -> Decimal {
+        self.get(product_id).map_or(price, |spec| spec.round_price(price))
+    }
+
+    /// Round a size down to the product's lot (unchanged for unknown products)
+    pub fn round_qty(&self, product_id: &str, qty: Decimal) -> Decimal {
+        self.get(product_id).map_or(qty, |spec| spec.round_qty(qty))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const RESPONSE: &str = r#"{
+        "result": "success",
+        "instruments": [
+            {"symbol": "PI_XBTUSD", "type": "futures_inverse", "underlying": "rr_xbtusd",
+             "tickSize": 0.5, "contractSize": 1, "tradeable": true, "maxPositionSize": 1000000,
+             "contractValueTradePrecision": 0,
+             "marginLevels": [{"contracts": 0, "initialMargin": 0.02, "maintenanceMargin": 0.01},
+                              {"contracts": 500000, "initialMargin": 0.04, "maintenanceMargin": 0.02}]},
+            {"symbol": "PF_ETHUSD", "type": "flexible_futures", "tickSize": 0.1, "contractSize": 1,
+             "tradeable": true, "contractValueTradePrecision": 3, "postOnly": false,
+             "marginLevels": [{"contracts": 0, "initialMargin": 0.1, "maintenanceMargin": 0.05}]},
+            {"symbol": "in_xbtusd", "type": "spot index", "tradeable": false}
+        ],
+        "serverTime": "2025-01-01T00:00:00.000Z"
+    }"#;
+
+    #[test]
+    fn test_parse_instruments_response() {
+        let registry = InstrumentRegistry::from_json(RESPONSE).unwrap();
+        assert_eq!(registry.symbols(), vec!["PF_ETHUSD", "PI_XBTUSD"]);
+
+        let inverse = registry.get("pi_xbtusd").unwrap();
+        assert_eq!(inverse.tick_size, dec!(0.5));
+        assert_eq!(inverse.lot_size(), dec!(1));
+        assert_eq!(inverse.max_leverage(), Some(dec!(50)));
+        let linear = registry.get("PF_ETHUSD").unwrap();
+        assert_eq!(linear.lot_size(), dec!(0.001));
+        assert_eq!(linear.max_leverage(), Some(dec!(10)));
+
+        let failed = InstrumentRegistry::from_json(r#"{"result":"error","error":"apiLimitExceeded"}"#);
+        assert!(matches!(failed, Err(FuturesError::Api { .. })));
+    }
+
+    #[test]
+    fn test_rounding_and_validation() {
+        let registry = InstrumentRegistry::from_json(RESPONSE).unwrap();
+        assert_eq!(registry.round_price("PI_XBTUSD", dec!(50000.3)), dec!(50000.5));
+        assert_eq!(registry.round_price("PF_ETHUSD", dec!(3000.04)), dec!(3000));
+        assert_eq!(registry.round_qty("PF_ETHUSD", dec!(1.23456)), dec!(1.234));
+        assert_eq!(registry.round_qty("UNKNOWN", dec!(1.23456)), dec!(1.23456));
+
+        assert!(registry.validate_order("PI_XBTUSD", Some(dec!(50000.5)), dec!(100)).is_ok());
+        assert!(registry.validate_order("PI_XBTUSD", None, dec!(100)).is_ok());
+        let reason = |result: FuturesResult<()>| match result {
+            Err(FuturesError::InvalidOrder { reason, .. }) => reason,
+            other => panic!("expected invalid order, got {:?}", other),
+        };
+        assert!(reason(registry.validate_order("PI_XBTUSD", Some(dec!(50000.2)), dec!(100))).contains("tick"));
+        assert!(reason(registry.validate_order("PI_XBTUSD", None, dec!(0.5))).contains("multiple"));
+        assert!(reason(registry.validate_order("PI_XBTUSD", None, dec!(2000000))).contains("maximum"));
+        assert_eq!(reason(registry.validate_order("PF_SOLUSD", None, dec!(1))), "unknown product");
+        assert!(matches!(
+            InstrumentRegistry::new().validate_order("PF_SOLUSD", None, dec!(1)),
+            Err(FuturesError::SpecsUnavailable)
+        ));
+    }
+}
@@ -14,8 +14,11 @@
 //! - **Orderbook**: Real-time Level 2 orderbook for futures contracts
 //! - **Ticker**: Price updates with mark price, index price, funding rate
 //! - **Trades**: Trade stream for futures markets
+//! - **Trade Tape**: Cumulative volume delta, liquidation flagging and rolling buy/sell imbalance
 //! - **Positions**: Real-time position tracking and margin updates
-//! - **Funding**: Funding rate updates and payments
+//! - **Funding**: Funding rate updates, and historical payments from the REST history API
+//! - **Margin Monitor**: Liquidation distance and margin utilization alerts
+//! - **Contract Specs**: Tick size, lot size and leverage limits from the REST API, with order validation
 //!
 //! # Differences from Spot API
 //!
@@ -58,14 +61,24 @@ pub mod connection;
 pub mod channels;
 pub mod types;
 pub mod error;
+pub mod funding;
+pub mod instruments;
+pub mod order_tracker;
+pub mod margin_monitor;
+pub mod trade_tape;
 
 // Re-export main types
 pub use connection::{FuturesConnection, FuturesConfig, ConnectionState};
 pub use auth::FuturesCredentials;
 pub use error::{FuturesError, FuturesResult};
+pub use funding::{FundingClient, FundingPayment};
+pub use instruments::{ContractSpec, InstrumentRegistry, MarginLevel};
+pub use margin_monitor::{MarginMonitor, MarginAlert, AlertLevel, PositionRisk};
+pub use order_tracker::{OrderTracker, TrackedOrder, LifecycleState, TrackerStats};
+pub use trade_tape::{TradeTape, TapeTrade, FlowImbalance};
 pub use types::{
     // Ticker
-    FuturesTicker, FundingRate, MarkPrice, IndexPrice,
+    FuturesTicker, FundingRate, MarkPrice, IndexPrice, Basis,
     // Book
     FuturesBookSnapshot, FuturesBookUpdate, BookLevel,
     // Trades
@@ -0,0 +1,470 @@
+//! Margin and liquidation monitoring for futures positions
+//!
+//! [`MarginMonitor`] consumes the `account_balances_and_margins` and
+//! `open_positions` feeds (plus ticker / mark price updates) and tracks how
+//! close the account is to trouble: margin utilization, an estimated
+//! liquidation price per position, and the distance from mark to that price
+//! in percent. Whenever a position or the account crosses a configured
+//! threshold, [`MarginMonitor::handle_event`] returns a [`MarginAlert`]; it
+//! also returns one when the level drops back to normal, so alerts can be
+//! cleared.
+//!
+//! The monitor does no I/O. Feed it every event from the connection and act
+//! on the alerts it returns.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_futures_ws::margin_monitor::{MarginMonitor, MarginAlert};
+//! use rust_decimal::Decimal;
+//!
+//! let mut monitor = MarginMonitor::new()
+//!     .with_warning_distance(Decimal::from(15))
+//!     .with_critical_distance(Decimal::from(5));
+//!
+//! // for event in events {
+//! //     for alert in monitor.handle_event(&event) {
+//! //         eprintln!("{:?}", alert);
+//! //     }
+//! // }
+//! assert!(monitor.utilization().is_none());
+//! ```
+
+use crate::types::{FuturesEvent, Position, PositionSide};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+use tracing::warn;
+
+/// Default distance-to-liquidation warning threshold (percent)
+pub const DEFAULT_WARNING_DISTANCE_PCT: u32 = 10;
+
+/// Default distance-to-liquidation critical threshold (percent)
+pub const DEFAULT_CRITICAL_DISTANCE_PCT: u32 = 5;
+
+/// Default margin utilization warning threshold (percent)
+pub const DEFAULT_UTILIZATION_WARNING_PCT: u32 = 70;
+
+/// Default margin utilization critical threshold (percent)
+pub const DEFAULT_UTILIZATION_CRITICAL_PCT: u32 = 90;
+
+/// Severity of a margin condition
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum AlertLevel {
+    /// Within limits
+    #[default]
+    Normal,
+    /// Past the warning threshold
+    Warning,
+    /// Past the critical threshold
+    Critical,
+}
+
+/// Alert emitted when a margin condition changes level
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MarginAlert {
+    /// A position's distance to liquidation crossed a threshold
+    LiquidationDistance {
+        /// Product ID
+        product_id: String,
+        /// New level
+        level: AlertLevel,
+        /// Distance from mark to liquidation price in percent
+        distance_pct: Decimal,
+        /// Liquidation price (reported or estimated)
+        liquidation_price: Decimal,
+    },
+    /// Account margin utilization crossed a threshold
+    Utilization {
+        /// New level
+        level: AlertLevel,
+        /// Initial margin as a percentage of portfolio value
+        utilization_pct: Decimal,
+    },
+}
+
+impl MarginAlert {
+    /// Level of the alert
+    pub fn level(&self) -> AlertLevel {
+        match self {
+            Self::LiquidationDistance { level, .. } | Self::Utilization { level, .. } => *level,
+        }
+    }
+}
+
+/// Risk snapshot for a single position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionRisk {
+    /// Product ID
+    pub product_id: String,
+    /// Position side
+    pub side: PositionSide,
+    /// Position size
+    pub size: Decimal,
+    /// Latest mark price
+    pub mark_price: Decimal,
+    /// Liquidation price, if it could be determined
+    pub liquidation_price: Option<Decimal>,
+    /// True when the liquidation price is estimated rather than reported
+    pub estimated: bool,
+    /// Distance from mark to liquidation in percent
+    pub distance_pct: Option<Decimal>,
+    /// Current alert level
+    pub level: AlertLevel,
+}
+
+/// Account-level margin figures
+#[derive(Debug, Clone, Copy, Default)]
+struct AccountMargins {
+    portfolio_value: Option<Decimal>,
+    initial_margin: Option<Decimal>,
+    maintenance_margin: Option<Decimal>,
+}
+
+/// Futures margin and liquidation-distance monitor
+#[derive(Debug, Clone)]
+pub struct MarginMonitor {
+    warning_distance_pct: Decimal,
+    critical_distance_pct: Decimal,
+    utilization_warning_pct: Decimal,
+    utilization_critical_pct: Decimal,
+    positions: HashMap<String, Position>,
+    account: AccountMargins,
+    position_levels: HashMap<String, AlertLevel>,
+    utilization_level: AlertLevel,
+}
+
+impl Default for MarginMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarginMonitor {
+    /// Create a monitor with default thresholds
+    pub fn new() -> Self {
+        Self {
+            warning_distance_pct: Decimal::from(DEFAULT_WARNING_DISTANCE_PCT),
+            critical_distance_pct: Decimal::from(DEFAULT_CRITICAL_DISTANCE_PCT),
+            utilization_warning_pct: Decimal::from(DEFAULT_UTILIZATION_WARNING_PCT),
+            utilization_critical_pct: Decimal::from(DEFAULT_UTILIZATION_CRITICAL_PCT),
+            positions: HashMap::new(),
+            account: AccountMargins::default(),
+            position_levels: HashMap::new(),
+            utilization_level: AlertLevel::Normal,
+        }
+    }
+
+    /// Warn when a position is within this many percent of liquidation
+    pub fn with_warning_distance(mut self, pct: Decimal) -> Self {
+        self.warning_distance_pct = pct;
+        self
+    }
+
+    /// Escalate to critical within this many percent of liquidation
+    pub fn with_critical_distance(mut self, pct: Decimal) -> Self {
+        self.critical_distance_pct = pct;
+        self
+    }
+
+    /// Warn when margin utilization reaches this percentage
+    pub fn with_utilization_warning(mut self, pct: Decimal) -> Self {
+        self.utilization_warning_pct = pct;
+        self
+    }
+
+    /// Escalate to critical when margin utilization reaches this percentage
+    pub fn with_utilization_critical(mut self, pct: Decimal) -> Self {
+        self.utilization_critical_pct = pct;
+        self
+    }
+
+    /// Apply a futures event and return any alerts whose level changed
+    pub fn handle_event(&mut self, event: &FuturesEvent) -> Vec<MarginAlert> {
+        match event {
+            FuturesEvent::Position(update) => {
+                for position in &update.positions {
+                    if position.size.is_zero() {
+                        self.positions.remove(&position.product_id);
+                    } else {
+                        self.positions.insert(position.product_id.clone(), position.clone());
+                    }
+                }
+            }
+            FuturesEvent::AccountUpdate(update) => {
+                self.account.portfolio_value = update.portfolio_value.or(self.account.portfolio_value);
+                self.account.initial_margin = update.initial_margin.or(self.account.initial_margin);
+                self.account.maintenance_margin =
+                    update.maintenance_margin.or(self.account.maintenance_margin);
+            }
+            FuturesEvent::Margin(info) => {
+                self.account = AccountMargins {
+                    portfolio_value: Some(info.portfolio_value),
+                    initial_margin: Some(info.initial_margin),
+                    maintenance_margin: Some(info.maintenance_margin),
+                };
+            }
+            FuturesEvent::MarkPrice(mark) => self.update_mark(&mark.product_id, mark.mark_price),
+            FuturesEvent::Ticker(ticker) => {
+                if let Some(mark) = ticker.mark_price {
+                    self.update_mark(&ticker.product_id, mark);
+                }
+            }
+            _ => return Vec::new(),
+        }
+        self.evaluate()
+    }
+
+    /// Update the mark price of an open position
+    pub fn update_mark(&mut self, product_id: &str, mark_price: Decimal) {
+        if let Some(position) = self.positions.get_mut(product_id) {
+            position.mark_price = mark_price;
+        }
+    }
+
+    /// Initial margin as a percentage of portfolio value
+    pub fn utilization(&self) -> Option<Decimal> {
+        let value = self.account.portfolio_value?;
+        let initial = self.account.initial_margin?;
+        if value <= Decimal::ZERO {
+            return None;
+        }
+        Some(initial / value * Decimal::from(100))
+    }
+
+    /// Liquidation price for a position and whether it is an estimate
+    ///
+    /// Uses the exchange-reported price when the positions feed has one.
+    /// Otherwise the account's margin above maintenance is split across
+    /// positions by notional and the price is where that share is lost; with
+    /// no account data the position's own margin is used, as for an isolated
+    /// position.
+    pub fn liquidation_price(&self, product_id: &str) -> Option<(Decimal, bool)> {
+        let position = self.positions.get(product_id)?;
+        if let Some(price) = position.liq_price {
+            return Some((price, false));
+        }
+        if position.size.is_zero() {
+            return None;
+        }
+
+        let buffer = match (self.account.portfolio_value, self.account.maintenance_margin) {
+            (Some(value), Some(maintenance)) => {
+                let total: Decimal = self.positions.values().map(|p| p.value().abs()).sum();
+                if total.is_zero() {
+                    return None;
+                }
+                (value - maintenance) * position.value().abs() / total
+            }
+            _ => position.margin,
+        };
+        let adverse_move = buffer / position.size.abs();
+        let price = match position.side {
+            PositionSide::Long => position.mark_price - adverse_move,
+            PositionSide::Short => position.mark_price + adverse_move,
+        };
+        Some((price.max(Decimal::ZERO), true))
+    }
+
+    /// Distance from mark to liquidation price in percent
+    pub fn distance_to_liquidation(&self, product_id: &str) -> Option<Decimal> {
+        let position = self.positions.get(product_id)?;
+        let (liquidation, _) = self.liquidation_price(product_id)?;
+        if position.mark_price.is_zero() {
+            return None;
+        }
+        let distance = match position.side {
+            PositionSide::Long => position.mark_price - liquidation,
+            PositionSide::Short => liquidation - position.mark_price,
+        };
+        Some((distance / position.mark_price * Decimal::from(100)).max(Decimal::ZERO))
+    }
+
+    /// Risk snapshot for every open position
+    pub fn position_risks(&self) -> Vec<PositionRisk> {
+        let mut risks: Vec<PositionRisk> = self
+            .positions
+            .values()
+            .map(|p| {
+                let liquidation = self.liquidation_price(&p.product_id);
+                let distance = self.distance_to_liquidation(&p.product_id);
+                PositionRisk {
+                    product_id: p.product_id.clone(),
+                    side: p.side,
+                    size: p.size,
+                    mark_price: p.mark_price,
+                    liquidation_price: liquidation.map(|(price, _)| price),
+                    estimated: liquidation.is_some_and(|(_, estimated)| estimated),
+                    distance_pct: distance,
+                    level: distance.map_or(AlertLevel::Normal, |d| self.distance_level(d)),
+                }
+            })
+            .collect();
+        risks.sort_by(|a, b| a.product_id.cmp(&b.product_id));
+        risks
+    }
+
+    /// Highest alert level across the account and all positions
+    pub fn worst_level(&self) -> AlertLevel {
+        self.position_levels
+            .values()
+            .copied()
+            .chain(std::iter::once(self.utilization_level))
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn distance_level(&self, distance_pct: Decimal) -> AlertLevel {
+        if distance_pct <= self.critical_distance_pct {
+            AlertLevel::Critical
+        } else if distance_pct <= self.warning_distance_pct {
+            AlertLevel::Warning
+        } else {
+            AlertLevel::Normal
+        }
+    }
+
+    fn evaluate(&mut self) -> Vec<MarginAlert> {
+        let mut alerts = Vec::new();
+
+        if let Some(utilization) = self.utilization() {
+            let level = if utilization >= self.utilization_critical_pct {
+                AlertLevel::Critical
+            } else if utilization >= self.utilization_warning_pct {
+                AlertLevel::Warning
+            } else {
+                AlertLevel::Normal
+            };
+            if level != self.utilization_level {
+                self.utilization_level = level;
+                alerts.push(MarginAlert::Utilization {
+                    level,
+                    utilization_pct: utilization,
+                });
+            }
+        }
+
+        self.position_levels.retain(|id, _| self.positions.contains_key(id));
+        for risk in self.position_risks() {
+            let (Some(distance_pct), Some(liquidation_price)) = (risk.distance_pct, risk.liquidation_price) else {
+                continue;
+            };
+            let previous = self.position_levels.insert(risk.product_id.clone(), risk.level);
+            if previous.unwrap_or_default() != risk.level {
+                alerts.push(MarginAlert::LiquidationDistance {
+                    product_id: risk.product_id,
+                    level: risk.level,
+                    distance_pct,
+                    liquidation_price,
+                });
+            }
+        }
+
+        for alert in &alerts {
+            if alert.level() == AlertLevel::Critical {
+                warn!("Margin alert: {:?}", alert);
+            }
+        }
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{MarginInfo, MarkPrice, PositionUpdate};
+
+    fn position(side: PositionSide, liq_price: Option<Decimal>) -> Position {
+        Position {
+            product_id: "PI_XBTUSD".to_string(),
+            side,
+            size: Decimal::ONE,
+            entry_price: Decimal::from(50000),
+            mark_price: Decimal::from(50000),
+            liq_price,
+            unrealized_pnl: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+            margin: Decimal::from(5000),
+            leverage: Decimal::from(10),
+        }
+    }
+
+    fn positions(positions: Vec<Position>) -> FuturesEvent {
+        FuturesEvent::Position(PositionUpdate {
+            positions,
+            account: None,
+            timestamp: "0".to_string(),
+        })
+    }
+
+    fn mark(price: i64) -> FuturesEvent {
+        FuturesEvent::MarkPrice(MarkPrice {
+            product_id: "PI_XBTUSD".to_string(),
+            mark_price: Decimal::from(price),
+            time: "0".to_string(),
+        })
+    }
+
+    #[test]
+    fn test_reported_liquidation_distance_alerts() {
+        let mut monitor = MarginMonitor::new();
+        let alerts = monitor.handle_event(&positions(vec![position(PositionSide::Long, Some(Decimal::from(45000)))]));
+        // 10% away counts as warning
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].level(), AlertLevel::Warning);
+
+        let alerts = monitor.handle_event(&mark(47000));
+        assert_eq!(alerts[0].level(), AlertLevel::Critical);
+        assert!(monitor.handle_event(&mark(46900)).is_empty());
+
+        let alerts = monitor.handle_event(&mark(60000));
+        assert_eq!(alerts[0].level(), AlertLevel::Normal);
+        assert_eq!(monitor.worst_level(), AlertLevel::Normal);
+    }
+
+    #[test]
+    fn test_estimated_liquidation_price() {
+        let mut monitor = MarginMonitor::new();
+        monitor.handle_event(&positions(vec![position(PositionSide::Short, None)]));
+        // Isolated fallback: 5000 margin on 1 contract -> 55000
+        assert_eq!(monitor.liquidation_price("PI_XBTUSD"), Some((Decimal::from(55000), true)));
+
+        monitor.handle_event(&FuturesEvent::Margin(MarginInfo {
+            available_margin: Decimal::from(5000),
+            initial_margin: Decimal::from(5000),
+            maintenance_margin: Decimal::from(2000),
+            portfolio_value: Decimal::from(10000),
+            unrealized_pnl: Decimal::ZERO,
+            margin_level: Decimal::from(200),
+        }));
+        // Cross margin: 10000 - 2000 buffer -> 58000, 16% away
+        assert_eq!(monitor.liquidation_price("PI_XBTUSD"), Some((Decimal::from(58000), true)));
+        assert_eq!(monitor.distance_to_liquidation("PI_XBTUSD"), Some(Decimal::from(16)));
+        assert_eq!(monitor.utilization(), Some(Decimal::from(50)));
+    }
+
+    #[test]
+    fn test_utilization_thresholds() {
+        let mut monitor = MarginMonitor::new().with_utilization_warning(Decimal::from(60));
+        let info = |initial: i64| {
+            FuturesEvent::Margin(MarginInfo {
+                available_margin: Decimal::ZERO,
+                initial_margin: Decimal::from(initial),
+                maintenance_margin: Decimal::ZERO,
+                portfolio_value: Decimal::from(100),
+                unrealized_pnl: Decimal::ZERO,
+                margin_level: Decimal::ZERO,
+            })
+        };
+
+        assert!(monitor.handle_event(&info(50)).is_empty());
+        assert_eq!(
+            monitor.handle_event(&info(65)),
+            vec![MarginAlert::Utilization {
+                level: AlertLevel::Warning,
+                utilization_pct: Decimal::from(65),
+            }]
+        );
+        assert_eq!(monitor.handle_event(&info(95))[0].level(), AlertLevel::Critical);
+        assert_eq!(monitor.worst_level(), AlertLevel::Critical);
+    }
+}
@@ -0,0 +1,541 @@
+//! Order lifecycle tracking for futures
+//!
+//! Mirrors the spot `OrderTracker` from `kraken-ws` for the futures private
+//! feeds: orders submitted with a client order ID are correlated with
+//! `open_orders` updates and `fills`, partial fills are aggregated into an
+//! average price, and slippage against the limit price is computed once the
+//! order trades.
+//!
+//! Futures feeds echo `cli_ord_id` on both orders and fills, so correlation
+//! is exact rather than the symbol/side heuristic the spot tracker falls
+//! back on. Fills are de-duplicated by `fill_id`, which makes replaying a
+//! `fills_snapshot` after a reconnect harmless.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_futures_ws::order_tracker::{OrderTracker, LifecycleState};
+//! use kraken_futures_ws::TradeSide;
+//! use rust_decimal::Decimal;
+//!
+//! let mut tracker = OrderTracker::new();
+//! tracker.track_submission("my-order-1", "PI_XBTUSD", TradeSide::Buy,
+//!     Decimal::from(1000), Some(Decimal::from(50000)));
+//!
+//! // Feed open_orders and fills events as they arrive:
+//! // tracker.handle_event(&event);
+//!
+//! let order = tracker.get_by_cli_ord_id("my-order-1").unwrap();
+//! assert_eq!(order.state, LifecycleState::Pending);
+//! ```
+
+use crate::types::{Fill, FuturesEvent, OpenOrder, OrderStatus, OrderType, TradeSide};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Order lifecycle state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum LifecycleState {
+    /// Submitted, not yet seen on the open_orders feed
+    Pending,
+    /// Resting (or untriggered) with no fills
+    Open,
+    /// Partially filled
+    PartiallyFilled,
+    /// Completely filled
+    Filled,
+    /// Canceled by the user or the engine
+    Canceled,
+    /// Rejected at submission
+    Rejected,
+}
+
+impl LifecycleState {
+    /// Map a futures order status
+    pub fn from_status(status: OrderStatus) -> Self {
+        match status {
+            OrderStatus::Open | OrderStatus::Untouched | OrderStatus::Triggered => Self::Open,
+            OrderStatus::PartiallyFilled => Self::PartiallyFilled,
+            OrderStatus::Filled => Self::Filled,
+            OrderStatus::Canceled => Self::Canceled,
+        }
+    }
+
+    /// Check if the order can still receive fills
+    pub fn is_active(&self) -> bool {
+        matches!(self, Self::Pending | Self::Open | Self::PartiallyFilled)
+    }
+
+    /// Check if the order reached a final state
+    pub fn is_terminal(&self) -> bool {
+        !self.is_active()
+    }
+}
+
+/// Tracked futures order
+#[derive(Debug, Clone)]
+pub struct TrackedOrder {
+    /// Client order ID used for correlation
+    pub cli_ord_id: Option<String>,
+    /// Exchange order ID (known once the order is acknowledged)
+    pub order_id: Option<String>,
+    /// Product ID
+    pub product_id: String,
+    /// Order side
+    pub side: TradeSide,
+    /// Order type, if reported by the feed
+    pub order_type: Option<OrderType>,
+    /// Order quantity (contracts)
+    pub qty: Decimal,
+    /// Limit price
+    pub limit_price: Option<Decimal>,
+    /// Reduce-only flag
+    pub reduce_only: bool,
+    /// Current lifecycle state
+    pub state: LifecycleState,
+    /// Cumulative filled quantity
+    pub filled_qty: Decimal,
+    /// Fills applied to this order
+    pub fills: Vec<Fill>,
+    /// Total fees paid
+    pub total_fees: Decimal,
+    /// Reject or cancel reason, when known
+    pub reason: Option<String>,
+    /// False for orders only known from fills, whose size is a lower bound
+    qty_known: bool,
+    submitted_at: Instant,
+    first_fill_at: Option<Instant>,
+    completed_at: Option<Instant>,
+}
+
+impl TrackedOrder {
+    fn new(cli_ord_id: Option<String>, product_id: String, side: TradeSide, qty: Decimal, limit_price: Option<Decimal>) -> Self {
+        Self {
+            cli_ord_id,
+            order_id: None,
+            product_id,
+            side,
+            order_type: None,
+            qty,
+            limit_price,
+            reduce_only: false,
+            state: LifecycleState::Pending,
+            filled_qty: Decimal::ZERO,
+            fills: Vec::new(),
+            total_fees: Decimal::ZERO,
+            reason: None,
+            qty_known: true,
+            submitted_at: Instant::now(),
+            first_fill_at: None,
+            completed_at: None,
+        }
+    }
+
+    fn from_open_order(order: &OpenOrder) -> Self {
+        let mut tracked = Self::new(
+            order.cli_ord_id.clone(),
+            order.product_id.clone(),
+            order.side,
+            order.qty,
+            order.limit_price,
+        );
+        tracked.order_id = Some(order.order_id.clone());
+        tracked
+    }
+
+    /// Remaining quantity to be filled
+    pub fn remaining_qty(&self) -> Decimal {
+        (self.qty - self.filled_qty).max(Decimal::ZERO)
+    }
+
+    /// Fill percentage (0 to 100)
+    pub fn fill_percentage(&self) -> Decimal {
+        if self.qty.is_zero() {
+            return Decimal::ZERO;
+        }
+        self.filled_qty / self.qty * Decimal::from(100)
+    }
+
+    /// Quantity-weighted average fill price
+    pub fn avg_fill_price(&self) -> Option<Decimal> {
+        let qty: Decimal = self.fills.iter().map(|f| f.qty).sum();
+        if qty.is_zero() {
+            return None;
+        }
+        let notional: Decimal = self.fills.iter().map(Fill::notional).sum();
+        Some(notional / qty)
+    }
+
+    /// Slippage vs the limit price in basis points (positive = worse)
+    pub fn slippage_bps(&self) -> Option<Decimal> {
+        self.slippage_vs_reference(self.limit_price?)
+    }
+
+    /// Slippage vs a reference price in basis points (positive = worse)
+    pub fn slippage_vs_reference(&self, reference: Decimal) -> Option<Decimal> {
+        let avg = self.avg_fill_price()?;
+        if reference.is_zero() {
+            return None;
+        }
+        let slippage = match self.side {
+            TradeSide::Buy => (avg - reference) / reference,
+            TradeSide::Sell => (reference - avg) / reference,
+        };
+        Some(slippage * Decimal::from(10_000))
+    }
+
+    /// Time from submission to first fill
+    pub fn time_to_first_fill(&self) -> Option<Duration> {
+        Some(self.first_fill_at?.duration_since(self.submitted_at))
+    }
+
+    /// Time from submission to a terminal state
+    pub fn time_to_complete(&self) -> Option<Duration> {
+        Some(self.completed_at?.duration_since(self.submitted_at))
+    }
+
+    fn transition(&mut self, state: LifecycleState) {
+        if self.state == state || self.state.is_terminal() {
+            return;
+        }
+        debug!(order = ?self.order_id, from = ?self.state, to = ?state, "Futures order transition");
+        self.state = state;
+        if state.is_terminal() {
+            self.completed_at = Some(Instant::now());
+        }
+    }
+
+    fn apply_open_order(&mut self, order: &OpenOrder) {
+        self.order_id = Some(order.order_id.clone());
+        self.order_type = Some(order.order_type);
+        self.qty = order.qty;
+        self.qty_known = true;
+        self.limit_price = order.limit_price.or(self.limit_price);
+        self.reduce_only = order.reduce_only;
+        // The feed's filled qty may run ahead of the fills feed
+        self.filled_qty = self.filled_qty.max(order.filled);
+        let state = match LifecycleState::from_status(order.status) {
+            LifecycleState::Open if !self.filled_qty.is_zero() => LifecycleState::PartiallyFilled,
+            state => state,
+        };
+        self.transition(state);
+    }
+
+    fn apply_fill(&mut self, fill: &Fill) {
+        if self.first_fill_at.is_none() {
+            self.first_fill_at = Some(Instant::now());
+        }
+        if self.order_id.is_none() {
+            self.order_id = Some(fill.order_id.clone());
+        }
+        self.total_fees += fill.fee_paid.unwrap_or(Decimal::ZERO);
+        self.fills.push(fill.clone());
+        let filled: Decimal = self.fills.iter().map(|f| f.qty).sum();
+        self.filled_qty = self.filled_qty.max(filled);
+        if !self.qty_known {
+            self.qty = self.filled_qty;
+            self.transition(LifecycleState::PartiallyFilled);
+        } else if self.filled_qty >= self.qty {
+            self.transition(LifecycleState::Filled);
+        } else {
+            self.transition(LifecycleState::PartiallyFilled);
+        }
+    }
+}
+
+/// Tracker statistics
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct TrackerStats {
+    /// Total orders tracked
+    pub total_tracked: u64,
+    /// Currently active orders
+    pub active_orders: u64,
+    /// Filled orders
+    pub filled_count: u64,
+    /// Canceled orders
+    pub canceled_count: u64,
+    /// Rejected orders
+    pub rejected_count: u64,
+    /// Fills applied (duplicates excluded)
+    pub total_fills: u64,
+    /// Average slippage of filled limit orders in basis points
+    pub avg_slippage_bps: Option<Decimal>,
+}
+
+/// Futures order lifecycle tracker
+#[derive(Debug, Default)]
+pub struct OrderTracker {
+    /// Orders by exchange order ID
+    orders: HashMap<String, TrackedOrder>,
+    /// Submitted orders not yet acknowledged, by client order ID
+    pending: HashMap<String, TrackedOrder>,
+    /// Client order ID -> exchange order ID
+    by_cli_ord_id: HashMap<String, String>,
+    /// Fill IDs already applied
+    seen_fills: HashSet<String>,
+    /// Fill count
+    total_fills: u64,
+}
+
+impl OrderTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Track an order at submission, before the exchange acknowledges it
+    pub fn track_submission(
+        &mut self,
+        cli_ord_id: &str,
+        product_id: &str,
+        side: TradeSide,
+        qty: Decimal,
+        limit_price: Option<Decimal>,
+    ) -> &TrackedOrder {
+        let order = TrackedOrder::new(Some(cli_ord_id.to_string()), product_id.to_string(), side, qty, limit_price);
+        self.pending.insert(cli_ord_id.to_string(), order);
+        &self.pending[cli_ord_id]
+    }
+
+    /// Mark a submitted order as rejected (e.g. send_order returned an error)
+    pub fn mark_rejected(&mut self, cli_ord_id: &str, reason: impl Into<String>) -> Option<&TrackedOrder> {
+        let order = self.pending.get_mut(cli_ord_id)?;
+        order.reason = Some(reason.into());
+        order.transition(LifecycleState::Rejected);
+        Some(order)
+    }
+
+    /// Apply any order-related futures event
+    pub fn handle_event(&mut self, event: &FuturesEvent) {
+        match event {
+            FuturesEvent::OpenOrders(snapshot) => {
+                for order in &snapshot.orders {
+                    self.handle_open_order(order);
+                }
+            }
+            FuturesEvent::OpenOrderUpdate(order) => {
+                self.handle_open_order(order);
+            }
+            FuturesEvent::Fill(fill) => {
+                self.handle_fill(fill);
+            }
+            FuturesEvent::Fills(snapshot) => {
+                for fill in &snapshot.fills {
+                    self.handle_fill(fill);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Apply an open_orders update
+    pub fn handle_open_order(&mut self, order: &OpenOrder) -> &TrackedOrder {
+        let tracked = self.entry(&order.order_id, order.cli_ord_id.as_deref(), || TrackedOrder::from_open_order(order));
+        tracked.apply_open_order(order);
+        tracked
+    }
+
+    /// Apply a fill; returns None if the fill was already applied
+    pub fn handle_fill(&mut self, fill: &Fill) -> Option<&TrackedOrder> {
+        if !self.seen_fills.insert(fill.fill_id.clone()) {
+            return None;
+        }
+        self.total_fills += 1;
+        let tracked = self.entry(&fill.order_id, fill.cli_ord_id.as_deref(), || {
+            // A fill for an order we never saw (placed from another session)
+            let mut order = TrackedOrder::new(fill.cli_ord_id.clone(), fill.instrument.clone(), fill.side, fill.qty, None);
+            order.order_id = Some(fill.order_id.clone());
+            order.qty_known = false;
+            order
+        });
+        tracked.apply_fill(fill);
+        Some(tracked)
+    }
+
+    /// Find or create the order for an exchange ID, promoting a pending
+    /// submission with the same client order ID
+    fn entry(&mut self, order_id: &str, cli_ord_id: Option<&str>, create: impl FnOnce() -> TrackedOrder) -> &mut TrackedOrder {
+        if !self.orders.contains_key(order_id) {
+            let order = cli_ord_id
+                .and_then(|id| self.pending.remove(id))
+                .unwrap_or_else(create);
+            if let Some(id) = cli_ord_id {
+                self.by_cli_ord_id.insert(id.to_string(), order_id.to_string());
+            }
+            self.orders.insert(order_id.to_string(), order);
+        }
+        self.orders.get_mut(order_id).expect("inserted above")
+    }
+
+    /// Get an order by exchange order ID
+    pub fn get(&self, order_id: &str) -> Option<&TrackedOrder> {
+        self.orders.get(order_id)
+    }
+
+    /// Get an order by client order ID
+    pub fn get_by_cli_ord_id(&self, cli_ord_id: &str) -> Option<&TrackedOrder> {
+        self.pending.get(cli_ord_id).or_else(|| {
+            self.by_cli_ord_id
+                .get(cli_ord_id)
+                .and_then(|id| self.orders.get(id))
+        })
+    }
+
+    /// All orders that can still receive fills
+    pub fn active_orders(&self) -> Vec<&TrackedOrder> {
+        self.all().filter(|o| o.state.is_active()).collect()
+    }
+
+    /// All orders in a given state
+    pub fn by_state(&self, state: LifecycleState) -> Vec<&TrackedOrder> {
+        self.all().filter(|o| o.state == state).collect()
+    }
+
+    /// All orders for a product
+    pub fn by_product(&self, product_id: &str) -> Vec<&TrackedOrder> {
+        self.all().filter(|o| o.product_id == product_id).collect()
+    }
+
+    fn all(&self) -> impl Iterator<Item = &TrackedOrder> {
+        self.orders.values().chain(self.pending.values())
+    }
+
+    /// Compute tracker statistics
+    pub fn stats(&self) -> TrackerStats {
+        let mut stats = TrackerStats {
+            total_fills: self.total_fills,
+            ..Default::default()
+        };
+        let mut slippages = Vec::new();
+        for order in self.all() {
+            stats.total_tracked += 1;
+            match order.state {
+                LifecycleState::Filled => {
+                    stats.filled_count += 1;
+                    slippages.extend(order.slippage_bps());
+                }
+                LifecycleState::Canceled => stats.canceled_count += 1,
+                LifecycleState::Rejected => stats.rejected_count += 1,
+                _ => stats.active_orders += 1,
+            }
+        }
+        if !slippages.is_empty() {
+            let total: Decimal = slippages.iter().sum();
+            stats.avg_slippage_bps = Some(total / Decimal::from(slippages.len()));
+        }
+        stats
+    }
+
+    /// Drop orders in a terminal state
+    pub fn clear_completed(&mut self) {
+        self.orders.retain(|_, o| o.state.is_active());
+        self.pending.retain(|_, o| o.state.is_active());
+        let orders = &self.orders;
+        self.by_cli_ord_id.retain(|_, id| orders.contains_key(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::FillType;
+
+    fn open_order(status: OrderStatus, filled: i64) -> OpenOrder {
+        OpenOrder {
+            order_id: "ord-1".to_string(),
+            cli_ord_id: Some("cli-1".to_string()),
+            product_id: "PI_XBTUSD".to_string(),
+            side: TradeSide::Buy,
+            order_type: OrderType::Limit,
+            limit_price: Some(Decimal::from(50000)),
+            stop_price: None,
+            qty: Decimal::from(10),
+            filled: Decimal::from(filled),
+            remaining: None,
+            reduce_only: false,
+            post_only: false,
+            status,
+            last_update_time: None,
+            timestamp: None,
+        }
+    }
+
+    fn fill(id: &str, price: i64, qty: i64) -> Fill {
+        Fill {
+            instrument: "PI_XBTUSD".to_string(),
+            order_id: "ord-1".to_string(),
+            cli_ord_id: Some("cli-1".to_string()),
+            fill_id: id.to_string(),
+            time: "2024-01-01T00:00:00Z".to_string(),
+            side: TradeSide::Buy,
+            price: Decimal::from(price),
+            qty: Decimal::from(qty),
+            fill_type: FillType::Maker,
+            fee_paid: Some(Decimal::ONE),
+            fee_currency: Some("USD".to_string()),
+            seq: None,
+        }
+    }
+
+    #[test]
+    fn test_submission_correlates_with_feeds() {
+        let mut tracker = OrderTracker::new();
+        tracker.track_submission("cli-1", "PI_XBTUSD", TradeSide::Buy, Decimal::from(10), Some(Decimal::from(50000)));
+
+        tracker.handle_event(&FuturesEvent::OpenOrderUpdate(open_order(OrderStatus::Open, 0)));
+        let order = tracker.get_by_cli_ord_id("cli-1").unwrap();
+        assert_eq!(order.order_id.as_deref(), Some("ord-1"));
+        assert_eq!(order.state, LifecycleState::Open);
+        assert!(tracker.get("ord-1").is_some());
+
+        tracker.handle_fill(&fill("f1", 50000, 4));
+        tracker.handle_fill(&fill("f2", 50010, 6));
+        let order = tracker.get("ord-1").unwrap();
+        assert_eq!(order.state, LifecycleState::Filled);
+        assert_eq!(order.avg_fill_price(), Some(Decimal::from(50006)));
+        assert_eq!(order.total_fees, Decimal::from(2));
+        // (50006 - 50000) / 50000 * 10000 = 1.2bp
+        assert_eq!(order.slippage_bps(), Some(Decimal::new(12, 1)));
+        assert!(order.time_to_complete().is_some());
+    }
+
+    #[test]
+    fn test_duplicate_fills_and_partial_state() {
+        let mut tracker = OrderTracker::new();
+        assert!(tracker.handle_fill(&fill("f1", 50000, 3)).is_some());
+        assert!(tracker.handle_fill(&fill("f1", 50000, 3)).is_none());
+
+        let order = tracker.get("ord-1").unwrap();
+        // Size unknown until open_orders reports it, so not treated as filled
+        assert_eq!(order.state, LifecycleState::PartiallyFilled);
+
+        tracker.handle_open_order(&open_order(OrderStatus::Open, 3));
+        let order = tracker.get("ord-1").unwrap();
+        assert_eq!(order.filled_qty, Decimal::from(3));
+        assert_eq!(order.remaining_qty(), Decimal::from(7));
+        assert_eq!(order.state, LifecycleState::PartiallyFilled);
+        assert_eq!(tracker.stats().total_fills, 1);
+    }
+
+    #[test]
+    fn test_reject_cancel_and_stats() {
+        let mut tracker = OrderTracker::new();
+        tracker.track_submission("cli-2", "PI_ETHUSD", TradeSide::Sell, Decimal::ONE, None);
+        tracker.mark_rejected("cli-2", "insufficientAvailableFunds");
+        tracker.track_submission("cli-1", "PI_XBTUSD", TradeSide::Buy, Decimal::from(10), None);
+        tracker.handle_open_order(&open_order(OrderStatus::Canceled, 0));
+
+        let stats = tracker.stats();
+        assert_eq!(stats.total_tracked, 2);
+        assert_eq!(stats.rejected_count, 1);
+        assert_eq!(stats.canceled_count, 1);
+        assert_eq!(stats.active_orders, 0);
+
+        tracker.clear_completed();
+        assert!(tracker.get_by_cli_ord_id("cli-1").is_none());
+        assert!(tracker.by_product("PI_ETHUSD").is_empty());
+    }
+}
@@ -0,0 +1,391 @@
+//! Futures trade tape, cumulative volume delta and flow imbalance
+//!
+//! [`TradeTape`] consumes the futures trade feed and keeps, per product:
+//!
+//! - a bounded tape of recent trades, each stamped with the running CVD;
+//! - cumulative volume delta (buy minus sell quantity since the tape
+//!   started), with liquidations also tallied on their own;
+//! - rolling buy/sell volume imbalance over each configured window.
+//!
+//! Imbalance uses the same definition as the spot-side
+//! `kraken_sdk::trade_flow::TradeFlow` — `(buy - sell) / (buy + sell)` over
+//! windows measured on exchange timestamps — so spot and futures flow can be
+//! compared directly.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_futures_ws::trade_tape::TradeTape;
+//! use kraken_futures_ws::{FuturesTrade, TradeSide, TradeType};
+//! use rust_decimal_macros::dec;
+//! use std::time::Duration;
+//!
+//! let trade = |side, trade_type, qty, time: &str| FuturesTrade {
+//!     product_id: "PF_XBTUSD".into(),
+//!     uid: time.into(),
+//!     side,
+//!     trade_type,
+//!     price: dec!(50000),
+//!     qty,
+//!     time: time.into(),
+//!     seq: None,
+//! };
+//!
+//! let mut tape = TradeTape::new().with_window(Duration::from_secs(60));
+//! tape.record(&trade(TradeSide::Buy, TradeType::Fill, dec!(3), "1700000000000"));
+//! let liq = tape.record(&trade(TradeSide::Sell, TradeType::Liquidation, dec!(1), "1700000001000"));
+//! assert!(liq.is_liquidation);
+//!
+//! assert_eq!(tape.cvd("PF_XBTUSD"), Some(dec!(2)));
+//! assert_eq!(tape.liquidation_delta("PF_XBTUSD"), Some(dec!(-1)));
+//! assert_eq!(tape.imbalance("PF_XBTUSD", Duration::from_secs(60)).unwrap().ratio, dec!(0.5));
+//! ```
+
+use crate::types::{FuturesEvent, FuturesTrade, TradeSide, TradeType};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Default imbalance windows (same as the spot side)
+pub const DEFAULT_WINDOWS: [Duration; 3] =
+    [Duration::from_secs(10), Duration::from_secs(60), Duration::from_secs(300)];
+
+/// Default number of trades kept on each product's tape
+pub const DEFAULT_TAPE_LEN: usize = 1000;
+
+/// A trade as recorded on the tape
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TapeTrade {
+    /// Product ID
+    pub product_id: String,
+    /// Trade ID
+    pub uid: String,
+    /// Aggressor side
+    pub side: TradeSide,
+    /// Fill, liquidation, assignment, ...
+    pub trade_type: TradeType,
+    /// Price
+    pub price: Decimal,
+    /// Quantity (contracts)
+    pub qty: Decimal,
+    /// Exchange time (ms since epoch), or local time if the trade had none
+    pub at_ms: i64,
+    /// The trade was a liquidation
+    pub is_liquidation: bool,
+    /// Cumulative volume delta including this trade
+    pub cvd: Decimal,
+}
+
+impl TapeTrade {
+    /// Signed quantity: positive for buys, negative for sells
+    pub fn delta(&self) -> Decimal {
+        match self.side {
+            TradeSide::Buy => self.qty,
+            TradeSide::Sell => -self.qty,
+        }
+    }
+}
+
+/// Buy and sell volume over a window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowImbalance {
+    /// Aggressive buy volume
+    pub buy_qty: Decimal,
+    /// Aggressive sell volume
+    pub sell_qty: Decimal,
+    /// Trades in the window
+    pub trades: usize,
+    /// (buy - sell) / (buy + sell), from -1 (all sells) to +1 (all buys)
+    pub ratio: Decimal,
+}
+
+impl FlowImbalance {
+    /// Buy volume divided by sell volume (None without sells)
+    pub fn buy_sell_ratio(&self) -> Option<Decimal> {
+        (!self.sell_qty.is_zero()).then(|| self.buy_qty / self.sell_qty)
+    }
+}
+
+/// Rolling volume over one window
+#[derive(Debug, Clone)]
+struct RollingWindow {
+    length_ms: i64,
+    trades: VecDeque<(i64, TradeSide, Decimal)>,
+    buy_qty: Decimal,
+    sell_qty: Decimal,
+}
+
+impl RollingWindow {
+    fn new(length: Duration) -> Self {
+        Self {
+            length_ms: length.as_millis() as i64,
+            trades: VecDeque::new(),
+            buy_qty: Decimal::ZERO,
+            sell_qty: Decimal::ZERO,
+        }
+    }
+
+    fn push(&mut self, at_ms: i64, side: TradeSide, qty: Decimal) {
+        match side {
+            TradeSide::Buy => self.buy_qty += qty,
+            TradeSide::Sell => self.sell_qty += qty,
+        }
+        self.trades.push_back((at_ms, side, qty));
+        while let Some(&(at, side, qty)) = self.trades.front() {
+            if at > at_ms - self.length_ms {
+                break;
+            }
+            match side {
+                TradeSide::Buy => self.buy_qty -= qty,
+                TradeSide::Sell => self.sell_qty -= qty,
+            }
+            self.trades.pop_front();
+        }
+    }
+
+    fn imbalance(&self) -> FlowImbalance {
+        let total = self.buy_qty + self.sell_qty;
+        let ratio = if total.is_zero() {
+            Decimal::ZERO
+        } else {
+            (self.buy_qty - self.sell_qty) / total
+        };
+        FlowImbalance {
+            buy_qty: self.buy_qty,
+            sell_qty: self.sell_qty,
+            trades: self.trades.len(),
+            ratio,
+        }
+    }
+}
+
+/// Per-product tape state
+#[derive(Debug, Clone)]
+struct ProductTape {
+    tape: VecDeque<TapeTrade>,
+    cvd: Decimal,
+    liquidation_delta: Decimal,
+    windows: Vec<(Duration, RollingWindow)>,
+}
+
+impl ProductTape {
+    fn new(windows: &[Duration]) -> Self {
+        Self {
+            tape: VecDeque::new(),
+            cvd: Decimal::ZERO,
+            liquidation_delta: Decimal::ZERO,
+            windows: windows.iter().map(|w| (*w, RollingWindow::new(*w))).collect(),
+        }
+    }
+}
+
+/// Per-product trade tape, CVD and imbalance windows
+#[derive(Debug, Clone)]
+pub struct TradeTape {
+    products: HashMap<String, ProductTape>,
+    windows: Vec<Duration>,
+    tape_len: usize,
+}
+
+impl Default for TradeTape {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TradeTape {
+    /// Create a tape without imbalance windows
+    ///
+    /// Add windows with [`with_window`](Self::with_window), or use
+    /// [`with_default_windows`](Self::with_default_windows).
+    pub fn new() -> Self {
+        Self {
+            products: HashMap::new(),
+            windows: Vec::new(),
+            tape_len: DEFAULT_TAPE_LEN,
+        }
+    }
+
+    /// Track imbalance over a window (ignored if already tracked)
+    pub fn with_window(mut self, window: Duration) -> Self {
+        if !window.is_zero() && !self.windows.contains(&window) {
+            self.windows.push(window);
+            self.windows.sort();
+        }
+        self
+    }
+
+    /// Track imbalance over 10s, 1m and 5m
+    pub fn with_default_windows(self) -> Self {
+        DEFAULT_WINDOWS.iter().fold(self, |tape, w| tape.with_window(*w))
+    }
+
+    /// Keep at most `len` trades per product
+    pub fn with_tape_len(mut self, len: usize) -> Self {
+        self.tape_len = len.max(1);
+        self
+    }
+
+    /// Configured windows, shortest first
+    pub fn windows(&self) -> &[Duration] {
+        &self.windows
+    }
+
+    /// Record a trade
+    pub fn record(&mut self, trade: &FuturesTrade) -> TapeTrade {
+        let windows = &self.windows;
+        let product = self
+            .products
+            .entry(trade.product_id.clone())
+            .or_insert_with(|| ProductTape::new(windows));
+
+        let at_ms = trade.time.parse::<i64>().unwrap_or_else(|_| now_ms());
+        let is_liquidation = trade.trade_type == TradeType::Liquidation;
+        let mut recorded = TapeTrade {
+            product_id: trade.product_id.clone(),
+            uid: trade.uid.clone(),
+            side: trade.side,
+            trade_type: trade.trade_type,
+            price: trade.price,
+            qty: trade.qty,
+            at_ms,
+            is_liquidation,
+            cvd: Decimal::ZERO,
+        };
+        product.cvd += recorded.delta();
+        if is_liquidation {
+            product.liquidation_delta += recorded.delta();
+        }
+        recorded.cvd = product.cvd;
+
+        for (_, window) in &mut product.windows {
+            window.push(at_ms, trade.side, trade.qty);
+        }
+        if product.tape.len() >= self.tape_len {
+            product.tape.pop_front();
+        }
+        product.tape.push_back(recorded.clone());
+        recorded
+    }
+
+    /// Record a trade event
+    pub fn handle_event(&mut self, event: &FuturesEvent) -> Option<TapeTrade> {
+        match event {
+            FuturesEvent::Trade(trade) => Some(self.record(trade)),
+            _ => None,
+        }
+    }
+
+    /// Cumulative volume delta since the product's first trade
+    pub fn cvd(&self, product_id: &str) -> Option<Decimal> {
+        Some(self.products.get(product_id)?.cvd)
+    }
+
+    /// Cumulative volume delta of liquidation trades only
+    pub fn liquidation_delta(&self, product_id: &str) -> Option<Decimal> {
+        Some(self.products.get(product_id)?.liquidation_delta)
+    }
+
+    /// CVD after each trade on the tape, oldest first, as `(at_ms, cvd)`
+    pub fn cvd_series(&self, product_id: &str) -> Vec<(i64, Decimal)> {
+        self.products
+            .get(product_id)
+            .map(|product| product.tape.iter().map(|t| (t.at_ms, t.cvd)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Current imbalance over a configured window
+    ///
+    /// Returns None if the window isn't configured or the product has no trades.
+    pub fn imbalance(&self, product_id: &str, window: Duration) -> Option<FlowImbalance> {
+        let product = self.products.get(product_id)?;
+        let (_, window) = product.windows.iter().find(|(w, _)| *w == window)?;
+        Some(window.imbalance())
+    }
+
+    /// Trades on the tape, oldest first
+    pub fn tape(&self, product_id: &str) -> Vec<&TapeTrade> {
+        self.products
+            .get(product_id)
+            .map(|product| product.tape.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Liquidations still on the tape, oldest first
+    pub fn liquidations(&self, product_id: &str) -> Vec<&TapeTrade> {
+        self.products
+            .get(product_id)
+            .map(|product| product.tape.iter().filter(|t| t.is_liquidation).collect())
+            .unwrap_or_default()
+    }
+
+    /// Products with recorded trades
+    pub fn products(&self) -> impl Iterator<Item = &str> {
+        self.products.keys().map(String::as_str)
+    }
+
+    /// Forget a product's tape and CVD
+    pub fn reset(&mut self, product_id: &str) {
+        self.products.remove(product_id);
+    }
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn trade(side: TradeSide, trade_type: TradeType, qty: Decimal, at_ms: i64) -> FuturesTrade {
+        FuturesTrade {
+            product_id: "PI_XBTUSD".to_string(),
+            uid: at_ms.to_string(),
+            side,
+            trade_type,
+            price: dec!(50000),
+            qty,
+            time: at_ms.to_string(),
+            seq: None,
+        }
+    }
+
+    #[test]
+    fn test_cvd_and_liquidations() {
+        let mut tape = TradeTape::new().with_tape_len(2);
+        tape.record(&trade(TradeSide::Buy, TradeType::Fill, dec!(5), 0));
+        tape.record(&trade(TradeSide::Sell, TradeType::Liquidation, dec!(2), 1));
+        let last = tape.record(&trade(TradeSide::Sell, TradeType::Fill, dec!(1), 2));
+
+        assert_eq!(last.cvd, dec!(2));
+        assert_eq!(tape.cvd("PI_XBTUSD"), Some(dec!(2)));
+        assert_eq!(tape.liquidation_delta("PI_XBTUSD"), Some(dec!(-2)));
+        // The tape is bounded; CVD keeps counting the dropped trade
+        assert_eq!(tape.cvd_series("PI_XBTUSD"), vec![(1, dec!(3)), (2, dec!(2))]);
+        assert_eq!(tape.liquidations("PI_XBTUSD").len(), 1);
+        assert!(tape.cvd("PF_ETHUSD").is_none());
+    }
+
+    #[test]
+    fn test_rolling_windows_expire_trades() {
+        let mut tape = TradeTape::new()
+            .with_window(Duration::from_secs(10))
+            .with_window(Duration::from_secs(60));
+        tape.record(&trade(TradeSide::Sell, TradeType::Fill, dec!(4), 0));
+        tape.record(&trade(TradeSide::Buy, TradeType::Fill, dec!(1), 30_000));
+
+        let short = tape.imbalance("PI_XBTUSD", Duration::from_secs(10)).unwrap();
+        assert_eq!((short.buy_qty, short.sell_qty, short.ratio), (dec!(1), dec!(0), dec!(1)));
+        assert_eq!(short.buy_sell_ratio(), None);
+        let long = tape.imbalance("PI_XBTUSD", Duration::from_secs(60)).unwrap();
+        assert_eq!((long.ratio, long.trades), (dec!(-0.6), 2));
+        assert_eq!(long.buy_sell_ratio(), Some(dec!(0.25)));
+        assert!(tape.imbalance("PI_XBTUSD", Duration::from_secs(5)).is_none());
+    }
+}
@@ -204,12 +204,51 @@ pub struct IndexPrice {
     pub time: String,
 }
 
+/// Basis between mark and index price, derived per product
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Basis {
+    /// Product ID
+    pub product_id: String,
+    /// Mark price used
+    pub mark_price: Decimal,
+    /// Index price used
+    pub index_price: Decimal,
+    /// Mark minus index
+    pub basis: Decimal,
+    /// Basis as a percentage of the index (None when the index is zero)
+    pub premium_pct: Option<Decimal>,
+}
+
+impl Basis {
+    /// Compute the basis from a mark and index price
+    pub fn new(product_id: impl Into<String>, mark_price: Decimal, index_price: Decimal) -> Self {
+        let basis = mark_price - index_price;
+        let premium_pct = if index_price.is_zero() {
+            None
+        } else {
+            Some(basis / index_price * Decimal::from(100))
+        };
+        Self {
+            product_id: product_id.into(),
+            mark_price,
+            index_price,
+            basis,
+            premium_pct,
+        }
+    }
+
+    /// Returns true if the perpetual trades above the index
+    pub fn is_premium(&self) -> bool {
+        self.basis > Decimal::ZERO
+    }
+}
+
 // ============================================================================
 // Orderbook Types
 // ============================================================================
 
 /// Orderbook snapshot for futures
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FuturesBookSnapshot {
     /// Product ID
     pub product_id: String,
@@ -239,7 +278,7 @@ pub struct FuturesBookUpdate {
 }
 
 /// Single orderbook level
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BookLevel {
     /// Price
     pub price: Decimal,
@@ -680,6 +719,8 @@ pub enum FuturesEvent {
     MarkPrice(MarkPrice),
     /// Index price update
     IndexPrice(IndexPrice),
+    /// Basis changed (derived from mark and index price)
+    Basis(Basis),
     /// Book snapshot
     BookSnapshot(FuturesBookSnapshot),
     /// Book update
@@ -794,4 +835,15 @@ mod tests {
         let roe = pos.roe().unwrap();
         assert_eq!(roe, Decimal::from(20)); // 100/500 * 100 = 20%
     }
+
+    #[test]
+    fn test_basis() {
+        let basis = Basis::new("PI_XBTUSD", Decimal::from(50100), Decimal::from(50000));
+        assert_eq!(basis.basis, Decimal::from(100));
+        assert_eq!(basis.premium_pct, Some(Decimal::new(2, 1))); // 0.2%
+        assert!(basis.is_premium());
+
+        let basis = Basis::new("PI_XBTUSD", Decimal::from(1), Decimal::ZERO);
+        assert_eq!(basis.premium_pct, None);
+    }
 }
@@ -74,7 +74,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     Some(Event::Market(MarketEvent::OrderbookSnapshot { symbol, .. })) => {
                         println!("[SNAPSHOT] {}", symbol);
                     }
-                    Some(Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot })) => {
+                    Some(Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot, .. })) => {
                         update_count += 1;
                         if update_count % 20 == 0 {
                             println!(
@@ -103,14 +103,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     println!("    {}. ${} x {}", i + 1, level.price, level.qty);
                 }
             }
-            FuturesEvent::BookUpdate(update) => {
-                // Just count updates, don't print every one
-                if event_count % 20 == 0 {
-                    println!("[Book Update] {} seq={} bids={} asks={}",
-                        update.product_id, update.seq,
-                        update.bids.len(), update.asks.len()
-                    );
-                }
+            // Just count updates, don't print every one
+            FuturesEvent::BookUpdate(update) if event_count % 20 == 0 => {
+                println!("[Book Update] {} seq={} bids={} asks={}",
+                    update.product_id, update.seq,
+                    update.bids.len(), update.asks.len()
+                );
             }
             FuturesEvent::Trade(trade) => {
                 println!("[Trade] {} {:?} {} @ ${}",
@@ -55,7 +55,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 match event {
                     Event::Market(MarketEvent::OrderbookUpdate { symbol, .. }) => {
                         let count = update_count_clone.fetch_add(1, Ordering::Relaxed) + 1;
-                        if count % 50 == 0 {
+                        if count.is_multiple_of(50) {
                             println!("[{}] Processed {} updates", symbol, count);
                         }
                     }
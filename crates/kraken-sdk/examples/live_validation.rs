@@ -258,10 +258,10 @@ async fn test_subscriptions(runner: &mut TestRunner) {
     let book_result = timeout(Duration::from_secs(60), async {
         while let Some(event) = events.recv().await {
             match event {
-                Event::Subscription(kraken_ws::SubscriptionEvent::Subscribed { channel, .. }) => {
-                    if channel == "book" {
-                        return Ok("subscribed");
-                    }
+                Event::Subscription(kraken_ws::SubscriptionEvent::Subscribed { channel, .. })
+                    if channel == "book" =>
+                {
+                    return Ok("subscribed");
                 }
                 Event::Market(kraken_ws::MarketEvent::OrderbookSnapshot { .. }) => {
                     return Ok("snapshot");
@@ -473,7 +473,7 @@ async fn test_orderbook_l2(runner: &mut TestRunner) {
     let start = Instant::now();
     if let Some(book) = conn.orderbook("BTC/USD") {
         if let (Some(bid), Some(ask), Some(mid)) = (book.best_bid(), book.best_ask(), book.mid_price()) {
-            let expected_mid = (bid.price + ask.price) / dec!(2);
+            let expected_mid = (bid.price + ask.price).0 / dec!(2);
             if mid == expected_mid {
                 runner.pass("ORDERBOOK", "Mid price", format!("mid={}", mid), start);
             } else {
@@ -777,8 +777,8 @@ async fn test_market_state(runner: &mut TestRunner) {
         let asks = book.asks_vec();
 
         if !bids.is_empty() && !asks.is_empty() {
-            let bid_vol: Decimal = bids.iter().take(5).map(|l| l.qty).sum();
-            let ask_vol: Decimal = asks.iter().take(5).map(|l| l.qty).sum();
+            let bid_vol: Decimal = bids.iter().take(5).map(|l| l.qty.0).sum();
+            let ask_vol: Decimal = asks.iter().take(5).map(|l| l.qty.0).sum();
             let total = bid_vol + ask_vol;
 
             if total > Decimal::ZERO {
@@ -1192,7 +1192,7 @@ async fn test_l3_orderbook(runner: &mut TestRunner) {
 
     // Test L3 VWAP
     let start = Instant::now();
-    if let Some(vwap) = book.vwap_bid(dec!(0.5)) {
+    if let Some(vwap) = book.bid_vwap(dec!(0.5)) {
         runner.pass("L3", "L3 VWAP", format!("vwap_bid={}", vwap), start);
     } else {
         runner.fail("L3", "L3 VWAP", "VWAP calculation failed", start);
@@ -14,6 +14,7 @@
 //! - Authentication and trading API integration
 
 use kraken_book::l3::{L3Book, L3Order, L3Side};
+use kraken_types::{Price, Qty};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 use std::time::Instant;
@@ -89,16 +90,17 @@ fn main() {
     }
 
     // 2. VWAP for execution cost estimation
-    let trade_size = dec!(2.0);
-    if let Some(vwap_buy) = book.vwap_ask(trade_size) {
-        let slippage = (vwap_buy - mid_price) / mid_price * dec!(100);
+    let trade_size = Qty::new(dec!(2.0));
+    let mid = Price::new(mid_price);
+    if let Some(vwap_buy) = book.ask_vwap(trade_size) {
+        let slippage = (vwap_buy - mid) / mid * dec!(100);
         println!("VWAP to buy {} BTC: ${} (slippage: {:.3}%)",
             trade_size, vwap_buy, slippage
         );
     }
 
-    if let Some(vwap_sell) = book.vwap_bid(trade_size) {
-        let slippage = (mid_price - vwap_sell) / mid_price * dec!(100);
+    if let Some(vwap_sell) = book.bid_vwap(trade_size) {
+        let slippage = (mid - vwap_sell) / mid * dec!(100);
         println!("VWAP to sell {} BTC: ${} (slippage: {:.3}%)",
             trade_size, vwap_sell, slippage
         );
@@ -235,8 +237,8 @@ fn benchmark_operations() {
     let vwap_calcs = 1000;
 
     for _ in 0..vwap_calcs {
-        let _ = book.vwap_ask(dec!(10.0));
-        let _ = book.vwap_bid(dec!(10.0));
+        let _ = book.ask_vwap(dec!(10.0));
+        let _ = book.bid_vwap(dec!(10.0));
     }
 
     let vwap_duration = start.elapsed();
@@ -39,7 +39,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             }
             event = events.recv() => {
                 match event {
-                    Some(Event::Market(MarketEvent::OrderbookSnapshot { symbol, snapshot })) => {
+                    Some(Event::Market(MarketEvent::OrderbookSnapshot { symbol, snapshot, .. })) => {
                         snapshot_received += 1;
                         let spread = snapshot.spread().unwrap_or_default();
                         spreads.insert(symbol.clone(), spread);
@@ -55,7 +55,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             println!("\nAll symbols synced! Monitoring updates...\n");
                         }
                     }
-                    Some(Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot })) => {
+                    Some(Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot, .. })) => {
                         let spread = snapshot.spread().unwrap_or_default();
                         let prev_spread = spreads.get(&symbol).cloned().unwrap_or_default();
 
@@ -25,12 +25,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     while let Some(event) = events.recv().await {
         match event {
-            Event::Market(MarketEvent::OrderbookSnapshot { symbol, snapshot }) => {
+            Event::Market(MarketEvent::OrderbookSnapshot { symbol, snapshot, .. }) => {
                 println!("=== SNAPSHOT for {} ===", symbol);
                 print_snapshot(&snapshot);
                 println!();
             }
-            Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot }) => {
+            Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot, .. }) => {
                 update_count += 1;
                 println!(
                     "[Update #{}/{}] {} | Mid: ${:.2} | Spread: ${:.4}",
@@ -45,7 +45,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             // Using Stream::next() instead of recv()
             event = pinned_events.next() => {
                 match event {
-                    Some(Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot })) => {
+                    Some(Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot, .. })) => {
                         update_count += 1;
                         if update_count % 10 == 0 {
                             let spread = snapshot.spread().unwrap_or_default();
@@ -0,0 +1,236 @@
+//! Several trading accounts in one process
+//!
+//! Each [`Account`] bundles the credentials, token refresh and rate limiter
+//! of one API key, so orders on one account never consume another account's
+//! request budget. An [`AccountRegistry`] looks accounts up by [`AccountId`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraken_sdk::accounts::AccountRegistry;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     // Reads KRAKEN_MAKER_API_KEY, KRAKEN_MAKER_PRIVATE_KEY, ...
+//!     let registry = AccountRegistry::from_env(["maker", "hedge"])?;
+//!     registry.start_auto_refresh().await;
+//!
+//!     let maker = registry.get(&"maker".into()).expect("registered");
+//!     let client = maker.trading_client().await?;
+//!     let mut tracker = maker.order_tracker();
+//!     # let _ = (client, &mut tracker);
+//!     Ok(())
+//! }
+//! ```
+
+use crate::auth::{AuthError, AutoRefreshConfig, AutoRefreshTokenManager, TokenManager};
+use kraken_types::AccountId;
+use kraken_ws::{KrakenRateLimiter, OrderTracker, SharedRateLimiter, TradingClient};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Credentials, token refresh and rate limiting for one API key
+#[derive(Debug)]
+pub struct Account {
+    id: AccountId,
+    tokens: AutoRefreshTokenManager,
+    rate_limiter: SharedRateLimiter,
+}
+
+impl Account {
+    /// Create an account from its token manager, with its own default rate limiter
+    ///
+    /// The account takes its id from the token manager.
+    pub fn new(tokens: AutoRefreshTokenManager) -> Self {
+        Self {
+            id: tokens.account().clone(),
+            tokens,
+            rate_limiter: Arc::new(KrakenRateLimiter::kraken_defaults()),
+        }
+    }
+
+    /// Use a specific rate limiter, e.g. one sized for the account's tier
+    pub fn with_rate_limiter(mut self, limiter: SharedRateLimiter) -> Self {
+        self.rate_limiter = limiter;
+        self
+    }
+
+    /// Account identifier
+    pub fn id(&self) -> &AccountId {
+        &self.id
+    }
+
+    /// Token manager for this account's private connections
+    pub fn tokens(&self) -> &AutoRefreshTokenManager {
+        &self.tokens
+    }
+
+    /// Rate limiter shared by everything trading on this account
+    pub fn rate_limiter(&self) -> &SharedRateLimiter {
+        &self.rate_limiter
+    }
+
+    /// Trading client with a fresh token, this account's label and rate limiter
+    pub async fn trading_client(&self) -> Result<TradingClient, AuthError> {
+        let token = self.tokens.get_valid_token().await?;
+        Ok(TradingClient::new(token)
+            .with_account(self.id.clone())
+            .with_rate_limiter(Arc::clone(&self.rate_limiter)))
+    }
+
+    /// Order tracker that attributes its orders to this account
+    pub fn order_tracker(&self) -> OrderTracker {
+        OrderTracker::new().with_account(self.id.clone())
+    }
+}
+
+/// Accounts by id
+#[derive(Debug, Default)]
+pub struct AccountRegistry {
+    accounts: HashMap<AccountId, Arc<Account>>,
+}
+
+impl AccountRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load each named account from its `KRAKEN_<ACCOUNT>_*` variables
+    ///
+    /// See [`TokenManager::from_env_for`]. Fails on the first account with
+    /// missing credentials.
+    pub fn from_env<I>(accounts: I) -> Result<Self, AuthError>
+    where
+        I: IntoIterator,
+        I::Item: Into<AccountId>,
+    {
+        Self::from_vars(accounts, |key| std::env::var(key).ok())
+    }
+
+    /// Load each named account from variables supplied by `lookup`
+    ///
+    /// See [`TokenManager::from_vars_for`].
+    pub fn from_vars<I>(accounts: I, lookup: impl Fn(&str) -> Option<String>) -> Result<Self, AuthError>
+    where
+        I: IntoIterator,
+        I::Item: Into<AccountId>,
+    {
+        let mut registry = Self::new();
+        for account in accounts {
+            let tokens = AutoRefreshTokenManager::from_token_manager(
+                TokenManager::from_vars_for(account, &lookup)?,
+                AutoRefreshConfig::default(),
+            );
+            registry.register(Account::new(tokens));
+        }
+        Ok(registry)
+    }
+
+    /// Add an account, returning the one it replaces under the same id
+    pub fn register(&mut self, account: Account) -> Option<Arc<Account>> {
+        self.accounts.insert(account.id().clone(), Arc::new(account))
+    }
+
+    /// Remove an account
+    pub fn remove(&mut self, id: &AccountId) -> Option<Arc<Account>> {
+        self.accounts.remove(id)
+    }
+
+    /// Look up an account
+    pub fn get(&self, id: &AccountId) -> Option<Arc<Account>> {
+        self.accounts.get(id).cloned()
+    }
+
+    /// Registered account ids, sorted
+    pub fn ids(&self) -> Vec<AccountId> {
+        let mut ids: Vec<_> = self.accounts.keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Iterate over all accounts
+    pub fn iter(&self) -> impl Iterator<Item = &Arc<Account>> {
+        self.accounts.values()
+    }
+
+    /// Number of registered accounts
+    pub fn len(&self) -> usize {
+        self.accounts.len()
+    }
+
+    /// Whether no accounts are registered
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty()
+    }
+
+    /// Start background token refresh for every account
+    pub async fn start_auto_refresh(&self) {
+        for account in self.accounts.values() {
+            account.tokens.start_auto_refresh().await;
+        }
+    }
+
+    /// Stop background token refresh for every account
+    pub fn stop_auto_refresh(&self) {
+        for account in self.accounts.values() {
+            account.tokens.stop_auto_refresh();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account(id: &str) -> Account {
+        let manager = TokenManager::new("key", "c2VjcmV0").with_account(id);
+        Account::new(AutoRefreshTokenManager::from_token_manager(manager, AutoRefreshConfig::default()))
+    }
+
+    #[test]
+    fn test_registry_lookup_and_replace() {
+        let mut registry = AccountRegistry::new();
+        assert!(registry.register(account("maker")).is_none());
+        assert!(registry.register(account("hedge")).is_none());
+        assert!(registry.register(account("maker")).is_some());
+
+        assert_eq!(registry.len(), 2);
+        assert_eq!(registry.ids(), vec![AccountId::new("hedge"), AccountId::new("maker")]);
+        let maker = registry.get(&"maker".into()).unwrap();
+        assert_eq!(maker.tokens().account().as_str(), "maker");
+        assert_eq!(maker.order_tracker().account(), maker.id());
+        assert!(registry.remove(&"hedge".into()).is_some());
+        assert!(registry.get(&"hedge".into()).is_none());
+    }
+
+    #[test]
+    fn test_accounts_have_isolated_rate_limiters() {
+        let maker = account("maker");
+        let hedge = account("hedge");
+        assert!(!Arc::ptr_eq(maker.rate_limiter(), hedge.rate_limiter()));
+
+        let shared = Arc::new(KrakenRateLimiter::kraken_defaults());
+        let hedge = hedge.with_rate_limiter(Arc::clone(&shared));
+        assert!(Arc::ptr_eq(hedge.rate_limiter(), &shared));
+    }
+
+    #[test]
+    fn test_from_vars_reads_prefixed_variables() {
+        let vars: HashMap<&str, &str> = [
+            ("KRAKEN_DESK_B_API_KEY", "desk-key"),
+            ("KRAKEN_DESK_B_PRIVATE_KEY", "c2VjcmV0"),
+            ("KRAKEN_API_KEY", "default-key"),
+            ("KRAKEN_PRIVATE_KEY", "c2VjcmV0"),
+        ]
+        .into_iter()
+        .collect();
+        let lookup = |key: &str| vars.get(key).map(|v| v.to_string());
+
+        let registry = AccountRegistry::from_vars(["desk-b", "default"], lookup).unwrap();
+        assert_eq!(registry.ids(), vec![AccountId::new("default"), AccountId::new("desk-b")]);
+
+        let missing = AccountRegistry::from_vars(["nobody"], lookup).unwrap_err();
+        assert!(matches!(missing, AuthError::EnvVarNotSet(var) if var == "KRAKEN_NOBODY_API_KEY"));
+    }
+}
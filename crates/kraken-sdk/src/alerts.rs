@@ -0,0 +1,461 @@
+//! Price and spread alert rules
+//!
+//! [`AlertManager`] holds a set of rules and checks them against top of book
+//! quotes. A rule fires when its condition becomes true, then stays quiet
+//! until the condition has cleared again, so a price sitting above its
+//! threshold is reported once rather than on every update. The cooldown
+//! additionally suppresses re-fires from a price flapping around the
+//! threshold.
+//!
+//! Quotes can come from [`KrakenClient`], [`MarketState`] or raw orderbook
+//! events, the same as [`ArbitrageScanner`](crate::arbitrage::ArbitrageScanner).
+//! Trade flow rules ([`AlertKind::LargeTrade`] and the flow imbalance kinds)
+//! are checked per trade against a [`TradeFlow`] with
+//! [`AlertManager::check_flow`]; a large-trade rule fires for every
+//! qualifying trade, subject to its cooldown.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::alerts::{AlertKind, AlertManager, AlertRule};
+//! use rust_decimal_macros::dec;
+//! use std::time::{Duration, Instant};
+//!
+//! let mut alerts = AlertManager::new();
+//! let id = alerts.add_rule(
+//!     AlertRule::new("BTC/USD", AlertKind::PriceAbove, dec!(100000))
+//!         .with_cooldown(Duration::from_secs(60)),
+//! );
+//!
+//! let now = Instant::now();
+//! assert!(alerts.check_quote("BTC/USD", dec!(99990), dec!(99995), now).is_empty());
+//!
+//! let fired = alerts.check_quote("BTC/USD", dec!(100010), dec!(100020), now);
+//! assert_eq!(fired[0].id, id);
+//! assert_eq!(fired[0].value, dec!(100015));
+//!
+//! // Still above: no repeat
+//! assert!(alerts.check_quote("BTC/USD", dec!(100100), dec!(100110), now).is_empty());
+//! ```
+
+use crate::client::KrakenClient;
+use crate::market::MarketState;
+use crate::trade_flow::{ClassifiedTrade, TradeFlow};
+use kraken_types::Decimal;
+use kraken_ws::{Event, MarketEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Identifier returned by [`AlertManager::add_rule`]
+pub type AlertId = u64;
+
+/// What an alert rule watches
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    /// Mid price rises above the threshold
+    PriceAbove,
+    /// Mid price falls below the threshold
+    PriceBelow,
+    /// Spread widens beyond the threshold, in basis points of mid
+    SpreadAboveBps,
+    /// A single trade's notional reaches the threshold (quote currency)
+    LargeTrade,
+    /// Trade flow imbalance over the shortest window rises above the threshold (-1 to +1)
+    FlowImbalanceAbove,
+    /// Trade flow imbalance over the shortest window falls below the threshold (-1 to +1)
+    FlowImbalanceBelow,
+}
+
+impl AlertKind {
+    /// All kinds, in display order
+    pub fn all() -> &'static [AlertKind] {
+        &[
+            AlertKind::PriceAbove,
+            AlertKind::PriceBelow,
+            AlertKind::SpreadAboveBps,
+            AlertKind::LargeTrade,
+            AlertKind::FlowImbalanceAbove,
+            AlertKind::FlowImbalanceBelow,
+        ]
+    }
+
+    /// Short human-readable description
+    pub fn describe(&self) -> &'static str {
+        match self {
+            AlertKind::PriceAbove => "price above",
+            AlertKind::PriceBelow => "price below",
+            AlertKind::SpreadAboveBps => "spread above (bps)",
+            AlertKind::LargeTrade => "trade notional at least",
+            AlertKind::FlowImbalanceAbove => "flow imbalance above",
+            AlertKind::FlowImbalanceBelow => "flow imbalance below",
+        }
+    }
+
+    /// Returns true for kinds checked against trades rather than quotes
+    pub fn is_trade_based(&self) -> bool {
+        matches!(self, AlertKind::LargeTrade | AlertKind::FlowImbalanceAbove | AlertKind::FlowImbalanceBelow)
+    }
+}
+
+/// A condition on one symbol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlertRule {
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Condition type
+    pub kind: AlertKind,
+    /// Price, basis points for spread rules, notional for large trades or
+    /// imbalance ratio for flow rules
+    pub threshold: Decimal,
+    /// Minimum time between two firings
+    pub cooldown: Duration,
+}
+
+impl AlertRule {
+    /// Create a rule without a cooldown
+    pub fn new(symbol: impl Into<String>, kind: AlertKind, threshold: Decimal) -> Self {
+        Self {
+            symbol: symbol.into(),
+            kind,
+            threshold,
+            cooldown: Duration::ZERO,
+        }
+    }
+
+    /// Set the minimum time between two firings
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Value the rule compares against its threshold, if the quote is usable
+    fn observe(&self, bid: Decimal, ask: Decimal) -> Option<Decimal> {
+        let mid = (bid + ask) / Decimal::TWO;
+        if mid <= Decimal::ZERO {
+            return None;
+        }
+        match self.kind {
+            AlertKind::PriceAbove | AlertKind::PriceBelow => Some(mid),
+            AlertKind::SpreadAboveBps => Some((ask - bid) / mid * Decimal::from(10_000)),
+            _ => None,
+        }
+    }
+
+    /// Value a trade flow rule compares against its threshold
+    fn observe_trade(&self, trade: &ClassifiedTrade, imbalance: Option<Decimal>) -> Option<Decimal> {
+        match self.kind {
+            AlertKind::LargeTrade => Some(trade.notional().0),
+            AlertKind::FlowImbalanceAbove | AlertKind::FlowImbalanceBelow => imbalance,
+            _ => None,
+        }
+    }
+
+    fn holds(&self, value: Decimal) -> bool {
+        match self.kind {
+            AlertKind::PriceAbove | AlertKind::SpreadAboveBps | AlertKind::FlowImbalanceAbove => value > self.threshold,
+            AlertKind::PriceBelow | AlertKind::FlowImbalanceBelow => value < self.threshold,
+            AlertKind::LargeTrade => value >= self.threshold,
+        }
+    }
+}
+
+impl fmt::Display for AlertRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.symbol, self.kind.describe(), self.threshold)
+    }
+}
+
+/// A rule that fired
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlertTrigger {
+    /// Rule that fired
+    pub id: AlertId,
+    /// Rule as it was when it fired
+    pub rule: AlertRule,
+    /// Observed mid price, spread in basis points, trade notional or imbalance ratio
+    pub value: Decimal,
+}
+
+impl fmt::Display for AlertTrigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (now {})", self.rule, self.value.round_dp(2))
+    }
+}
+
+#[derive(Debug, Clone)]
+struct RuleState {
+    rule: AlertRule,
+    /// Condition held at the last check
+    active: bool,
+    /// Fired since the condition last cleared
+    fired: bool,
+    last_fired: Option<Instant>,
+}
+
+impl RuleState {
+    fn new(rule: AlertRule) -> Self {
+        Self {
+            rule,
+            active: false,
+            fired: false,
+            last_fired: None,
+        }
+    }
+
+    /// Update with an observed value; returns true if the rule fires
+    ///
+    /// `latched` rules fire once per crossing; others fire on every
+    /// observation that holds.
+    fn observe(&mut self, value: Decimal, latched: bool, now: Instant) -> bool {
+        self.active = self.rule.holds(value);
+        if !self.active {
+            self.fired = false;
+            return false;
+        }
+        let cooling = self
+            .last_fired
+            .is_some_and(|at| now.saturating_duration_since(at) < self.rule.cooldown);
+        if (latched && self.fired) || cooling {
+            return false;
+        }
+        self.fired = true;
+        self.last_fired = Some(now);
+        true
+    }
+}
+
+/// Set of alert rules with per-rule firing state
+#[derive(Debug, Clone, Default)]
+pub struct AlertManager {
+    rules: BTreeMap<AlertId, RuleState>,
+    next_id: AlertId,
+}
+
+impl AlertManager {
+    /// Create a manager without rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a rule
+    pub fn add_rule(&mut self, rule: AlertRule) -> AlertId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.rules.insert(id, RuleState::new(rule));
+        id
+    }
+
+    /// Replace a rule, resetting its firing state
+    ///
+    /// Returns false if no rule has this id.
+    pub fn update_rule(&mut self, id: AlertId, rule: AlertRule) -> bool {
+        match self.rules.get_mut(&id) {
+            Some(state) => {
+                *state = RuleState::new(rule);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove a rule
+    pub fn remove_rule(&mut self, id: AlertId) -> Option<AlertRule> {
+        self.rules.remove(&id).map(|state| state.rule)
+    }
+
+    /// Look up a rule
+    pub fn rule(&self, id: AlertId) -> Option<&AlertRule> {
+        self.rules.get(&id).map(|state| &state.rule)
+    }
+
+    /// All rules, in the order they were added
+    pub fn rules(&self) -> impl Iterator<Item = (AlertId, &AlertRule)> {
+        self.rules.iter().map(|(id, state)| (*id, &state.rule))
+    }
+
+    /// Returns true if the rule's condition held at the last check
+    pub fn is_active(&self, id: AlertId) -> bool {
+        self.rules.get(&id).is_some_and(|state| state.active)
+    }
+
+    /// Number of rules
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    /// Returns true if there are no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Check a symbol's rules against its best bid and ask
+    pub fn check_quote(&mut self, symbol: &str, bid: Decimal, ask: Decimal, now: Instant) -> Vec<AlertTrigger> {
+        let mut fired = Vec::new();
+        for (id, state) in self.rules.iter_mut().filter(|(_, s)| s.rule.symbol == symbol) {
+            let Some(value) = state.rule.observe(bid, ask) else {
+                continue;
+            };
+            if state.observe(value, true, now) {
+                fired.push(AlertTrigger {
+                    id: *id,
+                    rule: state.rule.clone(),
+                    value,
+                });
+            }
+        }
+        fired
+    }
+
+    /// Check a symbol's trade flow rules against a trade just recorded in `flow`
+    ///
+    /// Imbalance rules use the shortest window configured on `flow`.
+    pub fn check_flow(&mut self, flow: &TradeFlow, trade: &ClassifiedTrade, now: Instant) -> Vec<AlertTrigger> {
+        let imbalance = flow
+            .windows()
+            .first()
+            .and_then(|window| flow.imbalance(&trade.symbol, *window))
+            .map(|imbalance| imbalance.ratio);
+        let mut fired = Vec::new();
+        for (id, state) in self.rules.iter_mut().filter(|(_, s)| s.rule.symbol == trade.symbol) {
+            let Some(value) = state.rule.observe_trade(trade, imbalance) else {
+                continue;
+            };
+            let latched = state.rule.kind != AlertKind::LargeTrade;
+            if state.observe(value, latched, now) {
+                fired.push(AlertTrigger {
+                    id: *id,
+                    rule: state.rule.clone(),
+                    value,
+                });
+            }
+        }
+        fired
+    }
+
+    /// Check rules against an orderbook event
+    pub fn handle_event(&mut self, event: &Event) -> Vec<AlertTrigger> {
+        if let Event::Market(
+            MarketEvent::OrderbookSnapshot { symbol, snapshot, .. }
+            | MarketEvent::OrderbookUpdate { symbol, snapshot, .. },
+        ) = event
+        {
+            if let (Some(bid), Some(ask)) = (snapshot.best_bid_price(), snapshot.best_ask_price()) {
+                return self.check_quote(symbol, bid, ask, Instant::now());
+            }
+        }
+        Vec::new()
+    }
+
+    /// Check every rule against the client's live orderbooks
+    pub fn check_client(&mut self, client: &KrakenClient) -> Vec<AlertTrigger> {
+        let now = Instant::now();
+        let mut fired = Vec::new();
+        for symbol in client.symbols() {
+            if let (Some(bid), Some(ask)) = (client.best_bid(symbol), client.best_ask(symbol)) {
+                fired.extend(self.check_quote(symbol, bid, ask, now));
+            }
+        }
+        fired
+    }
+
+    /// Check every rule against the pairs tracked by a [`MarketState`]
+    pub fn check_market(&mut self, market: &MarketState) -> Vec<AlertTrigger> {
+        let now = Instant::now();
+        let mut fired = Vec::new();
+        for symbol in market.symbols() {
+            if let Some(bbo) = market.bbo(symbol) {
+                fired.extend(self.check_quote(symbol, bbo.bid.price.0, bbo.ask.price.0, now));
+            }
+        }
+        fired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_fires_once_per_crossing_and_respects_cooldown() {
+        let mut alerts = AlertManager::new();
+        let id = alerts.add_rule(
+            AlertRule::new("ETH/USD", AlertKind::PriceBelow, dec!(3000)).with_cooldown(Duration::from_secs(30)),
+        );
+        let t0 = Instant::now();
+
+        assert_eq!(alerts.check_quote("ETH/USD", dec!(2990), dec!(2992), t0).len(), 1);
+        assert!(alerts.is_active(id));
+        assert!(alerts.check_quote("ETH/USD", dec!(2980), dec!(2982), t0).is_empty());
+
+        // Clears and crosses again inside the cooldown: suppressed
+        assert!(alerts.check_quote("ETH/USD", dec!(3010), dec!(3012), t0 + Duration::from_secs(5)).is_empty());
+        assert!(!alerts.is_active(id));
+        assert!(alerts.check_quote("ETH/USD", dec!(2990), dec!(2992), t0 + Duration::from_secs(10)).is_empty());
+
+        // Still below once the cooldown ends: fires
+        let fired = alerts.check_quote("ETH/USD", dec!(2990), dec!(2992), t0 + Duration::from_secs(31));
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].value, dec!(2991));
+    }
+
+    #[test]
+    fn test_spread_rule_and_symbol_filter() {
+        let mut alerts = AlertManager::new();
+        alerts.add_rule(AlertRule::new("BTC/USD", AlertKind::SpreadAboveBps, dec!(5)));
+        let now = Instant::now();
+
+        assert!(alerts.check_quote("ETH/USD", dec!(100), dec!(200), now).is_empty());
+        assert!(alerts.check_quote("BTC/USD", dec!(50000), dec!(50010), now).is_empty());
+        let fired = alerts.check_quote("BTC/USD", dec!(50000), dec!(50050), now);
+        assert_eq!(fired.len(), 1);
+        assert!(fired[0].value > dec!(9.99) && fired[0].value < dec!(10));
+    }
+
+    #[test]
+    fn test_update_and_remove_rules() {
+        let mut alerts = AlertManager::new();
+        let a = alerts.add_rule(AlertRule::new("BTC/USD", AlertKind::PriceAbove, dec!(100)));
+        let b = alerts.add_rule(AlertRule::new("BTC/USD", AlertKind::PriceBelow, dec!(50)));
+        let now = Instant::now();
+        assert_eq!(alerts.check_quote("BTC/USD", dec!(200), dec!(200), now).len(), 1);
+
+        // Editing resets the rule, so it can fire again immediately
+        assert!(alerts.update_rule(a, AlertRule::new("BTC/USD", AlertKind::PriceAbove, dec!(150))));
+        assert_eq!(alerts.check_quote("BTC/USD", dec!(200), dec!(200), now)[0].id, a);
+
+        assert_eq!(alerts.remove_rule(b).unwrap().threshold, dec!(50));
+        assert!(!alerts.update_rule(b, AlertRule::new("BTC/USD", AlertKind::PriceBelow, dec!(1))));
+        assert_eq!(alerts.rules().map(|(id, _)| id).collect::<Vec<_>>(), vec![a]);
+    }
+
+    #[test]
+    fn test_trade_flow_rules() {
+        use kraken_types::{Price, Qty, Side};
+
+        let mut flow = TradeFlow::new()
+            .with_window(Duration::from_secs(10))
+            .with_large_trade_notional(dec!(10000));
+        let mut alerts = AlertManager::new();
+        let large = alerts.add_rule(AlertRule::new("BTC/USD", AlertKind::LargeTrade, dec!(10000)));
+        let selling = alerts.add_rule(AlertRule::new("BTC/USD", AlertKind::FlowImbalanceBelow, dec!(-0.5)));
+        let now = Instant::now();
+
+        // Quotes never trigger trade rules
+        assert!(alerts.check_quote("BTC/USD", dec!(1), dec!(100000), now).is_empty());
+
+        let mut sell = |qty, at_us| flow.record("BTC/USD", Price::new(dec!(100)), Qty::new(qty), Some(Side::Sell), at_us);
+        let first = sell(dec!(200), 0);
+        let second = sell(dec!(100), 1);
+        let fired = alerts.check_flow(&flow, &first, now);
+        assert_eq!(fired.iter().map(|t| t.id).collect::<Vec<_>>(), vec![large, selling]);
+
+        // Large trades fire every time; the imbalance rule stays latched
+        let fired = alerts.check_flow(&flow, &second, now);
+        assert_eq!(fired.len(), 1);
+        assert_eq!((fired[0].id, fired[0].value), (large, dec!(10000)));
+    }
+}
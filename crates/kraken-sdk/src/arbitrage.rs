@@ -0,0 +1,370 @@
+//! Cross-rate and triangular arbitrage detection
+//!
+//! [`ArbitrageScanner`] builds a graph of the subscribed pairs from their top
+//! of book and looks for three-leg cycles (e.g. USD → BTC → ETH → USD) whose
+//! combined rate beats 1 after fees. Each pair gives two edges: selling the
+//! base at the bid, and buying it at the ask.
+//!
+//! Prices can come from [`KrakenClient`], [`MarketState`] or raw orderbook
+//! events. [`ArbitrageScanner::scan`] returns alerts only when an opportunity
+//! crosses the threshold or falls back below it, so it can be called on every
+//! update.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::arbitrage::{ArbitrageAlert, ArbitrageScanner};
+//! use rust_decimal_macros::dec;
+//!
+//! let mut scanner = ArbitrageScanner::new().with_fee_rate(dec!(0.001));
+//! scanner.update_quote("BTC/USD", dec!(50000), dec!(50010));
+//! scanner.update_quote("ETH/USD", dec!(3000), dec!(3001));
+//! scanner.update_quote("ETH/BTC", dec!(0.0500), dec!(0.0501));
+//!
+//! // Implied ETH/BTC from the USD legs
+//! let implied = scanner.implied_rate("ETH", "BTC").unwrap();
+//! assert!(implied > dec!(0.0599));
+//!
+//! // ETH is cheap against BTC: buy ETH with BTC, sell for USD, buy BTC
+//! let alerts = scanner.scan();
+//! assert!(matches!(&alerts[0], ArbitrageAlert::Opened(opp) if opp.profit_bps > dec!(100)));
+//! ```
+
+use crate::client::KrakenClient;
+use crate::market::MarketState;
+use kraken_types::{Decimal, Side};
+use kraken_ws::{Event, MarketEvent};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Top of book for one pair
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PairQuote {
+    /// Best bid
+    pub bid: Decimal,
+    /// Best ask
+    pub ask: Decimal,
+}
+
+/// One trade in an arbitrage cycle
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitrageLeg {
+    /// Pair to trade
+    pub symbol: String,
+    /// Side of the trade (buy base or sell base)
+    pub side: Side,
+    /// Price the leg executes at (ask for buys, bid for sells)
+    pub price: Decimal,
+    /// Asset spent
+    pub from: String,
+    /// Asset received
+    pub to: String,
+}
+
+/// A three-leg cycle and its return
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArbitrageOpportunity {
+    /// Trades in order, starting and ending in the same asset
+    pub legs: Vec<ArbitrageLeg>,
+    /// Return multiple before fees (1.0 = break-even)
+    pub gross_return: Decimal,
+    /// Return multiple after fees on every leg
+    pub net_return: Decimal,
+    /// Net profit in basis points
+    pub profit_bps: Decimal,
+}
+
+impl ArbitrageOpportunity {
+    /// Assets visited, e.g. `["USD", "BTC", "ETH", "USD"]`
+    pub fn path(&self) -> Vec<&str> {
+        let mut path: Vec<&str> = self.legs.iter().map(|l| l.from.as_str()).collect();
+        if let Some(last) = self.legs.last() {
+            path.push(&last.to);
+        }
+        path
+    }
+
+    fn key(&self) -> String {
+        self.path().join(">")
+    }
+}
+
+/// Notification from [`ArbitrageScanner::scan`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArbitrageAlert {
+    /// A cycle's profit rose above the threshold
+    Opened(ArbitrageOpportunity),
+    /// A previously reported cycle fell back below the threshold
+    Closed {
+        /// Assets visited, e.g. `["USD", "BTC", "ETH", "USD"]`
+        path: Vec<String>,
+    },
+}
+
+/// Pair graph with fee-adjusted cycle detection
+#[derive(Debug, Clone)]
+pub struct ArbitrageScanner {
+    /// Quotes keyed by symbol
+    quotes: BTreeMap<String, PairQuote>,
+    /// Taker fee charged on every leg (0.0026 = 0.26%)
+    fee_rate: Decimal,
+    /// Minimum net profit to report
+    threshold_bps: Decimal,
+    /// Cycles reported as open
+    open: HashSet<String>,
+}
+
+impl Default for ArbitrageScanner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArbitrageScanner {
+    /// Create a scanner with no fees and a zero threshold
+    pub fn new() -> Self {
+        Self {
+            quotes: BTreeMap::new(),
+            fee_rate: Decimal::ZERO,
+            threshold_bps: Decimal::ZERO,
+            open: HashSet::new(),
+        }
+    }
+
+    /// Set the fee charged on each leg (fraction, e.g. `0.0026`)
+    pub fn with_fee_rate(mut self, fee_rate: Decimal) -> Self {
+        self.fee_rate = fee_rate;
+        self
+    }
+
+    /// Only report cycles with at least this net profit
+    pub fn with_threshold_bps(mut self, bps: Decimal) -> Self {
+        self.threshold_bps = bps;
+        self
+    }
+
+    /// Set the top of book for a pair
+    ///
+    /// Ignored if the symbol isn't BASE/QUOTE or either price isn't positive.
+    pub fn update_quote(&mut self, symbol: &str, bid: Decimal, ask: Decimal) {
+        if symbol.split_once('/').is_none() || bid <= Decimal::ZERO || ask <= Decimal::ZERO {
+            return;
+        }
+        self.quotes.insert(symbol.to_string(), PairQuote { bid, ask });
+    }
+
+    /// Remove a pair from the graph
+    pub fn remove_quote(&mut self, symbol: &str) {
+        self.quotes.remove(symbol);
+    }
+
+    /// Update from an orderbook event
+    pub fn handle_event(&mut self, event: &Event) {
+        if let Event::Market(
+            MarketEvent::OrderbookSnapshot { symbol, snapshot, .. }
+            | MarketEvent::OrderbookUpdate { symbol, snapshot, .. },
+        ) = event
+        {
+            if let (Some(bid), Some(ask)) = (snapshot.best_bid_price(), snapshot.best_ask_price()) {
+                self.update_quote(symbol, bid, ask);
+            }
+        }
+    }
+
+    /// Update every pair from the client's live orderbooks
+    pub fn update_from_client(&mut self, client: &KrakenClient) {
+        for symbol in client.symbols() {
+            if let (Some(bid), Some(ask)) = (client.best_bid(symbol), client.best_ask(symbol)) {
+                self.update_quote(symbol, bid, ask);
+            }
+        }
+    }
+
+    /// Update every pair tracked by a [`MarketState`]
+    pub fn update_from_market(&mut self, market: &MarketState) {
+        for symbol in market.symbols() {
+            if let Some(bbo) = market.bbo(symbol) {
+                self.update_quote(symbol, bbo.bid.price.0, bbo.ask.price.0);
+            }
+        }
+    }
+
+    /// Pairs currently in the graph
+    pub fn symbols(&self) -> impl Iterator<Item = &str> {
+        self.quotes.keys().map(String::as_str)
+    }
+
+    /// Legs out of each asset
+    fn edges(&self) -> HashMap<&str, Vec<ArbitrageLeg>> {
+        let mut edges: HashMap<&str, Vec<ArbitrageLeg>> = HashMap::new();
+        for (symbol, quote) in &self.quotes {
+            let Some((base, quote_asset)) = symbol.split_once('/') else {
+                continue;
+            };
+            edges.entry(base).or_default().push(ArbitrageLeg {
+                symbol: symbol.clone(),
+                side: Side::Sell,
+                price: quote.bid,
+                from: base.to_string(),
+                to: quote_asset.to_string(),
+            });
+            edges.entry(quote_asset).or_default().push(ArbitrageLeg {
+                symbol: symbol.clone(),
+                side: Side::Buy,
+                price: quote.ask,
+                from: quote_asset.to_string(),
+                to: base.to_string(),
+            });
+        }
+        edges
+    }
+
+    /// Units of `to` received per unit of `from` for one leg
+    fn leg_rate(leg: &ArbitrageLeg) -> Decimal {
+        match leg.side {
+            Side::Sell => leg.price,
+            Side::Buy => Decimal::ONE / leg.price,
+        }
+    }
+
+    /// Best executable rate from `from` to `to`, before fees
+    ///
+    /// Uses the direct pair or a route through one intermediate asset,
+    /// whichever gives more of `to`.
+    pub fn implied_rate(&self, from: &str, to: &str) -> Option<Decimal> {
+        let edges = self.edges();
+        let mut best: Option<Decimal> = None;
+        for first in edges.get(from)? {
+            let rate = Self::leg_rate(first);
+            if first.to == to {
+                best = best.max(Some(rate));
+                continue;
+            }
+            for second in edges.get(first.to.as_str()).into_iter().flatten() {
+                if second.to == to && second.symbol != first.symbol {
+                    best = best.max(Some(rate * Self::leg_rate(second)));
+                }
+            }
+        }
+        best
+    }
+
+    /// All three-leg cycles, best first
+    pub fn opportunities(&self) -> Vec<ArbitrageOpportunity> {
+        let edges = self.edges();
+        let assets: BTreeSet<&str> = edges.keys().copied().collect();
+        let keep = Decimal::ONE - self.fee_rate;
+        let fee_factor = keep * keep * keep;
+
+        let mut found = Vec::new();
+        for &start in &assets {
+            for a in edges.get(start).into_iter().flatten() {
+                // Visit each cycle once, from its smallest asset
+                if a.to.as_str() <= start {
+                    continue;
+                }
+                for b in edges.get(a.to.as_str()).into_iter().flatten() {
+                    if b.to.as_str() <= start || b.to == a.from {
+                        continue;
+                    }
+                    for c in edges.get(b.to.as_str()).into_iter().flatten() {
+                        if c.to != start {
+                            continue;
+                        }
+                        let gross = Self::leg_rate(a) * Self::leg_rate(b) * Self::leg_rate(c);
+                        let net = gross * fee_factor;
+                        found.push(ArbitrageOpportunity {
+                            legs: vec![a.clone(), b.clone(), c.clone()],
+                            gross_return: gross,
+                            net_return: net,
+                            profit_bps: (net - Decimal::ONE) * Decimal::from(10_000),
+                        });
+                    }
+                }
+            }
+        }
+        found.sort_by_key(|o| std::cmp::Reverse(o.profit_bps));
+        found
+    }
+
+    /// Report cycles that crossed the threshold since the last scan
+    pub fn scan(&mut self) -> Vec<ArbitrageAlert> {
+        let mut alerts = Vec::new();
+        let mut now_open = HashSet::new();
+        for opp in self.opportunities() {
+            if opp.profit_bps <= Decimal::ZERO || opp.profit_bps < self.threshold_bps {
+                continue;
+            }
+            let key = opp.key();
+            if !self.open.contains(&key) {
+                alerts.push(ArbitrageAlert::Opened(opp));
+            }
+            now_open.insert(key);
+        }
+        let mut closed: Vec<&String> = self.open.difference(&now_open).collect();
+        closed.sort();
+        for key in closed {
+            alerts.push(ArbitrageAlert::Closed {
+                path: key.split('>').map(str::to_string).collect(),
+            });
+        }
+        self.open = now_open;
+        alerts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn consistent() -> ArbitrageScanner {
+        let mut scanner = ArbitrageScanner::new().with_fee_rate(dec!(0.0026));
+        scanner.update_quote("BTC/USD", dec!(50000), dec!(50010));
+        scanner.update_quote("ETH/USD", dec!(3000), dec!(3001));
+        scanner.update_quote("ETH/BTC", dec!(0.0599), dec!(0.0601));
+        scanner
+    }
+
+    #[test]
+    fn test_consistent_prices_have_no_opportunity() {
+        let mut scanner = consistent();
+        let opps = scanner.opportunities();
+        // Two directions around the one triangle
+        assert_eq!(opps.len(), 2);
+        assert!(opps.iter().all(|o| o.net_return < Decimal::ONE));
+        assert!(scanner.scan().is_empty());
+    }
+
+    #[test]
+    fn test_mispricing_opens_and_closes() {
+        let mut scanner = consistent().with_threshold_bps(dec!(10));
+        scanner.update_quote("ETH/BTC", dec!(0.0620), dec!(0.0621));
+
+        let alerts = scanner.scan();
+        assert_eq!(alerts.len(), 1);
+        let ArbitrageAlert::Opened(opp) = &alerts[0] else { panic!() };
+        // Sell ETH for BTC at the rich cross, buy it back with USD
+        assert_eq!(opp.path(), vec!["BTC", "USD", "ETH", "BTC"]);
+        assert_eq!(opp.legs[2].side, Side::Sell);
+        assert!(opp.net_return < opp.gross_return);
+
+        // Already reported: no repeat
+        assert!(scanner.scan().is_empty());
+
+        scanner.update_quote("ETH/BTC", dec!(0.0599), dec!(0.0601));
+        assert_eq!(
+            scanner.scan(),
+            vec![ArbitrageAlert::Closed { path: vec!["BTC".into(), "USD".into(), "ETH".into(), "BTC".into()] }]
+        );
+    }
+
+    #[test]
+    fn test_implied_rate_prefers_best_route() {
+        let scanner = consistent();
+        // Direct: 1 / 0.0601 ETH per BTC; via USD: 50000 / 3001
+        let direct = Decimal::ONE / dec!(0.0601);
+        let via_usd = dec!(50000) * (Decimal::ONE / dec!(3001));
+        assert_eq!(scanner.implied_rate("BTC", "ETH"), Some(direct.max(via_usd)));
+        assert_eq!(scanner.implied_rate("BTC", "SOL"), None);
+    }
+}
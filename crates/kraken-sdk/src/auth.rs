@@ -36,9 +36,14 @@
 //!
 //! - `KRAKEN_API_KEY` - Your Kraken API key
 //! - `KRAKEN_PRIVATE_KEY` - Your Kraken private key (base64 encoded)
+//!
+//! [`TokenManager::from_env_for`] reads `KRAKEN_<ACCOUNT>_API_KEY` and
+//! `KRAKEN_<ACCOUNT>_PRIVATE_KEY` instead, for processes trading several
+//! accounts (see [`crate::accounts`]).
 
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
 use hmac::{Hmac, Mac};
+use kraken_types::AccountId;
 use parking_lot::RwLock;
 use reqwest::Client;
 use secrecy::{ExposeSecret, SecretString};
@@ -103,6 +108,8 @@ pub struct TokenManager {
     /// Private key stored securely (zeroized on drop)
     private_key: SecretString,
     client: Client,
+    /// Account these credentials belong to
+    account: AccountId,
 }
 
 impl Clone for TokenManager {
@@ -111,6 +118,7 @@ impl Clone for TokenManager {
             api_key: self.api_key.clone(),
             private_key: SecretString::from(self.private_key.expose_secret().to_string()),
             client: self.client.clone(),
+            account: self.account.clone(),
         }
     }
 }
@@ -124,9 +132,21 @@ impl TokenManager {
             api_key: api_key.into(),
             private_key: SecretString::from(private_key.into()),
             client: Client::new(),
+            account: AccountId::default(),
         }
     }
 
+    /// Label the account these credentials belong to
+    pub fn with_account(mut self, account: impl Into<AccountId>) -> Self {
+        self.account = account.into();
+        self
+    }
+
+    /// Account these credentials belong to
+    pub fn account(&self) -> &AccountId {
+        &self.account
+    }
+
     /// Create a TokenManager from environment variables
     ///
     /// Reads `KRAKEN_API_KEY` and `KRAKEN_PRIVATE_KEY` from environment.
@@ -139,11 +159,42 @@ impl TokenManager {
         Ok(Self::new(api_key, private_key))
     }
 
+    /// Create a TokenManager for one account from environment variables
+    ///
+    /// Reads `KRAKEN_<ACCOUNT>_API_KEY` and `KRAKEN_<ACCOUNT>_PRIVATE_KEY`,
+    /// with the account name upper-cased and `-` replaced by `_`. The
+    /// default account reads the plain variables, like [`from_env`](Self::from_env).
+    pub fn from_env_for(account: impl Into<AccountId>) -> Result<Self, AuthError> {
+        Self::from_vars_for(account, |key| std::env::var(key).ok())
+    }
+
+    /// Create a TokenManager for one account from variables supplied by `lookup`
+    ///
+    /// Same variable names as [`from_env_for`](Self::from_env_for), read
+    /// through `lookup` instead of the process environment, e.g. from a
+    /// config file or a secrets store.
+    pub fn from_vars_for(
+        account: impl Into<AccountId>,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, AuthError> {
+        let account = account.into();
+        let prefix = if account.is_default() {
+            "KRAKEN".to_string()
+        } else {
+            format!("KRAKEN_{}", account.as_str().to_uppercase().replace('-', "_"))
+        };
+        let var = |name: &str| {
+            let key = format!("{prefix}_{name}");
+            lookup(&key).ok_or(AuthError::EnvVarNotSet(key))
+        };
+        Ok(Self::new(var("API_KEY")?, var("PRIVATE_KEY")?).with_account(account))
+    }
+
     /// Get a WebSocket authentication token
     ///
     /// Tokens are valid for 15 minutes. Call this method periodically
     /// to refresh the token before it expires.
-    #[instrument(skip(self))]
+    #[instrument(skip(self), fields(account = %self.account))]
     pub async fn get_token(&self) -> Result<String, AuthError> {
         let nonce = self.generate_nonce()?;
         let post_data = format!("nonce={}", nonce);
@@ -372,6 +423,7 @@ struct AutoRefreshInner {
 impl std::fmt::Debug for AutoRefreshTokenManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("AutoRefreshTokenManager")
+            .field("account", self.account())
             .field("state", &self.state())
             .field("has_token", &self.has_valid_token())
             .finish()
@@ -390,52 +442,33 @@ impl AutoRefreshTokenManager {
         private_key: impl Into<String>,
         config: AutoRefreshConfig,
     ) -> Self {
-        let (state_tx, _) = watch::channel(TokenState::NotInitialized);
-
-        Self {
-            inner: Arc::new(AutoRefreshInner {
-                token_manager: TokenManager::new(api_key, private_key),
-                cached_token: RwLock::new(None),
-                state: RwLock::new(TokenState::NotInitialized),
-                state_tx,
-                config,
-                shutdown: RwLock::new(false),
-            }),
-        }
+        Self::from_token_manager(TokenManager::new(api_key, private_key), config)
     }
 
     /// Create from environment variables
     pub fn from_env() -> Result<Self, AuthError> {
-        let token_manager = TokenManager::from_env()?;
+        Ok(Self::from_token_manager(TokenManager::from_env()?, AutoRefreshConfig::default()))
+    }
+
+    /// Wrap an existing TokenManager, keeping its account label
+    pub fn from_token_manager(token_manager: TokenManager, config: AutoRefreshConfig) -> Self {
         let (state_tx, _) = watch::channel(TokenState::NotInitialized);
 
-        Ok(Self {
+        Self {
             inner: Arc::new(AutoRefreshInner {
                 token_manager,
                 cached_token: RwLock::new(None),
                 state: RwLock::new(TokenState::NotInitialized),
                 state_tx,
-                config: AutoRefreshConfig::default(),
+                config,
                 shutdown: RwLock::new(false),
             }),
-        })
+        }
     }
 
     /// Create from environment variables with custom config
     pub fn from_env_with_config(config: AutoRefreshConfig) -> Result<Self, AuthError> {
-        let token_manager = TokenManager::from_env()?;
-        let (state_tx, _) = watch::channel(TokenState::NotInitialized);
-
-        Ok(Self {
-            inner: Arc::new(AutoRefreshInner {
-                token_manager,
-                cached_token: RwLock::new(None),
-                state: RwLock::new(TokenState::NotInitialized),
-                state_tx,
-                config,
-                shutdown: RwLock::new(false),
-            }),
-        })
+        Ok(Self::from_token_manager(TokenManager::from_env()?, config))
     }
 
     /// Get the current token state
@@ -633,6 +666,11 @@ impl AutoRefreshTokenManager {
     pub fn token_manager(&self) -> &TokenManager {
         &self.inner.token_manager
     }
+
+    /// Account whose tokens this manager refreshes
+    pub fn account(&self) -> &AccountId {
+        self.inner.token_manager.account()
+    }
 }
 
 #[cfg(test)]
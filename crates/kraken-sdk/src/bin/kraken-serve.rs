@@ -0,0 +1,87 @@
+//! kraken-serve - Local HTTP market data gateway
+//!
+//! Connects to Kraken for the given symbols and serves books, tickers,
+//! health and a server-sent event stream over HTTP (see
+//! [`kraken_sdk::serve`]):
+//!
+//! ```text
+//! kraken-serve BTC/USD ETH/USD
+//! kraken-serve --addr 127.0.0.1:9000 --depth 25 SOL/USD
+//! curl http://127.0.0.1:8080/books/BTC/USD?depth=5
+//! ```
+
+use kraken_sdk::prelude::*;
+use kraken_sdk::serve::MarketGateway;
+use std::sync::Arc;
+
+const USAGE: &str = "usage: kraken-serve [--addr HOST:PORT] [--depth 10|25|100|500|1000] SYMBOL...";
+
+struct Args {
+    addr: String,
+    depth: Depth,
+    symbols: Vec<String>,
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = Args {
+        addr: "127.0.0.1:8080".to_string(),
+        depth: Depth::D10,
+        symbols: Vec::new(),
+    };
+    let mut iter = std::env::args().skip(1);
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--addr" => args.addr = iter.next().ok_or("--addr needs a value")?,
+            "--depth" => {
+                let value = iter.next().ok_or("--depth needs a value")?;
+                args.depth = match value.as_str() {
+                    "10" => Depth::D10,
+                    "25" => Depth::D25,
+                    "100" => Depth::D100,
+                    "500" => Depth::D500,
+                    "1000" => Depth::D1000,
+                    _ => return Err(format!("unsupported depth {value}")),
+                };
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {arg}\n{USAGE}")),
+            _ => args.symbols.push(arg),
+        }
+    }
+    if args.symbols.is_empty() {
+        return Err(USAGE.to_string());
+    }
+    Ok(args)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("{message}");
+            std::process::exit(2);
+        }
+    };
+
+    let mut client = KrakenClient::builder(args.symbols)
+        .with_depth(args.depth)
+        .with_book(true)
+        .with_ticker(true)
+        .with_trade(true)
+        .connect()
+        .await?;
+    let events = client.events().expect("events are taken once");
+    let client = Arc::new(client);
+
+    let gateway = MarketGateway::new(4096).with_health(move || client.health());
+    let listener = tokio::net::TcpListener::bind(&args.addr).await?;
+    eprintln!("serving on http://{}", listener.local_addr()?);
+
+    let server = gateway.clone();
+    tokio::select! {
+        result = server.serve_listener(listener) => result?,
+        _ = gateway.forward(events) => eprintln!("event stream ended"),
+    }
+    Ok(())
+}
@@ -0,0 +1,408 @@
+//! Blocking client for applications without an async runtime
+//!
+//! [`KrakenClientBlocking`] runs the async client on a dedicated runtime
+//! thread, so GUIs, plugins and other sync code can use the SDK without
+//! adopting Tokio. Queries read the shared orderbook state directly and
+//! return owned values; events are delivered to callbacks registered with
+//! [`on_event`](KrakenClientBlocking::on_event).
+//!
+//! Callbacks run one at a time on the runtime thread, not on the connection
+//! task: a slow callback delays later events but never the feed itself. A
+//! panicking callback is logged and counted, and dispatch carries on.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraken_sdk::prelude::*;
+//! use std::time::Duration;
+//!
+//! fn main() -> Result<(), KrakenError> {
+//!     let client = KrakenClient::builder(["BTC/USD"])
+//!         .with_depth(Depth::D10)
+//!         .connect_blocking()?;
+//!
+//!     client.on_event(|event| {
+//!         if let Event::Connection(change) = event {
+//!             println!("connection: {:?}", change);
+//!         }
+//!     });
+//!
+//!     if let Some(book) = client.wait_for_snapshot("BTC/USD", Duration::from_secs(10)) {
+//!         println!("BTC/USD mid: {:?}", book.mid_price());
+//!     }
+//!
+//!     client.shutdown();
+//!     Ok(())
+//! }
+//! ```
+
+use crate::builder::KrakenClientBuilder;
+use crate::client::KrakenClient;
+use kraken_book::OrderbookSnapshot;
+use kraken_types::{KrakenError, SystemStatus};
+use kraken_ws::{ClockEstimate, ConnectionState, Event, EventReceiver, HealthStats, LatencyStats};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, RwLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, Notify};
+use tracing::warn;
+
+/// How often [`KrakenClientBlocking::wait_for_snapshot`] checks the book
+const SYNC_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+type EventCallback = Arc<dyn Fn(&Event) + Send + Sync>;
+
+/// Registered event callbacks, shared with the runtime thread
+#[derive(Default)]
+struct Callbacks {
+    handlers: RwLock<Arc<Vec<EventCallback>>>,
+    panics: AtomicU64,
+    /// Signalled when a callback is registered
+    registered: Notify,
+}
+
+impl Callbacks {
+    fn register(&self, callback: EventCallback) {
+        let mut handlers = self.handlers.write().unwrap_or_else(|e| e.into_inner());
+        let mut next = Vec::clone(&handlers);
+        next.push(callback);
+        *handlers = Arc::new(next);
+        self.registered.notify_one();
+    }
+
+    fn dispatch(&self, event: &Event) {
+        // Clone the list so a callback can register another without deadlocking
+        let handlers = Arc::clone(&self.handlers.read().unwrap_or_else(|e| e.into_inner()));
+        for handler in handlers.iter() {
+            if catch_unwind(AssertUnwindSafe(|| handler(event))).is_err() {
+                self.panics.fetch_add(1, Ordering::Relaxed);
+                warn!("Event callback panicked");
+            }
+        }
+    }
+}
+
+/// Blocking facade over [`KrakenClient`]
+///
+/// Owns a runtime thread that drives the connection and dispatches events.
+/// Dropping the facade (or calling [`shutdown`](Self::shutdown)) stops the
+/// connection and joins that thread.
+pub struct KrakenClientBlocking {
+    /// Async client, driven by the runtime thread
+    client: KrakenClient,
+    /// Callbacks fed by the runtime thread
+    callbacks: Arc<Callbacks>,
+    /// Tells the runtime thread to stop
+    stop: Option<oneshot::Sender<()>>,
+    /// Runtime thread
+    thread: Option<JoinHandle<()>>,
+}
+
+impl KrakenClientBlocking {
+    /// Start a runtime thread and connect with this builder's configuration
+    ///
+    /// Returns once the connection task is running; like
+    /// [`KrakenClientBuilder::connect`], it does not wait for the socket to
+    /// open. Must not be called from within an async runtime.
+    pub fn connect(builder: KrakenClientBuilder) -> Result<Self, KrakenError> {
+        let callbacks = Arc::new(Callbacks::default());
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let (stop_tx, stop_rx) = oneshot::channel();
+
+        let dispatch = Arc::clone(&callbacks);
+        let thread = std::thread::Builder::new()
+            .name("kraken-sdk-runtime".to_string())
+            .spawn(move || {
+                let runtime = match tokio::runtime::Builder::new_multi_thread()
+                    .worker_threads(1)
+                    .thread_name("kraken-sdk-worker")
+                    .enable_all()
+                    .build()
+                {
+                    Ok(runtime) => runtime,
+                    Err(e) => {
+                        let _ = ready_tx.send(Err(KrakenError::Configuration(format!(
+                            "failed to start runtime: {}",
+                            e
+                        ))));
+                        return;
+                    }
+                };
+
+                runtime.block_on(async move {
+                    let mut client = match builder.connect().await {
+                        Ok(client) => client,
+                        Err(e) => {
+                            let _ = ready_tx.send(Err(e));
+                            return;
+                        }
+                    };
+                    let events = client.events();
+                    if ready_tx.send(Ok(client)).is_err() {
+                        return;
+                    }
+                    dispatch_events(events, &dispatch, stop_rx).await;
+                });
+                // Dropping the runtime here cancels the connection task
+            })
+            .map_err(|e| KrakenError::Configuration(format!("failed to spawn runtime thread: {}", e)))?;
+
+        match ready_rx.recv() {
+            Ok(Ok(client)) => Ok(Self {
+                client,
+                callbacks,
+                stop: Some(stop_tx),
+                thread: Some(thread),
+            }),
+            Ok(Err(e)) => {
+                let _ = thread.join();
+                Err(e)
+            }
+            Err(_) => {
+                let _ = thread.join();
+                Err(KrakenError::ChannelClosed)
+            }
+        }
+    }
+
+    /// Register a callback invoked for every event
+    ///
+    /// Callbacks run on the runtime thread in registration order. Events
+    /// queue until the first callback is registered, as they would for an
+    /// async client that hasn't started reading its event stream.
+    pub fn on_event<F>(&self, callback: F)
+    where
+        F: Fn(&Event) + Send + Sync + 'static,
+    {
+        self.callbacks.register(Arc::new(callback));
+    }
+
+    /// Number of event callback invocations that panicked
+    pub fn callback_panics(&self) -> u64 {
+        self.callbacks.panics.load(Ordering::Relaxed)
+    }
+
+    /// The underlying async client, for methods not mirrored here
+    ///
+    /// Its sync methods can be called from any thread; async ones need a
+    /// runtime of the caller's own.
+    pub fn client(&self) -> &KrakenClient {
+        &self.client
+    }
+
+    /// Get the current connection state
+    pub fn state(&self) -> ConnectionState {
+        self.client.state()
+    }
+
+    /// Check if connected
+    pub fn is_connected(&self) -> bool {
+        self.client.is_connected()
+    }
+
+    /// Latest exchange system status
+    pub fn system_status(&self) -> Option<SystemStatus> {
+        self.client.system_status()
+    }
+
+    /// Get the configured symbols
+    pub fn symbols(&self) -> &[String] {
+        self.client.symbols()
+    }
+
+    /// Copy of the current orderbook for a symbol
+    pub fn snapshot(&self, symbol: &str) -> Option<OrderbookSnapshot> {
+        self.client.orderbook_snapshot(symbol)
+    }
+
+    /// Block until the orderbook for a symbol is synced, then return a copy
+    ///
+    /// Returns None if it isn't synced within `timeout`.
+    pub fn wait_for_snapshot(&self, symbol: &str, timeout: Duration) -> Option<OrderbookSnapshot> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(book) = self.client.orderbook(symbol) {
+                if book.is_synced() {
+                    return Some(book.snapshot());
+                }
+            }
+            if Instant::now() >= deadline {
+                return None;
+            }
+            std::thread::sleep(SYNC_POLL_INTERVAL);
+        }
+    }
+
+    /// Get the best bid for a symbol
+    pub fn best_bid(&self, symbol: &str) -> Option<Decimal> {
+        self.client.best_bid(symbol)
+    }
+
+    /// Get the best ask for a symbol
+    pub fn best_ask(&self, symbol: &str) -> Option<Decimal> {
+        self.client.best_ask(symbol)
+    }
+
+    /// Get the spread for a symbol
+    pub fn spread(&self, symbol: &str) -> Option<Decimal> {
+        self.client.spread(symbol)
+    }
+
+    /// Get the mid price for a symbol
+    pub fn mid_price(&self, symbol: &str) -> Option<Decimal> {
+        self.client.mid_price(symbol)
+    }
+
+    /// Get the last checksum for a symbol
+    pub fn checksum(&self, symbol: &str) -> Option<u32> {
+        self.client.checksum(symbol)
+    }
+
+    /// Check if orderbook is synced for a symbol
+    pub fn is_synced(&self, symbol: &str) -> bool {
+        self.client.is_synced(symbol)
+    }
+
+    /// Get the number of events dropped due to backpressure
+    pub fn dropped_event_count(&self) -> u64 {
+        self.client.dropped_event_count()
+    }
+
+    /// Exchange-to-client latency statistics
+    pub fn latency_stats(&self) -> Option<LatencyStats> {
+        self.client.latency_stats()
+    }
+
+    /// Connection health statistics
+    pub fn health(&self) -> HealthStats {
+        self.client.health()
+    }
+
+    /// Local clock offset and drift relative to the exchange
+    pub fn clock_estimate(&self) -> Option<ClockEstimate> {
+        self.client.clock_estimate()
+    }
+
+    /// Approximate bytes held by all orderbooks
+    pub fn approx_memory_bytes(&self) -> usize {
+        self.client.approx_memory_bytes()
+    }
+
+    /// Approximate bytes held by each orderbook, by symbol
+    pub fn memory_usage(&self) -> BTreeMap<String, usize> {
+        self.client.memory_usage()
+    }
+
+    /// Stop the connection and join the runtime thread
+    pub fn shutdown(mut self) {
+        self.close();
+    }
+
+    fn close(&mut self) {
+        self.client.shutdown();
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(thread) = self.thread.take() {
+            // Joining from a callback would wait on ourselves
+            if thread.thread().id() != std::thread::current().id() {
+                let _ = thread.join();
+            }
+        }
+    }
+}
+
+impl Drop for KrakenClientBlocking {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// Feed events to the callbacks until told to stop
+async fn dispatch_events(events: Option<EventReceiver>, callbacks: &Callbacks, mut stop: oneshot::Receiver<()>) {
+    if let Some(mut events) = events {
+        tokio::select! {
+            _ = &mut stop => return,
+            _ = callbacks.registered.notified() => {}
+        }
+        loop {
+            tokio::select! {
+                _ = &mut stop => return,
+                event = events.recv() => match event {
+                    Some(event) => callbacks.dispatch(&event),
+                    None => break,
+                },
+            }
+        }
+    }
+    // Keep the connection running until the facade is shut down
+    let _ = stop.await;
+}
+
+impl KrakenClientBuilder {
+    /// Connect without an async runtime, see [`KrakenClientBlocking`]
+    pub fn connect_blocking(self) -> Result<KrakenClientBlocking, KrakenError> {
+        KrakenClientBlocking::connect(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_ws::ConnectionEvent;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn test_dispatch_survives_panicking_callback() {
+        let callbacks = Callbacks::default();
+        let seen = Arc::new(AtomicUsize::new(0));
+        callbacks.register(Arc::new(|_: &Event| panic!("bad callback")));
+        let counter = Arc::clone(&seen);
+        callbacks.register(Arc::new(move |_: &Event| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }));
+
+        let event = Event::Connection(ConnectionEvent::SubscriptionsRestored { count: 1 });
+        callbacks.dispatch(&event);
+        callbacks.dispatch(&event);
+
+        assert_eq!(seen.load(Ordering::Relaxed), 2);
+        assert_eq!(callbacks.panics.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_events_reach_callbacks_without_caller_runtime() {
+        // Nothing listens on port 1, so the one allowed attempt fails fast
+        let client = KrakenClient::builder(["BTC/USD"])
+            .with_proxy(kraken_ws::ProxyConfig::socks5("127.0.0.1", 1))
+            .without_reconnect()
+            .connect_blocking()
+            .unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let tx = std::sync::Mutex::new(tx);
+        client.on_event(move |event| {
+            if let Event::Connection(ConnectionEvent::ReconnectFailed { .. }) = event {
+                let _ = tx.lock().unwrap().send(());
+            }
+        });
+
+        assert!(rx.recv_timeout(Duration::from_secs(10)).is_ok());
+        assert!(!client.is_connected());
+        assert!(client.wait_for_snapshot("BTC/USD", Duration::from_millis(20)).is_none());
+        client.shutdown();
+    }
+
+    #[test]
+    fn test_empty_symbols_fail_and_join() {
+        let builder = KrakenClient::builder(Vec::<String>::new());
+        assert!(matches!(
+            builder.connect_blocking(),
+            Err(KrakenError::InvalidState { .. })
+        ));
+    }
+}
@@ -0,0 +1,663 @@
+//! Bounded-memory stores for trades and candles
+//!
+//! Long-running processes can't keep every trade or candle they see in
+//! memory. [`BoundedStore`] keeps the most recent `capacity` items in a ring
+//! buffer ordered by time; older items are dropped, or, with the `spill`
+//! feature and [`BoundedStore::with_spill`], appended to segment files on
+//! disk.
+//!
+//! Queries don't care where an item lives: [`BoundedStore::range`] and
+//! [`BoundedStore::last`] read spilled segments (memory-mapped, one at a
+//! time) when the requested window reaches past the in-memory horizon, and
+//! return owned items in time order.
+//!
+//! | Item | Time key |
+//! |------|----------|
+//! | [`TradeData`] | `timestamp` |
+//! | [`TradeRecord`] | `timestamp` |
+//! | [`OhlcData`] | `interval_begin` |
+//! | anything else | implement [`Timestamped`] |
+//!
+//! [`CandleStore`](crate::candles::CandleStore) and the trade history of
+//! [`MarketState`](crate::market::MarketState) keep one store per series
+//! and take a [`SpillConfig`] to spill each series into its own
+//! subdirectory.
+//!
+//! # Spill segments
+//!
+//! Each segment is a JSON-lines file named `segment-NNNNNNNN.jsonl` holding
+//! up to [`SpillConfig::segment_len`] items. Segments found in the directory
+//! when the store is created are indexed and served by queries, so history
+//! survives restarts. With [`SpillConfig::max_segments`] set, the oldest
+//! segment is deleted when a new one would exceed the limit. The directory
+//! belongs to the store: files changed by another process while mapped
+//! give undefined results. Lines that can't be decoded, such as a last line
+//! torn by a crash mid-write, are skipped with a warning.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::bounded_store::BoundedStore;
+//! use kraken_types::{Side, TradeData};
+//! use rust_decimal_macros::dec;
+//!
+//! let trade = |id: u64, second: u32| TradeData {
+//!     symbol: "BTC/USD".to_string(),
+//!     side: Side::Buy,
+//!     price: dec!(42000),
+//!     qty: dec!(0.1),
+//!     ord_type: "market".to_string(),
+//!     trade_id: id,
+//!     timestamp: format!("2024-01-01T00:00:{:02}.000000Z", second),
+//! };
+//!
+//! let mut store = BoundedStore::new(2);
+//! for (id, second) in [(1, 0), (2, 1), (3, 2)] {
+//!     assert!(store.push(trade(id, second)));
+//! }
+//! // Without spill, the oldest trade was dropped
+//! assert_eq!(store.memory_len(), 2);
+//! assert_eq!(store.evicted(), 1);
+//! let ids: Vec<u64> = store.last(5).unwrap().iter().map(|t| t.trade_id).collect();
+//! assert_eq!(ids, vec![2, 3]);
+//! ```
+
+use crate::market::TradeRecord;
+use chrono::DateTime;
+use kraken_types::{OhlcData, TradeData};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::VecDeque;
+
+#[cfg(feature = "spill")]
+use std::path::PathBuf;
+
+/// Item with a position in time
+pub trait Timestamped {
+    /// Time key in Unix microseconds, or None if it can't be determined
+    fn timestamp_us(&self) -> Option<i64>;
+}
+
+impl Timestamped for TradeData {
+    fn timestamp_us(&self) -> Option<i64> {
+        rfc3339_us(&self.timestamp)
+    }
+}
+
+impl Timestamped for TradeRecord {
+    fn timestamp_us(&self) -> Option<i64> {
+        rfc3339_us(&self.timestamp)
+    }
+}
+
+impl Timestamped for OhlcData {
+    fn timestamp_us(&self) -> Option<i64> {
+        rfc3339_us(&self.interval_begin)
+    }
+}
+
+fn rfc3339_us(timestamp: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.timestamp_micros())
+}
+
+/// Error reading or writing spilled items
+#[derive(Debug, thiserror::Error)]
+pub enum StoreError {
+    /// Segment file couldn't be created, written, mapped or removed
+    #[error("spill I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    /// Item couldn't be encoded, or a segment line couldn't be decoded
+    #[error("spill encoding error: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Where and how evicted items are spilled to disk
+#[cfg(feature = "spill")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpillConfig {
+    /// Directory holding the segment files (created if missing)
+    pub dir: PathBuf,
+    /// Items per segment file
+    pub segment_len: usize,
+    /// Segments kept on disk; the oldest is deleted beyond this (None = unbounded)
+    pub max_segments: Option<usize>,
+}
+
+#[cfg(feature = "spill")]
+impl SpillConfig {
+    /// Spill to `dir` in segments of 10 000 items, without a disk limit
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            segment_len: 10_000,
+            max_segments: None,
+        }
+    }
+
+    /// Set the number of items per segment
+    pub fn with_segment_len(mut self, segment_len: usize) -> Self {
+        self.segment_len = segment_len.max(1);
+        self
+    }
+
+    /// Keep at most `max_segments` segments on disk
+    pub fn with_max_segments(mut self, max_segments: usize) -> Self {
+        self.max_segments = Some(max_segments.max(1));
+        self
+    }
+
+    /// Same settings in a subdirectory for one series, e.g. `"BTC/USD"`
+    ///
+    /// Characters other than ASCII letters, digits, `-` and `_` are
+    /// replaced with `_`.
+    pub fn for_series(&self, series: &str) -> Self {
+        let name: String = series
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        Self {
+            dir: self.dir.join(name),
+            ..self.clone()
+        }
+    }
+}
+
+/// Time-ordered ring buffer with optional spill to disk
+#[derive(Debug)]
+pub struct BoundedStore<T> {
+    memory: VecDeque<(i64, T)>,
+    capacity: usize,
+    evicted: u64,
+    #[cfg(feature = "spill")]
+    spill: Option<spill::SpillLog>,
+}
+
+impl<T> BoundedStore<T>
+where
+    T: Timestamped + Clone + Serialize + DeserializeOwned,
+{
+    /// Create a store keeping at most `capacity` items in memory
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            memory: VecDeque::new(),
+            capacity: capacity.max(1),
+            evicted: 0,
+            #[cfg(feature = "spill")]
+            spill: None,
+        }
+    }
+
+    /// Spill evicted items to disk instead of dropping them
+    ///
+    /// Segments already in `config.dir` are indexed and become queryable.
+    #[cfg(feature = "spill")]
+    pub fn with_spill(mut self, config: SpillConfig) -> Result<Self, StoreError> {
+        self.spill = Some(spill::SpillLog::open(config)?);
+        Ok(self)
+    }
+
+    /// Store for one series of a multi-series owner
+    ///
+    /// Spills to the series' subdirectory of `spill` if given. A spill
+    /// directory that can't be opened is logged and the series is kept in
+    /// memory only.
+    #[cfg(feature = "spill")]
+    pub(crate) fn for_series(capacity: usize, spill: Option<&SpillConfig>, series: &str) -> Self {
+        let Some(config) = spill else {
+            return Self::new(capacity);
+        };
+        let config = config.for_series(series);
+        let dir = config.dir.clone();
+        Self::new(capacity).with_spill(config).unwrap_or_else(|e| {
+            tracing::warn!("Can't spill {} to {}: {}", series, dir.display(), e);
+            Self::new(capacity)
+        })
+    }
+
+    /// Add an item, evicting the oldest one past capacity
+    ///
+    /// Returns false, without storing it, if the item has no timestamp.
+    /// Items older than the newest one are inserted in time order. A failed
+    /// spill write is logged and the evicted item is lost.
+    pub fn push(&mut self, item: T) -> bool {
+        let Some(at_us) = item.timestamp_us() else {
+            return false;
+        };
+        let index = self.memory.partition_point(|(t, _)| *t <= at_us);
+        self.memory.insert(index, (at_us, item));
+        while self.memory.len() > self.capacity {
+            if let Some((at_us, oldest)) = self.memory.pop_front() {
+                self.evict(at_us, oldest);
+            }
+        }
+        true
+    }
+
+    /// Add an item, replacing an in-memory item with the same time
+    ///
+    /// Returns false, without storing it, if the item has no timestamp.
+    pub fn upsert(&mut self, item: T) -> bool {
+        let Some(at_us) = item.timestamp_us() else {
+            return false;
+        };
+        let index = self.memory.partition_point(|(t, _)| *t < at_us);
+        match self.memory.get_mut(index) {
+            Some((t, existing)) if *t == at_us => {
+                *existing = item;
+                true
+            }
+            _ => self.push(item),
+        }
+    }
+
+    /// Whether an item with this time is in memory
+    pub fn contains(&self, at_us: i64) -> bool {
+        let index = self.memory.partition_point(|(t, _)| *t < at_us);
+        self.memory.get(index).is_some_and(|(t, _)| *t == at_us)
+    }
+
+    /// Drop every item in memory; spilled items stay on disk
+    pub fn clear(&mut self) {
+        self.memory.clear();
+    }
+
+    #[cfg(feature = "spill")]
+    fn evict(&mut self, at_us: i64, item: T) {
+        self.evicted += 1;
+        if let Some(spill) = &mut self.spill {
+            if let Err(e) = spill.append(at_us, &item) {
+                tracing::warn!("Failed to spill evicted item: {}", e);
+            }
+        }
+    }
+
+    #[cfg(not(feature = "spill"))]
+    fn evict(&mut self, _at_us: i64, _item: T) {
+        self.evicted += 1;
+    }
+
+    /// Items with `from_us <= time < to_us`, oldest first
+    ///
+    /// Reads spilled segments when the window starts before the in-memory
+    /// horizon.
+    pub fn range(&mut self, from_us: i64, to_us: i64) -> Result<Vec<T>, StoreError> {
+        let before_horizon = !matches!(self.horizon_us(), Some(horizon) if from_us >= horizon);
+        let mut items = if before_horizon {
+            self.read_spilled(from_us, to_us)?
+        } else {
+            Vec::new()
+        };
+        items.extend(self.memory.iter().filter(|(t, _)| (from_us..to_us).contains(t)).cloned());
+        items.sort_by_key(|(t, _)| *t);
+        Ok(items.into_iter().map(|(_, item)| item).collect())
+    }
+
+    /// The `n` newest items, oldest first, reading spilled items if needed
+    pub fn last(&mut self, n: usize) -> Result<Vec<T>, StoreError> {
+        let in_memory = n.min(self.memory.len());
+        let mut items = if n > in_memory {
+            self.read_spilled_last(n - in_memory)?
+        } else {
+            Vec::new()
+        };
+        items.extend(self.memory.iter().skip(self.memory.len() - in_memory).map(|(_, item)| item.clone()));
+        Ok(items)
+    }
+
+    #[cfg(feature = "spill")]
+    fn read_spilled(&mut self, from_us: i64, to_us: i64) -> Result<Vec<(i64, T)>, StoreError> {
+        self.spill.as_mut().map_or(Ok(Vec::new()), |spill| spill.read(from_us, to_us))
+    }
+
+    #[cfg(not(feature = "spill"))]
+    fn read_spilled(&mut self, _from_us: i64, _to_us: i64) -> Result<Vec<(i64, T)>, StoreError> {
+        Ok(Vec::new())
+    }
+
+    #[cfg(feature = "spill")]
+    fn read_spilled_last(&mut self, n: usize) -> Result<Vec<T>, StoreError> {
+        self.spill.as_mut().map_or(Ok(Vec::new()), |spill| spill.read_last(n))
+    }
+
+    #[cfg(not(feature = "spill"))]
+    fn read_spilled_last(&mut self, _n: usize) -> Result<Vec<T>, StoreError> {
+        Ok(Vec::new())
+    }
+
+    /// Items currently in memory, oldest first
+    pub fn in_memory(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.memory.iter().map(|(_, item)| item)
+    }
+
+    /// Newest item
+    pub fn latest(&self) -> Option<&T> {
+        self.memory.back().map(|(_, item)| item)
+    }
+
+    /// Time of the oldest item in memory; earlier queries go to disk
+    pub fn horizon_us(&self) -> Option<i64> {
+        self.memory.front().map(|(t, _)| *t)
+    }
+
+    /// Items in memory
+    pub fn memory_len(&self) -> usize {
+        self.memory.len()
+    }
+
+    /// Items on disk
+    pub fn spilled_len(&self) -> usize {
+        #[cfg(feature = "spill")]
+        return self.spill.as_ref().map_or(0, |spill| spill.len());
+        #[cfg(not(feature = "spill"))]
+        0
+    }
+
+    /// Items evicted from memory since creation, spilled or dropped
+    pub fn evicted(&self) -> u64 {
+        self.evicted
+    }
+
+    /// Maximum items kept in memory
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+#[cfg(feature = "spill")]
+mod spill {
+    use super::{SpillConfig, StoreError};
+    use memmap2::Mmap;
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
+    use std::fs::{self, File, OpenOptions};
+    use std::io::{BufWriter, Write};
+    use std::path::PathBuf;
+    use tracing::warn;
+
+    /// One spilled item, as written to a segment line
+    #[derive(Serialize, Deserialize)]
+    struct Line<T> {
+        at_us: i64,
+        item: T,
+    }
+
+    #[derive(Debug)]
+    struct Segment {
+        path: PathBuf,
+        first_us: i64,
+        last_us: i64,
+        len: usize,
+    }
+
+    /// Append-only segment files, indexed by time
+    #[derive(Debug)]
+    pub(super) struct SpillLog {
+        config: SpillConfig,
+        segments: Vec<Segment>,
+        /// Writer of the last segment, while it has room
+        writer: Option<BufWriter<File>>,
+        next_index: u64,
+    }
+
+    impl SpillLog {
+        pub(super) fn open(config: SpillConfig) -> Result<Self, StoreError> {
+            fs::create_dir_all(&config.dir)?;
+            let mut indexed: Vec<(u64, PathBuf)> = fs::read_dir(&config.dir)?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter_map(|path| segment_index(&path).map(|index| (index, path)))
+                .collect();
+            indexed.sort();
+
+            let next_index = indexed.last().map_or(0, |(index, _)| index + 1);
+            let mut segments = Vec::with_capacity(indexed.len());
+            for (_, path) in indexed {
+                let times: Vec<i64> = lines::<serde_json::Value>(&path)?
+                    .into_iter()
+                    .map(|line| line.at_us)
+                    .collect();
+                if let (Some(first_us), Some(last_us)) = (times.iter().min(), times.iter().max()) {
+                    segments.push(Segment {
+                        first_us: *first_us,
+                        last_us: *last_us,
+                        len: times.len(),
+                        path,
+                    });
+                }
+            }
+            Ok(Self {
+                config,
+                segments,
+                writer: None,
+                next_index,
+            })
+        }
+
+        pub(super) fn append<T: Serialize>(&mut self, at_us: i64, item: &T) -> Result<(), StoreError> {
+            let line = serde_json::to_string(&Line { at_us, item })?;
+            let writer = match self.writer.take() {
+                Some(writer) if self.segments.last().is_some_and(|s| s.len < self.config.segment_len) => writer,
+                finished => {
+                    if let Some(mut finished) = finished {
+                        finished.flush()?;
+                    }
+                    self.start_segment(at_us)?
+                }
+            };
+            let writer = self.writer.insert(writer);
+            writeln!(writer, "{}", line)?;
+
+            let segment = self.segments.last_mut().expect("segment started before writing");
+            segment.first_us = segment.first_us.min(at_us);
+            segment.last_us = segment.last_us.max(at_us);
+            segment.len += 1;
+            Ok(())
+        }
+
+        fn start_segment(&mut self, at_us: i64) -> Result<BufWriter<File>, StoreError> {
+            if let Some(max) = self.config.max_segments {
+                while self.segments.len() >= max {
+                    let oldest = self.segments.remove(0);
+                    fs::remove_file(&oldest.path)?;
+                }
+            }
+            let path = self.config.dir.join(format!("segment-{:08}.jsonl", self.next_index));
+            self.next_index += 1;
+            let file = OpenOptions::new().create(true).append(true).open(&path)?;
+            self.segments.push(Segment {
+                path,
+                first_us: at_us,
+                last_us: at_us,
+                len: 0,
+            });
+            Ok(BufWriter::new(file))
+        }
+
+        pub(super) fn len(&self) -> usize {
+            self.segments.iter().map(|segment| segment.len).sum()
+        }
+
+        /// Spilled items with `from_us <= time < to_us`
+        pub(super) fn read<T: DeserializeOwned>(&mut self, from_us: i64, to_us: i64) -> Result<Vec<(i64, T)>, StoreError> {
+            self.flush()?;
+            let mut items = Vec::new();
+            for segment in self.segments.iter().filter(|s| s.first_us < to_us && s.last_us >= from_us) {
+                items.extend(
+                    lines::<T>(&segment.path)?
+                        .into_iter()
+                        .filter(|line| (from_us..to_us).contains(&line.at_us))
+                        .map(|line| (line.at_us, line.item)),
+                );
+            }
+            Ok(items)
+        }
+
+        /// The `n` newest spilled items, oldest first
+        pub(super) fn read_last<T: DeserializeOwned>(&mut self, n: usize) -> Result<Vec<T>, StoreError> {
+            self.flush()?;
+            let mut items: Vec<Line<T>> = Vec::new();
+            let mut needed = n;
+            for segment in self.segments.iter().rev() {
+                if needed == 0 {
+                    break;
+                }
+                let mut segment_items = lines::<T>(&segment.path)?;
+                needed = needed.saturating_sub(segment_items.len());
+                segment_items.append(&mut items);
+                items = segment_items;
+            }
+            items.sort_by_key(|line| line.at_us);
+            let skip = items.len().saturating_sub(n);
+            Ok(items.into_iter().skip(skip).map(|line| line.item).collect())
+        }
+
+        fn flush(&mut self) -> Result<(), StoreError> {
+            if let Some(writer) = &mut self.writer {
+                writer.flush()?;
+            }
+            Ok(())
+        }
+    }
+
+    /// Decode every line of a segment through a read-only mapping
+    ///
+    /// Undecodable lines, such as a last line torn by a crash mid-write, are
+    /// skipped with a warning.
+    fn lines<T: DeserializeOwned>(path: &PathBuf) -> Result<Vec<Line<T>>, StoreError> {
+        let file = File::open(path)?;
+        if file.metadata()?.len() == 0 {
+            return Ok(Vec::new());
+        }
+        // SAFETY: segment files are only written by the owning store, which
+        // flushes before mapping and never truncates a file it still indexes.
+        let map = unsafe { Mmap::map(&file)? };
+        let lines = map
+            .split(|byte| *byte == b'\n')
+            .filter(|line| !line.is_empty())
+            .filter_map(|line| match serde_json::from_slice(line) {
+                Ok(line) => Some(line),
+                Err(e) => {
+                    warn!("Skipping unreadable spill line in {}: {}", path.display(), e);
+                    None
+                }
+            })
+            .collect();
+        Ok(lines)
+    }
+
+    /// Index of a `segment-NNNNNNNN.jsonl` file
+    fn segment_index(path: &std::path::Path) -> Option<u64> {
+        path.file_name()?
+            .to_str()?
+            .strip_prefix("segment-")?
+            .strip_suffix(".jsonl")?
+            .parse()
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_types::Side;
+    use rust_decimal_macros::dec;
+
+    fn trade(id: u64, second: u32) -> TradeData {
+        TradeData {
+            symbol: "BTC/USD".to_string(),
+            side: Side::Sell,
+            price: dec!(42000),
+            qty: dec!(0.5),
+            ord_type: "limit".to_string(),
+            trade_id: id,
+            timestamp: format!("2024-01-01T00:00:{:02}Z", second),
+        }
+    }
+
+    fn ids(trades: &[TradeData]) -> Vec<u64> {
+        trades.iter().map(|t| t.trade_id).collect()
+    }
+
+    fn second_us(second: i64) -> i64 {
+        1_704_067_200_000_000 + second * 1_000_000
+    }
+
+    #[test]
+    fn test_ring_keeps_newest_in_time_order() {
+        let mut store = BoundedStore::new(3);
+        for (id, second) in [(1, 0), (2, 1), (4, 3), (3, 2), (5, 4)] {
+            assert!(store.push(trade(id, second)));
+        }
+        let mut bad = trade(6, 5);
+        bad.timestamp = "yesterday".to_string();
+        assert!(!store.push(bad));
+
+        assert_eq!(ids(&store.last(10).unwrap()), vec![3, 4, 5]);
+        assert_eq!(ids(&store.range(second_us(0), second_us(4)).unwrap()), vec![3, 4]);
+        assert_eq!(store.horizon_us(), Some(second_us(2)));
+        assert_eq!(store.evicted(), 2);
+        assert_eq!(store.spilled_len(), 0);
+    }
+
+    #[cfg(feature = "spill")]
+    #[test]
+    fn test_spilled_items_are_queried_and_reindexed() {
+        let dir = std::env::temp_dir().join(format!("havklo-bounded-store-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = SpillConfig::new(&dir).with_segment_len(2).with_max_segments(2);
+
+        let mut store = BoundedStore::new(2).with_spill(config.clone()).unwrap();
+        for id in 0..8 {
+            store.push(trade(id, id as u32));
+        }
+        // 6 evicted into segments of 2, of which the newest 2 are kept
+        assert_eq!(store.spilled_len(), 4);
+        assert_eq!(ids(&store.range(second_us(0), second_us(10)).unwrap()), vec![2, 3, 4, 5, 6, 7]);
+        assert_eq!(ids(&store.range(second_us(3), second_us(7)).unwrap()), vec![3, 4, 5, 6]);
+        assert_eq!(ids(&store.last(3).unwrap()), vec![5, 6, 7]);
+        drop(store);
+
+        let mut reopened: BoundedStore<TradeData> = BoundedStore::new(2).with_spill(config).unwrap();
+        assert_eq!(reopened.spilled_len(), 4);
+        reopened.push(trade(8, 8));
+        assert_eq!(ids(&reopened.last(10).unwrap()), vec![2, 3, 4, 5, 8]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "spill")]
+    #[test]
+    fn test_torn_last_line_is_skipped_on_reopen() {
+        use std::io::Write;
+
+        let dir = std::env::temp_dir().join(format!("havklo-bounded-store-torn-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let config = SpillConfig::new(&dir).with_segment_len(10);
+
+        let mut store = BoundedStore::new(1).with_spill(config.clone()).unwrap();
+        for id in 0..3 {
+            store.push(trade(id, id as u32));
+        }
+        drop(store);
+        let segment = dir.join("segment-00000000.jsonl");
+        let mut file = std::fs::OpenOptions::new().append(true).open(&segment).unwrap();
+        write!(file, r#"{{"at_us":1704067203000000,"item":{{"symbol":"BTC"#).unwrap();
+        drop(file);
+
+        let mut reopened: BoundedStore<TradeData> = BoundedStore::new(1).with_spill(config).unwrap();
+        assert_eq!(reopened.spilled_len(), 2);
+        reopened.push(trade(3, 3));
+        reopened.push(trade(4, 4));
+        assert_eq!(ids(&reopened.range(second_us(0), second_us(10)).unwrap()), vec![0, 1, 3, 4]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_upsert_replaces_same_time() {
+        let mut store = BoundedStore::new(3);
+        store.push(trade(1, 0));
+        store.push(trade(2, 1));
+        assert!(store.upsert(trade(3, 1)));
+        assert!(store.upsert(trade(4, 2)));
+        assert_eq!(ids(&store.last(10).unwrap()), vec![1, 3, 4]);
+        assert!(store.contains(second_us(2)));
+        assert!(!store.contains(second_us(3)));
+    }
+}
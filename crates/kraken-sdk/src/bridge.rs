@@ -0,0 +1,407 @@
+//! Local market data bridge for non-Rust consumers
+//!
+//! [`MarketDataBridge`] re-publishes the SDK's normalized event stream over
+//! a local socket, so research notebooks and dashboards can consume books,
+//! trades and tickers without speaking Kraken's protocol.
+//!
+//! # Wire format
+//!
+//! Each message is one line of JSON, tagged by `type`:
+//!
+//! | `type`     | fields                                                     |
+//! |------------|------------------------------------------------------------|
+//! | `book`     | `symbol`, `bids`, `asks`, `checksum`, `state`              |
+//! | `book_diff`| `symbol`, `bids`/`asks` (`added`, `removed`, `changed`), `checksum`, `state` |
+//! | `trade`    | `symbol`, `side`, `price`, `qty`, `ord_type`, `trade_id`, `timestamp` |
+//! | `ticker`   | `symbol`, `bid`, `bid_qty`, `ask`, `ask_qty`, `last`, `volume`, ... |
+//! | `status`   | `connected`                                                |
+//!
+//! Decimals are strings. A client first receives a `book` message for every
+//! book the bridge has seen, then live messages; books are updated with
+//! `book_diff` messages (see [`kraken_book::SnapshotDiff`]) that apply to the
+//! previous state. Reading it from Python takes a few lines:
+//!
+//! ```python
+//! import json, socket
+//! s = socket.socket(socket.AF_UNIX)
+//! s.connect("/tmp/havklo.sock")
+//! for line in s.makefile():
+//!     msg = json.loads(line)
+//! ```
+//!
+//! Slow clients don't block the bridge: a client that falls more than the
+//! channel capacity behind is disconnected and can reconnect for a fresh
+//! set of snapshots.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraken_sdk::bridge::MarketDataBridge;
+//! use kraken_sdk::prelude::*;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut client = KrakenClient::builder(["BTC/USD"]).with_book(true).connect().await?;
+//! let events = client.events().unwrap();
+//!
+//! let bridge = MarketDataBridge::new(1024);
+//! let server = bridge.clone();
+//! tokio::spawn(async move { server.serve_unix("/tmp/havklo.sock").await });
+//! bridge.forward(events).await;
+//! # Ok(())
+//! # }
+//! ```
+
+use kraken_book::{OrderbookSnapshot, OrderbookState, SnapshotDiff};
+use kraken_types::{Level, TickerData, TradeData};
+use kraken_ws::{ConnectionEvent, Event, EventReceiver, MarketEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+/// One message on the bridge wire
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BridgeMessage {
+    /// Full orderbook state
+    Book {
+        /// Trading pair symbol
+        symbol: String,
+        /// Bid levels, best first
+        bids: Vec<Level>,
+        /// Ask levels, best first
+        asks: Vec<Level>,
+        /// Book checksum
+        checksum: u32,
+        /// Synchronization state
+        state: OrderbookState,
+    },
+    /// Changes since the previous book message for the symbol
+    BookDiff(SnapshotDiff),
+    /// Public trade
+    Trade(TradeData),
+    /// Ticker update
+    Ticker(TickerData),
+    /// Upstream connection status
+    Status {
+        /// Whether the bridge's Kraken connection is up
+        connected: bool,
+    },
+}
+
+impl BridgeMessage {
+    /// Full book message for a snapshot
+    pub fn book(snapshot: &OrderbookSnapshot) -> Self {
+        Self::Book {
+            symbol: snapshot.symbol.clone(),
+            bids: snapshot.bids.clone(),
+            asks: snapshot.asks.clone(),
+            checksum: snapshot.checksum,
+            state: snapshot.state,
+        }
+    }
+
+    /// Encode as one line of JSON (including the trailing newline)
+    pub fn to_line(&self) -> String {
+        let mut line = serde_json::to_string(self).unwrap_or_default();
+        line.push('\n');
+        line
+    }
+}
+
+/// Counters for a running bridge
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BridgeStats {
+    /// Messages published
+    pub published: u64,
+    /// Clients accepted
+    pub clients_accepted: u64,
+    /// Clients dropped for falling behind
+    pub clients_lagged: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    published: AtomicU64,
+    clients_accepted: AtomicU64,
+    clients_lagged: AtomicU64,
+}
+
+/// Fans normalized events out to local socket clients
+///
+/// Cloning is cheap; clones share the same clients and book state.
+#[derive(Debug, Clone)]
+pub struct MarketDataBridge {
+    tx: broadcast::Sender<Arc<str>>,
+    books: Arc<Mutex<HashMap<String, OrderbookSnapshot>>>,
+    counters: Arc<Counters>,
+}
+
+impl MarketDataBridge {
+    /// Create a bridge buffering up to `capacity` messages per client
+    pub fn new(capacity: usize) -> Self {
+        let (tx, _) = broadcast::channel(capacity.max(1));
+        Self {
+            tx,
+            books: Arc::new(Mutex::new(HashMap::new())),
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    /// Translate an event into bridge messages
+    ///
+    /// Book updates become diffs against the last book seen for the symbol.
+    /// Events with no wire representation produce nothing.
+    pub fn translate(&self, event: &Event) -> Vec<BridgeMessage> {
+        match event {
+            Event::Market(MarketEvent::OrderbookSnapshot { symbol, snapshot, .. }) => {
+                self.lock_books().insert(symbol.clone(), snapshot.clone());
+                vec![BridgeMessage::book(snapshot)]
+            }
+            Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot, .. }) => {
+                let previous = self.lock_books().insert(symbol.clone(), snapshot.clone());
+                match previous {
+                    Some(previous) => {
+                        let diff = previous.diff(snapshot);
+                        if diff.is_empty() && diff.checksum == previous.checksum {
+                            Vec::new()
+                        } else {
+                            vec![BridgeMessage::BookDiff(diff)]
+                        }
+                    }
+                    None => vec![BridgeMessage::book(snapshot)],
+                }
+            }
+            Event::Market(MarketEvent::Trade { trade, .. }) => vec![BridgeMessage::Trade(trade.clone())],
+            Event::Market(MarketEvent::Ticker { ticker, .. }) => vec![BridgeMessage::Ticker(ticker.clone())],
+            Event::Connection(ConnectionEvent::Connected { .. }) => {
+                vec![BridgeMessage::Status { connected: true }]
+            }
+            Event::Connection(ConnectionEvent::Disconnected { .. }) => {
+                vec![BridgeMessage::Status { connected: false }]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Publish an event to every connected client
+    pub fn publish(&self, event: &Event) {
+        for message in self.translate(event) {
+            self.counters.published.fetch_add(1, Ordering::Relaxed);
+            // No receivers is fine: the message still updated the book state
+            let _ = self.tx.send(Arc::from(message.to_line()));
+        }
+    }
+
+    /// Publish events until the stream ends
+    pub async fn forward(&self, mut events: EventReceiver) {
+        while let Some(event) = events.recv().await {
+            self.publish(&event);
+        }
+    }
+
+    /// Last book seen for a symbol
+    pub fn book(&self, symbol: &str) -> Option<OrderbookSnapshot> {
+        self.lock_books().get(symbol).cloned()
+    }
+
+    /// Last book seen for every symbol, ordered by symbol
+    pub fn books(&self) -> Vec<OrderbookSnapshot> {
+        let mut books: Vec<OrderbookSnapshot> = self.lock_books().values().cloned().collect();
+        books.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        books
+    }
+
+    /// Attach a new client: the current books, then a receiver for live lines
+    ///
+    /// Clients that transport lines themselves (e.g. over HTTP) use this
+    /// instead of the socket servers. A client whose receiver reports
+    /// `Lagged` must be dropped and resynced, since later diffs no longer
+    /// apply to its books.
+    pub fn subscribe(&self) -> (Vec<BridgeMessage>, broadcast::Receiver<Arc<str>>) {
+        // Subscribe before copying the books so no update falls in between
+        let rx = self.tx.subscribe();
+        let books = self.lock_books().values().map(BridgeMessage::book).collect();
+        self.counters.clients_accepted.fetch_add(1, Ordering::Relaxed);
+        (books, rx)
+    }
+
+    /// Count a client dropped for falling behind
+    pub(crate) fn record_lagged(&self) {
+        self.counters.clients_lagged.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Number of connected clients
+    pub fn client_count(&self) -> usize {
+        self.tx.receiver_count()
+    }
+
+    /// Current counters
+    pub fn stats(&self) -> BridgeStats {
+        BridgeStats {
+            published: self.counters.published.load(Ordering::Relaxed),
+            clients_accepted: self.counters.clients_accepted.load(Ordering::Relaxed),
+            clients_lagged: self.counters.clients_lagged.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Accept clients on a Unix domain socket
+    ///
+    /// A stale socket file at `path` is removed first. Runs until accepting
+    /// fails.
+    #[cfg(unix)]
+    pub async fn serve_unix(&self, path: impl AsRef<std::path::Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if path.exists() {
+            std::fs::remove_file(path)?;
+        }
+        let listener = tokio::net::UnixListener::bind(path)?;
+        loop {
+            let (stream, _) = listener.accept().await?;
+            self.spawn_client(stream);
+        }
+    }
+
+    /// Accept clients on a TCP address (use a loopback address)
+    pub async fn serve_tcp(&self, addr: impl ToSocketAddrs) -> io::Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        self.serve_tcp_listener(listener).await
+    }
+
+    /// Accept clients on an already bound TCP listener
+    pub async fn serve_tcp_listener(&self, listener: TcpListener) -> io::Result<()> {
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            debug!("Bridge client connected from {}", peer);
+            let _ = stream.set_nodelay(true);
+            self.spawn_client(stream);
+        }
+    }
+
+    fn spawn_client<W>(&self, writer: W)
+    where
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let (books, rx) = self.subscribe();
+        let books: Vec<String> = books.iter().map(BridgeMessage::to_line).collect();
+        let counters = self.counters.clone();
+        tokio::spawn(async move {
+            if let Err(e) = run_client(writer, books, rx, &counters).await {
+                debug!("Bridge client disconnected: {}", e);
+            }
+        });
+    }
+
+    fn lock_books(&self) -> std::sync::MutexGuard<'_, HashMap<String, OrderbookSnapshot>> {
+        self.books.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for MarketDataBridge {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+async fn run_client<W: AsyncWrite + Unpin>(
+    mut writer: W,
+    books: Vec<String>,
+    mut rx: broadcast::Receiver<Arc<str>>,
+    counters: &Counters,
+) -> io::Result<()> {
+    for line in books {
+        writer.write_all(line.as_bytes()).await?;
+    }
+    writer.flush().await?;
+    loop {
+        match rx.recv().await {
+            Ok(line) => {
+                writer.write_all(line.as_bytes()).await?;
+                writer.flush().await?;
+            }
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                // Diffs after a gap would corrupt the client's books
+                warn!("Dropping bridge client that fell {} messages behind", skipped);
+                counters.clients_lagged.fetch_add(1, Ordering::Relaxed);
+                return Ok(());
+            }
+            Err(broadcast::error::RecvError::Closed) => return Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_ws::ReceivedAt;
+    use rust_decimal_macros::dec;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+
+    fn book_event(update: bool, bid_qty: rust_decimal::Decimal) -> Event {
+        let snapshot = OrderbookSnapshot {
+            symbol: "BTC/USD".to_string(),
+            bids: vec![Level::new(dec!(100), bid_qty)],
+            asks: vec![Level::new(dec!(101), dec!(1))],
+            checksum: 7,
+            state: OrderbookState::Synced,
+        };
+        let symbol = "BTC/USD".to_string();
+        let received_at = ReceivedAt::now();
+        let event = if update {
+            MarketEvent::OrderbookUpdate { symbol, seq: 1, snapshot, received_at, exchange_ts_us: None }
+        } else {
+            MarketEvent::OrderbookSnapshot { symbol, seq: 1, snapshot, received_at, exchange_ts_us: None }
+        };
+        Event::Market(event)
+    }
+
+    #[test]
+    fn test_book_updates_become_diffs() {
+        let bridge = MarketDataBridge::new(16);
+        let messages = bridge.translate(&book_event(false, dec!(1)));
+        assert!(matches!(messages[0], BridgeMessage::Book { .. }));
+
+        let messages = bridge.translate(&book_event(true, dec!(2)));
+        let BridgeMessage::BookDiff(diff) = &messages[0] else {
+            panic!("expected a diff, got {:?}", messages);
+        };
+        assert_eq!(diff.bids.changed, vec![Level::new(dec!(100), dec!(2))]);
+
+        // An unchanged book produces nothing
+        assert!(bridge.translate(&book_event(true, dec!(2))).is_empty());
+
+        let line = messages[0].to_line();
+        assert!(line.starts_with("{\"type\":\"book_diff\""));
+        let decoded: BridgeMessage = serde_json::from_str(&line).unwrap();
+        assert!(matches!(decoded, BridgeMessage::BookDiff(d) if d == *diff));
+    }
+
+    #[tokio::test]
+    async fn test_tcp_client_gets_books_then_updates() {
+        let bridge = MarketDataBridge::new(16);
+        bridge.publish(&book_event(false, dec!(1)));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = bridge.clone();
+        tokio::spawn(async move { server.serve_tcp_listener(listener).await });
+
+        let stream = tokio::net::TcpStream::connect(addr).await.unwrap();
+        let mut lines = BufReader::new(stream).lines();
+        let first: BridgeMessage = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert!(matches!(first, BridgeMessage::Book { checksum: 7, .. }));
+
+        while bridge.client_count() == 0 {
+            tokio::task::yield_now().await;
+        }
+        bridge.publish(&book_event(true, dec!(3)));
+        let next: BridgeMessage = serde_json::from_str(&lines.next_line().await.unwrap().unwrap()).unwrap();
+        assert!(matches!(next, BridgeMessage::BookDiff(_)));
+        assert_eq!(bridge.stats().clients_accepted, 1);
+    }
+}
@@ -15,10 +15,13 @@
 //!     .with_trade(true);
 //! ```
 
+use crate::checkpoint::Checkpoint;
 use crate::filter::EventFilter;
-use kraken_types::{Channel, Depth};
-use kraken_ws::{ConnectionConfig, Endpoint, ReconnectConfig};
-use std::collections::HashSet;
+use crate::rest_backfill::{rest_backfiller, RestFetch};
+use kraken_book::MemoryLimits;
+use kraken_types::{Channel, Depth, Symbol};
+use kraken_ws::{Backfiller, BookSampler, CircuitBreakerConfig, DEFAULT_CALLBACK_BUDGET, ConnectionConfig, Endpoint, Backoff, BackoffStrategy, InlineDispatch, InlineHandler, MessageTap, ProxyConfig, PruningPolicy, ReconnectConfig, SharedRateLimiter};
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
 /// Configuration validation error
@@ -47,6 +50,15 @@ pub enum ConfigError {
     /// Timeout too short
     #[error("connection timeout must be at least 1 second")]
     TimeoutTooShort,
+
+    /// Channel can't be selected per symbol
+    #[error("channel {channel:?} can't be subscribed per symbol ({symbol})")]
+    UnsupportedSymbolChannel {
+        /// Symbol the channel was requested for
+        symbol: String,
+        /// Requested channel
+        channel: Channel,
+    },
 }
 
 /// OHLC (candlestick) interval in minutes
@@ -77,6 +89,29 @@ impl OhlcInterval {
     pub fn as_minutes(&self) -> u32 {
         *self as u32
     }
+
+    /// Interval for a number of minutes, if Kraken supports it
+    pub fn from_minutes(minutes: u32) -> Option<Self> {
+        [Self::M1, Self::M5, Self::M15, Self::M30, Self::H1, Self::H4, Self::D1, Self::W1, Self::D15]
+            .into_iter()
+            .find(|interval| interval.as_minutes() == minutes)
+    }
+}
+
+impl From<OhlcInterval> for kraken_types::OhlcInterval {
+    fn from(interval: OhlcInterval) -> Self {
+        match interval {
+            OhlcInterval::M1 => Self::M1,
+            OhlcInterval::M5 => Self::M5,
+            OhlcInterval::M15 => Self::M15,
+            OhlcInterval::M30 => Self::M30,
+            OhlcInterval::H1 => Self::H1,
+            OhlcInterval::H4 => Self::H4,
+            OhlcInterval::D1 => Self::D1,
+            OhlcInterval::W1 => Self::W1,
+            OhlcInterval::D15 => Self::D15,
+        }
+    }
 }
 
 /// Builder for configuring a Kraken client
@@ -97,6 +132,9 @@ pub struct KrakenClientBuilder {
     /// Orderbook depth
     pub depth: Depth,
 
+    /// Per-symbol orderbook depths that take precedence over `depth`
+    pub symbol_depths: HashMap<String, Depth>,
+
     /// WebSocket endpoint
     pub endpoint: Endpoint,
 
@@ -106,9 +144,18 @@ pub struct KrakenClientBuilder {
     /// Reconnection configuration
     pub reconnect_config: ReconnectConfig,
 
+    /// Backoff strategy for reconnect delays (None = exponential)
+    pub backoff: Option<Backoff>,
+
     /// Connection timeout
     pub connect_timeout: Duration,
 
+    /// Circuit breaker guarding reconnect attempts (None = disabled)
+    pub circuit_breaker: Option<CircuitBreakerConfig>,
+
+    /// Keep a warm standby connection for instant failover
+    pub standby: bool,
+
     /// Subscribe to orderbook channel
     pub subscribe_book: bool,
 
@@ -130,20 +177,91 @@ pub struct KrakenClientBuilder {
     /// Additional channels to subscribe to
     pub additional_channels: Vec<Channel>,
 
+    /// Per-symbol channel selections, overriding the channel flags
+    pub symbol_channels: Vec<(String, Vec<Channel>)>,
+
+    /// Periodic book sampling (None = disabled)
+    pub book_sampler: Option<BookSampler>,
+
+    /// Emit at most one book update per symbol per interval (None = every update)
+    pub conflation: Option<Duration>,
+
+    /// Per-symbol conflation intervals that take precedence over `conflation`
+    pub symbol_conflation: HashMap<String, Duration>,
+
+    /// Unsubscribe books the application stops reading (None = never)
+    pub pruning: Option<PruningPolicy>,
+
+    /// Audit every book's invariants at this interval (None = disabled)
+    pub audit_interval: Option<Duration>,
+
+    /// Outbound proxy (None = connect directly)
+    pub proxy: Option<ProxyConfig>,
+
+    /// Ask the server for permessage-deflate compressed frames
+    #[cfg(feature = "deflate")]
+    pub compression: bool,
+
+    /// Rate limiter pacing subscribe requests (None = no pacing)
+    pub rate_limiter: Option<SharedRateLimiter>,
+
+    /// Warn when the local clock is this far off the exchange's (None = disabled)
+    pub clock_skew_threshold: Option<Duration>,
+
+    /// Orderbook memory budgets (None = unlimited)
+    pub memory_limits: Option<MemoryLimits>,
+
+    /// Track per-level update counts and change times in every book
+    pub level_metadata: bool,
+
+    /// Time budget for each per-symbol book callback invocation
+    pub callback_budget: Duration,
+
+    /// Handler called on the read loop for selected channels
+    pub inline: Option<InlineDispatch>,
+
+    /// Copy of every raw frame sent or received
+    pub tap: Option<MessageTap>,
+
+    /// State to restore before connecting (None = start cold)
+    pub checkpoint: Option<Checkpoint>,
+
+    /// Fetch trades missed while disconnected from REST
+    pub trade_backfill: bool,
+
+    /// Candles fetched per OHLC interval on the first connect, and missed
+    /// candles on reconnect (None = no OHLC backfill)
+    pub ohlc_backfill: Option<usize>,
+
+    /// REST fetch function for backfill (None = reqwest with the `backfill` feature)
+    pub backfill_fetch: Option<RestFetch>,
+
+    /// Request initial book and trade snapshots on subscribe
+    pub snapshots: bool,
+
     /// Enable verbose logging
     pub verbose: bool,
 }
 
+/// Convert symbol arguments into the strings stored on the builder
+fn symbol_strings(symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> Vec<String> {
+    symbols.into_iter().map(|s| s.into().into_string()).collect()
+}
+
 impl Default for KrakenClientBuilder {
     fn default() -> Self {
         Self {
             symbols: Vec::new(),
             l3_symbols: Vec::new(),
             depth: Depth::D10,
+            symbol_depths: HashMap::new(),
             endpoint: Endpoint::Public,
             reconnect: true,
             reconnect_config: ReconnectConfig::default(),
+            backoff: None,
             connect_timeout: Duration::from_secs(10),
+            circuit_breaker: Some(CircuitBreakerConfig::default()),
+            standby: false,
             subscribe_book: true,
             subscribe_ticker: false,
             subscribe_trade: false,
@@ -151,6 +269,27 @@ impl Default for KrakenClientBuilder {
             ohlc_intervals: HashSet::new(),
             event_filter: None,
             additional_channels: Vec::new(),
+            symbol_channels: Vec::new(),
+            book_sampler: None,
+            conflation: None,
+            symbol_conflation: HashMap::new(),
+            pruning: None,
+            audit_interval: None,
+            proxy: None,
+            #[cfg(feature = "deflate")]
+            compression: false,
+            rate_limiter: None,
+            clock_skew_threshold: None,
+            memory_limits: None,
+            level_metadata: false,
+            callback_budget: DEFAULT_CALLBACK_BUDGET,
+            inline: None,
+            tap: None,
+            checkpoint: None,
+            trade_backfill: false,
+            ohlc_backfill: None,
+            backfill_fetch: None,
+            snapshots: true,
             verbose: false,
         }
     }
@@ -167,9 +306,9 @@ impl KrakenClientBuilder {
     /// let builder = KrakenClientBuilder::new(["BTC/USD", "ETH/USD"]);
     /// assert_eq!(builder.symbols.len(), 2);
     /// ```
-    pub fn new(symbols: impl IntoIterator<Item = impl Into<String>>) -> Self {
+    pub fn new(symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> Self {
         Self {
-            symbols: symbols.into_iter().map(Into::into).collect(),
+            symbols: symbol_strings(symbols),
             ..Default::default()
         }
     }
@@ -185,14 +324,14 @@ impl KrakenClientBuilder {
     ///     .with_symbol("ETH/USD");
     /// assert_eq!(builder.symbols.len(), 2);
     /// ```
-    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
-        self.symbols.push(symbol.into());
+    pub fn with_symbol(mut self, symbol: impl Into<Symbol>) -> Self {
+        self.symbols.push(symbol.into().into_string());
         self
     }
 
     /// Add multiple symbols
-    pub fn with_symbols(mut self, symbols: impl IntoIterator<Item = impl Into<String>>) -> Self {
-        self.symbols.extend(symbols.into_iter().map(Into::into));
+    pub fn with_symbols(mut self, symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> Self {
+        self.symbols.extend(symbol_strings(symbols));
         self
     }
 
@@ -204,6 +343,15 @@ impl KrakenClientBuilder {
         self
     }
 
+    /// Set the orderbook depth for a single symbol
+    ///
+    /// Overrides [`with_depth`](Self::with_depth) for that symbol, so a
+    /// major pair can be tracked at D1000 next to altcoins at D10.
+    pub fn with_symbol_depth(mut self, symbol: impl Into<Symbol>, depth: Depth) -> Self {
+        self.symbol_depths.insert(symbol.into().into_string(), depth);
+        self
+    }
+
     /// Set the WebSocket endpoint
     ///
     /// Use `Endpoint::Level3` for L3 orderbook data.
@@ -230,12 +378,40 @@ impl KrakenClientBuilder {
         self
     }
 
+    /// Use a custom backoff strategy for reconnect delays
+    ///
+    /// See [`kraken_ws::reconnect`] for the built-in strategies.
+    pub fn with_backoff(mut self, strategy: impl BackoffStrategy + 'static) -> Self {
+        self.backoff = Some(Backoff::new(strategy));
+        self
+    }
+
     /// Set the connection timeout
     pub fn with_timeout(mut self, timeout: Duration) -> Self {
         self.connect_timeout = timeout;
         self
     }
 
+    /// Set the circuit breaker configuration
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.circuit_breaker = Some(config);
+        self
+    }
+
+    /// Disable the circuit breaker
+    pub fn without_circuit_breaker(mut self) -> Self {
+        self.circuit_breaker = None;
+        self
+    }
+
+    /// Keep a warm standby connection and fail over to it instantly
+    ///
+    /// See `ConnectionConfig::with_standby`.
+    pub fn with_standby(mut self, enabled: bool) -> Self {
+        self.standby = enabled;
+        self
+    }
+
     /// Subscribe to the orderbook channel
     pub fn with_book(mut self, enabled: bool) -> Self {
         self.subscribe_book = enabled;
@@ -258,8 +434,8 @@ impl KrakenClientBuilder {
     ///
     /// Note: L3 data requires connection to the Level3 endpoint.
     /// Call `.with_endpoint(Endpoint::Level3)` to enable.
-    pub fn with_l3(mut self, symbols: impl IntoIterator<Item = impl Into<String>>) -> Self {
-        self.l3_symbols = symbols.into_iter().map(Into::into).collect();
+    pub fn with_l3(mut self, symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> Self {
+        self.l3_symbols = symbol_strings(symbols);
         self.subscribe_l3 = true;
         self
     }
@@ -299,6 +475,261 @@ impl KrakenClientBuilder {
         self
     }
 
+    /// Set the time budget for each book callback invocation
+    ///
+    /// See [`KrakenClient::on_book_update`](crate::KrakenClient::on_book_update).
+    pub fn with_callback_budget(mut self, budget: Duration) -> Self {
+        self.callback_budget = budget;
+        self
+    }
+
+    /// Handle events of `channels` synchronously on the connection task
+    ///
+    /// Those events bypass [`KrakenClient::events`](crate::KrakenClient::events),
+    /// saving the channel hop. The handler stalls the connection while it
+    /// runs, so it must return within microseconds; see [`kraken_ws::inline`].
+    pub fn with_inline_handler(
+        mut self,
+        channels: impl IntoIterator<Item = Channel>,
+        handler: impl InlineHandler + 'static,
+    ) -> Self {
+        self.inline = Some(InlineDispatch::new(channels, handler));
+        self
+    }
+
+    /// Copy every raw frame, inbound and outbound, to a tap
+    ///
+    /// Session tokens are redacted from captured frames by default.
+    /// Use [`MessageTap::file`] for a JSON-lines capture of the wire
+    /// traffic, or [`MessageTap::channel`] to inspect frames in-process.
+    pub fn with_message_tap(mut self, tap: MessageTap) -> Self {
+        self.tap = Some(tap);
+        self
+    }
+
+    /// Subscribe a symbol to its own set of channels
+    ///
+    /// The symbol is added if it isn't configured yet, and the channel flags
+    /// (`with_book`, `with_ticker`, `with_trade`) no longer apply to it.
+    /// Book, ticker, trade and level3 can be selected per symbol. The
+    /// selection is kept per subscription, so reconnects restore it as is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use kraken_sdk::builder::KrakenClientBuilder;
+    /// use kraken_types::Channel;
+    ///
+    /// let builder = KrakenClientBuilder::new(Vec::<String>::new())
+    ///     .subscribe("BTC/USD", &[Channel::Book, Channel::Trade])
+    ///     .subscribe("ETH/USD", &[Channel::Ticker]);
+    /// assert_eq!(builder.channels_for("ETH/USD"), vec![Channel::Ticker]);
+    /// ```
+    pub fn subscribe(mut self, symbol: impl Into<Symbol>, channels: &[Channel]) -> Self {
+        let symbol = symbol.into().into_string();
+        if !self.symbols.contains(&symbol) {
+            self.symbols.push(symbol.clone());
+        }
+        match self.symbol_channels.iter_mut().find(|(s, _)| *s == symbol) {
+            Some((_, selected)) => *selected = channels.to_vec(),
+            None => self.symbol_channels.push((symbol, channels.to_vec())),
+        }
+        self
+    }
+
+    /// Channels a symbol will be subscribed to
+    pub fn channels_for(&self, symbol: &str) -> Vec<Channel> {
+        if let Some((_, channels)) = self.symbol_channels.iter().find(|(s, _)| s == symbol) {
+            return channels.clone();
+        }
+        [
+            (self.subscribe_book, Channel::Book),
+            (self.subscribe_ticker, Channel::Ticker),
+            (self.subscribe_trade, Channel::Trade),
+        ]
+        .into_iter()
+        .filter_map(|(enabled, channel)| enabled.then_some(channel))
+        .collect()
+    }
+
+    /// Symbols to subscribe per channel, in configuration order
+    pub fn subscription_plan(&self) -> Vec<(Channel, Vec<String>)> {
+        let mut plan: Vec<(Channel, Vec<String>)> = Vec::new();
+        for symbol in &self.symbols {
+            for channel in self.channels_for(symbol) {
+                match plan.iter_mut().find(|(c, _)| *c == channel) {
+                    Some((_, symbols)) => symbols.push(symbol.clone()),
+                    None => plan.push((channel, vec![symbol.clone()])),
+                }
+            }
+        }
+        plan
+    }
+
+    /// Emit `MarketEvent::BookSample` for every book at a fixed interval
+    ///
+    /// Samples cover mid, spread, depth over the top 5 levels, and imbalance.
+    pub fn with_book_sampling(mut self, interval: Duration) -> Self {
+        self.book_sampler = Some(BookSampler::new(interval));
+        self
+    }
+
+    /// Emit book samples using a custom sampler configuration
+    pub fn with_book_sampler(mut self, sampler: BookSampler) -> Self {
+        self.book_sampler = Some(sampler);
+        self
+    }
+
+    /// Emit at most one `OrderbookUpdate` per symbol per `interval`
+    ///
+    /// The event carries the latest book; snapshots and checksum mismatches
+    /// are never delayed. See `ConnectionConfig::with_conflation`.
+    pub fn with_conflation(mut self, interval: Duration) -> Self {
+        self.conflation = Some(interval);
+        self
+    }
+
+    /// Override the conflation interval for one symbol (`Duration::ZERO` = off)
+    pub fn with_symbol_conflation(mut self, symbol: impl Into<Symbol>, interval: Duration) -> Self {
+        self.symbol_conflation.insert(symbol.into().into_string(), interval);
+        self
+    }
+
+    /// Unsubscribe books not read through the client's book accessors for a while
+    ///
+    /// Useful for screeners over many pairs. Only book subscriptions are
+    /// pruned; see `kraken_ws::pruning`.
+    pub fn with_pruning(mut self, policy: PruningPolicy) -> Self {
+        self.pruning = Some(policy);
+        self
+    }
+
+    /// Periodically check every book for checksum drift, crossing and depth violations
+    ///
+    /// Failures arrive as `MarketEvent::BookAuditFailed`. See `kraken_book::audit`.
+    pub fn with_audit(mut self, interval: Duration) -> Self {
+        self.audit_interval = Some(interval);
+        self
+    }
+
+    /// Connect through a SOCKS5 or HTTP CONNECT proxy
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Ask the server to compress what it sends (permessage-deflate)
+    ///
+    /// Savings and decompression time show up in the client's traffic stats.
+    #[cfg(feature = "deflate")]
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.compression = enabled;
+        self
+    }
+
+    /// Pace subscribe requests with a shared rate limiter
+    ///
+    /// The limiter stays readable through [`KrakenClient::rate_limiter`](crate::KrakenClient::rate_limiter),
+    /// e.g. to show utilization on a dashboard.
+    pub fn with_rate_limiter(mut self, limiter: SharedRateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Emit `ConnectionEvent::ClockSkew` when the local clock is more than
+    /// `threshold` off the exchange's
+    pub fn with_clock_skew_warning(mut self, threshold: Duration) -> Self {
+        self.clock_skew_threshold = Some(threshold);
+        self
+    }
+
+    /// Cap orderbook levels to stay within approximate memory budgets
+    ///
+    /// Useful at D1000 across many symbols. Caps are reported with
+    /// `ConnectionEvent::MemoryCapReached`.
+    pub fn with_memory_limits(mut self, limits: MemoryLimits) -> Self {
+        self.memory_limits = Some(limits);
+        self
+    }
+
+    /// Track per-level update counts and change times in every book
+    ///
+    /// Read them with `Orderbook::level_meta` or
+    /// `Orderbook::snapshot_extended`.
+    pub fn with_level_metadata(mut self) -> Self {
+        self.level_metadata = true;
+        self
+    }
+
+    /// Restore state from a checkpoint before connecting
+    ///
+    /// Stored books are seeded into the client so they can be read before
+    /// the first live snapshot; the checkpoint stays available through
+    /// [`KrakenClient::restored_checkpoint`](crate::KrakenClient::restored_checkpoint).
+    pub fn with_checkpoint(mut self, checkpoint: Checkpoint) -> Self {
+        self.checkpoint = Some(checkpoint);
+        self
+    }
+
+    /// Fetch trades missed while disconnected from REST `Trades`
+    ///
+    /// On reconnect, live trades are held until the gap since the last
+    /// delivered trade is fetched, then emitted after it, ordered and
+    /// de-duplicated by `trade_id`. See [`kraken_ws::backfill`].
+    pub fn with_trade_backfill(mut self, enabled: bool) -> Self {
+        self.trade_backfill = enabled;
+        self
+    }
+
+    /// Fetch OHLC history from REST before live candles
+    ///
+    /// The first connect fetches the last `n_candles` candles per interval
+    /// (Kraken returns at most 720); reconnects fetch the candles missed
+    /// since the last one delivered. Live candles replace fetched ones for
+    /// the same interval.
+    pub fn with_ohlc_backfill(mut self, n_candles: usize) -> Self {
+        self.ohlc_backfill = Some(n_candles);
+        self
+    }
+
+    /// Fetch backfill through a custom function mapping a URL to its body
+    ///
+    /// Required for backfill unless the `backfill` feature is enabled, which
+    /// fetches with `reqwest`.
+    pub fn with_backfill_fetch<F, Fut, E>(mut self, fetch: F) -> Self
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<String, E>> + Send + 'static,
+        E: std::fmt::Display,
+    {
+        self.backfill_fetch = Some(RestFetch::new(fetch));
+        self
+    }
+
+    /// Backfiller for the configured backfill, if any and a fetch is available
+    pub fn backfiller(&self) -> Option<Backfiller> {
+        if !self.trade_backfill && self.ohlc_backfill.is_none() {
+            return None;
+        }
+        #[cfg(feature = "backfill")]
+        let fetch = Some(self.backfill_fetch.clone().unwrap_or_else(RestFetch::reqwest));
+        #[cfg(not(feature = "backfill"))]
+        let fetch = self.backfill_fetch.clone();
+        Some(rest_backfiller(fetch?, self.trade_backfill, self.ohlc_backfill))
+    }
+
+    /// Request initial snapshots on subscribe (default: true)
+    ///
+    /// With `false`, books restored by [`with_checkpoint`](Self::with_checkpoint)
+    /// are updated by deltas alone instead of waiting for a snapshot, and
+    /// trades start with the next live trade rather than recent history.
+    /// Books with nothing restored still get a snapshot, as do all books
+    /// after a reconnect.
+    pub fn with_snapshots(mut self, snapshots: bool) -> Self {
+        self.snapshots = snapshots;
+        self
+    }
+
     /// Enable verbose logging
     pub fn verbose(mut self) -> Self {
         self.verbose = true;
@@ -345,6 +776,22 @@ impl KrakenClientBuilder {
             return Err(ConfigError::L3RequiresLevel3Endpoint);
         }
 
+        for (symbol, channels) in &self.symbol_channels {
+            for &channel in channels {
+                match channel {
+                    Channel::Book | Channel::Ticker | Channel::Trade => {}
+                    Channel::Level3 if matches!(self.endpoint, Endpoint::Level3) => {}
+                    Channel::Level3 => return Err(ConfigError::L3RequiresLevel3Endpoint),
+                    _ => {
+                        return Err(ConfigError::UnsupportedSymbolChannel {
+                            symbol: symbol.clone(),
+                            channel,
+                        })
+                    }
+                }
+            }
+        }
+
         // Check timeout
         if self.connect_timeout < Duration::from_secs(1) {
             return Err(ConfigError::TimeoutTooShort);
@@ -354,13 +801,22 @@ impl KrakenClientBuilder {
     }
 
     /// Check if a symbol has valid format (BASE/QUOTE)
-    fn is_valid_symbol(symbol: &str) -> bool {
-        let parts: Vec<&str> = symbol.split('/').collect();
-        if parts.len() != 2 {
+    pub(crate) fn is_valid_symbol(symbol: &str) -> bool {
+        let Ok(symbol) = symbol.parse::<Symbol>() else {
             return false;
-        }
-        // Both parts should be non-empty and alphabetic (with possible numbers like BTC2)
-        parts[0].len() >= 2 && parts[1].len() >= 2
+        };
+        // Asset codes are at least two characters (e.g. "OP", "BTC2")
+        symbol.base().map_or(0, str::len) >= 2 && symbol.quote().map_or(0, str::len) >= 2
+    }
+
+    /// Load a builder from a configuration file
+    ///
+    /// Reads the file, applies `KRAKEN_SDK_*` environment overrides and
+    /// validates the result. See [`KrakenSdkConfig`](crate::config::KrakenSdkConfig)
+    /// for the format.
+    #[cfg(feature = "config")]
+    pub fn from_config(path: impl AsRef<std::path::Path>) -> Result<Self, crate::config::SdkConfigError> {
+        crate::config::KrakenSdkConfig::load(path)?.to_builder()
     }
 
     /// Build and validate the configuration
@@ -376,13 +832,84 @@ impl KrakenClientBuilder {
         let mut config = ConnectionConfig::new()
             .with_endpoint(self.endpoint)
             .with_depth(self.depth)
-            .with_timeout(self.connect_timeout);
+            .with_timeout(self.connect_timeout)
+            .with_callback_budget(self.callback_budget);
+
+        for (symbol, depth) in &self.symbol_depths {
+            config = config.with_symbol_depth(symbol.as_str(), *depth);
+        }
 
         if self.reconnect {
             config = config.with_reconnect(self.reconnect_config.clone());
         } else {
             config = config.without_reconnect();
         }
+        config.backoff = self.backoff.clone();
+
+        config = match &self.circuit_breaker {
+            Some(breaker) => config.with_circuit_breaker(breaker.clone()),
+            None => config.without_circuit_breaker(),
+        };
+
+        if self.standby {
+            config = config.with_standby(true);
+        }
+
+        if let Some(sampler) = &self.book_sampler {
+            config = config.with_book_sampler(sampler.clone());
+        }
+
+        if let Some(interval) = self.conflation {
+            config = config.with_conflation(interval);
+        }
+        for (symbol, interval) in &self.symbol_conflation {
+            config = config.with_symbol_conflation(symbol.as_str(), *interval);
+        }
+
+        if let Some(policy) = self.pruning {
+            config = config.with_pruning(policy);
+        }
+
+        if let Some(interval) = self.audit_interval {
+            config = config.with_audit(interval);
+        }
+
+        if let Some(proxy) = &self.proxy {
+            config = config.with_proxy(proxy.clone());
+        }
+
+        #[cfg(feature = "deflate")]
+        {
+            config = config.with_compression(self.compression);
+        }
+
+        if let Some(limiter) = &self.rate_limiter {
+            config = config.with_rate_limiter(limiter.clone());
+        }
+
+        if let Some(threshold) = self.clock_skew_threshold {
+            config = config.with_clock_skew_warning(threshold);
+        }
+
+        if let Some(limits) = self.memory_limits {
+            config = config.with_memory_limits(limits);
+        }
+
+        if self.level_metadata {
+            config = config.with_level_metadata();
+        }
+
+        if let Some(inline) = &self.inline {
+            config = config.with_inline_dispatch(inline.clone());
+        }
+
+        if let Some(tap) = &self.tap {
+            config = config.with_message_tap(tap.clone());
+        }
+
+        if let Some(backfiller) = self.backfiller() {
+            config = config.with_backfill(backfiller);
+        }
 
         config
     }
@@ -404,6 +931,7 @@ impl KrakenClientBuilder {
             || self.subscribe_l3
             || !self.ohlc_intervals.is_empty()
             || !self.additional_channels.is_empty()
+            || !self.symbol_channels.is_empty()
     }
 }
 
@@ -493,6 +1021,20 @@ mod tests {
         assert!(builder.symbols.contains(&"LINK/USD".to_string()));
     }
 
+    #[test]
+    fn test_per_symbol_channel_validation() {
+        let builder = KrakenClientBuilder::new(["BTC/USD"]).subscribe("BTC/USD", &[Channel::Ohlc]);
+        assert_eq!(builder.symbols.len(), 1);
+        assert!(matches!(
+            builder.validate(),
+            Err(ConfigError::UnsupportedSymbolChannel { channel: Channel::Ohlc, .. })
+        ));
+
+        let builder = KrakenClientBuilder::new(["BTC/USD"]).subscribe("ETH/USD", &[Channel::Level3]);
+        assert!(matches!(builder.validate(), Err(ConfigError::L3RequiresLevel3Endpoint)));
+        assert_eq!(builder.channels_for("BTC/USD"), vec![Channel::Book]);
+    }
+
     #[test]
     fn test_builder_depth_levels() {
         let builder = KrakenClientBuilder::new(["BTC/USD"]).with_depth(Depth::D100);
@@ -503,6 +1045,12 @@ mod tests {
 
         let builder = KrakenClientBuilder::new(["BTC/USD"]).with_depth(Depth::D1000);
         assert_eq!(builder.depth, Depth::D1000);
+
+        let builder = KrakenClientBuilder::new(["BTC/USD", "DOT/USD"])
+            .with_symbol_depth("BTC/USD", Depth::D1000);
+        let config = builder.to_connection_config();
+        assert_eq!(config.depth_for("BTC/USD"), Depth::D1000);
+        assert_eq!(config.depth_for("DOT/USD"), Depth::D10);
     }
 
     #[test]
@@ -555,6 +1103,17 @@ mod tests {
         assert!(builder.has_subscriptions());
     }
 
+    #[test]
+    fn test_book_sampling_reaches_connection_config() {
+        let builder = KrakenClientBuilder::new(["BTC/USD"]);
+        assert!(builder.to_connection_config().book_sampler.is_none());
+
+        let config = builder.with_book_sampling(Duration::from_millis(250)).to_connection_config();
+        let sampler = config.book_sampler.expect("sampler configured");
+        assert_eq!(sampler.interval, Duration::from_millis(250));
+        assert_eq!(sampler.levels, 5);
+    }
+
     #[test]
     fn test_config_error_display() {
         let err = ConfigError::NoSymbols;
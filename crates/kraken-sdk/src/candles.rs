@@ -0,0 +1,380 @@
+//! Candle storage with historical backfill
+//!
+//! The WebSocket OHLC channel only delivers candles from the moment of
+//! subscription. [`CandleStore`] keeps candles per symbol and interval keyed
+//! by `interval_begin`, so history fetched from Kraken's REST `OHLC`
+//! endpoint can be merged in at startup and the live stream continues on top
+//! of it without duplicates.
+//!
+//! Live candles always win: [`CandleStore::apply`] replaces the candle for
+//! its interval, while [`CandleStore::merge_history`] only fills intervals
+//! the store doesn't have yet. [`CandleStore::gaps`] reports any interval
+//! starts still missing between the oldest and newest candle.
+//!
+//! Each series is a [`BoundedStore`] holding the newest `max_candles` in
+//! memory. With the `spill` feature and [`CandleStore::with_spill`], older
+//! candles are written to a subdirectory per series instead of dropped, and
+//! [`CandleStore::range`] reads them back when a window reaches past memory.
+//!
+//! To have the client fetch the history itself, before live candles and
+//! again after every reconnect, use
+//! [`with_ohlc_backfill`](crate::KrakenClientBuilder::with_ohlc_backfill)
+//! (see [`rest_backfill`](crate::rest_backfill)).
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::candles::{parse_rest_ohlc, CandleStore};
+//!
+//! let body = r#"{"error":[],"result":{"XXBTZUSD":[
+//!     [1704067200,"42000.0","42100.0","41900.0","42050.0","42010.0","12.5",340],
+//!     [1704067260,"42050.0","42080.0","42000.0","42020.0","42040.0","3.1",95]
+//! ],"last":1704067260}}"#;
+//!
+//! let history = parse_rest_ohlc(body, "BTC/USD", 1).unwrap();
+//! let mut store = CandleStore::new(1000);
+//! assert_eq!(store.merge_history(history), 2);
+//! assert!(store.gaps("BTC/USD", 1).is_empty());
+//! assert_eq!(store.latest("BTC/USD", 1).unwrap().trades, 95);
+//! ```
+
+use crate::bounded_store::{BoundedStore, StoreError};
+use chrono::{DateTime, Utc};
+use kraken_types::{Decimal, OhlcData};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[cfg(feature = "spill")]
+use crate::bounded_store::SpillConfig;
+
+/// Kraken REST OHLC endpoint
+pub const REST_OHLC_URL: &str = "https://api.kraken.com/0/public/OHLC";
+
+/// Error parsing a REST OHLC response
+#[derive(Debug, thiserror::Error)]
+pub enum CandleError {
+    /// Response was not valid JSON
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Kraken returned an error
+    #[error("Kraken API error: {0}")]
+    Api(String),
+
+    /// Response had an unexpected shape
+    #[error("unexpected OHLC response: {0}")]
+    Format(String),
+}
+
+/// Candles per symbol and interval, ordered by interval start
+#[derive(Debug)]
+pub struct CandleStore {
+    series: HashMap<(String, u32), BoundedStore<OhlcData>>,
+    max_candles: usize,
+    #[cfg(feature = "spill")]
+    spill: Option<SpillConfig>,
+}
+
+impl CandleStore {
+    /// Create a store keeping at most `max_candles` per series in memory
+    pub fn new(max_candles: usize) -> Self {
+        Self {
+            series: HashMap::new(),
+            max_candles: max_candles.max(1),
+            #[cfg(feature = "spill")]
+            spill: None,
+        }
+    }
+
+    /// Spill candles evicted from memory to disk instead of dropping them
+    ///
+    /// Each series gets a subdirectory of `config.dir` named after its
+    /// symbol and interval (e.g. `BTC_USD-1`). Candles spilled before a
+    /// restart are queryable again once their series is written to.
+    #[cfg(feature = "spill")]
+    pub fn with_spill(mut self, config: SpillConfig) -> Self {
+        self.spill = Some(config);
+        self
+    }
+
+    fn series_mut(&mut self, symbol: &str, interval: u32) -> &mut BoundedStore<OhlcData> {
+        let key = (symbol.to_string(), interval);
+        if !self.series.contains_key(&key) {
+            let series = self.new_series(symbol, interval);
+            self.series.insert(key.clone(), series);
+        }
+        self.series.get_mut(&key).expect("series inserted above")
+    }
+
+    #[cfg(feature = "spill")]
+    fn new_series(&self, symbol: &str, interval: u32) -> BoundedStore<OhlcData> {
+        BoundedStore::for_series(self.max_candles, self.spill.as_ref(), &format!("{}-{}", symbol, interval))
+    }
+
+    #[cfg(not(feature = "spill"))]
+    fn new_series(&self, _symbol: &str, _interval: u32) -> BoundedStore<OhlcData> {
+        BoundedStore::new(self.max_candles)
+    }
+
+    fn series(&self, symbol: &str, interval: u32) -> Option<&BoundedStore<OhlcData>> {
+        self.series.get(&(symbol.to_string(), interval))
+    }
+
+    /// Apply a live candle, replacing any candle for the same interval
+    ///
+    /// Returns false if `interval_begin` couldn't be parsed.
+    pub fn apply(&mut self, candle: OhlcData) -> bool {
+        if interval_start(&candle.interval_begin).is_none() {
+            return false;
+        }
+        self.series_mut(&candle.symbol, candle.interval).upsert(candle)
+    }
+
+    /// Merge historical candles, keeping any candle already stored
+    ///
+    /// Candles older than everything in a full series are skipped, since
+    /// they would be evicted at once. Returns the number of candles added.
+    pub fn merge_history(&mut self, candles: impl IntoIterator<Item = OhlcData>) -> usize {
+        let mut added = 0;
+        for candle in candles {
+            let Some(begin) = interval_start(&candle.interval_begin) else {
+                continue;
+            };
+            let at_us = begin * 1_000_000;
+            let series = self.series_mut(&candle.symbol, candle.interval);
+            let full = series.memory_len() >= series.capacity();
+            if series.contains(at_us) || (full && series.horizon_us().is_some_and(|horizon| at_us < horizon)) {
+                continue;
+            }
+            if series.push(candle) {
+                added += 1;
+            }
+        }
+        added
+    }
+
+    /// Candles for a series in memory, oldest first
+    pub fn candles(&self, symbol: &str, interval: u32) -> Vec<&OhlcData> {
+        self.series(symbol, interval)
+            .map(|s| s.in_memory().collect())
+            .unwrap_or_default()
+    }
+
+    /// Candles for a series starting in `from..to` (Unix seconds), oldest first
+    ///
+    /// Reads spilled candles when the window reaches past memory.
+    pub fn range(&mut self, symbol: &str, interval: u32, from: i64, to: i64) -> Result<Vec<OhlcData>, StoreError> {
+        match self.series.get_mut(&(symbol.to_string(), interval)) {
+            Some(series) => series.range(from.saturating_mul(1_000_000), to.saturating_mul(1_000_000)),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Most recent candle for a series
+    pub fn latest(&self, symbol: &str, interval: u32) -> Option<&OhlcData> {
+        self.series(symbol, interval)?.latest()
+    }
+
+    /// Number of candles in memory for a series
+    pub fn len(&self, symbol: &str, interval: u32) -> usize {
+        self.series(symbol, interval).map_or(0, BoundedStore::memory_len)
+    }
+
+    /// Every candle in memory across all series
+    pub fn all_candles(&self) -> impl Iterator<Item = &OhlcData> {
+        self.series.values().flat_map(BoundedStore::in_memory)
+    }
+
+    /// Maximum candles kept in memory per series
+    pub fn max_candles(&self) -> usize {
+        self.max_candles
+    }
+
+    /// Returns true if nothing is in memory
+    pub fn is_empty(&self) -> bool {
+        self.series.values().all(|series| series.memory_len() == 0)
+    }
+
+    /// Unix start times of intervals missing between the first and last candle
+    pub fn gaps(&self, symbol: &str, interval: u32) -> Vec<i64> {
+        let Some(series) = self.series(symbol, interval) else {
+            return Vec::new();
+        };
+        let step = i64::from(interval) * 60;
+        let mut missing = Vec::new();
+        let mut previous: Option<i64> = None;
+        for begin in series.in_memory().filter_map(|candle| interval_start(&candle.interval_begin)) {
+            if let Some(prev) = previous {
+                let mut expected = prev + step;
+                while expected < begin {
+                    missing.push(expected);
+                    expected += step;
+                }
+            }
+            previous = Some(begin);
+        }
+        missing
+    }
+}
+
+impl Default for CandleStore {
+    fn default() -> Self {
+        Self::new(1000)
+    }
+}
+
+fn interval_start(interval_begin: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(interval_begin)
+        .ok()
+        .map(|t| t.timestamp())
+}
+
+/// URL fetching the last `n_candles` candles for a REST pair name
+///
+/// `now` is the current Unix time in seconds. Kraken returns at most 720
+/// candles per request regardless of `since`.
+pub fn ohlc_backfill_url(rest_pair: &str, interval: u32, n_candles: usize, now: i64) -> String {
+    let since = now - i64::from(interval) * 60 * n_candles as i64;
+    format!("{}?pair={}&interval={}&since={}", REST_OHLC_URL, rest_pair, interval, since)
+}
+
+/// Parse a REST `OHLC` response into WebSocket-shaped candles
+///
+/// `interval_begin` is rendered in the same RFC 3339 form the WebSocket
+/// channel uses, so REST and live candles share keys.
+pub fn parse_rest_ohlc(body: &str, symbol: &str, interval: u32) -> Result<Vec<OhlcData>, CandleError> {
+    let value: serde_json::Value = serde_json::from_str(body)?;
+    if let Some(errors) = value.get("error").and_then(|e| e.as_array()) {
+        if !errors.is_empty() {
+            let messages: Vec<&str> = errors.iter().filter_map(|e| e.as_str()).collect();
+            return Err(CandleError::Api(messages.join(", ")));
+        }
+    }
+    let rows = value
+        .get("result")
+        .and_then(|r| r.as_object())
+        .and_then(|r| r.iter().find(|(key, _)| key.as_str() != "last"))
+        .and_then(|(_, rows)| rows.as_array())
+        .ok_or_else(|| CandleError::Format("missing result".to_string()))?;
+
+    rows.iter()
+        .map(|row| parse_row(row, symbol, interval))
+        .collect()
+}
+
+fn parse_row(row: &serde_json::Value, symbol: &str, interval: u32) -> Result<OhlcData, CandleError> {
+    let format_error = || CandleError::Format(row.to_string());
+    let fields = row.as_array().filter(|f| f.len() >= 8).ok_or_else(format_error)?;
+    let decimal = |index: usize| -> Result<Decimal, CandleError> {
+        fields[index]
+            .as_str()
+            .and_then(|s| Decimal::from_str(s).ok())
+            .ok_or_else(format_error)
+    };
+    let time = fields[0].as_i64().ok_or_else(format_error)?;
+    let begin = DateTime::<Utc>::from_timestamp(time, 0).ok_or_else(format_error)?;
+
+    Ok(OhlcData {
+        symbol: symbol.to_string(),
+        open: decimal(1)?,
+        high: decimal(2)?,
+        low: decimal(3)?,
+        close: decimal(4)?,
+        vwap: decimal(5)?,
+        volume: decimal(6)?,
+        trades: fields[7].as_u64().ok_or_else(format_error)?,
+        interval_begin: begin.to_rfc3339_opts(chrono::SecondsFormat::Nanos, true),
+        interval,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn candle(begin: &str, close: Decimal) -> OhlcData {
+        OhlcData {
+            symbol: "BTC/USD".to_string(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            vwap: close,
+            volume: dec!(1),
+            trades: 1,
+            interval_begin: begin.to_string(),
+            interval: 1,
+        }
+    }
+
+    #[test]
+    fn test_live_candles_win_over_history() {
+        let mut store = CandleStore::new(10);
+        store.apply(candle("2024-01-01T00:02:00.000000000Z", dec!(103)));
+
+        let history = vec![
+            candle("2024-01-01T00:00:00.000000000Z", dec!(100)),
+            candle("2024-01-01T00:01:00Z", dec!(101)),
+            candle("2024-01-01T00:02:00Z", dec!(999)),
+        ];
+        assert_eq!(store.merge_history(history), 2);
+        assert_eq!(store.len("BTC/USD", 1), 3);
+        assert_eq!(store.latest("BTC/USD", 1).unwrap().close, dec!(103));
+
+        // A live update for the current interval replaces it
+        store.apply(candle("2024-01-01T00:02:00.000000000Z", dec!(104)));
+        assert_eq!(store.len("BTC/USD", 1), 3);
+        assert_eq!(store.latest("BTC/USD", 1).unwrap().close, dec!(104));
+    }
+
+    #[test]
+    fn test_gaps_and_trimming() {
+        let mut store = CandleStore::new(3);
+        store.apply(candle("2024-01-01T00:00:00Z", dec!(1)));
+        store.apply(candle("2024-01-01T00:03:00Z", dec!(2)));
+        assert_eq!(store.gaps("BTC/USD", 1), vec![1704067260, 1704067320]);
+
+        store.apply(candle("2024-01-01T00:04:00Z", dec!(3)));
+        store.apply(candle("2024-01-01T00:05:00Z", dec!(4)));
+        assert_eq!(store.len("BTC/USD", 1), 3);
+        assert!(store.gaps("BTC/USD", 1).is_empty());
+    }
+
+    #[cfg(feature = "spill")]
+    #[test]
+    fn test_range_reads_spilled_candles() {
+        let dir = std::env::temp_dir().join(format!("havklo-candles-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut store = CandleStore::new(2).with_spill(SpillConfig::new(&dir));
+        for minute in 0..5 {
+            store.apply(candle(&format!("2024-01-01T00:0{}:00Z", minute), Decimal::from(minute)));
+        }
+        assert_eq!(store.len("BTC/USD", 1), 2);
+
+        let closes: Vec<Decimal> = store
+            .range("BTC/USD", 1, 1704067260, 1704067440)
+            .unwrap()
+            .into_iter()
+            .map(|c| c.close)
+            .collect();
+        assert_eq!(closes, vec![dec!(1), dec!(2), dec!(3)]);
+        assert!(dir.join("BTC_USD-1").is_dir());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_rest_response() {
+        let body = r#"{"error":[],"result":{"XXBTZUSD":[[1704067200,"42000.0","42100.0","41900.0","42050.0","42010.0","12.5",340]],"last":1704067200}}"#;
+        let candles = parse_rest_ohlc(body, "BTC/USD", 1).unwrap();
+        assert_eq!(candles[0].interval_begin, "2024-01-01T00:00:00.000000000Z");
+        assert_eq!(candles[0].close, dec!(42050.0));
+
+        let err = parse_rest_ohlc(r#"{"error":["EQuery:Unknown asset pair"]}"#, "X", 1).unwrap_err();
+        assert!(matches!(err, CandleError::Api(_)));
+        assert_eq!(
+            ohlc_backfill_url("XBTUSD", 5, 12, 1704067200),
+            "https://api.kraken.com/0/public/OHLC?pair=XBTUSD&interval=5&since=1704063600"
+        );
+    }
+}
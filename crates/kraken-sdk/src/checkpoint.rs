@@ -0,0 +1,732 @@
+//! State checkpointing for long-running clients
+//!
+//! A [`Checkpoint`] bundles the state that is slowest to rebuild after a
+//! restart: orderbook snapshots, the order tracker, the position tracker,
+//! and stored candles. It is saved to a directory as a manifest plus one
+//! generation directory holding one JSON file per component:
+//!
+//! | file                    | contents                                        |
+//! |-------------------------|-------------------------------------------------|
+//! | `manifest.json`         | format version, generation, save time, event id |
+//! | `gen-N/orderbooks.json` | one [`OrderbookSnapshot`] per book              |
+//! | `gen-N/orders.json`     | [`TrackerState`] of the order tracker           |
+//! | `gen-N/positions.json`  | [`PositionTracker`]                             |
+//! | `gen-N/candles.json`    | candle limit and every stored candle            |
+//!
+//! A save writes and syncs every file of a new generation in `gen-N.tmp`,
+//! renames it to `gen-N`, then replaces the manifest through a synced
+//! temporary file and a rename, syncing the directory after each rename.
+//! Only then are older generations removed. A crash at any point leaves a
+//! manifest naming one complete generation, never a mix of two, and a
+//! directory without a manifest is not a checkpoint.
+//!
+//! [`Checkpointer`] saves on an interval and once more after the client's
+//! run loop has exited. [`KrakenClient::resume_from_checkpoint`] loads a directory
+//! and returns a builder that seeds the books before connecting; restored
+//! books are readable at once but stay unsynced until the live snapshot
+//! replaces them.
+//!
+//! # Event ids
+//!
+//! The manifest records the last event id the client emitted, and a resumed
+//! client continues numbering after it (see [`kraken_ws::EventIdCursor`]).
+//! A checkpoint captured after the client's run loop has exited, like the
+//! final one [`Checkpointer`] saves, holds the last id ever emitted and is
+//! marked clean. Any other checkpoint may be followed by events it doesn't
+//! know about, so the resumed client skips [`EVENT_ID_CRASH_GAP`] ids rather
+//! than reuse any of them; consumers see that as a gap, which is what it is.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraken_sdk::checkpoint::Checkpoint;
+//! use kraken_sdk::prelude::*;
+//! use std::sync::{Arc, Mutex};
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let builder = KrakenClient::resume_from_checkpoint("./state")
+//!     .unwrap_or_else(|_| KrakenClient::builder(["BTC/USD"]));
+//! let client = builder.connect().await?;
+//!
+//! let positions = client
+//!     .restored_checkpoint()
+//!     .and_then(Checkpoint::position_tracker)
+//!     .unwrap_or_default();
+//! let positions = Arc::new(Mutex::new(positions));
+//!
+//! let checkpointer = client
+//!     .checkpointer("./state", Duration::from_secs(60))
+//!     .with_positions(Arc::clone(&positions));
+//! tokio::spawn(checkpointer.run());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`KrakenClient::resume_from_checkpoint`]: crate::KrakenClient::resume_from_checkpoint
+
+use crate::candles::CandleStore;
+use chrono::{DateTime, Utc};
+use kraken_book::OrderbookSnapshot;
+use kraken_types::OhlcData;
+use kraken_ws::{KrakenConnection, OrderTracker, PositionTracker, TrackerConfig, TrackerState};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Checkpoint format version written to the manifest
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+/// Event ids skipped when resuming from a checkpoint not saved at shutdown
+pub const EVENT_ID_CRASH_GAP: u64 = 1 << 32;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const GENERATION_PREFIX: &str = "gen-";
+const ORDERBOOKS_FILE: &str = "orderbooks.json";
+const ORDERS_FILE: &str = "orders.json";
+const POSITIONS_FILE: &str = "positions.json";
+const CANDLES_FILE: &str = "candles.json";
+
+/// Error saving or loading a checkpoint
+#[derive(Debug, thiserror::Error)]
+pub enum CheckpointError {
+    /// Reading or writing a checkpoint file failed
+    #[error("checkpoint I/O error: {0}")]
+    Io(#[from] io::Error),
+
+    /// A checkpoint file wasn't valid JSON for its component
+    #[error("invalid checkpoint file: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The directory has no manifest
+    #[error("no checkpoint in {0}")]
+    NotFound(PathBuf),
+
+    /// The checkpoint was written by an incompatible version
+    #[error("unsupported checkpoint version {found} (expected {expected})")]
+    Version {
+        /// Version in the manifest
+        found: u32,
+        /// Version this build reads
+        expected: u32,
+    },
+}
+
+/// Candles saved from a [`CandleStore`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CandleSnapshot {
+    /// Maximum candles the store kept per series
+    pub max_candles: usize,
+    /// Every stored candle
+    pub candles: Vec<OhlcData>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    /// Generation directory holding the components (None: the directory
+    /// itself, as written before generations)
+    #[serde(default)]
+    generation: Option<u64>,
+    saved_at: DateTime<Utc>,
+    symbols: Vec<String>,
+    #[serde(default)]
+    last_event_id: u64,
+    #[serde(default)]
+    clean_shutdown: bool,
+}
+
+/// Client state captured at one point in time
+///
+/// Components left as `None` aren't written, so they are absent from the
+/// saved generation.
+#[derive(Debug, Clone, Default)]
+pub struct Checkpoint {
+    /// When the checkpoint was saved (None until saved or loaded)
+    pub saved_at: Option<DateTime<Utc>>,
+    /// Symbols the client was configured with
+    pub symbols: Vec<String>,
+    /// Last event id emitted before the save
+    pub last_event_id: u64,
+    /// Captured after the run loop exited, so no later events were emitted
+    pub clean_shutdown: bool,
+    /// Orderbook snapshots
+    pub orderbooks: Vec<OrderbookSnapshot>,
+    /// Order tracker state
+    pub orders: Option<TrackerState>,
+    /// Position tracker
+    pub positions: Option<PositionTracker>,
+    /// Stored candles
+    pub candles: Option<CandleSnapshot>,
+}
+
+impl Checkpoint {
+    /// Create an empty checkpoint for a set of symbols
+    pub fn new(symbols: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            symbols: symbols.into_iter().map(Into::into).collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Capture a connection's books and event id
+    ///
+    /// Clean only once the run loop has exited. The event id is read after
+    /// that check, so a clean checkpoint holds the last id ever emitted.
+    pub(crate) fn capture(connection: &KrakenConnection, symbols: Vec<String>) -> Self {
+        let clean_shutdown = *connection.watch_stopped().borrow();
+        Self {
+            saved_at: Some(Utc::now()),
+            last_event_id: connection.last_event_id(),
+            clean_shutdown,
+            ..Self::new(symbols)
+        }
+        .with_orderbooks(connection.orderbook_snapshots())
+    }
+
+    /// Include orderbook snapshots
+    pub fn with_orderbooks(mut self, orderbooks: Vec<OrderbookSnapshot>) -> Self {
+        self.orderbooks = orderbooks;
+        self
+    }
+
+    /// Include an order tracker's state
+    pub fn with_orders(mut self, tracker: &OrderTracker) -> Self {
+        self.orders = Some(tracker.export_state());
+        self
+    }
+
+    /// Include a position tracker
+    pub fn with_positions(mut self, tracker: &PositionTracker) -> Self {
+        self.positions = Some(tracker.clone());
+        self
+    }
+
+    /// Include a candle store's contents
+    pub fn with_candles(mut self, store: &CandleStore) -> Self {
+        self.candles = Some(CandleSnapshot {
+            max_candles: store.max_candles(),
+            candles: store.all_candles().cloned().collect(),
+        });
+        self
+    }
+
+    /// Event id a resumed client should continue after
+    ///
+    /// The saved id for a clean shutdown, [`EVENT_ID_CRASH_GAP`] past it
+    /// otherwise.
+    pub fn resume_event_id(&self) -> u64 {
+        if self.clean_shutdown {
+            self.last_event_id
+        } else {
+            self.last_event_id.saturating_add(EVENT_ID_CRASH_GAP)
+        }
+    }
+
+    /// Stored snapshot for a symbol
+    pub fn orderbook(&self, symbol: &str) -> Option<&OrderbookSnapshot> {
+        self.orderbooks.iter().find(|book| book.symbol == symbol)
+    }
+
+    /// Rebuild the order tracker with the default configuration
+    pub fn order_tracker(&self) -> Option<OrderTracker> {
+        self.orders
+            .clone()
+            .map(|state| OrderTracker::from_state(state, TrackerConfig::default()))
+    }
+
+    /// Rebuild the position tracker
+    pub fn position_tracker(&self) -> Option<PositionTracker> {
+        self.positions.clone()
+    }
+
+    /// Rebuild the candle store
+    pub fn candle_store(&self) -> Option<CandleStore> {
+        self.candles.as_ref().map(|saved| {
+            let mut store = CandleStore::new(saved.max_candles);
+            store.merge_history(saved.candles.iter().cloned());
+            store
+        })
+    }
+
+    /// Write the checkpoint to a directory, creating it if needed
+    pub fn save(&self, dir: impl AsRef<Path>) -> Result<(), CheckpointError> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+
+        let generation = next_generation(dir)?;
+        let staging = dir.join(format!("{}.tmp", generation_name(generation)));
+        remove_dir_if_exists(&staging)?;
+        fs::create_dir(&staging)?;
+        write_synced(&staging.join(ORDERBOOKS_FILE), &self.orderbooks)?;
+        write_component(&staging.join(ORDERS_FILE), self.orders.as_ref())?;
+        write_component(&staging.join(POSITIONS_FILE), self.positions.as_ref())?;
+        write_component(&staging.join(CANDLES_FILE), self.candles.as_ref())?;
+        sync_dir(&staging)?;
+        fs::rename(&staging, dir.join(generation_name(generation)))?;
+        sync_dir(dir)?;
+
+        let manifest = Manifest {
+            version: CHECKPOINT_VERSION,
+            generation: Some(generation),
+            saved_at: self.saved_at.unwrap_or_else(Utc::now),
+            symbols: self.symbols.clone(),
+            last_event_id: self.last_event_id,
+            clean_shutdown: self.clean_shutdown,
+        };
+        let manifest_tmp = dir.join(MANIFEST_FILE).with_extension("json.tmp");
+        write_synced(&manifest_tmp, &manifest)?;
+        fs::rename(&manifest_tmp, dir.join(MANIFEST_FILE))?;
+        sync_dir(dir)?;
+
+        remove_stale_generations(dir, generation);
+        Ok(())
+    }
+
+    /// Load a checkpoint from a directory
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self, CheckpointError> {
+        let dir = dir.as_ref();
+        let manifest: Manifest = read_component(&dir.join(MANIFEST_FILE))?
+            .ok_or_else(|| CheckpointError::NotFound(dir.to_path_buf()))?;
+        if manifest.version != CHECKPOINT_VERSION {
+            return Err(CheckpointError::Version {
+                found: manifest.version,
+                expected: CHECKPOINT_VERSION,
+            });
+        }
+
+        let components = match manifest.generation {
+            Some(generation) => dir.join(generation_name(generation)),
+            None => dir.to_path_buf(),
+        };
+        Ok(Self {
+            saved_at: Some(manifest.saved_at),
+            symbols: manifest.symbols,
+            last_event_id: manifest.last_event_id,
+            clean_shutdown: manifest.clean_shutdown,
+            orderbooks: read_component(&components.join(ORDERBOOKS_FILE))?.unwrap_or_default(),
+            orders: read_component(&components.join(ORDERS_FILE))?,
+            positions: read_component(&components.join(POSITIONS_FILE))?,
+            candles: read_component(&components.join(CANDLES_FILE))?,
+        })
+    }
+}
+
+fn generation_name(generation: u64) -> String {
+    format!("{GENERATION_PREFIX}{generation}")
+}
+
+/// Generation number of a `gen-N` or `gen-N.tmp` entry
+fn parse_generation(name: &str) -> Option<u64> {
+    let rest = name.strip_prefix(GENERATION_PREFIX)?;
+    rest.strip_suffix(".tmp").unwrap_or(rest).parse().ok()
+}
+
+/// One past the highest generation in the directory, finished or not
+fn next_generation(dir: &Path) -> Result<u64, CheckpointError> {
+    let mut highest = 0;
+    for entry in fs::read_dir(dir)? {
+        if let Some(generation) = parse_generation(&entry?.file_name().to_string_lossy()) {
+            highest = highest.max(generation);
+        }
+    }
+    Ok(highest + 1)
+}
+
+/// Remove every generation but `current`, and components saved before generations
+///
+/// Failures only leave stale files behind, so they are logged, not returned.
+fn remove_stale_generations(dir: &Path, current: u64) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            warn!("Can't list {} to remove old checkpoints: {}", dir.display(), e);
+            return;
+        }
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let removed = match parse_generation(&name) {
+            Some(generation) if generation != current => fs::remove_dir_all(entry.path()),
+            None if [ORDERBOOKS_FILE, ORDERS_FILE, POSITIONS_FILE, CANDLES_FILE].contains(&name.as_str()) => {
+                fs::remove_file(entry.path())
+            }
+            _ => continue,
+        };
+        if let Err(e) = removed {
+            warn!("Can't remove old checkpoint file {}: {}", entry.path().display(), e);
+        }
+    }
+}
+
+fn remove_dir_if_exists(path: &Path) -> Result<(), CheckpointError> {
+    match fs::remove_dir_all(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Write `value` as JSON to a new file and sync it
+fn write_synced<T: Serialize + ?Sized>(path: &Path, value: &T) -> Result<(), CheckpointError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    serde_json::to_writer(&mut writer, value)?;
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    Ok(())
+}
+
+/// Write an optional component, leaving no file when absent
+fn write_component<T: Serialize>(path: &Path, value: Option<&T>) -> Result<(), CheckpointError> {
+    match value {
+        Some(value) => write_synced(path, value),
+        None => Ok(()),
+    }
+}
+
+/// Make renames and new entries in a directory durable
+#[cfg(unix)]
+fn sync_dir(dir: &Path) -> Result<(), CheckpointError> {
+    File::open(dir)?.sync_all()?;
+    Ok(())
+}
+
+/// Directories can't be opened for syncing here; renames are left to the OS
+#[cfg(not(unix))]
+fn sync_dir(_dir: &Path) -> Result<(), CheckpointError> {
+    Ok(())
+}
+
+/// Read a component, or None if its file doesn't exist
+fn read_component<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, CheckpointError> {
+    match fs::read(path) {
+        Ok(bytes) => Ok(Some(serde_json::from_slice(&bytes)?)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Saves client state to a directory on an interval
+///
+/// Created with [`KrakenClient::checkpointer`](crate::KrakenClient::checkpointer),
+/// which captures the client's books. Trackers and candle stores live in the
+/// application, so share them with `with_*`.
+pub struct Checkpointer {
+    dir: PathBuf,
+    interval: Duration,
+    connection: Arc<KrakenConnection>,
+    symbols: Vec<String>,
+    orders: Option<Arc<Mutex<OrderTracker>>>,
+    positions: Option<Arc<Mutex<PositionTracker>>>,
+    candles: Option<Arc<Mutex<CandleStore>>>,
+}
+
+impl Checkpointer {
+    pub(crate) fn new(
+        connection: Arc<KrakenConnection>,
+        symbols: Vec<String>,
+        dir: PathBuf,
+        interval: Duration,
+    ) -> Self {
+        Self {
+            dir,
+            interval,
+            connection,
+            symbols,
+            orders: None,
+            positions: None,
+            candles: None,
+        }
+    }
+
+    /// Include an order tracker in every checkpoint
+    pub fn with_orders(mut self, tracker: Arc<Mutex<OrderTracker>>) -> Self {
+        self.orders = Some(tracker);
+        self
+    }
+
+    /// Include a position tracker in every checkpoint
+    pub fn with_positions(mut self, tracker: Arc<Mutex<PositionTracker>>) -> Self {
+        self.positions = Some(tracker);
+        self
+    }
+
+    /// Include a candle store in every checkpoint
+    pub fn with_candles(mut self, store: Arc<Mutex<CandleStore>>) -> Self {
+        self.candles = Some(store);
+        self
+    }
+
+    /// Directory checkpoints are written to
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Capture the current state without writing it
+    ///
+    /// Each component is locked only while it is copied.
+    pub fn capture(&self) -> Checkpoint {
+        let mut checkpoint = Checkpoint::capture(&self.connection, self.symbols.clone());
+        if let Some(orders) = &self.orders {
+            checkpoint = checkpoint.with_orders(&lock(orders));
+        }
+        if let Some(positions) = &self.positions {
+            checkpoint = checkpoint.with_positions(&lock(positions));
+        }
+        if let Some(candles) = &self.candles {
+            checkpoint = checkpoint.with_candles(&lock(candles));
+        }
+        checkpoint
+    }
+
+    /// Capture and save a checkpoint now
+    pub fn save_now(&self) -> Result<Checkpoint, CheckpointError> {
+        let checkpoint = self.capture();
+        checkpoint.save(&self.dir)?;
+        debug!(
+            "Checkpointed {} books to {}",
+            checkpoint.orderbooks.len(),
+            self.dir.display()
+        );
+        Ok(checkpoint)
+    }
+
+    /// Save on every interval until the client's run loop exits
+    ///
+    /// The exit is observed through [`KrakenConnection::watch_stopped`], so
+    /// it is seen whatever state the connection is left in. A final
+    /// checkpoint is then saved and `run` returns. Failed saves are logged
+    /// and retried on the next tick.
+    pub async fn run(self) {
+        let mut stopped = self.connection.watch_stopped();
+        let mut tick = tokio::time::interval(self.interval.max(Duration::from_millis(10)));
+        tick.tick().await;
+        loop {
+            let last = tokio::select! {
+                _ = tick.tick() => false,
+                _ = stopped.wait_for(|stopped| *stopped) => true,
+            };
+            let checkpoint = self.capture();
+            let dir = self.dir.clone();
+            let saved = tokio::task::spawn_blocking(move || checkpoint.save(dir)).await;
+            match saved {
+                Ok(Ok(())) => {}
+                Ok(Err(e)) => warn!("Checkpoint to {} failed: {}", self.dir.display(), e),
+                Err(e) => warn!("Checkpoint task failed: {}", e),
+            }
+            if last {
+                return;
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_types::{Decimal, Level, Side};
+    use rust_decimal_macros::dec;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kraken-checkpoint-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn candle(begin: &str) -> OhlcData {
+        OhlcData {
+            symbol: "BTC/USD".to_string(),
+            open: dec!(1),
+            high: dec!(1),
+            low: dec!(1),
+            close: dec!(1),
+            vwap: dec!(1),
+            volume: dec!(1),
+            trades: 1,
+            interval_begin: begin.to_string(),
+            interval: 1,
+        }
+    }
+
+    #[test]
+    fn test_round_trip_all_components() {
+        let dir = temp_dir("round-trip");
+        let mut orders = OrderTracker::new();
+        orders.track_submission("req1", "BTC/USD", Side::Buy, dec!(1), Some(dec!(100)));
+        let mut positions = PositionTracker::new();
+        positions.apply_fill("BTC/USD", Side::Buy, dec!(2), dec!(100), Decimal::ZERO, None);
+        let mut candles = CandleStore::new(5);
+        candles.apply(candle("2024-01-01T00:00:00Z"));
+        candles.apply(candle("2024-01-01T00:01:00Z"));
+
+        let book = OrderbookSnapshot {
+            symbol: "BTC/USD".to_string(),
+            bids: vec![Level::new(dec!(100), dec!(1))],
+            asks: vec![Level::new(dec!(101), dec!(2))],
+            checksum: 42,
+            ..Default::default()
+        };
+        Checkpoint::new(["BTC/USD"])
+            .with_orderbooks(vec![book])
+            .with_orders(&orders)
+            .with_positions(&positions)
+            .with_candles(&candles)
+            .save(&dir)
+            .unwrap();
+
+        let loaded = Checkpoint::load(&dir).unwrap();
+        assert!(loaded.saved_at.is_some());
+        assert_eq!(loaded.symbols, vec!["BTC/USD"]);
+        assert_eq!(loaded.orderbook("BTC/USD").unwrap().checksum, 42);
+        assert!(loaded.order_tracker().unwrap().get_by_request_id("req1").is_some());
+        assert_eq!(loaded.position_tracker().unwrap().position("BTC").unwrap().qty, dec!(2));
+        let store = loaded.candle_store().unwrap();
+        assert_eq!(store.max_candles(), 5);
+        assert_eq!(store.len("BTC/USD", 1), 2);
+        assert!(dir.join("gen-1").join(ORDERS_FILE).exists());
+        assert!(!dir.join("gen-1.tmp").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_save_switches_generations_whole() {
+        let dir = temp_dir("generations");
+        Checkpoint::new(["BTC/USD"]).with_positions(&PositionTracker::new()).save(&dir).unwrap();
+
+        // A save that crashed before its rename leaves only a staging directory
+        let torn = dir.join("gen-2.tmp");
+        fs::create_dir_all(&torn).unwrap();
+        fs::write(torn.join(ORDERBOOKS_FILE), "[{").unwrap();
+        assert!(Checkpoint::load(&dir).unwrap().positions.is_some());
+
+        Checkpoint::new(["ETH/USD"]).save(&dir).unwrap();
+        let loaded = Checkpoint::load(&dir).unwrap();
+        assert_eq!(loaded.symbols, vec!["ETH/USD"]);
+        assert!(loaded.positions.is_none());
+        let mut entries: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        entries.sort();
+        assert_eq!(entries, vec!["gen-3", MANIFEST_FILE]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_loads_checkpoint_saved_before_generations() {
+        let dir = temp_dir("legacy");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(MANIFEST_FILE),
+            r#"{"version":1,"saved_at":"2024-01-01T00:00:00Z","symbols":["BTC/USD"],"last_event_id":7}"#,
+        )
+        .unwrap();
+        fs::write(dir.join(POSITIONS_FILE), serde_json::to_string(&PositionTracker::new()).unwrap()).unwrap();
+        let loaded = Checkpoint::load(&dir).unwrap();
+        assert_eq!(loaded.last_event_id, 7);
+        assert!(loaded.positions.is_some());
+
+        // The next save moves it into a generation and drops the old files
+        loaded.save(&dir).unwrap();
+        assert!(!dir.join(POSITIONS_FILE).exists());
+        assert!(Checkpoint::load(&dir).unwrap().positions.is_some());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_checkpointer_saves_and_returns_once_run_loop_exits() {
+        let dir = temp_dir("run");
+        let connection = Arc::new(KrakenConnection::with_defaults());
+        let checkpointer = Checkpointer::new(
+            Arc::clone(&connection),
+            vec!["BTC/USD".to_string()],
+            dir.clone(),
+            Duration::from_secs(3600),
+        );
+        let run = tokio::spawn(checkpointer.run());
+
+        connection.shutdown();
+        connection.connect_and_run().await.unwrap();
+        tokio::time::timeout(Duration::from_secs(5), run)
+            .await
+            .expect("checkpointer returned after the run loop exited")
+            .unwrap();
+        let saved = Checkpoint::load(&dir).unwrap();
+        assert_eq!(saved.symbols, vec!["BTC/USD"]);
+        assert!(saved.clean_shutdown);
+        assert_eq!(saved.last_event_id, connection.last_event_id());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_event_id_survives_restart() {
+        let dir = temp_dir("event-id");
+        let connection = KrakenConnection::with_defaults();
+        connection.resume_event_ids(1200);
+
+        let checkpoint = Checkpoint::capture(&connection, vec!["BTC/USD".to_string()]);
+        assert!(!checkpoint.clean_shutdown);
+        checkpoint.save(&dir).unwrap();
+        let loaded = Checkpoint::load(&dir).unwrap();
+        assert_eq!(loaded.last_event_id, 1200);
+        assert_eq!(loaded.resume_event_id(), 1200 + EVENT_ID_CRASH_GAP);
+
+        // Shutdown requested but the run loop may still emit
+        connection.shutdown();
+        assert!(!Checkpoint::capture(&connection, Vec::new()).clean_shutdown);
+
+        connection.connect_and_run().await.unwrap();
+        Checkpoint::capture(&connection, Vec::new()).save(&dir).unwrap();
+        assert_eq!(Checkpoint::load(&dir).unwrap().resume_event_id(), 1200);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_dropped_component_is_removed() {
+        let dir = temp_dir("dropped");
+        Checkpoint::new(["BTC/USD"])
+            .with_positions(&PositionTracker::new())
+            .save(&dir)
+            .unwrap();
+        assert!(Checkpoint::load(&dir).unwrap().positions.is_some());
+
+        Checkpoint::new(["BTC/USD"]).save(&dir).unwrap();
+        let loaded = Checkpoint::load(&dir).unwrap();
+        assert!(loaded.positions.is_none());
+        assert!(loaded.orders.is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_missing_and_incompatible_checkpoints() {
+        let dir = temp_dir("missing");
+        assert!(matches!(Checkpoint::load(&dir), Err(CheckpointError::NotFound(_))));
+
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join(MANIFEST_FILE),
+            r#"{"version":99,"saved_at":"2024-01-01T00:00:00Z","symbols":[]}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            Checkpoint::load(&dir),
+            Err(CheckpointError::Version { found: 99, .. })
+        ));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
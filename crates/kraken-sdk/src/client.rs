@@ -1,12 +1,23 @@
 //! High-level Kraken client
 
-use crate::builder::KrakenClientBuilder;
-use kraken_book::Orderbook;
-use kraken_types::KrakenError;
-use kraken_ws::{ConnectionState, EventReceiver, KrakenConnection};
+use crate::builder::{KrakenClientBuilder, OhlcInterval};
+use crate::checkpoint::{Checkpoint, CheckpointError, Checkpointer};
+use crate::composite::CompositePricer;
+use crate::portfolio::{PortfolioValuer, Valuation};
+use kraken_book::{ExtendedSnapshot, LevelMeta, Orderbook, OrderbookSnapshot, OrderbookState};
+use crate::pair_status::{apply_pair_statuses, parse_asset_pairs_status, PairStatusError, PairStatusPoller};
+use crate::rest_backfill::RestFetch;
+use kraken_types::{Channel, Formatting, KrakenError, Level, PairStatus, Precision, Symbol, SystemStatus};
+use kraken_ws::{
+    CallbackStats, ClockEstimate, ConnectionState, EventReceiver, HealthStats, InlineStats, KrakenConnection, LatencyStats,
+    RestorationProgress, SharedRateLimiter, Subscription, TransportStats,
+};
 use rust_decimal::Decimal;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tracing::{info, instrument};
+use std::time::{Duration, SystemTime};
+use tracing::{info, instrument, warn};
 
 /// High-level client for Kraken WebSocket API
 ///
@@ -47,16 +58,49 @@ pub struct KrakenClient {
     event_rx: Option<EventReceiver>,
     /// Configured symbols
     symbols: Vec<String>,
+    /// Checkpoint the client was resumed from
+    restored: Option<Checkpoint>,
 }
 
 impl KrakenClient {
     /// Create a new client builder
     pub fn builder(
-        symbols: impl IntoIterator<Item = impl Into<String>>,
+        symbols: impl IntoIterator<Item = impl Into<Symbol>>,
     ) -> KrakenClientBuilder {
         KrakenClientBuilder::new(symbols)
     }
 
+    /// Builder restoring the state saved in a checkpoint directory
+    ///
+    /// The builder subscribes to the checkpointed symbols and seeds their
+    /// books before connecting. Trackers and candles are returned through
+    /// [`restored_checkpoint`](Self::restored_checkpoint).
+    pub fn resume_from_checkpoint(path: impl AsRef<Path>) -> Result<KrakenClientBuilder, CheckpointError> {
+        let checkpoint = Checkpoint::load(path)?;
+        Ok(KrakenClientBuilder::new(checkpoint.symbols.clone()).with_checkpoint(checkpoint))
+    }
+
+    /// Checkpoint the client was resumed from, if any
+    pub fn restored_checkpoint(&self) -> Option<&Checkpoint> {
+        self.restored.as_ref()
+    }
+
+    /// Capture the client's books and last event id as a checkpoint
+    ///
+    /// Marked clean only once the connection's run loop has exited, e.g.
+    /// after [`shutdown`](Self::shutdown) has taken effect.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint::capture(&self.connection, self.symbols.clone())
+    }
+
+    /// Periodic checkpoint writer for this client
+    ///
+    /// Spawn [`Checkpointer::run`] to save every `interval` and once more,
+    /// marked clean, after the connection's run loop has exited.
+    pub fn checkpointer(&self, dir: impl Into<PathBuf>, interval: Duration) -> Checkpointer {
+        Checkpointer::new(Arc::clone(&self.connection), self.symbols.clone(), dir.into(), interval)
+    }
+
     /// Get the connection state
     pub fn state(&self) -> ConnectionState {
         self.connection.state()
@@ -67,12 +111,81 @@ impl KrakenClient {
         self.connection.is_connected()
     }
 
+    /// Last exchange system status (online, maintenance, cancel_only, ...)
+    pub fn system_status(&self) -> Option<SystemStatus> {
+        self.connection.system_status()
+    }
+
+    /// Watch system status changes, e.g. to gate a `TradingClient`
+    pub fn watch_system_status(&self) -> tokio::sync::watch::Receiver<Option<SystemStatus>> {
+        self.connection.watch_system_status()
+    }
+
+    /// Last trading status reported for a pair (online, post_only, ...)
+    pub fn pair_status(&self, symbol: &str) -> Option<PairStatus> {
+        self.connection.pair_status(symbol)
+    }
+
+    /// Pairs not trading normally, with their status
+    pub fn restricted_pairs(&self) -> HashMap<String, PairStatus> {
+        self.connection.restricted_pairs()
+    }
+
+    /// Watch pair statuses, e.g. to gate a `TradingClient`
+    pub fn watch_pair_status(&self) -> tokio::sync::watch::Receiver<HashMap<String, PairStatus>> {
+        self.connection.watch_pair_status()
+    }
+
+    /// REST `AssetPairs` poller feeding this client's pair statuses
+    ///
+    /// Spawn [`PairStatusPoller::run`] to poll every `interval` until the
+    /// connection's run loop exits.
+    pub fn pair_status_poller(&self, fetch: RestFetch, interval: Duration) -> PairStatusPoller {
+        PairStatusPoller::new(Arc::clone(&self.connection), fetch, interval)
+    }
+
+    /// Progress of restoring subscriptions after the last (re)connect
+    pub fn restoration_progress(&self) -> Option<RestorationProgress> {
+        self.connection.restoration_progress()
+    }
+
+    /// Returns true until every resent subscription is live and its book
+    /// re-synced; a good gate for trading after a reconnect
+    pub fn is_restoring(&self) -> bool {
+        self.connection.is_restoring()
+    }
+
+    /// Apply pair statuses from a REST `AssetPairs` response
+    ///
+    /// Records statuses exactly as the instrument channel does: pairs seen
+    /// for the first time are seeded silently, and changes to subscribed
+    /// pairs emit `ConnectionEvent::PairStatusChanged`. Returns the number of
+    /// pairs that changed.
+    pub fn apply_asset_pairs(&self, body: &str) -> Result<usize, PairStatusError> {
+        Ok(apply_pair_statuses(&self.connection, parse_asset_pairs_status(body)?))
+    }
+
     /// Get the subscribed symbols
     pub fn symbols(&self) -> &[String] {
         &self.symbols
     }
 
+    /// Whether `symbol` is subscribed on `channel`
+    pub fn is_subscribed(&self, channel: Channel, symbol: &str) -> bool {
+        self.connection.is_subscribed(channel, symbol)
+    }
+
+    /// Active subscriptions, as they are restored after a reconnect
+    pub fn subscriptions(&self) -> Vec<Subscription> {
+        self.connection.subscriptions()
+    }
+
     /// Get an orderbook by symbol
+    ///
+    /// The guard blocks updates to the book while held; never keep it
+    /// across an `.await`. Async code should use
+    /// [`orderbook_snapshot`](Self::orderbook_snapshot) or
+    /// [`with_orderbook`](Self::with_orderbook).
     pub fn orderbook(
         &self,
         symbol: &str,
@@ -80,16 +193,36 @@ impl KrakenClient {
         self.connection.orderbook(symbol)
     }
 
+    /// Owned copy of the current orderbook for a symbol
+    pub fn orderbook_snapshot(&self, symbol: &str) -> Option<OrderbookSnapshot> {
+        self.connection.orderbook_snapshot(symbol)
+    }
+
+    /// Run `f` against the orderbook for a symbol while briefly holding its lock
+    pub fn with_orderbook<R>(&self, symbol: &str, f: impl FnOnce(&Orderbook) -> R) -> Option<R> {
+        self.connection.with_orderbook(symbol, f)
+    }
+
+    /// Books unsubscribed because they weren't read (see `with_pruning`)
+    pub fn pruned_symbols(&self) -> Vec<String> {
+        self.connection.pruned_symbols()
+    }
+
+    /// Resubscribe a pruned book; false if it isn't pruned
+    pub fn resume_pruned(&self, symbol: &str) -> bool {
+        self.connection.resume_pruned(symbol)
+    }
+
     /// Get the best bid for a symbol
     pub fn best_bid(&self, symbol: &str) -> Option<Decimal> {
         self.orderbook(symbol)
-            .and_then(|book| book.best_bid().map(|l| l.price))
+            .and_then(|book| book.best_bid().map(|l| l.price.0))
     }
 
     /// Get the best ask for a symbol
     pub fn best_ask(&self, symbol: &str) -> Option<Decimal> {
         self.orderbook(symbol)
-            .and_then(|book| book.best_ask().map(|l| l.price))
+            .and_then(|book| book.best_ask().map(|l| l.price.0))
     }
 
     /// Get the spread for a symbol
@@ -102,6 +235,109 @@ impl KrakenClient {
         self.orderbook(symbol).and_then(|book| book.mid_price())
     }
 
+    /// Price of `base` in `quote` derived across books, e.g. BTC/EUR × EUR/USD
+    ///
+    /// Blends the direct pair and routes through common bridge assets,
+    /// weighting each by how recently its books updated. Use a
+    /// [`CompositePricer`] to choose the routes or weighting.
+    pub fn composite_price(&self, base: &str, quote: &str) -> Option<Decimal> {
+        let mut pricer = CompositePricer::new();
+        pricer.update_from_client(self);
+        pricer.price(base, quote).map(|composite| composite.price)
+    }
+
+    /// Value of `qty` units of an asset in `quote`, from the current books
+    pub fn asset_value(&self, asset: &str, qty: Decimal, quote: &str) -> Option<Decimal> {
+        if asset == quote {
+            return Some(qty);
+        }
+        self.composite_price(asset, quote).map(|price| price * qty)
+    }
+
+    /// Value a portfolio at the current books and add it to its history
+    ///
+    /// The valuer keeps balances between calls; feed them from the balances
+    /// channel or a REST `Balance` response (see [`PortfolioValuer`]).
+    pub fn portfolio_valuation(&self, valuer: &mut PortfolioValuer) -> Valuation {
+        valuer.update_from_client(self);
+        valuer.record()
+    }
+
+    /// Best bid level (price and quantity) for a symbol
+    pub fn best_bid_level(&self, symbol: &str) -> Option<Level> {
+        self.with_orderbook(symbol, |book| book.best_bid().cloned()).flatten()
+    }
+
+    /// Best ask level (price and quantity) for a symbol
+    pub fn best_ask_level(&self, symbol: &str) -> Option<Level> {
+        self.with_orderbook(symbol, |book| book.best_ask().cloned()).flatten()
+    }
+
+    /// Top `n` bid levels for a symbol, best first (empty if no book)
+    pub fn top_bids(&self, symbol: &str, n: usize) -> Vec<Level> {
+        self.with_orderbook(symbol, |book| book.top_bids(n)).unwrap_or_default()
+    }
+
+    /// Top `n` ask levels for a symbol, best first (empty if no book)
+    pub fn top_asks(&self, symbol: &str, n: usize) -> Vec<Level> {
+        self.with_orderbook(symbol, |book| book.top_asks(n)).unwrap_or_default()
+    }
+
+    /// All bid levels for a symbol, best first (empty if no book)
+    pub fn bids(&self, symbol: &str) -> Vec<Level> {
+        self.with_orderbook(symbol, Orderbook::bids_vec).unwrap_or_default()
+    }
+
+    /// All ask levels for a symbol, best first (empty if no book)
+    pub fn asks(&self, symbol: &str) -> Vec<Level> {
+        self.with_orderbook(symbol, Orderbook::asks_vec).unwrap_or_default()
+    }
+
+    /// Number of bid levels for a symbol
+    pub fn bid_count(&self, symbol: &str) -> Option<usize> {
+        self.with_orderbook(symbol, Orderbook::bid_count)
+    }
+
+    /// Number of ask levels for a symbol
+    pub fn ask_count(&self, symbol: &str) -> Option<usize> {
+        self.with_orderbook(symbol, Orderbook::ask_count)
+    }
+
+    /// Total quantity across all bid levels for a symbol
+    pub fn total_bid_qty(&self, symbol: &str) -> Option<Decimal> {
+        self.with_orderbook(symbol, Orderbook::total_bid_qty)
+    }
+
+    /// Total quantity across all ask levels for a symbol
+    pub fn total_ask_qty(&self, symbol: &str) -> Option<Decimal> {
+        self.with_orderbook(symbol, Orderbook::total_ask_qty)
+    }
+
+    /// Bid/ask quantity imbalance over the top `levels`, from -1 to 1
+    pub fn imbalance(&self, symbol: &str, levels: usize) -> Option<Decimal> {
+        self.with_orderbook(symbol, |book| book.imbalance(levels)).flatten()
+    }
+
+    /// Depth the book for a symbol is kept at
+    pub fn book_depth(&self, symbol: &str) -> Option<u32> {
+        self.with_orderbook(symbol, Orderbook::depth)
+    }
+
+    /// Synchronization state of the book for a symbol
+    pub fn book_state(&self, symbol: &str) -> Option<OrderbookState> {
+        self.with_orderbook(symbol, Orderbook::state)
+    }
+
+    /// Per-level metadata at a price, if tracked (see `with_level_metadata`)
+    pub fn level_meta(&self, symbol: &str, price: Decimal) -> Option<LevelMeta> {
+        self.with_orderbook(symbol, |book| book.level_meta(price)).flatten()
+    }
+
+    /// Owned copy of the book with per-level metadata, if tracked
+    pub fn orderbook_snapshot_extended(&self, symbol: &str) -> Option<ExtendedSnapshot> {
+        self.with_orderbook(symbol, Orderbook::snapshot_extended).flatten()
+    }
+
     /// Get the last checksum for a symbol
     pub fn checksum(&self, symbol: &str) -> Option<u32> {
         self.orderbook(symbol).map(|book| book.last_checksum())
@@ -114,6 +350,96 @@ impl KrakenClient {
             .unwrap_or(false)
     }
 
+    /// Precision learned from the instrument channel, for all symbols
+    pub fn formatting(&self) -> Formatting {
+        self.connection.formatting()
+    }
+
+    /// Price and quantity precision for a symbol
+    pub fn precision(&self, symbol: &str) -> Precision {
+        self.connection.precision(symbol)
+    }
+
+    /// Price with exactly the symbol's price decimals, for display
+    pub fn format_price(&self, symbol: &str, price: Decimal) -> String {
+        self.connection.format_price(symbol, price)
+    }
+
+    /// Quantity with exactly the symbol's quantity decimals, for display
+    pub fn format_qty(&self, symbol: &str, qty: Decimal) -> String {
+        self.connection.format_qty(symbol, qty)
+    }
+
+    /// Price rounded to the nearest valid tick, for order submission
+    pub fn round_to_tick(&self, symbol: &str, price: Decimal) -> Decimal {
+        self.connection.round_to_tick(symbol, price)
+    }
+
+    /// Quantity rounded down to a valid lot, for order submission
+    pub fn round_qty(&self, symbol: &str, qty: Decimal) -> Decimal {
+        self.connection.round_qty(symbol, qty)
+    }
+
+    /// Force a fresh server snapshot for one symbol's book
+    ///
+    /// Resolves once the new snapshot has been applied. See
+    /// [`KrakenConnection::request_snapshot`].
+    pub fn request_snapshot(
+        &self,
+        symbol: impl Into<Symbol>,
+    ) -> impl std::future::Future<Output = Result<(), KrakenError>> {
+        self.connection.request_snapshot(symbol)
+    }
+
+    /// Register a callback invoked on every orderbook change for a symbol
+    ///
+    /// Works alongside [`events`](Self::events) for integrations that don't
+    /// run their own event loop. The callback runs inline on the connection
+    /// task inside a panic guard; invocations over the callback budget are
+    /// logged and counted in [`callback_stats`](Self::callback_stats).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example() -> Result<(), kraken_types::KrakenError> {
+    /// use kraken_sdk::KrakenClient;
+    ///
+    /// let client = KrakenClient::builder(["BTC/USD"]).connect().await?;
+    /// client.on_book_update("BTC/USD", |snapshot| {
+    ///     println!("BTC/USD mid: {:?}", snapshot.mid_price());
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn on_book_update<F>(&self, symbol: impl Into<Symbol>, callback: F)
+    where
+        F: Fn(&OrderbookSnapshot) + Send + Sync + 'static,
+    {
+        self.connection.on_book_update(symbol, callback);
+    }
+
+    /// Register an async callback spawned on every orderbook change for a symbol
+    ///
+    /// Each invocation is cancelled once the callback budget elapses, and a
+    /// panic only ends that invocation.
+    pub fn on_book_update_async<F, Fut>(&self, symbol: impl Into<Symbol>, callback: F)
+    where
+        F: Fn(OrderbookSnapshot) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        self.connection.on_book_update_async(symbol, callback);
+    }
+
+    /// Invocation, panic, overrun and timeout counts for book callbacks
+    pub fn callback_stats(&self) -> CallbackStats {
+        self.connection.book_callbacks().stats()
+    }
+
+    /// Invocation, panic and overrun counts for the inline handler, if set
+    pub fn inline_stats(&self) -> Option<InlineStats> {
+        self.connection.inline_stats()
+    }
+
     /// Take the event receiver (can only be called once)
     ///
     /// Returns the event stream for processing market data and connection events.
@@ -129,6 +455,65 @@ impl KrakenClient {
         self.connection.dropped_event_count()
     }
 
+    /// Id of the last event emitted (see [`kraken_ws::SequencedEvent`])
+    pub fn last_event_id(&self) -> u64 {
+        self.connection.last_event_id()
+    }
+
+    /// Exchange-to-client latency statistics
+    ///
+    /// Returns None until a timestamped book update or trade has arrived.
+    pub fn latency_stats(&self) -> Option<LatencyStats> {
+        self.connection.latency_stats()
+    }
+
+    /// Connection health: per-channel message counts, heartbeat age,
+    /// checksum mismatches, dropped events and reconnect history
+    pub fn health(&self) -> HealthStats {
+        self.connection.health()
+    }
+
+    /// Messages and bytes sent and received, with decompression counters
+    /// when compression is enabled
+    pub fn traffic_stats(&self) -> TransportStats {
+        self.connection.traffic_stats()
+    }
+
+    /// Local clock offset and drift relative to the exchange
+    ///
+    /// Estimated from exchange timestamps on book updates and trades, plus
+    /// any samples passed to [`record_server_time`](Self::record_server_time).
+    pub fn clock_estimate(&self) -> Option<ClockEstimate> {
+        self.connection.clock_estimate()
+    }
+
+    /// Current time on the exchange's clock
+    pub fn estimated_server_time(&self) -> Option<SystemTime> {
+        self.connection.estimated_server_time()
+    }
+
+    /// Record a REST `Time` response (`unixtime`) and the local send and
+    /// receive times, in microseconds since the Unix epoch
+    pub fn record_server_time(&self, server_unix_secs: i64, sent_us: i64, received_us: i64) {
+        self.connection
+            .record_server_time(server_unix_secs, sent_us, received_us);
+    }
+
+    /// Approximate bytes held by all orderbooks
+    pub fn approx_memory_bytes(&self) -> usize {
+        self.connection.approx_memory_bytes()
+    }
+
+    /// Approximate bytes held by each orderbook, by symbol
+    pub fn memory_usage(&self) -> BTreeMap<String, usize> {
+        self.connection.memory_usage()
+    }
+
+    /// Rate limiter set with [`KrakenClientBuilder::with_rate_limiter`]
+    pub fn rate_limiter(&self) -> Option<&SharedRateLimiter> {
+        self.connection.rate_limiter()
+    }
+
     /// Request graceful shutdown
     #[instrument(skip(self))]
     pub fn shutdown(&self) {
@@ -147,22 +532,30 @@ impl KrakenClientBuilder {
                 actual: "no symbols provided".to_string(),
             });
         }
+        if (self.trade_backfill || self.ohlc_backfill.is_some()) && self.backfiller().is_none() {
+            return Err(KrakenError::InvalidState {
+                expected: "a backfill fetch function or the `backfill` feature".to_string(),
+                actual: "backfill enabled without a way to fetch".to_string(),
+            });
+        }
 
         // Create connection
         let config = self.to_connection_config();
         let connection = KrakenConnection::new(config);
 
-        // Set up subscriptions
-        if self.subscribe_book {
-            connection.subscribe_orderbook(self.symbols.clone());
-        }
-        if self.subscribe_ticker {
-            connection.subscribe_ticker(self.symbols.clone());
-        }
-        if self.subscribe_trade {
-            connection.subscribe_trade(self.symbols.clone());
+        // Seed books from the checkpoint so they're readable before the first
+        // snapshot, and so snapshot-less subscriptions can continue from them
+        if let Some(checkpoint) = &self.checkpoint {
+            for snapshot in &checkpoint.orderbooks {
+                connection.restore_orderbook(snapshot);
+            }
+            connection.resume_event_ids(checkpoint.resume_event_id());
+            info!("Restored {} orderbooks from checkpoint", checkpoint.orderbooks.len());
         }
 
+        // Set up subscriptions
+        self.subscribe_on(&connection);
+
         // Take the event receiver before spawning
         let event_rx = connection.take_event_receiver();
 
@@ -186,10 +579,52 @@ impl KrakenClientBuilder {
             connection,
             event_rx,
             symbols: self.symbols,
+            restored: self.checkpoint,
         })
     }
 }
 
+impl KrakenClientBuilder {
+    /// Register the configured subscriptions on a connection
+    fn subscribe_on(&self, connection: &KrakenConnection) {
+        for (channel, symbols) in self.subscription_plan() {
+            match channel {
+                Channel::Book if self.snapshots => {
+                    connection.subscribe_orderbook(symbols);
+                }
+                Channel::Book => {
+                    connection.subscribe_orderbook_without_snapshot(symbols);
+                }
+                Channel::Ticker => {
+                    connection.subscribe_ticker(symbols);
+                }
+                Channel::Trade => {
+                    connection.subscribe(Subscription::trade(symbols).with_snapshot(self.snapshots));
+                }
+                Channel::Level3 => {
+                    connection.subscribe_l3(symbols);
+                }
+                other => warn!("Channel {:?} can't be subscribed per symbol, skipping", other),
+            }
+        }
+
+        // OHLC intervals apply to symbols without their own channel selection
+        let ohlc_symbols: Vec<&String> = self
+            .symbols
+            .iter()
+            .filter(|symbol| !self.symbol_channels.iter().any(|(s, _)| s == *symbol))
+            .collect();
+        if ohlc_symbols.is_empty() {
+            return;
+        }
+        let mut intervals: Vec<OhlcInterval> = self.ohlc_intervals.iter().copied().collect();
+        intervals.sort_by_key(OhlcInterval::as_minutes);
+        for interval in intervals {
+            connection.subscribe_ohlc(ohlc_symbols.iter().map(|s| s.as_str()), interval.into());
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -205,4 +640,91 @@ mod tests {
         assert!(builder.subscribe_book);
         assert!(builder.subscribe_ticker);
     }
+
+    #[test]
+    fn test_book_queries_return_owned_data() {
+        use rust_decimal_macros::dec;
+
+        let connection = Arc::new(KrakenConnection::with_defaults());
+        connection.restore_orderbook(&OrderbookSnapshot {
+            symbol: "BTC/USD".to_string(),
+            bids: vec![Level::new(dec!(100), dec!(3)), Level::new(dec!(99), dec!(5))],
+            asks: vec![Level::new(dec!(101), dec!(1)), Level::new(dec!(102), dec!(2))],
+            checksum: 0,
+            state: OrderbookState::Synced,
+        });
+        let client = KrakenClient {
+            connection,
+            event_rx: None,
+            symbols: vec!["BTC/USD".to_string()],
+            restored: None,
+        };
+
+        assert_eq!(client.best_bid_level("BTC/USD"), Some(Level::new(dec!(100), dec!(3))));
+        assert_eq!(client.top_asks("BTC/USD", 1), vec![Level::new(dec!(101), dec!(1))]);
+        assert_eq!(client.bids("BTC/USD").len(), 2);
+        assert_eq!(client.ask_count("BTC/USD"), Some(2));
+        assert_eq!(client.total_bid_qty("BTC/USD"), Some(dec!(8)));
+        assert_eq!(client.imbalance("BTC/USD", 1), Some(dec!(0.5)));
+        assert_eq!(client.book_state("BTC/USD"), Some(OrderbookState::AwaitingSnapshot));
+
+        assert!(client.top_bids("ETH/USD", 5).is_empty());
+        assert_eq!(client.total_ask_qty("ETH/USD"), None);
+    }
+
+    #[test]
+    fn test_per_symbol_channels_are_restored_as_selected() {
+        let builder = KrakenClient::builder(["SOL/USD"])
+            .with_trade(true)
+            .subscribe("BTC/USD", &[Channel::Book, Channel::Trade])
+            .subscribe("ETH/USD", &[Channel::Ticker]);
+        let connection = KrakenConnection::new(builder.to_connection_config());
+        builder.subscribe_on(&connection);
+
+        let subs: Vec<(Channel, Vec<String>)> = connection
+            .subscriptions()
+            .into_iter()
+            .map(|sub| (sub.channel, sub.symbols))
+            .collect();
+        assert_eq!(
+            subs,
+            vec![
+                (Channel::Book, vec!["SOL/USD".to_string(), "BTC/USD".to_string()]),
+                (Channel::Trade, vec!["SOL/USD".to_string(), "BTC/USD".to_string()]),
+                (Channel::Ticker, vec!["ETH/USD".to_string()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ohlc_intervals_are_subscribed_with_backfill() {
+        let builder = KrakenClient::builder(["BTC/USD", "ETH/USD"])
+            .with_ohlc_intervals([OhlcInterval::H1, OhlcInterval::M5])
+            .subscribe("ETH/USD", &[Channel::Ticker])
+            .with_ohlc_backfill(100)
+            .with_backfill_fetch(|_url: String| async { Err::<String, _>("offline") });
+        let connection = KrakenConnection::new(builder.to_connection_config());
+        builder.subscribe_on(&connection);
+
+        let ohlc: Vec<(Vec<String>, Option<u32>)> = connection
+            .subscriptions()
+            .into_iter()
+            .filter(|sub| sub.channel == Channel::Ohlc)
+            .map(|sub| (sub.symbols, sub.interval))
+            .collect();
+        assert_eq!(
+            ohlc,
+            vec![(vec!["BTC/USD".to_string()], Some(5)), (vec!["BTC/USD".to_string()], Some(60))]
+        );
+        let backfiller = builder.backfiller().unwrap();
+        assert!(backfiller.covers(Channel::Ohlc));
+        assert!(!backfiller.covers(Channel::Trade));
+    }
+
+    #[cfg(not(feature = "backfill"))]
+    #[tokio::test]
+    async fn test_backfill_without_fetch_is_rejected() {
+        let result = KrakenClient::builder(["BTC/USD"]).with_trade_backfill(true).connect().await;
+        assert!(matches!(result, Err(KrakenError::InvalidState { .. })));
+    }
 }
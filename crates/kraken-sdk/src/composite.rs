@@ -0,0 +1,506 @@
+//! Composite prices across quote currencies
+//!
+//! Marking a position needs a price in one currency, but the direct pair may
+//! be illiquid or missing. [`CompositePricer`] derives the price of an asset
+//! by chaining book mids, e.g. BTC in USD as BTC/EUR × EUR/USD, and blends
+//! the routes it finds:
+//!
+//! | Setting | Default | Effect |
+//! |---------|---------|--------|
+//! | [`with_bridges`](CompositePricer::with_bridges) | USD, EUR, USDT, USDC, BTC | assets a route may pass through |
+//! | [`with_route`](CompositePricer::with_route) | none | pin the routes for one asset pair |
+//! | [`with_half_life`](CompositePricer::with_half_life) | 30s | leg age at which a route counts half |
+//! | [`with_max_age`](CompositePricer::with_max_age) | none | drop routes with an older leg |
+//! | [`with_selection`](CompositePricer::with_selection) | [`PathSelection::Weighted`] | how routes are combined |
+//!
+//! Routes are the direct pair plus one hop through each bridge asset. Each
+//! leg is weighted `half_life / (half_life + age)` and a route's weight is
+//! the product over its legs, so a route through a quiet book counts less
+//! than one through live books. Inverted pairs are used as `1 / mid`.
+//!
+//! A book that empties a side or crosses drops out of routing until it
+//! shows a usable mid again, rather than pricing from its last mid.
+//!
+//! [`KrakenClient::composite_price`] prices from the client's books with the
+//! defaults.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::composite::CompositePricer;
+//! use rust_decimal_macros::dec;
+//!
+//! let mut pricer = CompositePricer::new();
+//! pricer.update_mid("BTC/EUR", dec!(50000));
+//! pricer.update_mid("EUR/USD", dec!(1.1));
+//!
+//! let btc_usd = pricer.price("BTC", "USD").unwrap();
+//! assert_eq!(btc_usd.price, dec!(55000));
+//! assert_eq!(btc_usd.paths[0].symbols, ["BTC/EUR", "EUR/USD"]);
+//! ```
+
+use crate::client::KrakenClient;
+use chrono::{DateTime, Utc};
+use kraken_types::Decimal;
+use kraken_ws::{Event, MarketEvent};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::time::{Duration, Instant};
+
+/// Bridge assets used unless configured otherwise
+pub const DEFAULT_BRIDGES: [&str; 5] = ["USD", "EUR", "USDT", "USDC", "BTC"];
+
+/// How the prices of several routes are combined
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PathSelection {
+    /// Average of every route, weighted by staleness
+    #[default]
+    Weighted,
+    /// The route with the freshest legs
+    Freshest,
+    /// The direct pair when it has a price, otherwise [`Weighted`](Self::Weighted)
+    PreferDirect,
+}
+
+/// One route from base to quote and the price it implies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PricePath {
+    /// Assets visited, e.g. `["BTC", "EUR", "USD"]`
+    pub assets: Vec<String>,
+    /// Pair used for each leg
+    pub symbols: Vec<String>,
+    /// Units of quote per unit of base along this route
+    pub price: Decimal,
+    /// Age of the oldest leg
+    pub age: Duration,
+    /// Staleness weight (1 = every leg fresh)
+    pub weight: Decimal,
+}
+
+impl PricePath {
+    /// Returns true for the direct pair
+    pub fn is_direct(&self) -> bool {
+        self.symbols.len() == 1
+    }
+}
+
+/// A derived price and the routes behind it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompositePrice {
+    /// Units of quote per unit of base
+    pub price: Decimal,
+    /// Routes that went into the price, highest weight first
+    pub paths: Vec<PricePath>,
+}
+
+impl CompositePrice {
+    /// Age of the oldest leg used
+    pub fn age(&self) -> Duration {
+        self.paths.iter().map(|path| path.age).max().unwrap_or_default()
+    }
+}
+
+/// A pair's mid and when it was last updated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Mid {
+    mid: Decimal,
+    at: Instant,
+}
+
+/// Derives prices by chaining book mids through bridge assets
+#[derive(Debug, Clone)]
+pub struct CompositePricer {
+    mids: BTreeMap<String, Mid>,
+    bridges: Vec<String>,
+    routes: HashMap<(String, String), Vec<Vec<String>>>,
+    half_life: Duration,
+    max_age: Option<Duration>,
+    selection: PathSelection,
+}
+
+impl Default for CompositePricer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CompositePricer {
+    /// Create a pricer with the default bridges and weighting
+    pub fn new() -> Self {
+        Self {
+            mids: BTreeMap::new(),
+            bridges: DEFAULT_BRIDGES.iter().map(|asset| asset.to_string()).collect(),
+            routes: HashMap::new(),
+            half_life: Duration::from_secs(30),
+            max_age: None,
+            selection: PathSelection::Weighted,
+        }
+    }
+
+    /// Set the assets a route may pass through
+    pub fn with_bridges(mut self, bridges: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.bridges = bridges.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Pin a route for pricing `base` in `quote`
+    ///
+    /// `via` lists the intermediate assets in order (empty for the direct
+    /// pair). Once a pair has pinned routes, only those are tried; call
+    /// again to add more.
+    pub fn with_route(
+        mut self,
+        base: impl Into<String>,
+        quote: impl Into<String>,
+        via: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        let (base, quote) = (base.into(), quote.into());
+        let mut assets = vec![base.clone()];
+        assets.extend(via.into_iter().map(Into::into));
+        assets.push(quote.clone());
+        self.routes.entry((base, quote)).or_default().push(assets);
+        self
+    }
+
+    /// Set the leg age at which a route's weight halves
+    pub fn with_half_life(mut self, half_life: Duration) -> Self {
+        self.half_life = half_life.max(Duration::from_millis(1));
+        self
+    }
+
+    /// Ignore routes with a leg older than `max_age`
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// Set how routes are combined
+    pub fn with_selection(mut self, selection: PathSelection) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Set a pair's mid, updated now
+    pub fn update_mid(&mut self, symbol: &str, mid: Decimal) {
+        self.update_mid_at(symbol, mid, Instant::now());
+    }
+
+    /// Set a pair's mid, last updated at `at`
+    ///
+    /// Ignored if the symbol isn't BASE/QUOTE or the mid isn't positive.
+    pub fn update_mid_at(&mut self, symbol: &str, mid: Decimal, at: Instant) {
+        if symbol.split_once('/').is_none() || mid <= Decimal::ZERO {
+            return;
+        }
+        self.mids.insert(symbol.to_string(), Mid { mid, at });
+    }
+
+    /// Remove a pair
+    pub fn remove(&mut self, symbol: &str) {
+        self.mids.remove(symbol);
+    }
+
+    /// Pairs with a mid
+    pub fn symbols(&self) -> impl Iterator<Item = &str> {
+        self.mids.keys().map(String::as_str)
+    }
+
+    /// Update from an orderbook event
+    ///
+    /// A book with an empty side or a crossed top removes its pair.
+    pub fn handle_event(&mut self, event: &Event) {
+        if let Event::Market(
+            MarketEvent::OrderbookSnapshot {
+                symbol,
+                snapshot,
+                received_at,
+                ..
+            }
+            | MarketEvent::OrderbookUpdate {
+                symbol,
+                snapshot,
+                received_at,
+                ..
+            },
+        ) = event
+        {
+            match usable_mid(snapshot.spread(), snapshot.mid_price()) {
+                Some(mid) => self.update_mid_at(symbol, mid, received_at.instant),
+                None => self.remove(symbol),
+            }
+        }
+    }
+
+    /// Update every synced book of the client
+    ///
+    /// A book's age is taken from the exchange timestamp of its last
+    /// update; books that haven't carried one yet count as fresh. Synced
+    /// books with an empty side or a crossed top remove their pair.
+    pub fn update_from_client(&mut self, client: &KrakenClient) {
+        let (now, wall) = (Instant::now(), Utc::now());
+        for symbol in client.symbols() {
+            let quote = client.with_orderbook(symbol, |book| {
+                let age = book
+                    .last_timestamp()
+                    .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                    .and_then(|ts| (wall - ts.with_timezone(&Utc)).to_std().ok())
+                    .unwrap_or_default();
+                book.is_synced().then(|| (usable_mid(book.spread(), book.mid_price()), age))
+            });
+            match quote.flatten() {
+                Some((Some(mid), age)) => {
+                    self.update_mid_at(symbol, mid, now.checked_sub(age).unwrap_or(now))
+                }
+                Some((None, _)) => self.remove(symbol),
+                None => {}
+            }
+        }
+    }
+
+    /// Price of `base` in `quote` as of now
+    pub fn price(&self, base: &str, quote: &str) -> Option<CompositePrice> {
+        self.price_at(base, quote, Instant::now())
+    }
+
+    /// Price of `base` in `quote`, aging legs relative to `now`
+    ///
+    /// None if no route has a price.
+    pub fn price_at(&self, base: &str, quote: &str, now: Instant) -> Option<CompositePrice> {
+        let mut paths = self.paths_at(base, quote, now);
+        if paths.is_empty() {
+            return None;
+        }
+        match self.selection {
+            PathSelection::PreferDirect if paths.iter().any(PricePath::is_direct) => {
+                paths.retain(PricePath::is_direct);
+            }
+            PathSelection::Freshest => paths.truncate(1),
+            PathSelection::Weighted | PathSelection::PreferDirect => {}
+        }
+        let total: Decimal = paths.iter().map(|path| path.weight).sum();
+        let price = if paths.len() == 1 || total.is_zero() {
+            paths[0].price
+        } else {
+            paths.iter().map(|path| path.price * path.weight).sum::<Decimal>() / total
+        };
+        Some(CompositePrice { price, paths })
+    }
+
+    /// Every route with a price, highest weight first
+    pub fn paths_at(&self, base: &str, quote: &str, now: Instant) -> Vec<PricePath> {
+        let routes = match self.routes.get(&(base.to_string(), quote.to_string())) {
+            Some(pinned) => pinned.clone(),
+            None => {
+                let mut routes = vec![vec![base.to_string(), quote.to_string()]];
+                for bridge in self.bridges.iter().filter(|b| *b != base && *b != quote) {
+                    routes.push(vec![base.to_string(), bridge.clone(), quote.to_string()]);
+                }
+                routes
+            }
+        };
+        let mut paths: Vec<PricePath> = routes.into_iter().filter_map(|assets| self.path(assets, now)).collect();
+        // Stable: on equal weight, shorter routes (listed first) stay ahead
+        paths.sort_by_key(|path| Reverse(path.weight));
+        paths
+    }
+
+    /// Price a route, if every leg has a mid young enough
+    fn path(&self, assets: Vec<String>, now: Instant) -> Option<PricePath> {
+        let half_life = Decimal::from(self.half_life.as_millis() as u64);
+        let mut price = Decimal::ONE;
+        let mut weight = Decimal::ONE;
+        let mut age = Duration::ZERO;
+        let mut symbols = Vec::with_capacity(assets.len().saturating_sub(1));
+        for leg in assets.windows(2) {
+            let (symbol, rate, at) = self.leg(&leg[0], &leg[1])?;
+            let leg_age = now.saturating_duration_since(at);
+            if self.max_age.is_some_and(|max| leg_age > max) {
+                return None;
+            }
+            price *= rate;
+            weight *= half_life / (half_life + Decimal::from(leg_age.as_millis() as u64));
+            age = age.max(leg_age);
+            symbols.push(symbol);
+        }
+        Some(PricePath {
+            assets,
+            symbols,
+            price,
+            age,
+            weight,
+        })
+    }
+
+    /// Pair, rate and update time for converting `from` into `to`
+    fn leg(&self, from: &str, to: &str) -> Option<(String, Decimal, Instant)> {
+        let direct = format!("{}/{}", from, to);
+        if let Some(mid) = self.mids.get(&direct) {
+            return Some((direct, mid.mid, mid.at));
+        }
+        let inverse = format!("{}/{}", to, from);
+        let mid = self.mids.get(&inverse)?;
+        Some((inverse, Decimal::ONE / mid.mid, mid.at))
+    }
+}
+
+/// A book's mid, unless a side is empty or the top is crossed
+fn usable_mid(spread: Option<Decimal>, mid: Option<Decimal>) -> Option<Decimal> {
+    spread.filter(|spread| *spread >= Decimal::ZERO).and(mid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_book::OrderbookSnapshot;
+    use kraken_types::Level;
+    use kraken_ws::ReceivedAt;
+    use rust_decimal_macros::dec;
+
+    fn book_event(symbol: &str, bid: Option<Decimal>, ask: Option<Decimal>) -> Event {
+        let level = |price| Level::new(price, dec!(1));
+        Event::Market(MarketEvent::OrderbookUpdate {
+            symbol: symbol.to_string(),
+            seq: 1,
+            snapshot: OrderbookSnapshot {
+                symbol: symbol.to_string(),
+                bids: bid.map(level).into_iter().collect(),
+                asks: ask.map(level).into_iter().collect(),
+                ..Default::default()
+            },
+            received_at: ReceivedAt::now(),
+            exchange_ts_us: None,
+        })
+    }
+
+    /// BTC in USD through EUR and USDT, every leg fresh
+    fn two_routes(now: Instant) -> CompositePricer {
+        let mut pricer = CompositePricer::new();
+        pricer.update_mid_at("BTC/EUR", dec!(50000), now);
+        pricer.update_mid_at("EUR/USD", dec!(1.1), now);
+        pricer.update_mid_at("BTC/USDT", dec!(55200), now);
+        pricer.update_mid_at("USD/USDT", dec!(1), now);
+        pricer
+    }
+
+    #[test]
+    fn test_venue_dropping_out() {
+        let now = Instant::now();
+        let mut pricer = two_routes(now);
+        assert_eq!(pricer.price_at("BTC", "USD", now).unwrap().price, dec!(55100));
+
+        // One leg gone: the other route alone sets the price
+        pricer.remove("EUR/USD");
+        let price = pricer.price_at("BTC", "USD", now).unwrap();
+        assert_eq!(price.price, dec!(55200));
+        assert_eq!(price.paths.len(), 1);
+        assert_eq!(price.paths[0].symbols, ["BTC/USDT", "USD/USDT"]);
+
+        // A book that empties a side drops its pair rather than keeping the last mid
+        pricer.handle_event(&book_event("USD/USDT", Some(dec!(0.9999)), None));
+        assert!(pricer.symbols().all(|symbol| symbol != "USD/USDT"));
+        assert!(pricer.price_at("BTC", "USD", now).is_none());
+
+        // and comes back with its next usable book
+        pricer.handle_event(&book_event("USD/USDT", Some(dec!(0.9999)), Some(dec!(1.0001))));
+        assert_eq!(pricer.price("BTC", "USD").unwrap().price, dec!(55200));
+    }
+
+    #[test]
+    fn test_crossed_venues() {
+        let now = Instant::now();
+        let mut pricer = two_routes(now);
+
+        // Routes that disagree blend to their weighted average
+        pricer.update_mid_at("BTC/USDT", dec!(56200), now);
+        assert_eq!(pricer.price_at("BTC", "USD", now).unwrap().price, dec!(55600));
+
+        // A crossed book has no trustworthy mid; its route drops out
+        pricer.handle_event(&book_event("BTC/USDT", Some(dec!(57000)), Some(dec!(56000))));
+        let price = pricer.price("BTC", "USD").unwrap();
+        assert_eq!(price.price, dec!(55000));
+        assert_eq!(price.paths[0].assets, ["BTC", "EUR", "USD"]);
+
+        // A locked book (bid == ask) still prices
+        pricer.handle_event(&book_event("BTC/USDT", Some(dec!(55000)), Some(dec!(55000))));
+        assert_eq!(pricer.price("BTC", "USD").unwrap().paths.len(), 2);
+
+        // Non-positive mids and malformed symbols are ignored
+        pricer.update_mid_at("BTC/EUR", dec!(0), now);
+        pricer.update_mid_at("BTCEUR", dec!(50000), now);
+        assert_eq!(pricer.symbols().count(), 4);
+        assert_eq!(pricer.price_at("BTC", "EUR", now).unwrap().price, dec!(50000));
+    }
+
+    #[test]
+    fn test_staleness() {
+        let now = Instant::now();
+        let pricer = two_routes(now).with_half_life(Duration::from_secs(10));
+
+        // Each leg counts half at one half-life and a quarter at three;
+        // both legs of the route age together
+        let weight_at = |age: u64| {
+            let later = now + Duration::from_secs(age);
+            pricer.paths_at("BTC", "USD", later)[0].weight
+        };
+        assert_eq!(weight_at(0), dec!(1));
+        assert_eq!(weight_at(10), dec!(0.25));
+        assert_eq!(weight_at(30), dec!(0.0625));
+
+        // A refreshed leg outweighs a stale one
+        let mut pricer = pricer;
+        let later = now + Duration::from_secs(10);
+        pricer.update_mid_at("BTC/EUR", dec!(50000), later);
+        pricer.update_mid_at("EUR/USD", dec!(1.1), later);
+        let price = pricer.price_at("BTC", "USD", later).unwrap();
+        assert_eq!(price.paths[0].weight, dec!(1));
+        assert_eq!(price.paths[1].weight, dec!(0.25));
+        assert_eq!(price.price, dec!(55040));
+        assert_eq!(price.age(), Duration::from_secs(10));
+
+        // max_age drops routes with any leg past it, then everything
+        let bounded = pricer.with_max_age(Duration::from_secs(10));
+        assert_eq!(bounded.price_at("BTC", "USD", later).unwrap().paths.len(), 2);
+        let much_later = later + Duration::from_secs(5);
+        let price = bounded.price_at("BTC", "USD", much_later).unwrap();
+        assert_eq!(price.paths.len(), 1);
+        assert_eq!(price.price, dec!(55000));
+        assert!(bounded.price_at("BTC", "USD", much_later + Duration::from_secs(10)).is_none());
+    }
+
+    #[test]
+    fn test_routes_are_weighted_by_staleness() {
+        let now = Instant::now();
+        let mut pricer = CompositePricer::new();
+        pricer.update_mid_at("BTC/EUR", dec!(50000), now);
+        pricer.update_mid_at("EUR/USD", dec!(1.1), now);
+        pricer.update_mid_at("BTC/USDT", dec!(55100), now - Duration::from_secs(30));
+        pricer.update_mid_at("USD/USDT", dec!(1), now);
+
+        // EUR route at full weight, USDT route at half
+        let price = pricer.price_at("BTC", "USD", now).unwrap();
+        assert_eq!(price.paths.len(), 2);
+        assert_eq!(price.paths[0].assets, ["BTC", "EUR", "USD"]);
+        assert_eq!(price.paths[1].weight, dec!(0.5));
+        assert_eq!(price.price.round_dp(2), dec!(55033.33));
+        assert_eq!(price.age(), Duration::from_secs(30));
+
+        // Inverted legs work in the other direction too
+        let usd = pricer.price_at("USD", "BTC", now).unwrap();
+        assert_eq!(usd.paths[0].price.round_dp(12), (dec!(1) / dec!(55000)).round_dp(12));
+
+        let freshest = pricer.clone().with_selection(PathSelection::Freshest);
+        assert_eq!(freshest.price_at("BTC", "USD", now).unwrap().price, dec!(55000));
+        let bounded = pricer.clone().with_max_age(Duration::from_secs(10));
+        assert_eq!(bounded.price_at("BTC", "USD", now).unwrap().paths.len(), 1);
+
+        // A direct pair wins under PreferDirect; pinned routes replace the defaults
+        pricer.update_mid_at("BTC/USD", dec!(54900), now - Duration::from_secs(60));
+        let direct = pricer.clone().with_selection(PathSelection::PreferDirect);
+        assert_eq!(direct.price_at("BTC", "USD", now).unwrap().price, dec!(54900));
+        let pinned = pricer.with_route("BTC", "USD", ["USDT"]);
+        let price = pinned.price_at("BTC", "USD", now).unwrap();
+        assert_eq!(price.price, dec!(55100));
+        assert_eq!(price.paths[0].symbols, ["BTC/USDT", "USD/USDT"]);
+        assert!(pinned.price_at("BTC", "JPY", now).is_none());
+    }
+}
@@ -0,0 +1,708 @@
+//! Typed configuration files for the whole SDK
+//!
+//! [`KrakenSdkConfig`] describes a client in one file: endpoint, symbols and
+//! depths, channels, reconnect and circuit breaker settings, rate limiting
+//! and where credentials come from. Files are TOML, or YAML with the
+//! `config-yaml` feature, picked by extension.
+//!
+//! Environment variables override file values. `KRAKEN_SDK_<FIELD>` sets a
+//! top-level field and `__` steps into a section, so
+//! `KRAKEN_SDK_RECONNECT__MAX_ATTEMPTS=5` sets `reconnect.max_attempts`.
+//! Lists take comma-separated values (`KRAKEN_SDK_SYMBOLS=BTC/USD,ETH/USD`).
+//!
+//! Validation errors name the offending field, e.g.
+//! ``invalid value for `symbol_depths.BTC/USD`: 30 is not a book depth``.
+//!
+//! ```toml
+//! endpoint = "public"          # public | private | public_beta | private_beta | level3
+//! symbols = ["BTC/USD", "ETH/USD"]
+//! depth = 25
+//! connect_timeout_ms = 10000
+//!
+//! [symbol_depths]
+//! "BTC/USD" = 1000
+//!
+//! [channels]
+//! book = true
+//! ticker = true
+//! ohlc = [1, 60]
+//!
+//! [reconnect]
+//! max_attempts = 10
+//!
+//! [circuit_breaker]
+//! failure_threshold = 3
+//!
+//! [rate_limit]
+//! enabled = true
+//! tier = "starter"             # starter | pro | permissive
+//!
+//! [credentials]
+//! api_key_env = "KRAKEN_API_KEY"
+//! private_key_env = "KRAKEN_PRIVATE_KEY"
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::config::KrakenSdkConfig;
+//! use kraken_sdk::Depth;
+//!
+//! let mut config = KrakenSdkConfig::from_toml(r#"
+//!     symbols = ["BTC/USD"]
+//!     [channels]
+//!     trade = true
+//! "#).unwrap();
+//! config.apply_env([("KRAKEN_SDK_DEPTH".to_string(), "100".to_string())]).unwrap();
+//!
+//! let builder = config.to_builder().unwrap();
+//! assert_eq!(builder.depth, Depth::D100);
+//! assert!(builder.subscribe_trade);
+//! ```
+
+use crate::builder::{ConfigError, KrakenClientBuilder, OhlcInterval};
+use kraken_types::Depth;
+use kraken_ws::{CircuitBreakerConfig, Endpoint, KrakenRateLimiter, ReconnectConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Prefix of environment variables that override file values
+pub const ENV_PREFIX: &str = "KRAKEN_SDK_";
+
+/// Error loading or validating a configuration
+#[derive(Debug, thiserror::Error)]
+pub enum SdkConfigError {
+    /// The file couldn't be read
+    #[error("failed to read {path}: {source}")]
+    Io {
+        /// File path
+        path: String,
+        /// Underlying error
+        #[source]
+        source: std::io::Error,
+    },
+
+    /// The file isn't valid for its format
+    #[error("failed to parse {path}: {message}")]
+    Parse {
+        /// File path
+        path: String,
+        /// Parser message, including the location
+        message: String,
+    },
+
+    /// The file extension isn't a supported format
+    #[error("unsupported config format: {path}")]
+    UnsupportedFormat {
+        /// File path
+        path: String,
+    },
+
+    /// An environment override couldn't be applied
+    #[error("invalid environment override {var}: {message}")]
+    Env {
+        /// Variable name
+        var: String,
+        /// What was wrong with it
+        message: String,
+    },
+
+    /// A field holds an invalid value
+    #[error("invalid value for `{field}`: {message}")]
+    Invalid {
+        /// Dotted path of the field
+        field: String,
+        /// What was wrong with it
+        message: String,
+    },
+
+    /// A credentials variable named by the config isn't set
+    #[error("credentials variable {var} (from `{field}`) is not set")]
+    MissingCredential {
+        /// Config field naming the variable
+        field: &'static str,
+        /// Variable name
+        var: String,
+    },
+}
+
+impl SdkConfigError {
+    fn invalid(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::Invalid {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+/// Configuration for a whole client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KrakenSdkConfig {
+    /// WebSocket endpoint name
+    pub endpoint: String,
+    /// Symbols to subscribe to
+    pub symbols: Vec<String>,
+    /// Orderbook depth
+    pub depth: u32,
+    /// Per-symbol orderbook depths
+    pub symbol_depths: BTreeMap<String, u32>,
+    /// Connection timeout in milliseconds
+    pub connect_timeout_ms: u64,
+    /// Channel subscriptions
+    pub channels: ChannelsSection,
+    /// Reconnection settings
+    pub reconnect: ReconnectSection,
+    /// Circuit breaker settings
+    pub circuit_breaker: CircuitBreakerSection,
+    /// Subscribe request pacing
+    pub rate_limit: RateLimitSection,
+    /// Where API credentials come from
+    pub credentials: CredentialsSection,
+}
+
+impl Default for KrakenSdkConfig {
+    fn default() -> Self {
+        let builder = KrakenClientBuilder::default();
+        Self {
+            endpoint: "public".to_string(),
+            symbols: Vec::new(),
+            depth: builder.depth.as_u32(),
+            symbol_depths: BTreeMap::new(),
+            connect_timeout_ms: builder.connect_timeout.as_millis() as u64,
+            channels: ChannelsSection::default(),
+            reconnect: ReconnectSection::default(),
+            circuit_breaker: CircuitBreakerSection::default(),
+            rate_limit: RateLimitSection::default(),
+            credentials: CredentialsSection::default(),
+        }
+    }
+}
+
+/// Channel subscriptions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChannelsSection {
+    /// Subscribe to the orderbook channel
+    pub book: bool,
+    /// Subscribe to the ticker channel
+    pub ticker: bool,
+    /// Subscribe to the trade channel
+    pub trade: bool,
+    /// Subscribe to level3 for every symbol (requires the level3 endpoint)
+    pub level3: bool,
+    /// OHLC intervals in minutes
+    pub ohlc: Vec<u32>,
+}
+
+impl Default for ChannelsSection {
+    fn default() -> Self {
+        Self {
+            book: true,
+            ticker: false,
+            trade: false,
+            level3: false,
+            ohlc: Vec::new(),
+        }
+    }
+}
+
+/// Reconnection settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReconnectSection {
+    /// Reconnect automatically
+    pub enabled: bool,
+    /// Delay before the first attempt, in milliseconds
+    pub initial_delay_ms: u64,
+    /// Maximum delay between attempts, in milliseconds
+    pub max_delay_ms: u64,
+    /// Backoff multiplier
+    pub multiplier: f64,
+    /// Random jitter factor (0.0 to 1.0)
+    pub jitter: f64,
+    /// Maximum number of attempts (unset = unlimited)
+    pub max_attempts: Option<u32>,
+    /// Minimum delay after a rate-limit disconnect, in milliseconds
+    pub rate_limit_delay_ms: u64,
+    /// Minimum delay after a maintenance disconnect, in milliseconds
+    pub maintenance_delay_ms: u64,
+    /// Give up after this much continuous downtime, in milliseconds (unset = never)
+    pub max_downtime_ms: Option<u64>,
+}
+
+impl Default for ReconnectSection {
+    fn default() -> Self {
+        let config = ReconnectConfig::default();
+        Self {
+            enabled: true,
+            initial_delay_ms: config.initial_delay.as_millis() as u64,
+            max_delay_ms: config.max_delay.as_millis() as u64,
+            multiplier: config.multiplier,
+            jitter: config.jitter,
+            max_attempts: config.max_attempts,
+            rate_limit_delay_ms: config.rate_limit_delay.as_millis() as u64,
+            maintenance_delay_ms: config.maintenance_delay.as_millis() as u64,
+            max_downtime_ms: None,
+        }
+    }
+}
+
+impl ReconnectSection {
+    /// The equivalent reconnect configuration
+    pub fn to_reconnect_config(&self) -> ReconnectConfig {
+        ReconnectConfig {
+            initial_delay: Duration::from_millis(self.initial_delay_ms),
+            max_delay: Duration::from_millis(self.max_delay_ms),
+            multiplier: self.multiplier,
+            jitter: self.jitter,
+            max_attempts: self.max_attempts,
+            rate_limit_delay: Duration::from_millis(self.rate_limit_delay_ms),
+            maintenance_delay: Duration::from_millis(self.maintenance_delay_ms),
+            max_downtime: self.max_downtime_ms.map(Duration::from_millis),
+        }
+    }
+}
+
+/// Circuit breaker settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CircuitBreakerSection {
+    /// Guard reconnects with a circuit breaker
+    pub enabled: bool,
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    /// Consecutive successes that close it again
+    pub success_threshold: u32,
+    /// Seconds to stay open before trying again
+    pub timeout_secs: u64,
+}
+
+impl Default for CircuitBreakerSection {
+    fn default() -> Self {
+        let config = CircuitBreakerConfig::default();
+        Self {
+            enabled: true,
+            failure_threshold: config.failure_threshold,
+            success_threshold: config.success_threshold,
+            timeout_secs: config.timeout.as_secs(),
+        }
+    }
+}
+
+impl CircuitBreakerSection {
+    /// The equivalent circuit breaker configuration
+    pub fn to_circuit_breaker_config(&self) -> CircuitBreakerConfig {
+        CircuitBreakerConfig {
+            failure_threshold: self.failure_threshold,
+            success_threshold: self.success_threshold,
+            timeout: Duration::from_secs(self.timeout_secs),
+        }
+    }
+}
+
+/// Subscribe request pacing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimitSection {
+    /// Pace subscribe requests
+    pub enabled: bool,
+    /// Account tier whose limits apply: starter, pro or permissive
+    pub tier: String,
+}
+
+impl Default for RateLimitSection {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            tier: "starter".to_string(),
+        }
+    }
+}
+
+/// Where API credentials come from
+///
+/// Keys are never stored in the file itself, only the names of the
+/// environment variables holding them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CredentialsSection {
+    /// Variable holding the API key
+    pub api_key_env: String,
+    /// Variable holding the base64 private key
+    pub private_key_env: String,
+}
+
+impl Default for CredentialsSection {
+    fn default() -> Self {
+        Self {
+            api_key_env: "KRAKEN_API_KEY".to_string(),
+            private_key_env: "KRAKEN_PRIVATE_KEY".to_string(),
+        }
+    }
+}
+
+impl CredentialsSection {
+    /// Read the `(api_key, private_key)` pair from the environment
+    pub fn load(&self) -> Result<(String, String), SdkConfigError> {
+        let read = |field: &'static str, var: &str| {
+            std::env::var(var).map_err(|_| SdkConfigError::MissingCredential {
+                field,
+                var: var.to_string(),
+            })
+        };
+        Ok((
+            read("credentials.api_key_env", &self.api_key_env)?,
+            read("credentials.private_key_env", &self.private_key_env)?,
+        ))
+    }
+
+    /// Token manager for the configured credentials
+    #[cfg(feature = "auth")]
+    pub fn token_manager(&self) -> Result<crate::auth::TokenManager, SdkConfigError> {
+        let (api_key, private_key) = self.load()?;
+        Ok(crate::auth::TokenManager::new(api_key, private_key))
+    }
+}
+
+impl KrakenSdkConfig {
+    /// Read a file, apply `KRAKEN_SDK_*` overrides and validate
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SdkConfigError> {
+        let mut config = Self::from_file(path)?;
+        config.apply_env(std::env::vars())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Read a file as is, picking the format from its extension
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SdkConfigError> {
+        let path = path.as_ref();
+        let display = path.display().to_string();
+        let contents = std::fs::read_to_string(path).map_err(|source| SdkConfigError::Io {
+            path: display.clone(),
+            source,
+        })?;
+        let parsed = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&contents).map_err(|e| e.to_string()),
+            #[cfg(feature = "config-yaml")]
+            Some("yaml" | "yml") => serde_yaml::from_str(&contents).map_err(|e| e.to_string()),
+            _ => return Err(SdkConfigError::UnsupportedFormat { path: display }),
+        };
+        parsed.map_err(|message| SdkConfigError::Parse { path: display, message })
+    }
+
+    /// Parse TOML
+    pub fn from_toml(contents: &str) -> Result<Self, SdkConfigError> {
+        toml::from_str(contents).map_err(|e| SdkConfigError::Parse {
+            path: "<toml>".to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Parse YAML
+    #[cfg(feature = "config-yaml")]
+    pub fn from_yaml(contents: &str) -> Result<Self, SdkConfigError> {
+        serde_yaml::from_str(contents).map_err(|e| SdkConfigError::Parse {
+            path: "<yaml>".to_string(),
+            message: e.to_string(),
+        })
+    }
+
+    /// Apply `KRAKEN_SDK_*` overrides from `vars`, ignoring other variables
+    ///
+    /// Pass `std::env::vars()` for the process environment.
+    pub fn apply_env<K, V>(&mut self, vars: impl IntoIterator<Item = (K, V)>) -> Result<(), SdkConfigError>
+    where
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        let mut overrides: Vec<(String, String)> = vars
+            .into_iter()
+            .filter(|(key, _)| key.as_ref().starts_with(ENV_PREFIX))
+            .map(|(key, value)| (key.as_ref().to_string(), value.as_ref().to_string()))
+            .collect();
+        // Environment iteration order is unspecified
+        overrides.sort();
+
+        for (var, raw) in overrides {
+            let env_error = |message: String| SdkConfigError::Env {
+                var: var.clone(),
+                message,
+            };
+            let mut table = toml::Table::try_from(&*self).map_err(|e| env_error(e.to_string()))?;
+            let path: Vec<String> = var[ENV_PREFIX.len()..].to_lowercase().split("__").map(String::from).collect();
+            let (leaf, sections) = path.split_last().expect("split yields at least one part");
+
+            let mut target = &mut table;
+            for section in sections {
+                target = match target.entry(section.clone()).or_insert_with(|| toml::Table::new().into()) {
+                    toml::Value::Table(inner) => inner,
+                    _ => return Err(env_error(format!("`{section}` is not a section"))),
+                };
+            }
+            let value = env_value(&raw, target.get(leaf));
+            target.insert(leaf.clone(), value);
+
+            *self = table.try_into().map_err(|e: toml::de::Error| env_error(e.message().to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Check every field, naming the first invalid one
+    pub fn validate(&self) -> Result<(), SdkConfigError> {
+        self.to_builder().map(drop)
+    }
+
+    /// Validate and convert to a client builder
+    pub fn to_builder(&self) -> Result<KrakenClientBuilder, SdkConfigError> {
+        self.check_ranges()?;
+
+        let mut builder = KrakenClientBuilder::new(&self.symbols)
+            .with_endpoint(parse_endpoint(&self.endpoint)?)
+            .with_depth(parse_depth("depth", self.depth)?)
+            .with_timeout(Duration::from_millis(self.connect_timeout_ms))
+            .with_book(self.channels.book)
+            .with_ticker(self.channels.ticker)
+            .with_trade(self.channels.trade)
+            .with_reconnect(self.reconnect.enabled)
+            .with_reconnect_config(self.reconnect.to_reconnect_config());
+
+        for (symbol, depth) in &self.symbol_depths {
+            let depth = parse_depth(&format!("symbol_depths.{symbol}"), *depth)?;
+            builder = builder.with_symbol_depth(symbol, depth);
+        }
+
+        for (i, minutes) in self.channels.ohlc.iter().enumerate() {
+            let interval = OhlcInterval::from_minutes(*minutes).ok_or_else(|| {
+                SdkConfigError::invalid(
+                    format!("channels.ohlc[{i}]"),
+                    format!("{minutes} is not an OHLC interval (1, 5, 15, 30, 60, 240, 1440, 10080, 21600)"),
+                )
+            })?;
+            builder = builder.with_ohlc(interval);
+        }
+
+        if self.channels.level3 {
+            builder = builder.with_l3_enabled();
+        }
+
+        builder = if self.circuit_breaker.enabled {
+            builder.with_circuit_breaker(self.circuit_breaker.to_circuit_breaker_config())
+        } else {
+            builder.without_circuit_breaker()
+        };
+
+        if self.rate_limit.enabled {
+            let limiter = match self.rate_limit.tier.as_str() {
+                "starter" => KrakenRateLimiter::kraken_defaults(),
+                "pro" => KrakenRateLimiter::high_tier(),
+                "permissive" => KrakenRateLimiter::permissive(),
+                other => {
+                    return Err(SdkConfigError::invalid(
+                        "rate_limit.tier",
+                        format!("unknown tier {other:?} (expected starter, pro or permissive)"),
+                    ))
+                }
+            };
+            builder = builder.with_rate_limiter(Arc::new(limiter));
+        }
+
+        builder.validate().map_err(|e| SdkConfigError::invalid(builder_field(&e), e.to_string()))?;
+        Ok(builder)
+    }
+
+    /// Checks the builder doesn't make, with field paths
+    fn check_ranges(&self) -> Result<(), SdkConfigError> {
+        for (i, symbol) in self.symbols.iter().enumerate() {
+            if !KrakenClientBuilder::is_valid_symbol(symbol) {
+                return Err(SdkConfigError::invalid(
+                    format!("symbols[{i}]"),
+                    format!("{symbol:?} is not BASE/QUOTE (e.g. BTC/USD)"),
+                ));
+            }
+        }
+        if self.connect_timeout_ms < 1000 {
+            return Err(SdkConfigError::invalid("connect_timeout_ms", "must be at least 1000"));
+        }
+        let reconnect = &self.reconnect;
+        if reconnect.max_delay_ms < reconnect.initial_delay_ms {
+            return Err(SdkConfigError::invalid(
+                "reconnect.max_delay_ms",
+                format!("must be at least initial_delay_ms ({})", reconnect.initial_delay_ms),
+            ));
+        }
+        if !(1.0..).contains(&reconnect.multiplier) {
+            return Err(SdkConfigError::invalid("reconnect.multiplier", "must be at least 1.0"));
+        }
+        if !(0.0..=1.0).contains(&reconnect.jitter) {
+            return Err(SdkConfigError::invalid("reconnect.jitter", "must be between 0.0 and 1.0"));
+        }
+        let breaker = &self.circuit_breaker;
+        if breaker.enabled && breaker.failure_threshold == 0 {
+            return Err(SdkConfigError::invalid("circuit_breaker.failure_threshold", "must be at least 1"));
+        }
+        if breaker.enabled && breaker.success_threshold == 0 {
+            return Err(SdkConfigError::invalid("circuit_breaker.success_threshold", "must be at least 1"));
+        }
+        Ok(())
+    }
+}
+
+/// Parse an override, splitting comma lists and keeping string fields as strings
+fn env_value(raw: &str, current: Option<&toml::Value>) -> toml::Value {
+    match current {
+        Some(toml::Value::String(_)) => toml::Value::String(raw.to_string()),
+        Some(toml::Value::Array(_)) if !raw.trim_start().starts_with('[') => toml::Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(scalar_value)
+                .collect(),
+        ),
+        _ => scalar_value(raw),
+    }
+}
+
+/// A TOML literal, or the raw text as a string
+fn scalar_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {raw}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+fn parse_endpoint(name: &str) -> Result<Endpoint, SdkConfigError> {
+    match name {
+        "public" => Ok(Endpoint::Public),
+        "private" => Ok(Endpoint::Private),
+        "public_beta" => Ok(Endpoint::PublicBeta),
+        "private_beta" => Ok(Endpoint::PrivateBeta),
+        "level3" => Ok(Endpoint::Level3),
+        other => Err(SdkConfigError::invalid(
+            "endpoint",
+            format!("unknown endpoint {other:?} (expected public, private, public_beta, private_beta or level3)"),
+        )),
+    }
+}
+
+fn parse_depth(field: &str, depth: u32) -> Result<Depth, SdkConfigError> {
+    match depth {
+        10 => Ok(Depth::D10),
+        25 => Ok(Depth::D25),
+        100 => Ok(Depth::D100),
+        500 => Ok(Depth::D500),
+        1000 => Ok(Depth::D1000),
+        other => Err(SdkConfigError::invalid(
+            field,
+            format!("{other} is not a book depth (10, 25, 100, 500, 1000)"),
+        )),
+    }
+}
+
+/// Config field behind a builder validation error
+fn builder_field(error: &ConfigError) -> &'static str {
+    match error {
+        ConfigError::NoSymbols | ConfigError::InvalidSymbol { .. } => "symbols",
+        ConfigError::L3RequiresLevel3Endpoint => "channels.level3",
+        ConfigError::InvalidOhlcInterval { .. } => "channels.ohlc",
+        ConfigError::InvalidDepth { .. } => "depth",
+        ConfigError::TimeoutTooShort => "connect_timeout_ms",
+        ConfigError::UnsupportedSymbolChannel { .. } => "channels",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+        endpoint = "public_beta"
+        symbols = ["BTC/USD", "ETH/USD"]
+        depth = 25
+
+        [symbol_depths]
+        "BTC/USD" = 1000
+
+        [channels]
+        ticker = true
+        ohlc = [1, 60]
+
+        [reconnect]
+        max_attempts = 10
+
+        [circuit_breaker]
+        enabled = false
+
+        [rate_limit]
+        enabled = true
+        tier = "pro"
+    "#;
+
+    #[test]
+    fn test_file_maps_to_builder() {
+        let builder = KrakenSdkConfig::from_toml(FILE).unwrap().to_builder().unwrap();
+
+        assert_eq!(builder.endpoint, Endpoint::PublicBeta);
+        assert_eq!(builder.symbols, vec!["BTC/USD", "ETH/USD"]);
+        assert_eq!(builder.depth, Depth::D25);
+        assert_eq!(builder.symbol_depths.get("BTC/USD"), Some(&Depth::D1000));
+        assert!(builder.subscribe_book && builder.subscribe_ticker && !builder.subscribe_trade);
+        assert!(builder.ohlc_intervals.contains(&OhlcInterval::H1));
+        assert_eq!(builder.reconnect_config.max_attempts, Some(10));
+        assert!(builder.circuit_breaker.is_none());
+        assert!(builder.rate_limiter.is_some());
+
+        #[cfg(feature = "config-yaml")]
+        {
+            let yaml = "symbols: [BTC/USD]\ndepth: 100\nchannels:\n  trade: true\n";
+            let builder = KrakenSdkConfig::from_yaml(yaml).unwrap().to_builder().unwrap();
+            assert_eq!(builder.depth, Depth::D100);
+            assert!(builder.subscribe_trade);
+        }
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let mut config = KrakenSdkConfig::from_toml(FILE).unwrap();
+        config
+            .apply_env([
+                ("KRAKEN_SDK_SYMBOLS", "SOL/USD, XRP/USD"),
+                ("KRAKEN_SDK_RECONNECT__MAX_ATTEMPTS", "3"),
+                ("KRAKEN_SDK_CIRCUIT_BREAKER__ENABLED", "true"),
+                ("KRAKEN_SDK_CREDENTIALS__API_KEY_ENV", "PROD_KEY"),
+                ("HOME", "/root"),
+            ])
+            .unwrap();
+
+        assert_eq!(config.symbols, vec!["SOL/USD", "XRP/USD"]);
+        assert_eq!(config.reconnect.max_attempts, Some(3));
+        assert!(config.circuit_breaker.enabled);
+        assert_eq!(config.credentials.api_key_env, "PROD_KEY");
+
+        let err = config.apply_env([("KRAKEN_SDK_RECONNECT__RETRIES", "3")]).unwrap_err();
+        assert!(matches!(err, SdkConfigError::Env { ref var, .. } if var == "KRAKEN_SDK_RECONNECT__RETRIES"));
+        assert!(err.to_string().contains("retries"));
+    }
+
+    #[test]
+    fn test_validation_names_the_field() {
+        let field_of = |toml: &str| match KrakenSdkConfig::from_toml(toml).unwrap().validate() {
+            Err(SdkConfigError::Invalid { field, .. }) => field,
+            other => panic!("expected a field error, got {other:?}"),
+        };
+
+        assert_eq!(field_of("symbols = [\"BTC/USD\"]\ndepth = 30"), "depth");
+        assert_eq!(field_of("symbols = [\"BTC/USD\", \"ETHUSD\"]"), "symbols[1]");
+        assert_eq!(field_of("symbols = [\"BTC/USD\"]\n[symbol_depths]\n\"BTC/USD\" = 7"), "symbol_depths.BTC/USD");
+        assert_eq!(field_of("symbols = [\"BTC/USD\"]\n[reconnect]\njitter = 2.0"), "reconnect.jitter");
+        assert_eq!(field_of("symbols = [\"BTC/USD\"]\n[channels]\nlevel3 = true"), "channels.level3");
+        assert_eq!(field_of("symbols = []"), "symbols");
+
+        // Unknown keys are parse errors that quote the key
+        let err = KrakenSdkConfig::from_toml("symbols = []\n[channels]\nbooks = true").unwrap_err();
+        assert!(err.to_string().contains("books"));
+    }
+}
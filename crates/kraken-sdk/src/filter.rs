@@ -128,9 +128,29 @@ impl EventFilter {
             | MarketEvent::OrderbookUpdate { symbol, .. } => {
                 self.matches_symbol(symbol) && self.matches_channel(FilterChannel::Orderbook)
             }
-            MarketEvent::ChecksumMismatch { symbol, .. } => {
+            MarketEvent::ChecksumMismatch { symbol, .. }
+            | MarketEvent::OutOfOrderUpdate { symbol, .. }
+            | MarketEvent::UpdateBeforeSnapshot { symbol }
+            | MarketEvent::CrossedBook { symbol, .. }
+            | MarketEvent::DepthOverflow { symbol, .. }
+            | MarketEvent::DepthMismatch { symbol, .. }
+            | MarketEvent::BookAuditFailed { symbol, .. }
+            | MarketEvent::BookSample { symbol, .. } => {
                 self.matches_symbol(symbol) && self.matches_channel(FilterChannel::Orderbook)
             }
+            MarketEvent::Ticker { symbol, .. } => {
+                self.matches_symbol(symbol) && self.matches_channel(FilterChannel::Ticker)
+            }
+            MarketEvent::Trade { symbol, trade, .. } => {
+                let large_enough = match self.min_trade_size {
+                    Some(min) => trade.qty >= min,
+                    None => true,
+                };
+                self.matches_symbol(symbol) && self.matches_channel(FilterChannel::Trade) && large_enough
+            }
+            MarketEvent::Ohlc { symbol, .. } => {
+                self.matches_symbol(symbol) && self.matches_channel(FilterChannel::OHLC)
+            }
             MarketEvent::Status { .. } => self.matches_channel(FilterChannel::Status),
             MarketEvent::Heartbeat => self.matches_channel(FilterChannel::Heartbeat),
         }
@@ -371,11 +391,15 @@ mod tests {
     use super::*;
     use kraken_book::OrderbookSnapshot;
     use kraken_ws::ConnectionEvent;
+    use kraken_ws::ReceivedAt;
 
     fn book_event(symbol: &str) -> Event {
         Event::Market(MarketEvent::OrderbookUpdate {
             symbol: symbol.to_string(),
+            seq: 1,
             snapshot: OrderbookSnapshot::default(),
+            received_at: ReceivedAt::now(),
+            exchange_ts_us: None,
         })
     }
 
@@ -420,4 +444,33 @@ mod tests {
         assert!(all.matches(&book_event("BTC/USD")));
         assert!(!all.matches(&book_event("ETH/USD")));
     }
+
+    #[test]
+    fn test_min_trade_size() {
+        let trade = |qty| {
+            Event::Market(MarketEvent::Trade {
+                symbol: "BTC/USD".to_string(),
+                seq: 1,
+                trade: kraken_types::TradeData {
+                    symbol: "BTC/USD".to_string(),
+                    side: kraken_types::Side::Buy,
+                    price: rust_decimal_macros::dec!(50000),
+                    qty,
+                    ord_type: "market".to_string(),
+                    trade_id: 1,
+                    timestamp: "2024-01-01T00:00:00Z".to_string(),
+                },
+                received_at: ReceivedAt::now(),
+                exchange_ts_us: None,
+            })
+        };
+
+        let filter = FilterBuilder::new()
+            .trade_events()
+            .min_trade_size(rust_decimal_macros::dec!(1.0))
+            .build();
+        assert!(filter.matches(&trade(rust_decimal_macros::dec!(2.5))));
+        assert!(!filter.matches(&trade(rust_decimal_macros::dec!(0.1))));
+        assert!(!filter.matches(&book_event("BTC/USD")));
+    }
 }
@@ -26,7 +26,7 @@
 //!     let mut events = client.events().unwrap();
 //!     while let Some(event) = events.recv().await {
 //!         match event {
-//!             Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot }) => {
+//!             Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot, .. }) => {
 //!                 println!("{}: mid = {:?}", symbol, snapshot.mid_price());
 //!             }
 //!             _ => {}
@@ -43,13 +43,32 @@
 //! - **Automatic Reconnection**: Exponential backoff with jitter
 //! - **Orderbook Management**: State tracking with checksum validation
 //! - **Event-Driven**: Async event stream for all updates
+//! - **Checkpointing**: Persist books and trackers, resume warm after restarts
+//! - **Blocking Facade**: [`KrakenClientBlocking`] for code without an async runtime
 //! - **Type-Safe**: Full type safety with Rust's type system
 
+pub mod alerts;
+pub mod arbitrage;
+pub mod blocking;
+pub mod bounded_store;
 pub mod builder;
+pub mod candles;
+pub mod checkpoint;
 pub mod client;
+pub mod composite;
 pub mod filter;
+pub mod logger;
 pub mod market;
+pub mod pair_status;
+pub mod portfolio;
 pub mod prelude;
+pub mod regime;
+pub mod rest_backfill;
+pub mod rest_cache;
+pub mod rolling_stats;
+pub mod ticker_poller;
+pub mod trade_backfill;
+pub mod trade_flow;
 
 #[cfg(feature = "metrics")]
 pub mod metrics;
@@ -57,15 +76,41 @@ pub mod metrics;
 #[cfg(feature = "auth")]
 pub mod auth;
 
+#[cfg(feature = "auth")]
+pub mod accounts;
+
+#[cfg(feature = "db-sink")]
+pub mod sink;
+
+#[cfg(feature = "notify")]
+pub mod notify;
+
+#[cfg(feature = "notify")]
+pub mod order_hooks;
+
+#[cfg(feature = "config")]
+pub mod config;
+
+#[cfg(feature = "ipc")]
+pub mod bridge;
+
+#[cfg(feature = "serve")]
+pub mod serve;
+
+#[cfg(feature = "otel")]
+pub mod telemetry;
+
 // Re-export main types
+pub use blocking::KrakenClientBlocking;
 pub use builder::KrakenClientBuilder;
 pub use client::KrakenClient;
 
 // Re-export commonly used types from dependencies
-pub use kraken_book::{Orderbook, OrderbookSnapshot, OrderbookState, L3Book};
-pub use kraken_types::{Depth, KrakenError, Level, Symbol, Side, Channel};
+pub use kraken_book::{AuditViolation, ExtendedSnapshot, LevelMeta, MemoryLimits, Orderbook, OrderbookSnapshot, OrderbookState, L3Book};
+pub use kraken_types::{AccountId, Depth, KrakenError, Level, PairStatus, Symbol, Side, Channel};
 pub use kraken_ws::{
-    ConnectionState, Endpoint, Event, ReconnectConfig,
-    TradingClient, L3Event,
+    CircuitBreakerConfig, ConnectionState, Endpoint, Event, ReconnectConfig, PruningPolicy, LatencyStats, ReceivedAt, HealthStats,
+    ClockEstimate, MessageTap, RawFrame,
+    TradingClient, L3Event, PositionTracker, RiskManager, RiskLimits,
     PrivateEvent, MarketEvent, ConnectionEvent, SubscriptionEvent,
 };
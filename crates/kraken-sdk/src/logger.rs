@@ -0,0 +1,661 @@
+//! Market data logger
+//!
+//! Writes normalized rows for book tops, trades, tickers, and checksum
+//! mismatches to rotating CSV or JSON Lines files. Each row kind goes to its
+//! own file series so every file has a single, fixed schema:
+//!
+//! | kind        | columns                                                        |
+//! |-------------|----------------------------------------------------------------|
+//! | `book_top`  | ts_ms, symbol, bid, bid_qty, ask, ask_qty, checksum            |
+//! | `trades`    | ts_ms, symbol, trade_id, side, price, qty, ord_type, timestamp |
+//! | `tickers`   | ts_ms, symbol, bid, bid_qty, ask, ask_qty, last, volume, vwap  |
+//! | `checksums` | ts_ms, symbol, expected, computed                              |
+//!
+//! `ts_ms` is the local wall-clock time the event was logged. Book top rows
+//! are only written when the best bid or ask actually changes.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraken_sdk::logger::{LogFormat, MarketLogger};
+//! use kraken_sdk::prelude::*;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut client = KrakenClient::builder(["BTC/USD"]).connect().await?;
+//! let events = client.events().unwrap();
+//!
+//! let logger = MarketLogger::new("./capture")
+//!     .with_format(LogFormat::Csv)
+//!     .with_max_file_size(64 * 1024 * 1024)
+//!     .with_rotation_interval(Duration::from_secs(3600));
+//!
+//! tokio::spawn(logger.run(events));
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [`run`](MarketLogger::run) does all file I/O on a dedicated blocking
+//! thread, so a slow disk never stalls the async runtime. With the `gzip`
+//! feature, [`MarketLogger::with_gzip`] compresses each file to `.gz` once
+//! it is rotated out. Register a rotation hook with
+//! [`MarketLogger::on_rotate`] to upload or post-process finished files.
+
+use crate::filter::EventFilter;
+use kraken_types::Decimal;
+use kraken_ws::{Event, EventReceiver, MarketEvent};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// Events buffered between the event stream and the writer thread
+const WRITER_QUEUE: usize = 4096;
+
+/// Output file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    /// Comma-separated values with a header row
+    #[default]
+    Csv,
+    /// One JSON object per line
+    Jsonl,
+}
+
+impl LogFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            LogFormat::Csv => "csv",
+            LogFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// Kind of row written by the logger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RowKind {
+    /// Best bid/ask after an orderbook change
+    BookTop,
+    /// Public trade
+    Trade,
+    /// Ticker update
+    Ticker,
+    /// Checksum mismatch
+    Checksum,
+}
+
+impl RowKind {
+    /// File name stem for this kind
+    pub fn name(&self) -> &'static str {
+        match self {
+            RowKind::BookTop => "book_top",
+            RowKind::Trade => "trades",
+            RowKind::Ticker => "tickers",
+            RowKind::Checksum => "checksums",
+        }
+    }
+
+    fn columns(&self) -> &'static [&'static str] {
+        match self {
+            RowKind::BookTop => {
+                &["ts_ms", "symbol", "bid", "bid_qty", "ask", "ask_qty", "checksum"]
+            }
+            RowKind::Trade => &[
+                "ts_ms", "symbol", "trade_id", "side", "price", "qty", "ord_type", "timestamp",
+            ],
+            RowKind::Ticker => &[
+                "ts_ms", "symbol", "bid", "bid_qty", "ask", "ask_qty", "last", "volume", "vwap",
+            ],
+            RowKind::Checksum => &["ts_ms", "symbol", "expected", "computed"],
+        }
+    }
+}
+
+/// Callback invoked with the path of each file after it is closed
+pub type RotateHook = Box<dyn Fn(&Path) + Send + 'static>;
+
+/// One open output file and its rotation bookkeeping
+struct RotatingFile {
+    writer: BufWriter<File>,
+    path: PathBuf,
+    bytes: u64,
+    opened_at: Instant,
+}
+
+/// Rotating CSV/JSONL writer for market events
+pub struct MarketLogger {
+    dir: PathBuf,
+    prefix: String,
+    format: LogFormat,
+    max_file_size: Option<u64>,
+    rotation_interval: Option<Duration>,
+    filter: EventFilter,
+    on_rotate: Option<RotateHook>,
+    #[cfg(feature = "gzip")]
+    gzip: bool,
+    files: HashMap<RowKind, RotatingFile>,
+    last_top: HashMap<String, [Option<(Decimal, Decimal)>; 2]>,
+    file_seq: u64,
+    rows_written: u64,
+}
+
+impl MarketLogger {
+    /// Create a logger writing CSV files into `dir`
+    ///
+    /// The directory is created on the first write if it doesn't exist.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            prefix: String::new(),
+            format: LogFormat::Csv,
+            max_file_size: None,
+            rotation_interval: None,
+            filter: EventFilter::all(),
+            on_rotate: None,
+            #[cfg(feature = "gzip")]
+            gzip: false,
+            files: HashMap::new(),
+            last_top: HashMap::new(),
+            file_seq: 0,
+            rows_written: 0,
+        }
+    }
+
+    /// Set the output format
+    pub fn with_format(mut self, format: LogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Prefix every file name (e.g. a capture or host name)
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Rotate a file once it reaches this many bytes
+    pub fn with_max_file_size(mut self, bytes: u64) -> Self {
+        self.max_file_size = Some(bytes);
+        self
+    }
+
+    /// Rotate a file once it has been open this long
+    pub fn with_rotation_interval(mut self, interval: Duration) -> Self {
+        self.rotation_interval = Some(interval);
+        self
+    }
+
+    /// Only log events that pass this filter
+    pub fn with_filter(mut self, filter: EventFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Gzip every file once it is rotated out or closed
+    ///
+    /// The finished file is replaced by `<name>.gz`, and that is the path
+    /// handed to the rotation hook.
+    #[cfg(feature = "gzip")]
+    pub fn with_gzip(mut self) -> Self {
+        self.gzip = true;
+        self
+    }
+
+    /// Call `hook` with the path of every file after it is closed
+    ///
+    /// Use this to ship finished files elsewhere.
+    pub fn on_rotate<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&Path) + Send + 'static,
+    {
+        self.on_rotate = Some(Box::new(hook));
+        self
+    }
+
+    /// Total rows written so far
+    pub fn rows_written(&self) -> u64 {
+        self.rows_written
+    }
+
+    /// Paths of the files currently open
+    pub fn open_files(&self) -> Vec<PathBuf> {
+        self.files.values().map(|f| f.path.clone()).collect()
+    }
+
+    /// Log a single event
+    ///
+    /// Events that don't map to a row kind (connection, subscription, private,
+    /// L3, heartbeats) are ignored.
+    pub fn log(&mut self, event: &Event) -> io::Result<()> {
+        if !self.filter.matches(event) {
+            return Ok(());
+        }
+        let Event::Market(market) = event else {
+            return Ok(());
+        };
+        let ts = now_ms().to_string();
+
+        match market {
+            MarketEvent::OrderbookSnapshot { symbol, snapshot, .. }
+            | MarketEvent::OrderbookUpdate { symbol, snapshot, .. } => {
+                let top = [
+                    snapshot.bids.first().map(|l| (l.price.0, l.qty.0)),
+                    snapshot.asks.first().map(|l| (l.price.0, l.qty.0)),
+                ];
+                if self.last_top.get(symbol) == Some(&top) {
+                    return Ok(());
+                }
+                self.last_top.insert(symbol.clone(), top);
+
+                let [bid, ask] = top;
+                self.write_row(
+                    RowKind::BookTop,
+                    &[
+                        &ts,
+                        symbol,
+                        &opt(bid.map(|b| b.0)),
+                        &opt(bid.map(|b| b.1)),
+                        &opt(ask.map(|a| a.0)),
+                        &opt(ask.map(|a| a.1)),
+                        &snapshot.checksum,
+                    ],
+                )
+            }
+            MarketEvent::Trade { symbol, trade, .. } => self.write_row(
+                RowKind::Trade,
+                &[
+                    &ts,
+                    symbol,
+                    &trade.trade_id,
+                    &format!("{:?}", trade.side).to_lowercase(),
+                    &trade.price,
+                    &trade.qty,
+                    &trade.ord_type,
+                    &trade.timestamp,
+                ],
+            ),
+            MarketEvent::Ticker { symbol, ticker, .. } => self.write_row(
+                RowKind::Ticker,
+                &[
+                    &ts,
+                    symbol,
+                    &ticker.bid,
+                    &ticker.bid_qty,
+                    &ticker.ask,
+                    &ticker.ask_qty,
+                    &ticker.last,
+                    &ticker.volume,
+                    &ticker.vwap,
+                ],
+            ),
+            MarketEvent::ChecksumMismatch {
+                symbol,
+                expected,
+                computed,
+            } => self.write_row(RowKind::Checksum, &[&ts, symbol, expected, computed]),
+            MarketEvent::OutOfOrderUpdate { .. }
+            | MarketEvent::UpdateBeforeSnapshot { .. }
+            | MarketEvent::CrossedBook { .. }
+            | MarketEvent::DepthOverflow { .. }
+            | MarketEvent::DepthMismatch { .. }
+            | MarketEvent::BookAuditFailed { .. }
+            | MarketEvent::BookSample { .. }
+            | MarketEvent::Ohlc { .. }
+            | MarketEvent::Status { .. }
+            | MarketEvent::Heartbeat => Ok(()),
+        }
+    }
+
+    /// Consume events until the stream ends, then flush and close all files
+    ///
+    /// The logger moves to a blocking writer thread fed through a bounded
+    /// queue; when the disk falls behind, the queue applies backpressure to
+    /// the event stream. Returns the first write error.
+    pub async fn run(self, mut events: EventReceiver) -> io::Result<()> {
+        let (tx, mut rx) = mpsc::channel::<Event>(WRITER_QUEUE);
+        let writer = tokio::task::spawn_blocking(move || {
+            let mut logger = self;
+            while let Some(event) = rx.blocking_recv() {
+                logger.log(&event)?;
+            }
+            logger.close()
+        });
+
+        while let Some(event) = events.recv().await {
+            // The writer only hangs up after a write error
+            if tx.send(event).await.is_err() {
+                break;
+            }
+        }
+        drop(tx);
+        writer.await.map_err(io::Error::other)?
+    }
+
+    /// Flush all open files
+    pub fn flush(&mut self) -> io::Result<()> {
+        for file in self.files.values_mut() {
+            file.writer.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Flush and close all open files, running the rotation hook on each
+    pub fn close(&mut self) -> io::Result<()> {
+        let kinds: Vec<RowKind> = self.files.keys().copied().collect();
+        for kind in kinds {
+            self.close_file(kind)?;
+        }
+        Ok(())
+    }
+
+    fn write_row(&mut self, kind: RowKind, values: &[&dyn Display]) -> io::Result<()> {
+        let mut line = String::new();
+        match self.format {
+            LogFormat::Csv => {
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        line.push(',');
+                    }
+                    push_csv_field(&mut line, &value.to_string());
+                }
+            }
+            LogFormat::Jsonl => {
+                let object: serde_json::Map<String, serde_json::Value> = kind
+                    .columns()
+                    .iter()
+                    .zip(values)
+                    .map(|(column, value)| {
+                        (column.to_string(), serde_json::Value::String(value.to_string()))
+                    })
+                    .collect();
+                line = serde_json::Value::Object(object).to_string();
+            }
+        }
+        line.push('\n');
+
+        self.rotate_if_needed(kind)?;
+        if !self.files.contains_key(&kind) {
+            let file = self.open_file(kind)?;
+            self.files.insert(kind, file);
+        }
+        let file = self.files.get_mut(&kind).expect("file opened above");
+        file.writer.write_all(line.as_bytes())?;
+        file.bytes += line.len() as u64;
+        self.rows_written += 1;
+        Ok(())
+    }
+
+    fn rotate_if_needed(&mut self, kind: RowKind) -> io::Result<()> {
+        let Some(file) = self.files.get(&kind) else {
+            return Ok(());
+        };
+        let too_big = self.max_file_size.is_some_and(|max| file.bytes >= max);
+        let too_old = self
+            .rotation_interval
+            .is_some_and(|interval| file.opened_at.elapsed() >= interval);
+        if too_big || too_old {
+            self.close_file(kind)?;
+        }
+        Ok(())
+    }
+
+    fn open_file(&mut self, kind: RowKind) -> io::Result<RotatingFile> {
+        fs::create_dir_all(&self.dir)?;
+        self.file_seq += 1;
+        let name = format!(
+            "{}{}-{}-{:04}.{}",
+            self.prefix,
+            kind.name(),
+            now_ms(),
+            self.file_seq,
+            self.format.extension()
+        );
+        let path = self.dir.join(name);
+        let mut writer = BufWriter::new(File::create(&path)?);
+
+        let mut bytes = 0;
+        if self.format == LogFormat::Csv {
+            let header = format!("{}\n", kind.columns().join(","));
+            writer.write_all(header.as_bytes())?;
+            bytes = header.len() as u64;
+        }
+
+        Ok(RotatingFile {
+            writer,
+            path,
+            bytes,
+            opened_at: Instant::now(),
+        })
+    }
+
+    fn close_file(&mut self, kind: RowKind) -> io::Result<()> {
+        if let Some(mut file) = self.files.remove(&kind) {
+            file.writer.flush()?;
+            drop(file.writer);
+            #[cfg(feature = "gzip")]
+            let path = if self.gzip { gzip_file(&file.path)? } else { file.path };
+            #[cfg(not(feature = "gzip"))]
+            let path = file.path;
+            if let Some(hook) = &self.on_rotate {
+                hook(&path);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MarketLogger {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+impl std::fmt::Debug for MarketLogger {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MarketLogger")
+            .field("dir", &self.dir)
+            .field("format", &self.format)
+            .field("max_file_size", &self.max_file_size)
+            .field("rotation_interval", &self.rotation_interval)
+            .field("rows_written", &self.rows_written)
+            .finish()
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Compress `path` to `path.gz` and remove the original
+#[cfg(feature = "gzip")]
+fn gzip_file(path: &Path) -> io::Result<PathBuf> {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let mut name = path.as_os_str().to_owned();
+    name.push(".gz");
+    let target = PathBuf::from(name);
+
+    let mut source = File::open(path)?;
+    let mut encoder = GzEncoder::new(BufWriter::new(File::create(&target)?), Compression::default());
+    io::copy(&mut source, &mut encoder)?;
+    encoder.finish()?.flush()?;
+    fs::remove_file(path)?;
+    Ok(target)
+}
+
+fn opt(value: Option<Decimal>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn push_csv_field(line: &mut String, value: &str) {
+    if value.contains([',', '"', '\n']) {
+        line.push('"');
+        line.push_str(&value.replace('"', "\"\""));
+        line.push('"');
+    } else {
+        line.push_str(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_book::OrderbookSnapshot;
+    use kraken_ws::ReceivedAt;
+    use kraken_types::{Level, Side, TradeData};
+    use rust_decimal_macros::dec;
+    use std::sync::{Arc, Mutex};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("kraken-logger-{}-{}", name, now_ms()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn book_event(bid: Decimal) -> Event {
+        Event::Market(MarketEvent::OrderbookUpdate {
+            symbol: "BTC/USD".to_string(),
+            seq: 1,
+            snapshot: OrderbookSnapshot {
+                symbol: "BTC/USD".to_string(),
+                bids: vec![Level::new(bid, dec!(1))],
+                asks: vec![Level::new(dec!(101), dec!(2))],
+                checksum: 7,
+                ..Default::default()
+            },
+            received_at: ReceivedAt::now(),
+            exchange_ts_us: None,
+        })
+    }
+
+    fn trade_event(id: u64) -> Event {
+        Event::Market(MarketEvent::Trade {
+            symbol: "BTC/USD".to_string(),
+            seq: id,
+            trade: TradeData {
+                symbol: "BTC/USD".to_string(),
+                side: Side::Sell,
+                price: dec!(100.5),
+                qty: dec!(0.25),
+                ord_type: "limit".to_string(),
+                trade_id: id,
+                timestamp: "2024-01-01T00:00:00.000Z".to_string(),
+            },
+            received_at: ReceivedAt::now(),
+            exchange_ts_us: None,
+        })
+    }
+
+    #[test]
+    fn test_csv_rows_and_top_dedup() {
+        let dir = temp_dir("csv");
+        let mut logger = MarketLogger::new(&dir);
+
+        logger.log(&book_event(dec!(100))).unwrap();
+        logger.log(&book_event(dec!(100))).unwrap(); // unchanged top
+        logger.log(&book_event(dec!(99.5))).unwrap();
+        logger.log(&trade_event(1)).unwrap();
+        let paths = logger.open_files();
+        logger.close().unwrap();
+
+        assert_eq!(logger.rows_written(), 3);
+        let book = paths.iter().find(|p| p.to_string_lossy().contains("book_top")).unwrap();
+        let text = fs::read_to_string(book).unwrap();
+        let lines: Vec<_> = text.lines().collect();
+        assert_eq!(lines[0], "ts_ms,symbol,bid,bid_qty,ask,ask_qty,checksum");
+        assert_eq!(lines.len(), 3);
+        assert!(lines[2].ends_with(",BTC/USD,99.5,1,101,2,7"));
+
+        let trades = paths.iter().find(|p| p.to_string_lossy().contains("trades")).unwrap();
+        let text = fs::read_to_string(trades).unwrap();
+        assert!(text.lines().nth(1).unwrap().contains(",1,sell,100.5,0.25,limit,"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_jsonl_and_size_rotation() {
+        let dir = temp_dir("jsonl");
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let hook_closed = closed.clone();
+        let mut logger = MarketLogger::new(&dir)
+            .with_format(LogFormat::Jsonl)
+            .with_max_file_size(1)
+            .on_rotate(move |path| hook_closed.lock().unwrap().push(path.to_path_buf()));
+
+        for id in 0..3 {
+            logger.log(&trade_event(id)).unwrap();
+        }
+        logger.close().unwrap();
+
+        // Every row exceeds the 1-byte limit, so each lands in its own file
+        let closed = closed.lock().unwrap();
+        assert_eq!(closed.len(), 3);
+        let text = fs::read_to_string(&closed[2]).unwrap();
+        let row: serde_json::Value = serde_json::from_str(text.trim()).unwrap();
+        assert_eq!(row["trade_id"], "2");
+        assert_eq!(row["side"], "sell");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_run_writes_on_writer_thread() {
+        let dir = temp_dir("run");
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        for id in 0..2 {
+            tx.send(kraken_ws::SequencedEvent { id, event: trade_event(id) }).unwrap();
+        }
+        drop(tx);
+
+        MarketLogger::new(&dir).run(EventReceiver::Unbounded(rx)).await.unwrap();
+
+        let files: Vec<_> = fs::read_dir(&dir).unwrap().map(|e| e.unwrap().path()).collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(fs::read_to_string(&files[0]).unwrap().lines().count(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip_on_rotate() {
+        use std::io::Read;
+
+        let dir = temp_dir("gzip");
+        let closed = Arc::new(Mutex::new(Vec::new()));
+        let hook_closed = closed.clone();
+        let mut logger = MarketLogger::new(&dir)
+            .with_gzip()
+            .on_rotate(move |path| hook_closed.lock().unwrap().push(path.to_path_buf()));
+        logger.log(&trade_event(1)).unwrap();
+        let plain = logger.open_files().remove(0);
+        logger.close().unwrap();
+
+        let closed = closed.lock().unwrap();
+        assert!(closed[0].to_string_lossy().ends_with(".csv.gz"));
+        assert!(!plain.exists());
+        let mut text = String::new();
+        flate2::read::GzDecoder::new(File::open(&closed[0]).unwrap())
+            .read_to_string(&mut text)
+            .unwrap();
+        assert!(text.starts_with("ts_ms,symbol,trade_id"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_csv_field_escaping() {
+        let mut line = String::new();
+        push_csv_field(&mut line, "a,b");
+        line.push(',');
+        push_csv_field(&mut line, "say \"hi\"");
+        assert_eq!(line, "\"a,b\",\"say \"\"hi\"\"\"");
+    }
+}
@@ -7,7 +7,8 @@
 //!
 //! - **Orderbook State**: Best bid/ask, spread, mid price, depth snapshots
 //! - **VWAP Calculation**: Volume-weighted average price for order sizing
-//! - **Trade History**: Recent trades with configurable buffer size
+//! - **Trade History**: Recent trades with configurable buffer size, kept in a
+//!   [`BoundedStore`] per symbol and optionally spilled to disk
 //! - **Volatility Estimation**: Realized volatility from trade data
 //! - **Event Filtering**: Subscribe to specific symbols/channels with filters
 //!
@@ -15,7 +16,7 @@
 //!
 //! ```no_run
 //! use kraken_sdk::market::{MarketState, Spread};
-//! use kraken_types::Decimal;
+//! use kraken_types::{Decimal, Qty};
 //!
 //! let mut state = MarketState::new();
 //!
@@ -25,7 +26,7 @@
 //! }
 //!
 //! // Calculate VWAP for order sizing
-//! if let Some(vwap) = state.vwap_buy("BTC/USD", Decimal::from(10)) {
+//! if let Some(vwap) = state.buy_vwap("BTC/USD", Qty::new(Decimal::from(10))) {
 //!     println!("VWAP to buy 10 BTC: {}", vwap);
 //! }
 //! ```
@@ -38,18 +39,22 @@
 //! | Orderbook Depth | `book_snapshot()`, `bbo()` |
 //! | Spread Monitor | `spread()` with history |
 //! | Arbitrage Detector | `mid_price()` cross-symbol |
-//! | VWAP Calculator | `vwap_buy()`, `vwap_sell()` |
+//! | VWAP Calculator | `buy_vwap()`, `sell_vwap()` |
 //! | Market Maker | `bbo()`, `book_snapshot()`, `imbalance()` |
-//! | Trade Analytics | `recent_trades()`, `trade_volume()` |
+//! | Trade Analytics | `recent_trades()`, `trades_between()`, `traded_qty()` |
 //! | Volatility Tracker | `volatility()` |
 
+use crate::bounded_store::{BoundedStore, StoreError};
 use kraken_book::{Orderbook, OrderbookSnapshot, OrderbookState};
-use kraken_types::{BookData, Decimal, Level, Side};
+use kraken_types::{BookData, Decimal, Level, Notional, Price, Qty, Side};
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::HashMap;
 use tracing::instrument;
 
+#[cfg(feature = "spill")]
+use crate::bounded_store::SpillConfig;
+
 /// Maximum number of trades to keep in history per symbol
 const DEFAULT_TRADE_HISTORY_SIZE: usize = 1000;
 
@@ -131,7 +136,7 @@ pub struct BBO {
 impl BBO {
     /// Create from bid and ask levels
     pub fn new(bid: Level, ask: Level) -> Self {
-        let spread = Spread::new(bid.price, ask.price);
+        let spread = Spread::new(bid.price.0, ask.price.0);
         let total_qty = bid.qty + ask.qty;
         let imbalance = if total_qty.is_zero() {
             Decimal::ZERO
@@ -154,9 +159,9 @@ pub struct TradeRecord {
     /// Trading pair symbol
     pub symbol: String,
     /// Trade price
-    pub price: Decimal,
+    pub price: Price,
     /// Trade quantity
-    pub qty: Decimal,
+    pub qty: Qty,
     /// Trade side (buy/sell)
     pub side: Side,
     /// Timestamp (ISO 8601)
@@ -167,11 +172,17 @@ pub struct TradeRecord {
 
 impl TradeRecord {
     /// Create a new trade record
-    pub fn new(symbol: String, price: Decimal, qty: Decimal, side: Side, timestamp: String) -> Self {
+    pub fn new(
+        symbol: String,
+        price: impl Into<Price>,
+        qty: impl Into<Qty>,
+        side: Side,
+        timestamp: String,
+    ) -> Self {
         Self {
             symbol,
-            price,
-            qty,
+            price: price.into(),
+            qty: qty.into(),
             side,
             timestamp,
             trade_id: None,
@@ -179,9 +190,15 @@ impl TradeRecord {
     }
 
     /// Trade value (price * qty)
-    pub fn value(&self) -> Decimal {
+    pub fn notional(&self) -> Notional {
         self.price * self.qty
     }
+
+    /// Trade value (price * qty), as a bare Decimal
+    #[deprecated(note = "use `notional`, which returns a `Notional`")]
+    pub fn value(&self) -> Decimal {
+        self.notional().into()
+    }
 }
 
 /// Orderbook imbalance across multiple levels
@@ -236,10 +253,8 @@ impl ImbalanceSignal {
 struct SymbolState {
     /// L2 orderbook
     orderbook: Orderbook,
-    /// Recent trades
-    trades: VecDeque<TradeRecord>,
-    /// Maximum trade history size
-    max_trades: usize,
+    /// Recent trades, oldest first
+    trades: BoundedStore<TradeRecord>,
 }
 
 impl std::fmt::Debug for SymbolState {
@@ -247,27 +262,19 @@ impl std::fmt::Debug for SymbolState {
         f.debug_struct("SymbolState")
             .field("orderbook_symbol", &self.orderbook.symbol())
             .field("orderbook_state", &self.orderbook.state())
-            .field("trades_count", &self.trades.len())
-            .field("max_trades", &self.max_trades)
+            .field("trades_count", &self.trades.memory_len())
+            .field("max_trades", &self.trades.capacity())
             .finish()
     }
 }
 
 impl SymbolState {
-    fn new(symbol: &str, max_trades: usize) -> Self {
+    fn new(symbol: &str, trades: BoundedStore<TradeRecord>) -> Self {
         Self {
             orderbook: Orderbook::new(symbol),
-            trades: VecDeque::with_capacity(max_trades),
-            max_trades,
+            trades,
         }
     }
-
-    fn add_trade(&mut self, trade: TradeRecord) {
-        if self.trades.len() >= self.max_trades {
-            self.trades.pop_front();
-        }
-        self.trades.push_back(trade);
-    }
 }
 
 /// Unified market state manager
@@ -280,6 +287,9 @@ pub struct MarketState {
     symbols: HashMap<String, SymbolState>,
     /// Trade history size per symbol
     trade_history_size: usize,
+    /// Where trades evicted from memory are spilled
+    #[cfg(feature = "spill")]
+    trade_spill: Option<SpillConfig>,
 }
 
 impl Default for MarketState {
@@ -294,6 +304,8 @@ impl MarketState {
         Self {
             symbols: HashMap::new(),
             trade_history_size: DEFAULT_TRADE_HISTORY_SIZE,
+            #[cfg(feature = "spill")]
+            trade_spill: None,
         }
     }
 
@@ -303,11 +315,33 @@ impl MarketState {
         self
     }
 
+    /// Spill trades evicted from the in-memory history to disk
+    ///
+    /// Each symbol gets a subdirectory of `config.dir`;
+    /// [`trades_between`](Self::trades_between) reads it back.
+    #[cfg(feature = "spill")]
+    pub fn with_trade_spill(mut self, config: SpillConfig) -> Self {
+        self.trade_spill = Some(config);
+        self
+    }
+
     /// Get or create symbol state
     fn get_or_create_symbol(&mut self, symbol: &str) -> &mut SymbolState {
-        self.symbols.entry(symbol.to_string()).or_insert_with(|| {
-            SymbolState::new(symbol, self.trade_history_size)
-        })
+        if !self.symbols.contains_key(symbol) {
+            let state = SymbolState::new(symbol, self.new_trade_history(symbol));
+            self.symbols.insert(symbol.to_string(), state);
+        }
+        self.symbols.get_mut(symbol).expect("symbol inserted above")
+    }
+
+    #[cfg(feature = "spill")]
+    fn new_trade_history(&self, symbol: &str) -> BoundedStore<TradeRecord> {
+        BoundedStore::for_series(self.trade_history_size, self.trade_spill.as_ref(), symbol)
+    }
+
+    #[cfg(not(feature = "spill"))]
+    fn new_trade_history(&self, _symbol: &str) -> BoundedStore<TradeRecord> {
+        BoundedStore::new(self.trade_history_size)
     }
 
     /// Get symbol state (immutable)
@@ -325,7 +359,7 @@ impl MarketState {
         &mut self,
         data: &BookData,
         is_snapshot: bool,
-    ) -> Result<(), kraken_book::ChecksumMismatch> {
+    ) -> Result<(), kraken_book::ApplyError> {
         let state = self.get_or_create_symbol(&data.symbol);
         state.orderbook.apply_book_data(data, is_snapshot)?;
         Ok(())
@@ -353,7 +387,7 @@ impl MarketState {
         let state = self.get_symbol(symbol)?;
         let bid = state.orderbook.best_bid()?;
         let ask = state.orderbook.best_ask()?;
-        Some(Spread::new(bid.price, ask.price))
+        Some(Spread::new(bid.price.0, ask.price.0))
     }
 
     /// Get the mid price for a symbol
@@ -381,8 +415,8 @@ impl MarketState {
     pub fn imbalance(&self, symbol: &str, levels: usize) -> Option<BookImbalance> {
         let (bids, asks) = self.top_levels(symbol, levels)?;
 
-        let bid_qty: Decimal = bids.iter().map(|l| l.qty).sum();
-        let ask_qty: Decimal = asks.iter().map(|l| l.qty).sum();
+        let bid_qty: Decimal = bids.iter().map(|l| l.qty.0).sum();
+        let ask_qty: Decimal = asks.iter().map(|l| l.qty.0).sum();
 
         let total = bid_qty + ask_qty;
         let ratio = if total.is_zero() {
@@ -407,26 +441,38 @@ impl MarketState {
     /// Calculate VWAP for buying a given quantity
     ///
     /// Walks through the ask side to determine average price to buy the specified quantity.
-    #[instrument(skip(self))]
-    pub fn vwap_buy(&self, symbol: &str, qty: Decimal) -> Option<Decimal> {
+    #[instrument(skip(self, qty))]
+    pub fn buy_vwap(&self, symbol: &str, qty: impl Into<Qty>) -> Option<Price> {
         let state = self.get_symbol(symbol)?;
-        Self::calculate_vwap(state.orderbook.asks_vec().into_iter(), qty)
+        Self::calculate_vwap(state.orderbook.asks_vec().into_iter(), qty.into())
     }
 
     /// Calculate VWAP for selling a given quantity
     ///
     /// Walks through the bid side to determine average price to sell the specified quantity.
-    #[instrument(skip(self))]
-    pub fn vwap_sell(&self, symbol: &str, qty: Decimal) -> Option<Decimal> {
+    #[instrument(skip(self, qty))]
+    pub fn sell_vwap(&self, symbol: &str, qty: impl Into<Qty>) -> Option<Price> {
         let state = self.get_symbol(symbol)?;
-        Self::calculate_vwap(state.orderbook.bids_vec().into_iter(), qty)
+        Self::calculate_vwap(state.orderbook.bids_vec().into_iter(), qty.into())
+    }
+
+    /// Calculate VWAP for buying a given quantity, as a bare Decimal
+    #[deprecated(note = "use `buy_vwap`, which takes a `Qty` and returns a `Price`")]
+    pub fn vwap_buy(&self, symbol: &str, qty: Decimal) -> Option<Decimal> {
+        self.buy_vwap(symbol, qty).map(Decimal::from)
+    }
+
+    /// Calculate VWAP for selling a given quantity, as a bare Decimal
+    #[deprecated(note = "use `sell_vwap`, which takes a `Qty` and returns a `Price`")]
+    pub fn vwap_sell(&self, symbol: &str, qty: Decimal) -> Option<Decimal> {
+        self.sell_vwap(symbol, qty).map(Decimal::from)
     }
 
     /// Generic VWAP calculation across price levels
-    fn calculate_vwap(levels: impl Iterator<Item = Level>, target_qty: Decimal) -> Option<Decimal> {
+    fn calculate_vwap(levels: impl Iterator<Item = Level>, target_qty: Qty) -> Option<Price> {
         let mut remaining = target_qty;
-        let mut total_value = Decimal::ZERO;
-        let mut total_qty = Decimal::ZERO;
+        let mut total_value = Notional::ZERO;
+        let mut total_qty = Qty::ZERO;
 
         for level in levels {
             if remaining.is_zero() {
@@ -440,7 +486,7 @@ impl MarketState {
         }
 
         // Return None if we couldn't fill the entire quantity
-        if remaining > Decimal::ZERO {
+        if remaining > Qty::ZERO {
             return None;
         }
 
@@ -454,16 +500,11 @@ impl MarketState {
     /// Calculate slippage for a market order
     ///
     /// Returns (vwap, slippage_bps) where slippage is the difference from mid price.
-    pub fn market_order_slippage(
-        &self,
-        symbol: &str,
-        side: Side,
-        qty: Decimal,
-    ) -> Option<(Decimal, Decimal)> {
-        let mid = self.mid_price(symbol)?;
+    pub fn order_slippage(&self, symbol: &str, side: Side, qty: impl Into<Qty>) -> Option<(Price, Decimal)> {
+        let mid = Price(self.mid_price(symbol)?);
         let vwap = match side {
-            Side::Buy => self.vwap_buy(symbol, qty)?,
-            Side::Sell => self.vwap_sell(symbol, qty)?,
+            Side::Buy => self.buy_vwap(symbol, qty)?,
+            Side::Sell => self.sell_vwap(symbol, qty)?,
         };
 
         let slippage = match side {
@@ -474,15 +515,24 @@ impl MarketState {
         Some((vwap, slippage))
     }
 
+    /// Calculate slippage for a market order, with the VWAP as a bare Decimal
+    #[deprecated(note = "use `order_slippage`, which takes a `Qty` and returns the VWAP as a `Price`")]
+    pub fn market_order_slippage(&self, symbol: &str, side: Side, qty: Decimal) -> Option<(Decimal, Decimal)> {
+        self.order_slippage(symbol, side, qty)
+            .map(|(vwap, slippage)| (vwap.into(), slippage))
+    }
+
     // =========================================================================
     // Trade History Operations
     // =========================================================================
 
     /// Record a trade
-    pub fn record_trade(&mut self, trade: TradeRecord) {
+    ///
+    /// Returns false, without recording it, if the timestamp isn't RFC 3339.
+    pub fn record_trade(&mut self, trade: TradeRecord) -> bool {
         let symbol = trade.symbol.clone();
         let state = self.get_or_create_symbol(&symbol);
-        state.add_trade(trade);
+        state.trades.push(trade)
     }
 
     /// Get recent trades for a symbol
@@ -490,7 +540,7 @@ impl MarketState {
         self.get_symbol(symbol)
             .map(|s| {
                 s.trades
-                    .iter()
+                    .in_memory()
                     .rev()
                     .take(count)
                     .collect()
@@ -501,26 +551,37 @@ impl MarketState {
     /// Get all trades in history for a symbol
     pub fn all_trades(&self, symbol: &str) -> Vec<&TradeRecord> {
         self.get_symbol(symbol)
-            .map(|s| s.trades.iter().collect())
+            .map(|s| s.trades.in_memory().collect())
             .unwrap_or_default()
     }
 
+    /// Trades for a symbol with `from_us <= time < to_us` (Unix
+    /// microseconds), oldest first
+    ///
+    /// Reads spilled trades when the window reaches past memory.
+    pub fn trades_between(&mut self, symbol: &str, from_us: i64, to_us: i64) -> Result<Vec<TradeRecord>, StoreError> {
+        match self.symbols.get_mut(symbol) {
+            Some(state) => state.trades.range(from_us, to_us),
+            None => Ok(Vec::new()),
+        }
+    }
+
     /// Calculate total volume traded in recent history
-    pub fn trade_volume(&self, symbol: &str) -> Decimal {
+    pub fn traded_qty(&self, symbol: &str) -> Qty {
         self.get_symbol(symbol)
-            .map(|s| s.trades.iter().map(|t| t.qty).sum())
-            .unwrap_or(Decimal::ZERO)
+            .map(|s| s.trades.in_memory().map(|t| t.qty).sum())
+            .unwrap_or(Qty::ZERO)
     }
 
     /// Calculate VWAP from recent trades
-    pub fn trade_vwap(&self, symbol: &str) -> Option<Decimal> {
+    pub fn trades_vwap(&self, symbol: &str) -> Option<Price> {
         let state = self.get_symbol(symbol)?;
-        if state.trades.is_empty() {
+        if state.trades.memory_len() == 0 {
             return None;
         }
 
-        let total_value: Decimal = state.trades.iter().map(|t| t.value()).sum();
-        let total_qty: Decimal = state.trades.iter().map(|t| t.qty).sum();
+        let total_value: Notional = state.trades.in_memory().map(|t| t.notional()).sum();
+        let total_qty: Qty = state.trades.in_memory().map(|t| t.qty).sum();
 
         if total_qty.is_zero() {
             return None;
@@ -529,20 +590,32 @@ impl MarketState {
         Some(total_value / total_qty)
     }
 
+    /// Calculate total volume traded in recent history, as a bare Decimal
+    #[deprecated(note = "use `traded_qty`, which returns a `Qty`")]
+    pub fn trade_volume(&self, symbol: &str) -> Decimal {
+        self.traded_qty(symbol).into()
+    }
+
+    /// Calculate VWAP from recent trades, as a bare Decimal
+    #[deprecated(note = "use `trades_vwap`, which returns a `Price`")]
+    pub fn trade_vwap(&self, symbol: &str) -> Option<Decimal> {
+        self.trades_vwap(symbol).map(Decimal::from)
+    }
+
     /// Calculate realized volatility from trade data
     ///
     /// Uses log returns of trade prices to estimate volatility.
     /// Returns annualized volatility as a decimal (e.g., 0.5 = 50%).
     pub fn volatility(&self, symbol: &str) -> Option<Decimal> {
         let state = self.get_symbol(symbol)?;
-        if state.trades.len() < 2 {
+        if state.trades.memory_len() < 2 {
             return None;
         }
 
         // Calculate log returns
         let prices: Vec<f64> = state
             .trades
-            .iter()
+            .in_memory()
             .map(|t| t.price.to_string().parse::<f64>().unwrap_or(0.0))
             .collect();
 
@@ -670,9 +743,19 @@ mod tests {
         // History limited to 5
         let trades = state.recent_trades("BTC/USD", 10);
         assert_eq!(trades.len(), 5);
+        assert!(!state.record_trade(TradeRecord::new(
+            "BTC/USD".to_string(),
+            Decimal::ONE,
+            Decimal::ONE,
+            Side::Buy,
+            "later".to_string(),
+        )));
+        // 00:07 to 00:09; older trades were dropped without spill
+        let window = state.trades_between("BTC/USD", 1_704_067_620_000_000, 1_704_067_800_000_000).unwrap();
+        assert_eq!(window.len(), 3);
 
         // Volume and VWAP
-        assert_eq!(state.trade_volume("BTC/USD"), dec!(5));
-        assert!(state.trade_vwap("BTC/USD").is_some());
+        assert_eq!(state.traded_qty("BTC/USD"), dec!(5));
+        assert!(state.trades_vwap("BTC/USD").is_some());
     }
 }
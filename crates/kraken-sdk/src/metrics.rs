@@ -20,6 +20,7 @@
 //! - `kraken_errors_total` - Total errors by type
 //! - `kraken_reconnections_total` - Total reconnection attempts
 //! - `kraken_checksum_failures_total` - Checksum validation failures
+//! - `kraken_book_audit_violations_total` - Book audit violations by symbol and kind
 //! - `kraken_rate_limit_rejections_total` - Rate limit rejections by category
 //! - `kraken_rest_requests_total` - REST API requests by endpoint
 //! - `kraken_orders_total` - Orders by type and status
@@ -87,6 +88,13 @@ lazy_static! {
         &["symbol"]
     ).expect("metric: checksum_failures");
 
+    /// Invariant violations found by the periodic book audit
+    pub static ref BOOK_AUDIT_VIOLATIONS: CounterVec = register_counter_vec!(
+        "kraken_book_audit_violations_total",
+        "Total orderbook audit violations",
+        &["symbol", "kind"]
+    ).expect("metric: book_audit_violations");
+
     /// Rate limit rejections by category
     pub static ref RATE_LIMIT_REJECTIONS: CounterVec = register_counter_vec!(
         "kraken_rate_limit_rejections_total",
@@ -346,6 +354,11 @@ pub fn record_checksum_failure(symbol: &str) {
     CHECKSUM_FAILURES.with_label_values(&[symbol]).inc();
 }
 
+/// Record a book audit violation (`kind` from `AuditViolation::kind`)
+pub fn record_audit_violation(symbol: &str, kind: &str) {
+    BOOK_AUDIT_VIOLATIONS.with_label_values(&[symbol, kind]).inc();
+}
+
 /// Set connection status (0=disconnected, 1=connected)
 pub fn set_connection_status(connected: bool) {
     CONNECTION_STATUS.set(if connected { 1.0 } else { 0.0 });
@@ -0,0 +1,809 @@
+//! Alert and health notifications to Slack, Discord and webhooks
+//!
+//! A [`Notifier`] fans [`Notification`]s out to one or more
+//! [`NotificationSink`]s. Notifications come from fired alert rules
+//! ([`AlertTrigger`]) or from connection health events
+//! ([`Notification::from_event`]); routine events such as book updates and
+//! successful reconnects produce none.
+//!
+//! [`WebhookSink`] POSTs a JSON body rendered from a [`PayloadTemplate`].
+//! Presets cover Slack and Discord incoming webhooks; any other endpoint can
+//! take the generic payload or a custom template.
+//!
+//! [`Notifier::start`] gives each sink its own delivery task and bounded
+//! queue, and returns a [`NotifierHandle`] that only enqueues. Deliveries
+//! are retried with exponential backoff on network errors, 429 and 5xx
+//! responses without holding up the caller or the other sinks. A rate limit
+//! per sink keeps a flapping connection from flooding a channel:
+//! notifications over the limit, or arriving while a sink's queue is full,
+//! are dropped and counted in [`NotifierStats`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraken_sdk::notify::{Notifier, Severity, WebhookSink};
+//! use kraken_sdk::prelude::*;
+//! use std::time::Duration;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut client = KrakenClient::builder(["BTC/USD"]).connect().await?;
+//! let mut events = client.events().unwrap();
+//!
+//! let notifier = Notifier::new()
+//!     .with_sink(WebhookSink::slack("https://hooks.slack.com/services/T000/B000/XXXX"))
+//!     .with_min_severity(Severity::Warning)
+//!     .with_rate_limit(10, Duration::from_secs(60))
+//!     .start();
+//!
+//! while let Some(event) = events.recv().await {
+//!     notifier.publish(&event);
+//!     // ... the application's own handling of the event
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::alerts::AlertTrigger;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use kraken_ws::{ConnectionEvent, DisconnectReason, Event, EventReceiver, MarketEvent, SubscriptionEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Errors produced by notification sinks
+#[derive(Debug, Error)]
+pub enum NotifyError {
+    /// The request could not be sent or timed out
+    #[error("Notification request failed: {0}")]
+    Http(String),
+
+    /// The endpoint answered with a non-success status
+    #[error("Notification endpoint returned status {status}")]
+    Status {
+        /// HTTP status code
+        status: u16,
+    },
+}
+
+impl NotifyError {
+    /// Returns true if a later attempt may succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            NotifyError::Http(_) => true,
+            NotifyError::Status { status } => *status == 429 || *status >= 500,
+        }
+    }
+}
+
+/// How urgent a notification is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Informational
+    Info,
+    /// Needs attention
+    Warning,
+    /// Data or connectivity is compromised
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// A message for the outside world
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Notification {
+    /// Short headline
+    pub title: String,
+    /// Details
+    pub message: String,
+    /// Urgency
+    pub severity: Severity,
+    /// Trading pair the notification is about, if any
+    pub symbol: Option<String>,
+    /// When the notification was created
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Notification {
+    /// Create a notification stamped now
+    pub fn new(title: impl Into<String>, message: impl Into<String>, severity: Severity) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            severity,
+            symbol: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Attach the trading pair the notification is about
+    pub fn with_symbol(mut self, symbol: impl Into<String>) -> Self {
+        self.symbol = Some(symbol.into());
+        self
+    }
+
+    /// Notification for a health-relevant event, None for routine events
+    pub fn from_event(event: &Event) -> Option<Self> {
+        let notification = match event {
+            Event::Connection(ConnectionEvent::Disconnected { reason }) => {
+                if *reason == DisconnectReason::Shutdown {
+                    return None;
+                }
+                Self::new("Disconnected", format!("Connection lost: {:?}", reason), Severity::Warning)
+            }
+            Event::Connection(ConnectionEvent::ReconnectFailed { error }) => {
+                Self::new("Reconnect failed", format!("Giving up: {}", error), Severity::Critical)
+            }
+            Event::Connection(ConnectionEvent::Failover { endpoint, reason }) => Self::new(
+                "Failover",
+                format!("Switched to standby {} after: {}", endpoint, reason),
+                Severity::Warning,
+            ),
+            Event::Connection(ConnectionEvent::CircuitBreakerOpen { trips }) => Self::new(
+                "Circuit breaker open",
+                format!("Reconnects blocked (tripped {} times)", trips),
+                Severity::Critical,
+            ),
+            Event::Connection(ConnectionEvent::SystemStatusChanged { previous, current }) => {
+                let severity = if current.is_online() { Severity::Info } else { Severity::Warning };
+                let previous = previous.map_or_else(|| "unknown".to_string(), |status| status.to_string());
+                Self::new(
+                    "Exchange status changed",
+                    format!("{} -> {}", previous, current),
+                    severity,
+                )
+            }
+            Event::Connection(ConnectionEvent::PairStatusChanged { symbol, previous, current }) => {
+                let severity = if current.is_online() { Severity::Info } else { Severity::Warning };
+                let previous = previous.map_or_else(|| "unknown".to_string(), |status| status.to_string());
+                Self::new(
+                    "Pair status changed",
+                    format!("{}: {} -> {}", symbol, previous, current),
+                    severity,
+                )
+            }
+            Event::Connection(ConnectionEvent::MemoryCapReached { level_cap, limit_bytes, .. }) => Self::new(
+                "Orderbook memory cap reached",
+                format!("Keeping {} levels per side to stay under {} bytes", level_cap, limit_bytes),
+                Severity::Warning,
+            ),
+            Event::Connection(ConnectionEvent::ClockSkew { offset_us, .. }) => Self::new(
+                "Clock skew",
+                format!("Local clock is {} ms off the exchange", offset_us / 1000),
+                Severity::Warning,
+            ),
+            Event::Subscription(SubscriptionEvent::Rejected { channel, symbols, reason, .. }) => Self::new(
+                "Subscription rejected",
+                format!("{} {}: {}", channel, symbols.join(","), reason),
+                Severity::Warning,
+            ),
+            Event::Subscription(SubscriptionEvent::Stale { channel, symbol, silent_for }) => Self::new(
+                "Feed stale",
+                format!("No {} messages for {:?}", channel, silent_for),
+                Severity::Warning,
+            )
+            .with_symbol(symbol),
+            Event::Market(MarketEvent::ChecksumMismatch { symbol, expected, computed }) => Self::new(
+                "Checksum mismatch",
+                format!("Expected {}, computed {}; resyncing", expected, computed),
+                Severity::Warning,
+            )
+            .with_symbol(symbol),
+            Event::Market(MarketEvent::BookAuditFailed { symbol, violations }) => {
+                let kinds: Vec<_> = violations.iter().map(|violation| violation.kind()).collect();
+                Self::new(
+                    "Book audit failed",
+                    format!("Violations: {}", kinds.join(", ")),
+                    Severity::Critical,
+                )
+                .with_symbol(symbol)
+            }
+            _ => return None,
+        };
+        Some(notification)
+    }
+}
+
+impl From<&AlertTrigger> for Notification {
+    fn from(trigger: &AlertTrigger) -> Self {
+        Notification::new("Alert", trigger.to_string(), Severity::Warning).with_symbol(&trigger.rule.symbol)
+    }
+}
+
+/// JSON body with `{{placeholder}}` fields
+///
+/// Placeholders are `{{title}}`, `{{message}}`, `{{severity}}`, `{{symbol}}`
+/// (empty when absent) and `{{timestamp}}` (RFC 3339). Values are
+/// JSON-escaped but not quoted, so they belong inside string literals.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayloadTemplate(String);
+
+impl PayloadTemplate {
+    /// Use a custom template
+    pub fn new(template: impl Into<String>) -> Self {
+        Self(template.into())
+    }
+
+    /// Slack incoming webhook (`text` with the title in bold)
+    pub fn slack() -> Self {
+        Self::new(r#"{"text":"*[{{severity}}] {{title}}* {{symbol}}\n{{message}}"}"#)
+    }
+
+    /// Discord webhook (`content` with the title in bold)
+    pub fn discord() -> Self {
+        Self::new(r#"{"content":"**[{{severity}}] {{title}}** {{symbol}}\n{{message}}"}"#)
+    }
+
+    /// Flat object with every field
+    pub fn generic() -> Self {
+        Self::new(
+            r#"{"title":"{{title}}","message":"{{message}}","severity":"{{severity}}","symbol":"{{symbol}}","timestamp":"{{timestamp}}"}"#,
+        )
+    }
+
+    /// Fill in the placeholders
+    pub fn render(&self, notification: &Notification) -> String {
+        self.0
+            .replace("{{title}}", &json_escape(&notification.title))
+            .replace("{{message}}", &json_escape(&notification.message))
+            .replace("{{severity}}", &notification.severity.to_string())
+            .replace("{{symbol}}", &json_escape(notification.symbol.as_deref().unwrap_or("")))
+            .replace("{{timestamp}}", &notification.timestamp.to_rfc3339())
+    }
+}
+
+/// Escape a string for use inside a JSON string literal
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::to_string(value).unwrap_or_default();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// A place notifications are delivered to
+#[async_trait]
+pub trait NotificationSink: Send + Sync {
+    /// Deliver one notification (a single attempt; the notifier retries)
+    async fn deliver(&self, notification: &Notification) -> Result<(), NotifyError>;
+
+    /// Name for logs
+    fn name(&self) -> &str {
+        "sink"
+    }
+}
+
+/// POSTs a templated JSON body to a URL
+#[derive(Debug, Clone)]
+pub struct WebhookSink {
+    url: String,
+    template: PayloadTemplate,
+    headers: Vec<(String, String)>,
+    client: reqwest::Client,
+}
+
+impl WebhookSink {
+    /// Webhook with the generic payload
+    pub fn new(url: impl Into<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self {
+            url: url.into(),
+            template: PayloadTemplate::generic(),
+            headers: Vec::new(),
+            client,
+        }
+    }
+
+    /// Slack incoming webhook
+    pub fn slack(url: impl Into<String>) -> Self {
+        Self::new(url).with_template(PayloadTemplate::slack())
+    }
+
+    /// Discord webhook
+    pub fn discord(url: impl Into<String>) -> Self {
+        Self::new(url).with_template(PayloadTemplate::discord())
+    }
+
+    /// Use a different payload template
+    pub fn with_template(mut self, template: PayloadTemplate) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Send an extra header with every request (e.g. authorization)
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+#[async_trait]
+impl NotificationSink for WebhookSink {
+    async fn deliver(&self, notification: &Notification) -> Result<(), NotifyError> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .body(self.template.render(notification));
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        let response = request.send().await.map_err(|e| NotifyError::Http(e.to_string()))?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(NotifyError::Status {
+                status: status.as_u16(),
+            })
+        }
+    }
+
+    fn name(&self) -> &str {
+        "webhook"
+    }
+}
+
+/// Retry settings for failed deliveries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per notification, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry (doubles per retry)
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between retries
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Deliver once, never retry
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before retry number `retry` (1-based)
+    pub(crate) fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+/// Delivery counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NotifierStats {
+    /// Notifications delivered (per sink)
+    pub delivered: u64,
+    /// Notifications that failed after all retries (per sink)
+    pub failed: u64,
+    /// Notifications dropped by the rate limit (per sink)
+    pub rate_limited: u64,
+    /// Notifications dropped because a sink's queue was full (per sink)
+    pub queue_full: u64,
+    /// Notifications below the minimum severity
+    pub filtered: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    rate_limited: AtomicU64,
+    queue_full: AtomicU64,
+    filtered: AtomicU64,
+}
+
+impl Counters {
+    fn snapshot(&self) -> NotifierStats {
+        NotifierStats {
+            delivered: self.delivered.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            rate_limited: self.rate_limited.load(Ordering::Relaxed),
+            queue_full: self.queue_full.load(Ordering::Relaxed),
+            filtered: self.filtered.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Default number of notifications waiting per sink
+pub const DEFAULT_QUEUE_CAPACITY: usize = 64;
+
+/// Sinks, retry, rate limit and severity settings for a [`NotifierHandle`]
+pub struct Notifier {
+    sinks: Vec<Box<dyn NotificationSink>>,
+    retry: RetryPolicy,
+    rate_limit: Option<(usize, Duration)>,
+    min_severity: Severity,
+    queue_capacity: usize,
+}
+
+impl fmt::Debug for Notifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Notifier")
+            .field("sinks", &self.sinks.iter().map(|sink| sink.name()).collect::<Vec<_>>())
+            .field("retry", &self.retry)
+            .field("rate_limit", &self.rate_limit)
+            .field("min_severity", &self.min_severity)
+            .field("queue_capacity", &self.queue_capacity)
+            .finish()
+    }
+}
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Notifier {
+    /// Create a notifier without sinks
+    pub fn new() -> Self {
+        Self {
+            sinks: Vec::new(),
+            retry: RetryPolicy::default(),
+            rate_limit: None,
+            min_severity: Severity::Info,
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+        }
+    }
+
+    /// Add a sink
+    pub fn with_sink(mut self, sink: impl NotificationSink + 'static) -> Self {
+        self.sinks.push(Box::new(sink));
+        self
+    }
+
+    /// Set the retry policy
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Send at most `max` notifications per sink in any `per` window
+    pub fn with_rate_limit(mut self, max: usize, per: Duration) -> Self {
+        self.rate_limit = Some((max.max(1), per));
+        self
+    }
+
+    /// Drop notifications below this severity
+    pub fn with_min_severity(mut self, severity: Severity) -> Self {
+        self.min_severity = severity;
+        self
+    }
+
+    /// Notifications each sink may have waiting before new ones are dropped
+    pub fn with_queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity.max(1);
+        self
+    }
+
+    /// Spawn one delivery task per sink
+    ///
+    /// Must be called inside a Tokio runtime.
+    pub fn start(self) -> NotifierHandle {
+        let counters = Arc::new(Counters::default());
+        let mut sinks = Vec::with_capacity(self.sinks.len());
+        let mut workers = Vec::with_capacity(self.sinks.len());
+        for sink in self.sinks {
+            let (tx, rx) = mpsc::channel(self.queue_capacity);
+            workers.push(tokio::spawn(deliver_queued(sink, rx, self.retry, Arc::clone(&counters))));
+            sinks.push(SinkQueue {
+                tx,
+                recent: Mutex::new(VecDeque::new()),
+            });
+        }
+        NotifierHandle {
+            sinks,
+            workers,
+            rate_limit: self.rate_limit,
+            min_severity: self.min_severity,
+            counters,
+        }
+    }
+}
+
+struct SinkQueue {
+    tx: mpsc::Sender<Notification>,
+    /// Send times within the current rate limit window
+    recent: Mutex<VecDeque<Instant>>,
+}
+
+/// Queues notifications for a started [`Notifier`]
+///
+/// Nothing here waits on a sink: each sink has its own delivery task and
+/// queue, so a slow or failing endpoint holds up neither the caller nor the
+/// other sinks. Notifications that find a sink's queue full are dropped
+/// for that sink and counted in [`NotifierStats::queue_full`].
+///
+/// [`publish`](Self::publish) fits into an event loop that also does other
+/// work; [`forward`](Self::forward) is for a stream used only for alerting.
+pub struct NotifierHandle {
+    sinks: Vec<SinkQueue>,
+    workers: Vec<JoinHandle<()>>,
+    rate_limit: Option<(usize, Duration)>,
+    min_severity: Severity,
+    counters: Arc<Counters>,
+}
+
+impl fmt::Debug for NotifierHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NotifierHandle")
+            .field("sinks", &self.sinks.len())
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl NotifierHandle {
+    /// Delivery counters so far
+    pub fn stats(&self) -> NotifierStats {
+        self.counters.snapshot()
+    }
+
+    /// Queue a notification for every sink
+    pub fn notify(&self, notification: &Notification) {
+        if notification.severity < self.min_severity {
+            self.counters.filtered.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        let now = Instant::now();
+        for sink in &self.sinks {
+            if let Some((max, per)) = self.rate_limit {
+                let mut recent = sink.recent.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                while recent.front().is_some_and(|at| now.saturating_duration_since(*at) >= per) {
+                    recent.pop_front();
+                }
+                if recent.len() >= max {
+                    self.counters.rate_limited.fetch_add(1, Ordering::Relaxed);
+                    continue;
+                }
+                recent.push_back(now);
+            }
+            if sink.tx.try_send(notification.clone()).is_err() {
+                warn!("Notification queue full, dropping '{}'", notification.title);
+                self.counters.queue_full.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Queue a fired alert
+    pub fn notify_alert(&self, trigger: &AlertTrigger) {
+        self.notify(&Notification::from(trigger));
+    }
+
+    /// Queue a notification for a health event, if it warrants one
+    ///
+    /// See [`Notification::from_event`].
+    pub fn publish(&self, event: &Event) {
+        if let Some(notification) = Notification::from_event(event) {
+            self.notify(&notification);
+        }
+    }
+
+    /// Publish events until the stream ends
+    pub async fn forward(&self, mut events: EventReceiver) {
+        while let Some(event) = events.recv().await {
+            self.publish(&event);
+        }
+    }
+
+    /// Deliver everything already queued, then stop the delivery tasks
+    ///
+    /// Returns the final counters.
+    pub async fn close(self) -> NotifierStats {
+        drop(self.sinks);
+        for worker in self.workers {
+            let _ = worker.await;
+        }
+        self.counters.snapshot()
+    }
+}
+
+/// Deliver one sink's queue until every sender is gone
+async fn deliver_queued(
+    sink: Box<dyn NotificationSink>,
+    mut queue: mpsc::Receiver<Notification>,
+    retry: RetryPolicy,
+    counters: Arc<Counters>,
+) {
+    while let Some(notification) = queue.recv().await {
+        match deliver_with_retry(sink.as_ref(), &notification, &retry).await {
+            Ok(()) => {
+                counters.delivered.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                warn!("Dropping notification '{}' for {}: {}", notification.title, sink.name(), e);
+                counters.failed.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+    }
+}
+
+/// Deliver, retrying retryable errors with backoff
+async fn deliver_with_retry(
+    sink: &dyn NotificationSink,
+    notification: &Notification,
+    retry: &RetryPolicy,
+) -> Result<(), NotifyError> {
+    let mut attempt = 1;
+    loop {
+        match sink.deliver(notification).await {
+            Ok(()) => return Ok(()),
+            Err(e) if e.is_retryable() && attempt < retry.max_attempts => {
+                tokio::time::sleep(retry.backoff(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    /// Fails with `status` for the first `failures` attempts
+    struct FlakySink {
+        attempts: Arc<AtomicU32>,
+        failures: u32,
+        status: u16,
+    }
+
+    #[async_trait]
+    impl NotificationSink for FlakySink {
+        async fn deliver(&self, _notification: &Notification) -> Result<(), NotifyError> {
+            let attempt = self.attempts.fetch_add(1, Ordering::SeqCst);
+            if attempt < self.failures {
+                Err(NotifyError::Status { status: self.status })
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    fn flaky(failures: u32, status: u16) -> (FlakySink, Arc<AtomicU32>) {
+        let attempts = Arc::new(AtomicU32::new(0));
+        let sink = FlakySink {
+            attempts: Arc::clone(&attempts),
+            failures,
+            status,
+        };
+        (sink, attempts)
+    }
+
+    fn fast_retry() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+        }
+    }
+
+    #[test]
+    fn test_templates_render_valid_json() {
+        let notification = Notification::new("Feed \"stale\"", "line one\nline two", Severity::Critical).with_symbol("BTC/USD");
+        for template in [PayloadTemplate::slack(), PayloadTemplate::discord(), PayloadTemplate::generic()] {
+            let body: serde_json::Value = serde_json::from_str(&template.render(&notification)).unwrap();
+            assert!(body.is_object());
+        }
+        let body: serde_json::Value =
+            serde_json::from_str(&PayloadTemplate::generic().render(&notification)).unwrap();
+        assert_eq!(body["title"], "Feed \"stale\"");
+        assert_eq!(body["message"], "line one\nline two");
+        assert_eq!(body["severity"], "critical");
+        assert_eq!(body["symbol"], "BTC/USD");
+    }
+
+    #[tokio::test]
+    async fn test_retries_transient_failures_only() {
+        let (sink, attempts) = flaky(2, 503);
+        let notifier = Notifier::new().with_sink(sink).with_retry(fast_retry()).start();
+        notifier.notify(&Notification::new("t", "m", Severity::Warning));
+        assert_eq!(notifier.close().await.delivered, 1);
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        let (sink, attempts) = flaky(5, 400);
+        let notifier = Notifier::new().with_sink(sink).with_retry(fast_retry()).start();
+        notifier.notify(&Notification::new("t", "m", Severity::Warning));
+        assert_eq!(notifier.close().await.failed, 1);
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_and_severity_filter() {
+        let (sink, attempts) = flaky(0, 200);
+        let notifier = Notifier::new()
+            .with_sink(sink)
+            .with_rate_limit(2, Duration::from_secs(60))
+            .with_min_severity(Severity::Warning)
+            .start();
+
+        notifier.notify(&Notification::new("info", "m", Severity::Info));
+        for _ in 0..4 {
+            notifier.notify(&Notification::new("warn", "m", Severity::Warning));
+        }
+        let stats = notifier.close().await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!((stats.delivered, stats.rate_limited, stats.filtered), (2, 2, 1));
+
+        let event = Event::Connection(ConnectionEvent::ReconnectFailed { error: "gone".into() });
+        assert_eq!(Notification::from_event(&event).unwrap().severity, Severity::Critical);
+        let shutdown = Event::Connection(ConnectionEvent::Disconnected {
+            reason: DisconnectReason::Shutdown,
+        });
+        assert!(Notification::from_event(&shutdown).is_none());
+    }
+
+    /// Never answers until `release` is notified
+    struct StuckSink {
+        release: Arc<tokio::sync::Notify>,
+    }
+
+    #[async_trait]
+    impl NotificationSink for StuckSink {
+        async fn deliver(&self, _notification: &Notification) -> Result<(), NotifyError> {
+            self.release.notified().await;
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stuck_sink_holds_up_neither_caller_nor_other_sinks() {
+        let release = Arc::new(tokio::sync::Notify::new());
+        let (fast, attempts) = flaky(0, 200);
+        let notifier = Notifier::new()
+            .with_sink(StuckSink { release: Arc::clone(&release) })
+            .with_sink(fast)
+            .with_queue_capacity(1)
+            .start();
+
+        // The stuck sink takes the first notification and queues one more;
+        // the third finds its queue full
+        for _ in 0..3 {
+            notifier.notify(&Notification::new("t", "m", Severity::Warning));
+            tokio::task::yield_now().await;
+        }
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while attempts.load(Ordering::SeqCst) < 3 {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("fast sink delivered while the other was stuck");
+        assert_eq!(notifier.stats().queue_full, 1);
+
+        release.notify_one();
+        release.notify_one();
+        let stats = notifier.close().await;
+        assert_eq!((stats.delivered, stats.queue_full), (5, 1));
+    }
+}
@@ -0,0 +1,1086 @@
+//! Order lifecycle webhooks
+//!
+//! An [`OrderBridge`] turns private order events into flat [`OrderEvent`]s
+//! and forwards them to an [`OrderSink`], so back-office systems get order
+//! flow pushed to them instead of polling:
+//!
+//! | Kind | Produced by |
+//! |------|-------------|
+//! | `ack` | `new` executions, created orders |
+//! | `partial_fill` | trades that leave quantity open |
+//! | `fill` | the trade that completes the order |
+//! | `cancel` | cancellations |
+//! | `expire` | expiries |
+//! | `amend` | amended or modified orders |
+//! | `reject` | rejected orders |
+//!
+//! [`WebhookOrderSink`] POSTs each event to a URL, either as the event's own
+//! JSON or rendered from an [`OrderTemplate`]. A message queue is one
+//! [`OrderSink`] implementation away, and can use the same templates.
+//!
+//! # Delivery
+//!
+//! Delivery is at least once. [`OrderBridge::start`] spawns a journal task
+//! and a delivery task and returns an [`OrderBridgeHandle`] that only
+//! enqueues. Every event is appended to a [`DurableQueue`] (a JSON-lines
+//! journal, synced to disk on the blocking pool) as soon as it is
+//! published, whatever the endpoint is doing, and only acknowledged there
+//! once the sink accepts it, so events survive endpoint outages and process
+//! restarts. Events are delivered in order: while the oldest one keeps
+//! failing with a retryable error, newer ones wait behind it and the bridge
+//! retries every few seconds. Events the endpoint rejects outright (4xx
+//! other than 429) are dropped and counted in [`OrderBridgeStats::failed`].
+//!
+//! A restart can redeliver an event whose acknowledgement was lost, so
+//! receivers should deduplicate on [`OrderEvent::key`].
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraken_sdk::order_hooks::{DurableQueue, OrderBridge, OrderTemplate, WebhookOrderSink};
+//! use kraken_sdk::prelude::*;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut client = KrakenClient::builder(["BTC/USD"]).connect().await?;
+//! let mut events = client.events().unwrap();
+//!
+//! let sink = WebhookOrderSink::new("https://backoffice.example.com/orders")
+//!     .with_header("Authorization", "Bearer token")
+//!     .with_template(OrderTemplate::new(
+//!         r#"{"type":"{{kind}}","id":"{{order_id}}","filled":"{{filled_qty}}"}"#,
+//!     ));
+//! let queue = DurableQueue::open("orders.queue.jsonl")?;
+//! let bridge = OrderBridge::new(sink, queue).start();
+//!
+//! while let Some(event) = events.recv().await {
+//!     bridge.publish(&event);
+//!     // ... the application's own handling of the event
+//! }
+//! bridge.close().await;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::notify::{NotifyError, RetryPolicy};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use kraken_types::{Decimal, Side};
+use kraken_ws::{Event, EventReceiver, ExecutionType, OrderChange, OrderStatus, PrivateEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use thiserror::Error;
+use tokio::sync::{mpsc, watch, Notify};
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+/// Acknowledged entries the journal holds before it is rewritten
+pub const DEFAULT_COMPACT_AFTER: usize = 1000;
+
+/// Delivery keys remembered for duplicate suppression
+const RECENT_KEYS: usize = 1024;
+
+/// Errors produced by the durable queue
+#[derive(Debug, Error)]
+pub enum QueueError {
+    /// The journal could not be read or written
+    #[error("Queue journal I/O failed: {0}")]
+    Io(#[from] io::Error),
+
+    /// An event could not be encoded
+    #[error("Queue entry could not be encoded: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Stage of an order's life
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OrderEventKind {
+    /// The exchange accepted the order
+    Ack,
+    /// Part of the order filled
+    PartialFill,
+    /// The order filled completely
+    Fill,
+    /// The order was canceled
+    Cancel,
+    /// The order expired
+    Expire,
+    /// The order's price or quantity changed
+    Amend,
+    /// The exchange refused the order
+    Reject,
+}
+
+impl OrderEventKind {
+    /// Returns true if the order can't change any more
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Fill | Self::Cancel | Self::Expire | Self::Reject)
+    }
+}
+
+impl fmt::Display for OrderEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Ack => write!(f, "ack"),
+            Self::PartialFill => write!(f, "partial_fill"),
+            Self::Fill => write!(f, "fill"),
+            Self::Cancel => write!(f, "cancel"),
+            Self::Expire => write!(f, "expire"),
+            Self::Amend => write!(f, "amend"),
+            Self::Reject => write!(f, "reject"),
+        }
+    }
+}
+
+/// One order lifecycle step, as delivered to sinks
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderEvent {
+    /// Position in the queue, assigned by [`DurableQueue::push`]
+    pub seq: u64,
+    /// What happened
+    pub kind: OrderEventKind,
+    /// Exchange order ID
+    pub order_id: String,
+    /// Client order ID, if one was set
+    pub cl_ord_id: Option<String>,
+    /// Trading pair
+    pub symbol: String,
+    /// Order side
+    pub side: Side,
+    /// Order type (limit, market, ...)
+    pub order_type: String,
+    /// Original order quantity
+    pub order_qty: Option<Decimal>,
+    /// Limit price
+    pub limit_price: Option<Decimal>,
+    /// Cumulative filled quantity
+    pub filled_qty: Decimal,
+    /// Average fill price
+    pub avg_price: Option<Decimal>,
+    /// Quantity of this fill
+    pub last_qty: Option<Decimal>,
+    /// Price of this fill
+    pub last_price: Option<Decimal>,
+    /// Exchange timestamp
+    pub timestamp: String,
+    /// When the bridge saw the event
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl OrderEvent {
+    /// Lifecycle step for a private event, if it is one
+    ///
+    /// Pending executions, balances and unrecognised execution types produce
+    /// none.
+    pub fn from_private(event: &PrivateEvent) -> Option<Self> {
+        match event {
+            PrivateEvent::Execution { data, exec_type } => {
+                let status = data.order_status.as_deref().unwrap_or("").to_lowercase();
+                let kind = match exec_type {
+                    _ if status == "rejected" => OrderEventKind::Reject,
+                    ExecutionType::New => OrderEventKind::Ack,
+                    ExecutionType::Trade => {
+                        let complete = match (data.cum_qty, data.order_qty) {
+                            (Some(cum), Some(qty)) => cum >= qty,
+                            _ => false,
+                        };
+                        if status == "filled" || complete {
+                            OrderEventKind::Fill
+                        } else {
+                            OrderEventKind::PartialFill
+                        }
+                    }
+                    ExecutionType::Canceled => OrderEventKind::Cancel,
+                    ExecutionType::Expired => OrderEventKind::Expire,
+                    ExecutionType::Amended => OrderEventKind::Amend,
+                    ExecutionType::Unknown if data.exec_type.eq_ignore_ascii_case("rejected") => {
+                        OrderEventKind::Reject
+                    }
+                    ExecutionType::Pending | ExecutionType::Unknown => return None,
+                };
+                Some(Self {
+                    seq: 0,
+                    kind,
+                    order_id: data.order_id.clone(),
+                    cl_ord_id: data.cl_ord_id.clone(),
+                    symbol: data.symbol.clone(),
+                    side: data.side,
+                    order_type: data.order_type.clone(),
+                    order_qty: data.order_qty,
+                    limit_price: data.limit_price,
+                    filled_qty: data.cum_qty.unwrap_or(Decimal::ZERO),
+                    avg_price: data.avg_price,
+                    last_qty: data.last_qty,
+                    last_price: data.last_price,
+                    timestamp: data.timestamp.clone(),
+                    recorded_at: Utc::now(),
+                })
+            }
+            PrivateEvent::OrderUpdate { order, change } => {
+                let kind = match change {
+                    _ if order.status == OrderStatus::Rejected => OrderEventKind::Reject,
+                    OrderChange::Created => OrderEventKind::Ack,
+                    OrderChange::PartialFill => OrderEventKind::PartialFill,
+                    OrderChange::FullFill => OrderEventKind::Fill,
+                    OrderChange::Canceled => OrderEventKind::Cancel,
+                    OrderChange::Expired => OrderEventKind::Expire,
+                    OrderChange::Modified => OrderEventKind::Amend,
+                };
+                let last_fill = order.fills.last().filter(|_| {
+                    matches!(kind, OrderEventKind::PartialFill | OrderEventKind::Fill)
+                });
+                Some(Self {
+                    seq: 0,
+                    kind,
+                    order_id: order.order_id.clone(),
+                    cl_ord_id: None,
+                    symbol: order.symbol.clone(),
+                    side: order.side,
+                    order_type: order.order_type.clone(),
+                    order_qty: Some(order.order_qty),
+                    limit_price: order.limit_price,
+                    filled_qty: order.filled_qty,
+                    avg_price: order.avg_price,
+                    last_qty: last_fill.map(|fill| fill.qty),
+                    last_price: last_fill.map(|fill| fill.price),
+                    timestamp: order.last_update.clone(),
+                    recorded_at: Utc::now(),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Lifecycle step for any event, if it is one
+    pub fn from_event(event: &Event) -> Option<Self> {
+        match event {
+            Event::Private(private) => Self::from_private(private),
+            _ => None,
+        }
+    }
+
+    /// Idempotency key, stable across redeliveries
+    ///
+    /// The same step reported twice (as an execution and as an order update,
+    /// or again after a restart) has the same key.
+    pub fn key(&self) -> String {
+        format!("{}:{}:{}:{}", self.order_id, self.kind, self.filled_qty.normalize(), self.timestamp)
+    }
+}
+
+/// JSON body template for order events
+///
+/// Placeholders: `{{seq}}`, `{{key}}`, `{{kind}}`, `{{order_id}}`,
+/// `{{cl_ord_id}}`, `{{symbol}}`, `{{side}}`, `{{order_type}}`,
+/// `{{order_qty}}`, `{{limit_price}}`, `{{filled_qty}}`, `{{avg_price}}`,
+/// `{{last_qty}}`, `{{last_price}}`, `{{timestamp}}` and `{{recorded_at}}`.
+/// Absent values render empty. Values are JSON-escaped but not quoted, so
+/// they belong inside string literals.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderTemplate(Option<String>);
+
+impl OrderTemplate {
+    /// Use a custom template
+    pub fn new(template: impl Into<String>) -> Self {
+        Self(Some(template.into()))
+    }
+
+    /// The event serialized as-is
+    pub fn json() -> Self {
+        Self(None)
+    }
+
+    /// Render the body for an event
+    pub fn render(&self, event: &OrderEvent) -> String {
+        let Some(template) = &self.0 else {
+            return serde_json::to_string(event).unwrap_or_default();
+        };
+        let decimal = |value: Option<Decimal>| value.map(|v| v.to_string()).unwrap_or_default();
+        template
+            .replace("{{seq}}", &event.seq.to_string())
+            .replace("{{key}}", &json_escape(&event.key()))
+            .replace("{{kind}}", &event.kind.to_string())
+            .replace("{{order_id}}", &json_escape(&event.order_id))
+            .replace("{{cl_ord_id}}", &json_escape(event.cl_ord_id.as_deref().unwrap_or("")))
+            .replace("{{symbol}}", &json_escape(&event.symbol))
+            .replace("{{side}}", if event.side == Side::Buy { "buy" } else { "sell" })
+            .replace("{{order_type}}", &json_escape(&event.order_type))
+            .replace("{{order_qty}}", &decimal(event.order_qty))
+            .replace("{{limit_price}}", &decimal(event.limit_price))
+            .replace("{{filled_qty}}", &event.filled_qty.to_string())
+            .replace("{{avg_price}}", &decimal(event.avg_price))
+            .replace("{{last_qty}}", &decimal(event.last_qty))
+            .replace("{{last_price}}", &decimal(event.last_price))
+            .replace("{{timestamp}}", &json_escape(&event.timestamp))
+            .replace("{{recorded_at}}", &event.recorded_at.to_rfc3339())
+    }
+}
+
+/// Escape a string for use inside a JSON string literal
+fn json_escape(value: &str) -> String {
+    let quoted = serde_json::to_string(value).unwrap_or_default();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+/// A place order events are delivered to
+#[async_trait]
+pub trait OrderSink: Send + Sync {
+    /// Deliver one event (a single attempt; the bridge retries)
+    async fn deliver(&self, event: &OrderEvent) -> Result<(), NotifyError>;
+
+    /// Name for logs
+    fn name(&self) -> &str {
+        "sink"
+    }
+}
+
+/// POSTs each order event to a URL
+#[derive(Debug, Clone)]
+pub struct WebhookOrderSink {
+    url: String,
+    template: OrderTemplate,
+    headers: Vec<(String, String)>,
+    client: reqwest::Client,
+}
+
+impl WebhookOrderSink {
+    /// Webhook receiving the event's own JSON
+    pub fn new(url: impl Into<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .unwrap_or_default();
+        Self {
+            url: url.into(),
+            template: OrderTemplate::json(),
+            headers: Vec::new(),
+            client,
+        }
+    }
+
+    /// Use a different payload template
+    pub fn with_template(mut self, template: OrderTemplate) -> Self {
+        self.template = template;
+        self
+    }
+
+    /// Send an extra header with every request (e.g. authorization)
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+#[async_trait]
+impl OrderSink for WebhookOrderSink {
+    async fn deliver(&self, event: &OrderEvent) -> Result<(), NotifyError> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("Content-Type", "application/json")
+            .header("Idempotency-Key", event.key())
+            .body(self.template.render(event));
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        let response = request.send().await.map_err(|e| NotifyError::Http(e.to_string()))?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            Err(NotifyError::Status {
+                status: status.as_u16(),
+            })
+        }
+    }
+
+    fn name(&self) -> &str {
+        "webhook"
+    }
+}
+
+/// Journal line: an event entering the queue, or one leaving it
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum JournalEntry {
+    Enqueue(Box<OrderEvent>),
+    Ack(u64),
+}
+
+/// Undelivered order events, journaled to disk
+///
+/// Enqueued events are synced before [`DurableQueue::push`] returns.
+/// Acknowledgements are written but not synced: losing one to a crash only
+/// means a redelivery. The journal is rewritten with just the pending events
+/// on open and every [`DEFAULT_COMPACT_AFTER`] acknowledgements.
+#[derive(Debug)]
+pub struct DurableQueue {
+    path: Option<PathBuf>,
+    file: Option<File>,
+    pending: VecDeque<OrderEvent>,
+    next_seq: u64,
+    acked: usize,
+    compact_after: usize,
+}
+
+impl DurableQueue {
+    /// Open or create the journal at `path`, reloading pending events
+    ///
+    /// A torn last line from a crash is skipped with a warning.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, QueueError> {
+        let path = path.as_ref().to_path_buf();
+        let mut entries = BTreeMap::new();
+        let mut next_seq = 1;
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                for line in text.lines().filter(|line| !line.trim().is_empty()) {
+                    match serde_json::from_str(line) {
+                        Ok(JournalEntry::Enqueue(event)) => {
+                            next_seq = next_seq.max(event.seq + 1);
+                            entries.insert(event.seq, *event);
+                        }
+                        Ok(JournalEntry::Ack(seq)) => {
+                            entries.remove(&seq);
+                        }
+                        Err(e) => warn!("Skipping unreadable queue entry in {}: {}", path.display(), e),
+                    }
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e.into()),
+        }
+        let mut queue = Self {
+            path: Some(path),
+            file: None,
+            pending: entries.into_values().collect(),
+            next_seq,
+            acked: 0,
+            compact_after: DEFAULT_COMPACT_AFTER,
+        };
+        queue.compact()?;
+        Ok(queue)
+    }
+
+    /// Queue that keeps events in memory only
+    ///
+    /// Delivery is still retried in order, but pending events are lost when
+    /// the process exits.
+    pub fn in_memory() -> Self {
+        Self {
+            path: None,
+            file: None,
+            pending: VecDeque::new(),
+            next_seq: 1,
+            acked: 0,
+            compact_after: DEFAULT_COMPACT_AFTER,
+        }
+    }
+
+    /// Rewrite the journal after this many acknowledgements
+    pub fn with_compact_after(mut self, acks: usize) -> Self {
+        self.compact_after = acks.max(1);
+        self
+    }
+
+    /// Add an event, assigning its sequence number
+    ///
+    /// The event is queued even if the journal write fails; the error means
+    /// it would not survive a restart.
+    pub fn push(&mut self, mut event: OrderEvent) -> Result<u64, QueueError> {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        event.seq = seq;
+        let written = self.append(&JournalEntry::Enqueue(Box::new(event.clone())), true);
+        self.pending.push_back(event);
+        written.map(|()| seq)
+    }
+
+    /// Oldest undelivered event
+    pub fn front(&self) -> Option<&OrderEvent> {
+        self.pending.front()
+    }
+
+    /// Remove a delivered event
+    ///
+    /// Returns false if no pending event has that sequence number.
+    pub fn ack(&mut self, seq: u64) -> Result<bool, QueueError> {
+        let Some(index) = self.pending.iter().position(|event| event.seq == seq) else {
+            return Ok(false);
+        };
+        self.pending.remove(index);
+        self.append(&JournalEntry::Ack(seq), false)?;
+        self.acked += 1;
+        if self.acked >= self.compact_after {
+            self.compact()?;
+        }
+        Ok(true)
+    }
+
+    /// Undelivered events, oldest first
+    pub fn pending(&self) -> impl Iterator<Item = &OrderEvent> {
+        self.pending.iter()
+    }
+
+    /// Number of undelivered events
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns true if every event has been delivered
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Journal location, if the queue is durable
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    fn append(&mut self, entry: &JournalEntry, sync: bool) -> Result<(), QueueError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        if self.file.is_none() {
+            self.file = Some(OpenOptions::new().create(true).append(true).open(path)?);
+        }
+        if let Some(file) = &mut self.file {
+            file.write_all(line.as_bytes())?;
+            if sync {
+                file.sync_data()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Replace the journal with one holding only pending events
+    fn compact(&mut self) -> Result<(), QueueError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let tmp = path.with_extension("compact");
+        let mut out = String::new();
+        for event in &self.pending {
+            out.push_str(&serde_json::to_string(&JournalEntry::Enqueue(Box::new(event.clone())))?);
+            out.push('\n');
+        }
+        {
+            let mut file = File::create(&tmp)?;
+            file.write_all(out.as_bytes())?;
+            file.sync_all()?;
+        }
+        self.file = None;
+        fs::rename(&tmp, path)?;
+        self.acked = 0;
+        Ok(())
+    }
+}
+
+/// Shortest interval between retries of a failing event
+pub const MIN_REDELIVER_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Delivery counters
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OrderBridgeStats {
+    /// Events added to the queue
+    pub enqueued: u64,
+    /// Events the sink accepted
+    pub delivered: u64,
+    /// Events dropped after a non-retryable error
+    pub failed: u64,
+    /// Events skipped because the same step was already queued
+    pub duplicates: u64,
+    /// Journal writes that failed (the event stayed queued in memory)
+    pub journal_errors: u64,
+    /// Events still waiting for delivery
+    pub pending: usize,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    enqueued: AtomicU64,
+    delivered: AtomicU64,
+    failed: AtomicU64,
+    duplicates: AtomicU64,
+    journal_errors: AtomicU64,
+    pending: AtomicUsize,
+}
+
+impl Counters {
+    fn snapshot(&self) -> OrderBridgeStats {
+        OrderBridgeStats {
+            enqueued: self.enqueued.load(Ordering::Relaxed),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+            duplicates: self.duplicates.load(Ordering::Relaxed),
+            journal_errors: self.journal_errors.load(Ordering::Relaxed),
+            pending: self.pending.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Sink, queue and retry settings for an [`OrderBridgeHandle`]
+pub struct OrderBridge {
+    sink: Box<dyn OrderSink>,
+    queue: DurableQueue,
+    retry: RetryPolicy,
+    redeliver_every: Duration,
+}
+
+impl fmt::Debug for OrderBridge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OrderBridge")
+            .field("sink", &self.sink.name())
+            .field("queue", &self.queue.path())
+            .field("retry", &self.retry)
+            .field("redeliver_every", &self.redeliver_every)
+            .finish()
+    }
+}
+
+impl OrderBridge {
+    /// Bridge events to `sink`, queueing them in `queue`
+    ///
+    /// Events already pending in the queue are delivered first.
+    pub fn new(sink: impl OrderSink + 'static, queue: DurableQueue) -> Self {
+        Self {
+            sink: Box::new(sink),
+            queue,
+            retry: RetryPolicy::default(),
+            redeliver_every: Duration::from_secs(5),
+        }
+    }
+
+    /// Retry settings for each delivery attempt round
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// How often to retry while the oldest event can't be delivered
+    ///
+    /// Raised to [`MIN_REDELIVER_INTERVAL`] if shorter.
+    pub fn with_redeliver_interval(mut self, interval: Duration) -> Self {
+        self.redeliver_every = interval.max(MIN_REDELIVER_INTERVAL);
+        self
+    }
+
+    /// Spawn the journal and delivery tasks
+    ///
+    /// Must be called inside a Tokio runtime.
+    pub fn start(self) -> OrderBridgeHandle {
+        let counters = Arc::new(Counters::default());
+        counters.pending.store(self.queue.len(), Ordering::Relaxed);
+        let queue = Arc::new(Mutex::new(self.queue));
+        let wake = Arc::new(Notify::new());
+        let (closed_tx, closed_rx) = watch::channel(false);
+        let (tx, rx) = mpsc::unbounded_channel();
+
+        let journal = tokio::spawn(journal_events(
+            rx,
+            Arc::clone(&queue),
+            Arc::clone(&counters),
+            Arc::clone(&wake),
+            closed_tx,
+        ));
+        let delivery = tokio::spawn(deliver_pending(
+            self.sink,
+            queue,
+            self.retry,
+            self.redeliver_every,
+            Arc::clone(&counters),
+            wake,
+            closed_rx,
+        ));
+        OrderBridgeHandle {
+            tx,
+            journal,
+            delivery,
+            counters,
+        }
+    }
+}
+
+/// Queues order events for a started [`OrderBridge`]
+///
+/// Nothing here waits on the sink or the disk: events are journaled by one
+/// task and delivered by another, so an endpoint outage holds up neither
+/// the caller nor the journaling of newer events.
+///
+/// [`publish`](Self::publish) fits into an event loop that also does other
+/// work; [`forward`](Self::forward) is for a stream used only for the bridge.
+pub struct OrderBridgeHandle {
+    tx: mpsc::UnboundedSender<OrderEvent>,
+    journal: JoinHandle<()>,
+    delivery: JoinHandle<()>,
+    counters: Arc<Counters>,
+}
+
+impl fmt::Debug for OrderBridgeHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OrderBridgeHandle")
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl OrderBridgeHandle {
+    /// Delivery counters so far
+    pub fn stats(&self) -> OrderBridgeStats {
+        self.counters.snapshot()
+    }
+
+    /// Queue the lifecycle step in `event`, if any
+    pub fn publish(&self, event: &Event) {
+        if let Some(order_event) = OrderEvent::from_event(event) {
+            let _ = self.tx.send(order_event);
+        }
+    }
+
+    /// Publish events until the stream ends
+    pub async fn forward(&self, mut events: EventReceiver) {
+        while let Some(event) = events.recv().await {
+            self.publish(&event);
+        }
+    }
+
+    /// Journal everything published, make a last delivery attempt, then stop
+    ///
+    /// Events still undelivered stay in the queue for the next start.
+    /// Returns the final counters.
+    pub async fn close(self) -> OrderBridgeStats {
+        drop(self.tx);
+        let _ = self.journal.await;
+        let _ = self.delivery.await;
+        self.counters.snapshot()
+    }
+}
+
+/// Run a queue operation on the blocking pool, since journal writes sync to disk
+async fn with_queue<R, F>(queue: &Arc<Mutex<DurableQueue>>, f: F) -> R
+where
+    R: Send + 'static,
+    F: FnOnce(&mut DurableQueue) -> R + Send + 'static,
+{
+    let queue = Arc::clone(queue);
+    tokio::task::spawn_blocking(move || {
+        f(&mut queue.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    })
+    .await
+    .expect("order queue task panicked")
+}
+
+/// Journal published events as they arrive, skipping repeated steps
+async fn journal_events(
+    mut events: mpsc::UnboundedReceiver<OrderEvent>,
+    queue: Arc<Mutex<DurableQueue>>,
+    counters: Arc<Counters>,
+    wake: Arc<Notify>,
+    closed: watch::Sender<bool>,
+) {
+    let mut recent = VecDeque::new();
+    let mut recent_keys = HashSet::new();
+    while let Some(event) = events.recv().await {
+        let key = event.key();
+        if recent_keys.contains(&key) {
+            counters.duplicates.fetch_add(1, Ordering::Relaxed);
+            continue;
+        }
+        if recent.len() >= RECENT_KEYS {
+            if let Some(oldest) = recent.pop_front() {
+                recent_keys.remove(&oldest);
+            }
+        }
+        recent.push_back(key.clone());
+        recent_keys.insert(key);
+
+        counters.enqueued.fetch_add(1, Ordering::Relaxed);
+        let (pushed, pending) =
+            with_queue(&queue, move |queue| (queue.push(event), queue.len())).await;
+        counters.pending.store(pending, Ordering::Relaxed);
+        if let Err(e) = pushed {
+            warn!("Order event queued in memory only: {}", e);
+            counters.journal_errors.fetch_add(1, Ordering::Relaxed);
+        }
+        wake.notify_one();
+    }
+    closed.send_replace(true);
+}
+
+/// Deliver queued events in order until the journal task has finished
+///
+/// New events trigger a delivery round at once; while the oldest event
+/// keeps failing, rounds only run on the redeliver interval.
+async fn deliver_pending(
+    sink: Box<dyn OrderSink>,
+    queue: Arc<Mutex<DurableQueue>>,
+    retry: RetryPolicy,
+    redeliver_every: Duration,
+    counters: Arc<Counters>,
+    wake: Arc<Notify>,
+    mut closed: watch::Receiver<bool>,
+) {
+    let mut redeliver = tokio::time::interval(redeliver_every);
+    redeliver.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    redeliver.tick().await;
+    loop {
+        let closing = *closed.borrow_and_update();
+        let drained = flush(sink.as_ref(), &queue, &retry, &counters).await;
+        if closing {
+            return;
+        }
+        if drained {
+            tokio::select! {
+                _ = wake.notified() => {}
+                _ = redeliver.tick() => {}
+                _ = closed.changed() => {}
+            }
+        } else {
+            tokio::select! {
+                _ = redeliver.tick() => {}
+                _ = closed.changed() => {}
+            }
+        }
+    }
+}
+
+/// Deliver pending events in order until one fails with a retryable error
+///
+/// Returns true if the queue was emptied.
+async fn flush(
+    sink: &dyn OrderSink,
+    queue: &Arc<Mutex<DurableQueue>>,
+    retry: &RetryPolicy,
+    counters: &Counters,
+) -> bool {
+    loop {
+        let Some(event) = with_queue(queue, |queue| queue.front().cloned()).await else {
+            return true;
+        };
+        match deliver_with_retry(sink, &event, retry).await {
+            Ok(()) => {
+                counters.delivered.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) if e.is_retryable() => {
+                warn!("Order event {} for {} still pending: {}", event.seq, sink.name(), e);
+                return false;
+            }
+            Err(e) => {
+                warn!(
+                    "Dropping order event {} ({}) for {}: {}",
+                    event.seq,
+                    event.key(),
+                    sink.name(),
+                    e
+                );
+                counters.failed.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+        let seq = event.seq;
+        let (acked, pending) =
+            with_queue(queue, move |queue| (queue.ack(seq), queue.len())).await;
+        counters.pending.store(pending, Ordering::Relaxed);
+        if let Err(e) = acked {
+            warn!("Failed to journal acknowledgement of order event {}: {}", seq, e);
+            counters.journal_errors.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Deliver, retrying retryable errors with backoff
+async fn deliver_with_retry(
+    sink: &dyn OrderSink,
+    event: &OrderEvent,
+    retry: &RetryPolicy,
+) -> Result<(), NotifyError> {
+    let mut attempt = 1;
+    loop {
+        match sink.deliver(event).await {
+            Ok(()) => return Ok(()),
+            Err(e) if e.is_retryable() && attempt < retry.max_attempts => {
+                tokio::time::sleep(retry.backoff(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_types::ExecutionData;
+    use std::sync::atomic::AtomicU32;
+
+    /// Sequence numbers in delivery order
+    type Recorded = Arc<Mutex<Vec<u64>>>;
+
+    /// Fails with `status` for the first `failures` attempts, then records
+    struct FlakySink {
+        attempts: Arc<AtomicU32>,
+        failures: u32,
+        status: u16,
+        delivered: Recorded,
+    }
+
+    #[async_trait]
+    impl OrderSink for FlakySink {
+        async fn deliver(&self, event: &OrderEvent) -> Result<(), NotifyError> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < self.failures {
+                return Err(NotifyError::Status { status: self.status });
+            }
+            self.delivered.lock().unwrap().push(event.seq);
+            Ok(())
+        }
+    }
+
+    fn execution(exec_type: &str, order_id: &str, cum_qty: &str, status: &str) -> Event {
+        let data: ExecutionData = serde_json::from_value(serde_json::json!({
+            "exec_type": exec_type,
+            "order_id": order_id,
+            "symbol": "BTC/USD",
+            "side": "buy",
+            "order_type": "limit",
+            "order_qty": "1",
+            "limit_price": "50000",
+            "cum_qty": cum_qty,
+            "order_status": status,
+            "timestamp": "2024-01-01T00:00:00.000000Z",
+        }))
+        .unwrap();
+        let exec_type = ExecutionType::parse(&data.exec_type);
+        PrivateEvent::Execution { data, exec_type }.into()
+    }
+
+    fn temp_journal(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("havklo-order-hooks-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("queue.jsonl")
+    }
+
+    #[test]
+    fn test_lifecycle_mapping_and_templates() {
+        let kind = |event: &Event| OrderEvent::from_event(event).map(|e| e.kind);
+        assert_eq!(kind(&execution("new", "O1", "0", "new")), Some(OrderEventKind::Ack));
+        assert_eq!(kind(&execution("trade", "O1", "0.4", "partially_filled")), Some(OrderEventKind::PartialFill));
+        assert_eq!(kind(&execution("trade", "O1", "1", "filled")), Some(OrderEventKind::Fill));
+        assert_eq!(kind(&execution("canceled", "O1", "0.4", "canceled")), Some(OrderEventKind::Cancel));
+        assert_eq!(kind(&execution("new", "O2", "0", "rejected")), Some(OrderEventKind::Reject));
+        assert_eq!(kind(&execution("pending_new", "O3", "0", "pending")), None);
+
+        let event = OrderEvent::from_event(&execution("trade", "O\"1", "0.4", "partially_filled")).unwrap();
+        let body: serde_json::Value = serde_json::from_str(&OrderTemplate::json().render(&event)).unwrap();
+        assert_eq!(body["kind"], "partial_fill");
+        assert_eq!(body["side"], "buy");
+        let custom = OrderTemplate::new(r#"{"id":"{{order_id}}","kind":"{{kind}}","filled":"{{filled_qty}}","px":"{{avg_price}}"}"#);
+        let body: serde_json::Value = serde_json::from_str(&custom.render(&event)).unwrap();
+        assert_eq!(body["id"], "O\"1");
+        assert_eq!(body["filled"], "0.4");
+        assert_eq!(body["px"], "");
+    }
+
+    #[tokio::test]
+    async fn test_at_least_once_across_outage_and_restart() {
+        let path = temp_journal("restart");
+        let delivered: Recorded = Arc::new(Mutex::new(Vec::new()));
+        let retry = RetryPolicy {
+            max_attempts: 2,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(1),
+        };
+
+        // Endpoint down: events stay queued, in order, and the duplicate is skipped
+        let down = FlakySink {
+            attempts: Arc::new(AtomicU32::new(0)),
+            failures: u32::MAX,
+            status: 503,
+            delivered: Arc::clone(&delivered),
+        };
+        let bridge = OrderBridge::new(down, DurableQueue::open(&path).unwrap())
+            .with_retry(retry)
+            .start();
+        bridge.publish(&execution("new", "O1", "0", "new"));
+        bridge.publish(&execution("new", "O1", "0", "new"));
+        bridge.publish(&execution("trade", "O1", "1", "filled"));
+        let stats = bridge.close().await;
+        assert_eq!((stats.enqueued, stats.duplicates, stats.pending), (2, 1, 2));
+
+        // After a restart the backlog is delivered first, then new events
+        let up = FlakySink {
+            attempts: Arc::new(AtomicU32::new(0)),
+            failures: 1,
+            status: 429,
+            delivered: Arc::clone(&delivered),
+        };
+        let queue = DurableQueue::open(&path).unwrap();
+        assert_eq!(queue.len(), 2);
+        let bridge = OrderBridge::new(up, queue).with_retry(retry).start();
+        bridge.publish(&execution("new", "O2", "0", "new"));
+        let stats = bridge.close().await;
+        assert_eq!((stats.delivered, stats.pending), (3, 0));
+        assert_eq!(*delivered.lock().unwrap(), vec![1, 2, 3]);
+        assert!(DurableQueue::open(&path).unwrap().is_empty());
+
+        // Non-retryable rejections are dropped rather than blocking the queue
+        let rejecting = FlakySink {
+            attempts: Arc::new(AtomicU32::new(0)),
+            failures: 1,
+            status: 400,
+            delivered: Arc::clone(&delivered),
+        };
+        let bridge = OrderBridge::new(rejecting, DurableQueue::in_memory())
+            .with_retry(retry)
+            .start();
+        bridge.publish(&execution("new", "O3", "0", "new"));
+        bridge.publish(&execution("canceled", "O3", "0", "canceled"));
+        let stats = bridge.close().await;
+        assert_eq!((stats.failed, stats.delivered, stats.pending), (1, 1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_events_are_journaled_while_delivery_is_stuck() {
+        /// Holds every delivery until released
+        struct StuckSink(watch::Receiver<bool>);
+
+        #[async_trait]
+        impl OrderSink for StuckSink {
+            async fn deliver(&self, _event: &OrderEvent) -> Result<(), NotifyError> {
+                let mut released = self.0.clone();
+                let _ = released.wait_for(|released| *released).await;
+                Ok(())
+            }
+        }
+
+        let path = temp_journal("stuck");
+        let (release, released) = watch::channel(false);
+        let bridge = OrderBridge::new(StuckSink(released), DurableQueue::open(&path).unwrap())
+            .with_redeliver_interval(Duration::ZERO)
+            .start();
+        bridge.publish(&execution("new", "O1", "0", "new"));
+        bridge.publish(&execution("trade", "O1", "1", "filled"));
+
+        // Both reach the journal although the first delivery hasn't returned
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while bridge.stats().pending < 2 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 2);
+        assert_eq!(bridge.stats().delivered, 0);
+
+        release.send_replace(true);
+        let stats = bridge.close().await;
+        assert_eq!((stats.delivered, stats.pending), (2, 0));
+    }
+}
@@ -0,0 +1,247 @@
+//! Per-pair trading status from REST `AssetPairs`
+//!
+//! Kraken moves single pairs into `post_only`, `limit_only` or
+//! `cancel_only` during volatility and listings. The connection tracks
+//! those statuses from the instrument channel; see
+//! [`KrakenClient::pair_status`](crate::KrakenClient::pair_status). The
+//! first status seen for a pair is recorded silently, and later transitions
+//! of subscribed pairs are emitted as `ConnectionEvent::PairStatusChanged`.
+//!
+//! Clients not subscribed to the instrument channel can spawn a
+//! [`PairStatusPoller`] from
+//! [`KrakenClient::pair_status_poller`](crate::KrakenClient::pair_status_poller),
+//! which polls [`ASSET_PAIRS_URL`] and raises the same events. It fetches
+//! directly rather than through a [`RestCache`](crate::rest_cache::RestCache):
+//! the cache's TTL is meant for static metadata and would hide status
+//! changes.
+//!
+//! Pass [`KrakenClient::watch_pair_status`](crate::KrakenClient::watch_pair_status)
+//! to [`TradingClient::with_pair_status_gate`](kraken_ws::TradingClient::with_pair_status_gate)
+//! to hold orders to what each pair accepts.
+//!
+//! | Status | New orders accepted |
+//! |--------|---------------------|
+//! | `online`, `reduce_only` | all (reduce-only checked by the exchange) |
+//! | `limit_only` | limit, stop-loss-limit, take-profit-limit |
+//! | `post_only` | post-only limit |
+//! | `cancel_only`, `maintenance`, `work_in_progress`, `delisted` | none |
+//!
+//! [`PairStatus::permits`] applies this table to an order.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::pair_status::parse_asset_pairs_status;
+//! use kraken_types::{OrderType, PairStatus};
+//!
+//! let body = r#"{"error":[],"result":{
+//!     "XXBTZUSD":{"altname":"XBTUSD","wsname":"XBT/USD","status":"online"},
+//!     "XETHZUSD":{"altname":"ETHUSD","wsname":"ETH/USD","status":"post_only"}}}"#;
+//!
+//! let statuses = parse_asset_pairs_status(body).unwrap();
+//! assert_eq!(statuses["BTC/USD"], PairStatus::Online);
+//! assert!(!statuses["ETH/USD"].permits(OrderType::Market, false));
+//! ```
+
+use crate::rest_backfill::RestFetch;
+use kraken_types::PairStatus;
+use kraken_ws::KrakenConnection;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Kraken REST asset pairs endpoint
+pub const ASSET_PAIRS_URL: &str = "https://api.kraken.com/0/public/AssetPairs";
+
+/// Default interval between `AssetPairs` polls
+pub const DEFAULT_PAIR_STATUS_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Shortest interval between `AssetPairs` polls
+pub const MIN_PAIR_STATUS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Error reading pair statuses from an `AssetPairs` response
+#[derive(Debug, thiserror::Error)]
+pub enum PairStatusError {
+    /// Response was not valid JSON
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Kraken returned an error
+    #[error("Kraken API error: {0}")]
+    Api(String),
+
+    /// Response had an unexpected shape
+    #[error("unexpected AssetPairs response: {0}")]
+    Format(String),
+
+    /// The fetch function failed
+    #[error("fetch failed: {0}")]
+    Fetch(String),
+}
+
+/// Trading status by WebSocket symbol from an `AssetPairs` response
+///
+/// Pairs are keyed by `wsname` with REST asset codes mapped to WebSocket
+/// ones (`XBT/USD` → `BTC/USD`). Pairs without a `wsname` or with an
+/// unknown status are skipped.
+pub fn parse_asset_pairs_status(body: &str) -> Result<HashMap<String, PairStatus>, PairStatusError> {
+    let value: serde_json::Value = serde_json::from_str(body)?;
+    if let Some(errors) = value.get("error").and_then(|e| e.as_array()) {
+        if !errors.is_empty() {
+            let messages: Vec<&str> = errors.iter().filter_map(|e| e.as_str()).collect();
+            return Err(PairStatusError::Api(messages.join(", ")));
+        }
+    }
+    let result = value
+        .get("result")
+        .and_then(|r| r.as_object())
+        .ok_or_else(|| PairStatusError::Format("missing result".to_string()))?;
+
+    let mut statuses = HashMap::with_capacity(result.len());
+    for (key, pair) in result {
+        let wsname = pair.get("wsname").and_then(|v| v.as_str());
+        let status = pair.get("status").and_then(|v| v.as_str());
+        match (wsname, status.and_then(PairStatus::parse)) {
+            (Some(wsname), Some(status)) => {
+                statuses.insert(ws_symbol(wsname), status);
+            }
+            _ => debug!(pair = %key, ?status, "Skipping pair without wsname or known status"),
+        }
+    }
+    Ok(statuses)
+}
+
+/// Record pair statuses on a connection, returning how many changed
+pub fn apply_pair_statuses(connection: &KrakenConnection, statuses: HashMap<String, PairStatus>) -> usize {
+    statuses
+        .into_iter()
+        .filter(|(symbol, status)| connection.update_pair_status(symbol, *status))
+        .count()
+}
+
+/// Polls REST `AssetPairs` and records pair statuses on a connection
+pub struct PairStatusPoller {
+    connection: Arc<KrakenConnection>,
+    fetch: RestFetch,
+    interval: Duration,
+}
+
+impl std::fmt::Debug for PairStatusPoller {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PairStatusPoller")
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PairStatusPoller {
+    /// Create a poller feeding `connection`
+    ///
+    /// `fetch` performs the HTTP GET. The interval is raised to
+    /// [`MIN_PAIR_STATUS_INTERVAL`] if shorter.
+    pub fn new(connection: Arc<KrakenConnection>, fetch: RestFetch, interval: Duration) -> Self {
+        Self {
+            connection,
+            fetch,
+            interval: interval.max(MIN_PAIR_STATUS_INTERVAL),
+        }
+    }
+
+    /// Interval between polls
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Poll once, returning the number of pairs whose status changed
+    pub async fn poll_once(&self) -> Result<usize, PairStatusError> {
+        let body = self
+            .fetch
+            .get(ASSET_PAIRS_URL.to_string())
+            .await
+            .map_err(PairStatusError::Fetch)?;
+        let statuses = parse_asset_pairs_status(&body)?;
+        Ok(apply_pair_statuses(&self.connection, statuses))
+    }
+
+    /// Poll on every interval until the client's run loop exits
+    ///
+    /// The first poll runs immediately. Failed polls are logged and retried
+    /// on the next tick.
+    pub async fn run(self) {
+        let mut stopped = self.connection.watch_stopped();
+        let mut tick = tokio::time::interval(self.interval);
+        tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            tokio::select! {
+                _ = tick.tick() => {}
+                _ = stopped.wait_for(|stopped| *stopped) => return,
+            }
+            if let Err(e) = self.poll_once().await {
+                warn!(interval = ?self.interval, "AssetPairs poll failed: {}", e);
+            }
+        }
+    }
+}
+
+/// WebSocket v2 symbol for a REST `wsname`, e.g. `"XBT/USD"` → `"BTC/USD"`
+pub fn ws_symbol(wsname: &str) -> String {
+    let (base, quote) = wsname.split_once('/').unwrap_or((wsname, ""));
+    let ws_asset = |asset: &str| match asset {
+        "XBT" => "BTC".to_string(),
+        "XDG" => "DOGE".to_string(),
+        other => other.to_string(),
+    };
+    if quote.is_empty() {
+        return ws_asset(base);
+    }
+    format!("{}/{}", ws_asset(base), ws_asset(quote))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_statuses_keyed_by_ws_symbol() {
+        let body = r#"{"error":[],"result":{
+            "XXBTZUSD":{"wsname":"XBT/USD","status":"online"},
+            "XDGUSD":{"wsname":"XDG/USD","status":"limit_only"},
+            "NEWUSD":{"wsname":"NEW/USD","status":"work_in_progress"},
+            "ODDUSD":{"wsname":"ODD/USD","status":"halted"},
+            "NOWSUSD":{"status":"online"}}}"#;
+
+        let statuses = parse_asset_pairs_status(body).unwrap();
+        assert_eq!(statuses.len(), 3);
+        assert_eq!(statuses["DOGE/USD"], PairStatus::LimitOnly);
+        assert_eq!(statuses["NEW/USD"], PairStatus::WorkInProgress);
+        assert!(!statuses.contains_key("ODD/USD"));
+
+        let err = parse_asset_pairs_status(r#"{"error":["EGeneral:Too many requests"]}"#).unwrap_err();
+        assert!(matches!(err, PairStatusError::Api(_)));
+    }
+
+    #[tokio::test]
+    async fn test_poller_seeds_then_reports_changes() {
+        let body = Arc::new(std::sync::Mutex::new(
+            r#"{"error":[],"result":{"XETHZUSD":{"wsname":"ETH/USD","status":"limit_only"}}}"#,
+        ));
+        let current = Arc::clone(&body);
+        let fetch = RestFetch::new(move |url: String| {
+            assert_eq!(url, ASSET_PAIRS_URL);
+            let body = current.lock().unwrap().to_string();
+            async move { Ok::<_, String>(body) }
+        });
+        let connection = Arc::new(KrakenConnection::with_defaults());
+        let poller = PairStatusPoller::new(Arc::clone(&connection), fetch, Duration::ZERO);
+        assert_eq!(poller.interval(), MIN_PAIR_STATUS_INTERVAL);
+
+        // The first poll only seeds the status
+        assert_eq!(poller.poll_once().await.unwrap(), 0);
+        assert_eq!(connection.pair_status("ETH/USD"), Some(PairStatus::LimitOnly));
+
+        *body.lock().unwrap() = r#"{"error":[],"result":{"XETHZUSD":{"wsname":"ETH/USD","status":"online"}}}"#;
+        assert_eq!(poller.poll_once().await.unwrap(), 1);
+        assert_eq!(connection.pair_status("ETH/USD"), Some(PairStatus::Online));
+    }
+}
@@ -0,0 +1,559 @@
+//! Account valuation from balances and live prices
+//!
+//! [`PortfolioValuer`] holds an account's balances and values them in one
+//! quote currency with a [`CompositePricer`], so assets without a direct
+//! pair to the quote are still marked (e.g. DOT via DOT/EUR × EUR/USD).
+//!
+//! | Source | Feeds |
+//! |--------|-------|
+//! | balances channel | [`PortfolioValuer::handle_event`] |
+//! | REST `Balance` / `BalanceEx` | [`PortfolioValuer::apply_rest_balances`] |
+//! | orderbook events | [`PortfolioValuer::handle_event`] |
+//! | client books | [`KrakenClient::portfolio_valuation`] |
+//!
+//! Every [`record`](PortfolioValuer::record) adds the total to a bounded
+//! history for change-over-time queries. Assets with no route to the quote
+//! currency are listed in [`Valuation::unpriced`] and left out of the total.
+//!
+//! # Periodic valuation
+//!
+//! [`PortfolioValuer::start`] moves the valuer onto its own task, which
+//! records a [`Valuation`] every interval and publishes the latest one on a
+//! watch channel. The returned [`PortfolioHandle`] is fed like the other
+//! event consumers: [`publish`](PortfolioHandle::publish) from an event loop
+//! that also does other work, or [`forward`](PortfolioHandle::forward) a
+//! stream used only for valuation.
+//!
+//! The valuer is standalone: the client doesn't own one, and valuations are
+//! not client [`Event`]s, since the event stream only carries what the
+//! connection produces. Read them from [`PortfolioHandle::latest`] or
+//! [`PortfolioHandle::watch_valuations`]; [`PortfolioHandle::close`] hands
+//! the valuer back with its history.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::portfolio::PortfolioValuer;
+//! use rust_decimal_macros::dec;
+//!
+//! let mut valuer = PortfolioValuer::new("USD");
+//! valuer.apply_rest_balances(r#"{"error":[],"result":{"XXBT":"0.5","ZUSD":"1000"}}"#).unwrap();
+//! valuer.pricer_mut().update_mid("BTC/EUR", dec!(50000));
+//! valuer.pricer_mut().update_mid("EUR/USD", dec!(1.1));
+//!
+//! let valuation = valuer.record();
+//! assert_eq!(valuation.total, dec!(28500));
+//! assert_eq!(valuation.asset("BTC").unwrap().value, Some(dec!(27500)));
+//! ```
+
+use crate::client::KrakenClient;
+use crate::composite::CompositePricer;
+use crate::pair_status::ws_symbol;
+use chrono::{DateTime, Utc};
+use kraken_types::{BalanceData, Decimal};
+use kraken_ws::{BalanceInfo, Event, EventReceiver, MarketEvent, PrivateEvent};
+use std::collections::{BTreeMap, VecDeque};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tokio::task::JoinHandle;
+
+/// History points kept unless configured otherwise (a day of minutes)
+pub const DEFAULT_HISTORY_LEN: usize = 1440;
+
+/// Shortest interval between periodic valuations
+pub const MIN_VALUATION_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Error reading balances from a REST response
+#[derive(Debug, thiserror::Error)]
+pub enum PortfolioError {
+    /// Response was not valid JSON
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Kraken returned an error
+    #[error("Kraken API error: {0}")]
+    Api(String),
+
+    /// Response had an unexpected shape
+    #[error("unexpected Balance response: {0}")]
+    Format(String),
+}
+
+/// One asset's share of a valuation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AssetValue {
+    /// Asset identifier (e.g. "BTC")
+    pub asset: String,
+    /// Total balance
+    pub qty: Decimal,
+    /// Price in the quote currency (None if no route was found)
+    pub price: Option<Decimal>,
+    /// `qty × price`
+    pub value: Option<Decimal>,
+    /// Fraction of the total value
+    pub weight: Option<Decimal>,
+}
+
+/// An account valued in one currency
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Valuation {
+    /// Currency the values are in
+    pub quote: String,
+    /// Sum of every priced asset
+    pub total: Decimal,
+    /// Per-asset breakdown, largest value first
+    pub assets: Vec<AssetValue>,
+    /// Assets left out of the total for lack of a price
+    pub unpriced: Vec<String>,
+    /// When the valuation was taken
+    pub timestamp: DateTime<Utc>,
+}
+
+impl Valuation {
+    /// Breakdown entry for an asset
+    pub fn asset(&self, asset: &str) -> Option<&AssetValue> {
+        self.assets.iter().find(|value| value.asset == asset)
+    }
+
+    /// Returns true if every asset was priced
+    pub fn is_complete(&self) -> bool {
+        self.unpriced.is_empty()
+    }
+}
+
+/// Total value at one point in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValuationPoint {
+    /// When the valuation was taken
+    pub timestamp: DateTime<Utc>,
+    /// Total value in the quote currency
+    pub total: Decimal,
+}
+
+/// Change in total value between two points
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValueChange {
+    /// Earlier point
+    pub from: ValuationPoint,
+    /// Latest point
+    pub to: ValuationPoint,
+    /// `to.total - from.total`
+    pub absolute: Decimal,
+    /// Change in percent of `from.total` (None if it was zero)
+    pub percent: Option<Decimal>,
+}
+
+/// Values balances in a quote currency and keeps a history of totals
+#[derive(Debug, Clone)]
+pub struct PortfolioValuer {
+    quote: String,
+    balances: BTreeMap<String, Decimal>,
+    pricer: CompositePricer,
+    history: VecDeque<ValuationPoint>,
+    max_history: usize,
+    interval: Duration,
+}
+
+impl PortfolioValuer {
+    /// Value balances in `quote` (e.g. "USD")
+    pub fn new(quote: impl Into<String>) -> Self {
+        Self {
+            quote: quote.into(),
+            balances: BTreeMap::new(),
+            pricer: CompositePricer::new(),
+            history: VecDeque::new(),
+            max_history: DEFAULT_HISTORY_LEN,
+            interval: Duration::from_secs(60),
+        }
+    }
+
+    /// Price with a configured pricer (routes, weighting, max age)
+    pub fn with_pricer(mut self, pricer: CompositePricer) -> Self {
+        self.pricer = pricer;
+        self
+    }
+
+    /// Keep at most `len` history points
+    pub fn with_history_len(mut self, len: usize) -> Self {
+        self.max_history = len.max(1);
+        self
+    }
+
+    /// Set how often a [started](Self::start) valuer publishes a valuation
+    ///
+    /// Raised to [`MIN_VALUATION_INTERVAL`] if shorter.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval.max(MIN_VALUATION_INTERVAL);
+        self
+    }
+
+    /// Currency the values are in
+    pub fn quote(&self) -> &str {
+        &self.quote
+    }
+
+    /// The pricer, for feeding prices directly
+    pub fn pricer_mut(&mut self) -> &mut CompositePricer {
+        &mut self.pricer
+    }
+
+    /// Set an asset's total balance (zero removes it)
+    pub fn set_balance(&mut self, asset: &str, total: Decimal) {
+        if total.is_zero() {
+            self.balances.remove(asset);
+        } else {
+            self.balances.insert(asset.to_string(), total);
+        }
+    }
+
+    /// Apply entries from the balances channel (available plus held)
+    pub fn apply_balances(&mut self, balances: &[BalanceData]) {
+        for data in balances {
+            self.set_balance(&data.asset, BalanceInfo::from_data(data).total);
+        }
+    }
+
+    /// Replace balances with a REST `Balance` or `BalanceEx` response
+    ///
+    /// REST asset codes are mapped to WebSocket ones (`XXBT` → `BTC`,
+    /// `ZUSD` → `USD`), and staked or earn variants (`DOT.S`, `ETH.F`) are
+    /// folded into their asset. Returns the number of assets held.
+    pub fn apply_rest_balances(&mut self, body: &str) -> Result<usize, PortfolioError> {
+        let value: serde_json::Value = serde_json::from_str(body)?;
+        if let Some(errors) = value.get("error").and_then(|e| e.as_array()) {
+            if !errors.is_empty() {
+                let messages: Vec<&str> = errors.iter().filter_map(|e| e.as_str()).collect();
+                return Err(PortfolioError::Api(messages.join(", ")));
+            }
+        }
+        let result = value
+            .get("result")
+            .and_then(|r| r.as_object())
+            .ok_or_else(|| PortfolioError::Format("missing result".to_string()))?;
+
+        let mut balances: BTreeMap<String, Decimal> = BTreeMap::new();
+        for (code, entry) in result {
+            // `Balance` maps to a string, `BalanceEx` to an object
+            let amount = entry
+                .as_str()
+                .or_else(|| entry.get("balance").and_then(|b| b.as_str()))
+                .ok_or_else(|| PortfolioError::Format(format!("no balance for {}", code)))?;
+            let amount: Decimal = amount
+                .parse()
+                .map_err(|_| PortfolioError::Format(format!("bad balance for {}: {}", code, amount)))?;
+            *balances.entry(rest_asset(code)).or_default() += amount;
+        }
+        balances.retain(|_, total| !total.is_zero());
+        self.balances = balances;
+        Ok(self.balances.len())
+    }
+
+    /// Current balances by asset
+    pub fn balances(&self) -> &BTreeMap<String, Decimal> {
+        &self.balances
+    }
+
+    /// Update balances and prices from an SDK event
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::Private(private) => match private.as_ref() {
+                PrivateEvent::BalanceUpdate { balances, .. } => self.apply_balances(balances),
+                PrivateEvent::BalanceSnapshot { balances } => {
+                    self.balances.clear();
+                    for info in balances.values() {
+                        self.set_balance(&info.asset, info.total);
+                    }
+                }
+                _ => {}
+            },
+            _ => self.pricer.handle_event(event),
+        }
+    }
+
+    /// Update prices from the client's books
+    pub fn update_from_client(&mut self, client: &KrakenClient) {
+        self.pricer.update_from_client(client);
+    }
+
+    /// Value the balances at current prices
+    pub fn value(&self) -> Valuation {
+        let mut assets = Vec::with_capacity(self.balances.len());
+        let mut unpriced = Vec::new();
+        let mut total = Decimal::ZERO;
+        for (asset, qty) in &self.balances {
+            let price = if *asset == self.quote {
+                Some(Decimal::ONE)
+            } else {
+                self.pricer.price(asset, &self.quote).map(|composite| composite.price)
+            };
+            let value = price.map(|price| price * qty);
+            match value {
+                Some(value) => total += value,
+                None => unpriced.push(asset.clone()),
+            }
+            assets.push(AssetValue {
+                asset: asset.clone(),
+                qty: *qty,
+                price,
+                value,
+                weight: None,
+            });
+        }
+        for entry in &mut assets {
+            entry.weight = entry.value.filter(|_| !total.is_zero()).map(|value| value / total);
+        }
+        assets.sort_by_key(|entry| std::cmp::Reverse(entry.value));
+        Valuation {
+            quote: self.quote.clone(),
+            total,
+            assets,
+            unpriced,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// Value the balances and add the total to the history
+    pub fn record(&mut self) -> Valuation {
+        let valuation = self.value();
+        if self.history.len() >= self.max_history {
+            self.history.pop_front();
+        }
+        self.history.push_back(ValuationPoint {
+            timestamp: valuation.timestamp,
+            total: valuation.total,
+        });
+        valuation
+    }
+
+    /// Recorded totals, oldest first
+    pub fn history(&self) -> impl Iterator<Item = &ValuationPoint> {
+        self.history.iter()
+    }
+
+    /// Change from the oldest point within `window` to the latest
+    ///
+    /// None with fewer than two points in the window.
+    pub fn change_over(&self, window: Duration) -> Option<ValueChange> {
+        let to = *self.history.back()?;
+        let since = to.timestamp - chrono::Duration::from_std(window).ok()?;
+        let from = *self.history.iter().find(|point| point.timestamp >= since)?;
+        if from == to {
+            return None;
+        }
+        let absolute = to.total - from.total;
+        Some(ValueChange {
+            from,
+            to,
+            absolute,
+            percent: (!from.total.is_zero()).then(|| absolute / from.total * Decimal::ONE_HUNDRED),
+        })
+    }
+
+    /// Spawn a task that values the portfolio every interval
+    ///
+    /// The first valuation is recorded one interval after the start. Must
+    /// be called inside a Tokio runtime.
+    pub fn start(self) -> PortfolioHandle {
+        let (tx, events) = mpsc::unbounded_channel();
+        let (valuations, latest) = watch::channel(None);
+        PortfolioHandle {
+            tx,
+            latest,
+            task: tokio::spawn(value_periodically(self, events, valuations)),
+        }
+    }
+}
+
+/// Feeds a [started](PortfolioValuer::start) valuer and reads its valuations
+#[derive(Debug)]
+pub struct PortfolioHandle {
+    tx: mpsc::UnboundedSender<Event>,
+    latest: watch::Receiver<Option<Valuation>>,
+    task: JoinHandle<PortfolioValuer>,
+}
+
+impl PortfolioHandle {
+    /// Pass on `event` if it changes balances or prices
+    pub fn publish(&self, event: &Event) {
+        if moves_valuation(event) {
+            let _ = self.tx.send(event.clone());
+        }
+    }
+
+    /// Publish events until the stream ends
+    pub async fn forward(&self, mut events: EventReceiver) {
+        while let Some(event) = events.recv().await {
+            self.publish(&event);
+        }
+    }
+
+    /// Most recent periodic valuation (None before the first interval)
+    pub fn latest(&self) -> Option<Valuation> {
+        self.latest.borrow().clone()
+    }
+
+    /// Watch periodic valuations as they are recorded
+    pub fn watch_valuations(&self) -> watch::Receiver<Option<Valuation>> {
+        self.latest.clone()
+    }
+
+    /// Stop valuing and return the valuer with its history
+    ///
+    /// Events published before the call are applied first.
+    pub async fn close(self) -> PortfolioValuer {
+        drop(self.tx);
+        self.task.await.expect("portfolio valuation task panicked")
+    }
+}
+
+/// Whether `event` carries balances or book prices
+fn moves_valuation(event: &Event) -> bool {
+    match event {
+        Event::Private(private) => matches!(
+            private.as_ref(),
+            PrivateEvent::BalanceUpdate { .. } | PrivateEvent::BalanceSnapshot { .. }
+        ),
+        Event::Market(market) => matches!(
+            market,
+            MarketEvent::OrderbookSnapshot { .. } | MarketEvent::OrderbookUpdate { .. }
+        ),
+        _ => false,
+    }
+}
+
+/// Apply published events and record a valuation every interval
+async fn value_periodically(
+    mut valuer: PortfolioValuer,
+    mut events: mpsc::UnboundedReceiver<Event>,
+    valuations: watch::Sender<Option<Valuation>>,
+) -> PortfolioValuer {
+    let interval = valuer.interval;
+    let mut tick = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        tokio::select! {
+            event = events.recv() => match event {
+                Some(event) => valuer.handle_event(&event),
+                None => break,
+            },
+            _ = tick.tick() => {
+                valuations.send_replace(Some(valuer.record()));
+            }
+        }
+    }
+    valuer
+}
+
+/// WebSocket asset for a REST balance code, e.g. `"XXBT"` → `"BTC"`
+fn rest_asset(code: &str) -> String {
+    // Staked, opt-in rewards and earn variants hold the same asset
+    let code = code.split_once('.').map_or(code, |(asset, _)| asset);
+    // Legacy four-letter codes carry an X (crypto) or Z (fiat) prefix
+    let legacy = code.len() == 4
+        && matches!(
+            code,
+            "XXBT" | "XETH" | "XETC" | "XLTC" | "XXRP" | "XXLM" | "XXMR" | "XZEC" | "XMLN" | "XREP" | "XXDG"
+                | "ZUSD" | "ZEUR" | "ZGBP" | "ZJPY" | "ZCAD" | "ZAUD" | "ZCHF"
+        );
+    ws_symbol(if legacy { &code[1..] } else { code })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_ws::SequencedEvent;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_rest_balances_and_breakdown() {
+        let mut valuer = PortfolioValuer::new("USD");
+        let held = valuer
+            .apply_rest_balances(
+                r#"{"error":[],"result":{
+                    "XXBT":{"balance":"0.5","hold_trade":"0.1"},
+                    "DOT":{"balance":"100"},"DOT.S":{"balance":"50"},
+                    "ZUSD":{"balance":"1000"},"XXDG":{"balance":"0"},
+                    "NEW":{"balance":"7"}}}"#,
+            )
+            .unwrap();
+        assert_eq!(held, 4);
+        assert_eq!(valuer.balances()["DOT"], dec!(150));
+
+        valuer.pricer_mut().update_mid("BTC/USD", dec!(60000));
+        valuer.pricer_mut().update_mid("DOT/EUR", dec!(5));
+        valuer.pricer_mut().update_mid("EUR/USD", dec!(1.2));
+
+        let valuation = valuer.value();
+        assert_eq!(valuation.total, dec!(31900));
+        assert_eq!(valuation.unpriced, ["NEW"]);
+        assert_eq!(valuation.assets[0].asset, "BTC");
+        assert_eq!(valuation.asset("DOT").unwrap().value, Some(dec!(900)));
+        assert_eq!(valuation.asset("USD").unwrap().price, Some(dec!(1)));
+        assert_eq!(valuation.asset("NEW").unwrap().weight, None);
+
+        let err = valuer.apply_rest_balances(r#"{"error":["EAPI:Invalid key"]}"#).unwrap_err();
+        assert!(matches!(err, PortfolioError::Api(_)));
+    }
+
+    #[test]
+    fn test_history_and_change_over_time() {
+        let mut valuer = PortfolioValuer::new("USD").with_history_len(3);
+        valuer.set_balance("BTC", dec!(1));
+        assert!(valuer.change_over(Duration::from_secs(60)).is_none());
+
+        for mid in [dec!(40000), dec!(50000), dec!(51000), dec!(60000)] {
+            valuer.pricer_mut().update_mid("BTC/USD", mid);
+            valuer.record();
+        }
+        assert_eq!(valuer.history().count(), 3);
+
+        let change = valuer.change_over(Duration::from_secs(60)).unwrap();
+        assert_eq!(change.from.total, dec!(50000));
+        assert_eq!(change.absolute, dec!(10000));
+        assert_eq!(change.percent, Some(dec!(20)));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_started_valuer_publishes_on_interval() {
+        let mut valuer = PortfolioValuer::new("USD").with_interval(Duration::ZERO);
+        assert_eq!(valuer.interval, MIN_VALUATION_INTERVAL);
+        valuer.set_balance("USD", dec!(250));
+        let portfolio = valuer.start();
+        let mut valuations = portfolio.watch_valuations();
+
+        portfolio.publish(&Event::Private(Box::new(PrivateEvent::BalanceUpdate {
+            balances: vec![BalanceData {
+                asset: "USD".to_string(),
+                balance: dec!(400),
+                hold_trade: Some(dec!(100)),
+            }],
+            is_snapshot: false,
+        })));
+        assert!(portfolio.latest().is_none());
+
+        valuations.changed().await.unwrap();
+        assert_eq!(portfolio.latest().unwrap().total, dec!(500));
+
+        // A stream used only for valuation can be forwarded
+        let (tx, rx) = mpsc::unbounded_channel();
+        tx.send(SequencedEvent {
+            id: 1,
+            event: Event::Private(Box::new(PrivateEvent::BalanceUpdate {
+                balances: vec![BalanceData {
+                    asset: "USD".to_string(),
+                    balance: dec!(700),
+                    hold_trade: None,
+                }],
+                is_snapshot: false,
+            })),
+        })
+        .unwrap();
+        drop(tx);
+        portfolio.forward(EventReceiver::Unbounded(rx)).await;
+        valuations.changed().await.unwrap();
+        assert_eq!(portfolio.latest().unwrap().total, dec!(700));
+
+        let valuer = portfolio.close().await;
+        assert_eq!(valuer.history().count(), 2);
+    }
+}
@@ -7,11 +7,14 @@
 
 // Client
 pub use crate::client::KrakenClient;
+pub use crate::blocking::KrakenClientBlocking;
 pub use crate::builder::{KrakenClientBuilder, ConfigError, OhlcInterval};
 
 // Types from kraken-types
 pub use kraken_types::{
     Channel, Depth, KrakenError, Level, Side, Symbol,
+    // Unit-safe decimals
+    Price, Qty, Notional,
     BookData, SubscribeParams, SubscribeRequest,
     // Trading types
     AddOrderRequest, AddOrderParams, AmendOrderRequest, AmendOrderParams,
@@ -25,7 +28,7 @@ pub use kraken_types::{
 pub use kraken_ws::{
     ConnectionConfig, ConnectionState, Endpoint, Event,
     ConnectionEvent, MarketEvent, SubscriptionEvent,
-    ReconnectConfig,
+    ReconnectConfig, PruningPolicy,
     // Private channel events
     PrivateEvent, OrderStatus, TrackedOrder, OrderFill, ExecutionType, OrderChange, BalanceInfo,
     // L3 events
@@ -37,7 +40,7 @@ pub use kraken_ws::{
 // Orderbook types
 pub use kraken_book::{
     Orderbook, OrderbookSnapshot, OrderbookState,
-    ApplyResult, ChecksumMismatch,
+    ApplyError, ApplyResult, ChecksumMismatch,
     // L3 orderbook
     L3Book,
 };
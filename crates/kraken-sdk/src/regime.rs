@@ -0,0 +1,406 @@
+//! Spread and liquidity regime detection
+//!
+//! [`RegimeDetector`] classifies each symbol's market from two measures
+//! taken on every book update:
+//!
+//! - the spread in basis points of mid, and
+//! - the resting notional (price × qty) in the top N levels of both sides.
+//!
+//! Each is compared with rolling percentiles of its own recent history, so
+//! "wide" and "thin" are relative to what is usual for that symbol:
+//!
+//! | Measure | ≤ low percentile | between | ≥ high percentile |
+//! |---------|------------------|---------|-------------------|
+//! | spread  | [`Tight`](SpreadRegime::Tight) | [`Normal`](SpreadRegime::Normal) | [`Wide`](SpreadRegime::Wide) |
+//! | depth   | [`Thin`](LiquidityRegime::Thin) | [`Normal`](LiquidityRegime::Normal) | [`Deep`](LiquidityRegime::Deep) |
+//!
+//! Nothing is classified until `min_samples` observations are in the window.
+//! A new regime must hold for `confirm` consecutive observations before a
+//! [`RegimeChange`] is emitted, so a single flickering level doesn't toggle
+//! a strategy back and forth.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::regime::{RegimeDetector, SpreadRegime};
+//! use kraken_types::Decimal;
+//! use rust_decimal_macros::dec;
+//!
+//! let mut detector = RegimeDetector::new().with_min_samples(10).with_confirm(3);
+//! for i in 0..20 {
+//!     let spread_bps = dec!(2) + Decimal::from(i % 5);
+//!     assert!(detector.observe("BTC/USD", spread_bps, dec!(500000)).is_none());
+//! }
+//!
+//! // The spread blows out and stays out
+//! let change = (0..3)
+//!     .find_map(|_| detector.observe("BTC/USD", dec!(40), dec!(500000)))
+//!     .unwrap();
+//! assert_eq!(change.current.spread, SpreadRegime::Wide);
+//! ```
+
+use kraken_book::OrderbookSnapshot;
+use kraken_types::Decimal;
+use kraken_ws::{Event, MarketEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+/// Spread relative to its recent history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SpreadRegime {
+    /// At or below the low percentile
+    Tight,
+    /// Between the percentiles
+    Normal,
+    /// At or above the high percentile
+    Wide,
+}
+
+/// Top-of-book depth relative to its recent history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LiquidityRegime {
+    /// At or below the low percentile
+    Thin,
+    /// Between the percentiles
+    Normal,
+    /// At or above the high percentile
+    Deep,
+}
+
+/// Combined regime of a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Regime {
+    /// Spread regime
+    pub spread: SpreadRegime,
+    /// Liquidity regime
+    pub liquidity: LiquidityRegime,
+}
+
+impl fmt::Display for Regime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} spread / {:?} liquidity", self.spread, self.liquidity)
+    }
+}
+
+/// Percentile cut-offs currently in effect for a symbol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegimeThresholds {
+    /// Spreads at or below this are tight (bps)
+    pub tight_spread_bps: Decimal,
+    /// Spreads at or above this are wide (bps)
+    pub wide_spread_bps: Decimal,
+    /// Depth at or below this is thin (quote currency)
+    pub thin_depth: Decimal,
+    /// Depth at or above this is deep (quote currency)
+    pub deep_depth: Decimal,
+}
+
+impl RegimeThresholds {
+    fn classify(&self, spread_bps: Decimal, depth: Decimal) -> Regime {
+        let spread = if spread_bps <= self.tight_spread_bps {
+            SpreadRegime::Tight
+        } else if spread_bps >= self.wide_spread_bps {
+            SpreadRegime::Wide
+        } else {
+            SpreadRegime::Normal
+        };
+        let liquidity = if depth <= self.thin_depth {
+            LiquidityRegime::Thin
+        } else if depth >= self.deep_depth {
+            LiquidityRegime::Deep
+        } else {
+            LiquidityRegime::Normal
+        };
+        Regime { spread, liquidity }
+    }
+}
+
+/// A symbol moved into a new regime
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegimeChange {
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Regime before the change (None for the first classification)
+    pub previous: Option<Regime>,
+    /// Regime now in effect
+    pub current: Regime,
+    /// Spread that confirmed the change (bps)
+    pub spread_bps: Decimal,
+    /// Depth that confirmed the change (quote currency)
+    pub depth: Decimal,
+    /// Cut-offs the change was measured against
+    pub thresholds: RegimeThresholds,
+}
+
+impl fmt::Display for RegimeChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.previous {
+            Some(previous) => write!(f, "{}: {} -> {}", self.symbol, previous, self.current),
+            None => write!(f, "{}: {}", self.symbol, self.current),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct SymbolState {
+    spreads: VecDeque<Decimal>,
+    depths: VecDeque<Decimal>,
+    current: Option<Regime>,
+    /// Regime seen but not yet confirmed, with its consecutive count
+    pending: Option<(Regime, usize)>,
+}
+
+/// Classifies spread and liquidity regimes per symbol
+#[derive(Debug, Clone)]
+pub struct RegimeDetector {
+    window: usize,
+    min_samples: usize,
+    depth_levels: usize,
+    low_percentile: u8,
+    high_percentile: u8,
+    confirm: usize,
+    symbols: HashMap<String, SymbolState>,
+}
+
+impl Default for RegimeDetector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RegimeDetector {
+    /// Create a detector with default settings
+    ///
+    /// Defaults: 500-observation window, 50 samples before classifying,
+    /// depth over the top 10 levels, 20th/80th percentiles, and 3
+    /// observations to confirm a change.
+    pub fn new() -> Self {
+        Self {
+            window: 500,
+            min_samples: 50,
+            depth_levels: 10,
+            low_percentile: 20,
+            high_percentile: 80,
+            confirm: 3,
+            symbols: HashMap::new(),
+        }
+    }
+
+    /// Set how many observations the percentiles are computed over
+    pub fn with_window(mut self, window: usize) -> Self {
+        self.window = window.max(1);
+        self
+    }
+
+    /// Set how many observations are needed before classifying
+    pub fn with_min_samples(mut self, min_samples: usize) -> Self {
+        self.min_samples = min_samples.max(1);
+        self
+    }
+
+    /// Set how many levels per side count towards depth
+    pub fn with_depth_levels(mut self, levels: usize) -> Self {
+        self.depth_levels = levels.max(1);
+        self
+    }
+
+    /// Set the low and high percentiles (0-100)
+    pub fn with_percentiles(mut self, low: u8, high: u8) -> Self {
+        self.low_percentile = low.min(high).min(100);
+        self.high_percentile = high.max(low).min(100);
+        self
+    }
+
+    /// Set how many consecutive observations confirm a new regime
+    pub fn with_confirm(mut self, observations: usize) -> Self {
+        self.confirm = observations.max(1);
+        self
+    }
+
+    /// Record a spread (bps) and depth observation for a symbol
+    ///
+    /// The observation is classified against the history before it, then
+    /// added to the window. Returns a change once a new regime is confirmed.
+    pub fn observe(&mut self, symbol: &str, spread_bps: Decimal, depth: Decimal) -> Option<RegimeChange> {
+        let (window, confirm) = (self.window, self.confirm);
+        let thresholds = self.thresholds(symbol);
+        let state = self.symbols.entry(symbol.to_string()).or_default();
+
+        state.spreads.push_back(spread_bps);
+        state.depths.push_back(depth);
+        while state.spreads.len() > window {
+            state.spreads.pop_front();
+            state.depths.pop_front();
+        }
+
+        let thresholds = thresholds?;
+        let regime = thresholds.classify(spread_bps, depth);
+        if state.current == Some(regime) {
+            state.pending = None;
+            return None;
+        }
+
+        let count = match state.pending {
+            Some((pending, count)) if pending == regime => count + 1,
+            _ => 1,
+        };
+        if count < confirm {
+            state.pending = Some((regime, count));
+            return None;
+        }
+
+        state.pending = None;
+        let previous = state.current.replace(regime);
+        Some(RegimeChange {
+            symbol: symbol.to_string(),
+            previous,
+            current: regime,
+            spread_bps,
+            depth,
+            thresholds,
+        })
+    }
+
+    /// Record an observation taken from an orderbook snapshot
+    ///
+    /// Returns None without recording if either side is empty or the book
+    /// is crossed.
+    pub fn observe_book(&mut self, snapshot: &OrderbookSnapshot) -> Option<RegimeChange> {
+        let (spread_bps, depth) = self.measure(snapshot)?;
+        self.observe(&snapshot.symbol, spread_bps, depth)
+    }
+
+    /// Spread (bps) and top-N depth of a snapshot
+    pub fn measure(&self, snapshot: &OrderbookSnapshot) -> Option<(Decimal, Decimal)> {
+        let bid = snapshot.best_bid_price()?;
+        let ask = snapshot.best_ask_price()?;
+        let mid = (bid + ask) / Decimal::TWO;
+        if ask < bid || mid.is_zero() {
+            return None;
+        }
+        let spread_bps = (ask - bid) / mid * Decimal::from(10_000);
+        let depth = snapshot
+            .bids
+            .iter()
+            .take(self.depth_levels)
+            .chain(snapshot.asks.iter().take(self.depth_levels))
+            .map(|level| level.price.0 * level.qty.0)
+            .sum();
+        Some((spread_bps, depth))
+    }
+
+    /// Record an observation from an orderbook event
+    pub fn handle_event(&mut self, event: &Event) -> Option<RegimeChange> {
+        match event {
+            Event::Market(
+                MarketEvent::OrderbookSnapshot { snapshot, .. } | MarketEvent::OrderbookUpdate { snapshot, .. },
+            ) => self.observe_book(snapshot),
+            _ => None,
+        }
+    }
+
+    /// Regime currently in effect for a symbol
+    pub fn regime(&self, symbol: &str) -> Option<Regime> {
+        self.symbols.get(symbol)?.current
+    }
+
+    /// Percentile cut-offs over a symbol's current window
+    ///
+    /// None until the window holds `min_samples` observations.
+    pub fn thresholds(&self, symbol: &str) -> Option<RegimeThresholds> {
+        let state = self.symbols.get(symbol)?;
+        if state.spreads.len() < self.min_samples {
+            return None;
+        }
+        let (low, high) = (self.low_percentile, self.high_percentile);
+        let spreads = sorted(&state.spreads);
+        let depths = sorted(&state.depths);
+        Some(RegimeThresholds {
+            tight_spread_bps: percentile(&spreads, low),
+            wide_spread_bps: percentile(&spreads, high),
+            thin_depth: percentile(&depths, low),
+            deep_depth: percentile(&depths, high),
+        })
+    }
+
+    /// Forget a symbol's history and regime
+    pub fn reset(&mut self, symbol: &str) {
+        self.symbols.remove(symbol);
+    }
+}
+
+fn sorted(values: &VecDeque<Decimal>) -> Vec<Decimal> {
+    let mut sorted: Vec<Decimal> = values.iter().copied().collect();
+    sorted.sort_unstable();
+    sorted
+}
+
+/// Nearest-rank percentile of sorted, non-empty values
+fn percentile(sorted: &[Decimal], pct: u8) -> Decimal {
+    let rank = ((sorted.len() - 1) * usize::from(pct) + 50) / 100;
+    sorted[rank]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_types::Level;
+    use rust_decimal_macros::dec;
+
+    fn warm(detector: &mut RegimeDetector) {
+        for i in 0..20 {
+            let spread = dec!(5) + Decimal::from(i % 5);
+            let depth = dec!(1000) + Decimal::from(i % 5) * dec!(100);
+            assert!(detector.observe("BTC/USD", spread, depth).is_none());
+        }
+    }
+
+    #[test]
+    fn test_changes_are_confirmed() {
+        let mut detector = RegimeDetector::new().with_min_samples(20).with_confirm(2);
+        warm(&mut detector);
+        let thresholds = detector.thresholds("BTC/USD").unwrap();
+        assert_eq!(thresholds.tight_spread_bps, dec!(6));
+        assert_eq!(thresholds.wide_spread_bps, dec!(8));
+
+        // First classification
+        assert!(detector.observe("BTC/USD", dec!(7), dec!(1200)).is_none());
+        let change = detector.observe("BTC/USD", dec!(7), dec!(1200)).unwrap();
+        assert_eq!(change.previous, None);
+        assert_eq!(change.current, Regime { spread: SpreadRegime::Normal, liquidity: LiquidityRegime::Normal });
+
+        // A single blip is not confirmed
+        assert!(detector.observe("BTC/USD", dec!(50), dec!(100)).is_none());
+        assert!(detector.observe("BTC/USD", dec!(7), dec!(1200)).is_none());
+
+        assert!(detector.observe("BTC/USD", dec!(50), dec!(100)).is_none());
+        let change = detector.observe("BTC/USD", dec!(50), dec!(100)).unwrap();
+        assert_eq!(change.previous.unwrap().spread, SpreadRegime::Normal);
+        assert_eq!(change.current, Regime { spread: SpreadRegime::Wide, liquidity: LiquidityRegime::Thin });
+        assert_eq!(detector.regime("BTC/USD"), Some(change.current));
+        assert_eq!(change.to_string(), "BTC/USD: Normal spread / Normal liquidity -> Wide spread / Thin liquidity");
+    }
+
+    #[test]
+    fn test_measure_from_book() {
+        let detector = RegimeDetector::new().with_depth_levels(1);
+        let snapshot = OrderbookSnapshot {
+            symbol: "BTC/USD".to_string(),
+            bids: vec![Level::new(dec!(99.9), dec!(2)), Level::new(dec!(99), dec!(50))],
+            asks: vec![Level::new(dec!(100.1), dec!(1))],
+            ..Default::default()
+        };
+        let (spread_bps, depth) = detector.measure(&snapshot).unwrap();
+        assert_eq!(spread_bps, dec!(20));
+        assert_eq!(depth, dec!(299.9));
+
+        let crossed = OrderbookSnapshot {
+            bids: vec![Level::new(dec!(101), dec!(1))],
+            ..snapshot
+        };
+        assert!(detector.measure(&crossed).is_none());
+    }
+}
@@ -0,0 +1,224 @@
+//! Kraken REST backfill for the trade and OHLC channels
+//!
+//! [`kraken_ws::backfill`] holds live trades and candles on every connect
+//! until the window missed while disconnected has been fetched. This module
+//! fetches that window from Kraken's public REST endpoints:
+//!
+//! | Request | Endpoint | Window |
+//! |---------|----------|--------|
+//! | Trades | `Trades` ([`trades_backfill_url`]) | from the last trade delivered |
+//! | OHLC | `OHLC` ([`ohlc_backfill_url`]) | from the last candle delivered, or the last `n_candles` on the first connect |
+//!
+//! Like [`TickerPoller`](crate::ticker_poller::TickerPoller), the HTTP is a
+//! fetch function mapping a URL to a response body. With the `backfill`
+//! feature, [`RestFetch::reqwest`] provides one; the client builder uses it
+//! unless [`with_backfill_fetch`](crate::KrakenClientBuilder::with_backfill_fetch)
+//! sets another.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::rest_backfill::{rest_backfiller, RestFetch};
+//! use kraken_ws::backfill::{BackfillData, BackfillRequest};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let body = r#"{"error":[],"result":{"XXBTZUSD":[
+//!     [1704067200,"42000.0","42100.0","41900.0","42050.0","42010.0","12.5",340]
+//! ],"last":1704067200}}"#;
+//! let fetch = RestFetch::new(move |_url: String| async move { Ok::<_, String>(body.to_string()) });
+//! let backfiller = rest_backfiller(fetch, false, Some(60));
+//!
+//! let request = BackfillRequest::Ohlc { symbol: "BTC/USD".to_string(), interval: 1, since: None };
+//! let Ok(BackfillData::Ohlc(candles)) = backfiller.fetch(request).await else { panic!() };
+//! assert_eq!(candles[0].trades, 340);
+//! # }
+//! ```
+
+use crate::candles::{ohlc_backfill_url, parse_rest_ohlc, REST_OHLC_URL};
+use crate::ticker_poller::rest_pair_name;
+use crate::trade_backfill::{parse_rest_trades, trades_backfill_url, ResumePoint};
+use kraken_types::{Channel, KrakenError};
+use kraken_ws::backfill::{BackfillData, BackfillRequest, Backfiller};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+/// Candles fetched per interval on the first connect by default
+pub const DEFAULT_OHLC_BACKFILL: usize = 720;
+
+type FetchFuture = Pin<Box<dyn Future<Output = Result<String, String>> + Send>>;
+type FetchFn = dyn Fn(String) -> FetchFuture + Send + Sync;
+
+/// Fetch function mapping a REST URL to its response body
+#[derive(Clone)]
+pub struct RestFetch(Arc<FetchFn>);
+
+impl RestFetch {
+    /// Wrap an async fetch function
+    pub fn new<F, Fut, E>(fetch: F) -> Self
+    where
+        F: Fn(String) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, E>> + Send + 'static,
+        E: std::fmt::Display,
+    {
+        Self(Arc::new(move |url| {
+            let response = fetch(url);
+            Box::pin(async move { response.await.map_err(|e| e.to_string()) })
+        }))
+    }
+
+    /// Fetch with a shared `reqwest` client
+    #[cfg(feature = "backfill")]
+    pub fn reqwest() -> Self {
+        let client = reqwest::Client::new();
+        Self::new(move |url: String| {
+            let client = client.clone();
+            async move {
+                client
+                    .get(&url)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())?
+                    .text()
+                    .await
+            }
+        })
+    }
+
+    /// Fetch the body at `url`
+    pub async fn get(&self, url: String) -> Result<String, String> {
+        (self.0)(url).await
+    }
+}
+
+impl std::fmt::Debug for RestFetch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RestFetch").finish_non_exhaustive()
+    }
+}
+
+/// URL fetching the window for a backfill request
+///
+/// `n_candles` sizes the OHLC window when the request has no `since`, and
+/// `now` is the current Unix time in seconds.
+pub fn backfill_url(request: &BackfillRequest, n_candles: usize, now: i64) -> String {
+    match request {
+        BackfillRequest::Trades { symbol, after } => {
+            let since = ResumePoint {
+                trade_id: after.trade_id,
+                timestamp: after.timestamp.clone(),
+            };
+            trades_backfill_url(&rest_pair_name(symbol), Some(&since))
+        }
+        BackfillRequest::Ohlc { symbol, interval, since: None } => {
+            ohlc_backfill_url(&rest_pair_name(symbol), *interval, n_candles, now)
+        }
+        // `since` is exclusive; step back a second to include the last
+        // candle delivered, which may have been updated since
+        BackfillRequest::Ohlc { symbol, interval, since: Some(since) } => format!(
+            "{}?pair={}&interval={}&since={}",
+            REST_OHLC_URL,
+            rest_pair_name(symbol),
+            interval,
+            since - 1
+        ),
+    }
+}
+
+/// Parse the REST response for a backfill request
+pub fn parse_backfill(request: &BackfillRequest, body: &str) -> Result<BackfillData, KrakenError> {
+    match request {
+        BackfillRequest::Trades { symbol, .. } => parse_rest_trades(body, symbol)
+            .map(BackfillData::Trades)
+            .map_err(|e| match e {
+                crate::trade_backfill::TradeBackfillError::Api(message) => KrakenError::from_api_error(message),
+                other => KrakenError::UnexpectedMessage(other.to_string()),
+            }),
+        BackfillRequest::Ohlc { symbol, interval, .. } => parse_rest_ohlc(body, symbol, *interval)
+            .map(BackfillData::Ohlc)
+            .map_err(|e| match e {
+                crate::candles::CandleError::Api(message) => KrakenError::from_api_error(message),
+                other => KrakenError::UnexpectedMessage(other.to_string()),
+            }),
+    }
+}
+
+/// Backfiller fetching from Kraken REST
+///
+/// `trades` enables the trade channel; `ohlc` enables the OHLC channel with
+/// the number of candles fetched per interval on the first connect.
+pub fn rest_backfiller(fetch: RestFetch, trades: bool, ohlc: Option<usize>) -> Backfiller {
+    let n_candles = ohlc.unwrap_or(DEFAULT_OHLC_BACKFILL);
+    let channels = [(trades, Channel::Trade), (ohlc.is_some(), Channel::Ohlc)]
+        .into_iter()
+        .filter_map(|(enabled, channel)| enabled.then_some(channel));
+    Backfiller::new(channels, move |request: BackfillRequest| {
+        let fetch = fetch.clone();
+        async move {
+            let now = chrono::Utc::now().timestamp();
+            let url = backfill_url(&request, n_candles, now);
+            let body = fetch.get(url.clone()).await.map_err(|reason| KrakenError::ConnectionFailed { url, reason })?;
+            parse_backfill(&request, &body)
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_ws::backfill::TradeCursor;
+    use std::sync::Mutex;
+
+    type Urls = Arc<Mutex<Vec<String>>>;
+
+    fn stub(body: &'static str, urls: &Urls) -> RestFetch {
+        let urls = Arc::clone(urls);
+        RestFetch::new(move |url: String| {
+            urls.lock().unwrap().push(url);
+            async move { Ok::<_, String>(body.to_string()) }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_trades_resume_from_cursor() {
+        let body = r#"{"error":[],"result":{"XXBTZUSD":[
+            ["42000.0","0.5",1704067201.5,"b","l","",41],
+            ["42001.0","0.1",1704067202.5,"s","m","",42]
+        ],"last":"1704067202500000000"}}"#;
+        let urls = Urls::default();
+        let backfiller = rest_backfiller(stub(body, &urls), true, None);
+        assert!(backfiller.covers(Channel::Trade));
+        assert!(!backfiller.covers(Channel::Ohlc));
+
+        let request = BackfillRequest::Trades {
+            symbol: "BTC/USD".to_string(),
+            after: TradeCursor {
+                trade_id: 40,
+                timestamp: "2024-01-01T00:00:00.000000Z".to_string(),
+            },
+        };
+        let Ok(BackfillData::Trades(trades)) = backfiller.fetch(request).await else {
+            panic!("expected trades");
+        };
+        assert_eq!(trades.iter().map(|t| t.trade_id).collect::<Vec<_>>(), vec![41, 42]);
+        assert_eq!(urls.lock().unwrap()[0], "https://api.kraken.com/0/public/Trades?pair=XBTUSD&since=1704067200");
+    }
+
+    #[tokio::test]
+    async fn test_ohlc_since_includes_last_candle() {
+        let urls = Urls::default();
+        let backfiller = rest_backfiller(stub(r#"{"error":["EGeneral:Too many requests"]}"#, &urls), false, Some(10));
+        let request = BackfillRequest::Ohlc {
+            symbol: "ETH/USD".to_string(),
+            interval: 5,
+            since: Some(1_704_067_200),
+        };
+        let error = backfiller.fetch(request).await.unwrap_err();
+        assert!(error.is_rate_limit(), "{error}");
+        assert_eq!(
+            urls.lock().unwrap()[0],
+            "https://api.kraken.com/0/public/OHLC?pair=ETHUSD&interval=5&since=1704067199"
+        );
+    }
+}
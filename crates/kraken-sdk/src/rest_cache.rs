@@ -0,0 +1,367 @@
+//! Response cache for static REST endpoints
+//!
+//! `AssetPairs` and `Assets` change a few times a year, yet tools commonly
+//! fetch them on every start, paying the latency and the REST rate-limit
+//! cost each time. [`RestCache`] keeps those responses with a TTL, serves
+//! them stale for a further window while a single caller revalidates, and
+//! can persist them through a [`CacheBackend`] so they survive restarts.
+//!
+//! The cache does no I/O of its own: look a key up, fetch on
+//! [`CacheLookup::Miss`] or when told to revalidate, and [`store`](RestCache::store)
+//! the response.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::rest_cache::{CacheLookup, RestCache};
+//! use std::time::Duration;
+//!
+//! let mut cache = RestCache::new(Duration::from_secs(3600));
+//! let url = "https://api.kraken.com/0/public/AssetPairs";
+//!
+//! let body = match cache.lookup(url) {
+//!     CacheLookup::Fresh(body) => body,
+//!     CacheLookup::Stale { body, revalidate } => {
+//!         if revalidate {
+//!             // refresh in the background, then cache.store(url, fresh)
+//!         }
+//!         body
+//!     }
+//!     CacheLookup::Miss => {
+//!         let fetched = r#"{"error":[],"result":{}}"#.to_string(); // REST call here
+//!         cache.store(url, fetched.clone());
+//!         fetched
+//!     }
+//! };
+//! assert!(matches!(cache.lookup(url), CacheLookup::Fresh(_)));
+//! # let _ = body;
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::warn;
+
+/// REST paths whose responses are safe to cache
+pub const STATIC_ENDPOINTS: &[&str] = &["/0/public/AssetPairs", "/0/public/Assets"];
+
+/// Returns true if a URL or path points at a static endpoint
+pub fn is_static_endpoint(url: &str) -> bool {
+    let path = url.split('?').next().unwrap_or(url);
+    STATIC_ENDPOINTS.iter().any(|endpoint| path.ends_with(endpoint))
+}
+
+/// A cached response body and when it was stored
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// Raw response body
+    pub body: String,
+    /// Unix time (seconds) the response was stored
+    pub stored_at: u64,
+}
+
+/// Persistent storage behind the in-memory cache
+pub trait CacheBackend: Send + Sync {
+    /// Load an entry
+    fn load(&self, key: &str) -> Option<CachedResponse>;
+
+    /// Save an entry
+    fn save(&self, key: &str, entry: &CachedResponse);
+
+    /// Remove an entry
+    fn remove(&self, key: &str);
+}
+
+/// Backend storing one JSON file per key in a directory
+///
+/// File names are a readable prefix of the key plus a hash of the whole
+/// key, and each file records the key it was saved under, so keys that
+/// sanitize to the same name never read each other's entries.
+#[derive(Debug, Clone)]
+pub struct FileCacheBackend {
+    dir: PathBuf,
+}
+
+/// On-disk form of an entry, tagged with its full key
+#[derive(Serialize, Deserialize)]
+struct FileEntry<T> {
+    key: String,
+    response: T,
+}
+
+/// Longest readable key prefix kept in a file name
+const FILE_NAME_PREFIX: usize = 48;
+
+/// 64-bit FNV-1a, stable across builds and platforms
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+impl FileCacheBackend {
+    /// Store entries under `dir`, created on first save
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        let prefix: String = key
+            .chars()
+            .take(FILE_NAME_PREFIX)
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}-{:016x}.json", prefix, fnv1a(key.as_bytes())))
+    }
+}
+
+impl CacheBackend for FileCacheBackend {
+    fn load(&self, key: &str) -> Option<CachedResponse> {
+        let text = std::fs::read_to_string(self.path(key)).ok()?;
+        let stored: FileEntry<CachedResponse> = serde_json::from_str(&text).ok()?;
+        // A hash collision must read as a miss, not another key's body
+        (stored.key == key).then_some(stored.response)
+    }
+
+    fn save(&self, key: &str, entry: &CachedResponse) {
+        let result = std::fs::create_dir_all(&self.dir).and_then(|()| {
+            let stored = FileEntry { key: key.to_string(), response: entry };
+            let json = serde_json::to_string(&stored).map_err(std::io::Error::other)?;
+            std::fs::write(self.path(key), json)
+        });
+        if let Err(e) = result {
+            warn!(key, "Failed to persist cached response: {}", e);
+        }
+    }
+
+    fn remove(&self, key: &str) {
+        let _ = std::fs::remove_file(self.path(key));
+    }
+}
+
+/// Result of a cache lookup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheLookup {
+    /// Within the TTL; use as is
+    Fresh(String),
+    /// Past the TTL but inside the stale window; usable while refreshing
+    Stale {
+        /// Cached response body
+        body: String,
+        /// True for the one caller that should fetch a fresh copy
+        revalidate: bool,
+    },
+    /// Nothing usable cached; fetch and store
+    Miss,
+}
+
+/// TTL cache for static REST responses
+pub struct RestCache {
+    ttl: Duration,
+    stale_window: Duration,
+    entries: HashMap<String, CachedResponse>,
+    revalidating: HashSet<String>,
+    backend: Option<Box<dyn CacheBackend>>,
+}
+
+impl fmt::Debug for RestCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RestCache")
+            .field("ttl", &self.ttl)
+            .field("stale_window", &self.stale_window)
+            .field("entries", &self.entries.len())
+            .field("backend", &self.backend.is_some())
+            .finish()
+    }
+}
+
+impl RestCache {
+    /// Create an in-memory cache; entries are fresh for `ttl`
+    ///
+    /// The stale window defaults to the TTL.
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            stale_window: ttl,
+            entries: HashMap::new(),
+            revalidating: HashSet::new(),
+            backend: None,
+        }
+    }
+
+    /// Serve expired entries for up to `window` past the TTL while revalidating
+    pub fn with_stale_window(mut self, window: Duration) -> Self {
+        self.stale_window = window;
+        self
+    }
+
+    /// Persist entries through a backend
+    pub fn with_backend(mut self, backend: impl CacheBackend + 'static) -> Self {
+        self.backend = Some(Box::new(backend));
+        self
+    }
+
+    /// Look up a key at the current time
+    pub fn lookup(&mut self, key: &str) -> CacheLookup {
+        self.lookup_at(key, SystemTime::now())
+    }
+
+    /// Look up a key at `now`
+    pub fn lookup_at(&mut self, key: &str, now: SystemTime) -> CacheLookup {
+        if !self.entries.contains_key(key) {
+            if let Some(entry) = self.backend.as_ref().and_then(|b| b.load(key)) {
+                self.entries.insert(key.to_string(), entry);
+            }
+        }
+        let Some(entry) = self.entries.get(key) else {
+            return CacheLookup::Miss;
+        };
+
+        let age = Duration::from_secs(unix_secs(now).saturating_sub(entry.stored_at));
+        if age < self.ttl {
+            CacheLookup::Fresh(entry.body.clone())
+        } else if age < self.ttl + self.stale_window {
+            let body = entry.body.clone();
+            let revalidate = self.revalidating.insert(key.to_string());
+            CacheLookup::Stale { body, revalidate }
+        } else {
+            self.revalidating.remove(key);
+            CacheLookup::Miss
+        }
+    }
+
+    /// Store a response at the current time
+    pub fn store(&mut self, key: &str, body: String) {
+        self.store_at(key, body, SystemTime::now());
+    }
+
+    /// Store a response at `now`
+    pub fn store_at(&mut self, key: &str, body: String, now: SystemTime) {
+        let entry = CachedResponse {
+            body,
+            stored_at: unix_secs(now),
+        };
+        if let Some(backend) = &self.backend {
+            backend.save(key, &entry);
+        }
+        self.revalidating.remove(key);
+        self.entries.insert(key.to_string(), entry);
+    }
+
+    /// Drop a key so the next lookup misses
+    ///
+    /// Also call this when a revalidation fails, so another caller retries.
+    pub fn invalidate(&mut self, key: &str) {
+        self.entries.remove(key);
+        self.revalidating.remove(key);
+        if let Some(backend) = &self.backend {
+            backend.remove(key);
+        }
+    }
+
+    /// Drop every in-memory entry and its persisted copy
+    pub fn invalidate_all(&mut self) {
+        let keys: Vec<String> = self.entries.keys().cloned().collect();
+        for key in keys {
+            self.invalidate(&key);
+        }
+    }
+
+    /// Number of entries held in memory
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if nothing is held in memory
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: &str = "https://api.kraken.com/0/public/AssetPairs";
+
+    fn at(secs: u64) -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn test_fresh_stale_and_expired() {
+        let mut cache = RestCache::new(Duration::from_secs(60)).with_stale_window(Duration::from_secs(30));
+        assert_eq!(cache.lookup_at(KEY, at(1000)), CacheLookup::Miss);
+
+        cache.store_at(KEY, "v1".to_string(), at(1000));
+        assert_eq!(cache.lookup_at(KEY, at(1059)), CacheLookup::Fresh("v1".to_string()));
+
+        // Only the first stale reader revalidates
+        assert_eq!(
+            cache.lookup_at(KEY, at(1070)),
+            CacheLookup::Stale { body: "v1".to_string(), revalidate: true }
+        );
+        assert_eq!(
+            cache.lookup_at(KEY, at(1071)),
+            CacheLookup::Stale { body: "v1".to_string(), revalidate: false }
+        );
+        cache.store_at(KEY, "v2".to_string(), at(1072));
+        assert_eq!(cache.lookup_at(KEY, at(1073)), CacheLookup::Fresh("v2".to_string()));
+
+        assert_eq!(cache.lookup_at(KEY, at(1200)), CacheLookup::Miss);
+    }
+
+    #[test]
+    fn test_invalidate_and_static_endpoints() {
+        let mut cache = RestCache::new(Duration::from_secs(60));
+        cache.store(KEY, "v1".to_string());
+        assert_eq!(cache.len(), 1);
+        cache.invalidate(KEY);
+        assert_eq!(cache.lookup(KEY), CacheLookup::Miss);
+
+        assert!(is_static_endpoint(KEY));
+        assert!(is_static_endpoint("/0/public/Assets?asset=XBT"));
+        assert!(!is_static_endpoint("/0/public/Ticker?pair=XBTUSD"));
+    }
+
+    #[test]
+    fn test_file_backend_survives_restart() {
+        let dir = std::env::temp_dir().join(format!("havklo-rest-cache-{}", std::process::id()));
+        let mut cache = RestCache::new(Duration::from_secs(60)).with_backend(FileCacheBackend::new(&dir));
+        cache.store_at(KEY, "persisted".to_string(), at(1000));
+
+        let mut restarted = RestCache::new(Duration::from_secs(60)).with_backend(FileCacheBackend::new(&dir));
+        assert_eq!(restarted.lookup_at(KEY, at(1010)), CacheLookup::Fresh("persisted".to_string()));
+
+        restarted.invalidate_all();
+        let mut again = RestCache::new(Duration::from_secs(60)).with_backend(FileCacheBackend::new(&dir));
+        assert_eq!(again.lookup_at(KEY, at(1010)), CacheLookup::Miss);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_file_backend_keeps_similar_keys_apart() {
+        let dir = std::env::temp_dir().join(format!("havklo-rest-cache-keys-{}", std::process::id()));
+        let backend = FileCacheBackend::new(&dir);
+        let entry = |body: &str| CachedResponse {
+            body: body.to_string(),
+            stored_at: 1000,
+        };
+        // Both sanitize to the same readable name
+        backend.save("AssetPairs?pair=XBT/USD", &entry("slash"));
+        backend.save("AssetPairs?pair=XBT_USD", &entry("underscore"));
+        assert_eq!(backend.load("AssetPairs?pair=XBT/USD").unwrap().body, "slash");
+        assert_eq!(backend.load("AssetPairs?pair=XBT_USD").unwrap().body, "underscore");
+
+        // An entry under the wrong file name is not trusted
+        std::fs::copy(backend.path("AssetPairs?pair=XBT/USD"), backend.path("Assets")).unwrap();
+        assert_eq!(backend.load("Assets"), None);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,467 @@
+//! Rolling high, low, volume and VWAP computed from trades
+//!
+//! Kraken's ticker publishes 24h aggregates at its own cadence and only for
+//! the 24h window. [`RollingStats`] derives the same figures locally from the
+//! trade channel over any set of windows (1h, 4h and 24h by default),
+//! updated on every trade. It also keeps the latest exchange ticker per
+//! symbol, so [`RollingStats::ticker_24h`] can show both side by side and
+//! [`Ticker24h::drift`] reports how far they disagree.
+//!
+//! Windows are measured on exchange timestamps and end at the latest trade,
+//! or at the latest ticker for a symbol that has gone quiet. A window is
+//! [`complete`](WindowStats::complete) once trades have been recorded for
+//! its full length; until then local volume undercounts the exchange's.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::rolling_stats::RollingStats;
+//! use kraken_types::{Price, Qty};
+//! use rust_decimal_macros::dec;
+//! use std::time::Duration;
+//!
+//! const MIN: i64 = 60_000_000;
+//! let mut stats = RollingStats::new().with_window(Duration::from_secs(3600));
+//!
+//! stats.record("BTC/USD", Price::new(dec!(100)), Qty::new(dec!(2)), 0);
+//! stats.record("BTC/USD", Price::new(dec!(110)), Qty::new(dec!(1)), 40 * MIN);
+//! stats.record("BTC/USD", Price::new(dec!(105)), Qty::new(dec!(1)), 90 * MIN);
+//!
+//! // The first trade has left the hour
+//! let hour = stats.stats("BTC/USD", Duration::from_secs(3600)).unwrap();
+//! assert_eq!(hour.high, Price::new(dec!(110)));
+//! assert_eq!(hour.volume, Qty::new(dec!(2)));
+//! assert_eq!(hour.vwap, Some(Price::new(dec!(107.5))));
+//! ```
+
+use kraken_types::{Decimal, Notional, Price, Qty, TickerData};
+use kraken_ws::{Event, MarketEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// Default windows: 1h, 4h and 24h
+pub const DEFAULT_STATS_WINDOWS: [Duration; 3] = [
+    Duration::from_secs(3600),
+    Duration::from_secs(4 * 3600),
+    Duration::from_secs(24 * 3600),
+];
+
+/// The window the exchange ticker covers
+pub const TICKER_WINDOW: Duration = Duration::from_secs(24 * 3600);
+
+/// Aggregates over one window
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowStats {
+    /// Window length
+    pub window: Duration,
+    /// First trade price in the window
+    pub open: Price,
+    /// Highest trade price
+    pub high: Price,
+    /// Lowest trade price
+    pub low: Price,
+    /// Latest trade price
+    pub last: Price,
+    /// Traded quantity
+    pub volume: Qty,
+    /// Traded value (price * qty)
+    pub notional: Notional,
+    /// Volume-weighted average price (None if volume is zero)
+    pub vwap: Option<Price>,
+    /// Trades in the window
+    pub trades: usize,
+    /// Trades have been recorded for the full window length
+    pub complete: bool,
+}
+
+impl WindowStats {
+    /// Price change from open to last
+    pub fn change(&self) -> Price {
+        self.last - self.open
+    }
+
+    /// Price change from open to last, in percent
+    pub fn change_pct(&self) -> Option<Decimal> {
+        (!self.open.is_zero()).then(|| self.change().0 / self.open.0 * Decimal::ONE_HUNDRED)
+    }
+}
+
+/// Local minus exchange 24h figures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TickerDrift {
+    /// Volume difference, in percent of the exchange volume
+    pub volume_pct: Option<Decimal>,
+    /// VWAP difference, in basis points of the exchange VWAP
+    pub vwap_bps: Option<Decimal>,
+    /// High difference
+    pub high: Decimal,
+    /// Low difference
+    pub low: Decimal,
+}
+
+/// Local and exchange 24h statistics for one symbol
+#[derive(Debug, Clone)]
+pub struct Ticker24h {
+    /// Computed from recorded trades (None without a 24h window or trades)
+    pub local: Option<WindowStats>,
+    /// Latest exchange ticker
+    pub exchange: Option<TickerData>,
+}
+
+impl Ticker24h {
+    /// How far the local figures are from the exchange's
+    ///
+    /// None unless both sides are present. Only meaningful once the local
+    /// window is [`complete`](WindowStats::complete).
+    pub fn drift(&self) -> Option<TickerDrift> {
+        let local = self.local.as_ref()?;
+        let exchange = self.exchange.as_ref()?;
+        let relative = |local: Decimal, exchange: Decimal, scale: Decimal| {
+            (!exchange.is_zero()).then(|| (local - exchange) / exchange * scale)
+        };
+        Some(TickerDrift {
+            volume_pct: relative(local.volume.0, exchange.volume, Decimal::ONE_HUNDRED),
+            vwap_bps: local
+                .vwap
+                .and_then(|vwap| relative(vwap.0, exchange.vwap, Decimal::from(10_000))),
+            high: local.high.0 - exchange.high,
+            low: local.low.0 - exchange.low,
+        })
+    }
+}
+
+/// Running aggregates for one window over a symbol's shared trade buffer
+#[derive(Debug, Clone)]
+struct WindowState {
+    length_us: i64,
+    /// Sequence number of the oldest trade in the window
+    start: u64,
+    volume: Qty,
+    notional: Notional,
+    /// Candidates for the high: decreasing prices, by sequence number
+    maxima: VecDeque<(u64, Price)>,
+    /// Candidates for the low: increasing prices, by sequence number
+    minima: VecDeque<(u64, Price)>,
+}
+
+impl WindowState {
+    fn new(length: Duration) -> Self {
+        Self {
+            length_us: length.as_micros() as i64,
+            start: 0,
+            volume: Qty::ZERO,
+            notional: Notional::ZERO,
+            maxima: VecDeque::new(),
+            minima: VecDeque::new(),
+        }
+    }
+}
+
+/// Per-symbol trade buffer and windows
+#[derive(Debug, Clone)]
+struct SymbolStats {
+    /// Trades `(at_us, price, qty)` covering the longest window
+    trades: VecDeque<(i64, Price, Qty)>,
+    /// Sequence number of `trades[0]`
+    base: u64,
+    first_us: i64,
+    now_us: i64,
+    windows: Vec<(Duration, WindowState)>,
+}
+
+impl SymbolStats {
+    fn new(windows: &[Duration], at_us: i64) -> Self {
+        Self {
+            trades: VecDeque::new(),
+            base: 0,
+            first_us: at_us,
+            now_us: at_us,
+            windows: windows.iter().map(|w| (*w, WindowState::new(*w))).collect(),
+        }
+    }
+
+    fn next_seq(&self) -> u64 {
+        self.base + self.trades.len() as u64
+    }
+
+    fn push(&mut self, price: Price, qty: Qty, at_us: i64) {
+        let seq = self.next_seq();
+        self.trades.push_back((at_us, price, qty));
+        for (_, window) in &mut self.windows {
+            window.volume += qty;
+            window.notional += price * qty;
+            while window.maxima.back().is_some_and(|(_, p)| *p <= price) {
+                window.maxima.pop_back();
+            }
+            window.maxima.push_back((seq, price));
+            while window.minima.back().is_some_and(|(_, p)| *p >= price) {
+                window.minima.pop_back();
+            }
+            window.minima.push_back((seq, price));
+        }
+        self.advance(at_us);
+    }
+
+    /// Move every window's end to `now_us`, dropping trades that fell out
+    fn advance(&mut self, now_us: i64) {
+        self.now_us = self.now_us.max(now_us);
+        let end = self.next_seq();
+        for (_, window) in &mut self.windows {
+            while window.start < end {
+                let (at, price, qty) = self.trades[(window.start - self.base) as usize];
+                if at > self.now_us - window.length_us {
+                    break;
+                }
+                window.volume -= qty;
+                window.notional -= price * qty;
+                window.start += 1;
+            }
+            while window.maxima.front().is_some_and(|(seq, _)| *seq < window.start) {
+                window.maxima.pop_front();
+            }
+            while window.minima.front().is_some_and(|(seq, _)| *seq < window.start) {
+                window.minima.pop_front();
+            }
+        }
+        let keep_from = self.windows.iter().map(|(_, w)| w.start).min().unwrap_or(end);
+        while self.base < keep_from {
+            self.trades.pop_front();
+            self.base += 1;
+        }
+    }
+
+    fn stats(&self, length: Duration) -> Option<WindowStats> {
+        let (_, window) = self.windows.iter().find(|(w, _)| *w == length)?;
+        let (_, high) = *window.maxima.front()?;
+        let (_, low) = *window.minima.front()?;
+        let (_, open, _) = self.trades[(window.start - self.base) as usize];
+        let (_, last, _) = *self.trades.back()?;
+        Some(WindowStats {
+            window: length,
+            open,
+            high,
+            low,
+            last,
+            volume: window.volume,
+            notional: window.notional,
+            vwap: (!window.volume.is_zero()).then(|| window.notional / window.volume),
+            trades: (self.next_seq() - window.start) as usize,
+            complete: self.now_us - self.first_us >= window.length_us,
+        })
+    }
+}
+
+/// Per-symbol rolling trade statistics and the latest exchange ticker
+#[derive(Debug, Clone)]
+pub struct RollingStats {
+    symbols: HashMap<String, SymbolStats>,
+    tickers: HashMap<String, TickerData>,
+    windows: Vec<Duration>,
+}
+
+impl Default for RollingStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RollingStats {
+    /// Create a tracker without windows
+    ///
+    /// Add windows with [`with_window`](Self::with_window), or use
+    /// [`with_default_windows`](Self::with_default_windows).
+    pub fn new() -> Self {
+        Self {
+            symbols: HashMap::new(),
+            tickers: HashMap::new(),
+            windows: Vec::new(),
+        }
+    }
+
+    /// Track statistics over a window (ignored if already tracked)
+    ///
+    /// Windows apply to symbols first seen after they are added.
+    pub fn with_window(mut self, window: Duration) -> Self {
+        if !window.is_zero() && !self.windows.contains(&window) {
+            self.windows.push(window);
+            self.windows.sort();
+        }
+        self
+    }
+
+    /// Track statistics over 1h, 4h and 24h
+    pub fn with_default_windows(self) -> Self {
+        DEFAULT_STATS_WINDOWS.iter().fold(self, |stats, w| stats.with_window(*w))
+    }
+
+    /// Configured windows, shortest first
+    pub fn windows(&self) -> &[Duration] {
+        &self.windows
+    }
+
+    /// Record a trade at exchange time `at_us` (µs since epoch)
+    ///
+    /// Trades are expected in time order; an older timestamp is treated as
+    /// arriving at the latest time seen.
+    pub fn record(&mut self, symbol: &str, price: Price, qty: Qty, at_us: i64) {
+        let windows = &self.windows;
+        let stats = self
+            .symbols
+            .entry(symbol.to_string())
+            .or_insert_with(|| SymbolStats::new(windows, at_us));
+        stats.push(price, qty, at_us);
+    }
+
+    /// Record a trade, or store a ticker and expire trades up to its time
+    ///
+    /// Uses the exchange timestamp, or the local receive time if the
+    /// message had none.
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::Market(MarketEvent::Trade {
+                symbol,
+                trade,
+                received_at,
+                exchange_ts_us,
+                ..
+            }) => {
+                let at_us = exchange_ts_us.unwrap_or(received_at.wall_us);
+                self.record(symbol, trade.price.into(), trade.qty.into(), at_us);
+            }
+            Event::Market(MarketEvent::Ticker {
+                symbol,
+                ticker,
+                received_at,
+                exchange_ts_us,
+                ..
+            }) => {
+                if let Some(stats) = self.symbols.get_mut(symbol) {
+                    stats.advance(exchange_ts_us.unwrap_or(received_at.wall_us));
+                }
+                self.tickers.insert(symbol.clone(), ticker.clone());
+            }
+            _ => {}
+        }
+    }
+
+    /// Expire trades for every symbol up to `now_us`
+    ///
+    /// Without this, a symbol with no new trades or tickers keeps
+    /// reporting the window that ended at its last trade.
+    pub fn advance_to(&mut self, now_us: i64) {
+        for stats in self.symbols.values_mut() {
+            stats.advance(now_us);
+        }
+    }
+
+    /// Aggregates over a configured window
+    ///
+    /// None if the window isn't configured or holds no trades.
+    pub fn stats(&self, symbol: &str, window: Duration) -> Option<WindowStats> {
+        self.symbols.get(symbol)?.stats(window)
+    }
+
+    /// Latest exchange ticker
+    pub fn exchange_ticker(&self, symbol: &str) -> Option<&TickerData> {
+        self.tickers.get(symbol)
+    }
+
+    /// Local and exchange 24h statistics side by side
+    pub fn ticker_24h(&self, symbol: &str) -> Ticker24h {
+        Ticker24h {
+            local: self.stats(symbol, TICKER_WINDOW),
+            exchange: self.tickers.get(symbol).cloned(),
+        }
+    }
+
+    /// Symbols with recorded trades
+    pub fn symbols(&self) -> impl Iterator<Item = &str> {
+        self.symbols.keys().map(String::as_str)
+    }
+
+    /// Forget a symbol's trades and ticker
+    pub fn reset(&mut self, symbol: &str) {
+        self.symbols.remove(symbol);
+        self.tickers.remove(symbol);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const MIN: i64 = 60_000_000;
+
+    fn trade(stats: &mut RollingStats, price: Decimal, qty: Decimal, at_us: i64) {
+        stats.record("BTC/USD", Price::new(price), Qty::new(qty), at_us);
+    }
+
+    #[test]
+    fn test_high_low_follow_the_window() {
+        let hour = Duration::from_secs(3600);
+        let mut stats = RollingStats::new().with_window(hour).with_window(4 * hour);
+        trade(&mut stats, dec!(120), dec!(1), 0);
+        trade(&mut stats, dec!(90), dec!(1), 10 * MIN);
+        trade(&mut stats, dec!(100), dec!(1), 50 * MIN);
+        trade(&mut stats, dec!(105), dec!(1), 80 * MIN);
+
+        let short = stats.stats("BTC/USD", hour).unwrap();
+        assert_eq!((short.open, short.high, short.low), (Price::new(dec!(100)), Price::new(dec!(105)), Price::new(dec!(100))));
+        assert_eq!(short.trades, 2);
+        assert!(short.complete);
+        assert_eq!(short.change_pct(), Some(dec!(5)));
+
+        let long = stats.stats("BTC/USD", 4 * hour).unwrap();
+        assert_eq!((long.high, long.low, long.trades), (Price::new(dec!(120)), Price::new(dec!(90)), 4));
+        assert!(!long.complete);
+        assert!(stats.stats("BTC/USD", 2 * hour).is_none());
+
+        // Quiet symbol: everything expires
+        stats.advance_to(400 * MIN);
+        assert!(stats.stats("BTC/USD", 4 * hour).is_none());
+    }
+
+    #[test]
+    fn test_buffer_only_holds_longest_window() {
+        let mut stats = RollingStats::new().with_window(Duration::from_secs(60));
+        for i in 0..1000 {
+            trade(&mut stats, Decimal::from(100 + i % 7), dec!(1), i * 1_000_000);
+        }
+        let symbol = &stats.symbols["BTC/USD"];
+        assert_eq!(symbol.trades.len(), 60);
+        let minute = stats.stats("BTC/USD", Duration::from_secs(60)).unwrap();
+        assert_eq!(minute.volume, Qty::new(dec!(60)));
+        assert_eq!((minute.high, minute.low), (Price::new(dec!(106)), Price::new(dec!(100))));
+    }
+
+    #[test]
+    fn test_drift_against_exchange_ticker() {
+        let mut stats = RollingStats::new().with_default_windows();
+        trade(&mut stats, dec!(100), dec!(6), 0);
+        trade(&mut stats, dec!(110), dec!(5), MIN);
+
+        let mut side_by_side = stats.ticker_24h("BTC/USD");
+        assert!(side_by_side.drift().is_none());
+        side_by_side.exchange = Some(TickerData {
+            symbol: "BTC/USD".into(),
+            bid: dec!(109),
+            bid_qty: dec!(1),
+            ask: dec!(111),
+            ask_qty: dec!(1),
+            last: dec!(110),
+            volume: dec!(10),
+            vwap: dec!(105),
+            low: dec!(99),
+            high: dec!(110),
+            change: dec!(10),
+            change_pct: dec!(10),
+        });
+
+        let drift = side_by_side.drift().unwrap();
+        assert_eq!(drift.volume_pct, Some(dec!(10)));
+        assert_eq!(drift.vwap_bps.map(|bps| bps.round_dp(2)), Some(dec!(-43.29)));
+        assert_eq!((drift.high, drift.low), (dec!(0), dec!(1)));
+    }
+}
@@ -0,0 +1,404 @@
+//! Local HTTP market data gateway
+//!
+//! [`MarketGateway`] serves the SDK's live state over HTTP so other
+//! processes on the host can read books and tickers without their own
+//! Kraken connection. It is built on [`MarketDataBridge`], so the JSON
+//! shapes are the bridge's wire format.
+//!
+//! | Route                    | Response                                          |
+//! |--------------------------|---------------------------------------------------|
+//! | `GET /health`            | Connection and gateway health                     |
+//! | `GET /books`             | Every book as a `book` message (`?depth=N` trims) |
+//! | `GET /books/{symbol}`    | One book, e.g. `/books/BTC/USD`; 404 if unknown   |
+//! | `GET /tickers`           | Latest ticker per symbol                          |
+//! | `GET /tickers/{symbol}`  | One ticker; 404 if unknown                        |
+//! | `GET /events`            | Server-sent events, one bridge message per event  |
+//!
+//! `/events` starts with a `book` event per known book followed by live
+//! messages, exactly like a bridge socket client. A stream that falls behind
+//! is closed; SSE clients reconnect on their own and get fresh books.
+//!
+//! The `kraken-serve` binary wires this up for a list of symbols.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraken_sdk::prelude::*;
+//! use kraken_sdk::serve::MarketGateway;
+//! use std::sync::Arc;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut client = KrakenClient::builder(["BTC/USD"])
+//!     .with_book(true)
+//!     .with_ticker(true)
+//!     .connect()
+//!     .await?;
+//! let events = client.events().unwrap();
+//! let client = Arc::new(client);
+//!
+//! let gateway = MarketGateway::new(1024).with_health(move || client.health());
+//! let server = gateway.clone();
+//! tokio::spawn(async move { server.serve("127.0.0.1:8080").await });
+//! gateway.forward(events).await;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::bridge::{BridgeMessage, BridgeStats, MarketDataBridge};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::sse::{Event as SseEvent, KeepAlive, Sse};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use futures::stream::{self, Stream, StreamExt};
+use kraken_types::TickerData;
+use kraken_ws::{ConnectionEvent, Event, EventReceiver, HealthStats, MarketEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::convert::Infallible;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::net::{TcpListener, ToSocketAddrs};
+use tokio::sync::broadcast;
+use tracing::warn;
+
+type HealthProvider = Arc<dyn Fn() -> HealthStats + Send + Sync>;
+
+/// Body of `GET /health`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// Whether the upstream Kraken connection is up
+    pub connected: bool,
+    /// Seconds since the connection became ready
+    pub connected_for_secs: Option<f64>,
+    /// Seconds since any message arrived
+    pub since_last_message_secs: Option<f64>,
+    /// Seconds since the last heartbeat
+    pub heartbeat_age_secs: Option<f64>,
+    /// Messages received per channel
+    pub messages_by_channel: BTreeMap<String, u64>,
+    /// Book updates rejected for a checksum mismatch
+    pub checksum_mismatches: u64,
+    /// Reconnects since the client was created
+    pub total_reconnects: u64,
+    /// Books held by the gateway
+    pub books: usize,
+    /// Open `/events` streams
+    pub streams: usize,
+    /// Gateway counters
+    pub gateway: BridgeStats,
+}
+
+/// Query parameters of `GET /books`
+#[derive(Debug, Default, Deserialize)]
+struct BookQuery {
+    depth: Option<usize>,
+}
+
+/// HTTP gateway over a [`MarketDataBridge`]
+///
+/// Cloning is cheap; clones share the same state.
+#[derive(Clone)]
+pub struct MarketGateway {
+    bridge: MarketDataBridge,
+    tickers: Arc<Mutex<BTreeMap<String, TickerData>>>,
+    connected: Arc<AtomicBool>,
+    health: Option<HealthProvider>,
+}
+
+impl std::fmt::Debug for MarketGateway {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MarketGateway")
+            .field("bridge", &self.bridge)
+            .field("connected", &self.connected.load(Ordering::Relaxed))
+            .field("health", &self.health.is_some())
+            .finish()
+    }
+}
+
+impl MarketGateway {
+    /// Create a gateway buffering up to `capacity` messages per stream
+    pub fn new(capacity: usize) -> Self {
+        Self::with_bridge(MarketDataBridge::new(capacity))
+    }
+
+    /// Create a gateway sharing state with an existing bridge
+    ///
+    /// Events must then be published through the gateway so tickers and
+    /// connection status are tracked too.
+    pub fn with_bridge(bridge: MarketDataBridge) -> Self {
+        Self {
+            bridge,
+            tickers: Arc::new(Mutex::new(BTreeMap::new())),
+            connected: Arc::new(AtomicBool::new(false)),
+            health: None,
+        }
+    }
+
+    /// Report connection health from `provider`, usually `client.health()`
+    pub fn with_health<F>(mut self, provider: F) -> Self
+    where
+        F: Fn() -> HealthStats + Send + Sync + 'static,
+    {
+        self.health = Some(Arc::new(provider));
+        self
+    }
+
+    /// Underlying bridge, e.g. to serve the same state on a socket
+    pub fn bridge(&self) -> &MarketDataBridge {
+        &self.bridge
+    }
+
+    /// Update state from an event and publish it to every stream
+    pub fn publish(&self, event: &Event) {
+        match event {
+            Event::Market(MarketEvent::Ticker { symbol, ticker, .. }) => {
+                self.lock_tickers().insert(symbol.clone(), ticker.clone());
+            }
+            Event::Connection(ConnectionEvent::Connected { .. }) => {
+                self.connected.store(true, Ordering::Relaxed);
+            }
+            Event::Connection(ConnectionEvent::Disconnected { .. }) => {
+                self.connected.store(false, Ordering::Relaxed);
+            }
+            _ => {}
+        }
+        self.bridge.publish(event);
+    }
+
+    /// Publish events until the stream ends
+    pub async fn forward(&self, mut events: EventReceiver) {
+        while let Some(event) = events.recv().await {
+            self.publish(&event);
+        }
+    }
+
+    /// Latest ticker for a symbol
+    pub fn ticker(&self, symbol: &str) -> Option<TickerData> {
+        self.lock_tickers().get(symbol).cloned()
+    }
+
+    /// Current health report
+    pub fn health(&self) -> HealthReport {
+        let stats = self.health.as_ref().map(|provider| provider());
+        let secs = |d: std::time::Duration| d.as_secs_f64();
+        HealthReport {
+            connected: match &stats {
+                Some(stats) => stats.connected_since.is_some(),
+                None => self.connected.load(Ordering::Relaxed),
+            },
+            connected_for_secs: stats.as_ref().and_then(HealthStats::connected_for).map(secs),
+            since_last_message_secs: stats.as_ref().map(|s| secs(s.since_last_message)),
+            heartbeat_age_secs: stats.as_ref().and_then(HealthStats::heartbeat_age).map(secs),
+            messages_by_channel: stats
+                .as_ref()
+                .map(|s| s.messages_by_channel.iter().map(|(k, v)| (k.to_string(), *v)).collect())
+                .unwrap_or_default(),
+            checksum_mismatches: stats.as_ref().map_or(0, |s| s.checksum_mismatches),
+            total_reconnects: stats.as_ref().map_or(0, |s| s.total_reconnects),
+            books: self.bridge.books().len(),
+            streams: self.bridge.client_count(),
+            gateway: self.bridge.stats(),
+        }
+    }
+
+    /// Routes of the gateway, for mounting into a larger application
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/health", get(health))
+            .route("/books", get(books))
+            .route("/books/{*symbol}", get(book))
+            .route("/tickers", get(tickers))
+            .route("/tickers/{*symbol}", get(ticker))
+            .route("/events", get(events))
+            .with_state(self.clone())
+    }
+
+    /// Serve on a TCP address (use a loopback address)
+    pub async fn serve(&self, addr: impl ToSocketAddrs) -> io::Result<()> {
+        let listener = TcpListener::bind(addr).await?;
+        self.serve_listener(listener).await
+    }
+
+    /// Serve on an already bound TCP listener
+    pub async fn serve_listener(&self, listener: TcpListener) -> io::Result<()> {
+        axum::serve(listener, self.router()).await
+    }
+
+    fn lock_tickers(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, TickerData>> {
+        self.tickers.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Default for MarketGateway {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+/// Book message with at most `depth` levels per side
+fn book_message(snapshot: &kraken_book::OrderbookSnapshot, depth: Option<usize>) -> BridgeMessage {
+    let mut message = BridgeMessage::book(snapshot);
+    if let (Some(depth), BridgeMessage::Book { bids, asks, .. }) = (depth, &mut message) {
+        bids.truncate(depth);
+        asks.truncate(depth);
+    }
+    message
+}
+
+fn not_found(what: &str, symbol: &str) -> Response {
+    (StatusCode::NOT_FOUND, format!("no {what} for {symbol}")).into_response()
+}
+
+async fn health(State(gateway): State<MarketGateway>) -> Json<HealthReport> {
+    Json(gateway.health())
+}
+
+async fn books(State(gateway): State<MarketGateway>, Query(query): Query<BookQuery>) -> Json<Vec<BridgeMessage>> {
+    let books = gateway.bridge.books();
+    Json(books.iter().map(|book| book_message(book, query.depth)).collect())
+}
+
+async fn book(
+    State(gateway): State<MarketGateway>,
+    Path(symbol): Path<String>,
+    Query(query): Query<BookQuery>,
+) -> Response {
+    match gateway.bridge.book(&symbol) {
+        Some(snapshot) => Json(book_message(&snapshot, query.depth)).into_response(),
+        None => not_found("book", &symbol),
+    }
+}
+
+async fn tickers(State(gateway): State<MarketGateway>) -> Json<Vec<TickerData>> {
+    Json(gateway.lock_tickers().values().cloned().collect())
+}
+
+async fn ticker(State(gateway): State<MarketGateway>, Path(symbol): Path<String>) -> Response {
+    match gateway.ticker(&symbol) {
+        Some(ticker) => Json(ticker).into_response(),
+        None => not_found("ticker", &symbol),
+    }
+}
+
+async fn events(State(gateway): State<MarketGateway>) -> Sse<impl Stream<Item = Result<SseEvent, Infallible>>> {
+    let (books, rx) = gateway.bridge.subscribe();
+    let initial = stream::iter(
+        books
+            .into_iter()
+            .map(|book| Ok(SseEvent::default().data(book.to_line().trim_end()))),
+    );
+    let live = stream::unfold((rx, gateway), |(mut rx, gateway)| async move {
+        match rx.recv().await {
+            Ok(line) => Some((Ok(SseEvent::default().data(line.trim_end())), (rx, gateway))),
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                // Diffs after a gap would corrupt the client's books
+                warn!("Closing event stream that fell {} messages behind", skipped);
+                gateway.bridge.record_lagged();
+                None
+            }
+            Err(broadcast::error::RecvError::Closed) => None,
+        }
+    });
+    Sse::new(initial.chain(live)).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_book::{OrderbookSnapshot, OrderbookState};
+    use kraken_types::Level;
+    use kraken_ws::ReceivedAt;
+    use rust_decimal_macros::dec;
+    use std::net::SocketAddr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    fn book_event(symbol: &str) -> Event {
+        let snapshot = OrderbookSnapshot {
+            symbol: symbol.to_string(),
+            bids: vec![Level::new(dec!(100), dec!(1)), Level::new(dec!(99), dec!(2))],
+            asks: vec![Level::new(dec!(101), dec!(1)), Level::new(dec!(102), dec!(2))],
+            checksum: 7,
+            state: OrderbookState::Synced,
+        };
+        Event::Market(MarketEvent::OrderbookSnapshot {
+            symbol: symbol.to_string(),
+            seq: 1,
+            snapshot,
+            received_at: ReceivedAt::now(),
+            exchange_ts_us: None,
+        })
+    }
+
+    async fn start(gateway: &MarketGateway) -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = gateway.clone();
+        tokio::spawn(async move { server.serve_listener(listener).await });
+        addr
+    }
+
+    /// Status line and body of a plain GET
+    async fn get(addr: SocketAddr, path: &str) -> (String, String) {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let request = format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n");
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.lines().next().unwrap().to_string(), body.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_books_and_tickers_over_http() {
+        let gateway = MarketGateway::new(16);
+        gateway.publish(&book_event("BTC/USD"));
+        let addr = start(&gateway).await;
+
+        let (status, body) = get(addr, "/books/BTC/USD?depth=1").await;
+        assert!(status.contains("200"), "{status}");
+        let book: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(book["type"], "book");
+        assert_eq!(book["bids"].as_array().unwrap().len(), 1);
+
+        let (status, _) = get(addr, "/books/ETH/USD").await;
+        assert!(status.contains("404"), "{status}");
+        let (_, body) = get(addr, "/tickers").await;
+        assert_eq!(body, "[]");
+    }
+
+    #[tokio::test]
+    async fn test_health_without_provider_tracks_status_events() {
+        let gateway = MarketGateway::new(16);
+        assert!(!gateway.health().connected);
+        gateway.publish(&Event::Connection(ConnectionEvent::Connected {
+            api_version: "v2".to_string(),
+            connection_id: 1,
+        }));
+        gateway.publish(&book_event("BTC/USD"));
+
+        let report = gateway.health();
+        assert!(report.connected);
+        assert_eq!(report.books, 1);
+        assert_eq!(report.gateway.published, 2);
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_starts_with_books() {
+        let gateway = MarketGateway::new(16);
+        gateway.publish(&book_event("BTC/USD"));
+
+        let response = events(State(gateway.clone())).await.into_response();
+        let mut body = response.into_body().into_data_stream();
+        let first = body.next().await.unwrap().unwrap();
+        let first = String::from_utf8(first.to_vec()).unwrap();
+        assert!(first.starts_with("data: {\"type\":\"book\""), "{first}");
+
+        gateway.publish(&book_event("ETH/USD"));
+        let second = body.next().await.unwrap().unwrap();
+        assert!(String::from_utf8(second.to_vec()).unwrap().contains("ETH/USD"));
+    }
+}
@@ -0,0 +1,701 @@
+//! Batched event sinks for data collection
+//!
+//! Converts trades, best-bid/offer changes, and order lifecycle events into
+//! flat [`SinkRecord`]s and hands them to an [`EventSink`] in batches. The
+//! batching task sits behind a bounded queue, so a slow database applies
+//! backpressure instead of growing memory without limit.
+//!
+//! The table schema ships as versioned [`MIGRATIONS`] for SQLite and
+//! Postgres, with the matching parameterized `INSERT` statements from
+//! [`SqlDialect::insert_sql`]. Two database sinks are bundled:
+//!
+//! | Sink | Feature | Driver |
+//! |------|---------|--------|
+//! | `SqliteSink` | `sqlite` | rusqlite (bundled SQLite) |
+//! | `PostgresSink` | `postgres` | sqlx |
+//!
+//! Both run pending migrations when opened and write each batch in one
+//! transaction. Other backends implement [`EventSink`] and bind
+//! [`SinkRecord::values`] themselves.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraken_sdk::sink::{spawn_sink, BatchConfig, EventSink, SinkError, SinkRecord};
+//! use kraken_sdk::prelude::*;
+//!
+//! struct StdoutSink;
+//!
+//! #[async_trait::async_trait]
+//! impl EventSink for StdoutSink {
+//!     async fn write_batch(&mut self, records: &[SinkRecord]) -> Result<(), SinkError> {
+//!         println!("writing {} records", records.len());
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let mut client = KrakenClient::builder(["BTC/USD"]).connect().await?;
+//! let events = client.events().unwrap();
+//!
+//! let (handle, task) = spawn_sink(StdoutSink, BatchConfig::default());
+//! tokio::spawn(handle.forward(events));
+//! let stats = task.await??;
+//! println!("{} rows in {} batches", stats.records, stats.batches);
+//! # Ok(())
+//! # }
+//! ```
+
+use async_trait::async_trait;
+use kraken_types::{Decimal, Side};
+use kraken_ws::{Event, EventReceiver, MarketEvent, PrivateEvent};
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteSink;
+
+#[cfg(feature = "postgres")]
+mod postgres;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresSink;
+
+/// Errors produced by sinks
+#[derive(Debug, Error)]
+pub enum SinkError {
+    /// The storage backend failed
+    #[error("Sink backend error: {0}")]
+    Backend(String),
+
+    /// The queue is full (only from [`SinkHandle::try_send`])
+    #[error("Sink queue is full")]
+    Full,
+
+    /// The batching task has stopped
+    #[error("Sink is closed")]
+    Closed,
+}
+
+/// A public trade
+#[derive(Debug, Clone, PartialEq)]
+pub struct TradeRecord {
+    /// Local receive time (Unix milliseconds)
+    pub ts_ms: i64,
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Kraken trade ID
+    pub trade_id: i64,
+    /// Aggressor side
+    pub side: Side,
+    /// Trade price
+    pub price: Decimal,
+    /// Trade quantity
+    pub qty: Decimal,
+    /// Order type
+    pub ord_type: String,
+    /// Exchange timestamp
+    pub exchange_ts: String,
+}
+
+/// A change in the best bid or offer
+#[derive(Debug, Clone, PartialEq)]
+pub struct BboRecord {
+    /// Local receive time (Unix milliseconds)
+    pub ts_ms: i64,
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Best bid price
+    pub bid: Option<Decimal>,
+    /// Best bid quantity
+    pub bid_qty: Option<Decimal>,
+    /// Best ask price
+    pub ask: Option<Decimal>,
+    /// Best ask quantity
+    pub ask_qty: Option<Decimal>,
+}
+
+/// An order lifecycle transition
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderRecord {
+    /// Local receive time (Unix milliseconds)
+    pub ts_ms: i64,
+    /// Order ID
+    pub order_id: String,
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Order side
+    pub side: Side,
+    /// Order type
+    pub order_type: String,
+    /// What changed (created, partial_fill, ...)
+    pub change: String,
+    /// Status after the change
+    pub status: String,
+    /// Original quantity
+    pub order_qty: Decimal,
+    /// Cumulative filled quantity
+    pub filled_qty: Decimal,
+    /// Limit price
+    pub limit_price: Option<Decimal>,
+    /// Average fill price
+    pub avg_price: Option<Decimal>,
+}
+
+/// One row destined for storage
+#[derive(Debug, Clone, PartialEq)]
+pub enum SinkRecord {
+    /// Row for the `trades` table
+    Trade(TradeRecord),
+    /// Row for the `bbo` table
+    Bbo(BboRecord),
+    /// Row for the `order_events` table
+    Order(OrderRecord),
+}
+
+/// A bindable SQL parameter
+///
+/// Decimals are stored as text so no precision is lost.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlValue {
+    /// SQL NULL
+    Null,
+    /// 64-bit integer
+    Int(i64),
+    /// Text
+    Text(String),
+}
+
+impl From<Option<Decimal>> for SqlValue {
+    fn from(value: Option<Decimal>) -> Self {
+        value.map_or(SqlValue::Null, |v| SqlValue::Text(v.to_string()))
+    }
+}
+
+impl SinkRecord {
+    /// Table this record belongs to
+    pub fn table(&self) -> &'static str {
+        match self {
+            SinkRecord::Trade(_) => "trades",
+            SinkRecord::Bbo(_) => "bbo",
+            SinkRecord::Order(_) => "order_events",
+        }
+    }
+
+    /// Column names, in the same order as [`values`](Self::values)
+    pub fn columns(&self) -> &'static [&'static str] {
+        columns(self.table())
+    }
+
+    /// Parameter values, in the same order as [`columns`](Self::columns)
+    pub fn values(&self) -> Vec<SqlValue> {
+        let text = |s: &str| SqlValue::Text(s.to_string());
+        let dec = |d: Decimal| SqlValue::Text(d.to_string());
+        match self {
+            SinkRecord::Trade(t) => vec![
+                SqlValue::Int(t.ts_ms),
+                text(&t.symbol),
+                SqlValue::Int(t.trade_id),
+                text(side_name(t.side)),
+                dec(t.price),
+                dec(t.qty),
+                text(&t.ord_type),
+                text(&t.exchange_ts),
+            ],
+            SinkRecord::Bbo(b) => vec![
+                SqlValue::Int(b.ts_ms),
+                text(&b.symbol),
+                b.bid.into(),
+                b.bid_qty.into(),
+                b.ask.into(),
+                b.ask_qty.into(),
+            ],
+            SinkRecord::Order(o) => vec![
+                SqlValue::Int(o.ts_ms),
+                text(&o.order_id),
+                text(&o.symbol),
+                text(side_name(o.side)),
+                text(&o.order_type),
+                text(&o.change),
+                text(&o.status),
+                dec(o.order_qty),
+                dec(o.filled_qty),
+                o.limit_price.into(),
+                o.avg_price.into(),
+            ],
+        }
+    }
+}
+
+fn columns(table: &str) -> &'static [&'static str] {
+    match table {
+        "trades" => {
+            &["ts_ms", "symbol", "trade_id", "side", "price", "qty", "ord_type", "exchange_ts"]
+        }
+        "bbo" => &["ts_ms", "symbol", "bid", "bid_qty", "ask", "ask_qty"],
+        "order_events" => &[
+            "ts_ms",
+            "order_id",
+            "symbol",
+            "side",
+            "order_type",
+            "change",
+            "status",
+            "order_qty",
+            "filled_qty",
+            "limit_price",
+            "avg_price",
+        ],
+        _ => &[],
+    }
+}
+
+fn side_name(side: Side) -> &'static str {
+    match side {
+        Side::Buy => "buy",
+        Side::Sell => "sell",
+    }
+}
+
+/// SQL flavour for generated statements
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SqlDialect {
+    /// SQLite (`?` placeholders)
+    Sqlite,
+    /// Postgres (`$1` placeholders)
+    Postgres,
+}
+
+impl SqlDialect {
+    /// Parameterized INSERT statement for a table
+    pub fn insert_sql(&self, table: &str) -> String {
+        let cols = columns(table);
+        let placeholders: Vec<String> = (1..=cols.len())
+            .map(|i| match self {
+                SqlDialect::Sqlite => "?".to_string(),
+                SqlDialect::Postgres => format!("${}", i),
+            })
+            .collect();
+        format!(
+            "INSERT INTO {} ({}) VALUES ({})",
+            table,
+            cols.join(", "),
+            placeholders.join(", ")
+        )
+    }
+
+    /// Migrations newer than `current_version`, in order
+    pub fn pending_migrations(&self, current_version: u32) -> Vec<(u32, &'static str)> {
+        MIGRATIONS
+            .iter()
+            .filter(|m| m.version > current_version)
+            .map(|m| {
+                let sql = match self {
+                    SqlDialect::Sqlite => m.sqlite,
+                    SqlDialect::Postgres => m.postgres,
+                };
+                (m.version, sql)
+            })
+            .collect()
+    }
+}
+
+/// A versioned schema change
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    /// Schema version after applying this migration
+    pub version: u32,
+    /// Short description
+    pub name: &'static str,
+    /// SQLite DDL
+    pub sqlite: &'static str,
+    /// Postgres DDL
+    pub postgres: &'static str,
+}
+
+/// Schema migrations, oldest first
+///
+/// Track the applied version yourself (e.g. SQLite `PRAGMA user_version` or a
+/// one-row table) and apply [`SqlDialect::pending_migrations`] on startup.
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    name: "initial schema",
+    sqlite: "CREATE TABLE IF NOT EXISTS trades (
+    ts_ms INTEGER NOT NULL, symbol TEXT NOT NULL, trade_id INTEGER NOT NULL,
+    side TEXT NOT NULL, price TEXT NOT NULL, qty TEXT NOT NULL,
+    ord_type TEXT NOT NULL, exchange_ts TEXT NOT NULL,
+    PRIMARY KEY (symbol, trade_id));
+CREATE TABLE IF NOT EXISTS bbo (
+    ts_ms INTEGER NOT NULL, symbol TEXT NOT NULL,
+    bid TEXT, bid_qty TEXT, ask TEXT, ask_qty TEXT);
+CREATE INDEX IF NOT EXISTS bbo_symbol_ts ON bbo (symbol, ts_ms);
+CREATE TABLE IF NOT EXISTS order_events (
+    ts_ms INTEGER NOT NULL, order_id TEXT NOT NULL, symbol TEXT NOT NULL,
+    side TEXT NOT NULL, order_type TEXT NOT NULL, change TEXT NOT NULL,
+    status TEXT NOT NULL, order_qty TEXT NOT NULL, filled_qty TEXT NOT NULL,
+    limit_price TEXT, avg_price TEXT);
+CREATE INDEX IF NOT EXISTS order_events_order ON order_events (order_id, ts_ms);",
+    postgres: "CREATE TABLE IF NOT EXISTS trades (
+    ts_ms BIGINT NOT NULL, symbol TEXT NOT NULL, trade_id BIGINT NOT NULL,
+    side TEXT NOT NULL, price NUMERIC NOT NULL, qty NUMERIC NOT NULL,
+    ord_type TEXT NOT NULL, exchange_ts TEXT NOT NULL,
+    PRIMARY KEY (symbol, trade_id));
+CREATE TABLE IF NOT EXISTS bbo (
+    ts_ms BIGINT NOT NULL, symbol TEXT NOT NULL,
+    bid NUMERIC, bid_qty NUMERIC, ask NUMERIC, ask_qty NUMERIC);
+CREATE INDEX IF NOT EXISTS bbo_symbol_ts ON bbo (symbol, ts_ms);
+CREATE TABLE IF NOT EXISTS order_events (
+    ts_ms BIGINT NOT NULL, order_id TEXT NOT NULL, symbol TEXT NOT NULL,
+    side TEXT NOT NULL, order_type TEXT NOT NULL, change TEXT NOT NULL,
+    status TEXT NOT NULL, order_qty NUMERIC NOT NULL, filled_qty NUMERIC NOT NULL,
+    limit_price NUMERIC, avg_price NUMERIC);
+CREATE INDEX IF NOT EXISTS order_events_order ON order_events (order_id, ts_ms);",
+}];
+
+/// Destination for batches of records
+#[async_trait]
+pub trait EventSink: Send {
+    /// Persist a batch atomically where the backend allows it
+    async fn write_batch(&mut self, records: &[SinkRecord]) -> Result<(), SinkError>;
+
+    /// Called once after the final batch
+    async fn close(&mut self) -> Result<(), SinkError> {
+        Ok(())
+    }
+}
+
+/// Batching and backpressure settings
+#[derive(Debug, Clone)]
+pub struct BatchConfig {
+    /// Flush once this many records are buffered
+    pub max_batch: usize,
+    /// Flush at most this long after the first buffered record
+    pub max_delay: Duration,
+    /// Records that may be queued before senders are slowed down
+    pub queue_capacity: usize,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_batch: 500,
+            max_delay: Duration::from_secs(1),
+            queue_capacity: 10_000,
+        }
+    }
+}
+
+/// Totals reported when the batching task finishes
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SinkStats {
+    /// Records written
+    pub records: u64,
+    /// Batches written
+    pub batches: u64,
+}
+
+/// Sending side of a spawned sink
+#[derive(Debug, Clone)]
+pub struct SinkHandle {
+    tx: mpsc::Sender<SinkRecord>,
+}
+
+impl SinkHandle {
+    /// Queue a record, waiting while the queue is full
+    pub async fn send(&self, record: SinkRecord) -> Result<(), SinkError> {
+        self.tx.send(record).await.map_err(|_| SinkError::Closed)
+    }
+
+    /// Queue a record without waiting
+    ///
+    /// The record is dropped if the queue is full or the sink has stopped.
+    pub fn try_send(&self, record: SinkRecord) -> Result<(), SinkError> {
+        self.tx.try_send(record).map_err(|e| match e {
+            mpsc::error::TrySendError::Full(_) => SinkError::Full,
+            mpsc::error::TrySendError::Closed(_) => SinkError::Closed,
+        })
+    }
+
+    /// Convert events to records and queue them until the stream ends
+    ///
+    /// Book updates only produce a BBO row when the top of book changes.
+    /// Dropping the handle afterwards lets the sink flush and finish.
+    pub async fn forward(self, mut events: EventReceiver) -> Result<(), SinkError> {
+        let mut converter = RecordConverter::default();
+        while let Some(event) = events.recv().await {
+            if let Some(record) = converter.convert(&event) {
+                self.send(record).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Spawn a batching task in front of `sink`
+///
+/// The task finishes once every [`SinkHandle`] clone is dropped and the
+/// remaining records are flushed, or on the first backend error.
+pub fn spawn_sink<S>(sink: S, config: BatchConfig) -> (SinkHandle, JoinHandle<Result<SinkStats, SinkError>>)
+where
+    S: EventSink + 'static,
+{
+    let (tx, rx) = mpsc::channel(config.queue_capacity.max(1));
+    let task = tokio::spawn(run_batches(sink, rx, config));
+    (SinkHandle { tx }, task)
+}
+
+async fn run_batches<S: EventSink>(
+    mut sink: S,
+    mut rx: mpsc::Receiver<SinkRecord>,
+    config: BatchConfig,
+) -> Result<SinkStats, SinkError> {
+    let max_batch = config.max_batch.max(1);
+    let mut stats = SinkStats::default();
+    let mut batch = Vec::with_capacity(max_batch);
+
+    // Wait for the first record of each batch, then fill until full or late
+    while let Some(first) = rx.recv().await {
+        batch.push(first);
+        let deadline = tokio::time::Instant::now() + config.max_delay;
+        while batch.len() < max_batch {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(record)) => batch.push(record),
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        if let Err(e) = sink.write_batch(&batch).await {
+            warn!("Sink batch of {} records failed: {}", batch.len(), e);
+            return Err(e);
+        }
+        stats.records += batch.len() as u64;
+        stats.batches += 1;
+        batch.clear();
+    }
+
+    sink.close().await?;
+    Ok(stats)
+}
+
+/// Stateful event-to-record conversion (deduplicates unchanged BBOs)
+#[derive(Debug, Default)]
+pub struct RecordConverter {
+    last_bbo: HashMap<String, [Option<(Decimal, Decimal)>; 2]>,
+}
+
+impl RecordConverter {
+    /// Convert an event, returning None for events that aren't stored
+    pub fn convert(&mut self, event: &Event) -> Option<SinkRecord> {
+        let ts_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as i64)
+            .unwrap_or(0);
+
+        match event {
+            Event::Market(MarketEvent::Trade { symbol, trade, .. }) => {
+                Some(SinkRecord::Trade(TradeRecord {
+                    ts_ms,
+                    symbol: symbol.clone(),
+                    trade_id: trade.trade_id as i64,
+                    side: trade.side,
+                    price: trade.price,
+                    qty: trade.qty,
+                    ord_type: trade.ord_type.clone(),
+                    exchange_ts: trade.timestamp.clone(),
+                }))
+            }
+            Event::Market(
+                MarketEvent::OrderbookSnapshot { symbol, snapshot, .. }
+                | MarketEvent::OrderbookUpdate { symbol, snapshot, .. },
+            ) => {
+                let top = [
+                    snapshot.bids.first().map(|l| (l.price.0, l.qty.0)),
+                    snapshot.asks.first().map(|l| (l.price.0, l.qty.0)),
+                ];
+                if self.last_bbo.get(symbol) == Some(&top) {
+                    return None;
+                }
+                self.last_bbo.insert(symbol.clone(), top);
+                let [bid, ask] = top;
+                Some(SinkRecord::Bbo(BboRecord {
+                    ts_ms,
+                    symbol: symbol.clone(),
+                    bid: bid.map(|b| b.0),
+                    bid_qty: bid.map(|b| b.1),
+                    ask: ask.map(|a| a.0),
+                    ask_qty: ask.map(|a| a.1),
+                }))
+            }
+            Event::Private(private) => match private.as_ref() {
+                PrivateEvent::OrderUpdate { order, change } => {
+                    Some(SinkRecord::Order(OrderRecord {
+                        ts_ms,
+                        order_id: order.order_id.clone(),
+                        symbol: order.symbol.clone(),
+                        side: order.side,
+                        order_type: order.order_type.clone(),
+                        change: snake_case(&format!("{:?}", change)),
+                        status: snake_case(&format!("{:?}", order.status)),
+                        order_qty: order.order_qty,
+                        filled_qty: order.filled_qty,
+                        limit_price: order.limit_price,
+                        avg_price: order.avg_price,
+                    }))
+                }
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// `PartialFill` -> `partial_fill`
+fn snake_case(name: &str) -> String {
+    let mut out = String::with_capacity(name.len() + 4);
+    for (i, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if i > 0 {
+                out.push('_');
+            }
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_book::OrderbookSnapshot;
+    use kraken_ws::ReceivedAt;
+    use kraken_types::{Level, TradeData};
+    use rust_decimal_macros::dec;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Default)]
+    struct MemorySink {
+        batches: Arc<Mutex<Vec<Vec<SinkRecord>>>>,
+    }
+
+    #[async_trait]
+    impl EventSink for MemorySink {
+        async fn write_batch(&mut self, records: &[SinkRecord]) -> Result<(), SinkError> {
+            self.batches.lock().unwrap().push(records.to_vec());
+            Ok(())
+        }
+    }
+
+    pub(super) fn trade(id: u64) -> SinkRecord {
+        let event = Event::Market(MarketEvent::Trade {
+            symbol: "BTC/USD".to_string(),
+            seq: 1,
+            trade: TradeData {
+                symbol: "BTC/USD".to_string(),
+                side: Side::Buy,
+                price: dec!(50000.1),
+                qty: dec!(0.01),
+                ord_type: "market".to_string(),
+                trade_id: id,
+                timestamp: "2024-01-01T00:00:00Z".to_string(),
+            },
+            received_at: ReceivedAt::now(),
+            exchange_ts_us: None,
+        });
+        RecordConverter::default().convert(&event).unwrap()
+    }
+
+    #[test]
+    fn test_insert_sql_matches_values() {
+        let record = trade(1);
+        assert_eq!(record.columns().len(), record.values().len());
+        assert_eq!(
+            SqlDialect::Postgres.insert_sql("bbo"),
+            "INSERT INTO bbo (ts_ms, symbol, bid, bid_qty, ask, ask_qty) VALUES ($1, $2, $3, $4, $5, $6)"
+        );
+        assert!(SqlDialect::Sqlite.insert_sql("trades").ends_with("(?, ?, ?, ?, ?, ?, ?, ?)"));
+        assert_eq!(SqlDialect::Sqlite.pending_migrations(0).len(), MIGRATIONS.len());
+        assert!(SqlDialect::Sqlite.pending_migrations(1).is_empty());
+    }
+
+    #[test]
+    fn test_bbo_only_on_top_change() {
+        let book = |bid| {
+            Event::Market(MarketEvent::OrderbookUpdate {
+                symbol: "BTC/USD".to_string(),
+                seq: 1,
+                snapshot: OrderbookSnapshot {
+                    bids: vec![Level::new(bid, dec!(1))],
+                    ..Default::default()
+                },
+                received_at: ReceivedAt::now(),
+                exchange_ts_us: None,
+            })
+        };
+        let mut converter = RecordConverter::default();
+        assert!(converter.convert(&book(dec!(100))).is_some());
+        assert!(converter.convert(&book(dec!(100))).is_none());
+        let Some(SinkRecord::Bbo(bbo)) = converter.convert(&book(dec!(101))) else {
+            panic!("expected bbo record");
+        };
+        assert_eq!(bbo.bid, Some(dec!(101)));
+        assert_eq!(bbo.ask, None);
+        assert_eq!(SinkRecord::Bbo(bbo).values()[4], SqlValue::Null);
+    }
+
+    #[tokio::test]
+    async fn test_batches_by_size_and_flushes_on_close() {
+        let sink = MemorySink::default();
+        let config = BatchConfig {
+            max_batch: 2,
+            max_delay: Duration::from_secs(60),
+            queue_capacity: 8,
+        };
+        let (handle, task) = spawn_sink(sink.clone(), config);
+        for id in 0..5 {
+            handle.send(trade(id)).await.unwrap();
+        }
+        drop(handle);
+
+        let stats = task.await.unwrap().unwrap();
+        assert_eq!(stats, SinkStats { records: 5, batches: 3 });
+        let sizes: Vec<_> = sink.batches.lock().unwrap().iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![2, 2, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_try_send_reports_full_queue() {
+        struct Stalled;
+        #[async_trait]
+        impl EventSink for Stalled {
+            async fn write_batch(&mut self, _: &[SinkRecord]) -> Result<(), SinkError> {
+                std::future::pending().await
+            }
+        }
+
+        let config = BatchConfig {
+            max_batch: 1,
+            max_delay: Duration::from_millis(1),
+            queue_capacity: 1,
+        };
+        let (handle, task) = spawn_sink(Stalled, config);
+        // The first record is taken by the (stalled) writer, the second fills the queue
+        handle.send(trade(0)).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        handle.send(trade(1)).await.unwrap();
+        assert!(matches!(handle.try_send(trade(2)), Err(SinkError::Full)));
+        task.abort();
+    }
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("PartialFill"), "partial_fill");
+        assert_eq!(snake_case("New"), "new");
+    }
+}
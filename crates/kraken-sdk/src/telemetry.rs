@@ -0,0 +1,226 @@
+//! OpenTelemetry span export
+//!
+//! The SDK emits `tracing` spans for the connection lifecycle and for every
+//! tracked order. With the `otel` feature those spans can be exported over
+//! OTLP/HTTP to Jaeger, Tempo, or any OpenTelemetry collector.
+//!
+//! # Enabling
+//!
+//! ```toml
+//! [dependencies]
+//! kraken-sdk = { version = "0.1", features = ["otel"] }
+//! ```
+//!
+//! # Span Hierarchy
+//!
+//! ```text
+//! kraken_connection                     connect_and_run, one per client
+//! └── connect        url, attempt,      one per connection attempt
+//!     │              connection_id
+//!     ├── subscribe  channel, symbols,  one per subscribe request
+//!     │              req_id
+//!     └── handle_message  bytes, kind   one per inbound message (debug level)
+//!
+//! order_submit       orders, attempts,  TradingClient::execute
+//!                    order_id
+//! order              symbol, side,      root span per OrderTracker order,
+//!                    request_id,        open from submission until the order
+//!                    order_id, state    is filled, canceled, or rejected
+//! ```
+//!
+//! `order` spans carry an event for each state transition and fill, so the
+//! submit → ack → fill latency reads straight off the trace. Rejected orders
+//! are marked with an error status.
+//!
+//! `handle_message` spans are at debug level and not exported by the
+//! default `info` filter; add `kraken_ws=debug` to see them.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use kraken_sdk::telemetry::{init_otel, OtelConfig};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//!     // Jaeger accepts OTLP/HTTP on port 4318
+//!     let _guard = init_otel(OtelConfig::new().with_service_name("my-bot"))?;
+//!
+//!     // ... run the client; spans are flushed when the guard drops
+//!     Ok(())
+//! }
+//! ```
+
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::trace::{Tracer, TracerProvider};
+use opentelemetry_sdk::{runtime, Resource};
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
+
+/// Default OTLP/HTTP traces endpoint of a local collector
+pub const DEFAULT_OTLP_ENDPOINT: &str = "http://localhost:4318/v1/traces";
+
+/// Error setting up span export
+#[derive(Debug, thiserror::Error)]
+pub enum TelemetryError {
+    /// Exporter or tracer provider setup failed
+    #[error("OpenTelemetry setup failed: {0}")]
+    Otel(#[from] opentelemetry::trace::TraceError),
+
+    /// The filter directive couldn't be parsed
+    #[error("invalid filter: {0}")]
+    Filter(#[from] tracing_subscriber::filter::ParseError),
+
+    /// A global tracing subscriber is already installed
+    #[error("tracing subscriber already installed: {0}")]
+    Subscriber(#[from] tracing_subscriber::util::TryInitError),
+}
+
+/// OTLP export configuration
+#[derive(Debug, Clone)]
+pub struct OtelConfig {
+    /// OTLP/HTTP traces endpoint
+    pub endpoint: String,
+    /// `service.name` resource attribute
+    pub service_name: String,
+    /// `EnvFilter` directive selecting the exported spans
+    pub filter: String,
+}
+
+impl Default for OtelConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: DEFAULT_OTLP_ENDPOINT.to_string(),
+            service_name: "havklo".to_string(),
+            filter: "info".to_string(),
+        }
+    }
+}
+
+impl OtelConfig {
+    /// Create a config exporting to a local collector
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the OTLP/HTTP traces endpoint
+    ///
+    /// `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` takes precedence when set.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Set the `service.name` shown in the trace UI
+    pub fn with_service_name(mut self, name: impl Into<String>) -> Self {
+        self.service_name = name.into();
+        self
+    }
+
+    /// Set the filter directive, e.g. `"info,kraken_ws=debug"`
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = filter.into();
+        self
+    }
+}
+
+/// Keeps the exporter alive; flushes and shuts it down on drop
+///
+/// Drop it from a multi-threaded Tokio runtime: the batch exporter blocks
+/// on its worker task while flushing.
+#[derive(Debug)]
+pub struct OtelGuard {
+    provider: TracerProvider,
+}
+
+impl OtelGuard {
+    /// Export every finished span now
+    pub fn flush(&self) {
+        for result in self.provider.force_flush() {
+            if let Err(e) = result {
+                tracing::warn!("Span flush failed: {}", e);
+            }
+        }
+    }
+}
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.provider.shutdown() {
+            eprintln!("OpenTelemetry shutdown failed: {}", e);
+        }
+    }
+}
+
+/// Build an OpenTelemetry layer to compose with your own subscriber
+///
+/// Must be called within a Tokio runtime. Filtering is left to the caller.
+pub fn otel_layer<S>(config: &OtelConfig) -> Result<(OpenTelemetryLayer<S, Tracer>, OtelGuard), TelemetryError>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(config.endpoint.clone())
+        .build()?;
+    let provider = TracerProvider::builder()
+        .with_batch_exporter(exporter, runtime::Tokio)
+        .with_resource(Resource::new([KeyValue::new("service.name", config.service_name.clone())]))
+        .build();
+    let tracer = provider.tracer("kraken-sdk");
+    let layer = tracing_opentelemetry::layer().with_tracer(tracer);
+    Ok((layer, OtelGuard { provider }))
+}
+
+/// Install a global subscriber that logs to stdout and exports spans
+///
+/// Must be called within a Tokio runtime.
+pub fn init_otel(config: OtelConfig) -> Result<OtelGuard, TelemetryError> {
+    let filter = EnvFilter::try_new(&config.filter)?;
+    let (layer, guard) = otel_layer(&config)?;
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(layer)
+        .try_init()?;
+    Ok(guard)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_builder() {
+        let config = OtelConfig::new()
+            .with_endpoint("http://jaeger:4318/v1/traces")
+            .with_service_name("bot")
+            .with_filter("info,kraken_ws=debug");
+        assert_eq!(config.endpoint, "http://jaeger:4318/v1/traces");
+        assert_eq!(config.service_name, "bot");
+        assert_eq!(OtelConfig::default().endpoint, DEFAULT_OTLP_ENDPOINT);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_layer_records_spans_without_collector() {
+        let (layer, guard) = otel_layer(&OtelConfig::new()).unwrap();
+        let subscriber = tracing_subscriber::registry().with(layer);
+        tracing::subscriber::with_default(subscriber, || {
+            let span = tracing::info_span!("order", symbol = "BTC/USD");
+            span.in_scope(|| tracing::info!("Lifecycle state transition"));
+        });
+        // Nothing listens on the endpoint; export failures must not panic
+        drop(guard);
+    }
+
+    #[test]
+    fn test_invalid_filter_rejected() {
+        let err = init_otel(OtelConfig::new().with_filter("kraken_ws=[")).unwrap_err();
+        assert!(matches!(err, TelemetryError::Filter(_)));
+    }
+}
@@ -0,0 +1,366 @@
+//! REST ticker polling
+//!
+//! For environments that can't hold a WebSocket open (serverless functions,
+//! cron jobs), [`TickerPoller`] polls Kraken's REST `Ticker` endpoint for a
+//! batch of symbols in a single request and emits the same
+//! [`MarketEvent::Ticker`] events as the WebSocket path, so downstream code
+//! doesn't care which transport produced them. Only tickers that changed
+//! since the previous poll are emitted.
+//!
+//! The poller does no HTTP itself: it takes a fetch function mapping a URL
+//! to a response body, so any client (reqwest, a WASM `fetch`, a test stub)
+//! can be plugged in. Polls are never more frequent than
+//! [`MIN_POLL_INTERVAL`], and the interval backs off while Kraken reports a
+//! rate limit error.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::ticker_poller::TickerPoller;
+//! use std::time::Duration;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let body = r#"{"error":[],"result":{"XXBTZUSD":{
+//!     "a":["42001.0","1","1.000"],"b":["42000.0","2","2.000"],"c":["42000.5","0.1"],
+//!     "v":["100.0","250.0"],"p":["41900.0","41800.0"],"t":[10,20],
+//!     "l":["41000.0","40000.0"],"h":["43000.0","44000.0"],"o":"41000.0"}}}"#;
+//!
+//! let fetch = move |_url: String| async move { Ok::<_, String>(body.to_string()) };
+//! let mut poller = TickerPoller::new(fetch, ["BTC/USD"], Duration::from_secs(5));
+//!
+//! assert_eq!(poller.poll_once().await.unwrap().len(), 1);
+//! // Unchanged on the next poll, so nothing is emitted
+//! assert!(poller.poll_once().await.unwrap().is_empty());
+//! # }
+//! ```
+
+use futures::Stream;
+use kraken_types::{Decimal, TickerData};
+use kraken_ws::{MarketEvent, ReceivedAt};
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::str::FromStr;
+use std::time::Duration;
+use tracing::warn;
+
+/// Kraken REST ticker endpoint
+pub const REST_TICKER_URL: &str = "https://api.kraken.com/0/public/Ticker";
+
+/// Shortest interval between polls
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Longest interval while backing off from rate limit errors
+pub const MAX_BACKOFF_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Error polling the REST ticker
+#[derive(Debug, thiserror::Error)]
+pub enum TickerPollError {
+    /// The fetch function failed
+    #[error("fetch failed: {0}")]
+    Fetch(String),
+
+    /// Response was not valid JSON
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Kraken returned an error
+    #[error("Kraken API error: {0}")]
+    Api(String),
+
+    /// Response had an unexpected shape
+    #[error("unexpected Ticker response: {0}")]
+    Format(String),
+}
+
+impl TickerPollError {
+    /// Returns true if Kraken rejected the request for exceeding its rate limit
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, Self::Api(message) if message.contains("Rate limit"))
+    }
+}
+
+/// Polls REST tickers for a set of symbols and emits changes as market events
+pub struct TickerPoller<F> {
+    fetch: F,
+    symbols: Vec<String>,
+    base_interval: Duration,
+    interval: Duration,
+    last: HashMap<String, TickerData>,
+    /// Last sequence number emitted per symbol
+    seqs: HashMap<String, u64>,
+}
+
+impl<F> std::fmt::Debug for TickerPoller<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TickerPoller")
+            .field("symbols", &self.symbols)
+            .field("base_interval", &self.base_interval)
+            .field("interval", &self.interval)
+            .finish()
+    }
+}
+
+impl<F, Fut, E> TickerPoller<F>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<String, E>>,
+    E: std::fmt::Display,
+{
+    /// Create a poller for WebSocket-style symbols such as `"BTC/USD"`
+    ///
+    /// `fetch` performs an HTTP GET and returns the response body. The
+    /// interval is raised to [`MIN_POLL_INTERVAL`] if shorter.
+    pub fn new<S: Into<String>>(fetch: F, symbols: impl IntoIterator<Item = S>, interval: Duration) -> Self {
+        let interval = interval.max(MIN_POLL_INTERVAL);
+        Self {
+            fetch,
+            symbols: symbols.into_iter().map(Into::into).collect(),
+            base_interval: interval,
+            interval,
+            last: HashMap::new(),
+            seqs: HashMap::new(),
+        }
+    }
+
+    /// Symbols being polled
+    pub fn symbols(&self) -> &[String] {
+        &self.symbols
+    }
+
+    /// Interval until the next poll, including any rate limit backoff
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Most recent ticker seen for a symbol
+    pub fn last(&self, symbol: &str) -> Option<&TickerData> {
+        self.last.get(symbol)
+    }
+
+    /// Poll once, returning a ticker event for each symbol that changed
+    ///
+    /// A rate limit error doubles the interval up to
+    /// [`MAX_BACKOFF_INTERVAL`]; a successful poll resets it.
+    pub async fn poll_once(&mut self) -> Result<Vec<MarketEvent>, TickerPollError> {
+        let url = ticker_url(&self.symbols);
+        let result = match (self.fetch)(url).await {
+            Ok(body) => parse_rest_ticker(&body, &self.symbols),
+            Err(e) => Err(TickerPollError::Fetch(e.to_string())),
+        };
+        let tickers = match result {
+            Ok(tickers) => tickers,
+            Err(e) => {
+                if e.is_rate_limited() {
+                    self.interval = (self.interval * 2).min(MAX_BACKOFF_INTERVAL);
+                }
+                return Err(e);
+            }
+        };
+        self.interval = self.base_interval;
+
+        let received_at = ReceivedAt::now();
+        let mut events = Vec::new();
+        for ticker in tickers {
+            if self.last.get(&ticker.symbol) == Some(&ticker) {
+                continue;
+            }
+            self.last.insert(ticker.symbol.clone(), ticker.clone());
+            let seq = self.seqs.entry(ticker.symbol.clone()).or_insert(0);
+            *seq += 1;
+            events.push(MarketEvent::Ticker {
+                symbol: ticker.symbol.clone(),
+                seq: *seq,
+                ticker,
+                received_at,
+                exchange_ts_us: None,
+            });
+        }
+        Ok(events)
+    }
+
+    /// Poll forever, yielding changed tickers as they arrive
+    ///
+    /// The first poll runs immediately. Errors are logged and the poll is
+    /// retried after the (possibly backed-off) interval.
+    pub fn into_stream(self) -> impl Stream<Item = MarketEvent> {
+        let state = (self, VecDeque::new(), true);
+        futures::stream::unfold(state, |(mut poller, mut pending, mut first)| async move {
+            loop {
+                if let Some(event) = pending.pop_front() {
+                    return Some((event, (poller, pending, first)));
+                }
+                if !first {
+                    tokio::time::sleep(poller.interval).await;
+                }
+                first = false;
+                match poller.poll_once().await {
+                    Ok(events) => pending.extend(events),
+                    Err(e) => warn!(interval = ?poller.interval, "Ticker poll failed: {}", e),
+                }
+            }
+        })
+    }
+}
+
+/// REST pair name for a WebSocket symbol, e.g. `"BTC/USD"` → `"XBTUSD"`
+pub fn rest_pair_name(symbol: &str) -> String {
+    let (base, quote) = symbol.split_once('/').unwrap_or((symbol, ""));
+    let rest_asset = |asset: &str| match asset {
+        "BTC" => "XBT".to_string(),
+        "DOGE" => "XDG".to_string(),
+        other => other.to_string(),
+    };
+    format!("{}{}", rest_asset(base), rest_asset(quote))
+}
+
+/// URL fetching tickers for a batch of WebSocket symbols
+pub fn ticker_url(symbols: &[String]) -> String {
+    let pairs: Vec<String> = symbols.iter().map(|s| rest_pair_name(s)).collect();
+    format!("{}?pair={}", REST_TICKER_URL, pairs.join(","))
+}
+
+/// Parse a REST `Ticker` response into WebSocket-shaped tickers
+///
+/// Result keys are matched back to `symbols`, accepting both the short
+/// (`XBTUSD`) and the legacy prefixed (`XXBTZUSD`) pair names. REST has no
+/// rolling 24h change, so `change` is measured from today's open.
+pub fn parse_rest_ticker(body: &str, symbols: &[String]) -> Result<Vec<TickerData>, TickerPollError> {
+    let value: serde_json::Value = serde_json::from_str(body)?;
+    if let Some(errors) = value.get("error").and_then(|e| e.as_array()) {
+        if !errors.is_empty() {
+            let messages: Vec<&str> = errors.iter().filter_map(|e| e.as_str()).collect();
+            return Err(TickerPollError::Api(messages.join(", ")));
+        }
+    }
+    let result = value
+        .get("result")
+        .and_then(|r| r.as_object())
+        .ok_or_else(|| TickerPollError::Format("missing result".to_string()))?;
+
+    let by_pair: HashMap<String, &String> = symbols.iter().map(|s| (rest_pair_name(s), s)).collect();
+    let mut tickers = Vec::with_capacity(result.len());
+    for (key, fields) in result {
+        let symbol = by_pair.get(key).or_else(|| by_pair.get(&short_pair_name(key)));
+        match symbol {
+            Some(symbol) => tickers.push(parse_ticker(fields, symbol)?),
+            None => warn!(pair = %key, "REST ticker for unrequested pair"),
+        }
+    }
+    Ok(tickers)
+}
+
+/// `XXBTZUSD` → `XBTUSD`; other names are returned unchanged
+fn short_pair_name(key: &str) -> String {
+    let bytes = key.as_bytes();
+    let prefixed = |b: u8| b == b'X' || b == b'Z';
+    if bytes.len() == 8 && prefixed(bytes[0]) && prefixed(bytes[4]) {
+        format!("{}{}", &key[1..4], &key[5..])
+    } else {
+        key.to_string()
+    }
+}
+
+fn parse_ticker(fields: &serde_json::Value, symbol: &str) -> Result<TickerData, TickerPollError> {
+    let format_error = || TickerPollError::Format(fields.to_string());
+    let decimal = |value: Option<&serde_json::Value>| {
+        value
+            .and_then(|v| v.as_str())
+            .and_then(|s| Decimal::from_str(s).ok())
+            .ok_or_else(format_error)
+    };
+    let at = |key: &str, index: usize| decimal(fields.get(key).and_then(|v| v.get(index)));
+
+    let last = at("c", 0)?;
+    let open = decimal(fields.get("o"))?;
+    let change = last - open;
+    let change_pct = if open.is_zero() {
+        Decimal::ZERO
+    } else {
+        (change / open * Decimal::ONE_HUNDRED).round_dp(2)
+    };
+
+    Ok(TickerData {
+        symbol: symbol.to_string(),
+        bid: at("b", 0)?,
+        bid_qty: at("b", 2)?,
+        ask: at("a", 0)?,
+        ask_qty: at("a", 2)?,
+        last,
+        volume: at("v", 1)?,
+        vwap: at("p", 1)?,
+        low: at("l", 1)?,
+        high: at("h", 1)?,
+        change,
+        change_pct,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use rust_decimal_macros::dec;
+    use std::sync::{Arc, Mutex};
+
+    fn body(pair: &str, bid: &str) -> String {
+        format!(
+            r#"{{"error":[],"result":{{"{}":{{"a":["101.0","1","1.5"],"b":["{}","2","2.5"],"c":["100.5","0.1"],"v":["10.0","25.0"],"p":["99.0","98.0"],"t":[10,20],"l":["90.0","80.0"],"h":["110.0","120.0"],"o":"100.0"}}}}}}"#,
+            pair, bid
+        )
+    }
+
+    #[tokio::test]
+    async fn test_poll_emits_only_changes() {
+        let responses = Arc::new(Mutex::new(VecDeque::from(vec![
+            body("XXBTZUSD", "100.0"),
+            body("XXBTZUSD", "100.0"),
+            body("XXBTZUSD", "100.2"),
+        ])));
+        let urls = Arc::new(Mutex::new(Vec::new()));
+        let (queue, seen) = (Arc::clone(&responses), Arc::clone(&urls));
+        let fetch = move |url: String| {
+            seen.lock().unwrap().push(url);
+            let next = queue.lock().unwrap().pop_front();
+            async move { next.ok_or("exhausted") }
+        };
+        let mut poller = TickerPoller::new(fetch, ["BTC/USD"], Duration::from_millis(10));
+        assert_eq!(poller.interval(), MIN_POLL_INTERVAL);
+
+        let events = poller.poll_once().await.unwrap();
+        match &events[..] {
+            [MarketEvent::Ticker { symbol, ticker, .. }] => {
+                assert_eq!(symbol, "BTC/USD");
+                assert_eq!(ticker.bid_qty, dec!(2.5));
+                assert_eq!(ticker.change, dec!(0.5));
+                assert_eq!(ticker.change_pct, dec!(0.50));
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
+        assert!(poller.poll_once().await.unwrap().is_empty());
+        assert_eq!(poller.poll_once().await.unwrap().len(), 1);
+        assert_eq!(poller.last("BTC/USD").unwrap().bid, dec!(100.2));
+        assert_eq!(urls.lock().unwrap()[0], "https://api.kraken.com/0/public/Ticker?pair=XBTUSD");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_backs_off() {
+        let fetch = |_url: String| async { Ok::<_, String>(r#"{"error":["EAPI:Rate limit exceeded"]}"#.to_string()) };
+        let mut poller = TickerPoller::new(fetch, ["ETH/USD"], Duration::from_secs(20));
+        let err = poller.poll_once().await.unwrap_err();
+        assert!(err.is_rate_limited());
+        assert_eq!(poller.interval(), Duration::from_secs(40));
+        let _ = poller.poll_once().await;
+        assert_eq!(poller.interval(), MAX_BACKOFF_INTERVAL);
+    }
+
+    #[tokio::test]
+    async fn test_stream_yields_first_poll_immediately() {
+        let fetch = |_url: String| async { Ok::<_, String>(body("SOLUSD", "100.0")) };
+        let poller = TickerPoller::new(fetch, ["SOL/USD"], Duration::from_secs(60));
+        let stream = poller.into_stream();
+        futures::pin_mut!(stream);
+        let event = tokio::time::timeout(Duration::from_secs(1), stream.next()).await.unwrap();
+        assert!(matches!(event, Some(MarketEvent::Ticker { symbol, .. }) if symbol == "SOL/USD"));
+    }
+}
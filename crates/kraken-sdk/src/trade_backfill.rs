@@ -0,0 +1,306 @@
+//! Gapless trade tape across reconnects
+//!
+//! Trades that print while the WebSocket is down are never replayed on the
+//! trade channel. [`TradeBackfill`] closes that hole: on reconnect it starts
+//! buffering live trades for the symbol and hands back the point to fetch
+//! REST `Trades` from; once the REST response is merged in with
+//! [`TradeBackfill::apply_backfill`], the history and the buffered live
+//! trades come out as one sequence ordered and de-duplicated by `trade_id`.
+//!
+//! Kraken trade IDs are sequential per pair, so the component also counts
+//! any gap that remains after backfill.
+//!
+//! The client does this on the connection itself with
+//! [`with_trade_backfill`](crate::KrakenClientBuilder::with_trade_backfill)
+//! (see [`rest_backfill`](crate::rest_backfill)); use this type when
+//! merging trades from a feed the client doesn't own.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::trade_backfill::TradeBackfill;
+//!
+//! let mut backfill = TradeBackfill::new();
+//! // After a reconnect, fetch REST trades from here
+//! let since = backfill.on_reconnect("BTC/USD");
+//! assert!(since.is_none()); // nothing seen yet
+//!
+//! // Live trades are buffered until the backfill lands
+//! let released = backfill.apply_backfill("BTC/USD", Vec::new());
+//! assert!(released.is_empty());
+//! assert!(!backfill.is_backfilling("BTC/USD"));
+//! ```
+
+use chrono::{DateTime, Utc};
+use kraken_types::{Decimal, Side, TradeData};
+use std::collections::HashMap;
+use std::str::FromStr;
+use tracing::warn;
+
+/// Kraken REST recent trades endpoint
+pub const REST_TRADES_URL: &str = "https://api.kraken.com/0/public/Trades";
+
+/// Error parsing a REST `Trades` response
+#[derive(Debug, thiserror::Error)]
+pub enum TradeBackfillError {
+    /// Response was not valid JSON
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// Kraken returned an error
+    #[error("Kraken API error: {0}")]
+    Api(String),
+
+    /// Response had an unexpected shape
+    #[error("unexpected Trades response: {0}")]
+    Format(String),
+}
+
+/// Where to resume a symbol's tape from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResumePoint {
+    /// Last trade ID delivered downstream
+    pub trade_id: u64,
+    /// Timestamp of that trade (RFC 3339)
+    pub timestamp: String,
+}
+
+#[derive(Debug, Default)]
+struct SymbolTape {
+    last: Option<ResumePoint>,
+    buffer: Option<Vec<TradeData>>,
+    gaps: u64,
+}
+
+/// Merges REST trade history with the live trade stream per symbol
+#[derive(Debug, Default)]
+pub struct TradeBackfill {
+    tapes: HashMap<String, SymbolTape>,
+}
+
+impl TradeBackfill {
+    /// Create an empty backfill tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start buffering live trades for a symbol after a reconnect
+    ///
+    /// Returns the last trade delivered, which is where the REST fetch
+    /// should start; `None` means nothing was seen yet.
+    pub fn on_reconnect(&mut self, symbol: &str) -> Option<ResumePoint> {
+        let tape = self.tapes.entry(symbol.to_string()).or_default();
+        tape.buffer.get_or_insert_with(Vec::new);
+        tape.last.clone()
+    }
+
+    /// Returns true while a symbol waits for its REST backfill
+    pub fn is_backfilling(&self, symbol: &str) -> bool {
+        self.tapes.get(symbol).is_some_and(|t| t.buffer.is_some())
+    }
+
+    /// Handle a live trade
+    ///
+    /// Returns the trades to deliver downstream: nothing while backfilling
+    /// or for a duplicate, otherwise the trade itself.
+    pub fn handle_live(&mut self, trade: TradeData) -> Vec<TradeData> {
+        let tape = self.tapes.entry(trade.symbol.clone()).or_default();
+        if let Some(buffer) = &mut tape.buffer {
+            buffer.push(trade);
+            return Vec::new();
+        }
+        Self::release(tape, vec![trade])
+    }
+
+    /// Merge REST history and release it with the buffered live trades
+    ///
+    /// Trades are sorted by `trade_id`; anything at or before the last
+    /// delivered trade is dropped. Live buffering stops for the symbol.
+    pub fn apply_backfill(&mut self, symbol: &str, history: Vec<TradeData>) -> Vec<TradeData> {
+        let tape = self.tapes.entry(symbol.to_string()).or_default();
+        let mut trades = history;
+        trades.extend(tape.buffer.take().unwrap_or_default());
+        trades.sort_by_key(|t| t.trade_id);
+        trades.dedup_by_key(|t| t.trade_id);
+        Self::release(tape, trades)
+    }
+
+    fn release(tape: &mut SymbolTape, trades: Vec<TradeData>) -> Vec<TradeData> {
+        let mut released = Vec::with_capacity(trades.len());
+        for trade in trades {
+            if let Some(last) = &tape.last {
+                if trade.trade_id <= last.trade_id {
+                    continue;
+                }
+                if trade.trade_id > last.trade_id + 1 {
+                    tape.gaps += 1;
+                    warn!(
+                        symbol = %trade.symbol,
+                        from = last.trade_id,
+                        to = trade.trade_id,
+                        "Gap in trade tape"
+                    );
+                }
+            }
+            tape.last = Some(ResumePoint {
+                trade_id: trade.trade_id,
+                timestamp: trade.timestamp.clone(),
+            });
+            released.push(trade);
+        }
+        released
+    }
+
+    /// Last trade delivered for a symbol
+    pub fn last_seen(&self, symbol: &str) -> Option<&ResumePoint> {
+        self.tapes.get(symbol)?.last.as_ref()
+    }
+
+    /// Number of trade ID gaps seen for a symbol
+    pub fn gap_count(&self, symbol: &str) -> u64 {
+        self.tapes.get(symbol).map_or(0, |t| t.gaps)
+    }
+}
+
+/// URL fetching trades for a REST pair name since a resume point
+pub fn trades_backfill_url(rest_pair: &str, since: Option<&ResumePoint>) -> String {
+    let since = since
+        .and_then(|p| DateTime::parse_from_rfc3339(&p.timestamp).ok())
+        .map(|t| format!("&since={}", t.timestamp()))
+        .unwrap_or_default();
+    format!("{}?pair={}{}", REST_TRADES_URL, rest_pair, since)
+}
+
+/// Parse a REST `Trades` response into WebSocket-shaped trades
+///
+/// Rows are `[price, volume, time, side, order type, misc, trade_id]`.
+pub fn parse_rest_trades(body: &str, symbol: &str) -> Result<Vec<TradeData>, TradeBackfillError> {
+    let value: serde_json::Value = serde_json::from_str(body)?;
+    if let Some(errors) = value.get("error").and_then(|e| e.as_array()) {
+        if !errors.is_empty() {
+            let messages: Vec<&str> = errors.iter().filter_map(|e| e.as_str()).collect();
+            return Err(TradeBackfillError::Api(messages.join(", ")));
+        }
+    }
+    let rows = value
+        .get("result")
+        .and_then(|r| r.as_object())
+        .and_then(|r| r.iter().find(|(key, _)| key.as_str() != "last"))
+        .and_then(|(_, rows)| rows.as_array())
+        .ok_or_else(|| TradeBackfillError::Format("missing result".to_string()))?;
+
+    rows.iter().map(|row| parse_row(row, symbol)).collect()
+}
+
+fn parse_row(row: &serde_json::Value, symbol: &str) -> Result<TradeData, TradeBackfillError> {
+    let format_error = || TradeBackfillError::Format(row.to_string());
+    let fields = row.as_array().filter(|f| f.len() >= 7).ok_or_else(format_error)?;
+    let decimal = |index: usize| {
+        fields[index]
+            .as_str()
+            .and_then(|s| Decimal::from_str(s).ok())
+            .ok_or_else(format_error)
+    };
+    let side = match fields[3].as_str() {
+        Some("b") => Side::Buy,
+        Some("s") => Side::Sell,
+        _ => return Err(format_error()),
+    };
+    let ord_type = match fields[4].as_str() {
+        Some("m") => "market",
+        Some("l") => "limit",
+        _ => return Err(format_error()),
+    };
+
+    Ok(TradeData {
+        symbol: symbol.to_string(),
+        side,
+        price: decimal(0)?,
+        qty: decimal(1)?,
+        ord_type: ord_type.to_string(),
+        trade_id: fields[6].as_u64().ok_or_else(format_error)?,
+        timestamp: rest_time(&fields[2]).ok_or_else(format_error)?,
+    })
+}
+
+/// Convert REST fractional seconds to the WebSocket's RFC 3339 form
+fn rest_time(value: &serde_json::Value) -> Option<String> {
+    let text = value.to_string();
+    let (secs, frac) = text.split_once('.').unwrap_or((&text, ""));
+    let nanos: String = frac.chars().chain(std::iter::repeat('0')).take(9).collect();
+    let time = DateTime::<Utc>::from_timestamp(secs.parse().ok()?, nanos.parse().ok()?)?;
+    Some(time.to_rfc3339_opts(chrono::SecondsFormat::Micros, true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn trade(id: u64) -> TradeData {
+        TradeData {
+            symbol: "BTC/USD".to_string(),
+            side: Side::Buy,
+            price: dec!(100),
+            qty: dec!(1),
+            ord_type: "limit".to_string(),
+            trade_id: id,
+            timestamp: "2024-01-01T00:00:00.000000Z".to_string(),
+        }
+    }
+
+    fn ids(trades: &[TradeData]) -> Vec<u64> {
+        trades.iter().map(|t| t.trade_id).collect()
+    }
+
+    #[test]
+    fn test_reconnect_merges_history_and_buffer() {
+        let mut backfill = TradeBackfill::new();
+        assert_eq!(ids(&backfill.handle_live(trade(1))), vec![1]);
+        assert_eq!(ids(&backfill.handle_live(trade(2))), vec![2]);
+
+        let since = backfill.on_reconnect("BTC/USD").unwrap();
+        assert_eq!(since.trade_id, 2);
+        assert!(backfill.handle_live(trade(5)).is_empty());
+        assert!(backfill.handle_live(trade(6)).is_empty());
+
+        // REST overlaps both the delivered trades and the buffer
+        let released = backfill.apply_backfill("BTC/USD", vec![trade(2), trade(3), trade(4), trade(5)]);
+        assert_eq!(ids(&released), vec![3, 4, 5, 6]);
+        assert_eq!(backfill.gap_count("BTC/USD"), 0);
+
+        // Duplicates after backfill are dropped
+        assert!(backfill.handle_live(trade(6)).is_empty());
+        assert_eq!(ids(&backfill.handle_live(trade(7))), vec![7]);
+    }
+
+    #[test]
+    fn test_gap_counted_when_history_is_short() {
+        let mut backfill = TradeBackfill::new();
+        backfill.handle_live(trade(10));
+        backfill.on_reconnect("BTC/USD");
+        backfill.handle_live(trade(15));
+
+        let released = backfill.apply_backfill("BTC/USD", vec![trade(11)]);
+        assert_eq!(ids(&released), vec![11, 15]);
+        assert_eq!(backfill.gap_count("BTC/USD"), 1);
+    }
+
+    #[test]
+    fn test_parse_rest_trades() {
+        let body = r#"{"error":[],"result":{"XXBTZUSD":[["42000.10000","0.01000000",1704067200.1234,"s","l","",70001]],"last":"1704067200123400000"}}"#;
+        let trades = parse_rest_trades(body, "BTC/USD").unwrap();
+        assert_eq!(trades[0].trade_id, 70001);
+        assert_eq!(trades[0].side, Side::Sell);
+        assert_eq!(trades[0].timestamp, "2024-01-01T00:00:00.123400Z");
+
+        let point = ResumePoint {
+            trade_id: 70001,
+            timestamp: trades[0].timestamp.clone(),
+        };
+        assert_eq!(
+            trades_backfill_url("XBTUSD", Some(&point)),
+            "https://api.kraken.com/0/public/Trades?pair=XBTUSD&since=1704067200"
+        );
+    }
+}
@@ -0,0 +1,424 @@
+//! Trade aggressor classification and flow imbalance
+//!
+//! [`TradeFlow`] consumes the public trade channel and keeps, per symbol:
+//!
+//! - the aggressor of every trade: the side Kraken reports, or, for trades
+//!   recorded without one, the tick rule (uptick = buy, downtick = sell,
+//!   unchanged price = same as the last move);
+//! - rolling buy/sell volume imbalance over each configured window, as a
+//!   series with one point per trade;
+//! - trades whose notional reaches the large-trade threshold.
+//!
+//! Windows are measured on exchange timestamps, so replayed data produces
+//! the same series as live data. [`AlertManager::check_flow`] turns the
+//! latest trade into [`AlertKind::LargeTrade`] and flow imbalance alerts.
+//!
+//! [`AlertManager::check_flow`]: crate::alerts::AlertManager::check_flow
+//! [`AlertKind::LargeTrade`]: crate::alerts::AlertKind::LargeTrade
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_sdk::trade_flow::{AggressorSource, TradeFlow};
+//! use kraken_types::{Price, Qty, Side};
+//! use rust_decimal_macros::dec;
+//! use std::time::Duration;
+//!
+//! let mut flow = TradeFlow::new()
+//!     .with_window(Duration::from_secs(10))
+//!     .with_large_trade_notional(dec!(100000));
+//!
+//! flow.record("BTC/USD", Price::new(dec!(50000)), Qty::new(dec!(3)), Some(Side::Buy), 0);
+//! flow.record("BTC/USD", Price::new(dec!(50001)), Qty::new(dec!(1)), None, 1_000_000);
+//! let trade = flow.record("BTC/USD", Price::new(dec!(49999)), Qty::new(dec!(1)), None, 2_000_000);
+//! assert_eq!(trade.side, Some(Side::Sell));
+//! assert_eq!(trade.source, AggressorSource::TickRule);
+//!
+//! let imbalance = flow.imbalance("BTC/USD", Duration::from_secs(10)).unwrap();
+//! assert_eq!(imbalance.buy_qty, Qty::new(dec!(4)));
+//! assert_eq!(imbalance.ratio, dec!(0.6));
+//! assert_eq!(flow.large_trades("BTC/USD").len(), 1);
+//! ```
+
+use kraken_types::{Decimal, Notional, Price, Qty, Side};
+use kraken_ws::{Event, MarketEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+/// Default imbalance windows
+pub const DEFAULT_WINDOWS: [Duration; 3] =
+    [Duration::from_secs(10), Duration::from_secs(60), Duration::from_secs(300)];
+
+/// Default number of points kept per series (and large trades per symbol)
+pub const DEFAULT_SERIES_LEN: usize = 1000;
+
+/// How a trade's aggressor was determined
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AggressorSource {
+    /// Side reported by the exchange
+    Reported,
+    /// Inferred from the price move since the previous trade
+    TickRule,
+}
+
+/// A trade with its aggressor side
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassifiedTrade {
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Trade price
+    pub price: Price,
+    /// Trade quantity
+    pub qty: Qty,
+    /// Aggressor side (None if the tick rule had no earlier price move)
+    pub side: Option<Side>,
+    /// How `side` was determined
+    pub source: AggressorSource,
+    /// Exchange time (µs since epoch)
+    pub at_us: i64,
+    /// Notional reached the large-trade threshold
+    pub is_large: bool,
+}
+
+impl ClassifiedTrade {
+    /// Trade value (price * qty)
+    pub fn notional(&self) -> Notional {
+        self.price * self.qty
+    }
+}
+
+/// Buy and sell volume over a window
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlowImbalance {
+    /// Aggressive buy volume
+    pub buy_qty: Qty,
+    /// Aggressive sell volume
+    pub sell_qty: Qty,
+    /// Classified trades in the window
+    pub trades: usize,
+    /// (buy - sell) / (buy + sell), from -1 (all sells) to +1 (all buys)
+    pub ratio: Decimal,
+}
+
+/// One point of an imbalance series
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImbalancePoint {
+    /// Exchange time of the trade that produced the point (µs since epoch)
+    pub at_us: i64,
+    /// Imbalance over the window ending at `at_us`
+    pub imbalance: FlowImbalance,
+}
+
+/// Rolling volume over one window
+#[derive(Debug, Clone)]
+struct RollingWindow {
+    length_us: i64,
+    trades: VecDeque<(i64, Side, Qty)>,
+    buy_qty: Qty,
+    sell_qty: Qty,
+    series: VecDeque<ImbalancePoint>,
+}
+
+impl RollingWindow {
+    fn new(length: Duration) -> Self {
+        Self {
+            length_us: length.as_micros() as i64,
+            trades: VecDeque::new(),
+            buy_qty: Qty::ZERO,
+            sell_qty: Qty::ZERO,
+            series: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, at_us: i64, side: Option<Side>, qty: Qty, series_len: usize) {
+        if let Some(side) = side {
+            match side {
+                Side::Buy => self.buy_qty += qty,
+                Side::Sell => self.sell_qty += qty,
+            }
+            self.trades.push_back((at_us, side, qty));
+        }
+        while let Some(&(at, side, qty)) = self.trades.front() {
+            if at > at_us - self.length_us {
+                break;
+            }
+            match side {
+                Side::Buy => self.buy_qty -= qty,
+                Side::Sell => self.sell_qty -= qty,
+            }
+            self.trades.pop_front();
+        }
+
+        if self.series.len() >= series_len {
+            self.series.pop_front();
+        }
+        self.series.push_back(ImbalancePoint {
+            at_us,
+            imbalance: self.imbalance(),
+        });
+    }
+
+    fn imbalance(&self) -> FlowImbalance {
+        let total = self.buy_qty + self.sell_qty;
+        let ratio = if total.is_zero() {
+            Decimal::ZERO
+        } else {
+            (self.buy_qty - self.sell_qty) / total
+        };
+        FlowImbalance {
+            buy_qty: self.buy_qty,
+            sell_qty: self.sell_qty,
+            trades: self.trades.len(),
+            ratio,
+        }
+    }
+}
+
+/// Per-symbol flow state
+#[derive(Debug, Clone)]
+struct SymbolFlow {
+    last_price: Option<Price>,
+    /// Direction of the last non-zero price move
+    last_tick: Option<Side>,
+    windows: Vec<(Duration, RollingWindow)>,
+    large_trades: VecDeque<ClassifiedTrade>,
+}
+
+impl SymbolFlow {
+    fn new(windows: &[Duration]) -> Self {
+        Self {
+            last_price: None,
+            last_tick: None,
+            windows: windows.iter().map(|w| (*w, RollingWindow::new(*w))).collect(),
+            large_trades: VecDeque::new(),
+        }
+    }
+
+    fn window(&self, length: Duration) -> Option<&RollingWindow> {
+        self.windows.iter().find(|(w, _)| *w == length).map(|(_, window)| window)
+    }
+}
+
+/// Per-symbol aggressor classification, imbalance series and large trades
+#[derive(Debug, Clone)]
+pub struct TradeFlow {
+    symbols: HashMap<String, SymbolFlow>,
+    windows: Vec<Duration>,
+    large_trade_notional: Option<Notional>,
+    series_len: usize,
+}
+
+impl Default for TradeFlow {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TradeFlow {
+    /// Create a tracker without windows or large-trade threshold
+    ///
+    /// Add windows with [`with_window`](Self::with_window), or use
+    /// [`with_default_windows`](Self::with_default_windows).
+    pub fn new() -> Self {
+        Self {
+            symbols: HashMap::new(),
+            windows: Vec::new(),
+            large_trade_notional: None,
+            series_len: DEFAULT_SERIES_LEN,
+        }
+    }
+
+    /// Track imbalance over a window (ignored if already tracked)
+    pub fn with_window(mut self, window: Duration) -> Self {
+        if !window.is_zero() && !self.windows.contains(&window) {
+            self.windows.push(window);
+            self.windows.sort();
+        }
+        self
+    }
+
+    /// Track imbalance over 10s, 1m and 5m
+    pub fn with_default_windows(self) -> Self {
+        DEFAULT_WINDOWS.iter().fold(self, |flow, w| flow.with_window(*w))
+    }
+
+    /// Flag trades whose notional reaches this value (quote currency)
+    pub fn with_large_trade_notional(mut self, notional: impl Into<Notional>) -> Self {
+        self.large_trade_notional = Some(notional.into());
+        self
+    }
+
+    /// Keep at most `len` points per series and large trades per symbol
+    pub fn with_series_len(mut self, len: usize) -> Self {
+        self.series_len = len.max(1);
+        self
+    }
+
+    /// Configured windows, shortest first
+    pub fn windows(&self) -> &[Duration] {
+        &self.windows
+    }
+
+    /// Large-trade threshold, if set
+    pub fn large_trade_notional(&self) -> Option<Notional> {
+        self.large_trade_notional
+    }
+
+    /// Classify and record a trade
+    ///
+    /// `side` is the reported aggressor; pass None to fall back to the tick
+    /// rule. `at_us` is the exchange time in microseconds since the epoch.
+    pub fn record(&mut self, symbol: &str, price: Price, qty: Qty, side: Option<Side>, at_us: i64) -> ClassifiedTrade {
+        let windows = &self.windows;
+        let flow = self
+            .symbols
+            .entry(symbol.to_string())
+            .or_insert_with(|| SymbolFlow::new(windows));
+
+        if let Some(last) = flow.last_price {
+            if price > last {
+                flow.last_tick = Some(Side::Buy);
+            } else if price < last {
+                flow.last_tick = Some(Side::Sell);
+            }
+        }
+        flow.last_price = Some(price);
+
+        let (side, source) = match side {
+            Some(side) => (Some(side), AggressorSource::Reported),
+            None => (flow.last_tick, AggressorSource::TickRule),
+        };
+        let trade = ClassifiedTrade {
+            symbol: symbol.to_string(),
+            price,
+            qty,
+            side,
+            source,
+            at_us,
+            is_large: self.large_trade_notional.is_some_and(|min| price * qty >= min),
+        };
+
+        for (_, window) in &mut flow.windows {
+            window.push(at_us, side, qty, self.series_len);
+        }
+        if trade.is_large {
+            if flow.large_trades.len() >= self.series_len {
+                flow.large_trades.pop_front();
+            }
+            flow.large_trades.push_back(trade.clone());
+        }
+        trade
+    }
+
+    /// Record a trade event
+    ///
+    /// Uses the exchange timestamp, or the local receive time if the
+    /// message had none.
+    pub fn handle_event(&mut self, event: &Event) -> Option<ClassifiedTrade> {
+        if let Event::Market(MarketEvent::Trade {
+            symbol,
+            trade,
+            received_at,
+            exchange_ts_us,
+            ..
+        }) = event
+        {
+            let at_us = exchange_ts_us.unwrap_or(received_at.wall_us);
+            return Some(self.record(symbol, trade.price.into(), trade.qty.into(), Some(trade.side), at_us));
+        }
+        None
+    }
+
+    /// Current imbalance over a configured window
+    ///
+    /// Returns None if the window isn't configured or the symbol has no trades.
+    pub fn imbalance(&self, symbol: &str, window: Duration) -> Option<FlowImbalance> {
+        Some(self.symbols.get(symbol)?.window(window)?.imbalance())
+    }
+
+    /// Imbalance over a configured window after each trade, oldest first
+    pub fn imbalance_series(&self, symbol: &str, window: Duration) -> Vec<ImbalancePoint> {
+        self.symbols
+            .get(symbol)
+            .and_then(|flow| flow.window(window))
+            .map(|w| w.series.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    /// Large trades, oldest first
+    pub fn large_trades(&self, symbol: &str) -> Vec<&ClassifiedTrade> {
+        self.symbols
+            .get(symbol)
+            .map(|flow| flow.large_trades.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// Symbols with recorded trades
+    pub fn symbols(&self) -> impl Iterator<Item = &str> {
+        self.symbols.keys().map(String::as_str)
+    }
+
+    /// Forget a symbol's state
+    pub fn reset(&mut self, symbol: &str) {
+        self.symbols.remove(symbol);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const SEC: i64 = 1_000_000;
+
+    fn trade(flow: &mut TradeFlow, price: Decimal, qty: Decimal, side: Option<Side>, at_us: i64) -> ClassifiedTrade {
+        flow.record("BTC/USD", Price::new(price), Qty::new(qty), side, at_us)
+    }
+
+    #[test]
+    fn test_tick_rule_fallback() {
+        let mut flow = TradeFlow::new();
+        assert_eq!(trade(&mut flow, dec!(100), dec!(1), None, 0).side, None);
+        assert_eq!(trade(&mut flow, dec!(101), dec!(1), None, 1).side, Some(Side::Buy));
+        // Zero tick keeps the last direction
+        assert_eq!(trade(&mut flow, dec!(101), dec!(1), None, 2).side, Some(Side::Buy));
+
+        // A reported side wins, but the price move still updates the tick state
+        let reported = trade(&mut flow, dec!(100), dec!(1), Some(Side::Buy), 3);
+        assert_eq!((reported.side, reported.source), (Some(Side::Buy), AggressorSource::Reported));
+        assert_eq!(trade(&mut flow, dec!(100), dec!(1), None, 4).side, Some(Side::Sell));
+    }
+
+    #[test]
+    fn test_rolling_windows_expire_trades() {
+        let mut flow = TradeFlow::new()
+            .with_window(Duration::from_secs(10))
+            .with_window(Duration::from_secs(60));
+        trade(&mut flow, dec!(100), dec!(4), Some(Side::Sell), 0);
+        trade(&mut flow, dec!(100), dec!(1), Some(Side::Buy), 30 * SEC);
+
+        let short = flow.imbalance("BTC/USD", Duration::from_secs(10)).unwrap();
+        assert_eq!((short.buy_qty, short.sell_qty, short.ratio), (Qty::new(dec!(1)), Qty::ZERO, dec!(1)));
+        let long = flow.imbalance("BTC/USD", Duration::from_secs(60)).unwrap();
+        assert_eq!(long.ratio, dec!(-0.6));
+        assert_eq!(long.trades, 2);
+
+        let series = flow.imbalance_series("BTC/USD", Duration::from_secs(10));
+        assert_eq!(series.iter().map(|p| p.imbalance.ratio).collect::<Vec<_>>(), vec![dec!(-1), dec!(1)]);
+        assert!(flow.imbalance("BTC/USD", Duration::from_secs(5)).is_none());
+    }
+
+    #[test]
+    fn test_large_trades_are_bounded() {
+        let mut flow = TradeFlow::new().with_large_trade_notional(dec!(1000)).with_series_len(2);
+        assert!(!trade(&mut flow, dec!(100), dec!(9), Some(Side::Buy), 0).is_large);
+        for i in 1..=3 {
+            assert!(trade(&mut flow, dec!(100), dec!(10), Some(Side::Sell), i).is_large);
+        }
+        let large = flow.large_trades("BTC/USD");
+        assert_eq!(large.iter().map(|t| t.at_us).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(large[0].notional(), Notional::new(dec!(1000)));
+    }
+}
@@ -1,28 +1,15 @@
 //! Common test utilities and fixtures for integration tests
 //!
-//! Contains sample JSON messages captured from live Kraken API v2
+//! Contains sample JSON messages captured from live Kraken API v2. Longer,
+//! randomized sessions come from `kraken_fixtures::SessionGenerator`.
 
 use kraken_book::{compute_checksum, Orderbook, OrderbookState};
 use kraken_types::{BookData, Level, WsMessage};
 use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
 
-/// Sample status message from Kraken on connection
-pub const STATUS_MESSAGE: &str = r#"{
-    "channel": "status",
-    "type": "update",
-    "data": [{
-        "api_version": "v2",
-        "connection_id": 12345678901234567890,
-        "system": "online",
-        "version": "2.0.10"
-    }]
-}"#;
-
-/// Sample heartbeat message
-pub const HEARTBEAT_MESSAGE: &str = r#"{
-    "channel": "heartbeat"
-}"#;
+/// Status and heartbeat frames shared with `kraken-fixtures`
+pub use kraken_fixtures::messages::{HEARTBEAT_MESSAGE, STATUS_MESSAGE};
 
 /// Sample subscribe response
 pub const SUBSCRIBE_RESPONSE: &str = r#"{
@@ -6,7 +6,7 @@
 mod common;
 
 use common::*;
-use kraken_book::{compute_checksum, Orderbook, OrderbookState};
+use kraken_book::{compute_checksum, ApplyError, Orderbook, OrderbookState};
 use kraken_types::{ChannelMessage, Decimal, Level, WsMessage};
 use rust_decimal_macros::dec;
 
@@ -204,6 +204,21 @@ fn test_multi_symbol_concurrent_updates() {
     assert_eq!(eth_book.best_bid().unwrap().price, dec!(3500.0));
 }
 
+#[test]
+fn test_generated_session_stays_synced() {
+    use kraken_fixtures::{SessionConfig, SessionGenerator};
+
+    let config = SessionConfig::new("SOL/USD").with_start_mid(dec!(150)).with_volatility_bps(5.0);
+    let mut book = Orderbook::new("SOL/USD");
+    for frame in SessionGenerator::new(config, 1).take(2_000) {
+        if let WsMessage::Book(ChannelMessage { data, msg_type, .. }) = parse_message(&frame.json) {
+            book.apply_book_data(&data[0], msg_type == "snapshot").unwrap();
+        }
+    }
+    assert!(book.is_synced());
+    assert!(book.best_bid().unwrap().price < book.best_ask().unwrap().price);
+}
+
 // =============================================================================
 // Checksum Algorithm Tests
 // =============================================================================
@@ -406,10 +421,11 @@ fn test_update_before_snapshot_ignored() {
         vec![(dec!(101), dec!(1))],
     );
 
-    // Should be ignored (not synced yet)
+    // Dropped and reported (not synced yet)
     let result = book.apply_book_data(&data, false);
-    assert!(result.is_ok()); // Doesn't error, just ignores
+    assert!(matches!(result, Err(ApplyError::UpdateBeforeSnapshot { .. })));
 
-    // Still uninitialized
+    // Still uninitialized and empty
     assert_eq!(book.state(), OrderbookState::Uninitialized);
+    assert_eq!(book.bid_count(), 0);
 }
@@ -0,0 +1,141 @@
+//! Account identifiers for processes trading on several API keys
+//!
+//! An [`AccountId`] is a local label (e.g. `"desk-a"`), not anything Kraken
+//! assigns. Token managers, trading clients and order trackers carry one so
+//! requests, rate limits and orders can be attributed to the right key.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Local name of a trading account (one API key pair)
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct AccountId(String);
+
+impl AccountId {
+    /// Name used when a process trades on a single account
+    pub const DEFAULT: &'static str = "default";
+
+    /// Create an account ID
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    /// Get the ID as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether this is the [`DEFAULT`](Self::DEFAULT) account
+    pub fn is_default(&self) -> bool {
+        self.0 == Self::DEFAULT
+    }
+}
+
+impl Default for AccountId {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT)
+    }
+}
+
+impl fmt::Display for AccountId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for AccountId {
+    fn from(id: &str) -> Self {
+        Self::new(id)
+    }
+}
+
+impl From<String> for AccountId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
+
+impl AsRef<str> for AccountId {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::messages::ExecutionData;
+    use std::collections::BTreeMap;
+
+    /// Trade from Kraken's executions channel, as documented for API v2
+    const EXECUTION: &str = r#"{
+        "order_id": "OK4GJX-KSTLS-7DZZO5",
+        "order_userref": 3,
+        "symbol": "BTC/USD",
+        "order_qty": 0.005,
+        "cum_cost": 0.0,
+        "time_in_force": "GTC",
+        "exec_type": "trade",
+        "side": "sell",
+        "order_type": "limit",
+        "limit_price_type": "static",
+        "limit_price": 26500.0,
+        "exec_id": "TRQBI6-CTMAD-4HTOXT",
+        "trade_id": 38576574,
+        "last_qty": 0.005,
+        "last_price": 26500.0,
+        "cum_qty": 0.005,
+        "avg_price": 26500.0,
+        "fee_paid": 0.34,
+        "fee_currency": "USD",
+        "order_status": "filled",
+        "timestamp": "2023-09-22T10:33:05.709993Z"
+    }"#;
+
+    /// An execution tagged with the account whose stream it arrived on
+    #[derive(Deserialize)]
+    struct Attributed {
+        #[serde(default)]
+        account: AccountId,
+        #[serde(flatten)]
+        execution: ExecutionData,
+    }
+
+    #[test]
+    fn test_serializes_as_plain_string() {
+        let id = AccountId::new("desk-a");
+        assert_eq!(serde_json::to_string(&id).unwrap(), r#""desk-a""#);
+        assert_eq!(serde_json::from_str::<AccountId>(r#""desk-a""#).unwrap(), id);
+
+        let default: AccountId = serde_json::from_str(r#""default""#).unwrap();
+        assert!(default.is_default());
+        assert!(serde_json::from_str::<AccountId>("42").is_err());
+        assert!(serde_json::from_str::<AccountId>(r#"{"id":"desk-a"}"#).is_err());
+    }
+
+    #[test]
+    fn test_round_trip_as_map_key() {
+        let json = r#"{"desk-a":{"api_counter":12},"hedge":{"api_counter":3}}"#;
+        let limits: BTreeMap<AccountId, BTreeMap<String, u32>> =
+            serde_json::from_str(json).unwrap();
+        assert_eq!(limits[&AccountId::from("hedge")]["api_counter"], 3);
+        assert_eq!(limits.keys().map(AccountId::as_str).collect::<Vec<_>>(), ["desk-a", "hedge"]);
+        assert_eq!(serde_json::to_string(&limits).unwrap(), json);
+    }
+
+    #[test]
+    fn test_attributes_kraken_execution() {
+        let mut payload: serde_json::Value = serde_json::from_str(EXECUTION).unwrap();
+        let untagged: Attributed = serde_json::from_value(payload.clone()).unwrap();
+        assert!(untagged.account.is_default());
+        assert_eq!(untagged.execution.order_id, "OK4GJX-KSTLS-7DZZO5");
+
+        payload["account"] = serde_json::to_value(AccountId::new("desk-b")).unwrap();
+        let tagged: Attributed = serde_json::from_value(payload).unwrap();
+        assert_eq!(tagged.account, "desk-b".into());
+        assert_eq!(tagged.account.to_string(), "desk-b");
+        assert_eq!(tagged.execution.trade_id, Some(38576574));
+        assert_eq!(tagged.execution.fee_currency.as_deref(), Some("USD"));
+    }
+}
@@ -104,6 +104,47 @@ impl Depth {
     }
 }
 
+/// Level 3 orderbook depths
+///
+/// The level3 channel only accepts these depths, and each one draws from a
+/// different subscription rate counter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum L3Depth {
+    /// 10 orders per side
+    #[default]
+    D10 = 10,
+    /// 100 orders per side
+    D100 = 100,
+    /// 1000 orders per side
+    D1000 = 1000,
+}
+
+impl L3Depth {
+    /// Returns the depth as a u32
+    pub fn as_u32(&self) -> u32 {
+        *self as u32
+    }
+
+    /// Rate counter charged for subscribing at this depth
+    pub fn rate_limit_category(&self) -> crate::RateLimitCategory {
+        match self {
+            Self::D10 => crate::RateLimitCategory::L3Depth10,
+            Self::D100 => crate::RateLimitCategory::L3Depth100,
+            Self::D1000 => crate::RateLimitCategory::L3Depth1000,
+        }
+    }
+}
+
+impl From<L3Depth> for Depth {
+    fn from(depth: L3Depth) -> Self {
+        match depth {
+            L3Depth::D10 => Depth::D10,
+            L3Depth::D100 => Depth::D100,
+            L3Depth::D1000 => Depth::D1000,
+        }
+    }
+}
+
 
 /// Order types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -128,6 +169,13 @@ pub enum OrderType {
     TakeProfitLimit,
 }
 
+impl OrderType {
+    /// Parse an order type as sent to Kraken (e.g. "stop-loss-limit")
+    pub fn parse(order_type: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(order_type.to_string())).ok()
+    }
+}
+
 /// OHLC interval in minutes
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum OhlcInterval {
@@ -178,6 +226,26 @@ pub enum SystemStatus {
     Maintenance,
 }
 
+impl SystemStatus {
+    /// Returns true in normal operation
+    pub fn is_online(&self) -> bool {
+        *self == Self::Online
+    }
+
+    /// Returns true if new orders and amends are accepted
+    ///
+    /// Restricted modes (post, limit, reduce only) still accept orders that
+    /// meet their restriction; the exchange rejects the rest.
+    pub fn allows_new_orders(&self) -> bool {
+        !matches!(self, Self::CancelOnly | Self::Maintenance)
+    }
+
+    /// Returns true if cancels are accepted
+    pub fn allows_cancels(&self) -> bool {
+        *self != Self::Maintenance
+    }
+}
+
 impl std::fmt::Display for SystemStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -191,6 +259,86 @@ impl std::fmt::Display for SystemStatus {
     }
 }
 
+/// Trading status of one pair
+///
+/// Reported per pair by the instrument channel and the REST `AssetPairs`
+/// endpoint. Kraken moves single pairs into restricted modes during
+/// volatility, listings and delistings, independently of [`SystemStatus`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PairStatus {
+    /// Normal trading
+    Online,
+    /// Only cancels are accepted
+    CancelOnly,
+    /// Only post-only limit orders are accepted
+    PostOnly,
+    /// Only limit orders are accepted
+    LimitOnly,
+    /// Only orders reducing a margin position are accepted
+    ReduceOnly,
+    /// Pair is in maintenance
+    Maintenance,
+    /// Pair is being listed and not yet tradeable
+    WorkInProgress,
+    /// Pair no longer trades
+    Delisted,
+}
+
+impl PairStatus {
+    /// Parse a status string as sent by Kraken (e.g. "post_only")
+    pub fn parse(status: &str) -> Option<Self> {
+        serde_json::from_value(serde_json::Value::String(status.to_string())).ok()
+    }
+
+    /// Returns true in normal trading
+    pub fn is_online(&self) -> bool {
+        *self == Self::Online
+    }
+
+    /// Returns true if the pair accepts any new orders
+    pub fn allows_new_orders(&self) -> bool {
+        matches!(self, Self::Online | Self::PostOnly | Self::LimitOnly | Self::ReduceOnly)
+    }
+
+    /// Returns true if cancels are accepted
+    pub fn allows_cancels(&self) -> bool {
+        matches!(self, Self::Online | Self::CancelOnly | Self::PostOnly | Self::LimitOnly | Self::ReduceOnly)
+    }
+
+    /// Returns true if the pair accepts an order of this type
+    ///
+    /// Reduce-only pairs are accepted here; whether the order reduces a
+    /// position is for the exchange to check.
+    pub fn permits(&self, order_type: OrderType, post_only: bool) -> bool {
+        let limit = matches!(
+            order_type,
+            OrderType::Limit | OrderType::StopLossLimit | OrderType::TakeProfitLimit
+        );
+        match self {
+            Self::Online | Self::ReduceOnly => true,
+            Self::LimitOnly => limit,
+            Self::PostOnly => order_type == OrderType::Limit && post_only,
+            Self::CancelOnly | Self::Maintenance | Self::WorkInProgress | Self::Delisted => false,
+        }
+    }
+}
+
+impl std::fmt::Display for PairStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Online => write!(f, "online"),
+            Self::CancelOnly => write!(f, "cancel_only"),
+            Self::PostOnly => write!(f, "post_only"),
+            Self::LimitOnly => write!(f, "limit_only"),
+            Self::ReduceOnly => write!(f, "reduce_only"),
+            Self::Maintenance => write!(f, "maintenance"),
+            Self::WorkInProgress => write!(f, "work_in_progress"),
+            Self::Delisted => write!(f, "delisted"),
+        }
+    }
+}
+
 /// Ticker event trigger
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
@@ -216,6 +364,22 @@ mod tests {
         assert_eq!(parsed, Channel::Ticker);
     }
 
+    #[test]
+    fn test_pair_status_permits() {
+        assert_eq!(PairStatus::parse("post_only"), Some(PairStatus::PostOnly));
+        assert_eq!(PairStatus::parse("work_in_progress"), Some(PairStatus::WorkInProgress));
+        assert_eq!(PairStatus::parse("halted"), None);
+        assert_eq!(PairStatus::LimitOnly.to_string(), "limit_only");
+
+        assert!(PairStatus::PostOnly.permits(OrderType::Limit, true));
+        assert!(!PairStatus::PostOnly.permits(OrderType::Limit, false));
+        assert!(PairStatus::LimitOnly.permits(OrderType::StopLossLimit, false));
+        assert!(!PairStatus::LimitOnly.permits(OrderType::Market, false));
+        assert!(!PairStatus::CancelOnly.permits(OrderType::Limit, true));
+        assert!(PairStatus::CancelOnly.allows_cancels());
+        assert!(!PairStatus::Delisted.allows_cancels());
+    }
+
     #[test]
     fn test_depth_serde() {
         // Depth serializes as number
@@ -223,6 +387,13 @@ mod tests {
         assert_eq!(depth.as_u32(), 10);
     }
 
+    #[test]
+    fn test_l3_depth() {
+        assert_eq!(L3Depth::default().as_u32(), 10);
+        assert_eq!(L3Depth::D1000.rate_limit_category(), crate::RateLimitCategory::L3Depth1000);
+        assert_eq!(Depth::from(L3Depth::D100), Depth::D100);
+    }
+
     #[test]
     fn test_side_opposite() {
         assert_eq!(Side::Buy.opposite(), Side::Sell);
@@ -58,6 +58,33 @@ impl RecoveryStrategy {
         }
     }
 
+    /// Delay before retry number `attempt` (0 for the first retry)
+    ///
+    /// Backoff delays grow by `multiplier` per attempt up to `max_ms`.
+    /// Returns `None` for strategies that don't wait before retrying.
+    pub fn delay_for_attempt(&self, attempt: u32) -> Option<Duration> {
+        match self {
+            Self::Backoff {
+                initial_ms,
+                max_ms,
+                multiplier,
+            } => {
+                let factor = u64::from(*multiplier).saturating_pow(attempt);
+                Some(Duration::from_millis(initial_ms.saturating_mul(factor).min(*max_ms)))
+            }
+            Self::Retry { delay_ms, .. } => Some(Duration::from_millis(*delay_ms)),
+            _ => None,
+        }
+    }
+
+    /// Maximum number of retries, if the strategy limits them
+    pub fn max_attempts(&self) -> Option<u32> {
+        match self {
+            Self::Retry { max_attempts, .. } => Some(*max_attempts),
+            _ => None,
+        }
+    }
+
     /// Check if this strategy allows retry
     pub fn allows_retry(&self) -> bool {
         matches!(
@@ -399,6 +426,7 @@ impl KrakenErrorCode {
                     Self::InsufficientMargin
                 } else if normalized.contains("unknown asset pair")
                     || normalized.contains("unknown pair")
+                    || normalized.contains("pair not supported")
                 {
                     Self::UnknownAssetPair
                 } else if normalized.contains("permission denied") {
@@ -725,4 +753,14 @@ mod tests {
         assert!(KrakenErrorCode::RateLimitExceeded.is_rate_limit());
         assert!(KrakenErrorCode::InsufficientFunds.is_trading_error());
     }
+
+    #[test]
+    fn test_backoff_delay_grows_and_caps() {
+        let strategy = RecoveryStrategy::rate_limit_backoff();
+        assert_eq!(strategy.delay_for_attempt(0), Some(Duration::from_secs(1)));
+        assert_eq!(strategy.delay_for_attempt(2), Some(Duration::from_secs(4)));
+        assert_eq!(strategy.delay_for_attempt(10), Some(Duration::from_secs(60)));
+        assert_eq!(RecoveryStrategy::service_retry().max_attempts(), Some(3));
+        assert_eq!(RecoveryStrategy::Skip.delay_for_attempt(0), None);
+    }
 }
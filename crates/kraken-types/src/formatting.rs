@@ -0,0 +1,238 @@
+//! Price and quantity precision per instrument
+//!
+//! Kraken publishes each pair's decimal places and minimum increments on the
+//! instrument channel ([`InstrumentPair`]). [`Formatting`] keeps that data
+//! per symbol and is the one place prices and quantities are rounded and
+//! rendered, whether for checksums, display or order submission:
+//!
+//! | Helper | Rounding | Use |
+//! |--------|----------|-----|
+//! | [`Formatting::format_price`] | half-even to `price_precision` places, zero-padded | display, checksums |
+//! | [`Formatting::format_qty`] | half-even to `qty_precision` places, zero-padded | display, checksums |
+//! | [`Formatting::round_to_tick`] | nearest multiple of the price increment, ties to even | order prices |
+//! | [`Formatting::round_qty`] | down to a multiple of the qty increment | order sizes |
+//!
+//! Symbols without instrument data use the fallback precision (one price
+//! decimal and eight quantity decimals, Kraken's BTC/USD values, unless set
+//! with [`Formatting::with_fallback`]).
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_types::formatting::{Formatting, Precision};
+//! use rust_decimal_macros::dec;
+//!
+//! let formatting = Formatting::new().with_precision("ETH/USD", Precision::new(2, 8));
+//!
+//! assert_eq!(formatting.format_price("ETH/USD", dec!(3150.4)), "3150.40");
+//! assert_eq!(formatting.round_to_tick("ETH/USD", dec!(3150.456)), dec!(3150.46));
+//! assert_eq!(formatting.format_qty("ETH/USD", dec!(0.5)), "0.50000000");
+//! ```
+
+use crate::messages::InstrumentPair;
+use rust_decimal::{Decimal, RoundingStrategy};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Decimal places and increments of one instrument
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Precision {
+    /// Decimal places of prices
+    pub price_precision: u8,
+    /// Decimal places of quantities
+    pub qty_precision: u8,
+    /// Minimum price step, if coarser than one unit of the last decimal
+    pub price_increment: Option<Decimal>,
+    /// Minimum quantity step, if coarser than one unit of the last decimal
+    pub qty_increment: Option<Decimal>,
+}
+
+impl Precision {
+    /// Precision with the given decimal places and no coarser increments
+    pub const fn new(price_precision: u8, qty_precision: u8) -> Self {
+        Self {
+            price_precision,
+            qty_precision,
+            price_increment: None,
+            qty_increment: None,
+        }
+    }
+
+    /// Smallest price step
+    pub fn tick(&self) -> Decimal {
+        self.price_increment
+            .filter(|inc| *inc > Decimal::ZERO)
+            .unwrap_or_else(|| Decimal::new(1, u32::from(self.price_precision)))
+    }
+
+    /// Smallest quantity step
+    pub fn lot(&self) -> Decimal {
+        self.qty_increment
+            .filter(|inc| *inc > Decimal::ZERO)
+            .unwrap_or_else(|| Decimal::new(1, u32::from(self.qty_precision)))
+    }
+}
+
+impl Default for Precision {
+    fn default() -> Self {
+        Self::new(1, 8)
+    }
+}
+
+impl From<&InstrumentPair> for Precision {
+    fn from(pair: &InstrumentPair) -> Self {
+        Self {
+            price_precision: pair.price_precision,
+            qty_precision: pair.qty_precision,
+            price_increment: pair.price_increment,
+            qty_increment: pair.qty_increment,
+        }
+    }
+}
+
+/// Per-symbol precision with rounding and formatting helpers
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Formatting {
+    symbols: HashMap<String, Precision>,
+    fallback: Precision,
+}
+
+impl Formatting {
+    /// Create without instrument data
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set a symbol's precision
+    pub fn with_precision(mut self, symbol: impl Into<String>, precision: Precision) -> Self {
+        self.insert(symbol, precision);
+        self
+    }
+
+    /// Set the precision used for symbols without instrument data
+    pub fn with_fallback(mut self, fallback: Precision) -> Self {
+        self.fallback = fallback;
+        self
+    }
+
+    /// Set a symbol's precision
+    pub fn insert(&mut self, symbol: impl Into<String>, precision: Precision) {
+        self.symbols.insert(symbol.into(), precision);
+    }
+
+    /// Record precision from an instrument channel entry
+    pub fn apply_instrument(&mut self, pair: &InstrumentPair) {
+        self.insert(pair.symbol.clone(), Precision::from(pair));
+    }
+
+    /// Precision known for a symbol, without the fallback
+    pub fn get(&self, symbol: &str) -> Option<Precision> {
+        self.symbols.get(symbol).copied()
+    }
+
+    /// Precision for a symbol, or the fallback
+    pub fn precision(&self, symbol: &str) -> Precision {
+        self.get(symbol).unwrap_or(self.fallback)
+    }
+
+    /// Number of symbols with instrument data
+    pub fn len(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Returns true if no symbol has instrument data
+    pub fn is_empty(&self) -> bool {
+        self.symbols.is_empty()
+    }
+
+    /// Price with exactly the symbol's price decimals
+    pub fn format_price(&self, symbol: &str, price: Decimal) -> String {
+        format_decimal(price, self.precision(symbol).price_precision)
+    }
+
+    /// Quantity with exactly the symbol's quantity decimals
+    pub fn format_qty(&self, symbol: &str, qty: Decimal) -> String {
+        format_decimal(qty, self.precision(symbol).qty_precision)
+    }
+
+    /// Price rounded to the nearest valid tick
+    pub fn round_to_tick(&self, symbol: &str, price: Decimal) -> Decimal {
+        round_to_increment(price, self.precision(symbol).tick())
+    }
+
+    /// Quantity rounded down to a valid lot, so an order never exceeds it
+    pub fn round_qty(&self, symbol: &str, qty: Decimal) -> Decimal {
+        floor_to_increment(qty, self.precision(symbol).lot())
+    }
+}
+
+/// `value` rounded half-even and rescaled to exactly `decimals` places
+///
+/// The result's mantissa is the value's digits with the decimal point
+/// removed, which is what Kraken's checksum is computed over.
+pub fn fixed_point(value: Decimal, decimals: u8) -> Decimal {
+    let mut rounded = value.round_dp(u32::from(decimals));
+    rounded.rescale(u32::from(decimals));
+    rounded
+}
+
+/// `value` with exactly `decimals` places, e.g. `(1.5, 3)` → `"1.500"`
+pub fn format_decimal(value: Decimal, decimals: u8) -> String {
+    fixed_point(value, decimals).to_string()
+}
+
+/// Nearest multiple of `increment`, ties to the even multiple
+///
+/// Returns `value` unchanged if `increment` isn't positive.
+pub fn round_to_increment(value: Decimal, increment: Decimal) -> Decimal {
+    if increment <= Decimal::ZERO {
+        return value;
+    }
+    ((value / increment).round() * increment).normalize()
+}
+
+/// Largest multiple of `increment` not exceeding `value` in magnitude
+///
+/// Returns `value` unchanged if `increment` isn't positive.
+pub fn floor_to_increment(value: Decimal, increment: Decimal) -> Decimal {
+    if increment <= Decimal::ZERO {
+        return value;
+    }
+    ((value / increment).round_dp_with_strategy(0, RoundingStrategy::ToZero) * increment).normalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_fixed_point_formatting() {
+        assert_eq!(format_decimal(dec!(88813.5), 1), "88813.5");
+        assert_eq!(format_decimal(dec!(0.001), 8), "0.00100000");
+        assert_eq!(format_decimal(dec!(2.25), 1), "2.2");
+        assert_eq!(format_decimal(dec!(100), 2), "100.00");
+        assert_eq!(fixed_point(dec!(0.00460208), 8).mantissa(), 460208);
+    }
+
+    #[test]
+    fn test_increments_and_instrument_data() {
+        let pair: InstrumentPair = serde_json::from_str(
+            r#"{"symbol":"BTC/USD","price_precision":1,"qty_precision":8,"price_increment":0.5,"qty_increment":0.0001}"#,
+        )
+        .unwrap();
+        let mut formatting = Formatting::new().with_fallback(Precision::new(2, 4));
+        formatting.apply_instrument(&pair);
+
+        assert_eq!(formatting.round_to_tick("BTC/USD", dec!(100.74)), dec!(100.5));
+        assert_eq!(formatting.round_to_tick("BTC/USD", dec!(100.75)), dec!(101));
+        assert_eq!(formatting.round_qty("BTC/USD", dec!(0.12349)), dec!(0.1234));
+        assert_eq!(formatting.format_price("BTC/USD", dec!(100)), "100.0");
+
+        // Unknown symbols use the fallback
+        assert_eq!(formatting.format_price("XRP/USD", dec!(0.5)), "0.50");
+        assert_eq!(formatting.round_qty("XRP/USD", dec!(1.23456)), dec!(1.2345));
+        assert_eq!(formatting.get("XRP/USD"), None);
+        assert_eq!(round_to_increment(dec!(5), Decimal::ZERO), dec!(5));
+    }
+}
@@ -1,5 +1,6 @@
 //! Price level types with decimal precision
 
+use crate::units::{Notional, Price, Qty};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Deserializer, Serialize};
 
@@ -7,37 +8,38 @@ use serde::{Deserialize, Deserializer, Serialize};
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Level {
     /// Price of this level
-    #[serde(deserialize_with = "deserialize_decimal")]
-    pub price: Decimal,
+    pub price: Price,
     /// Quantity at this price level
-    #[serde(deserialize_with = "deserialize_decimal")]
-    pub qty: Decimal,
+    pub qty: Qty,
 }
 
 impl Level {
     /// Create a new price level
-    pub fn new(price: Decimal, qty: Decimal) -> Self {
-        Self { price, qty }
+    ///
+    /// Accepts [`Price`]/[`Qty`] or, during the migration, bare decimals.
+    pub fn new(price: impl Into<Price>, qty: impl Into<Qty>) -> Self {
+        Self {
+            price: price.into(),
+            qty: qty.into(),
+        }
     }
 
     /// Create a level from f64 values (for testing)
     pub fn from_f64(price: f64, qty: f64) -> Self {
         use rust_decimal::prelude::FromPrimitive;
         Self {
-            price: Decimal::from_f64(price).unwrap_or_default(),
-            qty: Decimal::from_f64(qty).unwrap_or_default(),
+            price: Price(Decimal::from_f64(price).unwrap_or_default()),
+            qty: Qty(Decimal::from_f64(qty).unwrap_or_default()),
         }
     }
 
     /// Get price as f64 (for JavaScript interop)
     pub fn price_f64(&self) -> f64 {
-        use rust_decimal::prelude::ToPrimitive;
         self.price.to_f64().unwrap_or(0.0)
     }
 
     /// Get quantity as f64 (for JavaScript interop)
     pub fn qty_f64(&self) -> f64 {
-        use rust_decimal::prelude::ToPrimitive;
         self.qty.to_f64().unwrap_or(0.0)
     }
 
@@ -45,11 +47,16 @@ impl Level {
     pub fn is_zero(&self) -> bool {
         self.qty.is_zero()
     }
+
+    /// Notional value resting at this level
+    pub fn notional(&self) -> Notional {
+        self.price * self.qty
+    }
 }
 
 /// CRITICAL: Custom deserializer to preserve decimal precision
 /// Kraken sends JSON numbers that lose precision with f64
-fn deserialize_decimal<'de, D>(deserializer: D) -> Result<Decimal, D::Error>
+pub(crate) fn deserialize_decimal<'de, D>(deserializer: D) -> Result<Decimal, D::Error>
 where
     D: Deserializer<'de>,
 {
@@ -5,30 +5,38 @@
 //!
 //! # Key Types
 //!
-//! - [`Symbol`] - Trading pair symbols (e.g., "BTC/USD")
+//! - [`Symbol`], [`Asset`] - Trading pair symbols (e.g., "BTC/USD") and asset codes
 //! - [`Level`] - Orderbook price level with decimal precision
+//! - [`Price`], [`Qty`], [`Notional`] - Unit-safe decimal newtypes
 //! - [`Channel`], [`Depth`], [`Side`] - Subscription enums
 //! - [`WsMessage`] - Parsed WebSocket message
 //! - [`KrakenError`] - Error types
 //! - [`KrakenApiError`], [`KrakenErrorCode`] - Comprehensive Kraken API error mapping
 //! - [`TokenBucket`], [`RateLimitConfig`] - Client-side rate limiting
+//! - [`AccountId`] - Local label for one of several trading accounts
 
+pub mod account;
 pub mod enums;
 pub mod error;
 pub mod error_codes;
+pub mod formatting;
 pub mod level;
 pub mod messages;
 pub mod rate_limit;
 pub mod symbol;
+pub mod units;
 
 // Re-export commonly used types
+pub use account::AccountId;
 pub use enums::*;
 pub use error::*;
 pub use error_codes::*;
+pub use formatting::{Formatting, Precision};
 pub use level::*;
 pub use messages::*;
 pub use rate_limit::*;
 pub use symbol::*;
+pub use units::{Notional, Price, Qty};
 
 // Re-export rust_decimal for users
 pub use rust_decimal::Decimal;
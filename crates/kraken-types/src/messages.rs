@@ -1,6 +1,6 @@
 //! Request and response message types for Kraken WebSocket API v2
 
-use crate::{Channel, Depth, Level, OhlcInterval, Side, SystemStatus, TickerTrigger};
+use crate::{Channel, Depth, L3Depth, Level, OhlcInterval, Side, SystemStatus, TickerTrigger};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
@@ -145,11 +145,33 @@ impl SubscribeParams {
         }
     }
 
+    /// Create level3 subscription params
+    ///
+    /// The level3 channel is only served by the L3 endpoint
+    /// (`wss://ws-l3.kraken.com/v2`) and always requires a token.
+    pub fn level3(symbols: Vec<String>, depth: L3Depth, token: String) -> Self {
+        Self {
+            channel: Channel::Level3,
+            symbol: symbols,
+            depth: Some(depth.as_u32()),
+            snapshot: Some(true),
+            interval: None,
+            event_trigger: None,
+            token: Some(token),
+        }
+    }
+
     /// Set the authentication token for private channels
     pub fn with_token(mut self, token: String) -> Self {
         self.token = Some(token);
         self
     }
+
+    /// Request or skip the initial snapshot
+    pub fn with_snapshot(mut self, snapshot: bool) -> Self {
+        self.snapshot = Some(snapshot);
+        self
+    }
 }
 
 /// Unsubscribe request message
@@ -230,6 +252,9 @@ pub struct MethodResponse {
     /// Error message if failed
     #[serde(default)]
     pub error: Option<String>,
+    /// Symbol the response refers to (set on per-symbol subscribe errors)
+    #[serde(default)]
+    pub symbol: Option<String>,
 }
 
 /// Subscription result details
@@ -294,7 +319,7 @@ pub struct BookData {
 }
 
 /// Ticker data
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TickerData {
     /// Trading pair symbol
     pub symbol: String,
@@ -323,7 +348,7 @@ pub struct TickerData {
 }
 
 /// Trade data
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TradeData {
     /// Trading pair symbol
     pub symbol: String,
@@ -342,7 +367,7 @@ pub struct TradeData {
 }
 
 /// OHLC candle data
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OhlcData {
     /// Trading pair symbol
     pub symbol: String,
@@ -457,6 +482,9 @@ pub struct ExecutionData {
     pub exec_type: String,
     /// Order ID
     pub order_id: String,
+    /// Client order ID (if one was set on the order)
+    #[serde(default)]
+    pub cl_ord_id: Option<String>,
     /// Execution ID
     #[serde(default)]
     pub exec_id: Option<String>,
@@ -637,6 +665,9 @@ pub struct AddOrderParams {
     /// Reduce-only flag
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reduce_only: Option<bool>,
+    /// Fund the order on margin (spot margin accounts only)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub margin: Option<bool>,
     /// WebSocket authentication token
     pub token: String,
 }
@@ -656,6 +687,27 @@ impl AddOrderRequest {
         self.req_id = Some(id);
         self
     }
+
+    /// Fund the order on margin
+    ///
+    /// Leverage is chosen by the account's margin settings; the WebSocket
+    /// API only accepts the on/off flag.
+    pub fn with_margin(mut self, margin: bool) -> Self {
+        self.params.margin = Some(margin);
+        self
+    }
+
+    /// Only allow the order to reduce an existing margin position
+    pub fn with_reduce_only(mut self, reduce_only: bool) -> Self {
+        self.params.reduce_only = Some(reduce_only);
+        self
+    }
+
+    /// Tag the order with a client order ID
+    pub fn with_cl_ord_id(mut self, cl_ord_id: impl Into<String>) -> Self {
+        self.params.cl_ord_id = Some(cl_ord_id.into());
+        self
+    }
 }
 
 /// Amend order request
@@ -868,6 +920,9 @@ pub struct BatchOrder {
     /// Client order ID
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cl_ord_id: Option<String>,
+    /// Post-only flag
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_only: Option<bool>,
 }
 
 impl BatchAddRequest {
@@ -1083,6 +1138,24 @@ pub enum WsMessage {
 pub type SubscribeResponse = MethodResponse;
 
 impl WsMessage {
+    /// Short name of the message kind, e.g. `"book"` or `"method"`
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Method(_) => "method",
+            Self::Status(_) => "status",
+            Self::Book(_) => "book",
+            Self::Ticker(_) => "ticker",
+            Self::Trade(_) => "trade",
+            Self::Ohlc(_) => "ohlc",
+            Self::Instrument(_) => "instrument",
+            Self::Executions(_) => "executions",
+            Self::Balances(_) => "balances",
+            Self::Level3(_) => "level3",
+            Self::Heartbeat => "heartbeat",
+            Self::Unknown(_) => "unknown",
+        }
+    }
+
     /// Parse a raw JSON message
     pub fn parse(json: &str) -> Result<Self, serde_json::Error> {
         let value: serde_json::Value = serde_json::from_str(json)?;
@@ -1259,4 +1332,13 @@ mod tests {
         assert!(json.contains("\"channel\":\"book\""));
         assert!(json.contains("\"depth\":10"));
     }
+
+    #[test]
+    fn test_level3_subscribe_serialization() {
+        let params = SubscribeParams::level3(vec!["BTC/USD".to_string()], L3Depth::D100, "tok".to_string());
+        let json = serde_json::to_string(&SubscribeRequest::new(params)).unwrap();
+        assert!(json.contains("\"channel\":\"level3\""));
+        assert!(json.contains("\"depth\":100"));
+        assert!(json.contains("\"token\":\"tok\""));
+    }
 }
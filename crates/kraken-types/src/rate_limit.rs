@@ -3,7 +3,14 @@
 //! This module provides token bucket-based rate limiting to prevent hitting
 //! Kraken's API rate limits. It supports different rate limits for different
 //! endpoint types (public REST, private REST, WebSocket orders, etc.)
+//!
+//! Order placement is governed by a different model: Kraken keeps a
+//! per-pair counter that each trading action increments and that decays at a
+//! rate set by the account's verification tier. [`TradingRateCounter`]
+//! tracks that counter, including the age-based penalty for cancelling or
+//! editing young orders.
 
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
 /// Token bucket rate limiter
@@ -126,6 +133,10 @@ pub struct RateLimitConfig {
     pub l3_depth_100: TokenBucketConfig,
     /// L3 subscription rate counter for depth=1000
     pub l3_depth_1000: TokenBucketConfig,
+    /// WebSocket subscribe requests (pacing for chunked subscriptions)
+    pub ws_subscriptions: TokenBucketConfig,
+    /// Per-pair trading counter limits
+    pub trading: TradingLimitConfig,
 }
 
 /// Configuration for a single token bucket
@@ -181,6 +192,11 @@ impl RateLimitConfig {
             l3_depth_10: TokenBucketConfig::new(5, 1.0),
             l3_depth_100: TokenBucketConfig::new(25, 5.0),
             l3_depth_1000: TokenBucketConfig::new(100, 20.0),
+
+            // Subscribe requests: burst of 10, then 5 per second
+            ws_subscriptions: TokenBucketConfig::new(10, 5.0),
+
+            trading: TradingLimitConfig::for_tier(VerificationTier::Starter),
         }
     }
 
@@ -195,6 +211,8 @@ impl RateLimitConfig {
             l3_depth_10: TokenBucketConfig::new(5, 1.0),
             l3_depth_100: TokenBucketConfig::new(25, 5.0),
             l3_depth_1000: TokenBucketConfig::new(100, 20.0),
+            ws_subscriptions: TokenBucketConfig::new(10, 5.0),
+            trading: TradingLimitConfig::for_tier(VerificationTier::Pro),
         }
     }
 
@@ -208,6 +226,8 @@ impl RateLimitConfig {
             l3_depth_10: TokenBucketConfig::new(1000, 100.0),
             l3_depth_100: TokenBucketConfig::new(1000, 100.0),
             l3_depth_1000: TokenBucketConfig::new(1000, 100.0),
+            ws_subscriptions: TokenBucketConfig::new(1000, 100.0),
+            trading: TradingLimitConfig::new(10_000.0, 1000.0),
         }
     }
 }
@@ -229,6 +249,10 @@ pub enum RateLimitCategory {
     L3Depth100,
     /// L3 orderbook subscription (depth 1000)
     L3Depth1000,
+    /// WebSocket subscribe requests
+    WsSubscribe,
+    /// Per-pair trading counter (add/amend/cancel)
+    Trading,
 }
 
 impl RateLimitCategory {
@@ -242,6 +266,11 @@ impl RateLimitCategory {
             Self::L3Depth10 => config.l3_depth_10,
             Self::L3Depth100 => config.l3_depth_100,
             Self::L3Depth1000 => config.l3_depth_1000,
+            Self::WsSubscribe => config.ws_subscriptions,
+            Self::Trading => TokenBucketConfig::new(
+                config.trading.max_counter as u32,
+                config.trading.decay_per_sec,
+            ),
         }
     }
 
@@ -279,6 +308,241 @@ impl RateLimitResult {
     }
 }
 
+/// Kraken account verification tier, which sets the trading counter limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum VerificationTier {
+    /// Starter: counter 60, decay 1/s
+    #[default]
+    Starter,
+    /// Intermediate: counter 125, decay 2.34/s
+    Intermediate,
+    /// Pro: counter 180, decay 3.75/s
+    Pro,
+}
+
+/// Limits for the per-pair trading counter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradingLimitConfig {
+    /// Counter value at which Kraken rejects further orders
+    pub max_counter: f64,
+    /// Counter decay per second
+    pub decay_per_sec: f64,
+}
+
+impl TradingLimitConfig {
+    /// Create a trading limit configuration
+    pub const fn new(max_counter: f64, decay_per_sec: f64) -> Self {
+        Self {
+            max_counter,
+            decay_per_sec,
+        }
+    }
+
+    /// Documented limits for a verification tier
+    pub const fn for_tier(tier: VerificationTier) -> Self {
+        match tier {
+            VerificationTier::Starter => Self::new(60.0, 1.0),
+            VerificationTier::Intermediate => Self::new(125.0, 2.34),
+            VerificationTier::Pro => Self::new(180.0, 3.75),
+        }
+    }
+}
+
+impl Default for TradingLimitConfig {
+    fn default() -> Self {
+        Self::for_tier(VerificationTier::default())
+    }
+}
+
+/// A trading request as seen by the rate counter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TradingAction {
+    /// Place a new order on a pair
+    Add {
+        /// Trading pair
+        symbol: String,
+    },
+    /// Amend an order in place
+    Amend {
+        /// Order being amended
+        order_id: String,
+    },
+    /// Edit an order (cancel and replace)
+    Edit {
+        /// Order being edited
+        order_id: String,
+    },
+    /// Cancel an order
+    Cancel {
+        /// Order being cancelled
+        order_id: String,
+    },
+}
+
+/// Counter penalty for cancelling an order of the given age
+pub fn cancel_penalty(order_age: Duration) -> f64 {
+    match order_age.as_secs() {
+        0..=4 => 8.0,
+        5..=9 => 6.0,
+        10..=14 => 5.0,
+        15..=44 => 4.0,
+        45..=89 => 2.0,
+        90..=299 => 1.0,
+        _ => 0.0,
+    }
+}
+
+/// Counter penalty for editing an order of the given age
+pub fn edit_penalty(order_age: Duration) -> f64 {
+    match order_age.as_secs() {
+        0..=4 => 6.0,
+        5..=9 => 5.0,
+        10..=14 => 4.0,
+        15..=44 => 2.0,
+        45..=89 => 1.0,
+        _ => 0.0,
+    }
+}
+
+/// Decaying per-pair counter for one pair
+#[derive(Debug, Clone, Copy)]
+struct PairCounter {
+    value: f64,
+    updated: Instant,
+}
+
+/// Model of Kraken's per-pair trading rate counter
+///
+/// Every action adds its cost to the counter of the pair it affects, and the
+/// counter decays linearly toward zero. Adds and amends cost 1; cancels and
+/// edits are charged by the age of the order, so the counter remembers when
+/// each order was opened (see [`order_opened`](Self::order_opened)). Actions
+/// on orders it doesn't know are charged the fixed part only.
+#[derive(Debug, Clone)]
+pub struct TradingRateCounter {
+    config: TradingLimitConfig,
+    counters: HashMap<String, PairCounter>,
+    orders: HashMap<String, (String, Instant)>,
+}
+
+impl Default for TradingRateCounter {
+    fn default() -> Self {
+        Self::new(TradingLimitConfig::default())
+    }
+}
+
+impl TradingRateCounter {
+    /// Create a counter with the given limits
+    pub fn new(config: TradingLimitConfig) -> Self {
+        Self {
+            config,
+            counters: HashMap::new(),
+            orders: HashMap::new(),
+        }
+    }
+
+    /// Create a counter with a tier's documented limits
+    pub fn for_tier(tier: VerificationTier) -> Self {
+        Self::new(TradingLimitConfig::for_tier(tier))
+    }
+
+    /// Limits in use
+    pub fn config(&self) -> TradingLimitConfig {
+        self.config
+    }
+
+    /// Remember when an order was opened, for age-based penalties
+    pub fn order_opened(&mut self, order_id: impl Into<String>, symbol: impl Into<String>) {
+        self.order_opened_at(order_id, symbol, Instant::now());
+    }
+
+    /// Remember an order with an explicit open time
+    pub fn order_opened_at(&mut self, order_id: impl Into<String>, symbol: impl Into<String>, at: Instant) {
+        self.orders.insert(order_id.into(), (symbol.into(), at));
+    }
+
+    /// Forget an order that was filled or cancelled externally
+    pub fn order_closed(&mut self, order_id: &str) {
+        self.orders.remove(order_id);
+    }
+
+    /// Number of orders tracked for age penalties
+    pub fn tracked_orders(&self) -> usize {
+        self.orders.len()
+    }
+
+    /// Pair affected by an action and its cost, if the pair is known
+    pub fn cost(&self, action: &TradingAction) -> Option<(String, f64)> {
+        let now = Instant::now();
+        let order = |id: &str| self.orders.get(id).map(|(symbol, at)| (symbol.clone(), now.duration_since(*at)));
+        match action {
+            TradingAction::Add { symbol } => Some((symbol.clone(), 1.0)),
+            TradingAction::Amend { order_id } => order(order_id).map(|(symbol, _)| (symbol, 1.0)),
+            TradingAction::Edit { order_id } => {
+                order(order_id).map(|(symbol, age)| (symbol, 1.0 + edit_penalty(age)))
+            }
+            TradingAction::Cancel { order_id } => {
+                order(order_id).map(|(symbol, age)| (symbol, cancel_penalty(age)))
+            }
+        }
+    }
+
+    /// Current counter value for a pair
+    pub fn counter(&self, symbol: &str) -> f64 {
+        self.projected(symbol, Duration::ZERO)
+    }
+
+    /// Counter value for a pair after `after` more decay, with no new actions
+    pub fn projected(&self, symbol: &str, after: Duration) -> f64 {
+        self.counters.get(symbol).map_or(0.0, |c| {
+            let elapsed = c.updated.elapsed() + after;
+            (c.value - elapsed.as_secs_f64() * self.config.decay_per_sec).max(0.0)
+        })
+    }
+
+    /// Time until the pair's counter decays to zero
+    pub fn time_to_zero(&self, symbol: &str) -> Duration {
+        if self.config.decay_per_sec <= 0.0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(self.counter(symbol) / self.config.decay_per_sec)
+    }
+
+    /// Record an action if it fits under the limit
+    ///
+    /// Returns the time to wait before the action would fit otherwise.
+    /// Actions whose pair is unknown are always allowed.
+    pub fn try_record(&mut self, action: &TradingAction) -> Result<(), Duration> {
+        let Some((symbol, cost)) = self.cost(action) else {
+            return Ok(());
+        };
+        let current = self.counter(&symbol);
+        let excess = current + cost - self.config.max_counter;
+        if excess > 0.0 {
+            if self.config.decay_per_sec <= 0.0 {
+                return Err(Duration::MAX);
+            }
+            return Err(Duration::from_secs_f64(excess / self.config.decay_per_sec));
+        }
+        self.counters.insert(
+            symbol,
+            PairCounter {
+                value: current + cost,
+                updated: Instant::now(),
+            },
+        );
+        if let TradingAction::Cancel { order_id } = action {
+            self.orders.remove(order_id);
+        }
+        Ok(())
+    }
+
+    /// Reset all counters (tracked orders are kept)
+    pub fn reset(&mut self) {
+        self.counters.clear();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -382,4 +646,42 @@ mod tests {
         assert!(!limited.is_allowed());
         assert_eq!(limited.wait_duration(), Some(Duration::from_secs(5)));
     }
+
+    #[test]
+    fn test_trading_counter_limits() {
+        let mut counter = TradingRateCounter::new(TradingLimitConfig::new(3.0, 1.0));
+        let add = TradingAction::Add { symbol: "BTC/USD".to_string() };
+
+        for _ in 0..3 {
+            assert!(counter.try_record(&add).is_ok());
+        }
+        let wait = counter.try_record(&add).unwrap_err();
+        assert!(wait > Duration::from_millis(900) && wait <= Duration::from_secs(1));
+        assert!(counter.counter("BTC/USD") > 2.9);
+        assert!(counter.projected("BTC/USD", Duration::from_secs(2)) < 1.1);
+
+        // Other pairs have their own counter
+        let eth = TradingAction::Add { symbol: "ETH/USD".to_string() };
+        assert!(counter.try_record(&eth).is_ok());
+    }
+
+    #[test]
+    fn test_cancel_penalty_by_order_age() {
+        assert_eq!(cancel_penalty(Duration::from_secs(1)), 8.0);
+        assert_eq!(cancel_penalty(Duration::from_secs(60)), 2.0);
+        assert_eq!(cancel_penalty(Duration::from_secs(600)), 0.0);
+        assert_eq!(edit_penalty(Duration::from_secs(12)), 4.0);
+
+        let mut counter = TradingRateCounter::for_tier(VerificationTier::Starter);
+        counter.order_opened("O1", "BTC/USD");
+        let cancel = TradingAction::Cancel { order_id: "O1".to_string() };
+        assert_eq!(counter.cost(&cancel), Some(("BTC/USD".to_string(), 8.0)));
+        counter.try_record(&cancel).unwrap();
+        assert!(counter.counter("BTC/USD") > 7.9);
+        assert_eq!(counter.tracked_orders(), 0);
+
+        // Unknown orders can't be attributed to a pair
+        assert_eq!(counter.cost(&cancel), None);
+        assert!(counter.try_record(&cancel).is_ok());
+    }
 }
@@ -1,11 +1,109 @@
 //! Trading pair symbols (BTC/USD format)
+//!
+//! [`Symbol`] and [`Asset`] are validated when parsed with [`FromStr`] or
+//! deserialized. The `From<&str>`/`From<String>` conversions stay infallible
+//! for compatibility and do not validate; use [`Symbol::is_valid`] to check
+//! a symbol built that way.
 
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::borrow::Borrow;
 use std::fmt;
 use std::str::FromStr;
 
+/// Asset code (e.g., "BTC", "USD")
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(transparent)]
+pub struct Asset(String);
+
+impl Asset {
+    /// Bitcoin
+    pub const BTC: &'static str = "BTC";
+    /// Ether
+    pub const ETH: &'static str = "ETH";
+    /// US dollar
+    pub const USD: &'static str = "USD";
+    /// Euro
+    pub const EUR: &'static str = "EUR";
+    /// Tether
+    pub const USDT: &'static str = "USDT";
+
+    /// Create a new asset from a string without validation
+    pub fn new(s: impl Into<String>) -> Self {
+        Self(s.into())
+    }
+
+    /// Get the asset code as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Consume the asset and return the inner string
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// Check if a string is a well-formed asset code
+    ///
+    /// Asset codes are non-empty and made of ASCII letters, digits and `.`
+    /// (e.g. "BTC", "1INCH", "ETH2.S").
+    pub fn is_valid_code(s: &str) -> bool {
+        !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '.')
+    }
+}
+
+impl FromStr for Asset {
+    type Err = SymbolParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(SymbolParseError::EmptyPart(s.to_string()));
+        }
+        if !Self::is_valid_code(s) {
+            return Err(SymbolParseError::InvalidCharacter(s.to_string()));
+        }
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl<'de> Deserialize<'de> for Asset {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl fmt::Display for Asset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl AsRef<str> for Asset {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&str> for Asset {
+    fn from(s: &str) -> Self {
+        Self(s.to_string())
+    }
+}
+
+impl From<String> for Asset {
+    fn from(s: String) -> Self {
+        Self(s)
+    }
+}
+
+impl From<Asset> for String {
+    fn from(asset: Asset) -> Self {
+        asset.0
+    }
+}
+
 /// Trading pair symbol (BTC/USD format - V2 API uses BTC, not XBT!)
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
 #[serde(transparent)]
 pub struct Symbol(String);
 
@@ -26,11 +124,26 @@ impl Symbol {
         Self(s.into())
     }
 
+    /// Create a symbol from its base and quote assets
+    pub fn from_parts(base: impl Into<Asset>, quote: impl Into<Asset>) -> Self {
+        Self(format!("{}/{}", base.into(), quote.into()))
+    }
+
     /// Get the symbol as a string slice
     pub fn as_str(&self) -> &str {
         &self.0
     }
 
+    /// Consume the symbol and return the inner string
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// Check if the symbol is a well-formed BASE/QUOTE pair
+    pub fn is_valid(&self) -> bool {
+        Self::from_str(&self.0).is_ok()
+    }
+
     /// Get the base currency (e.g., "BTC" from "BTC/USD")
     pub fn base(&self) -> Option<&str> {
         self.0.split('/').next()
@@ -40,6 +153,16 @@ impl Symbol {
     pub fn quote(&self) -> Option<&str> {
         self.0.split('/').nth(1)
     }
+
+    /// Get the base asset, if present
+    pub fn base_asset(&self) -> Option<Asset> {
+        self.base().filter(|s| !s.is_empty()).map(Asset::from)
+    }
+
+    /// Get the quote asset, if present
+    pub fn quote_asset(&self) -> Option<Asset> {
+        self.quote().filter(|s| !s.is_empty()).map(Asset::from)
+    }
 }
 
 impl FromStr for Symbol {
@@ -60,10 +183,21 @@ impl FromStr for Symbol {
             return Err(SymbolParseError::EmptyPart(s.to_string()));
         }
 
+        if !Asset::is_valid_code(parts[0]) || !Asset::is_valid_code(parts[1]) {
+            return Err(SymbolParseError::InvalidCharacter(s.to_string()));
+        }
+
         Ok(Self(s.to_string()))
     }
 }
 
+impl<'de> Deserialize<'de> for Symbol {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl fmt::Display for Symbol {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -88,17 +222,60 @@ impl From<String> for Symbol {
     }
 }
 
-/// Error parsing a symbol
-#[derive(Debug, Clone, thiserror::Error)]
+impl From<&String> for Symbol {
+    fn from(s: &String) -> Self {
+        Self(s.clone())
+    }
+}
+
+impl From<&Symbol> for Symbol {
+    fn from(s: &Symbol) -> Self {
+        s.clone()
+    }
+}
+
+impl From<Symbol> for String {
+    fn from(symbol: Symbol) -> Self {
+        symbol.0
+    }
+}
+
+impl Borrow<str> for Symbol {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl PartialEq<str> for Symbol {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for Symbol {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+/// Error parsing a symbol or asset
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
 pub enum SymbolParseError {
+    /// No '/' between base and quote
     #[error("Symbol must contain '/': {0}")]
     MissingSlash(String),
 
+    /// More than one '/'
     #[error("Invalid symbol format: {0}")]
     InvalidFormat(String),
 
+    /// Base, quote or asset code is empty
     #[error("Symbol has empty base or quote: {0}")]
     EmptyPart(String),
+
+    /// Asset code contains characters other than ASCII letters, digits or '.'
+    #[error("Invalid character in symbol: {0}")]
+    InvalidCharacter(String),
 }
 
 #[cfg(test)]
@@ -118,6 +295,27 @@ mod tests {
         assert!("BTCUSD".parse::<Symbol>().is_err());
         assert!("/USD".parse::<Symbol>().is_err());
         assert!("BTC/".parse::<Symbol>().is_err());
+        assert_eq!(
+            "BTC/U SD".parse::<Symbol>().unwrap_err(),
+            SymbolParseError::InvalidCharacter("BTC/U SD".into())
+        );
+        assert!(!Symbol::from("BTC/USD/EUR").is_valid());
+    }
+
+    #[test]
+    fn test_symbol_from_parts() {
+        let symbol = Symbol::from_parts("ETH", Asset::new("EUR"));
+        assert_eq!(symbol, "ETH/EUR");
+        assert_eq!(symbol.base_asset(), Some(Asset::new("ETH")));
+        assert_eq!(symbol.quote_asset().unwrap().as_str(), Asset::EUR);
+        assert!(symbol.is_valid());
+    }
+
+    #[test]
+    fn test_asset_parse() {
+        assert_eq!("1INCH".parse::<Asset>().unwrap().as_str(), "1INCH");
+        assert!("".parse::<Asset>().is_err());
+        assert!("BTC/USD".parse::<Asset>().is_err());
     }
 
     #[test]
@@ -128,5 +326,8 @@ mod tests {
 
         let parsed: Symbol = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed, symbol);
+
+        assert!(serde_json::from_str::<Symbol>("\"ETHUSD\"").is_err());
+        assert!(serde_json::from_str::<Asset>("\"ETH\"").is_ok());
     }
 }
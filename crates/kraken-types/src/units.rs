@@ -0,0 +1,263 @@
+//! Unit-safe newtypes for prices, quantities and notional values
+//!
+//! Bare [`Decimal`]s make it easy to pass a quantity where a price is
+//! expected. [`Price`] and [`Qty`] only combine in ways that make sense:
+//! `Price * Qty` is a [`Notional`], `Notional / Qty` is a [`Price`], and
+//! adding a price to a quantity does not compile.
+//!
+//! Both convert to and from [`Decimal`] with `From`/`Into`, so existing code
+//! keeps working while it migrates; the raw value is also available as the
+//! public `.0` field or [`Price::value`].
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_types::{Notional, Price, Qty};
+//! use rust_decimal_macros::dec;
+//!
+//! let fills = [(Price::new(dec!(100)), Qty::new(dec!(2))), (Price::new(dec!(103)), Qty::new(dec!(1)))];
+//! let notional: Notional = fills.iter().map(|(price, qty)| *price * *qty).sum();
+//! let qty: Qty = fills.iter().map(|(_, qty)| *qty).sum();
+//!
+//! assert_eq!(notional, Notional::new(dec!(303)));
+//! assert_eq!(notional / qty, Price::new(dec!(101)));
+//! ```
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+macro_rules! decimal_unit {
+    ($(#[$meta:meta])* $name:ident) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+        #[serde(transparent)]
+        pub struct $name(pub Decimal);
+
+        impl $name {
+            /// Zero
+            pub const ZERO: Self = Self(Decimal::ZERO);
+
+            /// Wrap a raw decimal
+            pub const fn new(value: Decimal) -> Self {
+                Self(value)
+            }
+
+            /// The raw decimal
+            pub const fn value(self) -> Decimal {
+                self.0
+            }
+
+            /// Check if the value is zero
+            pub fn is_zero(self) -> bool {
+                self.0.is_zero()
+            }
+
+            /// Check if the value is below zero
+            pub fn is_sign_negative(self) -> bool {
+                self.0.is_sign_negative()
+            }
+
+            /// Absolute value
+            pub fn abs(self) -> Self {
+                Self(self.0.abs())
+            }
+
+            /// Round to `dp` decimal places
+            pub fn round_dp(self, dp: u32) -> Self {
+                Self(self.0.round_dp(dp))
+            }
+
+            /// Convert to f64 (lossy, for display and interop)
+            pub fn to_f64(self) -> Option<f64> {
+                rust_decimal::prelude::ToPrimitive::to_f64(&self.0)
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                crate::level::deserialize_decimal(deserializer).map(Self)
+            }
+        }
+
+        impl From<Decimal> for $name {
+            fn from(value: Decimal) -> Self {
+                Self(value)
+            }
+        }
+
+        impl From<$name> for Decimal {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl PartialEq<Decimal> for $name {
+            fn eq(&self, other: &Decimal) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl PartialOrd<Decimal> for $name {
+            fn partial_cmp(&self, other: &Decimal) -> Option<std::cmp::Ordering> {
+                self.0.partial_cmp(other)
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, rhs: Self) {
+                self.0 += rhs.0;
+            }
+        }
+
+        impl SubAssign for $name {
+            fn sub_assign(&mut self, rhs: Self) {
+                self.0 -= rhs.0;
+            }
+        }
+
+        impl Neg for $name {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        /// Scale by a dimensionless factor
+        impl Mul<Decimal> for $name {
+            type Output = Self;
+            fn mul(self, rhs: Decimal) -> Self {
+                Self(self.0 * rhs)
+            }
+        }
+
+        /// Divide by a dimensionless factor
+        impl Div<Decimal> for $name {
+            type Output = Self;
+            fn div(self, rhs: Decimal) -> Self {
+                Self(self.0 / rhs)
+            }
+        }
+
+        /// Ratio of two values in the same unit
+        impl Div for $name {
+            type Output = Decimal;
+            fn div(self, rhs: Self) -> Decimal {
+                self.0 / rhs.0
+            }
+        }
+
+        impl Sum for $name {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                Self(iter.map(|v| v.0).sum())
+            }
+        }
+
+        impl<'a> Sum<&'a $name> for $name {
+            fn sum<I: Iterator<Item = &'a Self>>(iter: I) -> Self {
+                Self(iter.map(|v| v.0).sum())
+            }
+        }
+    };
+}
+
+decimal_unit!(
+    /// A price in quote currency per unit of base
+    Price
+);
+
+decimal_unit!(
+    /// A quantity in base currency
+    Qty
+);
+
+decimal_unit!(
+    /// A notional value in quote currency (price times quantity)
+    Notional
+);
+
+impl Mul<Qty> for Price {
+    type Output = Notional;
+    fn mul(self, rhs: Qty) -> Notional {
+        Notional(self.0 * rhs.0)
+    }
+}
+
+impl Mul<Price> for Qty {
+    type Output = Notional;
+    fn mul(self, rhs: Price) -> Notional {
+        Notional(self.0 * rhs.0)
+    }
+}
+
+/// Average price of a notional spread over a quantity
+impl Div<Qty> for Notional {
+    type Output = Price;
+    fn div(self, rhs: Qty) -> Price {
+        Price(self.0 / rhs.0)
+    }
+}
+
+/// Quantity a notional buys at a price
+impl Div<Price> for Notional {
+    type Output = Qty;
+    fn div(self, rhs: Price) -> Qty {
+        Qty(self.0 / rhs.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_unit_arithmetic() {
+        let price = Price::new(dec!(50000));
+        let qty = Qty::new(dec!(0.5));
+
+        assert_eq!(price * qty, Notional::new(dec!(25000)));
+        assert_eq!(qty * price, price * qty);
+        assert_eq!(Notional::new(dec!(25000)) / price, qty);
+        assert_eq!(Price::new(dec!(50010)) - price, Price::new(dec!(10)));
+        assert_eq!(price / dec!(2), Price::new(dec!(25000)));
+        assert_eq!(Price::new(dec!(50010)) / price, dec!(1.0002));
+    }
+
+    #[test]
+    fn test_decimal_interop_and_serde() {
+        let price: Price = dec!(88813.5).into();
+        let raw: Decimal = price.into();
+        assert_eq!(raw, dec!(88813.5));
+        assert!(price > dec!(88000));
+
+        // Numbers and strings both parse without going through f64
+        let parsed: Qty = serde_json::from_str("0.00460208").unwrap();
+        assert_eq!(parsed.to_string(), "0.00460208");
+        let parsed: Qty = serde_json::from_str("\"0.00460208\"").unwrap();
+        assert_eq!(parsed, dec!(0.00460208));
+        assert_eq!(serde_json::to_string(&price).unwrap(), serde_json::to_string(&raw).unwrap());
+    }
+}
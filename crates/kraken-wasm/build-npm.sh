@@ -0,0 +1,25 @@
+#!/usr/bin/env bash
+# Build the dual ESM/CommonJS npm package into pkg/
+#
+#   pkg/web/   wasm-pack --target web     (ESM; call `await init()` first)
+#   pkg/node/  wasm-pack --target nodejs  (CommonJS; initialized on require)
+#
+# pkg/package.json routes `import` to the web build and `require` / Node.js
+# (including Node ESM, which picks up the CommonJS named exports) to the
+# node build. Publish with `npm publish pkg/`.
+set -euo pipefail
+
+cd "$(dirname "$0")"
+
+rm -rf pkg
+wasm-pack build --release --target web --out-dir pkg/web --out-name kraken_wasm "$@"
+wasm-pack build --release --target nodejs --out-dir pkg/node --out-name kraken_wasm "$@"
+
+# Each wasm-pack build writes its own manifest; only the combined one ships
+rm -f pkg/web/package.json pkg/node/package.json pkg/web/.gitignore pkg/node/.gitignore
+
+version=$(grep -m1 '^version' ../../Cargo.toml | cut -d '"' -f 2)
+sed "s/\"version\": \"0.0.0\"/\"version\": \"${version}\"/" npm/package.json > pkg/package.json
+cp ../../LICENSE pkg/ 2>/dev/null || true
+
+echo "Built pkg/ (@havklo/kraken-wasm ${version})"
@@ -0,0 +1,35 @@
+// Keep a BTC/USD book in Node.js 18+ (or Electron's main process)
+//
+// Build the package first:  ./build-npm.sh
+// Then run:                 node examples/node/orderbook.mjs
+//
+// Node 22+ has a global WebSocket; on older versions use the `ws` package.
+
+import { WasmOrderbook, WasmRestClient, runtime } from '../../pkg/node/kraken_wasm.js';
+
+console.log('Running in:', runtime());
+
+const client = new WasmRestClient();
+const [ticker] = await client.get_tickers(['BTC/USD']);
+console.log(`REST ticker: bid ${ticker.bid} ask ${ticker.ask}`);
+
+const book = new WasmOrderbook('BTC/USD');
+const ws = new WebSocket('wss://ws.kraken.com/v2');
+
+ws.onopen = () => {
+    ws.send(JSON.stringify({
+        method: 'subscribe',
+        params: { channel: 'book', symbol: ['BTC/USD'], depth: 10 },
+    }));
+};
+
+ws.onmessage = (event) => {
+    try {
+        if (book.apply_message(event.data) !== 'ignored') {
+            console.log(`spread ${book.get_spread().toFixed(2)} mid ${book.get_mid_price().toFixed(2)}`);
+        }
+    } catch (e) {
+        console.error(`${e.code}: ${e.message}`);
+        if (e.category === 'book') ws.close();
+    }
+};
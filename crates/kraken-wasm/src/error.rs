@@ -0,0 +1,226 @@
+//! Structured errors thrown to JavaScript
+//!
+//! Every failing binding throws a plain object instead of a string:
+//!
+//! ```javascript
+//! try {
+//!     await client.get_balance();
+//! } catch (e) {
+//!     // { code: "EAPI:Invalid nonce", category: "auth", message: "...", retryable: false }
+//!     if (e.retryable) scheduleRetry();
+//!     else if (e.category === "auth") promptForKeys();
+//! }
+//! ```
+//!
+//! `code` is Kraken's own error code (e.g. `"EOrder:Insufficient funds"`)
+//! for errors returned by the API, and a snake_case local code (e.g.
+//! `"checksum_mismatch"`, `"http_error"`) otherwise.
+
+use kraken_book::ApplyError;
+use kraken_types::KrakenApiError;
+use serde::Serialize;
+use wasm_bindgen::JsValue;
+
+/// Broad kind of failure, for branching in JavaScript
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCategory {
+    /// Input couldn't be parsed (bad JSON, unexpected message shape)
+    Parse,
+    /// The orderbook rejected a message
+    Book,
+    /// An argument was invalid
+    InvalidInput,
+    /// The request never got a response
+    Network,
+    /// The server answered with a non-success HTTP status
+    Http,
+    /// Kraken returned an API error
+    Api,
+    /// Credentials are missing, malformed, or rejected
+    Auth,
+    /// A rate limit was hit
+    RateLimit,
+    /// Browser storage failed or held unreadable data
+    Storage,
+    /// Unexpected failure inside the bindings
+    Internal,
+}
+
+/// Error object thrown by the WASM bindings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WasmError {
+    /// Kraken error code, or a snake_case local code
+    pub code: String,
+    /// Broad kind of failure
+    pub category: ErrorCategory,
+    /// Human-readable description
+    pub message: String,
+    /// Whether the same call may succeed if retried later
+    pub retryable: bool,
+}
+
+impl WasmError {
+    /// Create an error
+    pub fn new(
+        category: ErrorCategory,
+        code: impl Into<String>,
+        message: impl Into<String>,
+        retryable: bool,
+    ) -> Self {
+        Self {
+            code: code.into(),
+            category,
+            message: message.into(),
+            retryable,
+        }
+    }
+
+    /// Input that couldn't be parsed
+    pub fn parse(message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Parse, "parse_error", message, false)
+    }
+
+    /// Invalid argument
+    pub fn invalid_input(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::InvalidInput, code, message, false)
+    }
+
+    /// Missing or malformed credentials
+    pub fn auth(code: impl Into<String>, message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Auth, code, message, false)
+    }
+
+    /// Request failed before a response arrived
+    pub fn network(message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Network, "network_error", message, true)
+    }
+
+    /// Non-success HTTP status
+    ///
+    /// 429 is reported as a rate limit; 429 and 5xx are retryable.
+    pub fn http(status: u16, status_text: &str) -> Self {
+        let message = format!("HTTP error: {} {}", status, status_text);
+        match status {
+            429 => Self::new(ErrorCategory::RateLimit, "http_429", message, true),
+            _ => Self::new(ErrorCategory::Http, "http_error", message, status >= 500),
+        }
+    }
+
+    /// Errors from a Kraken response's `error` array
+    ///
+    /// Classified by the first error; every message is kept. Auth errors
+    /// are never retryable, since the same keys will fail again.
+    pub fn api(errors: &[String]) -> Self {
+        let Some(first) = errors.first() else {
+            return Self::internal("Kraken reported an empty error list");
+        };
+        let parsed = KrakenApiError::parse(first);
+        let category = if parsed.is_rate_limit() {
+            ErrorCategory::RateLimit
+        } else if parsed.requires_reauth() || parsed.code.is_some_and(|code| code.is_auth_error()) {
+            ErrorCategory::Auth
+        } else {
+            ErrorCategory::Api
+        };
+        Self::new(
+            category,
+            api_code(first),
+            format!("Kraken API error: {}", errors.join(", ")),
+            category != ErrorCategory::Auth && parsed.is_retryable(),
+        )
+    }
+
+    /// Orderbook rejected a message
+    pub fn book(error: &ApplyError) -> Self {
+        Self::new(ErrorCategory::Book, error.kind(), error.to_string(), false)
+    }
+
+    /// Browser storage failure or unreadable stored data
+    pub fn storage(message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Storage, "storage_error", message, false)
+    }
+
+    /// Unexpected failure inside the bindings
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCategory::Internal, "internal_error", message, false)
+    }
+}
+
+impl std::fmt::Display for WasmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)
+    }
+}
+
+impl std::error::Error for WasmError {}
+
+impl From<WasmError> for JsValue {
+    fn from(error: WasmError) -> Self {
+        error
+            .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+            .unwrap_or_else(|_| JsValue::from_str(&error.to_string()))
+    }
+}
+
+/// Error code part of a Kraken error string
+///
+/// `"EGeneral:Invalid arguments:volume"` keeps `"EGeneral:Invalid arguments"`;
+/// the detail after the second colon stays in the message.
+fn api_code(error: &str) -> String {
+    let mut parts = error.splitn(3, ':');
+    match (parts.next(), parts.next()) {
+        (Some(prefix), Some(name)) => format!("{}:{}", prefix, name.trim()),
+        _ => error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_api_errors_are_classified() {
+        let error = WasmError::api(&["EAPI:Rate limit exceeded".to_string()]);
+        assert_eq!(error.category, ErrorCategory::RateLimit);
+        assert_eq!(error.code, "EAPI:Rate limit exceeded");
+        assert!(error.retryable);
+
+        let error = WasmError::api(&["EAPI:Invalid key".to_string()]);
+        assert_eq!(error.category, ErrorCategory::Auth);
+        assert!(!error.retryable);
+
+        let error = WasmError::api(&[
+            "EGeneral:Invalid arguments:volume".to_string(),
+            "EOrder:Insufficient funds".to_string(),
+        ]);
+        assert_eq!(error.category, ErrorCategory::Api);
+        assert_eq!(error.code, "EGeneral:Invalid arguments");
+        assert!(error.message.contains("Insufficient funds"));
+
+        assert_eq!(WasmError::api(&[]).category, ErrorCategory::Internal);
+    }
+
+    #[test]
+    fn test_http_status_retryability() {
+        assert!(WasmError::http(503, "Service Unavailable").retryable);
+        assert!(!WasmError::http(404, "Not Found").retryable);
+        let limited = WasmError::http(429, "Too Many Requests");
+        assert_eq!(limited.category, ErrorCategory::RateLimit);
+        assert!(limited.retryable);
+    }
+
+    #[test]
+    fn test_serializes_with_snake_case_category() {
+        let json = serde_json::to_value(WasmError::parse("bad json")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "code": "parse_error",
+                "category": "parse",
+                "message": "bad json",
+                "retryable": false,
+            })
+        );
+    }
+}
@@ -19,17 +19,38 @@
 //!         console.log('Spread:', book.get_spread());
 //!         console.log('Bids:', book.get_bids());
 //!     } catch (e) {
-//!         console.error('Orderbook error:', e);
+//!         // { code: "checksum_mismatch", category: "book", message, retryable }
+//!         console.error('Orderbook error:', e.code, e.message);
 //!     }
 //! };
 //! ```
-
-use kraken_book::{HistoryBuffer, Orderbook, OrderbookState, L3Book, L3Order, L3Side};
-use kraken_types::WsMessage;
+//!
+//! Failures throw a [`WasmError`] object rather than a string; see the
+//! [`error`] module.
+//!
+//! # Node.js
+//!
+//! The bindings only use globals available in browsers, workers and
+//! Node.js 18+ (see [`runtime`]). `build-npm.sh` packages a `web` (ESM) and
+//! a `nodejs` (CommonJS) build together; the Node build needs no `init()`.
+
+use kraken_book::memory::{enforce_book_limit, MemoryLimits};
+use kraken_book::{ApplyError, ApplyResult, HistoryBuffer, Orderbook, OrderbookState, L3Book, L3Order, L3Side};
+use kraken_types::formatting::format_decimal;
+use kraken_types::{BookData, WsMessage};
 use rust_decimal::prelude::ToPrimitive;
 use rust_decimal::Decimal;
 use wasm_bindgen::prelude::*;
 
+pub mod error;
+pub mod persistence;
+pub mod private;
+pub mod public;
+pub mod rate_limit;
+pub mod runtime;
+
+pub use error::{ErrorCategory, WasmError};
+
 /// Initialize panic hook for better error messages in browser console
 #[wasm_bindgen(start)]
 pub fn init() {
@@ -43,6 +64,31 @@ pub fn init() {
 pub struct WasmOrderbook {
     inner: Orderbook,
     history: Option<HistoryBuffer>,
+    /// Condition reported with the last applied message (crossed book, depth overflow)
+    last_warning: Option<String>,
+    /// Budget for the book and its history
+    memory_limits: MemoryLimits,
+}
+
+impl WasmOrderbook {
+    /// Apply book data, mapping [`ApplyError`] onto the JS conventions
+    ///
+    /// Updates before a snapshot are ignored, as they were before the error
+    /// existed. Conditions reported on an applied message are kept for
+    /// [`last_warning`](Self::last_warning). Anything else throws a
+    /// [`WasmError`] with category `"book"` and the error kind as its code.
+    fn apply_data(&mut self, data: &BookData, is_snapshot: bool) -> Result<ApplyResult, JsValue> {
+        self.last_warning = None;
+        match self.inner.apply_book_data(data, is_snapshot) {
+            Ok(result) => Ok(result),
+            Err(ApplyError::UpdateBeforeSnapshot { .. }) => Ok(ApplyResult::Ignored),
+            Err(e) if e.was_applied() => {
+                self.last_warning = Some(format!("{}: {}", e.kind(), e));
+                Ok(if is_snapshot { ApplyResult::Snapshot } else { ApplyResult::Update })
+            }
+            Err(e) => Err(WasmError::book(&e).into()),
+        }
+    }
 }
 
 #[wasm_bindgen]
@@ -56,6 +102,8 @@ impl WasmOrderbook {
         WasmOrderbook {
             inner: Orderbook::new(symbol),
             history: None,
+            last_warning: None,
+            memory_limits: MemoryLimits::new(),
         }
     }
 
@@ -69,35 +117,44 @@ impl WasmOrderbook {
         WasmOrderbook {
             inner: Orderbook::with_depth(symbol, depth),
             history: None,
+            last_warning: None,
+            memory_limits: MemoryLimits::new(),
         }
     }
 
+    /// Condition reported with the last applied message, if any
+    ///
+    /// Set when a crossed book or a snapshot deeper than the book's depth
+    /// was applied; formatted as `"<kind>: <message>"`.
+    #[wasm_bindgen]
+    pub fn last_warning(&self) -> Option<String> {
+        self.last_warning.clone()
+    }
+
     /// Apply a raw JSON message from the WebSocket
     ///
     /// Browser calls this with `event.data` from `ws.onmessage`.
     /// Returns the message type: "snapshot", "update", "ignored", or throws on error.
     #[wasm_bindgen]
     pub fn apply_message(&mut self, json: &str) -> Result<String, JsValue> {
-        let msg = WsMessage::parse(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let msg = WsMessage::parse(json).map_err(|e| WasmError::parse(e.to_string()))?;
 
         match msg {
             WsMessage::Book(book_msg) => {
                 if let Some(data) = book_msg.data.first() {
                     let is_snapshot = book_msg.msg_type == "snapshot";
-                    let result = self
-                        .inner
-                        .apply_book_data(data, is_snapshot)
-                        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+                    let result = self.apply_data(data, is_snapshot)?;
 
                     // Save to history if enabled
                     if let Some(history) = &mut self.history {
                         history.push(self.inner.snapshot());
                     }
+                    enforce_book_limit(&mut self.inner, self.history.as_mut(), &self.memory_limits);
 
                     match result {
-                        kraken_book::ApplyResult::Snapshot => Ok("snapshot".to_string()),
-                        kraken_book::ApplyResult::Update => Ok("update".to_string()),
-                        kraken_book::ApplyResult::Ignored => Ok("ignored".to_string()),
+                        ApplyResult::Snapshot => Ok("snapshot".to_string()),
+                        ApplyResult::Update => Ok("update".to_string()),
+                        ApplyResult::Ignored => Ok("ignored".to_string()),
                     }
                 } else {
                     Ok("ignored".to_string())
@@ -126,22 +183,19 @@ impl WasmOrderbook {
     /// ```
     #[wasm_bindgen]
     pub fn apply_and_get(&mut self, json: &str, depth: u32) -> Result<JsValue, JsValue> {
-        let msg = WsMessage::parse(json).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let msg = WsMessage::parse(json).map_err(|e| WasmError::parse(e.to_string()))?;
 
         let msg_type = match msg {
             WsMessage::Book(book_msg) => {
                 if let Some(data) = book_msg.data.first() {
                     let is_snapshot = book_msg.msg_type == "snapshot";
-                    let result = self
-                        .inner
-                        .apply_book_data(data, is_snapshot)
-                        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+                    let result = self.apply_data(data, is_snapshot)?;
 
                     // Skip history to avoid extra iteration
                     match result {
-                        kraken_book::ApplyResult::Snapshot => "snapshot",
-                        kraken_book::ApplyResult::Update => "update",
-                        kraken_book::ApplyResult::Ignored => "ignored",
+                        ApplyResult::Snapshot => "snapshot",
+                        ApplyResult::Update => "update",
+                        ApplyResult::Ignored => "ignored",
                     }
                 } else {
                     "ignored"
@@ -161,7 +215,7 @@ impl WasmOrderbook {
                 spread: 0.0,
                 mid_price: 0.0,
             };
-            return serde_wasm_bindgen::to_value(&response).map_err(|e| JsValue::from_str(&e.to_string()));
+            return serde_wasm_bindgen::to_value(&response).map_err(|e| WasmError::internal(e.to_string()).into());
         }
 
         // Safely collect data - use bids_vec/asks_vec which clone
@@ -191,7 +245,7 @@ impl WasmOrderbook {
             mid_price: if best_bid > 0.0 && best_ask > 0.0 { (best_ask + best_bid) / 2.0 } else { 0.0 },
         };
 
-        serde_wasm_bindgen::to_value(&response).map_err(|e| JsValue::from_str(&e.to_string()))
+        serde_wasm_bindgen::to_value(&response).map_err(|e| WasmError::internal(e.to_string()).into())
     }
 
     /// Get the trading pair symbol
@@ -358,6 +412,23 @@ impl WasmOrderbook {
         self.inner.set_precision(price_precision, qty_precision);
     }
 
+    /// Price as a string with the pair's price decimals, e.g. `"42000.0"`
+    ///
+    /// Uses the same rounding as the checksum, so displayed levels match
+    /// what Kraken publishes.
+    #[wasm_bindgen]
+    pub fn format_price(&self, price: f64) -> String {
+        let price = Decimal::try_from(price).unwrap_or(Decimal::ZERO);
+        format_decimal(price, self.inner.price_precision())
+    }
+
+    /// Quantity as a string with the pair's quantity decimals
+    #[wasm_bindgen]
+    pub fn format_qty(&self, qty: f64) -> String {
+        let qty = Decimal::try_from(qty).unwrap_or(Decimal::ZERO);
+        format_decimal(qty, self.inner.qty_precision())
+    }
+
     // ========== History/Time-Travel Features ==========
 
     /// Enable history tracking for time-travel feature
@@ -369,6 +440,23 @@ impl WasmOrderbook {
         self.history = Some(HistoryBuffer::new(max_snapshots as usize));
     }
 
+    /// Limit the approximate bytes held by the book and its history
+    ///
+    /// Over budget, the oldest history snapshots are dropped first, then the
+    /// book's deepest levels. Pass 0 to remove the limit.
+    #[wasm_bindgen]
+    pub fn set_memory_limit(&mut self, bytes: u32) {
+        self.memory_limits.per_book_bytes = (bytes > 0).then_some(bytes as usize);
+        enforce_book_limit(&mut self.inner, self.history.as_mut(), &self.memory_limits);
+    }
+
+    /// Approximate bytes held by the book and its history
+    #[wasm_bindgen]
+    pub fn approx_memory_bytes(&self) -> u32 {
+        let history = self.history.as_ref().map_or(0, |h| h.approx_bytes());
+        (self.inner.approx_bytes() + history) as u32
+    }
+
     /// Disable history tracking
     #[wasm_bindgen]
     pub fn disable_history(&mut self) {
@@ -439,6 +527,27 @@ impl WasmOrderbook {
             history.clear();
         }
     }
+
+    /// Restore book state and history loaded from a `WasmIndexedDbStore`
+    ///
+    /// The saved levels are shown until the next live snapshot replaces
+    /// them; history is restored if it was enabled when saved.
+    #[wasm_bindgen]
+    pub fn restore(&mut self, saved: &WasmPersistedBook) -> Result<(), JsValue> {
+        let manifest = &saved.inner.manifest;
+        if manifest.symbol != self.inner.symbol() {
+            return Err(WasmError::invalid_input(
+                "symbol_mismatch",
+                format!("Saved book is for {}, not {}", manifest.symbol, self.inner.symbol()),
+            )
+            .into());
+        }
+        self.inner.restore_snapshot(&manifest.book);
+        if let Some(history) = saved.inner.history_buffer() {
+            self.history = Some(history);
+        }
+        Ok(())
+    }
 }
 
 /// JavaScript-friendly price level
@@ -469,6 +578,146 @@ struct JsSnapshot {
     checksum: u32,
 }
 
+// ============================================================================
+// Heatmap WASM Bindings
+// ============================================================================
+
+use kraken_book::heatmap::{HeatmapBuilder, Normalization};
+
+/// Liquidity heatmap sampled from a `WasmOrderbook`
+///
+/// ```javascript
+/// const heatmap = new WasmHeatmap('BTC/USD', 5.0);
+/// heatmap.set_normalization('log');
+/// ws.onmessage = (event) => {
+///     book.apply_message(event.data);
+///     heatmap.sample(book, Date.now()); // at most one column per interval
+/// };
+/// const { rows, price_min, bucket_size, timestamps, bids, asks } = heatmap.to_object();
+/// ```
+#[wasm_bindgen]
+pub struct WasmHeatmap {
+    inner: HeatmapBuilder,
+}
+
+impl WasmHeatmap {
+    fn update(&mut self, f: impl FnOnce(HeatmapBuilder) -> HeatmapBuilder) {
+        let symbol = self.inner.symbol().to_string();
+        let inner = std::mem::replace(&mut self.inner, HeatmapBuilder::new(symbol, Decimal::ONE));
+        self.inner = f(inner);
+    }
+}
+
+#[wasm_bindgen]
+impl WasmHeatmap {
+    /// Create a heatmap bucketing prices into steps of `bucket_size`
+    ///
+    /// Samples once per second and keeps 600 columns and 1000 rows by default.
+    #[wasm_bindgen(constructor)]
+    pub fn new(symbol: &str, bucket_size: f64) -> Result<WasmHeatmap, JsValue> {
+        let bucket_size = Decimal::try_from(bucket_size)
+            .ok()
+            .filter(|size| *size > Decimal::ZERO)
+            .ok_or_else(|| WasmError::invalid_input("invalid_bucket_size", "bucket_size must be positive"))?;
+        Ok(WasmHeatmap {
+            inner: HeatmapBuilder::new(symbol, bucket_size),
+        })
+    }
+
+    /// Set the sampling cadence in milliseconds
+    #[wasm_bindgen]
+    pub fn set_interval_ms(&mut self, interval_ms: f64) {
+        self.update(|inner| inner.with_interval_ms(interval_ms.max(0.0) as u64));
+    }
+
+    /// Set how many columns are kept
+    #[wasm_bindgen]
+    pub fn set_max_columns(&mut self, max_columns: u32) {
+        self.update(|inner| inner.with_max_columns(max_columns as usize));
+    }
+
+    /// Set the most price rows a matrix may have, centred on the mid price
+    #[wasm_bindgen]
+    pub fn set_max_rows(&mut self, max_rows: u32) {
+        self.update(|inner| inner.with_max_rows(max_rows as usize));
+    }
+
+    /// Fade older columns by `factor` per column (1.0 disables decay)
+    #[wasm_bindgen]
+    pub fn set_decay(&mut self, factor: f64) {
+        self.update(|inner| inner.with_decay(factor));
+    }
+
+    /// Set value scaling: "none", "global", "per_column" or "log"
+    #[wasm_bindgen]
+    pub fn set_normalization(&mut self, normalization: &str) -> Result<(), JsValue> {
+        let normalization = match normalization {
+            "none" => Normalization::None,
+            "global" => Normalization::Global,
+            "per_column" => Normalization::PerColumn,
+            "log" => Normalization::Log,
+            other => {
+                return Err(WasmError::invalid_input(
+                    "invalid_normalization",
+                    format!("Unknown normalization: {}", other),
+                )
+                .into())
+            }
+        };
+        self.update(|inner| inner.with_normalization(normalization));
+        Ok(())
+    }
+
+    /// Sample the book if a column is due; returns true if one was added
+    #[wasm_bindgen]
+    pub fn sample(&mut self, book: &WasmOrderbook, timestamp_ms: f64) -> bool {
+        self.inner.sample(&book.inner.snapshot(), timestamp_ms.max(0.0) as u64)
+    }
+
+    /// Number of columns kept
+    #[wasm_bindgen]
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns true if nothing has been sampled
+    #[wasm_bindgen]
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Drop every column
+    #[wasm_bindgen]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Matrix as `{ symbol, bucket_size, price_min, rows, timestamps, bids, asks }`
+    ///
+    /// `bids`/`asks` are column-major: cell (column, row) is at
+    /// `column * rows + row`.
+    #[wasm_bindgen]
+    pub fn to_object(&self) -> Result<JsValue, JsValue> {
+        to_js(&self.inner.build())
+    }
+
+    /// Matrix limited to prices in `low..=high`
+    #[wasm_bindgen]
+    pub fn to_object_range(&self, low: f64, high: f64) -> Result<JsValue, JsValue> {
+        let bound = |price: f64| {
+            Decimal::try_from(price)
+                .map_err(|_| WasmError::invalid_input("invalid_price", format!("Invalid price: {}", price)))
+        };
+        to_js(&self.inner.build_range(bound(low)?, bound(high)?))
+    }
+
+    /// Matrix in the compact binary format (a `Uint8Array`)
+    #[wasm_bindgen]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.build().to_bytes()
+    }
+}
+
 // ============================================================================
 // L3 Orderbook WASM Bindings
 // ============================================================================
@@ -879,7 +1128,7 @@ impl WasmL3Book {
     #[wasm_bindgen]
     pub fn get_vwap_ask(&self, qty: f64) -> f64 {
         self.inner
-            .vwap_ask(Decimal::try_from(qty).unwrap_or(Decimal::ZERO))
+            .ask_vwap(Decimal::try_from(qty).unwrap_or(Decimal::ZERO))
             .and_then(|d| d.to_f64())
             .unwrap_or(0.0)
     }
@@ -890,7 +1139,7 @@ impl WasmL3Book {
     #[wasm_bindgen]
     pub fn get_vwap_bid(&self, qty: f64) -> f64 {
         self.inner
-            .vwap_bid(Decimal::try_from(qty).unwrap_or(Decimal::ZERO))
+            .bid_vwap(Decimal::try_from(qty).unwrap_or(Decimal::ZERO))
             .and_then(|d| d.to_f64())
             .unwrap_or(0.0)
     }
@@ -1029,7 +1278,10 @@ use web_sys::{Request, RequestInit, RequestMode, Response};
 /// WASM-compatible REST client for Kraken public endpoints
 ///
 /// Uses the browser's fetch API to make HTTP requests to Kraken's REST API.
-/// Only public endpoints are supported (no authentication required).
+/// Public endpoints need no authentication. Private endpoints are only
+/// available on a client created with `with_credentials_unsafe`, which
+/// requires acknowledging that API keys in a browser are exposed to every
+/// script on the page; use it for internal dashboards only.
 ///
 /// # Usage (JavaScript)
 ///
@@ -1047,10 +1299,17 @@ use web_sys::{Request, RequestInit, RequestMode, Response};
 /// // Get orderbook
 /// const book = await client.get_orderbook('ETHUSD', 10);
 /// console.log('ETH bids:', book.XETHZUSD.bids);
+///
+/// // Private endpoints (internal dashboards only)
+/// const priv = WasmRestClient.with_credentials_unsafe(
+///     apiKey, apiSecret, 'I understand the risks of keys in the browser');
+/// const balances = await priv.get_balance();      // [{ asset, balance }]
+/// const orders = await priv.get_open_orders();    // [{ txid, pair, side, ... }]
 /// ```
 #[wasm_bindgen]
 pub struct WasmRestClient {
     base_url: String,
+    credentials: Option<RefCell<private::PrivateCredentials>>,
 }
 
 #[wasm_bindgen]
@@ -1060,6 +1319,7 @@ impl WasmRestClient {
     pub fn new() -> WasmRestClient {
         WasmRestClient {
             base_url: "https://api.kraken.com".to_string(),
+            credentials: None,
         }
     }
 
@@ -1068,7 +1328,46 @@ impl WasmRestClient {
     pub fn with_base_url(base_url: &str) -> WasmRestClient {
         WasmRestClient {
             base_url: base_url.to_string(),
+            credentials: None,
+        }
+    }
+
+    /// Create a REST client that can call private endpoints
+    ///
+    /// Keys in the browser are readable by any script on the page and by
+    /// extensions. `acknowledgement` must be exactly
+    /// `"I understand the risks of keys in the browser"`.
+    ///
+    /// # Arguments
+    /// * `api_key` - Kraken API key
+    /// * `api_secret` - Base64 API secret
+    /// * `acknowledgement` - The risk acknowledgement phrase
+    #[wasm_bindgen]
+    pub fn with_credentials_unsafe(
+        api_key: &str,
+        api_secret: &str,
+        acknowledgement: &str,
+    ) -> Result<WasmRestClient, JsValue> {
+        if acknowledgement != private::BROWSER_KEY_ACKNOWLEDGEMENT {
+            return Err(WasmError::auth(
+                "acknowledgement_required",
+                format!(
+                    "Private endpoints require the acknowledgement \"{}\"",
+                    private::BROWSER_KEY_ACKNOWLEDGEMENT
+                ),
+            )
+            .into());
         }
+        let credentials = private::PrivateCredentials::new(api_key, api_secret)?;
+        let mut client = WasmRestClient::new();
+        client.credentials = Some(RefCell::new(credentials));
+        Ok(client)
+    }
+
+    /// Returns true if private endpoints are available
+    #[wasm_bindgen]
+    pub fn has_credentials(&self) -> bool {
+        self.credentials.is_some()
     }
 
     /// Get the base URL
@@ -1131,7 +1430,9 @@ impl WasmRestClient {
         self.fetch_public(&url).await
     }
 
-    /// Get ticker information
+    /// Get ticker information as Kraken's raw JSON
+    ///
+    /// See [`get_tickers`](Self::get_tickers) for normalized objects.
     ///
     /// # Arguments
     /// * `pair` - Trading pair(s), comma-separated (e.g., "XBTUSD" or "XBTUSD,ETHUSD")
@@ -1141,7 +1442,9 @@ impl WasmRestClient {
         self.fetch_public(&url).await
     }
 
-    /// Get OHLC data
+    /// Get OHLC data as Kraken's raw JSON
+    ///
+    /// See [`get_candles`](Self::get_candles) for normalized objects.
     ///
     /// # Arguments
     /// * `pair` - Trading pair
@@ -1156,7 +1459,9 @@ impl WasmRestClient {
         self.fetch_public(&url).await
     }
 
-    /// Get orderbook
+    /// Get orderbook as Kraken's raw JSON
+    ///
+    /// See [`get_book`](Self::get_book) for normalized objects.
     ///
     /// # Arguments
     /// * `pair` - Trading pair
@@ -1202,10 +1507,168 @@ impl WasmRestClient {
         self.fetch_public(&url).await
     }
 
+    // ========== Typed Public Endpoints ==========
+
+    /// Get tickers as plain objects with numeric fields
+    ///
+    /// Unlike [`get_ticker`](Self::get_ticker), results are keyed by the
+    /// symbols passed in rather than Kraken's legacy pair names. Returns an
+    /// array of `{ symbol, pair, bid, bid_qty, ask, ask_qty, last, last_qty,
+    /// open, high, low, volume, vwap, trades }` in the order requested.
+    /// Throws a `missing_symbols` error naming any symbol Kraken didn't
+    /// answer for.
+    ///
+    /// # Arguments
+    /// * `symbols` - Symbols (e.g., `["BTC/USD", "ETH/USD"]`) or REST pair names
+    #[wasm_bindgen]
+    pub async fn get_tickers(&self, symbols: Vec<String>) -> Result<JsValue, JsValue> {
+        let pairs: Vec<String> = symbols.iter().map(|s| public::rest_pair_name(s)).collect();
+        let url = format!("/0/public/Ticker?pair={}", pairs.join(","));
+        let result = self.fetch_public_value(&url).await?;
+        to_js(&public::parse_tickers(&result, &symbols)?)
+    }
+
+    /// Get an orderbook as `{ symbol, pair, bids, asks }`
+    ///
+    /// Levels are `{ price, qty, timestamp }` numbers, best first.
+    ///
+    /// # Arguments
+    /// * `symbol` - Symbol (e.g., "BTC/USD") or REST pair name
+    /// * `count` - Maximum number of bids/asks (1-500)
+    #[wasm_bindgen]
+    pub async fn get_book(&self, symbol: &str, count: Option<u16>) -> Result<JsValue, JsValue> {
+        let mut url = format!("/0/public/Depth?pair={}", public::rest_pair_name(symbol));
+        if let Some(c) = count {
+            url.push_str(&format!("&count={}", c));
+        }
+        let result = self.fetch_public_value(&url).await?;
+        to_js(&public::parse_book(&result, symbol)?)
+    }
+
+    /// Get candles as `{ symbol, pair, candles, last }`
+    ///
+    /// Candles are `{ time, open, high, low, close, vwap, volume, trades }`
+    /// numbers, oldest first; pass `last` as `since` to poll for newer ones.
+    ///
+    /// # Arguments
+    /// * `symbol` - Symbol (e.g., "BTC/USD") or REST pair name
+    /// * `interval` - Time interval in minutes
+    /// * `since` - Optional Unix timestamp to get data since
+    #[wasm_bindgen]
+    pub async fn get_candles(&self, symbol: &str, interval: u32, since: Option<u64>) -> Result<JsValue, JsValue> {
+        let mut url = format!("/0/public/OHLC?pair={}&interval={}", public::rest_pair_name(symbol), interval);
+        if let Some(s) = since {
+            url.push_str(&format!("&since={}", s));
+        }
+        let result = self.fetch_public_value(&url).await?;
+        to_js(&public::parse_candles(&result, symbol)?)
+    }
+
+    // ========== Private Endpoints ==========
+
+    /// Get account balances
+    ///
+    /// Returns an array of `{ asset, balance }`, sorted by asset
+    #[wasm_bindgen]
+    pub async fn get_balance(&self) -> Result<JsValue, JsValue> {
+        let result = self.fetch_private("/0/private/Balance", "").await?;
+        let balances = private::parse_balance(&result)?;
+        to_js(&balances)
+    }
+
+    /// Get open orders
+    ///
+    /// Returns an array of `{ txid, status, pair, side, order_type, price,
+    /// volume, volume_executed, opened_at, userref }`, oldest first
+    #[wasm_bindgen]
+    pub async fn get_open_orders(&self) -> Result<JsValue, JsValue> {
+        let result = self.fetch_private("/0/private/OpenOrders", "").await?;
+        let orders = private::parse_open_orders(&result)?;
+        to_js(&orders)
+    }
+
+    /// Call any private endpoint and return its raw result
+    ///
+    /// # Arguments
+    /// * `method` - Endpoint name (e.g., "TradeBalance")
+    /// * `params` - URL-encoded parameters without the nonce (e.g., "asset=ZUSD")
+    #[wasm_bindgen]
+    pub async fn query_private(&self, method: &str, params: Option<String>) -> Result<JsValue, JsValue> {
+        let path = format!("/0/private/{}", method);
+        let result = self.fetch_private(&path, params.as_deref().unwrap_or_default()).await?;
+        to_js(&result)
+    }
+
     // ========== Internal Helpers ==========
 
-    /// Fetch from a public endpoint
+    /// Sign and POST to a private endpoint, returning the parsed result
+    async fn fetch_private(&self, path: &str, params: &str) -> Result<serde_json::Value, JsValue> {
+        let credentials = self
+            .credentials
+            .as_ref()
+            .ok_or_else(|| {
+                WasmError::auth("missing_credentials", "Private endpoints need with_credentials_unsafe")
+            })?;
+
+        // Sign before awaiting so the RefCell borrow never spans a yield point
+        let (api_key, signature, body) = {
+            let mut credentials = credentials.borrow_mut();
+            let nonce = credentials.next_nonce(js_sys::Date::now() as u64);
+            let body = private::post_data(nonce, params);
+            let signature = credentials.sign(path, nonce, &body);
+            (credentials.api_key().to_string(), signature, body)
+        };
+
+        let url = format!("{}{}", self.base_url, path);
+        let opts = RequestInit::new();
+        opts.set_method("POST");
+        opts.set_mode(RequestMode::Cors);
+        opts.set_body(&JsValue::from_str(&body));
+
+        let request = Request::new_with_str_and_init(&url, &opts)
+            .map_err(|e| WasmError::internal(format!("Failed to create request: {:?}", e)))?;
+        let headers = request.headers();
+        for (name, value) in [
+            ("Accept", "application/json"),
+            ("Content-Type", "application/x-www-form-urlencoded; charset=utf-8"),
+            ("API-Key", api_key.as_str()),
+            ("API-Sign", signature.as_str()),
+        ] {
+            headers
+                .set(name, value)
+                .map_err(|e| WasmError::internal(format!("Failed to set header: {:?}", e)))?;
+        }
+
+        let resp_value = JsFuture::from(runtime::fetch(&request)?)
+            .await
+            .map_err(|e| WasmError::network(format!("Fetch failed: {:?}", e)))?;
+
+        let resp: Response = resp_value
+            .dyn_into()
+            .map_err(|_| WasmError::internal("Response is not a Response object"))?;
+
+        if !resp.ok() {
+            return Err(WasmError::http(resp.status(), &resp.status_text()).into());
+        }
+
+        let text = JsFuture::from(
+            resp.text()
+                .map_err(|e| WasmError::network(format!("Failed to read body: {:?}", e)))?,
+        )
+        .await
+        .map_err(|e| WasmError::network(format!("Failed to read body: {:?}", e)))?;
+
+        let text = text.as_string().unwrap_or_default();
+        Ok(private::take_result(&text)?)
+    }
+
+    /// Fetch from a public endpoint, returning its raw result
     async fn fetch_public(&self, path: &str) -> Result<JsValue, JsValue> {
+        to_js(&self.fetch_public_value(path).await?)
+    }
+
+    /// Fetch from a public endpoint, returning the parsed result
+    async fn fetch_public_value(&self, path: &str) -> Result<serde_json::Value, JsValue> {
         let url = format!("{}{}", self.base_url, path);
 
         let opts = RequestInit::new();
@@ -1213,59 +1676,43 @@ impl WasmRestClient {
         opts.set_mode(RequestMode::Cors);
 
         let request = Request::new_with_str_and_init(&url, &opts)
-            .map_err(|e| JsValue::from_str(&format!("Failed to create request: {:?}", e)))?;
+            .map_err(|e| WasmError::internal(format!("Failed to create request: {:?}", e)))?;
 
         request.headers()
             .set("Accept", "application/json")
-            .map_err(|e| JsValue::from_str(&format!("Failed to set header: {:?}", e)))?;
-
-        let window = web_sys::window()
-            .ok_or_else(|| JsValue::from_str("No window object available"))?;
+            .map_err(|e| WasmError::internal(format!("Failed to set header: {:?}", e)))?;
 
-        let resp_value = JsFuture::from(window.fetch_with_request(&request))
+        let resp_value = JsFuture::from(runtime::fetch(&request)?)
             .await
-            .map_err(|e| JsValue::from_str(&format!("Fetch failed: {:?}", e)))?;
+            .map_err(|e| WasmError::network(format!("Fetch failed: {:?}", e)))?;
 
         let resp: Response = resp_value
             .dyn_into()
-            .map_err(|_| JsValue::from_str("Response is not a Response object"))?;
+            .map_err(|_| WasmError::internal("Response is not a Response object"))?;
 
         if !resp.ok() {
-            return Err(JsValue::from_str(&format!(
-                "HTTP error: {} {}",
-                resp.status(),
-                resp.status_text()
-            )));
+            return Err(WasmError::http(resp.status(), &resp.status_text()).into());
         }
 
-        let json = JsFuture::from(
-            resp.json()
-                .map_err(|e| JsValue::from_str(&format!("Failed to get JSON: {:?}", e)))?,
+        let text = JsFuture::from(
+            resp.text()
+                .map_err(|e| WasmError::network(format!("Failed to read body: {:?}", e)))?,
         )
         .await
-        .map_err(|e| JsValue::from_str(&format!("Failed to parse JSON: {:?}", e)))?;
+        .map_err(|e| WasmError::network(format!("Failed to read body: {:?}", e)))?;
 
-        // Check for Kraken API errors in response
-        if let Ok(error_array) = js_sys::Reflect::get(&json, &JsValue::from_str("error")) {
-            if let Some(arr) = error_array.dyn_ref::<js_sys::Array>() {
-                if arr.length() > 0 {
-                    let errors: Vec<String> = arr
-                        .iter()
-                        .filter_map(|v| v.as_string())
-                        .collect();
-                    if !errors.is_empty() {
-                        return Err(JsValue::from_str(&format!("Kraken API error: {}", errors.join(", "))));
-                    }
-                }
-            }
-        }
-
-        // Return the result field
-        js_sys::Reflect::get(&json, &JsValue::from_str("result"))
-            .map_err(|_| JsValue::from_str("Response missing 'result' field"))
+        let text = text.as_string().unwrap_or_default();
+        Ok(private::take_result(&text)?)
     }
 }
 
+/// Convert to a plain JS value (objects rather than Maps)
+fn to_js<T: serde::Serialize>(value: &T) -> Result<JsValue, JsValue> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| WasmError::internal(e.to_string()).into())
+}
+
 impl Default for WasmRestClient {
     fn default() -> Self {
         Self::new()
@@ -1276,18 +1723,68 @@ impl Default for WasmRestClient {
 // Rate Limiter WASM Bindings
 // ============================================================================
 
-use std::cell::RefCell;
+use rate_limit::{RateScheduler, DEFAULT_BUCKET};
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
+/// Priority of a queued rate limiter acquisition
+///
+/// Within a bucket, higher priorities are served first and equal
+/// priorities in arrival order.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestPriority {
+    /// Served before everything else, e.g. order placement
+    High = 0,
+    /// Regular requests
+    Normal = 1,
+    /// Background refreshes
+    Low = 2,
+}
+
+enum Waiter {
+    Resolve(js_sys::Function),
+    Run {
+        callback: js_sys::Function,
+        resolve: js_sys::Function,
+        reject: js_sys::Function,
+    },
+}
+
+impl Waiter {
+    fn run(self) {
+        match self {
+            Waiter::Resolve(resolve) => {
+                resolve.call0(&JsValue::UNDEFINED).ok();
+            }
+            Waiter::Run { callback, resolve, reject } => match callback.call0(&JsValue::NULL) {
+                // A returned Promise is adopted by the outer one
+                Ok(value) => {
+                    resolve.call1(&JsValue::UNDEFINED, &value).ok();
+                }
+                Err(error) => {
+                    reject.call1(&JsValue::UNDEFINED, &error).ok();
+                }
+            },
+        }
+    }
+}
+
 /// WASM-compatible rate limiter for client-side request throttling
 ///
-/// Uses a token bucket algorithm to rate limit requests. This helps prevent
-/// hitting Kraken's API rate limits when making requests from the browser.
+/// Uses token buckets to rate limit requests. This helps prevent hitting
+/// Kraken's API rate limits when making requests from the browser.
+///
+/// A limiter starts with a single bucket used by `try_acquire`,
+/// `wait_for_token` and friends. More named buckets can be added (e.g.
+/// `public` and `private`), each with its own queue so one kind of request
+/// never starves the other. Queued acquisitions are served by
+/// [`RequestPriority`], then first come, first served.
 ///
 /// # Usage (JavaScript)
 ///
 /// ```javascript
-/// import init, { WasmRateLimiter } from 'kraken-wasm';
+/// import init, { WasmRateLimiter, RequestPriority } from 'kraken-wasm';
 ///
 /// await init();
 ///
@@ -1305,13 +1802,18 @@ use std::rc::Rc;
 /// // Or wait for availability
 /// await limiter.wait_for_token();
 /// await client.get_ticker('ETHUSD');
+///
+/// // Separate public and private budgets, with priorities
+/// const kraken = WasmRateLimiter.kraken();
+/// const book = await kraken.schedule('public', RequestPriority.Normal,
+///     () => client.get_orderbook('XBTUSD', 10));
+/// kraken.schedule('public', RequestPriority.Low, () => client.get_ticker('ETHUSD'));
+/// await kraken.acquire('private', RequestPriority.High);
 /// ```
 #[wasm_bindgen]
 pub struct WasmRateLimiter {
-    capacity: f64,
-    tokens: Rc<RefCell<f64>>,
-    refill_rate: f64,  // tokens per second
-    last_refill: Rc<RefCell<f64>>,  // timestamp in ms
+    inner: Rc<RefCell<RateScheduler<Waiter>>>,
+    timer_armed: Rc<Cell<bool>>,
 }
 
 #[wasm_bindgen]
@@ -1321,15 +1823,18 @@ impl WasmRateLimiter {
     /// # Arguments
     /// * `capacity` - Maximum number of tokens (requests)
     /// * `refill_rate` - Tokens added per second
+    ///
+    /// Fails if `capacity` is below 1 or `refill_rate` isn't positive.
     #[wasm_bindgen(constructor)]
-    pub fn new(capacity: f64, refill_rate: f64) -> WasmRateLimiter {
-        let now = js_sys::Date::now();
-        WasmRateLimiter {
-            capacity,
-            tokens: Rc::new(RefCell::new(capacity)),
-            refill_rate,
-            last_refill: Rc::new(RefCell::new(now)),
-        }
+    pub fn new(capacity: f64, refill_rate: f64) -> Result<WasmRateLimiter, JsValue> {
+        let mut scheduler = RateScheduler::new();
+        scheduler
+            .add_bucket(DEFAULT_BUCKET, capacity, refill_rate, js_sys::Date::now())
+            .map_err(|e| WasmError::invalid_input(e.code(), e.message()))?;
+        Ok(WasmRateLimiter {
+            inner: Rc::new(RefCell::new(scheduler)),
+            timer_armed: Rc::new(Cell::new(false)),
+        })
     }
 
     /// Create a rate limiter with Kraken's default public endpoint limits
@@ -1337,7 +1842,7 @@ impl WasmRateLimiter {
     /// 15 requests, refilling at 0.5 per second (30 per minute)
     #[wasm_bindgen]
     pub fn kraken_public() -> WasmRateLimiter {
-        WasmRateLimiter::new(15.0, 0.5)
+        WasmRateLimiter::new(15.0, 0.5).expect("valid public limits")
     }
 
     /// Create a rate limiter with Kraken's default private endpoint limits
@@ -1345,21 +1850,36 @@ impl WasmRateLimiter {
     /// 20 requests, refilling at 0.33 per second (20 per minute)
     #[wasm_bindgen]
     pub fn kraken_private() -> WasmRateLimiter {
-        WasmRateLimiter::new(20.0, 0.33)
+        WasmRateLimiter::new(20.0, 0.33).expect("valid private limits")
     }
 
-    /// Refill tokens based on time elapsed
-    fn refill(&self) {
-        let now = js_sys::Date::now();
-        let mut last = self.last_refill.borrow_mut();
-        let elapsed_secs = (now - *last) / 1000.0;
+    /// Create a rate limiter with `public` and `private` buckets
+    ///
+    /// Uses the limits of [`kraken_public`](Self::kraken_public) and
+    /// [`kraken_private`](Self::kraken_private); the default bucket
+    /// matches `public`.
+    #[wasm_bindgen]
+    pub fn kraken() -> WasmRateLimiter {
+        let limiter = WasmRateLimiter::kraken_public();
+        limiter.add_bucket("public", 15.0, 0.5).expect("valid public limits");
+        limiter.add_bucket("private", 20.0, 0.33).expect("valid private limits");
+        limiter
+    }
 
-        if elapsed_secs > 0.0 {
-            let mut tokens = self.tokens.borrow_mut();
-            let new_tokens = *tokens + (elapsed_secs * self.refill_rate);
-            *tokens = new_tokens.min(self.capacity);
-            *last = now;
-        }
+    /// Add a named bucket, replacing the limits of an existing one
+    ///
+    /// # Arguments
+    /// * `name` - Bucket name used by `try_acquire_from`, `acquire` and `schedule`
+    /// * `capacity` - Maximum number of tokens (requests)
+    /// * `refill_rate` - Tokens added per second
+    ///
+    /// Fails if `capacity` is below 1 or `refill_rate` isn't positive.
+    #[wasm_bindgen]
+    pub fn add_bucket(&self, name: &str, capacity: f64, refill_rate: f64) -> Result<(), JsValue> {
+        self.inner
+            .borrow_mut()
+            .add_bucket(name, capacity, refill_rate, js_sys::Date::now())
+            .map_err(|e| WasmError::invalid_input(e.code(), e.message()).into())
     }
 
     /// Try to acquire a token for making a request
@@ -1367,28 +1887,28 @@ impl WasmRateLimiter {
     /// Returns true if a token was acquired, false if rate limited
     #[wasm_bindgen]
     pub fn try_acquire(&self) -> bool {
-        self.refill();
-
-        let mut tokens = self.tokens.borrow_mut();
-        if *tokens >= 1.0 {
-            *tokens -= 1.0;
-            true
-        } else {
-            false
-        }
+        self.try_acquire_from(DEFAULT_BUCKET)
+    }
+
+    /// Try to acquire a token from a named bucket
+    ///
+    /// Returns false if rate limited, if others are queued on the bucket,
+    /// or if the bucket doesn't exist
+    #[wasm_bindgen]
+    pub fn try_acquire_from(&self, bucket: &str) -> bool {
+        self.inner.borrow_mut().try_acquire(bucket, js_sys::Date::now())
     }
 
     /// Get the number of available tokens
     #[wasm_bindgen]
     pub fn available(&self) -> f64 {
-        self.refill();
-        *self.tokens.borrow()
+        self.with_default(|bucket, now| bucket.available(now))
     }
 
     /// Get the maximum capacity
     #[wasm_bindgen]
     pub fn capacity(&self) -> f64 {
-        self.capacity
+        self.with_default(|bucket, _| bucket.capacity())
     }
 
     /// Get time until a token is available (in milliseconds)
@@ -1396,15 +1916,7 @@ impl WasmRateLimiter {
     /// Returns 0 if a token is immediately available
     #[wasm_bindgen]
     pub fn time_until_available(&self) -> f64 {
-        self.refill();
-
-        let tokens = *self.tokens.borrow();
-        if tokens >= 1.0 {
-            0.0
-        } else {
-            let needed = 1.0 - tokens;
-            (needed / self.refill_rate) * 1000.0
-        }
+        self.with_default(|bucket, now| bucket.wait_ms(now))
     }
 
     /// Get utilization percentage (0.0 to 1.0)
@@ -1412,16 +1924,14 @@ impl WasmRateLimiter {
     /// 0.0 = no tokens used, 1.0 = all tokens used
     #[wasm_bindgen]
     pub fn utilization(&self) -> f64 {
-        self.refill();
-        let tokens = *self.tokens.borrow();
-        1.0 - (tokens / self.capacity)
+        self.with_default(|bucket, now| 1.0 - (bucket.available(now) / bucket.capacity()))
     }
 
     /// Reset the limiter to full capacity
     #[wasm_bindgen]
     pub fn reset(&self) {
-        *self.tokens.borrow_mut() = self.capacity;
-        *self.last_refill.borrow_mut() = js_sys::Date::now();
+        self.with_default(|bucket, now| bucket.reset(now));
+        self.pump();
     }
 
     /// Wait for a token to become available (returns a Promise)
@@ -1429,49 +1939,30 @@ impl WasmRateLimiter {
     /// This is useful for async/await patterns in JavaScript
     #[wasm_bindgen]
     pub fn wait_for_token(&self) -> js_sys::Promise {
-        let tokens = self.tokens.clone();
-        let last_refill = self.last_refill.clone();
-        let capacity = self.capacity;
-        let refill_rate = self.refill_rate;
-
-        js_sys::Promise::new(&mut |resolve, _reject| {
-            // Check current availability
-            let now = js_sys::Date::now();
-            let elapsed_secs = (now - *last_refill.borrow()) / 1000.0;
-            let current_tokens = (*tokens.borrow() + elapsed_secs * refill_rate).min(capacity);
-
-            if current_tokens >= 1.0 {
-                // Token available, resolve immediately
-                *tokens.borrow_mut() = current_tokens - 1.0;
-                *last_refill.borrow_mut() = now;
-                resolve.call0(&JsValue::UNDEFINED).ok();
-            } else {
-                // Need to wait
-                let needed = 1.0 - current_tokens;
-                let wait_ms = (needed / refill_rate) * 1000.0;
-
-                let tokens_clone = tokens.clone();
-                let last_refill_clone = last_refill.clone();
-
-                let closure = wasm_bindgen::closure::Closure::once(Box::new(move || {
-                    let now = js_sys::Date::now();
-                    let elapsed = (now - *last_refill_clone.borrow()) / 1000.0;
-                    let new_tokens = (*tokens_clone.borrow() + elapsed * refill_rate).min(capacity);
-                    *tokens_clone.borrow_mut() = (new_tokens - 1.0).max(0.0);
-                    *last_refill_clone.borrow_mut() = now;
-                    resolve.call0(&JsValue::UNDEFINED).ok();
-                }) as Box<dyn FnOnce()>);
-
-                let window = web_sys::window().expect("window");
-                window
-                    .set_timeout_with_callback_and_timeout_and_arguments_0(
-                        closure.as_ref().unchecked_ref(),
-                        wait_ms as i32,
-                    )
-                    .ok();
-                closure.forget();
-            }
-        })
+        self.queue(DEFAULT_BUCKET, RequestPriority::Normal, None)
+    }
+
+    /// Wait for a token from a named bucket (returns a Promise)
+    ///
+    /// Rejects immediately if the bucket doesn't exist
+    #[wasm_bindgen]
+    pub fn acquire(&self, bucket: &str, priority: RequestPriority) -> js_sys::Promise {
+        self.queue(bucket, priority, None)
+    }
+
+    /// Run a callback once a named bucket has budget (returns a Promise)
+    ///
+    /// The Promise settles with the callback's result; a returned Promise
+    /// is awaited. Rejects immediately if the bucket doesn't exist
+    #[wasm_bindgen]
+    pub fn schedule(&self, bucket: &str, priority: RequestPriority, callback: js_sys::Function) -> js_sys::Promise {
+        self.queue(bucket, priority, Some(callback))
+    }
+
+    /// Number of acquisitions waiting across all buckets
+    #[wasm_bindgen]
+    pub fn queue_length(&self) -> usize {
+        self.inner.borrow().queued()
     }
 
     /// Check if making a request would exceed the rate limit
@@ -1479,7 +1970,342 @@ impl WasmRateLimiter {
     /// Returns true if the rate limit would be exceeded
     #[wasm_bindgen]
     pub fn is_limited(&self) -> bool {
-        self.refill();
-        *self.tokens.borrow() < 1.0
+        self.available() < 1.0
+    }
+}
+
+impl WasmRateLimiter {
+    fn with_default<R>(&self, f: impl FnOnce(&mut rate_limit::TokenBucket, f64) -> R) -> R {
+        let mut inner = self.inner.borrow_mut();
+        let bucket = inner.bucket_mut(DEFAULT_BUCKET).expect("default bucket always exists");
+        f(bucket, js_sys::Date::now())
+    }
+
+    fn queue(&self, bucket: &str, priority: RequestPriority, callback: Option<js_sys::Function>) -> js_sys::Promise {
+        let mut callback = callback;
+        let promise = js_sys::Promise::new(&mut |resolve, reject| {
+            let waiter = match callback.take() {
+                Some(callback) => Waiter::Run { callback, resolve, reject: reject.clone() },
+                None => Waiter::Resolve(resolve),
+            };
+            if self.inner.borrow_mut().enqueue(bucket, priority as u8, waiter).is_err() {
+                let error: JsValue = WasmError::invalid_input(
+                    "unknown_bucket",
+                    format!("unknown rate limit bucket: {}", bucket),
+                )
+                .into();
+                reject.call1(&JsValue::UNDEFINED, &error).ok();
+            }
+        });
+        self.pump();
+        promise
+    }
+
+    /// Run every waiter that has budget, then arm a timer for the next one
+    fn pump(&self) {
+        pump(&self.inner, &self.timer_armed);
+    }
+}
+
+fn pump(inner: &Rc<RefCell<RateScheduler<Waiter>>>, timer_armed: &Rc<Cell<bool>>) {
+    // Release the borrow before calling into JS, which may re-enter the limiter
+    let ready = inner.borrow_mut().drain_ready(js_sys::Date::now());
+    for waiter in ready {
+        waiter.run();
+    }
+
+    let Some(wait_ms) = inner.borrow_mut().next_wake_ms(js_sys::Date::now()) else {
+        return;
+    };
+    if timer_armed.get() {
+        return;
+    }
+    let (inner_clone, armed_clone) = (inner.clone(), timer_armed.clone());
+    let closure = wasm_bindgen::closure::Closure::once(Box::new(move || {
+        armed_clone.set(false);
+        pump(&inner_clone, &armed_clone);
+    }) as Box<dyn FnOnce()>);
+    let armed = runtime::set_timeout(closure.as_ref().unchecked_ref(), wait_ms.ceil().max(1.0) as i32);
+    timer_armed.set(armed);
+    closure.forget();
+}
+
+// ============================================================================
+// IndexedDB Persistence
+// ============================================================================
+
+use web_sys::{IdbDatabase, IdbObjectStore, IdbRequest, IdbTransaction, IdbTransactionMode};
+
+/// Object store holding every persisted book
+const IDB_STORE: &str = "orderbooks";
+
+/// Orderbook state and history loaded from IndexedDB
+///
+/// Pass it to `WasmOrderbook.restore()`.
+#[wasm_bindgen]
+pub struct WasmPersistedBook {
+    inner: persistence::PersistedBook,
+}
+
+#[wasm_bindgen]
+impl WasmPersistedBook {
+    /// Get the trading pair symbol
+    #[wasm_bindgen]
+    pub fn get_symbol(&self) -> String {
+        self.inner.manifest.symbol.clone()
+    }
+
+    /// Get the save time (Unix milliseconds)
+    #[wasm_bindgen]
+    pub fn get_saved_at(&self) -> f64 {
+        self.inner.manifest.saved_at_ms
+    }
+
+    /// Get the number of saved history snapshots
+    #[wasm_bindgen]
+    pub fn get_history_length(&self) -> u32 {
+        self.inner.history.len() as u32
+    }
+}
+
+/// IndexedDB storage for orderbook state and time-travel history
+///
+/// Lets a visualizer survive page reloads: save books periodically (or on
+/// `visibilitychange`) and restore them before the WebSocket reconnects.
+/// History is written in chunks, one transaction each, so large histories
+/// don't stall the page.
+///
+/// # Usage (JavaScript)
+///
+/// ```javascript
+/// import init, { WasmOrderbook, WasmIndexedDbStore } from 'kraken-wasm';
+///
+/// await init();
+///
+/// const store = await WasmIndexedDbStore.open('havklo');
+/// const book = new WasmOrderbook('BTC/USD');
+/// book.enable_history(1000);
+///
+/// const saved = await store.load_book('BTC/USD');
+/// if (saved) {
+///     book.restore(saved);
+/// }
+///
+/// document.addEventListener('visibilitychange', () => store.save_book(book));
+/// ```
+#[wasm_bindgen]
+pub struct WasmIndexedDbStore {
+    db: IdbDatabase,
+    chunk_size: usize,
+    /// Last generation handed out, so overlapping saves never share one
+    last_generation: Rc<Cell<u64>>,
+}
+
+#[wasm_bindgen]
+impl WasmIndexedDbStore {
+    /// Open (or create) a database
+    ///
+    /// # Arguments
+    /// * `name` - IndexedDB database name
+    #[wasm_bindgen]
+    pub async fn open(name: String) -> Result<WasmIndexedDbStore, JsValue> {
+        let factory = runtime::indexed_db()?;
+        let request = factory
+            .open_with_u32(&name, 1)
+            .map_err(|e| WasmError::storage(format!("Failed to open database {}: {:?}", name, e)))?;
+
+        let upgrade_request = request.clone();
+        let on_upgrade = Closure::<dyn FnMut()>::new(move || {
+            if let Ok(db) = upgrade_request.result() {
+                let db: IdbDatabase = db.unchecked_into();
+                if !db.object_store_names().contains(IDB_STORE) {
+                    db.create_object_store(IDB_STORE).ok();
+                }
+            }
+        });
+        request.set_onupgradeneeded(Some(on_upgrade.as_ref().unchecked_ref()));
+
+        let db = idb_result(&request)
+            .await?
+            .dyn_into::<IdbDatabase>()
+            .map_err(|_| WasmError::internal("Open request did not yield an IDBDatabase"))?;
+        Ok(WasmIndexedDbStore {
+            db,
+            chunk_size: persistence::DEFAULT_CHUNK_SIZE,
+            last_generation: Rc::new(Cell::new(0)),
+        })
+    }
+
+    /// Set the number of history snapshots written per transaction
+    #[wasm_bindgen]
+    pub fn set_chunk_size(&mut self, chunk_size: u32) {
+        self.chunk_size = (chunk_size as usize).max(1);
+    }
+
+    /// Save a book's state and history (returns a Promise)
+    ///
+    /// The book is captured immediately; writing continues in the
+    /// background. History goes under a new generation of chunk keys, the
+    /// manifest is switched to it last, and only then are the replaced
+    /// chunks deleted, so an interrupted save leaves either the old or the
+    /// new book loadable.
+    #[wasm_bindgen]
+    pub fn save_book(&self, book: &WasmOrderbook) -> js_sys::Promise {
+        let symbol = book.inner.symbol().to_string();
+        let snapshot = book.inner.snapshot();
+        let history = book.history.clone();
+        let chunk_size = self.chunk_size;
+        let saved_at_ms = js_sys::Date::now();
+        let last_generation = Rc::clone(&self.last_generation);
+        let db = self.db.clone();
+
+        wasm_bindgen_futures::future_to_promise(async move {
+            let current = read_manifest(&db, &symbol).await?.map_or(0, |m| m.generation);
+            let generation = current.max(last_generation.get()) + 1;
+            last_generation.set(generation);
+            let encoded = persistence::encode(&snapshot, history.as_ref(), chunk_size, generation, saved_at_ms)
+                .map_err(|e| WasmError::storage(e.to_string()))?;
+
+            for (index, chunk) in encoded.chunks.iter().enumerate() {
+                let (tx, store) = idb_store(&db, IdbTransactionMode::Readwrite)?;
+                let key = persistence::chunk_key(&symbol, generation, index);
+                store
+                    .put_with_key(&JsValue::from_str(chunk), &JsValue::from_str(&key))
+                    .map_err(|e| WasmError::storage(format!("Failed to write {}: {:?}", key, e)))?;
+                idb_complete(&tx).await?;
+            }
+
+            // Reading the manifest in the switching transaction names exactly
+            // the generation this save replaced, even with saves overlapping
+            let (tx, store) = idb_store(&db, IdbTransactionMode::Readwrite)?;
+            let key = JsValue::from_str(&persistence::manifest_key(&symbol));
+            let replaced = store
+                .get(&key)
+                .map_err(|e| WasmError::storage(format!("Failed to read manifest of {}: {:?}", symbol, e)))?;
+            store
+                .put_with_key(&JsValue::from_str(&encoded.manifest), &key)
+                .map_err(|e| WasmError::storage(format!("Failed to write manifest of {}: {:?}", symbol, e)))?;
+            idb_complete(&tx).await?;
+
+            let replaced = replaced
+                .result()
+                .map_err(|e| WasmError::storage(format!("Failed to read manifest of {}: {:?}", symbol, e)))?
+                .as_string()
+                .and_then(|json| persistence::decode_manifest(&json).ok());
+            if let Some(replaced) = replaced.filter(|m| m.generation != generation) {
+                let (tx, store) = idb_store(&db, IdbTransactionMode::Readwrite)?;
+                delete_chunks(&store, &symbol, &replaced)?;
+                idb_complete(&tx).await?;
+            }
+            Ok(JsValue::UNDEFINED)
+        })
+    }
+
+    /// Load a saved book, or null if none is stored
+    ///
+    /// # Arguments
+    /// * `symbol` - Trading pair symbol (e.g., "BTC/USD")
+    #[wasm_bindgen]
+    pub async fn load_book(&self, symbol: String) -> Result<JsValue, JsValue> {
+        let Some(manifest) = read_manifest(&self.db, &symbol).await? else {
+            return Ok(JsValue::NULL);
+        };
+
+        // Issue every read before awaiting so the transaction stays active
+        let (_tx, store) = idb_store(&self.db, IdbTransactionMode::Readonly)?;
+        let requests = (0..manifest.chunks)
+            .map(|index| {
+                let key = persistence::chunk_key(&symbol, manifest.generation, index);
+                store
+                    .get(&JsValue::from_str(&key))
+                    .map_err(|e| WasmError::storage(format!("Failed to read {}: {:?}", key, e)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut chunks = Vec::with_capacity(requests.len());
+        for request in &requests {
+            chunks.push(idb_result(request).await?.as_string().unwrap_or_default());
+        }
+
+        let inner = persistence::decode(manifest, &chunks).map_err(WasmError::storage)?;
+        Ok(WasmPersistedBook { inner }.into())
+    }
+
+    /// Delete a saved book and its history (returns a Promise)
+    #[wasm_bindgen]
+    pub async fn delete_book(&self, symbol: String) -> Result<(), JsValue> {
+        let Some(manifest) = read_manifest(&self.db, &symbol).await? else {
+            return Ok(());
+        };
+        let (tx, store) = idb_store(&self.db, IdbTransactionMode::Readwrite)?;
+        store
+            .delete(&JsValue::from_str(&persistence::manifest_key(&symbol)))
+            .map_err(|e| WasmError::storage(format!("Failed to delete manifest of {}: {:?}", symbol, e)))?;
+        delete_chunks(&store, &symbol, &manifest)?;
+        Ok(idb_complete(&tx).await?)
+    }
+
+    /// Close the database connection
+    #[wasm_bindgen]
+    pub fn close(&self) {
+        self.db.close();
+    }
+}
+
+fn idb_store(db: &IdbDatabase, mode: IdbTransactionMode) -> Result<(IdbTransaction, IdbObjectStore), WasmError> {
+    let tx = db
+        .transaction_with_str_and_mode(IDB_STORE, mode)
+        .map_err(|e| WasmError::storage(format!("Failed to start transaction: {:?}", e)))?;
+    let store = tx
+        .object_store(IDB_STORE)
+        .map_err(|e| WasmError::storage(format!("Failed to open object store {}: {:?}", IDB_STORE, e)))?;
+    Ok((tx, store))
+}
+
+/// Queue deletes for every history chunk of one saved generation
+fn delete_chunks(store: &IdbObjectStore, symbol: &str, manifest: &persistence::BookManifest) -> Result<(), WasmError> {
+    for index in 0..manifest.chunks {
+        let key = persistence::chunk_key(symbol, manifest.generation, index);
+        store
+            .delete(&JsValue::from_str(&key))
+            .map_err(|e| WasmError::storage(format!("Failed to delete {}: {:?}", key, e)))?;
+    }
+    Ok(())
+}
+
+/// Wait for a request to succeed and return its result
+async fn idb_result(request: &IdbRequest) -> Result<JsValue, WasmError> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        request.set_onsuccess(Some(&resolve));
+        request.set_onerror(Some(&reject));
+    });
+    JsFuture::from(promise)
+        .await
+        .map_err(|e| WasmError::storage(format!("IndexedDB request failed: {:?}", e)))?;
+    request
+        .result()
+        .map_err(|e| WasmError::storage(format!("Failed to read request result: {:?}", e)))
+}
+
+/// Wait for a transaction to commit
+async fn idb_complete(tx: &IdbTransaction) -> Result<(), WasmError> {
+    let promise = js_sys::Promise::new(&mut |resolve, reject| {
+        tx.set_oncomplete(Some(&resolve));
+        tx.set_onerror(Some(&reject));
+        tx.set_onabort(Some(&reject));
+    });
+    JsFuture::from(promise)
+        .await
+        .map(|_| ())
+        .map_err(|e| WasmError::storage(format!("IndexedDB transaction failed: {:?}", e)))
+}
+
+async fn read_manifest(db: &IdbDatabase, symbol: &str) -> Result<Option<persistence::BookManifest>, WasmError> {
+    let (_tx, store) = idb_store(db, IdbTransactionMode::Readonly)?;
+    let request = store
+        .get(&JsValue::from_str(&persistence::manifest_key(symbol)))
+        .map_err(|e| WasmError::storage(format!("Failed to read manifest of {}: {:?}", symbol, e)))?;
+    match idb_result(&request).await?.as_string() {
+        Some(json) => persistence::decode_manifest(&json).map(Some).map_err(WasmError::storage),
+        None => Ok(None),
     }
 }
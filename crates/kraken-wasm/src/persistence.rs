@@ -0,0 +1,201 @@
+//! Storage layout for persisted orderbooks
+//!
+//! [`WasmIndexedDbStore`](crate::WasmIndexedDbStore) saves a book as one
+//! manifest record plus its history split into chunks, so a long history is
+//! written as several small transactions instead of one large one. This
+//! module holds the JS-free half: keys, record encoding, and decoding.
+//!
+//! ```text
+//! {symbol}/manifest             BookManifest (book state, generation, chunk count)
+//! {symbol}/history/7/000000     JSON array of TimestampedSnapshot
+//! {symbol}/history/7/000001     ...
+//! ```
+//!
+//! Every save writes its chunks under a new generation, then switches the
+//! manifest to it, then deletes the previous generation's chunks. Chunks the
+//! manifest names are never overwritten, so a save interrupted at any point
+//! leaves the previous (or the new) book loadable, never a mix. Manifests
+//! written before generations existed are generation 0, whose chunks keep
+//! the old `{symbol}/history/000000` keys.
+
+use kraken_book::{HistoryBuffer, OrderbookSnapshot, TimestampedSnapshot};
+use serde::{Deserialize, Serialize};
+
+/// Version written into every manifest
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Default number of history snapshots per chunk
+pub const DEFAULT_CHUNK_SIZE: usize = 100;
+
+/// Key of a symbol's manifest record
+pub fn manifest_key(symbol: &str) -> String {
+    format!("{}/manifest", symbol)
+}
+
+/// Key of one history chunk record of a generation
+pub fn chunk_key(symbol: &str, generation: u64, index: usize) -> String {
+    match generation {
+        0 => format!("{}/history/{:06}", symbol, index),
+        _ => format!("{}/history/{}/{:06}", symbol, generation, index),
+    }
+}
+
+/// Top-level record describing a saved book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookManifest {
+    /// Layout version
+    pub version: u32,
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Book state at save time
+    pub book: OrderbookSnapshot,
+    /// History capacity, if history was enabled
+    pub history_capacity: Option<usize>,
+    /// Generation the history chunks are stored under
+    #[serde(default)]
+    pub generation: u64,
+    /// Number of history chunks
+    pub chunks: usize,
+    /// Save time (Unix milliseconds)
+    pub saved_at_ms: f64,
+}
+
+/// Records to write for one save
+#[derive(Debug, Clone)]
+pub struct EncodedBook {
+    /// Manifest JSON
+    pub manifest: String,
+    /// History chunk JSON, in key order
+    pub chunks: Vec<String>,
+}
+
+/// A book read back from storage
+#[derive(Debug, Clone)]
+pub struct PersistedBook {
+    /// Saved manifest
+    pub manifest: BookManifest,
+    /// Saved history, oldest first
+    pub history: Vec<TimestampedSnapshot>,
+}
+
+impl PersistedBook {
+    /// Rebuild the history buffer, if history was enabled when saved
+    pub fn history_buffer(&self) -> Option<HistoryBuffer> {
+        let capacity = self.manifest.history_capacity?;
+        Some(HistoryBuffer::from_snapshots(capacity, self.history.iter().cloned()))
+    }
+}
+
+/// Encode a book and its history into storage records of a generation
+pub fn encode(
+    book: &OrderbookSnapshot,
+    history: Option<&HistoryBuffer>,
+    chunk_size: usize,
+    generation: u64,
+    saved_at_ms: f64,
+) -> Result<EncodedBook, serde_json::Error> {
+    let entries: Vec<&TimestampedSnapshot> = history.map(|h| h.iter().collect()).unwrap_or_default();
+    let chunks = entries
+        .chunks(chunk_size.max(1))
+        .map(serde_json::to_string)
+        .collect::<Result<Vec<_>, _>>()?;
+    let manifest = BookManifest {
+        version: FORMAT_VERSION,
+        symbol: book.symbol.clone(),
+        book: book.clone(),
+        history_capacity: history.map(HistoryBuffer::capacity),
+        generation,
+        chunks: chunks.len(),
+        saved_at_ms,
+    };
+    Ok(EncodedBook {
+        manifest: serde_json::to_string(&manifest)?,
+        chunks,
+    })
+}
+
+/// Parse a manifest record
+pub fn decode_manifest(json: &str) -> Result<BookManifest, String> {
+    let manifest: BookManifest =
+        serde_json::from_str(json).map_err(|e| format!("Invalid manifest: {}", e))?;
+    if manifest.version != FORMAT_VERSION {
+        return Err(format!("Unsupported storage version {}", manifest.version));
+    }
+    Ok(manifest)
+}
+
+/// Assemble a manifest and its chunk records into a persisted book
+pub fn decode(manifest: BookManifest, chunks: &[String]) -> Result<PersistedBook, String> {
+    if chunks.len() != manifest.chunks {
+        return Err(format!(
+            "Expected {} history chunks, found {}",
+            manifest.chunks,
+            chunks.len()
+        ));
+    }
+    let mut history = Vec::new();
+    for (index, chunk) in chunks.iter().enumerate() {
+        let entries: Vec<TimestampedSnapshot> =
+            serde_json::from_str(chunk).map_err(|e| format!("Invalid history chunk {}: {}", index, e))?;
+        history.extend(entries);
+    }
+    Ok(PersistedBook { manifest, history })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_book::OrderbookState;
+    use kraken_types::Level;
+
+    fn snapshot(bid: f64) -> OrderbookSnapshot {
+        OrderbookSnapshot {
+            symbol: "BTC/USD".to_string(),
+            bids: vec![Level::from_f64(bid, 1.0)],
+            asks: vec![Level::from_f64(bid + 1.0, 1.0)],
+            checksum: 7,
+            state: OrderbookState::Synced,
+        }
+    }
+
+    #[test]
+    fn test_round_trip_in_chunks() {
+        let mut history = HistoryBuffer::new(50);
+        for i in 0..25 {
+            history.push(snapshot(100.0 + i as f64));
+        }
+        let encoded = encode(&snapshot(124.0), Some(&history), 10, 3, 1.0).unwrap();
+        assert_eq!(encoded.chunks.len(), 3);
+
+        let manifest = decode_manifest(&encoded.manifest).unwrap();
+        let persisted = decode(manifest, &encoded.chunks).unwrap();
+        assert_eq!(persisted.manifest.book.checksum, 7);
+        let restored = persisted.history_buffer().unwrap();
+        assert_eq!(restored.capacity(), 50);
+        assert_eq!(restored.len(), 25);
+        assert_eq!(restored.current_sequence(), 25);
+        assert_eq!(persisted.manifest.generation, 3);
+        assert_eq!(chunk_key("BTC/USD", 3, 2), "BTC/USD/history/3/000002");
+        assert_eq!(chunk_key("BTC/USD", 4, 2), "BTC/USD/history/4/000002");
+    }
+
+    #[test]
+    fn test_missing_chunk_and_version_rejected() {
+        let encoded = encode(&snapshot(100.0), None, 10, 1, 1.0).unwrap();
+        let manifest = decode_manifest(&encoded.manifest).unwrap();
+        assert!(decode(manifest.clone(), &[]).unwrap().history_buffer().is_none());
+        assert!(decode(BookManifest { chunks: 1, ..manifest }, &[]).is_err());
+
+        let future = encoded.manifest.replace("\"version\":1", "\"version\":99");
+        assert!(decode_manifest(&future).is_err());
+    }
+
+    #[test]
+    fn test_manifest_without_generation_reads_legacy_keys() {
+        let encoded = encode(&snapshot(100.0), None, 10, 0, 1.0).unwrap();
+        let legacy = encoded.manifest.replace("\"generation\":0,", "");
+        assert!(!legacy.contains("generation"));
+        assert_eq!(decode_manifest(&legacy).unwrap().generation, 0);
+        assert_eq!(chunk_key("BTC/USD", 0, 2), "BTC/USD/history/000002");
+    }
+}
@@ -0,0 +1,247 @@
+//! Request signing and typed responses for private REST endpoints
+//!
+//! Used by [`WasmRestClient`](crate::WasmRestClient) once it has been given
+//! credentials. Signing is pure Rust (HMAC-SHA512 over the path and the
+//! SHA-256 of nonce + POST data), so it behaves the same in the browser and
+//! in native tests.
+
+use crate::error::WasmError;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use sha2::{Digest, Sha256, Sha512};
+
+type HmacSha512 = Hmac<Sha512>;
+
+/// Phrase that must be passed to enable private endpoints in the browser
+///
+/// API keys in a browser can be read by any script on the page, browser
+/// extensions, and anyone with access to the machine. Only use this for
+/// internal dashboards, with keys restricted to the permissions they need.
+pub const BROWSER_KEY_ACKNOWLEDGEMENT: &str = "I understand the risks of keys in the browser";
+
+/// API key and decoded secret
+pub struct PrivateCredentials {
+    api_key: String,
+    secret: Vec<u8>,
+    last_nonce: u64,
+}
+
+impl std::fmt::Debug for PrivateCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PrivateCredentials")
+            .field("api_key", &self.api_key)
+            .field("secret", &"[REDACTED]")
+            .finish()
+    }
+}
+
+impl PrivateCredentials {
+    /// Create credentials from an API key and its base64 secret
+    pub fn new(api_key: &str, api_secret: &str) -> Result<Self, WasmError> {
+        if api_key.is_empty() {
+            return Err(WasmError::auth("empty_api_key", "API key is empty"));
+        }
+        let secret = BASE64.decode(api_secret).map_err(|e| {
+            WasmError::auth("invalid_api_secret", format!("API secret is not valid base64: {}", e))
+        })?;
+        Ok(Self {
+            api_key: api_key.to_string(),
+            secret,
+            last_nonce: 0,
+        })
+    }
+
+    /// API key sent in the `API-Key` header
+    pub fn api_key(&self) -> &str {
+        &self.api_key
+    }
+
+    /// Next nonce: the current time in milliseconds, strictly increasing
+    ///
+    /// Two requests in the same millisecond still get distinct nonces.
+    pub fn next_nonce(&mut self, now_ms: u64) -> u64 {
+        self.last_nonce = now_ms.max(self.last_nonce + 1);
+        self.last_nonce
+    }
+
+    /// `API-Sign` header value for a request
+    pub fn sign(&self, path: &str, nonce: u64, post_data: &str) -> String {
+        let mut sha256 = Sha256::new();
+        sha256.update(nonce.to_string().as_bytes());
+        sha256.update(post_data.as_bytes());
+        let digest = sha256.finalize();
+
+        let mut mac = HmacSha512::new_from_slice(&self.secret).expect("HMAC accepts keys of any length");
+        mac.update(path.as_bytes());
+        mac.update(&digest);
+        BASE64.encode(mac.finalize().into_bytes())
+    }
+}
+
+/// POST body for a private request: the nonce followed by `params`
+pub fn post_data(nonce: u64, params: &str) -> String {
+    if params.is_empty() {
+        format!("nonce={}", nonce)
+    } else {
+        format!("nonce={}&{}", nonce, params.trim_start_matches('&'))
+    }
+}
+
+/// Split a REST response into its `result`, or the Kraken error messages
+pub fn take_result(body: &str) -> Result<serde_json::Value, WasmError> {
+    let mut value: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| WasmError::parse(format!("Failed to parse JSON: {}", e)))?;
+    if let Some(errors) = value.get("error").and_then(|e| e.as_array()) {
+        let messages: Vec<String> = errors.iter().filter_map(|e| e.as_str()).map(str::to_string).collect();
+        if !messages.is_empty() {
+            return Err(WasmError::api(&messages));
+        }
+    }
+    value
+        .get_mut("result")
+        .map(serde_json::Value::take)
+        .ok_or_else(|| WasmError::parse("Response missing 'result' field"))
+}
+
+/// Balance of one asset
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AssetBalance {
+    /// Kraken asset name (e.g. "XXBT", "ZUSD")
+    pub asset: String,
+    /// Balance as returned by Kraken, to keep full precision
+    pub balance: String,
+}
+
+/// An open order
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OpenOrder {
+    /// Transaction ID
+    pub txid: String,
+    /// Order status ("pending" or "open")
+    pub status: String,
+    /// Asset pair
+    pub pair: String,
+    /// "buy" or "sell"
+    pub side: String,
+    /// Order type (e.g. "limit", "market")
+    pub order_type: String,
+    /// Primary price
+    pub price: String,
+    /// Order volume
+    pub volume: String,
+    /// Volume executed so far
+    pub volume_executed: String,
+    /// Unix time the order was opened
+    pub opened_at: f64,
+    /// User reference, if set
+    pub userref: Option<i64>,
+}
+
+/// Parse the `result` of `/0/private/Balance`, sorted by asset
+pub fn parse_balance(result: &serde_json::Value) -> Result<Vec<AssetBalance>, WasmError> {
+    let assets = result
+        .as_object()
+        .ok_or_else(|| WasmError::parse("Balance result is not an object"))?;
+    let mut balances: Vec<AssetBalance> = assets
+        .iter()
+        .map(|(asset, balance)| {
+            let balance = balance
+                .as_str()
+                .ok_or_else(|| WasmError::parse(format!("Invalid balance for {}", asset)))?;
+            Ok(AssetBalance {
+                asset: asset.clone(),
+                balance: balance.to_string(),
+            })
+        })
+        .collect::<Result<_, WasmError>>()?;
+    balances.sort_by(|a, b| a.asset.cmp(&b.asset));
+    Ok(balances)
+}
+
+/// Parse the `result` of `/0/private/OpenOrders`, oldest first
+pub fn parse_open_orders(result: &serde_json::Value) -> Result<Vec<OpenOrder>, WasmError> {
+    let open = result
+        .get("open")
+        .and_then(|o| o.as_object())
+        .ok_or_else(|| WasmError::parse("OpenOrders result missing 'open'"))?;
+    let text = |order: &serde_json::Value, key: &str| {
+        order.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string()
+    };
+    let mut orders: Vec<OpenOrder> = open
+        .iter()
+        .map(|(txid, order)| {
+            let descr = order
+                .get("descr")
+                .ok_or_else(|| WasmError::parse(format!("Order {} missing 'descr'", txid)))?;
+            Ok(OpenOrder {
+                txid: txid.clone(),
+                status: text(order, "status"),
+                pair: text(descr, "pair"),
+                side: text(descr, "type"),
+                order_type: text(descr, "ordertype"),
+                price: text(descr, "price"),
+                volume: text(order, "vol"),
+                volume_executed: text(order, "vol_exec"),
+                opened_at: order.get("opentm").and_then(|v| v.as_f64()).unwrap_or_default(),
+                userref: order.get("userref").and_then(|v| v.as_i64()),
+            })
+        })
+        .collect::<Result<_, WasmError>>()?;
+    orders.sort_by(|a, b| a.opened_at.total_cmp(&b.opened_at));
+    Ok(orders)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ErrorCategory;
+
+    #[test]
+    fn test_signature_matches_kraken_example() {
+        let creds = PrivateCredentials::new(
+            "API_KEY",
+            "kQH5HW/8p1uGOVjbgWA7FunAmGO8lsSUXNsu3eow76sz84Q18fWxnyRzBHCd3pd5nE9qa99HAZtuZuj6F1huXg==",
+        )
+        .unwrap();
+        let nonce = 1616492376594;
+        let body = post_data(nonce, "ordertype=limit&pair=XBTUSD&price=37500&type=buy&volume=1.25");
+        assert_eq!(
+            creds.sign("/0/private/AddOrder", nonce, &body),
+            "4/dpxb3iT4tp/ZCVEwSnEsLxx0bqyhLpdfOpc6fn7OR8+UClSV5n9E6aSS8MPtnRfp32bAb0nmbRn6H8ndwLUQ=="
+        );
+        assert!(!format!("{:?}", creds).contains("kQH5HW"));
+        assert!(PrivateCredentials::new("key", "not base64!").is_err());
+    }
+
+    #[test]
+    fn test_nonces_strictly_increase() {
+        let mut creds = PrivateCredentials::new("key", "c2VjcmV0").unwrap();
+        assert_eq!(creds.next_nonce(1000), 1000);
+        assert_eq!(creds.next_nonce(1000), 1001);
+        assert_eq!(creds.next_nonce(999), 1002);
+        assert_eq!(creds.next_nonce(2000), 2000);
+    }
+
+    #[test]
+    fn test_parse_typed_responses() {
+        let balance = take_result(r#"{"error":[],"result":{"ZUSD":"171288.6158","XXBT":"0.0011"}}"#).unwrap();
+        let balances = parse_balance(&balance).unwrap();
+        assert_eq!(balances[0].asset, "XXBT");
+        assert_eq!(balances[1].balance, "171288.6158");
+
+        let orders = take_result(
+            r#"{"error":[],"result":{"open":{"OQCLML-BW3P3-BUCMWZ":{"status":"open","opentm":1688666559.8974,"vol":"1.25","vol_exec":"0.37","userref":7,"descr":{"pair":"XBTUSD","type":"buy","ordertype":"limit","price":"30010.0"}}}}}"#,
+        )
+        .unwrap();
+        let orders = parse_open_orders(&orders).unwrap();
+        assert_eq!(orders[0].txid, "OQCLML-BW3P3-BUCMWZ");
+        assert_eq!(orders[0].side, "buy");
+        assert_eq!(orders[0].userref, Some(7));
+
+        let err = take_result(r#"{"error":["EAPI:Invalid key"]}"#).unwrap_err();
+        assert_eq!(err.code, "EAPI:Invalid key");
+        assert_eq!(err.category, ErrorCategory::Auth);
+    }
+}
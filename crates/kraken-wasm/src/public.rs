@@ -0,0 +1,342 @@
+//! Normalized responses for public REST endpoints
+//!
+//! Kraken's REST API keys results by legacy pair names (`XXBTZUSD`) and
+//! sends every number as a string inside positional arrays. The typed
+//! [`WasmRestClient`](crate::WasmRestClient) methods turn those into plain
+//! objects keyed by the symbol the caller asked for:
+//!
+//! ```javascript
+//! const [btc] = await client.get_tickers(['BTC/USD']);
+//! // { symbol: "BTC/USD", pair: "XXBTZUSD", bid: 42000.1, ask: 42000.2, last: 42000.1, ... }
+//! ```
+//!
+//! Symbols may be given in WebSocket form (`"BTC/USD"`) or as REST pair
+//! names (`"XBTUSD"`); results are matched back whichever name Kraken
+//! answers with.
+
+use crate::error::{ErrorCategory, WasmError};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Ticker for one pair
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Ticker {
+    /// Symbol as requested (e.g. "BTC/USD")
+    pub symbol: String,
+    /// Pair name Kraken answered with (e.g. "XXBTZUSD")
+    pub pair: String,
+    /// Best bid price
+    pub bid: f64,
+    /// Quantity at the best bid
+    pub bid_qty: f64,
+    /// Best ask price
+    pub ask: f64,
+    /// Quantity at the best ask
+    pub ask_qty: f64,
+    /// Last trade price
+    pub last: f64,
+    /// Last trade quantity
+    pub last_qty: f64,
+    /// Today's opening price
+    pub open: f64,
+    /// 24h high
+    pub high: f64,
+    /// 24h low
+    pub low: f64,
+    /// 24h volume
+    pub volume: f64,
+    /// 24h volume-weighted average price
+    pub vwap: f64,
+    /// 24h trade count
+    pub trades: u64,
+}
+
+/// One price level of a REST orderbook
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BookLevel {
+    /// Price
+    pub price: f64,
+    /// Quantity
+    pub qty: f64,
+    /// Unix time of the level's last update
+    pub timestamp: f64,
+}
+
+/// Orderbook for one pair
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Book {
+    /// Symbol as requested
+    pub symbol: String,
+    /// Pair name Kraken answered with
+    pub pair: String,
+    /// Bids, best first
+    pub bids: Vec<BookLevel>,
+    /// Asks, best first
+    pub asks: Vec<BookLevel>,
+}
+
+/// One OHLC candle
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Candle {
+    /// Unix time the interval starts
+    pub time: i64,
+    /// Opening price
+    pub open: f64,
+    /// Highest price
+    pub high: f64,
+    /// Lowest price
+    pub low: f64,
+    /// Closing price
+    pub close: f64,
+    /// Volume-weighted average price
+    pub vwap: f64,
+    /// Volume
+    pub volume: f64,
+    /// Trade count
+    pub trades: u64,
+}
+
+/// OHLC candles for one pair
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Candles {
+    /// Symbol as requested
+    pub symbol: String,
+    /// Pair name Kraken answered with
+    pub pair: String,
+    /// Candles, oldest first
+    pub candles: Vec<Candle>,
+    /// Cursor to pass as `since` to fetch newer candles
+    pub last: Option<i64>,
+}
+
+/// REST pair name for a symbol, e.g. `"BTC/USD"` → `"XBTUSD"`
+///
+/// Names without a slash are assumed to be REST names already.
+pub fn rest_pair_name(symbol: &str) -> String {
+    let Some((base, quote)) = symbol.split_once('/') else {
+        return symbol.to_string();
+    };
+    let rest_asset = |asset: &str| match asset {
+        "BTC" => "XBT".to_string(),
+        "DOGE" => "XDG".to_string(),
+        other => other.to_string(),
+    };
+    format!("{}{}", rest_asset(base), rest_asset(quote))
+}
+
+/// `XXBTZUSD` → `XBTUSD`; other names are returned unchanged
+fn short_pair_name(key: &str) -> String {
+    let bytes = key.as_bytes();
+    let prefixed = |b: u8| b == b'X' || b == b'Z';
+    if bytes.len() == 8 && prefixed(bytes[0]) && prefixed(bytes[4]) {
+        format!("{}{}", &key[1..4], &key[5..])
+    } else {
+        key.to_string()
+    }
+}
+
+/// Requested symbol a result key belongs to
+///
+/// With a single symbol requested, any key is taken to be its answer, so
+/// pairs Kraken renames in ways not covered here still resolve.
+fn match_symbol<'a>(key: &str, symbols: &'a [String]) -> Option<&'a String> {
+    let short = short_pair_name(key);
+    symbols
+        .iter()
+        .find(|symbol| {
+            let rest = rest_pair_name(symbol);
+            rest == key || rest == short || symbol.as_str() == key
+        })
+        .or(match symbols {
+            [only] => Some(only),
+            _ => None,
+        })
+}
+
+/// Pair entries of a `result` object, skipping the `last` cursor
+fn pair_entries(result: &Value) -> Result<impl Iterator<Item = (&String, &Value)>, WasmError> {
+    let entries = result
+        .as_object()
+        .ok_or_else(|| WasmError::parse("Result is not an object"))?;
+    Ok(entries.iter().filter(|(key, _)| key.as_str() != "last"))
+}
+
+fn number(value: Option<&Value>) -> Option<f64> {
+    match value? {
+        Value::String(s) => s.parse().ok(),
+        other => other.as_f64(),
+    }
+}
+
+fn malformed(what: &str, value: &Value) -> WasmError {
+    WasmError::parse(format!("Malformed {}: {}", what, value))
+}
+
+/// Parse the `result` of `/0/public/Ticker`, in the order of `symbols`
+///
+/// Pairs Kraken returned that weren't requested are dropped. Requested
+/// symbols without a ticker fail the call with a `missing_symbols` error
+/// naming them, rather than leaving the caller to notice the short array.
+pub fn parse_tickers(result: &Value, symbols: &[String]) -> Result<Vec<Ticker>, WasmError> {
+    let mut tickers = Vec::with_capacity(symbols.len());
+    for (key, fields) in pair_entries(result)? {
+        let Some(symbol) = match_symbol(key, symbols) else {
+            continue;
+        };
+        let at = |field: &str, index: usize| {
+            number(fields.get(field).and_then(|v| v.get(index))).ok_or_else(|| malformed("ticker", fields))
+        };
+        tickers.push(Ticker {
+            symbol: symbol.clone(),
+            pair: key.clone(),
+            bid: at("b", 0)?,
+            bid_qty: at("b", 2)?,
+            ask: at("a", 0)?,
+            ask_qty: at("a", 2)?,
+            last: at("c", 0)?,
+            last_qty: at("c", 1)?,
+            open: number(fields.get("o")).ok_or_else(|| malformed("ticker", fields))?,
+            high: at("h", 1)?,
+            low: at("l", 1)?,
+            volume: at("v", 1)?,
+            vwap: at("p", 1)?,
+            trades: fields.get("t").and_then(|t| t.get(1)).and_then(Value::as_u64).unwrap_or_default(),
+        });
+    }
+    let missing: Vec<&str> = symbols
+        .iter()
+        .filter(|symbol| !tickers.iter().any(|t| t.symbol == **symbol))
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return Err(WasmError::new(
+            ErrorCategory::Api,
+            "missing_symbols",
+            format!("No ticker returned for {}", missing.join(", ")),
+            false,
+        ));
+    }
+    tickers.sort_by_key(|t| symbols.iter().position(|s| *s == t.symbol));
+    Ok(tickers)
+}
+
+/// Parse the `result` of `/0/public/Depth` for `symbol`
+pub fn parse_book(result: &Value, symbol: &str) -> Result<Book, WasmError> {
+    let symbols = [symbol.to_string()];
+    let (key, book) = pair_entries(result)?
+        .find(|(key, _)| match_symbol(key, &symbols).is_some())
+        .ok_or_else(|| WasmError::parse(format!("Depth result has no book for {}", symbol)))?;
+    let side = |name: &str| -> Result<Vec<BookLevel>, WasmError> {
+        let rows = book.get(name).and_then(Value::as_array).ok_or_else(|| malformed("book", book))?;
+        rows.iter()
+            .map(|row| {
+                let field = |index: usize| number(row.get(index)).ok_or_else(|| malformed("book level", row));
+                Ok(BookLevel {
+                    price: field(0)?,
+                    qty: field(1)?,
+                    timestamp: field(2)?,
+                })
+            })
+            .collect()
+    };
+    Ok(Book {
+        symbol: symbol.to_string(),
+        pair: key.clone(),
+        bids: side("bids")?,
+        asks: side("asks")?,
+    })
+}
+
+/// Parse the `result` of `/0/public/OHLC` for `symbol`
+pub fn parse_candles(result: &Value, symbol: &str) -> Result<Candles, WasmError> {
+    let symbols = [symbol.to_string()];
+    let (key, rows) = pair_entries(result)?
+        .find(|(key, _)| match_symbol(key, &symbols).is_some())
+        .ok_or_else(|| WasmError::parse(format!("OHLC result has no candles for {}", symbol)))?;
+    let rows = rows.as_array().ok_or_else(|| malformed("OHLC", rows))?;
+    let candles = rows
+        .iter()
+        .map(|row| {
+            let field = |index: usize| number(row.get(index)).ok_or_else(|| malformed("candle", row));
+            Ok(Candle {
+                time: row.get(0).and_then(Value::as_i64).ok_or_else(|| malformed("candle", row))?,
+                open: field(1)?,
+                high: field(2)?,
+                low: field(3)?,
+                close: field(4)?,
+                vwap: field(5)?,
+                volume: field(6)?,
+                trades: row.get(7).and_then(Value::as_u64).unwrap_or_default(),
+            })
+        })
+        .collect::<Result<_, WasmError>>()?;
+    Ok(Candles {
+        symbol: symbol.to_string(),
+        pair: key.clone(),
+        candles,
+        last: result.get("last").and_then(Value::as_i64),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ticker(bid: &str) -> Value {
+        serde_json::json!({
+            "a": ["42000.2", "1", "1.500"], "b": [bid, "2", "2.000"], "c": ["42000.1", "0.01"],
+            "v": ["10", "250.5"], "p": ["41900", "41950.5"], "t": [100, 4200],
+            "l": ["41000", "40500"], "h": ["42500", "43000"], "o": "41800.0"
+        })
+    }
+
+    #[test]
+    fn test_tickers_match_legacy_pair_names() {
+        let result = serde_json::json!({ "XETHZUSD": ticker("2500.0"), "XXBTZUSD": ticker("42000.1") });
+        let symbols = vec!["BTC/USD".to_string(), "ETHUSD".to_string()];
+        let tickers = parse_tickers(&result, &symbols).unwrap();
+
+        assert_eq!(tickers.len(), 2);
+        assert_eq!(tickers[0].symbol, "BTC/USD");
+        assert_eq!(tickers[0].pair, "XXBTZUSD");
+        assert_eq!(tickers[0].bid, 42000.1);
+        assert_eq!(tickers[0].ask_qty, 1.5);
+        assert_eq!(tickers[0].high, 43000.0);
+        assert_eq!(tickers[0].trades, 4200);
+        assert_eq!(tickers[1].symbol, "ETHUSD");
+        assert_eq!(tickers[1].bid, 2500.0);
+    }
+
+    #[test]
+    fn test_unanswered_symbols_are_reported() {
+        let result = serde_json::json!({ "XXBTZUSD": ticker("42000.1") });
+        let symbols = vec!["BTC/USD".to_string(), "ETH/USD".to_string(), "SOL/USD".to_string()];
+        let error = parse_tickers(&result, &symbols).unwrap_err();
+        assert_eq!(error.code, "missing_symbols");
+        assert_eq!(error.category, ErrorCategory::Api);
+        assert!(error.message.ends_with("ETH/USD, SOL/USD"));
+    }
+
+    #[test]
+    fn test_book_and_candles_are_numeric() {
+        let depth = serde_json::json!({ "XXBTZUSD": {
+            "bids": [["42000.1", "0.5", 1704067200]],
+            "asks": [["42000.2", "1.25", 1704067201], ["42001.0", "3", 1704067202]]
+        }});
+        let book = parse_book(&depth, "BTC/USD").unwrap();
+        assert_eq!(book.bids[0], BookLevel { price: 42000.1, qty: 0.5, timestamp: 1704067200.0 });
+        assert_eq!(book.asks.len(), 2);
+
+        let ohlc = serde_json::json!({
+            "XXBTZUSD": [[1704067200, "42000.0", "42100.0", "41900.0", "42050.0", "42010.0", "12.5", 340]],
+            "last": 1704067200
+        });
+        let candles = parse_candles(&ohlc, "BTC/USD").unwrap();
+        assert_eq!(candles.candles[0].close, 42050.0);
+        assert_eq!(candles.candles[0].trades, 340);
+        assert_eq!(candles.last, Some(1704067200));
+
+        let err = parse_book(&serde_json::json!({ "XXBTZUSD": { "bids": "x" } }), "BTC/USD").unwrap_err();
+        assert_eq!(err.category, ErrorCategory::Parse);
+    }
+}
@@ -0,0 +1,279 @@
+//! Token buckets and a priority queue behind [`WasmRateLimiter`](crate::WasmRateLimiter)
+//!
+//! Kept free of JS types and clocks so the scheduling rules are plain Rust:
+//! every method takes the current time in milliseconds.
+//!
+//! Each named bucket has its own queue, so a backlog of private calls never
+//! holds up public ones. Within a bucket, waiters are served by priority and
+//! then in arrival order.
+
+use std::collections::HashMap;
+
+/// Bucket used by the single-bucket `WasmRateLimiter` API
+pub const DEFAULT_BUCKET: &str = "default";
+
+/// Bucket limits that could never grant a token
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvalidLimits {
+    /// Capacity below one token (or not finite)
+    Capacity,
+    /// Refill rate not positive (or not finite)
+    RefillRate,
+}
+
+impl InvalidLimits {
+    /// Stable error code
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Capacity => "invalid_capacity",
+            Self::RefillRate => "invalid_refill_rate",
+        }
+    }
+
+    /// Human-readable reason
+    pub fn message(&self) -> &'static str {
+        match self {
+            Self::Capacity => "capacity must be at least 1",
+            Self::RefillRate => "refill_rate must be positive",
+        }
+    }
+}
+
+/// Token bucket refilled continuously over time
+#[derive(Debug, Clone)]
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_rate: f64,
+    last_refill: f64,
+}
+
+impl TokenBucket {
+    /// Create a full bucket; `refill_rate` is tokens per second
+    ///
+    /// A bucket that can't hold a whole token or never refills would keep
+    /// its waiters queued forever, re-arming the wake timer without delay.
+    pub fn new(capacity: f64, refill_rate: f64, now_ms: f64) -> Result<Self, InvalidLimits> {
+        if !capacity.is_finite() || capacity < 1.0 {
+            return Err(InvalidLimits::Capacity);
+        }
+        if !refill_rate.is_finite() || refill_rate <= 0.0 {
+            return Err(InvalidLimits::RefillRate);
+        }
+        Ok(Self {
+            capacity,
+            tokens: capacity,
+            refill_rate,
+            last_refill: now_ms,
+        })
+    }
+
+    fn refill(&mut self, now_ms: f64) {
+        let elapsed_secs = (now_ms - self.last_refill) / 1000.0;
+        if elapsed_secs > 0.0 {
+            self.tokens = (self.tokens + elapsed_secs * self.refill_rate).min(self.capacity);
+            self.last_refill = now_ms;
+        }
+    }
+
+    /// Take a token if one is available
+    pub fn try_take(&mut self, now_ms: f64) -> bool {
+        self.refill(now_ms);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Tokens available now
+    pub fn available(&mut self, now_ms: f64) -> f64 {
+        self.refill(now_ms);
+        self.tokens
+    }
+
+    /// Milliseconds until a token is available, 0 if one is available now
+    pub fn wait_ms(&mut self, now_ms: f64) -> f64 {
+        self.refill(now_ms);
+        if self.tokens >= 1.0 {
+            0.0
+        } else {
+            (1.0 - self.tokens) / self.refill_rate * 1000.0
+        }
+    }
+
+    /// Maximum number of tokens
+    pub fn capacity(&self) -> f64 {
+        self.capacity
+    }
+
+    /// Refill to capacity
+    pub fn reset(&mut self, now_ms: f64) {
+        self.tokens = self.capacity;
+        self.last_refill = now_ms;
+    }
+}
+
+#[derive(Debug)]
+struct Waiter<T> {
+    priority: u8,
+    seq: u64,
+    item: T,
+}
+
+#[derive(Debug)]
+struct Lane<T> {
+    bucket: TokenBucket,
+    waiting: Vec<Waiter<T>>,
+}
+
+/// Named token buckets, each with a priority queue of waiters
+#[derive(Debug)]
+pub struct RateScheduler<T> {
+    lanes: HashMap<String, Lane<T>>,
+    next_seq: u64,
+}
+
+impl<T> Default for RateScheduler<T> {
+    fn default() -> Self {
+        Self {
+            lanes: HashMap::new(),
+            next_seq: 0,
+        }
+    }
+}
+
+impl<T> RateScheduler<T> {
+    /// Create a scheduler without buckets
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a bucket, replacing any bucket of the same name but keeping its waiters
+    pub fn add_bucket(&mut self, name: &str, capacity: f64, refill_rate: f64, now_ms: f64) -> Result<(), InvalidLimits> {
+        let bucket = TokenBucket::new(capacity, refill_rate, now_ms)?;
+        match self.lanes.get_mut(name) {
+            Some(lane) => lane.bucket = bucket,
+            None => {
+                self.lanes.insert(name.to_string(), Lane { bucket, waiting: Vec::new() });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if a bucket exists
+    pub fn has_bucket(&self, name: &str) -> bool {
+        self.lanes.contains_key(name)
+    }
+
+    /// Mutable access to a bucket
+    pub fn bucket_mut(&mut self, name: &str) -> Option<&mut TokenBucket> {
+        self.lanes.get_mut(name).map(|lane| &mut lane.bucket)
+    }
+
+    /// Take a token immediately, without queueing
+    ///
+    /// Fails while others are queued on the bucket, so callers can't jump
+    /// the queue. Returns false for unknown buckets.
+    pub fn try_acquire(&mut self, name: &str, now_ms: f64) -> bool {
+        match self.lanes.get_mut(name) {
+            Some(lane) if lane.waiting.is_empty() => lane.bucket.try_take(now_ms),
+            _ => false,
+        }
+    }
+
+    /// Queue an item on a bucket; lower `priority` values are served first
+    ///
+    /// Returns the item back if the bucket doesn't exist.
+    pub fn enqueue(&mut self, name: &str, priority: u8, item: T) -> Result<(), T> {
+        let Some(lane) = self.lanes.get_mut(name) else {
+            return Err(item);
+        };
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        lane.waiting.push(Waiter { priority, seq, item });
+        Ok(())
+    }
+
+    /// Remove and return every queued item that can run now
+    ///
+    /// Items come out in priority and then arrival order per bucket.
+    pub fn drain_ready(&mut self, now_ms: f64) -> Vec<T> {
+        let mut ready = Vec::new();
+        for lane in self.lanes.values_mut() {
+            lane.waiting.sort_by_key(|w| (w.priority, w.seq));
+            let mut granted = 0;
+            while granted < lane.waiting.len() && lane.bucket.try_take(now_ms) {
+                granted += 1;
+            }
+            ready.extend(lane.waiting.drain(..granted).map(|w| w.item));
+        }
+        ready
+    }
+
+    /// Milliseconds until the next queued item can run, if any are queued
+    pub fn next_wake_ms(&mut self, now_ms: f64) -> Option<f64> {
+        self.lanes
+            .values_mut()
+            .filter(|lane| !lane.waiting.is_empty())
+            .map(|lane| lane.bucket.wait_ms(now_ms))
+            .min_by(|a, b| a.total_cmp(b))
+    }
+
+    /// Number of queued items across all buckets
+    pub fn queued(&self) -> usize {
+        self.lanes.values().map(|lane| lane.waiting.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_then_fifo_within_bucket() {
+        let mut scheduler = RateScheduler::new();
+        scheduler.add_bucket("public", 1.0, 1.0, 0.0).unwrap();
+        assert!(scheduler.try_acquire("public", 0.0));
+
+        scheduler.enqueue("public", 2, "low").unwrap();
+        scheduler.enqueue("public", 1, "normal-1").unwrap();
+        scheduler.enqueue("public", 0, "high").unwrap();
+        scheduler.enqueue("public", 1, "normal-2").unwrap();
+        assert!(scheduler.drain_ready(0.0).is_empty());
+        assert!(!scheduler.try_acquire("public", 5000.0), "queue must not be jumped");
+
+        let mut order = Vec::new();
+        let mut now = 0.0;
+        while scheduler.queued() > 0 {
+            now += scheduler.next_wake_ms(now).unwrap().max(1.0);
+            order.extend(scheduler.drain_ready(now));
+        }
+        assert_eq!(order, vec!["high", "normal-1", "normal-2", "low"]);
+    }
+
+    #[test]
+    fn test_buckets_do_not_block_each_other() {
+        let mut scheduler = RateScheduler::new();
+        scheduler.add_bucket("public", 1.0, 0.5, 0.0).unwrap();
+        scheduler.add_bucket("private", 1.0, 0.5, 0.0).unwrap();
+        assert!(scheduler.try_acquire("private", 0.0));
+
+        scheduler.enqueue("private", 0, "order").unwrap();
+        scheduler.enqueue("public", 1, "ticker").unwrap();
+        assert_eq!(scheduler.drain_ready(0.0), vec!["ticker"]);
+        assert_eq!(scheduler.next_wake_ms(0.0), Some(2000.0));
+        assert_eq!(scheduler.enqueue("missing", 0, "x"), Err("x"));
+    }
+
+    #[test]
+    fn test_limits_that_never_grant_are_rejected() {
+        let mut scheduler = RateScheduler::<()>::new();
+        assert_eq!(scheduler.add_bucket("public", 15.0, 0.0, 0.0), Err(InvalidLimits::RefillRate));
+        assert_eq!(scheduler.add_bucket("public", 15.0, -1.0, 0.0), Err(InvalidLimits::RefillRate));
+        assert_eq!(scheduler.add_bucket("public", 0.5, 1.0, 0.0), Err(InvalidLimits::Capacity));
+        assert_eq!(scheduler.add_bucket("public", f64::NAN, 1.0, 0.0), Err(InvalidLimits::Capacity));
+        assert!(!scheduler.has_bucket("public"));
+    }
+}
@@ -0,0 +1,184 @@
+//! Host runtime access that works outside the browser
+//!
+//! The bindings only rely on globals every supported runtime provides, so
+//! the same build runs in browsers, web workers, Node.js 18+ (whose global
+//! `fetch` is undici) and Electron:
+//!
+//! | Global | Used by | Browser | Worker | Node.js |
+//! |--------|---------|---------|--------|---------|
+//! | `fetch` | `WasmRestClient` | yes | yes | 18+ |
+//! | `setTimeout` | `WasmRateLimiter` queue | yes | yes | yes |
+//! | `indexedDB` | `WasmIndexedDbStore` | yes | yes | no |
+//!
+//! Nothing here touches `window`, which doesn't exist in workers or Node.
+
+use crate::error::{ErrorCategory, WasmError};
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use web_sys::{IdbFactory, Request};
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(catch, js_name = fetch)]
+    fn global_fetch(request: &Request) -> Result<js_sys::Promise, JsValue>;
+
+    #[wasm_bindgen(catch, js_name = setTimeout)]
+    fn global_set_timeout(handler: &js_sys::Function, timeout: i32) -> Result<JsValue, JsValue>;
+}
+
+/// Kind of JavaScript host the module is running in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Runtime {
+    /// Browser main thread (including Electron renderers)
+    Browser,
+    /// Web, shared or service worker
+    Worker,
+    /// Node.js (including the Electron main process)
+    Node,
+    /// Anything else (Deno, Bun, embedded engines)
+    Unknown,
+}
+
+/// The globals that tell the runtimes apart
+#[derive(Debug, Clone, Copy, Default)]
+struct Globals {
+    window: bool,
+    document: bool,
+    import_scripts: bool,
+    /// `process.versions.node` is a string
+    node_version: bool,
+}
+
+impl Globals {
+    fn read() -> Self {
+        let global = js_sys::global();
+        let has = |name: &str| {
+            js_sys::Reflect::get(&global, &JsValue::from_str(name)).is_ok_and(|value| !value.is_undefined())
+        };
+        Self {
+            window: has("window"),
+            document: has("document"),
+            import_scripts: has("importScripts"),
+            node_version: js_sys::Reflect::get(&global, &JsValue::from_str("process"))
+                .and_then(|process| js_sys::Reflect::get(&process, &JsValue::from_str("versions")))
+                .and_then(|versions| js_sys::Reflect::get(&versions, &JsValue::from_str("node")))
+                .is_ok_and(|node| node.is_string()),
+        }
+    }
+}
+
+impl Runtime {
+    /// Detect the current runtime from its globals
+    pub fn detect() -> Self {
+        Self::from_globals(Globals::read())
+    }
+
+    fn from_globals(globals: Globals) -> Self {
+        if globals.window && globals.document {
+            Runtime::Browser
+        } else if globals.import_scripts {
+            Runtime::Worker
+        } else if globals.node_version {
+            Runtime::Node
+        } else {
+            Runtime::Unknown
+        }
+    }
+
+    /// Lowercase name reported to JavaScript
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Runtime::Browser => "browser",
+            Runtime::Worker => "worker",
+            Runtime::Node => "node",
+            Runtime::Unknown => "unknown",
+        }
+    }
+}
+
+/// Name of the runtime the module is running in
+///
+/// One of `"browser"`, `"worker"`, `"node"` or `"unknown"`.
+#[wasm_bindgen(js_name = runtime)]
+pub fn runtime_name() -> String {
+    Runtime::detect().as_str().to_string()
+}
+
+/// Start a request with the global `fetch`
+pub fn fetch(request: &Request) -> Result<js_sys::Promise, WasmError> {
+    global_fetch(request).map_err(|_| fetch_unavailable(Runtime::detect()))
+}
+
+fn fetch_unavailable(runtime: Runtime) -> WasmError {
+    WasmError::new(
+        ErrorCategory::Internal,
+        "fetch_unavailable",
+        format!("No global fetch in this runtime ({}); Node.js 18+ is required", runtime.as_str()),
+        false,
+    )
+}
+
+/// Run `handler` once after `timeout_ms`; returns false if no timer is available
+pub fn set_timeout(handler: &js_sys::Function, timeout_ms: i32) -> bool {
+    global_set_timeout(handler, timeout_ms).is_ok()
+}
+
+/// The global IndexedDB factory, if the runtime has one
+pub fn indexed_db() -> Result<IdbFactory, WasmError> {
+    js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("indexedDB"))
+        .ok()
+        .and_then(|factory| factory.dyn_into::<IdbFactory>().ok())
+        .ok_or_else(|| indexed_db_unavailable(Runtime::detect()))
+}
+
+fn indexed_db_unavailable(runtime: Runtime) -> WasmError {
+    WasmError::storage(format!("IndexedDB is not available in this runtime ({})", runtime.as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_from_globals() {
+        let browser = Globals { window: true, document: true, ..Globals::default() };
+        assert_eq!(Runtime::from_globals(browser), Runtime::Browser);
+        let worker = Globals { import_scripts: true, ..Globals::default() };
+        assert_eq!(Runtime::from_globals(worker), Runtime::Worker);
+        let node = Globals { node_version: true, ..Globals::default() };
+        assert_eq!(Runtime::from_globals(node), Runtime::Node);
+        assert_eq!(Runtime::from_globals(Globals::default()), Runtime::Unknown);
+    }
+
+    #[test]
+    fn test_runtime_detection_precedence() {
+        // Electron renderers expose both the DOM and Node's process
+        let electron =
+            Globals { window: true, document: true, import_scripts: false, node_version: true };
+        assert_eq!(Runtime::from_globals(electron), Runtime::Browser);
+        // A `window` without a document (jsdom-less shims, Deno) isn't a browser
+        let shimmed = Globals { window: true, node_version: true, ..Globals::default() };
+        assert_eq!(Runtime::from_globals(shimmed), Runtime::Node);
+        let deno = Globals { window: true, ..Globals::default() };
+        assert_eq!(Runtime::from_globals(deno), Runtime::Unknown);
+    }
+
+    #[test]
+    fn test_names_and_unavailable_errors() {
+        let names: Vec<_> = [Runtime::Browser, Runtime::Worker, Runtime::Node, Runtime::Unknown]
+            .into_iter()
+            .map(Runtime::as_str)
+            .collect();
+        assert_eq!(names, ["browser", "worker", "node", "unknown"]);
+
+        let error = fetch_unavailable(Runtime::Unknown);
+        assert_eq!(error.code, "fetch_unavailable");
+        assert_eq!(error.category, ErrorCategory::Internal);
+        assert!(error.message.contains("(unknown)"));
+        assert!(!error.retryable);
+
+        let error = indexed_db_unavailable(Runtime::Node);
+        assert_eq!(error.category, ErrorCategory::Storage);
+        assert!(error.message.contains("(node)"));
+    }
+}
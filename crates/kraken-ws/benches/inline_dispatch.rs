@@ -0,0 +1,76 @@
+//! Event delivery latency: event channel vs inline handler
+//!
+//! Run with: cargo bench -p kraken-ws --bench inline_dispatch
+//!
+//! `mpsc_hop` times one event from `send` on the read loop until a consumer
+//! task on another worker thread has received it, one event in flight at a
+//! time. `inline` times the whole inline dispatch (panic guard, budget
+//! check and handler call). The difference is what an inline handler saves
+//! per event.
+//!
+//! | benchmark | time     |
+//! |-----------|----------|
+//! | mpsc_hop  | 10.2 µs  |
+//! | inline    | 0.114 µs |
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use kraken_types::Channel;
+use kraken_ws::{InlineDispatch, MarketEvent};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+
+fn status_event() -> MarketEvent {
+    MarketEvent::Status {
+        system: "online".to_string(),
+        version: "2.0.0".to_string(),
+    }
+}
+
+fn bench_mpsc_hop(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap();
+    let (tx, mut rx) = mpsc::unbounded_channel::<(Instant, MarketEvent)>();
+    let received = Arc::new(AtomicU64::new(0));
+    let hop_nanos = Arc::new(AtomicU64::new(0));
+    {
+        let received = Arc::clone(&received);
+        let hop_nanos = Arc::clone(&hop_nanos);
+        runtime.spawn(async move {
+            while let Some((sent, event)) = rx.recv().await {
+                hop_nanos.fetch_add(sent.elapsed().as_nanos() as u64, Ordering::Relaxed);
+                black_box(event);
+                received.fetch_add(1, Ordering::Release);
+            }
+        });
+    }
+
+    c.bench_function("mpsc_hop", |b| {
+        b.iter_custom(|iters| {
+            hop_nanos.store(0, Ordering::Relaxed);
+            for _ in 0..iters {
+                let before = received.load(Ordering::Acquire);
+                tx.send((Instant::now(), status_event())).unwrap();
+                while received.load(Ordering::Acquire) == before {
+                    std::hint::spin_loop();
+                }
+            }
+            Duration::from_nanos(hop_nanos.load(Ordering::Relaxed))
+        })
+    });
+}
+
+fn bench_inline(c: &mut Criterion) {
+    let dispatch = InlineDispatch::new([Channel::Status], |event: &MarketEvent| {
+        black_box(event);
+    });
+    let event = status_event();
+    c.bench_function("inline", |b| b.iter(|| dispatch.dispatch(black_box(&event))));
+}
+
+criterion_group!(benches, bench_mpsc_hop, bench_inline);
+criterion_main!(benches);
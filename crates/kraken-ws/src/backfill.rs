@@ -0,0 +1,370 @@
+//! REST backfill for the trade and OHLC channels
+//!
+//! Neither channel replays what printed while the connection was down, and
+//! OHLC only starts at the current candle. With a [`Backfiller`] configured
+//! (`ConnectionConfig::with_backfill`), every connect fetches the missing
+//! window for each trade and OHLC subscription it covers. A symbol's live
+//! events are held back until its fetch completes; the history then goes out
+//! first, followed by the held live events, with the usual event ids and
+//! per-symbol `seq`:
+//!
+//! | Channel | Fetched from | Deduplicated by |
+//! |---------|--------------|-----------------|
+//! | Trade | the last trade delivered (nothing before the first one) | `trade_id` |
+//! | OHLC | the last candle delivered, or the backfiller's default window | `interval_begin`, live candles win |
+//!
+//! The backfiller does the HTTP itself, so any client can be plugged in;
+//! `kraken-sdk` provides one for Kraken's REST `Trades` and `OHLC`
+//! endpoints. A fetch that fails or exceeds the timeout releases the held
+//! events anyway, so REST trouble never stalls the live feed.
+
+use crate::latency::parse_exchange_timestamp;
+use kraken_types::{Channel, KrakenError, OhlcData, TradeData};
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default time allowed for one fetch before the held events are released
+pub const DEFAULT_BACKFILL_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Last trade delivered for a symbol
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TradeCursor {
+    /// Trade ID
+    pub trade_id: u64,
+    /// Trade timestamp (RFC 3339)
+    pub timestamp: String,
+}
+
+/// Window a [`Backfiller`] is asked to fetch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackfillRequest {
+    /// Trades printed after `after`
+    Trades {
+        /// Trading pair symbol
+        symbol: String,
+        /// Last trade delivered
+        after: TradeCursor,
+    },
+    /// Candles from `since` on
+    Ohlc {
+        /// Trading pair symbol
+        symbol: String,
+        /// Candle interval in minutes
+        interval: u32,
+        /// Start of the last candle delivered (Unix seconds), or `None` for
+        /// the backfiller's default history
+        since: Option<i64>,
+    },
+}
+
+impl BackfillRequest {
+    /// Trading pair symbol
+    pub fn symbol(&self) -> &str {
+        match self {
+            Self::Trades { symbol, .. } | Self::Ohlc { symbol, .. } => symbol,
+        }
+    }
+}
+
+/// History returned by a [`Backfiller`]
+#[derive(Debug, Clone)]
+pub enum BackfillData {
+    /// Trades, in any order
+    Trades(Vec<TradeData>),
+    /// Candles, in any order
+    Ohlc(Vec<OhlcData>),
+}
+
+type BackfillFuture = Pin<Box<dyn Future<Output = Result<BackfillData, KrakenError>> + Send>>;
+type BackfillFn = dyn Fn(BackfillRequest) -> BackfillFuture + Send + Sync;
+
+/// Fetches the history a connection missed
+#[derive(Clone)]
+pub struct Backfiller {
+    fetch: Arc<BackfillFn>,
+    channels: Vec<Channel>,
+    timeout: Duration,
+}
+
+impl Backfiller {
+    /// Backfill `channels` (trade and/or ohlc) with an async fetch function
+    pub fn new<F, Fut>(channels: impl IntoIterator<Item = Channel>, fetch: F) -> Self
+    where
+        F: Fn(BackfillRequest) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<BackfillData, KrakenError>> + Send + 'static,
+    {
+        Self {
+            fetch: Arc::new(move |request| Box::pin(fetch(request))),
+            channels: channels
+                .into_iter()
+                .filter(|c| matches!(c, Channel::Trade | Channel::Ohlc))
+                .collect(),
+            timeout: DEFAULT_BACKFILL_TIMEOUT,
+        }
+    }
+
+    /// Release held events if a fetch takes longer than `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Whether this backfiller covers a channel
+    pub fn covers(&self, channel: Channel) -> bool {
+        self.channels.contains(&channel)
+    }
+
+    /// Fetch one window, failing with [`KrakenError::ConnectionTimeout`]
+    /// after the timeout
+    pub async fn fetch(&self, request: BackfillRequest) -> Result<BackfillData, KrakenError> {
+        let url = format!("REST backfill for {}", request.symbol());
+        tokio::time::timeout(self.timeout, (self.fetch)(request))
+            .await
+            .map_err(|_| KrakenError::ConnectionTimeout {
+                url,
+                timeout: self.timeout,
+            })?
+    }
+}
+
+impl std::fmt::Debug for Backfiller {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Backfiller")
+            .field("channels", &self.channels)
+            .field("timeout", &self.timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Default)]
+struct TradeTape {
+    last: Option<TradeCursor>,
+    held: Option<Vec<TradeData>>,
+}
+
+#[derive(Debug, Default)]
+struct CandleTape {
+    /// `interval_begin` of the newest candle delivered (Unix microseconds)
+    last: Option<i64>,
+    held: Option<Vec<OhlcData>>,
+}
+
+/// Events released once a fetch completes
+#[derive(Debug, Clone)]
+pub(crate) enum Released {
+    Trades(Vec<TradeData>),
+    Ohlc(Vec<OhlcData>),
+}
+
+/// Holds live trades and candles while their history is fetched
+#[derive(Debug, Default)]
+pub(crate) struct BackfillTracker {
+    trades: HashMap<String, TradeTape>,
+    candles: HashMap<(String, u32), CandleTape>,
+}
+
+fn candle_start(candle: &OhlcData) -> Option<i64> {
+    parse_exchange_timestamp(&candle.interval_begin)
+}
+
+impl BackfillTracker {
+    /// Start holding live data for a connect and return what to fetch
+    ///
+    /// Trades are only fetched for symbols that delivered one before: with
+    /// nothing to resume from, the subscription snapshot is the history.
+    pub(crate) fn begin(&mut self, trades: &[String], candles: &[(String, u32)]) -> Vec<BackfillRequest> {
+        let mut requests = Vec::new();
+        for symbol in trades {
+            let tape = self.trades.entry(symbol.clone()).or_default();
+            if let Some(after) = tape.last.clone() {
+                tape.held.get_or_insert_with(Vec::new);
+                requests.push(BackfillRequest::Trades {
+                    symbol: symbol.clone(),
+                    after,
+                });
+            }
+        }
+        for (symbol, interval) in candles {
+            let tape = self.candles.entry((symbol.clone(), *interval)).or_default();
+            tape.held.get_or_insert_with(Vec::new);
+            requests.push(BackfillRequest::Ohlc {
+                symbol: symbol.clone(),
+                interval: *interval,
+                since: tape.last.map(|us| us.div_euclid(1_000_000)),
+            });
+        }
+        requests
+    }
+
+    /// Admit a live trade: `None` while held or if already delivered
+    pub(crate) fn admit_trade(&mut self, trade: TradeData) -> Option<TradeData> {
+        let tape = self.trades.entry(trade.symbol.clone()).or_default();
+        if let Some(held) = &mut tape.held {
+            held.push(trade);
+            return None;
+        }
+        Self::release_trades(tape, vec![trade]).pop()
+    }
+
+    /// Admit a live candle: `None` while held or if older than the last one
+    pub(crate) fn admit_candle(&mut self, candle: OhlcData) -> Option<OhlcData> {
+        let tape = self.candles.entry((candle.symbol.clone(), candle.interval)).or_default();
+        if let Some(held) = &mut tape.held {
+            held.push(candle);
+            return None;
+        }
+        Self::release_candles(tape, Vec::new(), vec![candle]).pop()
+    }
+
+    /// Merge a completed fetch with the held events and stop holding
+    ///
+    /// A failed fetch (`None`) releases the held events on their own.
+    pub(crate) fn complete(&mut self, request: &BackfillRequest, history: Option<BackfillData>) -> Released {
+        match request {
+            BackfillRequest::Trades { symbol, .. } => {
+                let tape = self.trades.entry(symbol.clone()).or_default();
+                let mut trades = match history {
+                    Some(BackfillData::Trades(trades)) => trades,
+                    _ => Vec::new(),
+                };
+                trades.retain(|t| t.symbol == *symbol);
+                trades.extend(tape.held.take().unwrap_or_default());
+                Released::Trades(Self::release_trades(tape, trades))
+            }
+            BackfillRequest::Ohlc { symbol, interval, .. } => {
+                let tape = self.candles.entry((symbol.clone(), *interval)).or_default();
+                let mut candles = match history {
+                    Some(BackfillData::Ohlc(candles)) => candles,
+                    _ => Vec::new(),
+                };
+                candles.retain(|c| c.symbol == *symbol && c.interval == *interval);
+                let held = tape.held.take().unwrap_or_default();
+                Released::Ohlc(Self::release_candles(tape, candles, held))
+            }
+        }
+    }
+
+    /// Trades after the last delivered one, in `trade_id` order
+    fn release_trades(tape: &mut TradeTape, mut trades: Vec<TradeData>) -> Vec<TradeData> {
+        trades.sort_by_key(|t| t.trade_id);
+        trades.dedup_by_key(|t| t.trade_id);
+        if let Some(last) = &tape.last {
+            trades.retain(|t| t.trade_id > last.trade_id);
+        }
+        if let Some(newest) = trades.last() {
+            tape.last = Some(TradeCursor {
+                trade_id: newest.trade_id,
+                timestamp: newest.timestamp.clone(),
+            });
+        }
+        trades
+    }
+
+    /// One candle per interval from the last delivered one on, live winning
+    fn release_candles(tape: &mut CandleTape, history: Vec<OhlcData>, live: Vec<OhlcData>) -> Vec<OhlcData> {
+        let mut merged: BTreeMap<i64, OhlcData> = BTreeMap::new();
+        // Later entries replace earlier ones: live after history, newest last
+        for candle in history.into_iter().chain(live) {
+            if let Some(start) = candle_start(&candle) {
+                merged.insert(start, candle);
+            }
+        }
+        if let Some(last) = tape.last {
+            merged = merged.split_off(&last);
+        }
+        if let Some((&start, _)) = merged.last_key_value() {
+            tape.last = Some(start);
+        }
+        merged.into_values().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_types::Side;
+    use rust_decimal_macros::dec;
+
+    fn trade(id: u64) -> TradeData {
+        TradeData {
+            symbol: "BTC/USD".to_string(),
+            side: Side::Buy,
+            price: dec!(100),
+            qty: dec!(1),
+            ord_type: "limit".to_string(),
+            trade_id: id,
+            timestamp: "2024-01-01T00:00:00.000000Z".to_string(),
+        }
+    }
+
+    fn candle(minute: u32, close: u32) -> OhlcData {
+        OhlcData {
+            symbol: "BTC/USD".to_string(),
+            open: dec!(100),
+            high: dec!(100),
+            low: dec!(100),
+            close: close.into(),
+            vwap: dec!(100),
+            volume: dec!(1),
+            trades: 1,
+            interval_begin: format!("2024-01-01T00:{:02}:00.000000000Z", minute),
+            interval: 1,
+        }
+    }
+
+    #[test]
+    fn test_trades_resume_after_last_delivered() {
+        let mut tracker = BackfillTracker::default();
+        // Nothing to resume from on the first connect
+        assert!(tracker.begin(&["BTC/USD".to_string()], &[]).is_empty());
+        assert_eq!(tracker.admit_trade(trade(1)).unwrap().trade_id, 1);
+        assert_eq!(tracker.admit_trade(trade(2)).unwrap().trade_id, 2);
+
+        let requests = tracker.begin(&["BTC/USD".to_string()], &[]);
+        let request = &requests[0];
+        assert!(matches!(request, BackfillRequest::Trades { after, .. } if after.trade_id == 2));
+        assert!(tracker.admit_trade(trade(5)).is_none());
+
+        let history = BackfillData::Trades(vec![trade(4), trade(2), trade(3), trade(5)]);
+        let Released::Trades(released) = tracker.complete(request, Some(history)) else {
+            panic!("expected trades");
+        };
+        let ids: Vec<u64> = released.iter().map(|t| t.trade_id).collect();
+        assert_eq!(ids, vec![3, 4, 5]);
+        assert!(tracker.admit_trade(trade(5)).is_none());
+        assert_eq!(tracker.admit_trade(trade(6)).unwrap().trade_id, 6);
+    }
+
+    #[test]
+    fn test_live_candles_win_and_failed_fetch_releases_held() {
+        let mut tracker = BackfillTracker::default();
+        let requests = tracker.begin(&[], &[("BTC/USD".to_string(), 1)]);
+        assert!(matches!(requests[0], BackfillRequest::Ohlc { since: None, .. }));
+        assert!(tracker.admit_candle(candle(2, 120)).is_none());
+        assert!(tracker.admit_candle(candle(2, 121)).is_none());
+
+        let history = BackfillData::Ohlc(vec![candle(0, 100), candle(1, 110), candle(2, 999)]);
+        let Released::Ohlc(released) = tracker.complete(&requests[0], Some(history)) else {
+            panic!("expected candles");
+        };
+        let closes: Vec<_> = released.iter().map(|c| c.close).collect();
+        assert_eq!(closes, vec![dec!(100), dec!(110), dec!(121)]);
+
+        // The current candle keeps updating; older ones are dropped
+        assert!(tracker.admit_candle(candle(1, 1)).is_none());
+        assert_eq!(tracker.admit_candle(candle(2, 122)).unwrap().close, dec!(122));
+
+        // Reconnect resumes from the last candle; a failed fetch still releases
+        let requests = tracker.begin(&[], &[("BTC/USD".to_string(), 1)]);
+        assert!(matches!(requests[0], BackfillRequest::Ohlc { since: Some(1704067320), .. }));
+        assert!(tracker.admit_candle(candle(3, 130)).is_none());
+        let Released::Ohlc(released) = tracker.complete(&requests[0], None) else {
+            panic!("expected candles");
+        };
+        assert_eq!(released.len(), 1);
+        assert_eq!(released[0].close, dec!(130));
+    }
+}
@@ -0,0 +1,284 @@
+//! Per-symbol orderbook callbacks
+//!
+//! For integrations that don't want to run their own event loop, callbacks
+//! can be registered per symbol and are invoked by the connection every time
+//! that symbol's book changes, alongside the regular event stream.
+//!
+//! Sync callbacks run inline on the connection task, each inside a panic
+//! guard; a callback that runs over its time budget is logged and counted.
+//! Async callbacks are spawned onto the runtime and cancelled once the budget
+//! elapses. A panicking callback never takes the connection down.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_ws::book_callbacks::BookCallbacks;
+//!
+//! let callbacks = BookCallbacks::new();
+//! callbacks.on_book_update("BTC/USD", |snapshot| {
+//!     println!("BTC/USD mid: {:?}", snapshot.mid_price());
+//! });
+//! assert_eq!(callbacks.count("BTC/USD"), 1);
+//! ```
+
+use kraken_book::OrderbookSnapshot;
+use parking_lot::RwLock;
+use std::collections::HashMap;
+use std::future::Future;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{error, warn};
+
+/// Default time budget for a single callback invocation
+pub const DEFAULT_CALLBACK_BUDGET: Duration = Duration::from_millis(5);
+
+/// Sync book callback
+pub type BookCallback = Arc<dyn Fn(&OrderbookSnapshot) + Send + Sync>;
+
+/// Future returned by an async book callback
+pub type BookFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Async book callback
+pub type AsyncBookCallback = Arc<dyn Fn(OrderbookSnapshot) -> BookFuture + Send + Sync>;
+
+#[derive(Clone)]
+enum Registered {
+    Sync(BookCallback),
+    Async(AsyncBookCallback),
+}
+
+/// Counters describing callback health
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CallbackStats {
+    /// Callback invocations started
+    pub invocations: u64,
+    /// Invocations that panicked
+    pub panics: u64,
+    /// Sync invocations that ran over budget
+    pub overruns: u64,
+    /// Async invocations cancelled at the budget
+    pub timeouts: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    invocations: AtomicU64,
+    panics: AtomicU64,
+    overruns: AtomicU64,
+    timeouts: AtomicU64,
+}
+
+/// Registry of orderbook callbacks keyed by symbol
+pub struct BookCallbacks {
+    callbacks: RwLock<HashMap<String, Vec<Registered>>>,
+    budget: Duration,
+    counters: Arc<Counters>,
+}
+
+impl std::fmt::Debug for BookCallbacks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BookCallbacks")
+            .field("symbols", &self.callbacks.read().keys().collect::<Vec<_>>())
+            .field("budget", &self.budget)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl Default for BookCallbacks {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BookCallbacks {
+    /// Create an empty registry with the default time budget
+    pub fn new() -> Self {
+        Self {
+            callbacks: RwLock::new(HashMap::new()),
+            budget: DEFAULT_CALLBACK_BUDGET,
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    /// Set the time budget for each callback invocation
+    pub fn with_budget(mut self, budget: Duration) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Time budget for each callback invocation
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Register a sync callback for a symbol's book updates
+    ///
+    /// Runs inline on the connection task, so keep it short.
+    pub fn on_book_update<F>(&self, symbol: impl Into<String>, callback: F)
+    where
+        F: Fn(&OrderbookSnapshot) + Send + Sync + 'static,
+    {
+        self.register(symbol.into(), Registered::Sync(Arc::new(callback)));
+    }
+
+    /// Register an async callback for a symbol's book updates
+    ///
+    /// Each invocation is spawned and cancelled once the budget elapses.
+    /// Must be used from within a Tokio runtime.
+    pub fn on_book_update_async<F, Fut>(&self, symbol: impl Into<String>, callback: F)
+    where
+        F: Fn(OrderbookSnapshot) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let callback: AsyncBookCallback = Arc::new(move |snapshot| Box::pin(callback(snapshot)));
+        self.register(symbol.into(), Registered::Async(callback));
+    }
+
+    fn register(&self, symbol: String, callback: Registered) {
+        self.callbacks.write().entry(symbol).or_default().push(callback);
+    }
+
+    /// Remove every callback registered for a symbol
+    pub fn remove(&self, symbol: &str) -> usize {
+        self.callbacks.write().remove(symbol).map_or(0, |c| c.len())
+    }
+
+    /// Number of callbacks registered for a symbol
+    pub fn count(&self, symbol: &str) -> usize {
+        self.callbacks.read().get(symbol).map_or(0, Vec::len)
+    }
+
+    /// Returns true if any callback is registered for a symbol
+    pub fn has_callbacks(&self, symbol: &str) -> bool {
+        self.count(symbol) > 0
+    }
+
+    /// Invoke every callback registered for the snapshot's symbol
+    pub fn dispatch(&self, snapshot: &OrderbookSnapshot) {
+        // Clone the list so callbacks may register more without deadlocking
+        let Some(callbacks) = self.callbacks.read().get(&snapshot.symbol).cloned() else {
+            return;
+        };
+        for callback in callbacks {
+            self.counters.invocations.fetch_add(1, Ordering::Relaxed);
+            match callback {
+                Registered::Sync(callback) => self.run_sync(&callback, snapshot),
+                Registered::Async(callback) => self.spawn_async(&callback, snapshot),
+            }
+        }
+    }
+
+    fn run_sync(&self, callback: &BookCallback, snapshot: &OrderbookSnapshot) {
+        let started = Instant::now();
+        if catch_unwind(AssertUnwindSafe(|| callback(snapshot))).is_err() {
+            self.counters.panics.fetch_add(1, Ordering::Relaxed);
+            error!(symbol = %snapshot.symbol, "Book callback panicked");
+        }
+        let elapsed = started.elapsed();
+        if elapsed > self.budget {
+            self.counters.overruns.fetch_add(1, Ordering::Relaxed);
+            warn!(symbol = %snapshot.symbol, ?elapsed, budget = ?self.budget, "Book callback over budget");
+        }
+    }
+
+    fn spawn_async(&self, callback: &AsyncBookCallback, snapshot: &OrderbookSnapshot) {
+        let Ok(future) = catch_unwind(AssertUnwindSafe(|| callback(snapshot.clone()))) else {
+            self.counters.panics.fetch_add(1, Ordering::Relaxed);
+            error!(symbol = %snapshot.symbol, "Async book callback panicked");
+            return;
+        };
+        let budget = self.budget;
+        let counters = Arc::clone(&self.counters);
+        let symbol = snapshot.symbol.clone();
+        let task = tokio::spawn(tokio::time::timeout(budget, future));
+        tokio::spawn(async move {
+            match task.await {
+                Ok(Ok(())) => {}
+                Ok(Err(_)) => {
+                    counters.timeouts.fetch_add(1, Ordering::Relaxed);
+                    warn!(%symbol, ?budget, "Async book callback cancelled at budget");
+                }
+                Err(e) if e.is_panic() => {
+                    counters.panics.fetch_add(1, Ordering::Relaxed);
+                    error!(%symbol, "Async book callback panicked");
+                }
+                Err(_) => {}
+            }
+        });
+    }
+
+    /// Callback health counters
+    pub fn stats(&self) -> CallbackStats {
+        CallbackStats {
+            invocations: self.counters.invocations.load(Ordering::Relaxed),
+            panics: self.counters.panics.load(Ordering::Relaxed),
+            overruns: self.counters.overruns.load(Ordering::Relaxed),
+            timeouts: self.counters.timeouts.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_book::Orderbook;
+
+    fn snapshot(symbol: &str) -> OrderbookSnapshot {
+        Orderbook::new(symbol).snapshot()
+    }
+
+    #[test]
+    fn test_sync_callbacks_are_per_symbol_and_panic_isolated() {
+        let callbacks = BookCallbacks::new();
+        let hits = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&hits);
+        callbacks.on_book_update("BTC/USD", move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+        callbacks.on_book_update("BTC/USD", |_| panic!("boom"));
+
+        callbacks.dispatch(&snapshot("BTC/USD"));
+        callbacks.dispatch(&snapshot("ETH/USD"));
+        assert_eq!(hits.load(Ordering::Relaxed), 1);
+
+        let stats = callbacks.stats();
+        assert_eq!(stats.invocations, 2);
+        assert_eq!(stats.panics, 1);
+        assert_eq!(callbacks.remove("BTC/USD"), 2);
+        assert!(!callbacks.has_callbacks("BTC/USD"));
+    }
+
+    #[test]
+    fn test_slow_sync_callback_counts_overrun() {
+        let callbacks = BookCallbacks::new().with_budget(Duration::from_millis(1));
+        callbacks.on_book_update("BTC/USD", |_| std::thread::sleep(Duration::from_millis(5)));
+        callbacks.dispatch(&snapshot("BTC/USD"));
+        assert_eq!(callbacks.stats().overruns, 1);
+    }
+
+    #[tokio::test]
+    async fn test_async_callback_cancelled_at_budget() {
+        let callbacks = BookCallbacks::new().with_budget(Duration::from_millis(5));
+        callbacks.on_book_update_async("BTC/USD", |_| async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+        });
+        callbacks.on_book_update_async("BTC/USD", |_| async { panic!("boom") });
+        callbacks.dispatch(&snapshot("BTC/USD"));
+
+        // Panic reporting can be slow with backtraces enabled
+        for _ in 0..200 {
+            let stats = callbacks.stats();
+            if stats.timeouts + stats.panics == 2 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let stats = callbacks.stats();
+        assert_eq!(stats.timeouts, 1);
+        assert_eq!(stats.panics, 1);
+    }
+}
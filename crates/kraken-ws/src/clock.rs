@@ -0,0 +1,311 @@
+//! Server clock synchronization and skew estimation
+//!
+//! [`ClockSync`] estimates the offset between the local clock and Kraken's
+//! from two kinds of measurement, each treated as a bound on
+//! `offset = local - server`:
+//!
+//! - **WebSocket timestamps.** A message stamped by the exchange at `t` and
+//!   received locally at `r` gives `r - t = latency + offset`. Latency is never
+//!   negative, so the smallest delta is an upper bound on the offset. Book
+//!   updates and trades arrive constantly, so this bound is tight and fresh.
+//! - **REST `Time` responses.** The server read its clock somewhere between
+//!   sending the request and receiving the reply, and reports whole seconds.
+//!   This brackets the offset from both sides, but only to about a second.
+//!
+//! The estimate is the midpoint of the intersection of recent bounds, or the
+//! WebSocket upper bound alone when no REST sample is available. Drift is the
+//! slope of the per-minute WebSocket minima, in parts per million.
+//!
+//! [`KrakenConnection`](crate::KrakenConnection) feeds its WebSocket
+//! timestamps in automatically; REST samples are recorded by the caller with
+//! [`ClockSync::record_rest_time`], since the SDK has no REST client.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_ws::clock::ClockSync;
+//!
+//! let mut clock = ClockSync::new();
+//! // Local clock is 2.3s ahead; messages take 2-4ms to arrive
+//! for i in 0..100_i64 {
+//!     let exchange_us = 1_700_000_000_000_000 + i * 10_000;
+//!     let latency_us = 2_000 + (i % 3) * 1_000;
+//!     clock.record_exchange_timestamp(exchange_us, exchange_us + latency_us + 2_300_000);
+//! }
+//! let estimate = clock.estimate().unwrap();
+//! assert_eq!(estimate.offset_us, 2_302_000);
+//!
+//! // A REST round trip bounds it from below as well
+//! let sent = 1_700_000_001_000_000 + 2_300_000;
+//! clock.record_rest_time(1_700_000_001, sent, sent + 40_000);
+//! let estimate = clock.estimate().unwrap();
+//! assert!(estimate.uncertainty_us.is_some());
+//! ```
+
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Width of the buckets WebSocket minima are kept in
+const BUCKET_US: i64 = 60_000_000;
+
+/// Buckets kept for the drift estimate (30 minutes)
+const MAX_BUCKETS: usize = 30;
+
+/// Buckets used for the offset bound (the current and previous minute)
+const OFFSET_BUCKETS: usize = 2;
+
+/// REST samples kept
+const MAX_REST_SAMPLES: usize = 8;
+
+/// REST samples older than this no longer bound the offset
+const REST_MAX_AGE_US: i64 = 10 * 60 * 1_000_000;
+
+/// Current time in microseconds since the Unix epoch
+fn now_us() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as i64)
+        .unwrap_or(0)
+}
+
+/// Bounds on the offset from one REST round trip
+#[derive(Debug, Clone, Copy)]
+struct RestSample {
+    received_us: i64,
+    lo_us: i64,
+    hi_us: i64,
+}
+
+/// Current clock offset estimate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClockEstimate {
+    /// Local clock minus server clock, in microseconds (positive = local is ahead)
+    pub offset_us: i64,
+    /// Half-width of the bracket around the offset (None if only bounded above)
+    pub uncertainty_us: Option<i64>,
+    /// Local clock drift relative to the server, in parts per million
+    pub drift_ppm: Option<f64>,
+    /// Local time of the newest sample, in microseconds since the Unix epoch
+    pub as_of_us: i64,
+}
+
+impl ClockEstimate {
+    /// Offset extrapolated to `local_us` using the drift estimate
+    pub fn offset_at(&self, local_us: i64) -> i64 {
+        let drift = self.drift_ppm.unwrap_or(0.0);
+        self.offset_us + (drift * (local_us - self.as_of_us) as f64 / 1_000_000.0) as i64
+    }
+
+    /// Server time corresponding to a local time, both in microseconds
+    pub fn server_time_us(&self, local_us: i64) -> i64 {
+        local_us - self.offset_at(local_us)
+    }
+}
+
+/// Notification that the local clock is further from the server than allowed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSkew {
+    /// Estimated offset, in microseconds (positive = local is ahead)
+    pub offset_us: i64,
+    /// Configured threshold, in microseconds
+    pub threshold_us: i64,
+}
+
+/// Offset and drift estimator fed by WebSocket and REST timestamps
+#[derive(Debug, Clone, Default)]
+pub struct ClockSync {
+    /// (bucket start, smallest receive - exchange delta), oldest first
+    ws_buckets: VecDeque<(i64, i64)>,
+    rest: VecDeque<RestSample>,
+    threshold_us: Option<i64>,
+    skewed: bool,
+}
+
+impl ClockSync {
+    /// Create an estimator without a skew threshold
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Report [`ClockSkew`] from [`check_skew`](Self::check_skew) past this offset
+    pub fn with_threshold(mut self, threshold: Duration) -> Self {
+        self.threshold_us = Some(threshold.as_micros() as i64);
+        self
+    }
+
+    /// Record an exchange timestamp and the local receive time (microseconds)
+    pub fn record_exchange_timestamp(&mut self, exchange_us: i64, received_us: i64) {
+        let delta = received_us - exchange_us;
+        let bucket = received_us.div_euclid(BUCKET_US) * BUCKET_US;
+        match self.ws_buckets.back_mut() {
+            Some((start, min)) if *start == bucket => *min = (*min).min(delta),
+            Some((start, _)) if *start > bucket => {}
+            _ => {
+                if self.ws_buckets.len() == MAX_BUCKETS {
+                    self.ws_buckets.pop_front();
+                }
+                self.ws_buckets.push_back((bucket, delta));
+            }
+        }
+    }
+
+    /// Record a REST `Time` response
+    ///
+    /// `server_unix_secs` is the `unixtime` field; `sent_us` and
+    /// `received_us` are the local times the request was sent and the
+    /// response arrived, in microseconds since the Unix epoch.
+    pub fn record_rest_time(&mut self, server_unix_secs: i64, sent_us: i64, received_us: i64) {
+        let server_lo = server_unix_secs * 1_000_000;
+        let server_hi = server_lo + 1_000_000;
+        if self.rest.len() == MAX_REST_SAMPLES {
+            self.rest.pop_front();
+        }
+        self.rest.push_back(RestSample {
+            received_us,
+            lo_us: sent_us - server_hi,
+            hi_us: received_us - server_lo,
+        });
+    }
+
+    /// Current offset estimate, None until a sample has been recorded
+    pub fn estimate(&self) -> Option<ClockEstimate> {
+        let ws_hi = self
+            .ws_buckets
+            .iter()
+            .rev()
+            .take(OFFSET_BUCKETS)
+            .map(|(_, min)| *min)
+            .min();
+        let newest_ws = self.ws_buckets.back().map(|(start, _)| *start);
+        let newest_rest = self.rest.back().map(|s| s.received_us);
+        let as_of_us = newest_ws.max(newest_rest)?;
+
+        let recent: Vec<&RestSample> = self
+            .rest
+            .iter()
+            .filter(|s| as_of_us - s.received_us <= REST_MAX_AGE_US)
+            .collect();
+        let rest_lo = recent.iter().map(|s| s.lo_us).max();
+        let rest_hi = recent.iter().map(|s| s.hi_us).min();
+
+        let hi = match (ws_hi, rest_hi) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        let (offset_us, uncertainty_us) = match (rest_lo, hi) {
+            (Some(lo), Some(hi)) if lo <= hi => ((lo + hi) / 2, Some((hi - lo) / 2)),
+            // Bounds disagree (the clock stepped or drifted): trust the freshest
+            (Some(_), Some(hi)) => match (ws_hi, recent.last()) {
+                (Some(ws), _) => (ws, None),
+                (None, Some(last)) => ((last.lo_us + last.hi_us) / 2, Some((last.hi_us - last.lo_us) / 2)),
+                (None, None) => (hi, None),
+            },
+            (None, Some(hi)) => (hi, None),
+            (_, None) => return None,
+        };
+
+        Some(ClockEstimate {
+            offset_us,
+            uncertainty_us,
+            drift_ppm: self.drift_ppm(),
+            as_of_us,
+        })
+    }
+
+    /// Least-squares slope of the per-minute minima, in parts per million
+    fn drift_ppm(&self) -> Option<f64> {
+        if self.ws_buckets.len() < 3 {
+            return None;
+        }
+        let n = self.ws_buckets.len() as f64;
+        let (x0, _) = self.ws_buckets[0];
+        let points: Vec<(f64, f64)> = self
+            .ws_buckets
+            .iter()
+            .map(|(start, min)| ((start - x0) as f64, *min as f64))
+            .collect();
+        let mean_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+        let mean_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+        let cov: f64 = points.iter().map(|(x, y)| (x - mean_x) * (y - mean_y)).sum();
+        let var: f64 = points.iter().map(|(x, _)| (x - mean_x).powi(2)).sum();
+        (var > 0.0).then(|| cov / var * 1_000_000.0)
+    }
+
+    /// Estimated current server time, in microseconds since the Unix epoch
+    pub fn estimated_server_time_us(&self) -> Option<i64> {
+        self.estimate().map(|e| e.server_time_us(now_us()))
+    }
+
+    /// Estimated current server time
+    pub fn estimated_server_time(&self) -> Option<SystemTime> {
+        let us = self.estimated_server_time_us()?;
+        Some(UNIX_EPOCH + Duration::from_micros(u64::try_from(us).ok()?))
+    }
+
+    /// Report the skew once each time the offset moves past the threshold
+    ///
+    /// Returns None without a threshold, while within it, and while still
+    /// past it after it has been reported.
+    pub fn check_skew(&mut self) -> Option<ClockSkew> {
+        let threshold_us = self.threshold_us?;
+        let offset_us = self.estimate()?.offset_us;
+        let exceeded = offset_us.abs() > threshold_us;
+        let report = exceeded && !self.skewed;
+        self.skewed = exceeded;
+        report.then_some(ClockSkew { offset_us, threshold_us })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const T0: i64 = 1_700_000_000_000_000;
+
+    #[test]
+    fn test_rest_and_ws_bounds_intersect() {
+        let mut clock = ClockSync::new();
+        // Local is 250ms ahead. REST: sent at server 10.9s, 60ms round trip
+        let sent = T0 + 10_900_000 + 250_000;
+        clock.record_rest_time(T0 / 1_000_000 + 10, sent, sent + 60_000);
+        let rest_only = clock.estimate().unwrap();
+        assert!(rest_only.uncertainty_us.unwrap() > 500_000);
+
+        // WS deltas: 250ms offset plus 5ms minimum latency
+        for i in 0..50 {
+            clock.record_exchange_timestamp(T0 + 11_000_000 + i * 1_000, T0 + 11_255_000 + i * 1_000 + i % 7);
+        }
+        let combined = clock.estimate().unwrap();
+        assert!(combined.offset_us >= 250_000 - 50_000 && combined.offset_us <= 255_000);
+        assert!(combined.uncertainty_us.unwrap() < rest_only.uncertainty_us.unwrap());
+        assert_eq!(combined.server_time_us(T0 + 1_000_000 + combined.offset_us), T0 + 1_000_000);
+    }
+
+    #[test]
+    fn test_drift_from_minute_minima() {
+        let mut clock = ClockSync::new();
+        // Offset grows by 60us per minute = 1 ppm
+        for minute in 0..10 {
+            let received = T0 + minute * BUCKET_US;
+            clock.record_exchange_timestamp(received - 5_000 - minute * 60, received);
+        }
+        let drift = clock.estimate().unwrap().drift_ppm.unwrap();
+        assert!((drift - 1.0).abs() < 1e-6, "drift {}", drift);
+    }
+
+    #[test]
+    fn test_skew_reported_once_per_excursion() {
+        let mut clock = ClockSync::new().with_threshold(Duration::from_millis(500));
+        clock.record_exchange_timestamp(T0, T0 + 10_000);
+        assert_eq!(clock.check_skew(), None);
+
+        clock.record_exchange_timestamp(T0 + BUCKET_US, T0 + BUCKET_US + 800_000);
+        clock.record_exchange_timestamp(T0 + 2 * BUCKET_US, T0 + 2 * BUCKET_US + 800_000);
+        assert_eq!(clock.check_skew(), Some(ClockSkew { offset_us: 800_000, threshold_us: 500_000 }));
+        assert_eq!(clock.check_skew(), None);
+
+        clock.record_exchange_timestamp(T0 + 3 * BUCKET_US, T0 + 3 * BUCKET_US + 20_000);
+        assert_eq!(clock.check_skew(), None);
+        assert!(!clock.skewed);
+    }
+}
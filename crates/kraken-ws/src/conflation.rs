@@ -0,0 +1,169 @@
+//! Per-symbol conflation of book update events
+//!
+//! With [`ConnectionConfig::with_conflation`](crate::ConnectionConfig::with_conflation)
+//! a symbol emits at most one `OrderbookUpdate` per interval. Updates inside
+//! the interval are still applied to the book; when the interval ends, one
+//! event carrying the latest state goes out. Snapshots and book diagnostics
+//! such as checksum mismatches are never held back, and a book that falls
+//! out of sync drops its held update until the next snapshot.
+
+use crate::latency::ReceivedAt;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// An update held back until its symbol's interval ends
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PendingUpdate {
+    /// Receive time of the latest held update
+    pub received_at: ReceivedAt,
+    /// Exchange timestamp of the latest held update
+    pub exchange_ts_us: Option<i64>,
+}
+
+#[derive(Debug)]
+struct Slot {
+    interval: Duration,
+    last_emit: Instant,
+    pending: Option<PendingUpdate>,
+}
+
+impl Slot {
+    fn due_at(&self) -> Instant {
+        self.last_emit + self.interval
+    }
+}
+
+/// Conflation state for every conflated symbol
+#[derive(Debug, Default)]
+pub(crate) struct Conflator {
+    slots: HashMap<String, Slot>,
+}
+
+impl Conflator {
+    /// Offer an applied update; true if it should be emitted now
+    ///
+    /// Otherwise it is held and replaces any update already held.
+    pub fn offer(&mut self, symbol: &str, interval: Duration, now: Instant, update: PendingUpdate) -> bool {
+        match self.slots.get_mut(symbol) {
+            Some(slot) if now < slot.last_emit + interval => {
+                slot.interval = interval;
+                slot.pending = Some(update);
+                false
+            }
+            Some(slot) => {
+                slot.interval = interval;
+                slot.last_emit = now;
+                slot.pending = None;
+                true
+            }
+            None => {
+                self.emitted(symbol, interval, now);
+                true
+            }
+        }
+    }
+
+    /// Record a full book emitted for `symbol`, dropping any held update
+    pub fn emitted(&mut self, symbol: &str, interval: Duration, now: Instant) {
+        self.slots.insert(
+            symbol.to_string(),
+            Slot {
+                interval,
+                last_emit: now,
+                pending: None,
+            },
+        );
+    }
+
+    /// Drop the update held for `symbol`, keeping its interval timing
+    pub fn discard(&mut self, symbol: &str) {
+        if let Some(slot) = self.slots.get_mut(symbol) {
+            slot.pending = None;
+        }
+    }
+
+    /// When the earliest held update is due
+    pub fn next_due(&self) -> Option<Instant> {
+        self.slots
+            .values()
+            .filter(|slot| slot.pending.is_some())
+            .map(Slot::due_at)
+            .min()
+    }
+
+    /// Take every held update whose interval has ended
+    pub fn take_due(&mut self, now: Instant) -> Vec<(String, PendingUpdate)> {
+        let mut due = Vec::new();
+        for (symbol, slot) in &mut self.slots {
+            if slot.pending.is_some() && slot.due_at() <= now {
+                due.push((symbol.clone(), slot.pending.take().expect("checked above")));
+                slot.last_emit = now;
+            }
+        }
+        due
+    }
+
+    /// Forget all state, e.g. when a new connection starts
+    pub fn clear(&mut self) {
+        self.slots.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn update() -> PendingUpdate {
+        PendingUpdate {
+            received_at: ReceivedAt::now(),
+            exchange_ts_us: None,
+        }
+    }
+
+    #[test]
+    fn test_updates_inside_interval_are_held_until_due() {
+        let mut conflator = Conflator::default();
+        let interval = Duration::from_millis(100);
+        let start = Instant::now();
+
+        assert!(conflator.offer("BTC/USD", interval, start, update()));
+        assert!(!conflator.offer("BTC/USD", interval, start + Duration::from_millis(10), update()));
+        assert!(!conflator.offer("BTC/USD", interval, start + Duration::from_millis(20), update()));
+        assert_eq!(conflator.next_due(), Some(start + interval));
+
+        assert!(conflator.take_due(start + Duration::from_millis(50)).is_empty());
+        let due = conflator.take_due(start + interval);
+        assert_eq!(due.len(), 1);
+        assert_eq!(conflator.next_due(), None);
+    }
+
+    #[test]
+    fn test_emitted_snapshot_drops_held_update() {
+        let mut conflator = Conflator::default();
+        let interval = Duration::from_millis(100);
+        let start = Instant::now();
+
+        conflator.offer("BTC/USD", interval, start, update());
+        conflator.offer("BTC/USD", interval, start + Duration::from_millis(10), update());
+        conflator.emitted("BTC/USD", interval, start + Duration::from_millis(20));
+
+        assert_eq!(conflator.next_due(), None);
+        assert!(!conflator.offer("BTC/USD", interval, start + Duration::from_millis(60), update()));
+        assert_eq!(conflator.next_due(), Some(start + Duration::from_millis(120)));
+    }
+
+    #[test]
+    fn test_discard_drops_held_update_only() {
+        let mut conflator = Conflator::default();
+        let interval = Duration::from_millis(100);
+        let start = Instant::now();
+
+        conflator.offer("BTC/USD", interval, start, update());
+        conflator.offer("BTC/USD", interval, start + Duration::from_millis(10), update());
+        conflator.discard("BTC/USD");
+        assert_eq!(conflator.next_due(), None);
+        // The interval still runs from the last emit
+        assert!(!conflator.offer("BTC/USD", interval, start + Duration::from_millis(50), update()));
+        assert_eq!(conflator.next_due(), Some(start + interval));
+    }
+}
@@ -1,25 +1,54 @@
 //! WebSocket connection management
 
+use crate::backfill::{BackfillData, BackfillRequest, BackfillTracker, Backfiller, Released};
+use crate::book_callbacks::{BookCallbacks, DEFAULT_CALLBACK_BUDGET};
 use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig};
 use crate::endpoint::Endpoint;
-use crate::events::{ConnectionEvent, DisconnectReason, Event, L3Event, MarketEvent, SubscriptionEvent};
-use crate::reconnect::ReconnectConfig;
-use crate::subscription::{Subscription, SubscriptionManager};
+use crate::health::{HealthStats, HealthTracker};
+use crate::inline::{InlineDispatch, InlineHandler, InlineStats};
+use crate::clock::{ClockEstimate, ClockSync};
+use crate::conflation::{Conflator, PendingUpdate};
+use crate::latency::{parse_exchange_timestamp, LatencyStats, LatencyTracker, ReceivedAt};
+use crate::events::{ConnectionEvent, DisconnectReason, Event, L3Event, MarketEvent, SequencedEvent, SubscriptionEvent};
+use crate::proxy::ProxyConfig;
+use crate::pruning::{AccessTracker, PruningPolicy};
+use crate::reconnect::{Backoff, BackoffStrategy, ReconnectConfig};
+use crate::restoration::{RestorationProgress, RestorationTracker, DEFAULT_RESTORE_TIMEOUT};
+use crate::sampler::BookSampler;
+use crate::standby::{ReadyStandby, Standby};
+use crate::tap::MessageTap;
+use crate::rate_limiter::SharedRateLimiter;
+use crate::subscription::{
+    BatchResolution, Subscription, SubscriptionManager, TokenRefresher, DEFAULT_MAX_SYMBOLS_PER_REQUEST,
+};
+use crate::transport::{
+    NetworkConfig, Transport, TransportError, TransportFactory, TransportStats, WsTransport,
+};
+use crate::watchdog::StaleWatchdog;
 
 use dashmap::DashMap;
-use futures_util::{SinkExt, StreamExt};
+use std::collections::{BTreeMap, HashMap};
 use std::pin::Pin;
 use std::task::{Context, Poll};
-use kraken_book::Orderbook;
-use kraken_types::{Channel, Depth, KrakenError, MethodResponse, WsMessage};
-use parking_lot::RwLock;
-use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::SystemTime;
+use kraken_book::audit::audit;
+use kraken_book::memory::{depth_within, enforce_book_limit, MemoryLimits};
+use kraken_book::{ApplyError, Orderbook, OrderbookSnapshot};
+use kraken_types::{
+    Channel, Decimal, Depth, Formatting, KrakenApiError, KrakenError, L3Depth, MethodResponse, Precision, SubscribeResult, RateLimitCategory, StatusData, SubscribeRequest,
+    OhlcData, OhlcInterval, PairStatus, Symbol, SystemStatus, TradeData,
+    UnsubscribeRequest, WsMessage,
+};
+use parking_lot::{Mutex, RwLock};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot, watch, Notify};
 use tokio::time::{timeout, Duration};
-use tokio_tungstenite::{connect_async, tungstenite::Message};
 use tracing::{debug, error, info, instrument, warn};
 
+/// How long a failed method response can explain a disconnect
+const API_ERROR_RELEVANCE: Duration = Duration::from_secs(5);
+
 /// WebSocket connection state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ConnectionState {
@@ -39,6 +68,9 @@ pub enum ConnectionState {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum BackpressurePolicy {
     /// Drop newest messages when channel is full (default)
+    ///
+    /// A message's events for one symbol are dropped together, so per-symbol
+    /// `seq` gaps always cover whole messages.
     #[default]
     DropNewest,
     /// Block until space is available (may cause connection issues)
@@ -52,10 +84,21 @@ pub struct ConnectionConfig {
     pub endpoint: Endpoint,
     /// Reconnection settings
     pub reconnect: ReconnectConfig,
+    /// Backoff strategy for reconnect delays (None = `reconnect`'s exponential backoff)
+    pub backoff: Option<Backoff>,
     /// Connection timeout
     pub connect_timeout: Duration,
+    /// How long to wait for instrument precision before subscribing books
+    pub instrument_timeout: Duration,
+    /// How long `request_snapshot` waits for the fresh snapshot
+    pub snapshot_timeout: Duration,
+    /// How long resubscribing after a connect may take before outstanding
+    /// subscriptions are reported as failed
+    pub restore_timeout: Duration,
     /// Orderbook depth to subscribe with
     pub depth: Depth,
+    /// Per-symbol orderbook depths that take precedence over `depth`
+    pub depth_overrides: HashMap<String, Depth>,
     /// Heartbeat timeout - disconnect if no heartbeat received within this duration
     /// Kraken sends heartbeats every ~5 seconds; default timeout is 30 seconds
     pub heartbeat_timeout: Option<Duration>,
@@ -65,6 +108,51 @@ pub struct ConnectionConfig {
     pub backpressure_policy: BackpressurePolicy,
     /// Circuit breaker configuration (None = disabled)
     pub circuit_breaker: Option<CircuitBreakerConfig>,
+    /// Periodic book sampling (None = disabled)
+    pub book_sampler: Option<BookSampler>,
+    /// Proxy and TLS options for the underlying socket
+    pub network: NetworkConfig,
+    /// Offer permessage-deflate on the built-in transport
+    #[cfg(feature = "deflate")]
+    pub compression: bool,
+    /// Custom transport (None = WebSocket over `network`)
+    pub transport: Option<TransportFactory>,
+    /// Maximum symbols per subscribe request (larger lists are chunked)
+    pub max_symbols_per_request: usize,
+    /// Rate limiter used to pace subscribe requests (None = no pacing)
+    pub rate_limiter: Option<SharedRateLimiter>,
+    /// Flag (channel, symbol) feeds silent for this long (None = disabled)
+    pub stale_threshold: Option<Duration>,
+    /// Resubscribe feeds flagged as stale
+    pub resubscribe_stale: bool,
+    /// Time budget for each per-symbol book callback invocation
+    pub callback_budget: Duration,
+    /// Emit `ConnectionEvent::ClockSkew` past this local clock offset (None = disabled)
+    pub clock_skew_threshold: Option<Duration>,
+    /// Per-book and total orderbook memory budgets (None = unlimited)
+    pub memory_limits: Option<MemoryLimits>,
+    /// Track per-level update counts and change times in every book
+    pub level_metadata: bool,
+    /// Keep a connected, unsubscribed standby for instant failover
+    pub standby: bool,
+    /// Endpoint for the standby (None = same as `endpoint`)
+    pub standby_endpoint: Option<Endpoint>,
+    /// Emit at most one book update per symbol per interval (None = every update)
+    pub conflation: Option<Duration>,
+    /// Per-symbol conflation intervals that take precedence over `conflation`
+    pub conflation_overrides: HashMap<String, Duration>,
+    /// Unsubscribe books the application stops reading (None = never)
+    pub pruning: Option<PruningPolicy>,
+    /// Check every book's invariants at this interval (None = disabled)
+    pub audit_interval: Option<Duration>,
+    /// Handler called on the read loop for selected channels (None = channel only)
+    pub inline: Option<InlineDispatch>,
+    /// Copy of every raw frame sent or received (None = disabled)
+    pub tap: Option<MessageTap>,
+    /// Fetches a new token when a stored one expires (None = resend as is)
+    pub token_refresher: Option<TokenRefresher>,
+    /// Fetches trades and candles missed while disconnected (None = disabled)
+    pub backfill: Option<Backfiller>,
 }
 
 impl Default for ConnectionConfig {
@@ -72,12 +160,40 @@ impl Default for ConnectionConfig {
         Self {
             endpoint: Endpoint::Public,
             reconnect: ReconnectConfig::default(),
+            backoff: None,
             connect_timeout: Duration::from_secs(10),
+            instrument_timeout: Duration::from_secs(2),
+            snapshot_timeout: Duration::from_secs(10),
+            restore_timeout: DEFAULT_RESTORE_TIMEOUT,
             depth: Depth::D10,
+            depth_overrides: HashMap::new(),
             heartbeat_timeout: Some(Duration::from_secs(30)),
             channel_capacity: None, // Unbounded by default for backwards compatibility
             backpressure_policy: BackpressurePolicy::default(),
             circuit_breaker: Some(CircuitBreakerConfig::default()), // Enabled by default
+            book_sampler: None,
+            network: NetworkConfig::default(),
+            #[cfg(feature = "deflate")]
+            compression: false,
+            transport: None,
+            max_symbols_per_request: DEFAULT_MAX_SYMBOLS_PER_REQUEST,
+            rate_limiter: None,
+            stale_threshold: None,
+            resubscribe_stale: false,
+            callback_budget: DEFAULT_CALLBACK_BUDGET,
+            clock_skew_threshold: None,
+            memory_limits: None,
+            level_metadata: false,
+            standby: false,
+            standby_endpoint: None,
+            conflation: None,
+            conflation_overrides: HashMap::new(),
+            pruning: None,
+            audit_interval: None,
+            inline: None,
+            tap: None,
+            token_refresher: None,
+            backfill: None,
         }
     }
 }
@@ -100,6 +216,15 @@ impl ConnectionConfig {
         self
     }
 
+    /// Use a custom backoff strategy for reconnect delays
+    ///
+    /// The limits in `reconnect` (attempts, per-reason minimum delays,
+    /// downtime budget) still apply. See [`crate::reconnect`].
+    pub fn with_backoff(mut self, strategy: impl BackoffStrategy + 'static) -> Self {
+        self.backoff = Some(Backoff::new(strategy));
+        self
+    }
+
     /// Disable automatic reconnection
     pub fn without_reconnect(mut self) -> Self {
         self.reconnect = ReconnectConfig::disabled();
@@ -112,12 +237,107 @@ impl ConnectionConfig {
         self
     }
 
+    /// Set how long to wait for instrument precision before subscribing books
+    ///
+    /// Books subscribed without it infer precision from their snapshots
+    /// until the instrument data arrives.
+    pub fn with_instrument_timeout(mut self, timeout: Duration) -> Self {
+        self.instrument_timeout = timeout;
+        self
+    }
+
+    /// Set how long `request_snapshot` waits for the fresh snapshot
+    pub fn with_snapshot_timeout(mut self, timeout: Duration) -> Self {
+        self.snapshot_timeout = timeout;
+        self
+    }
+
+    /// Set how long subscription restoration may take after a connect
+    ///
+    /// Subscriptions still unanswered or without their book snapshot at the
+    /// deadline fail with `"timeout"` and the round completes.
+    pub fn with_restore_timeout(mut self, timeout: Duration) -> Self {
+        self.restore_timeout = timeout;
+        self
+    }
+
     /// Set orderbook depth
     pub fn with_depth(mut self, depth: Depth) -> Self {
         self.depth = depth;
         self
     }
 
+    /// Override the orderbook depth for a single symbol
+    ///
+    /// Lets one connection track a major pair deep and altcoins shallow.
+    pub fn with_symbol_depth(mut self, symbol: impl Into<Symbol>, depth: Depth) -> Self {
+        self.depth_overrides.insert(symbol.into().into_string(), depth);
+        self
+    }
+
+    /// Orderbook depth configured for a symbol
+    pub fn depth_for(&self, symbol: &str) -> Depth {
+        self.depth_overrides.get(symbol).copied().unwrap_or(self.depth)
+    }
+
+    /// Emit at most one `OrderbookUpdate` per symbol per `interval`
+    ///
+    /// Every delta is still applied to the book; the event sent when the
+    /// interval ends carries the latest state. Snapshots, checksum
+    /// mismatches and other book diagnostics go out immediately. Book
+    /// callbacks keep running on every delta.
+    pub fn with_conflation(mut self, interval: Duration) -> Self {
+        self.conflation = Some(interval);
+        self
+    }
+
+    /// Override the conflation interval for a single symbol
+    ///
+    /// `Duration::ZERO` turns conflation off for the symbol.
+    pub fn with_symbol_conflation(mut self, symbol: impl Into<Symbol>, interval: Duration) -> Self {
+        self.conflation_overrides.insert(symbol.into().into_string(), interval);
+        self
+    }
+
+    /// Conflation interval configured for a symbol (None = not conflated)
+    pub fn conflation_for(&self, symbol: &str) -> Option<Duration> {
+        self.conflation_overrides
+            .get(symbol)
+            .copied()
+            .or(self.conflation)
+            .filter(|interval| !interval.is_zero())
+    }
+
+    /// Unsubscribe books the application hasn't read for a while
+    ///
+    /// See [`crate::pruning`] for what counts as a read.
+    pub fn with_pruning(mut self, policy: PruningPolicy) -> Self {
+        self.pruning = Some(policy);
+        self
+    }
+
+    /// Ask the server to compress what it sends (permessage-deflate)
+    ///
+    /// Deep books across many symbols shrink several times over for a small
+    /// decompression cost; [`KrakenConnection::traffic_stats`] reports both.
+    /// See [`crate::deflate`]. Has no effect with a custom transport.
+    #[cfg(feature = "deflate")]
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.compression = enabled;
+        self
+    }
+
+    /// Periodically audit every book for checksum drift and broken invariants
+    ///
+    /// Each pass recomputes checksums from scratch and checks sorting,
+    /// crossing, depth limits and quantities (see [`kraken_book::audit`]).
+    /// Violations are emitted as [`MarketEvent::BookAuditFailed`] and counted
+    /// in [`HealthStats::audit_violations`]; books are not resynced.
+    pub fn with_audit(mut self, interval: Duration) -> Self {
+        self.audit_interval = Some(interval);
+        self
+    }
+
     /// Set heartbeat timeout
     ///
     /// If no message is received within this duration, the connection is
@@ -136,7 +356,8 @@ impl ConnectionConfig {
     /// Set bounded channel capacity for backpressure handling
     ///
     /// When the channel is full and a new event arrives:
-    /// - `DropNewest`: The new event is dropped (default)
+    /// - `DropNewest`: The new event is dropped (default), together with the
+    ///   other events for its symbol from the same message
     /// - `Block`: The sender blocks until space is available (may cause connection issues)
     ///
     /// Recommended capacity: 1000-10000 depending on message rate
@@ -166,20 +387,312 @@ impl ConnectionConfig {
         self.circuit_breaker = None;
         self
     }
+
+    /// Emit a `MarketEvent::BookSample` for every synced book on a fixed cadence
+    ///
+    /// Samples are taken while connected, independent of the update rate.
+    pub fn with_book_sampler(mut self, sampler: BookSampler) -> Self {
+        self.book_sampler = Some(sampler);
+        self
+    }
+
+    /// Sample every book at `interval` using the default 5 levels
+    pub fn with_book_sampling(self, interval: Duration) -> Self {
+        self.with_book_sampler(BookSampler::new(interval))
+    }
+
+    /// Route the connection through a SOCKS5 or HTTP CONNECT proxy
+    pub fn with_proxy(mut self, proxy: ProxyConfig) -> Self {
+        self.network.proxy = Some(proxy);
+        self
+    }
+
+    /// Override the TLS server name used for SNI and certificate checks
+    pub fn with_tls_server_name(mut self, name: impl Into<String>) -> Self {
+        self.network.server_name = Some(name.into());
+        self
+    }
+
+    /// Use a custom rustls configuration instead of the platform TLS stack
+    ///
+    /// Use this to pin root certificates or present a client certificate.
+    #[cfg(feature = "rustls")]
+    pub fn with_rustls_config(
+        mut self,
+        config: std::sync::Arc<tokio_rustls::rustls::ClientConfig>,
+    ) -> Self {
+        self.network.rustls_config = Some(config);
+        self
+    }
+
+    /// Split subscriptions into requests of at most `max_symbols` symbols
+    pub fn with_max_symbols_per_request(mut self, max_symbols: usize) -> Self {
+        self.max_symbols_per_request = max_symbols.max(1);
+        self
+    }
+
+    /// Pace subscribe requests through a shared rate limiter
+    ///
+    /// Each request takes a `WsSubscribe` token, waiting when the bucket is
+    /// empty. Share the limiter across connections to pace them together.
+    pub fn with_rate_limiter(mut self, limiter: SharedRateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Emit `SubscriptionEvent::Stale` when a (channel, symbol) feed is silent
+    /// for longer than `threshold`
+    ///
+    /// Book and trade feeds only publish on change, so pick a threshold above
+    /// the quietest normal gap for the symbols involved.
+    pub fn with_stale_detection(mut self, threshold: Duration) -> Self {
+        self.stale_threshold = Some(threshold);
+        self
+    }
+
+    /// Unsubscribe and resubscribe feeds flagged as stale
+    ///
+    /// Has no effect unless stale detection is enabled.
+    pub fn with_stale_resubscribe(mut self) -> Self {
+        self.resubscribe_stale = true;
+        self
+    }
+
+    /// Emit `ConnectionEvent::ClockSkew` when the local clock drifts more
+    /// than `threshold` from Kraken's
+    ///
+    /// The offset is estimated from exchange timestamps on book and trade
+    /// messages; see [`crate::clock`].
+    pub fn with_clock_skew_warning(mut self, threshold: Duration) -> Self {
+        self.clock_skew_threshold = Some(threshold);
+        self
+    }
+
+    /// Shrink orderbooks to stay within approximate memory budgets
+    ///
+    /// A book over the per-book budget is resubscribed at a smaller depth
+    /// as soon as it is updated; the snapshot at that depth drops its tail.
+    /// The total is checked every second; when exceeded, the deepest books
+    /// are resubscribed first. Either emits
+    /// `ConnectionEvent::MemoryCapReached`. See [`kraken_book::memory`].
+    pub fn with_memory_limits(mut self, limits: MemoryLimits) -> Self {
+        self.memory_limits = Some(limits);
+        self
+    }
+
+    /// Track per-level update counts and change times in every book
+    ///
+    /// Read them with `Orderbook::level_meta` or
+    /// `Orderbook::snapshot_extended`. See [`kraken_book::level_meta`].
+    pub fn with_level_metadata(mut self) -> Self {
+        self.level_metadata = true;
+        self
+    }
+
+    /// Handle events of `channels` synchronously on the read loop
+    ///
+    /// Those events skip the event channel (and get no event id); see the
+    /// [`inline`](crate::inline) module for the time budget handlers must
+    /// respect.
+    pub fn with_inline_handler(
+        mut self,
+        channels: impl IntoIterator<Item = Channel>,
+        handler: impl InlineHandler + 'static,
+    ) -> Self {
+        self.inline = Some(InlineDispatch::new(channels, handler));
+        self
+    }
+
+    /// Route selected channels to a preconfigured inline handler
+    pub fn with_inline_dispatch(mut self, dispatch: InlineDispatch) -> Self {
+        self.inline = Some(dispatch);
+        self
+    }
+
+    /// Set the time budget for each per-symbol book callback invocation
+    ///
+    /// Sync callbacks over budget are logged; async ones are cancelled.
+    pub fn with_callback_budget(mut self, budget: Duration) -> Self {
+        self.callback_budget = budget;
+        self
+    }
+
+    /// Keep a warm standby connection for instant failover
+    ///
+    /// A second socket completes the handshake but stays unsubscribed. When
+    /// the primary dies it is promoted and resubscribed without waiting
+    /// through reconnect backoff, and `ConnectionEvent::Failover` is emitted.
+    pub fn with_standby(mut self, enabled: bool) -> Self {
+        self.standby = enabled;
+        self
+    }
+
+    /// Open the standby on another endpoint (e.g. `Endpoint::PublicBeta`)
+    ///
+    /// Implies [`with_standby(true)`](Self::with_standby).
+    pub fn with_standby_endpoint(mut self, endpoint: Endpoint) -> Self {
+        self.standby = true;
+        self.standby_endpoint = Some(endpoint);
+        self
+    }
+
+    /// Copy every raw frame, inbound and outbound, to a tap
+    ///
+    /// Frames are tapped before parsing, so malformed ones are captured too.
+    /// See [`crate::tap`] for the available sinks.
+    pub fn with_message_tap(mut self, tap: MessageTap) -> Self {
+        self.tap = Some(tap);
+        self
+    }
+
+    /// Use a custom transport instead of the built-in WebSocket client
+    ///
+    /// The factory is called with the endpoint URL on every connection
+    /// attempt, including reconnects. Proxy and TLS options are ignored when
+    /// a factory is set, since the transport owns the socket.
+    pub fn with_transport_factory<F>(mut self, factory: F) -> Self
+    where
+        F: Fn(&str) -> Box<dyn Transport> + Send + Sync + 'static,
+    {
+        self.transport = Some(TransportFactory::new(factory));
+        self
+    }
+
+    /// Fill trades and candles missed while disconnected from REST
+    ///
+    /// On every connect, live trade and OHLC events for the channels the
+    /// backfiller covers are held until the missed window is fetched, then
+    /// emitted after it. See the [`backfill`](crate::backfill) module.
+    pub fn with_backfill(mut self, backfiller: Backfiller) -> Self {
+        self.backfill = Some(backfiller);
+        self
+    }
+
+    /// Refresh expired subscription tokens before they are resent
+    ///
+    /// Tokens are only accepted for new connections within
+    /// [`TOKEN_LIFETIME`](crate::TOKEN_LIFETIME) of being issued. On every
+    /// (re)connect, subscriptions whose token has expired get the token
+    /// `refresh` returns.
+    pub fn with_token_refresher<F, Fut>(mut self, refresh: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = Result<String, KrakenError>> + Send + 'static,
+    {
+        self.token_refresher = Some(TokenRefresher::new(refresh));
+        self
+    }
+}
+
+/// Connect a transport and wait for the server's status message
+pub(crate) async fn handshake(
+    transport: &mut Box<dyn Transport>,
+    url: &str,
+    connect_timeout: Duration,
+) -> Result<StatusData, KrakenError> {
+    match timeout(connect_timeout, transport.connect()).await {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => {
+            return Err(KrakenError::ConnectionFailed {
+                url: url.to_string(),
+                reason: e.to_string(),
+            });
+        }
+        Err(_) => {
+            return Err(KrakenError::ConnectionTimeout {
+                url: url.to_string(),
+                timeout: connect_timeout,
+            });
+        }
+    }
+
+    loop {
+        match transport.recv().await {
+            Ok(Some(text)) => {
+                if let Ok(WsMessage::Status(mut status_msg)) = WsMessage::parse(&text) {
+                    if !status_msg.data.is_empty() {
+                        return Ok(status_msg.data.swap_remove(0));
+                    }
+                }
+            }
+            Ok(None) => {
+                return Err(KrakenError::WebSocket("Connection closed before ready".into()));
+            }
+            Err(TransportError::ConnectionClosed) => {
+                return Err(KrakenError::WebSocket("No status message received".into()));
+            }
+            Err(e) => {
+                return Err(KrakenError::WebSocket(e.to_string()));
+            }
+        }
+    }
+}
+
+/// Wait for the next tick of an optional interval (never completes when None)
+async fn next_tick(tick: &mut Option<tokio::time::Interval>) {
+    match tick {
+        Some(tick) => {
+            tick.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+/// Current instant on tokio's clock, which tests can pause and advance
+fn tokio_now() -> std::time::Instant {
+    tokio::time::Instant::now().into_std()
+}
+
+/// Next completed backfill fetch, or never once all have completed
+async fn next_backfill(
+    rx: &mut Option<mpsc::UnboundedReceiver<BackfillDone>>,
+) -> BackfillDone {
+    if let Some(done) = rx.as_mut() {
+        if let Some(done) = done.recv().await {
+            return done;
+        }
+        *rx = None;
+    }
+    std::future::pending().await
+}
+
+/// Sleep until `deadline`, or forever without one
+async fn sleep_until_opt(deadline: Option<std::time::Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(tokio::time::Instant::from_std(deadline)).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Transport traffic counters, summed over a connection's transports
+#[derive(Debug, Default)]
+struct Traffic {
+    /// Transports that have been closed
+    closed: TransportStats,
+    /// Latest reading of the transport in use
+    current: TransportStats,
+}
+
+impl Traffic {
+    /// Fold the final reading of a finished transport into the totals
+    fn close(&mut self, last: TransportStats) {
+        self.closed = self.closed + last;
+        self.current = TransportStats::default();
+    }
 }
 
 /// Event sender that handles both bounded and unbounded channels
 enum EventSender {
-    Unbounded(mpsc::UnboundedSender<Event>),
+    Unbounded(mpsc::UnboundedSender<SequencedEvent>),
     Bounded {
-        sender: mpsc::Sender<Event>,
+        sender: mpsc::Sender<SequencedEvent>,
         policy: BackpressurePolicy,
         dropped_count: std::sync::atomic::AtomicU64,
     },
 }
 
 impl EventSender {
-    fn send(&self, event: Event) {
+    fn send(&self, event: SequencedEvent) {
         match self {
             EventSender::Unbounded(tx) => {
                 let _ = tx.send(event);
@@ -200,6 +713,34 @@ impl EventSender {
         }
     }
 
+    /// Send events that are delivered together or not at all
+    ///
+    /// Under `DropNewest` the whole batch is dropped if the channel can't
+    /// take all of it.
+    fn send_batch(&self, events: Vec<SequencedEvent>) {
+        match self {
+            EventSender::Bounded {
+                sender,
+                policy: BackpressurePolicy::DropNewest,
+                dropped_count,
+            } if !events.is_empty() => match sender.try_reserve_many(events.len()) {
+                Ok(permits) => {
+                    for (permit, event) in permits.zip(events) {
+                        permit.send(event);
+                    }
+                }
+                Err(_) => {
+                    dropped_count.fetch_add(events.len() as u64, Ordering::Relaxed);
+                }
+            },
+            _ => {
+                for event in events {
+                    self.send(event);
+                }
+            }
+        }
+    }
+
     fn dropped_count(&self) -> u64 {
         match self {
             EventSender::Unbounded(_) => 0,
@@ -211,15 +752,20 @@ impl EventSender {
 /// Event receiver wrapper
 pub enum EventReceiver {
     /// Unbounded receiver
-    Unbounded(mpsc::UnboundedReceiver<Event>),
+    Unbounded(mpsc::UnboundedReceiver<SequencedEvent>),
     /// Bounded receiver
-    Bounded(mpsc::Receiver<Event>),
+    Bounded(mpsc::Receiver<SequencedEvent>),
 }
 
 impl EventReceiver {
     /// Receive the next event
     #[instrument(skip(self), level = "trace")]
     pub async fn recv(&mut self) -> Option<Event> {
+        self.recv_sequenced().await.map(|sequenced| sequenced.event)
+    }
+
+    /// Receive the next event with its connection-wide id
+    pub async fn recv_sequenced(&mut self) -> Option<SequencedEvent> {
         match self {
             EventReceiver::Unbounded(rx) => rx.recv().await,
             EventReceiver::Bounded(rx) => rx.recv().await,
@@ -231,13 +777,20 @@ impl futures::Stream for EventReceiver {
     type Item = Event;
 
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        match self.get_mut() {
+        let polled = match self.get_mut() {
             EventReceiver::Unbounded(rx) => Pin::new(rx).poll_recv(cx),
             EventReceiver::Bounded(rx) => Pin::new(rx).poll_recv(cx),
-        }
+        };
+        polled.map(|sequenced| sequenced.map(|sequenced| sequenced.event))
     }
 }
 
+/// Caller waiting for a requested snapshot to be applied
+type SnapshotWaiter = oneshot::Sender<Result<(), KrakenError>>;
+
+/// A finished backfill fetch, reported back to the connection loop
+type BackfillDone = (BackfillRequest, Result<BackfillData, KrakenError>);
+
 /// WebSocket connection to Kraken
 pub struct KrakenConnection {
     /// Configuration
@@ -254,12 +807,65 @@ pub struct KrakenConnection {
     shutdown: AtomicBool,
     /// Event sender
     event_tx: EventSender,
+    /// Last event id handed out; held while sending so ids stay in order
+    last_event_id: Mutex<u64>,
     /// Event receiver (for public consumption)
     event_rx: Arc<RwLock<Option<EventReceiver>>>,
     /// Last message timestamp for heartbeat monitoring
     last_message_time: Arc<RwLock<std::time::Instant>>,
     /// Circuit breaker for connection reliability
     circuit_breaker: Option<CircuitBreaker>,
+    /// Exchange-to-client latency samples
+    latency: Arc<RwLock<LatencyTracker>>,
+    /// Local clock offset and drift relative to the exchange
+    clock: RwLock<ClockSync>,
+    /// Transport traffic across all connection attempts
+    traffic: RwLock<Traffic>,
+    /// Per-channel counts, reconnect history and heartbeat tracking
+    health: RwLock<HealthTracker>,
+    /// Per-feed silence tracking (None = disabled)
+    watchdog: Option<RwLock<StaleWatchdog>>,
+    /// Per-symbol book update callbacks
+    book_callbacks: Arc<BookCallbacks>,
+    /// Last exchange system status (None until the first status message)
+    system_status: watch::Sender<Option<SystemStatus>>,
+    /// True once `connect_and_run` has returned
+    stopped: watch::Sender<bool>,
+    /// Last trading status per pair, from the instrument channel or REST
+    pair_status: watch::Sender<HashMap<String, PairStatus>>,
+    /// Progress of resending subscriptions after the last connect
+    restoration: RwLock<RestorationTracker>,
+    /// Last sequence number handed out per symbol
+    symbol_seq: RwLock<HashMap<String, u64>>,
+    /// Symbols waiting to be resubscribed for a fresh snapshot
+    snapshot_queue: RwLock<Vec<String>>,
+    /// Books moved to a smaller depth, with the depth to unsubscribe
+    depth_queue: RwLock<Vec<(String, Depth)>>,
+    /// Wakes the message loop when `snapshot_queue` or `depth_queue` has entries
+    snapshot_notify: Notify,
+    /// Callers waiting for the next applied snapshot, by symbol
+    snapshot_waiters: RwLock<HashMap<String, Vec<SnapshotWaiter>>>,
+    /// Symbols of snapshot resubscribes awaiting their response, by request ID
+    snapshot_requests: RwLock<HashMap<u64, String>>,
+    /// Book updates held back by conflation
+    conflator: RwLock<Conflator>,
+    /// Application reads per book, for pruning
+    access: RwLock<AccessTracker>,
+    /// Pruned books waiting to be resubscribed on this connection
+    resume_queue: RwLock<Vec<String>>,
+    /// Wakes the message loop when `resume_queue` has entries
+    resume_notify: Notify,
+    /// Price and quantity precision from the instrument channel
+    formatting: RwLock<Formatting>,
+    /// Instrument snapshots received, across connections
+    instrument_snapshots: AtomicU64,
+    /// Last failed method response on this connection and when it arrived,
+    /// to explain a disconnect shortly after
+    last_api_error: Mutex<Option<(KrakenApiError, tokio::time::Instant)>>,
+    /// Why the last connection ended, for the reconnect delay
+    disconnect_reason: Mutex<Option<DisconnectReason>>,
+    /// Live trades and candles held while their history is fetched
+    backfill: Mutex<BackfillTracker>,
 }
 
 impl KrakenConnection {
@@ -284,18 +890,53 @@ impl KrakenConnection {
         };
 
         let circuit_breaker = config.circuit_breaker.clone().map(CircuitBreaker::new);
+        let subscriptions =
+            SubscriptionManager::new().with_max_symbols_per_request(config.max_symbols_per_request);
+        let watchdog = config.stale_threshold.map(|t| RwLock::new(StaleWatchdog::new(t)));
+        let book_callbacks = Arc::new(BookCallbacks::new().with_budget(config.callback_budget));
+        let restoration = RestorationTracker::new().with_timeout(config.restore_timeout);
+        let clock = match config.clock_skew_threshold {
+            Some(threshold) => ClockSync::new().with_threshold(threshold),
+            None => ClockSync::new(),
+        };
 
         Self {
             config,
             state: Arc::new(RwLock::new(ConnectionState::Disconnected)),
             orderbooks: Arc::new(DashMap::new()),
-            subscriptions: Arc::new(RwLock::new(SubscriptionManager::new())),
+            subscriptions: Arc::new(RwLock::new(subscriptions)),
             reconnect_attempt: AtomicU32::new(0),
             shutdown: AtomicBool::new(false),
             event_tx,
+            last_event_id: Mutex::new(0),
             event_rx: Arc::new(RwLock::new(Some(event_rx))),
             last_message_time: Arc::new(RwLock::new(std::time::Instant::now())),
             circuit_breaker,
+            latency: Arc::new(RwLock::new(LatencyTracker::default())),
+            clock: RwLock::new(clock),
+            traffic: RwLock::new(Traffic::default()),
+            health: RwLock::new(HealthTracker::new()),
+            watchdog,
+            book_callbacks,
+            system_status: watch::channel(None).0,
+            stopped: watch::channel(false).0,
+            pair_status: watch::channel(HashMap::new()).0,
+            restoration: RwLock::new(restoration),
+            symbol_seq: RwLock::new(HashMap::new()),
+            snapshot_queue: RwLock::new(Vec::new()),
+            depth_queue: RwLock::new(Vec::new()),
+            snapshot_notify: Notify::new(),
+            snapshot_waiters: RwLock::new(HashMap::new()),
+            snapshot_requests: RwLock::new(HashMap::new()),
+            conflator: RwLock::new(Conflator::default()),
+            access: RwLock::new(AccessTracker::default()),
+            resume_queue: RwLock::new(Vec::new()),
+            resume_notify: Notify::new(),
+            formatting: RwLock::new(Formatting::new()),
+            instrument_snapshots: AtomicU64::new(0),
+            last_api_error: Mutex::new(None),
+            disconnect_reason: Mutex::new(None),
+            backfill: Mutex::new(BackfillTracker::default()),
         }
     }
 
@@ -314,11 +955,126 @@ impl KrakenConnection {
         self.state() == ConnectionState::Connected
     }
 
+    /// Last system status reported by Kraken
+    pub fn system_status(&self) -> Option<SystemStatus> {
+        *self.system_status.borrow()
+    }
+
+    /// Watch system status changes
+    ///
+    /// Pass the receiver to [`TradingClient::with_status_gate`](crate::TradingClient::with_status_gate)
+    /// to pause order submission outside normal operation.
+    pub fn watch_system_status(&self) -> watch::Receiver<Option<SystemStatus>> {
+        self.system_status.subscribe()
+    }
+
+    /// Record a status message, emitting an event when the status changes
+    fn update_system_status(&self, current: SystemStatus) {
+        let previous = *self.system_status.borrow();
+        if previous == Some(current) {
+            return;
+        }
+        self.system_status.send_replace(Some(current));
+        if current.is_online() {
+            info!(?previous, "Kraken system status: {}", current);
+        } else {
+            warn!(?previous, "Kraken system status: {}", current);
+        }
+        self.emit(ConnectionEvent::SystemStatusChanged { previous, current });
+    }
+
+    /// Last trading status reported for a pair
+    pub fn pair_status(&self, symbol: &str) -> Option<PairStatus> {
+        self.pair_status.borrow().get(symbol).copied()
+    }
+
+    /// Watch the trading status of every pair seen
+    ///
+    /// Pass the receiver to [`TradingClient::with_pair_status_gate`](crate::TradingClient::with_pair_status_gate)
+    /// to hold orders to what each pair accepts.
+    pub fn watch_pair_status(&self) -> watch::Receiver<HashMap<String, PairStatus>> {
+        self.pair_status.subscribe()
+    }
+
+    /// Pairs not trading normally, with their status
+    pub fn restricted_pairs(&self) -> HashMap<String, PairStatus> {
+        self.pair_status
+            .borrow()
+            .iter()
+            .filter(|(_, status)| !status.is_online())
+            .map(|(symbol, status)| (symbol.clone(), *status))
+            .collect()
+    }
+
+    /// Progress of restoring subscriptions after the last connect
+    ///
+    /// None before the first connect with subscriptions.
+    pub fn restoration_progress(&self) -> Option<RestorationProgress> {
+        self.restoration.read().progress()
+    }
+
+    /// Returns true while subscriptions resent on connect are not all live
+    /// and re-synced yet
+    pub fn is_restoring(&self) -> bool {
+        self.restoration.read().is_restoring()
+    }
+
+    /// Record a pair's trading status, emitting an event when it changes
+    ///
+    /// Called for every pair on the instrument channel. Statuses polled from
+    /// REST `AssetPairs` can be fed in here too. The first status seen for a
+    /// pair is recorded silently; later changes are logged and emitted only
+    /// for pairs with an active subscription. Returns true if a known status
+    /// changed.
+    pub fn update_pair_status(&self, symbol: &str, current: PairStatus) -> bool {
+        let mut previous = None;
+        self.pair_status.send_if_modified(|statuses| {
+            previous = statuses.insert(symbol.to_string(), current);
+            previous != Some(current)
+        });
+        let Some(previous) = previous.filter(|previous| *previous != current) else {
+            return false;
+        };
+        if !self.subscriptions.read().has_symbol(symbol) {
+            return true;
+        }
+        let previous = Some(previous);
+        if current.is_online() {
+            info!(symbol, ?previous, "Pair status: {}", current);
+        } else {
+            warn!(symbol, ?previous, "Pair status: {}", current);
+        }
+        self.emit(ConnectionEvent::PairStatusChanged {
+            symbol: symbol.to_string(),
+            previous,
+            current,
+        });
+        true
+    }
+
     /// Take the event receiver (can only be called once)
     pub fn take_event_receiver(&self) -> Option<EventReceiver> {
         self.event_rx.write().take()
     }
 
+    /// Id of the last event emitted (0 before the first)
+    pub fn last_event_id(&self) -> u64 {
+        *self.last_event_id.lock()
+    }
+
+    /// Continue event ids after `last_id`, e.g. one saved before a restart
+    ///
+    /// Never moves ids backwards.
+    pub fn resume_event_ids(&self, last_id: u64) {
+        let mut last = self.last_event_id.lock();
+        *last = (*last).max(last_id);
+    }
+
+    /// Inline handler counters (None without an inline handler)
+    pub fn inline_stats(&self) -> Option<InlineStats> {
+        self.config.inline.as_ref().map(InlineDispatch::stats)
+    }
+
     /// Get the number of dropped events due to backpressure
     ///
     /// Only meaningful when using a bounded channel with DropNewest policy.
@@ -327,190 +1083,805 @@ impl KrakenConnection {
     }
 
     /// Get an orderbook by symbol
+    ///
+    /// The returned guard holds a read lock on the book's shard and blocks
+    /// the connection from applying updates to it. Drop it promptly and
+    /// never hold it across an `.await`; prefer
+    /// [`orderbook_snapshot`](Self::orderbook_snapshot) or
+    /// [`with_orderbook`](Self::with_orderbook) in async code.
     pub fn orderbook(&self, symbol: &str) -> Option<dashmap::mapref::one::Ref<'_, String, Orderbook>>
     {
+        self.record_access(symbol);
         self.orderbooks.get(symbol)
     }
 
-    /// Subscribe to orderbook updates for symbols
-    #[instrument(skip(self), fields(symbols = ?symbols))]
-    pub fn subscribe_orderbook(&self, symbols: Vec<String>) -> u64 {
-        let sub = Subscription::orderbook(symbols, self.config.depth);
-        self.subscriptions.write().add(sub)
+    /// Owned copy of the current orderbook for a symbol
+    ///
+    /// Safe to keep across await points; the lock is released before
+    /// returning.
+    pub fn orderbook_snapshot(&self, symbol: &str) -> Option<OrderbookSnapshot> {
+        self.with_orderbook(symbol, Orderbook::snapshot)
     }
 
-    /// Subscribe to ticker updates
-    #[instrument(skip(self), fields(symbols = ?symbols))]
-    pub fn subscribe_ticker(&self, symbols: Vec<String>) -> u64 {
-        let sub = Subscription::ticker(symbols);
-        self.subscriptions.write().add(sub)
+    /// Run `f` against the orderbook for a symbol, without copying it
+    ///
+    /// The book is locked only while `f` runs, so `f` must not block.
+    pub fn with_orderbook<R>(&self, symbol: &str, f: impl FnOnce(&Orderbook) -> R) -> Option<R> {
+        self.record_access(symbol);
+        self.orderbooks.get(symbol).map(|book| f(&book))
     }
 
-    /// Subscribe to trade updates
-    #[instrument(skip(self), fields(symbols = ?symbols))]
-    pub fn subscribe_trade(&self, symbols: Vec<String>) -> u64 {
-        let sub = Subscription::trade(symbols);
-        self.subscriptions.write().add(sub)
+    /// Seed the orderbook for a symbol from a stored snapshot
+    ///
+    /// Call before connecting to show last-known levels right away. The book
+    /// stays unsynced until the live snapshot replaces it.
+    pub fn restore_orderbook(&self, snapshot: &OrderbookSnapshot) {
+        let mut book = self.new_orderbook(&snapshot.symbol);
+        book.restore_snapshot(snapshot);
+        self.orderbooks.insert(snapshot.symbol.clone(), book);
     }
 
-    /// Subscribe to L3 (Level 3) orderbook updates
-    ///
-    /// Note: L3 requires connection to the Level3 endpoint and special access.
-    /// Create a connection with `Endpoint::Level3` to use this subscription.
-    #[instrument(skip(self), fields(symbols = ?symbols))]
-    pub fn subscribe_l3(&self, symbols: Vec<String>) -> u64 {
-        let sub = Subscription::level3(symbols);
-        self.subscriptions.write().add(sub)
+    /// Owned copies of every orderbook currently held
+    pub fn orderbook_snapshots(&self) -> Vec<OrderbookSnapshot> {
+        self.orderbooks.iter().map(|book| book.snapshot()).collect()
     }
 
-    /// Connect and run the connection loop
-    #[instrument(skip(self), name = "kraken_connection")]
-    pub async fn connect_and_run(&self) -> Result<(), KrakenError> {
-        loop {
-            if self.shutdown.load(Ordering::Relaxed) {
-                break;
-            }
+    /// Copy of the precision learned from the instrument channel
+    pub fn formatting(&self) -> Formatting {
+        self.formatting.read().clone()
+    }
 
-            // Check circuit breaker before attempting connection
-            if let Some(ref breaker) = self.circuit_breaker {
-                if !breaker.allow_request() {
-                    let stats = breaker.stats();
-                    warn!(
-                        "Circuit breaker is open (tripped {} times), waiting for recovery",
-                        stats.trips
-                    );
-                    self.emit(ConnectionEvent::CircuitBreakerOpen {
-                        trips: stats.trips,
-                    });
-                    // Wait for the circuit breaker timeout before retrying
-                    tokio::time::sleep(Duration::from_secs(5)).await;
-                    continue;
-                }
-            }
+    /// Precision for a symbol, or the default if no instrument data arrived
+    pub fn precision(&self, symbol: &str) -> Precision {
+        self.formatting.read().precision(symbol)
+    }
 
-            // Update state
-            {
-                let mut state = self.state.write();
-                if *state == ConnectionState::Reconnecting {
-                    // Already reconnecting
+    /// Price with exactly the symbol's price decimals
+    pub fn format_price(&self, symbol: &str, price: Decimal) -> String {
+        self.formatting.read().format_price(symbol, price)
+    }
+
+    /// Quantity with exactly the symbol's quantity decimals
+    pub fn format_qty(&self, symbol: &str, qty: Decimal) -> String {
+        self.formatting.read().format_qty(symbol, qty)
+    }
+
+    /// Price rounded to the nearest valid tick for the symbol
+    pub fn round_to_tick(&self, symbol: &str, price: Decimal) -> Decimal {
+        self.formatting.read().round_to_tick(symbol, price)
+    }
+
+    /// Quantity rounded down to a valid lot for the symbol
+    pub fn round_qty(&self, symbol: &str, qty: Decimal) -> Decimal {
+        self.formatting.read().round_qty(symbol, qty)
+    }
+
+    /// Note an application read of a book, for pruning
+    fn record_access(&self, symbol: &str) {
+        let Some(policy) = self.config.pruning else {
+            return;
+        };
+        let pruned = self.access.write().touch(symbol, std::time::Instant::now());
+        if pruned && policy.resubscribe_on_access {
+            self.resume_pruned(symbol);
+        }
+    }
+
+    /// Books unsubscribed because the application stopped reading them
+    pub fn pruned_symbols(&self) -> Vec<String> {
+        self.access.read().pruned()
+    }
+
+    /// Resubscribe a book that was pruned for inactivity
+    ///
+    /// The subscribe request goes out right away when connected, otherwise
+    /// with the other subscriptions on the next connect. Returns false if
+    /// the symbol isn't pruned.
+    pub fn resume_pruned(&self, symbol: &str) -> bool {
+        let Some(subscription) = self.access.write().resume(symbol, std::time::Instant::now()) else {
+            return false;
+        };
+        debug!("Resubscribing pruned book {}", symbol);
+        self.subscriptions.write().add(subscription);
+        self.resume_queue.write().push(symbol.to_string());
+        self.resume_notify.notify_one();
+        true
+    }
+
+    /// Unsubscribe books the application hasn't read for the pruning period
+    async fn prune_idle_books(&self, transport: &mut Box<dyn Transport>) {
+        let Some(policy) = self.config.pruning else {
+            return;
+        };
+        let idle = self.access.read().idle(policy.idle_after, std::time::Instant::now());
+        for (symbol, idle_for) in idle {
+            let Some(subscription) = self.subscriptions.write().remove_symbol(Channel::Book, &symbol) else {
+                continue;
+            };
+            info!("Pruning book {} after {:?} without reads", symbol, idle_for);
+            let unsubscribe = UnsubscribeRequest::new(subscription.to_request(None).params);
+            self.access.write().prune(&symbol, subscription);
+            if let Some(watchdog) = &self.watchdog {
+                watchdog.write().forget(Channel::Book, &symbol);
+            }
+            if let Some(mut book) = self.orderbooks.get_mut(&symbol) {
+                book.reset();
+            }
+            match serde_json::to_string(&unsubscribe) {
+                Ok(json) => {
+                    if let Err(e) = transport.send(&json).await {
+                        warn!("Failed to unsubscribe pruned book {}: {}", symbol, e);
+                    }
+                }
+                Err(e) => warn!("Failed to encode unsubscribe for {}: {}", symbol, e),
+            }
+            self.emit(SubscriptionEvent::Pruned {
+                channel: Channel::Book.as_str().to_string(),
+                symbol,
+                idle_for,
+            });
+        }
+    }
+
+    /// Subscribe every book queued by `resume_pruned`
+    async fn send_resumed_books(&self, transport: &mut Box<dyn Transport>) {
+        let symbols = std::mem::take(&mut *self.resume_queue.write());
+        for symbol in symbols {
+            let request = self
+                .subscriptions
+                .read()
+                .all()
+                .iter()
+                .find(|sub| sub.channel == Channel::Book && sub.symbols.contains(&symbol))
+                .map(|sub| sub.to_request(None));
+            let Some(request) = request else {
+                continue;
+            };
+            if let Err(e) = self.send_subscribe(transport, &request).await {
+                warn!("Failed to resubscribe pruned book {}: {}", symbol, e);
+            }
+            if let Some(watchdog) = &self.watchdog {
+                watchdog.write().reset(Channel::Book, &symbol, tokio_now());
+            }
+        }
+    }
+
+    /// Register a callback invoked inline on every book change for a symbol
+    ///
+    /// Runs alongside the event stream, inside a panic guard. Keep it short:
+    /// it runs on the connection task.
+    pub fn on_book_update<F>(&self, symbol: impl Into<Symbol>, callback: F)
+    where
+        F: Fn(&OrderbookSnapshot) + Send + Sync + 'static,
+    {
+        self.book_callbacks.on_book_update(symbol.into().into_string(), callback);
+    }
+
+    /// Register an async callback spawned on every book change for a symbol
+    ///
+    /// Each invocation is cancelled once the callback budget elapses.
+    pub fn on_book_update_async<F, Fut>(&self, symbol: impl Into<Symbol>, callback: F)
+    where
+        F: Fn(OrderbookSnapshot) -> Fut + Send + Sync + 'static,
+        Fut: std::future::Future<Output = ()> + Send + 'static,
+    {
+        self.book_callbacks.on_book_update_async(symbol.into().into_string(), callback);
+    }
+
+    /// Registered per-symbol book callbacks
+    pub fn book_callbacks(&self) -> &BookCallbacks {
+        &self.book_callbacks
+    }
+
+    /// Group symbols by their configured orderbook depth
+    fn group_by_depth(&self, symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> Vec<(Depth, Vec<Symbol>)> {
+        let mut by_depth: Vec<(Depth, Vec<Symbol>)> = Vec::new();
+        for symbol in symbols.into_iter().map(Into::into) {
+            let depth = self.config.depth_for(symbol.as_str());
+            match by_depth.iter_mut().find(|(d, _)| *d == depth) {
+                Some((_, group)) => group.push(symbol),
+                None => by_depth.push((depth, vec![symbol])),
+            }
+        }
+        if by_depth.is_empty() {
+            by_depth.push((self.config.depth, Vec::new()));
+        }
+        by_depth
+    }
+
+    /// Subscribe to orderbook updates for symbols
+    ///
+    /// Symbols with a depth override in the config are subscribed in a
+    /// separate request per depth. Returns the first request ID. Calling it
+    /// again for symbols already subscribed is a no-op that returns the
+    /// existing ID.
+    #[instrument(skip(self, symbols))]
+    pub fn subscribe_orderbook(&self, symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> u64 {
+        let req_ids: Vec<u64> = self
+            .group_by_depth(symbols)
+            .into_iter()
+            .map(|(depth, group)| self.subscribe_orderbook_with_depth(group, depth))
+            .collect();
+        req_ids[0]
+    }
+
+    /// Subscribe to orderbook updates, continuing from restored books
+    ///
+    /// Books seeded with [`restore_orderbook`](Self::restore_orderbook) are
+    /// marked synced and updated by deltas alone; the first delta's checksum
+    /// confirms them or reports a `ChecksumMismatch`. See
+    /// [`subscribe`](Self::subscribe) for symbols without a restored book.
+    pub fn subscribe_orderbook_without_snapshot(&self, symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> u64 {
+        let req_ids: Vec<u64> = self
+            .group_by_depth(symbols)
+            .into_iter()
+            .map(|(depth, group)| self.subscribe(Subscription::orderbook(group, depth).with_snapshot(false)))
+            .collect();
+        req_ids[0]
+    }
+
+    /// Subscribe to orderbook updates and wait for the server's answer
+    ///
+    /// The subscription is registered immediately; the returned future
+    /// resolves once it has been sent on (re)connect and every symbol has
+    /// been confirmed or rejected. Rejected symbols are listed in the
+    /// resolution rather than returned as an error. Fails with
+    /// `KrakenError::ChannelClosed` if the subscriptions are cleared first.
+    pub fn subscribe_orderbook_confirmed(
+        &self,
+        symbols: impl IntoIterator<Item = impl Into<Symbol>>,
+    ) -> impl std::future::Future<Output = Result<BatchResolution, KrakenError>> {
+        let mut moved = Vec::new();
+        let receivers: Vec<_> = {
+            let mut subscriptions = self.subscriptions.write();
+            self.group_by_depth(symbols)
+                .into_iter()
+                .map(|(depth, group)| {
+                    let sub = Subscription::orderbook(group, depth);
+                    moved.extend(subscriptions.shallower_books(&sub));
+                    subscriptions.add_with_confirmation(sub).1
+                })
+                .collect()
+        };
+        self.queue_depth_changes(moved);
+        async move {
+            let mut combined = BatchResolution {
+                channel: Channel::Book,
+                live: Vec::new(),
+                rejected: Vec::new(),
+            };
+            for rx in receivers {
+                let resolution = rx.await.map_err(|_| KrakenError::ChannelClosed)?;
+                combined.live.extend(resolution.live);
+                combined.rejected.extend(resolution.rejected);
+            }
+            Ok(combined)
+        }
+    }
+
+    /// Subscribe to orderbook updates for symbols at a specific depth
+    ///
+    /// The depth also sizes the local books, and carries over on reconnect.
+    /// Each symbol has one book feed: a symbol subscribed at a smaller depth
+    /// is unsubscribed there and resubscribed at `depth`, and one subscribed
+    /// deeper is left as it is.
+    pub fn subscribe_orderbook_with_depth(
+        &self,
+        symbols: impl IntoIterator<Item = impl Into<Symbol>>,
+        depth: Depth,
+    ) -> u64 {
+        let sub = Subscription::orderbook(symbols, depth);
+        self.add_subscription(sub)
+    }
+
+    /// Force a fresh server snapshot for one subscribed book
+    ///
+    /// Unsubscribes and resubscribes only this symbol's book feed, leaving
+    /// every other subscription alone. The returned future resolves once
+    /// the next snapshot for the symbol has been applied, or fails with
+    /// `KrakenError::ChecksumMismatch` if that snapshot didn't validate.
+    /// Fails with `KrakenError::InvalidState` if the symbol has no book
+    /// subscription, `KrakenError::WebSocket` if the resubscribe could not
+    /// be sent, `KrakenError::SubscriptionRejected` if the server refused it,
+    /// `KrakenError::SubscriptionTimeout` if no snapshot arrives within
+    /// `snapshot_timeout`, and `KrakenError::ChannelClosed` on shutdown.
+    ///
+    /// Useful for reconciling a book after a suspected desync.
+    pub fn request_snapshot(
+        &self,
+        symbol: impl Into<Symbol>,
+    ) -> impl std::future::Future<Output = Result<(), KrakenError>> {
+        let symbol = symbol.into().into_string();
+        let subscribed = self
+            .subscriptions
+            .read()
+            .all()
+            .iter()
+            .any(|sub| sub.channel == Channel::Book && sub.symbols.contains(&symbol));
+        let waiter = if subscribed {
+            let (tx, rx) = oneshot::channel();
+            self.snapshot_waiters.write().entry(symbol.clone()).or_default().push(tx);
+            self.snapshot_queue.write().push(symbol);
+            self.snapshot_notify.notify_one();
+            Ok(rx)
+        } else {
+            Err(KrakenError::InvalidState {
+                expected: format!("book subscription for {}", symbol),
+                actual: "not subscribed".to_string(),
+            })
+        };
+        let timeout = self.config.snapshot_timeout;
+        async move {
+            match tokio::time::timeout(timeout, waiter?).await {
+                Ok(result) => result.map_err(|_| KrakenError::ChannelClosed)?,
+                Err(_) => Err(KrakenError::SubscriptionTimeout { timeout }),
+            }
+        }
+    }
+
+    /// Resolve callers waiting on a symbol's snapshot
+    fn resolve_snapshot_waiters(&self, symbol: &str, result: Result<(), KrakenError>) {
+        let waiters = self.snapshot_waiters.write().remove(symbol);
+        for waiter in waiters.into_iter().flatten() {
+            let _ = waiter.send(result.clone());
+        }
+    }
+
+    /// Resubscribe every symbol queued by `request_snapshot` or moved to
+    /// another depth
+    async fn send_snapshot_requests(&self, transport: &mut Box<dyn Transport>) {
+        let moved = std::mem::take(&mut *self.depth_queue.write());
+        for (symbol, previous) in &moved {
+            if let Err(e) = self.send_depth_change(transport, symbol, *previous).await {
+                warn!("Failed to resubscribe {} at another depth: {}", symbol, e);
+                self.resolve_snapshot_waiters(symbol, Err(e));
+            }
+        }
+        let mut symbols = std::mem::take(&mut *self.snapshot_queue.write());
+        // The new subscription already brings a snapshot
+        symbols.retain(|symbol| !moved.iter().any(|(m, _)| m == symbol));
+        symbols.sort();
+        symbols.dedup();
+        for symbol in symbols {
+            debug!("Requesting a fresh snapshot for {}", symbol);
+            let req_id = self.subscriptions.write().reserve_req_id();
+            self.snapshot_requests.write().insert(req_id, symbol.clone());
+            if let Err(e) = self.resubscribe(transport, Channel::Book, &symbol, Some(req_id)).await {
+                warn!("Failed to request a snapshot for {}: {}", symbol, e);
+                self.snapshot_requests.write().remove(&req_id);
+                self.resolve_snapshot_waiters(&symbol, Err(KrakenError::WebSocket(e.to_string())));
+            }
+        }
+    }
+
+    /// Unsubscribe a book at its previous depth and subscribe at the stored one
+    async fn send_depth_change(
+        &self,
+        transport: &mut Box<dyn Transport>,
+        symbol: &str,
+        previous: Depth,
+    ) -> Result<(), KrakenError> {
+        let request = self
+            .subscriptions
+            .read()
+            .all()
+            .iter()
+            .find(|sub| sub.channel == Channel::Book && sub.symbols.iter().any(|s| s == symbol))
+            .map(|sub| sub.to_request(None));
+        let Some(request) = request else {
+            return Ok(());
+        };
+        let mut params = request.params.clone();
+        params.depth = Some(previous.as_u32());
+        let unsubscribe = serde_json::to_string(&UnsubscribeRequest::new(params)).map_err(|e| {
+            KrakenError::InvalidJson {
+                message: e.to_string(),
+                raw: None,
+            }
+        })?;
+        transport
+            .send(&unsubscribe)
+            .await
+            .map_err(|e| KrakenError::WebSocket(e.to_string()))?;
+        self.send_subscribe(transport, &request).await
+    }
+
+    /// Move a book's subscription to a smaller depth
+    ///
+    /// The symbol leaves its current subscription and is queued to be
+    /// resubscribed; the snapshot at the new depth shrinks the book. Returns
+    /// false if the book is not subscribed deeper than `depth`.
+    fn shrink_book_depth(&self, symbol: &str, depth: Depth) -> bool {
+        let mut subscriptions = self.subscriptions.write();
+        let current = subscriptions
+            .all()
+            .iter()
+            .find(|sub| sub.channel == Channel::Book && sub.symbols.iter().any(|s| s == symbol))
+            .and_then(|sub| sub.depth);
+        let Some(current) = current.filter(|current| current.as_u32() > depth.as_u32()) else {
+            return false;
+        };
+        let Some(share) = subscriptions.remove_symbol(Channel::Book, symbol) else {
+            return false;
+        };
+        subscriptions.add(
+            Subscription {
+                depth: Some(depth),
+                ..share
+            }
+            .with_snapshot(true),
+        );
+        drop(subscriptions);
+        self.queue_depth_changes(vec![(symbol.to_string(), current)]);
+        true
+    }
+
+    /// Empty orderbook for a symbol, sized and configured for this connection
+    fn new_orderbook(&self, symbol: &str) -> Orderbook {
+        let mut book = Orderbook::with_depth(symbol, self.book_depth(symbol).as_u32());
+        book.set_level_metadata(self.config.level_metadata);
+        if let Some(precision) = self.formatting.read().get(symbol) {
+            book.set_precision(precision.price_precision, precision.qty_precision);
+        }
+        book
+    }
+
+    /// Depth of the book subscription covering a symbol
+    fn book_depth(&self, symbol: &str) -> Depth {
+        self.subscriptions
+            .read()
+            .all()
+            .iter()
+            .filter(|sub| sub.channel == Channel::Book && sub.symbols.iter().any(|s| s == symbol))
+            .find_map(|sub| sub.depth)
+            .unwrap_or_else(|| self.config.depth_for(symbol))
+    }
+
+    /// Subscribe to ticker updates
+    #[instrument(skip(self, symbols))]
+    pub fn subscribe_ticker(&self, symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> u64 {
+        let sub = Subscription::ticker(symbols);
+        self.add_subscription(sub)
+    }
+
+    /// Subscribe to trade updates
+    #[instrument(skip(self, symbols))]
+    pub fn subscribe_trade(&self, symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> u64 {
+        let sub = Subscription::trade(symbols);
+        self.add_subscription(sub)
+    }
+
+    /// Subscribe to OHLC candles at one interval
+    #[instrument(skip(self, symbols))]
+    pub fn subscribe_ohlc(&self, symbols: impl IntoIterator<Item = impl Into<Symbol>>, interval: OhlcInterval) -> u64 {
+        let sub = Subscription::ohlc(symbols, interval);
+        self.add_subscription(sub)
+    }
+
+    /// Subscribe to L3 (Level 3) orderbook updates
+    ///
+    /// Note: L3 requires connection to the Level3 endpoint and special access.
+    /// Create a connection with `Endpoint::Level3` to use this subscription.
+    /// Prefer [`subscribe_level3`](Self::subscribe_level3), which sends the
+    /// depth and token and checks the endpoint.
+    #[instrument(skip(self, symbols))]
+    pub fn subscribe_l3(&self, symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> u64 {
+        let sub = Subscription::level3(symbols);
+        self.add_subscription(sub)
+    }
+
+    /// Subscribe to authenticated L3 orderbook updates at a given depth
+    ///
+    /// Returns a configuration error unless the connection uses
+    /// `Endpoint::Level3`. With a rate limiter configured, each symbol is
+    /// paced against the depth's L3 rate counter.
+    pub fn subscribe_level3(
+        &self,
+        symbols: impl IntoIterator<Item = impl Into<Symbol>>,
+        depth: L3Depth,
+        token: impl Into<String>,
+    ) -> Result<u64, KrakenError> {
+        if self.config.endpoint != Endpoint::Level3 {
+            return Err(KrakenError::Configuration(format!(
+                "level3 subscriptions require Endpoint::Level3, connection uses {:?}",
+                self.config.endpoint
+            )));
+        }
+        let sub = Subscription::level3_with_token(symbols, depth, token);
+        Ok(self.add_subscription(sub))
+    }
+
+    /// Active subscriptions, as they are restored after a reconnect
+    pub fn subscriptions(&self) -> Vec<Subscription> {
+        self.subscriptions.read().all().to_vec()
+    }
+
+    /// Active subscriptions with the IDs the subscribe calls returned
+    pub fn subscription_entries(&self) -> Vec<(u64, Subscription)> {
+        self.subscriptions.read().entries().map(|(id, sub)| (id, sub.clone())).collect()
+    }
+
+    /// Whether `symbol` is subscribed on `channel`
+    ///
+    /// True once the subscription is registered, before the server has
+    /// confirmed it.
+    pub fn is_subscribed(&self, channel: Channel, symbol: &str) -> bool {
+        self.subscriptions.read().is_subscribed(channel, symbol)
+    }
+
+    /// Register a subscription (traced with its channel and symbols)
+    #[instrument(skip(self, sub), fields(channel = ?sub.channel, symbols = ?sub.symbols))]
+    fn add_subscription(&self, sub: Subscription) -> u64 {
+        let mut subscriptions = self.subscriptions.write();
+        let moved = subscriptions.shallower_books(&sub);
+        let sub_id = subscriptions.add(sub);
+        drop(subscriptions);
+        self.queue_depth_changes(moved);
+        sub_id
+    }
+
+    /// Queue books whose subscription moved away from `previous` depth
+    ///
+    /// The message loop unsubscribes the previous depth and subscribes the
+    /// stored one; the snapshot at the new depth resizes the book.
+    fn queue_depth_changes(&self, moved: Vec<(String, Depth)>) {
+        if moved.is_empty() {
+            return;
+        }
+        let mut queue = self.depth_queue.write();
+        for (symbol, previous) in moved {
+            // A book moved twice before the first move went out still
+            // unsubscribes the depth the server knows
+            if !queue.iter().any(|(queued, _)| *queued == symbol) {
+                queue.push((symbol, previous));
+            }
+        }
+        drop(queue);
+        self.snapshot_notify.notify_one();
+    }
+
+    /// Register a subscription, sent on (re)connect
+    ///
+    /// A book subscription built with `with_snapshot(false)` applies deltas
+    /// on top of books seeded with
+    /// [`restore_orderbook`](Self::restore_orderbook). Symbols without a
+    /// restored book still request a snapshot, since there is nothing to
+    /// apply deltas to. Returns the first request ID.
+    pub fn subscribe(&self, sub: Subscription) -> u64 {
+        if sub.channel != Channel::Book || sub.snapshot {
+            return self.add_subscription(sub);
+        }
+        let (trusted, cold): (Vec<String>, Vec<String>) = sub.symbols.iter().cloned().partition(|symbol| {
+            self.orderbooks
+                .get_mut(symbol)
+                .is_some_and(|mut book| book.trust_restored_state())
+        });
+        if !cold.is_empty() {
+            debug!("No restored book for {:?}, subscribing with a snapshot", cold);
+        }
+        let req_ids: Vec<u64> = [(trusted, false), (cold, true)]
+            .into_iter()
+            .filter(|(symbols, _)| !symbols.is_empty())
+            .map(|(symbols, snapshot)| {
+                self.add_subscription(Subscription {
+                    symbols,
+                    snapshot,
+                    ..sub.clone()
+                })
+            })
+            .collect();
+        req_ids.first().copied().unwrap_or_else(|| self.add_subscription(sub))
+    }
+
+    /// Connect and run the connection loop
+    #[instrument(skip(self), name = "kraken_connection")]
+    pub async fn connect_and_run(&self) -> Result<(), KrakenError> {
+        self.stopped.send_replace(false);
+        let mut standby = Standby::new(self.config.standby);
+        let mut promoted = None;
+        let mut previous_delay = Duration::ZERO;
+        let mut down_since = tokio::time::Instant::now();
+        let result = loop {
+            if self.shutdown.load(Ordering::Relaxed) {
+                break Ok(());
+            }
+
+            // Check circuit breaker before attempting connection
+            if let (None, Some(breaker)) = (&promoted, &self.circuit_breaker) {
+                if !breaker.allow_request() {
+                    let stats = breaker.stats();
+                    warn!(
+                        "Circuit breaker is open (tripped {} times), waiting for recovery",
+                        stats.trips
+                    );
+                    self.emit(ConnectionEvent::CircuitBreakerOpen {
+                        trips: stats.trips,
+                    });
+                    // Wait for the circuit breaker timeout before retrying
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
+                }
+            }
+
+            // Update state
+            {
+                let mut state = self.state.write();
+                if *state == ConnectionState::Reconnecting {
+                    // Already reconnecting
                 } else {
                     *state = ConnectionState::Connecting;
                 }
             }
 
-            match self.connect_internal().await {
+            match self.connect_internal(promoted.take(), &mut standby).await {
                 Ok(()) => {
                     // Normal shutdown - record success
                     if let Some(ref breaker) = self.circuit_breaker {
                         breaker.record_success();
                     }
-                    break;
+                    break Ok(());
                 }
                 Err(e) => {
+                    if let Some(ready) = standby.take_ready() {
+                        warn!("Connection failed, failing over to standby: {}", e);
+                        self.emit(ConnectionEvent::Failover {
+                            endpoint: ready.transport.endpoint().to_string(),
+                            reason: e.to_string(),
+                        });
+                        *self.state.write() = ConnectionState::Reconnecting;
+                        promoted = Some(ready);
+                        continue;
+                    }
+
                     // Record failure with circuit breaker
                     if let Some(ref breaker) = self.circuit_breaker {
                         breaker.record_failure();
                     }
 
                     let attempt = self.reconnect_attempt.fetch_add(1, Ordering::Relaxed) + 1;
+                    if attempt == 1 {
+                        // First failure since the last successful connection
+                        previous_delay = Duration::ZERO;
+                        down_since = tokio::time::Instant::now();
+                    }
 
                     if !self.config.reconnect.should_reconnect(attempt) {
                         error!("Reconnection attempts exhausted after {} tries", attempt);
                         self.emit(ConnectionEvent::ReconnectFailed {
                             error: e.to_string(),
                         });
-                        return Err(e);
+                        break Err(e);
                     }
 
-                    let delay = self.config.reconnect.delay_with_jitter(attempt);
+                    let reason = self.disconnect_reason.lock().take();
+                    let backoff = match &self.config.backoff {
+                        Some(backoff) => backoff.delay(attempt, previous_delay),
+                        None => self.config.reconnect.delay(attempt, previous_delay),
+                    };
+                    let delay = backoff.max(self.config.reconnect.minimum_delay(reason.as_ref()));
+                    previous_delay = delay;
+
+                    let downtime = down_since.elapsed();
+                    if let Some(budget) = self.config.reconnect.max_downtime {
+                        if downtime + delay > budget {
+                            error!(
+                                "Downtime budget of {:?} exceeded after {} attempts ({:?} down)",
+                                budget, attempt, downtime
+                            );
+                            self.emit(ConnectionEvent::DowntimeExceeded {
+                                downtime,
+                                attempts: attempt,
+                            });
+                            self.emit(ConnectionEvent::ReconnectFailed {
+                                error: e.to_string(),
+                            });
+                            break Err(e);
+                        }
+                    }
                     warn!(
+                        ?reason,
                         "Connection failed, reconnecting in {:?} (attempt {}): {}",
                         delay, attempt, e
                     );
 
+                    self.health.write().record_reconnect(attempt, delay, e.to_string());
                     self.emit(ConnectionEvent::Reconnecting { attempt, delay });
                     *self.state.write() = ConnectionState::Reconnecting;
 
                     tokio::time::sleep(delay).await;
                 }
             }
-        }
+        };
 
-        *self.state.write() = ConnectionState::Disconnected;
-        Ok(())
+        standby.close().await;
+        if result.is_ok() {
+            *self.state.write() = ConnectionState::Disconnected;
+            self.health.write().record_disconnected();
+        }
+        self.stopped.send_replace(true);
+        result
     }
 
     /// Internal connection logic
-    async fn connect_internal(&self) -> Result<(), KrakenError> {
-        let url = self.config.endpoint.url();
-        info!("Connecting to {}", url);
-
-        // Connect with timeout
-        let connect_result = timeout(self.config.connect_timeout, connect_async(url)).await;
-
-        let (ws_stream, _response) = match connect_result {
-            Ok(Ok((stream, response))) => (stream, response),
-            Ok(Err(e)) => {
-                return Err(KrakenError::ConnectionFailed {
-                    url: url.to_string(),
-                    reason: e.to_string(),
-                });
+    #[instrument(
+        skip_all,
+        name = "connect",
+        fields(
+            url = tracing::field::Empty,
+            attempt = self.reconnect_attempt.load(Ordering::Relaxed),
+            connection_id = tracing::field::Empty,
+        )
+    )]
+    async fn connect_internal(
+        &self,
+        promoted: Option<ReadyStandby>,
+        standby: &mut Standby,
+    ) -> Result<(), KrakenError> {
+        let (mut transport, status) = match promoted {
+            Some(ready) => {
+                info!("Promoting standby connection to {}", ready.transport.endpoint());
+                (ready.transport, Some(ready.status))
             }
-            Err(_) => {
-                return Err(KrakenError::ConnectionTimeout {
-                    url: url.to_string(),
-                    timeout: self.config.connect_timeout,
-                });
+            None => {
+                let url = self.config.endpoint.url();
+                info!("Connecting to {}", url);
+                (self.make_transport(url), None)
             }
         };
+        let result = self.run_transport(&mut transport, status, standby).await;
+        self.traffic.write().close(transport.stats());
+        result
+    }
 
-        let (mut write, mut read) = ws_stream.split();
-
-        // Wait for status message
-        let mut connected = false;
-        while let Some(msg_result) = read.next().await {
-            match msg_result {
-                Ok(Message::Text(text)) => {
-                    if let Ok(WsMessage::Status(status_msg)) = WsMessage::parse(&text) {
-                        if let Some(data) = status_msg.data.first() {
-                            info!(
-                                "Connected to Kraken API {} (connection_id: {})",
-                                data.api_version, data.connection_id
-                            );
-
-                            self.emit(ConnectionEvent::Connected {
-                                api_version: data.api_version.clone(),
-                                connection_id: data.connection_id,
-                            });
-
-                            connected = true;
-                            break;
-                        }
-                    }
-                }
-                Ok(Message::Close(_)) => {
-                    return Err(KrakenError::WebSocket("Connection closed before ready".into()));
-                }
-                Err(e) => {
-                    return Err(KrakenError::WebSocket(e.to_string()));
-                }
-                _ => {}
+    /// Handshake unless already done, restore subscriptions, then read until
+    /// the connection ends
+    async fn run_transport(
+        &self,
+        transport: &mut Box<dyn Transport>,
+        status: Option<StatusData>,
+        standby: &mut Standby,
+    ) -> Result<(), KrakenError> {
+        let data = match status {
+            Some(status) => status,
+            None => {
+                handshake(transport, self.config.endpoint.url(), self.config.connect_timeout).await?
             }
-        }
+        };
 
-        if !connected {
-            return Err(KrakenError::WebSocket(
-                "No status message received".into(),
-            ));
-        }
+        info!(
+            "Connected to Kraken API {} (connection_id: {})",
+            data.api_version, data.connection_id
+        );
+        tracing::Span::current().record("url", transport.endpoint());
+        tracing::Span::current().record("connection_id", data.connection_id);
+        self.update_system_status(data.system);
+        self.emit(ConnectionEvent::Connected {
+            api_version: data.api_version,
+            connection_id: data.connection_id,
+        });
 
         // Update state and reset reconnect counter
         *self.state.write() = ConnectionState::Connected;
         self.reconnect_attempt.store(0, Ordering::Relaxed);
+        *self.last_api_error.lock() = None;
+        self.health.write().record_connected(std::time::Instant::now());
+
+        self.refresh_expired_tokens().await;
 
         // Subscribe to instrument channel first to get precision info
         // This is needed for correct checksum calculation
-        let requests = self.subscriptions.write().restoration_requests();
+        let (requests, restoring) = {
+            let mut subscriptions = self.subscriptions.write();
+            let requests = subscriptions.restoration_requests();
+            let restoring = subscriptions.all().to_vec();
+            // Any later connection has to rebuild the books from a snapshot
+            subscriptions.request_book_snapshots();
+            (requests, restoring)
+        };
+        let restoring = self.restoration.write().begin(&restoring, tokio_now());
+        for event in restoring {
+            self.emit(event);
+        }
 
         // Collect symbols from pending book subscriptions
         let book_symbols: Vec<String> = requests
@@ -536,29 +1907,47 @@ impl KrakenConnection {
             });
             let json = instrument_request.to_string();
             debug!("Sending instrument subscription: {}", json);
-            write
-                .send(Message::Text(json))
+            transport
+                .send(&json)
                 .await
                 .map_err(|e| KrakenError::WebSocket(e.to_string()))?;
 
-            // Wait briefly for instrument data to arrive
-            // This ensures we have precision info before processing book data
-            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+            self.await_instrument_snapshot(transport, &book_symbols).await?;
         }
 
+        // Live trades and candles are held from the first subscribe on
+        let mut backfill_rx = self.start_backfill();
+
         // Send subscription requests
         for (_req_id, request) in &requests {
-            let json = serde_json::to_string(request).map_err(|e| {
-                KrakenError::InvalidJson {
-                    message: e.to_string(),
-                    raw: None,
-                }
-            })?;
-            debug!("Sending subscription: {}", json);
-            write
-                .send(Message::Text(json))
-                .await
-                .map_err(|e| KrakenError::WebSocket(e.to_string()))?;
+            self.send_subscribe(transport, request).await?;
+        }
+
+        // Silence is measured from (re)subscription
+        if let Some(watchdog) = &self.watchdog {
+            let now = tokio_now();
+            let mut watchdog = watchdog.write();
+            for (_, request) in &requests {
+                for symbol in &request.params.symbol {
+                    watchdog.reset(request.params.channel, symbol, now);
+                }
+            }
+        }
+
+        // Depth changes went out with the restored subscriptions, and
+        // snapshot requests from an earlier connection will never be answered
+        self.depth_queue.write().clear();
+        self.snapshot_requests.write().clear();
+        if self.config.pruning.is_some() {
+            // Resumed books went out with the restored subscriptions
+            self.resume_queue.write().clear();
+            let now = std::time::Instant::now();
+            let mut access = self.access.write();
+            for (_, request) in requests.iter().filter(|(_, r)| r.params.channel == Channel::Book) {
+                for symbol in &request.params.symbol {
+                    access.watch(symbol, now);
+                }
+            }
         }
 
         if !requests.is_empty() {
@@ -569,77 +1958,270 @@ impl KrakenConnection {
 
         // Reset heartbeat timer
         *self.last_message_time.write() = std::time::Instant::now();
+        // Held updates belong to the previous connection's books
+        self.conflator.write().clear();
+
+        self.refill_standby(standby);
+
+        let mut sample_tick = self.config.book_sampler.as_ref().map(|sampler| {
+            let mut tick = tokio::time::interval(sampler.interval);
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            tick
+        });
+        let mut stale_tick = self.config.stale_threshold.map(|threshold| {
+            let mut tick = tokio::time::interval((threshold / 2).max(Duration::from_millis(10)));
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            tick
+        });
+        let mut prune_tick = self.config.pruning.map(|policy| {
+            let mut tick = tokio::time::interval(policy.check_interval());
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            tick
+        });
+        let mut audit_tick = self.config.audit_interval.map(|interval| {
+            let mut tick = tokio::time::interval(interval.max(Duration::from_millis(10)));
+            tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            tick
+        });
+        let mut memory_tick = self
+            .config
+            .memory_limits
+            .and_then(|limits| limits.total_bytes)
+            .map(|_| {
+                let mut tick = tokio::time::interval(Duration::from_secs(1));
+                tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                tick
+            });
 
         // Main message loop with heartbeat timeout
         loop {
             if self.shutdown.load(Ordering::Relaxed) {
                 info!("Shutdown requested, closing connection");
-                let _ = write.send(Message::Close(None)).await;
+                let _ = transport.close().await;
                 break;
             }
 
             // Use heartbeat timeout or a default long timeout
             let heartbeat_timeout = self.config.heartbeat_timeout.unwrap_or(Duration::from_secs(3600));
 
+            self.traffic.write().current = transport.stats();
+            let conflation_due = self.conflator.read().next_due();
+            let restore_due = self.restoration.read().deadline();
+
             let msg_result = tokio::select! {
-                msg = read.next() => msg,
-                _ = tokio::time::sleep(heartbeat_timeout) => {
+                msg = transport.recv() => msg,
+                // Deadline is relative to the last message so sample ticks don't postpone it
+                _ = tokio::time::sleep_until(tokio::time::Instant::from_std(
+                    *self.last_message_time.read() + heartbeat_timeout,
+                )) => {
+                    // Ping/pong frames never surface from recv() but prove the link is alive
+                    if let Some(frame_at) = transport.last_frame_at() {
+                        let mut last = self.last_message_time.write();
+                        if frame_at > *last {
+                            *last = frame_at;
+                        }
+                    }
                     // Check if we've actually timed out
                     let elapsed = self.last_message_time.read().elapsed();
                     if elapsed >= heartbeat_timeout {
                         warn!("Heartbeat timeout: no message received for {:?}", elapsed);
-                        self.emit(ConnectionEvent::Disconnected {
-                            reason: DisconnectReason::HeartbeatTimeout,
-                        });
+                        self.disconnected(DisconnectReason::HeartbeatTimeout);
                         return Err(KrakenError::WebSocket("Heartbeat timeout".into()));
                     }
                     continue;
                 }
-            };
-
-            match msg_result {
-                Some(Ok(Message::Text(text))) => {
-                    *self.last_message_time.write() = std::time::Instant::now();
-                    self.handle_message(&text);
+                _ = next_tick(&mut sample_tick) => {
+                    self.emit_book_samples();
+                    continue;
                 }
-                Some(Ok(Message::Ping(data))) => {
-                    *self.last_message_time.write() = std::time::Instant::now();
-                    let _ = write.send(Message::Pong(data)).await;
+                _ = sleep_until_opt(conflation_due) => {
+                    self.flush_conflated();
+                    continue;
                 }
-                Some(Ok(Message::Pong(_))) => {
-                    *self.last_message_time.write() = std::time::Instant::now();
+                _ = sleep_until_opt(restore_due) => {
+                    let expired = self.restoration.write().expire(tokio_now());
+                    for event in expired {
+                        self.emit(event);
+                    }
+                    continue;
                 }
-                Some(Ok(Message::Close(_))) => {
-                    info!("Server closed connection");
-                    self.emit(ConnectionEvent::Disconnected {
-                        reason: DisconnectReason::ServerClosed,
-                    });
-                    return Err(KrakenError::WebSocket("Server closed connection".into()));
+                _ = next_tick(&mut stale_tick) => {
+                    self.check_stale_feeds(transport).await;
+                    continue;
                 }
-                Some(Err(e)) => {
-                    error!("WebSocket error: {}", e);
-                    self.emit(ConnectionEvent::Disconnected {
-                        reason: DisconnectReason::NetworkError(e.to_string()),
-                    });
-                    return Err(KrakenError::WebSocket(e.to_string()));
+                _ = next_tick(&mut memory_tick) => {
+                    self.enforce_total_memory();
+                    continue;
                 }
-                Some(Ok(_)) => {}
-                None => {
-                    info!("WebSocket stream ended");
-                    break;
+                _ = next_tick(&mut audit_tick) => {
+                    self.audit_books();
+                    continue;
                 }
-            }
+                _ = self.snapshot_notify.notified() => {
+                    self.send_snapshot_requests(transport).await;
+                    continue;
+                }
+                _ = next_tick(&mut prune_tick) => {
+                    self.prune_idle_books(transport).await;
+                    continue;
+                }
+                _ = self.resume_notify.notified() => {
+                    self.send_resumed_books(transport).await;
+                    continue;
+                }
+                _ = standby.run() => {
+                    self.refill_standby(standby);
+                    continue;
+                }
+                (request, result) = next_backfill(&mut backfill_rx) => {
+                    self.complete_backfill(request, result);
+                    continue;
+                }
+            };
+
+            self.handle_received(msg_result, transport.as_ref())?;
         }
 
         Ok(())
     }
 
-    /// Handle an incoming message
-    fn handle_message(&self, text: &str) {
-        match WsMessage::parse(text) {
+    /// Handle one `recv` result, emitting `Disconnected` if the connection ended
+    fn handle_received(
+        &self,
+        msg_result: Result<Option<String>, TransportError>,
+        transport: &dyn Transport,
+    ) -> Result<(), KrakenError> {
+        match msg_result {
+            Ok(Some(text)) => {
+                let received_at = ReceivedAt::now();
+                *self.last_message_time.write() = received_at.instant;
+                self.handle_message(&text, received_at);
+                Ok(())
+            }
+            Ok(None) => {
+                let frame = transport.close_frame();
+                info!(?frame, "Server closed connection");
+                let from_frame = frame.map(|frame| DisconnectReason::from_close_frame(frame.code, &frame.reason));
+                let reason = match from_frame {
+                    Some(reason) if reason != DisconnectReason::ServerClosed => reason,
+                    _ => self.inferred_disconnect_reason().unwrap_or(DisconnectReason::ServerClosed),
+                };
+                self.disconnected(reason);
+                Err(KrakenError::WebSocket("Server closed connection".into()))
+            }
+            Err(e) => {
+                error!("WebSocket error: {}", e);
+                let reason = self
+                    .inferred_disconnect_reason()
+                    .unwrap_or_else(|| DisconnectReason::NetworkError(e.to_string()));
+                self.disconnected(reason);
+                Err(KrakenError::WebSocket(e.to_string()))
+            }
+        }
+    }
+
+    /// Disconnect reason implied by a recent API error or the system status
+    ///
+    /// The error is used up: it explains at most one disconnect, and only
+    /// within [`API_ERROR_RELEVANCE`] of arriving.
+    fn inferred_disconnect_reason(&self) -> Option<DisconnectReason> {
+        self.last_api_error
+            .lock()
+            .take()
+            .filter(|(_, at)| at.elapsed() <= API_ERROR_RELEVANCE)
+            .and_then(|(error, _)| DisconnectReason::from_api_error(&error))
+            .or_else(|| {
+                (self.system_status() == Some(SystemStatus::Maintenance)).then_some(DisconnectReason::MaintenanceMode)
+            })
+    }
+
+    /// Record why the connection ended and emit `Disconnected`
+    fn disconnected(&self, reason: DisconnectReason) {
+        *self.disconnect_reason.lock() = Some(reason.clone());
+        self.emit(ConnectionEvent::Disconnected { reason });
+    }
+
+    /// Handle messages until the book symbols' precision is known
+    ///
+    /// Returns once every symbol has instrument data or an instrument
+    /// snapshot arrives (symbols missing from it have none to wait for).
+    /// After `instrument_timeout` the books are subscribed anyway and infer
+    /// precision from their snapshots.
+    async fn await_instrument_snapshot(
+        &self,
+        transport: &mut Box<dyn Transport>,
+        symbols: &[String],
+    ) -> Result<(), KrakenError> {
+        let seen = self.instrument_snapshots.load(Ordering::Acquire);
+        let deadline = tokio::time::Instant::now() + self.config.instrument_timeout;
+        loop {
+            let known = {
+                let formatting = self.formatting.read();
+                symbols.iter().all(|symbol| formatting.get(symbol).is_some())
+            };
+            if known || self.instrument_snapshots.load(Ordering::Acquire) != seen {
+                return Ok(());
+            }
+
+            match tokio::time::timeout_at(deadline, transport.recv()).await {
+                Ok(msg_result) => self.handle_received(msg_result, transport.as_ref())?,
+                Err(_) => {
+                    warn!(
+                        "No instrument data after {:?}, inferring book precision from snapshots",
+                        self.config.instrument_timeout
+                    );
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Open a new standby transport if the slot is empty
+    fn refill_standby(&self, standby: &mut Standby) {
+        if standby.needs_transport() {
+            let endpoint = self.config.standby_endpoint.unwrap_or(self.config.endpoint);
+            debug!("Opening standby connection to {}", endpoint.url());
+            standby.start(self.make_transport(endpoint.url()), self.config.connect_timeout);
+        }
+    }
+
+    /// Build the transport for one connection attempt
+    fn make_transport(&self, url: &str) -> Box<dyn Transport> {
+        let transport: Box<dyn Transport> = match &self.config.transport {
+            Some(factory) => factory.create(url),
+            None => {
+                let transport = WsTransport::new(url)
+                    .with_timeout(self.config.connect_timeout)
+                    .with_network(self.config.network.clone());
+                #[cfg(feature = "deflate")]
+                let transport = transport.with_compression(self.config.compression);
+                Box::new(transport)
+            }
+        };
+        match &self.config.tap {
+            Some(tap) => tap.wrap(transport),
+            None => transport,
+        }
+    }
+
+    /// Handle an incoming message
+    #[instrument(
+        skip_all,
+        level = "debug",
+        name = "handle_message",
+        fields(bytes = text.len(), kind = tracing::field::Empty)
+    )]
+    fn handle_message(&self, text: &str, received_at: ReceivedAt) {
+        let parsed = WsMessage::parse(text);
+        if let Ok(msg) = &parsed {
+            tracing::Span::current().record("kind", msg.kind());
+            self.health.write().record_message(msg.kind(), received_at.instant);
+        }
+        match parsed {
             Ok(msg) => match msg {
                 WsMessage::Status(status_msg) => {
                     if let Some(data) = status_msg.data.first() {
+                        self.update_system_status(data.system);
                         self.emit(MarketEvent::Status {
                             system: data.system.to_string(),
                             version: data.api_version.clone(),
@@ -650,77 +2232,173 @@ impl KrakenConnection {
                     self.handle_subscribe_response(&resp);
                 }
                 WsMessage::Book(book_msg) => {
-                    if let Some(data) = book_msg.data.first() {
+                    // Updates still in flight for a pruned book are dropped
+                    let data = book_msg.data.first().filter(|data| {
+                        self.config.pruning.is_none() || !self.access.read().is_pruned(&data.symbol)
+                    });
+                    if let Some(data) = data {
                         let symbol = &data.symbol;
                         let is_snapshot = book_msg.msg_type == "snapshot";
+                        let exchange_ts_us =
+                            data.timestamp.as_deref().and_then(parse_exchange_timestamp);
+                        self.touch_feed(Channel::Book, symbol);
+                        self.record_latency(exchange_ts_us, received_at);
+
+                        // A snapshot starts the book over at the current subscription depth
+                        let depth = is_snapshot.then(|| self.book_depth(symbol).as_u32());
 
                         // Get or create orderbook
                         let mut orderbook =
-                            self.orderbooks.entry(symbol.clone()).or_insert_with(|| {
-                                Orderbook::with_depth(symbol, self.config.depth as u32)
-                            });
+                            self.orderbooks.entry(symbol.clone()).or_insert_with(|| self.new_orderbook(symbol));
+                        if let Some(depth) = depth.filter(|depth| *depth != orderbook.depth()) {
+                            debug!("Book depth for {} changed from {} to {}", symbol, orderbook.depth(), depth);
+                            orderbook.set_depth(depth);
+                        }
 
                         // Apply the update
-                        match orderbook.apply_book_data(data, is_snapshot) {
-                            Ok(_result) => {
-                                let snapshot = orderbook.snapshot();
-                                let event = if is_snapshot {
-                                    MarketEvent::OrderbookSnapshot {
-                                        symbol: symbol.clone(),
-                                        snapshot,
-                                    }
-                                } else {
-                                    MarketEvent::OrderbookUpdate {
-                                        symbol: symbol.clone(),
-                                        snapshot,
-                                    }
-                                };
-                                self.emit(event);
-                            }
-                            Err(mismatch) => {
-                                warn!(
-                                    "Checksum mismatch for {}: expected {}, computed {}",
-                                    mismatch.symbol, mismatch.expected, mismatch.computed
-                                );
-                                self.emit(MarketEvent::ChecksumMismatch {
-                                    symbol: symbol.clone(),
+                        let outcome = orderbook.apply_book_data(data, is_snapshot);
+                        let applied = match &outcome {
+                            Ok(_) => true,
+                            Err(error) => error.was_applied(),
+                        };
+                        if is_snapshot {
+                            let result = match &outcome {
+                                Err(ApplyError::ChecksumMismatch(mismatch)) => Err(KrakenError::ChecksumMismatch {
+                                    symbol: mismatch.symbol.clone(),
                                     expected: mismatch.expected,
                                     computed: mismatch.computed,
-                                });
+                                }),
+                                _ => Ok(()),
+                            };
+                            self.resolve_snapshot_waiters(symbol, result);
+                        }
+                        // The book event and its diagnostics go out as one batch
+                        let mut batch = Vec::with_capacity(2);
+                        if applied {
+                            self.enforce_book_memory(&mut orderbook);
+                            let emit_book =
+                                self.conflate(symbol, is_snapshot, outcome.is_ok(), received_at, exchange_ts_us);
+                            // Held updates only need a copy of the book for callbacks
+                            if emit_book || self.book_callbacks.has_callbacks(symbol) {
+                                let snapshot = orderbook.snapshot();
+                                // Release the book so callbacks can read it
+                                drop(orderbook);
+                                self.book_callbacks.dispatch(&snapshot);
+                                if emit_book {
+                                    let seq = self.next_seq(symbol);
+                                    let event = if is_snapshot {
+                                        MarketEvent::OrderbookSnapshot {
+                                            symbol: symbol.clone(),
+                                            seq,
+                                            snapshot,
+                                            received_at,
+                                            exchange_ts_us,
+                                        }
+                                    } else {
+                                        MarketEvent::OrderbookUpdate {
+                                            symbol: symbol.clone(),
+                                            seq,
+                                            snapshot,
+                                            received_at,
+                                            exchange_ts_us,
+                                        }
+                                    };
+                                    batch.push(event);
+                                }
+                            }
+                        }
+                        let synced = is_snapshot && outcome.is_ok();
+                        if let Err(error) = outcome {
+                            batch.push(self.apply_error_event(error));
+                        }
+                        self.emit_symbol_batches(batch);
+                        if synced {
+                            let restored = self.restoration.write().on_snapshot(symbol);
+                            for event in restored {
+                                self.emit(event);
                             }
                         }
                     }
                 }
-                WsMessage::Ticker(_ticker_msg) => {
-                    // Ticker channel - emit via MarketEvent in future version
-                    debug!("Ticker update received");
+                WsMessage::Ticker(ticker_msg) => {
+                    let mut events = Vec::with_capacity(ticker_msg.data.len());
+                    for ticker in ticker_msg.data {
+                        self.touch_feed(Channel::Ticker, &ticker.symbol);
+                        events.push(MarketEvent::Ticker {
+                            symbol: ticker.symbol.clone(),
+                            seq: self.next_seq(&ticker.symbol),
+                            ticker,
+                            received_at,
+                            exchange_ts_us: None,
+                        });
+                    }
+                    self.emit_symbol_batches(events);
                 }
-                WsMessage::Trade(_trade_msg) => {
-                    // Trade channel - emit via MarketEvent in future version
-                    debug!("Trade update received");
+                WsMessage::Trade(trade_msg) => {
+                    let backfilled = self.backfills(Channel::Trade);
+                    let mut events = Vec::with_capacity(trade_msg.data.len());
+                    for trade in trade_msg.data {
+                        let exchange_ts_us = parse_exchange_timestamp(&trade.timestamp);
+                        self.touch_feed(Channel::Trade, &trade.symbol);
+                        self.record_latency(exchange_ts_us, received_at);
+                        let trade = match backfilled {
+                            true => match self.backfill.lock().admit_trade(trade) {
+                                Some(trade) => trade,
+                                None => continue,
+                            },
+                            false => trade,
+                        };
+                        events.push(self.trade_event(trade, received_at));
+                    }
+                    self.emit_symbol_batches(events);
                 }
-                WsMessage::Ohlc(_ohlc_msg) => {
-                    // OHLC channel - emit via MarketEvent in future version
-                    debug!("OHLC update received");
+                WsMessage::Ohlc(ohlc_msg) => {
+                    let backfilled = self.backfills(Channel::Ohlc);
+                    let mut events = Vec::with_capacity(ohlc_msg.data.len());
+                    for candle in ohlc_msg.data {
+                        self.touch_feed(Channel::Ohlc, &candle.symbol);
+                        let candle = match backfilled {
+                            true => match self.backfill.lock().admit_candle(candle) {
+                                Some(candle) => candle,
+                                None => continue,
+                            },
+                            false => candle,
+                        };
+                        events.push(self.ohlc_event(candle, received_at));
+                    }
+                    self.emit_symbol_batches(events);
                 }
                 WsMessage::Instrument(instrument_msg) => {
                     // Update precision for each trading pair from instrument data
                     for pair in &instrument_msg.data.pairs {
                         let symbol = &pair.symbol;
+                        self.formatting.write().apply_instrument(pair);
+                        if let Some(status) = pair.status.as_deref() {
+                            match PairStatus::parse(status) {
+                                Some(status) => {
+                                    self.update_pair_status(symbol, status);
+                                }
+                                None => debug!("Unknown status {:?} for {}", status, symbol),
+                            }
+                        }
 
-                        // Get or create orderbook and update its precision
-                        let mut orderbook =
-                            self.orderbooks.entry(symbol.clone()).or_insert_with(|| {
-                                Orderbook::with_depth(symbol, self.config.depth as u32)
-                            });
-
-                        orderbook.set_precision(pair.price_precision, pair.qty_precision);
+                        // Books created later pick it up in `new_orderbook`
+                        if let Some(mut orderbook) = self.orderbooks.get_mut(symbol) {
+                            let was_synced = orderbook.is_synced();
+                            orderbook.set_precision(pair.price_precision, pair.qty_precision);
+                            if !was_synced && orderbook.is_synced() {
+                                info!("{} resynced with instrument precision", symbol);
+                            }
+                        }
 
                         debug!(
                             "Updated precision for {}: price={}, qty={}",
                             symbol, pair.price_precision, pair.qty_precision
                         );
                     }
+                    if instrument_msg.msg_type == "snapshot" {
+                        self.instrument_snapshots.fetch_add(1, Ordering::Release);
+                    }
                 }
                 WsMessage::Executions(_executions_msg) => {
                     // Private channel: order executions - requires auth feature
@@ -733,6 +2411,7 @@ impl KrakenConnection {
                 WsMessage::Level3(l3_msg) => {
                     // L3 orderbook data
                     if let Some(data) = l3_msg.data.first() {
+                        self.touch_feed(Channel::Level3, &data.symbol);
                         let is_snapshot = l3_msg.msg_type == "snapshot";
                         let event = L3Event::from_data(data, is_snapshot);
                         debug!(
@@ -762,32 +2441,694 @@ impl KrakenConnection {
         }
     }
 
+    /// Warn if the server acknowledged a book at a depth other than requested
+    fn check_ack_depth(&self, result: &SubscribeResult) {
+        let (Some(symbol), Some(server)) = (&result.symbol, result.depth) else {
+            return;
+        };
+        if result.channel != Channel::Book.as_str() {
+            return;
+        }
+        let configured = self.book_depth(symbol).as_u32();
+        if server != configured {
+            warn!(
+                "{} book acknowledged at depth {}, configured {}; keeping {}",
+                symbol, server, configured, configured
+            );
+            self.emit(MarketEvent::DepthMismatch {
+                symbol: symbol.clone(),
+                configured,
+                server,
+            });
+        }
+    }
+
     /// Handle subscription response
     fn handle_subscribe_response(&self, resp: &MethodResponse) {
+        *self.last_api_error.lock() = match (resp.success, &resp.error) {
+            (false, Some(error)) => Some((KrakenApiError::parse(error), tokio::time::Instant::now())),
+            _ => None,
+        };
+        // A snapshot resubscribe that succeeded resolves with the snapshot
+        if let Some(symbol) = resp.req_id.and_then(|req_id| self.snapshot_requests.write().remove(&req_id)) {
+            if !resp.success {
+                let reason = resp.error.clone().unwrap_or_default();
+                warn!("Snapshot request for {} rejected: {}", symbol, reason);
+                self.resolve_snapshot_waiters(
+                    &symbol,
+                    Err(KrakenError::SubscriptionRejected {
+                        channel: Channel::Book.as_str().to_string(),
+                        reason,
+                    }),
+                );
+            }
+            return;
+        }
         if let Some(req_id) = resp.req_id {
-            if resp.success {
-                self.subscriptions.write().confirm(req_id);
+            let symbol = resp
+                .result
+                .as_ref()
+                .and_then(|result| result.symbol.as_deref())
+                .or(resp.symbol.as_deref());
+            let outcome = if resp.success {
+                Ok(())
+            } else {
+                Err(resp.error.clone().unwrap_or_default())
+            };
+
+            let (request, resolution) = {
+                let mut subscriptions = self.subscriptions.write();
+                let request = subscriptions.request(req_id).cloned();
+                if subscriptions.request_channel(req_id).is_none() {
+                    // Not part of a tracked batch
+                    if resp.success {
+                        subscriptions.confirm(req_id);
+                    } else {
+                        subscriptions.reject(req_id);
+                    }
+                }
+                (request, subscriptions.resolve(req_id, symbol, outcome.clone()))
+            };
+
+            // Restoration events follow the responses they depend on
+            let restored = match (&resolution, &request) {
+                (Some(resolution), _) => self.restoration.write().on_resolution(resolution),
+                (None, Some(request)) if request.symbols.is_empty() => {
+                    let outcome = outcome.as_ref().map(|_| ()).map_err(String::as_str);
+                    self.restoration.write().on_channel_response(request.channel, outcome)
+                }
+                _ => Vec::new(),
+            };
 
+            if resp.success {
                 if let Some(result) = &resp.result {
+                    self.check_ack_depth(result);
                     self.emit(SubscriptionEvent::Subscribed {
                         channel: result.channel.clone(),
                         symbols: result.symbol.clone().into_iter().collect(),
                     });
                 }
             } else {
-                self.subscriptions.write().reject(req_id);
-
+                let reason = resp.error.clone().unwrap_or_default();
+                let symbols = match (symbol, &request) {
+                    (Some(symbol), _) => vec![symbol.to_string()],
+                    (None, Some(request)) => request.symbols.clone(),
+                    (None, None) => Vec::new(),
+                };
                 self.emit(SubscriptionEvent::Rejected {
-                    channel: "unknown".to_string(),
-                    reason: resp.error.clone().unwrap_or_default(),
+                    channel: request.map_or("unknown", |r| r.channel.as_str()).to_string(),
+                    symbols,
+                    error: KrakenApiError::parse(&reason),
+                    reason,
+                });
+            }
+
+            if let Some(resolution) = resolution {
+                if !resolution.rejected.is_empty() {
+                    warn!(
+                        "{} subscription: {} symbols live, {} rejected",
+                        resolution.channel.as_str(),
+                        resolution.live.len(),
+                        resolution.rejected.len()
+                    );
+                }
+                self.emit(SubscriptionEvent::BatchResolved {
+                    channel: resolution.channel.as_str().to_string(),
+                    live: resolution.live,
+                    rejected: resolution.rejected,
                 });
             }
+            for event in restored {
+                self.emit(event);
+            }
+        }
+    }
+
+    /// Resubscribe a book that is over the per-book memory budget at a
+    /// smaller depth
+    fn enforce_book_memory(&self, book: &mut Orderbook) {
+        let Some(limits) = &self.config.memory_limits else {
+            return;
+        };
+        let bytes_before = book.approx_bytes();
+        let Some(depth) = enforce_book_limit(book, None, limits).and_then(|eviction| eviction.depth) else {
+            return;
+        };
+        if !self.shrink_book_depth(book.symbol(), depth) {
+            return;
+        }
+        warn!(
+            "{} book over its memory budget, resubscribing at depth {}",
+            book.symbol(),
+            depth.as_u32()
+        );
+        self.emit(ConnectionEvent::MemoryCapReached {
+            symbol: Some(book.symbol().to_string()),
+            level_cap: depth.as_u32() as usize,
+            bytes_before,
+            limit_bytes: limits.per_book_bytes.unwrap_or_default(),
+        });
+    }
+
+    /// Resubscribe the deepest books at a smaller depth when all books
+    /// together are over budget
+    fn enforce_total_memory(&self) {
+        let Some(limit_bytes) = self.config.memory_limits.and_then(|limits| limits.total_bytes) else {
+            return;
+        };
+        let levels: Vec<(usize, usize)> = self
+            .orderbooks
+            .iter()
+            .map(|book| (book.bid_count(), book.ask_count()))
+            .collect();
+        let Some(cap) = self.config.memory_limits.and_then(|limits| limits.plan_total(&levels)) else {
+            return;
+        };
+        let depth = depth_within(cap);
+        let bytes_before = self.approx_memory_bytes();
+        // Books already resubscribed keep their old levels until the new
+        // snapshot arrives; those are skipped by `shrink_book_depth`
+        let deeper: Vec<String> = self
+            .orderbooks
+            .iter()
+            .filter(|book| book.bid_count().max(book.ask_count()) > depth.as_u32() as usize)
+            .map(|book| book.symbol().to_string())
+            .collect();
+        let moved = deeper.iter().filter(|symbol| self.shrink_book_depth(symbol, depth)).count();
+        if moved == 0 {
+            return;
+        }
+        warn!(
+            "Orderbooks over the {} byte memory budget, resubscribing {} at depth {}",
+            limit_bytes,
+            moved,
+            depth.as_u32()
+        );
+        self.emit(ConnectionEvent::MemoryCapReached {
+            symbol: None,
+            level_cap: depth.as_u32() as usize,
+            bytes_before,
+            limit_bytes,
+        });
+    }
+
+    /// Check every book's invariants and report the ones that fail
+    fn audit_books(&self) {
+        let failed: Vec<_> = self
+            .orderbooks
+            .iter()
+            .filter_map(|book| {
+                let violations = audit(&book);
+                (!violations.is_empty()).then(|| (book.key().clone(), violations))
+            })
+            .collect();
+        for (symbol, violations) in failed {
+            warn!(
+                "Audit of {} found {} violation(s): {:?}",
+                symbol,
+                violations.len(),
+                violations
+            );
+            self.health.write().record_audit_violations(violations.len());
+            self.emit(MarketEvent::BookAuditFailed { symbol, violations });
+        }
+    }
+
+    /// Approximate bytes held by all orderbooks
+    pub fn approx_memory_bytes(&self) -> usize {
+        self.orderbooks.iter().map(|book| book.approx_bytes()).sum()
+    }
+
+    /// Approximate bytes held by each orderbook, by symbol
+    pub fn memory_usage(&self) -> BTreeMap<String, usize> {
+        self.orderbooks
+            .iter()
+            .map(|book| (book.key().clone(), book.approx_bytes()))
+            .collect()
+    }
+
+    /// Log a book apply failure and build its market event
+    fn apply_error_event(&self, error: ApplyError) -> MarketEvent {
+        if error.was_applied() {
+            debug!("{}", error);
+        } else {
+            warn!("{}", error);
+        }
+        match error {
+            ApplyError::ChecksumMismatch(mismatch) => {
+                self.health.write().record_checksum_mismatch();
+                MarketEvent::ChecksumMismatch {
+                    symbol: mismatch.symbol,
+                    expected: mismatch.expected,
+                    computed: mismatch.computed,
+                }
+            }
+            ApplyError::OutOfOrderUpdate { symbol, last, received } => {
+                MarketEvent::OutOfOrderUpdate { symbol, last, received }
+            }
+            ApplyError::UpdateBeforeSnapshot { symbol } => MarketEvent::UpdateBeforeSnapshot { symbol },
+            ApplyError::CrossedBook { symbol, best_bid, best_ask } => {
+                MarketEvent::CrossedBook { symbol, best_bid, best_ask }
+            }
+            ApplyError::DepthOverflow { symbol, levels, depth } => {
+                MarketEvent::DepthOverflow { symbol, levels, depth }
+            }
+        }
+    }
+
+    fn touch_feed(&self, channel: Channel, symbol: &str) {
+        if let Some(watchdog) = &self.watchdog {
+            watchdog.write().touch(channel, symbol, tokio_now());
+        }
+    }
+
+    /// Report feeds that went quiet, resubscribing them if configured
+    async fn check_stale_feeds(&self, transport: &mut Box<dyn Transport>) {
+        let Some(watchdog) = &self.watchdog else {
+            return;
+        };
+        let stale = watchdog.write().check(tokio_now());
+
+        for feed in stale {
+            warn!(
+                "{} feed for {} silent for {:?}",
+                feed.channel.as_str(),
+                feed.symbol,
+                feed.silent_for
+            );
+            self.emit(SubscriptionEvent::Stale {
+                channel: feed.channel.as_str().to_string(),
+                symbol: feed.symbol.clone(),
+                silent_for: feed.silent_for,
+            });
+
+            if self.config.resubscribe_stale {
+                if let Err(e) = self.resubscribe(transport, feed.channel, &feed.symbol, None).await {
+                    warn!("Failed to resubscribe {}: {}", feed.symbol, e);
+                }
+                watchdog.write().reset(feed.channel, &feed.symbol, tokio_now());
+            }
+        }
+    }
+
+    /// Whether live events on `channel` pass through the backfill tracker
+    fn backfills(&self, channel: Channel) -> bool {
+        self.config.backfill.as_ref().is_some_and(|b| b.covers(channel))
+    }
+
+    /// Hold live data for the backfilled subscriptions and spawn their fetches
+    fn start_backfill(&self) -> Option<mpsc::UnboundedReceiver<BackfillDone>> {
+        let backfiller = self.config.backfill.as_ref()?;
+        let mut trades = Vec::new();
+        let mut candles = Vec::new();
+        for sub in self.subscriptions.read().all() {
+            match (sub.channel, sub.interval) {
+                (Channel::Trade, _) if backfiller.covers(Channel::Trade) => trades.extend(sub.symbols.iter().cloned()),
+                (Channel::Ohlc, Some(interval)) if backfiller.covers(Channel::Ohlc) => {
+                    candles.extend(sub.symbols.iter().map(|symbol| (symbol.clone(), interval)));
+                }
+                _ => {}
+            }
+        }
+        let requests = self.backfill.lock().begin(&trades, &candles);
+        if requests.is_empty() {
+            return None;
+        }
+
+        debug!("Backfilling {} trade and OHLC windows", requests.len());
+        let (tx, rx) = mpsc::unbounded_channel();
+        for request in requests {
+            let backfiller = backfiller.clone();
+            let tx = tx.clone();
+            tokio::spawn(async move {
+                let result = backfiller.fetch(request.clone()).await;
+                let _ = tx.send((request, result));
+            });
+        }
+        Some(rx)
+    }
+
+    /// Emit a fetched window, then the live events held while it loaded
+    fn complete_backfill(&self, request: BackfillRequest, result: Result<BackfillData, KrakenError>) {
+        let history = match result {
+            Ok(history) => Some(history),
+            Err(e) => {
+                warn!(symbol = request.symbol(), "Backfill failed, releasing live data without it: {}", e);
+                None
+            }
+        };
+        let released = self.backfill.lock().complete(&request, history);
+        let received_at = ReceivedAt::now();
+        let events = match released {
+            Released::Trades(trades) => trades.into_iter().map(|t| self.trade_event(t, received_at)).collect(),
+            Released::Ohlc(candles) => candles.into_iter().map(|c| self.ohlc_event(c, received_at)).collect(),
+        };
+        self.emit_symbol_batches(events);
+    }
+
+    fn trade_event(&self, trade: TradeData, received_at: ReceivedAt) -> MarketEvent {
+        MarketEvent::Trade {
+            symbol: trade.symbol.clone(),
+            seq: self.next_seq(&trade.symbol),
+            exchange_ts_us: parse_exchange_timestamp(&trade.timestamp),
+            trade,
+            received_at,
+        }
+    }
+
+    fn ohlc_event(&self, candle: OhlcData, received_at: ReceivedAt) -> MarketEvent {
+        MarketEvent::Ohlc {
+            symbol: candle.symbol.clone(),
+            seq: self.next_seq(&candle.symbol),
+            candle,
+            received_at,
+        }
+    }
+
+    /// Swap expired subscription tokens for a fresh one before restoring
+    async fn refresh_expired_tokens(&self) {
+        let now = std::time::Instant::now();
+        if !self.subscriptions.read().has_expired_tokens(now) {
+            return;
+        }
+        let Some(refresher) = &self.config.token_refresher else {
+            warn!("Resending expired subscription tokens; configure a token refresher to replace them");
+            return;
+        };
+        match refresher.refresh().await {
+            Ok(token) => {
+                let replaced = self.subscriptions.write().replace_expired_tokens(
+                    &token,
+                    std::time::Instant::now() + crate::TOKEN_LIFETIME,
+                    now,
+                );
+                info!("Refreshed the token of {} subscriptions", replaced);
+            }
+            Err(e) => warn!("Token refresh failed, resending expired tokens: {}", e),
+        }
+    }
+
+    /// Pace and send one subscribe request
+    #[instrument(
+        skip_all,
+        name = "subscribe",
+        fields(channel = ?request.params.channel, symbols = request.params.symbol.len(), req_id = ?request.req_id)
+    )]
+    async fn send_subscribe(
+        &self,
+        transport: &mut Box<dyn Transport>,
+        request: &SubscribeRequest,
+    ) -> Result<(), KrakenError> {
+        self.pace_subscribe(request).await;
+        let json = serde_json::to_string(request).map_err(|e| KrakenError::InvalidJson {
+            message: e.to_string(),
+            raw: None,
+        })?;
+        debug!("Sending subscription: {}", json);
+        transport
+            .send(&json)
+            .await
+            .map_err(|e| KrakenError::WebSocket(e.to_string()))
+    }
+
+    /// Unsubscribe and resubscribe a single feed
+    ///
+    /// `req_id` tags the subscribe request so its response can be matched.
+    async fn resubscribe(
+        &self,
+        transport: &mut Box<dyn Transport>,
+        channel: Channel,
+        symbol: &str,
+        req_id: Option<u64>,
+    ) -> Result<(), TransportError> {
+        let symbols = vec![symbol.to_string()];
+        // Reuse the stored subscription so depth and token carry over
+        let stored = self
+            .subscriptions
+            .read()
+            .all()
+            .iter()
+            .find(|sub| sub.channel == channel && sub.symbols.iter().any(|s| s == symbol))
+            .map(|sub| Subscription {
+                symbols: symbols.clone(),
+                ..sub.clone()
+            });
+        let subscription = match (stored, channel) {
+            // Resubscribing a book is always a request for a fresh snapshot
+            (Some(sub), Channel::Book) => sub.with_snapshot(true),
+            (Some(sub), _) => sub,
+            (None, Channel::Book) => {
+                let depth = self.config.depth_for(symbol);
+                Subscription::orderbook(symbols, depth)
+            }
+            (None, Channel::Level3) => Subscription::level3(symbols),
+            (None, channel) => Subscription::new(channel, symbols),
+        };
+        let subscribe = subscription.to_request(req_id);
+        let unsubscribe = UnsubscribeRequest::new(subscribe.params.clone());
+
+        let encode = |e: serde_json::Error| TransportError::Protocol(e.to_string());
+        transport.send(&serde_json::to_string(&unsubscribe).map_err(encode)?).await?;
+        self.pace_subscribe(&subscribe).await;
+        transport.send(&serde_json::to_string(&subscribe).map_err(encode)?).await
+    }
+
+    /// Wait for a subscribe token when a rate limiter is configured
+    ///
+    /// Level3 requests also take one token per symbol from the rate counter
+    /// for their depth.
+    async fn pace_subscribe(&self, request: &SubscribeRequest) {
+        if let Some(limiter) = &self.config.rate_limiter {
+            while let Some(wait) = limiter.try_acquire_subscribe().wait_duration() {
+                debug!("Pacing subscribe request for {:?}", wait);
+                tokio::time::sleep(wait).await;
+            }
+            if request.params.channel == Channel::Level3 {
+                let category = RateLimitCategory::from_l3_depth(request.params.depth.unwrap_or(10));
+                let symbols = request.params.symbol.len().max(1) as u32;
+                while let Some(wait) = limiter.try_acquire_n(category, symbols).wait_duration() {
+                    debug!("Pacing level3 subscribe for {:?}", wait);
+                    tokio::time::sleep(wait).await;
+                }
+            }
         }
     }
 
     /// Emit an event
     fn emit(&self, event: impl Into<Event>) {
-        self.event_tx.send(event.into());
+        let event = event.into();
+        if self.dispatch_inline(&event) {
+            return;
+        }
+        let mut last_id = self.last_event_id.lock();
+        *last_id += 1;
+        self.event_tx.send(SequencedEvent { id: *last_id, event });
+    }
+
+    /// Hand an event to the inline handler if its channel is routed there
+    fn dispatch_inline(&self, event: &Event) -> bool {
+        match (&self.config.inline, event) {
+            (Some(inline), Event::Market(market)) if inline.handles(market) => {
+                inline.dispatch(market);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Stamp ids on a batch and send it while holding the id lock
+    fn emit_batch(&self, mut events: Vec<Event>) {
+        if self.config.inline.is_some() {
+            events.retain(|event| !self.dispatch_inline(event));
+            if events.is_empty() {
+                return;
+            }
+        }
+        let mut last_id = self.last_event_id.lock();
+        let batch = events
+            .into_iter()
+            .map(|event| {
+                *last_id += 1;
+                SequencedEvent { id: *last_id, event }
+            })
+            .collect();
+        self.event_tx.send_batch(batch);
+    }
+
+    /// Emit market events as one batch per symbol
+    ///
+    /// Keeps per-symbol delivery consistent under `DropNewest`: a message's
+    /// events for a symbol arrive together or not at all, even when symbols
+    /// are interleaved. Batches go out in order of each symbol's first event.
+    fn emit_symbol_batches(&self, events: Vec<MarketEvent>) {
+        let mut batches: Vec<(Option<String>, Vec<Event>)> = Vec::new();
+        for event in events {
+            let symbol = event.symbol().map(str::to_string);
+            match batches.iter_mut().find(|(batch_symbol, _)| *batch_symbol == symbol) {
+                Some((_, batch)) => batch.push(event.into()),
+                None => batches.push((symbol, vec![event.into()])),
+            }
+        }
+        for (_, batch) in batches {
+            self.emit_batch(batch);
+        }
+    }
+
+    /// Next per-symbol sequence number (starts at 1)
+    fn next_seq(&self, symbol: &str) -> u64 {
+        let mut seqs = self.symbol_seq.write();
+        match seqs.get_mut(symbol) {
+            Some(seq) => {
+                *seq += 1;
+                *seq
+            }
+            None => {
+                seqs.insert(symbol.to_string(), 1);
+                1
+            }
+        }
+    }
+
+    fn record_latency(&self, exchange_ts_us: Option<i64>, received_at: ReceivedAt) {
+        if let Some(exchange_us) = exchange_ts_us {
+            self.latency.write().record(exchange_us, received_at);
+            let skew = {
+                let mut clock = self.clock.write();
+                clock.record_exchange_timestamp(exchange_us, received_at.wall_us);
+                clock.check_skew()
+            };
+            if let Some(skew) = skew {
+                warn!(
+                    "Local clock is {}ms off the exchange (threshold {}ms)",
+                    skew.offset_us / 1000,
+                    skew.threshold_us / 1000
+                );
+                self.emit(ConnectionEvent::ClockSkew {
+                    offset_us: skew.offset_us,
+                    threshold_us: skew.threshold_us,
+                });
+            }
+        }
+    }
+
+    /// Record a REST `Time` response to tighten the clock offset estimate
+    ///
+    /// `sent_us` and `received_us` are the local wall-clock times of the
+    /// request and response, in microseconds since the Unix epoch.
+    pub fn record_server_time(&self, server_unix_secs: i64, sent_us: i64, received_us: i64) {
+        self.clock
+            .write()
+            .record_rest_time(server_unix_secs, sent_us, received_us);
+    }
+
+    /// Local clock offset and drift relative to the exchange
+    ///
+    /// Returns None until a timestamped message or REST sample has arrived.
+    pub fn clock_estimate(&self) -> Option<ClockEstimate> {
+        self.clock.read().estimate()
+    }
+
+    /// Current time on the exchange's clock, as estimated from the offset
+    pub fn estimated_server_time(&self) -> Option<SystemTime> {
+        self.clock.read().estimated_server_time()
+    }
+
+    /// Messages and payload bytes sent and received over the connection's lifetime
+    ///
+    /// The sum of [`Transport::stats`] over every transport used so far,
+    /// including decompression counters when compression is enabled.
+    /// Useful for sizing bandwidth at deep book depths across many symbols.
+    pub fn traffic_stats(&self) -> TransportStats {
+        let traffic = self.traffic.read();
+        traffic.closed + traffic.current
+    }
+
+    /// Exchange-to-client latency statistics over recent timestamped messages
+    ///
+    /// Returns None until a message carrying an exchange timestamp (book
+    /// updates, trades) has been received. See [`crate::latency`] for how to
+    /// read the numbers.
+    pub fn latency_stats(&self) -> Option<LatencyStats> {
+        self.latency.read().stats()
+    }
+
+    /// Per-channel message counts, reconnect history and heartbeat age
+    ///
+    /// See [`crate::health`] for turning two snapshots into message rates.
+    pub fn health(&self) -> HealthStats {
+        let mut stats = self.health.read().snapshot(std::time::Instant::now());
+        stats.since_last_message = self.time_since_last_message();
+        stats.dropped_events = self.dropped_event_count();
+        stats
+    }
+
+    /// Rate limiter pacing subscribe requests, if one was configured
+    pub fn rate_limiter(&self) -> Option<&SharedRateLimiter> {
+        self.config.rate_limiter.as_ref()
+    }
+
+    /// Emit a sample for every synced orderbook
+    fn emit_book_samples(&self) {
+        let Some(sampler) = &self.config.book_sampler else {
+            return;
+        };
+        let samples: Vec<_> = self
+            .orderbooks
+            .iter()
+            .filter_map(|book| sampler.sample(&book).map(|s| (book.key().clone(), s)))
+            .collect();
+        for (symbol, sample) in samples {
+            self.emit(MarketEvent::BookSample { symbol, sample });
+        }
+    }
+
+    /// Whether an applied book message should be emitted now
+    ///
+    /// Updates for a conflated symbol inside its interval are held for
+    /// [`flush_conflated`](Self::flush_conflated). Snapshots always go out.
+    /// A message that left the book out of sync is not emitted and drops
+    /// the held update, which would otherwise go out with the broken book.
+    fn conflate(
+        &self,
+        symbol: &str,
+        is_snapshot: bool,
+        synced: bool,
+        received_at: ReceivedAt,
+        exchange_ts_us: Option<i64>,
+    ) -> bool {
+        let Some(interval) = self.config.conflation_for(symbol) else {
+            return true;
+        };
+        let mut conflator = self.conflator.write();
+        if !synced {
+            conflator.discard(symbol);
+            return false;
+        }
+        if is_snapshot {
+            conflator.emitted(symbol, interval, received_at.instant);
+            return true;
+        }
+        conflator.offer(symbol, interval, received_at.instant, PendingUpdate { received_at, exchange_ts_us })
+    }
+
+    /// Emit the latest state of every book whose held update is due
+    fn flush_conflated(&self) {
+        let due = self.conflator.write().take_due(std::time::Instant::now());
+        for (symbol, pending) in due {
+            // A book resyncing since the update was held has nothing valid to show
+            let Some(book) = self.orderbooks.get(&symbol).filter(|book| book.is_synced()) else {
+                continue;
+            };
+            let snapshot = book.snapshot();
+            drop(book);
+            self.emit(MarketEvent::OrderbookUpdate {
+                seq: self.next_seq(&symbol),
+                symbol,
+                snapshot,
+                received_at: pending.received_at,
+                exchange_ts_us: pending.exchange_ts_us,
+            });
+        }
     }
 
     /// Request shutdown
@@ -796,6 +3137,8 @@ impl KrakenConnection {
         info!("Shutdown requested");
         self.shutdown.store(true, Ordering::Relaxed);
         *self.state.write() = ConnectionState::ShuttingDown;
+        // Pending snapshot requests fail with ChannelClosed
+        self.snapshot_waiters.write().clear();
     }
 
     /// Request shutdown and wait for disconnection
@@ -823,6 +3166,15 @@ impl KrakenConnection {
         }
     }
 
+    /// Watch whether [`connect_and_run`](Self::connect_and_run) has returned
+    ///
+    /// Turns true when the run loop exits, after a shutdown or for any other
+    /// reason, and back to false if it is started again. Nothing the run
+    /// loop emits arrives after it turns true.
+    pub fn watch_stopped(&self) -> watch::Receiver<bool> {
+        self.stopped.subscribe()
+    }
+
     /// Check if shutdown has been requested
     pub fn is_shutting_down(&self) -> bool {
         self.shutdown.load(Ordering::Relaxed)
@@ -851,9 +3203,1201 @@ mod tests {
     }
 
     #[test]
-    fn test_connection_state() {
+    fn test_symbol_depth_overrides() {
+        let config = ConnectionConfig::new().with_symbol_depth("BTC/USD", Depth::D1000);
+        assert_eq!(config.depth_for("BTC/USD"), Depth::D1000);
+        assert_eq!(config.depth_for("DOT/USD"), Depth::D10);
+
+        let conn = KrakenConnection::new(config);
+        conn.subscribe_orderbook(["BTC/USD", "DOT/USD", "ADA/USD"]);
+        let subs = conn.subscriptions.read();
+        assert_eq!(subs.count(), 2);
+        assert_eq!(subs.all()[0].depth, Some(Depth::D1000));
+        assert_eq!(subs.all()[0].symbols, vec!["BTC/USD"]);
+        assert_eq!(subs.all()[1].depth, Some(Depth::D10));
+        assert_eq!(subs.all()[1].symbols, vec!["DOT/USD", "ADA/USD"]);
+    }
+
+    #[test]
+    fn test_owned_and_scoped_orderbook_access() {
         let conn = KrakenConnection::with_defaults();
-        assert_eq!(conn.state(), ConnectionState::Disconnected);
+        assert!(conn.orderbook_snapshot("BTC/USD").is_none());
+        assert!(conn.with_orderbook("BTC/USD", |book| book.is_synced()).is_none());
+
+        conn.orderbooks.insert("BTC/USD".to_string(), conn.new_orderbook("BTC/USD"));
+        let snapshot = conn.orderbook_snapshot("BTC/USD").unwrap();
+        assert_eq!(snapshot.symbol, "BTC/USD");
+        // No guard is left behind, so the book is writable again
+        assert!(conn.orderbooks.get_mut("BTC/USD").is_some());
+        assert_eq!(conn.with_orderbook("BTC/USD", |book| book.is_synced()), Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_event_ids_count_drops_and_resume() {
+        let conn = KrakenConnection::new(ConnectionConfig::new().with_channel_capacity(2, BackpressurePolicy::DropNewest));
+        let mut events = conn.take_event_receiver().unwrap();
+        conn.resume_event_ids(41);
+        conn.resume_event_ids(7);
+
+        for _ in 0..3 {
+            conn.emit(ConnectionEvent::Disconnected { reason: DisconnectReason::Timeout });
+        }
+        assert_eq!(conn.last_event_id(), 44);
+        assert_eq!(events.recv_sequenced().await.unwrap().id, 42);
+        assert_eq!(events.recv_sequenced().await.unwrap().id, 43);
+
+        // The third event was dropped, so the next delivered id skips it
+        conn.emit(ConnectionEvent::Disconnected { reason: DisconnectReason::Timeout });
+        assert_eq!(events.recv_sequenced().await.unwrap().id, 45);
+    }
+
+    #[test]
+    fn test_restore_orderbook_seeds_unsynced_book() {
+        use kraken_types::{Decimal, Level};
+
+        let conn = KrakenConnection::with_defaults();
+        conn.subscribe_orderbook_with_depth(["BTC/USD"], Depth::D25);
+        conn.restore_orderbook(&OrderbookSnapshot {
+            symbol: "BTC/USD".to_string(),
+            bids: (0..40).map(|i| Level::new(Decimal::from(100 - i), Decimal::ONE)).collect(),
+            asks: vec![Level::new(Decimal::from(101), Decimal::ONE)],
+            checksum: 7,
+            ..Default::default()
+        });
+
+        let restored = conn.orderbook_snapshots();
+        assert_eq!(restored.len(), 1);
+        assert_eq!(restored[0].bids.len(), 25);
+        assert_eq!(restored[0].checksum, 7);
+        assert_eq!(conn.with_orderbook("BTC/USD", |book| book.is_synced()), Some(false));
+    }
+
+    #[test]
+    fn test_subscribe_without_snapshot_trusts_restored_books() {
+        use kraken_types::{Decimal, Level};
+
+        let conn = KrakenConnection::with_defaults();
+        conn.restore_orderbook(&OrderbookSnapshot {
+            symbol: "BTC/USD".to_string(),
+            bids: vec![Level::new(Decimal::from(100), Decimal::ONE)],
+            asks: vec![Level::new(Decimal::from(101), Decimal::ONE)],
+            ..Default::default()
+        });
+        conn.subscribe_orderbook_without_snapshot(["BTC/USD", "ETH/USD"]);
+
+        assert_eq!(conn.with_orderbook("BTC/USD", |book| book.is_synced()), Some(true));
+        let subs = conn.subscriptions();
+        assert_eq!(subs.len(), 2);
+        assert_eq!((subs[0].symbols.as_slice(), subs[0].snapshot), (&["BTC/USD".to_string()][..], false));
+        assert_eq!((subs[1].symbols.as_slice(), subs[1].snapshot), (&["ETH/USD".to_string()][..], true));
+    }
+
+    #[test]
+    fn test_subscribe_orderbook_with_depth_sizes_book() {
+        let conn = KrakenConnection::with_defaults();
+        conn.subscribe_orderbook_with_depth(["BTC/USD"], Depth::D100);
+        assert_eq!(conn.book_depth("BTC/USD"), Depth::D100);
+        assert_eq!(conn.book_depth("ETH/USD"), Depth::D10);
+    }
+
+    #[test]
+    fn test_second_book_depth_moves_the_feed() {
+        let conn = KrakenConnection::with_defaults();
+        let first = conn.subscribe_orderbook_with_depth(["BTC/USD", "ETH/USD"], Depth::D10);
+
+        // Deeper: BTC/USD moves and the old depth is queued for unsubscribe
+        let deep = conn.subscribe_orderbook_with_depth(["BTC/USD"], Depth::D100);
+        assert_ne!(deep, first);
+        assert_eq!(conn.book_depth("BTC/USD"), Depth::D100);
+        assert_eq!(*conn.depth_queue.read(), vec![("BTC/USD".to_string(), Depth::D10)]);
+
+        // Shallower: already covered, nothing changes
+        assert_eq!(conn.subscribe_orderbook_with_depth(["BTC/USD"], Depth::D25), deep);
+        assert_eq!(conn.book_depth("BTC/USD"), Depth::D100);
+        assert_eq!(conn.depth_queue.read().len(), 1);
+
+        let books: Vec<_> = conn
+            .subscriptions()
+            .into_iter()
+            .map(|sub| (sub.symbols, sub.depth))
+            .collect();
+        assert_eq!(
+            books,
+            vec![
+                (vec!["ETH/USD".to_string()], Some(Depth::D10)),
+                (vec!["BTC/USD".to_string()], Some(Depth::D100)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_book_callbacks_run_for_their_symbol() {
+        use crate::scenario::Scenario;
+        use rust_decimal_macros::dec;
+        use std::sync::atomic::AtomicU64;
+
+        let config = ConnectionConfig::new().without_reconnect().with_transport_factory(|url| {
+            Box::new(
+                Scenario::new()
+                    .send_status()
+                    .send_snapshot("BTC/USD", &[(dec!(100), dec!(1))], &[(dec!(101), dec!(2))])
+                    .send_snapshot("ETH/USD", &[(dec!(10), dec!(1))], &[(dec!(11), dec!(2))])
+                    .close()
+                    .into_transport(url),
+            )
+        });
+        let conn = Arc::new(KrakenConnection::new(config));
+        conn.subscribe_orderbook(["BTC/USD", "ETH/USD"]);
+
+        let hits = Arc::new(AtomicU64::new(0));
+        let counter = Arc::clone(&hits);
+        let reader = Arc::clone(&conn);
+        conn.on_book_update("BTC/USD", move |snapshot| {
+            // The live book is readable from inside the callback
+            assert!(reader.orderbook("BTC/USD").is_some());
+            assert_eq!(snapshot.symbol, "BTC/USD");
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        assert!(conn.connect_and_run().await.is_err());
+        assert_eq!(hits.load(Ordering::Relaxed), 1);
+        assert_eq!(conn.book_callbacks().stats().invocations, 1);
+    }
+
+    #[test]
+    fn test_system_status_transitions() {
+        let conn = KrakenConnection::with_defaults();
+        let mut events = conn.take_event_receiver().unwrap();
+        let watch = conn.watch_system_status();
+
+        conn.update_system_status(SystemStatus::Online);
+        conn.update_system_status(SystemStatus::Online);
+        conn.update_system_status(SystemStatus::Maintenance);
+        assert_eq!(*watch.borrow(), Some(SystemStatus::Maintenance));
+
+        let EventReceiver::Unbounded(rx) = &mut events else {
+            panic!("expected unbounded receiver");
+        };
+        let mut transitions = Vec::new();
+        while let Ok(SequencedEvent {
+            event: Event::Connection(ConnectionEvent::SystemStatusChanged { previous, current }),
+            ..
+        }) = rx.try_recv()
+        {
+            transitions.push((previous, current));
+        }
+        assert_eq!(
+            transitions,
+            vec![
+                (None, SystemStatus::Online),
+                (Some(SystemStatus::Online), SystemStatus::Maintenance),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_pair_status_transitions_from_instrument_channel() {
+        let conn = KrakenConnection::with_defaults();
+        conn.subscribe_orderbook(["ETH/USD"]);
+        let statuses = conn.watch_pair_status();
+        let mut events = conn.take_event_receiver().unwrap();
+        let instrument = |btc: &str, eth: &str| {
+            format!(
+                r#"{{"channel":"instrument","type":"update","data":{{"assets":[],"pairs":[
+                    {{"symbol":"BTC/USD","price_precision":1,"qty_precision":8,"status":"{}"}},
+                    {{"symbol":"ETH/USD","price_precision":2,"qty_precision":8,"status":"{}"}}]}}}}"#,
+                btc, eth
+            )
+        };
+
+        // First sight of a restricted pair seeds it without an event
+        conn.handle_message(&instrument("limit_only", "online"), ReceivedAt::now());
+        conn.handle_message(&instrument("cancel_only", "post_only"), ReceivedAt::now());
+        conn.handle_message(&instrument("cancel_only", "post_only"), ReceivedAt::now());
+        assert_eq!(conn.pair_status("BTC/USD"), Some(PairStatus::CancelOnly));
+        assert_eq!(statuses.borrow().get("ETH/USD"), Some(&PairStatus::PostOnly));
+        assert_eq!(conn.pair_status("XRP/USD"), None);
+        assert_eq!(conn.restricted_pairs().get("ETH/USD"), Some(&PairStatus::PostOnly));
+        // Fed from a REST poll
+        assert!(conn.update_pair_status("ETH/USD", PairStatus::Online));
+
+        let EventReceiver::Unbounded(rx) = &mut events else {
+            panic!("expected unbounded receiver");
+        };
+        let mut transitions = Vec::new();
+        while let Ok(SequencedEvent { event, .. }) = rx.try_recv() {
+            if let Event::Connection(ConnectionEvent::PairStatusChanged { symbol, previous, current }) = event {
+                transitions.push((symbol, previous, current));
+            }
+        }
+        assert_eq!(
+            transitions,
+            vec![
+                ("ETH/USD".to_string(), Some(PairStatus::Online), PairStatus::PostOnly),
+                ("ETH/USD".to_string(), Some(PairStatus::PostOnly), PairStatus::Online),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_connection_state() {
+        let conn = KrakenConnection::with_defaults();
+        assert_eq!(conn.state(), ConnectionState::Disconnected);
         assert!(!conn.is_connected());
     }
+
+    #[test]
+    fn test_subscribe_level3_requires_l3_endpoint() {
+        let public = KrakenConnection::with_defaults();
+        assert!(matches!(
+            public.subscribe_level3(["BTC/USD"], L3Depth::D10, "tok"),
+            Err(KrakenError::Configuration(_))
+        ));
+
+        let l3 = KrakenConnection::new(ConnectionConfig::new().with_endpoint(Endpoint::Level3));
+        assert!(l3.subscribe_level3(["BTC/USD"], L3Depth::D100, "tok").is_ok());
+        let subs = l3.subscriptions.read();
+        assert_eq!(subs.all()[0].depth, Some(Depth::D100));
+    }
+
+    #[tokio::test]
+    async fn test_transport_factory_drives_connection() {
+        use crate::scenario::Scenario;
+        use rust_decimal_macros::dec;
+
+        let config = ConnectionConfig::new().without_reconnect().with_transport_factory(|url| {
+            Box::new(
+                Scenario::new()
+                    .send_status()
+                    .send_snapshot("BTC/USD", &[(dec!(100), dec!(1))], &[(dec!(101), dec!(2))])
+                    .close()
+                    .into_transport(url),
+            )
+        });
+        let conn = KrakenConnection::new(config);
+        conn.subscribe_orderbook(vec!["BTC/USD".to_string()]);
+        let mut events = conn.take_event_receiver().unwrap();
+
+        // The scripted server closes, and reconnection is disabled
+        assert!(conn.connect_and_run().await.is_err());
+        assert_eq!(conn.orderbook("BTC/USD").unwrap().best_bid().unwrap().price, dec!(100));
+
+        let mut saw_connected = false;
+        let mut saw_snapshot = false;
+        while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+            match event {
+                Event::Connection(ConnectionEvent::Connected { .. }) => saw_connected = true,
+                Event::Market(MarketEvent::OrderbookSnapshot { .. }) => saw_snapshot = true,
+                _ => {}
+            }
+        }
+        assert!(saw_connected && saw_snapshot);
+        // Status, the instrument snapshot awaited before subscribing, and the book
+        assert_eq!(conn.traffic_stats().messages_received, 3);
+        // The instrument and book subscriptions
+        assert_eq!(conn.traffic_stats().messages_sent, 2);
+        assert_eq!(
+            conn.orderbook("BTC/USD").unwrap().precision_source(),
+            kraken_book::PrecisionSource::Configured
+        );
+        assert_eq!(conn.health().messages_by_channel.get("book"), Some(&1));
+    }
+
+    #[tokio::test]
+    async fn test_disconnect_reason_from_close_frame_and_api_error() {
+        use crate::scenario::Scenario;
+
+        let cases = [
+            (Scenario::new().send_status().close_with(1008, ""), DisconnectReason::RateLimited),
+            (
+                Scenario::new().send_status().send_subscribe_error("EService:Unavailable").close(),
+                DisconnectReason::MaintenanceMode,
+            ),
+            (Scenario::new().send_status().close_with(1011, "internal error"), DisconnectReason::ProtocolError { code: 1011 }),
+            (Scenario::new().send_status().close(), DisconnectReason::ServerClosed),
+        ];
+        for (scenario, expected) in cases {
+            let config = ConnectionConfig::new()
+                .without_reconnect()
+                .with_transport_factory(move |url| Box::new(scenario.clone().into_transport(url)));
+            let conn = KrakenConnection::new(config);
+            let mut events = conn.take_event_receiver().unwrap();
+            assert!(conn.connect_and_run().await.is_err());
+
+            let mut reason = None;
+            while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+                if let Event::Connection(ConnectionEvent::Disconnected { reason: r }) = event {
+                    reason = Some(r);
+                }
+            }
+            assert_eq!(reason, Some(expected));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stale_api_error_does_not_explain_disconnect() {
+        let conn = KrakenConnection::with_defaults();
+        let rejected = |conn: &KrakenConnection| {
+            *conn.last_api_error.lock() =
+                Some((KrakenApiError::parse("EAPI:Rate limit exceeded"), tokio::time::Instant::now()));
+        };
+
+        rejected(&conn);
+        assert_eq!(conn.inferred_disconnect_reason(), Some(DisconnectReason::RateLimited));
+        // Used up by the disconnect it explained
+        assert_eq!(conn.inferred_disconnect_reason(), None);
+
+        rejected(&conn);
+        tokio::time::advance(API_ERROR_RELEVANCE + Duration::from_secs(1)).await;
+        assert_eq!(conn.inferred_disconnect_reason(), None);
+    }
+
+    #[tokio::test]
+    async fn test_control_frames_refresh_heartbeat_deadline() {
+        use crate::scenario::Scenario;
+
+        // 200 ms without a data frame, but a ping every 20 ms
+        let mut scenario = Scenario::new().send_status();
+        for _ in 0..10 {
+            scenario = scenario.delay(Duration::from_millis(20)).send_ping();
+        }
+        let config = ConnectionConfig::new()
+            .without_reconnect()
+            .with_heartbeat_timeout(Duration::from_millis(60))
+            .with_transport_factory(move |url| Box::new(scenario.clone().send_heartbeat().close().into_transport(url)));
+        let conn = KrakenConnection::new(config);
+        let mut events = conn.take_event_receiver().unwrap();
+        assert!(conn.connect_and_run().await.is_err());
+
+        let mut reason = None;
+        while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+            if let Event::Connection(ConnectionEvent::Disconnected { reason: r }) = event {
+                reason = Some(r);
+            }
+        }
+        assert_eq!(reason, Some(DisconnectReason::ServerClosed));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_downtime_budget_stops_custom_backoff() {
+        use crate::transport::MockTransport;
+
+        let config = ConnectionConfig::new()
+            .without_circuit_breaker()
+            .with_backoff(|attempt: u32, _previous: Duration| Duration::from_millis(10) * attempt)
+            .with_reconnect(ReconnectConfig::new().with_max_downtime(Duration::from_millis(45)))
+            .with_transport_factory(|url| {
+                let mut mock = MockTransport::new(url);
+                mock.fail_connect = true;
+                Box::new(mock)
+            });
+        let conn = KrakenConnection::new(config);
+        let mut events = conn.take_event_receiver().unwrap();
+        assert!(conn.connect_and_run().await.is_err());
+
+        let mut delays = Vec::new();
+        let mut terminal = Vec::new();
+        while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+            match event {
+                Event::Connection(ConnectionEvent::Reconnecting { delay, .. }) => delays.push(delay.as_millis()),
+                Event::Connection(ConnectionEvent::DowntimeExceeded { attempts, downtime }) => {
+                    assert_eq!(downtime, Duration::from_millis(30));
+                    terminal.push(attempts)
+                }
+                Event::Connection(ConnectionEvent::ReconnectFailed { .. }) => terminal.push(0),
+                _ => {}
+            }
+        }
+        // 10 + 20 ms slept; a 30 ms third delay would end past the 45 ms budget
+        assert_eq!(delays, vec![10, 20]);
+        assert_eq!(terminal, vec![3, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_ack_at_other_depth_warns() {
+        use crate::scenario::Scenario;
+
+        let ack = r#"{"method":"subscribe","req_id":1,"result":{"channel":"book","depth":25,"snapshot":true,"symbol":"BTC/USD"},"success":true,"time_in":"2025-12-21T12:28:24.000000Z","time_out":"2025-12-21T12:28:24.001000Z"}"#;
+        let config = ConnectionConfig::new().without_reconnect().with_transport_factory(move |url| {
+            Box::new(Scenario::new().send_status().send_raw(ack).close().into_transport(url))
+        });
+        let conn = KrakenConnection::new(config);
+        conn.subscribe_orderbook(["BTC/USD"]);
+        let mut events = conn.take_event_receiver().unwrap();
+        assert!(conn.connect_and_run().await.is_err());
+
+        let mut mismatches = Vec::new();
+        while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+            if let Event::Market(MarketEvent::DepthMismatch { symbol, configured, server }) = event {
+                mismatches.push((symbol, configured, server));
+            }
+        }
+        assert_eq!(mismatches, vec![("BTC/USD".to_string(), 10, 25)]);
+    }
+
+    #[tokio::test]
+    async fn test_message_tap_records_wire_traffic() {
+        use crate::scenario::Scenario;
+        use crate::scenario::fixtures;
+        use crate::tap::{Direction, MessageTap};
+
+        let (tap, mut frames) = MessageTap::channel();
+        let config = ConnectionConfig::new()
+            .without_reconnect()
+            .with_message_tap(tap)
+            .with_transport_factory(|url| {
+                Box::new(Scenario::new().send_status().send_malformed().close().into_transport(url))
+            });
+        let conn = KrakenConnection::new(config);
+        conn.subscribe_ticker(["BTC/USD"]);
+        assert!(conn.connect_and_run().await.is_err());
+
+        let mut captured = Vec::new();
+        while let Ok(frame) = frames.try_recv() {
+            captured.push((frame.direction, frame.text));
+        }
+        assert_eq!(captured[0], (Direction::Inbound, fixtures::STATUS_MESSAGE.to_string()));
+        assert!(captured
+            .iter()
+            .any(|(direction, text)| *direction == Direction::Outbound && text.contains("ticker")));
+        assert!(captured.contains(&(Direction::Inbound, fixtures::MALFORMED_MESSAGE.to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_inline_handler_takes_selected_channels() {
+        use crate::scenario::Scenario;
+        use rust_decimal_macros::dec;
+
+        let inline_books = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&inline_books);
+        let config = ConnectionConfig::new()
+            .without_reconnect()
+            .with_inline_handler([Channel::Book], move |event: &MarketEvent| {
+                if let MarketEvent::OrderbookSnapshot { .. } = event {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+            .with_transport_factory(|url| {
+                Box::new(
+                    Scenario::new()
+                        .send_status()
+                        .send_snapshot("BTC/USD", &[(dec!(100), dec!(1))], &[(dec!(101), dec!(2))])
+                        .close()
+                        .into_transport(url),
+                )
+            });
+        let conn = KrakenConnection::new(config);
+        conn.subscribe_orderbook(["BTC/USD"]);
+        let mut events = conn.take_event_receiver().unwrap();
+        assert!(conn.connect_and_run().await.is_err());
+
+        assert_eq!(inline_books.load(Ordering::Relaxed), 1);
+        assert_eq!(conn.inline_stats().unwrap().invocations, 1);
+        let mut saw_connected = false;
+        while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+            match event {
+                Event::Connection(ConnectionEvent::Connected { .. }) => saw_connected = true,
+                Event::Market(market) => assert_ne!(market.channel(), Some(Channel::Book)),
+                _ => {}
+            }
+        }
+        assert!(saw_connected);
+    }
+
+    #[tokio::test]
+    async fn test_conflation_emits_latest_book_once_per_interval() {
+        use crate::scenario::Scenario;
+        use rust_decimal_macros::dec;
+
+        let config = ConnectionConfig::new()
+            .without_reconnect()
+            .with_conflation(Duration::from_millis(40))
+            .with_transport_factory(|url| {
+                Box::new(
+                    Scenario::new()
+                        .send_status()
+                        .send_snapshot("BTC/USD", &[(dec!(100), dec!(1))], &[(dec!(101), dec!(2))])
+                        .send_update("BTC/USD", &[(dec!(100), dec!(2))], &[])
+                        .send_update("BTC/USD", &[(dec!(100), dec!(3))], &[])
+                        .delay(Duration::from_millis(60))
+                        .send_update("BTC/USD", &[(dec!(100), dec!(4))], &[])
+                        .send_corrupt_update("BTC/USD", &[(dec!(100), dec!(5))], &[])
+                        .delay(Duration::from_millis(100))
+                        .close()
+                        .into_transport(url),
+                )
+            });
+        let conn = KrakenConnection::new(config);
+        conn.subscribe_orderbook(["BTC/USD"]);
+        let mut events = conn.take_event_receiver().unwrap();
+        assert!(conn.connect_and_run().await.is_err());
+
+        let mut kinds = Vec::new();
+        while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+            match event {
+                Event::Market(MarketEvent::OrderbookSnapshot { .. }) => kinds.push("snapshot".to_string()),
+                Event::Market(MarketEvent::OrderbookUpdate { snapshot, .. }) => {
+                    kinds.push(format!("update {}", snapshot.bids[0].qty))
+                }
+                Event::Market(MarketEvent::ChecksumMismatch { .. }) => kinds.push("mismatch".to_string()),
+                _ => {}
+            }
+        }
+        // Updates 2 and 3 collapse into one. The mismatch isn't held back,
+        // and update 4, held when it hit, is dropped rather than sent with
+        // the desynced book
+        assert_eq!(kinds, vec!["snapshot", "update 3", "mismatch"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_silent_feed_is_reported_stale() {
+        use crate::scenario::Scenario;
+        use rust_decimal_macros::dec;
+
+        let config = ConnectionConfig::new()
+            .without_reconnect()
+            .with_stale_detection(Duration::from_millis(30))
+            .with_stale_resubscribe()
+            .with_transport_factory(|url| {
+                Box::new(
+                    Scenario::new()
+                        .send_status()
+                        .send_snapshot("BTC/USD", &[(dec!(100), dec!(1))], &[(dec!(101), dec!(2))])
+                        .delay(Duration::from_millis(100))
+                        .close()
+                        .into_transport(url),
+                )
+            });
+        let conn = KrakenConnection::new(config);
+        conn.subscribe_orderbook(vec!["BTC/USD".to_string(), "ETH/USD".to_string()]);
+        let mut events = conn.take_event_receiver().unwrap();
+        let _ = conn.connect_and_run().await;
+
+        let mut stale = Vec::new();
+        while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+            if let Event::Subscription(SubscriptionEvent::Stale { channel, symbol, silent_for }) = event {
+                assert_eq!(channel, "book");
+                assert!(silent_for > Duration::from_millis(30));
+                stale.push(symbol);
+            }
+        }
+        assert!(stale.contains(&"ETH/USD".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_chunked_subscription_reports_batch() {
+        use crate::scenario::{fixtures, Scenario};
+
+        let rejection = r#"{"method":"subscribe","req_id":2,"symbol":"C/USD","error":"Currency pair not supported","success":false,"time_in":"2025-12-21T12:28:24.000000Z","time_out":"2025-12-21T12:28:24.001000Z"}"#;
+        let config = ConnectionConfig::new()
+            .without_reconnect()
+            .with_max_symbols_per_request(2)
+            .with_transport_factory(move |url| {
+                Box::new(
+                    Scenario::new()
+                        .send_status()
+                        .send_raw(fixtures::subscribe_ack("ticker", "A/USD", 1))
+                        .send_raw(fixtures::subscribe_ack("ticker", "B/USD", 1))
+                        .send_raw(rejection)
+                        .close()
+                        .into_transport(url),
+                )
+            });
+        let conn = KrakenConnection::new(config);
+        conn.subscribe_ticker(["A/USD", "B/USD", "C/USD"]);
+        let mut events = conn.take_event_receiver().unwrap();
+        let _ = conn.connect_and_run().await;
+
+        let mut resolved = None;
+        let mut restoration = Vec::new();
+        while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+            match event {
+                Event::Subscription(SubscriptionEvent::BatchResolved { channel, live, rejected }) => {
+                    resolved = Some((channel, live, rejected));
+                }
+                Event::Subscription(SubscriptionEvent::Restoring { symbols, .. }) => {
+                    restoration.push(format!("restoring {}", symbols.len()));
+                }
+                Event::Subscription(SubscriptionEvent::Restored { symbols, .. }) => {
+                    restoration.push(format!("restored {}", symbols.len()));
+                }
+                Event::Subscription(SubscriptionEvent::RestoreFailed { symbols, .. }) => {
+                    restoration.push(format!("failed {}", symbols.len()));
+                }
+                Event::Connection(ConnectionEvent::RestorationComplete { restored, failed, .. }) => {
+                    restoration.push(format!("complete {}/{}", restored, failed));
+                }
+                _ => {}
+            }
+        }
+        let (channel, live, rejected) = resolved.expect("batch resolved");
+        assert_eq!(channel, "ticker");
+        assert_eq!(live, vec!["A/USD".to_string(), "B/USD".to_string()]);
+        assert_eq!(rejected, vec![("C/USD".to_string(), "Currency pair not supported".to_string())]);
+        assert_eq!(restoration, ["restoring 3", "restored 2", "failed 1", "complete 0/1"]);
+        assert!(!conn.is_restoring());
+    }
+
+    #[tokio::test]
+    async fn test_memory_limits_shrink_book_depth() {
+        use kraken_book::memory::APPROX_LEVEL_BYTES;
+        use kraken_types::{Decimal, Level};
+
+        let deep_book = |symbol: &str, levels: i64| {
+            let mut book = Orderbook::with_depth(symbol, 1000);
+            book.restore_snapshot(&OrderbookSnapshot {
+                bids: (0..levels).map(|i| Level::new(Decimal::from(10_000 - i), Decimal::ONE)).collect(),
+                asks: (0..levels).map(|i| Level::new(Decimal::from(10_001 + i), Decimal::ONE)).collect(),
+                ..Default::default()
+            });
+            book
+        };
+        let limits = MemoryLimits::new()
+            .with_per_book_bytes(400 * APPROX_LEVEL_BYTES)
+            .with_total_bytes(150 * APPROX_LEVEL_BYTES);
+        let conn = KrakenConnection::new(
+            ConnectionConfig::new()
+                .with_depth(Depth::D1000)
+                .with_memory_limits(limits),
+        );
+        let mut events = conn.take_event_receiver().unwrap();
+        conn.subscribe_orderbook(["A/USD", "B/USD"]);
+
+        let mut book = deep_book("A/USD", 500);
+        conn.enforce_book_memory(&mut book);
+        // Levels stay until the snapshot at the new depth arrives
+        assert_eq!(book.bid_count(), 500);
+        assert_eq!(conn.book_depth("A/USD"), Depth::D100);
+        assert_eq!(conn.book_depth("B/USD"), Depth::D1000);
+        conn.orderbooks.insert("A/USD".into(), book);
+        conn.orderbooks.insert("B/USD".into(), deep_book("B/USD", 20));
+
+        conn.enforce_total_memory();
+        assert_eq!(conn.book_depth("A/USD"), Depth::D25);
+        // Shallow enough already, whatever its subscription
+        assert_eq!(conn.book_depth("B/USD"), Depth::D1000);
+        assert_eq!(*conn.depth_queue.read(), vec![("A/USD".to_string(), Depth::D1000)]);
+        // Nothing new to report on the next pass
+        conn.enforce_total_memory();
+
+        let mut caps = Vec::new();
+        while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+            if let Event::Connection(ConnectionEvent::MemoryCapReached { symbol, .. }) = event {
+                caps.push(symbol);
+            }
+        }
+        assert_eq!(caps, vec![Some("A/USD".to_string()), None]);
+    }
+
+    #[tokio::test]
+    async fn test_book_over_budget_is_resubscribed_at_smaller_depth() {
+        use crate::scenario::Scenario;
+        use crate::tap::{Direction, MessageTap};
+        use kraken_book::memory::APPROX_LEVEL_BYTES;
+        use kraken_types::Decimal;
+
+        let bids: Vec<_> = (0..100).map(|i| (Decimal::from(10_000 - i), Decimal::ONE)).collect();
+        let asks: Vec<_> = (0..100).map(|i| (Decimal::from(10_001 + i), Decimal::ONE)).collect();
+        let (tap, mut frames) = MessageTap::channel();
+        let config = ConnectionConfig::new()
+            .without_reconnect()
+            .with_depth(Depth::D100)
+            .with_memory_limits(MemoryLimits::new().with_per_book_bytes(60 * APPROX_LEVEL_BYTES))
+            .with_message_tap(tap)
+            .with_transport_factory(move |url| {
+                Box::new(
+                    Scenario::new()
+                        .send_status()
+                        .send_snapshot("A/USD", &bids, &asks)
+                        .delay(Duration::from_millis(50))
+                        .close()
+                        .into_transport(url),
+                )
+            });
+        let conn = KrakenConnection::new(config);
+        conn.subscribe_orderbook(["A/USD"]);
+        let _ = conn.connect_and_run().await;
+
+        let mut outbound = Vec::new();
+        while let Ok(frame) = frames.try_recv() {
+            if frame.direction == Direction::Outbound && frame.text.contains("\"book\"") {
+                let json: serde_json::Value = serde_json::from_str(&frame.text).unwrap();
+                outbound.push((json["method"].as_str().unwrap().to_string(), json["params"]["depth"].as_u64()));
+            }
+        }
+        assert_eq!(
+            outbound,
+            vec![
+                ("subscribe".to_string(), Some(100)),
+                ("unsubscribe".to_string(), Some(100)),
+                ("subscribe".to_string(), Some(25)),
+            ]
+        );
+        assert_eq!(conn.book_depth("A/USD"), Depth::D25);
+    }
+
+    #[tokio::test]
+    async fn test_audit_reports_inconsistent_books() {
+        use kraken_types::{Decimal, Level};
+
+        let conn = KrakenConnection::new(ConnectionConfig::new().with_audit(Duration::from_secs(1)));
+        let mut events = conn.take_event_receiver().unwrap();
+
+        let mut crossed = Orderbook::new("A/USD");
+        crossed.restore_snapshot(&OrderbookSnapshot {
+            bids: vec![Level::new(Decimal::from(101), Decimal::ONE)],
+            asks: vec![Level::new(Decimal::from(100), Decimal::ONE)],
+            ..Default::default()
+        });
+        conn.orderbooks.insert("A/USD".into(), crossed);
+        conn.orderbooks.insert("B/USD".into(), Orderbook::new("B/USD"));
+
+        conn.audit_books();
+        let event = timeout(Duration::from_millis(10), events.recv()).await.unwrap().unwrap();
+        match event {
+            Event::Market(MarketEvent::BookAuditFailed { symbol, violations }) => {
+                assert_eq!(symbol, "A/USD");
+                assert_eq!(violations.len(), 1);
+                assert_eq!(violations[0].kind(), "crossed");
+            }
+            other => panic!("expected audit failure, got {:?}", other),
+        }
+        assert!(timeout(Duration::from_millis(10), events.recv()).await.is_err());
+        assert_eq!(conn.health().audit_violations, 1);
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_orderbook_confirmed_reports_rejection() {
+        use crate::scenario::{fixtures, Scenario};
+
+        let rejection = r#"{"method":"subscribe","req_id":1,"symbol":"C/USD","error":"EGeneral:Invalid arguments","success":false,"time_in":"2025-12-21T12:28:24.000000Z","time_out":"2025-12-21T12:28:24.001000Z"}"#;
+        let config = ConnectionConfig::new()
+            .without_reconnect()
+            .with_transport_factory(move |url| {
+                Box::new(
+                    Scenario::new()
+                        .send_status()
+                        .send_raw(fixtures::subscribe_ack("book", "A/USD", 1))
+                        .send_raw(rejection)
+                        .close()
+                        .into_transport(url),
+                )
+            });
+        let conn = KrakenConnection::new(config);
+        let confirmed = conn.subscribe_orderbook_confirmed(["A/USD", "C/USD"]);
+        let mut events = conn.take_event_receiver().unwrap();
+        let _ = conn.connect_and_run().await;
+
+        let resolution = confirmed.await.unwrap();
+        assert_eq!(resolution.live, vec!["A/USD".to_string()]);
+        assert_eq!(resolution.rejected[0].0, "C/USD");
+
+        let mut rejected = None;
+        while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+            if let Event::Subscription(SubscriptionEvent::Rejected { channel, symbols, error, .. }) = event {
+                rejected = Some((channel, symbols, error));
+            }
+        }
+        let (channel, symbols, error) = rejected.expect("rejection event");
+        assert_eq!(channel, "book");
+        assert_eq!(symbols, vec!["C/USD".to_string()]);
+        assert_eq!(error.category, kraken_types::ErrorCategory::General);
+    }
+
+    #[test]
+    fn test_interleaved_symbols_are_batched_per_symbol() {
+        use futures::FutureExt;
+
+        let trade = |symbol: &str, id: u64| {
+            format!(
+                r#"{{"symbol":"{symbol}","side":"buy","price":100.0,"qty":1.0,"ord_type":"market","trade_id":{id},"timestamp":"2024-01-01T00:00:00.000000Z"}}"#
+            )
+        };
+        let conn = KrakenConnection::new(
+            ConnectionConfig::new().with_channel_capacity(2, BackpressurePolicy::DropNewest),
+        );
+        let mut events = conn.take_event_receiver().unwrap();
+        let message = format!(
+            r#"{{"channel":"trade","type":"update","data":[{},{},{}]}}"#,
+            trade("ETH/USD", 1),
+            trade("BTC/USD", 1),
+            trade("ETH/USD", 2)
+        );
+        conn.handle_message(&message, ReceivedAt::now());
+
+        // Both ETH trades go out together; the BTC batch no longer fits
+        let mut received = Vec::new();
+        while let Some(Some(Event::Market(event))) = events.recv().now_or_never() {
+            received.push((event.symbol().unwrap().to_string(), event.seq().unwrap()));
+        }
+        assert_eq!(received, vec![("ETH/USD".to_string(), 1), ("ETH/USD".to_string(), 2)]);
+        assert_eq!(conn.dropped_event_count(), 1);
+    }
+
+    #[test]
+    fn test_per_symbol_seq_survives_backpressure() {
+        use futures::FutureExt;
+
+        let trade = |symbol: &str, id: u64| {
+            format!(
+                r#"{{"symbol":"{symbol}","side":"buy","price":100.0,"qty":1.0,"ord_type":"market","trade_id":{id},"timestamp":"2024-01-01T00:00:00.000000Z"}}"#
+            )
+        };
+        let message = |trades: Vec<String>| {
+            format!(r#"{{"channel":"trade","type":"update","data":[{}]}}"#, trades.join(","))
+        };
+        let conn = KrakenConnection::new(
+            ConnectionConfig::new().with_channel_capacity(3, BackpressurePolicy::DropNewest),
+        );
+        let mut events = conn.take_event_receiver().unwrap();
+        let mut received = Vec::new();
+        let drain = |events: &mut EventReceiver| {
+            let mut out = Vec::new();
+            while let Some(Some(event)) = events.recv().now_or_never() {
+                if let Event::Market(event) = event {
+                    out.push((event.symbol().unwrap().to_string(), event.seq().unwrap()));
+                }
+            }
+            out
+        };
+
+        // Two BTC trades fit; the ETH pair doesn't, so neither is delivered
+        conn.handle_message(&message(vec![trade("BTC/USD", 1), trade("BTC/USD", 2)]), ReceivedAt::now());
+        conn.handle_message(&message(vec![trade("ETH/USD", 1), trade("ETH/USD", 2)]), ReceivedAt::now());
+        assert_eq!(conn.dropped_event_count(), 2);
+        received.extend(drain(&mut events));
+
+        // One message with both symbols is split into a batch per symbol
+        conn.handle_message(
+            &message(vec![trade("ETH/USD", 3), trade("BTC/USD", 3), trade("BTC/USD", 4), trade("BTC/USD", 5)]),
+            ReceivedAt::now(),
+        );
+        received.extend(drain(&mut events));
+
+        let seqs = |symbol: &str| -> Vec<u64> {
+            received.iter().filter(|(s, _)| s == symbol).map(|(_, seq)| *seq).collect()
+        };
+        assert_eq!(seqs("BTC/USD"), vec![1, 2]);
+        // The gap covers exactly the dropped message
+        assert_eq!(seqs("ETH/USD"), vec![3]);
+        assert_eq!(conn.dropped_event_count(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_request_snapshot_resolves_on_fresh_snapshot() {
+        use crate::scenario::Scenario;
+        use crate::tap::{Direction, MessageTap};
+        use rust_decimal_macros::dec;
+
+        let (tap, mut frames) = MessageTap::channel();
+        let config = ConnectionConfig::new().without_reconnect().with_message_tap(tap).with_transport_factory(|url| {
+            Box::new(
+                Scenario::new()
+                    .send_status()
+                    .send_snapshot("BTC/USD", &[(dec!(100), dec!(1))], &[(dec!(101), dec!(2))])
+                    .delay(Duration::from_millis(100))
+                    .send_snapshot("BTC/USD", &[(dec!(99), dec!(1))], &[(dec!(101), dec!(2))])
+                    .delay(Duration::from_millis(100))
+                    .close()
+                    .into_transport(url),
+            )
+        });
+        let conn = Arc::new(KrakenConnection::new(config));
+        conn.subscribe_orderbook(["BTC/USD"]);
+        assert!(matches!(
+            conn.request_snapshot("ETH/USD").await,
+            Err(KrakenError::InvalidState { .. })
+        ));
+
+        let runner = Arc::clone(&conn);
+        let run = tokio::spawn(async move { runner.connect_and_run().await });
+        while conn.orderbook("BTC/USD").is_none() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+
+        let refreshed = timeout(Duration::from_secs(1), conn.request_snapshot("BTC/USD")).await;
+        assert!(matches!(refreshed, Ok(Ok(()))));
+        assert_eq!(conn.orderbook("BTC/USD").unwrap().best_bid().unwrap().price, dec!(99));
+
+        // The initial subscribe, then an unsubscribe and subscribe pair for the refresh
+        let mut book_frames = Vec::new();
+        while let Ok(frame) = frames.try_recv() {
+            if frame.direction == Direction::Outbound && frame.text.contains("\"book\"") {
+                let json: serde_json::Value = serde_json::from_str(&frame.text).unwrap();
+                assert_eq!(json["params"]["symbol"], serde_json::json!(["BTC/USD"]));
+                book_frames.push((json["method"].as_str().unwrap().to_string(), json["params"]["snapshot"].clone()));
+            }
+        }
+        let snapshot = serde_json::Value::Bool(true);
+        assert_eq!(
+            book_frames,
+            vec![
+                ("subscribe".to_string(), snapshot.clone()),
+                ("unsubscribe".to_string(), snapshot.clone()),
+                ("subscribe".to_string(), snapshot),
+            ]
+        );
+
+        // Requests still pending at shutdown fail instead of hanging
+        let pending = conn.request_snapshot("BTC/USD");
+        conn.shutdown();
+        assert!(matches!(pending.await, Err(KrakenError::ChannelClosed)));
+        let _ = run.await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_request_snapshot_fails_on_rejection_or_timeout() {
+        let conn = KrakenConnection::new(ConnectionConfig::new().with_snapshot_timeout(Duration::from_secs(5)));
+        conn.subscribe_orderbook(["BTC/USD"]);
+
+        let rejected = conn.request_snapshot("BTC/USD");
+        conn.snapshot_requests.write().insert(7, "BTC/USD".to_string());
+        conn.handle_message(
+            r#"{"method":"subscribe","req_id":7,"symbol":"BTC/USD","error":"EGeneral:Internal error","success":false,"time_in":"2025-12-21T12:28:24.000000Z","time_out":"2025-12-21T12:28:24.001000Z"}"#,
+            ReceivedAt::now(),
+        );
+        assert!(matches!(rejected.await, Err(KrakenError::SubscriptionRejected { reason, .. }) if reason == "EGeneral:Internal error"));
+
+        // Nothing ever answers this one
+        let unanswered = conn.request_snapshot("BTC/USD").await;
+        assert!(matches!(unanswered, Err(KrakenError::SubscriptionTimeout { timeout }) if timeout == Duration::from_secs(5)));
+    }
+
+    #[tokio::test]
+    async fn test_unread_books_are_pruned_and_resumed_on_access() {
+        use crate::scenario::Scenario;
+        use rust_decimal_macros::dec;
+
+        let config = ConnectionConfig::new()
+            .without_reconnect()
+            .with_pruning(PruningPolicy::new(Duration::from_millis(60)).with_resubscribe_on_access(true))
+            .with_transport_factory(|url| {
+                Box::new(
+                    Scenario::new()
+                        .send_status()
+                        .send_snapshot("BTC/USD", &[(dec!(100), dec!(1))], &[(dec!(101), dec!(2))])
+                        .send_snapshot("ETH/USD", &[(dec!(10), dec!(1))], &[(dec!(11), dec!(2))])
+                        .delay(Duration::from_millis(500))
+                        .close()
+                        .into_transport(url),
+                )
+            });
+        let conn = Arc::new(KrakenConnection::new(config));
+        conn.subscribe_orderbook(["BTC/USD", "ETH/USD"]);
+        conn.subscribe_ticker(["ETH/USD"]);
+        let mut events = conn.take_event_receiver().unwrap();
+        let runner = Arc::clone(&conn);
+        let run = tokio::spawn(async move { runner.connect_and_run().await });
+
+        // Keep reading BTC/USD only
+        for _ in 0..30 {
+            let _ = conn.orderbook_snapshot("BTC/USD");
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(conn.pruned_symbols(), vec!["ETH/USD".to_string()]);
+        let symbols_on = |channel: Channel| -> Vec<String> {
+            conn.subscriptions()
+                .into_iter()
+                .filter(|sub| sub.channel == channel)
+                .flat_map(|sub| sub.symbols)
+                .collect()
+        };
+        assert_eq!(symbols_on(Channel::Book), vec!["BTC/USD".to_string()]);
+        // Other channels for a pruned symbol stay subscribed
+        assert_eq!(symbols_on(Channel::Ticker), vec!["ETH/USD".to_string()]);
+
+        let mut pruned = Vec::new();
+        while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+            if let Event::Subscription(SubscriptionEvent::Pruned { symbol, idle_for, .. }) = event {
+                assert!(idle_for >= Duration::from_millis(60));
+                pruned.push(symbol);
+            }
+        }
+        assert_eq!(pruned, vec!["ETH/USD".to_string()]);
+
+        // Reading the pruned book brings its subscription back
+        assert!(conn.with_orderbook("ETH/USD", |book| book.best_bid().is_none()).unwrap_or(true));
+        assert!(conn.pruned_symbols().is_empty());
+        assert_eq!(conn.subscriptions().len(), 3);
+        assert!(!conn.resume_pruned("ETH/USD"));
+
+        conn.shutdown();
+        let _ = run.await;
+    }
+
+    #[tokio::test]
+    async fn test_standby_takes_over_without_backoff() {
+        use crate::scenario::Scenario;
+        use rust_decimal_macros::dec;
+        use std::sync::atomic::AtomicUsize;
+
+        let opened = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&opened);
+        let config = ConnectionConfig::new()
+            .without_reconnect()
+            .with_standby_endpoint(Endpoint::PublicBeta)
+            .with_transport_factory(move |url| {
+                let scenario = match counter.fetch_add(1, Ordering::Relaxed) {
+                    // Primary dies shortly after its snapshot
+                    0 => Scenario::new()
+                        .send_status()
+                        .send_snapshot("BTC/USD", &[(dec!(100), dec!(1))], &[(dec!(101), dec!(2))])
+                        .delay(Duration::from_millis(50))
+                        .close(),
+                    // Standby idles until it's promoted and resubscribed
+                    1 => Scenario::new()
+                        .send_status()
+                        .delay(Duration::from_millis(100))
+                        .send_snapshot("BTC/USD", &[(dec!(200), dec!(1))], &[(dec!(201), dec!(2))])
+                        .close(),
+                    // The replacement standby never becomes ready
+                    _ => Scenario::new().delay(Duration::from_secs(10)),
+                };
+                Box::new(scenario.into_transport(url))
+            });
+        let conn = KrakenConnection::new(config);
+        conn.subscribe_orderbook(["BTC/USD"]);
+        let mut events = conn.take_event_receiver().unwrap();
+
+        // Reconnection is disabled, so only the failover keeps the feed alive
+        assert!(conn.connect_and_run().await.is_err());
+        assert_eq!(conn.orderbook("BTC/USD").unwrap().best_bid().unwrap().price, dec!(200));
+        assert_eq!(opened.load(Ordering::Relaxed), 3);
+
+        let mut connected = 0;
+        let mut failover = None;
+        while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+            match event {
+                Event::Connection(ConnectionEvent::Connected { .. }) => connected += 1,
+                Event::Connection(ConnectionEvent::Failover { endpoint, .. }) => failover = Some(endpoint),
+                Event::Connection(ConnectionEvent::Reconnecting { .. }) => panic!("failover went through backoff"),
+                _ => {}
+            }
+        }
+        assert_eq!(connected, 2);
+        assert_eq!(failover.as_deref(), Some(Endpoint::PublicBeta.url()));
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_backfills_before_live_data() {
+        use crate::backfill::{BackfillData, BackfillRequest, Backfiller};
+        use crate::scenario::Scenario;
+        use rust_decimal_macros::dec;
+        use std::sync::atomic::AtomicUsize;
+
+        let trade = |id: u64| {
+            format!(
+                r#"{{"symbol":"BTC/USD","side":"buy","price":100.0,"qty":1.0,"ord_type":"market","trade_id":{id},"timestamp":"2024-01-01T00:00:0{id}.000000Z"}}"#
+            )
+        };
+        let candle_json = |minute: u32, close: u32| {
+            format!(
+                r#"{{"symbol":"BTC/USD","open":100.0,"high":100.0,"low":100.0,"close":{close}.0,"vwap":100.0,"volume":1.0,"trades":1,"interval_begin":"2024-01-01T00:{minute:02}:00.000000000Z","interval":1}}"#
+            )
+        };
+        let trades = move |ids: &[u64]| {
+            let data: Vec<String> = ids.iter().map(|id| trade(*id)).collect();
+            format!(r#"{{"channel":"trade","type":"update","data":[{}]}}"#, data.join(","))
+        };
+        let candles = move |bars: &[(u32, u32)]| {
+            let data: Vec<String> = bars.iter().map(|(m, c)| candle_json(*m, *c)).collect();
+            format!(r#"{{"channel":"ohlc","type":"update","data":[{}]}}"#, data.join(","))
+        };
+
+        // The mocked REST responses overlap both what was already delivered
+        // and what arrives live
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&requests);
+        let backfiller = Backfiller::new([Channel::Trade, Channel::Ohlc], move |request: BackfillRequest| {
+            let history = match &request {
+                BackfillRequest::Trades { .. } => {
+                    BackfillData::Trades((2..=5).map(|id| serde_json::from_str(&trade(id)).unwrap()).collect())
+                }
+                // The first connect gets older history, the reconnect the gap
+                BackfillRequest::Ohlc { since: None, .. } => {
+                    BackfillData::Ohlc(vec![serde_json::from_str(&candle_json(0, 100)).unwrap()])
+                }
+                BackfillRequest::Ohlc { .. } => BackfillData::Ohlc(
+                    [(1, 101), (2, 102), (3, 103)]
+                        .into_iter()
+                        .map(|(m, c)| serde_json::from_str(&candle_json(m, c)).unwrap())
+                        .collect(),
+                ),
+            };
+            seen.lock().push(request);
+            async move {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+                Ok(history)
+            }
+        });
+
+        let opened = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&opened);
+        let config = ConnectionConfig::new()
+            .without_circuit_breaker()
+            .with_reconnect(ReconnectConfig::new().with_initial_delay(Duration::from_millis(1)).with_max_attempts(2))
+            .with_backfill(backfiller)
+            .with_transport_factory(move |url| {
+                let scenario = match counter.fetch_add(1, Ordering::Relaxed) {
+                    0 => Scenario::new()
+                        .send_status()
+                        .send_raw(trades(&[1, 2]))
+                        .send_raw(candles(&[(1, 101)]))
+                        .delay(Duration::from_millis(50))
+                        .close(),
+                    // Live data arrives while the backfill is in flight
+                    1 => Scenario::new()
+                        .send_status()
+                        .send_raw(trades(&[5, 6]))
+                        .send_raw(candles(&[(3, 113)]))
+                        .delay(Duration::from_millis(100))
+                        .close(),
+                    _ => Scenario::new().close(),
+                };
+                Box::new(scenario.into_transport(url))
+            });
+        let conn = KrakenConnection::new(config);
+        conn.subscribe_trade(["BTC/USD"]);
+        conn.subscribe_ohlc(["BTC/USD"], OhlcInterval::M1);
+        let mut events = conn.take_event_receiver().unwrap();
+        let _ = conn.connect_and_run().await;
+
+        let mut trade_ids = Vec::new();
+        let mut closes = Vec::new();
+        let mut seqs = Vec::new();
+        while let Ok(Some(event)) = timeout(Duration::from_millis(10), events.recv()).await {
+            match event {
+                Event::Market(MarketEvent::Trade { trade, seq, .. }) => {
+                    trade_ids.push(trade.trade_id);
+                    seqs.push(seq);
+                }
+                Event::Market(MarketEvent::Ohlc { candle, seq, .. }) => {
+                    closes.push(candle.close);
+                    seqs.push(seq);
+                }
+                _ => {}
+            }
+        }
+
+        // Gapless, in order, each trade once
+        assert_eq!(trade_ids, vec![1, 2, 3, 4, 5, 6]);
+        // The last candle delivered is re-sent, and the live candle replaces
+        // the fetched one for the same interval
+        assert_eq!(closes, vec![dec!(100), dec!(101), dec!(101), dec!(102), dec!(113)]);
+        assert!(seqs.windows(2).all(|w| w[0] < w[1]));
+
+        let requests = requests.lock();
+        assert!(requests.contains(&BackfillRequest::Trades {
+            symbol: "BTC/USD".to_string(),
+            after: crate::backfill::TradeCursor {
+                trade_id: 2,
+                timestamp: "2024-01-01T00:00:02.000000Z".to_string(),
+            },
+        }));
+        assert!(requests.contains(&BackfillRequest::Ohlc {
+            symbol: "BTC/USD".to_string(),
+            interval: 1,
+            since: Some(1_704_067_260),
+        }));
+    }
 }
@@ -0,0 +1,529 @@
+//! permessage-deflate (RFC 7692) for inbound messages
+//!
+//! tungstenite 0.21 has no extension support and fails the connection on
+//! any frame with a reserved bit set. [`Inflater`] therefore sits between
+//! the socket and tungstenite, below the WebSocket layer:
+//!
+//! 1. The upgrade request offers `permessage-deflate` ([`OFFER`]).
+//! 2. The HTTP response passes through unchanged; the inflater notes
+//!    whether the server accepted the extension and with which parameters.
+//! 3. From then on every compressed server message (RSV1 set) is inflated
+//!    and handed on as one plain frame. Uncompressed messages and control
+//!    frames pass through byte for byte.
+//!
+//! The client never compresses what it sends, which the extension allows,
+//! so only the receive direction is rewritten. Without the server's
+//! acceptance the inflater is a plain pass-through.
+//!
+//! Enable it per connection with
+//! [`ConnectionConfig::with_compression`](crate::ConnectionConfig::with_compression);
+//! the savings and the CPU cost show up in
+//! [`TransportStats`](crate::TransportStats). See `benches/deflate.rs` for
+//! numbers on book traffic.
+
+use crate::transport::TransportStats;
+use flate2::{Decompress, FlushDecompress, Status};
+use std::io;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// `Sec-WebSocket-Extensions` value the client sends
+///
+/// `client_no_context_takeover` costs nothing since the client never
+/// compresses, and saves the server a compression context per connection.
+pub const OFFER: &str = "permessage-deflate; client_no_context_takeover";
+
+/// Largest HTTP response head accepted before giving up on negotiation
+const MAX_RESPONSE_HEAD: usize = 64 * 1024;
+
+/// Largest message after inflation (tungstenite's default message limit)
+const MAX_MESSAGE_SIZE: usize = 64 << 20;
+
+/// Trailer removed by the sender from every compressed message
+const DEFLATE_TRAILER: [u8; 4] = [0x00, 0x00, 0xff, 0xff];
+
+/// Decompression counters, shared with the transport that reports them
+#[derive(Debug, Default)]
+pub(crate) struct Counters {
+    negotiated: AtomicBool,
+    messages: AtomicU64,
+    compressed_bytes: AtomicU64,
+    decompressed_bytes: AtomicU64,
+    nanos: AtomicU64,
+}
+
+impl Counters {
+    /// Whether the server accepted the extension on the latest connection
+    pub(crate) fn negotiated(&self) -> bool {
+        self.negotiated.load(Ordering::Relaxed)
+    }
+
+    /// Fill in the compression fields of `stats`
+    pub(crate) fn add_to(&self, stats: &mut TransportStats) {
+        stats.compressed_messages_received += self.messages.load(Ordering::Relaxed);
+        stats.compressed_bytes_received += self.compressed_bytes.load(Ordering::Relaxed);
+        stats.decompressed_bytes_received += self.decompressed_bytes.load(Ordering::Relaxed);
+        stats.decompression_time += Duration::from_nanos(self.nanos.load(Ordering::Relaxed));
+    }
+}
+
+/// How far the inbound byte stream has got
+#[derive(Debug)]
+enum Phase {
+    /// Collecting the HTTP response head
+    Handshake(Vec<u8>),
+    /// Extension accepted: rewriting compressed messages
+    Frames,
+    /// Extension not accepted: bytes pass through untouched
+    Passthrough,
+}
+
+/// Compressed message still waiting for its final frame
+#[derive(Debug)]
+struct Compressed {
+    opcode: u8,
+    payload: Vec<u8>,
+}
+
+/// Frame header fields the inflater looks at
+#[derive(Debug, Clone, Copy)]
+struct Header {
+    fin: bool,
+    rsv1: bool,
+    opcode: u8,
+    masked: bool,
+    /// Header length in bytes, mask key included
+    len: usize,
+    payload_len: usize,
+}
+
+impl Header {
+    /// Parse the header at the start of `buf` (None until it is complete)
+    fn parse(buf: &[u8]) -> Option<Header> {
+        let (&first, rest) = buf.split_first()?;
+        let &second = rest.first()?;
+        let (payload_len, mut len) = match second & 0x7f {
+            126 => (u16::from_be_bytes(buf.get(2..4)?.try_into().ok()?) as usize, 4),
+            127 => (u64::from_be_bytes(buf.get(2..10)?.try_into().ok()?) as usize, 10),
+            short => (short as usize, 2),
+        };
+        let masked = second & 0x80 != 0;
+        if masked {
+            len += 4;
+        }
+        (buf.len() >= len).then_some(Header {
+            fin: first & 0x80 != 0,
+            rsv1: first & 0x40 != 0,
+            opcode: first & 0x0f,
+            masked,
+            len,
+            payload_len,
+        })
+    }
+
+    fn is_control(&self) -> bool {
+        self.opcode & 0x08 != 0
+    }
+}
+
+/// Rewrites a server's inbound byte stream, inflating compressed messages
+///
+/// Feed it the raw bytes read from the socket, starting with the HTTP
+/// upgrade response; it appends what tungstenite should see.
+pub struct Inflater {
+    phase: Phase,
+    /// Bytes of an incomplete frame
+    pending: Vec<u8>,
+    message: Option<Compressed>,
+    decompress: Decompress,
+    /// `server_no_context_takeover`: every message starts a fresh window
+    reset_each_message: bool,
+    counters: Arc<Counters>,
+}
+
+impl std::fmt::Debug for Inflater {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Inflater")
+            .field("phase", &self.phase)
+            .field("pending", &self.pending.len())
+            .field("reset_each_message", &self.reset_each_message)
+            .finish()
+    }
+}
+
+impl Default for Inflater {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Inflater {
+    /// Inflater waiting for the upgrade response
+    pub fn new() -> Self {
+        Self::with_counters(Arc::default())
+    }
+
+    pub(crate) fn with_counters(counters: Arc<Counters>) -> Self {
+        counters.negotiated.store(false, Ordering::Relaxed);
+        Self {
+            phase: Phase::Handshake(Vec::new()),
+            pending: Vec::new(),
+            message: None,
+            decompress: Decompress::new(false),
+            reset_each_message: false,
+            counters,
+        }
+    }
+
+    /// Whether the server accepted the extension
+    ///
+    /// False until the upgrade response has been fed.
+    pub fn is_negotiated(&self) -> bool {
+        self.counters.negotiated()
+    }
+
+    /// Compression counters so far (only the compression fields are set)
+    pub fn stats(&self) -> TransportStats {
+        let mut stats = TransportStats::default();
+        self.counters.add_to(&mut stats);
+        stats
+    }
+
+    /// Process `input`, appending the rewritten stream to `out`
+    ///
+    /// Fails on a compressed message that doesn't inflate, or that inflates
+    /// past 64 MiB.
+    pub fn feed(&mut self, input: &[u8], out: &mut Vec<u8>) -> io::Result<()> {
+        match &mut self.phase {
+            Phase::Passthrough => {
+                out.extend_from_slice(input);
+                Ok(())
+            }
+            Phase::Frames => {
+                self.pending.extend_from_slice(input);
+                self.drain_frames(out)
+            }
+            Phase::Handshake(head) => {
+                head.extend_from_slice(input);
+                let Some(end) = head.windows(4).position(|w| w == b"\r\n\r\n").map(|at| at + 4)
+                else {
+                    if head.len() > MAX_RESPONSE_HEAD {
+                        out.append(head);
+                        self.phase = Phase::Passthrough;
+                    }
+                    return Ok(());
+                };
+                let head = std::mem::take(head);
+                out.extend_from_slice(&head[..end]);
+                match accepted_params(&head[..end]) {
+                    Some(params) => {
+                        self.reset_each_message = params.contains("server_no_context_takeover");
+                        self.counters.negotiated.store(true, Ordering::Relaxed);
+                        self.phase = Phase::Frames;
+                    }
+                    None => self.phase = Phase::Passthrough,
+                }
+                self.feed(&head[end..], out)
+            }
+        }
+    }
+
+    /// Rewrite every complete frame in `pending`
+    fn drain_frames(&mut self, out: &mut Vec<u8>) -> io::Result<()> {
+        let pending = std::mem::take(&mut self.pending);
+        let mut start = 0;
+        while let Some(header) = Header::parse(&pending[start..]) {
+            let frame_len = header.len + header.payload_len;
+            if pending.len() - start < frame_len {
+                break;
+            }
+            let frame = &pending[start..start + frame_len];
+            self.apply(header, frame, out)?;
+            start += frame_len;
+        }
+        self.pending = pending;
+        self.pending.drain(..start);
+        Ok(())
+    }
+
+    fn apply(&mut self, header: Header, frame: &[u8], out: &mut Vec<u8>) -> io::Result<()> {
+        // Server frames are never masked; tungstenite rejects masked ones itself
+        if header.is_control() || header.masked {
+            out.extend_from_slice(frame);
+            return Ok(());
+        }
+        let payload = &frame[header.len..];
+        match (header.opcode, self.message.as_mut()) {
+            (0, Some(message)) => message.payload.extend_from_slice(payload),
+            (opcode, _) if opcode != 0 && header.rsv1 => {
+                self.message = Some(Compressed { opcode, payload: payload.to_vec() });
+            }
+            _ => out.extend_from_slice(frame),
+        }
+        if header.fin {
+            if let Some(message) = self.message.take() {
+                self.inflate(message, out)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Inflate a whole message and append it as one final frame
+    fn inflate(&mut self, message: Compressed, out: &mut Vec<u8>) -> io::Result<()> {
+        let started = Instant::now();
+        let compressed_len = message.payload.len();
+        let mut input = message.payload;
+        input.extend_from_slice(&DEFLATE_TRAILER);
+
+        let mut data = Vec::with_capacity(input.len() * 4);
+        let mut offset = 0;
+        loop {
+            if data.len() == data.capacity() {
+                data.reserve(data.capacity());
+            }
+            let (in_before, out_before) = (self.decompress.total_in(), self.decompress.total_out());
+            let status = self
+                .decompress
+                .decompress_vec(&input[offset..], &mut data, FlushDecompress::Sync)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            offset += (self.decompress.total_in() - in_before) as usize;
+            if data.len() > MAX_MESSAGE_SIZE {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "inflated message too large",
+                ));
+            }
+            let progressed =
+                self.decompress.total_in() > in_before || self.decompress.total_out() > out_before;
+            let drained = offset == input.len() && data.len() < data.capacity();
+            if drained || status == Status::StreamEnd || !progressed {
+                break;
+            }
+        }
+        if self.reset_each_message {
+            self.decompress.reset(false);
+        }
+
+        write_header(out, message.opcode, data.len());
+        out.extend_from_slice(&data);
+
+        self.counters.messages.fetch_add(1, Ordering::Relaxed);
+        self.counters.compressed_bytes.fetch_add(compressed_len as u64, Ordering::Relaxed);
+        self.counters.decompressed_bytes.fetch_add(data.len() as u64, Ordering::Relaxed);
+        self.counters.nanos.fetch_add(started.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// Parameters of an accepted `permessage-deflate`, if the response has one
+fn accepted_params(head: &[u8]) -> Option<String> {
+    let head = std::str::from_utf8(head).ok()?;
+    head.split("\r\n")
+        .filter_map(|line| line.split_once(':'))
+        .filter(|(name, _)| name.trim().eq_ignore_ascii_case("sec-websocket-extensions"))
+        .flat_map(|(_, value)| value.split(','))
+        .find_map(|extension| {
+            let (name, params) = extension.split_once(';').unwrap_or((extension, ""));
+            (name.trim() == "permessage-deflate").then(|| params.to_ascii_lowercase())
+        })
+}
+
+/// Append an unmasked, final frame header
+fn write_header(out: &mut Vec<u8>, opcode: u8, len: usize) {
+    out.push(0x80 | opcode);
+    if len < 126 {
+        out.push(len as u8);
+    } else if let Ok(len) = u16::try_from(len) {
+        out.push(126);
+        out.extend_from_slice(&len.to_be_bytes());
+    } else {
+        out.push(127);
+        out.extend_from_slice(&(len as u64).to_be_bytes());
+    }
+}
+
+/// Socket wrapper that runs everything read through an [`Inflater`]
+pub(crate) struct InflateIo<S> {
+    inner: S,
+    inflater: Inflater,
+    /// Rewritten bytes not handed to the reader yet
+    ready: Vec<u8>,
+    ready_from: usize,
+}
+
+impl<S> InflateIo<S> {
+    pub(crate) fn new(inner: S, counters: Arc<Counters>) -> Self {
+        Self {
+            inner,
+            inflater: Inflater::with_counters(counters),
+            ready: Vec::new(),
+            ready_from: 0,
+        }
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for InflateIo<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        loop {
+            if this.ready_from < this.ready.len() {
+                let n = buf.remaining().min(this.ready.len() - this.ready_from);
+                buf.put_slice(&this.ready[this.ready_from..this.ready_from + n]);
+                this.ready_from += n;
+                if this.ready_from == this.ready.len() {
+                    this.ready.clear();
+                    this.ready_from = 0;
+                }
+                return Poll::Ready(Ok(()));
+            }
+            let mut scratch = [0u8; 16 * 1024];
+            let mut raw = ReadBuf::new(&mut scratch);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut raw))?;
+            if raw.filled().is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+            this.inflater.feed(raw.filled(), &mut this.ready)?;
+        }
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for InflateIo<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::{Compress, Compression, FlushCompress};
+
+    const ACCEPTED: &[u8] = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\
+        Sec-WebSocket-Extensions: permessage-deflate; server_no_context_takeover\r\n\r\n";
+
+    /// Compress `text` the way a server does, trailer stripped
+    fn deflate(compress: &mut Compress, text: &str) -> Vec<u8> {
+        let mut out = Vec::with_capacity(text.len() + 64);
+        compress.compress_vec(text.as_bytes(), &mut out, FlushCompress::Sync).unwrap();
+        assert!(out.ends_with(&DEFLATE_TRAILER));
+        out.truncate(out.len() - DEFLATE_TRAILER.len());
+        out
+    }
+
+    fn frame(first: u8, payload: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_header(&mut out, 0, payload.len());
+        out[0] = first;
+        out.extend_from_slice(payload);
+        out
+    }
+
+    fn text(payload: &str) -> Vec<u8> {
+        frame(0x81, payload.as_bytes())
+    }
+
+    #[test]
+    fn test_compressed_messages_inflate_to_plain_frames() {
+        let book = format!(r#"{{"channel":"book","data":[{}]}}"#, "[1.5,2.25],".repeat(200));
+        let mut compress = Compress::new(Compression::default(), false);
+        let whole = deflate(&mut compress, &book);
+        compress.reset();
+        let split = deflate(&mut compress, r#"{"channel":"heartbeat"}"#);
+
+        let mut wire = ACCEPTED.to_vec();
+        wire.extend(frame(0xc1, &whole));
+        // Fragmented, with a ping between the fragments
+        wire.extend(frame(0x41, &split[..3]));
+        wire.extend(frame(0x89, b"hi"));
+        wire.extend(frame(0x80, &split[3..]));
+        wire.extend(text("plain"));
+
+        // Fed one byte at a time, so every header and frame arrives split
+        let mut inflater = Inflater::new();
+        let mut out = Vec::new();
+        for byte in &wire {
+            inflater.feed(std::slice::from_ref(byte), &mut out).unwrap();
+        }
+
+        let mut expected = ACCEPTED.to_vec();
+        expected.extend(text(&book));
+        expected.extend(frame(0x89, b"hi"));
+        expected.extend(text(r#"{"channel":"heartbeat"}"#));
+        expected.extend(text("plain"));
+        assert!(inflater.is_negotiated());
+        assert!(out == expected);
+
+        let stats = inflater.stats();
+        assert_eq!(stats.compressed_messages_received, 2);
+        assert_eq!(stats.compressed_bytes_received, (whole.len() + split.len()) as u64);
+        assert_eq!(stats.decompressed_bytes_received, (book.len() + 23) as u64);
+        assert!(stats.compression_ratio().unwrap() > 10.0);
+    }
+
+    #[test]
+    fn test_shared_window_across_messages() {
+        // Without server_no_context_takeover later messages refer back to earlier ones
+        let accepted = b"HTTP/1.1 101 Switching Protocols\r\n\
+            Sec-WebSocket-Extensions: permessage-deflate\r\n\r\n";
+        let update = r#"{"channel":"book","type":"update","data":[{"symbol":"BTC/USD"}]}"#;
+        let mut compress = Compress::new(Compression::default(), false);
+        let mut wire = accepted.to_vec();
+        let first = deflate(&mut compress, update);
+        let second = deflate(&mut compress, update);
+        assert!(second.len() < first.len());
+        wire.extend(frame(0xc1, &first));
+        wire.extend(frame(0xc1, &second));
+
+        let mut inflater = Inflater::new();
+        let mut out = Vec::new();
+        inflater.feed(&wire, &mut out).unwrap();
+        let mut expected = accepted.to_vec();
+        expected.extend(text(update));
+        expected.extend(text(update));
+        assert!(out == expected);
+    }
+
+    #[test]
+    fn test_passthrough_without_acceptance() {
+        let response = b"HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\n\r\n";
+        let mut wire = response.to_vec();
+        wire.extend(text("hello"));
+        wire.extend(frame(0xc1, b"\x01\x02")); // left for tungstenite to reject
+
+        let mut inflater = Inflater::new();
+        let mut out = Vec::new();
+        inflater.feed(&wire[..10], &mut out).unwrap();
+        assert!(out.is_empty());
+        inflater.feed(&wire[10..], &mut out).unwrap();
+        assert_eq!(out, wire);
+        assert!(!inflater.is_negotiated());
+    }
+
+    #[test]
+    fn test_corrupt_message_is_an_error() {
+        let mut wire = ACCEPTED.to_vec();
+        wire.extend(frame(0xc1, &[0xff; 8]));
+        let err = Inflater::new().feed(&wire, &mut Vec::new()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
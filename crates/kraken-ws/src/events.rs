@@ -2,13 +2,57 @@
 //!
 //! This module provides event types for both public market data and
 //! private account data (executions, balances).
+//!
+//! # Ordering
+//!
+//! Events are delivered in the order the connection produced them, on a
+//! single channel. Across symbols and channels there is no ordering beyond
+//! that: a BTC/USD trade may arrive before an ETH/USD book update that the
+//! exchange stamped earlier.
+//!
+//! Per symbol, the data events ([`MarketEvent::OrderbookSnapshot`],
+//! [`MarketEvent::OrderbookUpdate`], [`MarketEvent::Ticker`],
+//! [`MarketEvent::Trade`] and [`MarketEvent::Ohlc`]) carry a `seq` that starts at 1 and increases by
+//! one per event across all of the symbol's channels, for the lifetime of
+//! the connection. Events always arrive in `seq` order.
+//!
+//! With a bounded channel and `BackpressurePolicy::DropNewest`, the events
+//! produced by one inbound message for one symbol (a book update and its
+//! diagnostics, or a batch of trades) are delivered together or dropped
+//! together. A gap in `seq` therefore means whole messages were dropped,
+//! never part of one.
+//!
+//! # Event ids
+//!
+//! Every event also gets a connection-wide `id`, starting at 1 and
+//! increasing by one per emitted event, including events later dropped by
+//! backpressure. Read it with `EventReceiver::recv_sequenced`. Ids can be
+//! carried across restarts: the connection resumes numbering after a given
+//! id, so a downstream pipeline can use [`EventIdCursor`] to skip redelivered
+//! events and notice lost ones.
 
-use kraken_book::OrderbookSnapshot;
-use kraken_types::{BalanceData, Decimal, ExecutionData, L3Data, L3Order, Side};
+use crate::latency::ReceivedAt;
+use crate::sampler::BookSample;
+use kraken_book::{AuditViolation, OrderbookSnapshot};
+use kraken_types::{
+    BalanceData, Channel, Decimal, ExecutionData, KrakenApiError, KrakenErrorCode, L3Data, L3Order, OhlcData, PairStatus, Side, SystemStatus, TickerData, TradeData,
+};
 use std::collections::HashMap;
 use std::time::Duration;
 
 /// Reason for disconnection
+///
+/// Closes are classified from the close frame first, then from an API
+/// error the server sent just before and the last system status. Close
+/// reasons are matched on whole words, so "migrate" or "author" don't count:
+///
+/// | Reason | Close frame | API error / status |
+/// |--------|-------------|--------------------|
+/// | `RateLimited` | 1008, or a reason with "rate limit" or "too many" | `EAPI:Rate limit exceeded`, `EGeneral:Too many requests`, ... |
+/// | `AuthExpired` | a reason with "token", "session", "auth" or "unauthorized" | `EAPI:Invalid session`, `EAPI:Invalid key`, ... |
+/// | `MaintenanceMode` | 1013, or a reason with "maintenance" | `EService:Unavailable`, status `maintenance` |
+/// | `ProtocolError` | any other code except 1000, 1001 and 1005 | |
+/// | `ServerClosed` | 1000, 1001, 1005 or no frame | |
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DisconnectReason {
     /// Server closed the connection
@@ -23,6 +67,61 @@ pub enum DisconnectReason {
     AuthFailed,
     /// No heartbeat/message received within timeout period
     HeartbeatTimeout,
+    /// Disconnected for exceeding rate limits
+    RateLimited,
+    /// Session token expired or was revoked
+    AuthExpired,
+    /// Kraken is under maintenance
+    MaintenanceMode,
+    /// Closed with an unexpected WebSocket close code
+    ProtocolError {
+        /// Close code sent by the server
+        code: u16,
+    },
+}
+
+impl DisconnectReason {
+    /// Classify a WebSocket close frame
+    pub fn from_close_frame(code: u16, reason: &str) -> Self {
+        let reason = reason.to_ascii_lowercase();
+        let words: Vec<&str> = reason
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .collect();
+        let has = |options: &[&str]| words.iter().any(|word| options.contains(word));
+        let follows = |first: &str, second: &[&str]| {
+            words.windows(2).any(|pair| pair[0] == first && second.contains(&pair[1]))
+        };
+
+        if follows("rate", &["limit", "limits", "limited"]) || has(&["ratelimit", "ratelimited"]) || follows("too", &["many"]) {
+            Self::RateLimited
+        } else if has(&["token", "session", "auth", "authentication", "unauthorized", "unauthorised"]) {
+            Self::AuthExpired
+        } else if has(&["maintenance"]) {
+            Self::MaintenanceMode
+        } else {
+            match code {
+                1000 | 1001 | 1005 => Self::ServerClosed,
+                1008 => Self::RateLimited,
+                1013 => Self::MaintenanceMode,
+                code => Self::ProtocolError { code },
+            }
+        }
+    }
+
+    /// Reason implied by an API error, if it explains a disconnect
+    pub fn from_api_error(error: &KrakenApiError) -> Option<Self> {
+        let code = error.code?;
+        if code.is_rate_limit() {
+            Some(Self::RateLimited)
+        } else if code.is_auth_error() {
+            Some(Self::AuthExpired)
+        } else if code == KrakenErrorCode::ServiceUnavailable {
+            Some(Self::MaintenanceMode)
+        } else {
+            None
+        }
+    }
 }
 
 /// Connection lifecycle events
@@ -47,6 +146,13 @@ pub enum ConnectionEvent {
         /// Delay before this attempt
         delay: Duration,
     },
+    /// Downtime budget exceeded; followed by `ReconnectFailed`
+    DowntimeExceeded {
+        /// Time since the first failure after the last successful connection
+        downtime: Duration,
+        /// Reconnect attempts made
+        attempts: u32,
+    },
     /// Reconnection attempts exhausted
     ReconnectFailed {
         /// Final error
@@ -57,11 +163,66 @@ pub enum ConnectionEvent {
         /// Number of subscriptions restored
         count: usize,
     },
+    /// Every subscription resent on connect has been restored or has failed
+    ///
+    /// Books count as restored once their snapshot has been applied, so
+    /// this is the point to resume trading after a reconnect.
+    RestorationComplete {
+        /// Subscriptions restored in full
+        restored: usize,
+        /// Subscriptions with at least one rejected symbol
+        failed: usize,
+        /// Time since the subscriptions were resent
+        elapsed: Duration,
+    },
+    /// The primary connection failed and the warm standby took over
+    Failover {
+        /// URL of the promoted standby
+        endpoint: String,
+        /// Why the primary connection failed
+        reason: String,
+    },
     /// Circuit breaker is open (blocking reconnection attempts)
     CircuitBreakerOpen {
         /// Number of times circuit has been tripped
         trips: u64,
     },
+    /// Exchange system status changed (e.g. online to maintenance)
+    SystemStatusChanged {
+        /// Previous status (None for the first status received)
+        previous: Option<SystemStatus>,
+        /// New status
+        current: SystemStatus,
+    },
+    /// A subscribed pair's trading status changed (e.g. online to post_only)
+    ///
+    /// Not emitted for the first status seen for a pair.
+    PairStatusChanged {
+        /// Trading pair symbol
+        symbol: String,
+        /// Previous status
+        previous: Option<PairStatus>,
+        /// New status
+        current: PairStatus,
+    },
+    /// Orderbooks exceeded a memory budget and were resubscribed at a smaller depth
+    MemoryCapReached {
+        /// Book that was resubscribed (None when the total budget was exceeded)
+        symbol: Option<String>,
+        /// Depth the books were resubscribed at, in levels per side
+        level_cap: usize,
+        /// Approximate bytes before the cap
+        bytes_before: usize,
+        /// Configured budget
+        limit_bytes: usize,
+    },
+    /// Local clock offset from the exchange exceeded the configured threshold
+    ClockSkew {
+        /// Estimated local minus exchange time, in microseconds
+        offset_us: i64,
+        /// Configured threshold, in microseconds
+        threshold_us: i64,
+    },
 }
 
 /// Subscription-specific events
@@ -76,10 +237,14 @@ pub enum SubscriptionEvent {
     },
     /// Subscription rejected
     Rejected {
-        /// Channel name
+        /// Channel name ("unknown" if the request ID was not recognized)
         channel: String,
+        /// Symbols the rejection applies to
+        symbols: Vec<String>,
         /// Rejection reason
         reason: String,
+        /// Parsed error code and category
+        error: KrakenApiError,
     },
     /// Unsubscribed from channel
     Unsubscribed {
@@ -88,6 +253,56 @@ pub enum SubscriptionEvent {
         /// Symbol(s)
         symbols: Vec<String>,
     },
+    /// A subscribed feed has received no messages for longer than the threshold
+    Stale {
+        /// Channel name
+        channel: String,
+        /// Trading pair symbol
+        symbol: String,
+        /// Time since the feed's last message
+        silent_for: Duration,
+    },
+    /// Every symbol of a (possibly chunked) subscription has been answered
+    BatchResolved {
+        /// Channel name
+        channel: String,
+        /// Symbols that are live
+        live: Vec<String>,
+        /// Symbols that were rejected, with the reason
+        rejected: Vec<(String, String)>,
+    },
+    /// A subscription is being resent after a connect
+    Restoring {
+        /// Channel name
+        channel: String,
+        /// Symbol(s)
+        symbols: Vec<String>,
+    },
+    /// A resent subscription is live again, with books re-synced
+    Restored {
+        /// Channel name
+        channel: String,
+        /// Symbols that are live
+        symbols: Vec<String>,
+    },
+    /// The server rejected symbols of a resent subscription
+    RestoreFailed {
+        /// Channel name
+        channel: String,
+        /// Symbols rejected for this reason (empty for symbol-less channels)
+        symbols: Vec<String>,
+        /// Rejection reason
+        error: String,
+    },
+    /// A book was unsubscribed because the application stopped reading it
+    Pruned {
+        /// Channel name
+        channel: String,
+        /// Trading pair symbol
+        symbol: String,
+        /// Time since the application last read the book
+        idle_for: Duration,
+    },
 }
 
 /// Market data events
@@ -97,15 +312,27 @@ pub enum MarketEvent {
     OrderbookSnapshot {
         /// Trading pair symbol
         symbol: String,
+        /// Per-symbol sequence number (see the module docs)
+        seq: u64,
         /// Full orderbook state
         snapshot: OrderbookSnapshot,
+        /// Local receive time
+        received_at: ReceivedAt,
+        /// Exchange timestamp (Unix microseconds), when the message carries one
+        exchange_ts_us: Option<i64>,
     },
     /// Orderbook updated
     OrderbookUpdate {
         /// Trading pair symbol
         symbol: String,
+        /// Per-symbol sequence number (see the module docs)
+        seq: u64,
         /// Updated orderbook state
         snapshot: OrderbookSnapshot,
+        /// Local receive time
+        received_at: ReceivedAt,
+        /// Exchange timestamp (Unix microseconds), when the message carries one
+        exchange_ts_us: Option<i64>,
     },
     /// Checksum validation failed
     ChecksumMismatch {
@@ -116,6 +343,103 @@ pub enum MarketEvent {
         /// Computed checksum
         computed: u32,
     },
+    /// Book update timestamped before the last applied one (book needs a snapshot)
+    OutOfOrderUpdate {
+        /// Trading pair symbol
+        symbol: String,
+        /// Timestamp of the last applied message
+        last: String,
+        /// Timestamp of the dropped update
+        received: String,
+    },
+    /// Book update received before a snapshot (dropped)
+    UpdateBeforeSnapshot {
+        /// Trading pair symbol
+        symbol: String,
+    },
+    /// Best bid at or above best ask after an applied update
+    CrossedBook {
+        /// Trading pair symbol
+        symbol: String,
+        /// Best bid price
+        best_bid: Decimal,
+        /// Best ask price
+        best_ask: Decimal,
+    },
+    /// Snapshot deeper than the local book (truncated)
+    DepthOverflow {
+        /// Trading pair symbol
+        symbol: String,
+        /// Levels on the deeper side of the snapshot
+        levels: usize,
+        /// Local book depth
+        depth: u32,
+    },
+    /// Server acknowledged a book subscription at a depth other than requested
+    ///
+    /// The local book keeps the configured depth: deeper data is truncated,
+    /// shallower data leaves the book with fewer levels.
+    DepthMismatch {
+        /// Trading pair symbol
+        symbol: String,
+        /// Depth requested for the symbol
+        configured: u32,
+        /// Depth in the server's acknowledgement
+        server: u32,
+    },
+    /// Periodic consistency audit found a book breaking its invariants
+    ///
+    /// See [`ConnectionConfig::with_audit`](crate::ConnectionConfig::with_audit).
+    BookAuditFailed {
+        /// Trading pair symbol
+        symbol: String,
+        /// Everything the audit found
+        violations: Vec<AuditViolation>,
+    },
+    /// Periodic book summary (see [`BookSampler`](crate::BookSampler))
+    BookSample {
+        /// Trading pair symbol
+        symbol: String,
+        /// Sampled metrics
+        sample: BookSample,
+    },
+    /// Ticker update received
+    Ticker {
+        /// Trading pair symbol
+        symbol: String,
+        /// Per-symbol sequence number (see the module docs)
+        seq: u64,
+        /// Ticker fields
+        ticker: TickerData,
+        /// Local receive time
+        received_at: ReceivedAt,
+        /// Exchange timestamp (Unix microseconds), when the message carries one
+        exchange_ts_us: Option<i64>,
+    },
+    /// Public trade received (one event per trade)
+    Trade {
+        /// Trading pair symbol
+        symbol: String,
+        /// Per-symbol sequence number (see the module docs)
+        seq: u64,
+        /// Trade details
+        trade: TradeData,
+        /// Local receive time
+        received_at: ReceivedAt,
+        /// Exchange timestamp (Unix microseconds), when the message carries one
+        exchange_ts_us: Option<i64>,
+    },
+    /// OHLC candle opened or updated (one event per candle)
+    Ohlc {
+        /// Trading pair symbol
+        symbol: String,
+        /// Per-symbol sequence number (see the module docs)
+        seq: u64,
+        /// Candle, keyed by `interval` and `interval_begin`
+        candle: OhlcData,
+        /// Local receive time
+        received_at: ReceivedAt,
+    },
     /// Status message from server
     Status {
         /// System status (online, maintenance, etc.)
@@ -127,6 +451,89 @@ pub enum MarketEvent {
     Heartbeat,
 }
 
+impl MarketEvent {
+    /// Trading pair symbol, for events about one pair
+    pub fn symbol(&self) -> Option<&str> {
+        match self {
+            Self::OrderbookSnapshot { symbol, .. }
+            | Self::OrderbookUpdate { symbol, .. }
+            | Self::ChecksumMismatch { symbol, .. }
+            | Self::OutOfOrderUpdate { symbol, .. }
+            | Self::UpdateBeforeSnapshot { symbol }
+            | Self::CrossedBook { symbol, .. }
+            | Self::DepthOverflow { symbol, .. }
+            | Self::DepthMismatch { symbol, .. }
+            | Self::BookAuditFailed { symbol, .. }
+            | Self::BookSample { symbol, .. }
+            | Self::Ticker { symbol, .. }
+            | Self::Trade { symbol, .. }
+            | Self::Ohlc { symbol, .. } => Some(symbol),
+            Self::Status { .. } | Self::Heartbeat => None,
+        }
+    }
+
+    /// Channel the event came from (None for heartbeats)
+    pub fn channel(&self) -> Option<Channel> {
+        match self {
+            Self::OrderbookSnapshot { .. }
+            | Self::OrderbookUpdate { .. }
+            | Self::ChecksumMismatch { .. }
+            | Self::OutOfOrderUpdate { .. }
+            | Self::UpdateBeforeSnapshot { .. }
+            | Self::CrossedBook { .. }
+            | Self::DepthOverflow { .. }
+            | Self::DepthMismatch { .. }
+            | Self::BookAuditFailed { .. }
+            | Self::BookSample { .. } => Some(Channel::Book),
+            Self::Ticker { .. } => Some(Channel::Ticker),
+            Self::Trade { .. } => Some(Channel::Trade),
+            Self::Ohlc { .. } => Some(Channel::Ohlc),
+            Self::Status { .. } => Some(Channel::Status),
+            Self::Heartbeat => None,
+        }
+    }
+
+    /// Per-symbol sequence number, for data events
+    pub fn seq(&self) -> Option<u64> {
+        match self {
+            Self::OrderbookSnapshot { seq, .. }
+            | Self::OrderbookUpdate { seq, .. }
+            | Self::Ticker { seq, .. }
+            | Self::Trade { seq, .. }
+            | Self::Ohlc { seq, .. } => Some(*seq),
+            _ => None,
+        }
+    }
+
+    /// Local receive time, for events stamped on arrival
+    pub fn received_at(&self) -> Option<ReceivedAt> {
+        match self {
+            Self::OrderbookSnapshot { received_at, .. }
+            | Self::OrderbookUpdate { received_at, .. }
+            | Self::Ticker { received_at, .. }
+            | Self::Trade { received_at, .. }
+            | Self::Ohlc { received_at, .. } => Some(*received_at),
+            _ => None,
+        }
+    }
+
+    /// Exchange timestamp (Unix microseconds), if the message carried one
+    pub fn exchange_ts_us(&self) -> Option<i64> {
+        match self {
+            Self::OrderbookSnapshot { exchange_ts_us, .. }
+            | Self::OrderbookUpdate { exchange_ts_us, .. }
+            | Self::Ticker { exchange_ts_us, .. }
+            | Self::Trade { exchange_ts_us, .. } => *exchange_ts_us,
+            _ => None,
+        }
+    }
+
+    /// Exchange-to-client delta in microseconds (latency plus clock skew)
+    pub fn latency_us(&self) -> Option<i64> {
+        Some(self.received_at()?.delta_us(self.exchange_ts_us()?))
+    }
+}
+
 // ============================================================================
 // Private Channel Events
 // ============================================================================
@@ -524,10 +931,124 @@ impl From<L3Event> for Event {
     }
 }
 
+/// Event stamped with its connection-wide id
+#[derive(Debug, Clone)]
+pub struct SequencedEvent {
+    /// Connection-wide event id (see the module docs)
+    pub id: u64,
+    /// The event
+    pub event: Event,
+}
+
+/// How an event id relates to the last one a consumer processed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventIdCheck {
+    /// The id directly follows the last one (or is the first seen)
+    Next,
+    /// Ids were skipped: events were dropped or lost across a restart
+    Gap {
+        /// Number of ids skipped
+        missing: u64,
+    },
+    /// The id was already processed
+    Duplicate,
+}
+
+/// Last event id a consumer processed, for dedup and gap detection
+///
+/// Persist [`last`](Self::last) next to the consumer's own output and
+/// restore it with [`resume_after`](Self::resume_after).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventIdCursor {
+    last: Option<u64>,
+}
+
+impl EventIdCursor {
+    /// Cursor that hasn't seen any event
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cursor that has processed every id up to `last`
+    pub fn resume_after(last: u64) -> Self {
+        Self { last: Some(last) }
+    }
+
+    /// Last id processed
+    pub fn last(&self) -> Option<u64> {
+        self.last
+    }
+
+    /// Classify `id` and advance past it unless it is a duplicate
+    pub fn check(&mut self, id: u64) -> EventIdCheck {
+        let check = match self.last {
+            None => EventIdCheck::Next,
+            Some(last) if id <= last => return EventIdCheck::Duplicate,
+            Some(last) if id == last + 1 => EventIdCheck::Next,
+            Some(last) => EventIdCheck::Gap { missing: id - last - 1 },
+        };
+        self.last = Some(id);
+        check
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_disconnect_reason_mapping() {
+        assert_eq!(DisconnectReason::from_close_frame(1000, ""), DisconnectReason::ServerClosed);
+        assert_eq!(DisconnectReason::from_close_frame(1008, ""), DisconnectReason::RateLimited);
+        assert_eq!(
+            DisconnectReason::from_close_frame(4000, "Session token expired"),
+            DisconnectReason::AuthExpired
+        );
+        assert_eq!(DisconnectReason::from_close_frame(1013, ""), DisconnectReason::MaintenanceMode);
+        assert_eq!(
+            DisconnectReason::from_close_frame(4000, "Rate-limit exceeded"),
+            DisconnectReason::RateLimited
+        );
+        assert_eq!(
+            DisconnectReason::from_close_frame(1000, "Scheduled maintenance"),
+            DisconnectReason::MaintenanceMode
+        );
+        // Words that merely contain a keyword don't count
+        assert_eq!(
+            DisconnectReason::from_close_frame(1001, "server migrate, see author notes"),
+            DisconnectReason::ServerClosed
+        );
+        assert_eq!(
+            DisconnectReason::from_close_frame(4001, "accelerated shutdown"),
+            DisconnectReason::ProtocolError { code: 4001 }
+        );
+        assert_eq!(
+            DisconnectReason::from_close_frame(1011, "internal error"),
+            DisconnectReason::ProtocolError { code: 1011 }
+        );
+
+        let reason = |raw: &str| DisconnectReason::from_api_error(&KrakenApiError::parse(raw));
+        assert_eq!(reason("EGeneral:Too many requests"), Some(DisconnectReason::RateLimited));
+        assert_eq!(reason("EAPI:Invalid session"), Some(DisconnectReason::AuthExpired));
+        assert_eq!(reason("EService:Unavailable"), Some(DisconnectReason::MaintenanceMode));
+        assert_eq!(reason("EGeneral:Unknown asset pair"), None);
+    }
+
+    #[test]
+    fn test_event_id_cursor() {
+        let mut cursor = EventIdCursor::new();
+        assert_eq!(cursor.check(5), EventIdCheck::Next);
+        assert_eq!(cursor.check(6), EventIdCheck::Next);
+        assert_eq!(cursor.check(6), EventIdCheck::Duplicate);
+        assert_eq!(cursor.check(3), EventIdCheck::Duplicate);
+        assert_eq!(cursor.check(10), EventIdCheck::Gap { missing: 3 });
+        assert_eq!(cursor.last(), Some(10));
+
+        let mut resumed = EventIdCursor::resume_after(10);
+        assert_eq!(resumed.check(10), EventIdCheck::Duplicate);
+        assert_eq!(resumed.check(11), EventIdCheck::Next);
+    }
+
     #[test]
     fn test_order_status_parsing() {
         assert_eq!(OrderStatus::parse("pending"), OrderStatus::Pending);
@@ -0,0 +1,925 @@
+//! Execution algorithms
+//!
+//! Algorithms that work a parent order through child orders:
+//!
+//! - [`Twap`] - slices the order evenly over a duration
+//! - [`Iceberg`] - shows only part of the size and replenishes on fill
+//! - [`PegToMid`] - rests a limit order at the mid price plus an offset and
+//!   follows the book
+//!
+//! Algorithms don't perform I/O. They are driven by timer ticks, orderbook
+//! snapshots and executions, and return [`AlgoAction`]s that
+//! [`TradingClient::algo_request`](crate::TradingClient::algo_request) turns
+//! into requests. Progress is reported as [`AlgoEvent`]s.
+//!
+//! Child orders carry a client order ID (`<prefix>-<n>`) so executions can be
+//! matched back to the algorithm.
+//!
+//! Child quantities are rounded down to the pair's lot with
+//! [`Formatting::round_qty`] (set the pair's precision with
+//! `with_formatting`; the fallback is eight decimals). Whatever the rounding
+//! leaves over goes out with the last child, so the children always add up to
+//! the parent quantity. Limit prices are rounded to the pair's tick with
+//! [`Formatting::round_to_tick`] once the formatting has instrument data for
+//! the symbol; until then they go out as given.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_ws::execution::{AlgoAction, ExecutionAlgo, Twap};
+//! use kraken_types::Side;
+//! use rust_decimal_macros::dec;
+//! use std::time::{Duration, Instant};
+//!
+//! let start = Instant::now();
+//! let mut twap = Twap::new("twap1", "BTC/USD", Side::Buy, dec!(1), Duration::from_secs(60), 4);
+//!
+//! let actions = twap.start(start);
+//! assert!(matches!(&actions[0], AlgoAction::Place(child) if child.qty == dec!(0.25)));
+//! assert!(twap.on_tick(start + Duration::from_secs(10)).is_empty());
+//! assert_eq!(twap.on_tick(start + Duration::from_secs(15)).len(), 1);
+//! ```
+
+use crate::events::{Event, MarketEvent, PrivateEvent};
+use kraken_book::OrderbookSnapshot;
+use kraken_types::formatting::Formatting;
+use kraken_types::{Decimal, ExecutionData, Side};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// A child order to place
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChildOrder {
+    /// Client order ID assigned by the algorithm
+    pub cl_ord_id: String,
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Order side
+    pub side: Side,
+    /// Order quantity
+    pub qty: Decimal,
+    /// Limit price (`None` for a market order)
+    pub limit_price: Option<Decimal>,
+    /// Reject instead of taking liquidity
+    pub post_only: bool,
+}
+
+/// Order operation requested by an algorithm
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlgoAction {
+    /// Place a new child order
+    Place(ChildOrder),
+    /// Cancel a working child order
+    Cancel {
+        /// Client order ID of the child
+        cl_ord_id: String,
+    },
+    /// Change a working child order in place
+    Amend {
+        /// Exchange order ID of the child
+        order_id: String,
+        /// New limit price
+        limit_price: Decimal,
+        /// New quantity, if it changes
+        order_qty: Option<Decimal>,
+    },
+}
+
+/// Lifecycle state of an algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlgoState {
+    /// Created but not started
+    Pending,
+    /// Working the order
+    Running,
+    /// Target quantity fully filled
+    Completed,
+    /// Cancelled before completion
+    Cancelled,
+}
+
+impl AlgoState {
+    /// Check if the algorithm has finished
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Self::Completed | Self::Cancelled)
+    }
+}
+
+/// Progress event from an algorithm
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlgoEvent {
+    /// A child order was sent
+    ChildPlaced {
+        /// Client order ID of the child
+        cl_ord_id: String,
+        /// Child quantity
+        qty: Decimal,
+    },
+    /// A child order was (partially) filled
+    Fill {
+        /// Client order ID of the child
+        cl_ord_id: String,
+        /// Fill quantity
+        qty: Decimal,
+        /// Fill price
+        price: Decimal,
+        /// Total filled so far
+        filled: Decimal,
+    },
+    /// The target quantity is fully filled
+    Completed {
+        /// Total filled quantity
+        filled: Decimal,
+        /// Volume-weighted average fill price
+        avg_price: Option<Decimal>,
+    },
+    /// The algorithm was cancelled
+    Cancelled {
+        /// Quantity filled before cancellation
+        filled: Decimal,
+    },
+}
+
+/// Snapshot of an algorithm's progress
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlgoProgress {
+    /// Lifecycle state
+    pub state: AlgoState,
+    /// Parent order quantity
+    pub target_qty: Decimal,
+    /// Quantity filled so far
+    pub filled_qty: Decimal,
+    /// Volume-weighted average fill price
+    pub avg_price: Option<Decimal>,
+    /// Number of working child orders
+    pub working_children: usize,
+}
+
+impl AlgoProgress {
+    /// Quantity not yet filled
+    pub fn remaining_qty(&self) -> Decimal {
+        (self.target_qty - self.filled_qty).max(Decimal::ZERO)
+    }
+
+    /// Filled fraction in percent
+    pub fn fill_percentage(&self) -> Decimal {
+        if self.target_qty.is_zero() {
+            return Decimal::ZERO;
+        }
+        self.filled_qty / self.target_qty * Decimal::ONE_HUNDRED
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Child {
+    order_id: Option<String>,
+    qty: Decimal,
+    filled: Decimal,
+    limit_price: Option<Decimal>,
+}
+
+impl Child {
+    fn remaining(&self) -> Decimal {
+        (self.qty - self.filled).max(Decimal::ZERO)
+    }
+}
+
+/// Parent order state shared by all algorithms
+#[derive(Debug, Clone)]
+pub struct AlgoCore {
+    prefix: String,
+    symbol: String,
+    side: Side,
+    target_qty: Decimal,
+    filled_qty: Decimal,
+    filled_notional: Decimal,
+    state: AlgoState,
+    next_child: u64,
+    children: HashMap<String, Child>,
+    events: Vec<AlgoEvent>,
+    formatting: Formatting,
+}
+
+impl AlgoCore {
+    /// Create the state for a parent order
+    ///
+    /// `prefix` is used for child client order IDs and should be unique.
+    pub fn new(prefix: impl Into<String>, symbol: impl Into<String>, side: Side, qty: Decimal) -> Self {
+        Self {
+            prefix: prefix.into(),
+            symbol: symbol.into(),
+            side,
+            target_qty: qty,
+            filled_qty: Decimal::ZERO,
+            filled_notional: Decimal::ZERO,
+            state: AlgoState::Pending,
+            next_child: 0,
+            children: HashMap::new(),
+            events: Vec::new(),
+            formatting: Formatting::default(),
+        }
+    }
+
+    /// Set the precision child quantities are rounded to
+    pub fn set_formatting(&mut self, formatting: Formatting) {
+        self.formatting = formatting;
+    }
+
+    /// Quantity rounded down to a valid lot for the symbol
+    pub fn round_qty(&self, qty: Decimal) -> Decimal {
+        self.formatting.round_qty(&self.symbol, qty)
+    }
+
+    /// Price rounded to the symbol's tick, if its precision is known
+    pub fn round_price(&self, price: Decimal) -> Decimal {
+        match self.formatting.get(&self.symbol) {
+            Some(_) => self.formatting.round_to_tick(&self.symbol, price),
+            None => price,
+        }
+    }
+
+    /// Trading pair symbol
+    pub fn symbol(&self) -> &str {
+        &self.symbol
+    }
+
+    /// Order side
+    pub fn side(&self) -> Side {
+        self.side
+    }
+
+    /// Lifecycle state
+    pub fn state(&self) -> AlgoState {
+        self.state
+    }
+
+    /// Quantity not yet filled
+    pub fn remaining_qty(&self) -> Decimal {
+        (self.target_qty - self.filled_qty).max(Decimal::ZERO)
+    }
+
+    /// Quantity in working child orders that hasn't filled yet
+    pub fn working_qty(&self) -> Decimal {
+        self.children.values().map(Child::remaining).sum()
+    }
+
+    /// Current progress
+    pub fn progress(&self) -> AlgoProgress {
+        AlgoProgress {
+            state: self.state,
+            target_qty: self.target_qty,
+            filled_qty: self.filled_qty,
+            avg_price: self.avg_price(),
+            working_children: self.children.len(),
+        }
+    }
+
+    fn avg_price(&self) -> Option<Decimal> {
+        (!self.filled_qty.is_zero()).then(|| self.filled_notional / self.filled_qty)
+    }
+
+    fn place(&mut self, qty: Decimal, limit_price: Option<Decimal>) -> AlgoAction {
+        let limit_price = limit_price.map(|price| self.round_price(price));
+        self.next_child += 1;
+        let cl_ord_id = format!("{}-{}", self.prefix, self.next_child);
+        self.children.insert(
+            cl_ord_id.clone(),
+            Child {
+                order_id: None,
+                qty,
+                filled: Decimal::ZERO,
+                limit_price,
+            },
+        );
+        self.events.push(AlgoEvent::ChildPlaced {
+            cl_ord_id: cl_ord_id.clone(),
+            qty,
+        });
+        AlgoAction::Place(ChildOrder {
+            cl_ord_id,
+            symbol: self.symbol.clone(),
+            side: self.side,
+            qty,
+            limit_price,
+            post_only: false,
+        })
+    }
+
+    /// Record the exchange order ID of a child
+    pub fn ack(&mut self, cl_ord_id: &str, order_id: impl Into<String>) {
+        if let Some(child) = self.children.get_mut(cl_ord_id) {
+            child.order_id = Some(order_id.into());
+        }
+    }
+
+    /// Record a child fill, returning true if the child is now complete
+    fn fill(&mut self, cl_ord_id: &str, qty: Decimal, price: Decimal) -> bool {
+        let Some(child) = self.children.get_mut(cl_ord_id) else {
+            return false;
+        };
+        child.filled += qty;
+        let done = child.remaining().is_zero();
+        if done {
+            self.children.remove(cl_ord_id);
+        }
+        self.filled_qty += qty;
+        self.filled_notional += qty * price;
+        self.events.push(AlgoEvent::Fill {
+            cl_ord_id: cl_ord_id.to_string(),
+            qty,
+            price,
+            filled: self.filled_qty,
+        });
+        if self.remaining_qty().is_zero() && self.state == AlgoState::Running {
+            self.state = AlgoState::Completed;
+            self.events.push(AlgoEvent::Completed {
+                filled: self.filled_qty,
+                avg_price: self.avg_price(),
+            });
+        }
+        done
+    }
+
+    /// Child client order ID for an execution
+    fn child_for(&self, exec: &ExecutionData) -> Option<String> {
+        if let Some(id) = exec.cl_ord_id.as_ref().filter(|id| self.children.contains_key(*id)) {
+            return Some(id.clone());
+        }
+        self.children
+            .iter()
+            .find(|(_, c)| c.order_id.as_deref() == Some(exec.order_id.as_str()))
+            .map(|(id, _)| id.clone())
+    }
+
+    fn cancel(&mut self) -> Vec<AlgoAction> {
+        if self.state.is_terminal() {
+            return Vec::new();
+        }
+        self.state = AlgoState::Cancelled;
+        self.events.push(AlgoEvent::Cancelled {
+            filled: self.filled_qty,
+        });
+        let mut ids: Vec<String> = self.children.drain().map(|(id, _)| id).collect();
+        ids.sort();
+        ids.into_iter()
+            .map(|cl_ord_id| AlgoAction::Cancel { cl_ord_id })
+            .collect()
+    }
+}
+
+/// Common interface of execution algorithms
+pub trait ExecutionAlgo: Send {
+    /// Shared parent order state
+    fn core(&self) -> &AlgoCore;
+
+    /// Shared parent order state, mutably
+    fn core_mut(&mut self) -> &mut AlgoCore;
+
+    /// Start working the order
+    fn start(&mut self, now: Instant) -> Vec<AlgoAction>;
+
+    /// Advance time-driven logic
+    fn on_tick(&mut self, _now: Instant) -> Vec<AlgoAction> {
+        Vec::new()
+    }
+
+    /// React to a new orderbook state for the algorithm's symbol
+    fn on_book(&mut self, _book: &OrderbookSnapshot, _now: Instant) -> Vec<AlgoAction> {
+        Vec::new()
+    }
+
+    /// React to a child fill
+    ///
+    /// Called by [`on_execution`](Self::on_execution); the default just
+    /// records the fill.
+    fn on_fill(&mut self, cl_ord_id: &str, qty: Decimal, price: Decimal) -> Vec<AlgoAction> {
+        self.core_mut().fill(cl_ord_id, qty, price);
+        Vec::new()
+    }
+
+    /// Record the exchange order ID of a child (from the add_order response)
+    fn on_ack(&mut self, cl_ord_id: &str, order_id: &str) {
+        self.core_mut().ack(cl_ord_id, order_id);
+    }
+
+    /// Feed an execution from the executions channel
+    ///
+    /// Executions that don't belong to one of this algorithm's children are
+    /// ignored.
+    fn on_execution(&mut self, exec: &ExecutionData) -> Vec<AlgoAction> {
+        let Some(cl_ord_id) = self.core().child_for(exec) else {
+            return Vec::new();
+        };
+        self.core_mut().ack(&cl_ord_id, exec.order_id.clone());
+        match (exec.last_qty, exec.last_price) {
+            (Some(qty), Some(price)) if !qty.is_zero() => self.on_fill(&cl_ord_id, qty, price),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Feed an SDK event (orderbook updates and executions)
+    fn on_event(&mut self, event: &Event, now: Instant) -> Vec<AlgoAction> {
+        if self.core().state().is_terminal() {
+            return Vec::new();
+        }
+        match event {
+            Event::Market(MarketEvent::OrderbookSnapshot { symbol, snapshot, .. })
+            | Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot, .. })
+                if symbol == self.core().symbol() =>
+            {
+                self.on_book(snapshot, now)
+            }
+            Event::Private(private) => match private.as_ref() {
+                PrivateEvent::Execution { data, .. } => self.on_execution(data),
+                _ => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    /// Stop the algorithm and cancel its working children
+    fn cancel(&mut self) -> Vec<AlgoAction> {
+        self.core_mut().cancel()
+    }
+
+    /// Current progress
+    fn progress(&self) -> AlgoProgress {
+        self.core().progress()
+    }
+
+    /// Take the progress events produced since the last call
+    fn drain_events(&mut self) -> Vec<AlgoEvent> {
+        std::mem::take(&mut self.core_mut().events)
+    }
+}
+
+// ============================================================================
+// TWAP
+// ============================================================================
+
+/// Time-weighted average price: equal slices at a fixed interval
+#[derive(Debug, Clone)]
+pub struct Twap {
+    core: AlgoCore,
+    duration: Duration,
+    slices: u32,
+    sent_slices: u32,
+    limit_price: Option<Decimal>,
+    started_at: Option<Instant>,
+}
+
+impl Twap {
+    /// Slice `qty` into `slices` orders spread evenly over `duration`
+    ///
+    /// The first slice is sent on start and the last at
+    /// `duration * (slices - 1) / slices`.
+    pub fn new(
+        id: impl Into<String>,
+        symbol: impl Into<String>,
+        side: Side,
+        qty: Decimal,
+        duration: Duration,
+        slices: u32,
+    ) -> Self {
+        Self {
+            core: AlgoCore::new(id, symbol, side, qty),
+            duration,
+            slices: slices.max(1),
+            sent_slices: 0,
+            limit_price: None,
+            started_at: None,
+        }
+    }
+
+    /// Send slices as limit orders at `price` instead of market orders
+    pub fn with_limit_price(mut self, price: Decimal) -> Self {
+        self.limit_price = Some(price);
+        self
+    }
+
+    /// Round slices to the symbol's precision in `formatting`
+    pub fn with_formatting(mut self, formatting: Formatting) -> Self {
+        self.core.set_formatting(formatting);
+        self
+    }
+
+    /// Interval between slices
+    pub fn interval(&self) -> Duration {
+        self.duration / self.slices
+    }
+
+    fn send_due(&mut self, now: Instant) -> Vec<AlgoAction> {
+        let Some(started_at) = self.started_at else {
+            return Vec::new();
+        };
+        if self.core.state != AlgoState::Running {
+            return Vec::new();
+        }
+        let elapsed = now.saturating_duration_since(started_at);
+        let interval = self.interval();
+        let due = if interval.is_zero() {
+            self.slices
+        } else {
+            ((elapsed.as_nanos() / interval.as_nanos()) as u32 + 1).min(self.slices)
+        };
+
+        let mut actions = Vec::new();
+        // Rounded down; the last slice takes the remainder
+        let slice_qty = self.core.round_qty(self.core.target_qty / Decimal::from(self.slices));
+        while self.sent_slices < due {
+            self.sent_slices += 1;
+            let unsent = self.core.remaining_qty() - self.core.working_qty();
+            let qty = if self.sent_slices == self.slices {
+                unsent
+            } else {
+                slice_qty.min(unsent)
+            };
+            if qty > Decimal::ZERO {
+                actions.push(self.core.place(qty, self.limit_price));
+            }
+        }
+        actions
+    }
+}
+
+impl ExecutionAlgo for Twap {
+    fn core(&self) -> &AlgoCore {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut AlgoCore {
+        &mut self.core
+    }
+
+    fn start(&mut self, now: Instant) -> Vec<AlgoAction> {
+        if self.core.state != AlgoState::Pending {
+            return Vec::new();
+        }
+        self.core.state = AlgoState::Running;
+        self.started_at = Some(now);
+        self.send_due(now)
+    }
+
+    fn on_tick(&mut self, now: Instant) -> Vec<AlgoAction> {
+        self.send_due(now)
+    }
+}
+
+// ============================================================================
+// Iceberg
+// ============================================================================
+
+/// Iceberg: one visible child at a time, replenished when it fills
+#[derive(Debug, Clone)]
+pub struct Iceberg {
+    core: AlgoCore,
+    display_qty: Decimal,
+    limit_price: Decimal,
+}
+
+impl Iceberg {
+    /// Work `qty` at `limit_price`, showing at most `display_qty` at a time
+    pub fn new(
+        id: impl Into<String>,
+        symbol: impl Into<String>,
+        side: Side,
+        qty: Decimal,
+        display_qty: Decimal,
+        limit_price: Decimal,
+    ) -> Self {
+        Self {
+            core: AlgoCore::new(id, symbol, side, qty),
+            display_qty,
+            limit_price,
+        }
+    }
+
+    /// Round children to the symbol's precision in `formatting`
+    pub fn with_formatting(mut self, formatting: Formatting) -> Self {
+        self.core.set_formatting(formatting);
+        self
+    }
+
+    fn replenish(&mut self) -> Vec<AlgoAction> {
+        if self.core.state != AlgoState::Running || !self.core.children.is_empty() {
+            return Vec::new();
+        }
+        let remaining = self.core.remaining_qty();
+        let display = self.core.round_qty(self.display_qty);
+        // A remainder too small for another lot goes out with this child
+        let qty = if display.is_zero() || self.core.round_qty(remaining - display) <= Decimal::ZERO {
+            remaining
+        } else {
+            display
+        };
+        if qty <= Decimal::ZERO {
+            return Vec::new();
+        }
+        vec![self.core.place(qty, Some(self.limit_price))]
+    }
+}
+
+impl ExecutionAlgo for Iceberg {
+    fn core(&self) -> &AlgoCore {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut AlgoCore {
+        &mut self.core
+    }
+
+    fn start(&mut self, _now: Instant) -> Vec<AlgoAction> {
+        if self.core.state != AlgoState::Pending {
+            return Vec::new();
+        }
+        self.core.state = AlgoState::Running;
+        self.replenish()
+    }
+
+    fn on_fill(&mut self, cl_ord_id: &str, qty: Decimal, price: Decimal) -> Vec<AlgoAction> {
+        if self.core.fill(cl_ord_id, qty, price) {
+            self.replenish()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+// ============================================================================
+// Peg to mid
+// ============================================================================
+
+/// Limit order pegged to the mid price plus an offset
+///
+/// Buys rest at `mid - offset` and sells at `mid + offset`. The order is
+/// amended when the target moves by at least the reprice threshold.
+#[derive(Debug, Clone)]
+pub struct PegToMid {
+    core: AlgoCore,
+    offset: Decimal,
+    reprice_threshold: Decimal,
+    price_decimals: Option<u32>,
+    last_target: Option<Decimal>,
+}
+
+impl PegToMid {
+    /// Peg `qty` to the mid price, `offset` away on the passive side
+    pub fn new(
+        id: impl Into<String>,
+        symbol: impl Into<String>,
+        side: Side,
+        qty: Decimal,
+        offset: Decimal,
+    ) -> Self {
+        Self {
+            core: AlgoCore::new(id, symbol, side, qty),
+            offset,
+            reprice_threshold: Decimal::ZERO,
+            price_decimals: None,
+            last_target: None,
+        }
+    }
+
+    /// Only reprice when the target moves by at least `threshold`
+    pub fn with_reprice_threshold(mut self, threshold: Decimal) -> Self {
+        self.reprice_threshold = threshold;
+        self
+    }
+
+    /// Round prices to the pair's price precision
+    pub fn with_price_decimals(mut self, decimals: u32) -> Self {
+        self.price_decimals = Some(decimals);
+        self
+    }
+
+    /// Round prices to the symbol's tick in `formatting`
+    pub fn with_formatting(mut self, formatting: Formatting) -> Self {
+        self.core.set_formatting(formatting);
+        self
+    }
+
+    fn target(&self, mid: Decimal) -> Decimal {
+        let price = match self.core.side {
+            Side::Buy => mid - self.offset,
+            Side::Sell => mid + self.offset,
+        };
+        match self.price_decimals {
+            Some(dp) => price.round_dp(dp),
+            None => self.core.round_price(price),
+        }
+    }
+
+    fn work(&mut self) -> Vec<AlgoAction> {
+        let Some(target) = self.last_target else {
+            return Vec::new();
+        };
+        if self.core.state != AlgoState::Running {
+            return Vec::new();
+        }
+
+        let Some(child) = self.core.children.values_mut().next() else {
+            let qty = self.core.remaining_qty();
+            if qty <= Decimal::ZERO {
+                return Vec::new();
+            }
+            return vec![self.core.place(qty, Some(target))];
+        };
+
+        let current = child.limit_price.unwrap_or(target);
+        let moved = (target - current).abs();
+        if moved.is_zero() || moved < self.reprice_threshold {
+            return Vec::new();
+        }
+        // Can't amend before the exchange order ID is known
+        let Some(order_id) = child.order_id.clone() else {
+            return Vec::new();
+        };
+        child.limit_price = Some(target);
+        vec![AlgoAction::Amend {
+            order_id,
+            limit_price: target,
+            order_qty: None,
+        }]
+    }
+}
+
+impl ExecutionAlgo for PegToMid {
+    fn core(&self) -> &AlgoCore {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut AlgoCore {
+        &mut self.core
+    }
+
+    fn start(&mut self, _now: Instant) -> Vec<AlgoAction> {
+        if self.core.state != AlgoState::Pending {
+            return Vec::new();
+        }
+        self.core.state = AlgoState::Running;
+        self.work()
+    }
+
+    fn on_book(&mut self, book: &OrderbookSnapshot, _now: Instant) -> Vec<AlgoAction> {
+        let Some(mid) = book.mid_price() else {
+            return Vec::new();
+        };
+        self.last_target = Some(self.target(mid));
+        self.work()
+    }
+
+    fn on_fill(&mut self, cl_ord_id: &str, qty: Decimal, price: Decimal) -> Vec<AlgoAction> {
+        if self.core.fill(cl_ord_id, qty, price) {
+            self.work()
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_book::OrderbookState;
+    use kraken_types::Level;
+    use rust_decimal_macros::dec;
+
+    fn child_id(action: &AlgoAction) -> String {
+        match action {
+            AlgoAction::Place(child) => child.cl_ord_id.clone(),
+            other => panic!("expected placement, got {other:?}"),
+        }
+    }
+
+    fn book(bid: Decimal, ask: Decimal) -> OrderbookSnapshot {
+        OrderbookSnapshot {
+            symbol: "BTC/USD".into(),
+            bids: vec![Level::new(bid, dec!(1))],
+            asks: vec![Level::new(ask, dec!(1))],
+            checksum: 0,
+            state: OrderbookState::Synced,
+        }
+    }
+
+    #[test]
+    fn test_twap_slices_and_completes() {
+        let start = Instant::now();
+        let mut twap = Twap::new("t", "BTC/USD", Side::Sell, dec!(1), Duration::from_secs(30), 3);
+
+        let first = twap.start(start);
+        assert_eq!(first.len(), 1);
+        // Catching up after a late tick sends every due slice
+        let rest = twap.on_tick(start + Duration::from_secs(25));
+        assert_eq!(rest.len(), 2);
+        assert!(twap.on_tick(start + Duration::from_secs(60)).is_empty());
+
+        for action in first.iter().chain(&rest) {
+            let AlgoAction::Place(child) = action else { unreachable!() };
+            twap.on_fill(&child.cl_ord_id, child.qty, dec!(100));
+        }
+        let progress = twap.progress();
+        assert_eq!(progress.state, AlgoState::Completed);
+        assert_eq!(progress.filled_qty, dec!(1));
+        assert!(matches!(
+            twap.drain_events().last(),
+            Some(AlgoEvent::Completed { avg_price: Some(p), .. }) if *p == dec!(100)
+        ));
+    }
+
+    #[test]
+    fn test_iceberg_replenishes_on_fill() {
+        let mut iceberg = Iceberg::new("i", "BTC/USD", Side::Buy, dec!(5), dec!(2), dec!(100));
+        let first = child_id(&iceberg.start(Instant::now())[0]);
+
+        assert!(iceberg.on_fill(&first, dec!(1), dec!(100)).is_empty());
+        let next = iceberg.on_fill(&first, dec!(1), dec!(100));
+        assert!(matches!(&next[0], AlgoAction::Place(c) if c.qty == dec!(2)));
+
+        let next = iceberg.on_fill(&child_id(&next[0]), dec!(2), dec!(100));
+        // Last child only shows what's left
+        assert!(matches!(&next[0], AlgoAction::Place(c) if c.qty == dec!(1)));
+
+        let cancel = iceberg.cancel();
+        assert!(matches!(&cancel[0], AlgoAction::Cancel { cl_ord_id } if *cl_ord_id == child_id(&next[0])));
+        assert_eq!(iceberg.progress().state, AlgoState::Cancelled);
+    }
+
+    #[test]
+    fn test_uneven_targets_round_to_lots() {
+        use kraken_types::formatting::Precision;
+
+        let formatting = Formatting::new().with_precision("BTC/USD", Precision::new(1, 2));
+        let qty = |action: &AlgoAction| match action {
+            AlgoAction::Place(child) => child.qty,
+            other => panic!("expected placement, got {other:?}"),
+        };
+
+        let start = Instant::now();
+        let mut twap = Twap::new("t", "BTC/USD", Side::Buy, dec!(1), Duration::from_secs(30), 3)
+            .with_formatting(formatting.clone());
+        let mut slices = twap.start(start);
+        slices.extend(twap.on_tick(start + Duration::from_secs(30)));
+        let sizes: Vec<Decimal> = slices.iter().map(qty).collect();
+        assert_eq!(sizes, vec![dec!(0.33), dec!(0.33), dec!(0.34)]);
+
+        let mut iceberg = Iceberg::new("i", "BTC/USD", Side::Buy, dec!(1.005), dec!(0.5), dec!(100))
+            .with_formatting(formatting);
+        let first = iceberg.start(start);
+        assert_eq!(qty(&first[0]), dec!(0.5));
+        let second = iceberg.on_fill(&child_id(&first[0]), dec!(0.5), dec!(100));
+        // 0.005 can't be shown on its own, so it joins the last child
+        assert_eq!(qty(&second[0]), dec!(0.505));
+    }
+
+    #[test]
+    fn test_limit_prices_round_to_ticks() {
+        use kraken_types::formatting::Precision;
+
+        let formatting = Formatting::new().with_precision("BTC/USD", Precision::new(1, 8));
+        let price = |action: &AlgoAction| match action {
+            AlgoAction::Place(child) => child.limit_price,
+            other => panic!("expected placement, got {other:?}"),
+        };
+        let now = Instant::now();
+
+        let mut twap = Twap::new("t", "BTC/USD", Side::Buy, dec!(1), Duration::from_secs(10), 1)
+            .with_limit_price(dec!(100.04))
+            .with_formatting(formatting.clone());
+        assert_eq!(price(&twap.start(now)[0]), Some(dec!(100)));
+
+        let mut peg = PegToMid::new("p", "BTC/USD", Side::Buy, dec!(1), dec!(0.33)).with_formatting(formatting);
+        peg.start(now);
+        let placed = peg.on_book(&book(dec!(99), dec!(101)), now);
+        assert_eq!(price(&placed[0]), Some(dec!(99.7)));
+
+        // Unknown precision: sent as given
+        let mut iceberg = Iceberg::new("i", "XRP/USD", Side::Buy, dec!(1), dec!(1), dec!(0.51234));
+        assert_eq!(price(&iceberg.start(now)[0]), Some(dec!(0.51234)));
+    }
+
+    #[test]
+    fn test_peg_follows_mid() {
+        let now = Instant::now();
+        let mut peg = PegToMid::new("p", "BTC/USD", Side::Buy, dec!(1), dec!(5))
+            .with_reprice_threshold(dec!(2));
+        assert!(peg.start(now).is_empty());
+
+        let placed = peg.on_book(&book(dec!(99), dec!(101)), now);
+        assert!(matches!(&placed[0], AlgoAction::Place(c) if c.limit_price == Some(dec!(95))));
+
+        // No amend until the order is acknowledged
+        assert!(peg.on_book(&book(dec!(109), dec!(111)), now).is_empty());
+        peg.on_ack(&child_id(&placed[0]), "OID1");
+        let amended = peg.on_book(&book(dec!(109), dec!(111)), now);
+        assert_eq!(
+            amended,
+            vec![AlgoAction::Amend {
+                order_id: "OID1".into(),
+                limit_price: dec!(105),
+                order_qty: None,
+            }]
+        );
+        // Moves below the threshold are ignored
+        assert!(peg.on_book(&book(dec!(110), dec!(112)), now).is_empty());
+    }
+}
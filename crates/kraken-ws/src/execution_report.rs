@@ -0,0 +1,373 @@
+//! Post-trade execution reports
+//!
+//! [`ExecutionReport`] summarises the orders in an [`OrderTracker`], or a
+//! list of [`LifecycleOrder`]s loaded from persisted tracker state, into fill
+//! ratios, slippage, time-to-first-fill percentiles and fee totals, overall
+//! and per symbol. Reports serialize to JSON and render to Markdown for
+//! daily review.
+//!
+//! Time to first fill uses the tracker's monotonic timings when available.
+//! Orders restored from JSON don't carry those, so their `created_at` and
+//! first fill timestamps are used instead.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_ws::execution_report::ExecutionReport;
+//! use kraken_ws::order_tracker::{Fill, LifecycleOrder, LifecycleState};
+//! use kraken_types::Side;
+//! use rust_decimal_macros::dec;
+//!
+//! let mut order = LifecycleOrder::new_pending(None, "BTC/USD".into(), Side::Buy, dec!(2), Some(dec!(100)));
+//! order.created_at = "2024-01-01T00:00:00Z".into();
+//! order.fills.push(Fill {
+//!     exec_id: Some("T1".into()),
+//!     price: dec!(100.5),
+//!     qty: dec!(2),
+//!     fee: dec!(0.52),
+//!     fee_currency: Some("USD".into()),
+//!     timestamp: "2024-01-01T00:00:00.250Z".into(),
+//!     latency: None,
+//! });
+//! order.filled_qty = dec!(2);
+//! order.lifecycle_state = LifecycleState::Filled;
+//!
+//! let report = ExecutionReport::from_orders([&order]);
+//! assert_eq!(report.summary.fill_ratio, Some(dec!(1)));
+//! assert_eq!(report.summary.avg_slippage_bps, Some(dec!(50)));
+//! assert_eq!(report.summary.fees["USD"], dec!(0.52));
+//! assert!(report.to_markdown().contains("| BTC/USD |"));
+//! ```
+
+use crate::order_tracker::{LifecycleOrder, LifecycleState, OrderTracker};
+use kraken_types::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::time::Duration;
+
+/// Fee currency used when neither the fill nor the order names one
+pub const UNKNOWN_FEE_CURRENCY: &str = "unknown";
+
+/// Distribution of time to first fill, in milliseconds
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FillTimePercentiles {
+    /// Orders with a measurable time to first fill
+    pub samples: usize,
+    /// Median
+    pub p50_ms: f64,
+    /// 90th percentile
+    pub p90_ms: f64,
+    /// 99th percentile
+    pub p99_ms: f64,
+    /// Slowest
+    pub max_ms: f64,
+}
+
+impl FillTimePercentiles {
+    /// Nearest-rank percentiles, None if there are no samples
+    pub fn from_durations(durations: &[Duration]) -> Option<Self> {
+        if durations.is_empty() {
+            return None;
+        }
+        let mut ms: Vec<f64> = durations.iter().map(|d| d.as_secs_f64() * 1000.0).collect();
+        ms.sort_by(f64::total_cmp);
+        let rank = |p: f64| ms[((p * ms.len() as f64).ceil() as usize).clamp(1, ms.len()) - 1];
+        Some(Self {
+            samples: ms.len(),
+            p50_ms: rank(0.50),
+            p90_ms: rank(0.90),
+            p99_ms: rank(0.99),
+            max_ms: ms[ms.len() - 1],
+        })
+    }
+}
+
+/// Execution metrics over a set of orders
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReportSection {
+    /// Orders included
+    pub orders: usize,
+    /// Fully filled orders
+    pub filled: usize,
+    /// Orders with some but not all quantity filled (open or closed)
+    pub partially_filled: usize,
+    /// Canceled or expired orders
+    pub canceled: usize,
+    /// Rejected orders
+    pub rejected: usize,
+    /// Orders still working
+    pub active: usize,
+    /// Quantity ordered
+    pub ordered_qty: Decimal,
+    /// Quantity filled
+    pub filled_qty: Decimal,
+    /// Filled quantity over ordered quantity (None if nothing was ordered)
+    pub fill_ratio: Option<Decimal>,
+    /// Traded value (price × qty over all fills)
+    pub notional: Decimal,
+    /// Number of fills
+    pub fills: usize,
+    /// Mean slippage against the limit price, in basis points (positive = worse)
+    pub avg_slippage_bps: Option<Decimal>,
+    /// Time from submission to first fill
+    pub time_to_first_fill: Option<FillTimePercentiles>,
+    /// Fees paid, by currency
+    pub fees: BTreeMap<String, Decimal>,
+}
+
+impl ReportSection {
+    fn from_orders(orders: &[&LifecycleOrder]) -> Self {
+        let mut section = Self {
+            orders: orders.len(),
+            ..Self::default()
+        };
+        let mut slippages = Vec::new();
+        let mut first_fill_times = Vec::new();
+
+        for order in orders {
+            match order.lifecycle_state {
+                LifecycleState::Filled => section.filled += 1,
+                LifecycleState::Canceled | LifecycleState::Expired => section.canceled += 1,
+                LifecycleState::Rejected => section.rejected += 1,
+                LifecycleState::Pending | LifecycleState::New | LifecycleState::PartiallyFilled => {
+                    section.active += 1
+                }
+            }
+            if order.filled_qty > Decimal::ZERO && order.filled_qty < order.original_qty {
+                section.partially_filled += 1;
+            }
+
+            section.ordered_qty += order.original_qty;
+            section.filled_qty += order.filled_qty;
+            section.fills += order.fills.len();
+            for fill in &order.fills {
+                section.notional += fill.value();
+                let currency = fill
+                    .fee_currency
+                    .as_deref()
+                    .or(order.fee_currency.as_deref())
+                    .unwrap_or(UNKNOWN_FEE_CURRENCY);
+                *section.fees.entry(currency.to_string()).or_default() += fill.fee;
+            }
+
+            slippages.extend(order.slippage_bps());
+            first_fill_times.extend(time_to_first_fill(order));
+        }
+
+        if section.ordered_qty > Decimal::ZERO {
+            section.fill_ratio = Some(section.filled_qty / section.ordered_qty);
+        }
+        if !slippages.is_empty() {
+            section.avg_slippage_bps = Some(slippages.iter().sum::<Decimal>() / Decimal::from(slippages.len()));
+        }
+        section.time_to_first_fill = FillTimePercentiles::from_durations(&first_fill_times);
+        section
+    }
+}
+
+/// Time to first fill from tracker timings, falling back to timestamps
+fn time_to_first_fill(order: &LifecycleOrder) -> Option<Duration> {
+    if let Some(elapsed) = order.time_to_first_fill() {
+        return Some(elapsed);
+    }
+    let created = chrono::DateTime::parse_from_rfc3339(&order.created_at).ok()?;
+    let first = chrono::DateTime::parse_from_rfc3339(&order.fills.first()?.timestamp).ok()?;
+    (first - created).to_std().ok()
+}
+
+/// Summary of execution quality, overall and per symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExecutionReport {
+    /// When the report was generated (RFC 3339)
+    pub generated_at: String,
+    /// All orders
+    pub summary: ReportSection,
+    /// Orders grouped by symbol
+    pub by_symbol: BTreeMap<String, ReportSection>,
+}
+
+impl ExecutionReport {
+    /// Report on every order in a tracker, including pending ones
+    pub fn from_tracker(tracker: &OrderTracker) -> Self {
+        Self::from_orders(tracker.orders())
+    }
+
+    /// Report on a set of orders, e.g. deserialized from persisted tracker state
+    pub fn from_orders<'a>(orders: impl IntoIterator<Item = &'a LifecycleOrder>) -> Self {
+        let orders: Vec<&LifecycleOrder> = orders.into_iter().collect();
+        let mut grouped: BTreeMap<&str, Vec<&LifecycleOrder>> = BTreeMap::new();
+        for order in &orders {
+            grouped.entry(order.symbol.as_str()).or_default().push(order);
+        }
+        Self {
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            summary: ReportSection::from_orders(&orders),
+            by_symbol: grouped
+                .into_iter()
+                .map(|(symbol, orders)| (symbol.to_string(), ReportSection::from_orders(&orders)))
+                .collect(),
+        }
+    }
+
+    /// Serialize as pretty-printed JSON
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(self)
+    }
+
+    /// Render as a Markdown document
+    pub fn to_markdown(&self) -> String {
+        let s = &self.summary;
+        let mut md = String::new();
+        let _ = writeln!(md, "# Execution Report\n");
+        let _ = writeln!(md, "Generated {}\n", self.generated_at);
+        let _ = writeln!(md, "## Summary\n");
+        let _ = writeln!(md, "| Metric | Value |");
+        let _ = writeln!(md, "|---|---|");
+        let _ = writeln!(
+            md,
+            "| Orders | {} ({} filled, {} partial, {} canceled, {} rejected, {} active) |",
+            s.orders, s.filled, s.partially_filled, s.canceled, s.rejected, s.active
+        );
+        let _ = writeln!(md, "| Quantity filled | {} / {} |", s.filled_qty, s.ordered_qty);
+        let _ = writeln!(md, "| Fill ratio | {} |", format_ratio(s.fill_ratio));
+        let _ = writeln!(md, "| Fills | {} |", s.fills);
+        let _ = writeln!(md, "| Notional | {} |", s.notional.round_dp(2));
+        let _ = writeln!(md, "| Avg slippage | {} |", format_bps(s.avg_slippage_bps));
+        let _ = writeln!(md, "| Time to first fill | {} |", format_fill_times(s.time_to_first_fill.as_ref()));
+        let _ = writeln!(md, "| Fees | {} |", format_fees(&s.fees));
+
+        if !self.by_symbol.is_empty() {
+            let _ = writeln!(md, "\n## By Symbol\n");
+            let _ = writeln!(
+                md,
+                "| Symbol | Orders | Filled | Fill ratio | Avg slippage | First fill p50 | First fill p99 | Fees |"
+            );
+            let _ = writeln!(md, "|---|---:|---:|---:|---:|---:|---:|---|");
+            for (symbol, section) in &self.by_symbol {
+                let times = section.time_to_first_fill.as_ref();
+                let _ = writeln!(
+                    md,
+                    "| {} | {} | {} | {} | {} | {} | {} | {} |",
+                    symbol,
+                    section.orders,
+                    section.filled,
+                    format_ratio(section.fill_ratio),
+                    format_bps(section.avg_slippage_bps),
+                    times.map_or("-".to_string(), |t| format!("{:.0} ms", t.p50_ms)),
+                    times.map_or("-".to_string(), |t| format!("{:.0} ms", t.p99_ms)),
+                    format_fees(&section.fees),
+                );
+            }
+        }
+        md
+    }
+}
+
+fn format_ratio(ratio: Option<Decimal>) -> String {
+    ratio.map_or("-".to_string(), |r| format!("{:.2}%", r * Decimal::from(100)))
+}
+
+fn format_bps(bps: Option<Decimal>) -> String {
+    bps.map_or("-".to_string(), |b| format!("{:.2} bps", b))
+}
+
+fn format_fill_times(times: Option<&FillTimePercentiles>) -> String {
+    times.map_or("-".to_string(), |t| {
+        format!(
+            "p50 {:.0} ms, p90 {:.0} ms, p99 {:.0} ms, max {:.0} ms (n={})",
+            t.p50_ms, t.p90_ms, t.p99_ms, t.max_ms, t.samples
+        )
+    })
+}
+
+fn format_fees(fees: &BTreeMap<String, Decimal>) -> String {
+    if fees.is_empty() {
+        return "-".to_string();
+    }
+    fees.iter()
+        .map(|(currency, amount)| format!("{} {}", amount.normalize(), currency))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::order_tracker::Fill;
+    use kraken_types::Side;
+    use rust_decimal_macros::dec;
+
+    fn order(symbol: &str, side: Side, qty: Decimal, limit: Decimal, fills: &[(Decimal, Decimal, &str)]) -> LifecycleOrder {
+        let mut order = LifecycleOrder::new_pending(None, symbol.to_string(), side, qty, Some(limit));
+        order.created_at = "2024-01-01T00:00:00Z".to_string();
+        for (price, fill_qty, at) in fills {
+            order.fills.push(Fill {
+                exec_id: None,
+                price: *price,
+                qty: *fill_qty,
+                fee: *price * *fill_qty * dec!(0.001),
+                fee_currency: None,
+                timestamp: at.to_string(),
+                latency: None,
+            });
+            order.filled_qty += *fill_qty;
+        }
+        order.fee_currency = Some("USD".to_string());
+        order.lifecycle_state = if order.filled_qty == qty {
+            LifecycleState::Filled
+        } else {
+            LifecycleState::Canceled
+        };
+        order
+    }
+
+    #[test]
+    fn test_summary_and_symbol_breakdown() {
+        let orders = [
+            order("BTC/USD", Side::Buy, dec!(1), dec!(100), &[(dec!(101), dec!(1), "2024-01-01T00:00:00.100Z")]),
+            order("BTC/USD", Side::Sell, dec!(2), dec!(100), &[(dec!(100), dec!(1), "2024-01-01T00:00:00.300Z")]),
+            order("ETH/USD", Side::Buy, dec!(4), dec!(10), &[]),
+        ];
+        let report = ExecutionReport::from_orders(&orders);
+
+        let s = &report.summary;
+        assert_eq!((s.orders, s.filled, s.partially_filled, s.canceled), (3, 1, 1, 2));
+        assert_eq!(s.fill_ratio, Some(dec!(2) / dec!(7)));
+        assert_eq!(s.avg_slippage_bps, Some(dec!(50)));
+        assert_eq!(s.fees["USD"], dec!(0.201));
+        let times = s.time_to_first_fill.as_ref().unwrap();
+        assert_eq!((times.samples, times.p50_ms, times.max_ms), (2, 100.0, 300.0));
+
+        let btc = &report.by_symbol["BTC/USD"];
+        assert_eq!(btc.fill_ratio, Some(dec!(2) / dec!(3)));
+        let eth = &report.by_symbol["ETH/USD"];
+        assert_eq!((eth.fills, eth.fill_ratio), (0, Some(dec!(0))));
+        assert!(eth.time_to_first_fill.is_none());
+    }
+
+    #[test]
+    fn test_round_trips_through_json_and_persisted_orders() {
+        let orders = vec![order("BTC/USD", Side::Buy, dec!(1), dec!(100), &[(dec!(100), dec!(1), "2024-01-01T00:00:01Z")])];
+        let persisted: Vec<LifecycleOrder> =
+            serde_json::from_str(&serde_json::to_string(&orders).unwrap()).unwrap();
+        let report = ExecutionReport::from_orders(&persisted);
+        assert_eq!(report.summary.time_to_first_fill.as_ref().unwrap().p50_ms, 1000.0);
+
+        let json = report.to_json().unwrap();
+        let back: ExecutionReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, report);
+
+        let md = report.to_markdown();
+        assert!(md.contains("| Fill ratio | 100.00% |"));
+        assert!(md.contains("| Fees | 0.1 USD |"));
+    }
+
+    #[test]
+    fn test_percentiles_use_nearest_rank() {
+        let durations: Vec<Duration> = (1..=100).map(Duration::from_millis).collect();
+        let p = FillTimePercentiles::from_durations(&durations).unwrap();
+        assert_eq!((p.p50_ms, p.p90_ms, p.p99_ms, p.max_ms), (50.0, 90.0, 99.0, 100.0));
+        assert!(FillTimePercentiles::from_durations(&[]).is_none());
+    }
+}
@@ -0,0 +1,236 @@
+//! Connection health counters
+//!
+//! [`HealthTracker`] is updated by [`KrakenConnection`](crate::KrakenConnection)
+//! as messages arrive and the connection cycles. [`HealthStats`] is a
+//! point-in-time copy for dashboards and health checks.
+//!
+//! Message counts are cumulative over the connection's lifetime. For rates,
+//! keep the previous snapshot and call [`HealthStats::rates_since`].
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_ws::health::HealthTracker;
+//! use std::time::{Duration, Instant};
+//!
+//! let start = Instant::now();
+//! let mut tracker = HealthTracker::new();
+//! tracker.record_connected(start);
+//! let before = tracker.snapshot(start);
+//!
+//! for _ in 0..20 {
+//!     tracker.record_message("book", start);
+//! }
+//! tracker.record_heartbeat(start);
+//!
+//! let after = tracker.snapshot(start + Duration::from_secs(2));
+//! assert_eq!(after.rates_since(&before)["book"], 10.0);
+//! assert_eq!(after.heartbeat_age(), Some(Duration::from_secs(2)));
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Number of reconnects kept in [`HealthStats::reconnects`]
+pub const RECONNECT_HISTORY_LEN: usize = 20;
+
+/// One failed connection attempt that led to a reconnect
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReconnectRecord {
+    /// When the attempt failed
+    pub at: SystemTime,
+    /// Consecutive attempt number
+    pub attempt: u32,
+    /// Delay before the next attempt
+    pub delay: Duration,
+    /// Why the connection failed
+    pub reason: String,
+}
+
+/// Snapshot of connection health
+#[derive(Debug, Clone)]
+pub struct HealthStats {
+    /// When the snapshot was taken
+    pub captured_at: Instant,
+    /// When the current connection became ready (None while disconnected)
+    pub connected_since: Option<Instant>,
+    /// When the last heartbeat arrived
+    pub last_heartbeat: Option<Instant>,
+    /// Time since any message arrived
+    pub since_last_message: Duration,
+    /// Messages received per channel (`"book"`, `"ticker"`, `"heartbeat"`, ...)
+    pub messages_by_channel: BTreeMap<&'static str, u64>,
+    /// Book updates rejected for a checksum mismatch
+    pub checksum_mismatches: u64,
+    /// Invariant violations found by the periodic book audit
+    pub audit_violations: u64,
+    /// Events dropped by a bounded event channel
+    pub dropped_events: u64,
+    /// Reconnects since creation
+    pub total_reconnects: u64,
+    /// Most recent reconnects, oldest first
+    pub reconnects: Vec<ReconnectRecord>,
+}
+
+impl HealthStats {
+    /// Time since the last heartbeat, as of the snapshot
+    pub fn heartbeat_age(&self) -> Option<Duration> {
+        self.last_heartbeat
+            .map(|at| self.captured_at.saturating_duration_since(at))
+    }
+
+    /// Time since the current connection became ready
+    pub fn connected_for(&self) -> Option<Duration> {
+        self.connected_since
+            .map(|at| self.captured_at.saturating_duration_since(at))
+    }
+
+    /// Messages received across all channels
+    pub fn total_messages(&self) -> u64 {
+        self.messages_by_channel.values().sum()
+    }
+
+    /// Messages per second on each channel between an earlier snapshot and this one
+    pub fn rates_since(&self, earlier: &HealthStats) -> BTreeMap<&'static str, f64> {
+        let secs = self
+            .captured_at
+            .saturating_duration_since(earlier.captured_at)
+            .as_secs_f64();
+        self.messages_by_channel
+            .iter()
+            .map(|(channel, count)| {
+                let before = earlier.messages_by_channel.get(channel).copied().unwrap_or(0);
+                let delta = count.saturating_sub(before) as f64;
+                (*channel, if secs > 0.0 { delta / secs } else { 0.0 })
+            })
+            .collect()
+    }
+}
+
+/// Running health counters for one connection
+#[derive(Debug, Clone, Default)]
+pub struct HealthTracker {
+    connected_since: Option<Instant>,
+    last_heartbeat: Option<Instant>,
+    messages: BTreeMap<&'static str, u64>,
+    checksum_mismatches: u64,
+    audit_violations: u64,
+    total_reconnects: u64,
+    reconnects: VecDeque<ReconnectRecord>,
+}
+
+impl HealthTracker {
+    /// Create a tracker with zeroed counters
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a received message
+    pub fn record_message(&mut self, channel: &'static str, at: Instant) {
+        *self.messages.entry(channel).or_insert(0) += 1;
+        if channel == "heartbeat" {
+            self.last_heartbeat = Some(at);
+        }
+    }
+
+    /// Note a heartbeat without counting a message
+    pub fn record_heartbeat(&mut self, at: Instant) {
+        self.last_heartbeat = Some(at);
+    }
+
+    /// Count a checksum mismatch
+    pub fn record_checksum_mismatch(&mut self) {
+        self.checksum_mismatches += 1;
+    }
+
+    /// Count violations found by a book audit
+    pub fn record_audit_violations(&mut self, count: usize) {
+        self.audit_violations += count as u64;
+    }
+
+    /// Mark the connection as ready
+    pub fn record_connected(&mut self, at: Instant) {
+        self.connected_since = Some(at);
+    }
+
+    /// Record a failed attempt and the delay before the next one
+    pub fn record_reconnect(&mut self, attempt: u32, delay: Duration, reason: impl Into<String>) {
+        self.connected_since = None;
+        self.total_reconnects += 1;
+        if self.reconnects.len() == RECONNECT_HISTORY_LEN {
+            self.reconnects.pop_front();
+        }
+        self.reconnects.push_back(ReconnectRecord {
+            at: SystemTime::now(),
+            attempt,
+            delay,
+            reason: reason.into(),
+        });
+    }
+
+    /// Mark the connection as closed
+    pub fn record_disconnected(&mut self) {
+        self.connected_since = None;
+    }
+
+    /// Copy the counters
+    ///
+    /// `since_last_message` and `dropped_events` are left at zero; the
+    /// connection fills them in from its own state.
+    pub fn snapshot(&self, now: Instant) -> HealthStats {
+        HealthStats {
+            captured_at: now,
+            connected_since: self.connected_since,
+            last_heartbeat: self.last_heartbeat,
+            since_last_message: Duration::ZERO,
+            messages_by_channel: self.messages.clone(),
+            checksum_mismatches: self.checksum_mismatches,
+            audit_violations: self.audit_violations,
+            dropped_events: 0,
+            total_reconnects: self.total_reconnects,
+            reconnects: self.reconnects.iter().cloned().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_history_is_bounded() {
+        let mut tracker = HealthTracker::new();
+        tracker.record_connected(Instant::now());
+        for attempt in 1..=25 {
+            tracker.record_reconnect(attempt, Duration::from_secs(1), "timeout");
+        }
+        let stats = tracker.snapshot(Instant::now());
+        assert_eq!(stats.total_reconnects, 25);
+        assert_eq!(stats.reconnects.len(), RECONNECT_HISTORY_LEN);
+        assert_eq!(stats.reconnects[0].attempt, 6);
+        assert!(stats.connected_since.is_none());
+    }
+
+    #[test]
+    fn test_counts_and_rates() {
+        let start = Instant::now();
+        let mut tracker = HealthTracker::new();
+        tracker.record_message("ticker", start);
+        let before = tracker.snapshot(start);
+
+        tracker.record_message("heartbeat", start + Duration::from_millis(500));
+        tracker.record_message("ticker", start);
+        tracker.record_checksum_mismatch();
+        tracker.record_audit_violations(2);
+        let after = tracker.snapshot(start + Duration::from_secs(1));
+
+        assert_eq!(after.total_messages(), 3);
+        assert_eq!(after.checksum_mismatches, 1);
+        assert_eq!(after.audit_violations, 2);
+        assert_eq!(after.heartbeat_age(), Some(Duration::from_millis(500)));
+        let rates = after.rates_since(&before);
+        assert_eq!(rates["ticker"], 1.0);
+        assert_eq!(rates["heartbeat"], 1.0);
+        assert_eq!(before.rates_since(&before)["ticker"], 0.0);
+    }
+}
@@ -0,0 +1,223 @@
+//! Receive timestamps and exchange-to-client latency tracking
+//!
+//! Every market event carries a [`ReceivedAt`] stamp taken when its frame was
+//! parsed, and the exchange timestamp when Kraken provides one. The
+//! [`LatencyTracker`] compares the two.
+//!
+//! # Interpreting the numbers
+//!
+//! A one-way measurement can't separate network latency from clock offset:
+//! `received - exchange = latency + (local clock - exchange clock)`. The
+//! smallest delta seen over a window is the best estimate of the fixed part
+//! (base latency plus skew), and everything above it is queueing and jitter.
+//! With an NTP-synced host the minimum is close to the true base latency; a
+//! negative minimum means the local clock is behind the exchange.
+
+use std::collections::VecDeque;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Default number of samples kept by [`LatencyTracker`]
+const DEFAULT_WINDOW: usize = 1024;
+
+/// Local receive time of an event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReceivedAt {
+    /// Monotonic instant (for measuring local processing intervals)
+    pub instant: Instant,
+    /// Wall-clock time in microseconds since the Unix epoch
+    pub wall_us: i64,
+}
+
+impl ReceivedAt {
+    /// Stamp the current time
+    pub fn now() -> Self {
+        let wall_us = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_micros() as i64)
+            .unwrap_or(0);
+        Self {
+            instant: Instant::now(),
+            wall_us,
+        }
+    }
+
+    /// Microseconds between an exchange timestamp and this receive time
+    pub fn delta_us(&self, exchange_us: i64) -> i64 {
+        self.wall_us - exchange_us
+    }
+}
+
+/// Parse a Kraken RFC 3339 timestamp into microseconds since the Unix epoch
+pub fn parse_exchange_timestamp(timestamp: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .ok()
+        .map(|dt| dt.timestamp_micros())
+}
+
+/// Summary of recent exchange-to-client deltas (microseconds)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LatencyStats {
+    /// Samples in the window
+    pub samples: usize,
+    /// Total samples recorded since creation
+    pub total_samples: u64,
+    /// Smallest delta (base latency plus clock skew)
+    pub min_us: i64,
+    /// Largest delta
+    pub max_us: i64,
+    /// Mean delta
+    pub mean_us: i64,
+    /// Median delta
+    pub p50_us: i64,
+    /// 99th percentile delta
+    pub p99_us: i64,
+    /// Median delta above the minimum (queueing and jitter, skew-free)
+    pub excess_p50_us: i64,
+}
+
+impl LatencyStats {
+    /// Estimated local clock offset relative to the exchange
+    ///
+    /// This is the window minimum, which includes the base network latency,
+    /// so it is an upper bound on `local - exchange` skew.
+    pub fn estimated_skew_us(&self) -> i64 {
+        self.min_us
+    }
+}
+
+/// Rolling window of exchange-to-client deltas
+#[derive(Debug, Clone)]
+pub struct LatencyTracker {
+    window: usize,
+    deltas: VecDeque<i64>,
+    total: u64,
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_WINDOW)
+    }
+}
+
+impl LatencyTracker {
+    /// Create a tracker keeping the last `window` samples
+    pub fn new(window: usize) -> Self {
+        let window = window.max(1);
+        Self {
+            window,
+            deltas: VecDeque::with_capacity(window.min(DEFAULT_WINDOW)),
+            total: 0,
+        }
+    }
+
+    /// Record an event with a known exchange timestamp
+    pub fn record(&mut self, exchange_us: i64, received_at: ReceivedAt) {
+        self.record_delta(received_at.delta_us(exchange_us));
+    }
+
+    /// Record a precomputed delta in microseconds
+    pub fn record_delta(&mut self, delta_us: i64) {
+        if self.deltas.len() == self.window {
+            self.deltas.pop_front();
+        }
+        self.deltas.push_back(delta_us);
+        self.total += 1;
+    }
+
+    /// Number of samples in the window
+    pub fn len(&self) -> usize {
+        self.deltas.len()
+    }
+
+    /// Check if no samples have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.deltas.is_empty()
+    }
+
+    /// Drop all samples
+    pub fn reset(&mut self) {
+        self.deltas.clear();
+        self.total = 0;
+    }
+
+    /// Compute statistics over the current window
+    pub fn stats(&self) -> Option<LatencyStats> {
+        if self.deltas.is_empty() {
+            return None;
+        }
+        let mut sorted: Vec<i64> = self.deltas.iter().copied().collect();
+        sorted.sort_unstable();
+
+        let n = sorted.len();
+        let percentile = |p: usize| sorted[((n - 1) * p) / 100];
+        let min_us = sorted[0];
+        let p50_us = percentile(50);
+        let sum: i128 = sorted.iter().map(|&d| d as i128).sum();
+
+        Some(LatencyStats {
+            samples: n,
+            total_samples: self.total,
+            min_us,
+            max_us: sorted[n - 1],
+            mean_us: (sum / n as i128) as i64,
+            p50_us,
+            p99_us: percentile(99),
+            excess_p50_us: p50_us - min_us,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_exchange_timestamp() {
+        assert_eq!(
+            parse_exchange_timestamp("2023-10-06T17:35:55.440295Z"),
+            Some(1_696_613_755_440_295)
+        );
+        assert_eq!(parse_exchange_timestamp("not a time"), None);
+    }
+
+    #[test]
+    fn test_stats_and_skew() {
+        let mut tracker = LatencyTracker::new(100);
+        assert!(tracker.stats().is_none());
+
+        // Local clock 5ms ahead, base latency 1ms, varying queueing delay
+        for extra in 0..100 {
+            tracker.record_delta(6_000 + extra * 10);
+        }
+        let stats = tracker.stats().unwrap();
+        assert_eq!(stats.samples, 100);
+        assert_eq!(stats.estimated_skew_us(), 6_000);
+        assert_eq!(stats.max_us, 6_990);
+        assert_eq!(stats.p50_us, 6_490);
+        assert_eq!(stats.excess_p50_us, 490);
+    }
+
+    #[test]
+    fn test_window_evicts_oldest() {
+        let mut tracker = LatencyTracker::new(2);
+        tracker.record_delta(1);
+        tracker.record_delta(2);
+        tracker.record_delta(3);
+
+        let stats = tracker.stats().unwrap();
+        assert_eq!(stats.samples, 2);
+        assert_eq!(stats.total_samples, 3);
+        assert_eq!(stats.min_us, 2);
+    }
+
+    #[test]
+    fn test_received_at_delta() {
+        let received = ReceivedAt {
+            instant: Instant::now(),
+            wall_us: 1_000_500,
+        };
+        let mut tracker = LatencyTracker::default();
+        tracker.record(1_000_000, received);
+        assert_eq!(tracker.stats().unwrap().mean_us, 500);
+    }
+}
@@ -9,6 +9,7 @@
 //! - Subscription management with restoration after reconnect
 //! - Orderbook state maintenance with checksum validation
 //! - Event-driven architecture with async streams
+//! - Optional permessage-deflate compression (`deflate` feature)
 //!
 //! # Example
 //!
@@ -41,35 +42,86 @@
 //! }
 //! ```
 
+pub mod backfill;
+pub mod book_callbacks;
 pub mod circuit_breaker;
+pub mod clock;
+mod conflation;
 pub mod connection;
+#[cfg(feature = "deflate")]
+pub mod deflate;
 pub mod endpoint;
 pub mod events;
+pub mod execution;
+pub mod execution_report;
+pub mod health;
 pub mod hooks;
+pub mod inline;
+pub mod latency;
+pub mod margin;
 pub mod order_tracker;
+pub mod position;
+pub mod proxy;
+pub mod pruning;
+pub mod quoter;
 pub mod rate_limiter;
 pub mod reconnect;
+pub mod restoration;
+pub mod risk;
+pub mod sampler;
+mod standby;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod scenario;
 pub mod subscription;
+pub mod tap;
 pub mod trading;
 pub mod transport;
+pub mod watchdog;
 
 // Re-export main types
+pub use backfill::{BackfillData, BackfillRequest, Backfiller, TradeCursor, DEFAULT_BACKFILL_TIMEOUT};
+pub use book_callbacks::{BookCallbacks, CallbackStats, DEFAULT_CALLBACK_BUDGET};
+pub use clock::{ClockEstimate, ClockSkew, ClockSync};
 pub use circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitState, CircuitBreakerStats};
 pub use connection::{ConnectionConfig, ConnectionState, KrakenConnection, BackpressurePolicy, EventReceiver};
 pub use endpoint::Endpoint;
 pub use events::{
     ConnectionEvent, DisconnectReason, Event, MarketEvent, SubscriptionEvent,
     PrivateEvent, OrderStatus, TrackedOrder, OrderFill, ExecutionType, OrderChange, BalanceInfo,
-    L3Event,
+    L3Event, SequencedEvent, EventIdCheck, EventIdCursor,
+};
+pub use execution::{
+    AlgoAction, AlgoEvent, AlgoProgress, AlgoState, ChildOrder, ExecutionAlgo, Iceberg, PegToMid, Twap,
 };
-pub use order_tracker::{OrderTracker, LifecycleOrder, LifecycleState, Fill, TrackerConfig, TrackerStats};
+pub use execution_report::{ExecutionReport, FillTimePercentiles, ReportSection};
+pub use health::{HealthStats, HealthTracker, ReconnectRecord};
+pub use inline::{InlineDispatch, InlineHandler, InlineStats, DEFAULT_INLINE_BUDGET};
+pub use latency::{LatencyStats, LatencyTracker, ReceivedAt};
+pub use margin::{MarginAccount, MarginMetrics, MarginPosition, MarginStatus};
+pub use order_tracker::{OrderTracker, LifecycleOrder, LifecycleState, Amendment, AmendSource, Fill, TrackerConfig, TrackerState, TrackerStats};
+pub use position::{AssetPosition, PositionChange, PositionChangeReason, PositionTracker};
+pub use proxy::{ProxyConfig, ProxyError, ProxyKind};
+pub use pruning::PruningPolicy;
+pub use quoter::{Quote, QuoteContext, Quoter, QuoterConfig, ReferencePrice};
 pub use rate_limiter::{KrakenRateLimiter, SharedRateLimiter};
-pub use reconnect::ReconnectConfig;
-pub use subscription::Subscription;
-pub use trading::TradingClient;
-pub use transport::{Transport, TransportError, WsTransport};
+pub use reconnect::{Backoff, BackoffStrategy, DecorrelatedJitter, Fibonacci, ReconnectConfig};
+pub use restoration::{RestorationProgress, RestorationTracker, DEFAULT_RESTORE_TIMEOUT};
+pub use risk::{OrderIntent, OrderIntents, RiskLimits, RiskManager, RiskViolation};
+pub use sampler::{BookSample, BookSampler};
+pub use subscription::{
+    BatchResolution, RequestRecord, Subscription, TokenRefresher, DEFAULT_MAX_SYMBOLS_PER_REQUEST, TOKEN_LIFETIME,
+};
+pub use tap::{Direction, FrameSink, MessageTap, RawFrame, DEFAULT_TAP_CAPACITY};
+pub use trading::{AlgoRequest, OrderKind, OrderKinds, RetryPolicy, DEFAULT_RATE_LIMIT_WAIT, StatusPolicy, TradingActions, TradingClient, TradingError, TradingResponse, TradingSession};
+pub use transport::{
+    connect_websocket, CloseFrame, NetworkConfig, Transport, TransportError, TransportFactory, TransportStats, WsStream,
+    WsTransport,
+};
+pub use watchdog::{StaleFeed, StaleWatchdog};
 pub use hooks::{Hooks, ConnectInfo, DisconnectInfo, SubscriptionInfo, ChecksumInfo};
 
 // Re-export MockTransport when test-utils feature is enabled
 #[cfg(any(test, feature = "test-utils"))]
 pub use transport::MockTransport;
+#[cfg(any(test, feature = "test-utils"))]
+pub use scenario::{Scenario, ScenarioStep, ScenarioTransport};
@@ -0,0 +1,363 @@
+//! Spot margin risk metrics
+//!
+//! [`MarginAccount`] combines collateral (from balances) with open margin
+//! positions and live mark prices to compute the account's margin level and
+//! an estimated liquidation price per position.
+//!
+//! Kraken defines the margin level as `equity / used margin * 100`, where
+//! equity is collateral plus unrealized PnL. A margin call is issued at 80%
+//! and positions are liquidated at 40%. The liquidation price is an
+//! estimate: it assumes every other position stays at its current mark.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_ws::margin::{MarginAccount, MarginPosition, MarginStatus};
+//! use kraken_types::Side;
+//! use rust_decimal_macros::dec;
+//!
+//! let mut account = MarginAccount::new();
+//! account.set_collateral(dec!(10000));
+//! account.set_position(MarginPosition::new("BTC/USD", Side::Buy, dec!(1), dec!(50000), dec!(10000)));
+//! account.update_mark("BTC/USD", dec!(49000));
+//!
+//! let metrics = account.metrics();
+//! assert_eq!(metrics.margin_level, Some(dec!(90)));
+//! assert_eq!(metrics.status, MarginStatus::Healthy);
+//! // Equity hits 40% of used margin when BTC drops to 44,000
+//! assert_eq!(account.liquidation_price("BTC/USD"), Some(dec!(44000)));
+//! ```
+
+use crate::events::{Event, MarketEvent};
+use kraken_types::{BalanceData, Decimal, Side};
+use std::collections::HashMap;
+
+/// Margin level (percent) at which Kraken issues a margin call
+pub const DEFAULT_MARGIN_CALL_LEVEL: Decimal = Decimal::from_parts(80, 0, 0, false, 0);
+
+/// Margin level (percent) at which Kraken liquidates positions
+pub const DEFAULT_LIQUIDATION_LEVEL: Decimal = Decimal::from_parts(40, 0, 0, false, 0);
+
+/// An open spot margin position
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarginPosition {
+    /// Trading pair symbol (e.g., "BTC/USD")
+    pub symbol: String,
+    /// Position direction
+    pub side: Side,
+    /// Position size in the base asset
+    pub qty: Decimal,
+    /// Average entry price
+    pub entry_price: Decimal,
+    /// Margin used by the position, in the quote asset
+    pub margin_used: Decimal,
+}
+
+impl MarginPosition {
+    /// Create a new margin position
+    pub fn new(
+        symbol: impl Into<String>,
+        side: Side,
+        qty: Decimal,
+        entry_price: Decimal,
+        margin_used: Decimal,
+    ) -> Self {
+        Self {
+            symbol: symbol.into(),
+            side,
+            qty,
+            entry_price,
+            margin_used,
+        }
+    }
+
+    /// Position size, negative for shorts
+    pub fn signed_qty(&self) -> Decimal {
+        match self.side {
+            Side::Buy => self.qty,
+            Side::Sell => -self.qty,
+        }
+    }
+
+    /// Unrealized PnL at a mark price, in the quote asset
+    pub fn unrealized_pnl(&self, mark: Decimal) -> Decimal {
+        (mark - self.entry_price) * self.signed_qty()
+    }
+}
+
+/// How close the account is to Kraken's margin thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarginStatus {
+    /// Above the margin call level (or no margin in use)
+    Healthy,
+    /// At or below the margin call level
+    MarginCall,
+    /// At or below the liquidation level
+    Liquidation,
+}
+
+/// Point-in-time margin metrics for an account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarginMetrics {
+    /// Collateral plus unrealized PnL
+    pub equity: Decimal,
+    /// Total margin used by open positions
+    pub used_margin: Decimal,
+    /// Equity not committed as margin
+    pub free_margin: Decimal,
+    /// Unrealized PnL across all positions
+    pub unrealized_pnl: Decimal,
+    /// Margin level in percent (`None` without open positions)
+    pub margin_level: Option<Decimal>,
+    /// Status relative to the configured thresholds
+    pub status: MarginStatus,
+}
+
+/// Collateral, open margin positions and mark prices for one account
+#[derive(Debug, Clone)]
+pub struct MarginAccount {
+    collateral: Decimal,
+    positions: HashMap<String, MarginPosition>,
+    marks: HashMap<String, Decimal>,
+    margin_call_level: Decimal,
+    liquidation_level: Decimal,
+}
+
+impl Default for MarginAccount {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MarginAccount {
+    /// Create an empty account using Kraken's default thresholds
+    pub fn new() -> Self {
+        Self {
+            collateral: Decimal::ZERO,
+            positions: HashMap::new(),
+            marks: HashMap::new(),
+            margin_call_level: DEFAULT_MARGIN_CALL_LEVEL,
+            liquidation_level: DEFAULT_LIQUIDATION_LEVEL,
+        }
+    }
+
+    /// Set the margin call level (percent)
+    pub fn with_margin_call_level(mut self, level: Decimal) -> Self {
+        self.margin_call_level = level;
+        self
+    }
+
+    /// Set the liquidation level (percent)
+    pub fn with_liquidation_level(mut self, level: Decimal) -> Self {
+        self.liquidation_level = level;
+        self
+    }
+
+    /// Set the collateral value in the quote asset
+    pub fn set_collateral(&mut self, collateral: Decimal) {
+        self.collateral = collateral;
+    }
+
+    /// Collateral value in the quote asset
+    pub fn collateral(&self) -> Decimal {
+        self.collateral
+    }
+
+    /// Value balances in `quote` and use the total as collateral
+    ///
+    /// Each asset is valued at the mark of `ASSET/quote`; assets without a
+    /// mark are left out. Returns the new collateral value.
+    pub fn collateral_from_balances(&mut self, balances: &[BalanceData], quote: &str) -> Decimal {
+        let mut total = Decimal::ZERO;
+        for balance in balances {
+            let qty = balance.balance + balance.hold_trade.unwrap_or(Decimal::ZERO);
+            if balance.asset == quote {
+                total += qty;
+            } else if let Some(mark) = self.mark(&format!("{}/{}", balance.asset, quote)) {
+                total += qty * mark;
+            }
+        }
+        self.collateral = total;
+        total
+    }
+
+    /// Add or replace the position for a symbol
+    pub fn set_position(&mut self, position: MarginPosition) {
+        self.positions.insert(position.symbol.clone(), position);
+    }
+
+    /// Remove the position for a symbol
+    pub fn remove_position(&mut self, symbol: &str) -> Option<MarginPosition> {
+        self.positions.remove(symbol)
+    }
+
+    /// Position for a symbol
+    pub fn position(&self, symbol: &str) -> Option<&MarginPosition> {
+        self.positions.get(symbol)
+    }
+
+    /// All open positions
+    pub fn positions(&self) -> impl Iterator<Item = &MarginPosition> {
+        self.positions.values()
+    }
+
+    /// Record the latest mark price for a symbol
+    pub fn update_mark(&mut self, symbol: &str, price: Decimal) {
+        self.marks.insert(symbol.to_string(), price);
+    }
+
+    /// Latest mark price for a symbol
+    pub fn mark(&self, symbol: &str) -> Option<Decimal> {
+        self.marks.get(symbol).copied()
+    }
+
+    /// Update mark prices from orderbook and ticker events
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::Market(MarketEvent::OrderbookSnapshot { symbol, snapshot, .. })
+            | Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot, .. }) => {
+                if let Some(mid) = snapshot.mid_price() {
+                    self.update_mark(symbol, mid);
+                }
+            }
+            Event::Market(MarketEvent::Ticker { symbol, ticker, .. })
+                if ticker.bid > Decimal::ZERO && ticker.ask > Decimal::ZERO =>
+            {
+                self.update_mark(symbol, (ticker.bid + ticker.ask) / Decimal::TWO);
+            }
+            _ => {}
+        }
+    }
+
+    /// Unrealized PnL of a position at its current mark
+    ///
+    /// Positions without a mark contribute zero.
+    fn position_pnl(&self, position: &MarginPosition) -> Decimal {
+        self.mark(&position.symbol)
+            .map(|mark| position.unrealized_pnl(mark))
+            .unwrap_or(Decimal::ZERO)
+    }
+
+    fn used_margin(&self) -> Decimal {
+        self.positions.values().map(|p| p.margin_used).sum()
+    }
+
+    /// Current margin metrics
+    ///
+    /// Positions without a mark price are valued at their entry price.
+    pub fn metrics(&self) -> MarginMetrics {
+        let unrealized_pnl: Decimal = self.positions.values().map(|p| self.position_pnl(p)).sum();
+        let used_margin = self.used_margin();
+        let equity = self.collateral + unrealized_pnl;
+        let margin_level = (!used_margin.is_zero())
+            .then(|| equity / used_margin * Decimal::ONE_HUNDRED);
+        let status = match margin_level {
+            Some(level) if level <= self.liquidation_level => MarginStatus::Liquidation,
+            Some(level) if level <= self.margin_call_level => MarginStatus::MarginCall,
+            _ => MarginStatus::Healthy,
+        };
+        MarginMetrics {
+            equity,
+            used_margin,
+            free_margin: equity - used_margin,
+            unrealized_pnl,
+            margin_level,
+            status,
+        }
+    }
+
+    /// Estimated price at which the account reaches the liquidation level
+    ///
+    /// Solves for the mark of `symbol` that brings the margin level down to
+    /// the liquidation threshold, holding every other position at its
+    /// current mark. Returns `None` without a position, or if no positive
+    /// price would trigger liquidation.
+    pub fn liquidation_price(&self, symbol: &str) -> Option<Decimal> {
+        let position = self.positions.get(symbol)?;
+        let signed_qty = position.signed_qty();
+        if signed_qty.is_zero() {
+            return None;
+        }
+        let other_pnl: Decimal = self
+            .positions
+            .values()
+            .filter(|p| p.symbol != symbol)
+            .map(|p| self.position_pnl(p))
+            .sum();
+        let liquidation_equity = self.used_margin() * self.liquidation_level / Decimal::ONE_HUNDRED;
+        let allowed_pnl = liquidation_equity - self.collateral - other_pnl;
+        let price = position.entry_price + allowed_pnl / signed_qty;
+        (price > Decimal::ZERO).then_some(price)
+    }
+
+    /// Distance from the current mark to the liquidation price, in percent
+    /// of the mark
+    pub fn distance_to_liquidation(&self, symbol: &str) -> Option<Decimal> {
+        let mark = self.mark(symbol)?;
+        let liquidation = self.liquidation_price(symbol)?;
+        if mark.is_zero() {
+            return None;
+        }
+        Some((mark - liquidation).abs() / mark * Decimal::ONE_HUNDRED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_metrics_and_status() {
+        let mut account = MarginAccount::new();
+        account.set_collateral(dec!(5000));
+        account.set_position(MarginPosition::new("ETH/USD", Side::Sell, dec!(10), dec!(3000), dec!(6000)));
+
+        account.update_mark("ETH/USD", dec!(2900));
+        let metrics = account.metrics();
+        assert_eq!(metrics.unrealized_pnl, dec!(1000));
+        assert_eq!(metrics.equity, dec!(6000));
+        assert_eq!(metrics.free_margin, dec!(0));
+        assert_eq!(metrics.margin_level, Some(dec!(100)));
+        assert_eq!(metrics.status, MarginStatus::Healthy);
+
+        account.update_mark("ETH/USD", dec!(3100));
+        assert_eq!(account.metrics().status, MarginStatus::MarginCall);
+
+        account.update_mark("ETH/USD", dec!(3300));
+        assert_eq!(account.metrics().status, MarginStatus::Liquidation);
+
+        account.remove_position("ETH/USD");
+        let metrics = account.metrics();
+        assert_eq!(metrics.margin_level, None);
+        assert_eq!(metrics.status, MarginStatus::Healthy);
+    }
+
+    #[test]
+    fn test_liquidation_price_for_short() {
+        let mut account = MarginAccount::new();
+        account.set_collateral(dec!(5000));
+        account.set_position(MarginPosition::new("ETH/USD", Side::Sell, dec!(10), dec!(3000), dec!(6000)));
+        account.update_mark("ETH/USD", dec!(3000));
+
+        // Liquidation equity is 2400, so the short can lose 2600
+        assert_eq!(account.liquidation_price("ETH/USD"), Some(dec!(3260)));
+        let distance = account.distance_to_liquidation("ETH/USD").unwrap();
+        assert_eq!(distance.round_dp(2), dec!(8.67));
+        assert_eq!(account.liquidation_price("BTC/USD"), None);
+    }
+
+    #[test]
+    fn test_collateral_from_balances() {
+        let mut account = MarginAccount::new();
+        account.update_mark("BTC/USD", dec!(50000));
+        let balances = vec![
+            BalanceData { asset: "USD".into(), balance: dec!(1000), hold_trade: None },
+            BalanceData { asset: "BTC".into(), balance: dec!(0.1), hold_trade: Some(dec!(0.1)) },
+            BalanceData { asset: "DOGE".into(), balance: dec!(500), hold_trade: None },
+        ];
+
+        assert_eq!(account.collateral_from_balances(&balances, "USD"), dec!(11000));
+        assert_eq!(account.collateral(), dec!(11000));
+    }
+}
@@ -10,6 +10,8 @@
 //! - **State Machine**: Pending → New → PartialFill → Filled/Canceled
 //! - **Fill Aggregation**: Calculate average fill price across partial fills
 //! - **Slippage Tracking**: Compare expected vs actual execution price
+//! - **Amendment History**: Price and quantity changes from `amend_order`
+//! - **Account Attribution**: Orders carry the [`AccountId`] they were placed for
 //! - **Timing Metrics**: Time to first fill, time to complete
 //! - **Query API**: Filter orders by status, symbol, or custom criteria
 //!
@@ -59,13 +61,22 @@
 //! │   Filled    │               │  Canceled   │ │  Rejected   │
 //! └─────────────┘               └─────────────┘ └─────────────┘
 //! ```
-
-use kraken_types::{Decimal, ExecutionData, Side};
+//!
+//! # Tracing
+//!
+//! Every tracked order opens a root `order` span (fields `symbol`, `side`,
+//! `request_id`, `order_id`, `state`) that closes when the order reaches a
+//! terminal state. State transitions and fills are recorded as events inside
+//! it, so an exported trace shows submit → ack → fill latency directly.
+
+use crate::events::ExecutionType;
+use crate::trading::TradingResponse;
+use kraken_types::{AccountId, AmendOrderRequest, Decimal, ExecutionData, Side};
 use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::{Duration, Instant};
-use tracing::{debug, instrument, warn};
+use tracing::{field, info, info_span, instrument, warn, Span};
 
 /// Order lifecycle state
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -157,6 +168,42 @@ impl Fill {
     }
 }
 
+/// One price or quantity change made by amending an order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Amendment {
+    /// When the amendment took effect (ISO 8601)
+    pub timestamp: String,
+    /// Limit price before the amendment
+    pub old_price: Option<Decimal>,
+    /// Limit price after the amendment
+    pub new_price: Option<Decimal>,
+    /// Order quantity before the amendment
+    pub old_qty: Decimal,
+    /// Order quantity after the amendment
+    pub new_qty: Decimal,
+    /// `amend_id` from the `amend_order` response, once it is seen
+    #[serde(default)]
+    pub amend_id: Option<String>,
+    /// `exec_id` of the `amended` execution update, once it is seen
+    #[serde(default)]
+    pub exec_id: Option<String>,
+}
+
+impl Amendment {
+    fn same_result(&self, price: Option<Decimal>, qty: Decimal) -> bool {
+        self.new_price == price && self.new_qty == qty
+    }
+}
+
+/// Where an amendment was reported, with the ID that identifies it there
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AmendSource<'a> {
+    /// Successful `amend_order` response, keyed by its `amend_id`
+    Response(Option<&'a str>),
+    /// `amended` execution update, keyed by its `exec_id`
+    Execution(Option<&'a str>),
+}
+
 /// Tracked order with complete lifecycle data
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LifecycleOrder {
@@ -166,16 +213,22 @@ pub struct LifecycleOrder {
     pub order_id: Option<String>,
     /// User reference (cl_ord_id)
     pub user_ref: Option<String>,
+    /// Account the order was placed for
+    #[serde(default)]
+    pub account: AccountId,
     /// Trading symbol
     pub symbol: String,
     /// Order side
     pub side: Side,
     /// Order type (limit, market, etc.)
     pub order_type: String,
-    /// Original quantity
+    /// Order quantity, as last amended
     pub original_qty: Decimal,
-    /// Limit price (if limit order)
+    /// Limit price (if limit order), as last amended
     pub limit_price: Option<Decimal>,
+    /// Price and quantity changes, oldest first
+    #[serde(default)]
+    pub amendments: Vec<Amendment>,
     /// Current lifecycle state
     pub lifecycle_state: LifecycleState,
     /// Cumulative filled quantity
@@ -203,6 +256,9 @@ pub struct LifecycleOrder {
     /// Internal tracking: completion time
     #[serde(skip)]
     completion_time: Option<Instant>,
+    /// Lifecycle span, closed once the order is terminal
+    #[serde(skip)]
+    span: Option<Span>,
 }
 
 impl LifecycleOrder {
@@ -215,15 +271,28 @@ impl LifecycleOrder {
         limit_price: Option<Decimal>,
     ) -> Self {
         let now = chrono::Utc::now().to_rfc3339();
+        let span = info_span!(
+            parent: None,
+            "order",
+            otel.kind = "client",
+            otel.status_code = field::Empty,
+            symbol = %symbol,
+            side = ?side,
+            request_id = request_id.as_deref(),
+            order_id = field::Empty,
+            state = %LifecycleState::Pending,
+        );
         Self {
             request_id,
             order_id: None,
             user_ref: None,
+            account: AccountId::default(),
             symbol,
             side,
             order_type: if limit_price.is_some() { "limit" } else { "market" }.to_string(),
             original_qty: qty,
             limit_price,
+            amendments: Vec::new(),
             lifecycle_state: LifecycleState::Pending,
             filled_qty: Decimal::ZERO,
             fills: Vec::new(),
@@ -236,6 +305,7 @@ impl LifecycleOrder {
             submission_time: Some(Instant::now()),
             first_fill_time: None,
             completion_time: None,
+            span: Some(span),
         }
     }
 
@@ -264,6 +334,8 @@ impl LifecycleOrder {
 
     /// Calculate slippage vs limit price (in basis points)
     ///
+    /// Measured against the latest amended limit price.
+    ///
     /// Positive = worse than expected (paid more for buy, received less for sell)
     /// Negative = better than expected
     pub fn slippage_bps(&self) -> Option<Decimal> {
@@ -320,6 +392,100 @@ impl LifecycleOrder {
         self.submission_time.map(|start| end.duration_since(start))
     }
 
+    /// Whether the order's price or quantity was ever amended
+    pub fn was_amended(&self) -> bool {
+        !self.amendments.is_empty()
+    }
+
+    /// Number of amendments
+    pub fn amendment_count(&self) -> usize {
+        self.amendments.len()
+    }
+
+    /// Limit price the order was submitted with, before any amendment
+    pub fn original_limit_price(&self) -> Option<Decimal> {
+        match self.amendments.first() {
+            Some(first) => first.old_price,
+            None => self.limit_price,
+        }
+    }
+
+    /// Record a price and/or quantity change
+    ///
+    /// `None` keeps the current value. Each amendment is reported twice,
+    /// by the `amend_order` response and by the execution feed, under
+    /// different IDs. A report whose ID was already recorded is a replay;
+    /// otherwise it is paired with the oldest recorded amendment that only
+    /// the other source has reported and that has the same result. Returns
+    /// false (recording nothing) for replays and paired reports, so every
+    /// amendment is counted once, including repeats of an identical change.
+    pub fn apply_amendment(
+        &mut self,
+        new_price: Option<Decimal>,
+        new_qty: Option<Decimal>,
+        source: AmendSource<'_>,
+        timestamp: impl Into<String>,
+    ) -> bool {
+        let price = new_price.or(self.limit_price);
+        let qty = new_qty.unwrap_or(self.original_qty);
+        match source {
+            AmendSource::Response(id) => {
+                if id.is_some() && self.amendments.iter().any(|a| a.amend_id.as_deref() == id) {
+                    return false;
+                }
+                let unpaired = self
+                    .amendments
+                    .iter_mut()
+                    .find(|a| a.amend_id.is_none() && a.exec_id.is_some() && a.same_result(price, qty));
+                if let Some(amendment) = unpaired {
+                    amendment.amend_id = id.map(str::to_string);
+                    return false;
+                }
+            }
+            AmendSource::Execution(id) => {
+                if id.is_some() && self.amendments.iter().any(|a| a.exec_id.as_deref() == id) {
+                    return false;
+                }
+                let unpaired = self
+                    .amendments
+                    .iter_mut()
+                    .find(|a| a.exec_id.is_none() && a.amend_id.is_some() && a.same_result(price, qty));
+                if let Some(amendment) = unpaired {
+                    amendment.exec_id = id.map(str::to_string);
+                    return false;
+                }
+            }
+        }
+        let (amend_id, exec_id) = match source {
+            AmendSource::Response(id) => (id.map(str::to_string), None),
+            AmendSource::Execution(id) => (None, id.map(str::to_string)),
+        };
+        let amendment = Amendment {
+            timestamp: timestamp.into(),
+            old_price: self.limit_price,
+            new_price: price,
+            old_qty: self.original_qty,
+            new_qty: qty,
+            amend_id,
+            exec_id,
+        };
+        if let Some(span) = &self.span {
+            let _entered = span.enter();
+            info!(
+                old_price = ?amendment.old_price,
+                new_price = ?amendment.new_price,
+                old_qty = %amendment.old_qty,
+                new_qty = %amendment.new_qty,
+                "Order amended"
+            );
+        }
+        self.updated_at = amendment.timestamp.clone();
+        self.limit_price = price;
+        self.original_qty = qty;
+        self.amendments.push(amendment);
+        true
+    }
+
     /// Number of fills
     pub fn fill_count(&self) -> usize {
         self.fills.len()
@@ -333,13 +499,27 @@ impl LifecycleOrder {
     /// Update order state from execution data
     #[instrument(skip(self, exec))]
     pub fn apply_execution(&mut self, exec: &ExecutionData) {
+        let span = self.span.clone().unwrap_or_else(Span::none);
+        let _entered = span.enter();
         let now = chrono::Utc::now().to_rfc3339();
         self.updated_at = now.clone();
 
         // Update order ID if we get it
         if self.order_id.is_none() && !exec.order_id.is_empty() {
             self.order_id = Some(exec.order_id.clone());
-            debug!(order_id = %exec.order_id, "Order ID assigned");
+            info!(order_id = %exec.order_id, "Order ID assigned");
+        }
+        if let Some(order_id) = &self.order_id {
+            span.record("order_id", order_id.as_str());
+        }
+
+        if ExecutionType::parse(&exec.exec_type) == ExecutionType::Amended {
+            self.apply_amendment(
+                exec.limit_price,
+                exec.order_qty,
+                AmendSource::Execution(exec.exec_id.as_deref()),
+                exec.timestamp.clone(),
+            );
         }
 
         // Update cumulative filled quantity
@@ -373,8 +553,14 @@ impl LifecycleOrder {
             // Track first fill time
             if is_first_fill {
                 self.first_fill_time = Some(Instant::now());
-                debug!("First fill recorded");
             }
+            info!(
+                price = %last_price,
+                qty = %last_qty,
+                latency = ?self.submission_time.map(|t| t.elapsed()),
+                first = is_first_fill,
+                "Fill recorded"
+            );
         }
 
         // Update lifecycle state
@@ -383,16 +569,21 @@ impl LifecycleOrder {
 
             // Track state transition
             if self.lifecycle_state != new_state {
-                debug!(
+                info!(
                     old_state = %self.lifecycle_state,
                     new_state = %new_state,
                     "Lifecycle state transition"
                 );
                 self.lifecycle_state = new_state;
+                span.record("state", field::display(new_state));
 
-                // Track completion time
+                // Track completion time and close the lifecycle span
                 if new_state.is_terminal() {
                     self.completion_time = Some(Instant::now());
+                    if new_state == LifecycleState::Rejected {
+                        span.record("otel.status_code", "ERROR");
+                    }
+                    self.span = None;
                 }
             }
         }
@@ -441,6 +632,8 @@ pub struct OrderTracker {
     config: TrackerConfig,
     /// Order count for statistics
     stats: TrackerStats,
+    /// Account stamped on every order this tracker creates
+    account: AccountId,
 }
 
 /// Tracker statistics
@@ -480,9 +673,24 @@ impl OrderTracker {
             pending_orders: HashMap::new(),
             config,
             stats: TrackerStats::default(),
+            account: AccountId::default(),
         }
     }
 
+    /// Attribute every order this tracker sees to `account`
+    ///
+    /// Use one tracker per account's execution stream; orders keep the
+    /// label when exported or merged elsewhere.
+    pub fn with_account(mut self, account: impl Into<AccountId>) -> Self {
+        self.account = account.into();
+        self
+    }
+
+    /// Account this tracker attributes orders to
+    pub fn account(&self) -> &AccountId {
+        &self.account
+    }
+
     /// Track a new order submission
     #[instrument(skip(self))]
     pub fn track_submission(
@@ -493,13 +701,14 @@ impl OrderTracker {
         qty: Decimal,
         limit_price: Option<Decimal>,
     ) -> &LifecycleOrder {
-        let order = LifecycleOrder::new_pending(
+        let mut order = LifecycleOrder::new_pending(
             Some(request_id.to_string()),
             symbol.to_string(),
             side,
             qty,
             limit_price,
         );
+        order.account = self.account.clone();
 
         self.stats.total_tracked += 1;
         self.stats.active_orders += 1;
@@ -579,6 +788,7 @@ impl OrderTracker {
             exec.limit_price,
         );
         order.order_id = Some(order_id.clone());
+        order.account = self.account.clone();
         order.apply_execution(exec);
 
         self.stats.total_tracked += 1;
@@ -600,6 +810,31 @@ impl OrderTracker {
         self.orders_by_id.get(order_id)
     }
 
+    /// Record the amendment from a successful `amend_order` response
+    ///
+    /// Returns the amended order, or None if the request failed or the
+    /// order isn't tracked. The response's `amend_id` and the `exec_id` of
+    /// the matching `amended` execution update identify the change, so it
+    /// is counted once whichever arrives first.
+    pub fn handle_amend_response(
+        &mut self,
+        request: &AmendOrderRequest,
+        response: &TradingResponse,
+    ) -> Option<&LifecycleOrder> {
+        if !response.success {
+            return None;
+        }
+        let params = &request.params;
+        let order = self.orders_by_id.get_mut(&params.order_id)?;
+        order.apply_amendment(
+            params.limit_price,
+            params.order_qty,
+            AmendSource::Response(response.amend_id()),
+            chrono::Utc::now().to_rfc3339(),
+        );
+        Some(order)
+    }
+
     // =========================================================================
     // Query API
     // =========================================================================
@@ -622,6 +857,16 @@ impl OrderTracker {
             .and_then(|id| self.orders_by_id.get(id))
     }
 
+    /// All tracked orders, including pending ones not yet acknowledged
+    pub fn orders(&self) -> impl Iterator<Item = &LifecycleOrder> {
+        self.orders_by_id.values().chain(self.pending_orders.values())
+    }
+
+    /// Orders that were amended at least once
+    pub fn amended_orders(&self) -> Vec<&LifecycleOrder> {
+        self.orders_by_id.values().filter(|o| o.was_amended()).collect()
+    }
+
     /// Get all orders by lifecycle state
     pub fn by_state(&self, state: LifecycleState) -> Vec<&LifecycleOrder> {
         self.orders_by_id
@@ -651,6 +896,14 @@ impl OrderTracker {
             .collect()
     }
 
+    /// Get all orders placed for an account
+    pub fn by_account(&self, account: &AccountId) -> Vec<&LifecycleOrder> {
+        self.orders_by_id
+            .values()
+            .filter(|o| &o.account == account)
+            .collect()
+    }
+
     /// Get all orders for a side
     pub fn by_side(&self, side: Side) -> Vec<&LifecycleOrder> {
         self.orders_by_id
@@ -737,6 +990,54 @@ impl OrderTracker {
         self.pending_orders.clear();
         self.stats = TrackerStats::default();
     }
+
+    /// Export orders, correlations, and statistics for persistence
+    ///
+    /// Timing metrics and open lifecycle spans are process-local and are
+    /// not part of the state.
+    pub fn export_state(&self) -> TrackerState {
+        TrackerState {
+            orders: self.orders_by_id.values().cloned().collect(),
+            pending: self.pending_orders.values().cloned().collect(),
+            request_ids: self.orders_by_request_id.clone(),
+            stats: self.stats.clone(),
+            account: self.account.clone(),
+        }
+    }
+
+    /// Rebuild a tracker from exported state
+    pub fn from_state(state: TrackerState, config: TrackerConfig) -> Self {
+        let mut tracker = Self::with_config(config).with_account(state.account);
+        tracker.orders_by_id = state
+            .orders
+            .into_iter()
+            .filter_map(|order| Some((order.order_id.clone()?, order)))
+            .collect();
+        tracker.pending_orders = state
+            .pending
+            .into_iter()
+            .filter_map(|order| Some((order.request_id.clone()?, order)))
+            .collect();
+        tracker.orders_by_request_id = state.request_ids;
+        tracker.stats = state.stats;
+        tracker
+    }
+}
+
+/// Serializable contents of an [`OrderTracker`]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TrackerState {
+    /// Orders with a Kraken order ID
+    pub orders: Vec<LifecycleOrder>,
+    /// Submitted orders not yet acknowledged
+    pub pending: Vec<LifecycleOrder>,
+    /// Request ID to order ID correlations
+    pub request_ids: HashMap<String, String>,
+    /// Statistics at export time
+    pub stats: TrackerStats,
+    /// Account the tracker attributes orders to
+    #[serde(default)]
+    pub account: AccountId,
 }
 
 /// Aggregate fill statistics
@@ -806,6 +1107,224 @@ mod tests {
         assert_eq!(order.lifecycle_state, LifecycleState::Pending);
     }
 
+    #[test]
+    fn test_order_span_closes_when_terminal() {
+        let mut tracker = OrderTracker::new();
+        tracker.track_submission("req1", "BTC/USD", Side::Buy, dec!(1), Some(dec!(100)));
+        assert!(tracker.get_by_request_id("req1").unwrap().span.is_some());
+
+        let exec = |status: &str| -> ExecutionData {
+            serde_json::from_value(serde_json::json!({
+                "exec_type": status,
+                "order_id": "O1",
+                "symbol": "BTC/USD",
+                "side": "buy",
+                "order_type": "limit",
+                "order_status": status,
+                "timestamp": "2024-01-01T00:00:00Z",
+            }))
+            .unwrap()
+        };
+        let order = tracker.handle_execution(&exec("new")).unwrap();
+        assert!(order.span.is_some());
+        let order = tracker.handle_execution(&exec("filled")).unwrap();
+        assert_eq!(order.lifecycle_state, LifecycleState::Filled);
+        assert!(order.span.is_none());
+    }
+
+    fn exec(value: serde_json::Value) -> ExecutionData {
+        let mut base = serde_json::json!({
+            "order_id": "O1",
+            "symbol": "BTC/USD",
+            "side": "buy",
+            "order_type": "limit",
+            "timestamp": "2024-01-01T00:00:00Z",
+        });
+        base.as_object_mut().unwrap().extend(value.as_object().unwrap().clone());
+        serde_json::from_value(base).unwrap()
+    }
+
+    #[test]
+    fn test_amended_execution_updates_history_and_slippage() {
+        let mut tracker = OrderTracker::new();
+        tracker.track_submission("req1", "BTC/USD", Side::Buy, dec!(2), Some(dec!(100)));
+        tracker.handle_execution(&exec(serde_json::json!({"exec_type": "new", "order_status": "new"})));
+
+        let order = tracker
+            .handle_execution(&exec(serde_json::json!({
+                "exec_type": "amended",
+                "order_status": "new",
+                "limit_price": "102",
+                "order_qty": "2",
+                "timestamp": "2024-01-01T00:00:05Z",
+            })))
+            .unwrap();
+        assert!(order.was_amended());
+        assert_eq!(order.amendment_count(), 1);
+        assert_eq!(order.original_limit_price(), Some(dec!(100)));
+        assert_eq!(order.amendments[0].new_price, Some(dec!(102)));
+
+        let order = tracker
+            .handle_execution(&exec(serde_json::json!({
+                "exec_type": "trade",
+                "order_status": "filled",
+                "last_price": "102",
+                "last_qty": "2",
+                "cum_qty": "2",
+            })))
+            .unwrap();
+        // Filled at the amended price, so no slippage
+        assert_eq!(order.slippage_bps(), Some(dec!(0)));
+        assert_eq!(tracker.amended_orders().len(), 1);
+    }
+
+    #[test]
+    fn test_amend_response_and_execution_count_once() {
+        use kraken_types::AmendOrderParams;
+
+        let mut tracker = OrderTracker::new();
+        tracker.track_submission("req1", "BTC/USD", Side::Buy, dec!(2), Some(dec!(100)));
+        tracker.handle_execution(&exec(serde_json::json!({"exec_type": "new", "order_status": "new"})));
+
+        let request = AmendOrderRequest::new(AmendOrderParams {
+            order_id: "O1".to_string(),
+            limit_price: None,
+            trigger_price: None,
+            order_qty: Some(dec!(1)),
+            post_only: None,
+            token: "tok".to_string(),
+        });
+        let response: TradingResponse = serde_json::from_value(serde_json::json!({
+            "method": "amend_order",
+            "success": true,
+            "result": {"order_id": "O1", "amend_id": "A1"},
+        }))
+        .unwrap();
+        let order = tracker.handle_amend_response(&request, &response).unwrap();
+        assert_eq!(order.remaining_qty(), dec!(1));
+
+        let order = tracker
+            .handle_execution(&exec(serde_json::json!({
+                "exec_type": "amended",
+                "order_status": "new",
+                "limit_price": "100",
+                "order_qty": "1",
+                "exec_id": "E1",
+            })))
+            .unwrap();
+        assert_eq!(order.amendment_count(), 1);
+        assert_eq!(order.amendments[0].old_qty, dec!(2));
+        assert_eq!(order.amendments[0].amend_id.as_deref(), Some("A1"));
+        assert_eq!(order.amendments[0].exec_id.as_deref(), Some("E1"));
+    }
+
+    #[test]
+    fn test_repeated_identical_amendments_are_each_recorded() {
+        use kraken_types::AmendOrderParams;
+
+        let mut tracker = OrderTracker::new();
+        tracker.track_submission("req1", "BTC/USD", Side::Buy, dec!(2), Some(dec!(100)));
+        tracker.handle_execution(&exec(serde_json::json!({"exec_type": "new", "order_status": "new"})));
+
+        // 2 -> 1 -> 2 -> 1: the first and last changes are identical
+        let quantities = [dec!(1), dec!(2), dec!(1)];
+        for (i, qty) in quantities.iter().enumerate() {
+            let request = AmendOrderRequest::new(AmendOrderParams {
+                order_id: "O1".to_string(),
+                limit_price: None,
+                trigger_price: None,
+                order_qty: Some(*qty),
+                post_only: None,
+                token: "tok".to_string(),
+            });
+            let response: TradingResponse = serde_json::from_value(serde_json::json!({
+                "method": "amend_order",
+                "success": true,
+                "result": {"order_id": "O1", "amend_id": format!("A{i}")},
+            }))
+            .unwrap();
+            tracker.handle_amend_response(&request, &response);
+        }
+        // The execution feed lags behind and reports all three afterwards,
+        // then replays the last one
+        for (i, qty) in quantities.iter().chain([dec!(1)].iter()).enumerate() {
+            let exec_id = format!("E{}", i.min(2));
+            tracker.handle_execution(&exec(serde_json::json!({
+                "exec_type": "amended",
+                "order_status": "new",
+                "order_qty": qty.to_string(),
+                "exec_id": exec_id,
+            })));
+        }
+
+        let order = tracker.get("O1").unwrap();
+        assert_eq!(order.amendment_count(), 3);
+        assert_eq!(order.remaining_qty(), dec!(1));
+        for (i, amendment) in order.amendments.iter().enumerate() {
+            assert_eq!(amendment.amend_id, Some(format!("A{i}")));
+            assert_eq!(amendment.exec_id, Some(format!("E{i}")));
+        }
+
+        // Amendments made elsewhere only reach the execution feed; two
+        // identical ones are still two amendments
+        for exec_id in ["E3", "E4"] {
+            tracker.handle_execution(&exec(serde_json::json!({
+                "exec_type": "amended",
+                "order_status": "new",
+                "limit_price": "101",
+                "exec_id": exec_id,
+            })));
+        }
+        assert_eq!(tracker.get("O1").unwrap().amendment_count(), 5);
+    }
+
+    #[test]
+    fn test_tracker_stamps_account_on_orders() {
+        let mut tracker = OrderTracker::new().with_account("desk-a");
+        tracker.track_submission("req1", "BTC/USD", Side::Buy, dec!(1), Some(dec!(100)));
+        tracker.handle_execution(&exec(serde_json::json!({"exec_type": "new", "order_status": "new"})));
+        tracker.handle_execution(&exec(serde_json::json!({
+            "order_id": "O2",
+            "symbol": "ETH/USD",
+            "exec_type": "new",
+            "order_status": "new",
+        })));
+
+        let desk_a = AccountId::new("desk-a");
+        assert_eq!(tracker.by_account(&desk_a).len(), 2);
+        assert!(tracker.by_account(&AccountId::default()).is_empty());
+        assert_eq!(tracker.get("O1").unwrap().account, desk_a);
+    }
+
+    #[test]
+    fn test_state_round_trip_keeps_correlation() {
+        let mut tracker = OrderTracker::new().with_account("desk-a");
+        tracker.track_submission("req1", "BTC/USD", Side::Buy, dec!(1), Some(dec!(100)));
+        tracker.handle_execution(&exec(serde_json::json!({"exec_type": "new", "order_status": "new"})));
+        tracker.track_submission("req2", "ETH/USD", Side::Sell, dec!(3), None);
+
+        let json = serde_json::to_string(&tracker.export_state()).unwrap();
+        let state: TrackerState = serde_json::from_str(&json).unwrap();
+        let mut restored = OrderTracker::from_state(state, TrackerConfig::default());
+
+        assert_eq!(restored.account(), &AccountId::new("desk-a"));
+        assert_eq!(restored.get_by_request_id("req1").unwrap().order_id.as_deref(), Some("O1"));
+        assert!(restored.get_by_request_id("req2").is_some());
+        assert_eq!(restored.stats().active_orders, 2);
+
+        let order = restored
+            .handle_execution(&exec(serde_json::json!({
+                "exec_type": "trade",
+                "order_status": "filled",
+                "last_price": "100",
+                "last_qty": "1",
+                "cum_qty": "1",
+            })))
+            .unwrap();
+        assert_eq!(order.lifecycle_state, LifecycleState::Filled);
+        assert_eq!(restored.stats().filled_count, 1);
+    }
+
     #[test]
     fn test_fill_calculations() {
         let mut order = LifecycleOrder::new_pending(
@@ -0,0 +1,503 @@
+//! Spot position and PnL tracking
+//!
+//! [`PositionTracker`] keeps a per-asset inventory for spot accounts, fed by
+//! the executions channel and balance snapshots, and marks positions to the
+//! live mid price from the orderbook (or ticker) feed.
+//!
+//! # Cost basis
+//!
+//! Only quantity acquired through tracked fills has a known cost basis. A
+//! balance snapshot sets the total inventory, but holdings that existed
+//! before tracking started carry no entry price and don't contribute to PnL.
+//! Sells reduce the tracked quantity first and realize PnL against the
+//! average entry price.
+//!
+//! Fees charged in the quote asset are counted in `fees_paid`. Fees charged
+//! in the base asset reduce the base position: a buy's cost is spread over
+//! the quantity actually received, and base units given up on a sell are
+//! realized as a loss at the entry price.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_ws::position::PositionTracker;
+//! use kraken_types::Side;
+//! use rust_decimal_macros::dec;
+//!
+//! let mut tracker = PositionTracker::new();
+//! tracker.apply_fill("BTC/USD", Side::Buy, dec!(1), dec!(50000), dec!(0), None);
+//! tracker.update_mark("BTC/USD", dec!(51000));
+//!
+//! assert_eq!(tracker.position("BTC").unwrap().qty, dec!(1));
+//! assert_eq!(tracker.unrealized_pnl("BTC/USD"), Some(dec!(1000)));
+//! ```
+
+use crate::events::{Event, MarketEvent, PrivateEvent};
+use kraken_types::{BalanceData, Decimal, ExecutionData, Side};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use tracing::debug;
+
+/// Inventory and cost basis for a single asset
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssetPosition {
+    /// Asset identifier (e.g., "BTC")
+    pub asset: String,
+    /// Total inventory (balances plus fills since the last snapshot)
+    pub qty: Decimal,
+    /// Quantity acquired through tracked fills (has a cost basis)
+    pub tracked_qty: Decimal,
+    /// Average entry price of the tracked quantity
+    pub avg_entry_price: Option<Decimal>,
+    /// Asset the entry price is quoted in
+    pub cost_currency: Option<String>,
+    /// Realized PnL in the cost currency
+    pub realized_pnl: Decimal,
+    /// Fees paid in the cost currency
+    pub fees_paid: Decimal,
+}
+
+impl AssetPosition {
+    fn new(asset: &str) -> Self {
+        Self {
+            asset: asset.to_string(),
+            qty: Decimal::ZERO,
+            tracked_qty: Decimal::ZERO,
+            avg_entry_price: None,
+            cost_currency: None,
+            realized_pnl: Decimal::ZERO,
+            fees_paid: Decimal::ZERO,
+        }
+    }
+
+    /// Check if the position holds no inventory
+    pub fn is_flat(&self) -> bool {
+        self.qty.is_zero()
+    }
+
+    /// Unrealized PnL of the tracked quantity at `mark`
+    pub fn unrealized_pnl(&self, mark: Decimal) -> Option<Decimal> {
+        self.avg_entry_price
+            .map(|avg| (mark - avg) * self.tracked_qty)
+    }
+}
+
+/// Why a position changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PositionChangeReason {
+    /// An execution filled
+    Fill {
+        /// Trading pair of the fill
+        symbol: String,
+        /// Side of the fill
+        side: Side,
+    },
+    /// A balance snapshot or update set the inventory
+    Balance,
+}
+
+/// A change in an asset's inventory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PositionChange {
+    /// Asset that changed
+    pub asset: String,
+    /// Inventory before the change
+    pub previous_qty: Decimal,
+    /// Inventory after the change
+    pub qty: Decimal,
+    /// What caused the change
+    pub reason: PositionChangeReason,
+}
+
+/// Number of recent execution IDs remembered for deduplication
+pub const DEFAULT_EXEC_HISTORY: usize = 10_000;
+
+/// Spot position tracker
+///
+/// Serializes with its inventory, marks, and the most recent applied
+/// execution IDs, so a restored tracker won't double-count fills replayed
+/// after a restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PositionTracker {
+    /// Positions keyed by asset
+    positions: HashMap<String, AssetPosition>,
+    /// Latest mark price per symbol
+    marks: HashMap<String, Decimal>,
+    /// Execution IDs already applied
+    seen_execs: HashSet<String>,
+    /// `seen_execs` in the order they were applied, oldest first
+    #[serde(default)]
+    exec_order: VecDeque<String>,
+    /// Maximum number of execution IDs remembered
+    #[serde(default = "default_exec_history")]
+    exec_history: usize,
+}
+
+fn default_exec_history() -> usize {
+    DEFAULT_EXEC_HISTORY
+}
+
+impl Default for PositionTracker {
+    fn default() -> Self {
+        Self {
+            positions: HashMap::new(),
+            marks: HashMap::new(),
+            seen_execs: HashSet::new(),
+            exec_order: VecDeque::new(),
+            exec_history: DEFAULT_EXEC_HISTORY,
+        }
+    }
+}
+
+impl PositionTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how many recent execution IDs are remembered for deduplication
+    ///
+    /// The oldest IDs are forgotten first; replays older than the history
+    /// are applied again.
+    pub fn with_exec_history(mut self, executions: usize) -> Self {
+        self.exec_history = executions.max(1);
+        self
+    }
+
+    /// Feed an SDK event, returning any position changes
+    ///
+    /// Executions and balances update inventory; orderbook and ticker events
+    /// update mark prices.
+    pub fn handle_event(&mut self, event: &Event) -> Vec<PositionChange> {
+        match event {
+            Event::Private(private) => match private.as_ref() {
+                PrivateEvent::Execution { data, .. } => self.handle_execution(data),
+                PrivateEvent::BalanceUpdate { balances, .. } => self.apply_balances(balances),
+                PrivateEvent::BalanceSnapshot { balances } => {
+                    let mut changes = Vec::new();
+                    for info in balances.values() {
+                        changes.extend(self.set_balance(&info.asset, info.total));
+                    }
+                    changes
+                }
+                PrivateEvent::OrderUpdate { .. } => Vec::new(),
+            },
+            Event::Market(MarketEvent::OrderbookSnapshot { symbol, snapshot, .. })
+            | Event::Market(MarketEvent::OrderbookUpdate { symbol, snapshot, .. }) => {
+                if let Some(mid) = snapshot.mid_price() {
+                    self.update_mark(symbol, mid);
+                }
+                Vec::new()
+            }
+            Event::Market(MarketEvent::Ticker { symbol, ticker, .. }) => {
+                if ticker.bid > Decimal::ZERO && ticker.ask > Decimal::ZERO {
+                    self.update_mark(symbol, (ticker.bid + ticker.ask) / Decimal::TWO);
+                }
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Apply an execution from the executions channel
+    ///
+    /// Only trade executions with a last fill change positions. Executions
+    /// seen before (by `exec_id`) are ignored.
+    pub fn handle_execution(&mut self, exec: &ExecutionData) -> Vec<PositionChange> {
+        let (Some(qty), Some(price)) = (exec.last_qty, exec.last_price) else {
+            return Vec::new();
+        };
+        if let Some(exec_id) = &exec.exec_id {
+            if !self.remember_exec(exec_id) {
+                debug!(exec_id, "Ignoring duplicate execution");
+                return Vec::new();
+            }
+        }
+        self.apply_fill(
+            &exec.symbol,
+            exec.side,
+            qty,
+            price,
+            exec.fee_paid.unwrap_or(Decimal::ZERO),
+            exec.fee_currency.as_deref(),
+        )
+    }
+
+    /// Record an execution ID, returning false if it was already applied
+    fn remember_exec(&mut self, exec_id: &str) -> bool {
+        if !self.seen_execs.insert(exec_id.to_string()) {
+            return false;
+        }
+        self.exec_order.push_back(exec_id.to_string());
+        while self.exec_order.len() > self.exec_history {
+            if let Some(oldest) = self.exec_order.pop_front() {
+                self.seen_execs.remove(&oldest);
+            }
+        }
+        true
+    }
+
+    /// Apply a fill of `qty` base units at `price`
+    ///
+    /// `fee` is charged in `fee_currency`, defaulting to the quote asset.
+    /// Fills without a positive quantity are ignored.
+    pub fn apply_fill(
+        &mut self,
+        symbol: &str,
+        side: Side,
+        qty: Decimal,
+        price: Decimal,
+        fee: Decimal,
+        fee_currency: Option<&str>,
+    ) -> Vec<PositionChange> {
+        let Some((base, quote)) = symbol.split_once('/') else {
+            return Vec::new();
+        };
+        if qty <= Decimal::ZERO {
+            return Vec::new();
+        }
+        let reason = PositionChangeReason::Fill {
+            symbol: symbol.to_string(),
+            side,
+        };
+        let notional = qty * price;
+        let fee_currency = fee_currency.unwrap_or(quote);
+
+        let base_change = {
+            let position = self.entry(base);
+            let previous_qty = position.qty;
+            if position.cost_currency.as_deref() != Some(quote) && !position.tracked_qty.is_zero() {
+                // Cost basis in another quote can't be combined; restart it
+                position.tracked_qty = Decimal::ZERO;
+                position.avg_entry_price = None;
+            }
+            position.cost_currency = Some(quote.to_string());
+
+            let base_fee = if fee_currency == base { fee } else { Decimal::ZERO };
+            match side {
+                Side::Buy => {
+                    let cost = position.avg_entry_price.unwrap_or(Decimal::ZERO) * position.tracked_qty
+                        + notional;
+                    position.tracked_qty += (qty - base_fee).max(Decimal::ZERO);
+                    position.avg_entry_price =
+                        (!position.tracked_qty.is_zero()).then(|| cost / position.tracked_qty);
+                }
+                Side::Sell => {
+                    let avg = position.avg_entry_price.unwrap_or(Decimal::ZERO);
+                    let closed = qty.min(position.tracked_qty);
+                    let burned = base_fee.min(position.tracked_qty - closed);
+                    position.realized_pnl += (price - avg) * closed - avg * burned;
+                    position.tracked_qty -= closed + burned;
+                    if position.tracked_qty.is_zero() {
+                        position.avg_entry_price = None;
+                    }
+                }
+            }
+            match side {
+                Side::Buy => position.qty += qty - base_fee,
+                Side::Sell => position.qty -= qty + base_fee,
+            }
+            if fee_currency == quote {
+                position.fees_paid += fee;
+            }
+            PositionChange {
+                asset: base.to_string(),
+                previous_qty,
+                qty: position.qty,
+                reason: reason.clone(),
+            }
+        };
+
+        let quote_change = {
+            let position = self.entry(quote);
+            let previous_qty = position.qty;
+            match side {
+                Side::Buy => position.qty -= notional,
+                Side::Sell => position.qty += notional,
+            }
+            if fee_currency == quote {
+                position.qty -= fee;
+            }
+            PositionChange {
+                asset: quote.to_string(),
+                previous_qty,
+                qty: position.qty,
+                reason,
+            }
+        };
+
+        vec![base_change, quote_change]
+    }
+
+    /// Apply balances from the balances channel
+    pub fn apply_balances(&mut self, balances: &[BalanceData]) -> Vec<PositionChange> {
+        balances
+            .iter()
+            .filter_map(|b| self.set_balance(&b.asset, b.balance + b.hold_trade.unwrap_or(Decimal::ZERO)))
+            .collect()
+    }
+
+    /// Set the total inventory of an asset from a balance
+    ///
+    /// The tracked quantity is capped at the new inventory. Returns `None` if
+    /// the inventory didn't change.
+    pub fn set_balance(&mut self, asset: &str, total: Decimal) -> Option<PositionChange> {
+        let position = self.entry(asset);
+        let previous_qty = position.qty;
+        position.qty = total;
+        if position.tracked_qty > total {
+            position.tracked_qty = total.max(Decimal::ZERO);
+            if position.tracked_qty.is_zero() {
+                position.avg_entry_price = None;
+            }
+        }
+        (previous_qty != total).then(|| PositionChange {
+            asset: asset.to_string(),
+            previous_qty,
+            qty: total,
+            reason: PositionChangeReason::Balance,
+        })
+    }
+
+    /// Set the mark price for a symbol
+    pub fn update_mark(&mut self, symbol: &str, price: Decimal) {
+        self.marks.insert(symbol.to_string(), price);
+    }
+
+    /// Latest mark price for a symbol
+    pub fn mark(&self, symbol: &str) -> Option<Decimal> {
+        self.marks.get(symbol).copied()
+    }
+
+    /// Position for an asset
+    pub fn position(&self, asset: &str) -> Option<&AssetPosition> {
+        self.positions.get(asset)
+    }
+
+    /// All positions
+    pub fn positions(&self) -> impl Iterator<Item = &AssetPosition> {
+        self.positions.values()
+    }
+
+    /// Unrealized PnL of the base asset, marked to the symbol's price
+    ///
+    /// Returns `None` without a mark price, or if the position's cost basis
+    /// is quoted in a different asset.
+    pub fn unrealized_pnl(&self, symbol: &str) -> Option<Decimal> {
+        let (base, quote) = symbol.split_once('/')?;
+        let position = self.positions.get(base)?;
+        if position.cost_currency.as_deref() != Some(quote) {
+            return None;
+        }
+        position.unrealized_pnl(self.mark(symbol)?)
+    }
+
+    /// Realized PnL of the base asset, net of fees
+    pub fn realized_pnl(&self, symbol: &str) -> Option<Decimal> {
+        let (base, quote) = symbol.split_once('/')?;
+        let position = self.positions.get(base)?;
+        if position.cost_currency.as_deref() != Some(quote) {
+            return None;
+        }
+        Some(position.realized_pnl - position.fees_paid)
+    }
+
+    /// Remove all positions, marks and seen executions
+    pub fn clear(&mut self) {
+        self.positions.clear();
+        self.marks.clear();
+        self.seen_execs.clear();
+        self.exec_order.clear();
+    }
+
+    fn entry(&mut self, asset: &str) -> &mut AssetPosition {
+        self.positions
+            .entry(asset.to_string())
+            .or_insert_with(|| AssetPosition::new(asset))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_fills_track_cost_basis_and_pnl() {
+        let mut tracker = PositionTracker::new();
+        tracker.set_balance("USD", dec!(100000));
+        tracker.apply_fill("BTC/USD", Side::Buy, dec!(1), dec!(100), dec!(1), None);
+        tracker.apply_fill("BTC/USD", Side::Buy, dec!(1), dec!(200), dec!(1), None);
+
+        let btc = tracker.position("BTC").unwrap();
+        assert_eq!(btc.qty, dec!(2));
+        assert_eq!(btc.avg_entry_price, Some(dec!(150)));
+        assert_eq!(tracker.position("USD").unwrap().qty, dec!(99698));
+
+        tracker.update_mark("BTC/USD", dec!(160));
+        assert_eq!(tracker.unrealized_pnl("BTC/USD"), Some(dec!(20)));
+        assert_eq!(tracker.unrealized_pnl("BTC/EUR"), None);
+
+        let changes = tracker.apply_fill("BTC/USD", Side::Sell, dec!(1), dec!(170), dec!(0), None);
+        assert_eq!(changes[0].previous_qty, dec!(2));
+        assert_eq!(changes[0].qty, dec!(1));
+        assert_eq!(tracker.realized_pnl("BTC/USD"), Some(dec!(18)));
+        assert_eq!(tracker.unrealized_pnl("BTC/USD"), Some(dec!(10)));
+    }
+
+    #[test]
+    fn test_untracked_inventory_has_no_pnl() {
+        let mut tracker = PositionTracker::new();
+        tracker.set_balance("ETH", dec!(5));
+        tracker.apply_fill("ETH/USD", Side::Buy, dec!(1), dec!(2000), dec!(0), None);
+
+        // Selling more than the tracked quantity only realizes the tracked part
+        tracker.apply_fill("ETH/USD", Side::Sell, dec!(3), dec!(2100), dec!(0), None);
+        let eth = tracker.position("ETH").unwrap();
+        assert_eq!(eth.qty, dec!(3));
+        assert!(eth.tracked_qty.is_zero());
+        assert_eq!(eth.avg_entry_price, None);
+        assert_eq!(tracker.realized_pnl("ETH/USD"), Some(dec!(100)));
+
+        // A snapshot below the tracked quantity caps it
+        tracker.apply_fill("ETH/USD", Side::Buy, dec!(2), dec!(2000), dec!(0), None);
+        assert!(tracker.set_balance("ETH", dec!(1)).is_some());
+        assert_eq!(tracker.position("ETH").unwrap().tracked_qty, dec!(1));
+        assert!(tracker.set_balance("ETH", dec!(1)).is_none());
+    }
+
+    #[test]
+    fn test_zero_fill_and_base_fees() {
+        let mut tracker = PositionTracker::new();
+        assert!(tracker
+            .apply_fill("BTC/USD", Side::Buy, dec!(0), dec!(100), dec!(0), None)
+            .is_empty());
+        assert!(tracker.position("BTC").is_none());
+
+        // 0.01 BTC of a 1 BTC buy goes to fees: 0.99 BTC cost 99 USD
+        tracker.apply_fill("BTC/USD", Side::Buy, dec!(1), dec!(99), dec!(0.01), Some("BTC"));
+        let btc = tracker.position("BTC").unwrap();
+        assert_eq!(btc.qty, dec!(0.99));
+        assert_eq!(btc.tracked_qty, dec!(0.99));
+        assert_eq!(btc.avg_entry_price, Some(dec!(100)));
+
+        // Selling 0.5 BTC plus a 0.01 BTC fee at entry realizes only the fee
+        tracker.apply_fill("BTC/USD", Side::Sell, dec!(0.5), dec!(100), dec!(0.01), Some("BTC"));
+        let btc = tracker.position("BTC").unwrap();
+        assert_eq!(btc.qty, dec!(0.48));
+        assert_eq!(btc.tracked_qty, dec!(0.48));
+        assert_eq!(tracker.realized_pnl("BTC/USD"), Some(dec!(-1)));
+    }
+
+    #[test]
+    fn test_exec_history_is_bounded() {
+        let mut tracker = PositionTracker::new().with_exec_history(2);
+        for id in ["E1", "E2", "E3"] {
+            assert!(tracker.remember_exec(id));
+        }
+        assert!(!tracker.remember_exec("E3"));
+        assert_eq!(tracker.seen_execs.len(), 2);
+        // The oldest ID has been forgotten
+        assert!(tracker.remember_exec("E1"));
+    }
+}
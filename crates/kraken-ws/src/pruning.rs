@@ -0,0 +1,162 @@
+//! Inactivity-based pruning of book subscriptions
+//!
+//! Screeners often subscribe to far more books than they look at. With
+//! [`ConnectionConfig::with_pruning`](crate::ConnectionConfig::with_pruning)
+//! a book the application hasn't read through `orderbook`,
+//! `orderbook_snapshot` or `with_orderbook` for the policy's idle period is
+//! unsubscribed, freeing bandwidth and subscribe budget. Pruned books are
+//! left empty and are not restored on reconnect.
+//!
+//! Only book subscriptions are pruned. Ticker, trade, OHLC and the other
+//! per-symbol channels are delivered as events rather than read through an
+//! accessor, so the connection can't tell whether they are still used;
+//! their subscriptions are left alone, including for a pruned symbol.
+//!
+//! With [`PruningPolicy::with_resubscribe_on_access`] reading a pruned book
+//! resubscribes it; the read itself sees an empty book until the new
+//! snapshot arrives. Otherwise books come back through
+//! [`KrakenConnection::resume_pruned`](crate::KrakenConnection::resume_pruned).
+//!
+//! ```
+//! use kraken_ws::{ConnectionConfig, PruningPolicy};
+//! use std::time::Duration;
+//!
+//! let config = ConnectionConfig::new().with_pruning(
+//!     PruningPolicy::new(Duration::from_secs(600)).with_resubscribe_on_access(true),
+//! );
+//! ```
+
+use crate::subscription::Subscription;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// When to unsubscribe unread books
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PruningPolicy {
+    /// Unsubscribe a book not read for this long
+    pub idle_after: Duration,
+    /// Resubscribe a pruned book when the application reads it
+    pub resubscribe_on_access: bool,
+}
+
+impl PruningPolicy {
+    /// Prune books not read for `idle_after`
+    pub fn new(idle_after: Duration) -> Self {
+        Self {
+            idle_after,
+            resubscribe_on_access: false,
+        }
+    }
+
+    /// Resubscribe pruned books when they are read again
+    pub fn with_resubscribe_on_access(mut self, enabled: bool) -> Self {
+        self.resubscribe_on_access = enabled;
+        self
+    }
+
+    /// How often to look for idle books
+    pub(crate) fn check_interval(&self) -> Duration {
+        (self.idle_after / 4).max(Duration::from_millis(10))
+    }
+}
+
+/// Last application read per book, and the subscriptions of pruned books
+#[derive(Debug, Default)]
+pub(crate) struct AccessTracker {
+    last_access: HashMap<String, Instant>,
+    pruned: HashMap<String, Subscription>,
+}
+
+impl AccessTracker {
+    /// Start the idle clock for a subscribed book, unless it already runs
+    pub fn watch(&mut self, symbol: &str, now: Instant) {
+        self.last_access.entry(symbol.to_string()).or_insert(now);
+    }
+
+    /// Record a read of a watched book; true if the book is currently pruned
+    pub fn touch(&mut self, symbol: &str, now: Instant) -> bool {
+        if let Some(last) = self.last_access.get_mut(symbol) {
+            *last = now;
+        }
+        self.pruned.contains_key(symbol)
+    }
+
+    /// Whether a book is currently pruned
+    pub fn is_pruned(&self, symbol: &str) -> bool {
+        self.pruned.contains_key(symbol)
+    }
+
+    /// Watched books not read for `idle_after`, oldest read first
+    pub fn idle(&self, idle_after: Duration, now: Instant) -> Vec<(String, Duration)> {
+        let mut idle: Vec<_> = self
+            .last_access
+            .iter()
+            .filter(|(symbol, _)| !self.pruned.contains_key(*symbol))
+            .map(|(symbol, last)| (symbol.clone(), now.saturating_duration_since(*last)))
+            .filter(|(_, idle_for)| *idle_for >= idle_after)
+            .collect();
+        idle.sort_by_key(|(_, idle_for)| std::cmp::Reverse(*idle_for));
+        idle
+    }
+
+    /// Remember the subscription a pruned book had
+    pub fn prune(&mut self, symbol: &str, subscription: Subscription) {
+        self.pruned.insert(symbol.to_string(), subscription);
+    }
+
+    /// Take a pruned book's subscription back out, restarting its idle clock
+    pub fn resume(&mut self, symbol: &str, now: Instant) -> Option<Subscription> {
+        let subscription = self.pruned.remove(symbol)?;
+        self.last_access.insert(symbol.to_string(), now);
+        Some(subscription)
+    }
+
+    /// Currently pruned books, sorted
+    pub fn pruned(&self) -> Vec<String> {
+        let mut symbols: Vec<_> = self.pruned.keys().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_types::Depth;
+
+    #[test]
+    fn test_idle_books_are_reported_once_pruned() {
+        let mut tracker = AccessTracker::default();
+        let start = Instant::now();
+        let idle_after = Duration::from_secs(60);
+        tracker.watch("BTC/USD", start);
+        tracker.watch("ETH/USD", start);
+        tracker.touch("BTC/USD", start + Duration::from_secs(50));
+        // Watching again doesn't restart the clock
+        tracker.watch("ETH/USD", start + Duration::from_secs(50));
+
+        let now = start + Duration::from_secs(70);
+        assert_eq!(tracker.idle(idle_after, now), vec![("ETH/USD".to_string(), Duration::from_secs(70))]);
+
+        tracker.prune("ETH/USD", Subscription::orderbook(["ETH/USD"], Depth::D10));
+        assert!(tracker.idle(idle_after, now).is_empty());
+        assert_eq!(tracker.pruned(), vec!["ETH/USD".to_string()]);
+    }
+
+    #[test]
+    fn test_touching_pruned_book_reports_it() {
+        let mut tracker = AccessTracker::default();
+        let start = Instant::now();
+        tracker.watch("BTC/USD", start);
+        assert!(!tracker.touch("BTC/USD", start));
+
+        tracker.prune("BTC/USD", Subscription::orderbook(["BTC/USD"], Depth::D25));
+        assert!(tracker.touch("BTC/USD", start));
+
+        let later = start + Duration::from_secs(90);
+        let subscription = tracker.resume("BTC/USD", later).unwrap();
+        assert_eq!(subscription.depth, Some(Depth::D25));
+        assert!(!tracker.touch("BTC/USD", later));
+        assert!(tracker.idle(Duration::from_secs(60), later).is_empty());
+    }
+}
@@ -0,0 +1,520 @@
+//! Two-sided quoting with automatic re-quotes
+//!
+//! [`Quoter`] keeps a bid and an ask at configurable offsets around a
+//! reference price (mid or microprice) from the L2 book. It re-quotes when the
+//! reference moves by more than a threshold or when the inventory skew
+//! changes, and moves resting quotes with amend-in-place rather than
+//! cancel/replace, which costs one request instead of two against the rate
+//! limit.
+//!
+//! Like the [execution algorithms](crate::execution), the quoter performs no
+//! I/O: feed it books and executions and send the returned [`AlgoAction`]s
+//! with [`TradingClient::algo_request`](crate::TradingClient::algo_request).
+//!
+//! # Skew
+//!
+//! By default both quotes shift by `-inventory * skew_per_unit`, so a long
+//! position lowers prices to sell more readily. [`Quoter::with_skew_fn`] and
+//! [`Quoter::with_size_fn`] replace the skew and quote sizes with custom logic.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_ws::quoter::{Quoter, QuoterConfig};
+//! use kraken_book::OrderbookSnapshot;
+//! use kraken_types::Level;
+//! use rust_decimal_macros::dec;
+//!
+//! let mut quoter = Quoter::new("mm", "BTC/USD", QuoterConfig::new(dec!(5), dec!(0.1)));
+//! let book = OrderbookSnapshot {
+//!     bids: vec![Level::new(dec!(99), dec!(1))],
+//!     asks: vec![Level::new(dec!(101), dec!(1))],
+//!     ..Default::default()
+//! };
+//!
+//! // First book places both sides around the mid of 100
+//! let actions = quoter.on_book(&book);
+//! assert_eq!(actions.len(), 2);
+//! assert_eq!(quoter.bid().unwrap().price, dec!(95));
+//! assert_eq!(quoter.ask().unwrap().price, dec!(105));
+//! ```
+
+use crate::execution::{AlgoAction, ChildOrder};
+use kraken_book::OrderbookSnapshot;
+use kraken_types::{Decimal, ExecutionData, Side};
+use std::fmt;
+use std::sync::Arc;
+
+/// Price the quotes are centered on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReferencePrice {
+    /// Midpoint of best bid and ask
+    #[default]
+    Mid,
+    /// Size-weighted top of book
+    Microprice,
+}
+
+/// Inputs to the skew and size hooks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteContext {
+    /// Current inventory in base units (negative when short)
+    pub inventory: Decimal,
+    /// Reference price before skew
+    pub reference: Decimal,
+}
+
+/// Custom skew: returns the price shift applied to both quotes
+pub type SkewFn = Arc<dyn Fn(&QuoteContext) -> Decimal + Send + Sync>;
+/// Custom sizing: returns (bid qty, ask qty); zero disables a side
+pub type SizeFn = Arc<dyn Fn(&QuoteContext) -> (Decimal, Decimal) + Send + Sync>;
+
+/// Quoter settings
+#[derive(Debug, Clone)]
+pub struct QuoterConfig {
+    /// Distance of each quote from the reference price
+    pub half_spread: Decimal,
+    /// Quote size on each side
+    pub qty: Decimal,
+    /// Price the quotes are centered on
+    pub reference: ReferencePrice,
+    /// Minimum price change that triggers an amend
+    pub requote_threshold: Decimal,
+    /// Price shift per unit of inventory
+    pub skew_per_unit: Decimal,
+    /// Stop quoting the side that would grow inventory past this
+    pub max_inventory: Option<Decimal>,
+    /// Round prices to the pair's price precision
+    pub price_decimals: Option<u32>,
+}
+
+impl QuoterConfig {
+    /// Quote `qty` on each side, `half_spread` away from the mid
+    pub fn new(half_spread: Decimal, qty: Decimal) -> Self {
+        Self {
+            half_spread,
+            qty,
+            reference: ReferencePrice::Mid,
+            requote_threshold: Decimal::ZERO,
+            skew_per_unit: Decimal::ZERO,
+            max_inventory: None,
+            price_decimals: None,
+        }
+    }
+
+    /// Center quotes on the given reference price
+    pub fn with_reference(mut self, reference: ReferencePrice) -> Self {
+        self.reference = reference;
+        self
+    }
+
+    /// Only re-quote when the price moves by at least `threshold`
+    pub fn with_requote_threshold(mut self, threshold: Decimal) -> Self {
+        self.requote_threshold = threshold;
+        self
+    }
+
+    /// Shift quotes by `-inventory * per_unit`
+    pub fn with_skew_per_unit(mut self, per_unit: Decimal) -> Self {
+        self.skew_per_unit = per_unit;
+        self
+    }
+
+    /// Stop adding to inventory beyond `max` (absolute)
+    pub fn with_max_inventory(mut self, max: Decimal) -> Self {
+        self.max_inventory = Some(max);
+        self
+    }
+
+    /// Round prices to `decimals` places
+    pub fn with_price_decimals(mut self, decimals: u32) -> Self {
+        self.price_decimals = Some(decimals);
+        self
+    }
+}
+
+/// A resting quote
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Quote {
+    /// Client order ID
+    pub cl_ord_id: String,
+    /// Exchange order ID, once acknowledged
+    pub order_id: Option<String>,
+    /// Quote price
+    pub price: Decimal,
+    /// Remaining quantity
+    pub qty: Decimal,
+}
+
+/// Two-sided quoting engine
+pub struct Quoter {
+    prefix: String,
+    symbol: String,
+    config: QuoterConfig,
+    skew_fn: Option<SkewFn>,
+    size_fn: Option<SizeFn>,
+    inventory: Decimal,
+    bid: Option<Quote>,
+    ask: Option<Quote>,
+    reference: Option<Decimal>,
+    last_skew: Decimal,
+    next_id: u64,
+    active: bool,
+}
+
+impl fmt::Debug for Quoter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Quoter")
+            .field("symbol", &self.symbol)
+            .field("config", &self.config)
+            .field("skew_fn", &self.skew_fn.as_ref().map(|_| "..."))
+            .field("size_fn", &self.size_fn.as_ref().map(|_| "..."))
+            .field("inventory", &self.inventory)
+            .field("bid", &self.bid)
+            .field("ask", &self.ask)
+            .field("active", &self.active)
+            .finish()
+    }
+}
+
+impl Quoter {
+    /// Create a quoter for `symbol`
+    ///
+    /// `prefix` is used for quote client order IDs and should be unique.
+    pub fn new(prefix: impl Into<String>, symbol: impl Into<String>, config: QuoterConfig) -> Self {
+        Self {
+            prefix: prefix.into(),
+            symbol: symbol.into(),
+            config,
+            skew_fn: None,
+            size_fn: None,
+            inventory: Decimal::ZERO,
+            bid: None,
+            ask: None,
+            reference: None,
+            last_skew: Decimal::ZERO,
+            next_id: 0,
+            active: true,
+        }
+    }
+
+    /// Replace the default linear skew
+    pub fn with_skew_fn<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&QuoteContext) -> Decimal + Send + Sync + 'static,
+    {
+        self.skew_fn = Some(Arc::new(f));
+        self
+    }
+
+    /// Size each side with custom logic
+    pub fn with_size_fn<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&QuoteContext) -> (Decimal, Decimal) + Send + Sync + 'static,
+    {
+        self.size_fn = Some(Arc::new(f));
+        self
+    }
+
+    /// Quoter settings
+    pub fn config(&self) -> &QuoterConfig {
+        &self.config
+    }
+
+    /// Current inventory in base units
+    pub fn inventory(&self) -> Decimal {
+        self.inventory
+    }
+
+    /// Resting bid, if any
+    pub fn bid(&self) -> Option<&Quote> {
+        self.bid.as_ref()
+    }
+
+    /// Resting ask, if any
+    pub fn ask(&self) -> Option<&Quote> {
+        self.ask.as_ref()
+    }
+
+    /// Set inventory (e.g., from a balance snapshot) and re-quote
+    pub fn set_inventory(&mut self, inventory: Decimal) -> Vec<AlgoAction> {
+        self.inventory = inventory;
+        self.requote()
+    }
+
+    /// React to a new book for the quoted symbol
+    pub fn on_book(&mut self, book: &OrderbookSnapshot) -> Vec<AlgoAction> {
+        let reference = match self.config.reference {
+            ReferencePrice::Mid => book.mid_price(),
+            ReferencePrice::Microprice => book.microprice(),
+        };
+        let Some(reference) = reference else {
+            return Vec::new();
+        };
+        self.reference = Some(reference);
+        self.requote()
+    }
+
+    /// Record the exchange order ID of a quote
+    pub fn on_ack(&mut self, cl_ord_id: &str, order_id: &str) {
+        for quote in [self.bid.as_mut(), self.ask.as_mut()].into_iter().flatten() {
+            if quote.cl_ord_id == cl_ord_id {
+                quote.order_id = Some(order_id.to_string());
+            }
+        }
+    }
+
+    /// Record a fill on one of the quotes, then re-quote for the new skew
+    pub fn on_fill(&mut self, cl_ord_id: &str, qty: Decimal) -> Vec<AlgoAction> {
+        let (slot, side) = if self.bid.as_ref().is_some_and(|q| q.cl_ord_id == cl_ord_id) {
+            (&mut self.bid, Side::Buy)
+        } else if self.ask.as_ref().is_some_and(|q| q.cl_ord_id == cl_ord_id) {
+            (&mut self.ask, Side::Sell)
+        } else {
+            return Vec::new();
+        };
+        if let Some(quote) = slot {
+            quote.qty -= qty;
+            if quote.qty <= Decimal::ZERO {
+                *slot = None;
+            }
+        }
+        match side {
+            Side::Buy => self.inventory += qty,
+            Side::Sell => self.inventory -= qty,
+        }
+        self.requote()
+    }
+
+    /// Feed an execution from the executions channel
+    pub fn on_execution(&mut self, exec: &ExecutionData) -> Vec<AlgoAction> {
+        let cl_ord_id = [self.bid.as_ref(), self.ask.as_ref()]
+            .into_iter()
+            .flatten()
+            .find(|q| {
+                exec.cl_ord_id.as_deref() == Some(q.cl_ord_id.as_str())
+                    || q.order_id.as_deref() == Some(exec.order_id.as_str())
+            })
+            .map(|q| q.cl_ord_id.clone());
+        let Some(cl_ord_id) = cl_ord_id else {
+            return Vec::new();
+        };
+        self.on_ack(&cl_ord_id, &exec.order_id);
+        match exec.last_qty {
+            Some(qty) if !qty.is_zero() => self.on_fill(&cl_ord_id, qty),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Stop quoting and cancel both quotes
+    pub fn cancel_all(&mut self) -> Vec<AlgoAction> {
+        self.active = false;
+        [self.bid.take(), self.ask.take()]
+            .into_iter()
+            .flatten()
+            .map(|q| AlgoAction::Cancel {
+                cl_ord_id: q.cl_ord_id,
+            })
+            .collect()
+    }
+
+    /// Resume quoting after [`cancel_all`](Self::cancel_all)
+    pub fn resume(&mut self) -> Vec<AlgoAction> {
+        self.active = true;
+        self.requote()
+    }
+
+    /// Price shift for the current inventory
+    pub fn skew(&self, reference: Decimal) -> Decimal {
+        let ctx = QuoteContext {
+            inventory: self.inventory,
+            reference,
+        };
+        match &self.skew_fn {
+            Some(f) => f(&ctx),
+            None => -self.inventory * self.config.skew_per_unit,
+        }
+    }
+
+    fn sizes(&self, reference: Decimal) -> (Decimal, Decimal) {
+        let ctx = QuoteContext {
+            inventory: self.inventory,
+            reference,
+        };
+        let (mut bid_qty, mut ask_qty) = match &self.size_fn {
+            Some(f) => f(&ctx),
+            None => (self.config.qty, self.config.qty),
+        };
+        if let Some(max) = self.config.max_inventory {
+            if self.inventory >= max {
+                bid_qty = Decimal::ZERO;
+            }
+            if self.inventory <= -max {
+                ask_qty = Decimal::ZERO;
+            }
+        }
+        (bid_qty, ask_qty)
+    }
+
+    fn round(&self, price: Decimal) -> Decimal {
+        match self.config.price_decimals {
+            Some(dp) => price.round_dp(dp),
+            None => price,
+        }
+    }
+
+    fn requote(&mut self) -> Vec<AlgoAction> {
+        let Some(reference) = self.reference else {
+            return Vec::new();
+        };
+        if !self.active {
+            return Vec::new();
+        }
+        let skew = self.skew(reference);
+        let skew_changed = skew != self.last_skew;
+        self.last_skew = skew;
+
+        let center = reference + skew;
+        let bid_price = self.round(center - self.config.half_spread);
+        let ask_price = self.round(center + self.config.half_spread);
+        let (bid_qty, ask_qty) = self.sizes(reference);
+
+        let mut actions = Vec::new();
+        actions.extend(self.update_side(Side::Buy, bid_price, bid_qty, skew_changed));
+        actions.extend(self.update_side(Side::Sell, ask_price, ask_qty, skew_changed));
+        actions
+    }
+
+    fn update_side(&mut self, side: Side, price: Decimal, qty: Decimal, force: bool) -> Option<AlgoAction> {
+        let threshold = self.config.requote_threshold;
+        let slot = match side {
+            Side::Buy => &mut self.bid,
+            Side::Sell => &mut self.ask,
+        };
+
+        match slot {
+            Some(quote) if qty <= Decimal::ZERO => {
+                let cl_ord_id = quote.cl_ord_id.clone();
+                *slot = None;
+                Some(AlgoAction::Cancel { cl_ord_id })
+            }
+            Some(quote) => {
+                let moved = (price - quote.price).abs();
+                let resize = qty != quote.qty;
+                if moved.is_zero() && !resize {
+                    return None;
+                }
+                if !force && !resize && moved < threshold {
+                    return None;
+                }
+                // Amend needs the exchange order ID
+                let order_id = quote.order_id.clone()?;
+                quote.price = price;
+                quote.qty = qty;
+                Some(AlgoAction::Amend {
+                    order_id,
+                    limit_price: price,
+                    order_qty: resize.then_some(qty),
+                })
+            }
+            None if qty > Decimal::ZERO => {
+                self.next_id += 1;
+                let cl_ord_id = format!("{}-{}", self.prefix, self.next_id);
+                *slot = Some(Quote {
+                    cl_ord_id: cl_ord_id.clone(),
+                    order_id: None,
+                    price,
+                    qty,
+                });
+                Some(AlgoAction::Place(ChildOrder {
+                    cl_ord_id,
+                    symbol: self.symbol.clone(),
+                    side,
+                    qty,
+                    limit_price: Some(price),
+                    post_only: true,
+                }))
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_types::Level;
+    use rust_decimal_macros::dec;
+
+    fn book(bid: Decimal, ask: Decimal) -> OrderbookSnapshot {
+        OrderbookSnapshot {
+            symbol: "BTC/USD".into(),
+            bids: vec![Level::new(bid, dec!(1))],
+            asks: vec![Level::new(ask, dec!(1))],
+            ..Default::default()
+        }
+    }
+
+    fn acked(quoter: &mut Quoter) {
+        let bid = quoter.bid().unwrap().cl_ord_id.clone();
+        let ask = quoter.ask().unwrap().cl_ord_id.clone();
+        quoter.on_ack(&bid, "BID");
+        quoter.on_ack(&ask, "ASK");
+    }
+
+    #[test]
+    fn test_requotes_with_amend_past_threshold() {
+        let config = QuoterConfig::new(dec!(1), dec!(1)).with_requote_threshold(dec!(2));
+        let mut quoter = Quoter::new("q", "BTC/USD", config);
+        quoter.on_book(&book(dec!(99), dec!(101)));
+        acked(&mut quoter);
+
+        // Small move: no requote
+        assert!(quoter.on_book(&book(dec!(100), dec!(102))).is_empty());
+
+        let actions = quoter.on_book(&book(dec!(104), dec!(106)));
+        assert_eq!(
+            actions,
+            vec![
+                AlgoAction::Amend { order_id: "BID".into(), limit_price: dec!(104), order_qty: None },
+                AlgoAction::Amend { order_id: "ASK".into(), limit_price: dec!(106), order_qty: None },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fill_skews_and_inventory_limit() {
+        let config = QuoterConfig::new(dec!(1), dec!(1))
+            .with_requote_threshold(dec!(100))
+            .with_skew_per_unit(dec!(0.5))
+            .with_max_inventory(dec!(1));
+        let mut quoter = Quoter::new("q", "BTC/USD", config);
+        quoter.on_book(&book(dec!(99), dec!(101)));
+        acked(&mut quoter);
+        let bid_id = quoter.bid().unwrap().cl_ord_id.clone();
+
+        // Bid fills: long 1, at the limit, so only the ask is quoted and it
+        // moves down by the skew even though the move is below the threshold
+        let actions = quoter.on_fill(&bid_id, dec!(1));
+        assert_eq!(quoter.inventory(), dec!(1));
+        assert!(quoter.bid().is_none());
+        assert_eq!(
+            actions,
+            vec![AlgoAction::Amend { order_id: "ASK".into(), limit_price: dec!(100.5), order_qty: None }]
+        );
+
+        let actions = quoter.cancel_all();
+        assert_eq!(actions.len(), 1);
+        assert!(quoter.on_book(&book(dec!(50), dec!(60))).is_empty());
+    }
+
+    #[test]
+    fn test_custom_size_fn() {
+        let mut quoter = Quoter::new("q", "BTC/USD", QuoterConfig::new(dec!(1), dec!(1)))
+            .with_size_fn(|ctx| (dec!(2) - ctx.inventory, dec!(2) + ctx.inventory));
+        quoter.set_inventory(dec!(1));
+        let actions = quoter.on_book(&book(dec!(99), dec!(101)));
+        assert!(matches!(&actions[0], AlgoAction::Place(c) if c.qty == dec!(1) && c.post_only));
+        assert!(matches!(&actions[1], AlgoAction::Place(c) if c.qty == dec!(3)));
+    }
+}
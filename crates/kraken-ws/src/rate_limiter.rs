@@ -5,11 +5,13 @@
 
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 
 use parking_lot::Mutex;
 use tracing::instrument;
 use kraken_types::{
-    RateLimitCategory, RateLimitConfig, RateLimitResult, TokenBucket, TokenBucketConfig,
+    KrakenError, RateLimitCategory, RateLimitConfig, RateLimitResult, TokenBucket, TokenBucketConfig,
+    TradingAction, TradingRateCounter,
 };
 
 /// Thread-safe rate limiter for managing API rate limits
@@ -24,6 +26,8 @@ pub struct KrakenRateLimiter {
     buckets: HashMap<RateLimitCategory, Mutex<TokenBucket>>,
     /// Custom per-symbol buckets (for L3 subscriptions)
     symbol_buckets: Mutex<HashMap<String, TokenBucket>>,
+    /// Per-pair trading counter (Kraken's order penalty model)
+    trading: Mutex<TradingRateCounter>,
 }
 
 impl Default for KrakenRateLimiter {
@@ -66,8 +70,13 @@ impl KrakenRateLimiter {
             RateLimitCategory::L3Depth1000,
             Mutex::new(config.l3_depth_1000.create_bucket()),
         );
+        buckets.insert(
+            RateLimitCategory::WsSubscribe,
+            Mutex::new(config.ws_subscriptions.create_bucket()),
+        );
 
         Self {
+            trading: Mutex::new(TradingRateCounter::new(config.trading)),
             config,
             buckets,
             symbol_buckets: Mutex::new(HashMap::new()),
@@ -168,6 +177,11 @@ impl KrakenRateLimiter {
         self.try_acquire(RateLimitCategory::WsOrders)
     }
 
+    /// Try to acquire for a WebSocket subscribe request
+    pub fn try_acquire_subscribe(&self) -> RateLimitResult {
+        self.try_acquire(RateLimitCategory::WsSubscribe)
+    }
+
     /// Try to acquire for a connection attempt
     pub fn try_acquire_connection(&self) -> RateLimitResult {
         self.try_acquire(RateLimitCategory::Connection)
@@ -190,12 +204,65 @@ impl KrakenRateLimiter {
         }
     }
 
+    /// Try to record a trading action against its pair's counter
+    pub fn try_acquire_trading(&self, action: &TradingAction) -> RateLimitResult {
+        match self.trading.lock().try_record(action) {
+            Ok(()) => RateLimitResult::Allowed,
+            Err(wait) => RateLimitResult::Limited {
+                wait,
+                category: RateLimitCategory::Trading,
+            },
+        }
+    }
+
+    /// Wait until a trading action fits under its pair's counter, then record it
+    ///
+    /// Fails with [`KrakenError::RateLimited`] as soon as the counter can't
+    /// drain within `max_wait`, instead of sleeping first.
+    #[instrument(skip(self), level = "debug")]
+    pub async fn acquire_trading(&self, action: &TradingAction, max_wait: Duration) -> Result<(), KrakenError> {
+        let deadline = tokio::time::Instant::now() + max_wait;
+        loop {
+            match self.try_acquire_trading(action) {
+                RateLimitResult::Allowed => return Ok(()),
+                RateLimitResult::Limited { wait, .. } => {
+                    let now = tokio::time::Instant::now();
+                    if now + wait > deadline {
+                        return Err(KrakenError::RateLimited { retry_after: wait });
+                    }
+                    tokio::time::sleep(wait).await;
+                }
+            }
+        }
+    }
+
+    /// Current trading counter value for a pair
+    pub fn trading_counter(&self, symbol: &str) -> f64 {
+        self.trading.lock().counter(symbol)
+    }
+
+    /// Trading counter value for a pair after `after` more decay
+    pub fn projected_trading_counter(&self, symbol: &str, after: std::time::Duration) -> f64 {
+        self.trading.lock().projected(symbol, after)
+    }
+
+    /// Record that an order opened, so cancels are charged by its age
+    pub fn trading_order_opened(&self, order_id: &str, symbol: &str) {
+        self.trading.lock().order_opened(order_id, symbol);
+    }
+
+    /// Forget an order that filled or was cancelled outside this limiter
+    pub fn trading_order_closed(&self, order_id: &str) {
+        self.trading.lock().order_closed(order_id);
+    }
+
     /// Reset all rate limiters
     pub fn reset_all(&self) {
         for bucket in self.buckets.values() {
             bucket.lock().reset();
         }
         self.symbol_buckets.lock().clear();
+        self.trading.lock().reset();
     }
 
     /// Reset a specific category
@@ -328,6 +395,35 @@ mod tests {
         assert_eq!(limiter.available(RateLimitCategory::WsOrders), limiter2.available(RateLimitCategory::WsOrders));
     }
 
+    #[test]
+    fn test_trading_counter() {
+        let limiter = KrakenRateLimiter::kraken_defaults();
+        let add = TradingAction::Add { symbol: "BTC/USD".to_string() };
+
+        // Starter tier allows a counter of 60
+        for _ in 0..60 {
+            assert!(limiter.try_acquire_trading(&add).is_allowed());
+        }
+        let result = limiter.try_acquire_trading(&add);
+        assert!(!result.is_allowed());
+        assert!(limiter.trading_counter("BTC/USD") > 59.0);
+        assert!(limiter.projected_trading_counter("BTC/USD", std::time::Duration::from_secs(10)) < 50.5);
+    }
+
+    #[tokio::test]
+    async fn test_acquire_trading_gives_up_past_max_wait() {
+        let limiter = KrakenRateLimiter::kraken_defaults();
+        let add = TradingAction::Add { symbol: "BTC/USD".to_string() };
+        for _ in 0..60 {
+            limiter.acquire_trading(&add, Duration::ZERO).await.unwrap();
+        }
+
+        let err = limiter.acquire_trading(&add, Duration::from_millis(10)).await.unwrap_err();
+        assert!(matches!(err, KrakenError::RateLimited { retry_after } if retry_after > Duration::from_millis(10)));
+        // Nothing was charged for the refused action
+        assert!(limiter.trading_counter("BTC/USD") < 60.5);
+    }
+
     #[tokio::test]
     async fn test_async_acquire() {
         let limiter = KrakenRateLimiter::permissive();
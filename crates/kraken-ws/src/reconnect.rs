@@ -1,7 +1,147 @@
-//! Reconnection configuration with exponential backoff
+//! Reconnection configuration and backoff strategies
+//!
+//! The delay before each reconnect attempt comes from a [`BackoffStrategy`].
+//! [`ReconnectConfig`] itself is the default, exponential one; others are
+//! plugged in with `ConnectionConfig::with_backoff`:
+//!
+//! | Strategy | Delay for attempt n |
+//! |----------|---------------------|
+//! | [`ReconnectConfig`] | `initial * multiplier^(n-1)`, capped, ± jitter |
+//! | [`Fibonacci`] | `initial * fib(n)`, capped |
+//! | [`DecorrelatedJitter`] | random between `base` and 3× the previous delay, capped |
+//! | closure | `Fn(attempt, previous) -> Duration` |
+//!
+//! Some disconnects are only prolonged by quick retries, so whatever the
+//! strategy, the delay is raised to a per-reason minimum:
+//!
+//! | Reason | Minimum delay (default) |
+//! |--------|-------------------------|
+//! | [`DisconnectReason::RateLimited`] | `rate_limit_delay` (15s) |
+//! | [`DisconnectReason::MaintenanceMode`] | `maintenance_delay` (60s) |
+//! | anything else | none |
+//!
+//! With [`ReconnectConfig::with_max_downtime`], reconnecting stops once the
+//! next attempt would start past the budget, measured from the first failure
+//! since the last successful connection.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_ws::{ConnectionConfig, DecorrelatedJitter, ReconnectConfig};
+//! use std::time::Duration;
+//!
+//! let config = ConnectionConfig::new()
+//!     .with_backoff(DecorrelatedJitter::new(Duration::from_millis(100), Duration::from_secs(30)))
+//!     .with_reconnect(ReconnectConfig::new().with_max_downtime(Duration::from_secs(300)));
+//! assert!(config.backoff.is_some());
+//! ```
 
+use crate::events::DisconnectReason;
+use std::sync::Arc;
 use std::time::Duration;
 
+/// Computes the delay before each reconnect attempt
+///
+/// Implemented for every `Fn(u32, Duration) -> Duration + Send + Sync`
+/// closure taking the attempt number and the previous delay.
+pub trait BackoffStrategy: Send + Sync {
+    /// Delay before attempt `attempt` (1-indexed)
+    ///
+    /// `previous` is the delay used before the previous attempt, zero for
+    /// the first attempt after a successful connection.
+    fn delay(&self, attempt: u32, previous: Duration) -> Duration;
+}
+
+impl<F> BackoffStrategy for F
+where
+    F: Fn(u32, Duration) -> Duration + Send + Sync,
+{
+    fn delay(&self, attempt: u32, previous: Duration) -> Duration {
+        self(attempt, previous)
+    }
+}
+
+/// Shared handle to a backoff strategy
+#[derive(Clone)]
+pub struct Backoff(Arc<dyn BackoffStrategy>);
+
+impl Backoff {
+    /// Wrap a strategy
+    pub fn new(strategy: impl BackoffStrategy + 'static) -> Self {
+        Self(Arc::new(strategy))
+    }
+
+    /// Delay before attempt `attempt`
+    pub fn delay(&self, attempt: u32, previous: Duration) -> Duration {
+        self.0.delay(attempt, previous)
+    }
+}
+
+impl std::fmt::Debug for Backoff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Backoff(..)")
+    }
+}
+
+/// Delays growing with the Fibonacci sequence: 1, 1, 2, 3, 5, ... × `initial`
+///
+/// Grows more gently than doubling, so early attempts stay close together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fibonacci {
+    /// Delay unit
+    pub initial: Duration,
+    /// Maximum delay
+    pub max: Duration,
+}
+
+impl Fibonacci {
+    /// Create with a delay unit and cap
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self { initial, max }
+    }
+}
+
+impl BackoffStrategy for Fibonacci {
+    fn delay(&self, attempt: u32, _previous: Duration) -> Duration {
+        let (mut a, mut b) = (1u32, 1u32);
+        for _ in 1..attempt.max(1) {
+            (a, b) = (b, a.saturating_add(b));
+            if self.initial.saturating_mul(a) >= self.max {
+                break;
+            }
+        }
+        self.initial.saturating_mul(a).min(self.max)
+    }
+}
+
+/// "Decorrelated jitter" backoff
+///
+/// Each delay is drawn uniformly between `base` and three times the previous
+/// delay, then capped. Clients that disconnect together drift apart quickly,
+/// which spreads their reconnects out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DecorrelatedJitter {
+    /// Minimum delay
+    pub base: Duration,
+    /// Maximum delay
+    pub max: Duration,
+}
+
+impl DecorrelatedJitter {
+    /// Create with a minimum and maximum delay
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self { base, max }
+    }
+}
+
+impl BackoffStrategy for DecorrelatedJitter {
+    fn delay(&self, _attempt: u32, previous: Duration) -> Duration {
+        let upper = previous.saturating_mul(3).max(self.base);
+        let span = (upper - self.base).as_secs_f64();
+        (self.base + Duration::from_secs_f64(rand::random::<f64>() * span)).min(self.max)
+    }
+}
+
 /// Configuration for automatic reconnection with exponential backoff
 #[derive(Debug, Clone)]
 pub struct ReconnectConfig {
@@ -15,6 +155,12 @@ pub struct ReconnectConfig {
     pub jitter: f64,
     /// Maximum number of reconnection attempts (None = unlimited)
     pub max_attempts: Option<u32>,
+    /// Minimum delay after being disconnected for rate limiting
+    pub rate_limit_delay: Duration,
+    /// Minimum delay after being disconnected for maintenance
+    pub maintenance_delay: Duration,
+    /// Stop reconnecting after this much continuous downtime (None = never)
+    pub max_downtime: Option<Duration>,
 }
 
 impl Default for ReconnectConfig {
@@ -25,6 +171,9 @@ impl Default for ReconnectConfig {
             multiplier: 2.0,
             jitter: 0.2,
             max_attempts: None, // Retry forever
+            rate_limit_delay: Duration::from_secs(15),
+            maintenance_delay: Duration::from_secs(60),
+            max_downtime: None,
         }
     }
 }
@@ -65,6 +214,27 @@ impl ReconnectConfig {
         self
     }
 
+    /// Set the minimum delay after a rate-limit disconnect
+    pub fn with_rate_limit_delay(mut self, delay: Duration) -> Self {
+        self.rate_limit_delay = delay;
+        self
+    }
+
+    /// Set the minimum delay after a maintenance disconnect
+    pub fn with_maintenance_delay(mut self, delay: Duration) -> Self {
+        self.maintenance_delay = delay;
+        self
+    }
+
+    /// Stop reconnecting once downtime would exceed `budget`
+    ///
+    /// The connection then emits `ConnectionEvent::DowntimeExceeded`
+    /// followed by `ConnectionEvent::ReconnectFailed`.
+    pub fn with_max_downtime(mut self, budget: Duration) -> Self {
+        self.max_downtime = Some(budget);
+        self
+    }
+
     /// Disable reconnection
     pub fn disabled() -> Self {
         Self {
@@ -105,6 +275,15 @@ impl ReconnectConfig {
         self.apply_jitter(base)
     }
 
+    /// Minimum delay before reconnecting after `reason`
+    pub fn minimum_delay(&self, reason: Option<&DisconnectReason>) -> Duration {
+        match reason {
+            Some(DisconnectReason::RateLimited) => self.rate_limit_delay,
+            Some(DisconnectReason::MaintenanceMode) => self.maintenance_delay,
+            _ => Duration::ZERO,
+        }
+    }
+
     /// Check if should attempt reconnection
     pub fn should_reconnect(&self, attempt: u32) -> bool {
         match self.max_attempts {
@@ -114,6 +293,12 @@ impl ReconnectConfig {
     }
 }
 
+impl BackoffStrategy for ReconnectConfig {
+    fn delay(&self, attempt: u32, _previous: Duration) -> Duration {
+        self.delay_with_jitter(attempt)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -159,4 +344,59 @@ mod tests {
         let disabled = ReconnectConfig::disabled();
         assert!(!disabled.should_reconnect(0));
     }
+
+    #[test]
+    fn test_reason_minimum_delays() {
+        let config = ReconnectConfig::new().with_maintenance_delay(Duration::from_secs(90));
+
+        assert_eq!(config.minimum_delay(None), Duration::ZERO);
+        assert_eq!(config.minimum_delay(Some(&DisconnectReason::ServerClosed)), Duration::ZERO);
+        assert_eq!(config.minimum_delay(Some(&DisconnectReason::RateLimited)), Duration::from_secs(15));
+        assert_eq!(config.minimum_delay(Some(&DisconnectReason::MaintenanceMode)), Duration::from_secs(90));
+    }
+
+    /// Delays for attempts 1..=n, feeding each delay back as `previous`
+    fn schedule(strategy: &dyn BackoffStrategy, n: u32) -> Vec<u64> {
+        let mut previous = Duration::ZERO;
+        (1..=n)
+            .map(|attempt| {
+                previous = strategy.delay(attempt, previous);
+                previous.as_millis() as u64
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_exponential_schedule() {
+        let config = ReconnectConfig::new()
+            .with_initial_delay(Duration::from_millis(100))
+            .with_max_delay(Duration::from_millis(1000))
+            .with_jitter(0.0);
+        assert_eq!(schedule(&config, 6), vec![100, 200, 400, 800, 1000, 1000]);
+    }
+
+    #[test]
+    fn test_fibonacci_schedule() {
+        let fibonacci = Fibonacci::new(Duration::from_millis(100), Duration::from_millis(1000));
+        assert_eq!(schedule(&fibonacci, 8), vec![100, 100, 200, 300, 500, 800, 1000, 1000]);
+        assert_eq!(fibonacci.delay(u32::MAX, Duration::ZERO), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_decorrelated_jitter_schedule() {
+        let jitter = DecorrelatedJitter::new(Duration::from_millis(100), Duration::from_secs(5));
+        let mut previous = Duration::ZERO;
+        for attempt in 1..=50 {
+            let delay = jitter.delay(attempt, previous);
+            assert!(delay >= Duration::from_millis(100));
+            assert!(delay <= (previous * 3).max(Duration::from_millis(100)).min(Duration::from_secs(5)));
+            previous = delay;
+        }
+    }
+
+    #[test]
+    fn test_closure_schedule() {
+        let linear = |attempt: u32, _previous: Duration| Duration::from_millis(250) * attempt;
+        assert_eq!(schedule(&linear, 4), vec![250, 500, 750, 1000]);
+    }
 }
@@ -0,0 +1,423 @@
+//! Subscription restoration progress
+//!
+//! After every connect the connection resends its subscriptions. A
+//! [`RestorationTracker`] follows each one until the server has answered
+//! every symbol and, for books requested with a snapshot, the snapshot has
+//! been applied, so applications can tell when the connection is back to
+//! where it was:
+//!
+//! | Event | When |
+//! |-------|------|
+//! | [`SubscriptionEvent::Restoring`] | a subscription is resent |
+//! | [`SubscriptionEvent::Restored`] | its symbols are acknowledged and their books re-synced |
+//! | [`SubscriptionEvent::RestoreFailed`] | the server rejected some of its symbols (one event per reason), or the round timed out |
+//! | [`ConnectionEvent::RestorationComplete`] | every subscription has been restored or has failed |
+//!
+//! Subscriptions without symbols (`executions`, `balances`, ...) are
+//! restored once the server acknowledges the channel. A disconnect before
+//! completion abandons the round; the next connect starts a new one.
+//!
+//! A round that outlives its timeout ([`DEFAULT_RESTORE_TIMEOUT`] unless
+//! set with [`RestorationTracker::with_timeout`]) fails whatever is still
+//! outstanding with the error `"timeout"`, so a lost response or a snapshot
+//! that never arrives can't keep [`RestorationTracker::is_restoring`] true.
+
+use crate::events::{ConnectionEvent, Event, SubscriptionEvent};
+use crate::subscription::{BatchResolution, Subscription};
+use kraken_types::Channel;
+use std::collections::{BTreeMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// How long a restoration round may take by default
+pub const DEFAULT_RESTORE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Error reported for subscriptions still outstanding at the deadline
+const TIMEOUT_ERROR: &str = "timeout";
+
+/// How far the current restoration round has got
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestorationProgress {
+    /// Subscriptions being restored
+    pub total: usize,
+    /// Subscriptions restored in full
+    pub restored: usize,
+    /// Subscriptions with at least one rejected symbol
+    pub failed: usize,
+    /// When the round started
+    pub started: Instant,
+}
+
+impl RestorationProgress {
+    /// Subscriptions still waiting for the server or a snapshot
+    pub fn pending(&self) -> usize {
+        self.total.saturating_sub(self.restored + self.failed)
+    }
+
+    /// Returns true once every subscription has been answered
+    pub fn is_complete(&self) -> bool {
+        self.pending() == 0
+    }
+}
+
+/// One subscription being restored
+#[derive(Debug)]
+struct Restoring {
+    channel: Channel,
+    symbols: Vec<String>,
+    /// Symbols the server hasn't answered yet
+    unanswered: HashSet<String>,
+    /// Books acknowledged or not, whose snapshot hasn't been applied yet
+    awaiting_snapshot: HashSet<String>,
+    /// Symbol-less subscription still waiting for its acknowledgement
+    channel_pending: bool,
+    rejected: Vec<(String, String)>,
+    channel_error: Option<String>,
+    done: bool,
+}
+
+impl Restoring {
+    fn is_settled(&self) -> bool {
+        !self.channel_pending && self.unanswered.is_empty() && self.awaiting_snapshot.is_empty()
+    }
+}
+
+/// Follows subscriptions from resend to acknowledgement and re-sync
+#[derive(Debug)]
+pub struct RestorationTracker {
+    subscriptions: Vec<Restoring>,
+    started: Option<Instant>,
+    restored: usize,
+    failed: usize,
+    timeout: Duration,
+}
+
+impl Default for RestorationTracker {
+    fn default() -> Self {
+        Self {
+            subscriptions: Vec::new(),
+            started: None,
+            restored: 0,
+            failed: 0,
+            timeout: DEFAULT_RESTORE_TIMEOUT,
+        }
+    }
+}
+
+impl RestorationTracker {
+    /// Create an idle tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how long a round may take before outstanding subscriptions fail
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Start a round for the subscriptions about to be resent
+    ///
+    /// Returns a `Restoring` event per subscription. An earlier unfinished
+    /// round is abandoned.
+    pub fn begin(&mut self, subscriptions: &[Subscription], now: Instant) -> Vec<Event> {
+        self.subscriptions.clear();
+        self.restored = 0;
+        self.failed = 0;
+        self.started = (!subscriptions.is_empty()).then_some(now);
+        subscriptions
+            .iter()
+            .map(|sub| {
+                let snapshots = sub.channel == Channel::Book && sub.snapshot;
+                self.subscriptions.push(Restoring {
+                    channel: sub.channel,
+                    symbols: sub.symbols.clone(),
+                    unanswered: sub.symbols.iter().cloned().collect(),
+                    awaiting_snapshot: if snapshots {
+                        sub.symbols.iter().cloned().collect()
+                    } else {
+                        HashSet::new()
+                    },
+                    channel_pending: sub.symbols.is_empty(),
+                    rejected: Vec::new(),
+                    channel_error: None,
+                    done: false,
+                });
+                SubscriptionEvent::Restoring {
+                    channel: sub.channel.as_str().to_string(),
+                    symbols: sub.symbols.clone(),
+                }
+                .into()
+            })
+            .collect()
+    }
+
+    /// Record the server's answer for every symbol of a subscription
+    pub fn on_resolution(&mut self, resolution: &BatchResolution) -> Vec<Event> {
+        let answered = |symbol: &String| {
+            resolution.live.contains(symbol) || resolution.rejected.iter().any(|(s, _)| s == symbol)
+        };
+        if let Some(sub) = self
+            .subscriptions
+            .iter_mut()
+            .find(|sub| !sub.done && sub.channel == resolution.channel && sub.unanswered.iter().any(answered))
+        {
+            for symbol in &resolution.live {
+                sub.unanswered.remove(symbol);
+            }
+            for (symbol, reason) in &resolution.rejected {
+                if sub.unanswered.remove(symbol) {
+                    sub.awaiting_snapshot.remove(symbol);
+                    sub.rejected.push((symbol.clone(), reason.clone()));
+                }
+            }
+        }
+        self.settle()
+    }
+
+    /// Record the server's answer for a subscription without symbols
+    pub fn on_channel_response(&mut self, channel: Channel, outcome: Result<(), &str>) -> Vec<Event> {
+        if let Some(sub) = self
+            .subscriptions
+            .iter_mut()
+            .find(|sub| !sub.done && sub.channel == channel && sub.channel_pending)
+        {
+            sub.channel_pending = false;
+            sub.channel_error = outcome.err().map(str::to_string);
+        }
+        self.settle()
+    }
+
+    /// Record an applied book snapshot
+    pub fn on_snapshot(&mut self, symbol: &str) -> Vec<Event> {
+        if self.started.is_none() {
+            return Vec::new();
+        }
+        for sub in self.subscriptions.iter_mut().filter(|sub| !sub.done) {
+            sub.awaiting_snapshot.remove(symbol);
+        }
+        self.settle()
+    }
+
+    /// Progress of the current or last round (None before the first)
+    pub fn progress(&self) -> Option<RestorationProgress> {
+        Some(RestorationProgress {
+            total: self.subscriptions.len(),
+            restored: self.restored,
+            failed: self.failed,
+            started: self.started?,
+        })
+    }
+
+    /// Returns true while subscriptions are still being restored
+    pub fn is_restoring(&self) -> bool {
+        self.progress().is_some_and(|progress| !progress.is_complete())
+    }
+
+    /// When the current round times out (None when no round is running)
+    pub fn deadline(&self) -> Option<Instant> {
+        self.started.filter(|_| self.is_restoring()).map(|started| started + self.timeout)
+    }
+
+    /// Fail everything still outstanding if the round is past its deadline
+    ///
+    /// Symbols without an answer or a snapshot are reported in a
+    /// `RestoreFailed` event with the error `"timeout"`, followed by
+    /// `RestorationComplete`.
+    pub fn expire(&mut self, now: Instant) -> Vec<Event> {
+        match self.deadline() {
+            Some(deadline) if now >= deadline => {}
+            _ => return Vec::new(),
+        }
+        for sub in self.subscriptions.iter_mut().filter(|sub| !sub.done) {
+            let mut outstanding: Vec<String> = sub
+                .symbols
+                .iter()
+                .filter(|symbol| sub.unanswered.contains(*symbol) || sub.awaiting_snapshot.contains(*symbol))
+                .cloned()
+                .collect();
+            sub.unanswered.clear();
+            sub.awaiting_snapshot.clear();
+            sub.rejected
+                .extend(outstanding.drain(..).map(|symbol| (symbol, TIMEOUT_ERROR.to_string())));
+            if sub.channel_pending {
+                sub.channel_pending = false;
+                sub.channel_error = Some(TIMEOUT_ERROR.to_string());
+            }
+        }
+        self.settle()
+    }
+
+    /// Events for subscriptions that just settled, and for the round
+    fn settle(&mut self) -> Vec<Event> {
+        let Some(started) = self.started else {
+            return Vec::new();
+        };
+        let mut events = Vec::new();
+        let mut settled_any = false;
+        for sub in self.subscriptions.iter_mut().filter(|sub| !sub.done && sub.is_settled()) {
+            sub.done = true;
+            settled_any = true;
+            let channel = sub.channel.as_str().to_string();
+            let live: Vec<String> = sub
+                .symbols
+                .iter()
+                .filter(|symbol| !sub.rejected.iter().any(|(s, _)| s == *symbol))
+                .cloned()
+                .collect();
+            let mut failures: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+            for (symbol, reason) in &sub.rejected {
+                failures.entry(reason).or_default().push(symbol.clone());
+            }
+            if let Some(error) = &sub.channel_error {
+                failures.entry(error).or_default();
+            }
+
+            if failures.is_empty() || !live.is_empty() {
+                events.push(
+                    SubscriptionEvent::Restored {
+                        channel: channel.clone(),
+                        symbols: live,
+                    }
+                    .into(),
+                );
+            }
+            if failures.is_empty() {
+                self.restored += 1;
+            } else {
+                self.failed += 1;
+            }
+            for (error, symbols) in failures {
+                events.push(
+                    SubscriptionEvent::RestoreFailed {
+                        channel: channel.clone(),
+                        symbols,
+                        error: error.to_string(),
+                    }
+                    .into(),
+                );
+            }
+        }
+        if settled_any && self.restored + self.failed == self.subscriptions.len() {
+            events.push(
+                ConnectionEvent::RestorationComplete {
+                    restored: self.restored,
+                    failed: self.failed,
+                    elapsed: started.elapsed(),
+                }
+                .into(),
+            );
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_types::Depth;
+
+    fn kinds(events: &[Event]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match event {
+                Event::Subscription(SubscriptionEvent::Restoring { channel, .. }) => format!("restoring {}", channel),
+                Event::Subscription(SubscriptionEvent::Restored { channel, symbols }) => {
+                    format!("restored {} {:?}", channel, symbols)
+                }
+                Event::Subscription(SubscriptionEvent::RestoreFailed { channel, symbols, error }) => {
+                    format!("failed {} {:?} {}", channel, symbols, error)
+                }
+                Event::Connection(ConnectionEvent::RestorationComplete { restored, failed, .. }) => {
+                    format!("complete {}/{}", restored, failed)
+                }
+                other => format!("{:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_books_restore_after_ack_and_snapshot() {
+        let mut tracker = RestorationTracker::new();
+        let subs = [
+            Subscription::orderbook(["BTC/USD", "ETH/USD"], Depth::D10),
+            Subscription::trade(["BTC/USD", "BAD/USD"]),
+            Subscription::new(Channel::Executions, Vec::<String>::new()),
+        ];
+        let events = tracker.begin(&subs, Instant::now());
+        assert_eq!(kinds(&events), ["restoring book", "restoring trade", "restoring executions"]);
+        assert!(tracker.is_restoring());
+
+        // Acknowledged but not re-synced yet
+        let events = tracker.on_resolution(&BatchResolution {
+            channel: Channel::Book,
+            live: vec!["BTC/USD".to_string(), "ETH/USD".to_string()],
+            rejected: Vec::new(),
+        });
+        assert!(events.is_empty());
+        assert!(tracker.on_snapshot("BTC/USD").is_empty());
+
+        let events = tracker.on_resolution(&BatchResolution {
+            channel: Channel::Trade,
+            live: vec!["BTC/USD".to_string()],
+            rejected: vec![("BAD/USD".to_string(), "Currency pair not supported".to_string())],
+        });
+        assert_eq!(
+            kinds(&events),
+            [
+                "restored trade [\"BTC/USD\"]",
+                "failed trade [\"BAD/USD\"] Currency pair not supported"
+            ]
+        );
+
+        assert_eq!(kinds(&tracker.on_channel_response(Channel::Executions, Ok(()))), ["restored executions []"]);
+        assert_eq!(tracker.progress().unwrap().pending(), 1);
+
+        let events = tracker.on_snapshot("ETH/USD");
+        assert_eq!(kinds(&events), ["restored book [\"BTC/USD\", \"ETH/USD\"]", "complete 2/1"]);
+        assert!(!tracker.is_restoring());
+
+        // Snapshots outside a round change nothing
+        assert!(tracker.on_snapshot("ETH/USD").is_empty());
+    }
+
+    #[test]
+    fn test_round_times_out_outstanding_subscriptions() {
+        let mut tracker = RestorationTracker::new().with_timeout(Duration::from_secs(5));
+        let subs = [
+            Subscription::orderbook(["BTC/USD", "ETH/USD"], Depth::D10),
+            Subscription::trade(["BTC/USD"]),
+            Subscription::new(Channel::Executions, Vec::<String>::new()),
+        ];
+        let start = Instant::now();
+        tracker.begin(&subs, start);
+        assert_eq!(tracker.deadline(), Some(start + Duration::from_secs(5)));
+
+        // Both books acknowledged, one snapshot lost; the trade ack is lost too
+        tracker.on_resolution(&BatchResolution {
+            channel: Channel::Book,
+            live: vec!["BTC/USD".to_string(), "ETH/USD".to_string()],
+            rejected: Vec::new(),
+        });
+        tracker.on_snapshot("BTC/USD");
+        assert!(tracker.expire(start + Duration::from_secs(4)).is_empty());
+        assert!(tracker.is_restoring());
+
+        let events = tracker.expire(start + Duration::from_secs(5));
+        assert_eq!(
+            kinds(&events),
+            [
+                "restored book [\"BTC/USD\"]",
+                "failed book [\"ETH/USD\"] timeout",
+                "failed trade [\"BTC/USD\"] timeout",
+                "failed executions [] timeout",
+                "complete 0/3"
+            ]
+        );
+        assert!(!tracker.is_restoring());
+        assert_eq!(tracker.deadline(), None);
+
+        // Late answers for the abandoned round change nothing
+        assert!(tracker.on_snapshot("ETH/USD").is_empty());
+        assert!(tracker.on_channel_response(Channel::Executions, Ok(())).is_empty());
+    }
+}
@@ -0,0 +1,750 @@
+//! Pre-trade risk limits
+//!
+//! [`RiskManager`] checks order requests against configured limits before
+//! they leave the process. [`TradingClient`](crate::TradingClient) consults
+//! it in [`execute`](crate::TradingClient::execute) and rejects violating
+//! orders locally with a [`RiskViolation`].
+//!
+//! The manager also holds the kill switch: once engaged, every new order and
+//! amend is rejected until it is released. Cancel requests are never blocked.
+//!
+//! Position limits count resting orders as well as filled inventory: a buy
+//! is checked against the position plus every open buy on the asset, a sell
+//! against the position minus every open sell. Orders are tracked with their
+//! remaining quantity from the acknowledgement of the request that placed
+//! them, or from the executions channel. Amends are checked as a change to
+//! the order they modify.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_ws::risk::{OrderIntent, RiskLimits, RiskManager, RiskViolation};
+//! use kraken_types::Side;
+//! use rust_decimal_macros::dec;
+//! use std::time::{Duration, Instant};
+//!
+//! let mut risk = RiskManager::new(
+//!     RiskLimits::new()
+//!         .with_max_order_qty(dec!(1))
+//!         .with_max_orders_per_window(10, Duration::from_secs(1)),
+//! );
+//!
+//! let order = OrderIntent::new("BTC/USD", Side::Buy, dec!(2), Some(dec!(50000)));
+//! assert!(matches!(
+//!     risk.check(&[order], Instant::now()),
+//!     Err(RiskViolation::OrderTooLarge { .. })
+//! ));
+//! ```
+
+use kraken_types::{
+    AddOrderRequest, AmendOrderRequest, BatchAddRequest, BatchCancelRequest, CancelAllRequest,
+    CancelOnDisconnectRequest, CancelOrderRequest, Decimal, ExecutionData, Side,
+};
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// An order about to be submitted, as seen by the risk checks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderIntent {
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Order side
+    pub side: Side,
+    /// Order quantity in base units
+    pub qty: Decimal,
+    /// Limit price, if any
+    pub price: Option<Decimal>,
+}
+
+impl OrderIntent {
+    /// Create a new order intent
+    pub fn new(symbol: impl Into<String>, side: Side, qty: Decimal, price: Option<Decimal>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            side,
+            qty,
+            price,
+        }
+    }
+
+    /// Base asset of the symbol
+    pub fn base(&self) -> &str {
+        self.symbol.split('/').next().unwrap_or(&self.symbol)
+    }
+
+    /// Signed change in base position if the order fills
+    pub fn position_delta(&self) -> Decimal {
+        match self.side {
+            Side::Buy => self.qty,
+            Side::Sell => -self.qty,
+        }
+    }
+}
+
+/// An amend about to be submitted, as seen by the risk checks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AmendIntent {
+    /// Order being amended
+    pub order_id: String,
+    /// New order quantity, if changed
+    pub qty: Option<Decimal>,
+    /// New limit price, if changed
+    pub price: Option<Decimal>,
+}
+
+/// Requests that may submit or change orders
+///
+/// Implemented for every trading request; only order-creating requests
+/// return intents, and only amends return an amend intent.
+pub trait OrderIntents {
+    /// Orders this request would create
+    fn order_intents(&self) -> Vec<OrderIntent> {
+        Vec::new()
+    }
+
+    /// Order change this request would make
+    fn amend_intent(&self) -> Option<AmendIntent> {
+        None
+    }
+}
+
+impl OrderIntents for AddOrderRequest {
+    fn order_intents(&self) -> Vec<OrderIntent> {
+        let p = &self.params;
+        vec![OrderIntent::new(&p.symbol, p.side, p.order_qty, p.limit_price)]
+    }
+}
+
+impl OrderIntents for BatchAddRequest {
+    fn order_intents(&self) -> Vec<OrderIntent> {
+        self.params
+            .orders
+            .iter()
+            .map(|o| OrderIntent::new(&o.symbol, o.side, o.order_qty, o.limit_price))
+            .collect()
+    }
+}
+
+impl OrderIntents for AmendOrderRequest {
+    fn amend_intent(&self) -> Option<AmendIntent> {
+        let p = &self.params;
+        Some(AmendIntent {
+            order_id: p.order_id.clone(),
+            qty: p.order_qty,
+            price: p.limit_price,
+        })
+    }
+}
+impl OrderIntents for CancelOrderRequest {}
+impl OrderIntents for CancelAllRequest {}
+impl OrderIntents for CancelOnDisconnectRequest {}
+impl OrderIntents for BatchCancelRequest {}
+
+/// A pre-trade limit that an order would break
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RiskViolation {
+    /// Kill switch is engaged
+    #[error("Kill switch engaged: new orders are blocked")]
+    KillSwitchEngaged,
+
+    /// Order quantity above the maximum
+    #[error("Order quantity {qty} exceeds maximum {max}")]
+    OrderTooLarge {
+        /// Requested quantity
+        qty: Decimal,
+        /// Configured maximum
+        max: Decimal,
+    },
+
+    /// Order notional above the symbol's maximum
+    #[error("Order notional {notional} on {symbol} exceeds maximum {max}")]
+    NotionalExceeded {
+        /// Trading pair
+        symbol: String,
+        /// Order notional (qty × price)
+        notional: Decimal,
+        /// Configured maximum
+        max: Decimal,
+    },
+
+    /// No limit price or mark to value the order against a notional limit
+    #[error("No reference price for {symbol} to check notional limit")]
+    NoReferencePrice {
+        /// Trading pair
+        symbol: String,
+    },
+
+    /// Too many open orders
+    #[error("{open} open orders, maximum is {max}")]
+    TooManyOpenOrders {
+        /// Currently open orders
+        open: usize,
+        /// Configured maximum
+        max: usize,
+    },
+
+    /// Resulting position above the asset's maximum
+    #[error("Position in {asset} would be {resulting}, maximum is {max}")]
+    PositionLimit {
+        /// Base asset
+        asset: String,
+        /// Position if the order fills
+        resulting: Decimal,
+        /// Configured maximum (absolute)
+        max: Decimal,
+    },
+
+    /// Order submission rate above the limit
+    #[error("More than {max} orders within {window:?}")]
+    SubmissionRate {
+        /// Configured maximum per window
+        max: u32,
+        /// Window length
+        window: Duration,
+    },
+
+    /// Amend of an order whose symbol and side aren't known
+    #[error("Order {order_id} is not tracked; can't check the amend against limits")]
+    UnknownOrder {
+        /// Order being amended
+        order_id: String,
+    },
+}
+
+/// Configured risk limits
+///
+/// Every limit is optional; an empty `RiskLimits` only enforces the kill switch.
+#[derive(Debug, Clone, Default)]
+pub struct RiskLimits {
+    /// Maximum quantity of a single order
+    pub max_order_qty: Option<Decimal>,
+    /// Maximum notional of a single order, per symbol
+    pub max_notional: HashMap<String, Decimal>,
+    /// Notional limit for symbols without an entry in `max_notional`
+    pub default_max_notional: Option<Decimal>,
+    /// Maximum number of open orders
+    pub max_open_orders: Option<usize>,
+    /// Maximum absolute position, per base asset
+    pub max_position: HashMap<String, Decimal>,
+    /// Maximum order submissions per time window
+    pub max_orders_per_window: Option<(u32, Duration)>,
+}
+
+impl RiskLimits {
+    /// Create limits with nothing configured
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum quantity of a single order
+    pub fn with_max_order_qty(mut self, qty: Decimal) -> Self {
+        self.max_order_qty = Some(qty);
+        self
+    }
+
+    /// Set the maximum notional of a single order on `symbol`
+    pub fn with_max_notional(mut self, symbol: impl Into<String>, notional: Decimal) -> Self {
+        self.max_notional.insert(symbol.into(), notional);
+        self
+    }
+
+    /// Set the notional limit for symbols without their own limit
+    pub fn with_default_max_notional(mut self, notional: Decimal) -> Self {
+        self.default_max_notional = Some(notional);
+        self
+    }
+
+    /// Set the maximum number of open orders
+    pub fn with_max_open_orders(mut self, max: usize) -> Self {
+        self.max_open_orders = Some(max);
+        self
+    }
+
+    /// Set the maximum absolute position in `asset`
+    pub fn with_max_position(mut self, asset: impl Into<String>, qty: Decimal) -> Self {
+        self.max_position.insert(asset.into(), qty);
+        self
+    }
+
+    /// Allow at most `max` order submissions per `window`
+    pub fn with_max_orders_per_window(mut self, max: u32, window: Duration) -> Self {
+        self.max_orders_per_window = Some((max, window));
+        self
+    }
+
+    /// Whether any limit needs the symbol or side of an order
+    fn needs_order_details(&self) -> bool {
+        !self.max_notional.is_empty() || self.default_max_notional.is_some() || !self.max_position.is_empty()
+    }
+
+    fn notional_limit(&self, symbol: &str) -> Option<Decimal> {
+        self.max_notional
+            .get(symbol)
+            .copied()
+            .or(self.default_max_notional)
+    }
+}
+
+/// Pre-trade risk checks and kill switch
+#[derive(Debug, Default)]
+pub struct RiskManager {
+    limits: RiskLimits,
+    /// Open orders, with their remaining quantity when known
+    open_orders: HashMap<String, Option<OrderIntent>>,
+    /// Signed base position per asset
+    positions: HashMap<String, Decimal>,
+    /// Reference prices for orders without a limit price
+    marks: HashMap<String, Decimal>,
+    /// Recent submission times
+    submissions: VecDeque<Instant>,
+    kill_switch: bool,
+}
+
+impl RiskManager {
+    /// Create a risk manager with the given limits
+    pub fn new(limits: RiskLimits) -> Self {
+        Self {
+            limits,
+            ..Default::default()
+        }
+    }
+
+    /// Configured limits
+    pub fn limits(&self) -> &RiskLimits {
+        &self.limits
+    }
+
+    /// Replace the configured limits
+    pub fn set_limits(&mut self, limits: RiskLimits) {
+        self.limits = limits;
+    }
+
+    /// Check orders against every limit
+    ///
+    /// Orders in a batch are checked cumulatively: open orders, positions and
+    /// the submission rate account for the earlier orders in the slice.
+    pub fn check(&self, orders: &[OrderIntent], now: Instant) -> Result<(), RiskViolation> {
+        if orders.is_empty() {
+            return Ok(());
+        }
+        if self.kill_switch {
+            return Err(RiskViolation::KillSwitchEngaged);
+        }
+
+        if let Some((max, window)) = self.limits.max_orders_per_window {
+            let recent = self
+                .submissions
+                .iter()
+                .filter(|t| now.saturating_duration_since(**t) < window)
+                .count();
+            if recent + orders.len() > max as usize {
+                return Err(RiskViolation::SubmissionRate { max, window });
+            }
+        }
+
+        if let Some(max) = self.limits.max_open_orders {
+            let open = self.open_orders.len();
+            if open + orders.len() > max {
+                return Err(RiskViolation::TooManyOpenOrders { open, max });
+            }
+        }
+
+        let mut exposure: HashMap<&str, Exposure> = HashMap::new();
+        for order in orders {
+            self.check_order(order)?;
+
+            let asset = order.base();
+            if let Some(max) = self.limits.max_position.get(asset) {
+                let exposure = exposure.entry(asset).or_insert_with(|| self.exposure(asset, None));
+                exposure.add(order.side, order.qty);
+                exposure.check(asset, *max)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check an amend against every limit
+    ///
+    /// The amended order replaces the original: its new quantity and price
+    /// are checked like a new order's, and position limits count the new
+    /// remaining quantity instead of the old one. Amends of untracked orders
+    /// fail with [`RiskViolation::UnknownOrder`] when a notional or position
+    /// limit is configured.
+    pub fn check_amend(&self, amend: &AmendIntent) -> Result<(), RiskViolation> {
+        if self.kill_switch {
+            return Err(RiskViolation::KillSwitchEngaged);
+        }
+        let Some(Some(original)) = self.open_orders.get(&amend.order_id) else {
+            if let (Some(max), Some(qty)) = (self.limits.max_order_qty, amend.qty) {
+                if qty > max {
+                    return Err(RiskViolation::OrderTooLarge { qty, max });
+                }
+            }
+            if self.limits.needs_order_details() {
+                return Err(RiskViolation::UnknownOrder {
+                    order_id: amend.order_id.clone(),
+                });
+            }
+            return Ok(());
+        };
+
+        let amended = amended(original, amend);
+        self.check_order(&amended)?;
+        let asset = amended.base();
+        if let Some(max) = self.limits.max_position.get(asset) {
+            let mut exposure = self.exposure(asset, Some(&amend.order_id));
+            exposure.add(amended.side, amended.qty);
+            exposure.check(asset, *max)?;
+        }
+        Ok(())
+    }
+
+    /// Per-order limits: quantity and notional
+    fn check_order(&self, order: &OrderIntent) -> Result<(), RiskViolation> {
+        if let Some(max) = self.limits.max_order_qty {
+            if order.qty > max {
+                return Err(RiskViolation::OrderTooLarge { qty: order.qty, max });
+            }
+        }
+
+        if let Some(max) = self.limits.notional_limit(&order.symbol) {
+            let price = order
+                .price
+                .or_else(|| self.mark(&order.symbol))
+                .ok_or_else(|| RiskViolation::NoReferencePrice {
+                    symbol: order.symbol.clone(),
+                })?;
+            let notional = order.qty * price;
+            if notional > max {
+                return Err(RiskViolation::NotionalExceeded {
+                    symbol: order.symbol.clone(),
+                    notional,
+                    max,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Position in `asset` if every resting order on it filled, optionally
+    /// leaving one order out
+    fn exposure(&self, asset: &str, except: Option<&str>) -> Exposure {
+        let position = self.position(asset);
+        let mut exposure = Exposure {
+            long: position,
+            short: position,
+        };
+        for (order_id, order) in &self.open_orders {
+            if let Some(order) = order.as_ref().filter(|o| o.base() == asset) {
+                if except != Some(order_id.as_str()) {
+                    exposure.add(order.side, order.qty);
+                }
+            }
+        }
+        exposure
+    }
+
+    /// Record that `count` orders were submitted at `now`
+    pub fn record_submission(&mut self, count: usize, now: Instant) {
+        if let Some((_, window)) = self.limits.max_orders_per_window {
+            while self
+                .submissions
+                .front()
+                .is_some_and(|t| now.saturating_duration_since(*t) >= window)
+            {
+                self.submissions.pop_front();
+            }
+        }
+        for _ in 0..count {
+            self.submissions.push_back(now);
+        }
+    }
+
+    /// Record an order acknowledged by Kraken as open
+    ///
+    /// Prefer [`order_placed`](Self::order_placed) when the order is known,
+    /// so its resting quantity counts towards position limits.
+    pub fn order_opened(&mut self, order_id: impl Into<String>) {
+        self.open_orders.entry(order_id.into()).or_insert(None);
+    }
+
+    /// Record an order acknowledged by Kraken as open, with what it rests
+    pub fn order_placed(&mut self, order_id: impl Into<String>, order: OrderIntent) {
+        self.open_orders.insert(order_id.into(), Some(order));
+    }
+
+    /// Record an amend acknowledged by Kraken
+    pub fn order_amended(&mut self, amend: &AmendIntent) {
+        if let Some(Some(order)) = self.open_orders.get_mut(&amend.order_id) {
+            *order = amended(order, amend);
+        }
+    }
+
+    /// Record an order that is no longer open
+    pub fn order_closed(&mut self, order_id: &str) {
+        self.open_orders.remove(order_id);
+    }
+
+    /// Forget every open order (e.g., after a successful cancel-all)
+    pub fn orders_cleared(&mut self) {
+        self.open_orders.clear();
+    }
+
+    /// Number of open orders
+    pub fn open_order_count(&self) -> usize {
+        self.open_orders.len()
+    }
+
+    /// Update open orders and positions from an execution
+    ///
+    /// Fills move quantity from the order's resting remainder into the
+    /// position.
+    pub fn handle_execution(&mut self, exec: &ExecutionData) {
+        if let Some(qty) = exec.last_qty {
+            let delta = match exec.side {
+                Side::Buy => qty,
+                Side::Sell => -qty,
+            };
+            let asset = exec.symbol.split('/').next().unwrap_or(&exec.symbol);
+            *self.positions.entry(asset.to_string()).or_insert(Decimal::ZERO) += delta;
+        }
+
+        let status = exec.order_status.as_deref().unwrap_or(&exec.exec_type);
+        match status {
+            "new" | "pending_new" | "partially_filled" => {
+                let remaining = exec
+                    .order_qty
+                    .map(|qty| qty - exec.cum_qty.unwrap_or(Decimal::ZERO))
+                    .map(|qty| OrderIntent::new(&exec.symbol, exec.side, qty.max(Decimal::ZERO), exec.limit_price));
+                let tracked = self.open_orders.entry(exec.order_id.clone()).or_insert(None);
+                match (tracked.as_mut(), remaining, exec.last_qty) {
+                    (_, Some(remaining), _) => *tracked = Some(remaining),
+                    (Some(order), None, Some(filled)) => order.qty = (order.qty - filled).max(Decimal::ZERO),
+                    _ => {}
+                }
+            }
+            "filled" | "canceled" | "cancelled" | "expired" => self.order_closed(&exec.order_id),
+            _ => {}
+        }
+    }
+
+    /// Set the position in an asset (e.g., from a balance snapshot)
+    pub fn set_position(&mut self, asset: impl Into<String>, qty: Decimal) {
+        self.positions.insert(asset.into(), qty);
+    }
+
+    /// Current position in an asset
+    pub fn position(&self, asset: &str) -> Decimal {
+        self.positions.get(asset).copied().unwrap_or(Decimal::ZERO)
+    }
+
+    /// Set the reference price used for orders without a limit price
+    pub fn update_mark(&mut self, symbol: impl Into<String>, price: Decimal) {
+        self.marks.insert(symbol.into(), price);
+    }
+
+    /// Reference price for a symbol
+    pub fn mark(&self, symbol: &str) -> Option<Decimal> {
+        self.marks.get(symbol).copied()
+    }
+
+    /// Block all new orders
+    pub fn engage_kill_switch(&mut self) {
+        if !self.kill_switch {
+            warn!("Risk kill switch engaged");
+        }
+        self.kill_switch = true;
+    }
+
+    /// Allow new orders again
+    pub fn release_kill_switch(&mut self) {
+        self.kill_switch = false;
+    }
+
+    /// Check if the kill switch is engaged
+    pub fn is_killed(&self) -> bool {
+        self.kill_switch
+    }
+}
+
+/// Extremes of a position if resting orders fill
+#[derive(Debug, Clone, Copy)]
+struct Exposure {
+    /// Position plus every buy
+    long: Decimal,
+    /// Position minus every sell
+    short: Decimal,
+}
+
+impl Exposure {
+    fn add(&mut self, side: Side, qty: Decimal) {
+        match side {
+            Side::Buy => self.long += qty,
+            Side::Sell => self.short -= qty,
+        }
+    }
+
+    fn check(&self, asset: &str, max: Decimal) -> Result<(), RiskViolation> {
+        for resulting in [self.long, self.short] {
+            if resulting.abs() > max {
+                return Err(RiskViolation::PositionLimit {
+                    asset: asset.to_string(),
+                    resulting,
+                    max,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `order` with the amend's quantity and price applied
+fn amended(order: &OrderIntent, amend: &AmendIntent) -> OrderIntent {
+    OrderIntent {
+        qty: amend.qty.unwrap_or(order.qty),
+        price: amend.price.or(order.price),
+        ..order.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn buy(qty: Decimal, price: Option<Decimal>) -> OrderIntent {
+        OrderIntent::new("BTC/USD", Side::Buy, qty, price)
+    }
+
+    #[test]
+    fn test_order_limits() {
+        let mut risk = RiskManager::new(
+            RiskLimits::new()
+                .with_max_notional("BTC/USD", dec!(10000))
+                .with_max_position("BTC", dec!(1)),
+        );
+        let now = Instant::now();
+
+        assert!(risk.check(&[buy(dec!(0.1), Some(dec!(50000)))], now).is_ok());
+        assert!(matches!(
+            risk.check(&[buy(dec!(0.3), Some(dec!(50000)))], now),
+            Err(RiskViolation::NotionalExceeded { .. })
+        ));
+        assert_eq!(
+            risk.check(&[buy(dec!(0.1), None)], now),
+            Err(RiskViolation::NoReferencePrice { symbol: "BTC/USD".into() })
+        );
+
+        risk.update_mark("BTC/USD", dec!(1000));
+        risk.set_position("BTC", dec!(0.5));
+        // Two orders together would take the position past the limit
+        let batch = [buy(dec!(0.3), None), buy(dec!(0.3), None)];
+        assert!(matches!(
+            risk.check(&batch, now),
+            Err(RiskViolation::PositionLimit { .. })
+        ));
+        assert!(risk.check(&[OrderIntent::new("BTC/USD", Side::Sell, dec!(1.5), None)], now).is_ok());
+    }
+
+    #[test]
+    fn test_rate_open_orders_and_kill_switch() {
+        let mut risk = RiskManager::new(
+            RiskLimits::new()
+                .with_max_open_orders(2)
+                .with_max_orders_per_window(2, Duration::from_secs(1)),
+        );
+        let start = Instant::now();
+        let order = [buy(dec!(1), Some(dec!(1)))];
+
+        risk.record_submission(2, start);
+        assert!(matches!(
+            risk.check(&order, start),
+            Err(RiskViolation::SubmissionRate { .. })
+        ));
+        assert!(risk.check(&order, start + Duration::from_secs(1)).is_ok());
+
+        risk.order_opened("O1");
+        risk.order_opened("O2");
+        let later = start + Duration::from_secs(5);
+        assert!(matches!(
+            risk.check(&order, later),
+            Err(RiskViolation::TooManyOpenOrders { open: 2, max: 2 })
+        ));
+        risk.order_closed("O1");
+        assert!(risk.check(&order, later).is_ok());
+
+        risk.engage_kill_switch();
+        assert_eq!(risk.check(&order, later), Err(RiskViolation::KillSwitchEngaged));
+        // Requests that create no orders are never blocked
+        assert!(risk.check(&[], later).is_ok());
+        risk.release_kill_switch();
+        assert!(risk.check(&order, later).is_ok());
+    }
+
+    #[test]
+    fn test_resting_orders_count_towards_position() {
+        let mut risk = RiskManager::new(RiskLimits::new().with_max_position("BTC", dec!(1)));
+        let now = Instant::now();
+        risk.order_placed("O1", buy(dec!(0.8), Some(dec!(100))));
+
+        // 0.8 resting + 0.3 new would be 1.1 BTC if both fill
+        assert_eq!(
+            risk.check(&[buy(dec!(0.3), Some(dec!(100)))], now),
+            Err(RiskViolation::PositionLimit {
+                asset: "BTC".into(),
+                resulting: dec!(1.1),
+                max: dec!(1),
+            })
+        );
+        // A sell is checked against the short side only
+        assert!(risk.check(&[OrderIntent::new("BTC/USD", Side::Sell, dec!(1), None)], now).is_ok());
+
+        risk.order_closed("O1");
+        assert!(risk.check(&[buy(dec!(0.3), Some(dec!(100)))], now).is_ok());
+    }
+
+    #[test]
+    fn test_amends_are_checked_against_the_order() {
+        let mut risk = RiskManager::new(
+            RiskLimits::new()
+                .with_max_order_qty(dec!(2))
+                .with_max_position("BTC", dec!(1)),
+        );
+        risk.order_placed("O1", buy(dec!(0.5), Some(dec!(100))));
+        let amend = |qty| AmendIntent {
+            order_id: "O1".into(),
+            qty: Some(qty),
+            price: None,
+        };
+
+        // The new quantity replaces the old one rather than adding to it
+        assert!(risk.check_amend(&amend(dec!(1))).is_ok());
+        assert!(matches!(
+            risk.check_amend(&amend(dec!(1.5))),
+            Err(RiskViolation::PositionLimit { .. })
+        ));
+        assert!(matches!(
+            risk.check_amend(&amend(dec!(3))),
+            Err(RiskViolation::OrderTooLarge { .. })
+        ));
+        assert_eq!(
+            risk.check_amend(&AmendIntent {
+                order_id: "O2".into(),
+                qty: Some(dec!(0.1)),
+                price: None,
+            }),
+            Err(RiskViolation::UnknownOrder { order_id: "O2".into() })
+        );
+
+        risk.order_amended(&amend(dec!(1)));
+        assert!(matches!(
+            risk.check(&[buy(dec!(0.1), Some(dec!(100)))], Instant::now()),
+            Err(RiskViolation::PositionLimit { .. })
+        ));
+
+        risk.engage_kill_switch();
+        assert_eq!(risk.check_amend(&amend(dec!(0.1))), Err(RiskViolation::KillSwitchEngaged));
+    }
+}
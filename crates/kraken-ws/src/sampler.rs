@@ -0,0 +1,161 @@
+//! Fixed-cadence orderbook sampling
+//!
+//! Book updates arrive at whatever rate the market produces them. For analysis
+//! it is often more useful to have a regular time series, so the connection
+//! can take a [`BookSample`] of every synced book on a fixed interval and emit
+//! it as [`MarketEvent::BookSample`](crate::MarketEvent::BookSample).
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_ws::{BookSampler, ConnectionConfig};
+//! use std::time::Duration;
+//!
+//! let config = ConnectionConfig::new()
+//!     .with_book_sampler(BookSampler::new(Duration::from_millis(250)).with_levels(5));
+//! ```
+
+use kraken_book::Orderbook;
+use kraken_types::Decimal;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Summary metrics of one book at one instant
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookSample {
+    /// Wall-clock sample time (Unix milliseconds)
+    pub timestamp_ms: u64,
+    /// Best bid price
+    pub best_bid: Option<Decimal>,
+    /// Best ask price
+    pub best_ask: Option<Decimal>,
+    /// Mid price
+    pub mid: Option<Decimal>,
+    /// Spread (ask - bid)
+    pub spread: Option<Decimal>,
+    /// Number of levels per side summed into the depth figures
+    pub levels: usize,
+    /// Total bid quantity over the top `levels`
+    pub bid_depth: Decimal,
+    /// Total ask quantity over the top `levels`
+    pub ask_depth: Decimal,
+    /// (bid_depth - ask_depth) / (bid_depth + ask_depth), in -1..=1
+    pub imbalance: Option<Decimal>,
+}
+
+/// Configuration for periodic book sampling
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BookSampler {
+    /// Time between samples
+    pub interval: Duration,
+    /// Levels per side included in depth and imbalance
+    pub levels: usize,
+}
+
+impl Default for BookSampler {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1),
+            levels: 5,
+        }
+    }
+}
+
+impl BookSampler {
+    /// Sample every `interval` over the default 5 levels
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            ..Default::default()
+        }
+    }
+
+    /// Set the number of levels per side used for depth and imbalance
+    pub fn with_levels(mut self, levels: usize) -> Self {
+        self.levels = levels.max(1);
+        self
+    }
+
+    /// Take a sample of a book
+    ///
+    /// Returns None unless the book is synced.
+    pub fn sample(&self, book: &Orderbook) -> Option<BookSample> {
+        if !book.is_synced() {
+            return None;
+        }
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        Some(self.sample_at(book, timestamp_ms))
+    }
+
+    /// Take a sample with an explicit timestamp
+    pub fn sample_at(&self, book: &Orderbook, timestamp_ms: u64) -> BookSample {
+        let bid_depth: Decimal = book.top_bids(self.levels).iter().map(|l| l.qty.0).sum();
+        let ask_depth: Decimal = book.top_asks(self.levels).iter().map(|l| l.qty.0).sum();
+        let total = bid_depth + ask_depth;
+        let imbalance = (!total.is_zero()).then(|| (bid_depth - ask_depth) / total);
+
+        BookSample {
+            timestamp_ms,
+            best_bid: book.best_bid().map(|l| l.price.0),
+            best_ask: book.best_ask().map(|l| l.price.0),
+            mid: book.mid_price(),
+            spread: book.spread(),
+            levels: self.levels,
+            bid_depth,
+            ask_depth,
+            imbalance,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_book::compute_checksum;
+    use kraken_types::{BookData, Level};
+    use rust_decimal_macros::dec;
+
+    fn synced_book() -> Orderbook {
+        let bids = vec![
+            Level::new(dec!(100), dec!(3)),
+            Level::new(dec!(99), dec!(1)),
+            Level::new(dec!(98), dec!(5)),
+        ];
+        let asks = vec![Level::new(dec!(101), dec!(1)), Level::new(dec!(102), dec!(1))];
+        let checksum = compute_checksum(&bids, &asks);
+        let mut book = Orderbook::new("BTC/USD");
+        book.apply_book_data(
+            &BookData {
+                symbol: "BTC/USD".to_string(),
+                bids,
+                asks,
+                checksum,
+                timestamp: None,
+            },
+            true,
+        )
+        .unwrap();
+        book
+    }
+
+    #[test]
+    fn test_sample_metrics() {
+        let sample = BookSampler::default().with_levels(2).sample_at(&synced_book(), 1_000);
+
+        assert_eq!(sample.timestamp_ms, 1_000);
+        assert_eq!(sample.mid, Some(dec!(100.5)));
+        assert_eq!(sample.spread, Some(dec!(1)));
+        assert_eq!(sample.bid_depth, dec!(4));
+        assert_eq!(sample.ask_depth, dec!(2));
+        assert_eq!(sample.imbalance.unwrap().round_dp(4), dec!(0.3333));
+    }
+
+    #[test]
+    fn test_unsynced_book_is_not_sampled() {
+        let sampler = BookSampler::default();
+        assert!(sampler.sample(&Orderbook::new("BTC/USD")).is_none());
+        assert!(sampler.sample(&synced_book()).is_some());
+    }
+}
@@ -0,0 +1,691 @@
+//! Scripted transport scenarios for integration tests
+//!
+//! Building realistic test sessions with [`MockTransport`](crate::MockTransport)
+//! means hand-writing every JSON frame and computing checksums by hand. The
+//! [`Scenario`] builder scripts a whole session instead: snapshots and deltas
+//! (with checksums computed from a shadow book), slow-server delays, dropped
+//! connections, malformed frames, and expectations about what the client
+//! sends back. Like the server, the transport answers an `instrument`
+//! subscription with an instrument snapshot (default precision for every
+//! scripted book) ahead of the remaining steps.
+//!
+//! Available with the `test-utils` feature.
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_ws::scenario::Scenario;
+//! use kraken_ws::Transport;
+//! use rust_decimal_macros::dec;
+//! use std::time::Duration;
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let mut transport = Scenario::new()
+//!     .send_status()
+//!     .send_snapshot("BTC/USD", &[(dec!(100), dec!(1))], &[(dec!(101), dec!(2))])
+//!     .delay(Duration::from_millis(5))
+//!     .send_update("BTC/USD", &[(dec!(100), dec!(3))], &[])
+//!     .drop_connection()
+//!     .expect_resubscribe()
+//!     .into_transport("wss://mock.test");
+//!
+//! transport.connect().await.unwrap();
+//! while let Ok(Some(frame)) = transport.recv().await {
+//!     println!("{}", frame);
+//! }
+//!
+//! // Reconnect and restore subscriptions
+//! transport.connect().await.unwrap();
+//! transport.send(r#"{"method":"subscribe","params":{"channel":"book"}}"#).await.unwrap();
+//! let _ = transport.recv().await;
+//!
+//! transport.assert_expectations_met();
+//! # }
+//! ```
+
+use crate::transport::{CloseFrame, Transport, TransportError, TransportStats};
+use async_trait::async_trait;
+use kraken_book::compute_checksum;
+use kraken_types::Level;
+use rust_decimal::Decimal;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::Duration;
+
+/// A single scripted step in a scenario
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScenarioStep {
+    /// Deliver a text frame to the client
+    Frame(String),
+    /// Wait before delivering the next step (simulates a slow server)
+    Delay(Duration),
+    /// Deliver a ping control frame (keeps the connection alive, carries no data)
+    Ping,
+    /// Drop the connection abruptly (recv returns an error)
+    Drop,
+    /// Close the connection gracefully (recv returns `None`)
+    Close,
+    /// Close the connection with a close code and reason
+    CloseWith(u16, String),
+    /// Expect the client to send a subscribe request on the current connection
+    ExpectResubscribe,
+}
+
+/// Shadow copy of a book, used to compute checksums for scripted deltas
+#[derive(Debug, Clone, Default)]
+struct ShadowBook {
+    bids: BTreeMap<Reverse<Decimal>, Decimal>,
+    asks: BTreeMap<Decimal, Decimal>,
+}
+
+impl ShadowBook {
+    fn apply(&mut self, bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) {
+        for &(price, qty) in bids {
+            if qty.is_zero() {
+                self.bids.remove(&Reverse(price));
+            } else {
+                self.bids.insert(Reverse(price), qty);
+            }
+        }
+        for &(price, qty) in asks {
+            if qty.is_zero() {
+                self.asks.remove(&price);
+            } else {
+                self.asks.insert(price, qty);
+            }
+        }
+    }
+
+    fn checksum(&self) -> u32 {
+        let bids: Vec<Level> = self
+            .bids
+            .iter()
+            .take(10)
+            .map(|(Reverse(p), q)| Level::new(*p, *q))
+            .collect();
+        let asks: Vec<Level> = self
+            .asks
+            .iter()
+            .take(10)
+            .map(|(p, q)| Level::new(*p, *q))
+            .collect();
+        compute_checksum(&bids, &asks)
+    }
+}
+
+/// Builder for a scripted server session
+///
+/// Steps are played back in order by the [`ScenarioTransport`] returned from
+/// [`Scenario::into_transport`].
+#[derive(Debug, Clone, Default)]
+pub struct Scenario {
+    steps: Vec<ScenarioStep>,
+    books: HashMap<String, ShadowBook>,
+    next_req_id: u64,
+}
+
+impl Scenario {
+    /// Create an empty scenario
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send the status message Kraken emits on connect
+    pub fn send_status(self) -> Self {
+        self.send_raw(fixtures::STATUS_MESSAGE)
+    }
+
+    /// Send a heartbeat
+    pub fn send_heartbeat(self) -> Self {
+        self.send_raw(fixtures::HEARTBEAT_MESSAGE)
+    }
+
+    /// Send a successful subscribe acknowledgement
+    pub fn send_subscribe_ack(mut self, channel: &str, symbol: &str) -> Self {
+        self.next_req_id += 1;
+        let frame = fixtures::subscribe_ack(channel, symbol, self.next_req_id);
+        self.send_raw(frame)
+    }
+
+    /// Send a subscribe rejection
+    pub fn send_subscribe_error(self, error: &str) -> Self {
+        self.send_raw(fixtures::subscribe_error(error))
+    }
+
+    /// Send a book snapshot, replacing the shadow book for `symbol`
+    ///
+    /// The checksum is computed from the supplied levels.
+    pub fn send_snapshot(
+        mut self,
+        symbol: &str,
+        bids: &[(Decimal, Decimal)],
+        asks: &[(Decimal, Decimal)],
+    ) -> Self {
+        let book = self.books.entry(symbol.to_string()).or_default();
+        *book = ShadowBook::default();
+        book.apply(bids, asks);
+        let checksum = book.checksum();
+        self.send_raw(fixtures::book_message(symbol, "snapshot", bids, asks, checksum))
+    }
+
+    /// Send a book delta for `symbol`
+    ///
+    /// The checksum reflects the shadow book after the delta is applied, so a
+    /// correct client stays in sync. A quantity of zero removes the level.
+    pub fn send_update(
+        mut self,
+        symbol: &str,
+        bids: &[(Decimal, Decimal)],
+        asks: &[(Decimal, Decimal)],
+    ) -> Self {
+        let book = self.books.entry(symbol.to_string()).or_default();
+        book.apply(bids, asks);
+        let checksum = book.checksum();
+        self.send_raw(fixtures::book_message(symbol, "update", bids, asks, checksum))
+    }
+
+    /// Send a book delta carrying a deliberately wrong checksum
+    ///
+    /// The shadow book still applies the delta, so later updates carry the
+    /// checksum the server would have sent.
+    pub fn send_corrupt_update(
+        mut self,
+        symbol: &str,
+        bids: &[(Decimal, Decimal)],
+        asks: &[(Decimal, Decimal)],
+    ) -> Self {
+        let book = self.books.entry(symbol.to_string()).or_default();
+        book.apply(bids, asks);
+        let checksum = book.checksum().wrapping_add(1);
+        self.send_raw(fixtures::book_message(symbol, "update", bids, asks, checksum))
+    }
+
+    /// Send a truncated, unparseable frame
+    pub fn send_malformed(self) -> Self {
+        self.send_raw(fixtures::MALFORMED_MESSAGE)
+    }
+
+    /// Send an arbitrary text frame
+    pub fn send_raw(mut self, frame: impl Into<String>) -> Self {
+        self.steps.push(ScenarioStep::Frame(frame.into()));
+        self
+    }
+
+    /// Pause before the next step
+    pub fn delay(mut self, duration: Duration) -> Self {
+        self.steps.push(ScenarioStep::Delay(duration));
+        self
+    }
+
+    /// Send a ping control frame
+    pub fn send_ping(mut self) -> Self {
+        self.steps.push(ScenarioStep::Ping);
+        self
+    }
+
+    /// Drop the connection without a close frame
+    pub fn drop_connection(mut self) -> Self {
+        self.steps.push(ScenarioStep::Drop);
+        self
+    }
+
+    /// Close the connection gracefully
+    pub fn close(mut self) -> Self {
+        self.steps.push(ScenarioStep::Close);
+        self
+    }
+
+    /// Close the connection with a close code and reason
+    pub fn close_with(mut self, code: u16, reason: &str) -> Self {
+        self.steps.push(ScenarioStep::CloseWith(code, reason.to_string()));
+        self
+    }
+
+    /// Expect the client to resubscribe on the current connection
+    ///
+    /// Satisfied if a `subscribe` request was sent since the last `connect()`,
+    /// either before this step is reached or at any point afterwards.
+    pub fn expect_resubscribe(mut self) -> Self {
+        self.steps.push(ScenarioStep::ExpectResubscribe);
+        self
+    }
+
+    /// Get the scripted steps
+    pub fn steps(&self) -> &[ScenarioStep] {
+        &self.steps
+    }
+
+    /// Build a transport that plays back this scenario
+    pub fn into_transport(self, url: impl Into<String>) -> ScenarioTransport {
+        let mut symbols: Vec<String> = self.books.into_keys().collect();
+        symbols.sort();
+        ScenarioTransport {
+            url: url.into(),
+            instrument_snapshot: fixtures::instrument_snapshot(&symbols),
+            replies: VecDeque::new(),
+            connected: false,
+            steps: self.steps.into(),
+            sent_messages: Vec::new(),
+            sent_on_connection: 0,
+            connect_count: 0,
+            pending_resubscribe: false,
+            unmet: Vec::new(),
+            delay_until: None,
+            close_frame: None,
+            last_frame_at: None,
+            stats: TransportStats::default(),
+        }
+    }
+}
+
+/// Transport that plays back a [`Scenario`]
+///
+/// Once the script is exhausted, `recv()` returns
+/// [`TransportError::ConnectionClosed`], like [`MockTransport`](crate::MockTransport).
+pub struct ScenarioTransport {
+    url: String,
+    /// Sent in reply to an instrument subscription
+    instrument_snapshot: String,
+    /// Replies queued ahead of the scripted steps
+    replies: VecDeque<String>,
+    connected: bool,
+    steps: VecDeque<ScenarioStep>,
+    sent_messages: Vec<String>,
+    /// Index into `sent_messages` where the current connection started
+    sent_on_connection: usize,
+    connect_count: u32,
+    pending_resubscribe: bool,
+    unmet: Vec<String>,
+    /// End of an in-progress delay, kept so a cancelled `recv()` resumes it
+    delay_until: Option<tokio::time::Instant>,
+    /// Close frame of the current connection
+    close_frame: Option<CloseFrame>,
+    /// Arrival of the last played frame, pings included
+    last_frame_at: Option<std::time::Instant>,
+    stats: TransportStats,
+}
+
+impl ScenarioTransport {
+    /// All messages sent by the client, across connections
+    pub fn sent_messages(&self) -> &[String] {
+        &self.sent_messages
+    }
+
+    /// Number of successful `connect()` calls
+    pub fn connect_count(&self) -> u32 {
+        self.connect_count
+    }
+
+    /// Check if every step has been played
+    pub fn is_finished(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Get descriptions of expectations that were not met
+    pub fn unmet_expectations(&self) -> Vec<String> {
+        let mut unmet = self.unmet.clone();
+        if self.pending_resubscribe {
+            unmet.push(format!(
+                "expected a subscribe request on connection #{}",
+                self.connect_count
+            ));
+        }
+        unmet
+    }
+
+    /// Panic if any expectation was not met
+    pub fn assert_expectations_met(&self) {
+        let unmet = self.unmet_expectations();
+        assert!(unmet.is_empty(), "unmet scenario expectations: {:?}", unmet);
+    }
+
+    /// Count a frame handed to the client
+    fn received(&mut self, frame: String) -> Result<Option<String>, TransportError> {
+        self.stats.messages_received += 1;
+        self.stats.bytes_received += frame.len() as u64;
+        Ok(Some(frame))
+    }
+
+    fn subscribed_on_connection(&self) -> bool {
+        self.sent_messages[self.sent_on_connection..]
+            .iter()
+            .any(|m| is_subscribe(m))
+    }
+}
+
+fn is_subscribe(message: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(message)
+        .ok()
+        .and_then(|v| v.get("method").and_then(|m| m.as_str()).map(|m| m == "subscribe"))
+        .unwrap_or(false)
+}
+
+#[async_trait]
+impl Transport for ScenarioTransport {
+    async fn connect(&mut self) -> Result<(), TransportError> {
+        if self.pending_resubscribe {
+            self.pending_resubscribe = false;
+            self.unmet.push(format!(
+                "expected a subscribe request on connection #{}",
+                self.connect_count
+            ));
+        }
+        self.connected = true;
+        self.replies.clear();
+        self.close_frame = None;
+        self.connect_count += 1;
+        self.sent_on_connection = self.sent_messages.len();
+        Ok(())
+    }
+
+    async fn send(&mut self, message: &str) -> Result<(), TransportError> {
+        if !self.connected {
+            return Err(TransportError::NotConnected);
+        }
+        if is_subscribe(message) {
+            self.pending_resubscribe = false;
+            if message.contains(r#""channel":"instrument""#) {
+                self.replies.push_back(self.instrument_snapshot.clone());
+            }
+        }
+        self.stats.messages_sent += 1;
+        self.stats.bytes_sent += message.len() as u64;
+        self.sent_messages.push(message.to_string());
+        Ok(())
+    }
+
+    async fn recv(&mut self) -> Result<Option<String>, TransportError> {
+        if !self.connected {
+            return Err(TransportError::NotConnected);
+        }
+        if let Some(reply) = self.replies.pop_front() {
+            return self.received(reply);
+        }
+        if let Some(deadline) = self.delay_until {
+            tokio::time::sleep_until(deadline).await;
+            self.delay_until = None;
+        }
+        while let Some(step) = self.steps.pop_front() {
+            match step {
+                ScenarioStep::Frame(frame) => {
+                    self.last_frame_at = Some(std::time::Instant::now());
+                    return self.received(frame);
+                }
+                ScenarioStep::Ping => self.last_frame_at = Some(std::time::Instant::now()),
+                ScenarioStep::Delay(duration) => {
+                    let deadline = tokio::time::Instant::now() + duration;
+                    self.delay_until = Some(deadline);
+                    tokio::time::sleep_until(deadline).await;
+                    self.delay_until = None;
+                }
+                ScenarioStep::Drop => {
+                    self.connected = false;
+                    return Err(TransportError::ConnectionClosed);
+                }
+                ScenarioStep::Close => {
+                    self.connected = false;
+                    return Ok(None);
+                }
+                ScenarioStep::CloseWith(code, reason) => {
+                    self.connected = false;
+                    self.close_frame = Some(CloseFrame { code, reason });
+                    return Ok(None);
+                }
+                ScenarioStep::ExpectResubscribe => {
+                    if !self.subscribed_on_connection() {
+                        self.pending_resubscribe = true;
+                    }
+                }
+            }
+        }
+        Err(TransportError::ConnectionClosed)
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        self.connected = false;
+        Ok(())
+    }
+
+    fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    fn endpoint(&self) -> &str {
+        &self.url
+    }
+
+    fn stats(&self) -> TransportStats {
+        self.stats
+    }
+
+    fn close_frame(&self) -> Option<CloseFrame> {
+        self.close_frame.clone()
+    }
+
+    fn last_frame_at(&self) -> Option<std::time::Instant> {
+        self.last_frame_at
+    }
+}
+
+/// Canned server frames captured from Kraken API v2
+pub mod fixtures {
+    use kraken_book::compute_checksum;
+    use kraken_types::Level;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
+
+    /// Status message sent by Kraken on connection
+    pub const STATUS_MESSAGE: &str = r#"{"channel":"status","type":"update","data":[{"api_version":"v2","connection_id":12345678901234567890,"system":"online","version":"2.0.10"}]}"#;
+
+    /// Heartbeat message
+    pub const HEARTBEAT_MESSAGE: &str = r#"{"channel":"heartbeat"}"#;
+
+    /// Truncated book frame that fails to parse
+    pub const MALFORMED_MESSAGE: &str = r#"{"channel":"book","type":"update","data":[{"symbol":"BTC/USD","bids":[{"price":"#;
+
+    /// Successful subscribe acknowledgement
+    pub fn subscribe_ack(channel: &str, symbol: &str, req_id: u64) -> String {
+        format!(
+            r#"{{"method":"subscribe","req_id":{},"result":{{"channel":"{}","snapshot":true,"symbol":"{}"}},"success":true,"time_in":"2025-12-21T12:28:24.000000Z","time_out":"2025-12-21T12:28:24.001000Z"}}"#,
+            req_id, channel, symbol
+        )
+    }
+
+    /// Failed subscribe response
+    pub fn subscribe_error(error: &str) -> String {
+        format!(
+            r#"{{"method":"subscribe","error":"{}","success":false,"time_in":"2025-12-21T12:28:24.000000Z","time_out":"2025-12-21T12:28:24.001000Z"}}"#,
+            error
+        )
+    }
+
+    /// Instrument snapshot giving each symbol the default checksum precision
+    pub fn instrument_snapshot(symbols: &[String]) -> String {
+        let pairs = symbols
+            .iter()
+            .map(|symbol| format!(r#"{{"symbol":"{}","price_precision":1,"qty_precision":8}}"#, symbol))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!(
+            r#"{{"channel":"instrument","type":"snapshot","data":{{"assets":[],"pairs":[{}]}}}}"#,
+            pairs
+        )
+    }
+
+    /// Book frame (`kind` is `"snapshot"` or `"update"`) with an explicit checksum
+    pub fn book_message(
+        symbol: &str,
+        kind: &str,
+        bids: &[(Decimal, Decimal)],
+        asks: &[(Decimal, Decimal)],
+        checksum: u32,
+    ) -> String {
+        format!(
+            r#"{{"channel":"book","type":"{}","data":[{{"symbol":"{}","bids":[{}],"asks":[{}],"checksum":{},"timestamp":"2025-12-21T12:28:24.113018Z"}}]}}"#,
+            kind,
+            symbol,
+            levels_json(bids),
+            levels_json(asks),
+            checksum
+        )
+    }
+
+    fn levels_json(levels: &[(Decimal, Decimal)]) -> String {
+        levels
+            .iter()
+            .map(|(p, q)| format!(r#"{{"price":{},"qty":{}}}"#, p, q))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// BTC/USD bid levels used by the canned snapshot
+    pub fn btc_usd_bids() -> Vec<(Decimal, Decimal)> {
+        vec![
+            (dec!(100000.0), dec!(1.5)),
+            (dec!(99999.0), dec!(2.0)),
+            (dec!(99998.0), dec!(0.5)),
+        ]
+    }
+
+    /// BTC/USD ask levels used by the canned snapshot
+    pub fn btc_usd_asks() -> Vec<(Decimal, Decimal)> {
+        vec![
+            (dec!(100001.0), dec!(1.0)),
+            (dec!(100002.0), dec!(2.5)),
+            (dec!(100003.0), dec!(0.75)),
+        ]
+    }
+
+    /// Canned BTC/USD book snapshot with a valid checksum
+    pub fn btc_usd_snapshot() -> String {
+        let bids = btc_usd_bids();
+        let asks = btc_usd_asks();
+        let checksum = compute_checksum(&to_levels(&bids), &to_levels(&asks));
+        book_message("BTC/USD", "snapshot", &bids, &asks, checksum)
+    }
+
+    fn to_levels(levels: &[(Decimal, Decimal)]) -> Vec<Level> {
+        levels.iter().map(|&(p, q)| Level::new(p, q)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_book::{Orderbook, OrderbookState};
+    use kraken_types::WsMessage;
+    use rust_decimal_macros::dec;
+
+    fn apply(book: &mut Orderbook, frame: &str) -> bool {
+        match WsMessage::parse(frame).unwrap() {
+            WsMessage::Book(msg) => {
+                let is_snapshot = msg.msg_type == "snapshot";
+                book.apply_book_data(&msg.data[0], is_snapshot).is_ok()
+            }
+            other => panic!("expected book message, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scenario_checksums_track_shadow_book() {
+        let mut transport = Scenario::new()
+            .send_snapshot("BTC/USD", &fixtures::btc_usd_bids(), &fixtures::btc_usd_asks())
+            .send_update("BTC/USD", &[(dec!(100000.0), dec!(2.0))], &[])
+            .send_update("BTC/USD", &[(dec!(99998.0), dec!(0))], &[(dec!(100004.0), dec!(1))])
+            .send_corrupt_update("BTC/USD", &[(dec!(99997.0), dec!(1))], &[])
+            .into_transport("wss://mock.test");
+        transport.connect().await.unwrap();
+
+        let mut book = Orderbook::new("BTC/USD");
+        for _ in 0..3 {
+            let frame = transport.recv().await.unwrap().unwrap();
+            assert!(apply(&mut book, &frame));
+        }
+        assert_eq!(book.state(), OrderbookState::Synced);
+        assert_eq!(book.bid_count(), 2);
+
+        let frame = transport.recv().await.unwrap().unwrap();
+        assert!(!apply(&mut book, &frame));
+        assert!(transport.is_finished());
+    }
+
+    #[tokio::test]
+    async fn test_scenario_drop_and_resubscribe() {
+        let mut transport = Scenario::new()
+            .send_status()
+            .drop_connection()
+            .send_status()
+            .expect_resubscribe()
+            .send_heartbeat()
+            .into_transport("wss://mock.test");
+
+        transport.connect().await.unwrap();
+        assert!(transport.recv().await.unwrap().is_some());
+        assert!(matches!(
+            transport.recv().await,
+            Err(TransportError::ConnectionClosed)
+        ));
+        assert!(!transport.is_connected());
+
+        transport.connect().await.unwrap();
+        assert!(transport.recv().await.unwrap().is_some());
+        // Subscription is sent after the expectation step has been reached
+        let frame = transport.recv().await.unwrap().unwrap();
+        assert!(frame.contains("heartbeat"));
+        assert_eq!(transport.unmet_expectations().len(), 1);
+
+        transport
+            .send(r#"{"method":"subscribe","params":{"channel":"book"}}"#)
+            .await
+            .unwrap();
+        transport.assert_expectations_met();
+        assert_eq!(transport.connect_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_scenario_missing_resubscribe_is_reported() {
+        let mut transport = Scenario::new()
+            .expect_resubscribe()
+            .into_transport("wss://mock.test");
+        transport.connect().await.unwrap();
+        assert!(transport.recv().await.is_err());
+        transport.connect().await.unwrap();
+
+        assert_eq!(transport.unmet_expectations().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_scenario_malformed_and_close() {
+        let mut transport = Scenario::new()
+            .send_malformed()
+            .delay(Duration::from_millis(1))
+            .close()
+            .into_transport("wss://mock.test");
+        transport.connect().await.unwrap();
+
+        let frame = transport.recv().await.unwrap().unwrap();
+        assert!(WsMessage::parse(&frame).is_err());
+        assert!(transport.recv().await.unwrap().is_none());
+        assert!(!transport.is_connected());
+    }
+
+    #[test]
+    fn test_fixtures_parse() {
+        assert!(matches!(
+            WsMessage::parse(fixtures::STATUS_MESSAGE).unwrap(),
+            WsMessage::Status(_)
+        ));
+        assert!(matches!(
+            WsMessage::parse(fixtures::HEARTBEAT_MESSAGE).unwrap(),
+            WsMessage::Heartbeat
+        ));
+        assert!(matches!(
+            WsMessage::parse(&fixtures::subscribe_ack("book", "BTC/USD", 1)).unwrap(),
+            WsMessage::Method(_)
+        ));
+
+        let mut book = Orderbook::new("BTC/USD");
+        assert!(apply(&mut book, &fixtures::btc_usd_snapshot()));
+    }
+}
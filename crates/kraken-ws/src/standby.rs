@@ -0,0 +1,271 @@
+//! Warm standby connection for fast failover
+//!
+//! With [`ConnectionConfig::with_standby`](crate::ConnectionConfig::with_standby)
+//! the connection keeps a second socket open next to the primary one. It has
+//! completed the handshake but carries no subscriptions. When the primary
+//! dies, the standby is promoted and resubscribed right away, skipping the
+//! reconnect backoff, and a new standby is opened behind it.
+
+use crate::connection::handshake;
+use crate::transport::Transport;
+use kraken_types::{KrakenError, StatusData};
+use std::future::Future;
+use std::pin::Pin;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, warn};
+
+/// Delay before reopening a standby that failed or dropped
+const STANDBY_RETRY_DELAY: Duration = Duration::from_secs(5);
+
+/// A standby that finished its handshake
+pub(crate) struct ReadyStandby {
+    /// Connected transport, no subscriptions sent yet
+    pub transport: Box<dyn Transport>,
+    /// Status message received on connect
+    pub status: StatusData,
+}
+
+type Handshaking = Pin<Box<dyn Future<Output = Result<ReadyStandby, KrakenError>> + Send>>;
+
+enum State {
+    /// Standby mode is off
+    Disabled,
+    /// Needs a transport to be opened
+    Idle,
+    /// Handshake in progress
+    Connecting(Handshaking),
+    /// Connected and waiting to be promoted
+    Ready(ReadyStandby),
+    /// Waiting before the next attempt
+    Backoff(Instant),
+}
+
+/// Standby slot driven from the connection's message loop
+pub(crate) struct Standby {
+    state: State,
+}
+
+impl Standby {
+    /// A slot that opens standbys when `enabled`
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            state: if enabled { State::Idle } else { State::Disabled },
+        }
+    }
+
+    /// Whether a transport should be opened with [`start`](Self::start)
+    pub fn needs_transport(&self) -> bool {
+        matches!(self.state, State::Idle)
+    }
+
+    /// Begin the handshake on a fresh transport
+    pub fn start(&mut self, mut transport: Box<dyn Transport>, connect_timeout: Duration) {
+        self.state = State::Connecting(Box::pin(async move {
+            let url = transport.endpoint().to_string();
+            let status = handshake(&mut transport, &url, connect_timeout).await?;
+            Ok(ReadyStandby { transport, status })
+        }));
+    }
+
+    /// Take the ready standby, leaving the slot to be refilled
+    pub fn take_ready(&mut self) -> Option<ReadyStandby> {
+        match std::mem::replace(&mut self.state, State::Idle) {
+            State::Ready(ready) => Some(ready),
+            other => {
+                self.state = other;
+                None
+            }
+        }
+    }
+
+    /// Advance the handshake and keep a ready standby drained
+    ///
+    /// Completes when the slot needs a new transport. Cancel safe: progress
+    /// is kept in the slot.
+    pub async fn run(&mut self) {
+        loop {
+            match &mut self.state {
+                State::Disabled => std::future::pending::<()>().await,
+                State::Idle => return,
+                State::Connecting(handshaking) => match handshaking.as_mut().await {
+                    Ok(ready) => {
+                        debug!("Standby connected to {}", ready.transport.endpoint());
+                        self.state = State::Ready(ready);
+                    }
+                    Err(e) => {
+                        warn!("Standby connection failed: {}", e);
+                        self.state = State::Backoff(Instant::now() + STANDBY_RETRY_DELAY);
+                    }
+                },
+                // Nothing is subscribed, so this only sees heartbeats and status
+                State::Ready(ready) => match ready.transport.recv().await {
+                    Ok(Some(_)) => {}
+                    Ok(None) | Err(_) => {
+                        warn!("Standby connection to {} dropped", ready.transport.endpoint());
+                        self.state = State::Backoff(Instant::now() + STANDBY_RETRY_DELAY);
+                    }
+                },
+                State::Backoff(until) => {
+                    tokio::time::sleep_until(*until).await;
+                    self.state = State::Idle;
+                }
+            }
+        }
+    }
+
+    /// Close a ready standby
+    pub async fn close(&mut self) {
+        if let Some(mut ready) = self.take_ready() {
+            let _ = ready.transport.close().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scenario::Scenario;
+    use crate::{ConnectionConfig, Endpoint, KrakenConnection, ReconnectConfig};
+    use rust_decimal_macros::dec;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+    fn transport(scenario: Scenario) -> Box<dyn Transport> {
+        Box::new(scenario.into_transport(Endpoint::PublicBeta.url()))
+    }
+
+    /// Drive the slot until it asks for a transport, or for at most `limit`
+    async fn run_for(standby: &mut Standby, limit: Duration) -> bool {
+        tokio::time::timeout(limit, standby.run()).await.is_ok()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_ready_standby_is_promoted_once() {
+        let mut standby = Standby::new(true);
+        assert!(standby.needs_transport());
+        assert!(standby.take_ready().is_none());
+
+        let idle = Scenario::new().send_status().delay(Duration::from_secs(60));
+        standby.start(transport(idle), CONNECT_TIMEOUT);
+        assert!(!standby.needs_transport());
+        // Connects, then sits on the idle socket
+        assert!(!run_for(&mut standby, Duration::from_secs(1)).await);
+
+        let ready = standby.take_ready().expect("standby should be ready");
+        assert_eq!(ready.status.connection_id, 12345678901234567890);
+        assert_eq!(ready.transport.endpoint(), Endpoint::PublicBeta.url());
+        assert!(ready.transport.is_connected());
+
+        // Promotion empties the slot so a new standby opens behind it
+        assert!(standby.take_ready().is_none());
+        assert!(standby.needs_transport());
+        assert!(run_for(&mut standby, Duration::from_millis(1)).await);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_standby_backs_off_before_reopening() {
+        let dropping = || Scenario::new().send_status().delay(Duration::from_secs(2)).drop_connection();
+        let mut standby = Standby::new(true);
+
+        // Closes before sending a status message
+        standby.start(transport(Scenario::new().send_heartbeat().close()), CONNECT_TIMEOUT);
+        let started = Instant::now();
+        assert!(run_for(&mut standby, Duration::from_secs(60)).await);
+        assert!(started.elapsed() >= STANDBY_RETRY_DELAY);
+        assert!(standby.needs_transport());
+
+        // Connects, then drops while waiting to be promoted
+        standby.start(transport(dropping()), CONNECT_TIMEOUT);
+        assert!(!run_for(&mut standby, Duration::from_secs(1)).await);
+        assert!(standby.take_ready().is_some_and(|ready| ready.transport.is_connected()));
+
+        standby.start(transport(dropping()), CONNECT_TIMEOUT);
+        assert!(!run_for(&mut standby, Duration::from_secs(3)).await);
+        // A dropped standby is never handed out
+        assert!(standby.take_ready().is_none());
+        assert!(!standby.needs_transport());
+        assert!(run_for(&mut standby, STANDBY_RETRY_DELAY).await);
+        assert!(standby.needs_transport());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_disabled_slot_never_asks_for_a_transport() {
+        let mut standby = Standby::new(false);
+        assert!(!standby.needs_transport());
+        assert!(!run_for(&mut standby, Duration::from_secs(60)).await);
+        assert!(standby.take_ready().is_none());
+        standby.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_to_primary_after_promoted_standby_dies() {
+        let urls = Arc::new(parking_lot::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&urls);
+        let opened = AtomicUsize::new(0);
+        let config = ConnectionConfig::new()
+            .without_circuit_breaker()
+            .with_reconnect(ReconnectConfig::new().with_initial_delay(Duration::from_millis(1)))
+            .with_standby_endpoint(Endpoint::PublicBeta)
+            .with_transport_factory(move |url| {
+                seen.lock().push(url.to_string());
+                let book = |bid| {
+                    Scenario::new().send_status().send_snapshot(
+                        "BTC/USD",
+                        &[(bid, dec!(1))],
+                        &[(bid + dec!(1), dec!(2))],
+                    )
+                };
+                let scenario = match opened.fetch_add(1, Ordering::Relaxed) {
+                    // Primary dies shortly after its snapshot
+                    0 => book(dec!(100)).delay(Duration::from_millis(50)).close(),
+                    // Standby is promoted, then dies too
+                    1 => Scenario::new()
+                        .send_status()
+                        .delay(Duration::from_millis(100))
+                        .send_snapshot("BTC/USD", &[(dec!(200), dec!(1))], &[(dec!(201), dec!(2))])
+                        .delay(Duration::from_millis(50))
+                        .drop_connection(),
+                    // Its replacement never becomes ready, so the feed goes
+                    // back to the primary endpoint through a reconnect
+                    2 => Scenario::new().delay(Duration::from_secs(10)),
+                    3 => book(dec!(300)).delay(Duration::from_millis(200)).close(),
+                    _ => Scenario::new().delay(Duration::from_millis(200)),
+                };
+                Box::new(scenario.into_transport(url))
+            });
+        let conn = Arc::new(KrakenConnection::new(config));
+        conn.subscribe_orderbook(["BTC/USD"]);
+        let runner = Arc::clone(&conn);
+        let run = tokio::spawn(async move { runner.connect_and_run().await });
+
+        let best_bid =
+            || conn.orderbook("BTC/USD").and_then(|book| book.best_bid().map(|l| l.price));
+        let mut bids = Vec::new();
+        for _ in 0..200 {
+            if let Some(bid) = best_bid() {
+                if bids.last() != Some(&bid) {
+                    bids.push(bid);
+                }
+                if bid == dec!(300) {
+                    break;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+        assert_eq!(bids, vec![dec!(100), dec!(200), dec!(300)]);
+        assert_eq!(
+            urls.lock()[..4],
+            [
+                Endpoint::Public.url(),
+                Endpoint::PublicBeta.url(),
+                Endpoint::PublicBeta.url(),
+                Endpoint::Public.url(),
+            ]
+        );
+
+        conn.shutdown();
+        let _ = run.await;
+    }
+}
@@ -1,7 +1,31 @@
 //! Subscription management
 
-use kraken_types::{Channel, Depth, SubscribeParams, SubscribeRequest};
-use std::collections::HashSet;
+use kraken_types::{
+    Channel, Depth, KrakenError, KrakenErrorCode, L3Depth, OhlcInterval, SubscribeParams, SubscribeRequest, Symbol,
+};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+/// Default maximum number of symbols sent in a single subscribe request
+///
+/// Large symbol lists are split into several requests of at most this size.
+pub const DEFAULT_MAX_SYMBOLS_PER_REQUEST: usize = 50;
+
+/// How long a WebSocket token can be used after it was issued
+///
+/// Kraken only accepts a token for new connections within 15 minutes of
+/// `GetWebSocketsToken`; a live subscription keeps working past that, but a
+/// reconnect needs a fresh token.
+pub const TOKEN_LIFETIME: Duration = Duration::from_secs(15 * 60);
+
+/// Convert symbol arguments into the wire representation
+fn symbol_strings(symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> Vec<String> {
+    symbols.into_iter().map(|s| s.into().into_string()).collect()
+}
 
 /// Active subscription tracker
 #[derive(Debug, Clone)]
@@ -14,46 +38,72 @@ pub struct Subscription {
     pub depth: Option<Depth>,
     /// Request snapshot on subscribe
     pub snapshot: bool,
+    /// Authentication token (level3 and private channels)
+    pub token: Option<String>,
+    /// When `token` stops being accepted for new connections
+    pub token_expires_at: Option<Instant>,
+    /// Candle interval in minutes (ohlc channel only)
+    pub interval: Option<u32>,
 }
 
 impl Subscription {
     /// Create a new subscription
-    pub fn new(channel: Channel, symbols: Vec<String>) -> Self {
+    pub fn new(channel: Channel, symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> Self {
         Self {
             channel,
-            symbols,
+            symbols: symbol_strings(symbols),
             depth: None,
             snapshot: true,
+            token: None,
+            token_expires_at: None,
+            interval: None,
         }
     }
 
     /// Create an orderbook subscription
-    pub fn orderbook(symbols: Vec<String>, depth: Depth) -> Self {
+    pub fn orderbook(symbols: impl IntoIterator<Item = impl Into<Symbol>>, depth: Depth) -> Self {
         Self {
             channel: Channel::Book,
-            symbols,
+            symbols: symbol_strings(symbols),
             depth: Some(depth),
             snapshot: true,
+            token: None,
+            token_expires_at: None,
+            interval: None,
         }
     }
 
     /// Create a ticker subscription
-    pub fn ticker(symbols: Vec<String>) -> Self {
+    pub fn ticker(symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> Self {
         Self {
             channel: Channel::Ticker,
-            symbols,
+            symbols: symbol_strings(symbols),
             depth: None,
             snapshot: true,
+            token: None,
+            token_expires_at: None,
+            interval: None,
         }
     }
 
     /// Create a trade subscription
-    pub fn trade(symbols: Vec<String>) -> Self {
+    pub fn trade(symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> Self {
         Self {
             channel: Channel::Trade,
-            symbols,
+            symbols: symbol_strings(symbols),
             depth: None,
             snapshot: true,
+            token: None,
+            token_expires_at: None,
+            interval: None,
+        }
+    }
+
+    /// Create an OHLC (candle) subscription
+    pub fn ohlc(symbols: impl IntoIterator<Item = impl Into<Symbol>>, interval: OhlcInterval) -> Self {
+        Self {
+            interval: Some(interval as u32),
+            ..Self::new(Channel::Ohlc, symbols)
         }
     }
 
@@ -61,18 +111,76 @@ impl Subscription {
     ///
     /// Note: L3 requires connection to the Level3 endpoint (wss://ws-l3.kraken.com/v2)
     /// and special access permissions.
-    pub fn level3(symbols: Vec<String>) -> Self {
+    pub fn level3(symbols: impl IntoIterator<Item = impl Into<Symbol>>) -> Self {
         Self {
             channel: Channel::Level3,
-            symbols,
+            symbols: symbol_strings(symbols),
             depth: None,
             snapshot: true,
+            token: None,
+            token_expires_at: None,
+            interval: None,
+        }
+    }
+
+    /// Create an authenticated L3 subscription at the given depth
+    ///
+    /// Each symbol draws from the depth's L3 rate counter when the connection
+    /// has a rate limiter. Subscribe through
+    /// [`KrakenConnection::subscribe_level3`](crate::KrakenConnection::subscribe_level3)
+    /// to have the endpoint checked.
+    pub fn level3_with_token(
+        symbols: impl IntoIterator<Item = impl Into<Symbol>>,
+        depth: L3Depth,
+        token: impl Into<String>,
+    ) -> Self {
+        Self {
+            depth: Some(depth.into()),
+            token: Some(token.into()),
+            token_expires_at: Some(Instant::now() + TOKEN_LIFETIME),
+            ..Self::level3(symbols)
+        }
+    }
+
+    /// Set when the token stops being accepted (default: [`TOKEN_LIFETIME`]
+    /// from creating the subscription)
+    pub fn with_token_expiry(mut self, expires_at: Instant) -> Self {
+        self.token_expires_at = Some(expires_at);
+        self
+    }
+
+    /// Whether the token can no longer be sent on a new connection
+    pub fn token_expired(&self, now: Instant) -> bool {
+        self.token.is_some() && self.token_expires_at.is_some_and(|at| at <= now)
+    }
+
+    /// Request or skip the initial snapshot
+    ///
+    /// Book subscriptions only skip it on the first request: once the
+    /// connection drops the local book is stale, so reconnects ask for a
+    /// snapshot again.
+    pub fn with_snapshot(mut self, snapshot: bool) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
+    /// Split into subscriptions of at most `max_symbols` symbols each
+    pub fn chunks(&self, max_symbols: usize) -> Vec<Subscription> {
+        if self.symbols.is_empty() {
+            return vec![self.clone()];
         }
+        self.symbols
+            .chunks(max_symbols.max(1))
+            .map(|symbols| Subscription {
+                symbols: symbols.to_vec(),
+                ..self.clone()
+            })
+            .collect()
     }
 
     /// Convert to a subscribe request
     pub fn to_request(&self, req_id: Option<u64>) -> SubscribeRequest {
-        let params = match self.channel {
+        let mut params = match self.channel {
             Channel::Book => SubscribeParams::book(self.symbols.clone(), self.depth.unwrap_or(Depth::D10)),
             Channel::Ticker => SubscribeParams::ticker(self.symbols.clone()),
             Channel::Trade => SubscribeParams::trade(self.symbols.clone()),
@@ -81,11 +189,14 @@ impl Subscription {
                 symbol: self.symbols.clone(),
                 depth: self.depth.map(|d| d.as_u32()),
                 snapshot: Some(self.snapshot),
-                interval: None,
+                interval: self.interval,
                 event_trigger: None,
-                token: None,
+                token: self.token.clone(),
             },
         };
+        if !self.snapshot {
+            params = params.with_snapshot(false);
+        }
 
         SubscribeRequest {
             method: "subscribe",
@@ -95,15 +206,109 @@ impl Subscription {
     }
 }
 
+/// Fetches a fresh WebSocket token for authenticated subscriptions
+///
+/// Called on reconnect when a stored token has expired, e.g. with
+/// `kraken_auth::TokenProvider::get_ws_token`.
+#[derive(Clone)]
+pub struct TokenRefresher(Arc<RefreshFn>);
+
+type RefreshFuture = Pin<Box<dyn Future<Output = Result<String, KrakenError>> + Send>>;
+type RefreshFn = dyn Fn() -> RefreshFuture + Send + Sync;
+
+impl TokenRefresher {
+    /// Wrap an async closure returning a new token
+    pub fn new<F, Fut>(refresh: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, KrakenError>> + Send + 'static,
+    {
+        Self(Arc::new(move || Box::pin(refresh())))
+    }
+
+    /// Fetch a new token
+    pub async fn refresh(&self) -> Result<String, KrakenError> {
+        (self.0)().await
+    }
+}
+
+impl std::fmt::Debug for TokenRefresher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TokenRefresher(..)")
+    }
+}
+
+/// Outcome of one subscription once every symbol has been acked or rejected
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchResolution {
+    /// Channel the subscription is for
+    pub channel: Channel,
+    /// Symbols the server confirmed
+    pub live: Vec<String>,
+    /// Symbols the server rejected, with the reason
+    pub rejected: Vec<(String, String)>,
+}
+
+/// Channel and symbols of a subscribe request sent on the current connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestRecord {
+    /// Channel requested
+    pub channel: Channel,
+    /// Symbols requested (empty for symbol-less channels)
+    pub symbols: Vec<String>,
+}
+
+/// Symbols of one subscription still awaiting a response
+#[derive(Debug)]
+struct PendingBatch {
+    /// ID returned by [`SubscriptionManager::add`] for the subscription
+    sub_id: u64,
+    channel: Channel,
+    outstanding: HashSet<String>,
+    live: Vec<String>,
+    rejected: Vec<(String, String)>,
+}
+
 /// Manages active subscriptions for reconnection restoration
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct SubscriptionManager {
     /// Active subscriptions keyed by channel + symbols
     subscriptions: Vec<Subscription>,
+    /// ID returned by `add` for each entry of `subscriptions`
+    ids: Vec<u64>,
     /// Pending subscription requests
     pending: HashSet<u64>,
     /// Next request ID
     next_req_id: u64,
+    /// Maximum symbols per subscribe request
+    max_symbols_per_request: usize,
+    /// Request ID -> (batch ID, symbols still unanswered in that request)
+    requests: HashMap<u64, (u64, HashSet<String>)>,
+    /// Batch ID -> progress of one subscription's chunks
+    batches: HashMap<u64, PendingBatch>,
+    /// Request ID -> what was requested, for every request sent on this connection
+    sent: HashMap<u64, RequestRecord>,
+    /// Subscription ID -> callers waiting for its resolution
+    waiters: HashMap<u64, Vec<oneshot::Sender<BatchResolution>>>,
+    /// Subscription ID -> last resolution, answered to duplicate requests
+    resolutions: HashMap<u64, BatchResolution>,
+}
+
+impl Default for SubscriptionManager {
+    fn default() -> Self {
+        Self {
+            subscriptions: Vec::new(),
+            ids: Vec::new(),
+            pending: HashSet::new(),
+            next_req_id: 0,
+            max_symbols_per_request: DEFAULT_MAX_SYMBOLS_PER_REQUEST,
+            requests: HashMap::new(),
+            batches: HashMap::new(),
+            sent: HashMap::new(),
+            waiters: HashMap::new(),
+            resolutions: HashMap::new(),
+        }
+    }
 }
 
 impl SubscriptionManager {
@@ -112,13 +317,161 @@ impl SubscriptionManager {
         Self::default()
     }
 
+    /// Set the maximum number of symbols per subscribe request
+    pub fn with_max_symbols_per_request(mut self, max_symbols: usize) -> Self {
+        self.max_symbols_per_request = max_symbols.max(1);
+        self
+    }
+
     /// Add a subscription
+    ///
+    /// Symbols already subscribed on the same channel at the same depth are
+    /// dropped from `sub`. If nothing new is left, the ID of the existing
+    /// subscription is returned and no request is queued.
+    ///
+    /// A symbol has one book feed at a time, since two depths would feed
+    /// one local orderbook. A book already held at the same or a larger
+    /// depth covers the request. A book held at a smaller depth moves to
+    /// `sub`, leaving its old subscription; the server keeps sending the old
+    /// depth until it is unsubscribed (see
+    /// [`shallower_books`](Self::shallower_books)).
     pub fn add(&mut self, sub: Subscription) -> u64 {
+        self.insert(sub).0
+    }
+
+    /// Add a subscription, returning its ID and whether it is new
+    fn insert(&mut self, mut sub: Subscription) -> (u64, bool) {
+        let (channel, depth, interval) = (sub.channel, sub.depth, sub.interval);
+        let existing = if sub.symbols.is_empty() {
+            self.position(|s| s.channel == channel && s.depth == depth && s.interval == interval && s.symbols.is_empty())
+        } else {
+            let mut first = None;
+            let mut seen = HashSet::new();
+            sub.symbols.retain(|symbol| {
+                if !seen.insert(symbol.clone()) {
+                    return false;
+                }
+                match self.covering(channel, symbol, depth, interval) {
+                    Some(index) => {
+                        first.get_or_insert(index);
+                        false
+                    }
+                    None => true,
+                }
+            });
+            first.filter(|_| sub.symbols.is_empty())
+        };
+        if let Some(index) = existing {
+            return (self.ids[index], false);
+        }
+        if channel == Channel::Book {
+            for symbol in &sub.symbols {
+                self.remove_symbol(Channel::Book, symbol);
+            }
+        }
+
         let req_id = self.next_req_id;
         self.next_req_id += 1;
         self.pending.insert(req_id);
         self.subscriptions.push(sub);
-        req_id
+        self.ids.push(req_id);
+        (req_id, true)
+    }
+
+    fn position(&self, matches: impl Fn(&Subscription) -> bool) -> Option<usize> {
+        self.subscriptions.iter().position(matches)
+    }
+
+    /// Index of the subscription carrying `symbol` on `channel` at `depth`
+    ///
+    /// Books are covered by a subscription at any depth at least as large.
+    fn covering(&self, channel: Channel, symbol: &str, depth: Option<Depth>, interval: Option<u32>) -> Option<usize> {
+        self.position(|s| {
+            let depth_covers = if channel == Channel::Book {
+                s.depth.map(|d| d.as_u32()) >= depth.map(|d| d.as_u32())
+            } else {
+                s.depth == depth
+            };
+            s.channel == channel
+                && depth_covers
+                && s.interval == interval
+                && s.symbols.iter().any(|held| held == symbol)
+        })
+    }
+
+    /// Book symbols of `sub` currently held at a smaller depth, with that depth
+    ///
+    /// These are the books [`add`](Self::add) moves to `sub`.
+    pub fn shallower_books(&self, sub: &Subscription) -> Vec<(String, Depth)> {
+        let Some(depth) = sub.depth.filter(|_| sub.channel == Channel::Book) else {
+            return Vec::new();
+        };
+        sub.symbols
+            .iter()
+            .filter_map(|symbol| {
+                let held = self
+                    .subscriptions
+                    .iter()
+                    .find(|s| s.channel == Channel::Book && s.symbols.iter().any(|held| held == symbol))?
+                    .depth?;
+                (held.as_u32() < depth.as_u32()).then(|| (symbol.clone(), held))
+            })
+            .collect()
+    }
+
+    /// Add a subscription and get notified once the server has answered
+    /// every symbol
+    ///
+    /// The receiver fires after the subscription is next sent and resolved,
+    /// and is dropped without a value if the subscriptions are cleared. A
+    /// subscription without symbols resolves immediately, as does a
+    /// duplicate of one the server has already answered (with that answer
+    /// for the requested symbols).
+    pub fn add_with_confirmation(&mut self, sub: Subscription) -> (u64, oneshot::Receiver<BatchResolution>) {
+        let (tx, rx) = oneshot::channel();
+        let channel = sub.channel;
+        let requested = sub.symbols.clone();
+        let (sub_id, new) = self.insert(sub);
+        if requested.is_empty() {
+            let _ = tx.send(BatchResolution {
+                channel,
+                live: Vec::new(),
+                rejected: Vec::new(),
+            });
+        } else if let Some(resolution) = self.resolutions.get(&sub_id).filter(|_| !new) {
+            let _ = tx.send(BatchResolution {
+                channel,
+                live: resolution.live.iter().filter(|s| requested.contains(s)).cloned().collect(),
+                rejected: resolution
+                    .rejected
+                    .iter()
+                    .filter(|(s, _)| requested.contains(s))
+                    .cloned()
+                    .collect(),
+            });
+        } else {
+            self.waiters.entry(sub_id).or_default().push(tx);
+        }
+        (sub_id, rx)
+    }
+
+    /// Whether `symbol` is subscribed on `channel`, at any depth
+    pub fn is_subscribed(&self, channel: Channel, symbol: &str) -> bool {
+        self.subscriptions
+            .iter()
+            .any(|sub| sub.channel == channel && sub.symbols.iter().any(|s| s == symbol))
+    }
+
+    /// Whether `symbol` is subscribed on any channel
+    pub fn has_symbol(&self, symbol: &str) -> bool {
+        self.subscriptions
+            .iter()
+            .any(|sub| sub.symbols.iter().any(|s| s == symbol))
+    }
+
+    /// Active subscriptions with the IDs `add` returned for them
+    pub fn entries(&self) -> impl Iterator<Item = (u64, &Subscription)> {
+        self.ids.iter().copied().zip(&self.subscriptions)
     }
 
     /// Mark a subscription as confirmed
@@ -132,11 +485,51 @@ impl SubscriptionManager {
         // Note: we don't remove from subscriptions - let caller decide
     }
 
+    /// Drop one symbol from the subscription covering it on `channel`
+    ///
+    /// Returns that symbol's share of the subscription (same depth and
+    /// token), so it can be added back later. A subscription left without
+    /// symbols is removed entirely.
+    pub fn remove_symbol(&mut self, channel: Channel, symbol: &str) -> Option<Subscription> {
+        let index = self
+            .subscriptions
+            .iter()
+            .position(|sub| sub.channel == channel && sub.symbols.iter().any(|s| s == symbol))?;
+        let sub = &mut self.subscriptions[index];
+        sub.symbols.retain(|s| s != symbol);
+        let removed = Subscription {
+            symbols: vec![symbol.to_string()],
+            ..sub.clone()
+        };
+        if sub.symbols.is_empty() {
+            self.subscriptions.remove(index);
+            let id = self.ids.remove(index);
+            self.resolutions.remove(&id);
+        }
+        Some(removed)
+    }
+
     /// Get all active subscriptions (for restoration after reconnect)
     pub fn all(&self) -> &[Subscription] {
         &self.subscriptions
     }
 
+    /// Whether any subscription holds a token that has expired by `now`
+    pub fn has_expired_tokens(&self, now: Instant) -> bool {
+        self.subscriptions.iter().any(|sub| sub.token_expired(now))
+    }
+
+    /// Replace every expired token, returning how many subscriptions changed
+    pub fn replace_expired_tokens(&mut self, token: &str, expires_at: Instant, now: Instant) -> usize {
+        let mut replaced = 0;
+        for sub in self.subscriptions.iter_mut().filter(|sub| sub.token_expired(now)) {
+            sub.token = Some(token.to_string());
+            sub.token_expires_at = Some(expires_at);
+            replaced += 1;
+        }
+        replaced
+    }
+
     /// Get number of active subscriptions
     pub fn count(&self) -> usize {
         self.subscriptions.len()
@@ -145,7 +538,13 @@ impl SubscriptionManager {
     /// Clear all subscriptions
     pub fn clear(&mut self) {
         self.subscriptions.clear();
+        self.ids.clear();
         self.pending.clear();
+        self.requests.clear();
+        self.batches.clear();
+        self.sent.clear();
+        self.waiters.clear();
+        self.resolutions.clear();
     }
 
     /// Check if any subscriptions are pending confirmation
@@ -153,19 +552,177 @@ impl SubscriptionManager {
         !self.pending.is_empty()
     }
 
+    /// Reserve a request ID for a request this manager does not track
+    pub fn reserve_req_id(&mut self) -> u64 {
+        let req_id = self.next_req_id;
+        self.next_req_id += 1;
+        req_id
+    }
+
     /// Get subscribe requests for all active subscriptions (for restoration)
+    ///
+    /// Subscriptions with more symbols than the per-request limit are split
+    /// into several requests. Each subscription is tracked as one batch that
+    /// resolves once every symbol has been acked or rejected. The
+    /// subscriptions themselves are left as they are; see
+    /// [`request_book_snapshots`](Self::request_book_snapshots).
     pub fn restoration_requests(&mut self) -> Vec<(u64, SubscribeRequest)> {
         let mut requests = Vec::new();
+        // Requests from an earlier connection will never be answered
+        self.pending.clear();
+        self.requests.clear();
+        self.batches.clear();
+        self.sent.clear();
+
+        for (sub, sub_id) in self.subscriptions.iter().zip(&self.ids) {
+            let batch_id = self.next_req_id;
+            if !sub.symbols.is_empty() {
+                self.batches.insert(
+                    batch_id,
+                    PendingBatch {
+                        sub_id: *sub_id,
+                        channel: sub.channel,
+                        outstanding: sub.symbols.iter().cloned().collect(),
+                        live: Vec::new(),
+                        rejected: Vec::new(),
+                    },
+                );
+            }
 
-        for sub in &self.subscriptions {
-            let req_id = self.next_req_id;
-            self.next_req_id += 1;
-            self.pending.insert(req_id);
-            requests.push((req_id, sub.to_request(Some(req_id))));
+            for chunk in sub.chunks(self.max_symbols_per_request) {
+                let req_id = self.next_req_id;
+                self.next_req_id += 1;
+                self.pending.insert(req_id);
+                if !chunk.symbols.is_empty() {
+                    self.requests
+                        .insert(req_id, (batch_id, chunk.symbols.iter().cloned().collect()));
+                }
+                self.sent.insert(
+                    req_id,
+                    RequestRecord {
+                        channel: chunk.channel,
+                        symbols: chunk.symbols.clone(),
+                    },
+                );
+                requests.push((req_id, chunk.to_request(Some(req_id))));
+            }
         }
 
         requests
     }
+
+    /// Make every book subscription ask for a snapshot from now on
+    ///
+    /// A book may skip the snapshot on its first request only. Once it has
+    /// been sent, any later request restores state a new connection no
+    /// longer has, so call this after the first round of requests is out.
+    pub fn request_book_snapshots(&mut self) {
+        for sub in &mut self.subscriptions {
+            if sub.channel == Channel::Book {
+                sub.snapshot = true;
+            }
+        }
+    }
+
+    /// Channel of the batch a request belongs to
+    pub fn request_channel(&self, req_id: u64) -> Option<Channel> {
+        let (batch_id, _) = self.requests.get(&req_id)?;
+        self.batches.get(batch_id).map(|batch| batch.channel)
+    }
+
+    /// What a request sent on the current connection asked for
+    ///
+    /// Unlike [`request_channel`](Self::request_channel), this stays
+    /// available after the request has been answered.
+    pub fn request(&self, req_id: u64) -> Option<&RequestRecord> {
+        self.sent.get(&req_id)
+    }
+
+    /// Record the server's answer for one symbol of a request
+    ///
+    /// Kraken answers multi-symbol subscribes with one response per symbol.
+    /// A rejection without a symbol applies to every unanswered symbol of
+    /// the request. Returns the batch outcome once its last symbol resolves.
+    ///
+    /// Symbols rejected as unknown pairs are dropped from their subscription,
+    /// so they are not resent on every reconnect.
+    pub fn resolve(
+        &mut self,
+        req_id: u64,
+        symbol: Option<&str>,
+        outcome: Result<(), String>,
+    ) -> Option<BatchResolution> {
+        let (batch_id, unanswered) = self.requests.get_mut(&req_id)?;
+        let batch_id = *batch_id;
+        let symbols: Vec<String> = match symbol {
+            Some(symbol) if unanswered.remove(symbol) => vec![symbol.to_string()],
+            Some(_) => Vec::new(),
+            None => unanswered.drain().collect(),
+        };
+        if unanswered.is_empty() {
+            self.requests.remove(&req_id);
+            self.pending.remove(&req_id);
+        }
+
+        let batch = self.batches.get_mut(&batch_id)?;
+        let sub_id = batch.sub_id;
+        for symbol in &symbols {
+            batch.outstanding.remove(symbol);
+            match &outcome {
+                Ok(()) => batch.live.push(symbol.clone()),
+                Err(reason) => batch.rejected.push((symbol.clone(), reason.clone())),
+            }
+        }
+        let done = batch.outstanding.is_empty();
+        if matches!(&outcome, Err(reason) if is_permanent_rejection(reason)) {
+            for symbol in &symbols {
+                self.drop_symbol(sub_id, symbol);
+            }
+        }
+        if !done {
+            return None;
+        }
+
+        let batch = self.batches.remove(&batch_id)?;
+        let resolution = BatchResolution {
+            channel: batch.channel,
+            live: batch.live,
+            rejected: batch.rejected,
+        };
+        for waiter in self.waiters.remove(&batch.sub_id).into_iter().flatten() {
+            let _ = waiter.send(resolution.clone());
+        }
+        if self.ids.contains(&batch.sub_id) {
+            self.resolutions.insert(batch.sub_id, resolution.clone());
+        }
+        Some(resolution)
+    }
+
+    /// Drop `symbol` from the subscription with ID `sub_id`, removing the
+    /// subscription once it has no symbols left
+    fn drop_symbol(&mut self, sub_id: u64, symbol: &str) {
+        let Some(index) = self.ids.iter().position(|id| *id == sub_id) else {
+            return;
+        };
+        let sub = &mut self.subscriptions[index];
+        sub.symbols.retain(|s| s != symbol);
+        if sub.symbols.is_empty() {
+            self.subscriptions.remove(index);
+            self.ids.remove(index);
+            self.resolutions.remove(&sub_id);
+        }
+    }
+}
+
+/// Whether a subscribe rejection will be repeated on every retry
+///
+/// Only unknown or unsupported pairs qualify; rate limits, outages and
+/// other rejections may clear up by the next reconnect.
+fn is_permanent_rejection(reason: &str) -> bool {
+    matches!(
+        KrakenErrorCode::from_error_string(reason),
+        Some(KrakenErrorCode::UnknownAssetPair | KrakenErrorCode::UnknownAsset | KrakenErrorCode::QueryUnknownAssetPair)
+    )
 }
 
 #[cfg(test)]
@@ -180,6 +737,98 @@ mod tests {
         assert!(sub.snapshot);
     }
 
+    #[test]
+    fn test_book_snapshot_skipped_only_on_first_request() {
+        let mut manager = SubscriptionManager::new();
+        manager.add(Subscription::orderbook(["BTC/USD"], Depth::D10).with_snapshot(false));
+        manager.add(Subscription::trade(["BTC/USD"]).with_snapshot(false));
+
+        let first = manager.restoration_requests();
+        assert_eq!(first[0].1.params.snapshot, Some(false));
+        assert_eq!(first[1].1.params.snapshot, Some(false));
+        // Building requests doesn't touch the subscriptions
+        assert_eq!(manager.restoration_requests()[0].1.params.snapshot, Some(false));
+
+        manager.request_book_snapshots();
+        let reconnect = manager.restoration_requests();
+        assert_eq!(reconnect[0].1.params.snapshot, Some(true));
+        assert_eq!(reconnect[1].1.params.snapshot, Some(false));
+    }
+
+    #[test]
+    fn test_duplicate_symbols_are_not_resubscribed() {
+        let mut manager = SubscriptionManager::new();
+        let btc = manager.add(Subscription::orderbook(["BTC/USD", "ETH/USD"], Depth::D10));
+
+        // Fully covered: same id, nothing queued
+        assert_eq!(manager.add(Subscription::orderbook(["ETH/USD", "BTC/USD"], Depth::D10)), btc);
+        assert_eq!(manager.count(), 1);
+
+        // Overlap keeps only the new symbol
+        let sol = manager.add(Subscription::orderbook(["BTC/USD", "SOL/USD", "SOL/USD"], Depth::D10));
+        assert_ne!(sol, btc);
+        assert_eq!(manager.all()[1].symbols, vec!["SOL/USD"]);
+
+        // A deeper book moves the symbol instead of adding a second feed
+        let deeper = Subscription::orderbook(["BTC/USD"], Depth::D100);
+        assert_eq!(manager.shallower_books(&deeper), vec![("BTC/USD".to_string(), Depth::D10)]);
+        let deep = manager.add(deeper);
+        assert_eq!(manager.count(), 3);
+        assert_eq!(manager.all()[0].symbols, vec!["ETH/USD"]);
+        // ...which then covers shallower requests
+        let shallower = Subscription::orderbook(["BTC/USD"], Depth::D25);
+        assert!(manager.shallower_books(&shallower).is_empty());
+        assert_eq!(manager.add(shallower), deep);
+        let feeds = manager.all().iter().filter(|s| s.symbols.iter().any(|s| s == "BTC/USD")).count();
+        assert_eq!(feeds, 1);
+
+        assert!(manager.is_subscribed(Channel::Book, "SOL/USD"));
+        assert!(!manager.is_subscribed(Channel::Trade, "SOL/USD"));
+        let ids: Vec<u64> = manager.entries().map(|(id, _)| id).collect();
+        assert_eq!(&ids[..2], &[btc, sol]);
+    }
+
+    #[test]
+    fn test_duplicate_confirmation_reuses_resolution() {
+        let mut manager = SubscriptionManager::new();
+        manager.add(Subscription::trade(["BTC/USD", "BAD/USD"]));
+        let (req_id, _) = manager.restoration_requests()[0];
+        manager.resolve(req_id, Some("BTC/USD"), Ok(()));
+        manager.resolve(req_id, Some("BAD/USD"), Err("EService:Unavailable".to_string()));
+
+        let (_, mut rx) = manager.add_with_confirmation(Subscription::trade(["BAD/USD"]));
+        let resolution = rx.try_recv().unwrap();
+        assert!(resolution.live.is_empty());
+        assert_eq!(resolution.rejected[0].0, "BAD/USD");
+    }
+
+    #[test]
+    fn test_level3_with_token() {
+        let sub = Subscription::level3_with_token(["BTC/USD"], L3Depth::D1000, "tok");
+        let request = sub.to_request(Some(3));
+        assert_eq!(request.params.channel, Channel::Level3);
+        assert_eq!(request.params.depth, Some(1000));
+        assert_eq!(request.params.token.as_deref(), Some("tok"));
+
+        let chunks = sub.chunks(1);
+        assert_eq!(chunks[0].token.as_deref(), Some("tok"));
+    }
+
+    #[test]
+    fn test_expired_tokens_are_replaced() {
+        let now = Instant::now();
+        let mut manager = SubscriptionManager::new();
+        manager.add(Subscription::level3_with_token(["BTC/USD"], L3Depth::D10, "old").with_token_expiry(now));
+        manager.add(Subscription::level3_with_token(["ETH/USD"], L3Depth::D10, "fresh"));
+        manager.add(Subscription::ticker(["BTC/USD"]));
+
+        assert!(manager.has_expired_tokens(now));
+        assert_eq!(manager.replace_expired_tokens("new", now + TOKEN_LIFETIME, now), 1);
+        assert!(!manager.has_expired_tokens(now));
+        let tokens: Vec<_> = manager.all().iter().map(|s| s.token.as_deref()).collect();
+        assert_eq!(tokens, vec![Some("new"), Some("fresh"), None]);
+    }
+
     #[test]
     fn test_subscription_manager() {
         let mut manager = SubscriptionManager::new();
@@ -198,4 +847,110 @@ mod tests {
 
         assert!(!manager.has_pending());
     }
+
+    #[test]
+    fn test_remove_symbol_keeps_the_rest_of_the_subscription() {
+        let mut manager = SubscriptionManager::new();
+        manager.add(Subscription::orderbook(["BTC/USD", "ETH/USD"], Depth::D25));
+        manager.add(Subscription::ticker(["ETH/USD"]));
+
+        let removed = manager.remove_symbol(Channel::Book, "ETH/USD").unwrap();
+        assert_eq!(removed.symbols, vec!["ETH/USD".to_string()]);
+        assert_eq!(removed.depth, Some(Depth::D25));
+        assert_eq!(manager.all()[0].symbols, vec!["BTC/USD".to_string()]);
+        assert!(manager.remove_symbol(Channel::Book, "ETH/USD").is_none());
+
+        manager.remove_symbol(Channel::Book, "BTC/USD").unwrap();
+        assert_eq!(manager.count(), 1);
+        assert_eq!(manager.all()[0].channel, Channel::Ticker);
+    }
+
+    #[test]
+    fn test_unsupported_pairs_are_not_restored() {
+        let mut manager = SubscriptionManager::new();
+        manager.add(Subscription::trade(["BTC/USD", "BAD/USD"]));
+        manager.add(Subscription::ticker(["GONE/USD"]));
+        let requests = manager.restoration_requests();
+        manager.resolve(requests[0].0, Some("BTC/USD"), Ok(()));
+        manager.resolve(requests[0].0, Some("BAD/USD"), Err("Currency pair not supported".to_string()));
+        let resolution = manager
+            .resolve(requests[1].0, None, Err("Currency pair not supported".to_string()))
+            .unwrap();
+        assert_eq!(resolution.rejected[0].0, "GONE/USD");
+
+        assert_eq!(manager.count(), 1);
+        let requests = manager.restoration_requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].1.params.symbol, vec!["BTC/USD".to_string()]);
+
+        // Transient rejections are retried on the next reconnect
+        manager.resolve(requests[0].0, None, Err("EService:Unavailable".to_string()));
+        assert_eq!(manager.restoration_requests().len(), 1);
+    }
+
+    fn symbols(n: usize) -> Vec<String> {
+        (0..n).map(|i| format!("SYM{i}/USD")).collect()
+    }
+
+    #[test]
+    fn test_large_symbol_lists_are_chunked() {
+        let mut manager = SubscriptionManager::new().with_max_symbols_per_request(2);
+        manager.add(Subscription::ticker(symbols(5)));
+        manager.add(Subscription::trade(symbols(1)));
+
+        let requests = manager.restoration_requests();
+        let sizes: Vec<usize> = requests.iter().map(|(_, r)| r.params.symbol.len()).collect();
+        assert_eq!(sizes, vec![2, 2, 1, 1]);
+        assert_eq!(manager.request_channel(requests[2].0), Some(Channel::Ticker));
+    }
+
+    #[test]
+    fn test_confirmation_waiter_and_request_registry() {
+        let mut manager = SubscriptionManager::new().with_max_symbols_per_request(1);
+        manager.add(Subscription::ticker(symbols(1)));
+        let (_, mut rx) = manager.add_with_confirmation(Subscription::trade(symbols(2)));
+
+        let requests = manager.restoration_requests();
+        let trade_req = requests[2].0;
+        assert!(manager.resolve(requests[1].0, None, Ok(())).is_none());
+        assert!(rx.try_recv().is_err());
+        manager.resolve(trade_req, None, Err("EGeneral:Invalid arguments".into()));
+
+        let resolution = rx.try_recv().unwrap();
+        assert_eq!(resolution.live, vec!["SYM0/USD".to_string()]);
+        assert_eq!(resolution.rejected[0].0, "SYM1/USD");
+        // Still known after it has been answered
+        assert_eq!(
+            manager.request(trade_req),
+            Some(&RequestRecord {
+                channel: Channel::Trade,
+                symbols: vec!["SYM1/USD".to_string()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_batch_resolves_with_partial_failure() {
+        let mut manager = SubscriptionManager::new().with_max_symbols_per_request(2);
+        manager.add(Subscription::ticker(symbols(3)));
+        let requests = manager.restoration_requests();
+        let (first, second) = (requests[0].0, requests[1].0);
+
+        assert!(manager.resolve(first, Some("SYM0/USD"), Ok(())).is_none());
+        assert!(manager.resolve(first, Some("SYM1/USD"), Err("unsupported".into())).is_none());
+        // Symbol-less rejection covers the rest of the request
+        let resolution = manager.resolve(second, None, Err("rate limited".into())).unwrap();
+
+        assert_eq!(resolution.channel, Channel::Ticker);
+        assert_eq!(resolution.live, vec!["SYM0/USD".to_string()]);
+        assert_eq!(
+            resolution.rejected,
+            vec![
+                ("SYM1/USD".to_string(), "unsupported".to_string()),
+                ("SYM2/USD".to_string(), "rate limited".to_string()),
+            ]
+        );
+        assert!(!manager.has_pending());
+        assert!(manager.resolve(first, Some("SYM0/USD"), Ok(())).is_none());
+    }
 }
@@ -0,0 +1,417 @@
+//! Raw message tap
+//!
+//! A [`MessageTap`] receives every text frame exchanged with the server,
+//! inbound and outbound, exactly as it crossed the wire and before any
+//! parsing. Frames that fail to parse are captured too, so the tap is the
+//! record to reach for when debugging checksum mismatches or keeping
+//! compliance logs.
+//!
+//! | Sink | Created with | Delivers |
+//! |------|--------------|----------|
+//! | closure | [`MessageTap::new`] | each [`RawFrame`] by reference, on the connection task |
+//! | channel | [`MessageTap::channel`] | owned frames on a bounded receiver; frames are dropped while it is full |
+//! | file | [`MessageTap::file`] | one JSON object per line, appended by a writer thread |
+//!
+//! Sinks run on the connection task, so a custom sink should return quickly.
+//! The built-in sinks never block it: the file sink hands lines to its
+//! writer thread through a bounded queue and drops them, with a warning,
+//! while the disk can't keep up.
+//! Handshakes, subscriptions, pings and standby connections are all tapped;
+//! [`RawFrame::endpoint`] tells connections apart.
+//!
+//! # Redaction
+//!
+//! String values of the `"token"` field are replaced with `[REDACTED]`
+//! before a frame reaches the sink, so authenticated subscribes and orders
+//! don't leak the session token into captures. The rest of the frame is
+//! left byte for byte. Change the fields with
+//! [`MessageTap::with_redacted_fields`].
+//!
+//! # Example
+//!
+//! ```
+//! use kraken_ws::{ConnectionConfig, MessageTap};
+//!
+//! let (tap, _frames) = MessageTap::channel();
+//! let config = ConnectionConfig::new().with_message_tap(tap);
+//! assert!(config.tap.is_some());
+//! ```
+
+use crate::transport::{CloseFrame, Transport, TransportError, TransportStats};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fs::{File, OpenOptions};
+use std::io::{LineWriter, Write};
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// Frames buffered by [`MessageTap::channel`] and [`MessageTap::file`]
+pub const DEFAULT_TAP_CAPACITY: usize = 4096;
+
+/// Replacement for redacted values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Direction of a frame relative to the client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    /// Received from the server
+    Inbound,
+    /// Sent to the server
+    Outbound,
+}
+
+/// One text frame as it crossed the wire
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RawFrame {
+    /// Whether the frame was received or sent
+    pub direction: Direction,
+    /// When the frame was received, or handed to the socket
+    pub timestamp: DateTime<Utc>,
+    /// URL of the connection that carried the frame
+    pub endpoint: String,
+    /// Frame payload, unmodified apart from redacted fields
+    pub text: String,
+}
+
+/// Destination for tapped frames
+///
+/// Implemented for every `Fn(&RawFrame) + Send + Sync` closure.
+pub trait FrameSink: Send + Sync {
+    /// Record one frame
+    fn record(&self, frame: &RawFrame);
+}
+
+impl<F> FrameSink for F
+where
+    F: Fn(&RawFrame) + Send + Sync,
+{
+    fn record(&self, frame: &RawFrame) {
+        self(frame)
+    }
+}
+
+/// Appends frames to a file as JSON lines from a writer thread
+struct FileSink {
+    lines: Option<SyncSender<String>>,
+    writer: Mutex<Option<JoinHandle<()>>>,
+    dropped: AtomicU64,
+}
+
+impl FileSink {
+    fn spawn(file: File, capacity: usize) -> std::io::Result<Self> {
+        let (tx, rx) = sync_channel::<String>(capacity.max(1));
+        let writer = std::thread::Builder::new()
+            .name("kraken-tap-writer".to_string())
+            .spawn(move || {
+                let mut file = LineWriter::new(file);
+                for line in rx {
+                    if let Err(e) = writeln!(file, "{}", line) {
+                        warn!("Failed to write tapped frame: {}", e);
+                    }
+                }
+            })?;
+        Ok(Self {
+            lines: Some(tx),
+            writer: Mutex::new(Some(writer)),
+            dropped: AtomicU64::new(0),
+        })
+    }
+}
+
+impl FrameSink for FileSink {
+    fn record(&self, frame: &RawFrame) {
+        let line = match serde_json::to_string(frame) {
+            Ok(line) => line,
+            Err(e) => return warn!("Failed to encode tapped frame: {}", e),
+        };
+        let Some(lines) = &self.lines else {
+            return;
+        };
+        match lines.try_send(line) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+                if dropped == 1 || dropped.is_multiple_of(1000) {
+                    warn!("Tap file writer is behind, {} frames dropped", dropped);
+                }
+            }
+            Err(TrySendError::Disconnected(_)) => {}
+        }
+    }
+}
+
+impl Drop for FileSink {
+    fn drop(&mut self) {
+        // Closing the queue lets the writer finish what is buffered
+        self.lines = None;
+        if let Some(writer) = self.writer.lock().take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+/// Shared handle to a frame sink
+#[derive(Clone)]
+pub struct MessageTap {
+    sink: Arc<dyn FrameSink>,
+    redacted: Arc<[String]>,
+}
+
+impl std::fmt::Debug for MessageTap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("MessageTap(..)")
+    }
+}
+
+impl MessageTap {
+    /// Tap frames into a custom sink
+    pub fn new(sink: impl FrameSink + 'static) -> Self {
+        Self {
+            sink: Arc::new(sink),
+            redacted: Arc::from(["token".to_string()]),
+        }
+    }
+
+    /// Tap frames into a channel holding [`DEFAULT_TAP_CAPACITY`] frames
+    ///
+    /// Frames are dropped while the channel is full or once the receiver is
+    /// gone.
+    pub fn channel() -> (Self, mpsc::Receiver<RawFrame>) {
+        Self::channel_with_capacity(DEFAULT_TAP_CAPACITY)
+    }
+
+    /// Tap frames into a channel holding up to `capacity` frames
+    pub fn channel_with_capacity(capacity: usize) -> (Self, mpsc::Receiver<RawFrame>) {
+        let (tx, rx) = mpsc::channel(capacity.max(1));
+        let tap = Self::new(move |frame: &RawFrame| {
+            let _ = tx.try_send(frame.clone());
+        });
+        (tap, rx)
+    }
+
+    /// Append frames to a file, one JSON object per line
+    ///
+    /// The file is created if missing. Lines are written and flushed by a
+    /// writer thread, with up to [`DEFAULT_TAP_CAPACITY`] frames queued; the
+    /// thread drains the queue and exits once the last clone of the tap is
+    /// dropped.
+    pub fn file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(FileSink::spawn(file, DEFAULT_TAP_CAPACITY)?))
+    }
+
+    /// Replace the fields whose string values are redacted (default: `token`)
+    ///
+    /// Pass an empty list to record frames verbatim.
+    pub fn with_redacted_fields<S: Into<String>>(mut self, fields: impl IntoIterator<Item = S>) -> Self {
+        self.redacted = fields.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Record a frame
+    pub fn record(&self, direction: Direction, endpoint: &str, text: &str) {
+        let text = self
+            .redacted
+            .iter()
+            .fold(Cow::Borrowed(text), |text, field| match redact(&text, field) {
+                Cow::Owned(redacted) => Cow::Owned(redacted),
+                Cow::Borrowed(_) => text,
+            });
+        self.sink.record(&RawFrame {
+            direction,
+            timestamp: Utc::now(),
+            endpoint: endpoint.to_string(),
+            text: text.into_owned(),
+        });
+    }
+
+    /// Wrap a transport so its frames are tapped
+    pub fn wrap(&self, transport: Box<dyn Transport>) -> Box<dyn Transport> {
+        Box::new(TappedTransport {
+            inner: transport,
+            tap: self.clone(),
+        })
+    }
+}
+
+/// Replace the string value of every `"field": "..."` pair with [`REDACTED`]
+///
+/// Works on the raw text so frames that aren't valid JSON are handled, and
+/// everything outside the replaced values is left untouched.
+fn redact<'a>(text: &'a str, field: &str) -> Cow<'a, str> {
+    let key = format!("\"{}\"", field);
+    if !text.contains(&key) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find(&key) {
+        let (head, tail) = rest.split_at(at + key.len());
+        out.push_str(head);
+        rest = tail;
+        let after_colon = tail.trim_start().strip_prefix(':').map(str::trim_start);
+        let Some(value) = after_colon.and_then(|v| v.strip_prefix('"')) else {
+            continue;
+        };
+        // End of the string value, skipping escaped characters
+        let mut escaped = false;
+        let Some(end) = value.char_indices().find_map(|(i, c)| match (escaped, c) {
+            (true, _) => {
+                escaped = false;
+                None
+            }
+            (false, '\\') => {
+                escaped = true;
+                None
+            }
+            (false, '"') => Some(i),
+            _ => None,
+        }) else {
+            continue;
+        };
+        out.push_str(&tail[..tail.len() - value.len()]);
+        out.push_str(REDACTED);
+        rest = &value[end..];
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+/// Transport that copies every frame to a [`MessageTap`]
+struct TappedTransport {
+    inner: Box<dyn Transport>,
+    tap: MessageTap,
+}
+
+#[async_trait]
+impl Transport for TappedTransport {
+    async fn connect(&mut self) -> Result<(), TransportError> {
+        self.inner.connect().await
+    }
+
+    async fn send(&mut self, message: &str) -> Result<(), TransportError> {
+        self.inner.send(message).await?;
+        self.tap.record(Direction::Outbound, self.inner.endpoint(), message);
+        Ok(())
+    }
+
+    async fn recv(&mut self) -> Result<Option<String>, TransportError> {
+        let received = self.inner.recv().await?;
+        if let Some(text) = &received {
+            self.tap.record(Direction::Inbound, self.inner.endpoint(), text);
+        }
+        Ok(received)
+    }
+
+    async fn close(&mut self) -> Result<(), TransportError> {
+        self.inner.close().await
+    }
+
+    fn is_connected(&self) -> bool {
+        self.inner.is_connected()
+    }
+
+    fn endpoint(&self) -> &str {
+        self.inner.endpoint()
+    }
+
+    fn stats(&self) -> TransportStats {
+        self.inner.stats()
+    }
+
+    fn close_frame(&self) -> Option<CloseFrame> {
+        self.inner.close_frame()
+    }
+
+    fn last_frame_at(&self) -> Option<std::time::Instant> {
+        self.inner.last_frame_at()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::MockTransport;
+
+    #[tokio::test]
+    async fn test_tap_captures_both_directions_unparsed() {
+        let (tap, mut frames) = MessageTap::channel();
+        let mut mock = MockTransport::new("wss://mock.test");
+        mock.push_response(r#"{"channel":"book","type":"upd"#);
+        let mut transport = tap.wrap(Box::new(mock));
+
+        transport.connect().await.unwrap();
+        transport.send(r#"{"method":"ping"}"#).await.unwrap();
+        transport.recv().await.unwrap();
+
+        let sent = frames.try_recv().unwrap();
+        assert_eq!(sent.direction, Direction::Outbound);
+        assert_eq!(sent.text, r#"{"method":"ping"}"#);
+        let received = frames.try_recv().unwrap();
+        assert_eq!(received.direction, Direction::Inbound);
+        assert_eq!(received.text, r#"{"channel":"book","type":"upd"#);
+        assert_eq!(received.endpoint, "wss://mock.test");
+
+        let line = serde_json::to_string(&received).unwrap();
+        assert!(line.contains(r#""direction":"inbound""#));
+    }
+
+    #[test]
+    fn test_tokens_are_redacted() {
+        let (tap, mut frames) = MessageTap::channel();
+        let subscribe = r#"{"method":"subscribe","params":{"channel":"executions","token": "abc\"def"},"req_id":1}"#;
+        tap.record(Direction::Outbound, "wss://mock.test", subscribe);
+        assert_eq!(
+            frames.try_recv().unwrap().text,
+            r#"{"method":"subscribe","params":{"channel":"executions","token": "[REDACTED]"},"req_id":1}"#
+        );
+
+        // Not a string value, and not the whole key: left alone
+        let other = r#"{"token":null,"tokens":"x"}"#;
+        tap.record(Direction::Inbound, "wss://mock.test", other);
+        assert_eq!(frames.try_recv().unwrap().text, other);
+
+        let (verbatim, mut frames) = MessageTap::channel();
+        let verbatim = verbatim.with_redacted_fields(Vec::<String>::new());
+        verbatim.record(Direction::Outbound, "wss://mock.test", subscribe);
+        assert_eq!(frames.try_recv().unwrap().text, subscribe);
+    }
+
+    #[test]
+    fn test_channel_sink_is_bounded() {
+        let (tap, mut frames) = MessageTap::channel_with_capacity(2);
+        for text in ["1", "2", "3"] {
+            tap.record(Direction::Inbound, "wss://mock.test", text);
+        }
+        assert_eq!(frames.try_recv().unwrap().text, "1");
+        assert_eq!(frames.try_recv().unwrap().text, "2");
+        assert!(frames.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_file_sink_writes_from_its_thread() {
+        let path = std::env::temp_dir().join(format!("kraken-tap-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let tap = MessageTap::file(&path).unwrap();
+        tap.record(Direction::Outbound, "wss://mock.test", r#"{"token":"secret"}"#);
+        tap.record(Direction::Inbound, "wss://mock.test", "pong");
+        // The last handle drains the queue before returning
+        drop(tap);
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let frames: Vec<RawFrame> = contents.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].text, r#"{"token":"[REDACTED]"}"#);
+        assert_eq!(frames[1].text, "pong");
+    }
+}
@@ -27,15 +27,297 @@
 //! // Create a cancel order request
 //! let cancel_request = client.cancel_order("ORDER123");
 //! ```
+//!
+//! # Automatic retry
+//!
+//! [`TradingClient::execute`] sends a request through a [`TradingSession`] and
+//! interprets error responses with [`KrakenApiError`]. Rate limit and transient
+//! service errors are retried after the backoff from their
+//! [`RecoveryStrategy`], invalid sessions trigger a token refresh followed by a
+//! retry, and errors that need user intervention surface as
+//! [`TradingError::UserAction`]. The request is rebuilt for every attempt so
+//! it carries the current token and a fresh `req_id`.
+//!
+//! With a [`Formatting`] attached ([`TradingClient::with_formatting`]), every
+//! new order, batch order and algorithm child is rounded before it is built:
+//! prices to the pair's tick and quantities down to its lot. Symbols the
+//! formatting has no instrument data for are sent as given, and amends carry
+//! no symbol so they aren't rounded.
+//!
+//! Only idempotent requests (cancels) are resent blindly after a backoff.
+//! New orders get a client order ID, generated if the request has none and
+//! kept across attempts, and are looked up by that ID through
+//! [`TradingSession::find_order`] before being resent: an order that already
+//! reached the book is returned instead of placed twice. Amends, and orders
+//! the session can't look up, fail with [`TradingError::NotRetried`].
+//!
+//! With a [`RiskManager`] attached, orders and amends are checked against its
+//! limits before every attempt and rejected locally with
+//! [`TradingError::Risk`]. Accepted orders, including every order of a
+//! `batch_add`, are tracked as open in the manager.
+//! [`TradingClient::kill_switch`] blocks new orders and cancels all open ones.
+//!
+//! With a [`KrakenRateLimiter`](crate::KrakenRateLimiter) attached, every
+//! attempt first waits until its add, amend or cancel fits under the pair's
+//! trading counter, so orders are paced instead of rejected with
+//! `EOrder:Rate limit exceeded`.
+//!
+//! With a status gate from
+//! [`KrakenConnection::watch_system_status`](crate::KrakenConnection::watch_system_status),
+//! requests the exchange wouldn't accept in its current mode (new orders in
+//! `cancel_only`, anything in `maintenance`) are rejected or held according
+//! to a [`StatusPolicy`], and go out as soon as the status allows them.
+//!
+//! With a pair status gate from
+//! [`KrakenConnection::watch_pair_status`](crate::KrakenConnection::watch_pair_status),
+//! new orders are held to what their pair accepts, per
+//! [`PairStatus::permits`]: plain limit orders on a `post_only` pair are sent
+//! post-only, and orders the pair can't take (market orders in `limit_only`,
+//! anything in `cancel_only`) fail locally with
+//! [`TradingError::PairRestricted`].
 
+use crate::execution::AlgoAction;
+use crate::rate_limiter::SharedRateLimiter;
+use crate::risk::{AmendIntent, OrderIntent, OrderIntents, RiskManager, RiskViolation};
+use async_trait::async_trait;
 use kraken_types::{
     AddOrderParams, AddOrderRequest, AmendOrderParams, AmendOrderRequest,
     BatchAddParams, BatchAddRequest, BatchCancelParams, BatchCancelRequest,
     BatchOrder, CancelAllRequest, CancelOnDisconnectRequest, CancelOrderParams,
-    CancelOrderRequest, Decimal, Side, TimeInForce,
+    CancelOrderRequest, AccountId, Decimal, Formatting, KrakenApiError, KrakenError, OrderType, PairStatus,
+    RecoveryStrategy, Side, SystemStatus, TimeInForce, TradingAction,
 };
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tracing::{debug, field, info, instrument, warn, Span};
+
+/// Default longest wait for the trading counter in [`TradingClient::execute`]
+pub const DEFAULT_RATE_LIMIT_WAIT: Duration = Duration::from_secs(10);
+
+/// Error returned by [`TradingClient::execute`]
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum TradingError {
+    /// Retryable error persisted after all allowed attempts
+    #[error("Request failed after {attempts} attempts: {}", .error.raw)]
+    RetriesExhausted {
+        /// Number of attempts made
+        attempts: u32,
+        /// Last error returned by Kraken
+        error: KrakenApiError,
+    },
+
+    /// Session was still rejected after re-authenticating
+    #[error("Re-authentication did not resolve: {}", .error.raw)]
+    ReauthFailed {
+        /// Last error returned by Kraken
+        error: KrakenApiError,
+    },
+
+    /// Error that needs user intervention (e.g., insufficient funds)
+    #[error("{message}: {}", .error.raw)]
+    UserAction {
+        /// Suggested action
+        message: &'static str,
+        /// Error returned by Kraken
+        error: KrakenApiError,
+    },
+
+    /// Request rejected and not retryable
+    #[error("Request rejected: {}", .0.raw)]
+    Rejected(KrakenApiError),
+
+    /// Retryable error on a request that couldn't be resent safely
+    ///
+    /// Returned for amends, and for orders whose placement a lookup by
+    /// client order ID couldn't rule out.
+    #[error("Request not retried: {}", .error.raw)]
+    NotRetried {
+        /// Error returned by Kraken
+        error: KrakenApiError,
+    },
+
+    /// Session failed to send the request or obtain a token
+    #[error("Session error: {0}")]
+    Session(String),
+
+    /// Response could not be parsed
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+
+    /// Order rejected locally by the risk manager
+    #[error("Risk check failed: {0}")]
+    Risk(#[from] RiskViolation),
+
+    /// Trading counter wouldn't drain within the client's rate limit wait
+    #[error("Trading rate limit: retry after {retry_after:?}")]
+    RateLimited {
+        /// How long until the counter allows the request
+        retry_after: Duration,
+    },
+
+    /// Exchange isn't accepting this request in its current system status
+    #[error("Exchange is in {status} mode")]
+    SystemUnavailable {
+        /// System status that blocked the request
+        status: SystemStatus,
+    },
+
+    /// Pair isn't accepting this order in its current trading status
+    #[error("{symbol} is {status}")]
+    PairRestricted {
+        /// Trading pair symbol
+        symbol: String,
+        /// Pair status that blocked the order
+        status: PairStatus,
+    },
+}
+
+/// Response to a trading request
+#[derive(Debug, Clone, Deserialize)]
+pub struct TradingResponse {
+    /// Method name (add_order, cancel_order, ...)
+    pub method: String,
+    /// Whether the request succeeded
+    pub success: bool,
+    /// Method-specific result (order_id, cl_ord_id, ...)
+    #[serde(default)]
+    pub result: Option<serde_json::Value>,
+    /// Error message if failed
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Echoed request ID
+    #[serde(default)]
+    pub req_id: Option<u64>,
+}
+
+impl TradingResponse {
+    /// Order ID from the result, for order-creating requests
+    pub fn order_id(&self) -> Option<&str> {
+        self.result.as_ref()?.get("order_id")?.as_str()
+    }
+
+    /// Amendment ID from the result of an `amend_order` request
+    pub fn amend_id(&self) -> Option<&str> {
+        self.result.as_ref()?.get("amend_id")?.as_str()
+    }
+
+    /// Order IDs from the result, in request order
+    ///
+    /// One entry for `add_order`, one per order for `batch_add` (whose
+    /// result is a list, or an object with an `orders` list). Orders the
+    /// batch failed to place have no ID and come back as `None`.
+    pub fn order_ids(&self) -> Vec<Option<&str>> {
+        let Some(result) = self.result.as_ref() else {
+            return Vec::new();
+        };
+        let items = match result {
+            serde_json::Value::Array(items) => items,
+            _ => match result.get("orders") {
+                Some(serde_json::Value::Array(items)) => items,
+                _ => return self.order_id().map(Some).into_iter().collect(),
+            },
+        };
+        items
+            .iter()
+            .map(|item| item.get("order_id").and_then(serde_json::Value::as_str))
+            .collect()
+    }
+
+    /// Parsed Kraken error, if the request failed
+    pub fn api_error(&self) -> Option<KrakenApiError> {
+        if self.success {
+            return None;
+        }
+        Some(KrakenApiError::parse(self.error.as_deref().unwrap_or("")))
+    }
+}
+
+/// Limits applied by [`TradingClient::execute`]
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first
+    pub max_attempts: u32,
+    /// Upper bound on any single backoff delay
+    pub max_delay: Duration,
+    /// Maximum number of token refreshes per request
+    pub max_reauths: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            max_delay: Duration::from_secs(60),
+            max_reauths: 1,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy that never retries
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            max_reauths: 0,
+            ..Default::default()
+        }
+    }
+
+    /// Set the maximum number of attempts
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Set the upper bound on backoff delays
+    pub fn with_max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Set the maximum number of token refreshes per request
+    pub fn with_max_reauths(mut self, reauths: u32) -> Self {
+        self.max_reauths = reauths;
+        self
+    }
+}
+
+/// What [`TradingClient::execute`] does while the system status blocks a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatusPolicy {
+    /// Fail at once with [`TradingError::SystemUnavailable`]
+    #[default]
+    Reject,
+    /// Hold the request until the status allows it, for at most `max_wait`
+    Queue {
+        /// Longest time to wait before failing
+        max_wait: Duration,
+    },
+}
+
+/// Channel used by [`TradingClient::execute`] to reach Kraken
+///
+/// Implementations send a request over an authenticated WebSocket and return
+/// the matching response text, and know how to obtain a fresh token.
+#[async_trait]
+pub trait TradingSession: Send {
+    /// Send a JSON request and wait for its response message
+    async fn request(&mut self, json: &str) -> Result<String, TradingError>;
+
+    /// Obtain a new WebSocket token
+    async fn reauthenticate(&mut self) -> Result<String, TradingError>;
+
+    /// Look up an order by client order ID, returning its exchange order ID
+    ///
+    /// Used to reconcile new orders before they are resent. The default
+    /// can't look orders up, so failed orders aren't resent.
+    async fn find_order(&mut self, _cl_ord_id: &str) -> Result<Option<String>, TradingError> {
+        Err(TradingError::Session("order lookup not supported".to_string()))
+    }
+}
 
 /// Trading client for WebSocket order management
 ///
@@ -47,6 +329,26 @@ pub struct TradingClient {
     token: String,
     /// Request ID counter
     req_id_counter: AtomicU64,
+    /// Prefix of generated client order IDs, unique per client
+    cl_ord_prefix: u32,
+    /// Retry limits for `execute`
+    retry_policy: RetryPolicy,
+    /// Pre-trade risk checks for `execute`
+    risk: Option<RiskManager>,
+    /// Trading counter pacing for `execute`
+    rate_limiter: Option<SharedRateLimiter>,
+    /// Longest `execute` waits for the trading counter
+    rate_limit_wait: Duration,
+    /// Exchange system status watched by `execute`
+    status: Option<watch::Receiver<Option<SystemStatus>>>,
+    /// What to do while the status blocks a request
+    status_policy: StatusPolicy,
+    /// Per-pair trading status checked by `execute`
+    pair_status: Option<watch::Receiver<HashMap<String, PairStatus>>>,
+    /// Account the token belongs to
+    account: AccountId,
+    /// Precision new orders are rounded to
+    formatting: Option<Formatting>,
 }
 
 impl TradingClient {
@@ -55,14 +357,194 @@ impl TradingClient {
         Self {
             token,
             req_id_counter: AtomicU64::new(1),
+            cl_ord_prefix: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |d| d.subsec_nanos() ^ d.as_secs() as u32),
+            retry_policy: RetryPolicy::default(),
+            risk: None,
+            rate_limiter: None,
+            rate_limit_wait: DEFAULT_RATE_LIMIT_WAIT,
+            status: None,
+            status_policy: StatusPolicy::default(),
+            pair_status: None,
+            account: AccountId::default(),
+            formatting: None,
         }
     }
 
+    /// Label the account this client trades for
+    ///
+    /// Purely local: it lets trackers and logs attribute orders when one
+    /// process trades several API keys.
+    pub fn with_account(mut self, account: impl Into<AccountId>) -> Self {
+        self.account = account.into();
+        self
+    }
+
+    /// Account this client trades for
+    pub fn account(&self) -> &AccountId {
+        &self.account
+    }
+
+    /// Round new orders with the pair precision in `formatting`
+    ///
+    /// Pass [`KrakenConnection::formatting`](crate::KrakenConnection::formatting)
+    /// to use what the instrument channel reported. Prices are rounded to the
+    /// nearest tick and quantities down to the lot; symbols without
+    /// instrument data are left alone.
+    pub fn with_formatting(mut self, formatting: Formatting) -> Self {
+        self.formatting = Some(formatting);
+        self
+    }
+
+    /// Replace the precision new orders are rounded to
+    pub fn set_formatting(&mut self, formatting: Formatting) {
+        self.formatting = Some(formatting);
+    }
+
+    /// Get the formatting, if configured
+    pub fn formatting(&self) -> Option<&Formatting> {
+        self.formatting.as_ref()
+    }
+
+    /// Pace requests sent by [`execute`](Self::execute) with a rate limiter
+    ///
+    /// The limiter can be shared with other clients trading on the same
+    /// account, since Kraken's counters are per account and pair.
+    pub fn with_rate_limiter(mut self, limiter: SharedRateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Fail with [`TradingError::RateLimited`] instead of waiting longer
+    /// than `max_wait` for the trading counter (default:
+    /// [`DEFAULT_RATE_LIMIT_WAIT`])
+    pub fn with_rate_limit_wait(mut self, max_wait: Duration) -> Self {
+        self.rate_limit_wait = max_wait;
+        self
+    }
+
+    /// Get the rate limiter, if configured
+    pub fn rate_limiter(&self) -> Option<&SharedRateLimiter> {
+        self.rate_limiter.as_ref()
+    }
+
+    /// Pause requests the exchange's system status doesn't allow
+    ///
+    /// New orders and amends need a status that accepts orders; cancels only
+    /// fail in maintenance. An unknown status lets everything through.
+    pub fn with_status_gate(
+        mut self,
+        status: watch::Receiver<Option<SystemStatus>>,
+        policy: StatusPolicy,
+    ) -> Self {
+        self.status = Some(status);
+        self.status_policy = policy;
+        self
+    }
+
+    /// Last system status seen by the status gate, if configured
+    pub fn system_status(&self) -> Option<SystemStatus> {
+        self.status.as_ref().and_then(|status| *status.borrow())
+    }
+
+    /// Hold new orders to what each pair's trading status accepts
+    ///
+    /// Plain limit orders on a `post_only` pair are built post-only; orders
+    /// the pair doesn't permit fail with [`TradingError::PairRestricted`].
+    /// Pairs without a known status are let through.
+    pub fn with_pair_status_gate(mut self, statuses: watch::Receiver<HashMap<String, PairStatus>>) -> Self {
+        self.pair_status = Some(statuses);
+        self
+    }
+
+    /// Last trading status seen for a pair by the pair status gate
+    pub fn pair_status(&self, symbol: &str) -> Option<PairStatus> {
+        self.pair_status.as_ref()?.borrow().get(symbol).copied()
+    }
+
+    /// Check orders against a risk manager before sending them
+    pub fn with_risk_manager(mut self, risk: RiskManager) -> Self {
+        self.risk = Some(risk);
+        self
+    }
+
+    /// Get the risk manager, if configured
+    pub fn risk_manager(&self) -> Option<&RiskManager> {
+        self.risk.as_ref()
+    }
+
+    /// Get the risk manager mutably (to feed executions, positions, marks)
+    pub fn risk_manager_mut(&mut self) -> Option<&mut RiskManager> {
+        self.risk.as_mut()
+    }
+
+    /// Set the retry policy used by [`execute`](Self::execute)
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Get the retry policy
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.retry_policy
+    }
+
     /// Get the next request ID
     fn next_req_id(&self) -> u64 {
         self.req_id_counter.fetch_add(1, Ordering::SeqCst)
     }
 
+    /// Generate a client order ID (18 characters, Kraken's free-text limit)
+    fn next_cl_ord_id(&self) -> String {
+        format!("hv{:08x}{:08x}", self.cl_ord_prefix, self.next_req_id() as u32)
+    }
+
+    /// Formatting to round `symbol`'s orders with, if it has instrument data
+    fn formatting_for(&self, symbol: &str) -> Option<&Formatting> {
+        self.formatting.as_ref().filter(|f| f.get(symbol).is_some())
+    }
+
+    /// Build an add order request with rounded quantity and prices
+    ///
+    /// Plain limit orders on a `post_only` pair are made post-only.
+    fn add_request(&self, mut params: AddOrderParams) -> AddOrderRequest {
+        if let Some(formatting) = self.formatting_for(&params.symbol) {
+            let symbol = params.symbol.as_str();
+            params.order_qty = formatting.round_qty(symbol, params.order_qty);
+            params.limit_price = params.limit_price.map(|p| formatting.round_to_tick(symbol, p));
+            params.trigger_price = params.trigger_price.map(|p| formatting.round_to_tick(symbol, p));
+        }
+        if self.pair_status(&params.symbol) == Some(PairStatus::PostOnly)
+            && params.order_type == "limit"
+            && params.post_only != Some(true)
+        {
+            debug!(symbol = %params.symbol, "Pair is post_only, sending limit order post-only");
+            params.post_only = Some(true);
+        }
+        AddOrderRequest::new(params).with_req_id(self.next_req_id())
+    }
+
+    /// Batch orders with rounded quantities and prices
+    ///
+    /// Plain limit orders on a `post_only` pair are made post-only.
+    fn round_batch(&self, mut orders: Vec<BatchOrder>) -> Vec<BatchOrder> {
+        for order in &mut orders {
+            if let Some(formatting) = self.formatting_for(&order.symbol) {
+                order.order_qty = formatting.round_qty(&order.symbol, order.order_qty);
+                order.limit_price = order.limit_price.map(|p| formatting.round_to_tick(&order.symbol, p));
+            }
+            if self.pair_status(&order.symbol) == Some(PairStatus::PostOnly)
+                && order.order_type == "limit"
+                && order.post_only != Some(true)
+            {
+                debug!(symbol = %order.symbol, "Pair is post_only, sending limit order post-only");
+                order.post_only = Some(true);
+            }
+        }
+        orders
+    }
+
     /// Update the authentication token
     pub fn set_token(&mut self, token: String) {
         self.token = token;
@@ -90,9 +572,10 @@ impl TradingClient {
             cl_ord_id: None,
             post_only: None,
             reduce_only: None,
+            margin: None,
             token: self.token.clone(),
         };
-        AddOrderRequest::new(params).with_req_id(self.next_req_id())
+        self.add_request(params)
     }
 
     /// Create a limit order request
@@ -114,9 +597,10 @@ impl TradingClient {
             cl_ord_id: None,
             post_only: None,
             reduce_only: None,
+            margin: None,
             token: self.token.clone(),
         };
-        AddOrderRequest::new(params).with_req_id(self.next_req_id())
+        self.add_request(params)
     }
 
     /// Create a post-only limit order request
@@ -138,9 +622,10 @@ impl TradingClient {
             cl_ord_id: None,
             post_only: Some(true),
             reduce_only: None,
+            margin: None,
             token: self.token.clone(),
         };
-        AddOrderRequest::new(params).with_req_id(self.next_req_id())
+        self.add_request(params)
     }
 
     /// Create a stop-loss order request
@@ -162,9 +647,10 @@ impl TradingClient {
             cl_ord_id: None,
             post_only: None,
             reduce_only: None,
+            margin: None,
             token: self.token.clone(),
         };
-        AddOrderRequest::new(params).with_req_id(self.next_req_id())
+        self.add_request(params)
     }
 
     /// Create a stop-loss limit order request
@@ -187,9 +673,10 @@ impl TradingClient {
             cl_ord_id: None,
             post_only: None,
             reduce_only: None,
+            margin: None,
             token: self.token.clone(),
         };
-        AddOrderRequest::new(params).with_req_id(self.next_req_id())
+        self.add_request(params)
     }
 
     /// Create a take-profit order request
@@ -211,14 +698,15 @@ impl TradingClient {
             cl_ord_id: None,
             post_only: None,
             reduce_only: None,
+            margin: None,
             token: self.token.clone(),
         };
-        AddOrderRequest::new(params).with_req_id(self.next_req_id())
+        self.add_request(params)
     }
 
     /// Create a custom add order request with all parameters
     pub fn custom_order(&self, params: AddOrderParams) -> AddOrderRequest {
-        AddOrderRequest::new(params).with_req_id(self.next_req_id())
+        self.add_request(params)
     }
 
     // ========================================================================
@@ -311,7 +799,7 @@ impl TradingClient {
     /// Create a batch add orders request
     pub fn batch_add(&self, orders: Vec<BatchOrder>) -> BatchAddRequest {
         let params = BatchAddParams {
-            orders,
+            orders: self.round_batch(orders),
             token: self.token.clone(),
             deadline: None,
             validate: None,
@@ -322,7 +810,7 @@ impl TradingClient {
     /// Create a batch add orders request with validation only
     pub fn batch_add_validate(&self, orders: Vec<BatchOrder>) -> BatchAddRequest {
         let params = BatchAddParams {
-            orders,
+            orders: self.round_batch(orders),
             token: self.token.clone(),
             deadline: None,
             validate: Some(true),
@@ -339,6 +827,319 @@ impl TradingClient {
         };
         BatchCancelRequest::new(params).with_req_id(self.next_req_id())
     }
+
+    /// Create the request for an execution algorithm action
+    pub fn algo_request(&self, action: &AlgoAction) -> AlgoRequest {
+        match action {
+            AlgoAction::Place(child) => {
+                let (order_type, time_in_force) = match child.limit_price {
+                    Some(_) => ("limit", Some(TimeInForce::GTC)),
+                    None => ("market", None),
+                };
+                AlgoRequest::Add(self.custom_order(AddOrderParams {
+                    order_type: order_type.to_string(),
+                    side: child.side,
+                    symbol: child.symbol.clone(),
+                    order_qty: child.qty,
+                    limit_price: child.limit_price,
+                    time_in_force,
+                    trigger_price: None,
+                    cl_ord_id: Some(child.cl_ord_id.clone()),
+                    post_only: child.post_only.then_some(true),
+                    reduce_only: None,
+                    margin: None,
+                    token: self.token.clone(),
+                }))
+            }
+            AlgoAction::Cancel { cl_ord_id } => AlgoRequest::Cancel(self.cancel_by_client_id(cl_ord_id)),
+            AlgoAction::Amend {
+                order_id,
+                limit_price,
+                order_qty,
+            } => AlgoRequest::Amend(self.amend_order(AmendOrderParams {
+                order_id: order_id.clone(),
+                limit_price: Some(*limit_price),
+                trigger_price: None,
+                order_qty: *order_qty,
+                post_only: None,
+                token: self.token.clone(),
+            })),
+        }
+    }
+
+    // ========================================================================
+    // Execution
+    // ========================================================================
+
+    /// Send a request, retrying according to Kraken's error code
+    ///
+    /// `build` is called for every attempt, e.g.
+    /// `client.execute(&mut session, |c| c.cancel_order("O1"))`.
+    /// Orders are checked by the risk manager (if any) before each attempt,
+    /// and wait for trading counter budget if a rate limiter is attached.
+    /// New orders keep the client order IDs of the first attempt, and are
+    /// only resent after [`TradingSession::find_order`] finds none of them.
+    /// Runs in an `order_submit` span recording the attempts and order ID.
+    #[instrument(
+        skip_all,
+        name = "order_submit",
+        fields(orders = field::Empty, attempts = field::Empty, order_id = field::Empty)
+    )]
+    pub async fn execute<R, S>(
+        &mut self,
+        session: &mut S,
+        mut build: impl FnMut(&Self) -> R,
+    ) -> Result<TradingResponse, TradingError>
+    where
+        R: ToWsJson + OrderIntents + TradingActions + OrderKinds + RetrySafety,
+        S: TradingSession + ?Sized,
+    {
+        let mut attempts = 0;
+        let mut retries = 0;
+        let mut reauths = 0;
+        let mut cl_ord_ids: Vec<String> = Vec::new();
+
+        loop {
+            let mut request = build(self);
+            cl_ord_ids = request.client_order_ids(&mut |i| {
+                cl_ord_ids.get(i).cloned().unwrap_or_else(|| self.next_cl_ord_id())
+            });
+            let orders = request.order_intents();
+            let amend = request.amend_intent();
+            Span::current().record("orders", orders.len());
+            self.await_status(&request.trading_actions()).await?;
+            self.check_pair_status(&request.order_kinds())?;
+            if let Some(risk) = self.risk.as_mut() {
+                let now = Instant::now();
+                risk.check(&orders, now)?;
+                if let Some(amend) = &amend {
+                    risk.check_amend(amend)?;
+                }
+                risk.record_submission(orders.len(), now);
+            }
+            let json = request
+                .to_ws_json()
+                .map_err(|e| TradingError::InvalidResponse(e.to_string()))?;
+            if let Some(limiter) = &self.rate_limiter {
+                for action in request.trading_actions() {
+                    limiter
+                        .acquire_trading(&action, self.rate_limit_wait)
+                        .await
+                        .map_err(|e| match e {
+                            KrakenError::RateLimited { retry_after } => TradingError::RateLimited { retry_after },
+                            e => TradingError::Session(e.to_string()),
+                        })?;
+                }
+            }
+            attempts += 1;
+            Span::current().record("attempts", attempts);
+
+            let text = session.request(&json).await?;
+            let response: TradingResponse = serde_json::from_str(&text)
+                .map_err(|e| TradingError::InvalidResponse(e.to_string()))?;
+            let Some(error) = response.api_error() else {
+                self.record_accepted(&response, &orders);
+                if let (Some(risk), Some(amend)) = (self.risk.as_mut(), &amend) {
+                    risk.order_amended(amend);
+                }
+                return Ok(response);
+            };
+
+            match error.recovery_strategy() {
+                strategy @ (RecoveryStrategy::Backoff { .. } | RecoveryStrategy::Retry { .. }) => {
+                    if !request.idempotent() {
+                        if cl_ord_ids.is_empty() {
+                            return Err(TradingError::NotRetried { error });
+                        }
+                        if let Some(placed) = Self::reconcile(session, &response.method, &cl_ord_ids, &error).await? {
+                            self.record_accepted(&placed, &orders);
+                            return Ok(placed);
+                        }
+                    }
+                    let limit = strategy
+                        .max_attempts()
+                        .map_or(self.retry_policy.max_attempts, |m| {
+                            self.retry_policy.max_attempts.min(m + 1)
+                        });
+                    if attempts >= limit {
+                        return Err(TradingError::RetriesExhausted { attempts, error });
+                    }
+                    let delay = strategy
+                        .delay_for_attempt(retries)
+                        .unwrap_or_default()
+                        .min(self.retry_policy.max_delay);
+                    retries += 1;
+                    debug!(error = %error.raw, ?delay, attempts, "Retrying trading request");
+                    tokio::time::sleep(delay).await;
+                }
+                RecoveryStrategy::Reauthenticate => {
+                    if reauths >= self.retry_policy.max_reauths {
+                        return Err(TradingError::ReauthFailed { error });
+                    }
+                    reauths += 1;
+                    warn!(error = %error.raw, "Trading session rejected, re-authenticating");
+                    let token = session.reauthenticate().await?;
+                    self.set_token(token);
+                }
+                RecoveryStrategy::UserAction { message } => {
+                    return Err(TradingError::UserAction { message, error });
+                }
+                _ => return Err(TradingError::Rejected(error)),
+            }
+        }
+    }
+
+    /// Book-keeping for a request the exchange accepted
+    ///
+    /// Every placed order is tracked as open by the risk manager and the
+    /// rate limiter, matched to its intent by position in the request.
+    fn record_accepted(&mut self, response: &TradingResponse, orders: &[OrderIntent]) {
+        if let Some(order_id) = response.order_id() {
+            Span::current().record("order_id", order_id);
+        }
+        if orders.is_empty() {
+            return;
+        }
+        for (order_id, order) in response.order_ids().into_iter().zip(orders) {
+            let Some(order_id) = order_id else {
+                continue;
+            };
+            if let Some(risk) = self.risk.as_mut() {
+                risk.order_placed(order_id, order.clone());
+            }
+            if let Some(limiter) = &self.rate_limiter {
+                limiter.trading_order_opened(order_id, &order.symbol);
+            }
+        }
+    }
+
+    /// Look up new orders by client order ID before resending them
+    ///
+    /// Returns `None` if none of them reached the exchange, so the request
+    /// can be resent, and a synthesized success response if all of them did.
+    /// Anything else (a lookup failure, or only some orders placed) fails
+    /// with [`TradingError::NotRetried`].
+    async fn reconcile<S>(
+        session: &mut S,
+        method: &str,
+        cl_ord_ids: &[String],
+        error: &KrakenApiError,
+    ) -> Result<Option<TradingResponse>, TradingError>
+    where
+        S: TradingSession + ?Sized,
+    {
+        let not_retried = || TradingError::NotRetried { error: error.clone() };
+        let mut placed = Vec::new();
+        for cl_ord_id in cl_ord_ids {
+            match session.find_order(cl_ord_id).await {
+                Ok(Some(order_id)) => placed.push(serde_json::json!({ "order_id": order_id, "cl_ord_id": cl_ord_id })),
+                Ok(None) => {}
+                Err(e) => {
+                    warn!(error = %e, cl_ord_id, "Can't reconcile order, not resending");
+                    return Err(not_retried());
+                }
+            }
+        }
+        if placed.is_empty() {
+            return Ok(None);
+        }
+        if placed.len() < cl_ord_ids.len() {
+            return Err(not_retried());
+        }
+        info!(orders = placed.len(), "Order already placed, not resending");
+        let result = match <[_; 1]>::try_from(placed) {
+            Ok([single]) => single,
+            Err(placed) => serde_json::Value::Array(placed),
+        };
+        Ok(Some(TradingResponse {
+            method: method.to_string(),
+            success: true,
+            result: Some(result),
+            error: None,
+            req_id: None,
+        }))
+    }
+
+    /// Wait until the system status allows the actions, per the status policy
+    async fn await_status(&mut self, actions: &[TradingAction]) -> Result<(), TradingError> {
+        let Some(status) = self.status.as_mut() else {
+            return Ok(());
+        };
+        let adds = actions
+            .iter()
+            .any(|action| !matches!(action, TradingAction::Cancel { .. }));
+        let allowed = |current: &Option<SystemStatus>| match current {
+            None => true,
+            Some(s) if adds => s.allows_new_orders(),
+            Some(s) => s.allows_cancels(),
+        };
+
+        let Some(blocked) = *status.borrow() else {
+            return Ok(());
+        };
+        if allowed(&Some(blocked)) {
+            return Ok(());
+        }
+        let StatusPolicy::Queue { max_wait } = self.status_policy else {
+            return Err(TradingError::SystemUnavailable { status: blocked });
+        };
+
+        info!(status = %blocked, ?max_wait, "Holding trading request until the exchange accepts it");
+        let resumed = matches!(
+            tokio::time::timeout(max_wait, status.wait_for(allowed)).await,
+            Ok(Ok(_))
+        );
+        if resumed {
+            return Ok(());
+        }
+        Err(TradingError::SystemUnavailable {
+            status: (*status.borrow()).unwrap_or(blocked),
+        })
+    }
+
+    /// Reject new orders their pair's trading status doesn't permit
+    ///
+    /// Order types the SDK doesn't know are only checked against whether the
+    /// pair takes new orders at all.
+    fn check_pair_status(&self, orders: &[OrderKind]) -> Result<(), TradingError> {
+        let Some(statuses) = self.pair_status.as_ref() else {
+            return Ok(());
+        };
+        let statuses = statuses.borrow();
+        for order in orders {
+            let Some(&status) = statuses.get(&order.symbol) else {
+                continue;
+            };
+            let permitted = match OrderType::parse(&order.order_type) {
+                Some(order_type) => status.permits(order_type, order.post_only),
+                None => status.allows_new_orders(),
+            };
+            if !permitted {
+                return Err(TradingError::PairRestricted {
+                    symbol: order.symbol.clone(),
+                    status,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Block new orders and cancel every open order
+    ///
+    /// Installs a default risk manager if none is configured. New orders stay
+    /// blocked until [`RiskManager::release_kill_switch`] is called. Once the
+    /// cancel is acknowledged, the manager forgets its open orders.
+    pub async fn kill_switch<S>(&mut self, session: &mut S) -> Result<TradingResponse, TradingError>
+    where
+        S: TradingSession + ?Sized,
+    {
+        self.risk.get_or_insert_with(RiskManager::default).engage_kill_switch();
+        let response = self.execute(session, |c| c.cancel_all()).await?;
+        if let Some(risk) = self.risk.as_mut() {
+            risk.orders_cleared();
+        }
+        Ok(response)
+    }
 }
 
 /// Trait for types that can be serialized to JSON for WebSocket sending
@@ -356,10 +1157,249 @@ impl ToWsJson for CancelAllRequest {}
 impl ToWsJson for CancelOnDisconnectRequest {}
 impl ToWsJson for BatchAddRequest {}
 impl ToWsJson for BatchCancelRequest {}
+impl ToWsJson for AlgoRequest {}
+
+/// Trading counter actions a request performs
+///
+/// Used by [`TradingClient::execute`] to charge the rate limiter. Requests
+/// that don't touch the per-pair counter use the default (no actions).
+pub trait TradingActions {
+    /// Actions to charge against the trading counter
+    fn trading_actions(&self) -> Vec<TradingAction> {
+        Vec::new()
+    }
+}
+
+impl TradingActions for AddOrderRequest {
+    fn trading_actions(&self) -> Vec<TradingAction> {
+        vec![TradingAction::Add {
+            symbol: self.params.symbol.clone(),
+        }]
+    }
+}
+
+impl TradingActions for BatchAddRequest {
+    fn trading_actions(&self) -> Vec<TradingAction> {
+        self.params
+            .orders
+            .iter()
+            .map(|o| TradingAction::Add { symbol: o.symbol.clone() })
+            .collect()
+    }
+}
+
+impl TradingActions for AmendOrderRequest {
+    fn trading_actions(&self) -> Vec<TradingAction> {
+        vec![TradingAction::Amend {
+            order_id: self.params.order_id.clone(),
+        }]
+    }
+}
+
+impl TradingActions for CancelOrderRequest {
+    fn trading_actions(&self) -> Vec<TradingAction> {
+        cancel_actions(&self.params.order_id)
+    }
+}
+
+impl TradingActions for BatchCancelRequest {
+    fn trading_actions(&self) -> Vec<TradingAction> {
+        cancel_actions(&self.params.orders)
+    }
+}
+
+impl TradingActions for CancelAllRequest {}
+impl TradingActions for CancelOnDisconnectRequest {}
+
+impl TradingActions for AlgoRequest {
+    fn trading_actions(&self) -> Vec<TradingAction> {
+        match self {
+            Self::Add(request) => request.trading_actions(),
+            Self::Cancel(request) => request.trading_actions(),
+            Self::Amend(request) => request.trading_actions(),
+        }
+    }
+}
+
+/// New order as checked against its pair's trading status
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OrderKind {
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Order type as sent to Kraken (e.g. "limit")
+    pub order_type: String,
+    /// Whether the order is post-only
+    pub post_only: bool,
+}
+
+/// Order types a request submits
+///
+/// Used by [`TradingClient::execute`] to check new orders against a pair
+/// status gate. Requests that create no orders use the default (none).
+pub trait OrderKinds {
+    /// Symbol, type and post-only flag of each new order
+    fn order_kinds(&self) -> Vec<OrderKind> {
+        Vec::new()
+    }
+}
+
+impl OrderKinds for AddOrderRequest {
+    fn order_kinds(&self) -> Vec<OrderKind> {
+        vec![OrderKind {
+            symbol: self.params.symbol.clone(),
+            order_type: self.params.order_type.clone(),
+            post_only: self.params.post_only == Some(true),
+        }]
+    }
+}
+
+impl OrderKinds for BatchAddRequest {
+    fn order_kinds(&self) -> Vec<OrderKind> {
+        self.params
+            .orders
+            .iter()
+            .map(|o| OrderKind {
+                symbol: o.symbol.clone(),
+                order_type: o.order_type.clone(),
+                post_only: o.post_only == Some(true),
+            })
+            .collect()
+    }
+}
+
+impl OrderKinds for AmendOrderRequest {}
+impl OrderKinds for CancelOrderRequest {}
+impl OrderKinds for BatchCancelRequest {}
+impl OrderKinds for CancelAllRequest {}
+impl OrderKinds for CancelOnDisconnectRequest {}
+
+impl OrderKinds for AlgoRequest {
+    fn order_kinds(&self) -> Vec<OrderKind> {
+        match self {
+            Self::Add(request) => request.order_kinds(),
+            Self::Cancel(_) | Self::Amend(_) => Vec::new(),
+        }
+    }
+}
+
+/// Whether [`TradingClient::execute`] may resend a request
+///
+/// Requests are not idempotent by default. New orders are made safe to
+/// resend by client order IDs that stay the same across attempts.
+pub trait RetrySafety {
+    /// Whether sending the request twice has the same effect as once
+    fn idempotent(&self) -> bool {
+        false
+    }
+
+    /// Client order IDs of the orders this request creates
+    ///
+    /// Orders without one get `assign(index)`, with `index` counting the
+    /// orders of the request.
+    fn client_order_ids(&mut self, _assign: &mut dyn FnMut(usize) -> String) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+impl RetrySafety for AddOrderRequest {
+    fn client_order_ids(&mut self, assign: &mut dyn FnMut(usize) -> String) -> Vec<String> {
+        vec![self.params.cl_ord_id.get_or_insert_with(|| assign(0)).clone()]
+    }
+}
+
+impl RetrySafety for BatchAddRequest {
+    fn client_order_ids(&mut self, assign: &mut dyn FnMut(usize) -> String) -> Vec<String> {
+        self.params
+            .orders
+            .iter_mut()
+            .enumerate()
+            .map(|(i, order)| order.cl_ord_id.get_or_insert_with(|| assign(i)).clone())
+            .collect()
+    }
+}
+
+impl RetrySafety for AmendOrderRequest {}
+
+impl RetrySafety for CancelOrderRequest {
+    fn idempotent(&self) -> bool {
+        true
+    }
+}
+
+impl RetrySafety for BatchCancelRequest {
+    fn idempotent(&self) -> bool {
+        true
+    }
+}
+
+impl RetrySafety for CancelAllRequest {
+    fn idempotent(&self) -> bool {
+        true
+    }
+}
+
+impl RetrySafety for CancelOnDisconnectRequest {
+    fn idempotent(&self) -> bool {
+        true
+    }
+}
+
+impl RetrySafety for AlgoRequest {
+    fn idempotent(&self) -> bool {
+        match self {
+            Self::Add(request) => request.idempotent(),
+            Self::Cancel(request) => request.idempotent(),
+            Self::Amend(request) => request.idempotent(),
+        }
+    }
+
+    fn client_order_ids(&mut self, assign: &mut dyn FnMut(usize) -> String) -> Vec<String> {
+        match self {
+            Self::Add(request) => request.client_order_ids(assign),
+            Self::Cancel(_) | Self::Amend(_) => Vec::new(),
+        }
+    }
+}
+
+fn cancel_actions(order_ids: &[String]) -> Vec<TradingAction> {
+    order_ids
+        .iter()
+        .map(|id| TradingAction::Cancel { order_id: id.clone() })
+        .collect()
+}
+
+/// Request produced for an [`AlgoAction`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum AlgoRequest {
+    /// Place a child order
+    Add(AddOrderRequest),
+    /// Cancel a child order
+    Cancel(CancelOrderRequest),
+    /// Reprice a child order
+    Amend(AmendOrderRequest),
+}
+
+impl OrderIntents for AlgoRequest {
+    fn order_intents(&self) -> Vec<OrderIntent> {
+        match self {
+            Self::Add(request) => request.order_intents(),
+            Self::Cancel(_) | Self::Amend(_) => Vec::new(),
+        }
+    }
+
+    fn amend_intent(&self) -> Option<AmendIntent> {
+        match self {
+            Self::Amend(request) => request.amend_intent(),
+            Self::Add(_) | Self::Cancel(_) => None,
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rust_decimal_macros::dec;
 
     #[test]
     fn test_market_order() {
@@ -389,6 +1429,56 @@ mod tests {
         assert!(json.contains("\"limit_price\":\"3000\""));
     }
 
+    #[test]
+    fn test_margin_order() {
+        let client = TradingClient::new("test_token".to_string());
+        let order = client
+            .market_order("BTC/USD", Side::Sell, Decimal::ONE)
+            .with_margin(true)
+            .with_reduce_only(true);
+
+        let json = serde_json::to_string(&order).unwrap();
+        assert!(json.contains("\"margin\":true"));
+        assert!(json.contains("\"reduce_only\":true"));
+
+        let spot = client.market_order("BTC/USD", Side::Buy, Decimal::ONE);
+        assert!(!serde_json::to_string(&spot).unwrap().contains("margin"));
+    }
+
+    #[test]
+    fn test_orders_are_rounded_to_pair_precision() {
+        use kraken_types::Precision;
+
+        let mut precision = Precision::new(1, 4);
+        precision.price_increment = Some(dec!(0.5));
+        let client = TradingClient::new("test_token".to_string())
+            .with_formatting(Formatting::new().with_precision("BTC/USD", precision));
+
+        let order = client.limit_order("BTC/USD", Side::Buy, dec!(0.123456), dec!(50000.74));
+        assert_eq!(order.params.order_qty, dec!(0.1234));
+        assert_eq!(order.params.limit_price, Some(dec!(50000.5)));
+
+        let stop = client.stop_loss_order("BTC/USD", Side::Sell, dec!(1), dec!(49000.3));
+        assert_eq!(stop.params.trigger_price, Some(dec!(49000.5)));
+
+        let batch = client.batch_add(vec![BatchOrder {
+            order_type: "limit".to_string(),
+            side: Side::Buy,
+            symbol: "BTC/USD".to_string(),
+            order_qty: dec!(2.00009),
+            limit_price: Some(dec!(100.2)),
+            cl_ord_id: None,
+            post_only: None,
+        }]);
+        assert_eq!(batch.params.orders[0].order_qty, dec!(2));
+        assert_eq!(batch.params.orders[0].limit_price, Some(dec!(100)));
+
+        // No instrument data for the pair: sent as given
+        let other = client.limit_order("XRP/USD", Side::Buy, dec!(1.123456789), dec!(0.51234));
+        assert_eq!(other.params.order_qty, dec!(1.123456789));
+        assert_eq!(other.params.limit_price, Some(dec!(0.51234)));
+    }
+
     #[test]
     fn test_cancel_order() {
         let client = TradingClient::new("test_token".to_string());
@@ -408,4 +1498,404 @@ mod tests {
 
         assert!(order1.req_id.unwrap() < order2.req_id.unwrap());
     }
+
+    /// Session replaying canned responses and recording sent requests
+    struct MockSession {
+        responses: Vec<&'static str>,
+        sent: Vec<String>,
+        reauths: u32,
+        /// Orders `find_order` reports, or None if lookups aren't supported
+        placed: Option<Vec<(String, String)>>,
+    }
+
+    impl MockSession {
+        fn new(mut responses: Vec<&'static str>) -> Self {
+            responses.reverse();
+            Self {
+                responses,
+                sent: Vec::new(),
+                reauths: 0,
+                placed: None,
+            }
+        }
+
+        fn with_lookup(mut self, placed: Vec<(String, String)>) -> Self {
+            self.placed = Some(placed);
+            self
+        }
+    }
+
+    #[async_trait]
+    impl TradingSession for MockSession {
+        async fn request(&mut self, json: &str) -> Result<String, TradingError> {
+            self.sent.push(json.to_string());
+            self.responses
+                .pop()
+                .map(str::to_string)
+                .ok_or_else(|| TradingError::Session("no response".into()))
+        }
+
+        async fn reauthenticate(&mut self) -> Result<String, TradingError> {
+            self.reauths += 1;
+            Ok(format!("token_{}", self.reauths))
+        }
+
+        async fn find_order(&mut self, cl_ord_id: &str) -> Result<Option<String>, TradingError> {
+            let placed = self
+                .placed
+                .as_ref()
+                .ok_or_else(|| TradingError::Session("no lookup".into()))?;
+            Ok(placed.iter().find(|(cl, _)| cl == cl_ord_id).map(|(_, id)| id.clone()))
+        }
+    }
+
+    const OK: &str = r#"{"method":"cancel_order","success":true,"result":{"order_id":"O1"},"req_id":1}"#;
+    const RATE_LIMITED: &str = r#"{"method":"add_order","success":false,"error":"EAPI:Rate limit exceeded"}"#;
+    const INVALID_SESSION: &str = r#"{"method":"add_order","success":false,"error":"EAPI:Invalid session"}"#;
+    const NO_FUNDS: &str = r#"{"method":"add_order","success":false,"error":"EOrder:Insufficient funds"}"#;
+
+    fn fast_client() -> TradingClient {
+        TradingClient::new("test_token".to_string())
+            .with_retry_policy(RetryPolicy::default().with_max_delay(Duration::from_millis(1)))
+    }
+
+    #[tokio::test]
+    async fn test_execute_retries_rate_limit() {
+        let mut client = fast_client();
+        let mut session = MockSession::new(vec![RATE_LIMITED, RATE_LIMITED, OK]);
+
+        let response = client
+            .execute(&mut session, |c| c.cancel_order("O1"))
+            .await
+            .unwrap();
+        assert!(response.success);
+        assert_eq!(session.sent.len(), 3);
+        // Each attempt gets a fresh req_id
+        assert_ne!(session.sent[0], session.sent[1]);
+
+        let mut session = MockSession::new(vec![RATE_LIMITED; 5]);
+        let err = client
+            .with_retry_policy(RetryPolicy::none())
+            .execute(&mut session, |c| c.cancel_order("O1"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TradingError::RetriesExhausted { attempts: 1, .. }));
+    }
+
+    fn cl_ord_id(json: &str) -> String {
+        let value: serde_json::Value = serde_json::from_str(json).unwrap();
+        value["params"]["cl_ord_id"].as_str().unwrap().to_string()
+    }
+
+    #[tokio::test]
+    async fn test_execute_reconciles_orders_before_resending() {
+        let mut client = fast_client();
+        let order = |c: &TradingClient| c.market_order("BTC/USD", Side::Buy, Decimal::ONE);
+
+        // Without a lookup, a failed order isn't resent
+        let mut session = MockSession::new(vec![RATE_LIMITED, OK]);
+        let err = client.execute(&mut session, order).await.unwrap_err();
+        assert!(matches!(err, TradingError::NotRetried { .. }));
+        assert_eq!(session.sent.len(), 1);
+
+        // Not found: resent under the same client order ID
+        let mut session = MockSession::new(vec![RATE_LIMITED, OK]).with_lookup(Vec::new());
+        client.execute(&mut session, order).await.unwrap();
+        assert_eq!(session.sent.len(), 2);
+        let id = cl_ord_id(&session.sent[0]);
+        assert_eq!(id.len(), 18);
+        assert_eq!(cl_ord_id(&session.sent[1]), id);
+
+        // Found: the placed order is returned, not placed twice
+        let mut session = MockSession::new(vec![RATE_LIMITED, OK])
+            .with_lookup(vec![("mine".to_string(), "OPLACED".to_string())]);
+        let response = client
+            .execute(&mut session, |c| order(c).with_cl_ord_id("mine"))
+            .await
+            .unwrap();
+        assert_eq!(response.order_id(), Some("OPLACED"));
+        assert_eq!(session.sent.len(), 1);
+
+        // Amends are never resent
+        let mut session = MockSession::new(vec![RATE_LIMITED, OK]).with_lookup(Vec::new());
+        let err = client
+            .execute(&mut session, |c| c.amend_qty("O1", Decimal::ONE))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TradingError::NotRetried { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_execute_reauthenticates_invalid_session() {
+        let mut client = fast_client();
+        let mut session = MockSession::new(vec![INVALID_SESSION, OK]);
+
+        client
+            .execute(&mut session, |c| c.cancel_order("O1"))
+            .await
+            .unwrap();
+        assert_eq!(client.token(), "token_1");
+        assert!(session.sent[1].contains("token_1"));
+
+        let mut session = MockSession::new(vec![INVALID_SESSION, INVALID_SESSION]);
+        let err = client
+            .execute(&mut session, |c| c.cancel_order("O1"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TradingError::ReauthFailed { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_execute_surfaces_user_action() {
+        let mut client = fast_client();
+        let mut session = MockSession::new(vec![NO_FUNDS]);
+
+        let err = client
+            .execute(&mut session, |c| c.market_order("BTC/USD", Side::Buy, Decimal::ONE))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TradingError::UserAction { .. }));
+        assert_eq!(session.sent.len(), 1);
+    }
+
+    #[test]
+    fn test_algo_request() {
+        use crate::execution::{ExecutionAlgo, Iceberg};
+
+        let client = TradingClient::new("test_token".to_string());
+        let mut iceberg = Iceberg::new("ice", "BTC/USD", Side::Buy, Decimal::TWO, Decimal::ONE, Decimal::ONE_HUNDRED);
+        let actions = iceberg.start(std::time::Instant::now());
+
+        let json = client.algo_request(&actions[0]).to_ws_json().unwrap();
+        assert!(json.contains("\"method\":\"add_order\""));
+        assert!(json.contains("\"cl_ord_id\":\"ice-1\""));
+
+        let json = client.algo_request(&iceberg.cancel()[0]).to_ws_json().unwrap();
+        assert!(json.contains("\"method\":\"cancel_order\""));
+    }
+
+    #[tokio::test]
+    async fn test_execute_applies_risk_checks_and_kill_switch() {
+        use crate::risk::RiskLimits;
+
+        let limits = RiskLimits::new().with_max_order_qty(Decimal::ONE);
+        let mut client = fast_client().with_risk_manager(RiskManager::new(limits));
+        let mut session = MockSession::new(vec![OK, OK]);
+
+        let err = client
+            .execute(&mut session, |c| c.market_order("BTC/USD", Side::Buy, Decimal::TWO))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TradingError::Risk(RiskViolation::OrderTooLarge { .. })));
+        assert!(session.sent.is_empty());
+
+        client
+            .execute(&mut session, |c| c.market_order("BTC/USD", Side::Buy, Decimal::ONE))
+            .await
+            .unwrap();
+        assert_eq!(client.risk_manager().unwrap().open_order_count(), 1);
+
+        client.kill_switch(&mut session).await.unwrap();
+        assert!(session.sent[1].contains("cancel_all"));
+        assert_eq!(client.risk_manager().unwrap().open_order_count(), 0);
+        let err = client
+            .execute(&mut session, |c| c.market_order("BTC/USD", Side::Buy, Decimal::ONE))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TradingError::Risk(RiskViolation::KillSwitchEngaged)));
+    }
+
+    #[tokio::test]
+    async fn test_execute_tracks_batch_orders_and_amends() {
+        use crate::risk::RiskLimits;
+
+        const BATCH_OK: &str =
+            r#"{"method":"batch_add","success":true,"result":[{"order_id":"B1"},{"order_id":"B2"}]}"#;
+        let limits = RiskLimits::new().with_max_position("BTC", dec!(1));
+        let mut client = fast_client().with_risk_manager(RiskManager::new(limits));
+        let mut session = MockSession::new(vec![BATCH_OK, OK]);
+        let order = |qty| BatchOrder {
+            order_type: "limit".to_string(),
+            side: Side::Buy,
+            symbol: "BTC/USD".to_string(),
+            order_qty: qty,
+            limit_price: Some(dec!(100)),
+            cl_ord_id: None,
+            post_only: None,
+        };
+
+        client
+            .execute(&mut session, |c| c.batch_add(vec![order(dec!(0.4)), order(dec!(0.4))]))
+            .await
+            .unwrap();
+        assert_eq!(client.risk_manager().unwrap().open_order_count(), 2);
+
+        // Both resting buys count: raising one to 0.7 would reach 1.1 BTC
+        let err = client
+            .execute(&mut session, |c| c.amend_qty("B1", dec!(0.7)))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TradingError::Risk(RiskViolation::PositionLimit { .. })));
+        assert_eq!(session.sent.len(), 1);
+        client
+            .execute(&mut session, |c| c.amend_qty("B1", dec!(0.5)))
+            .await
+            .unwrap();
+        let err = client
+            .execute(&mut session, |c| c.limit_order("BTC/USD", Side::Buy, dec!(0.2), dec!(100)))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TradingError::Risk(RiskViolation::PositionLimit { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_execute_charges_trading_counter() {
+        let limiter = crate::rate_limiter::shared_rate_limiter();
+        let mut client = fast_client().with_rate_limiter(limiter.clone());
+        let mut session = MockSession::new(vec![OK, OK]);
+
+        client
+            .execute(&mut session, |c| c.market_order("BTC/USD", Side::Buy, Decimal::ONE))
+            .await
+            .unwrap();
+        assert!((limiter.trading_counter("BTC/USD") - 1.0).abs() < 0.1);
+
+        // Cancelling the order right away carries the maximum age penalty
+        client
+            .execute(&mut session, |c| c.cancel_order("O1"))
+            .await
+            .unwrap();
+        assert!((limiter.trading_counter("BTC/USD") - 9.0).abs() < 0.1);
+    }
+
+    #[tokio::test]
+    async fn test_execute_fails_when_rate_limit_wait_is_exceeded() {
+        let limiter = crate::rate_limiter::shared_rate_limiter();
+        let add = TradingAction::Add { symbol: "BTC/USD".to_string() };
+        while limiter.try_acquire_trading(&add).is_allowed() {}
+        let mut client = fast_client()
+            .with_rate_limiter(limiter)
+            .with_rate_limit_wait(Duration::from_millis(10));
+        let mut session = MockSession::new(vec![OK]);
+
+        let err = client
+            .execute(&mut session, |c| c.market_order("BTC/USD", Side::Buy, Decimal::ONE))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TradingError::RateLimited { .. }));
+        assert!(session.sent.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_status_gate_rejects_or_queues() {
+        let (status_tx, status_rx) = watch::channel(Some(SystemStatus::CancelOnly));
+        let mut client = fast_client().with_status_gate(status_rx.clone(), StatusPolicy::Reject);
+        let mut session = MockSession::new(vec![OK, OK]);
+
+        // Cancels still go out in cancel_only, new orders don't
+        client.execute(&mut session, |c| c.cancel_order("O1")).await.unwrap();
+        let err = client
+            .execute(&mut session, |c| c.market_order("BTC/USD", Side::Buy, Decimal::ONE))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TradingError::SystemUnavailable { status: SystemStatus::CancelOnly }));
+        assert_eq!(session.sent.len(), 1);
+
+        // Queued orders go out once the exchange is back online
+        let mut client = fast_client().with_status_gate(
+            status_rx,
+            StatusPolicy::Queue { max_wait: Duration::from_secs(5) },
+        );
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            status_tx.send_replace(Some(SystemStatus::Online));
+        });
+        client
+            .execute(&mut session, |c| c.market_order("BTC/USD", Side::Buy, Decimal::ONE))
+            .await
+            .unwrap();
+        assert_eq!(session.sent.len(), 2);
+        assert_eq!(client.system_status(), Some(SystemStatus::Online));
+    }
+
+    #[tokio::test]
+    async fn test_status_gate_queue_times_out() {
+        let (_status_tx, status_rx) = watch::channel(Some(SystemStatus::Maintenance));
+        let mut client = fast_client().with_status_gate(
+            status_rx,
+            StatusPolicy::Queue { max_wait: Duration::from_millis(10) },
+        );
+        let mut session = MockSession::new(vec![OK]);
+        let err = client.execute(&mut session, |c| c.cancel_order("O1")).await.unwrap_err();
+        assert!(matches!(err, TradingError::SystemUnavailable { status: SystemStatus::Maintenance }));
+        assert!(session.sent.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_pair_status_gate_enforces_permitted_orders() {
+        let statuses = HashMap::from([
+            ("BTC/USD".to_string(), PairStatus::PostOnly),
+            ("ETH/USD".to_string(), PairStatus::LimitOnly),
+            ("SOL/USD".to_string(), PairStatus::CancelOnly),
+        ]);
+        let (_statuses_tx, statuses_rx) = watch::channel(statuses);
+        let mut client = fast_client().with_pair_status_gate(statuses_rx);
+        let mut session = MockSession::new(vec![OK, OK, OK]);
+
+        // Limit orders on a post_only pair go out post-only
+        client
+            .execute(&mut session, |c| c.limit_order("BTC/USD", Side::Buy, dec!(1), dec!(100)))
+            .await
+            .unwrap();
+        assert!(session.sent[0].contains(r#""post_only":true"#));
+
+        let err = client
+            .execute(&mut session, |c| c.market_order("ETH/USD", Side::Buy, dec!(1)))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TradingError::PairRestricted { status: PairStatus::LimitOnly, .. }));
+        client
+            .execute(&mut session, |c| c.limit_order("ETH/USD", Side::Buy, dec!(1), dec!(100)))
+            .await
+            .unwrap();
+
+        // Cancels still go out on a cancel_only pair, new orders don't
+        let err = client
+            .execute(&mut session, |c| c.limit_order("SOL/USD", Side::Sell, dec!(1), dec!(100)))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, TradingError::PairRestricted { ref symbol, .. } if symbol == "SOL/USD"));
+        client.execute(&mut session, |c| c.cancel_order("O1")).await.unwrap();
+        assert_eq!(session.sent.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_pair_status_gate_makes_batch_limit_orders_post_only() {
+        const BATCH_OK: &str =
+            r#"{"method":"batch_add","success":true,"result":[{"order_id":"B1"},{"order_id":"B2"}]}"#;
+        let statuses = HashMap::from([("BTC/USD".to_string(), PairStatus::PostOnly)]);
+        let (_statuses_tx, statuses_rx) = watch::channel(statuses);
+        let mut client = fast_client().with_pair_status_gate(statuses_rx);
+        let mut session = MockSession::new(vec![BATCH_OK]);
+        let order = |order_type: &str, limit_price| BatchOrder {
+            order_type: order_type.to_string(),
+            side: Side::Buy,
+            symbol: "BTC/USD".to_string(),
+            order_qty: dec!(1),
+            limit_price,
+            cl_ord_id: None,
+            post_only: None,
+        };
+
+        // Both limit orders go out post-only rather than being rejected
+        let batch = vec![order("limit", Some(dec!(100))), order("limit", Some(dec!(99)))];
+        client.execute(&mut session, |c| c.batch_add(batch.clone())).await.unwrap();
+        assert_eq!(session.sent[0].matches(r#""post_only":true"#).count(), 2);
+
+        // A market order in the batch still fails the whole batch
+        let batch = vec![order("limit", Some(dec!(100))), order("market", None)];
+        let err = client.execute(&mut session, |c| c.batch_add(batch.clone())).await.unwrap_err();
+        assert!(matches!(err, TradingError::PairRestricted { status: PairStatus::PostOnly, .. }));
+        assert_eq!(session.sent.len(), 1);
+    }
 }
@@ -2,6 +2,9 @@
 //!
 //! This module provides a trait-based abstraction over WebSocket connections,
 //! enabling unit testing of connection logic without real network calls.
+//! [`KrakenConnection`](crate::KrakenConnection) runs over this trait too, so
+//! instrumented or replay transports can be plugged in with
+//! [`ConnectionConfig::with_transport_factory`](crate::ConnectionConfig::with_transport_factory).
 //!
 //! # Example
 //!
@@ -19,15 +22,20 @@
 //! }
 //! ```
 
+use crate::proxy::ProxyConfig;
 use async_trait::async_trait;
 use futures_util::{SinkExt, StreamExt};
+use std::sync::Arc;
 use std::time::Duration;
 use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::TcpStream;
 use tokio::time::timeout;
-use tokio_tungstenite::{
-    connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream,
-};
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::handshake::client::Request;
+#[cfg(feature = "deflate")]
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::{client_async, tungstenite::Message, WebSocketStream};
 use tracing::{debug, instrument};
 
 /// Transport layer errors
@@ -87,13 +95,253 @@ pub trait Transport: Send + Sync {
 
     /// Get the endpoint URL
     fn endpoint(&self) -> &str;
+
+    /// Traffic counters since the transport was created
+    ///
+    /// Transports that don't track traffic return zeroes.
+    fn stats(&self) -> TransportStats {
+        TransportStats::default()
+    }
+
+    /// Close frame the server sent on the current connection, if any
+    ///
+    /// Transports without close frames return `None`.
+    fn close_frame(&self) -> Option<CloseFrame> {
+        None
+    }
+
+    /// When the last inbound frame of any kind arrived
+    ///
+    /// Unlike [`recv`](Self::recv), this counts control frames (ping/pong),
+    /// so a quiet but live connection still passes heartbeat checks.
+    /// Transports without control frames return `None`.
+    fn last_frame_at(&self) -> Option<std::time::Instant> {
+        None
+    }
+}
+
+/// Code and reason of a WebSocket close frame
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloseFrame {
+    /// Close code (RFC 6455 section 7.4)
+    pub code: u16,
+    /// Close reason text
+    pub reason: String,
+}
+
+/// Message and payload byte counters for a transport
+///
+/// `bytes_received` and `bytes_sent` are uncompressed payload sizes. With
+/// permessage-deflate negotiated (see [`crate::deflate`]), the compressed
+/// messages among those received are also counted in the `compressed_*`
+/// and `decompressed_*` fields, which is where the bandwidth saved and the
+/// CPU spent show up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TransportStats {
+    /// Data messages received
+    pub messages_received: u64,
+    /// Payload bytes received
+    pub bytes_received: u64,
+    /// Messages sent
+    pub messages_sent: u64,
+    /// Payload bytes sent
+    pub bytes_sent: u64,
+    /// Messages that arrived compressed
+    pub compressed_messages_received: u64,
+    /// Payload bytes of the compressed messages as received
+    pub compressed_bytes_received: u64,
+    /// Size of the compressed messages after decompression
+    pub decompressed_bytes_received: u64,
+    /// Time spent decompressing
+    pub decompression_time: Duration,
+}
+
+impl TransportStats {
+    /// Average received message size in bytes
+    pub fn avg_message_size(&self) -> f64 {
+        if self.messages_received == 0 {
+            return 0.0;
+        }
+        self.bytes_received as f64 / self.messages_received as f64
+    }
+
+    /// Decompressed over compressed size (None before any compressed message)
+    pub fn compression_ratio(&self) -> Option<f64> {
+        (self.compressed_bytes_received > 0)
+            .then(|| self.decompressed_bytes_received as f64 / self.compressed_bytes_received as f64)
+    }
+
+    /// Received payload bytes that compression kept off the wire
+    pub fn bytes_saved(&self) -> u64 {
+        self.decompressed_bytes_received.saturating_sub(self.compressed_bytes_received)
+    }
+}
+
+impl std::ops::Add for TransportStats {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            messages_received: self.messages_received + other.messages_received,
+            bytes_received: self.bytes_received + other.bytes_received,
+            messages_sent: self.messages_sent + other.messages_sent,
+            bytes_sent: self.bytes_sent + other.bytes_sent,
+            compressed_messages_received: self.compressed_messages_received
+                + other.compressed_messages_received,
+            compressed_bytes_received: self.compressed_bytes_received
+                + other.compressed_bytes_received,
+            decompressed_bytes_received: self.decompressed_bytes_received
+                + other.decompressed_bytes_received,
+            decompression_time: self.decompression_time + other.decompression_time,
+        }
+    }
+}
+
+/// Byte stream a WebSocket runs over (plain TCP, TLS, or a proxy tunnel)
+pub trait WsIo: AsyncRead + AsyncWrite + Unpin + Send + Sync {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send + Sync> WsIo for T {}
+
+/// WebSocket over a type-erased byte stream
+pub type WsStream = WebSocketStream<Box<dyn WsIo>>;
+
+/// How the TCP and TLS layers under the WebSocket are established
+///
+/// The default connects directly with the platform TLS stack.
+#[derive(Debug, Clone, Default)]
+pub struct NetworkConfig {
+    /// Route the connection through a proxy
+    pub proxy: Option<ProxyConfig>,
+    /// TLS server name (SNI and certificate verification) if not the URL host
+    pub server_name: Option<String>,
+    /// Custom rustls configuration (pinned roots, client certificates)
+    #[cfg(feature = "rustls")]
+    pub rustls_config: Option<Arc<tokio_rustls::rustls::ClientConfig>>,
+}
+
+/// Open a WebSocket to `url` according to `network`
+///
+/// With the default [`NetworkConfig`] this behaves like
+/// `tokio_tungstenite::connect_async` with native TLS.
+pub async fn connect_websocket(url: &str, network: &NetworkConfig) -> Result<WsStream, TransportError> {
+    open_websocket(url, network, |_, io| io).await
+}
+
+/// [`connect_websocket`], with `layer` adjusting the upgrade request and
+/// wrapping the byte stream before the handshake
+async fn open_websocket(
+    url: &str,
+    network: &NetworkConfig,
+    layer: impl FnOnce(&mut Request, Box<dyn WsIo>) -> Box<dyn WsIo>,
+) -> Result<WsStream, TransportError> {
+    let failed = |e: &dyn std::fmt::Display| TransportError::ConnectionFailed(e.to_string());
+
+    let mut request = url.into_client_request().map_err(|e| failed(&e))?;
+    let uri = request.uri();
+    let secure = match uri.scheme_str() {
+        Some("wss") => true,
+        Some("ws") => false,
+        other => return Err(TransportError::ConnectionFailed(format!("unsupported scheme {other:?}"))),
+    };
+    let host = uri
+        .host()
+        .ok_or_else(|| TransportError::ConnectionFailed(format!("no host in {url}")))?
+        .to_string();
+    let port = uri.port_u16().unwrap_or(if secure { 443 } else { 80 });
+
+    let tcp = match &network.proxy {
+        Some(proxy) => {
+            debug!(proxy = %proxy.host, "Connecting through proxy");
+            proxy.connect(&host, port).await.map_err(|e| failed(&e))?
+        }
+        None => TcpStream::connect((host.as_str(), port)).await.map_err(|e| failed(&e))?,
+    };
+
+    let io: Box<dyn WsIo> = if secure {
+        let server_name = network.server_name.clone().unwrap_or(host);
+        tls_connect(network, server_name, tcp).await?
+    } else {
+        Box::new(tcp)
+    };
+    let io = layer(&mut request, io);
+
+    let (stream, _response) = client_async(request, io).await.map_err(|e| failed(&e))?;
+    Ok(stream)
+}
+
+async fn tls_connect(
+    network: &NetworkConfig,
+    server_name: String,
+    tcp: TcpStream,
+) -> Result<Box<dyn WsIo>, TransportError> {
+    let failed = |e: &dyn std::fmt::Display| TransportError::ConnectionFailed(e.to_string());
+
+    #[cfg(feature = "rustls")]
+    if let Some(config) = &network.rustls_config {
+        let name = tokio_rustls::rustls::pki_types::ServerName::try_from(server_name)
+            .map_err(|e| failed(&e))?;
+        let tls = tokio_rustls::TlsConnector::from(config.clone())
+            .connect(name, tcp)
+            .await
+            .map_err(|e| failed(&e))?;
+        return Ok(Box::new(tls));
+    }
+    #[cfg(not(feature = "rustls"))]
+    let _ = network;
+
+    let connector = native_tls::TlsConnector::new().map_err(|e| failed(&e))?;
+    let tls = tokio_native_tls::TlsConnector::from(connector)
+        .connect(&server_name, tcp)
+        .await
+        .map_err(|e| failed(&e))?;
+    Ok(Box::new(tls))
+}
+
+/// Creates a fresh [`Transport`] for each connection attempt
+///
+/// The factory receives the endpoint URL. Use it to supply instrumented,
+/// proxied, or replay transports to [`KrakenConnection`](crate::KrakenConnection).
+#[derive(Clone)]
+pub struct TransportFactory(Arc<FactoryFn>);
+
+type FactoryFn = dyn Fn(&str) -> Box<dyn Transport> + Send + Sync;
+
+impl TransportFactory {
+    /// Wrap a closure that builds a transport for a URL
+    pub fn new<F>(factory: F) -> Self
+    where
+        F: Fn(&str) -> Box<dyn Transport> + Send + Sync + 'static,
+    {
+        Self(Arc::new(factory))
+    }
+
+    /// Build a transport for `url`
+    pub fn create(&self, url: &str) -> Box<dyn Transport> {
+        (self.0)(url)
+    }
+}
+
+impl std::fmt::Debug for TransportFactory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TransportFactory(..)")
+    }
 }
 
 /// Real WebSocket transport using tokio-tungstenite
 pub struct WsTransport {
     url: String,
-    stream: Option<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+    stream: Option<WsStream>,
     connect_timeout: Duration,
+    network: NetworkConfig,
+    stats: TransportStats,
+    close_frame: Option<CloseFrame>,
+    /// Arrival of the last frame, control frames included
+    last_frame_at: Option<std::time::Instant>,
+    /// Offer permessage-deflate on connect
+    #[cfg(feature = "deflate")]
+    compression: bool,
+    #[cfg(feature = "deflate")]
+    inflate: Arc<crate::deflate::Counters>,
 }
 
 impl WsTransport {
@@ -103,6 +351,14 @@ impl WsTransport {
             url: url.into(),
             stream: None,
             connect_timeout: Duration::from_secs(10),
+            network: NetworkConfig::default(),
+            stats: TransportStats::default(),
+            close_frame: None,
+            last_frame_at: None,
+            #[cfg(feature = "deflate")]
+            compression: false,
+            #[cfg(feature = "deflate")]
+            inflate: Arc::default(),
         }
     }
 
@@ -111,6 +367,44 @@ impl WsTransport {
         self.connect_timeout = timeout;
         self
     }
+
+    /// Set proxy and TLS options
+    pub fn with_network(mut self, network: NetworkConfig) -> Self {
+        self.network = network;
+        self
+    }
+
+    /// Offer permessage-deflate so the server can compress what it sends
+    ///
+    /// See [`crate::deflate`]. Servers that decline send uncompressed
+    /// frames as before.
+    #[cfg(feature = "deflate")]
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.compression = enabled;
+        self
+    }
+
+    /// Whether the server accepted permessage-deflate on the current connection
+    #[cfg(feature = "deflate")]
+    pub fn is_compressed(&self) -> bool {
+        self.stream.is_some() && self.compression && self.inflate.negotiated()
+    }
+
+    /// Open the WebSocket, with permessage-deflate if enabled
+    async fn open(&self) -> Result<WsStream, TransportError> {
+        #[cfg(feature = "deflate")]
+        if self.compression {
+            let counters = Arc::clone(&self.inflate);
+            return open_websocket(&self.url, &self.network, |request, io| {
+                request
+                    .headers_mut()
+                    .insert("Sec-WebSocket-Extensions", HeaderValue::from_static(crate::deflate::OFFER));
+                Box::new(crate::deflate::InflateIo::new(io, counters))
+            })
+            .await;
+        }
+        connect_websocket(&self.url, &self.network).await
+    }
 }
 
 #[async_trait]
@@ -119,14 +413,14 @@ impl Transport for WsTransport {
     async fn connect(&mut self) -> Result<(), TransportError> {
         debug!("Connecting to WebSocket");
 
-        let connect_future = connect_async(&self.url);
+        let connect_future = self.open();
 
-        let (ws_stream, _response) = timeout(self.connect_timeout, connect_future)
+        let ws_stream = timeout(self.connect_timeout, connect_future)
             .await
-            .map_err(|_| TransportError::Timeout(self.connect_timeout))?
-            .map_err(|e| TransportError::ConnectionFailed(e.to_string()))?;
+            .map_err(|_| TransportError::Timeout(self.connect_timeout))??;
 
         self.stream = Some(ws_stream);
+        self.close_frame = None;
         debug!("WebSocket connected");
         Ok(())
     }
@@ -140,6 +434,8 @@ impl Transport for WsTransport {
             .await
             .map_err(|e| TransportError::SendFailed(e.to_string()))?;
 
+        self.stats.messages_sent += 1;
+        self.stats.bytes_sent += message.len() as u64;
         Ok(())
     }
 
@@ -147,15 +443,29 @@ impl Transport for WsTransport {
     async fn recv(&mut self) -> Result<Option<String>, TransportError> {
         let stream = self.stream.as_mut().ok_or(TransportError::NotConnected)?;
 
-        match stream.next().await {
-            Some(Ok(Message::Text(text))) => Ok(Some(text)),
+        let frame = stream.next().await;
+        if matches!(frame, Some(Ok(_))) {
+            self.last_frame_at = Some(std::time::Instant::now());
+        }
+        match frame {
+            Some(Ok(Message::Text(text))) => {
+                self.stats.messages_received += 1;
+                self.stats.bytes_received += text.len() as u64;
+                Ok(Some(text))
+            }
             Some(Ok(Message::Binary(data))) => {
+                self.stats.messages_received += 1;
+                self.stats.bytes_received += data.len() as u64;
                 // Try to convert binary to string
                 String::from_utf8(data)
                     .map(Some)
                     .map_err(|e| TransportError::Protocol(e.to_string()))
             }
-            Some(Ok(Message::Close(_))) => {
+            Some(Ok(Message::Close(frame))) => {
+                self.close_frame = frame.map(|frame| CloseFrame {
+                    code: frame.code.into(),
+                    reason: frame.reason.into_owned(),
+                });
                 self.stream = None;
                 Ok(None)
             }
@@ -193,6 +503,25 @@ impl Transport for WsTransport {
     fn endpoint(&self) -> &str {
         &self.url
     }
+
+    fn stats(&self) -> TransportStats {
+        #[cfg(feature = "deflate")]
+        {
+            let mut stats = self.stats;
+            self.inflate.add_to(&mut stats);
+            stats
+        }
+        #[cfg(not(feature = "deflate"))]
+        self.stats
+    }
+
+    fn close_frame(&self) -> Option<CloseFrame> {
+        self.close_frame.clone()
+    }
+
+    fn last_frame_at(&self) -> Option<std::time::Instant> {
+        self.last_frame_at
+    }
 }
 
 /// Mock transport for testing
@@ -338,4 +667,65 @@ mod tests {
         let response = transport.recv().await.unwrap();
         assert!(response.is_none()); // Closed
     }
+
+    #[cfg(feature = "deflate")]
+    #[tokio::test]
+    async fn test_ws_transport_inflates_negotiated_compression() {
+        use flate2::{Compress, Compression, FlushCompress};
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+        use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+
+        let levels = r#"{"price":1.5,"qty":2.25},"#.repeat(100);
+        let message = format!(r#"{{"channel":"book","data":[{}]}}"#, levels);
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let payload = message.clone();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            let request = String::from_utf8(request).unwrap();
+            assert!(request.contains(crate::deflate::OFFER));
+            let key = request
+                .lines()
+                .find_map(|line| {
+                    let (name, value) = line.split_once(':')?;
+                    name.eq_ignore_ascii_case("sec-websocket-key").then(|| value.trim().to_string())
+                })
+                .unwrap();
+
+            let mut compressed = Vec::with_capacity(payload.len());
+            Compress::new(Compression::default(), false)
+                .compress_vec(payload.as_bytes(), &mut compressed, FlushCompress::Sync)
+                .unwrap();
+            compressed.truncate(compressed.len() - 4);
+            let mut response = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+                 Sec-WebSocket-Accept: {}\r\nSec-WebSocket-Extensions: permessage-deflate\r\n\r\n",
+                derive_accept_key(key.as_bytes())
+            )
+            .into_bytes();
+            response.extend_from_slice(&[0xc1, compressed.len() as u8]);
+            response.extend_from_slice(&compressed);
+            socket.write_all(&response).await.unwrap();
+            compressed.len()
+        });
+
+        let mut transport = WsTransport::new(url).with_compression(true);
+        transport.connect().await.unwrap();
+        assert_eq!(transport.recv().await.unwrap().as_deref(), Some(message.as_str()));
+        assert!(transport.is_compressed());
+
+        let stats = transport.stats();
+        let compressed = server.await.unwrap() as u64;
+        assert_eq!(stats.messages_received, 1);
+        assert_eq!(stats.compressed_messages_received, 1);
+        assert_eq!(stats.compressed_bytes_received, compressed);
+        assert_eq!(stats.decompressed_bytes_received, message.len() as u64);
+        assert_eq!(stats.bytes_saved(), message.len() as u64 - compressed);
+    }
 }
@@ -0,0 +1,142 @@
+//! Per-subscription staleness detection
+//!
+//! The connection-level heartbeat only proves the socket is alive. A single
+//! symbol's feed can still go quiet, for example after a server-side
+//! unsubscribe. [`StaleWatchdog`] tracks the last message time for every
+//! (channel, symbol) pair and reports the ones that have been silent for
+//! longer than a threshold.
+//!
+//! Book and trade feeds only publish when something changes, so choose a
+//! threshold well above the quietest expected gap for the symbols involved.
+
+use kraken_types::Channel;
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// A (channel, symbol) feed that has gone quiet
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleFeed {
+    /// Channel of the feed
+    pub channel: Channel,
+    /// Trading pair symbol
+    pub symbol: String,
+    /// Time since the last message
+    pub silent_for: Duration,
+}
+
+/// Tracks last-message times per (channel, symbol)
+#[derive(Debug, Clone)]
+pub struct StaleWatchdog {
+    threshold: Duration,
+    last_seen: HashMap<(Channel, String), Instant>,
+    /// Feeds already reported in the current silent period
+    reported: HashSet<(Channel, String)>,
+}
+
+impl StaleWatchdog {
+    /// Create a watchdog that flags feeds silent for longer than `threshold`
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            last_seen: HashMap::new(),
+            reported: HashSet::new(),
+        }
+    }
+
+    /// Silence threshold
+    pub fn threshold(&self) -> Duration {
+        self.threshold
+    }
+
+    /// Start watching a feed, counting silence from `now`
+    ///
+    /// Does nothing if the feed is already watched.
+    pub fn watch(&mut self, channel: Channel, symbol: &str, now: Instant) {
+        self.last_seen.entry((channel, symbol.to_string())).or_insert(now);
+    }
+
+    /// Record a message for a feed
+    pub fn touch(&mut self, channel: Channel, symbol: &str, now: Instant) {
+        let key = (channel, symbol.to_string());
+        self.reported.remove(&key);
+        self.last_seen.insert(key, now);
+    }
+
+    /// Restart the silence clock for a feed (e.g. after resubscribing)
+    pub fn reset(&mut self, channel: Channel, symbol: &str, now: Instant) {
+        self.touch(channel, symbol, now);
+    }
+
+    /// Stop watching a feed
+    pub fn forget(&mut self, channel: Channel, symbol: &str) {
+        let key = (channel, symbol.to_string());
+        self.last_seen.remove(&key);
+        self.reported.remove(&key);
+    }
+
+    /// Number of watched feeds
+    pub fn len(&self) -> usize {
+        self.last_seen.len()
+    }
+
+    /// Check if no feeds are watched
+    pub fn is_empty(&self) -> bool {
+        self.last_seen.is_empty()
+    }
+
+    /// Feeds that crossed the threshold since the last check
+    ///
+    /// Each silent period is reported once; a new message re-arms the feed.
+    pub fn check(&mut self, now: Instant) -> Vec<StaleFeed> {
+        let mut stale = Vec::new();
+        for (key, last) in &self.last_seen {
+            let silent_for = now.saturating_duration_since(*last);
+            if silent_for > self.threshold && self.reported.insert(key.clone()) {
+                stale.push(StaleFeed {
+                    channel: key.0,
+                    symbol: key.1.clone(),
+                    silent_for,
+                });
+            }
+        }
+        stale.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        stale
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_silent_feed_once() {
+        let start = Instant::now();
+        let mut watchdog = StaleWatchdog::new(Duration::from_secs(10));
+        watchdog.watch(Channel::Book, "BTC/USD", start);
+        watchdog.watch(Channel::Book, "ETH/USD", start);
+
+        watchdog.touch(Channel::Book, "ETH/USD", start + Duration::from_secs(8));
+        let stale = watchdog.check(start + Duration::from_secs(11));
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].symbol, "BTC/USD");
+        assert_eq!(stale[0].silent_for, Duration::from_secs(11));
+
+        // Not reported again until it recovers and goes quiet again
+        assert!(watchdog.check(start + Duration::from_secs(15)).is_empty());
+        watchdog.touch(Channel::Book, "BTC/USD", start + Duration::from_secs(16));
+        let stale = watchdog.check(start + Duration::from_secs(30));
+        assert_eq!(stale.len(), 2);
+    }
+
+    #[test]
+    fn test_watch_does_not_reset_existing_feed() {
+        let start = Instant::now();
+        let mut watchdog = StaleWatchdog::new(Duration::from_secs(5));
+        watchdog.watch(Channel::Trade, "BTC/USD", start);
+        watchdog.watch(Channel::Trade, "BTC/USD", start + Duration::from_secs(4));
+        assert_eq!(watchdog.check(start + Duration::from_secs(6)).len(), 1);
+
+        watchdog.forget(Channel::Trade, "BTC/USD");
+        assert!(watchdog.is_empty());
+    }
+}
@@ -219,10 +219,10 @@ async fn test_multiple_channel_subscription() {
     let _ = timeout(Duration::from_secs(30), async {
         while let Some(event) = events.recv().await {
             match &event {
-                Event::Subscription(SubscriptionEvent::Subscribed { channel, .. }) => {
-                    if channel == "ticker" {
-                        ticker_sub = true;
-                    }
+                Event::Subscription(SubscriptionEvent::Subscribed { channel, .. })
+                    if channel == "ticker" =>
+                {
+                    ticker_sub = true;
                 }
                 Event::Market(MarketEvent::OrderbookSnapshot { .. }) => book_received = true,
                 Event::Market(MarketEvent::OrderbookUpdate { .. }) => book_received = true,
@@ -350,12 +350,10 @@ async fn test_orderbook_updates() {
                 Event::Market(MarketEvent::OrderbookSnapshot { .. }) => {
                     snapshot_received = true;
                 }
-                Event::Market(MarketEvent::OrderbookUpdate { .. }) => {
-                    if snapshot_received {
-                        updates_received += 1;
-                        if updates_received >= 5 {
-                            break;
-                        }
+                Event::Market(MarketEvent::OrderbookUpdate { .. }) if snapshot_received => {
+                    updates_received += 1;
+                    if updates_received >= 5 {
+                        break;
                     }
                 }
                 _ => {}
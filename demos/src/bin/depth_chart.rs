@@ -16,8 +16,8 @@ fn draw_depth_chart(bids: &[Level], asks: &[Level]) {
     let half_width = 25;
 
     // Calculate max volume for scaling
-    let max_bid_vol: Decimal = bids.iter().take(levels_to_show).map(|l| l.qty).max().unwrap_or(dec!(1));
-    let max_ask_vol: Decimal = asks.iter().take(levels_to_show).map(|l| l.qty).max().unwrap_or(dec!(1));
+    let max_bid_vol: Qty = bids.iter().take(levels_to_show).map(|l| l.qty).max().unwrap_or(Qty::new(dec!(1)));
+    let max_ask_vol: Qty = asks.iter().take(levels_to_show).map(|l| l.qty).max().unwrap_or(Qty::new(dec!(1)));
     let max_vol = max_bid_vol.max(max_ask_vol);
 
     // Print asks (reversed - highest first)
@@ -49,12 +49,12 @@ fn draw_depth_chart(bids: &[Level], asks: &[Level]) {
     let spread = if !bids.is_empty() && !asks.is_empty() {
         asks[0].price - bids[0].price
     } else {
-        Decimal::ZERO
+        Price::ZERO
     };
     let mid = if !bids.is_empty() && !asks.is_empty() {
         (asks[0].price + bids[0].price) / dec!(2)
     } else {
-        Decimal::ZERO
+        Price::ZERO
     };
 
     println!(
@@ -94,9 +94,9 @@ fn draw_depth_chart(bids: &[Level], asks: &[Level]) {
         "Mid:".white(),
         mid,
         "Bid Vol:".green(),
-        bids.iter().take(levels_to_show).map(|l| l.qty).sum::<Decimal>(),
+        bids.iter().take(levels_to_show).map(|l| l.qty).sum::<Qty>(),
         "Ask Vol:".red(),
-        asks.iter().take(levels_to_show).map(|l| l.qty).sum::<Decimal>()
+        asks.iter().take(levels_to_show).map(|l| l.qty).sum::<Qty>()
     );
 }
 
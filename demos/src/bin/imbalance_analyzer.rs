@@ -49,10 +49,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         // Build L3 book for imbalance calculation
         let mut book = L3Book::new("BTC/USD", 50);
         for (i, level) in bids.iter().enumerate() {
-            book.add_order(L3Order::new(format!("b{}", i), level.price, level.qty), L3Side::Bid);
+            book.add_order(L3Order::new(format!("b{}", i), level.price.0, level.qty.0), L3Side::Bid);
         }
         for (i, level) in asks.iter().enumerate() {
-            book.add_order(L3Order::new(format!("a{}", i), level.price, level.qty), L3Side::Ask);
+            book.add_order(L3Order::new(format!("a{}", i), level.price.0, level.qty.0), L3Side::Ask);
         }
 
         let imbalance = book.imbalance().unwrap_or(0.0);
@@ -67,8 +67,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             0.0
         };
 
-        let bid_volume: Decimal = bids.iter().map(|l| l.qty).sum();
-        let ask_volume: Decimal = asks.iter().map(|l| l.qty).sum();
+        let bid_volume: Decimal = bids.iter().map(|l| l.qty.0).sum();
+        let ask_volume: Decimal = asks.iter().map(|l| l.qty.0).sum();
         let best_bid = bids[0].price;
         let best_ask = asks[0].price;
 
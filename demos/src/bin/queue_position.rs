@@ -46,26 +46,26 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         // Add market orders, split each level into 3 orders
         for (i, level) in bids.iter().enumerate() {
-            let qty_per_order = level.qty / dec!(3);
+            let qty_per_order = level.qty.0 / dec!(3);
             for j in 0..3 {
                 book.add_order(
-                    L3Order::new(format!("bid_{}_{}", i, j), level.price, qty_per_order),
+                    L3Order::new(format!("bid_{}_{}", i, j), level.price.0, qty_per_order),
                     L3Side::Bid,
                 );
             }
         }
         for (i, level) in asks.iter().enumerate() {
-            let qty_per_order = level.qty / dec!(3);
+            let qty_per_order = level.qty.0 / dec!(3);
             for j in 0..3 {
                 book.add_order(
-                    L3Order::new(format!("ask_{}_{}", i, j), level.price, qty_per_order),
+                    L3Order::new(format!("ask_{}_{}", i, j), level.price.0, qty_per_order),
                     L3Side::Ask,
                 );
             }
         }
 
-        let best_bid = bids[0].price;
-        let best_ask = asks[0].price;
+        let best_bid = bids[0].price.0;
+        let best_ask = asks[0].price.0;
 
         // Add our hypothetical orders
         let our_orders = [
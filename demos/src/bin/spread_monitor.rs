@@ -67,7 +67,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             print!(
                 "│ {} {}/s",
                 "Rate:".dimmed(),
-                if elapsed > 0 { update_count / elapsed } else { 0 }
+                update_count.checked_div(elapsed).unwrap_or(0)
             );
 
             use std::io::Write;
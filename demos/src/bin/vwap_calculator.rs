@@ -28,7 +28,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("{} Connected. Building orderbook...\n", "✓".green());
     tokio::time::sleep(Duration::from_secs(3)).await;
 
-    let sizes = [dec!(0.1), dec!(0.5), dec!(1.0), dec!(5.0), dec!(10.0)];
+    let sizes = [dec!(0.1), dec!(0.5), dec!(1.0), dec!(5.0), dec!(10.0)].map(Qty::new);
 
     loop {
         tokio::time::sleep(Duration::from_secs(2)).await;
@@ -49,13 +49,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let mut book = L3Book::new("BTC/USD", 100);
         for (i, level) in bids.iter().enumerate() {
             book.add_order(
-                L3Order::new(format!("bid_{}", i), level.price, level.qty),
+                L3Order::new(format!("bid_{}", i), level.price.0, level.qty.0),
                 L3Side::Bid,
             );
         }
         for (i, level) in asks.iter().enumerate() {
             book.add_order(
-                L3Order::new(format!("ask_{}", i), level.price, level.qty),
+                L3Order::new(format!("ask_{}", i), level.price.0, level.qty.0),
                 L3Side::Ask,
             );
         }
@@ -85,8 +85,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         );
 
         for size in &sizes {
-            if let Some(vwap) = book.vwap_ask(*size) {
-                let cost = vwap * size;
+            if let Some(vwap) = book.ask_vwap(*size) {
+                let cost = vwap * *size;
                 let slippage = vwap - best_ask;
                 let bps = if !best_ask.is_zero() {
                     (slippage / best_ask * dec!(10000)).round()
@@ -0,0 +1,331 @@
+//! Paginated downloads from Kraken's public REST endpoints
+//!
+//! Pages are requested with `since` and followed through the `last` cursor
+//! each response carries, until the requested range is covered or Kraken has
+//! nothing newer. Every request first takes a token from the shared REST
+//! rate limiter, and rate limit errors from Kraken are retried with backoff.
+//!
+//! Rows are parsed with the SDK's REST parsers, so downloaded candles and
+//! trades have the same shape as the WebSocket ones.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, Utc};
+use kraken_sdk::candles::{parse_rest_ohlc, REST_OHLC_URL};
+use kraken_sdk::ticker_poller::rest_pair_name;
+use kraken_sdk::trade_backfill::{parse_rest_trades, REST_TRADES_URL};
+use kraken_types::{Decimal, OhlcData, RateLimitCategory, TradeData};
+use kraken_ws::SharedRateLimiter;
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Kraken REST recent spreads endpoint
+pub const REST_SPREAD_URL: &str = "https://api.kraken.com/0/public/Spread";
+
+/// Attempts per page while Kraken reports a rate limit
+const MAX_ATTEMPTS: u32 = 5;
+
+/// First wait after a rate limit error, doubled on each retry
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Time range to download, in Unix seconds (`until` exclusive)
+#[derive(Debug, Clone, Copy)]
+pub struct Range {
+    pub since: i64,
+    pub until: i64,
+}
+
+impl Range {
+    fn contains(&self, time: i64) -> bool {
+        time >= self.since && time < self.until
+    }
+}
+
+/// One top-of-book spread sample
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Spread {
+    pub time: i64,
+    pub bid: Decimal,
+    pub ask: Decimal,
+}
+
+/// Downloads pages through a fetch function mapping a URL to a body
+pub struct Fetcher<F> {
+    fetch: F,
+    limiter: SharedRateLimiter,
+    retry_delay: Duration,
+}
+
+impl<F, Fut> Fetcher<F>
+where
+    F: FnMut(String) -> Fut,
+    Fut: Future<Output = Result<String>>,
+{
+    pub fn new(fetch: F, limiter: SharedRateLimiter) -> Self {
+        Self {
+            fetch,
+            limiter,
+            retry_delay: RETRY_DELAY,
+        }
+    }
+
+    /// Candles of `interval` minutes starting inside `range`
+    ///
+    /// Kraken only serves the most recent 720 candles per interval, so older
+    /// starts come back truncated.
+    pub async fn ohlc(&mut self, symbol: &str, interval: u32, range: Range) -> Result<Vec<OhlcData>> {
+        let pair = rest_pair_name(symbol);
+        let mut candles = BTreeMap::new();
+        let mut cursor = range.since.to_string();
+        loop {
+            let url = format!("{REST_OHLC_URL}?pair={pair}&interval={interval}&since={cursor}");
+            let body = self.page(&url).await?;
+            let page = parse_rest_ohlc(&body, symbol, interval)?;
+            let before = candles.len();
+            for candle in page {
+                let begin = unix_secs(&candle.interval_begin)?;
+                if range.contains(begin) {
+                    candles.insert(begin, candle);
+                }
+            }
+            match next_cursor(&body, &cursor) {
+                Some(next) if candles.len() > before && cursor_secs(&next) < range.until => cursor = next,
+                _ => break,
+            }
+        }
+        Ok(candles.into_values().collect())
+    }
+
+    /// Trades executed inside `range`, ordered by trade ID
+    pub async fn trades(&mut self, symbol: &str, range: Range) -> Result<Vec<TradeData>> {
+        let pair = rest_pair_name(symbol);
+        let mut trades = BTreeMap::new();
+        let mut cursor = range.since.to_string();
+        loop {
+            let url = format!("{REST_TRADES_URL}?pair={pair}&since={cursor}");
+            let body = self.page(&url).await?;
+            let page = parse_rest_trades(&body, symbol)?;
+            let before = trades.len();
+            for trade in page {
+                if range.contains(unix_secs(&trade.timestamp)?) {
+                    trades.insert(trade.trade_id, trade);
+                }
+            }
+            match next_cursor(&body, &cursor) {
+                Some(next) if trades.len() > before && cursor_secs(&next) < range.until => cursor = next,
+                _ => break,
+            }
+        }
+        Ok(trades.into_values().collect())
+    }
+
+    /// Spread samples inside `range`
+    ///
+    /// Kraken only keeps recent spreads, so this covers the last few hours
+    /// at most. Samples carry whole seconds and have no ID, so pages that
+    /// overlap at the cursor are deduplicated on (time, bid, ask); distinct
+    /// samples within one second are all kept.
+    pub async fn spreads(&mut self, symbol: &str, range: Range) -> Result<Vec<Spread>> {
+        let pair = rest_pair_name(symbol);
+        let mut spreads = Vec::new();
+        let mut seen = HashSet::new();
+        let mut cursor = range.since.to_string();
+        loop {
+            let url = format!("{REST_SPREAD_URL}?pair={pair}&since={cursor}");
+            let body = self.page(&url).await?;
+            let before = spreads.len();
+            spreads.extend(
+                parse_rest_spreads(&body)?
+                    .into_iter()
+                    .filter(|s| range.contains(s.time) && seen.insert(s.clone())),
+            );
+            match next_cursor(&body, &cursor) {
+                Some(next) if spreads.len() > before && cursor_secs(&next) < range.until => cursor = next,
+                _ => break,
+            }
+        }
+        Ok(spreads)
+    }
+
+    /// Fetch one page, waiting for the rate limiter and retrying rate limit errors
+    async fn page(&mut self, url: &str) -> Result<String> {
+        let mut delay = self.retry_delay;
+        for attempt in 1..=MAX_ATTEMPTS {
+            self.limiter.acquire(RateLimitCategory::RestPublic).await;
+            let body = (self.fetch)(url.to_string()).await.with_context(|| format!("GET {url}"))?;
+            match api_errors(&body) {
+                Some(errors) if errors.contains("Rate limit") && attempt < MAX_ATTEMPTS => {
+                    eprintln!("rate limited, retrying in {delay:?}");
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                }
+                _ => return Ok(body),
+            }
+        }
+        unreachable!("the last attempt always returns")
+    }
+}
+
+/// Kraken's `error` array joined, if non-empty
+fn api_errors(body: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let errors: Vec<&str> = value.get("error")?.as_array()?.iter().filter_map(|e| e.as_str()).collect();
+    (!errors.is_empty()).then(|| errors.join(", "))
+}
+
+/// The response's `last` cursor, if it moved past `current`
+fn next_cursor(body: &str, current: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let next = match value.get("result")?.get("last")? {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => n.to_string(),
+        _ => return None,
+    };
+    (next != current).then_some(next)
+}
+
+/// Seconds of a cursor, which is in seconds or (for trades) nanoseconds
+fn cursor_secs(cursor: &str) -> i64 {
+    let value: i64 = cursor.parse().unwrap_or(i64::MAX);
+    if value > 100_000_000_000 {
+        value / 1_000_000_000
+    } else {
+        value
+    }
+}
+
+fn unix_secs(timestamp: &str) -> Result<i64> {
+    Ok(DateTime::parse_from_rfc3339(timestamp)
+        .with_context(|| format!("bad timestamp {timestamp}"))?
+        .with_timezone(&Utc)
+        .timestamp())
+}
+
+/// Parse a REST `Spread` response; rows are `[time, bid, ask]`
+pub fn parse_rest_spreads(body: &str) -> Result<Vec<Spread>> {
+    let value: serde_json::Value = serde_json::from_str(body)?;
+    if let Some(errors) = api_errors(body) {
+        bail!("Kraken API error: {errors}");
+    }
+    let rows = value
+        .get("result")
+        .and_then(|r| r.as_object())
+        .and_then(|r| r.iter().find(|(key, _)| key.as_str() != "last"))
+        .and_then(|(_, rows)| rows.as_array())
+        .ok_or_else(|| anyhow!("unexpected Spread response: missing result"))?;
+
+    rows.iter()
+        .map(|row| {
+            let fields = row.as_array().filter(|f| f.len() >= 3);
+            let decimal = |index: usize| {
+                fields
+                    .and_then(|f| f[index].as_str())
+                    .and_then(|s| Decimal::from_str(s).ok())
+            };
+            match (fields.and_then(|f| f[0].as_i64()), decimal(1), decimal(2)) {
+                (Some(time), Some(bid), Some(ask)) => Ok(Spread { time, bid, ask }),
+                _ => bail!("unexpected Spread row: {row}"),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_ws::KrakenRateLimiter;
+    use rust_decimal_macros::dec;
+    use std::sync::{Arc, Mutex};
+
+    fn trades_page(rows: &[(u64, &str)], last: &str) -> String {
+        let rows: Vec<String> = rows
+            .iter()
+            .map(|(id, time)| format!(r#"["100.0","1.0",{time},"b","l","",{id}]"#))
+            .collect();
+        format!(r#"{{"error":[],"result":{{"XXBTZUSD":[{}],"last":"{last}"}}}}"#, rows.join(","))
+    }
+
+    #[tokio::test]
+    async fn test_trades_follow_cursor_until_range_end() {
+        let pages = vec![
+            trades_page(&[(1, "1700000000.1"), (2, "1700000001.5")], "1700000001500000000"),
+            trades_page(&[(2, "1700000001.5"), (3, "1700000002.0"), (4, "1700000099.0")], "1700000099000000000"),
+        ];
+        let urls = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&urls);
+        let mut pages = pages.into_iter();
+        let fetch = move |url: String| {
+            seen.lock().unwrap().push(url);
+            let body = pages.next().unwrap_or_else(|| trades_page(&[], "0"));
+            async move { Ok(body) }
+        };
+
+        let limiter = Arc::new(KrakenRateLimiter::permissive());
+        let range = Range { since: 1_700_000_000, until: 1_700_000_010 };
+        let trades = Fetcher::new(fetch, limiter).trades("BTC/USD", range).await.unwrap();
+
+        assert_eq!(trades.iter().map(|t| t.trade_id).collect::<Vec<_>>(), vec![1, 2, 3]);
+        let urls = urls.lock().unwrap();
+        assert_eq!(urls.len(), 2);
+        assert!(urls[0].ends_with("pair=XBTUSD&since=1700000000"));
+        assert!(urls[1].ends_with("since=1700000001500000000"));
+    }
+
+    #[tokio::test]
+    async fn test_spreads_keep_samples_sharing_a_second() {
+        let page = |rows: &[(i64, &str)], last: i64| {
+            let rows: Vec<String> = rows.iter().map(|(time, bid)| format!(r#"[{time},"{bid}","101.0"]"#)).collect();
+            format!(r#"{{"error":[],"result":{{"XXBTZUSD":[{}],"last":{last}}}}}"#, rows.join(","))
+        };
+        let mut pages = vec![
+            page(&[(1_700_000_000, "100.0"), (1_700_000_001, "100.1"), (1_700_000_001, "100.2")], 1_700_000_001),
+            // The next page starts over at the cursor second
+            page(&[(1_700_000_001, "100.1"), (1_700_000_001, "100.2"), (1_700_000_001, "100.3")], 1_700_000_002),
+        ]
+        .into_iter();
+        let fetch = move |_url: String| {
+            let body = pages.next().unwrap_or_else(|| page(&[], 1_700_000_002));
+            async move { Ok(body) }
+        };
+
+        let range = Range { since: 1_700_000_000, until: 1_700_000_010 };
+        let spreads = Fetcher::new(fetch, Arc::new(KrakenRateLimiter::permissive()))
+            .spreads("BTC/USD", range)
+            .await
+            .unwrap();
+
+        let bids: Vec<_> = spreads.iter().map(|s| (s.time, s.bid)).collect();
+        assert_eq!(
+            bids,
+            vec![
+                (1_700_000_000, dec!(100.0)),
+                (1_700_000_001, dec!(100.1)),
+                (1_700_000_001, dec!(100.2)),
+                (1_700_000_001, dec!(100.3)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_errors_are_retried() {
+        let mut bodies = vec![
+            r#"{"error":["EAPI:Rate limit exceeded"]}"#.to_string(),
+            r#"{"error":[],"result":{"XXBTZUSD":[[1700000000,"100.0","100.5"]],"last":1700000000}}"#.to_string(),
+        ]
+        .into_iter();
+        let fetch = move |_url: String| {
+            let body = bodies.next().unwrap_or_else(|| {
+                r#"{"error":[],"result":{"XXBTZUSD":[],"last":1700000000}}"#.to_string()
+            });
+            async move { Ok(body) }
+        };
+
+        let mut fetcher = Fetcher::new(fetch, Arc::new(KrakenRateLimiter::permissive()));
+        fetcher.retry_delay = Duration::from_millis(1);
+        let range = Range { since: 1_699_999_000, until: 1_700_001_000 };
+        let spreads = fetcher.spreads("BTC/USD", range).await.unwrap();
+
+        assert_eq!(spreads, vec![Spread { time: 1_700_000_000, bid: dec!(100.0), ask: dec!(100.5) }]);
+    }
+}
@@ -0,0 +1,164 @@
+//! havklo-fetch - Historical Kraken data downloader
+//!
+//! Downloads OHLC candles, trades and spreads for one or more pairs over a
+//! time range from Kraken's public REST API and writes one CSV per pair:
+//!
+//! ```text
+//! havklo-fetch trades --pair BTC/USD --pair ETH/USD --since 2024-06-01 --until 2024-06-02
+//! havklo-fetch ohlc --pair BTC/USD --interval 60 --since 2024-06-01T00:00:00Z
+//! havklo-fetch spreads --pair SOL/USD --since 1717200000 --out-dir -
+//! ```
+//!
+//! Files are named `<BASE>-<QUOTE>_<dataset>.csv`; `--out-dir -` writes to
+//! stdout instead. Requests are paced with the SDK's REST rate limiter for
+//! the chosen `--tier`.
+
+mod fetch;
+mod output;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use fetch::{Fetcher, Range};
+use kraken_ws::KrakenRateLimiter;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[derive(Parser)]
+#[command(name = "havklo-fetch", about = "Download historical Kraken market data to CSV")]
+struct Cli {
+    #[command(subcommand)]
+    dataset: Dataset,
+}
+
+#[derive(Subcommand)]
+enum Dataset {
+    /// OHLC candles (Kraken serves the most recent 720 per interval)
+    Ohlc {
+        /// Candle interval in minutes
+        #[arg(long, default_value_t = 1)]
+        interval: u32,
+        #[command(flatten)]
+        common: Common,
+    },
+    /// Individual trades
+    Trades {
+        #[command(flatten)]
+        common: Common,
+    },
+    /// Top-of-book spreads (Kraken keeps only recent samples)
+    Spreads {
+        #[command(flatten)]
+        common: Common,
+    },
+}
+
+#[derive(Args)]
+struct Common {
+    /// Pair to download, e.g. BTC/USD (repeatable)
+    #[arg(long = "pair", required = true)]
+    pairs: Vec<String>,
+    /// Range start: RFC 3339, YYYY-MM-DD or Unix seconds
+    #[arg(long)]
+    since: String,
+    /// Range end (exclusive), same formats; defaults to now
+    #[arg(long)]
+    until: Option<String>,
+    /// Output directory, or "-" for stdout
+    #[arg(long, default_value = ".")]
+    out_dir: PathBuf,
+    /// Account tier whose REST limits to respect
+    #[arg(long, value_enum, default_value_t = Tier::Starter)]
+    tier: Tier,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Tier {
+    Starter,
+    Pro,
+}
+
+impl Common {
+    fn range(&self) -> Result<Range> {
+        let since = parse_time(&self.since).context("--since")?;
+        let until = match &self.until {
+            Some(until) => parse_time(until).context("--until")?,
+            None => Utc::now().timestamp(),
+        };
+        if until <= since {
+            bail!("--until must be after --since");
+        }
+        Ok(Range { since, until })
+    }
+
+    /// Writer for one pair's dataset
+    fn output(&self, pair: &str, dataset: &str) -> Result<Box<dyn Write>> {
+        if self.out_dir.as_os_str() == "-" {
+            return Ok(Box::new(std::io::stdout().lock()));
+        }
+        std::fs::create_dir_all(&self.out_dir)
+            .with_context(|| format!("creating {}", self.out_dir.display()))?;
+        let path = self.out_dir.join(format!("{}_{dataset}.csv", pair.replace('/', "-")));
+        let file = File::create(&path).with_context(|| format!("creating {}", path.display()))?;
+        eprintln!("writing {}", path.display());
+        Ok(Box::new(BufWriter::new(file)))
+    }
+}
+
+fn parse_time(text: &str) -> Result<i64> {
+    if let Ok(secs) = text.parse::<i64>() {
+        return Ok(secs);
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(text) {
+        return Ok(time.timestamp());
+    }
+    let date = NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        .with_context(|| format!("{text:?} is not RFC 3339, YYYY-MM-DD or Unix seconds"))?;
+    Ok(date.and_hms_opt(0, 0, 0).expect("midnight is valid").and_utc().timestamp())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    let common = match &cli.dataset {
+        Dataset::Ohlc { common, .. } | Dataset::Trades { common } | Dataset::Spreads { common } => common,
+    };
+    let range = common.range()?;
+    let limiter = Arc::new(match common.tier {
+        Tier::Starter => KrakenRateLimiter::kraken_defaults(),
+        Tier::Pro => KrakenRateLimiter::high_tier(),
+    });
+
+    let client = reqwest::Client::new();
+    let fetch = move |url: String| {
+        let client = client.clone();
+        async move {
+            let response = client.get(url).send().await?.error_for_status()?;
+            Ok(response.text().await?)
+        }
+    };
+    let mut fetcher = Fetcher::new(fetch, limiter);
+
+    for pair in &common.pairs {
+        match &cli.dataset {
+            Dataset::Ohlc { interval, .. } => {
+                let candles = fetcher.ohlc(pair, *interval, range).await?;
+                eprintln!("{pair}: {} candles", candles.len());
+                output::write_ohlc(&mut common.output(pair, &format!("ohlc_{interval}"))?, &candles)?;
+            }
+            Dataset::Trades { .. } => {
+                let trades = fetcher.trades(pair, range).await?;
+                eprintln!("{pair}: {} trades", trades.len());
+                output::write_trades(&mut common.output(pair, "trades")?, &trades)?;
+            }
+            Dataset::Spreads { .. } => {
+                let spreads = fetcher.spreads(pair, range).await?;
+                eprintln!("{pair}: {} spreads", spreads.len());
+                output::write_spreads(&mut common.output(pair, "spreads")?, &spreads)?;
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,44 @@
+//! CSV output
+//!
+//! One header row, then one row per record. Prices and quantities are
+//! written exactly as Kraken sent them; none of the fields need quoting.
+
+use crate::fetch::Spread;
+use anyhow::Result;
+use kraken_types::{OhlcData, Side, TradeData};
+use std::io::Write;
+
+pub fn write_ohlc(out: &mut impl Write, candles: &[OhlcData]) -> Result<()> {
+    writeln!(out, "interval_begin,open,high,low,close,vwap,volume,trades")?;
+    for c in candles {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{}",
+            c.interval_begin, c.open, c.high, c.low, c.close, c.vwap, c.volume, c.trades
+        )?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+pub fn write_trades(out: &mut impl Write, trades: &[TradeData]) -> Result<()> {
+    writeln!(out, "trade_id,timestamp,side,price,qty,ord_type")?;
+    for t in trades {
+        let side = match t.side {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        };
+        writeln!(out, "{},{},{},{},{},{}", t.trade_id, t.timestamp, side, t.price, t.qty, t.ord_type)?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+pub fn write_spreads(out: &mut impl Write, spreads: &[Spread]) -> Result<()> {
+    writeln!(out, "time,bid,ask")?;
+    for s in spreads {
+        writeln!(out, "{},{},{}", s.time, s.bid, s.ask)?;
+    }
+    out.flush()?;
+    Ok(())
+}
@@ -2,12 +2,33 @@
 
 #![allow(dead_code)]
 
+use crate::config::{AlertConfig, BookLayout, Config};
 use anyhow::Result;
+use kraken_sdk::alerts::{AlertId, AlertKind, AlertManager, AlertRule};
 use kraken_sdk::prelude::*;
+use kraken_sdk::{HealthStats, LatencyStats};
+use kraken_types::formatting::{format_decimal, Precision};
+use kraken_types::RateLimitCategory;
 use ratatui::style::Color;
 use rust_decimal::Decimal;
-use std::collections::{HashMap, VecDeque};
-use std::time::Instant;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+/// How long the Alerts tab flashes after an alert fires
+const ALERT_FLASH: Duration = Duration::from_secs(3);
+
+/// Maximum number of entries kept in the alert history
+const ALERT_HISTORY_LEN: usize = 50;
+
+/// How often per-channel message rates are recomputed
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// Rate limiter buckets shown on the Health tab
+const HEALTH_RATE_LIMITS: [(&str, RateLimitCategory); 3] = [
+    ("Subscribe", RateLimitCategory::WsSubscribe),
+    ("Connection", RateLimitCategory::Connection),
+    ("WS orders", RateLimitCategory::WsOrders),
+];
 
 /// Beautiful color theme inspired by Bloomberg terminal
 pub struct Theme;
@@ -32,6 +53,7 @@ pub enum Tab {
     Imbalance,
     Futures,
     Alerts,
+    Health,
 }
 
 impl Tab {
@@ -42,11 +64,12 @@ impl Tab {
             Tab::Imbalance => "Imbalance",
             Tab::Futures => "Futures",
             Tab::Alerts => "Alerts",
+            Tab::Health => "Health",
         }
     }
 
     pub fn all() -> &'static [Tab] {
-        &[Tab::Orderbook, Tab::Dashboard, Tab::Imbalance, Tab::Futures, Tab::Alerts]
+        &[Tab::Orderbook, Tab::Dashboard, Tab::Imbalance, Tab::Futures, Tab::Alerts, Tab::Health]
     }
 }
 
@@ -67,6 +90,20 @@ pub struct OrderbookData {
     pub mid_price: Option<Decimal>,
     pub checksum_valid: bool,
     pub update_count: u64,
+    /// Decimal places from the instrument channel
+    pub precision: Precision,
+}
+
+impl OrderbookData {
+    /// Price with the pair's price decimals
+    pub fn format_price(&self, price: Decimal) -> String {
+        format_decimal(price, self.precision.price_precision)
+    }
+
+    /// Quantity with the pair's quantity decimals
+    pub fn format_qty(&self, qty: Decimal) -> String {
+        format_decimal(qty, self.precision.qty_precision)
+    }
 }
 
 impl Default for OrderbookData {
@@ -78,6 +115,7 @@ impl Default for OrderbookData {
             mid_price: None,
             checksum_valid: true,
             update_count: 0,
+            precision: Precision::new(2, 4),
         }
     }
 }
@@ -114,11 +152,56 @@ pub struct FuturesData {
     pub premium: Option<Decimal>,
 }
 
+/// Connection health as shown on the Health tab
+#[derive(Debug, Clone, Default)]
+pub struct HealthView {
+    /// State reported by the SDK connection
+    pub ws_state: Option<kraken_sdk::ConnectionState>,
+    pub system_status: Option<String>,
+    pub stats: Option<HealthStats>,
+    /// Messages per second by channel over the last rate window
+    pub channel_rates: BTreeMap<&'static str, f64>,
+    pub latency: Option<LatencyStats>,
+    /// Rate limiter utilization (0.0 - 1.0) by bucket
+    pub rate_limits: Vec<(&'static str, f64)>,
+    rate_baseline: Option<HealthStats>,
+}
+
+/// Field focused in the alert editor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AlertField {
+    Symbol,
+    Condition,
+    Threshold,
+    Cooldown,
+}
+
+impl AlertField {
+    pub fn all() -> &'static [AlertField] {
+        &[AlertField::Symbol, AlertField::Condition, AlertField::Threshold, AlertField::Cooldown]
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            AlertField::Symbol => "Symbol",
+            AlertField::Condition => "Condition",
+            AlertField::Threshold => "Threshold",
+            AlertField::Cooldown => "Cooldown (s)",
+        }
+    }
+}
+
+/// Alert rule being created or edited in the Alerts tab
 #[derive(Debug, Clone)]
-pub struct Alert {
-    pub symbol: String,
-    pub condition: String,
-    pub triggered: bool,
+pub struct AlertForm {
+    /// Rule being edited, or None for a new rule
+    pub editing: Option<AlertId>,
+    pub field: AlertField,
+    pub symbol_idx: usize,
+    pub kind: AlertKind,
+    pub threshold: String,
+    pub cooldown: String,
+    pub error: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -131,6 +214,7 @@ pub struct App {
     // UI State
     pub current_tab: Tab,
     pub selected_symbol_idx: usize,
+    pub book_layout: BookLayout,
     pub show_splash: bool,
     pub paused: bool,
     pub splash_progress: f64,
@@ -152,8 +236,16 @@ pub struct App {
     pub imbalance_history: VecDeque<f64>,
 
     // Alerts
-    pub alerts: Vec<Alert>,
+    pub config: Config,
+    pub alert_manager: AlertManager,
+    pub selected_alert: usize,
+    pub alert_form: Option<AlertForm>,
     pub alert_history: VecDeque<AlertEvent>,
+    alert_flash_until: Option<Instant>,
+    bell_pending: bool,
+
+    // Health
+    pub health: HealthView,
 
     // Stats
     pub update_count: u64,
@@ -164,14 +256,11 @@ pub struct App {
 
 impl App {
     pub fn new() -> Self {
-        let symbols = vec![
-            "BTC/USD".to_string(),
-            "ETH/USD".to_string(),
-            "SOL/USD".to_string(),
-            "XRP/USD".to_string(),
-            "DOT/USD".to_string(),
-            "LINK/USD".to_string(),
-        ];
+        Self::with_config(&Config::default())
+    }
+
+    pub fn with_config(config: &Config) -> Self {
+        let symbols = config.symbols.clone();
 
         let mut symbol_data = HashMap::new();
         for s in &symbols {
@@ -202,9 +291,15 @@ impl App {
             },
         ];
 
+        let mut alert_manager = AlertManager::new();
+        for alert in &config.alerts {
+            alert_manager.add_rule(alert.to_rule());
+        }
+
         Self {
             current_tab: Tab::Orderbook,
             selected_symbol_idx: 0,
+            book_layout: config.orderbook.layout,
             show_splash: true,
             paused: false,
             splash_progress: 0.0,
@@ -223,19 +318,15 @@ impl App {
             imbalance: 0.0,
             imbalance_history: VecDeque::with_capacity(60),
 
-            alerts: vec![
-                Alert {
-                    symbol: "BTC/USD".to_string(),
-                    condition: "above $100,000".to_string(),
-                    triggered: false,
-                },
-                Alert {
-                    symbol: "ETH/USD".to_string(),
-                    condition: "below $3,000".to_string(),
-                    triggered: false,
-                },
-            ],
-            alert_history: VecDeque::with_capacity(50),
+            config: config.clone(),
+            alert_manager,
+            selected_alert: 0,
+            alert_form: None,
+            alert_history: VecDeque::with_capacity(ALERT_HISTORY_LEN),
+            alert_flash_until: None,
+            bell_pending: false,
+
+            health: HealthView::default(),
 
             update_count: 0,
             updates_per_second: 0.0,
@@ -268,6 +359,18 @@ impl App {
         self.selected_symbol_idx = (self.selected_symbol_idx + self.symbols.len() - 1) % self.symbols.len();
     }
 
+    /// Symbols shown by the current layout, starting at the selected one
+    pub fn visible_symbols(&self) -> Vec<&str> {
+        let count = self.book_layout.panes().min(self.symbols.len());
+        (0..count)
+            .map(|i| self.symbols[(self.selected_symbol_idx + i) % self.symbols.len()].as_str())
+            .collect()
+    }
+
+    pub fn cycle_layout(&mut self) {
+        self.book_layout = self.book_layout.next();
+    }
+
     pub fn toggle_pause(&mut self) {
         self.paused = !self.paused;
     }
@@ -277,6 +380,196 @@ impl App {
         self.reconnect_count += 1;
     }
 
+    /// Alert rules in display order
+    pub fn alert_rules(&self) -> Vec<(AlertId, &AlertRule)> {
+        self.alert_manager.rules().collect()
+    }
+
+    pub fn select_next_alert(&mut self) {
+        let len = self.alert_manager.len();
+        if len > 0 {
+            self.selected_alert = (self.selected_alert + 1) % len;
+        }
+    }
+
+    pub fn select_prev_alert(&mut self) {
+        let len = self.alert_manager.len();
+        if len > 0 {
+            self.selected_alert = (self.selected_alert + len - 1) % len;
+        }
+    }
+
+    /// Open the editor for a new rule on the selected symbol
+    pub fn new_alert(&mut self) {
+        let threshold = self
+            .symbol_data
+            .get(self.selected_symbol())
+            .and_then(|d| d.price)
+            .map(|p| p.round_dp(2).to_string())
+            .unwrap_or_default();
+        self.alert_form = Some(AlertForm {
+            editing: None,
+            field: AlertField::Threshold,
+            symbol_idx: self.selected_symbol_idx,
+            kind: AlertKind::PriceAbove,
+            threshold,
+            cooldown: "60".to_string(),
+            error: None,
+        });
+    }
+
+    /// Open the editor for the selected rule
+    pub fn edit_alert(&mut self) {
+        let Some((id, rule)) = self.alert_rules().get(self.selected_alert).copied() else {
+            return;
+        };
+        let form = AlertForm {
+            editing: Some(id),
+            field: AlertField::Threshold,
+            symbol_idx: self.symbols.iter().position(|s| *s == rule.symbol).unwrap_or(0),
+            kind: rule.kind,
+            threshold: rule.threshold.to_string(),
+            cooldown: rule.cooldown.as_secs().to_string(),
+            error: None,
+        };
+        self.alert_form = Some(form);
+    }
+
+    pub fn delete_alert(&mut self) {
+        let Some((id, _)) = self.alert_rules().get(self.selected_alert).copied() else {
+            return;
+        };
+        self.alert_manager.remove_rule(id);
+        self.selected_alert = self.selected_alert.min(self.alert_manager.len().saturating_sub(1));
+        self.save_alerts();
+    }
+
+    pub fn clear_alert_history(&mut self) {
+        self.alert_history.clear();
+    }
+
+    /// Move focus in the editor by `delta` fields
+    pub fn alert_form_focus(&mut self, delta: isize) {
+        if let Some(form) = &mut self.alert_form {
+            let fields = AlertField::all();
+            let idx = fields.iter().position(|f| *f == form.field).unwrap_or(0) as isize;
+            form.field = fields[(idx + delta).rem_euclid(fields.len() as isize) as usize];
+        }
+    }
+
+    /// Cycle the focused symbol or condition field
+    pub fn alert_form_cycle(&mut self, delta: isize) {
+        let symbol_count = self.symbols.len() as isize;
+        if let Some(form) = &mut self.alert_form {
+            match form.field {
+                AlertField::Symbol => {
+                    form.symbol_idx = (form.symbol_idx as isize + delta).rem_euclid(symbol_count) as usize;
+                }
+                AlertField::Condition => {
+                    // Alerts are checked against quotes only
+                    let kinds: Vec<AlertKind> =
+                        AlertKind::all().iter().copied().filter(|k| !k.is_trade_based()).collect();
+                    let idx = kinds.iter().position(|k| *k == form.kind).unwrap_or(0) as isize;
+                    form.kind = kinds[(idx + delta).rem_euclid(kinds.len() as isize) as usize];
+                }
+                AlertField::Threshold | AlertField::Cooldown => {}
+            }
+        }
+    }
+
+    /// Type into the focused threshold or cooldown field
+    pub fn alert_form_input(&mut self, c: char) {
+        if let Some(form) = &mut self.alert_form {
+            match form.field {
+                AlertField::Threshold if c.is_ascii_digit() || (c == '.' && !form.threshold.contains('.')) => {
+                    form.threshold.push(c);
+                }
+                AlertField::Cooldown if c.is_ascii_digit() => form.cooldown.push(c),
+                _ => {}
+            }
+        }
+    }
+
+    pub fn alert_form_backspace(&mut self) {
+        if let Some(form) = &mut self.alert_form {
+            match form.field {
+                AlertField::Threshold => {
+                    form.threshold.pop();
+                }
+                AlertField::Cooldown => {
+                    form.cooldown.pop();
+                }
+                AlertField::Symbol | AlertField::Condition => {}
+            }
+        }
+    }
+
+    pub fn cancel_alert_form(&mut self) {
+        self.alert_form = None;
+    }
+
+    /// Validate the editor, apply it to the alert manager and save the config
+    pub fn submit_alert_form(&mut self) {
+        let Some(form) = &mut self.alert_form else {
+            return;
+        };
+        let threshold = match form.threshold.parse::<Decimal>() {
+            Ok(t) if t > Decimal::ZERO => t,
+            _ => {
+                form.error = Some("Threshold must be a positive number".to_string());
+                return;
+            }
+        };
+        let Ok(cooldown) = form.cooldown.parse::<u64>() else {
+            form.error = Some("Cooldown must be a whole number of seconds".to_string());
+            return;
+        };
+        let rule = AlertRule::new(&self.symbols[form.symbol_idx], form.kind, threshold)
+            .with_cooldown(Duration::from_secs(cooldown));
+
+        match form.editing {
+            Some(id) => {
+                self.alert_manager.update_rule(id, rule);
+            }
+            None => {
+                self.alert_manager.add_rule(rule);
+                self.selected_alert = self.alert_manager.len() - 1;
+            }
+        }
+        self.alert_form = None;
+        self.save_alerts();
+    }
+
+    /// Write the current rules to the config file
+    fn save_alerts(&mut self) {
+        self.config.alerts = self
+            .alert_manager
+            .rules()
+            .map(|(_, rule)| AlertConfig::new(rule.clone()))
+            .collect();
+        if let Err(e) = self.config.save() {
+            self.log_alert(format!("Could not save alerts: {e:#}"));
+        }
+    }
+
+    fn log_alert(&mut self, message: String) {
+        self.alert_history.push_front(AlertEvent {
+            timestamp: chrono::Local::now(),
+            message,
+        });
+        self.alert_history.truncate(ALERT_HISTORY_LEN);
+    }
+
+    /// Whether an alert fired recently enough to flash the Alerts tab
+    pub fn alert_flashing(&self) -> bool {
+        self.alert_flash_until.is_some_and(|until| Instant::now() < until)
+    }
+
+    /// Returns true once per batch of fired alerts, so the caller rings the bell
+    pub fn take_bell(&mut self) -> bool {
+        std::mem::take(&mut self.bell_pending)
+    }
+
     pub fn uptime(&self) -> std::time::Duration {
         self.start_time.elapsed()
     }
@@ -313,12 +606,13 @@ impl App {
                 let asks = orderbook.asks_vec();
 
                 let ob_data = self.orderbooks.entry(symbol.clone()).or_default();
-                ob_data.bids = bids.iter().map(|l| (l.price, l.qty)).collect();
-                ob_data.asks = asks.iter().map(|l| (l.price, l.qty)).collect();
+                ob_data.bids = bids.iter().map(|l| (l.price.0, l.qty.0)).collect();
+                ob_data.asks = asks.iter().map(|l| (l.price.0, l.qty.0)).collect();
                 ob_data.spread = client.spread(symbol);
                 ob_data.mid_price = client.mid_price(symbol);
                 ob_data.checksum_valid = true;
                 ob_data.update_count += 1;
+                ob_data.precision = client.precision(symbol);
             }
 
             // Update symbol data
@@ -345,6 +639,17 @@ impl App {
             }
         }
 
+        self.health.sample(client);
+
+        let fired = self.alert_manager.check_client(client);
+        if !fired.is_empty() {
+            for trigger in fired {
+                self.log_alert(trigger.to_string());
+            }
+            self.alert_flash_until = Some(Instant::now() + ALERT_FLASH);
+            self.bell_pending = true;
+        }
+
         self.update_count += 1;
         self.connection_state = ConnectionState::Connected;
     }
@@ -355,6 +660,7 @@ impl App {
         let client = KrakenClient::builder(&self.symbols)
             .with_depth(Depth::D25)
             .with_book(true)
+            .with_rate_limiter(kraken_ws::rate_limiter::shared_rate_limiter())
             .connect()
             .await?;
 
@@ -363,11 +669,101 @@ impl App {
         self.start_time = Instant::now();
 
         // Log alert
-        self.alert_history.push_front(AlertEvent {
-            timestamp: chrono::Local::now(),
-            message: "Session started - Connected to Kraken".to_string(),
-        });
+        self.log_alert("Session started - Connected to Kraken".to_string());
 
         Ok(())
     }
 }
+
+impl HealthView {
+    /// Refresh from the client, recomputing message rates once per window
+    pub fn sample(&mut self, client: &KrakenClient) {
+        self.record_stats(client.health());
+        self.ws_state = Some(client.state());
+        self.system_status = client.system_status().map(|s| s.to_string());
+        self.latency = client.latency_stats();
+        self.rate_limits = client
+            .rate_limiter()
+            .map(|limiter| {
+                HEALTH_RATE_LIMITS
+                    .iter()
+                    .map(|(name, category)| (*name, limiter.utilization(*category)))
+                    .collect()
+            })
+            .unwrap_or_default();
+    }
+
+    /// Take a stats snapshot; message rates move once a full window has passed
+    fn record_stats(&mut self, stats: HealthStats) {
+        match &self.rate_baseline {
+            Some(baseline) if stats.captured_at.duration_since(baseline.captured_at) < RATE_WINDOW => {}
+            Some(baseline) => {
+                self.channel_rates = stats.rates_since(baseline);
+                self.rate_baseline = Some(stats.clone());
+            }
+            None => self.rate_baseline = Some(stats.clone()),
+        }
+        self.stats = Some(stats);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(at: Instant, book: u64, heartbeat: u64) -> HealthStats {
+        HealthStats {
+            captured_at: at,
+            connected_since: Some(at),
+            last_heartbeat: Some(at),
+            since_last_message: Duration::ZERO,
+            messages_by_channel: BTreeMap::from([("book", book), ("heartbeat", heartbeat)]),
+            checksum_mismatches: 0,
+            audit_violations: 0,
+            dropped_events: 0,
+            total_reconnects: 0,
+            reconnects: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_rates_wait_for_a_full_window() {
+        let start = Instant::now();
+        let mut health = HealthView::default();
+
+        // The first snapshot only sets the baseline
+        health.record_stats(stats(start, 100, 1));
+        assert!(health.channel_rates.is_empty());
+        assert_eq!(health.stats.as_ref().unwrap().total_messages(), 101);
+
+        // Inside the window the shown rates hold, the totals don't
+        health.record_stats(stats(start + Duration::from_millis(500), 150, 1));
+        assert!(health.channel_rates.is_empty());
+        assert_eq!(health.stats.as_ref().unwrap().total_messages(), 151);
+
+        // A full window later rates are measured from the baseline
+        health.record_stats(stats(start + Duration::from_secs(2), 300, 3));
+        assert_eq!(health.channel_rates["book"], 100.0);
+        assert_eq!(health.channel_rates["heartbeat"], 1.0);
+
+        // and the baseline moves up
+        health.record_stats(stats(start + Duration::from_millis(2_500), 390, 3));
+        assert_eq!(health.channel_rates["book"], 100.0);
+        health.record_stats(stats(start + Duration::from_secs(3), 400, 4));
+        assert_eq!(health.channel_rates["book"], 100.0);
+        assert_eq!(health.channel_rates["heartbeat"], 1.0);
+    }
+
+    #[test]
+    fn test_rates_drop_to_zero_when_a_channel_goes_quiet() {
+        let start = Instant::now();
+        let mut health = HealthView::default();
+        health.record_stats(stats(start, 0, 0));
+        health.record_stats(stats(start + Duration::from_secs(1), 50, 1));
+        assert_eq!(health.channel_rates["book"], 50.0);
+
+        health.record_stats(stats(start + Duration::from_secs(2), 50, 2));
+        assert_eq!(health.channel_rates["book"], 0.0);
+        assert_eq!(health.channel_rates["heartbeat"], 1.0);
+    }
+}
@@ -0,0 +1,284 @@
+//! User configuration
+//!
+//! Read from `$HAVKLO_CONFIG`, or `$XDG_CONFIG_HOME/havklo/config.toml`
+//! (`~/.config/havklo/config.toml`). A missing file means defaults.
+//!
+//! ```toml
+//! symbols = ["BTC/USD", "ETH/USD", "SOL/USD", "XRP/USD"]
+//!
+//! [orderbook]
+//! layout = "quad"   # single | dual | triple | quad
+//!
+//! [[alerts]]
+//! symbol = "BTC/USD"
+//! condition = "price_above"   # price_above | price_below | spread_above_bps
+//! threshold = "100000"
+//! cooldown_secs = 60
+//! ```
+//!
+//! Alert rules edited in the Alerts tab are written back with [`Config::save`].
+
+use anyhow::{bail, Context, Result};
+use kraken_sdk::alerts::{AlertKind, AlertRule};
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub symbols: Vec<String>,
+    pub orderbook: OrderbookConfig,
+    pub alerts: Vec<AlertConfig>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            symbols: ["BTC/USD", "ETH/USD", "SOL/USD", "XRP/USD", "DOT/USD", "LINK/USD"]
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            orderbook: OrderbookConfig::default(),
+            alerts: vec![
+                AlertConfig::new(AlertRule::new("BTC/USD", AlertKind::PriceAbove, dec!(100000))),
+                AlertConfig::new(AlertRule::new("ETH/USD", AlertKind::PriceBelow, dec!(3000))),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct OrderbookConfig {
+    pub layout: BookLayout,
+}
+
+/// How many orderbooks the orderbook tab shows at once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BookLayout {
+    /// One full-size book with the metrics sidebar
+    #[default]
+    Single,
+    /// Two compact books side by side
+    Dual,
+    /// Three compact books side by side
+    Triple,
+    /// Four compact books in a 2x2 grid
+    Quad,
+}
+
+impl BookLayout {
+    pub fn panes(&self) -> usize {
+        match self {
+            BookLayout::Single => 1,
+            BookLayout::Dual => 2,
+            BookLayout::Triple => 3,
+            BookLayout::Quad => 4,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            BookLayout::Single => BookLayout::Dual,
+            BookLayout::Dual => BookLayout::Triple,
+            BookLayout::Triple => BookLayout::Quad,
+            BookLayout::Quad => BookLayout::Single,
+        }
+    }
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            BookLayout::Single => "Single",
+            BookLayout::Dual => "1x2",
+            BookLayout::Triple => "1x3",
+            BookLayout::Quad => "2x2",
+        }
+    }
+}
+
+/// One alert rule as stored in the config file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AlertConfig {
+    pub symbol: String,
+    pub condition: AlertKind,
+    pub threshold: Decimal,
+    #[serde(default = "default_cooldown_secs")]
+    pub cooldown_secs: u64,
+}
+
+fn default_cooldown_secs() -> u64 {
+    60
+}
+
+impl AlertConfig {
+    pub fn new(rule: AlertRule) -> Self {
+        Self {
+            symbol: rule.symbol,
+            condition: rule.kind,
+            threshold: rule.threshold,
+            cooldown_secs: if rule.cooldown.is_zero() { default_cooldown_secs() } else { rule.cooldown.as_secs() },
+        }
+    }
+
+    pub fn to_rule(&self) -> AlertRule {
+        AlertRule::new(&self.symbol, self.condition, self.threshold)
+            .with_cooldown(Duration::from_secs(self.cooldown_secs))
+    }
+}
+
+impl Config {
+    /// Path the config is read from, if one can be determined
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("HAVKLO_CONFIG") {
+            return Some(PathBuf::from(path));
+        }
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("havklo").join("config.toml"))
+    }
+
+    /// Load the config file, falling back to defaults if it doesn't exist
+    pub fn load() -> Result<Self> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Load the config at `path`, falling back to defaults if it doesn't exist
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e).with_context(|| format!("reading {}", path.display())),
+        };
+        Self::parse(&text).with_context(|| format!("parsing {}", path.display()))
+    }
+
+    /// Parse the contents of a config file
+    fn parse(text: &str) -> Result<Self> {
+        let config: Config = toml::from_str(text)?;
+        if config.symbols.is_empty() {
+            bail!("`symbols` must list at least one pair");
+        }
+        Ok(config)
+    }
+
+    /// Write the config back to [`Config::path`], creating its directory
+    pub fn save(&self) -> Result<PathBuf> {
+        let Some(path) = Self::path() else {
+            bail!("no config path: set HAVKLO_CONFIG or HOME");
+        };
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).with_context(|| format!("creating {}", dir.display()))?;
+        }
+        let text = toml::to_string_pretty(self).context("serializing config")?;
+        std::fs::write(&path, text).with_context(|| format!("writing {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_file_uses_defaults() {
+        let config = Config::parse("").unwrap();
+        assert_eq!(config.symbols, Config::default().symbols);
+        assert_eq!(config.orderbook.layout, BookLayout::Single);
+        assert_eq!(config.alerts.len(), 2);
+
+        // Sections left out keep their defaults
+        let config = Config::parse("[orderbook]\nlayout = \"quad\"\n").unwrap();
+        assert_eq!(config.orderbook.layout, BookLayout::Quad);
+        assert_eq!(config.symbols.len(), 6);
+    }
+
+    #[test]
+    fn test_parses_documented_example() {
+        let config = Config::parse(
+            r#"
+            symbols = ["BTC/USD", "ETH/USD"]
+
+            [orderbook]
+            layout = "dual"
+
+            [[alerts]]
+            symbol = "BTC/USD"
+            condition = "spread_above_bps"
+            threshold = "12.5"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.symbols, ["BTC/USD", "ETH/USD"]);
+        assert_eq!(config.orderbook.layout.panes(), 2);
+        let rule = config.alerts[0].to_rule();
+        assert_eq!(rule.kind, AlertKind::SpreadAboveBps);
+        assert_eq!(rule.threshold, dec!(12.5));
+        assert_eq!(rule.cooldown, Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_invalid_files_are_rejected() {
+        let cases = [
+            ("symbols = []", "at least one pair"),
+            ("[orderbook]\nlayout = \"hex\"", "unknown variant"),
+            ("symbol = [\"BTC/USD\"]", "unknown field"),
+            ("[orderbook]\ncolumns = 3", "unknown field"),
+            ("[[alerts]]\nsymbol = \"BTC/USD\"\ncondition = \"price_above\"", "threshold"),
+            ("symbols = \"BTC/USD\"", "invalid type"),
+            ("symbols = [", "expected"),
+        ];
+        for (text, expected) in cases {
+            let err = Config::parse(text).unwrap_err();
+            assert!(format!("{:#}", err).contains(expected), "{:?}: {:#}", text, err);
+        }
+    }
+
+    #[test]
+    fn test_load_from_file() {
+        let dir = std::env::temp_dir().join(format!("havklo-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        // A missing file means defaults
+        let missing = Config::load_from(&dir.join("missing.toml")).unwrap();
+        assert_eq!(missing.symbols, Config::default().symbols);
+
+        // Errors name the file
+        let path = dir.join("config.toml");
+        std::fs::write(&path, "symbols = []").unwrap();
+        let err = Config::load_from(&path).unwrap_err();
+        assert!(format!("{:#}", err).contains("config.toml"));
+
+        // What save writes, load reads back
+        let mut config = Config::default();
+        config.orderbook.layout = BookLayout::Triple;
+        config.alerts.truncate(1);
+        std::fs::write(&path, toml::to_string_pretty(&config).unwrap()).unwrap();
+        let loaded = Config::load_from(&path).unwrap();
+        assert_eq!(loaded.orderbook.layout, BookLayout::Triple);
+        assert_eq!(loaded.alerts.len(), 1);
+        assert_eq!(loaded.alerts[0].to_rule(), config.alerts[0].to_rule());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_layouts_cycle_through_every_pane_count() {
+        let mut layout = BookLayout::default();
+        let mut panes = Vec::new();
+        for _ in 0..4 {
+            panes.push(layout.panes());
+            layout = layout.next();
+        }
+        assert_eq!(panes, [1, 2, 3, 4]);
+        assert_eq!(layout, BookLayout::Single);
+    }
+}
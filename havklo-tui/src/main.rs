@@ -3,23 +3,28 @@
 //! Run with: cargo run -p havklo-tui
 
 mod app;
+mod config;
 mod data;
 mod ui;
 mod widgets;
 
 use anyhow::Result;
 use app::App;
+use config::Config;
 use crossterm::{
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::prelude::*;
-use std::io::stdout;
+use std::io::{stdout, Write};
 use std::time::{Duration, Instant};
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    // Load config before touching the terminal so errors print normally
+    let config = Config::load()?;
+
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = stdout();
@@ -28,7 +33,7 @@ async fn main() -> Result<()> {
     let mut terminal = Terminal::new(backend)?;
 
     // Run the app
-    let result = run_app(&mut terminal).await;
+    let result = run_app(&mut terminal, &config).await;
 
     // Restore terminal
     disable_raw_mode()?;
@@ -46,8 +51,8 @@ async fn main() -> Result<()> {
     Ok(())
 }
 
-async fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
-    let mut app = App::new();
+async fn run_app<B: Backend>(terminal: &mut Terminal<B>, config: &Config) -> Result<()> {
+    let mut app = App::with_config(config);
 
     // Show splash screen first
     app.show_splash = true;
@@ -70,22 +75,42 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
         let timeout = tick_rate.saturating_sub(last_tick.elapsed());
         if event::poll(timeout)? {
             if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
+                if key.kind == KeyEventKind::Press && app.alert_form.is_some() {
+                    // The alert editor takes all keys while open
+                    match key.code {
+                        KeyCode::Esc => app.cancel_alert_form(),
+                        KeyCode::Enter => app.submit_alert_form(),
+                        KeyCode::Tab | KeyCode::Down => app.alert_form_focus(1),
+                        KeyCode::BackTab | KeyCode::Up => app.alert_form_focus(-1),
+                        KeyCode::Left => app.alert_form_cycle(-1),
+                        KeyCode::Right => app.alert_form_cycle(1),
+                        KeyCode::Backspace => app.alert_form_backspace(),
+                        KeyCode::Char(c) => app.alert_form_input(c),
+                        _ => {}
+                    }
+                } else if key.kind == KeyEventKind::Press {
+                    let on_alerts = app.current_tab == app::Tab::Alerts;
                     match key.code {
-                        KeyCode::Char('q') | KeyCode::Char('Q') => {
-                            if !app.show_splash {
-                                return Ok(());
-                            }
+                        KeyCode::Char('q') | KeyCode::Char('Q') if !app.show_splash => {
+                            return Ok(());
                         }
                         KeyCode::Char('1') => app.current_tab = app::Tab::Orderbook,
                         KeyCode::Char('2') => app.current_tab = app::Tab::Dashboard,
                         KeyCode::Char('3') => app.current_tab = app::Tab::Imbalance,
                         KeyCode::Char('4') => app.current_tab = app::Tab::Futures,
                         KeyCode::Char('5') => app.current_tab = app::Tab::Alerts,
+                        KeyCode::Char('6') => app.current_tab = app::Tab::Health,
                         KeyCode::Tab => app.next_tab(),
                         KeyCode::BackTab => app.prev_tab(),
                         KeyCode::Left => app.prev_symbol(),
                         KeyCode::Right => app.next_symbol(),
+                        KeyCode::Char('l') | KeyCode::Char('L') => app.cycle_layout(),
+                        KeyCode::Char('a') | KeyCode::Char('A') if on_alerts => app.new_alert(),
+                        KeyCode::Char('e') | KeyCode::Char('E') | KeyCode::Enter if on_alerts => app.edit_alert(),
+                        KeyCode::Char('d') | KeyCode::Char('D') if on_alerts => app.delete_alert(),
+                        KeyCode::Char('c') | KeyCode::Char('C') if on_alerts => app.clear_alert_history(),
+                        KeyCode::Up if on_alerts => app.select_prev_alert(),
+                        KeyCode::Down if on_alerts => app.select_next_alert(),
                         KeyCode::Char(' ') => app.toggle_pause(),
                         KeyCode::Char('r') | KeyCode::Char('R') => app.reconnect(),
                         _ => {}
@@ -98,6 +123,13 @@ async fn run_app<B: Backend>(terminal: &mut Terminal<B>) -> Result<()> {
         if last_tick.elapsed() >= tick_rate {
             app.tick();
             last_tick = Instant::now();
+
+            // Terminal bell when an alert fires
+            if app.take_bell() {
+                let mut out = stdout();
+                out.write_all(b"\x07")?;
+                out.flush()?;
+            }
         }
 
         // Transition from splash after delay
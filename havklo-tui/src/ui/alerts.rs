@@ -1,6 +1,6 @@
-//! Alerts view with active alerts and history
+//! Alerts view with alert rules, the rule editor and history
 
-use crate::app::{App, Theme};
+use crate::app::{AlertField, AlertForm, App, Theme};
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 
@@ -18,7 +18,7 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
         .direction(Direction::Vertical)
         .margin(1)
         .constraints([
-            Constraint::Length(app.alerts.len() as u16 + 4),  // Active alerts
+            Constraint::Length(app.alert_manager.len() as u16 + 4),  // Active alerts
             Constraint::Min(5),                               // History
             Constraint::Length(3),                            // Controls
         ])
@@ -27,11 +27,16 @@ pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
     render_active_alerts(frame, app, layout[0]);
     render_history(frame, app, layout[1]);
     render_controls(frame, layout[2]);
+
+    if let Some(form) = &app.alert_form {
+        render_form(frame, app, form, area);
+    }
 }
 
 fn render_active_alerts(frame: &mut Frame, app: &App, area: Rect) {
+    let title_color = if app.alert_flashing() { Theme::ASK } else { Theme::HIGHLIGHT };
     let block = Block::default()
-        .title(Span::styled(" ACTIVE ALERTS ", Style::default().fg(Theme::HIGHLIGHT)))
+        .title(Span::styled(" ACTIVE ALERTS ", Style::default().fg(title_color)))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(Theme::BORDER));
@@ -40,15 +45,25 @@ fn render_active_alerts(frame: &mut Frame, app: &App, area: Rect) {
     frame.render_widget(block, area);
 
     let mut lines = Vec::new();
-    for alert in &app.alerts {
-        let status_icon = if alert.triggered { "●" } else { "◉" };
-        let status_color = if alert.triggered { Theme::SUCCESS } else { Theme::HIGHLIGHT };
-        let status_text = if alert.triggered { "TRIGGERED" } else { "WATCHING" };
+    for (i, (id, rule)) in app.alert_rules().into_iter().enumerate() {
+        let triggered = app.alert_manager.is_active(id);
+        let status_icon = if triggered { "●" } else { "◉" };
+        let status_color = if triggered { Theme::SUCCESS } else { Theme::HIGHLIGHT };
+        let status_text = if triggered { "TRIGGERED" } else { "WATCHING" };
+        let marker = if i == app.selected_alert && app.alert_form.is_none() { "▶" } else { " " };
 
         let line = Line::from(vec![
-            Span::styled(format!("  {} ", status_icon), Style::default().fg(status_color)),
-            Span::styled(&alert.symbol, Style::default().fg(Theme::ACCENT).bold()),
-            Span::styled(format!(" {} ", alert.condition), Style::default().fg(Theme::FG)),
+            Span::styled(format!(" {}", marker), Style::default().fg(Theme::ACCENT)),
+            Span::styled(format!("{} ", status_icon), Style::default().fg(status_color)),
+            Span::styled(rule.symbol.clone(), Style::default().fg(Theme::ACCENT).bold()),
+            Span::styled(
+                format!(" {} {} ", rule.kind.describe(), rule.threshold),
+                Style::default().fg(Theme::FG),
+            ),
+            Span::styled(
+                format!("  cooldown {}s", rule.cooldown.as_secs()),
+                Style::default().fg(Theme::MUTED),
+            ),
             Span::raw("          "),
             Span::styled(format!("Status: {}", status_text), Style::default().fg(Theme::MUTED)),
         ]);
@@ -113,8 +128,12 @@ fn render_controls(frame: &mut Frame, area: Rect) {
     let controls = Line::from(vec![
         Span::styled("[A]", Style::default().fg(Theme::ACCENT).bold()),
         Span::styled(" Add Alert  ", Style::default().fg(Theme::MUTED)),
+        Span::styled("[E]", Style::default().fg(Theme::ACCENT).bold()),
+        Span::styled(" Edit  ", Style::default().fg(Theme::MUTED)),
         Span::styled("[D]", Style::default().fg(Theme::ACCENT).bold()),
         Span::styled(" Delete  ", Style::default().fg(Theme::MUTED)),
+        Span::styled("[↑↓]", Style::default().fg(Theme::ACCENT).bold()),
+        Span::styled(" Select  ", Style::default().fg(Theme::MUTED)),
         Span::styled("[C]", Style::default().fg(Theme::ACCENT).bold()),
         Span::styled(" Clear History", Style::default().fg(Theme::MUTED)),
     ]);
@@ -124,3 +143,59 @@ fn render_controls(frame: &mut Frame, area: Rect) {
         .block(Block::default().padding(Padding::new(0, 0, 1, 0)));
     frame.render_widget(controls_widget, area);
 }
+
+fn render_form(frame: &mut Frame, app: &App, form: &AlertForm, area: Rect) {
+    let width = 50.min(area.width);
+    let height = 10.min(area.height);
+    let popup = Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    };
+
+    let title = if form.editing.is_some() { " EDIT ALERT " } else { " NEW ALERT " };
+    let block = Block::default()
+        .title(Span::styled(title, Style::default().fg(Theme::HIGHLIGHT).bold()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Theme::ACCENT))
+        .style(Style::default().bg(Theme::BG));
+
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+
+    let mut lines = vec![Line::raw("")];
+    for field in AlertField::all() {
+        let focused = *field == form.field;
+        let value = match field {
+            AlertField::Symbol => format!("◀ {} ▶", app.symbols[form.symbol_idx]),
+            AlertField::Condition => format!("◀ {} ▶", form.kind.describe()),
+            AlertField::Threshold => form.threshold.clone(),
+            AlertField::Cooldown => form.cooldown.clone(),
+        };
+        let cursor = if focused && matches!(field, AlertField::Threshold | AlertField::Cooldown) { "▏" } else { "" };
+        let (label_style, value_style) = if focused {
+            (Style::default().fg(Theme::ACCENT).bold(), Style::default().fg(Theme::HIGHLIGHT).bold())
+        } else {
+            (Style::default().fg(Theme::MUTED), Style::default().fg(Theme::FG))
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<14}", field.title()), label_style),
+            Span::styled(value, value_style),
+            Span::styled(cursor, Style::default().fg(Theme::ACCENT)),
+        ]));
+    }
+
+    lines.push(Line::raw(""));
+    lines.push(match &form.error {
+        Some(error) => Line::from(Span::styled(format!("  {}", error), Style::default().fg(Theme::ASK))),
+        None => Line::from(Span::styled(
+            "  ↑↓ field  ←→ change  Enter save  Esc cancel",
+            Style::default().fg(Theme::MUTED),
+        )),
+    });
+
+    frame.render_widget(Paragraph::new(lines), inner);
+}
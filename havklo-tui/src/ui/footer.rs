@@ -8,7 +8,8 @@ pub fn render(frame: &mut Frame, _app: &App, area: Rect) {
     let keybindings = vec![
         ("Q", "Quit"),
         ("←→", "Symbol"),
-        ("1-5", "View"),
+        ("1-6", "View"),
+        ("L", "Layout"),
         ("Tab", "Next"),
         ("Space", "Pause"),
         ("R", "Reconnect"),
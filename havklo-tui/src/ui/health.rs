@@ -0,0 +1,274 @@
+//! Connection health view: state, heartbeats, message rates and rate limits
+
+use crate::app::{App, HealthView, Theme};
+use ratatui::prelude::*;
+use ratatui::widgets::*;
+use std::time::Duration;
+
+/// Heartbeats arrive about once a second; older than this is worth a look
+const HEARTBEAT_WARN: Duration = Duration::from_secs(5);
+
+pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
+    let block = Block::default()
+        .title(Span::styled(" CONNECTION HEALTH ", Style::default().fg(Theme::FG).bold()))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Theme::BORDER));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(8),  // Connection + reliability
+            Constraint::Min(6),     // Rates + rate limits
+            Constraint::Length(8),  // Reconnect history
+        ])
+        .split(inner);
+
+    let top = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[0]);
+    let middle = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(rows[1]);
+
+    let health = &app.health;
+    render_connection(frame, app, top[0]);
+    render_reliability(frame, health, top[1]);
+    render_rates(frame, health, middle[0]);
+    render_rate_limits(frame, health, middle[1]);
+    render_reconnects(frame, health, rows[2]);
+}
+
+fn panel(title: &str) -> Block<'_> {
+    Block::default()
+        .title(Span::styled(format!(" {} ", title), Style::default().fg(Theme::HIGHLIGHT)))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Theme::BORDER))
+}
+
+fn row<'a>(label: &'a str, value: String, color: Color) -> Line<'a> {
+    Line::from(vec![
+        Span::styled(format!("  {:<18}", label), Style::default().fg(Theme::MUTED)),
+        Span::styled(value, Style::default().fg(color).bold()),
+    ])
+}
+
+fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs >= 3600 {
+        format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+    } else if secs >= 60 {
+        format!("{}m {:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{:.1}s", d.as_secs_f64())
+    }
+}
+
+fn state_label(state: Option<kraken_sdk::ConnectionState>) -> (String, Color) {
+    match state {
+        Some(kraken_sdk::ConnectionState::Connected) => ("CONNECTED".to_string(), Theme::SUCCESS),
+        Some(kraken_sdk::ConnectionState::Reconnecting) => ("RECONNECTING".to_string(), Theme::WARNING),
+        Some(state) => (format!("{:?}", state).to_uppercase(), Theme::HIGHLIGHT),
+        None => ("NOT STARTED".to_string(), Theme::MUTED),
+    }
+}
+
+fn heartbeat_label(age: Option<Duration>) -> (String, Color) {
+    match age {
+        Some(age) if age > HEARTBEAT_WARN => (format_duration(age), Theme::ASK),
+        Some(age) => (format_duration(age), Theme::SUCCESS),
+        None => ("-".to_string(), Theme::MUTED),
+    }
+}
+
+fn render_connection(frame: &mut Frame, app: &App, area: Rect) {
+    let health = &app.health;
+    let stats = health.stats.as_ref();
+
+    let (state, state_color) = state_label(health.ws_state);
+    let connected_for = stats
+        .and_then(|s| s.connected_for())
+        .map(format_duration)
+        .unwrap_or_else(|| "-".to_string());
+    let (heartbeat, heartbeat_color) = heartbeat_label(stats.and_then(|s| s.heartbeat_age()));
+    let last_message = stats
+        .map(|s| format_duration(s.since_last_message))
+        .unwrap_or_else(|| "-".to_string());
+
+    let lines = vec![
+        row("State", state, state_color),
+        row(
+            "Exchange",
+            health.system_status.clone().unwrap_or_else(|| "-".to_string()),
+            Theme::FG,
+        ),
+        row("Connected for", connected_for, Theme::FG),
+        row("Heartbeat age", heartbeat, heartbeat_color),
+        row("Last message", last_message, Theme::FG),
+        row(
+            "Reconnects",
+            stats.map_or(0, |s| s.total_reconnects).to_string(),
+            Theme::FG,
+        ),
+    ];
+    frame.render_widget(Paragraph::new(lines).block(panel("CONNECTION")), area);
+}
+
+fn render_reliability(frame: &mut Frame, health: &HealthView, area: Rect) {
+    let stats = health.stats.as_ref();
+    let count_color = |n: u64| if n == 0 { Theme::SUCCESS } else { Theme::ASK };
+    let dropped = stats.map_or(0, |s| s.dropped_events);
+    let mismatches = stats.map_or(0, |s| s.checksum_mismatches);
+
+    let mut lines = vec![
+        row("Dropped events", dropped.to_string(), count_color(dropped)),
+        row("Checksum errors", mismatches.to_string(), count_color(mismatches)),
+        row(
+            "Messages total",
+            stats.map_or(0, |s| s.total_messages()).to_string(),
+            Theme::FG,
+        ),
+    ];
+    match &health.latency {
+        Some(latency) => {
+            lines.push(row("Latency p50", format!("{:.1}ms", latency.p50_us as f64 / 1000.0), Theme::FG));
+            lines.push(row("Latency p99", format!("{:.1}ms", latency.p99_us as f64 / 1000.0), Theme::FG));
+            lines.push(row("Jitter p50", format!("{:.1}ms", latency.excess_p50_us as f64 / 1000.0), Theme::FG));
+        }
+        None => lines.push(row("Latency", "waiting for data".to_string(), Theme::MUTED)),
+    }
+    frame.render_widget(Paragraph::new(lines).block(panel("RELIABILITY")), area);
+}
+
+fn render_rates(frame: &mut Frame, health: &HealthView, area: Rect) {
+    let counts = health.stats.as_ref().map(|s| &s.messages_by_channel);
+    let mut lines = vec![Line::from(vec![
+        Span::styled(format!("  {:<14}", "CHANNEL"), Style::default().fg(Theme::MUTED).bold()),
+        Span::styled(format!("{:>10}", "MSG/S"), Style::default().fg(Theme::MUTED).bold()),
+        Span::styled(format!("{:>12}", "TOTAL"), Style::default().fg(Theme::MUTED).bold()),
+    ])];
+
+    for (channel, total) in counts.into_iter().flatten() {
+        let rate = health.channel_rates.get(channel).copied().unwrap_or(0.0);
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<14}", channel), Style::default().fg(Theme::ACCENT)),
+            Span::styled(format!("{:>10.1}", rate), Style::default().fg(Theme::FG).bold()),
+            Span::styled(format!("{:>12}", total), Style::default().fg(Theme::MUTED)),
+        ]));
+    }
+    if lines.len() == 1 {
+        lines.push(Line::from(Span::styled("  No messages yet", Style::default().fg(Theme::MUTED))));
+    }
+    frame.render_widget(Paragraph::new(lines).block(panel("MESSAGE RATES")), area);
+}
+
+fn render_rate_limits(frame: &mut Frame, health: &HealthView, area: Rect) {
+    let block = panel("RATE LIMITS");
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if health.rate_limits.is_empty() {
+        frame.render_widget(
+            Paragraph::new(Span::styled("  No rate limiter configured", Style::default().fg(Theme::MUTED))),
+            inner,
+        );
+        return;
+    }
+
+    let bars = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(health.rate_limits.iter().map(|_| Constraint::Length(1)).collect::<Vec<_>>())
+        .split(inner);
+    for ((name, utilization), bar) in health.rate_limits.iter().zip(bars.iter()) {
+        let color = match utilization {
+            u if *u >= 0.8 => Theme::ASK,
+            u if *u >= 0.5 => Theme::WARNING,
+            _ => Theme::SUCCESS,
+        };
+        let gauge = Gauge::default()
+            .label(format!("{} {:.0}%", name, utilization * 100.0))
+            .ratio(utilization.clamp(0.0, 1.0))
+            .gauge_style(Style::default().fg(color).bg(Theme::BORDER));
+        frame.render_widget(gauge, *bar);
+    }
+}
+
+fn render_reconnects(frame: &mut Frame, health: &HealthView, area: Rect) {
+    let block = panel("RECONNECT HISTORY");
+    let inner = block.inner(area);
+
+    let mut lines = Vec::new();
+    if let Some(stats) = &health.stats {
+        for record in stats.reconnects.iter().rev().take(inner.height as usize) {
+            let at: chrono::DateTime<chrono::Local> = record.at.into();
+            lines.push(Line::from(vec![
+                Span::styled(format!("  {} ", at.format("%H:%M:%S")), Style::default().fg(Theme::MUTED)),
+                Span::styled(format!("#{:<3}", record.attempt), Style::default().fg(Theme::WARNING)),
+                Span::styled(
+                    format!(" retry in {:<8}", format_duration(record.delay)),
+                    Style::default().fg(Theme::FG),
+                ),
+                Span::styled(record.reason.clone(), Style::default().fg(Theme::ASK)),
+            ]));
+        }
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled("  No reconnects this session", Style::default().fg(Theme::MUTED))));
+    }
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kraken_sdk::ConnectionState;
+
+    #[test]
+    fn test_connection_state_through_a_reconnect() {
+        let shown: Vec<_> = [
+            None,
+            Some(ConnectionState::Connecting),
+            Some(ConnectionState::Connected),
+            Some(ConnectionState::Reconnecting),
+            Some(ConnectionState::Connected),
+            Some(ConnectionState::ShuttingDown),
+            Some(ConnectionState::Disconnected),
+        ]
+        .into_iter()
+        .map(state_label)
+        .collect();
+        assert_eq!(
+            shown,
+            [
+                ("NOT STARTED".to_string(), Theme::MUTED),
+                ("CONNECTING".to_string(), Theme::HIGHLIGHT),
+                ("CONNECTED".to_string(), Theme::SUCCESS),
+                ("RECONNECTING".to_string(), Theme::WARNING),
+                ("CONNECTED".to_string(), Theme::SUCCESS),
+                ("SHUTTINGDOWN".to_string(), Theme::HIGHLIGHT),
+                ("DISCONNECTED".to_string(), Theme::HIGHLIGHT),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_heartbeat_turns_red_when_stale() {
+        assert_eq!(heartbeat_label(None), ("-".to_string(), Theme::MUTED));
+        assert_eq!(heartbeat_label(Some(Duration::from_millis(800))), ("0.8s".to_string(), Theme::SUCCESS));
+        assert_eq!(heartbeat_label(Some(HEARTBEAT_WARN)).1, Theme::SUCCESS);
+        assert_eq!(heartbeat_label(Some(Duration::from_secs(90))), ("1m 30s".to_string(), Theme::ASK));
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_millis(2_340)), "2.3s");
+        assert_eq!(format_duration(Duration::from_secs(605)), "10m 05s");
+        assert_eq!(format_duration(Duration::from_secs(7_380)), "2h 03m");
+    }
+}
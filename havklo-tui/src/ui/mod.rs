@@ -8,6 +8,7 @@ mod dashboard;
 mod imbalance;
 mod futures;
 mod alerts;
+mod health;
 
 use crate::app::{App, Tab, Theme};
 use ratatui::prelude::*;
@@ -51,7 +52,12 @@ fn render_tabs(frame: &mut Frame, app: &App, area: Rect) {
             let num = format!("[{}] ", i + 1);
             let title = tab.title();
 
-            if *tab == app.current_tab {
+            if *tab == Tab::Alerts && app.alert_flashing() {
+                Line::from(vec![
+                    Span::styled(num, Style::default().fg(Theme::MUTED)),
+                    Span::styled(format!("🔔 {}", title), Style::default().fg(Theme::BG).bg(Theme::HIGHLIGHT).bold()),
+                ])
+            } else if *tab == app.current_tab {
                 Line::from(vec![
                     Span::styled(num, Style::default().fg(Theme::MUTED)),
                     Span::styled(title, Style::default().fg(Theme::ACCENT).bold()),
@@ -91,5 +97,6 @@ fn render_content(frame: &mut Frame, app: &mut App, area: Rect) {
         Tab::Imbalance => imbalance::render(frame, app, area),
         Tab::Futures => futures::render(frame, app, area),
         Tab::Alerts => alerts::render(frame, app, area),
+        Tab::Health => health::render(frame, app, area),
     }
 }
@@ -1,11 +1,17 @@
 //! Orderbook view with depth chart visualization
 
-use crate::app::{App, Theme};
+use crate::app::{App, OrderbookData, Theme};
+use crate::config::BookLayout;
 use ratatui::prelude::*;
 use ratatui::widgets::*;
 use rust_decimal::Decimal;
 
 pub fn render(frame: &mut Frame, app: &mut App, area: Rect) {
+    if app.book_layout != BookLayout::Single {
+        render_grid(frame, app, area);
+        return;
+    }
+
     // Split into main content and sidebar
     let layout = Layout::default()
         .direction(Direction::Horizontal)
@@ -115,16 +121,16 @@ fn render_depth_chart(frame: &mut Frame, data: &crate::app::OrderbookData, area:
         let line = Line::from(vec![
             Span::styled(bar, Style::default().fg(Theme::ASK)),
             Span::raw("  "),
-            Span::styled(format!("{:.4}", qty), Style::default().fg(Theme::FG)),
+            Span::styled(data.format_qty(*qty), Style::default().fg(Theme::FG)),
             Span::raw("  "),
-            Span::styled(format!("${:.2}", price), Style::default().fg(Theme::ASK)),
+            Span::styled(format!("${}", data.format_price(*price)), Style::default().fg(Theme::ASK)),
         ]);
         lines.push(line);
     }
 
     // Spread line
     let spread_str = data.spread
-        .map(|s| format!("━━━━━━━━━━ SPREAD ${:.2} ━━━━━━━━━━", s))
+        .map(|s| format!("━━━━━━━━━━ SPREAD ${} ━━━━━━━━━━", data.format_price(s)))
         .unwrap_or_else(|| "━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━".to_string());
     lines.push(Line::from(Span::styled(spread_str, Style::default().fg(Theme::HIGHLIGHT))));
 
@@ -142,9 +148,9 @@ fn render_depth_chart(frame: &mut Frame, data: &crate::app::OrderbookData, area:
         let line = Line::from(vec![
             Span::styled(bar, Style::default().fg(Theme::BID)),
             Span::raw("  "),
-            Span::styled(format!("{:.4}", qty), Style::default().fg(Theme::FG)),
+            Span::styled(data.format_qty(*qty), Style::default().fg(Theme::FG)),
             Span::raw("  "),
-            Span::styled(format!("${:.2}", price), Style::default().fg(Theme::BID)),
+            Span::styled(format!("${}", data.format_price(*price)), Style::default().fg(Theme::BID)),
         ]);
         lines.push(line);
     }
@@ -186,7 +192,9 @@ fn render_sidebar(frame: &mut Frame, app: &App, area: Rect) {
 
     // Spread
     let spread = ob_data.and_then(|d| d.spread);
-    let spread_text = spread.map(|s| format!("${:.2}", s)).unwrap_or("-".to_string());
+    let spread_text = ob_data
+        .and_then(|d| spread.map(|s| format!("${}", d.format_price(s))))
+        .unwrap_or("-".to_string());
     let spread_bps = spread.and_then(|s| {
         ob_data.and_then(|d| d.mid_price).map(|m| {
             if !m.is_zero() { (s / m * Decimal::from(10000)).to_string() } else { "-".to_string() }
@@ -204,7 +212,9 @@ fn render_sidebar(frame: &mut Frame, app: &App, area: Rect) {
 
     // Mid price with sparkline
     let mid = ob_data.and_then(|d| d.mid_price);
-    let mid_text = mid.map(|m| format!("${:.2}", m)).unwrap_or("-".to_string());
+    let mid_text = ob_data
+        .and_then(|d| mid.map(|m| format!("${}", d.format_price(m))))
+        .unwrap_or("-".to_string());
     let change = sym_data.map(|d| d.change_pct).unwrap_or(0.0);
     let change_color = if change > 0.0 { Theme::BID } else if change < 0.0 { Theme::ASK } else { Theme::FG };
 
@@ -309,3 +319,165 @@ fn render_imbalance_mini(imbalance: f64) -> String {
     let empty = 5 - filled.min(5);
     format!("{}{}", "▰".repeat(filled.min(5)), "▱".repeat(empty))
 }
+
+/// Several compact books at once, starting at the selected symbol
+fn render_grid(frame: &mut Frame, app: &App, area: Rect) {
+    let outer = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(6),     // Panes
+            Constraint::Length(1),  // Symbol selector
+        ])
+        .split(area);
+
+    let symbols = app.visible_symbols();
+    let panes = match app.book_layout {
+        BookLayout::Quad if symbols.len() == 4 => {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Ratio(1, 2); 2])
+                .split(outer[0]);
+            rows.iter()
+                .flat_map(|row| {
+                    Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints([Constraint::Ratio(1, 2); 2])
+                        .split(*row)
+                        .to_vec()
+                })
+                .collect::<Vec<_>>()
+        }
+        _ => Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Ratio(1, symbols.len() as u32); symbols.len()])
+            .split(outer[0])
+            .to_vec(),
+    };
+
+    for (symbol, pane) in symbols.iter().zip(panes) {
+        render_pane(frame, app, symbol, pane);
+    }
+
+    let mut selector = vec![Span::styled("◀ ", Style::default().fg(Theme::MUTED))];
+    selector.extend(app.symbols.iter().map(|s| {
+        let short = s.split('/').next().unwrap_or(s);
+        let style = if symbols.contains(&s.as_str()) {
+            Style::default().fg(Theme::ACCENT).bold()
+        } else {
+            Style::default().fg(Theme::MUTED)
+        };
+        Span::styled(format!(" {} ", short), style)
+    }));
+    selector.push(Span::styled(" ▶", Style::default().fg(Theme::MUTED)));
+    selector.push(Span::styled(
+        format!("   Layout {}", app.book_layout.title()),
+        Style::default().fg(Theme::MUTED),
+    ));
+    frame.render_widget(Paragraph::new(Line::from(selector)).alignment(Alignment::Center), outer[1]);
+}
+
+/// Compact book: BBO header over a few levels per side
+fn render_pane(frame: &mut Frame, app: &App, symbol: &str, area: Rect) {
+    let synced = app.symbol_data.get(symbol).map(|d| d.synced).unwrap_or(false);
+    let (sync_icon, sync_color) = if synced { ("●", Theme::SUCCESS) } else { ("○", Theme::MUTED) };
+
+    let block = Block::default()
+        .title(Line::from(vec![
+            Span::styled(format!(" {} ", symbol), Style::default().fg(Theme::FG).bold()),
+            Span::styled(format!("{} ", sync_icon), Style::default().fg(sync_color)),
+        ]))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(Theme::BORDER))
+        .style(Style::default().bg(Theme::BG));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some(data) = app.orderbooks.get(symbol) else {
+        let loading = Paragraph::new("Waiting for data...")
+            .style(Style::default().fg(Theme::MUTED))
+            .alignment(Alignment::Center);
+        frame.render_widget(loading, inner);
+        return;
+    };
+
+    let layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),  // BBO
+            Constraint::Min(1),     // Depth
+        ])
+        .split(inner);
+
+    render_bbo(frame, data, layout[0]);
+    render_compact_depth(frame, data, layout[1]);
+}
+
+fn render_bbo(frame: &mut Frame, data: &OrderbookData, area: Rect) {
+    let side = |level: Option<&(Decimal, Decimal)>, color: Color| match level {
+        Some((price, qty)) => vec![
+            Span::styled(format!("${}", data.format_price(*price)), Style::default().fg(color).bold()),
+            Span::styled(format!(" × {}", data.format_qty(*qty)), Style::default().fg(Theme::FG)),
+        ],
+        None => vec![Span::styled("-", Style::default().fg(Theme::MUTED))],
+    };
+
+    let mut bbo = side(data.bids.first(), Theme::BID);
+    bbo.push(Span::styled("  │  ", Style::default().fg(Theme::BORDER)));
+    bbo.extend(side(data.asks.first(), Theme::ASK));
+
+    let spread_bps = match (data.spread, data.mid_price) {
+        (Some(spread), Some(mid)) if !mid.is_zero() => format!("{:.1} bps", spread / mid * Decimal::from(10000)),
+        _ => "-".to_string(),
+    };
+    let stats = Line::from(vec![
+        Span::styled("Spread ", Style::default().fg(Theme::MUTED)),
+        Span::styled(
+            data.spread.map(|s| format!("${}", data.format_price(s))).unwrap_or("-".to_string()),
+            Style::default().fg(Theme::HIGHLIGHT),
+        ),
+        Span::styled(format!(" ({})", spread_bps), Style::default().fg(Theme::MUTED)),
+    ]);
+
+    let widget = Paragraph::new(vec![Line::from(bbo), stats]).alignment(Alignment::Center);
+    frame.render_widget(widget, area);
+}
+
+fn render_compact_depth(frame: &mut Frame, data: &OrderbookData, area: Rect) {
+    let levels = (area.height.saturating_sub(1) / 2) as usize;
+    let bar_width = area.width.saturating_sub(24) as usize;
+    let max_qty = data.bids.iter().take(levels)
+        .chain(data.asks.iter().take(levels))
+        .map(|(_, q)| *q)
+        .max()
+        .unwrap_or(Decimal::ONE);
+
+    let row = |price: &Decimal, qty: &Decimal, color: Color| {
+        let bar_len = if max_qty.is_zero() {
+            0
+        } else {
+            ((*qty / max_qty) * Decimal::from(bar_width as u32))
+                .round()
+                .to_string()
+                .parse::<usize>()
+                .unwrap_or(0)
+                .min(bar_width)
+        };
+        Line::from(vec![
+            Span::styled(format!("{:>12}", data.format_price(*price)), Style::default().fg(color)),
+            Span::styled(format!(" {:>10} ", data.format_qty(*qty)), Style::default().fg(Theme::FG)),
+            Span::styled("▓".repeat(bar_len), Style::default().fg(color)),
+        ])
+    };
+
+    let mut lines: Vec<Line> = data.asks.iter().take(levels).rev()
+        .map(|(price, qty)| row(price, qty, Theme::ASK))
+        .collect();
+    lines.push(Line::from(Span::styled(
+        "─".repeat(area.width as usize),
+        Style::default().fg(Theme::BORDER),
+    )));
+    lines.extend(data.bids.iter().take(levels).map(|(price, qty)| row(price, qty, Theme::BID)));
+
+    frame.render_widget(Paragraph::new(lines), area);
+}